@@ -0,0 +1,128 @@
+use regex::Regex;
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+/// Access and quotas for the review corpus export. The export is disabled when no keys are set.
+#[derive(Clone)]
+pub struct CorpusConfig {
+    pub api_keys: Vec<String>,
+    pub requests_per_hour: u32,
+    pub rows_per_hour: u64,
+}
+
+/// Placeholders replacing personal data, applied in order (CNPJ before CPF, both before phones,
+/// since the shorter patterns would otherwise match inside the longer ones).
+static PII_PATTERNS: LazyLock<Vec<(Regex, &'static str)>> = LazyLock::new(|| {
+    [
+        (r"(?i)\b(?:https?://|www\.)\S+", "[url]"),
+        (r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}", "[email]"),
+        (r"\b\d{2}\.?\d{3}\.?\d{3}/?\d{4}-?\d{2}\b", "[cnpj]"),
+        (r"\b\d{3}\.?\d{3}\.?\d{3}-?\d{2}\b", "[cpf]"),
+        (
+            r"(?:\+?55[\s-]?)?(?:\(?\d{2}\)?[\s-]?)?9?\d{4}[\s-]?\d{4}\b",
+            "[phone]",
+        ),
+    ]
+    .into_iter()
+    .map(|(pattern, replacement)| {
+        (
+            Regex::new(pattern).expect("invalid PII pattern"),
+            replacement,
+        )
+    })
+    .collect()
+});
+
+static WHITESPACE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\s+").expect("invalid whitespace pattern"));
+
+/// Collapses whitespace (review texts are full of stray line breaks) and replaces URLs,
+/// e-mail addresses, CPF/CNPJ numbers and phone numbers with placeholders.
+pub fn clean_review_text(text: &str) -> String {
+    let mut cleaned = WHITESPACE.replace_all(text, " ").trim().to_string();
+    for (pattern, replacement) in PII_PATTERNS.iter() {
+        cleaned = pattern.replace_all(&cleaned, *replacement).into_owned();
+    }
+    cleaned
+}
+
+const QUOTA_WINDOW: Duration = Duration::from_secs(3600);
+
+struct KeyUsage {
+    window_start: Instant,
+    requests: u32,
+    rows: u64,
+}
+
+/// Rows set aside for one export. Rows left unsent are handed back with [`CorpusQuotas::refund`].
+pub struct RowReservation {
+    api_key: String,
+    window_start: Instant,
+    pub rows: u64,
+}
+
+/// Hourly request and row budgets per API key, in fixed windows starting at a key's first request.
+#[derive(Clone)]
+pub struct CorpusQuotas {
+    requests_per_hour: u32,
+    rows_per_hour: u64,
+    usage: Arc<Mutex<HashMap<String, KeyUsage>>>,
+}
+
+impl CorpusQuotas {
+    pub fn new(config: &CorpusConfig) -> Self {
+        Self {
+            requests_per_hour: config.requests_per_hour,
+            rows_per_hour: config.rows_per_hour,
+            usage: Arc::default(),
+        }
+    }
+
+    /// Counts a request against `api_key` and reserves up to `wanted` rows. When the key is out of
+    /// requests or rows, returns the seconds until its window resets instead.
+    pub fn reserve(&self, api_key: &str, wanted: u64) -> Result<RowReservation, u64> {
+        let now = Instant::now();
+        let mut usage = self.usage.lock().expect("corpus quota registry poisoned");
+        let key_usage = usage.entry(api_key.to_string()).or_insert(KeyUsage {
+            window_start: now,
+            requests: 0,
+            rows: 0,
+        });
+
+        if now.duration_since(key_usage.window_start) >= QUOTA_WINDOW {
+            *key_usage = KeyUsage {
+                window_start: now,
+                requests: 0,
+                rows: 0,
+            };
+        }
+
+        if key_usage.requests >= self.requests_per_hour || key_usage.rows >= self.rows_per_hour {
+            let resets_in = QUOTA_WINDOW.saturating_sub(now.duration_since(key_usage.window_start));
+            return Err(resets_in.as_secs().max(1));
+        }
+
+        let rows = wanted.min(self.rows_per_hour - key_usage.rows);
+        key_usage.requests += 1;
+        key_usage.rows += rows;
+
+        Ok(RowReservation {
+            api_key: api_key.to_string(),
+            window_start: key_usage.window_start,
+            rows,
+        })
+    }
+
+    /// Returns the unused part of a reservation, unless its window has already rolled over.
+    pub fn refund(&self, reservation: &RowReservation, used: u64) {
+        let mut usage = self.usage.lock().expect("corpus quota registry poisoned");
+        if let Some(key_usage) = usage.get_mut(&reservation.api_key)
+            && key_usage.window_start == reservation.window_start
+        {
+            key_usage.rows = key_usage
+                .rows
+                .saturating_sub(reservation.rows.saturating_sub(used));
+        }
+    }
+}
@@ -0,0 +1,5 @@
+//! Read-side reporting: the review corpus export, the dashboard counters, payment
+//! reconciliation and the payment method breakdown.
+
+pub mod corpus;
+pub mod services;
@@ -0,0 +1,228 @@
+use bytes::Bytes;
+use futures::TryStreamExt;
+use std::collections::HashSet;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::io;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tracing::{error, info, instrument};
+use validator::Validate;
+
+use domain::cache::{self, ResponseCache};
+use domain::error::{AppError, AppResult};
+use domain::models::{
+    CorpusRecord, DuplicateCustomerStats, DuplicateCustomersQuery, PaginatedResponse,
+    PaymentAnalytics, PaymentAnalyticsQuery, PaymentMismatch, ReconciliationQuery,
+    ReviewCorpusQuery, TodayStats,
+};
+use domain::repositories::{CustomerRepository, OrderRepository, StatsRepository};
+use domain::services::{EXPORT_CHANNEL_CAPACITY, send_chunk};
+
+use crate::corpus::{CorpusConfig, CorpusQuotas, clean_review_text};
+
+/// Live dashboard counters. The database keeps them current, so reads are a single row.
+#[derive(Clone)]
+pub struct StatsService {
+    repository: Arc<dyn StatsRepository>,
+    cache: ResponseCache,
+}
+
+impl StatsService {
+    pub fn new(repository: Arc<dyn StatsRepository>, cache: ResponseCache) -> Self {
+        Self { repository, cache }
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_today(&self) -> AppResult<TodayStats> {
+        self.cache
+            .get_or_load(cache::TODAY_STATS, "today", || async {
+                Ok(self.repository.today().await?)
+            })
+            .await
+    }
+}
+
+/// Finance reconciliation: orders whose payments don't add up to their items and freight,
+/// whether they came from the Olist import or were placed here.
+#[derive(Clone)]
+pub struct ReconciliationService {
+    repository: Arc<dyn OrderRepository>,
+}
+
+impl ReconciliationService {
+    pub fn new(repository: Arc<dyn OrderRepository>) -> Self {
+        Self { repository }
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_payment_mismatches(
+        &self,
+        query: ReconciliationQuery,
+    ) -> AppResult<PaginatedResponse<PaymentMismatch>> {
+        query.validate()?;
+        let pagination = query.pagination();
+        let (_, _, page, page_size) = pagination.normalize();
+
+        let (mismatches, total_records) = self
+            .repository
+            .find_payment_mismatches(&query.filter(), &pagination)
+            .await?;
+
+        Ok(PaginatedResponse::new(
+            mismatches,
+            total_records,
+            page,
+            page_size,
+        ))
+    }
+}
+
+/// How customers pay: payments grouped by method over a purchase date range.
+#[derive(Clone)]
+pub struct PaymentAnalyticsService {
+    repository: Arc<dyn OrderRepository>,
+}
+
+impl PaymentAnalyticsService {
+    pub fn new(repository: Arc<dyn OrderRepository>) -> Self {
+        Self { repository }
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_payment_methods(
+        &self,
+        query: PaymentAnalyticsQuery,
+    ) -> AppResult<PaymentAnalytics> {
+        query.validate()?;
+        let methods = self
+            .repository
+            .find_payment_method_stats(&query.filter())
+            .await?;
+        Ok(PaymentAnalytics::new(&query, methods))
+    }
+}
+
+/// Customer duplication in the Olist data, which gives every order its own `customer_id`.
+#[derive(Clone)]
+pub struct CustomerDuplicationService {
+    repository: Arc<dyn CustomerRepository>,
+}
+
+impl CustomerDuplicationService {
+    pub fn new(repository: Arc<dyn CustomerRepository>) -> Self {
+        Self { repository }
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_stats(
+        &self,
+        query: DuplicateCustomersQuery,
+    ) -> AppResult<DuplicateCustomerStats> {
+        let distribution = self.repository.count_rows_per_unique_id().await?;
+        let most_duplicated = self.repository.find_most_duplicated(query.limit()).await?;
+        Ok(DuplicateCustomerStats::new(distribution, most_duplicated))
+    }
+}
+
+/// Streams cleaned review texts to NLP consumers under per-key quotas.
+#[derive(Clone)]
+pub struct ReviewCorpusService {
+    repository: Arc<dyn OrderRepository>,
+    api_keys: Arc<[String]>,
+    quotas: CorpusQuotas,
+}
+
+impl ReviewCorpusService {
+    pub fn new(repository: Arc<dyn OrderRepository>, config: &CorpusConfig) -> Self {
+        Self {
+            repository,
+            api_keys: config.api_keys.clone().into(),
+            quotas: CorpusQuotas::new(config),
+        }
+    }
+
+    /// Checks the key and its quota, then streams the corpus as JSONL on a background task.
+    /// Duplicate texts (after cleaning) are emitted once; rows left unsent are refunded.
+    #[instrument(skip(self, api_key))]
+    pub fn stream_corpus(
+        &self,
+        api_key: Option<&str>,
+        query: ReviewCorpusQuery,
+    ) -> AppResult<ReceiverStream<io::Result<Bytes>>> {
+        if self.api_keys.is_empty() {
+            return Err(AppError::FeatureDisabled("Review corpus export"));
+        }
+        let api_key = api_key
+            .filter(|key| self.api_keys.iter().any(|known| known == key))
+            .ok_or(AppError::Unauthorized)?;
+
+        let reservation = self
+            .quotas
+            .reserve(api_key, query.limit.unwrap_or(u64::MAX))
+            .map_err(AppError::QuotaExceeded)?;
+
+        let (tx, rx) = mpsc::channel(EXPORT_CHANNEL_CAPACITY);
+        let repository = self.repository.clone();
+        let quotas = self.quotas.clone();
+
+        tokio::spawn(async move {
+            let mut sent = 0;
+            if let Err(e) = write_corpus(
+                repository.as_ref(),
+                &query,
+                reservation.rows,
+                &mut sent,
+                &tx,
+            )
+            .await
+            {
+                error!("Review corpus export failed: {:?}", e);
+                let _ = tx.send(Err(e)).await;
+            }
+            quotas.refund(&reservation, sent);
+            info!("Review corpus export sent {} rows", sent);
+        });
+
+        Ok(ReceiverStream::new(rx))
+    }
+}
+
+async fn write_corpus(
+    repository: &dyn OrderRepository,
+    query: &ReviewCorpusQuery,
+    max_rows: u64,
+    sent: &mut u64,
+    tx: &mpsc::Sender<io::Result<Bytes>>,
+) -> io::Result<()> {
+    let min_length = query.min_length.unwrap_or(1);
+    let mut seen = HashSet::new();
+    let mut reviews = repository.stream_review_texts();
+
+    while *sent < max_rows
+        && let Some(review) = reviews.try_next().await.map_err(io::Error::other)?
+    {
+        let text = clean_review_text(&review.review_comment_message);
+        // Only a hash of each text is kept, so memory stays small on large corpora.
+        let mut hasher = DefaultHasher::new();
+        text.to_lowercase().hash(&mut hasher);
+        if text.chars().count() < min_length || !seen.insert(hasher.finish()) {
+            continue;
+        }
+
+        let mut line = serde_json::to_vec(&CorpusRecord {
+            review_id: review.review_id,
+            review_score: review.review_score,
+            lang: query.lang,
+            text,
+        })?;
+        line.push(b'\n');
+
+        if !send_chunk(tx, line).await {
+            return Ok(());
+        }
+        *sent += 1;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,260 @@
+use axum::{
+    body::HttpBody,
+    extract::{MatchedPath, Request, State},
+    http::header,
+    middleware::Next,
+    response::Response,
+};
+use sha2::{Digest, Sha256};
+use std::time::Instant;
+use tracing::{error, info, warn};
+
+use crate::client_ip::ClientIp;
+use crate::config::{AccessLogConfig, AccessLogLevel};
+use crate::error::REQUEST_ID_HEADER;
+use crate::handlers::{ACTOR_HEADER, API_KEY_HEADER};
+
+/// Hex digits of the key's SHA-256 logged in place of the key itself.
+const API_KEY_FINGERPRINT_CHARS: usize = 8;
+
+/// The route a request matched, e.g. `/api/v1/orders/{id}`, handed out to [`access_log`]
+/// by [`expose_matched_path`]. Only the router knows it.
+#[derive(Clone)]
+struct RouteTemplate(MatchedPath);
+
+/// Copies the matched route onto the response.
+pub async fn expose_matched_path(matched: MatchedPath, request: Request, next: Next) -> Response {
+    let mut response = next.run(request).await;
+    response.extensions_mut().insert(RouteTemplate(matched));
+    response
+}
+
+/// Logs one line per request, under the `access_log` target: method, route template, status,
+/// latency, response size, and who made it. Server errors are logged as errors and requests
+/// slower than the threshold as warnings.
+pub async fn access_log(
+    State(config): State<AccessLogConfig>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if config.level == AccessLogLevel::Off {
+        return next.run(request).await;
+    }
+
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+    let headers = request.headers();
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(str::to_string)
+    };
+    let request_id = header(REQUEST_ID_HEADER).unwrap_or_default();
+    let actor = header(ACTOR_HEADER).unwrap_or_default();
+    let api_key = header(API_KEY_HEADER)
+        .map(|key| {
+            let mut fingerprint = hex::encode(Sha256::digest(key.as_bytes()));
+            fingerprint.truncate(API_KEY_FINGERPRINT_CHARS);
+            fingerprint
+        })
+        .unwrap_or_default();
+    let client_ip = request
+        .extensions()
+        .get::<ClientIp>()
+        .map(ToString::to_string)
+        .unwrap_or_default();
+
+    let started = Instant::now();
+    let response = next.run(request).await;
+    let latency = started.elapsed();
+
+    let status = response.status();
+    let slow = config
+        .slow_request_threshold
+        .is_some_and(|threshold| latency > threshold);
+    if config.level == AccessLogLevel::Errors
+        && !slow
+        && !status.is_client_error()
+        && !status.is_server_error()
+    {
+        return response;
+    }
+
+    let route = response
+        .extensions()
+        .get::<RouteTemplate>()
+        .map(|RouteTemplate(matched)| matched.as_str().to_string())
+        .unwrap_or(path);
+    // Streamed bodies (exports, event streams) have no size until they finish
+    let bytes = response
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse().ok())
+        .or_else(|| response.body().size_hint().exact())
+        .map(|bytes: u64| bytes.to_string())
+        .unwrap_or_default();
+    let latency_ms = latency.as_secs_f64() * 1000.0;
+
+    macro_rules! log {
+        ($level:ident, $message:literal) => {
+            $level!(
+                target: "access_log",
+                %method,
+                %route,
+                status = status.as_u16(),
+                latency_ms = format_args!("{:.1}", latency_ms),
+                %bytes,
+                actor,
+                api_key,
+                client_ip,
+                request_id,
+                $message
+            )
+        };
+    }
+    if status.is_server_error() {
+        log!(error, "request failed");
+    } else if slow {
+        log!(warn, "slow request");
+    } else {
+        log!(info, "request");
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::StatusCode;
+    use axum::{Router, middleware, routing::get};
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use tower::ServiceExt;
+
+    /// Collects what the subscriber writes so a test can read its log lines.
+    #[derive(Clone, Default)]
+    struct Captured(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Captured {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Sends one request through the access log and returns the lines it wrote.
+    async fn log_lines(
+        level: AccessLogLevel,
+        slow_request_threshold: Option<Duration>,
+        request: Request,
+    ) -> Vec<String> {
+        let captured = Captured::default();
+        let subscriber = tracing_subscriber::fmt()
+            .with_ansi(false)
+            .with_writer({
+                let captured = captured.clone();
+                move || captured.clone()
+            })
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let config = AccessLogConfig {
+            level,
+            slow_request_threshold,
+        };
+        Router::new()
+            .route("/orders/{id}", get(|| async { "order" }))
+            .route(
+                "/broken",
+                get(|| async { StatusCode::INTERNAL_SERVER_ERROR }),
+            )
+            .route_layer(middleware::from_fn(expose_matched_path))
+            .layer(middleware::from_fn_with_state(config, access_log))
+            .oneshot(request)
+            .await
+            .unwrap();
+
+        let output = String::from_utf8(captured.0.lock().unwrap().clone()).unwrap();
+        output.lines().map(str::to_string).collect()
+    }
+
+    fn get_request(path: &str) -> Request {
+        Request::get(path).body(Body::empty()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn logs_the_route_template_and_a_fingerprint_of_the_api_key() {
+        let request = Request::get("/orders/e481f51cbdc54678b7cc49136f2d6af7")
+            .header(API_KEY_HEADER, "super-secret-key")
+            .header(ACTOR_HEADER, "ops@example.com")
+            .body(Body::empty())
+            .unwrap();
+        let lines = log_lines(AccessLogLevel::All, None, request).await;
+
+        assert_eq!(lines.len(), 1, "{lines:?}");
+        let line = &lines[0];
+        assert!(line.contains(" INFO access_log: request"), "{line}");
+        assert!(line.contains("route=/orders/{id}"), "{line}");
+        assert!(line.contains("status=200"), "{line}");
+        assert!(line.contains("bytes=5"), "{line}");
+        assert!(line.contains("actor=\"ops@example.com\""), "{line}");
+        let mut fingerprint = hex::encode(Sha256::digest(b"super-secret-key"));
+        fingerprint.truncate(API_KEY_FINGERPRINT_CHARS);
+        assert!(
+            line.contains(&format!("api_key=\"{fingerprint}\"")),
+            "{line}"
+        );
+        assert!(!line.contains("super-secret-key"), "{line}");
+    }
+
+    #[tokio::test]
+    async fn logs_unmatched_paths_as_they_were_requested() {
+        let lines = log_lines(AccessLogLevel::All, None, get_request("/nowhere")).await;
+        assert_eq!(lines.len(), 1, "{lines:?}");
+        assert!(lines[0].contains("route=/nowhere"), "{}", lines[0]);
+        assert!(lines[0].contains("status=404"), "{}", lines[0]);
+    }
+
+    #[tokio::test]
+    async fn errors_level_only_logs_failures_and_slow_requests() {
+        let ok = log_lines(AccessLogLevel::Errors, None, get_request("/orders/1")).await;
+        assert!(ok.is_empty(), "{ok:?}");
+
+        let failed = log_lines(AccessLogLevel::Errors, None, get_request("/broken")).await;
+        assert_eq!(failed.len(), 1, "{failed:?}");
+        assert!(
+            failed[0].contains("ERROR access_log: request failed"),
+            "{}",
+            failed[0]
+        );
+
+        let slow = log_lines(
+            AccessLogLevel::Errors,
+            Some(Duration::ZERO),
+            get_request("/orders/1"),
+        )
+        .await;
+        assert_eq!(slow.len(), 1, "{slow:?}");
+        assert!(
+            slow[0].contains("WARN access_log: slow request"),
+            "{}",
+            slow[0]
+        );
+    }
+
+    #[tokio::test]
+    async fn off_logs_nothing() {
+        let lines = log_lines(AccessLogLevel::Off, None, get_request("/broken")).await;
+        assert!(lines.is_empty(), "{lines:?}");
+    }
+}
@@ -0,0 +1,37 @@
+use std::time::Duration;
+use tracing::{error, info};
+
+use domain::runtime::{JobRuns, SELLER_BADGES_JOB};
+use domain::services::SellerService;
+
+/// Periodically recomputes seller badges. The first refresh runs at startup.
+///
+/// Does nothing when `refresh_minutes` is 0.
+pub async fn run(service: SellerService, refresh_minutes: u64, job_runs: JobRuns) {
+    job_runs.register(
+        SELLER_BADGES_JOB,
+        (refresh_minutes > 0).then(|| format!("every {} minutes", refresh_minutes)),
+    );
+    if refresh_minutes == 0 {
+        info!("Seller badge refresh is disabled.");
+        return;
+    }
+
+    let mut interval = tokio::time::interval(Duration::from_secs(refresh_minutes * 60));
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    loop {
+        interval.tick().await;
+        job_runs.record_start(SELLER_BADGES_JOB);
+        match service.refresh_badges().await {
+            Ok(count) => {
+                job_runs.record_success(SELLER_BADGES_JOB, format!("{} badges awarded", count));
+                info!("Seller badges refreshed, {} awarded.", count);
+            }
+            Err(e) => {
+                job_runs.record_failure(SELLER_BADGES_JOB, format!("{:?}", e));
+                error!("Seller badge refresh failed: {:?}", e);
+            }
+        }
+    }
+}
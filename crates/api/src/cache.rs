@@ -0,0 +1,30 @@
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::info;
+
+use domain::cache::ResponseCache;
+use domain::error::AppError;
+use persistence::cache::RedisCacheStore;
+
+use crate::config::CacheConfig;
+
+/// Connects the Redis response cache named by `REDIS_URL`. Without one the cache is
+/// disabled and every read goes to the database.
+pub async fn connect(config: &CacheConfig) -> Result<ResponseCache, AppError> {
+    let Some(url) = &config.redis_url else {
+        return Ok(ResponseCache::default());
+    };
+
+    info!(
+        "Connecting to Redis response cache (TTL {}s)...",
+        config.ttl_seconds
+    );
+    let store = RedisCacheStore::connect(url)
+        .await
+        .map_err(|e| AppError::ConfigError(format!("Failed to connect to Redis: {}", e)))?;
+
+    Ok(ResponseCache::new(
+        Arc::new(store),
+        Duration::from_secs(config.ttl_seconds),
+    ))
+}
@@ -0,0 +1,314 @@
+use async_trait::async_trait;
+use bigdecimal::BigDecimal;
+use serde::Deserialize;
+use serde_json::json;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+use tracing::info;
+
+use domain::carriers::{CarrierProvider, MockCarrier};
+use domain::error::{AppError, AppResult};
+use domain::models::{FreightQuote, Parcel, Shipment, ShippingLabel, TrackingEvent};
+use domain::money::Money;
+
+use crate::config::{CarrierBackend, CarrierConfig, CorreiosConfig};
+
+/// How long a Correios token is reused. Tokens last until the end of the day; renewing
+/// early spares parsing their expiry, which is given in Brasília time.
+const TOKEN_LIFETIME: Duration = Duration::from_secs(30 * 60);
+
+/// Builds the carrier named by `CARRIER_PROVIDER`.
+pub fn connect(config: &CarrierConfig) -> Result<Arc<dyn CarrierProvider>, AppError> {
+    let carrier: Arc<dyn CarrierProvider> = match config.provider {
+        CarrierBackend::Mock => Arc::new(MockCarrier::default()),
+        CarrierBackend::Correios => Arc::new(CorreiosCarrier::new(
+            config.correios.clone(),
+            Duration::from_secs(config.timeout_seconds),
+        )?),
+    };
+    info!("Quoting freight with the {} carrier.", carrier.name());
+    Ok(carrier)
+}
+
+/// [`CarrierProvider`] over the Correios REST API: `preco` and `prazo` for quotes,
+/// `prepostagem` for labels and `srorastro` for tracking.
+pub struct CorreiosCarrier {
+    client: reqwest::Client,
+    config: CorreiosConfig,
+    token: Mutex<Option<(String, Instant)>>,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    token: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PriceResponse {
+    pc_final: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DeadlineResponse {
+    prazo_entrega: Option<u32>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PrePostingResponse {
+    codigo_objeto: String,
+}
+
+#[derive(Deserialize)]
+struct TrackingResponse {
+    #[serde(default)]
+    objetos: Vec<TrackedObject>,
+}
+
+#[derive(Deserialize)]
+struct TrackedObject {
+    /// Set instead of `eventos` when the object is unknown.
+    mensagem: Option<String>,
+    #[serde(default)]
+    eventos: Vec<TrackedEvent>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TrackedEvent {
+    codigo: String,
+    descricao: String,
+    dt_hr_criado: chrono::NaiveDateTime,
+    unidade: Option<TrackedUnit>,
+}
+
+#[derive(Deserialize)]
+struct TrackedUnit {
+    endereco: Option<TrackedAddress>,
+}
+
+#[derive(Deserialize)]
+struct TrackedAddress {
+    cidade: Option<String>,
+    uf: Option<String>,
+}
+
+impl CorreiosCarrier {
+    pub const NAME: &'static str = "correios";
+
+    pub fn new(config: CorreiosConfig, timeout: Duration) -> Result<Self, AppError> {
+        let client = reqwest::Client::builder()
+            .timeout(timeout)
+            .build()
+            .map_err(|e| {
+                AppError::ConfigError(format!("Failed to build the Correios HTTP client: {}", e))
+            })?;
+        Ok(Self {
+            client,
+            config,
+            token: Mutex::new(None),
+        })
+    }
+
+    /// A bearer token for the posting card, reusing the last one while it is fresh.
+    async fn token(&self) -> AppResult<String> {
+        let mut cached = self.token.lock().await;
+        if let Some((token, issued)) = cached.as_ref()
+            && issued.elapsed() < TOKEN_LIFETIME
+        {
+            return Ok(token.clone());
+        }
+
+        let response = self
+            .client
+            .post(format!(
+                "{}/token/v1/autentica/cartaopostagem",
+                self.config.base_url
+            ))
+            .basic_auth(&self.config.username, Some(&self.config.access_code))
+            .json(&json!({ "numero": self.config.posting_card }))
+            .send()
+            .await;
+        let TokenResponse { token } = read_json(response, "authentication").await?;
+        *cached = Some((token.clone(), Instant::now()));
+        Ok(token)
+    }
+
+    async fn get<T: for<'de> Deserialize<'de>>(
+        &self,
+        path: &str,
+        query: &[(&str, String)],
+        what: &str,
+    ) -> AppResult<T> {
+        let response = self
+            .client
+            .get(format!("{}{}", self.config.base_url, path))
+            .bearer_auth(self.token().await?)
+            .query(query)
+            .send()
+            .await;
+        read_json(response, what).await
+    }
+
+    /// Code of a service by its configured name.
+    fn service_code(&self, service: &str) -> AppResult<&str> {
+        self.config
+            .services
+            .iter()
+            .find(|(name, _)| name == service)
+            .map(|(_, code)| code.as_str())
+            .ok_or_else(|| AppError::CarrierError(format!("Unknown service {}", service)))
+    }
+}
+
+#[async_trait]
+impl CarrierProvider for CorreiosCarrier {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    async fn quote(&self, parcel: &Parcel) -> AppResult<Vec<FreightQuote>> {
+        let mut quotes = Vec::with_capacity(self.config.services.len());
+        for (service, code) in &self.config.services {
+            let price: PriceResponse = self
+                .get(
+                    &format!("/preco/v1/nacional/{}", code),
+                    &[
+                        ("cepOrigem", parcel.origin_cep.clone()),
+                        ("cepDestino", parcel.destination_cep.clone()),
+                        ("psObjeto", parcel.weight_g.to_string()),
+                        // 2 is a box; letters and rolls are priced differently.
+                        ("tpObjeto", "2".to_string()),
+                        ("comprimento", parcel.length_cm.to_string()),
+                        ("largura", parcel.width_cm.to_string()),
+                        ("altura", parcel.height_cm.to_string()),
+                    ],
+                    "price",
+                )
+                .await?;
+            let deadline: DeadlineResponse = self
+                .get(
+                    &format!("/prazo/v1/nacional/{}", code),
+                    &[
+                        ("cepOrigem", parcel.origin_cep.clone()),
+                        ("cepDestino", parcel.destination_cep.clone()),
+                    ],
+                    "deadline",
+                )
+                .await?;
+
+            quotes.push(FreightQuote {
+                carrier: Self::NAME,
+                service: service.clone(),
+                price: parse_reais(&price.pc_final)?.into(),
+                delivery_days: deadline.prazo_entrega,
+                currency: Money::CURRENCY,
+            });
+        }
+        Ok(quotes)
+    }
+
+    async fn create_label(&self, shipment: &Shipment) -> AppResult<ShippingLabel> {
+        let parcel = &shipment.parcel;
+        let body = json!({
+            "idCorreios": self.config.username,
+            "remetente": { "endereco": { "cep": parcel.origin_cep } },
+            "destinatario": { "endereco": { "cep": parcel.destination_cep } },
+            "codigoServico": self.service_code(&shipment.service)?,
+            "numeroCartaoPostagem": self.config.posting_card,
+            "observacao": shipment.reference,
+            "pesoInformado": parcel.weight_g.to_string(),
+            "codigoFormatoObjetoInformado": "2",
+            "alturaInformada": parcel.height_cm.to_string(),
+            "larguraInformada": parcel.width_cm.to_string(),
+            "comprimentoInformado": parcel.length_cm.to_string(),
+        });
+        let response = self
+            .client
+            .post(format!(
+                "{}/prepostagem/v1/prepostagens",
+                self.config.base_url
+            ))
+            .bearer_auth(self.token().await?)
+            .json(&body)
+            .send()
+            .await;
+        let PrePostingResponse { codigo_objeto } = read_json(response, "pre-posting").await?;
+
+        Ok(ShippingLabel {
+            carrier: Self::NAME,
+            tracking_code: codigo_objeto,
+            label_url: None,
+        })
+    }
+
+    async fn track(&self, tracking_code: &str) -> AppResult<Vec<TrackingEvent>> {
+        let tracking: TrackingResponse = self
+            .get(
+                &format!("/srorastro/v1/objetos/{}", tracking_code),
+                &[("resultado", "T".to_string())],
+                "tracking",
+            )
+            .await?;
+        let Some(object) = tracking.objetos.into_iter().next() else {
+            return Err(AppError::NotFound);
+        };
+        if object.eventos.is_empty() && object.mensagem.is_some() {
+            return Err(AppError::NotFound);
+        }
+
+        // Correios lists the latest event first.
+        Ok(object
+            .eventos
+            .into_iter()
+            .rev()
+            .map(|event| TrackingEvent {
+                status: event.codigo,
+                description: event.descricao,
+                location: event
+                    .unidade
+                    .and_then(|unit| unit.endereco)
+                    .and_then(|address| match (address.cidade, address.uf) {
+                        (Some(city), Some(state)) => Some(format!("{}/{}", city, state)),
+                        (city, state) => city.or(state),
+                    }),
+                occurred_at: event.dt_hr_criado,
+            })
+            .collect())
+    }
+}
+
+/// Decodes a Correios response, turning a failed request or an error status into a
+/// [`AppError::CarrierError`] naming the call.
+async fn read_json<T: for<'de> Deserialize<'de>>(
+    response: reqwest::Result<reqwest::Response>,
+    what: &str,
+) -> AppResult<T> {
+    let response = response
+        .map_err(|e| AppError::CarrierError(format!("Correios {} request failed: {}", what, e)))?;
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(AppError::CarrierError(format!(
+            "Correios {} request returned {}: {}",
+            what,
+            status,
+            body.chars().take(300).collect::<String>()
+        )));
+    }
+    response.json().await.map_err(|e| {
+        AppError::CarrierError(format!("Unexpected Correios {} response: {}", what, e))
+    })
+}
+
+/// Parses an amount in the Brazilian format the API uses, e.g. `1.234,56`.
+fn parse_reais(amount: &str) -> AppResult<BigDecimal> {
+    amount
+        .replace('.', "")
+        .replace(',', ".")
+        .parse()
+        .map_err(|_| AppError::CarrierError(format!("Unexpected Correios price {}", amount)))
+}
@@ -0,0 +1,151 @@
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+
+use domain::error::AppError;
+use domain::events::{ChangePublisher, ChangeStream};
+use domain::models::ChangeEvent;
+
+use crate::config::{ChangeStreamBackend, ChangeStreamConfig};
+
+/// Connects the broker named by `CHANGE_STREAM_BACKEND` and starts publishing to it. The
+/// returned stream is what the audit service feeds; it is disabled without a backend.
+pub async fn start(config: &ChangeStreamConfig) -> Result<ChangeStream, AppError> {
+    let publisher = match config.backend {
+        ChangeStreamBackend::None => return Ok(ChangeStream::default()),
+        ChangeStreamBackend::Kafka => kafka(config)?,
+        ChangeStreamBackend::Nats => nats(config).await?,
+    };
+    info!(
+        "Publishing changes to {} as {:?}.",
+        config.topic, config.format
+    );
+
+    let (stream, receiver) = ChangeStream::channel(config.buffer);
+    tokio::spawn(run(receiver, publisher));
+    Ok(stream)
+}
+
+/// Publishes changes one at a time, in the order they were made. A change the broker
+/// refuses is logged and skipped.
+async fn run(mut receiver: mpsc::Receiver<ChangeEvent>, publisher: Arc<dyn ChangePublisher>) {
+    while let Some(change) = receiver.recv().await {
+        if let Err(e) = publisher.publish(&change).await {
+            warn!(
+                "Failed to publish the {} of {} {} to the change stream: {}",
+                change.action.as_str(),
+                change.entity_type,
+                change.entity_id,
+                e
+            );
+        }
+    }
+}
+
+#[cfg(feature = "kafka")]
+fn kafka(config: &ChangeStreamConfig) -> Result<Arc<dyn ChangePublisher>, AppError> {
+    use persistence::streaming::KafkaChangePublisher;
+
+    let publisher = KafkaChangePublisher::connect(&config.url, config.topic.clone(), config.format)
+        .map_err(|e| {
+            AppError::ConfigError(format!("Failed to create the Kafka producer: {}", e))
+        })?;
+    Ok(Arc::new(publisher))
+}
+
+#[cfg(not(feature = "kafka"))]
+fn kafka(_config: &ChangeStreamConfig) -> Result<Arc<dyn ChangePublisher>, AppError> {
+    Err(AppError::ConfigError(
+        "CHANGE_STREAM_BACKEND=kafka needs a build with the `kafka` feature".to_string(),
+    ))
+}
+
+#[cfg(feature = "nats")]
+async fn nats(config: &ChangeStreamConfig) -> Result<Arc<dyn ChangePublisher>, AppError> {
+    use persistence::streaming::NatsChangePublisher;
+
+    let publisher = NatsChangePublisher::connect(&config.url, config.topic.clone(), config.format)
+        .await
+        .map_err(|e| AppError::ConfigError(format!("Failed to connect to NATS: {}", e)))?;
+    Ok(Arc::new(publisher))
+}
+
+#[cfg(not(feature = "nats"))]
+async fn nats(_config: &ChangeStreamConfig) -> Result<Arc<dyn ChangePublisher>, AppError> {
+    Err(AppError::ConfigError(
+        "CHANGE_STREAM_BACKEND=nats needs a build with the `nats` feature".to_string(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use chrono::Utc;
+    use domain::models::AuditAction;
+    use std::sync::Mutex;
+
+    /// Refuses the changes of entity `refused` and records the rest.
+    struct Recorder {
+        published: Mutex<Vec<String>>,
+        refused: &'static str,
+    }
+
+    #[async_trait]
+    impl ChangePublisher for Recorder {
+        async fn publish(&self, change: &ChangeEvent) -> Result<(), String> {
+            if change.entity_id == self.refused {
+                return Err("broker unavailable".to_string());
+            }
+            self.published
+                .lock()
+                .unwrap()
+                .push(change.entity_id.clone());
+            Ok(())
+        }
+    }
+
+    fn change(stream: &ChangeStream, entity_id: &str) {
+        stream.emit(ChangeEvent {
+            tenant_id: stream.tenant().clone(),
+            entity_type: "customer",
+            entity_id: entity_id.to_string(),
+            action: AuditAction::Create,
+            actor: "ops".to_string(),
+            diff: serde_json::json!({}),
+            occurred_at: Utc::now(),
+        });
+    }
+
+    #[tokio::test]
+    async fn publishes_in_order_and_skips_refused_changes() {
+        let recorder = Arc::new(Recorder {
+            published: Mutex::new(Vec::new()),
+            refused: "c2",
+        });
+        let (stream, receiver) = ChangeStream::channel(8);
+        for entity_id in ["c1", "c2", "c3"] {
+            change(&stream, entity_id);
+        }
+        drop(stream);
+
+        run(receiver, recorder.clone()).await;
+        assert_eq!(*recorder.published.lock().unwrap(), ["c1", "c3"]);
+    }
+
+    #[cfg(not(feature = "kafka"))]
+    #[tokio::test]
+    async fn a_backend_left_out_of_the_build_is_a_config_error() {
+        let config = ChangeStreamConfig {
+            backend: ChangeStreamBackend::Kafka,
+            url: "localhost:9092".to_string(),
+            topic: "changes".to_string(),
+            format: Default::default(),
+            buffer: 8,
+        };
+        assert!(matches!(
+            start(&config).await,
+            Err(AppError::ConfigError(message)) if message.contains("`kafka` feature")
+        ));
+    }
+}
@@ -0,0 +1,317 @@
+use clap::{Parser, Subcommand, ValueEnum};
+use futures::StreamExt;
+use sqlx::migrate::{Migration, Migrator};
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use tracing::{info, warn};
+
+use domain::error::{AppError, AppResult};
+use domain::models::ExportFormat;
+use domain::tenancy::TenantId;
+use importer::export::encode_rows;
+use importer::geolocation::{GEOLOCATION_PATH, import_geolocation as load_geolocation};
+use importer::import::{Dataset, import_dataset};
+use importer::seed::{SeedOptions, seed as seed_data};
+
+use crate::database::Database;
+use crate::state::AppState;
+
+#[derive(Debug, Parser)]
+#[command(version, about = "Brazilian e-commerce API server and data tools")]
+pub struct Cli {
+    /// Defaults to `serve` when omitted.
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Run migrations and start the HTTP server.
+    Serve,
+    /// Apply or revert database migrations.
+    Migrate {
+        #[command(subcommand)]
+        action: MigrateAction,
+    },
+    /// Import a CSV dataset through the services layer.
+    Import {
+        #[arg(long, value_enum)]
+        dataset: Dataset,
+        /// Defaults to the bundled Olist file for the dataset.
+        #[arg(long)]
+        path: Option<PathBuf>,
+        /// The marketplace whose data the command reads or writes.
+        #[arg(long, default_value_t = TenantId::default())]
+        tenant: TenantId,
+    },
+    /// Load the Olist geolocation dataset, one averaged location per CEP prefix, for
+    /// freight estimates.
+    ImportGeolocation {
+        /// Defaults to `data/olist_geolocation_dataset.csv`.
+        #[arg(long)]
+        path: Option<PathBuf>,
+    },
+    /// Generate fake Brazilian customers, sellers, products and orders.
+    Seed {
+        #[command(flatten)]
+        options: SeedOptions,
+        /// The marketplace whose data the command reads or writes.
+        #[arg(long, default_value_t = TenantId::default())]
+        tenant: TenantId,
+    },
+    /// Export every row of an entity.
+    Export {
+        #[arg(long, value_enum)]
+        entity: ExportEntity,
+        #[arg(long, value_enum, default_value_t = ExportFormatArg::Csv)]
+        format: ExportFormatArg,
+        /// Defaults to stdout.
+        #[arg(long)]
+        output: Option<PathBuf>,
+        /// The marketplace whose data the command reads or writes.
+        #[arg(long, default_value_t = TenantId::default())]
+        tenant: TenantId,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum MigrateAction {
+    /// Apply all pending migrations.
+    Run,
+    /// Revert applied migrations newer than `--target` (defaults to the latest one only).
+    Revert {
+        #[arg(long)]
+        target: Option<i64>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportEntity {
+    Customers,
+    Orders,
+    Products,
+}
+
+/// [`ExportFormat`] as a command-line value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormatArg {
+    Csv,
+    Ndjson,
+}
+
+impl From<ExportFormatArg> for ExportFormat {
+    fn from(format: ExportFormatArg) -> Self {
+        match format {
+            ExportFormatArg::Csv => ExportFormat::Csv,
+            ExportFormatArg::Ndjson => ExportFormat::Ndjson,
+        }
+    }
+}
+
+pub async fn migrate(database: &Database, action: MigrateAction) -> AppResult<()> {
+    let migrator = database.migrator();
+
+    match action {
+        MigrateAction::Run => {
+            database.run_migrations().await?;
+            info!("Migrations applied.");
+        }
+        MigrateAction::Revert { target } => {
+            let applied = database.applied_migrations().await?;
+            if applied.is_empty() {
+                warn!("No applied migrations to revert.");
+                return Ok(());
+            }
+            let target = target.unwrap_or_else(|| applied.get(1).copied().unwrap_or(0));
+
+            if let Some(migration) = irreversible_migration(migrator, &applied, target) {
+                return Err(AppError::ConfigError(format!(
+                    "Migration {} ({}) has no .down.sql script; revert to --target {} or later",
+                    migration.version, migration.description, migration.version
+                )));
+            }
+
+            database.undo_migrations(target).await?;
+            info!("Reverted migrations newer than {}.", target);
+        }
+    }
+
+    Ok(())
+}
+
+/// The newest applied migration above `target` that has no down script. `Migrator::undo`
+/// skips those silently, which would leave the schema between versions.
+fn irreversible_migration<'a>(
+    migrator: &'a Migrator,
+    applied: &[i64],
+    target: i64,
+) -> Option<&'a Migration> {
+    applied
+        .iter()
+        .filter(|&&version| version > target)
+        .find_map(|&version| {
+            let reversible = migrator
+                .iter()
+                .any(|m| m.version == version && m.migration_type.is_down_migration());
+            if reversible {
+                None
+            } else {
+                migrator.iter().find(|m| m.version == version)
+            }
+        })
+}
+
+pub async fn import(state: &AppState, dataset: Dataset, path: Option<PathBuf>) -> AppResult<()> {
+    let path = path.unwrap_or_else(|| PathBuf::from(dataset.default_path()));
+
+    info!(
+        "Starting {} import from {}...",
+        dataset.as_str(),
+        path.display()
+    );
+    let batch = import_dataset(&state.import_targets(), dataset, &path.to_string_lossy()).await?;
+    info!(
+        "Import batch {} finished: {} succeeded, {} failed.",
+        batch.batch_id, batch.success_count, batch.error_count
+    );
+
+    Ok(())
+}
+
+pub async fn import_geolocation(state: &AppState, path: Option<PathBuf>) -> AppResult<()> {
+    let path = path.unwrap_or_else(|| PathBuf::from(GEOLOCATION_PATH));
+
+    info!("Loading zip code locations from {}...", path.display());
+    let report = load_geolocation(&state.freight_service, &path.to_string_lossy()).await?;
+    info!(
+        "Read {} geolocation rows ({} skipped), saved {} zip code prefixes.",
+        report.rows, report.skipped, report.prefixes
+    );
+
+    Ok(())
+}
+
+pub async fn seed(state: &AppState, options: SeedOptions) -> AppResult<()> {
+    let report = seed_data(&state.import_targets(), &options).await?;
+    info!(
+        "Seed {} finished: {} orders with {} items, {} failed.",
+        report.seed, report.orders, report.order_items, report.error_count
+    );
+
+    Ok(())
+}
+
+pub async fn export(
+    state: &AppState,
+    entity: ExportEntity,
+    format: ExportFormatArg,
+    output: Option<PathBuf>,
+) -> AppResult<()> {
+    let format = ExportFormat::from(format);
+    let mut chunks = match entity {
+        ExportEntity::Customers => encode_rows(state.customer_service.export_all(), format).boxed(),
+        ExportEntity::Orders => encode_rows(state.order_service.export_all(), format).boxed(),
+        ExportEntity::Products => encode_rows(state.product_service.export_all(), format).boxed(),
+    };
+
+    let mut writer: Box<dyn Write> = match &output {
+        Some(path) => Box::new(File::create(path).map_err(|e| {
+            AppError::ConfigError(format!("Failed to create {}: {}", path.display(), e))
+        })?),
+        None => Box::new(io::stdout().lock()),
+    };
+
+    let write_error = |e: io::Error| AppError::ConfigError(format!("Export failed: {}", e));
+    while let Some(chunk) = chunks.next().await {
+        writer
+            .write_all(&chunk.map_err(write_error)?)
+            .map_err(write_error)?;
+    }
+    writer.flush().map_err(write_error)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+
+    async fn sqlite_database() -> Database {
+        let options = SqliteConnectOptions::new()
+            .in_memory(true)
+            .foreign_keys(true);
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .idle_timeout(None)
+            .max_lifetime(None)
+            .connect_with(options)
+            .await
+            .unwrap();
+        Database::Sqlite(pool)
+    }
+
+    async fn tables(database: &Database) -> Vec<String> {
+        let Database::Sqlite(pool) = database else {
+            unreachable!()
+        };
+        sqlx::query_scalar(
+            "SELECT name FROM sqlite_master \
+             WHERE type = 'table' AND name NOT LIKE 'sqlite_%' AND name <> '_sqlx_migrations' \
+             ORDER BY name",
+        )
+        .fetch_all(pool)
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn sqlite_migrations_revert_to_an_empty_schema_and_apply_again() {
+        let database = sqlite_database().await;
+        let count = database
+            .migrator()
+            .iter()
+            .filter(|m| m.migration_type.is_up_migration())
+            .count();
+
+        migrate(&database, MigrateAction::Run).await.unwrap();
+        let schema = tables(&database).await;
+        assert!(schema.contains(&"webhook_subscriptions".to_string()));
+
+        migrate(&database, MigrateAction::Revert { target: None })
+            .await
+            .unwrap();
+        assert_eq!(
+            database.applied_migrations().await.unwrap().len(),
+            count - 1
+        );
+
+        migrate(&database, MigrateAction::Revert { target: Some(0) })
+            .await
+            .unwrap();
+        assert!(database.applied_migrations().await.unwrap().is_empty());
+        assert!(tables(&database).await.is_empty());
+
+        migrate(&database, MigrateAction::Run).await.unwrap();
+        assert_eq!(database.applied_migrations().await.unwrap().len(), count);
+        assert_eq!(tables(&database).await, schema);
+    }
+
+    #[test]
+    fn refuses_to_revert_migrations_without_a_down_script() {
+        let migrator = sqlx::migrate!("../../migrations");
+        let applied: Vec<i64> = migrator
+            .iter()
+            .filter(|m| !m.migration_type.is_down_migration())
+            .map(|m| m.version)
+            .rev()
+            .collect();
+
+        let refused = irreversible_migration(&migrator, &applied, 0).unwrap();
+        assert_eq!(refused.version, 20251212203556);
+        assert_eq!(refused.description, "create reviews table");
+
+        assert!(irreversible_migration(&migrator, &applied, 20251212203556).is_none());
+    }
+}
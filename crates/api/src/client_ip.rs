@@ -0,0 +1,251 @@
+use axum::{
+    extract::{ConnectInfo, OptionalFromRequestParts, Request, State},
+    http::{HeaderMap, request::Parts},
+    middleware::Next,
+    response::Response,
+};
+use ipnet::IpNet;
+use std::convert::Infallible;
+use std::fmt;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+
+const FORWARDED_HEADER: &str = "forwarded";
+const X_FORWARDED_FOR_HEADER: &str = "x-forwarded-for";
+
+/// The address of the client a request came from, resolved through trusted proxies by
+/// [`resolve_client_ip`]. Absent when the server wasn't given peer addresses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClientIp(pub IpAddr);
+
+impl fmt::Display for ClientIp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<S: Send + Sync> OptionalFromRequestParts<S> for ClientIp {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        _state: &S,
+    ) -> Result<Option<Self>, Self::Rejection> {
+        Ok(parts.extensions.get::<ClientIp>().copied())
+    }
+}
+
+/// The load balancers and reverse proxies (`TRUSTED_PROXIES`) whose `Forwarded` and
+/// `X-Forwarded-For` headers are believed. Anyone else could put any address there.
+#[derive(Clone, Default)]
+pub struct TrustedProxies(Arc<Vec<IpNet>>);
+
+impl TrustedProxies {
+    pub fn new(networks: Vec<IpNet>) -> Self {
+        Self(Arc::new(networks))
+    }
+
+    fn contains(&self, ip: &IpAddr) -> bool {
+        self.0.iter().any(|network| network.contains(ip))
+    }
+
+    /// The client behind `peer`. Forwarding headers only count when `peer` is trusted; the
+    /// chain is then read from the nearest hop back, and the first untrusted address is the
+    /// client. A chain of nothing but trusted proxies yields its farthest hop. An unparseable
+    /// hop ends the walk at the last trusted address, since whatever lies beyond it came
+    /// through a hop nobody vouches for.
+    fn resolve(&self, peer: IpAddr, headers: &HeaderMap) -> IpAddr {
+        if !self.contains(&peer) {
+            return peer;
+        }
+
+        let chain = forwarded_chain(headers);
+        let mut client = peer;
+        for hop in chain.iter().rev() {
+            let Some(hop) = hop else {
+                break;
+            };
+            client = *hop;
+            if !self.contains(hop) {
+                break;
+            }
+        }
+        client
+    }
+}
+
+/// The `for=` addresses of `Forwarded`, or else the addresses in `X-Forwarded-For`, from the
+/// original client to the nearest proxy. Unparseable entries (`unknown`, obfuscated names)
+/// are `None`.
+fn forwarded_chain(headers: &HeaderMap) -> Vec<Option<IpAddr>> {
+    let values = |name: &str| -> Vec<String> {
+        headers
+            .get_all(name)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(|element| element.trim().to_string())
+            .collect()
+    };
+
+    let forwarded = values(FORWARDED_HEADER);
+    if !forwarded.is_empty() {
+        return forwarded
+            .iter()
+            .map(|element| {
+                element.split(';').find_map(|pair| {
+                    let (key, value) = pair.trim().split_once('=')?;
+                    key.eq_ignore_ascii_case("for")
+                        .then(|| parse_node(value.trim_matches('"')))
+                        .flatten()
+                })
+            })
+            .collect();
+    }
+
+    values(X_FORWARDED_FOR_HEADER)
+        .iter()
+        .map(|element| parse_node(element))
+        .collect()
+}
+
+/// Parses `203.0.113.7`, `203.0.113.7:4711`, `2001:db8::1` or `[2001:db8::1]:4711`.
+fn parse_node(node: &str) -> Option<IpAddr> {
+    if let Ok(ip) = node.parse() {
+        return Some(ip);
+    }
+    if let Ok(address) = node.parse::<SocketAddr>() {
+        return Some(address.ip());
+    }
+    node.strip_prefix('[')
+        .and_then(|rest| rest.split_once(']'))
+        .and_then(|(ip, _)| ip.parse().ok())
+}
+
+/// Adds the request's [`ClientIp`], for the audit log and any per-client limits.
+pub async fn resolve_client_ip(
+    State(trusted): State<TrustedProxies>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let peer = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(peer)| peer.ip());
+    if let Some(peer) = peer {
+        let client = trusted.resolve(peer, request.headers());
+        request.extensions_mut().insert(ClientIp(client));
+    }
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn ip(address: &str) -> IpAddr {
+        address.parse().unwrap()
+    }
+
+    fn trusted(networks: &[&str]) -> TrustedProxies {
+        TrustedProxies::new(networks.iter().map(|net| net.parse().unwrap()).collect())
+    }
+
+    fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.append(*name, HeaderValue::from_static(value));
+        }
+        headers
+    }
+
+    #[test]
+    fn parses_addresses_with_and_without_ports() {
+        assert_eq!(parse_node("203.0.113.7"), Some(ip("203.0.113.7")));
+        assert_eq!(parse_node("203.0.113.7:4711"), Some(ip("203.0.113.7")));
+        assert_eq!(parse_node("2001:db8::1"), Some(ip("2001:db8::1")));
+        assert_eq!(parse_node("[2001:db8::1]:4711"), Some(ip("2001:db8::1")));
+        assert_eq!(parse_node("[2001:db8::1]"), Some(ip("2001:db8::1")));
+        assert_eq!(parse_node("unknown"), None);
+        assert_eq!(parse_node("_hidden"), None);
+    }
+
+    #[test]
+    fn ignores_headers_from_untrusted_peers() {
+        let headers = headers(&[(X_FORWARDED_FOR_HEADER, "198.51.100.1")]);
+        let proxies = trusted(&["10.0.0.0/8"]);
+        assert_eq!(
+            proxies.resolve(ip("203.0.113.7"), &headers),
+            ip("203.0.113.7")
+        );
+        assert_eq!(
+            TrustedProxies::default().resolve(ip("10.0.0.1"), &headers),
+            ip("10.0.0.1")
+        );
+    }
+
+    #[test]
+    fn takes_the_first_untrusted_hop_from_the_nearest() {
+        let headers = headers(&[(X_FORWARDED_FOR_HEADER, "192.0.2.9, 198.51.100.1, 10.0.0.2")]);
+        let proxies = trusted(&["10.0.0.0/8"]);
+        assert_eq!(
+            proxies.resolve(ip("10.0.0.1"), &headers),
+            ip("198.51.100.1")
+        );
+    }
+
+    #[test]
+    fn yields_the_farthest_hop_of_an_all_trusted_chain() {
+        let headers = headers(&[(X_FORWARDED_FOR_HEADER, "10.0.0.3, 10.0.0.2")]);
+        let proxies = trusted(&["10.0.0.0/8"]);
+        assert_eq!(proxies.resolve(ip("10.0.0.1"), &headers), ip("10.0.0.3"));
+        assert_eq!(
+            proxies.resolve(ip("10.0.0.1"), &HeaderMap::new()),
+            ip("10.0.0.1")
+        );
+    }
+
+    #[test]
+    fn prefers_forwarded_over_x_forwarded_for() {
+        let headers = headers(&[
+            (
+                FORWARDED_HEADER,
+                "for=192.0.2.60;proto=http, For=\"[2001:db8::17]:4711\"",
+            ),
+            (X_FORWARDED_FOR_HEADER, "198.51.100.1"),
+        ]);
+        assert_eq!(
+            forwarded_chain(&headers),
+            vec![Some(ip("192.0.2.60")), Some(ip("2001:db8::17"))]
+        );
+    }
+
+    #[test]
+    fn joins_repeated_headers_and_keeps_unknown_entries_in_place() {
+        let headers = headers(&[
+            (X_FORWARDED_FOR_HEADER, "192.0.2.1, unknown"),
+            (X_FORWARDED_FOR_HEADER, "10.0.0.2"),
+        ]);
+        assert_eq!(
+            forwarded_chain(&headers),
+            vec![Some(ip("192.0.2.1")), None, Some(ip("10.0.0.2"))]
+        );
+    }
+
+    #[test]
+    fn stops_at_the_first_unparseable_hop() {
+        let proxies = trusted(&["127.0.0.1/32", "10.0.0.0/8"]);
+        let unknown_last = headers(&[(X_FORWARDED_FOR_HEADER, "1.2.3.4, unknown")]);
+        assert_eq!(
+            proxies.resolve(ip("127.0.0.1"), &unknown_last),
+            ip("127.0.0.1")
+        );
+
+        let unknown_between = headers(&[(X_FORWARDED_FOR_HEADER, "1.2.3.4, unknown, 10.0.0.2")]);
+        assert_eq!(
+            proxies.resolve(ip("10.0.0.1"), &unknown_between),
+            ip("10.0.0.2")
+        );
+    }
+}
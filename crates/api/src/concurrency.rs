@@ -0,0 +1,126 @@
+use axum::{Router, error_handling::HandleErrorLayer};
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tower::{
+    BoxError, ServiceBuilder, limit::GlobalConcurrencyLimitLayer, load_shed::LoadShedLayer,
+};
+
+use domain::error::AppError;
+
+use crate::error::ApiError;
+
+/// Caps how many requests run at once across every router it is applied to, so a burst can't
+/// queue hundreds of requests on a pool of ten database connections. Requests over the cap are
+/// answered with `503` and `Retry-After` right away instead of waiting.
+///
+/// The permit is held until the response head is sent, so streams and WebSockets only count
+/// while they are being opened; long-polls count for as long as they wait.
+#[derive(Clone)]
+pub struct ConcurrencyLimit {
+    /// `None` when the limit is disabled.
+    permits: Option<Arc<Semaphore>>,
+    retry_after_seconds: u64,
+}
+
+impl ConcurrencyLimit {
+    /// `max_requests` of 0 disables the limit.
+    pub fn new(max_requests: usize, retry_after_seconds: u64) -> Self {
+        Self {
+            permits: (max_requests > 0).then(|| Arc::new(Semaphore::new(max_requests))),
+            retry_after_seconds,
+        }
+    }
+
+    pub fn apply<S>(&self, router: Router<S>) -> Router<S>
+    where
+        S: Clone + Send + Sync + 'static,
+    {
+        let Some(permits) = &self.permits else {
+            return router;
+        };
+        let retry_after = self.retry_after_seconds;
+        router.layer(
+            ServiceBuilder::new()
+                .layer(HandleErrorLayer::new(move |_: BoxError| async move {
+                    ApiError(AppError::Overloaded(retry_after))
+                }))
+                .layer(LoadShedLayer::new())
+                .layer(GlobalConcurrencyLimitLayer::with_semaphore(permits.clone())),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::{Request, StatusCode, header};
+    use axum::routing::get;
+    use tokio::sync::Notify;
+    use tower::ServiceExt;
+
+    /// A route whose requests hold their permit until `release` is notified.
+    fn blocking_router(entered: Arc<Notify>, release: Arc<Notify>) -> Router {
+        Router::new()
+            .route(
+                "/slow",
+                get(move || async move {
+                    entered.notify_one();
+                    release.notified().await;
+                }),
+            )
+            .route("/fast", get(|| async {}))
+    }
+
+    async fn status(router: &Router, path: &str) -> (StatusCode, Option<String>) {
+        let response = router
+            .clone()
+            .oneshot(Request::get(path).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let retry_after = response
+            .headers()
+            .get(header::RETRY_AFTER)
+            .map(|value| value.to_str().unwrap().to_string());
+        (response.status(), retry_after)
+    }
+
+    #[tokio::test]
+    async fn sheds_requests_over_the_limit_until_a_permit_frees() {
+        let (entered, release) = (Arc::new(Notify::new()), Arc::new(Notify::new()));
+        let router =
+            ConcurrencyLimit::new(1, 7).apply(blocking_router(entered.clone(), release.clone()));
+
+        let slow = tokio::spawn({
+            let router = router.clone();
+            async move { status(&router, "/slow").await }
+        });
+        entered.notified().await;
+
+        assert_eq!(
+            status(&router, "/fast").await,
+            (StatusCode::SERVICE_UNAVAILABLE, Some("7".to_string()))
+        );
+
+        release.notify_one();
+        assert_eq!(slow.await.unwrap(), (StatusCode::OK, None));
+        assert_eq!(status(&router, "/fast").await, (StatusCode::OK, None));
+    }
+
+    #[tokio::test]
+    async fn a_limit_of_zero_lets_everything_through() {
+        let (entered, release) = (Arc::new(Notify::new()), Arc::new(Notify::new()));
+        let router =
+            ConcurrencyLimit::new(0, 7).apply(blocking_router(entered.clone(), release.clone()));
+
+        let slow = tokio::spawn({
+            let router = router.clone();
+            async move { status(&router, "/slow").await }
+        });
+        entered.notified().await;
+
+        assert_eq!(status(&router, "/fast").await, (StatusCode::OK, None));
+        release.notify_one();
+        assert_eq!(slow.await.unwrap(), (StatusCode::OK, None));
+    }
+}
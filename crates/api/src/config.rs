@@ -0,0 +1,1698 @@
+use analytics::corpus::CorpusConfig;
+use axum::http::HeaderName;
+use bigdecimal::BigDecimal;
+use cron::Schedule;
+use domain::config::{
+    AmendmentConfig, DeletePolicy, DeletePolicyConfig, FreightConfig, FreightRate,
+    NotificationConfig, OutboxConfig, ProductImageConfig, RetentionConfig, ScorecardConfig,
+    SupportConfig, WebhookConfig,
+};
+use domain::error::AppError;
+use domain::models::{DataQualityRule, NotificationKind};
+use domain::notifications::{NotificationTemplate, NotificationTemplates};
+use domain::tenancy::TenantId;
+use importer::services::ImportConfig;
+use ipnet::IpNet;
+use persistence::collation::SortCollation;
+use persistence::retry::RetryPolicy;
+use persistence::streaming::ChangeFormat;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::fs;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tower_http::compression::CompressionLayer;
+use tower_http::cors::{AllowHeaders, AllowMethods, AllowOrigin, Any, CorsLayer};
+
+#[derive(Clone)]
+pub struct AppConfig {
+    pub database_url: String,
+    /// Postgres read replica for repository reads; writes always go to `database_url`.
+    pub database_replica_url: Option<String>,
+    pub port: u16,
+    /// Serve HTTPS with this certificate instead of plain HTTP.
+    pub tls: Option<TlsConfig>,
+    /// Proxies whose `Forwarded`/`X-Forwarded-For` headers name the real client.
+    pub trusted_proxies: Vec<IpNet>,
+    pub cors: CorsConfig,
+    pub pool: PoolConfig,
+    pub warmup: WarmupConfig,
+    pub collation: SortCollation,
+    pub similarity_enabled: bool,
+    pub amendments: AmendmentConfig,
+    pub freight: FreightConfig,
+    pub request_timeout_secs: u64,
+    pub max_body_bytes: usize,
+    pub concurrency: ConcurrencyConfig,
+    pub support: SupportConfig,
+    pub corpus: CorpusConfig,
+    pub import: ImportConfig,
+    pub delete_policies: DeletePolicyConfig,
+    pub public_ids: PublicIdConfig,
+    pub compression_enabled: bool,
+    /// Start in read-only mode, refusing writes until it is switched off.
+    pub read_only: bool,
+    /// Told to clients whose writes are refused while `read_only` is on.
+    pub read_only_reason: Option<String>,
+    pub cache: CacheConfig,
+    pub webhooks: WebhookConfig,
+    pub outbox: OutboxConfig,
+    pub event_stream: EventStreamConfig,
+    pub change_stream: ChangeStreamConfig,
+    pub carrier: CarrierConfig,
+    pub storage: StorageConfig,
+    pub product_images: ProductImageConfig,
+    pub zip_lookup: ZipLookupConfig,
+    pub payments: PaymentConfig,
+    pub notifier: NotifierConfig,
+    pub notifications: NotificationConfig,
+    pub retention: RetentionConfig,
+    pub jobs: JobsConfig,
+    /// Minutes between seller badge refreshes; 0 disables the job.
+    pub seller_badges_refresh_minutes: u64,
+    pub seller_scorecard: ScorecardConfig,
+    /// Rules evaluated by the data quality job, from the file named by `DATA_QUALITY_RULES_PATH`.
+    pub data_quality_rules: Vec<DataQualityRule>,
+    /// `tracing` filter directives, e.g. `info` or `info,sqlx=warn`.
+    pub log_level: String,
+    pub access_log: AccessLogConfig,
+    pub tenancy: TenancyConfig,
+}
+
+/// Configuration values from the optional file named by `APP_CONFIG` (TOML or YAML), with
+/// environment variables taking precedence.
+///
+/// File keys map onto the environment variable names by joining nested keys with `_` and
+/// upper-casing, so `[database] max_connections` is overridden by `DATABASE_MAX_CONNECTIONS`.
+/// Arrays are joined with commas.
+#[derive(Default)]
+pub struct ConfigSource {
+    file: HashMap<String, String>,
+}
+
+impl ConfigSource {
+    pub fn load() -> Result<Self, AppError> {
+        let Ok(path) = env::var("APP_CONFIG") else {
+            return Ok(Self::default());
+        };
+
+        let contents = fs::read_to_string(&path).map_err(|e| {
+            AppError::ConfigError(format!("Failed to read config file {}: {}", path, e))
+        })?;
+
+        let value: Value = match Path::new(&path).extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => toml::from_str(&contents)
+                .map_err(|e| AppError::ConfigError(format!("Invalid TOML in {}: {}", path, e)))?,
+            Some("yaml" | "yml") => serde_yaml::from_str(&contents)
+                .map_err(|e| AppError::ConfigError(format!("Invalid YAML in {}: {}", path, e)))?,
+            _ => {
+                return Err(AppError::ConfigError(format!(
+                    "APP_CONFIG must point to a .toml, .yaml or .yml file, got {}",
+                    path
+                )));
+            }
+        };
+
+        let mut file = HashMap::new();
+        flatten_config("", &value, &mut file);
+        Ok(Self { file })
+    }
+
+    /// A source whose `values` stand in for the config file, still overridden by the
+    /// environment.
+    pub fn from_values<'a>(values: impl IntoIterator<Item = (&'a str, &'a str)>) -> Self {
+        Self {
+            file: values
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+        }
+    }
+
+    /// Looks `name` up in the environment first, then in the config file.
+    pub fn var(&self, name: &str) -> Result<String, env::VarError> {
+        env::var(name).or_else(|e| self.file.get(name).cloned().ok_or(e))
+    }
+}
+
+fn flatten_config(prefix: &str, value: &Value, out: &mut HashMap<String, String>) {
+    match value {
+        Value::Object(table) => {
+            for (key, nested) in table {
+                let key = key.to_uppercase();
+                let name = if prefix.is_empty() {
+                    key
+                } else {
+                    format!("{}_{}", prefix, key)
+                };
+                flatten_config(&name, nested, out);
+            }
+        }
+        Value::Array(items) => {
+            let joined = items
+                .iter()
+                .map(|item| match item {
+                    Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join(",");
+            out.insert(prefix.to_string(), joined);
+        }
+        Value::String(s) => {
+            out.insert(prefix.to_string(), s.clone());
+        }
+        Value::Null => {}
+        other => {
+            out.insert(prefix.to_string(), other.to_string());
+        }
+    }
+}
+
+/// PEM files for HTTPS, re-read on `SIGHUP`.
+#[derive(Clone)]
+pub struct TlsConfig {
+    /// Certificate chain, leaf first.
+    pub cert_path: PathBuf,
+    pub key_path: PathBuf,
+}
+
+/// CORS policies by route group; see [`crate::cors`] for which requests fall in which.
+#[derive(Clone)]
+pub struct CorsConfig {
+    /// Reads outside `/admin`, from `CORS_*`.
+    pub public: CorsPolicy,
+    /// `/admin`, `/load-data` and every write, from `CORS_ADMIN_*`. Each setting left unset
+    /// falls back to the public one.
+    pub admin: CorsPolicy,
+}
+
+#[derive(Clone)]
+pub struct CorsPolicy {
+    pub allowed_origins: AllowOrigin,
+    pub allow_credentials: bool,
+    pub max_age_seconds: u64,
+    /// Response headers scripts on other origins may read.
+    pub exposed_headers: Vec<HeaderName>,
+}
+
+#[derive(Clone)]
+pub struct PoolConfig {
+    pub max_connections: u32,
+    pub min_connections: u32,
+    pub acquire_timeout_seconds: u64,
+    /// Idle connections above `min_connections` are closed after this long; 0 keeps them open.
+    pub idle_timeout_seconds: u64,
+    /// Server-side `statement_timeout` for every pooled connection; 0 disables it.
+    pub statement_timeout_ms: u64,
+    /// Retries of queries that failed transiently, e.g. during a failover.
+    pub retry: RetryPolicy,
+    /// How long reads stay on the primary after the replica failed to answer.
+    pub replica_recheck_seconds: u64,
+}
+
+#[derive(Clone)]
+pub struct CacheConfig {
+    /// Responses are cached only when set.
+    pub redis_url: Option<String>,
+    pub ttl_seconds: u64,
+    /// Per-lookup capacity of the in-process product, category and centroid cache; 0 disables
+    /// it.
+    pub lookup_max_entries: u64,
+    pub lookup_ttl_seconds: u64,
+}
+
+#[derive(Clone)]
+pub struct EventStreamConfig {
+    /// Outbox events are published to a Redis stream only when set.
+    pub redis_url: Option<String>,
+    pub key: String,
+    /// Approximate number of entries the stream is trimmed to.
+    pub max_len: usize,
+}
+
+/// Broker the change stream is published to.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ChangeStreamBackend {
+    #[default]
+    None,
+    /// Needs a build with the `kafka` feature.
+    Kafka,
+    /// Needs a build with the `nats` feature.
+    Nats,
+}
+
+impl std::str::FromStr for ChangeStreamBackend {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_lowercase().as_str() {
+            "none" | "" => Ok(ChangeStreamBackend::None),
+            "kafka" => Ok(ChangeStreamBackend::Kafka),
+            "nats" => Ok(ChangeStreamBackend::Nats),
+            other => Err(format!("unknown change stream backend '{}'", other)),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct ChangeStreamConfig {
+    pub backend: ChangeStreamBackend,
+    /// Kafka bootstrap servers or NATS server URL.
+    pub url: String,
+    /// Kafka topic, or the NATS subject prefix the entity type is appended to.
+    pub topic: String,
+    pub format: ChangeFormat,
+    /// Changes queued for the publisher before new ones are dropped.
+    pub buffer: usize,
+}
+
+/// Carrier that quotes freight and posts parcels.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CarrierBackend {
+    /// Prices from a fixed rate table, without any external call.
+    #[default]
+    Mock,
+    Correios,
+}
+
+impl std::str::FromStr for CarrierBackend {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_lowercase().as_str() {
+            "mock" | "" => Ok(CarrierBackend::Mock),
+            "correios" => Ok(CarrierBackend::Correios),
+            other => Err(format!("unknown carrier provider '{}'", other)),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct CarrierConfig {
+    pub provider: CarrierBackend,
+    pub timeout_seconds: u64,
+    pub correios: CorreiosConfig,
+}
+
+/// Credentials for the Correios API, issued with a contract in the Meu Correios portal.
+#[derive(Clone)]
+pub struct CorreiosConfig {
+    pub base_url: String,
+    pub username: String,
+    /// The API access code generated in the portal, not the portal password.
+    pub access_code: String,
+    /// Número do cartão de postagem the token is issued for.
+    pub posting_card: String,
+    /// Services to quote, as `(name, product code)` pairs.
+    pub services: Vec<(String, String)>,
+}
+
+/// Where uploaded files are stored.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum StorageBackend {
+    /// Kept in the process, for development and tests; nothing serves the files.
+    #[default]
+    Memory,
+    /// An S3-compatible service: AWS S3, MinIO, Cloudflare R2 and the like.
+    S3,
+}
+
+impl std::str::FromStr for StorageBackend {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_lowercase().as_str() {
+            "memory" | "" => Ok(StorageBackend::Memory),
+            "s3" => Ok(StorageBackend::S3),
+            other => Err(format!("unknown storage provider '{}'", other)),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct StorageConfig {
+    pub provider: StorageBackend,
+    pub timeout_seconds: u64,
+    pub s3: S3Config,
+}
+
+/// Bucket and credentials for S3-compatible storage. Objects are addressed path-style,
+/// `{endpoint}/{bucket}/{key}`, which every S3-compatible service accepts.
+#[derive(Clone)]
+pub struct S3Config {
+    pub endpoint: String,
+    pub region: String,
+    pub bucket: String,
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    /// Base of the URLs stored objects are served from, such as a CDN in front of the
+    /// bucket. Defaults to `{endpoint}/{bucket}`.
+    pub public_url: String,
+}
+
+/// Where CEP lookups are answered.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ZipLookupBackend {
+    /// The public ViaCEP API.
+    #[default]
+    ViaCep,
+    /// The geolocation table: city and state of the CEP's prefix, without an external call.
+    Geolocation,
+}
+
+impl std::str::FromStr for ZipLookupBackend {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_lowercase().as_str() {
+            "viacep" | "" => Ok(ZipLookupBackend::ViaCep),
+            "geolocation" => Ok(ZipLookupBackend::Geolocation),
+            other => Err(format!("unknown zip lookup provider '{}'", other)),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct ZipLookupConfig {
+    pub provider: ZipLookupBackend,
+    pub viacep_base_url: String,
+    pub timeout_seconds: u64,
+}
+
+/// Provider that authorizes, captures and refunds payments.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PaymentBackend {
+    /// Payments kept in memory, without any external call.
+    #[default]
+    Sandbox,
+}
+
+impl std::str::FromStr for PaymentBackend {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_lowercase().as_str() {
+            "sandbox" | "" => Ok(PaymentBackend::Sandbox),
+            other => Err(format!("unknown payment provider '{}'", other)),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct PaymentConfig {
+    pub provider: PaymentBackend,
+    /// Secret the provider signs its notifications with. Notifications are refused while it
+    /// is empty.
+    pub webhook_secret: String,
+}
+
+/// Channel notifications are sent through.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NotifierBackend {
+    /// The application log only.
+    #[default]
+    Log,
+    /// Email through an SMTP server.
+    Smtp,
+}
+
+impl std::str::FromStr for NotifierBackend {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_lowercase().as_str() {
+            "log" | "" => Ok(NotifierBackend::Log),
+            "smtp" => Ok(NotifierBackend::Smtp),
+            other => Err(format!("unknown notification provider '{}'", other)),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct NotifierConfig {
+    pub provider: NotifierBackend,
+    /// Addresses notifications are emailed to. The Olist data has no customer contact
+    /// details, so these are the shop's own mailboxes.
+    pub recipients: Vec<String>,
+    /// Built-in templates, with those found in `NOTIFICATION_TEMPLATE_DIR` in their place.
+    pub templates: NotificationTemplates,
+    pub smtp: SmtpConfig,
+}
+
+/// How the connection to the SMTP server is secured.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SmtpSecurity {
+    /// Plain connection upgraded with STARTTLS, usually on port 587.
+    #[default]
+    StartTls,
+    /// TLS from the start, usually on port 465.
+    Tls,
+    /// No encryption, for local relays and test servers only.
+    None,
+}
+
+impl std::str::FromStr for SmtpSecurity {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_lowercase().as_str() {
+            "starttls" | "" => Ok(SmtpSecurity::StartTls),
+            "tls" => Ok(SmtpSecurity::Tls),
+            "none" => Ok(SmtpSecurity::None),
+            other => Err(format!("unknown SMTP security '{}'", other)),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct SmtpConfig {
+    pub host: String,
+    pub port: u16,
+    pub security: SmtpSecurity,
+    /// No authentication when empty.
+    pub username: String,
+    pub password: String,
+    /// Sender address, e.g. `Shop <noreply@example.com>`.
+    pub from: String,
+    pub timeout_seconds: u64,
+}
+
+/// Cron schedules of the jobs run by [`crate::scheduler`]; `None` disables a job.
+#[derive(Clone)]
+pub struct JobsConfig {
+    pub refresh_analytics_views: Option<Schedule>,
+    pub retry_failed_webhooks: Option<Schedule>,
+    pub apply_retention: Option<Schedule>,
+    pub tag_review_sentiment: Option<Schedule>,
+    pub flag_late_orders: Option<Schedule>,
+    pub evaluate_data_quality: Option<Schedule>,
+    /// Failed webhook deliveries older than this are no longer retried.
+    pub failed_webhooks_max_age_hours: i64,
+    /// Orders that were due more than this many days ago are not flagged as late.
+    pub late_orders_max_age_days: i64,
+}
+
+/// How many requests run at once before the rest are shed with `503`. A limit of 0 disables
+/// it.
+#[derive(Clone)]
+pub struct ConcurrencyConfig {
+    pub max_requests: usize,
+    /// Separate, higher limit for `/health/*`, so probes still answer under load.
+    pub health_max_requests: usize,
+    /// Sent as `Retry-After` with shed requests.
+    pub retry_after_seconds: u64,
+}
+
+/// Which requests get an access log line.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AccessLogLevel {
+    /// None.
+    Off,
+    /// Those answered with 4xx or 5xx, and slow ones.
+    Errors,
+    /// Every request.
+    #[default]
+    All,
+}
+
+impl std::str::FromStr for AccessLogLevel {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_lowercase().as_str() {
+            "off" | "none" => Ok(AccessLogLevel::Off),
+            "errors" => Ok(AccessLogLevel::Errors),
+            "all" | "" => Ok(AccessLogLevel::All),
+            other => Err(format!("unknown access log level '{}'", other)),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct AccessLogConfig {
+    pub level: AccessLogLevel,
+    /// Requests taking longer are logged as warnings; `None` disables the warning.
+    pub slow_request_threshold: Option<Duration>,
+}
+
+#[derive(Clone)]
+pub struct WarmupConfig {
+    pub enabled: bool,
+    pub pool_connections: u32,
+    pub canary_attempts: u32,
+    pub canary_timeout_seconds: u64,
+}
+
+/// How entity ids appear on the public tracking and storefront endpoints.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PublicIdMode {
+    /// Raw dataset ids, as stored.
+    #[default]
+    Plain,
+    /// Ids run through a keyed permutation; raw ids are not accepted on those endpoints.
+    Obfuscated,
+}
+
+impl std::str::FromStr for PublicIdMode {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_lowercase().as_str() {
+            "plain" => Ok(PublicIdMode::Plain),
+            "obfuscated" => Ok(PublicIdMode::Obfuscated),
+            other => Err(format!("unknown public id codec '{}'", other)),
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct PublicIdConfig {
+    pub mode: PublicIdMode,
+    /// Key for the obfuscated mode. Changing it changes every public id.
+    pub secret: String,
+}
+
+/// How a request names the tenant (marketplace) it belongs to.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TenantResolver {
+    /// Every request belongs to the default tenant.
+    #[default]
+    Off,
+    /// The tenant is the value of the `TENANT_HEADER` request header.
+    Header,
+    /// The tenant is the subdomain of `TENANT_BASE_DOMAIN` in the `Host` header, e.g. `acme`
+    /// for `acme.shop.example`.
+    Subdomain,
+}
+
+impl std::str::FromStr for TenantResolver {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_lowercase().as_str() {
+            "off" | "none" | "" => Ok(TenantResolver::Off),
+            "header" => Ok(TenantResolver::Header),
+            "subdomain" => Ok(TenantResolver::Subdomain),
+            other => Err(format!("unknown tenant resolver '{}'", other)),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct TenancyConfig {
+    pub resolver: TenantResolver,
+    pub header: HeaderName,
+    /// Domain whose subdomains name tenants, without a leading dot.
+    pub base_domain: Option<String>,
+    /// Tenants this deployment serves; requests naming any other are refused. Only the
+    /// default tenant while the resolver is off.
+    pub tenants: Vec<TenantId>,
+}
+
+pub fn load_config() -> Result<AppConfig, AppError> {
+    load_config_from(&ConfigSource::load()?)
+}
+
+pub fn load_config_from(source: &ConfigSource) -> Result<AppConfig, AppError> {
+    let database_url = source
+        .var("DATABASE_URL")
+        .map_err(|_| AppError::ConfigError("DATABASE_URL must be set".to_string()))?;
+
+    let port = source
+        .var("PORT")
+        .unwrap_or_else(|_| "3000".to_string())
+        .parse()
+        .map_err(|e| AppError::ConfigError(format!("Invalid PORT: {}", e)))?;
+
+    Ok(AppConfig {
+        database_url,
+        database_replica_url: source
+            .var("DATABASE_REPLICA_URL")
+            .ok()
+            .filter(|url| !url.trim().is_empty()),
+        port,
+        tls: load_tls_config(source)?,
+        trusted_proxies: load_trusted_proxies(source)?,
+        cors: load_cors_config(source)?,
+        pool: load_pool_config(source),
+        warmup: load_warmup_config(source),
+        collation: source
+            .var("TEXT_COLLATION")
+            .unwrap_or_else(|_| "default".to_string())
+            .parse()
+            .map_err(|e| AppError::ConfigError(format!("Invalid TEXT_COLLATION: {}", e)))?,
+        similarity_enabled: source
+            .var("SIMILARITY_ENABLED")
+            .unwrap_or_else(|_| "false".to_string())
+            .parse()
+            .unwrap_or(false),
+        amendments: load_amendment_config(source)?,
+        freight: load_freight_config(source)?,
+        request_timeout_secs: source
+            .var("REQUEST_TIMEOUT_SECS")
+            .unwrap_or_else(|_| "30".to_string())
+            .parse()
+            .unwrap_or(30),
+        max_body_bytes: source
+            .var("MAX_BODY_BYTES")
+            .unwrap_or_else(|_| "2097152".to_string())
+            .parse()
+            .unwrap_or(2_097_152),
+        concurrency: load_concurrency_config(source),
+        support: load_support_config(source),
+        corpus: load_corpus_config(source),
+        import: load_import_config(source),
+        delete_policies: load_delete_policy_config(source)?,
+        public_ids: load_public_id_config(source)?,
+        log_level: source
+            .var("LOGGING_LEVEL")
+            .or_else(|_| source.var("RUST_LOG"))
+            .unwrap_or_else(|_| "info".to_string()),
+        access_log: load_access_log_config(source)?,
+        tenancy: load_tenancy_config(source)?,
+        seller_badges_refresh_minutes: source
+            .var("SELLER_BADGES_REFRESH_MINUTES")
+            .unwrap_or_else(|_| "60".to_string())
+            .parse()
+            .unwrap_or(60),
+        seller_scorecard: load_scorecard_config(source),
+        data_quality_rules: load_data_quality_rules(source)?,
+        compression_enabled: source
+            .var("COMPRESSION_ENABLED")
+            .unwrap_or_else(|_| "true".to_string())
+            .parse()
+            .unwrap_or(true),
+        read_only: source
+            .var("READ_ONLY_MODE")
+            .unwrap_or_else(|_| "false".to_string())
+            .parse()
+            .unwrap_or(false),
+        read_only_reason: source
+            .var("READ_ONLY_REASON")
+            .ok()
+            .filter(|reason| !reason.trim().is_empty()),
+        cache: load_cache_config(source),
+        webhooks: load_webhook_config(source),
+        outbox: load_outbox_config(source),
+        event_stream: load_event_stream_config(source),
+        change_stream: load_change_stream_config(source)?,
+        carrier: load_carrier_config(source)?,
+        storage: load_storage_config(source)?,
+        product_images: load_product_image_config(source),
+        zip_lookup: load_zip_lookup_config(source)?,
+        payments: load_payment_config(source)?,
+        notifier: load_notifier_config(source)?,
+        notifications: load_notification_config(source),
+        retention: load_retention_config(source),
+        jobs: load_jobs_config(source)?,
+    })
+}
+
+/// HTTPS is on when both `TLS_CERT_PATH` and `TLS_KEY_PATH` are set.
+pub fn load_tls_config(source: &ConfigSource) -> Result<Option<TlsConfig>, AppError> {
+    let path = |name: &str| source.var(name).ok().filter(|path| !path.trim().is_empty());
+    match (path("TLS_CERT_PATH"), path("TLS_KEY_PATH")) {
+        (Some(cert_path), Some(key_path)) => Ok(Some(TlsConfig {
+            cert_path: cert_path.into(),
+            key_path: key_path.into(),
+        })),
+        (None, None) => Ok(None),
+        _ => Err(AppError::ConfigError(
+            "TLS_CERT_PATH and TLS_KEY_PATH must be set together".to_string(),
+        )),
+    }
+}
+
+/// `TRUSTED_PROXIES`: comma-separated addresses or CIDR ranges, e.g. `10.0.0.0/8,127.0.0.1`.
+pub fn load_trusted_proxies(source: &ConfigSource) -> Result<Vec<IpNet>, AppError> {
+    source
+        .var("TRUSTED_PROXIES")
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|proxy| !proxy.is_empty())
+        .map(|proxy| {
+            proxy
+                .parse::<IpNet>()
+                .or_else(|_| proxy.parse::<IpAddr>().map(IpNet::from))
+                .map_err(|_| {
+                    AppError::ConfigError(format!(
+                        "Invalid TRUSTED_PROXIES entry '{}': expected an address or CIDR range",
+                        proxy
+                    ))
+                })
+        })
+        .collect()
+}
+
+pub fn load_cors_config(source: &ConfigSource) -> Result<CorsConfig, AppError> {
+    Ok(CorsConfig {
+        public: load_cors_policy(source, "CORS", None)?,
+        admin: load_cors_policy(source, "CORS_ADMIN", Some("CORS"))?,
+    })
+}
+
+/// Reads the policy under `prefix` (`{prefix}_ALLOWED_ORIGINS`, ...), taking each unset
+/// setting from `fallback`.
+fn load_cors_policy(
+    source: &ConfigSource,
+    prefix: &str,
+    fallback: Option<&str>,
+) -> Result<CorsPolicy, AppError> {
+    let var = |name: &str| {
+        source
+            .var(&format!("{}_{}", prefix, name))
+            .or_else(|e| match fallback {
+                Some(fallback) => source.var(&format!("{}_{}", fallback, name)),
+                None => Err(e),
+            })
+    };
+
+    let allowed_origins_str = var("ALLOWED_ORIGINS").unwrap_or_else(|_| "*".to_string());
+    let allow_credentials = var("ALLOW_CREDENTIALS")
+        .unwrap_or_else(|_| "false".to_string())
+        .parse()
+        .unwrap_or(false);
+
+    let allowed_origins = if allowed_origins_str == "*" {
+        // Browsers refuse credentialed responses for a wildcard origin.
+        if allow_credentials {
+            return Err(AppError::ConfigError(format!(
+                "{}_ALLOW_CREDENTIALS=true needs an explicit {}_ALLOWED_ORIGINS list, not \"*\"",
+                prefix, prefix
+            )));
+        }
+        Any.into()
+    } else {
+        let origins: Vec<_> = allowed_origins_str
+            .split(',')
+            .map(|s| s.trim().parse())
+            .collect::<Result<_, _>>()
+            .map_err(|e| AppError::ConfigError(format!("Invalid CORS origin: {}", e)))?;
+        AllowOrigin::list(origins)
+    };
+
+    let exposed_headers = var("EXPOSED_HEADERS")
+        .unwrap_or_else(|_| "x-request-id,api-version,deprecation,link,retry-after".to_string())
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(|name| {
+            name.parse().map_err(|e| {
+                AppError::ConfigError(format!(
+                    "Invalid {}_EXPOSED_HEADERS header '{}': {}",
+                    prefix, name, e
+                ))
+            })
+        })
+        .collect::<Result<_, _>>()?;
+
+    Ok(CorsPolicy {
+        allowed_origins,
+        allow_credentials,
+        max_age_seconds: var("MAX_AGE")
+            .unwrap_or_else(|_| "3600".to_string())
+            .parse()
+            .unwrap_or(3600),
+        exposed_headers,
+    })
+}
+
+pub fn load_pool_config(source: &ConfigSource) -> PoolConfig {
+    PoolConfig {
+        max_connections: source
+            .var("DATABASE_MAX_CONNECTIONS")
+            .unwrap_or_else(|_| "10".to_string())
+            .parse()
+            .unwrap_or(10),
+        min_connections: source
+            .var("DATABASE_MIN_CONNECTIONS")
+            .unwrap_or_else(|_| "0".to_string())
+            .parse()
+            .unwrap_or(0),
+        acquire_timeout_seconds: source
+            .var("DATABASE_ACQUIRE_TIMEOUT_SECONDS")
+            .unwrap_or_else(|_| "5".to_string())
+            .parse()
+            .unwrap_or(5),
+        idle_timeout_seconds: source
+            .var("DATABASE_IDLE_TIMEOUT_SECONDS")
+            .unwrap_or_else(|_| "600".to_string())
+            .parse()
+            .unwrap_or(600),
+        statement_timeout_ms: source
+            .var("DATABASE_STATEMENT_TIMEOUT_MS")
+            .unwrap_or_else(|_| "0".to_string())
+            .parse()
+            .unwrap_or(0),
+        retry: RetryPolicy {
+            max_attempts: source
+                .var("DATABASE_RETRY_MAX_ATTEMPTS")
+                .unwrap_or_else(|_| "3".to_string())
+                .parse::<u32>()
+                .unwrap_or(3)
+                .max(1),
+            base_delay: Duration::from_millis(
+                source
+                    .var("DATABASE_RETRY_BASE_DELAY_MS")
+                    .unwrap_or_else(|_| "50".to_string())
+                    .parse()
+                    .unwrap_or(50),
+            ),
+            max_delay: Duration::from_millis(
+                source
+                    .var("DATABASE_RETRY_MAX_DELAY_MS")
+                    .unwrap_or_else(|_| "1000".to_string())
+                    .parse()
+                    .unwrap_or(1000),
+            ),
+        },
+        replica_recheck_seconds: source
+            .var("DATABASE_REPLICA_RECHECK_SECONDS")
+            .unwrap_or_else(|_| "30".to_string())
+            .parse()
+            .unwrap_or(30),
+    }
+}
+
+pub fn load_cache_config(source: &ConfigSource) -> CacheConfig {
+    CacheConfig {
+        redis_url: source
+            .var("REDIS_URL")
+            .ok()
+            .filter(|url| !url.trim().is_empty()),
+        ttl_seconds: source
+            .var("CACHE_TTL_SECONDS")
+            .unwrap_or_else(|_| "60".to_string())
+            .parse()
+            .unwrap_or(60),
+        lookup_max_entries: source
+            .var("LOOKUP_CACHE_MAX_ENTRIES")
+            .unwrap_or_else(|_| "10000".to_string())
+            .parse()
+            .unwrap_or(10000),
+        lookup_ttl_seconds: source
+            .var("LOOKUP_CACHE_TTL_SECONDS")
+            .unwrap_or_else(|_| "3600".to_string())
+            .parse()
+            .unwrap_or(3600),
+    }
+}
+
+pub fn load_webhook_config(source: &ConfigSource) -> WebhookConfig {
+    WebhookConfig {
+        poll_interval_seconds: source
+            .var("WEBHOOK_POLL_INTERVAL_SECONDS")
+            .unwrap_or_else(|_| "5".to_string())
+            .parse()
+            .unwrap_or(5),
+        timeout_seconds: source
+            .var("WEBHOOK_TIMEOUT_SECONDS")
+            .unwrap_or_else(|_| "10".to_string())
+            .parse()
+            .unwrap_or(10),
+        max_attempts: source
+            .var("WEBHOOK_MAX_ATTEMPTS")
+            .unwrap_or_else(|_| "8".to_string())
+            .parse()
+            .unwrap_or(8),
+        retry_base_seconds: source
+            .var("WEBHOOK_RETRY_BASE_SECONDS")
+            .unwrap_or_else(|_| "30".to_string())
+            .parse()
+            .unwrap_or(30),
+    }
+}
+
+pub fn load_retention_config(source: &ConfigSource) -> RetentionConfig {
+    RetentionConfig {
+        review_years: source
+            .var("RETENTION_REVIEWS_YEARS")
+            .unwrap_or_else(|_| "0".to_string())
+            .parse()
+            .unwrap_or(0),
+        inactive_customer_years: source
+            .var("RETENTION_INACTIVE_CUSTOMERS_YEARS")
+            .unwrap_or_else(|_| "0".to_string())
+            .parse()
+            .unwrap_or(0),
+    }
+}
+
+pub fn load_outbox_config(source: &ConfigSource) -> OutboxConfig {
+    OutboxConfig {
+        poll_interval_seconds: source
+            .var("OUTBOX_POLL_INTERVAL_SECONDS")
+            .unwrap_or_else(|_| "1".to_string())
+            .parse()
+            .unwrap_or(1),
+        batch_size: source
+            .var("OUTBOX_BATCH_SIZE")
+            .unwrap_or_else(|_| "100".to_string())
+            .parse()
+            .unwrap_or(100),
+        retry_base_seconds: source
+            .var("OUTBOX_RETRY_BASE_SECONDS")
+            .unwrap_or_else(|_| "5".to_string())
+            .parse()
+            .unwrap_or(5),
+        retention_days: source
+            .var("OUTBOX_RETENTION_DAYS")
+            .unwrap_or_else(|_| "7".to_string())
+            .parse()
+            .unwrap_or(7),
+    }
+}
+
+pub fn load_event_stream_config(source: &ConfigSource) -> EventStreamConfig {
+    EventStreamConfig {
+        redis_url: source
+            .var("EVENT_STREAM_REDIS_URL")
+            .ok()
+            .filter(|url| !url.trim().is_empty()),
+        key: source
+            .var("EVENT_STREAM_KEY")
+            .unwrap_or_else(|_| "brazilian_ecommerce:events".to_string()),
+        max_len: source
+            .var("EVENT_STREAM_MAX_LEN")
+            .unwrap_or_else(|_| "100000".to_string())
+            .parse()
+            .unwrap_or(100_000),
+    }
+}
+
+pub fn load_change_stream_config(source: &ConfigSource) -> Result<ChangeStreamConfig, AppError> {
+    let backend: ChangeStreamBackend = source
+        .var("CHANGE_STREAM_BACKEND")
+        .unwrap_or_else(|_| "none".to_string())
+        .parse()
+        .map_err(|e| AppError::ConfigError(format!("Invalid CHANGE_STREAM_BACKEND: {}", e)))?;
+    let url = source.var("CHANGE_STREAM_URL").unwrap_or_default();
+
+    if backend != ChangeStreamBackend::None && url.trim().is_empty() {
+        return Err(AppError::ConfigError(
+            "CHANGE_STREAM_URL must be set when CHANGE_STREAM_BACKEND is kafka or nats".to_string(),
+        ));
+    }
+
+    Ok(ChangeStreamConfig {
+        backend,
+        url,
+        topic: source
+            .var("CHANGE_STREAM_TOPIC")
+            .unwrap_or_else(|_| "brazilian_ecommerce.changes".to_string()),
+        format: source
+            .var("CHANGE_STREAM_FORMAT")
+            .unwrap_or_else(|_| "json".to_string())
+            .parse()
+            .map_err(|e| AppError::ConfigError(format!("Invalid CHANGE_STREAM_FORMAT: {}", e)))?,
+        buffer: source
+            .var("CHANGE_STREAM_BUFFER")
+            .unwrap_or_else(|_| "10000".to_string())
+            .parse()
+            .unwrap_or(10_000),
+    })
+}
+
+pub fn load_carrier_config(source: &ConfigSource) -> Result<CarrierConfig, AppError> {
+    let provider: CarrierBackend = source
+        .var("CARRIER_PROVIDER")
+        .unwrap_or_else(|_| "mock".to_string())
+        .parse()
+        .map_err(|e| AppError::ConfigError(format!("Invalid CARRIER_PROVIDER: {}", e)))?;
+
+    let services = source
+        .var("CORREIOS_SERVICES")
+        .unwrap_or_else(|_| "sedex:03220,pac:03298".to_string())
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| match entry.split_once(':') {
+            Some((name, code)) if !name.trim().is_empty() && !code.trim().is_empty() => {
+                Ok((name.trim().to_lowercase(), code.trim().to_string()))
+            }
+            _ => Err(AppError::ConfigError(format!(
+                "Invalid CORREIOS_SERVICES entry '{}', expected name:code",
+                entry
+            ))),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let correios = CorreiosConfig {
+        base_url: source
+            .var("CORREIOS_BASE_URL")
+            .unwrap_or_else(|_| "https://api.correios.com.br".to_string())
+            .trim_end_matches('/')
+            .to_string(),
+        username: source.var("CORREIOS_USERNAME").unwrap_or_default(),
+        access_code: source.var("CORREIOS_ACCESS_CODE").unwrap_or_default(),
+        posting_card: source.var("CORREIOS_POSTING_CARD").unwrap_or_default(),
+        services,
+    };
+
+    if provider == CarrierBackend::Correios
+        && [
+            &correios.username,
+            &correios.access_code,
+            &correios.posting_card,
+        ]
+        .iter()
+        .any(|value| value.trim().is_empty())
+    {
+        return Err(AppError::ConfigError(
+            "CORREIOS_USERNAME, CORREIOS_ACCESS_CODE and CORREIOS_POSTING_CARD must be set when CARRIER_PROVIDER is correios".to_string(),
+        ));
+    }
+    if provider == CarrierBackend::Correios && correios.services.is_empty() {
+        return Err(AppError::ConfigError(
+            "CORREIOS_SERVICES must name at least one service".to_string(),
+        ));
+    }
+
+    Ok(CarrierConfig {
+        provider,
+        timeout_seconds: source
+            .var("CARRIER_TIMEOUT_SECONDS")
+            .unwrap_or_else(|_| "10".to_string())
+            .parse()
+            .unwrap_or(10),
+        correios,
+    })
+}
+
+pub fn load_storage_config(source: &ConfigSource) -> Result<StorageConfig, AppError> {
+    let provider: StorageBackend = source
+        .var("STORAGE_PROVIDER")
+        .unwrap_or_else(|_| "memory".to_string())
+        .parse()
+        .map_err(|e| AppError::ConfigError(format!("Invalid STORAGE_PROVIDER: {}", e)))?;
+
+    let region = source
+        .var("S3_REGION")
+        .unwrap_or_else(|_| "us-east-1".to_string());
+    let endpoint = source
+        .var("S3_ENDPOINT")
+        .ok()
+        .filter(|endpoint| !endpoint.trim().is_empty())
+        .unwrap_or_else(|| format!("https://s3.{}.amazonaws.com", region))
+        .trim_end_matches('/')
+        .to_string();
+    let bucket = source.var("S3_BUCKET").unwrap_or_default();
+    let public_url = source
+        .var("S3_PUBLIC_URL")
+        .ok()
+        .filter(|url| !url.trim().is_empty())
+        .unwrap_or_else(|| format!("{}/{}", endpoint, bucket))
+        .trim_end_matches('/')
+        .to_string();
+    let s3 = S3Config {
+        endpoint,
+        region,
+        bucket,
+        access_key_id: source.var("S3_ACCESS_KEY_ID").unwrap_or_default(),
+        secret_access_key: source.var("S3_SECRET_ACCESS_KEY").unwrap_or_default(),
+        public_url,
+    };
+
+    if provider == StorageBackend::S3
+        && [&s3.bucket, &s3.access_key_id, &s3.secret_access_key]
+            .iter()
+            .any(|value| value.trim().is_empty())
+    {
+        return Err(AppError::ConfigError(
+            "S3_BUCKET, S3_ACCESS_KEY_ID and S3_SECRET_ACCESS_KEY must be set when STORAGE_PROVIDER is s3".to_string(),
+        ));
+    }
+
+    Ok(StorageConfig {
+        provider,
+        timeout_seconds: source
+            .var("STORAGE_TIMEOUT_SECONDS")
+            .unwrap_or_else(|_| "30".to_string())
+            .parse()
+            .unwrap_or(30),
+        s3,
+    })
+}
+
+pub fn load_product_image_config(source: &ConfigSource) -> ProductImageConfig {
+    ProductImageConfig {
+        max_bytes: source
+            .var("PRODUCT_IMAGES_MAX_BYTES")
+            .unwrap_or_else(|_| "1048576".to_string())
+            .parse()
+            .unwrap_or(1_048_576),
+    }
+}
+
+pub fn load_zip_lookup_config(source: &ConfigSource) -> Result<ZipLookupConfig, AppError> {
+    Ok(ZipLookupConfig {
+        provider: source
+            .var("ZIP_LOOKUP_PROVIDER")
+            .unwrap_or_else(|_| "viacep".to_string())
+            .parse()
+            .map_err(|e| AppError::ConfigError(format!("Invalid ZIP_LOOKUP_PROVIDER: {}", e)))?,
+        viacep_base_url: source
+            .var("VIACEP_BASE_URL")
+            .unwrap_or_else(|_| "https://viacep.com.br".to_string())
+            .trim_end_matches('/')
+            .to_string(),
+        timeout_seconds: source
+            .var("ZIP_LOOKUP_TIMEOUT_SECONDS")
+            .unwrap_or_else(|_| "5".to_string())
+            .parse()
+            .unwrap_or(5),
+    })
+}
+
+pub fn load_payment_config(source: &ConfigSource) -> Result<PaymentConfig, AppError> {
+    Ok(PaymentConfig {
+        provider: source
+            .var("PAYMENT_PROVIDER")
+            .unwrap_or_else(|_| "sandbox".to_string())
+            .parse()
+            .map_err(|e| AppError::ConfigError(format!("Invalid PAYMENT_PROVIDER: {}", e)))?,
+        webhook_secret: source.var("PAYMENT_WEBHOOK_SECRET").unwrap_or_default(),
+    })
+}
+
+pub fn load_notifier_config(source: &ConfigSource) -> Result<NotifierConfig, AppError> {
+    let provider: NotifierBackend = source
+        .var("NOTIFICATION_PROVIDER")
+        .unwrap_or_else(|_| "log".to_string())
+        .parse()
+        .map_err(|e| AppError::ConfigError(format!("Invalid NOTIFICATION_PROVIDER: {}", e)))?;
+    let recipients: Vec<String> = source
+        .var("NOTIFICATION_RECIPIENTS")
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|address| !address.is_empty())
+        .map(str::to_string)
+        .collect();
+
+    let mut templates = NotificationTemplates::default();
+    if let Ok(dir) = source.var("NOTIFICATION_TEMPLATE_DIR") {
+        for kind in NotificationKind::ALL {
+            let path = Path::new(&dir).join(format!("{}.txt", kind.as_str()));
+            if !path.exists() {
+                continue;
+            }
+            let text = fs::read_to_string(&path).map_err(|e| {
+                AppError::ConfigError(format!(
+                    "Failed to read notification template {}: {}",
+                    path.display(),
+                    e
+                ))
+            })?;
+            templates = templates.with(kind, NotificationTemplate::parse(&text));
+        }
+    }
+
+    let security: SmtpSecurity = source
+        .var("SMTP_SECURITY")
+        .unwrap_or_else(|_| "starttls".to_string())
+        .parse()
+        .map_err(|e| AppError::ConfigError(format!("Invalid SMTP_SECURITY: {}", e)))?;
+    let default_port = match security {
+        SmtpSecurity::StartTls => 587,
+        SmtpSecurity::Tls => 465,
+        SmtpSecurity::None => 25,
+    };
+    let smtp = SmtpConfig {
+        host: source.var("SMTP_HOST").unwrap_or_default(),
+        port: source
+            .var("SMTP_PORT")
+            .ok()
+            .and_then(|port| port.parse().ok())
+            .unwrap_or(default_port),
+        security,
+        username: source.var("SMTP_USERNAME").unwrap_or_default(),
+        password: source.var("SMTP_PASSWORD").unwrap_or_default(),
+        from: source.var("SMTP_FROM").unwrap_or_default(),
+        timeout_seconds: source
+            .var("SMTP_TIMEOUT_SECONDS")
+            .unwrap_or_else(|_| "10".to_string())
+            .parse()
+            .unwrap_or(10),
+    };
+
+    if provider == NotifierBackend::Smtp
+        && (smtp.host.trim().is_empty() || smtp.from.trim().is_empty() || recipients.is_empty())
+    {
+        return Err(AppError::ConfigError(
+            "SMTP_HOST, SMTP_FROM and NOTIFICATION_RECIPIENTS must be set when NOTIFICATION_PROVIDER is smtp".to_string(),
+        ));
+    }
+
+    Ok(NotifierConfig {
+        provider,
+        recipients,
+        templates,
+        smtp,
+    })
+}
+
+pub fn load_notification_config(source: &ConfigSource) -> NotificationConfig {
+    NotificationConfig {
+        poll_interval_seconds: source
+            .var("NOTIFICATION_POLL_INTERVAL_SECONDS")
+            .unwrap_or_else(|_| "5".to_string())
+            .parse()
+            .unwrap_or(5),
+        max_attempts: source
+            .var("NOTIFICATION_MAX_ATTEMPTS")
+            .unwrap_or_else(|_| "5".to_string())
+            .parse()
+            .unwrap_or(5),
+        retry_base_seconds: source
+            .var("NOTIFICATION_RETRY_BASE_SECONDS")
+            .unwrap_or_else(|_| "60".to_string())
+            .parse()
+            .unwrap_or(60),
+    }
+}
+
+pub fn load_jobs_config(source: &ConfigSource) -> Result<JobsConfig, AppError> {
+    let schedule = |name: &str, default: &str| -> Result<Option<Schedule>, AppError> {
+        let expression = source.var(name).unwrap_or_else(|_| default.to_string());
+        let expression = expression.trim();
+        if expression.is_empty() {
+            return Ok(None);
+        }
+        // Five-field crontab lines are accepted too; the parser wants seconds first.
+        let expression = if expression.split_whitespace().count() == 5 {
+            format!("0 {}", expression)
+        } else {
+            expression.to_string()
+        };
+        expression
+            .parse()
+            .map(Some)
+            .map_err(|e| AppError::ConfigError(format!("Invalid {} '{}': {}", name, expression, e)))
+    };
+
+    Ok(JobsConfig {
+        refresh_analytics_views: schedule("JOBS_REFRESH_ANALYTICS_VIEWS", "0 * * * *")?,
+        retry_failed_webhooks: schedule("JOBS_RETRY_FAILED_WEBHOOKS", "30 * * * *")?,
+        apply_retention: schedule("JOBS_APPLY_RETENTION", "0 3 * * *")?,
+        tag_review_sentiment: schedule("JOBS_TAG_REVIEW_SENTIMENT", "*/15 * * * *")?,
+        flag_late_orders: schedule("JOBS_FLAG_LATE_ORDERS", "45 * * * *")?,
+        evaluate_data_quality: schedule("JOBS_EVALUATE_DATA_QUALITY", "15 2 * * *")?,
+        failed_webhooks_max_age_hours: source
+            .var("JOBS_FAILED_WEBHOOKS_MAX_AGE_HOURS")
+            .unwrap_or_else(|_| "24".to_string())
+            .parse()
+            .unwrap_or(24),
+        late_orders_max_age_days: source
+            .var("JOBS_LATE_ORDERS_MAX_AGE_DAYS")
+            .unwrap_or_else(|_| "30".to_string())
+            .parse()
+            .unwrap_or(30),
+    })
+}
+
+/// A data quality rules file: a `rules` array (JSON) or `[[rules]]` tables (TOML).
+#[derive(Deserialize)]
+struct DataQualityRulesFile {
+    #[serde(default)]
+    rules: Vec<DataQualityRule>,
+}
+
+/// No rules unless `DATA_QUALITY_RULES_PATH` names a `.toml` or `.json` file; every rule in it
+/// must be valid and uniquely named.
+pub fn load_data_quality_rules(source: &ConfigSource) -> Result<Vec<DataQualityRule>, AppError> {
+    let Some(path) = source
+        .var("DATA_QUALITY_RULES_PATH")
+        .ok()
+        .filter(|path| !path.trim().is_empty())
+    else {
+        return Ok(Vec::new());
+    };
+
+    let contents = fs::read_to_string(&path).map_err(|e| {
+        AppError::ConfigError(format!("Failed to read data quality rules {}: {}", path, e))
+    })?;
+    let file: DataQualityRulesFile = match Path::new(&path).extension().and_then(|ext| ext.to_str())
+    {
+        Some("toml") => toml::from_str(&contents)
+            .map_err(|e| AppError::ConfigError(format!("Invalid TOML in {}: {}", path, e)))?,
+        Some("json") => serde_json::from_str(&contents)
+            .map_err(|e| AppError::ConfigError(format!("Invalid JSON in {}: {}", path, e)))?,
+        _ => {
+            return Err(AppError::ConfigError(format!(
+                "DATA_QUALITY_RULES_PATH must point to a .toml or .json file, got {}",
+                path
+            )));
+        }
+    };
+
+    let mut names = HashSet::new();
+    for rule in &file.rules {
+        rule.validate().map_err(|e| {
+            AppError::ConfigError(format!("Invalid data quality rule '{}': {}", rule.name, e))
+        })?;
+        if !names.insert(rule.name.as_str()) {
+            return Err(AppError::ConfigError(format!(
+                "Duplicate data quality rule '{}'",
+                rule.name
+            )));
+        }
+    }
+    Ok(file.rules)
+}
+
+pub fn load_access_log_config(source: &ConfigSource) -> Result<AccessLogConfig, AppError> {
+    let level = source
+        .var("ACCESS_LOG")
+        .unwrap_or_else(|_| "all".to_string())
+        .parse()
+        .map_err(|e| AppError::ConfigError(format!("Invalid ACCESS_LOG: {}", e)))?;
+    let threshold_ms: u64 = source
+        .var("SLOW_REQUEST_THRESHOLD_MS")
+        .unwrap_or_else(|_| "1000".to_string())
+        .parse()
+        .unwrap_or(1000);
+    Ok(AccessLogConfig {
+        level,
+        slow_request_threshold: (threshold_ms > 0).then(|| Duration::from_millis(threshold_ms)),
+    })
+}
+
+pub fn load_concurrency_config(source: &ConfigSource) -> ConcurrencyConfig {
+    ConcurrencyConfig {
+        max_requests: source
+            .var("MAX_CONCURRENT_REQUESTS")
+            .unwrap_or_else(|_| "32".to_string())
+            .parse()
+            .unwrap_or(32),
+        health_max_requests: source
+            .var("HEALTH_MAX_CONCURRENT_REQUESTS")
+            .unwrap_or_else(|_| "256".to_string())
+            .parse()
+            .unwrap_or(256),
+        retry_after_seconds: source
+            .var("LOAD_SHED_RETRY_AFTER_SECONDS")
+            .unwrap_or_else(|_| "1".to_string())
+            .parse()
+            .unwrap_or(1),
+    }
+}
+
+pub fn load_warmup_config(source: &ConfigSource) -> WarmupConfig {
+    WarmupConfig {
+        enabled: source
+            .var("WARMUP_ENABLED")
+            .unwrap_or_else(|_| "false".to_string())
+            .parse()
+            .unwrap_or(false),
+        pool_connections: source
+            .var("WARMUP_POOL_CONNECTIONS")
+            .unwrap_or_else(|_| "5".to_string())
+            .parse()
+            .unwrap_or(5),
+        canary_attempts: source
+            .var("WARMUP_CANARY_ATTEMPTS")
+            .unwrap_or_else(|_| "5".to_string())
+            .parse()
+            .unwrap_or(5),
+        canary_timeout_seconds: source
+            .var("WARMUP_CANARY_TIMEOUT_SECONDS")
+            .unwrap_or_else(|_| "5".to_string())
+            .parse()
+            .unwrap_or(5),
+    }
+}
+
+pub fn load_tenancy_config(source: &ConfigSource) -> Result<TenancyConfig, AppError> {
+    let resolver: TenantResolver = source
+        .var("TENANT_RESOLVER")
+        .unwrap_or_else(|_| "off".to_string())
+        .parse()
+        .map_err(|e| AppError::ConfigError(format!("Invalid TENANT_RESOLVER: {}", e)))?;
+    let header = source
+        .var("TENANT_HEADER")
+        .unwrap_or_else(|_| "x-tenant-id".to_string())
+        .trim()
+        .parse()
+        .map_err(|e| AppError::ConfigError(format!("Invalid TENANT_HEADER: {}", e)))?;
+    let base_domain = source
+        .var("TENANT_BASE_DOMAIN")
+        .ok()
+        .map(|domain| domain.trim().trim_start_matches('.').to_lowercase())
+        .filter(|domain| !domain.is_empty());
+
+    if resolver == TenantResolver::Subdomain && base_domain.is_none() {
+        return Err(AppError::ConfigError(
+            "TENANT_BASE_DOMAIN must be set when TENANT_RESOLVER is subdomain".to_string(),
+        ));
+    }
+
+    let tenants = match resolver {
+        TenantResolver::Off => vec![TenantId::default()],
+        _ => {
+            let mut tenants = Vec::new();
+            for tenant in source
+                .var("TENANTS")
+                .unwrap_or_else(|_| TenantId::DEFAULT.to_string())
+                .split(',')
+                .filter(|tenant| !tenant.trim().is_empty())
+            {
+                let tenant: TenantId = tenant
+                    .parse()
+                    .map_err(|e| AppError::ConfigError(format!("Invalid TENANTS: {}", e)))?;
+                if !tenants.contains(&tenant) {
+                    tenants.push(tenant);
+                }
+            }
+            if tenants.is_empty() {
+                return Err(AppError::ConfigError(
+                    "TENANTS must name at least one tenant".to_string(),
+                ));
+            }
+            tenants
+        }
+    };
+
+    Ok(TenancyConfig {
+        resolver,
+        header,
+        base_domain,
+        tenants,
+    })
+}
+
+pub fn load_amendment_config(source: &ConfigSource) -> Result<AmendmentConfig, AppError> {
+    let decimal = |name: &str, default: &str| -> Result<BigDecimal, AppError> {
+        source
+            .var(name)
+            .unwrap_or_else(|_| default.to_string())
+            .parse()
+            .map_err(|e| AppError::ConfigError(format!("Invalid {}: {}", name, e)))
+    };
+
+    Ok(AmendmentConfig {
+        window_hours: source
+            .var("ORDER_AMENDMENT_WINDOW_HOURS")
+            .unwrap_or_else(|_| "24".to_string())
+            .parse()
+            .unwrap_or(24),
+        freight_base: decimal("FREIGHT_BASE", "10.00")?,
+        freight_per_region: decimal("FREIGHT_PER_REGION", "4.50")?,
+        tax_rate: decimal("ORDER_TAX_RATE", "0")?,
+    })
+}
+
+/// `FREIGHT_RATE_TABLE` lists bands as `max_km:base:per_kg`, by increasing distance; `*` as
+/// the last band's limit covers every longer distance.
+pub fn load_freight_config(source: &ConfigSource) -> Result<FreightConfig, AppError> {
+    let table = source.var("FREIGHT_RATE_TABLE").unwrap_or_else(|_| {
+        "300:14.00:1.50,1000:19.00:2.20,2500:27.00:3.10,*:36.00:4.20".to_string()
+    });
+    let invalid = |entry: &str, reason: &str| {
+        AppError::ConfigError(format!(
+            "Invalid FREIGHT_RATE_TABLE entry '{}': {}",
+            entry, reason
+        ))
+    };
+
+    let mut rates: Vec<FreightRate> = Vec::new();
+    for entry in table.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let fields: Vec<&str> = entry.split(':').map(str::trim).collect();
+        let [max_km, base, per_kg] = fields[..] else {
+            return Err(invalid(entry, "expected max_km:base:per_kg"));
+        };
+        if rates.last().is_some_and(|rate| rate.max_km.is_none()) {
+            return Err(invalid(entry, "no band can follow the '*' band"));
+        }
+        let max_km = match max_km {
+            "*" => None,
+            km => Some(
+                km.parse::<f64>()
+                    .ok()
+                    .filter(|km| km.is_finite() && *km > 0.0)
+                    .ok_or_else(|| invalid(entry, "max_km must be a positive number or *"))?,
+            ),
+        };
+        if let (Some(max_km), Some(Some(previous))) = (max_km, rates.last().map(|rate| rate.max_km))
+            && max_km <= previous
+        {
+            return Err(invalid(entry, "bands must be ordered by increasing max_km"));
+        }
+        let decimal = |value: &str| {
+            value
+                .parse::<BigDecimal>()
+                .map_err(|e| invalid(entry, &e.to_string()))
+        };
+        rates.push(FreightRate {
+            max_km,
+            base: decimal(base)?,
+            per_kg: decimal(per_kg)?,
+        });
+    }
+
+    if rates.is_empty() {
+        return Err(AppError::ConfigError(
+            "FREIGHT_RATE_TABLE must define at least one band".to_string(),
+        ));
+    }
+    Ok(FreightConfig { rates })
+}
+
+pub fn load_support_config(source: &ConfigSource) -> SupportConfig {
+    SupportConfig {
+        first_response_hours: source
+            .var("SUPPORT_FIRST_RESPONSE_SLA_HOURS")
+            .unwrap_or_else(|_| "24".to_string())
+            .parse()
+            .unwrap_or(24),
+        resolution_hours: source
+            .var("SUPPORT_RESOLUTION_SLA_HOURS")
+            .unwrap_or_else(|_| "72".to_string())
+            .parse()
+            .unwrap_or(72),
+    }
+}
+
+pub fn load_scorecard_config(source: &ConfigSource) -> ScorecardConfig {
+    ScorecardConfig {
+        min_on_time_rate: source
+            .var("SELLER_SCORECARD_MIN_ON_TIME_RATE")
+            .unwrap_or_else(|_| "0.9".to_string())
+            .parse()
+            .unwrap_or(0.9),
+        max_cancellation_rate: source
+            .var("SELLER_SCORECARD_MAX_CANCELLATION_RATE")
+            .unwrap_or_else(|_| "0.05".to_string())
+            .parse()
+            .unwrap_or(0.05),
+        min_review_average: source
+            .var("SELLER_SCORECARD_MIN_REVIEW_AVERAGE")
+            .unwrap_or_else(|_| "4.0".to_string())
+            .parse()
+            .unwrap_or(4.0),
+        min_orders: source
+            .var("SELLER_SCORECARD_MIN_ORDERS")
+            .unwrap_or_else(|_| "10".to_string())
+            .parse()
+            .unwrap_or(10),
+    }
+}
+
+pub fn load_corpus_config(source: &ConfigSource) -> CorpusConfig {
+    CorpusConfig {
+        api_keys: source
+            .var("CORPUS_API_KEYS")
+            .unwrap_or_default()
+            .split(',')
+            .map(|key| key.trim().to_string())
+            .filter(|key| !key.is_empty())
+            .collect(),
+        requests_per_hour: source
+            .var("CORPUS_REQUESTS_PER_HOUR")
+            .unwrap_or_else(|_| "10".to_string())
+            .parse()
+            .unwrap_or(10),
+        rows_per_hour: source
+            .var("CORPUS_ROWS_PER_HOUR")
+            .unwrap_or_else(|_| "100000".to_string())
+            .parse()
+            .unwrap_or(100_000),
+    }
+}
+
+pub fn load_import_config(source: &ConfigSource) -> ImportConfig {
+    ImportConfig {
+        concurrency: source
+            .var("IMPORT_CONCURRENCY")
+            .unwrap_or_else(|_| "4".to_string())
+            .parse()
+            .unwrap_or(4)
+            .max(1),
+    }
+}
+
+pub fn load_delete_policy_config(source: &ConfigSource) -> Result<DeletePolicyConfig, AppError> {
+    let policy = |name: &str| -> Result<DeletePolicy, AppError> {
+        source
+            .var(name)
+            .unwrap_or_else(|_| "cascade".to_string())
+            .parse()
+            .map_err(|e| AppError::ConfigError(format!("Invalid {}: {}", name, e)))
+    };
+
+    Ok(DeletePolicyConfig {
+        customer_orders: policy("DELETE_POLICY_CUSTOMER_ORDERS")?,
+        customer_support_cases: policy("DELETE_POLICY_CUSTOMER_SUPPORT_CASES")?,
+    })
+}
+
+pub fn load_public_id_config(source: &ConfigSource) -> Result<PublicIdConfig, AppError> {
+    let mode: PublicIdMode = source
+        .var("PUBLIC_ID_CODEC")
+        .unwrap_or_else(|_| "plain".to_string())
+        .parse()
+        .map_err(|e| AppError::ConfigError(format!("Invalid PUBLIC_ID_CODEC: {}", e)))?;
+    let secret = source.var("PUBLIC_ID_SECRET").unwrap_or_default();
+
+    if mode == PublicIdMode::Obfuscated && secret.trim().is_empty() {
+        return Err(AppError::ConfigError(
+            "PUBLIC_ID_SECRET must be set when PUBLIC_ID_CODEC is obfuscated".to_string(),
+        ));
+    }
+
+    Ok(PublicIdConfig { mode, secret })
+}
+
+/// Response compression negotiated from `Accept-Encoding`. When disabled every encoding is
+/// switched off, so responses pass through unchanged.
+pub fn create_compression_layer(enabled: bool) -> CompressionLayer {
+    CompressionLayer::new()
+        .gzip(enabled)
+        .br(enabled)
+        .zstd(enabled)
+}
+
+pub fn create_cors_layer(policy: &CorsPolicy) -> CorsLayer {
+    // A wildcard can't be combined with credentials, so credentialed policies echo what the
+    // preflight asked for instead.
+    let (methods, headers) = if policy.allow_credentials {
+        (
+            AllowMethods::mirror_request(),
+            AllowHeaders::mirror_request(),
+        )
+    } else {
+        (Any.into(), Any.into())
+    };
+    CorsLayer::new()
+        .allow_origin(policy.allowed_origins.clone())
+        .allow_methods(methods)
+        .allow_headers(headers)
+        .allow_credentials(policy.allow_credentials)
+        .expose_headers(policy.exposed_headers.clone())
+        .max_age(Duration::from_secs(policy.max_age_seconds))
+}
@@ -0,0 +1,65 @@
+use axum::{
+    extract::{Request, State},
+    http::{Method, header},
+    middleware::Next,
+    response::Response,
+};
+use tower::{Layer, ServiceExt};
+use tower_http::cors::CorsLayer;
+
+use crate::config::{CorsConfig, create_cors_layer};
+use crate::versioning::unversioned_path;
+
+/// Path prefixes under the admin policy whatever the method.
+const ADMIN_PREFIXES: [&str; 2] = ["/admin/", "/load-data"];
+
+/// The CORS layer of each route group, built once from [`CorsConfig`].
+#[derive(Clone)]
+pub struct CorsPolicies {
+    public: CorsLayer,
+    admin: CorsLayer,
+}
+
+impl CorsPolicies {
+    pub fn new(config: &CorsConfig) -> Self {
+        Self {
+            public: create_cors_layer(&config.public),
+            admin: create_cors_layer(&config.admin),
+        }
+    }
+
+    /// Admin routes and writes get the admin policy, everything else the public one. A
+    /// preflight is judged by the method it asks about.
+    fn for_request(&self, request: &Request) -> &CorsLayer {
+        let method = if request.method() == Method::OPTIONS {
+            request
+                .headers()
+                .get(header::ACCESS_CONTROL_REQUEST_METHOD)
+                .and_then(|value| Method::from_bytes(value.as_bytes()).ok())
+                .unwrap_or(Method::OPTIONS)
+        } else {
+            request.method().clone()
+        };
+        let path = unversioned_path(request.uri().path());
+
+        let is_read = matches!(method, Method::GET | Method::HEAD | Method::OPTIONS);
+        if !is_read || ADMIN_PREFIXES.iter().any(|prefix| path.starts_with(prefix)) {
+            &self.admin
+        } else {
+            &self.public
+        }
+    }
+}
+
+/// Applies the CORS policy of the route group the request belongs to.
+pub async fn cors_by_route(
+    State(policies): State<CorsPolicies>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let cors = policies.for_request(&request).layer(next);
+    match cors.oneshot(request).await {
+        Ok(response) => response,
+        Err(never) => match never {},
+    }
+}
@@ -0,0 +1,430 @@
+use sqlx::migrate::Migrator;
+use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use sqlx::{PgPool, SqlitePool};
+use std::{str::FromStr, sync::Arc, time::Duration};
+use tracing::info;
+
+use domain::error::AppError;
+use domain::repositories::{
+    AuditRepository, CategoryRepository, CouponRepository, CustomerRepository,
+    DataQualityRepository, DiagnosticsRepository, EmbeddingRepository, GeolocationRepository,
+    ImportRepository, InventoryRepository, LateOrderRepository, MaintenanceRepository,
+    NotificationRepository, OrderRepository, OutboxRepository, PaymentTransactionRepository,
+    ProductRepository, RetentionRepository, ReviewSentimentRepository, SellerRepository,
+    StatsRepository, SupportRepository, WebhookRepository,
+};
+use domain::tenancy::TenantId;
+use persistence::collation::SortCollation;
+#[cfg(feature = "test-utils")]
+use persistence::memory::{
+    InMemoryAuditRepository, InMemoryCategoryRepository, InMemoryCouponRepository,
+    InMemoryCustomerRepository, InMemoryDataQualityRepository, InMemoryDiagnosticsRepository,
+    InMemoryEmbeddingRepository, InMemoryGeolocationRepository, InMemoryImportRepository,
+    InMemoryInventoryRepository, InMemoryLateOrderRepository, InMemoryMaintenanceRepository,
+    InMemoryNotificationRepository, InMemoryOrderRepository, InMemoryOutboxRepository,
+    InMemoryPaymentTransactionRepository, InMemoryProductRepository, InMemoryRetentionRepository,
+    InMemoryReviewSentimentRepository, InMemorySellerRepository, InMemoryStatsRepository,
+    InMemorySupportRepository, InMemoryWebhookRepository, MemoryStore,
+};
+use persistence::replica::ReadPool;
+use persistence::repositories::{
+    PgAuditRepository, PgCategoryRepository, PgCouponRepository, PgCustomerRepository,
+    PgDataQualityRepository, PgDiagnosticsRepository, PgEmbeddingRepository,
+    PgGeolocationRepository, PgImportRepository, PgInventoryRepository, PgLateOrderRepository,
+    PgMaintenanceRepository, PgNotificationRepository, PgOrderRepository, PgOutboxRepository,
+    PgPaymentTransactionRepository, PgProductRepository, PgRetentionRepository,
+    PgReviewSentimentRepository, PgSellerRepository, PgStatsRepository, PgSupportRepository,
+    PgWebhookRepository,
+};
+use persistence::sqlite::{
+    SqliteAuditRepository, SqliteCategoryRepository, SqliteCouponRepository,
+    SqliteCustomerRepository, SqliteDataQualityRepository, SqliteDiagnosticsRepository,
+    SqliteEmbeddingRepository, SqliteGeolocationRepository, SqliteImportRepository,
+    SqliteInventoryRepository, SqliteLateOrderRepository, SqliteMaintenanceRepository,
+    SqliteNotificationRepository, SqliteOrderRepository, SqliteOutboxRepository,
+    SqlitePaymentTransactionRepository, SqliteProductRepository, SqliteRetentionRepository,
+    SqliteReviewSentimentRepository, SqliteSellerRepository, SqliteStatsRepository,
+    SqliteSupportRepository, SqliteWebhookRepository,
+};
+
+use crate::config::AppConfig;
+
+static PG_MIGRATOR: Migrator = sqlx::migrate!("../../migrations");
+static SQLITE_MIGRATOR: Migrator = sqlx::migrate!("../../migrations/sqlite");
+
+/// The database behind the API, picked from the `DATABASE_URL` scheme: `sqlite:` URLs open a
+/// SQLite file (or `sqlite::memory:`) for demos and tests, anything else is PostgreSQL. Builds
+/// with the `test-utils` feature also accept `memory:`, which keeps everything in process.
+#[derive(Clone)]
+pub enum Database {
+    /// The primary takes every write; `reads` sends repository reads to the replica when
+    /// `DATABASE_REPLICA_URL` is set.
+    Postgres {
+        pool: PgPool,
+        reads: ReadPool,
+    },
+    Sqlite(SqlitePool),
+    #[cfg(feature = "test-utils")]
+    Memory(MemoryStore),
+}
+
+/// One implementation of every repository trait, all backed by the same database.
+pub struct Repositories {
+    pub customers: Arc<dyn CustomerRepository>,
+    pub sellers: Arc<dyn SellerRepository>,
+    pub orders: Arc<dyn OrderRepository>,
+    pub products: Arc<dyn ProductRepository>,
+    pub categories: Arc<dyn CategoryRepository>,
+    pub audit: Arc<dyn AuditRepository>,
+    pub embeddings: Arc<dyn EmbeddingRepository>,
+    pub inventory: Arc<dyn InventoryRepository>,
+    pub geolocation: Arc<dyn GeolocationRepository>,
+    pub support: Arc<dyn SupportRepository>,
+    pub coupons: Arc<dyn CouponRepository>,
+    pub payment_transactions: Arc<dyn PaymentTransactionRepository>,
+    pub maintenance: Arc<dyn MaintenanceRepository>,
+    pub diagnostics: Arc<dyn DiagnosticsRepository>,
+    pub data_quality: Arc<dyn DataQualityRepository>,
+    pub imports: Arc<dyn ImportRepository>,
+    pub stats: Arc<dyn StatsRepository>,
+    pub webhooks: Arc<dyn WebhookRepository>,
+    pub outbox: Arc<dyn OutboxRepository>,
+    pub notifications: Arc<dyn NotificationRepository>,
+    pub retention: Arc<dyn RetentionRepository>,
+    pub review_sentiments: Arc<dyn ReviewSentimentRepository>,
+    pub late_orders: Arc<dyn LateOrderRepository>,
+}
+
+impl Database {
+    pub async fn connect(config: &AppConfig) -> Result<Self, AppError> {
+        info!("Connecting to database at {}...", config.database_url);
+
+        #[cfg(feature = "test-utils")]
+        if config.database_url.starts_with("memory:") {
+            info!("Using in-memory repositories; nothing is persisted.");
+            return Ok(Database::Memory(MemoryStore::new()));
+        }
+
+        let database = if config.database_url.starts_with("sqlite:") {
+            Database::Sqlite(connect_sqlite(config).await?)
+        } else {
+            connect_postgres(config).await?
+        };
+
+        info!("Database connection pool created.");
+        Ok(database)
+    }
+
+    /// Migrations for this backend; SQLite has its own schema under `migrations/sqlite`.
+    pub fn migrator(&self) -> &'static Migrator {
+        match self {
+            Database::Postgres { .. } => &PG_MIGRATOR,
+            Database::Sqlite(_) => &SQLITE_MIGRATOR,
+            // No schema to migrate; run and undo are no-ops for the in-memory store.
+            #[cfg(feature = "test-utils")]
+            Database::Memory(_) => &SQLITE_MIGRATOR,
+        }
+    }
+
+    pub async fn run_migrations(&self) -> Result<(), AppError> {
+        match self {
+            Database::Postgres { pool, .. } => self.migrator().run(pool).await,
+            Database::Sqlite(pool) => self.migrator().run(pool).await,
+            #[cfg(feature = "test-utils")]
+            Database::Memory(_) => Ok(()),
+        }
+        .map_err(AppError::MigrationError)
+    }
+
+    /// Fails when `TEXT_COLLATION` names a collation the database lacks. SQLite ignores it.
+    pub async fn check_collation(&self, collation: SortCollation) -> Result<(), AppError> {
+        let Database::Postgres { pool, .. } = self else {
+            return Ok(());
+        };
+        if collation
+            .is_available(pool)
+            .await
+            .map_err(AppError::DatabaseError)?
+        {
+            Ok(())
+        } else {
+            Err(AppError::ConfigError(format!(
+                "TEXT_COLLATION={} needs {}, which the database doesn't have; see the migration \
+                 logs for why it wasn't created.",
+                collation,
+                match collation {
+                    SortCollation::Unaccent => "the unaccent extension",
+                    _ => "the ICU collation pt_br_natural",
+                }
+            )))
+        }
+    }
+
+    pub async fn undo_migrations(&self, target: i64) -> Result<(), AppError> {
+        match self {
+            Database::Postgres { pool, .. } => self.migrator().undo(pool, target).await,
+            Database::Sqlite(pool) => self.migrator().undo(pool, target).await,
+            #[cfg(feature = "test-utils")]
+            Database::Memory(_) => Ok(()),
+        }
+        .map_err(AppError::MigrationError)
+    }
+
+    /// Versions of the applied migrations, newest first.
+    pub async fn applied_migrations(&self) -> Result<Vec<i64>, AppError> {
+        let query = "SELECT version FROM _sqlx_migrations ORDER BY version DESC";
+        match self {
+            Database::Postgres { pool, .. } => sqlx::query_scalar(query).fetch_all(pool).await,
+            Database::Sqlite(pool) => sqlx::query_scalar(query).fetch_all(pool).await,
+            #[cfg(feature = "test-utils")]
+            Database::Memory(_) => Ok(Vec::new()),
+        }
+        .map_err(AppError::DatabaseError)
+    }
+
+    pub fn repositories(&self, config: &AppConfig, tenant: &TenantId) -> Repositories {
+        match self {
+            Database::Postgres { pool, reads } => {
+                let retry = config.pool.retry;
+                Repositories {
+                    customers: Arc::new(PgCustomerRepository::new(
+                        pool.clone(),
+                        reads.clone(),
+                        config.collation,
+                        retry,
+                        tenant.clone(),
+                    )),
+                    sellers: Arc::new(PgSellerRepository::new(
+                        pool.clone(),
+                        reads.clone(),
+                        config.collation,
+                        retry,
+                        tenant.clone(),
+                    )),
+                    orders: Arc::new(PgOrderRepository::new(
+                        pool.clone(),
+                        reads.clone(),
+                        retry,
+                        tenant.clone(),
+                    )),
+                    products: Arc::new(PgProductRepository::new(
+                        pool.clone(),
+                        reads.clone(),
+                        config.collation,
+                        retry,
+                        tenant.clone(),
+                    )),
+                    categories: Arc::new(PgCategoryRepository::new(
+                        pool.clone(),
+                        reads.clone(),
+                        retry,
+                        tenant.clone(),
+                    )),
+                    audit: Arc::new(PgAuditRepository::new(
+                        pool.clone(),
+                        reads.clone(),
+                        retry,
+                        tenant.clone(),
+                    )),
+                    embeddings: Arc::new(PgEmbeddingRepository::new(pool.clone(), tenant.clone())),
+                    inventory: Arc::new(PgInventoryRepository::new(
+                        pool.clone(),
+                        reads.clone(),
+                        retry,
+                        tenant.clone(),
+                    )),
+                    geolocation: Arc::new(PgGeolocationRepository::new(pool.clone())),
+                    support: Arc::new(PgSupportRepository::new(
+                        pool.clone(),
+                        reads.clone(),
+                        retry,
+                        tenant.clone(),
+                    )),
+                    coupons: Arc::new(PgCouponRepository::new(
+                        pool.clone(),
+                        reads.clone(),
+                        retry,
+                        tenant.clone(),
+                    )),
+                    payment_transactions: Arc::new(PgPaymentTransactionRepository::new(
+                        pool.clone(),
+                        reads.clone(),
+                        retry,
+                        tenant.clone(),
+                    )),
+                    maintenance: Arc::new(PgMaintenanceRepository::new(
+                        pool.clone(),
+                        tenant.clone(),
+                    )),
+                    diagnostics: Arc::new(PgDiagnosticsRepository::new(pool.clone())),
+                    data_quality: Arc::new(PgDataQualityRepository::new(
+                        pool.clone(),
+                        tenant.clone(),
+                    )),
+                    imports: Arc::new(PgImportRepository::new(pool.clone(), tenant.clone())),
+                    stats: Arc::new(PgStatsRepository::new(reads.clone(), retry, tenant.clone())),
+                    webhooks: Arc::new(PgWebhookRepository::new(pool.clone(), tenant.clone())),
+                    outbox: Arc::new(PgOutboxRepository::new(pool.clone(), tenant.clone())),
+                    notifications: Arc::new(PgNotificationRepository::new(
+                        pool.clone(),
+                        tenant.clone(),
+                    )),
+                    retention: Arc::new(PgRetentionRepository::new(pool.clone(), tenant.clone())),
+                    review_sentiments: Arc::new(PgReviewSentimentRepository::new(
+                        pool.clone(),
+                        tenant.clone(),
+                    )),
+                    late_orders: Arc::new(PgLateOrderRepository::new(pool.clone(), tenant.clone())),
+                }
+            }
+            Database::Sqlite(pool) => Repositories {
+                customers: Arc::new(SqliteCustomerRepository::new(pool.clone(), tenant.clone())),
+                sellers: Arc::new(SqliteSellerRepository::new(pool.clone(), tenant.clone())),
+                orders: Arc::new(SqliteOrderRepository::new(pool.clone(), tenant.clone())),
+                products: Arc::new(SqliteProductRepository::new(pool.clone(), tenant.clone())),
+                categories: Arc::new(SqliteCategoryRepository::new(pool.clone(), tenant.clone())),
+                audit: Arc::new(SqliteAuditRepository::new(pool.clone(), tenant.clone())),
+                embeddings: Arc::new(SqliteEmbeddingRepository::new(pool.clone(), tenant.clone())),
+                inventory: Arc::new(SqliteInventoryRepository::new(pool.clone(), tenant.clone())),
+                geolocation: Arc::new(SqliteGeolocationRepository::new(pool.clone())),
+                support: Arc::new(SqliteSupportRepository::new(pool.clone(), tenant.clone())),
+                coupons: Arc::new(SqliteCouponRepository::new(pool.clone(), tenant.clone())),
+                payment_transactions: Arc::new(SqlitePaymentTransactionRepository::new(
+                    pool.clone(),
+                    tenant.clone(),
+                )),
+                maintenance: Arc::new(SqliteMaintenanceRepository),
+                diagnostics: Arc::new(SqliteDiagnosticsRepository::new(pool.clone())),
+                data_quality: Arc::new(SqliteDataQualityRepository::new(
+                    pool.clone(),
+                    tenant.clone(),
+                )),
+                imports: Arc::new(SqliteImportRepository::new(pool.clone(), tenant.clone())),
+                stats: Arc::new(SqliteStatsRepository::new(pool.clone(), tenant.clone())),
+                webhooks: Arc::new(SqliteWebhookRepository::new(pool.clone(), tenant.clone())),
+                outbox: Arc::new(SqliteOutboxRepository::new(pool.clone(), tenant.clone())),
+                notifications: Arc::new(SqliteNotificationRepository::new(
+                    pool.clone(),
+                    tenant.clone(),
+                )),
+                retention: Arc::new(SqliteRetentionRepository::new(pool.clone(), tenant.clone())),
+                review_sentiments: Arc::new(SqliteReviewSentimentRepository::new(
+                    pool.clone(),
+                    tenant.clone(),
+                )),
+                late_orders: Arc::new(SqliteLateOrderRepository::new(pool.clone(), tenant.clone())),
+            },
+            #[cfg(feature = "test-utils")]
+            Database::Memory(store) => {
+                let store = store.for_tenant(tenant);
+                Repositories {
+                    customers: Arc::new(InMemoryCustomerRepository::new(store.clone())),
+                    sellers: Arc::new(InMemorySellerRepository::new(store.clone())),
+                    orders: Arc::new(InMemoryOrderRepository::new(store.clone())),
+                    products: Arc::new(InMemoryProductRepository::new(store.clone())),
+                    categories: Arc::new(InMemoryCategoryRepository::new(store.clone())),
+                    audit: Arc::new(InMemoryAuditRepository::new(store.clone())),
+                    embeddings: Arc::new(InMemoryEmbeddingRepository::new(store.clone())),
+                    inventory: Arc::new(InMemoryInventoryRepository::new(store.clone())),
+                    geolocation: Arc::new(InMemoryGeolocationRepository::new(store.clone())),
+                    support: Arc::new(InMemorySupportRepository::new(store.clone())),
+                    coupons: Arc::new(InMemoryCouponRepository::new(store.clone())),
+                    payment_transactions: Arc::new(InMemoryPaymentTransactionRepository::new(
+                        store.clone(),
+                    )),
+                    maintenance: Arc::new(InMemoryMaintenanceRepository),
+                    diagnostics: Arc::new(InMemoryDiagnosticsRepository),
+                    data_quality: Arc::new(InMemoryDataQualityRepository::new(store.clone())),
+                    imports: Arc::new(InMemoryImportRepository::new(store.clone())),
+                    stats: Arc::new(InMemoryStatsRepository::new(store.clone())),
+                    webhooks: Arc::new(InMemoryWebhookRepository::new(store.clone())),
+                    outbox: Arc::new(InMemoryOutboxRepository::new(store.clone())),
+                    notifications: Arc::new(InMemoryNotificationRepository::new(store.clone())),
+                    retention: Arc::new(InMemoryRetentionRepository::new(store.clone())),
+                    review_sentiments: Arc::new(InMemoryReviewSentimentRepository),
+                    late_orders: Arc::new(InMemoryLateOrderRepository::new(store.clone())),
+                }
+            }
+        }
+    }
+}
+
+/// Connects to the primary. The replica is connected lazily, so an unreachable replica
+/// doesn't hold up startup; reads fall back to the primary until it answers.
+async fn connect_postgres(config: &AppConfig) -> Result<Database, AppError> {
+    let pool = pg_pool_options(config)
+        .connect_with(pg_connect_options(&config.database_url, config)?)
+        .await
+        .map_err(AppError::DatabaseError)?;
+
+    let reads = match &config.database_replica_url {
+        Some(replica_url) => {
+            info!("Routing reads to the read replica.");
+            let replica =
+                pg_pool_options(config).connect_lazy_with(pg_connect_options(replica_url, config)?);
+            ReadPool::with_replica(
+                pool.clone(),
+                replica,
+                Duration::from_secs(config.pool.replica_recheck_seconds),
+            )
+        }
+        None => ReadPool::primary(pool.clone()),
+    };
+    Ok(Database::Postgres { pool, reads })
+}
+
+fn pg_connect_options(url: &str, config: &AppConfig) -> Result<PgConnectOptions, AppError> {
+    let mut connect_options = PgConnectOptions::from_str(url).map_err(AppError::DatabaseError)?;
+    if config.pool.statement_timeout_ms > 0 {
+        connect_options = connect_options.options([(
+            "statement_timeout",
+            config.pool.statement_timeout_ms.to_string(),
+        )]);
+    }
+    Ok(connect_options)
+}
+
+fn pg_pool_options(config: &AppConfig) -> PgPoolOptions {
+    PgPoolOptions::new()
+        .max_connections(config.pool.max_connections)
+        .min_connections(config.pool.min_connections)
+        .acquire_timeout(Duration::from_secs(config.pool.acquire_timeout_seconds))
+        .idle_timeout(
+            (config.pool.idle_timeout_seconds > 0)
+                .then(|| Duration::from_secs(config.pool.idle_timeout_seconds)),
+        )
+}
+
+/// Opens a SQLite pool, creating the file when missing. An in-memory database lives only as
+/// long as its connection, so it gets a single connection that is never closed.
+async fn connect_sqlite(config: &AppConfig) -> Result<SqlitePool, AppError> {
+    let connect_options = SqliteConnectOptions::from_str(&config.database_url)
+        .map_err(AppError::DatabaseError)?
+        .create_if_missing(true)
+        .foreign_keys(true);
+
+    let in_memory =
+        config.database_url.contains(":memory:") || config.database_url.contains("mode=memory");
+    let pool_options = if in_memory {
+        SqlitePoolOptions::new()
+            .max_connections(1)
+            .min_connections(1)
+            .idle_timeout(None)
+            .max_lifetime(None)
+    } else {
+        SqlitePoolOptions::new()
+            .max_connections(config.pool.max_connections)
+            .min_connections(config.pool.min_connections)
+            .idle_timeout(
+                (config.pool.idle_timeout_seconds > 0)
+                    .then(|| Duration::from_secs(config.pool.idle_timeout_seconds)),
+            )
+    };
+
+    pool_options
+        .acquire_timeout(Duration::from_secs(config.pool.acquire_timeout_seconds))
+        .connect_with(connect_options)
+        .await
+        .map_err(AppError::DatabaseError)
+}
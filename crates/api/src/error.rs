@@ -0,0 +1,246 @@
+use axum::{
+    body::Body,
+    extract::Request,
+    http::{StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Json, Response},
+};
+use domain::error::AppError;
+use std::any::Any;
+use tracing::error;
+
+use crate::versioning::ApiVersion;
+
+/// Set on every request by the server, or kept when the client sends one, and echoed in the
+/// response.
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+pub type ApiResult<T> = Result<T, ApiError>;
+
+/// An [`AppError`] rendered as a JSON error response. Handlers return [`ApiResult`], so `?`
+/// on a service call converts the domain error.
+#[derive(Debug)]
+pub struct ApiError(pub AppError);
+
+impl From<AppError> for ApiError {
+    fn from(error: AppError) -> Self {
+        ApiError(error)
+    }
+}
+
+impl From<validator::ValidationErrors> for ApiError {
+    fn from(error: validator::ValidationErrors) -> Self {
+        ApiError(error.into())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let (status, msg) = match &self.0 {
+            AppError::NotFound => (StatusCode::NOT_FOUND, "Resource Not Found".to_string()),
+            AppError::RouteNotFound(request) => (
+                StatusCode::NOT_FOUND,
+                format!("No endpoint matches {}.", request),
+            ),
+            AppError::MethodNotAllowed(request) => (
+                StatusCode::METHOD_NOT_ALLOWED,
+                format!(
+                    "{} is not supported; the Allow header lists the methods that are.",
+                    request
+                ),
+            ),
+            AppError::ValidationError(e) => {
+                (StatusCode::BAD_REQUEST, format!("Validation error: {}", e))
+            }
+            AppError::NoChangesToUpdate => (
+                StatusCode::BAD_REQUEST,
+                "No valid fields provided for update.".to_string(),
+            ),
+            AppError::AlreadyExists(msg) => (StatusCode::CONFLICT, msg.clone()),
+            AppError::InsufficientStock(msg) => (StatusCode::CONFLICT, msg.clone()),
+            AppError::AmendmentNotAllowed(msg) => (StatusCode::CONFLICT, msg.clone()),
+            AppError::CouponRejected(msg) => (StatusCode::UNPROCESSABLE_ENTITY, msg.clone()),
+            AppError::PaymentNotAllowed(msg) => (StatusCode::CONFLICT, msg.clone()),
+            AppError::JobAlreadyRunning(msg) => (StatusCode::CONFLICT, msg.clone()),
+            AppError::RollbackNotAllowed(msg) => (StatusCode::CONFLICT, msg.clone()),
+            AppError::DeleteRestricted(msg) => (StatusCode::CONFLICT, msg.clone()),
+            AppError::RequestTimeout => (
+                StatusCode::REQUEST_TIMEOUT,
+                "Request took too long to process.".to_string(),
+            ),
+            AppError::PayloadTooLarge => (
+                StatusCode::PAYLOAD_TOO_LARGE,
+                "Request body exceeds the configured size limit.".to_string(),
+            ),
+            AppError::Unauthorized => (
+                StatusCode::UNAUTHORIZED,
+                "A valid API key is required.".to_string(),
+            ),
+            AppError::InvalidSignature => (
+                StatusCode::UNAUTHORIZED,
+                "The notification signature is missing or invalid.".to_string(),
+            ),
+            AppError::QuotaExceeded(retry_after) => (
+                StatusCode::TOO_MANY_REQUESTS,
+                format!("Quota exhausted, retry in {} seconds.", retry_after),
+            ),
+            AppError::Overloaded(retry_after) => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                format!(
+                    "The server is at capacity, retry in {} seconds.",
+                    retry_after
+                ),
+            ),
+            AppError::ReadOnly(reason) => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                match reason {
+                    Some(reason) => format!(
+                        "The service is read-only ({}); reads still work, retry writes later.",
+                        reason
+                    ),
+                    None => "The service is read-only; reads still work, retry writes later."
+                        .to_string(),
+                },
+            ),
+            AppError::UnsupportedApiVersion(version) => (
+                StatusCode::BAD_REQUEST,
+                format!(
+                    "API version '{}' is not supported; supported versions: {}.",
+                    version,
+                    ApiVersion::ALL
+                        .iter()
+                        .map(|version| version.number().to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            ),
+            AppError::TenantNotResolved(msg) => (StatusCode::BAD_REQUEST, msg.clone()),
+            AppError::FeatureDisabled(feature) => (
+                StatusCode::NOT_IMPLEMENTED,
+                format!("{} is not enabled on this deployment.", feature),
+            ),
+            AppError::DatabaseError(e) => {
+                error!("Database Error: {:?}", e);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Database Operation Failed".to_string(),
+                )
+            }
+            AppError::MigrationError(e) => {
+                error!("Migration Error: {:?}", e);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Database Migration Failed".to_string(),
+                )
+            }
+            AppError::CacheError(e) => {
+                error!("Cache Error: {}", e);
+                (
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    "Response Cache Unavailable".to_string(),
+                )
+            }
+            AppError::CarrierError(e) => {
+                error!("Carrier Error: {}", e);
+                (
+                    StatusCode::BAD_GATEWAY,
+                    format!("Carrier request failed: {}", e),
+                )
+            }
+            AppError::ZipLookupError(e) => {
+                error!("Zip Lookup Error: {}", e);
+                (StatusCode::BAD_GATEWAY, format!("CEP lookup failed: {}", e))
+            }
+            AppError::PaymentError(e) => {
+                error!("Payment Error: {}", e);
+                (
+                    StatusCode::BAD_GATEWAY,
+                    format!("Payment provider request failed: {}", e),
+                )
+            }
+            AppError::StorageError(e) => {
+                error!("Storage Error: {}", e);
+                (
+                    StatusCode::BAD_GATEWAY,
+                    format!("Object storage request failed: {}", e),
+                )
+            }
+            AppError::Panic(e) => {
+                error!("Handler panicked: {}", e);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Internal Server Error".to_string(),
+                )
+            }
+            AppError::ConfigError(e) => {
+                error!("Configuration Error: {}", e);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Configuration Error: {}", e),
+                )
+            }
+        };
+
+        let mut response =
+            (status, Json(serde_json::json!({"error": msg.clone()}))).into_response();
+        response.extensions_mut().insert(ErrorMessage(msg));
+        if let AppError::QuotaExceeded(retry_after) | AppError::Overloaded(retry_after) = self.0 {
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, retry_after.into());
+        }
+        response
+    }
+}
+
+/// Renders a handler panic caught by `CatchPanicLayer` as a JSON 500, so the client gets an
+/// answer instead of a dropped connection.
+pub fn panic_response(panic: Box<dyn Any + Send + 'static>) -> Response {
+    let message = if let Some(message) = panic.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = panic.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic payload".to_string()
+    };
+    ApiError(AppError::Panic(message)).into_response()
+}
+
+/// The message of an [`ApiError`] response, so [`json_error_responses`] can render it again
+/// with the request id.
+#[derive(Debug, Clone)]
+struct ErrorMessage(String);
+
+/// Rewrites the bare 408/413 responses produced by the timeout and body-limit layers (and by
+/// extractors hitting the limit) into the JSON error format used by every other endpoint, and
+/// adds the request's `X-Request-Id` to every JSON error as `request_id`.
+pub async fn json_error_responses(request: Request, next: Next) -> Response {
+    let request_id = request
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+    let response = next.run(request).await;
+
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .is_some_and(|value| value.as_bytes().starts_with(b"application/json"));
+    let response = match response.status() {
+        _ if is_json => response,
+        StatusCode::REQUEST_TIMEOUT => ApiError(AppError::RequestTimeout).into_response(),
+        StatusCode::PAYLOAD_TOO_LARGE => ApiError(AppError::PayloadTooLarge).into_response(),
+        _ => response,
+    };
+
+    let (Some(request_id), Some(ErrorMessage(msg))) = (
+        request_id,
+        response.extensions().get::<ErrorMessage>().cloned(),
+    ) else {
+        return response;
+    };
+    let (mut parts, _) = response.into_parts();
+    parts.headers.remove(header::CONTENT_LENGTH);
+    let body = serde_json::json!({"error": msg, "request_id": request_id});
+    Response::from_parts(parts, Body::from(body.to_string()))
+}
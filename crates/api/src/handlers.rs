@@ -0,0 +1,1687 @@
+use axum::{
+    body::{Body, Bytes},
+    extract::{
+        FromRequestParts, OriginalUri, Path, Query, Request, State,
+        ws::{CloseFrame, Message, WebSocket, WebSocketUpgrade, close_code},
+    },
+    http::{HeaderMap, Method, StatusCode, Uri, header, request::Parts},
+    middleware::Next,
+    response::{
+        IntoResponse, Json, Response,
+        sse::{Event, KeepAlive, Sse},
+    },
+};
+use futures::stream;
+use serde::Serialize;
+use std::convert::Infallible;
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::watch;
+use validator::Validate;
+
+use domain::error::AppError;
+use domain::ids::{CustomerId, OrderId, ProductId, SellerId};
+use domain::models::{
+    AddItemToOrderDto, AdjustStockDto, AdminStatsQuery, AmendOrderDto, ApplyCouponDto,
+    ArchiveOrdersQuery, AuditSearchQuery, AuthorizePaymentDto, CityValuesQuery, CreateCategoryDto,
+    CreateCouponDto, CreateCustomerAddressDto, CreateCustomerDto, CreateOrderDto, CreateProductDto,
+    CreateRefundDto, CreateSellerDto, CreateStockLocationDto, CreateSupportCaseDto,
+    CreateSupportMessageDto, CreateWebhookDto, CustomerSearchQuery, DataQualityQuery,
+    DeleteReceipt, DuplicateCustomersQuery, ExportFormat, ExportQuery, FreightEstimateDto,
+    FreightQuoteDto, ImportErrorQuery, LoadDataQuery, LoadJob, MergeCustomersDto,
+    NearbySellersQuery, NotificationQuery, OrderFeedEvent, OrderIssue, OrderSampleQuery,
+    OrderSearchQuery, OrderStatusWaitQuery, PaginatedResponse, PaginationLinks, PaginationParams,
+    PaymentAnalyticsQuery, ProductIssue, ProductSearchQuery, RecommendationsQuery,
+    ReconciliationQuery, ReviewCorpusQuery, ReviewQuery, SellerSearchQuery, SetReadOnlyDto,
+    SetStockDto, SimilarProductsQuery, SupportCaseSearchQuery, UpdateCategoryDto,
+    UpdateCustomerAddressDto, UpdateCustomerDto, UpdateProductDto, UpdateSupportCaseDto,
+    WebhookDeliveryQuery,
+};
+use domain::runtime::ReadOnlyMode;
+use domain::services::EMBEDDING_REFRESH_BATCH_SIZE;
+use importer::export::encode_rows;
+use importer::import::Dataset;
+use importer::jobs::LoadRequest;
+use importer::seed::{SeedOptions, seed};
+use importer::validate::validate_datasets;
+
+use crate::client_ip::ClientIp;
+use crate::error::{ApiError, ApiResult};
+use crate::multipart::parse_uploads;
+use crate::state::AppState;
+
+pub(crate) const ACTOR_HEADER: &str = "x-actor";
+const ANONYMOUS_ACTOR: &str = "anonymous";
+/// Width of `audit_log.actor`.
+const ACTOR_MAX_CHARS: usize = 100;
+
+/// Identity recorded in the audit log for write operations: the `X-Actor` header followed by
+/// the client's address, e.g. `alice (203.0.113.7)`. The name is shortened to fit the column.
+pub struct Actor(pub String);
+
+impl<S: Send + Sync> FromRequestParts<S> for Actor {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let name = parts
+            .headers
+            .get(ACTOR_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .unwrap_or(ANONYMOUS_ACTOR);
+
+        let suffix = match parts.extensions.get::<ClientIp>() {
+            Some(client_ip) => format!(" ({})", client_ip),
+            None => String::new(),
+        };
+        let name: String = name
+            .chars()
+            .take(ACTOR_MAX_CHARS - suffix.chars().count())
+            .collect();
+        Ok(Actor(name + &suffix))
+    }
+}
+
+const PREFER_HEADER: &str = "prefer";
+const PREFERENCE_APPLIED_HEADER: &str = "preference-applied";
+const RETURN_REPRESENTATION: &str = "return=representation";
+const RESPOND_ASYNC: &str = "respond-async";
+
+/// Whether one of the request's `Prefer` headers (RFC 7240) lists `preference`.
+fn prefers(parts: &Parts, preference: &str) -> bool {
+    parts
+        .headers
+        .get_all(PREFER_HEADER)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|requested| requested.trim().eq_ignore_ascii_case(preference))
+}
+
+/// Whether the client sent `Prefer: return=representation`.
+pub struct ReturnRepresentation(pub bool);
+
+impl<S: Send + Sync> FromRequestParts<S> for ReturnRepresentation {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(ReturnRepresentation(prefers(parts, RETURN_REPRESENTATION)))
+    }
+}
+
+/// Whether the client sent `Prefer: respond-async`, asking for a `202` instead of waiting
+/// for a long-running operation.
+pub struct RespondAsync(pub bool);
+
+impl<S: Send + Sync> FromRequestParts<S> for RespondAsync {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(RespondAsync(prefers(parts, RESPOND_ASYNC)))
+    }
+}
+
+/// A bare `204`, or the receipt as JSON when the client asked for a representation.
+fn delete_response(receipt: DeleteReceipt, representation: ReturnRepresentation) -> Response {
+    if representation.0 {
+        (
+            [(PREFERENCE_APPLIED_HEADER, RETURN_REPRESENTATION)],
+            Json(receipt),
+        )
+            .into_response()
+    } else {
+        StatusCode::NO_CONTENT.into_response()
+    }
+}
+
+/// The page as JSON with `self`/`first`/`last`/`prev`/`next` links, repeated in a `Link`
+/// header (RFC 8288). Links are relative and keep the request's other query parameters.
+fn paginated_response<T: Serialize>(uri: &Uri, mut response: PaginatedResponse<T>) -> Response {
+    let page = response.meta.page;
+    let last = response.meta.total_pages;
+    // Without a total, a full page is the only hint that another one follows.
+    let has_next = match last {
+        Some(last) => page < last,
+        None => response.data.len() as u32 == response.meta.page_size,
+    };
+    let links = PaginationLinks {
+        self_link: page_link(uri, page),
+        first: page_link(uri, 1),
+        last: last.map(|last| page_link(uri, last)),
+        prev: (page > 1)
+            .then(|| page_link(uri, last.map_or(page - 1, |last| (page - 1).min(last)))),
+        next: has_next.then(|| page_link(uri, page + 1)),
+    };
+
+    let link_header = [
+        ("self", Some(&links.self_link)),
+        ("first", Some(&links.first)),
+        ("last", links.last.as_ref()),
+        ("prev", links.prev.as_ref()),
+        ("next", links.next.as_ref()),
+    ]
+    .into_iter()
+    .filter_map(|(rel, url)| url.map(|url| format!("<{}>; rel=\"{}\"", url, rel)))
+    .collect::<Vec<_>>()
+    .join(", ");
+
+    response.links = Some(links);
+    ([(header::LINK, link_header)], Json(response)).into_response()
+}
+
+/// The request path and query with `page` set to the given page.
+fn page_link(uri: &Uri, page: u32) -> String {
+    let page = format!("page={}", page);
+    let mut params: Vec<&str> = uri
+        .query()
+        .unwrap_or_default()
+        .split('&')
+        .filter(|param| !param.is_empty() && param.split('=').next() != Some("page"))
+        .collect();
+    params.push(&page);
+    format!("{}?{}", uri.path(), params.join("&"))
+}
+
+// --- Fallback Handlers ---
+
+/// Answers requests no route matches, in place of Axum's empty 404.
+pub async fn route_not_found_handler(method: Method, OriginalUri(uri): OriginalUri) -> ApiError {
+    ApiError(AppError::RouteNotFound(format!(
+        "{} {}",
+        method,
+        uri.path()
+    )))
+}
+
+/// Answers a route called with a method it doesn't serve, in place of Axum's empty 405. Axum
+/// still adds the `Allow` header.
+pub async fn method_not_allowed_handler(method: Method, OriginalUri(uri): OriginalUri) -> ApiError {
+    ApiError(AppError::MethodNotAllowed(format!(
+        "{} {}",
+        method,
+        uri.path()
+    )))
+}
+
+// --- Health Handlers ---
+
+pub async fn liveness_handler() -> impl IntoResponse {
+    Json(serde_json::json!({ "status": "ok" }))
+}
+
+pub async fn readiness_handler(State(state): State<AppState>) -> impl IntoResponse {
+    if state.readiness.is_ready() {
+        (
+            StatusCode::OK,
+            Json(serde_json::json!({ "status": "ready" })),
+        )
+    } else {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({ "status": "warming_up" })),
+        )
+    }
+}
+
+// --- Customer Handlers ---
+
+pub async fn create_customer_handler(
+    State(state): State<AppState>,
+    Actor(actor): Actor,
+    Json(payload): Json<CreateCustomerDto>,
+) -> ApiResult<impl IntoResponse> {
+    let payload = state.zip_lookup_service.complete_customer(payload).await?;
+    let customer = state
+        .customer_service
+        .create_customer(payload, &actor)
+        .await?;
+    Ok((StatusCode::CREATED, Json(customer)))
+}
+
+pub async fn get_customers_handler(
+    State(state): State<AppState>,
+    OriginalUri(uri): OriginalUri,
+    Query(query): Query<CustomerSearchQuery>,
+) -> ApiResult<Response> {
+    if let Some(fields) = query.fields()? {
+        let response = state
+            .customer_service
+            .get_customers_sparse(query, &fields)
+            .await?;
+        return Ok(paginated_response(&uri, response));
+    }
+    let response = state.customer_service.get_customers(query).await?;
+    Ok(paginated_response(&uri, response))
+}
+
+pub async fn get_customer_states_handler(
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let values = state.customer_service.get_state_values().await?;
+    Ok(Json(values))
+}
+
+pub async fn get_customer_cities_handler(
+    State(state): State<AppState>,
+    Query(query): Query<CityValuesQuery>,
+) -> ApiResult<impl IntoResponse> {
+    let values = state.customer_service.get_city_values(query).await?;
+    Ok(Json(values))
+}
+
+pub async fn get_customer_by_id_handler(
+    Path(id): Path<CustomerId>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let customer = state.customer_service.get_customer_by_id(&id).await?;
+    Ok(Json(customer))
+}
+
+pub async fn update_customer_handler(
+    Path(id): Path<CustomerId>,
+    State(state): State<AppState>,
+    Actor(actor): Actor,
+    Json(payload): Json<UpdateCustomerDto>,
+) -> ApiResult<impl IntoResponse> {
+    let customer = state
+        .customer_service
+        .update_customer(&id, payload, &actor)
+        .await?;
+    Ok((StatusCode::OK, Json(customer)))
+}
+
+pub async fn delete_customer_handler(
+    Path(id): Path<CustomerId>,
+    State(state): State<AppState>,
+    Actor(actor): Actor,
+    representation: ReturnRepresentation,
+) -> ApiResult<Response> {
+    let receipt = state.customer_service.delete_customer(&id, &actor).await?;
+    Ok(delete_response(receipt, representation))
+}
+
+pub async fn restore_customer_handler(
+    Path(id): Path<CustomerId>,
+    State(state): State<AppState>,
+    Actor(actor): Actor,
+) -> ApiResult<impl IntoResponse> {
+    let customer = state.customer_service.restore_customer(&id, &actor).await?;
+    Ok(Json(customer))
+}
+
+pub async fn anonymize_customer_handler(
+    Path(id): Path<CustomerId>,
+    State(state): State<AppState>,
+    Actor(actor): Actor,
+) -> ApiResult<impl IntoResponse> {
+    let customer = state
+        .customer_service
+        .anonymize_customer(&id, &actor)
+        .await?;
+    Ok(Json(customer))
+}
+
+pub async fn merge_customers_handler(
+    State(state): State<AppState>,
+    Actor(actor): Actor,
+    Json(payload): Json<MergeCustomersDto>,
+) -> ApiResult<impl IntoResponse> {
+    let merge = state
+        .customer_service
+        .merge_customers(payload, &actor)
+        .await?;
+    Ok(Json(merge))
+}
+
+pub async fn export_customer_handler(
+    Path(id): Path<CustomerId>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let customer = state.customer_service.get_customer_by_id(&id).await?;
+    let stream = state.order_service.export_customer(customer);
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/json".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"customer-{}.json\"", id),
+            ),
+        ],
+        Body::from_stream(stream),
+    ))
+}
+
+pub async fn export_customers_handler(
+    State(state): State<AppState>,
+    Query(query): Query<ExportQuery>,
+) -> impl IntoResponse {
+    let stream = encode_rows(state.customer_service.export_all(), query.format);
+    export_response("customers", query.format, Body::from_stream(stream))
+}
+
+pub async fn export_orders_handler(
+    State(state): State<AppState>,
+    Query(query): Query<ExportQuery>,
+) -> impl IntoResponse {
+    let stream = encode_rows(state.order_service.export_all(), query.format);
+    export_response("orders", query.format, Body::from_stream(stream))
+}
+
+pub async fn export_products_handler(
+    State(state): State<AppState>,
+    Query(query): Query<ExportQuery>,
+) -> impl IntoResponse {
+    let stream = encode_rows(state.product_service.export_all(), query.format);
+    export_response("products", query.format, Body::from_stream(stream))
+}
+
+fn export_response(entity: &str, format: ExportFormat, body: Body) -> impl IntoResponse {
+    (
+        [
+            (header::CONTENT_TYPE, format.content_type().to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}.{}\"", entity, format.extension()),
+            ),
+        ],
+        body,
+    )
+}
+
+/// Every live customer row sharing `unique_id`, with all their orders.
+pub async fn get_unique_customer_handler(
+    Path(unique_id): Path<String>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let customers = state
+        .customer_service
+        .get_customers_by_unique_id(&unique_id)
+        .await?;
+    let customer = state
+        .order_service
+        .get_unique_customer(unique_id, customers)
+        .await?;
+    Ok(Json(customer))
+}
+
+pub async fn get_customer_history_handler(
+    Path(id): Path<CustomerId>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let history = state
+        .customer_service
+        .get_customer_location_history(&id)
+        .await?;
+    Ok(Json(history))
+}
+
+pub async fn create_customer_address_handler(
+    Path(id): Path<CustomerId>,
+    State(state): State<AppState>,
+    Actor(actor): Actor,
+    Json(payload): Json<CreateCustomerAddressDto>,
+) -> ApiResult<impl IntoResponse> {
+    let address = state
+        .customer_service
+        .create_address(&id, payload, &actor)
+        .await?;
+    Ok((StatusCode::CREATED, Json(address)))
+}
+
+pub async fn get_customer_addresses_handler(
+    Path(id): Path<CustomerId>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let addresses = state.customer_service.get_addresses(&id).await?;
+    Ok(Json(addresses))
+}
+
+pub async fn get_customer_address_handler(
+    Path((id, address_id)): Path<(CustomerId, i64)>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let address = state.customer_service.get_address(&id, address_id).await?;
+    Ok(Json(address))
+}
+
+pub async fn update_customer_address_handler(
+    Path((id, address_id)): Path<(CustomerId, i64)>,
+    State(state): State<AppState>,
+    Actor(actor): Actor,
+    Json(payload): Json<UpdateCustomerAddressDto>,
+) -> ApiResult<impl IntoResponse> {
+    let address = state
+        .customer_service
+        .update_address(&id, address_id, payload, &actor)
+        .await?;
+    Ok(Json(address))
+}
+
+pub async fn delete_customer_address_handler(
+    Path((id, address_id)): Path<(CustomerId, i64)>,
+    State(state): State<AppState>,
+    Actor(actor): Actor,
+    representation: ReturnRepresentation,
+) -> ApiResult<Response> {
+    let receipt = state
+        .customer_service
+        .delete_address(&id, address_id, &actor)
+        .await?;
+    Ok(delete_response(receipt, representation))
+}
+
+pub async fn get_customer_orders_handler(
+    Path(id): Path<CustomerId>,
+    State(state): State<AppState>,
+    OriginalUri(uri): OriginalUri,
+    Query(pagination): Query<PaginationParams>,
+) -> ApiResult<Response> {
+    let response = state
+        .order_service
+        .get_orders_by_customer(&id, &pagination)
+        .await?;
+    Ok(paginated_response(&uri, response))
+}
+
+/// Sellers within `radius_km` (default 50) of the customer's zip code prefix, nearest first.
+pub async fn get_nearby_sellers_handler(
+    Path(id): Path<CustomerId>,
+    State(state): State<AppState>,
+    Query(query): Query<NearbySellersQuery>,
+) -> ApiResult<impl IntoResponse> {
+    let sellers = state
+        .nearby_seller_service
+        .find_near_customer(&id, &query)
+        .await?;
+    Ok(Json(sellers))
+}
+
+pub async fn get_customer_recommendations_handler(
+    Path(id): Path<CustomerId>,
+    State(state): State<AppState>,
+    Query(query): Query<RecommendationsQuery>,
+) -> ApiResult<impl IntoResponse> {
+    let customer = state.customer_service.get_customer_by_id(&id).await?;
+    let products = state
+        .product_service
+        .get_recommendations(&customer, query.limit())
+        .await?;
+    Ok(Json(state.id_codec.encode_response(products)))
+}
+
+// --- Seller Handlers ---
+
+pub async fn create_seller_handler(
+    State(state): State<AppState>,
+    Actor(actor): Actor,
+    Json(payload): Json<CreateSellerDto>,
+) -> ApiResult<impl IntoResponse> {
+    let seller = state.seller_service.create_seller(payload, &actor).await?;
+    Ok((StatusCode::CREATED, Json(seller)))
+}
+
+pub async fn get_sellers_handler(
+    State(state): State<AppState>,
+    OriginalUri(uri): OriginalUri,
+    Query(query): Query<SellerSearchQuery>,
+) -> ApiResult<Response> {
+    if let Some(fields) = query.fields()? {
+        let response = state
+            .seller_service
+            .get_sellers_sparse(query, &fields)
+            .await?;
+        return Ok(paginated_response(&uri, response));
+    }
+    let response = state.seller_service.get_sellers(query).await?;
+    Ok(paginated_response(&uri, response))
+}
+
+pub async fn get_seller_badge_thresholds_handler(
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let thresholds = state.seller_service.get_badge_thresholds().await?;
+    Ok(Json(thresholds))
+}
+
+pub async fn get_seller_by_id_handler(
+    Path(id): Path<SellerId>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let id = state.id_codec.decode(id);
+    let seller = state.seller_service.get_seller_by_id(&id).await?;
+    Ok(Json(state.id_codec.encode_response(seller)))
+}
+
+pub async fn get_seller_scorecard_handler(
+    Path(id): Path<SellerId>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let id = state.id_codec.decode(id);
+    let scorecard = state.seller_service.get_scorecard(&id).await?;
+    Ok(Json(state.id_codec.encode_response(scorecard)))
+}
+
+// --- Inventory Handlers ---
+
+pub async fn create_stock_location_handler(
+    Path(seller_id): Path<SellerId>,
+    State(state): State<AppState>,
+    Actor(actor): Actor,
+    Json(payload): Json<CreateStockLocationDto>,
+) -> ApiResult<impl IntoResponse> {
+    state.seller_service.get_seller_by_id(&seller_id).await?;
+    let location = state
+        .inventory_service
+        .create_location(&seller_id, payload, &actor)
+        .await?;
+    Ok((StatusCode::CREATED, Json(location)))
+}
+
+pub async fn get_stock_locations_handler(
+    Path(seller_id): Path<SellerId>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    state.seller_service.get_seller_by_id(&seller_id).await?;
+    let locations = state
+        .inventory_service
+        .get_locations_by_seller(&seller_id)
+        .await?;
+    Ok(Json(locations))
+}
+
+pub async fn get_location_stock_handler(
+    Path(location_id): Path<i64>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let stock = state
+        .inventory_service
+        .get_location_stock(location_id)
+        .await?;
+    Ok(Json(stock))
+}
+
+pub async fn get_product_stock_handler(
+    Path(id): Path<ProductId>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let stock = state.inventory_service.get_product_stock(&id).await?;
+    Ok(Json(stock))
+}
+
+pub async fn set_location_stock_handler(
+    Path((location_id, product_id)): Path<(i64, ProductId)>,
+    State(state): State<AppState>,
+    Actor(actor): Actor,
+    Json(payload): Json<SetStockDto>,
+) -> ApiResult<impl IntoResponse> {
+    let stock = state
+        .inventory_service
+        .set_stock(location_id, &product_id, payload, &actor)
+        .await?;
+    Ok(Json(stock))
+}
+
+pub async fn adjust_location_stock_handler(
+    Path((location_id, product_id)): Path<(i64, ProductId)>,
+    State(state): State<AppState>,
+    Actor(actor): Actor,
+    Json(payload): Json<AdjustStockDto>,
+) -> ApiResult<impl IntoResponse> {
+    let stock = state
+        .inventory_service
+        .adjust_stock(location_id, &product_id, payload, &actor)
+        .await?;
+    Ok(Json(stock))
+}
+
+// --- Order Handlers ---
+
+pub async fn create_order_handler(
+    State(state): State<AppState>,
+    Actor(actor): Actor,
+    Json(payload): Json<CreateOrderDto>,
+) -> ApiResult<impl IntoResponse> {
+    if let Some(address_id) = payload.shipping_address_id {
+        state
+            .customer_service
+            .get_address(&payload.customer_id, address_id)
+            .await?;
+    }
+    let order = state.order_service.create_order(payload, &actor).await?;
+    Ok((StatusCode::CREATED, Json(order)))
+}
+
+pub async fn get_orders_handler(
+    State(state): State<AppState>,
+    OriginalUri(uri): OriginalUri,
+    Query(query): Query<OrderSearchQuery>,
+) -> ApiResult<Response> {
+    if let Some(fields) = query.fields()? {
+        let response = state
+            .order_service
+            .get_orders_sparse(query, &fields)
+            .await?;
+        return Ok(paginated_response(&uri, response));
+    }
+    let response = state.order_service.get_orders(query).await?;
+    Ok(paginated_response(&uri, response))
+}
+
+pub async fn sample_orders_handler(
+    State(state): State<AppState>,
+    Query(query): Query<OrderSampleQuery>,
+) -> ApiResult<impl IntoResponse> {
+    let sample = state.order_service.sample_orders(query).await?;
+    Ok(Json(sample))
+}
+
+pub async fn wait_for_order_status_handler(
+    Path(id): Path<OrderId>,
+    State(state): State<AppState>,
+    Query(query): Query<OrderStatusWaitQuery>,
+) -> ApiResult<impl IntoResponse> {
+    let id = state.id_codec.decode(id);
+    let poll = state
+        .order_service
+        .wait_for_status_change(&id, query.since_version, query.wait()?)
+        .await?;
+    Ok(Json(state.id_codec.encode_response(poll)))
+}
+
+/// Server-sent events of new orders and status changes, named `order.created` and
+/// `order.status_changed`. A client too slow to keep up gets a `lagged` event carrying the
+/// number of events it missed, and should re-read what it shows.
+pub async fn stream_orders_handler(State(state): State<AppState>) -> impl IntoResponse {
+    let codec = state.id_codec.clone();
+    let events = stream::unfold(state.order_service.subscribe_feed(), move |mut feed| {
+        let codec = codec.clone();
+        async move {
+            let event = match feed.recv().await {
+                Ok(event) => {
+                    let name = event.name();
+                    let data = match codec.encode_response(event) {
+                        OrderFeedEvent::Created(order) => serde_json::to_string(&order),
+                        OrderFeedEvent::StatusChanged(change) => serde_json::to_string(&change),
+                    };
+                    Event::default().event(name).data(data.unwrap_or_default())
+                }
+                Err(RecvError::Lagged(missed)) => {
+                    Event::default().event("lagged").data(missed.to_string())
+                }
+                Err(RecvError::Closed) => return None,
+            };
+            Some((Ok::<_, Infallible>(event), feed))
+        }
+    });
+
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
+
+/// Orders the late-delivery check flagged that still haven't arrived, most overdue first.
+pub async fn get_late_orders_handler(
+    State(state): State<AppState>,
+    OriginalUri(uri): OriginalUri,
+    Query(pagination): Query<PaginationParams>,
+) -> ApiResult<Response> {
+    let response = state.late_order_service.get_late_orders(pagination).await?;
+    Ok(paginated_response(&uri, response))
+}
+
+pub async fn get_order_statuses_handler(
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let values = state.order_service.get_status_values().await?;
+    Ok(Json(values))
+}
+
+pub async fn get_order_by_id_handler(
+    Path(id): Path<OrderId>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let id = state.id_codec.decode(id);
+    let response = state.order_service.get_order_by_id(&id).await?;
+    Ok(Json(state.id_codec.encode_response(response)))
+}
+
+pub async fn add_item_to_order_by_id_handler(
+    State(state): State<AppState>,
+    Path(order_id): Path<OrderId>,
+    Actor(actor): Actor,
+    Json(payload): Json<AddItemToOrderDto>,
+) -> ApiResult<impl IntoResponse> {
+    let order_item = state
+        .order_service
+        .add_item_to_order(&order_id, payload, &actor)
+        .await?;
+    Ok((StatusCode::CREATED, Json(order_item)))
+}
+
+pub async fn amend_order_handler(
+    Path(order_id): Path<OrderId>,
+    State(state): State<AppState>,
+    Actor(actor): Actor,
+    Json(payload): Json<AmendOrderDto>,
+) -> ApiResult<impl IntoResponse> {
+    let amendment = state
+        .order_service
+        .amend_order(&order_id, payload, &actor)
+        .await?;
+    Ok((StatusCode::CREATED, Json(amendment)))
+}
+
+/// Applies a coupon to the order and returns its items with the new discount line and total.
+pub async fn apply_coupon_handler(
+    Path(order_id): Path<OrderId>,
+    State(state): State<AppState>,
+    Actor(actor): Actor,
+    Json(payload): Json<ApplyCouponDto>,
+) -> ApiResult<impl IntoResponse> {
+    let totals = state
+        .order_service
+        .apply_coupon(&order_id, payload, &actor)
+        .await?;
+    Ok(Json(totals))
+}
+
+// --- Coupon Handlers ---
+
+pub async fn create_coupon_handler(
+    State(state): State<AppState>,
+    Actor(actor): Actor,
+    Json(payload): Json<CreateCouponDto>,
+) -> ApiResult<impl IntoResponse> {
+    let coupon = state.coupon_service.create_coupon(payload, &actor).await?;
+    Ok((StatusCode::CREATED, Json(coupon)))
+}
+
+pub async fn get_coupon_handler(
+    Path(code): Path<String>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let coupon = state.coupon_service.get_coupon(&code).await?;
+    Ok(Json(coupon))
+}
+
+// --- Payment Handlers ---
+
+/// Asks the payment provider to authorize a payment for the order. Declined payments are
+/// recorded too, with status `failed`.
+pub async fn authorize_payment_handler(
+    Path(order_id): Path<OrderId>,
+    State(state): State<AppState>,
+    Actor(actor): Actor,
+    Json(payload): Json<AuthorizePaymentDto>,
+) -> ApiResult<impl IntoResponse> {
+    let transaction = state
+        .payment_service
+        .authorize(&order_id, payload, &actor)
+        .await?;
+    Ok((StatusCode::CREATED, Json(transaction)))
+}
+
+pub async fn get_payment_transactions_handler(
+    Path(order_id): Path<OrderId>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let transactions = state
+        .payment_service
+        .get_order_transactions(&order_id)
+        .await?;
+    Ok(Json(transactions))
+}
+
+pub async fn capture_payment_handler(
+    Path(id): Path<i64>,
+    State(state): State<AppState>,
+    Actor(actor): Actor,
+) -> ApiResult<impl IntoResponse> {
+    let transaction = state.payment_service.capture(id, &actor).await?;
+    Ok(Json(transaction))
+}
+
+/// Receives the payment provider's asynchronous notifications. The raw body is needed to
+/// check the signature, so it is only parsed by the provider.
+pub async fn payment_webhook_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> ApiResult<impl IntoResponse> {
+    let signature = headers
+        .get(state.payment_service.signature_header())
+        .and_then(|value| value.to_str().ok());
+    let result = state
+        .payment_service
+        .handle_notification(signature, &body)
+        .await?;
+    Ok(Json(result))
+}
+
+/// Quotes each item's freight with the configured carrier. An optional JSON body picks the
+/// service and, with `apply`, sets the items' `freight_value` to the quotes.
+pub async fn quote_order_freight_handler(
+    Path(order_id): Path<OrderId>,
+    State(state): State<AppState>,
+    Actor(actor): Actor,
+    payload: Option<Json<FreightQuoteDto>>,
+) -> ApiResult<impl IntoResponse> {
+    let dto = payload.map(|Json(dto)| dto).unwrap_or_default();
+    let quote = state
+        .shipping_service
+        .quote_order(&order_id, dto, &actor)
+        .await?;
+    Ok(Json(quote))
+}
+
+/// Street, neighborhood, city and state of a CEP, from the configured lookup provider.
+pub async fn get_cep_handler(
+    Path(code): Path<String>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let address = state.zip_lookup_service.lookup(&code).await?;
+    Ok(Json(address))
+}
+
+/// Estimates a product's freight between two CEP prefixes from their distance and the
+/// configured rate table.
+pub async fn estimate_freight_handler(
+    State(state): State<AppState>,
+    Json(payload): Json<FreightEstimateDto>,
+) -> ApiResult<impl IntoResponse> {
+    let estimate = state.freight_service.estimate(payload).await?;
+    Ok(Json(estimate))
+}
+
+pub async fn get_order_amendments_handler(
+    Path(order_id): Path<OrderId>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let amendments = state.order_service.get_order_amendments(&order_id).await?;
+    Ok(Json(amendments))
+}
+
+pub async fn get_products_by_order_id_handler(
+    Path(id): Path<OrderId>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let response = state.order_service.get_products_by_order_id(&id).await?;
+    Ok(Json(response))
+}
+
+pub async fn get_payments_by_order_id_handler(
+    Path(id): Path<OrderId>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let response = state.order_service.get_payments_by_order_id(&id).await?;
+    Ok(Json(response))
+}
+
+pub async fn get_order_summary_handler(
+    Path(id): Path<OrderId>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let response = state.order_service.get_order_summary(&id).await?;
+    Ok(Json(response))
+}
+
+pub async fn create_refund_handler(
+    Path(id): Path<OrderId>,
+    State(state): State<AppState>,
+    Actor(actor): Actor,
+    Json(payload): Json<CreateRefundDto>,
+) -> ApiResult<impl IntoResponse> {
+    let refund = state
+        .order_service
+        .refund_order(&id, payload, &actor)
+        .await?;
+    Ok((StatusCode::CREATED, Json(refund)))
+}
+
+pub async fn get_refunds_handler(
+    Path(id): Path<OrderId>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let response = state.order_service.get_refunds_by_order_id(&id).await?;
+    Ok(Json(response))
+}
+
+pub async fn get_reviews_by_order_id_handler(
+    Path(id): Path<OrderId>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let response = state.order_service.get_reviews_by_order_id(&id).await?;
+    Ok(Json(response))
+}
+
+// --- Product Handlers ---
+
+pub async fn create_product_handler(
+    State(state): State<AppState>,
+    Actor(actor): Actor,
+    Json(dto): Json<CreateProductDto>,
+) -> ApiResult<impl IntoResponse> {
+    let product = state.product_service.create_product(dto, &actor).await?;
+    Ok((StatusCode::CREATED, Json(product)))
+}
+
+pub async fn get_products_handler(
+    State(state): State<AppState>,
+    OriginalUri(uri): OriginalUri,
+    Query(query): Query<ProductSearchQuery>,
+) -> ApiResult<Response> {
+    if let Some(fields) = query.fields()? {
+        let mut response = state
+            .product_service
+            .get_products_sparse(query, &fields)
+            .await?;
+        for row in &mut response.data {
+            state.id_codec.encode_field::<ProductId>(row, "product_id");
+        }
+        return Ok(paginated_response(&uri, response));
+    }
+    let response = state.product_service.get_products(query).await?;
+    Ok(paginated_response(
+        &uri,
+        state.id_codec.encode_response(response),
+    ))
+}
+
+pub async fn get_product_categories_handler(
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let values = state.product_service.get_category_values().await?;
+    Ok(Json(values))
+}
+
+pub async fn get_product_by_id_handler(
+    State(state): State<AppState>,
+    Path(id): Path<ProductId>,
+) -> ApiResult<impl IntoResponse> {
+    let id = state.id_codec.decode(id);
+    let product = state.product_service.get_product_by_id(&id).await?;
+    Ok(Json(state.id_codec.encode_response(product)))
+}
+
+pub async fn update_product_handler(
+    State(state): State<AppState>,
+    Path(id): Path<ProductId>,
+    Actor(actor): Actor,
+    Json(payload): Json<UpdateProductDto>,
+) -> ApiResult<impl IntoResponse> {
+    let id = state.id_codec.decode(id);
+    let product = state
+        .product_service
+        .update_product(&id, payload, &actor)
+        .await?;
+    Ok(Json(state.id_codec.encode_response(product)))
+}
+
+/// Uploads images to the product as `multipart/form-data`, one file per part. They are added
+/// after its current images, in the order sent.
+pub async fn upload_product_images_handler(
+    State(state): State<AppState>,
+    Path(id): Path<ProductId>,
+    Actor(actor): Actor,
+    headers: HeaderMap,
+    body: Bytes,
+) -> ApiResult<impl IntoResponse> {
+    let id = state.id_codec.decode(id);
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok());
+    let uploads = parse_uploads(content_type, &body)?;
+    let images = state
+        .product_image_service
+        .upload_images(&id, uploads, &actor)
+        .await?;
+    Ok((
+        StatusCode::CREATED,
+        Json(state.id_codec.encode_response(images)),
+    ))
+}
+
+pub async fn get_product_images_handler(
+    State(state): State<AppState>,
+    Path(id): Path<ProductId>,
+) -> ApiResult<impl IntoResponse> {
+    let id = state.id_codec.decode(id);
+    let images = state.product_image_service.get_images(&id).await?;
+    Ok(Json(state.id_codec.encode_response(images)))
+}
+
+pub async fn delete_product_image_handler(
+    State(state): State<AppState>,
+    Path((id, image_id)): Path<(ProductId, i64)>,
+    Actor(actor): Actor,
+    representation: ReturnRepresentation,
+) -> ApiResult<Response> {
+    let id = state.id_codec.decode(id);
+    let receipt = state
+        .product_image_service
+        .delete_image(&id, image_id, &actor)
+        .await?;
+    Ok(delete_response(receipt, representation))
+}
+
+pub async fn get_similar_products_handler(
+    State(state): State<AppState>,
+    Path(id): Path<ProductId>,
+    Query(query): Query<SimilarProductsQuery>,
+) -> ApiResult<impl IntoResponse> {
+    let id = state.id_codec.decode(id);
+    let products = state
+        .similarity_service
+        .get_similar_products(&id, query.limit())
+        .await?;
+    Ok(Json(state.id_codec.encode_response(products)))
+}
+
+pub async fn refresh_product_embeddings_handler(
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let refreshed = state
+        .similarity_service
+        .refresh_missing_embeddings(EMBEDDING_REFRESH_BATCH_SIZE)
+        .await?;
+    Ok(Json(serde_json::json!({ "refreshed_count": refreshed })))
+}
+
+// --- Category Handlers ---
+
+pub async fn create_category_handler(
+    State(state): State<AppState>,
+    Actor(actor): Actor,
+    Json(payload): Json<CreateCategoryDto>,
+) -> ApiResult<impl IntoResponse> {
+    let category = state
+        .category_service
+        .create_category(payload, &actor)
+        .await?;
+    Ok((StatusCode::CREATED, Json(category)))
+}
+
+pub async fn get_category_handler(
+    Path(name): Path<String>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let category = state.category_service.get_category(&name).await?;
+    Ok(Json(category))
+}
+
+pub async fn update_category_handler(
+    Path(name): Path<String>,
+    State(state): State<AppState>,
+    Actor(actor): Actor,
+    Json(payload): Json<UpdateCategoryDto>,
+) -> ApiResult<impl IntoResponse> {
+    let category = state
+        .category_service
+        .update_category(&name, payload, &actor)
+        .await?;
+    Ok(Json(category))
+}
+
+pub async fn delete_category_handler(
+    Path(name): Path<String>,
+    State(state): State<AppState>,
+    Actor(actor): Actor,
+    representation: ReturnRepresentation,
+) -> ApiResult<Response> {
+    let receipt = state
+        .category_service
+        .delete_category(&name, &actor)
+        .await?;
+    Ok(delete_response(receipt, representation))
+}
+
+// --- Audit Handlers ---
+
+pub async fn get_audit_entries_handler(
+    State(state): State<AppState>,
+    OriginalUri(uri): OriginalUri,
+    Query(query): Query<AuditSearchQuery>,
+) -> ApiResult<Response> {
+    let response = state.audit_service.get_entries(query).await?;
+    Ok(paginated_response(&uri, response))
+}
+
+// --- Support Handlers ---
+
+pub async fn create_support_case_handler(
+    State(state): State<AppState>,
+    Actor(actor): Actor,
+    Json(payload): Json<CreateSupportCaseDto>,
+) -> ApiResult<impl IntoResponse> {
+    let case = state.support_service.create_case(payload, &actor).await?;
+    Ok((StatusCode::CREATED, Json(case)))
+}
+
+pub async fn get_support_cases_handler(
+    State(state): State<AppState>,
+    OriginalUri(uri): OriginalUri,
+    Query(query): Query<SupportCaseSearchQuery>,
+) -> ApiResult<Response> {
+    let response = state.support_service.get_cases(query).await?;
+    Ok(paginated_response(&uri, response))
+}
+
+pub async fn get_support_case_handler(
+    Path(id): Path<i64>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let case = state.support_service.get_case(id).await?;
+    Ok(Json(case))
+}
+
+pub async fn update_support_case_handler(
+    Path(id): Path<i64>,
+    State(state): State<AppState>,
+    Actor(actor): Actor,
+    Json(payload): Json<UpdateSupportCaseDto>,
+) -> ApiResult<impl IntoResponse> {
+    let case = state
+        .support_service
+        .update_case(id, payload, &actor)
+        .await?;
+    Ok(Json(case))
+}
+
+pub async fn delete_support_case_handler(
+    Path(id): Path<i64>,
+    State(state): State<AppState>,
+    Actor(actor): Actor,
+    representation: ReturnRepresentation,
+) -> ApiResult<Response> {
+    let receipt = state.support_service.delete_case(id, &actor).await?;
+    Ok(delete_response(receipt, representation))
+}
+
+pub async fn add_support_message_handler(
+    Path(id): Path<i64>,
+    State(state): State<AppState>,
+    Actor(actor): Actor,
+    Json(payload): Json<CreateSupportMessageDto>,
+) -> ApiResult<impl IntoResponse> {
+    let message = state
+        .support_service
+        .add_message(id, payload, &actor)
+        .await?;
+    Ok((StatusCode::CREATED, Json(message)))
+}
+
+pub async fn get_support_analytics_handler(
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let volume = state.support_service.get_volume_by_category().await?;
+    Ok(Json(volume))
+}
+
+pub async fn get_reconciliation_handler(
+    State(state): State<AppState>,
+    OriginalUri(uri): OriginalUri,
+    Query(query): Query<ReconciliationQuery>,
+) -> ApiResult<Response> {
+    let response = state
+        .reconciliation_service
+        .get_payment_mismatches(query)
+        .await?;
+    Ok(paginated_response(&uri, response))
+}
+
+pub async fn get_payment_analytics_handler(
+    State(state): State<AppState>,
+    Query(query): Query<PaymentAnalyticsQuery>,
+) -> ApiResult<impl IntoResponse> {
+    let analytics = state
+        .payment_analytics_service
+        .get_payment_methods(query)
+        .await?;
+    Ok(Json(analytics))
+}
+
+pub async fn get_duplicate_customers_handler(
+    State(state): State<AppState>,
+    Query(query): Query<DuplicateCustomersQuery>,
+) -> ApiResult<impl IntoResponse> {
+    let stats = state.customer_duplication_service.get_stats(query).await?;
+    Ok(Json(stats))
+}
+
+// --- Maintenance Handlers ---
+
+pub async fn refresh_all_handler(
+    State(state): State<AppState>,
+    Actor(actor): Actor,
+) -> ApiResult<impl IntoResponse> {
+    let job = state.maintenance_service.start_refresh_all(&actor).await?;
+    Ok((StatusCode::ACCEPTED, Json(job)))
+}
+
+pub async fn archive_orders_handler(
+    State(state): State<AppState>,
+    Actor(actor): Actor,
+    Query(query): Query<ArchiveOrdersQuery>,
+) -> ApiResult<impl IntoResponse> {
+    let job = state
+        .maintenance_service
+        .start_archive_orders(query, &actor)
+        .await?;
+    Ok((StatusCode::ACCEPTED, Json(job)))
+}
+
+pub async fn flush_caches_handler(
+    State(state): State<AppState>,
+    Actor(actor): Actor,
+) -> ApiResult<impl IntoResponse> {
+    let flushed = state.maintenance_service.flush_caches(&actor).await?;
+    Ok(Json(flushed))
+}
+
+pub async fn get_maintenance_job_handler(
+    Path(id): Path<u64>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let job = state.maintenance_service.get_job(id)?;
+    Ok(Json(job))
+}
+
+pub async fn get_today_stats_handler(
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let stats = state.stats_service.get_today().await?;
+    Ok(Json(stats))
+}
+
+pub async fn diagnostics_handler(State(state): State<AppState>) -> impl IntoResponse {
+    Json(state.diagnostics_service.run_checks().await)
+}
+
+pub async fn get_admin_stats_handler(
+    State(state): State<AppState>,
+    Query(query): Query<AdminStatsQuery>,
+) -> ApiResult<impl IntoResponse> {
+    Ok(Json(state.diagnostics_service.get_stats(query).await?))
+}
+
+pub async fn get_data_quality_rules_handler(
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    Ok(Json(state.data_quality_service.get_rule_results().await?))
+}
+
+pub async fn get_product_quality_handler(
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    Ok(Json(state.data_quality_service.get_product_report().await?))
+}
+
+pub async fn get_flagged_products_handler(
+    State(state): State<AppState>,
+    OriginalUri(uri): OriginalUri,
+    Query(query): Query<DataQualityQuery<ProductIssue>>,
+) -> ApiResult<Response> {
+    let response = state
+        .data_quality_service
+        .get_flagged_products(query)
+        .await?;
+    Ok(paginated_response(&uri, response))
+}
+
+pub async fn get_order_quality_handler(
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    Ok(Json(state.data_quality_service.get_order_report().await?))
+}
+
+pub async fn get_flagged_orders_handler(
+    State(state): State<AppState>,
+    OriginalUri(uri): OriginalUri,
+    Query(query): Query<DataQualityQuery<OrderIssue>>,
+) -> ApiResult<Response> {
+    let response = state.data_quality_service.get_flagged_orders(query).await?;
+    Ok(paginated_response(&uri, response))
+}
+
+pub async fn get_read_only_handler(State(state): State<AppState>) -> impl IntoResponse {
+    Json(state.maintenance_service.read_only_status())
+}
+
+pub async fn set_read_only_handler(
+    State(state): State<AppState>,
+    Actor(actor): Actor,
+    Json(dto): Json<SetReadOnlyDto>,
+) -> ApiResult<impl IntoResponse> {
+    Ok(Json(
+        state.maintenance_service.set_read_only(dto, &actor).await?,
+    ))
+}
+
+/// The only paths that still take writes in read-only mode, matched exactly.
+const READ_ONLY_EXEMPT_PATHS: &[&str] = &[
+    // The toggle itself, or read-only mode could never be switched off again.
+    "/admin/read-only",
+];
+
+/// Answers every write with `503` while read-only mode is on.
+pub async fn reject_writes_when_read_only(
+    State(read_only): State<ReadOnlyMode>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let path = request.uri().path();
+    let is_write = !matches!(
+        *request.method(),
+        Method::GET | Method::HEAD | Method::OPTIONS
+    );
+    if is_write && !READ_ONLY_EXEMPT_PATHS.contains(&path) {
+        let status = read_only.status();
+        if status.enabled {
+            return ApiError(AppError::ReadOnly(status.reason)).into_response();
+        }
+    }
+    next.run(request).await
+}
+
+pub async fn get_jobs_handler(State(state): State<AppState>) -> impl IntoResponse {
+    Json(state.job_runs.all())
+}
+
+/// What the data retention job would delete or anonymize if it ran now.
+pub async fn retention_dry_run_handler(
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    Ok(Json(state.retention_service.dry_run().await?))
+}
+
+pub async fn get_import_batches_handler(
+    State(state): State<AppState>,
+    OriginalUri(uri): OriginalUri,
+    Query(pagination): Query<PaginationParams>,
+) -> ApiResult<Response> {
+    let response = state.import_service.get_batches(pagination).await?;
+    Ok(paginated_response(&uri, response))
+}
+
+pub async fn get_import_batch_handler(
+    Path(id): Path<i64>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let batch = state.import_service.get_batch(id).await?;
+    Ok(Json(batch))
+}
+
+pub async fn rollback_import_batch_handler(
+    Path(id): Path<i64>,
+    State(state): State<AppState>,
+    Actor(actor): Actor,
+) -> ApiResult<impl IntoResponse> {
+    let rollback = state.import_service.rollback_batch(id, &actor).await?;
+    Ok(Json(rollback))
+}
+
+// --- Webhook Handlers ---
+
+pub async fn create_webhook_handler(
+    State(state): State<AppState>,
+    Actor(actor): Actor,
+    Json(payload): Json<CreateWebhookDto>,
+) -> ApiResult<impl IntoResponse> {
+    let webhook = state
+        .webhook_service
+        .create_subscription(payload, &actor)
+        .await?;
+    Ok((StatusCode::CREATED, Json(webhook)))
+}
+
+pub async fn get_webhooks_handler(
+    State(state): State<AppState>,
+    OriginalUri(uri): OriginalUri,
+    Query(pagination): Query<PaginationParams>,
+) -> ApiResult<Response> {
+    let response = state.webhook_service.get_subscriptions(pagination).await?;
+    Ok(paginated_response(&uri, response))
+}
+
+pub async fn get_webhook_handler(
+    Path(id): Path<i64>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let webhook = state.webhook_service.get_subscription(id).await?;
+    Ok(Json(webhook))
+}
+
+pub async fn delete_webhook_handler(
+    Path(id): Path<i64>,
+    State(state): State<AppState>,
+    Actor(actor): Actor,
+    representation: ReturnRepresentation,
+) -> ApiResult<Response> {
+    let receipt = state
+        .webhook_service
+        .delete_subscription(id, &actor)
+        .await?;
+    Ok(delete_response(receipt, representation))
+}
+
+pub async fn get_webhook_deliveries_handler(
+    Path(id): Path<i64>,
+    State(state): State<AppState>,
+    OriginalUri(uri): OriginalUri,
+    Query(query): Query<WebhookDeliveryQuery>,
+) -> ApiResult<Response> {
+    let response = state.webhook_service.get_deliveries(id, query).await?;
+    Ok(paginated_response(&uri, response))
+}
+
+pub async fn get_webhook_delivery_handler(
+    Path((id, delivery_id)): Path<(i64, i64)>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let delivery = state.webhook_service.get_delivery(id, delivery_id).await?;
+    Ok(Json(delivery))
+}
+
+pub async fn get_notifications_handler(
+    State(state): State<AppState>,
+    OriginalUri(uri): OriginalUri,
+    Query(query): Query<NotificationQuery>,
+) -> ApiResult<Response> {
+    let response = state.notification_service.get_notifications(query).await?;
+    Ok(paginated_response(&uri, response))
+}
+
+pub async fn get_notification_handler(
+    Path(id): Path<i64>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let notification = state.notification_service.get_notification(id).await?;
+    Ok(Json(notification))
+}
+
+pub(crate) const API_KEY_HEADER: &str = "x-api-key";
+
+pub async fn get_reviews_handler(
+    State(state): State<AppState>,
+    OriginalUri(uri): OriginalUri,
+    Query(query): Query<ReviewQuery>,
+) -> ApiResult<Response> {
+    let response = state.review_sentiment_service.get_reviews(query).await?;
+    Ok(paginated_response(&uri, response))
+}
+
+pub async fn review_corpus_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<ReviewCorpusQuery>,
+) -> ApiResult<impl IntoResponse> {
+    let api_key = headers
+        .get(API_KEY_HEADER)
+        .and_then(|value| value.to_str().ok());
+    let stream = state.review_corpus_service.stream_corpus(api_key, query)?;
+
+    Ok((
+        [(header::CONTENT_TYPE, "application/x-ndjson")],
+        Body::from_stream(stream),
+    ))
+}
+
+// --- Data Loader Handler (Optimized) ---
+
+/// The datasets `/load-data` imports, in order.
+const LOAD_DATASETS: [Dataset; 3] = [Dataset::Customers, Dataset::Sellers, Dataset::Orders];
+
+/// Imports the bundled customers, sellers and orders as a load job. The response waits for
+/// the job unless the client sent `Prefer: respond-async`, in which case it is a `202` with
+/// the job, which can be polled or followed over `/load-data/jobs/{id}/ws`. An optional JSON
+/// body picks the datasets and where the first one starts, or resumes an interrupted job
+/// from its checkpoints. With `dry_run` the files are only checked and the validation
+/// report returned.
+pub async fn load_data_from_csv_handler(
+    State(state): State<AppState>,
+    Query(query): Query<LoadDataQuery>,
+    OriginalUri(uri): OriginalUri,
+    RespondAsync(respond_async): RespondAsync,
+    request: Option<Json<LoadRequest>>,
+) -> ApiResult<Response> {
+    let request = request.map(|Json(request)| request).unwrap_or_default();
+
+    if query.dry_run {
+        request.validate()?;
+        let datasets = request.datasets.as_deref().unwrap_or(&LOAD_DATASETS);
+        let report = validate_datasets(&state.import_targets(), datasets).await?;
+        return Ok(Json(report).into_response());
+    }
+
+    // Note: In a real world scenario, file paths should be configurable or uploaded via Multipart
+    let (job, handle) = state
+        .load_jobs
+        .start(state.import_targets(), &request, &LOAD_DATASETS)
+        .await?;
+
+    if respond_async {
+        // Relative to the path the client called, so the job is polled on the same version
+        let location = format!("{}/jobs/{}", uri.path(), job.job_id);
+        return Ok((
+            StatusCode::ACCEPTED,
+            [
+                (header::LOCATION, location.as_str()),
+                (
+                    header::HeaderName::from_static(PREFERENCE_APPLIED_HEADER),
+                    RESPOND_ASYNC,
+                ),
+            ],
+            Json(job),
+        )
+            .into_response());
+    }
+
+    let batches = handle.await.map_err(|e| {
+        AppError::ConfigError(format!(
+            "Load job {} stopped unexpectedly: {}",
+            job.job_id, e
+        ))
+    })??;
+
+    Ok(Json(serde_json::json!({
+        "message": "Data load processed",
+        "job_id": job.job_id,
+        "success_count": batches.iter().map(|batch| batch.success_count).sum::<i32>(),
+        "error_count": batches.iter().map(|batch| batch.error_count).sum::<i32>(),
+        "batch_ids": batches.iter().map(|batch| batch.batch_id).collect::<Vec<_>>()
+    }))
+    .into_response())
+}
+
+pub async fn get_load_job_handler(
+    Path(id): Path<u64>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let job = state.load_jobs.get(id)?;
+    Ok(Json(job))
+}
+
+/// Rows of the job's batches that failed to import. With `format=csv` (or `ndjson`) the
+/// whole report is downloaded instead of a page.
+pub async fn get_load_job_errors_handler(
+    Path(id): Path<u64>,
+    State(state): State<AppState>,
+    OriginalUri(uri): OriginalUri,
+    Query(query): Query<ImportErrorQuery>,
+) -> ApiResult<Response> {
+    let job = state.load_jobs.get(id)?;
+    let batch_ids: Vec<i64> = job.datasets.iter().filter_map(|d| d.batch_id).collect();
+
+    if let Some(format) = query.format {
+        let stream = state.import_service.export_errors(batch_ids, format);
+        return Ok(export_response(
+            &format!("load-job-{}-errors", id),
+            format,
+            Body::from_stream(stream),
+        )
+        .into_response());
+    }
+
+    let errors = state
+        .import_service
+        .get_errors(&batch_ids, query.pagination())
+        .await?;
+    Ok(paginated_response(&uri, errors))
+}
+
+/// Least time between two progress frames; changes in between are coalesced.
+const LOAD_JOB_FRAME_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Streams a load job over a WebSocket: the job as JSON now and after each change, at most
+/// every `LOAD_JOB_FRAME_INTERVAL`, then a normal close once it has finished.
+pub async fn load_job_ws_handler(
+    Path(id): Path<u64>,
+    State(state): State<AppState>,
+    ws: WebSocketUpgrade,
+) -> ApiResult<Response> {
+    let receiver = state.load_jobs.subscribe(id)?;
+    Ok(ws.on_upgrade(move |socket| follow_load_job(socket, receiver)))
+}
+
+async fn follow_load_job(mut socket: WebSocket, mut receiver: watch::Receiver<LoadJob>) {
+    loop {
+        let job = receiver.borrow_and_update().clone();
+        let frame = serde_json::to_string(&job).unwrap_or_default();
+        if socket.send(Message::Text(frame.into())).await.is_err() {
+            return;
+        }
+        if job.finished_at.is_some() {
+            break;
+        }
+
+        tokio::time::sleep(LOAD_JOB_FRAME_INTERVAL).await;
+        loop {
+            tokio::select! {
+                changed = receiver.changed() => match changed {
+                    Ok(()) => break,
+                    Err(_) => return,
+                },
+                message = socket.recv() => match message {
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+                    // Anything else the client sends is ignored.
+                    Some(Ok(_)) => {}
+                },
+            }
+        }
+    }
+
+    let _ = socket
+        .send(Message::Close(Some(CloseFrame {
+            code: close_code::NORMAL,
+            reason: "load job finished".into(),
+        })))
+        .await;
+}
+
+pub async fn seed_data_handler(
+    State(state): State<AppState>,
+    Query(options): Query<SeedOptions>,
+) -> ApiResult<impl IntoResponse> {
+    let report = seed(&state.import_targets(), &options).await?;
+    Ok((StatusCode::CREATED, Json(report)))
+}
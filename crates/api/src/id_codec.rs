@@ -0,0 +1,251 @@
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+
+use domain::ids::{EntityId, validate_olist_id};
+use domain::models::{
+    Order, OrderFeedEvent, OrderStatusPoll, PaginatedResponse, Product, ProductImage,
+    RecommendedProduct, Seller, SellerScorecard, SimilarProduct, SparseRow,
+};
+
+use crate::config::{PublicIdConfig, PublicIdMode};
+
+const FEISTEL_ROUNDS: u8 = 4;
+
+/// Translates between raw ids and the ids shown on public endpoints.
+///
+/// The obfuscated form is a keyed Feistel permutation of the 128-bit Olist id, so it is still
+/// 32 hex characters and decodes back exactly, but cannot be matched against the public
+/// dataset without the secret. Each entity kind gets its own permutation. Ids outside the
+/// Olist format pass through unchanged.
+#[derive(Clone, Default)]
+pub struct IdCodec {
+    secret: Option<Arc<[u8]>>,
+}
+
+impl IdCodec {
+    pub fn new(config: &PublicIdConfig) -> Self {
+        match config.mode {
+            PublicIdMode::Plain => Self::default(),
+            PublicIdMode::Obfuscated => Self {
+                secret: Some(Arc::from(config.secret.as_bytes())),
+            },
+        }
+    }
+
+    /// Raw id to public id.
+    pub fn encode<T: EntityId>(&self, id: &T) -> T {
+        self.permute(id.as_ref(), true)
+    }
+
+    /// Public id, as received in a path, to raw id.
+    pub fn decode<T: EntityId>(&self, id: T) -> T {
+        if self.secret.is_none() {
+            return id;
+        }
+        self.permute(id.as_ref(), false)
+    }
+
+    /// Rewrites every entity id in a response body to its public form.
+    pub fn encode_response<R: PublicIds>(&self, response: R) -> R {
+        response.encode_ids(self)
+    }
+
+    /// Rewrites the id stored under `field` in a sparse row, if that field was selected.
+    pub fn encode_field<T: EntityId>(&self, row: &mut SparseRow, field: &str) {
+        if let Some(serde_json::Value::String(id)) = row.0.get_mut(field) {
+            *id = self.encode(&T::from(id.clone())).as_ref().to_string();
+        }
+    }
+
+    fn permute<T: EntityId>(&self, id: &str, forward: bool) -> T {
+        let (Some(secret), Some(block)) = (&self.secret, parse_olist_id(id)) else {
+            return T::from(id.to_string());
+        };
+        let (mut left, mut right) = ((block >> 64) as u64, block as u64);
+        if forward {
+            for round in 0..FEISTEL_ROUNDS {
+                (left, right) = (right, left ^ round_key(secret, T::KIND, round, right));
+            }
+        } else {
+            for round in (0..FEISTEL_ROUNDS).rev() {
+                (left, right) = (right ^ round_key(secret, T::KIND, round, left), left);
+            }
+        }
+
+        T::from(format!("{:032x}", ((left as u128) << 64) | right as u128))
+    }
+}
+
+fn parse_olist_id(id: &str) -> Option<u128> {
+    validate_olist_id(&id).ok()?;
+    u128::from_str_radix(id, 16).ok()
+}
+
+fn round_key(secret: &[u8], kind: &str, round: u8, half: u64) -> u64 {
+    let digest = Sha256::new()
+        .chain_update(secret)
+        .chain_update([0])
+        .chain_update(kind)
+        .chain_update([round])
+        .chain_update(half.to_be_bytes())
+        .finalize();
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&digest[..8]);
+    u64::from_be_bytes(bytes)
+}
+
+/// Response bodies served on public endpoints.
+pub trait PublicIds {
+    fn encode_ids(self, codec: &IdCodec) -> Self;
+}
+
+impl PublicIds for Order {
+    fn encode_ids(mut self, codec: &IdCodec) -> Self {
+        self.order_id = codec.encode(&self.order_id);
+        self.customer_id = codec.encode(&self.customer_id);
+        self
+    }
+}
+
+impl PublicIds for OrderStatusPoll {
+    fn encode_ids(mut self, codec: &IdCodec) -> Self {
+        self.status.order_id = codec.encode(&self.status.order_id);
+        self
+    }
+}
+
+impl PublicIds for OrderFeedEvent {
+    fn encode_ids(self, codec: &IdCodec) -> Self {
+        match self {
+            OrderFeedEvent::Created(order) => OrderFeedEvent::Created(order.encode_ids(codec)),
+            OrderFeedEvent::StatusChanged(mut change) => {
+                change.order_id = codec.encode(&change.order_id);
+                OrderFeedEvent::StatusChanged(change)
+            }
+        }
+    }
+}
+
+impl PublicIds for Product {
+    fn encode_ids(mut self, codec: &IdCodec) -> Self {
+        self.product_id = codec.encode(&self.product_id);
+        self
+    }
+}
+
+impl PublicIds for ProductImage {
+    fn encode_ids(mut self, codec: &IdCodec) -> Self {
+        self.product_id = codec.encode(&self.product_id);
+        self
+    }
+}
+
+impl PublicIds for SimilarProduct {
+    fn encode_ids(mut self, codec: &IdCodec) -> Self {
+        self.product = self.product.encode_ids(codec);
+        self
+    }
+}
+
+impl PublicIds for RecommendedProduct {
+    fn encode_ids(mut self, codec: &IdCodec) -> Self {
+        self.product = self.product.encode_ids(codec);
+        self
+    }
+}
+
+impl PublicIds for Seller {
+    fn encode_ids(mut self, codec: &IdCodec) -> Self {
+        self.seller_id = codec.encode(&self.seller_id);
+        self
+    }
+}
+
+impl PublicIds for SellerScorecard {
+    fn encode_ids(mut self, codec: &IdCodec) -> Self {
+        self.seller_id = codec.encode(&self.seller_id);
+        self
+    }
+}
+
+impl<T: PublicIds> PublicIds for Vec<T> {
+    fn encode_ids(self, codec: &IdCodec) -> Self {
+        self.into_iter()
+            .map(|item| item.encode_ids(codec))
+            .collect()
+    }
+}
+
+impl<T: PublicIds> PublicIds for PaginatedResponse<T> {
+    fn encode_ids(mut self, codec: &IdCodec) -> Self {
+        self.data = self.data.encode_ids(codec);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use domain::ids::{CustomerId, OrderId};
+
+    const RAW: &str = "e481f51cbdc54678b7cc49136f2d6af7";
+
+    fn with_mode(mode: PublicIdMode, secret: &str) -> IdCodec {
+        IdCodec::new(&PublicIdConfig {
+            mode,
+            secret: secret.to_string(),
+        })
+    }
+
+    fn obfuscated() -> IdCodec {
+        with_mode(PublicIdMode::Obfuscated, "test-secret")
+    }
+
+    #[test]
+    fn plain_mode_passes_ids_through() {
+        let codec = with_mode(PublicIdMode::Plain, "ignored");
+        let id = OrderId::from(RAW.to_string());
+        assert_eq!(codec.encode(&id), id);
+        assert_eq!(codec.decode(id.clone()), id);
+    }
+
+    #[test]
+    fn obfuscated_ids_keep_the_olist_format_and_decode_back() {
+        let codec = obfuscated();
+        let id = OrderId::from(RAW.to_string());
+        let public = codec.encode(&id);
+        assert_ne!(public, id);
+        assert!(validate_olist_id(&public).is_ok(), "{public}");
+        assert_eq!(codec.decode(public), id);
+    }
+
+    #[test]
+    fn round_trips_the_extremes_of_the_id_space() {
+        let codec = obfuscated();
+        for raw in ["0".repeat(32), "f".repeat(32)] {
+            let id = OrderId::from(raw);
+            assert_eq!(codec.decode(codec.encode(&id)), id);
+        }
+    }
+
+    #[test]
+    fn each_entity_kind_and_secret_gets_its_own_permutation() {
+        let codec = obfuscated();
+        let order = codec.encode(&OrderId::from(RAW.to_string()));
+        let customer = codec.encode(&CustomerId::from(RAW.to_string()));
+        assert_ne!(order.as_str(), customer.as_str());
+
+        let other = with_mode(PublicIdMode::Obfuscated, "other-secret");
+        assert_ne!(other.encode(&OrderId::from(RAW.to_string())), order);
+    }
+
+    #[test]
+    fn ids_outside_the_olist_format_pass_through() {
+        let codec = obfuscated();
+        for raw in ["not-an-id", "E481F51CBDC54678B7CC49136F2D6AF7", &RAW[1..]] {
+            let id = OrderId::from(raw.to_string());
+            assert_eq!(codec.encode(&id), id);
+            assert_eq!(codec.decode(id.clone()), id);
+        }
+    }
+}
@@ -0,0 +1,275 @@
+//! The HTTP server and command line behind the `brazilian_ecommerce` binary.
+
+pub mod access_log;
+pub mod badges;
+pub mod cache;
+pub mod carriers;
+pub mod changes;
+pub mod cli;
+pub mod client_ip;
+pub mod concurrency;
+pub mod config;
+pub mod cors;
+pub mod database;
+pub mod error;
+pub mod handlers;
+pub mod id_codec;
+pub mod multipart;
+pub mod notifications;
+pub mod outbox;
+pub mod payments;
+pub mod routes;
+pub mod scheduler;
+pub mod state;
+pub mod storage;
+pub mod tenancy;
+#[cfg(feature = "test-utils")]
+pub mod testing;
+pub mod tls;
+pub mod versioning;
+pub mod warmup;
+pub mod webhooks;
+pub mod zip_lookup;
+
+use axum::{Router, extract::DefaultBodyLimit, middleware};
+use std::{collections::HashMap, net::SocketAddr, time::Duration};
+use tower_http::catch_panic::CatchPanicLayer;
+use tower_http::limit::RequestBodyLimitLayer;
+use tower_http::request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer};
+use tracing::{info, warn};
+
+use domain::error::AppError;
+use domain::events::OrderStatusEvents;
+use domain::runtime::{ReadOnlyMode, Readiness};
+use domain::tenancy::TenantId;
+
+use crate::access_log::access_log;
+use crate::client_ip::{TrustedProxies, resolve_client_ip};
+use crate::concurrency::ConcurrencyLimit;
+use crate::config::{AppConfig, create_compression_layer};
+use crate::cors::{CorsPolicies, cors_by_route};
+use crate::database::Database;
+use crate::error::{json_error_responses, panic_response};
+use crate::state::AppState;
+
+/// Migrates the database, starts the background jobs of every tenant and builds the router,
+/// which dispatches to one router per tenant, with every layer the server runs behind.
+pub async fn app(config: &AppConfig, database: Database) -> Result<Router, AppError> {
+    database.run_migrations().await?;
+    database.check_collation(config.collation).await?;
+
+    let readiness = Readiness::default();
+
+    // SQLite has no LISTEN/NOTIFY, so status waits there simply run to their timeout.
+    let order_status_events: HashMap<TenantId, OrderStatusEvents> = config
+        .tenancy
+        .tenants
+        .iter()
+        .map(|tenant| (tenant.clone(), OrderStatusEvents::default()))
+        .collect();
+    if let Database::Postgres { pool, .. } = &database {
+        tokio::spawn(persistence::events::run(
+            pool.clone(),
+            order_status_events.clone(),
+        ));
+    }
+
+    // Brokers and providers are shared; each tenant gets its own state over repositories
+    // scoped to its rows, cache keys under its id, and its own background jobs. Jobs over the
+    // whole database start once, after the loop.
+    let cache = cache::connect(&config.cache).await?;
+    let event_publisher = outbox::connect(&config.event_stream).await?;
+    let changes = changes::start(&config.change_stream).await?;
+    let carrier = carriers::connect(&config.carrier)?;
+    let payment_provider = payments::connect(&config.payments);
+    let notifier = notifications::connect(&config.notifier)?;
+    let storage = storage::connect(&config.storage)?;
+    let request_timeout = Duration::from_secs(config.request_timeout_secs);
+    // Every tenant's router draws on the same permits, as they all share one pool.
+    let api_limit = ConcurrencyLimit::new(
+        config.concurrency.max_requests,
+        config.concurrency.retry_after_seconds,
+    );
+    let health_limit = ConcurrencyLimit::new(
+        config.concurrency.health_max_requests,
+        config.concurrency.retry_after_seconds,
+    );
+    // One switch for the whole server: turning read-only on refuses writes for every tenant.
+    let read_only = ReadOnlyMode::new(config.read_only, config.read_only_reason.clone());
+
+    let mut routers = HashMap::new();
+    let mut warmed = Vec::new();
+    for tenant in &config.tenancy.tenants {
+        let repositories = database.repositories(config, tenant);
+        let zip_lookup = zip_lookup::connect(&config.zip_lookup, repositories.geolocation.clone())?;
+        let app_state = AppState::new(
+            config,
+            repositories,
+            readiness.clone(),
+            read_only.clone(),
+            order_status_events[tenant].clone(),
+            cache.for_tenant(tenant),
+            event_publisher.clone(),
+            changes.for_tenant(tenant),
+            carrier.clone(),
+            zip_lookup,
+            payment_provider.clone(),
+            notifier.clone(),
+            storage.clone(),
+        );
+        tokio::spawn(badges::run(
+            app_state.seller_service.clone(),
+            config.seller_badges_refresh_minutes,
+            app_state.job_runs.clone(),
+        ));
+        let order_service = app_state.order_service.clone();
+        tokio::spawn(async move { order_service.feed_status_changes().await });
+        tokio::spawn(outbox::run(app_state.outbox_service.clone()));
+        tokio::spawn(webhooks::run(app_state.webhook_service.clone()));
+        tokio::spawn(notifications::run(app_state.notification_service.clone()));
+        scheduler::start_tenant(&config.jobs, &app_state);
+        warmed.push((tenant.clone(), app_state.clone()));
+
+        routers.insert(
+            tenant.clone(),
+            routes::create_router(
+                app_state,
+                request_timeout,
+                api_limit.clone(),
+                health_limit.clone(),
+            ),
+        );
+    }
+    scheduler::start_shared(&config.jobs, warmed.iter().map(|(_, state)| state));
+    tokio::spawn(warmup::run(
+        database,
+        config.warmup.clone(),
+        readiness,
+        warmed,
+    ));
+
+    Ok(with_server_layers(
+        tenancy::dispatch(&config.tenancy, routers),
+        config,
+    ))
+}
+
+/// Wraps the tenant dispatch in the layers every request goes through, whichever tenant
+/// serves it: body limit, panic and error rendering, request ids, access log, client IP,
+/// compression and CORS.
+fn with_server_layers(router: Router, config: &AppConfig) -> Router {
+    router
+        .layer(DefaultBodyLimit::disable())
+        .layer(RequestBodyLimitLayer::new(config.max_body_bytes))
+        .layer(CatchPanicLayer::custom(panic_response))
+        .layer(middleware::from_fn(json_error_responses))
+        .layer(PropagateRequestIdLayer::x_request_id())
+        .layer(middleware::from_fn_with_state(
+            config.access_log.clone(),
+            access_log,
+        ))
+        .layer(middleware::from_fn_with_state(
+            TrustedProxies::new(config.trusted_proxies.clone()),
+            resolve_client_ip,
+        ))
+        .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid))
+        .layer(create_compression_layer(config.compression_enabled))
+        .layer(middleware::from_fn_with_state(
+            CorsPolicies::new(&config.cors),
+            cors_by_route,
+        ))
+}
+
+pub async fn serve(config: AppConfig, database: Database) -> Result<(), AppError> {
+    let app = app(&config, database).await?;
+
+    let addr = SocketAddr::from(([0, 0, 0, 0], config.port));
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .map_err(|e| AppError::ConfigError(format!("Failed to bind TCP listener: {}", e)))?;
+
+    if let Some(tls) = &config.tls {
+        info!("Server listening on https://{}", addr);
+        return tls::serve(listener, app, tls, shutdown_signal()).await;
+    }
+
+    info!("Server listening on http://{}", addr);
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .with_graceful_shutdown(shutdown_signal())
+    .await
+    .map_err(|e| AppError::ConfigError(format!("Axum server failed: {}", e)))?;
+
+    Ok(())
+}
+
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("failed to install Ctrl+C handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("failed to install signal handler")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+
+    warn!("Signal received, starting graceful shutdown...");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::{Body, to_bytes};
+    use axum::http::{Request, StatusCode};
+    use axum::routing::get;
+    use serde_json::Value;
+    use tower::ServiceExt;
+
+    use crate::config::{ConfigSource, load_config_from};
+    use crate::error::REQUEST_ID_HEADER;
+
+    async fn panics() -> &'static str {
+        panic!("handler blew up")
+    }
+
+    #[tokio::test]
+    async fn handler_panics_become_json_errors_carrying_the_request_id() {
+        let config = load_config_from(&ConfigSource::from_values([(
+            "DATABASE_URL",
+            "postgres://localhost/unused",
+        )]))
+        .expect("valid configuration");
+        let router = Router::new().route("/panic", get(panics));
+
+        let response = with_server_layers(router, &config)
+            .oneshot(Request::get("/panic").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        let request_id = response.headers()[REQUEST_ID_HEADER]
+            .to_str()
+            .unwrap()
+            .to_string();
+        let body: Value =
+            serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await.unwrap())
+                .unwrap();
+        assert_eq!(body["error"], "Internal Server Error");
+        assert_eq!(body["request_id"], request_id.as_str());
+    }
+}
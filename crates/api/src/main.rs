@@ -0,0 +1,105 @@
+use clap::Parser;
+use dotenvy::dotenv;
+use tracing_subscriber::EnvFilter;
+
+use api::cache;
+use api::carriers;
+use api::cli::{self, Cli, Command};
+use api::config::{AppConfig, load_config};
+use api::database::Database;
+use api::notifications;
+use api::payments;
+use api::serve;
+use api::state::AppState;
+use api::storage;
+use api::zip_lookup;
+use domain::error::AppError;
+use domain::events::{ChangeStream, OrderStatusEvents};
+use domain::runtime::{ReadOnlyMode, Readiness};
+use domain::tenancy::TenantId;
+
+#[tokio::main]
+async fn main() -> std::result::Result<(), AppError> {
+    dotenv().ok();
+    let command = Cli::parse().command.unwrap_or(Command::Serve);
+    let config = load_config()?;
+
+    let log_filter = EnvFilter::try_new(&config.log_level)
+        .map_err(|e| AppError::ConfigError(format!("Invalid LOGGING_LEVEL: {}", e)))?;
+    let subscriber = tracing_subscriber::fmt().with_env_filter(log_filter);
+    // One-off commands log to stderr so exports can be piped from stdout.
+    if matches!(command, Command::Serve) {
+        subscriber.init();
+    } else {
+        subscriber.with_writer(std::io::stderr).init();
+    }
+
+    let database = Database::connect(&config).await?;
+
+    match command {
+        Command::Serve => serve(config, database).await,
+        Command::Migrate { action } => cli::migrate(&database, action).await,
+        Command::Import {
+            dataset,
+            path,
+            tenant,
+        } => {
+            cli::import(
+                &command_state(&config, &database, &tenant).await?,
+                dataset,
+                path,
+            )
+            .await
+        }
+        Command::ImportGeolocation { path } => {
+            let state = command_state(&config, &database, &TenantId::default()).await?;
+            cli::import_geolocation(&state, path).await
+        }
+        Command::Seed { options, tenant } => {
+            cli::seed(&command_state(&config, &database, &tenant).await?, options).await
+        }
+        Command::Export {
+            entity,
+            format,
+            output,
+            tenant,
+        } => {
+            let state = command_state(&config, &database, &tenant).await?;
+            cli::export(&state, entity, format, output).await
+        }
+    }
+}
+
+/// State for one-off commands. They share the server's response cache, so the rows they
+/// write invalidate it like API writes do. They don't relay events: the outbox rows their
+/// writes record are published by the server. Nor do they feed the change stream. The
+/// tenant must be one the server is configured to serve.
+async fn command_state(
+    config: &AppConfig,
+    database: &Database,
+    tenant: &TenantId,
+) -> Result<AppState, AppError> {
+    if !config.tenancy.tenants.contains(tenant) {
+        return Err(AppError::ConfigError(format!(
+            "Tenant '{}' is not listed in TENANTS.",
+            tenant
+        )));
+    }
+    let repositories = database.repositories(config, tenant);
+    let zip_lookup = zip_lookup::connect(&config.zip_lookup, repositories.geolocation.clone())?;
+    Ok(AppState::new(
+        config,
+        repositories,
+        Readiness::default(),
+        ReadOnlyMode::new(config.read_only, config.read_only_reason.clone()),
+        OrderStatusEvents::default(),
+        cache::connect(&config.cache).await?.for_tenant(tenant),
+        None,
+        ChangeStream::default().for_tenant(tenant),
+        carriers::connect(&config.carrier)?,
+        zip_lookup,
+        payments::connect(&config.payments),
+        notifications::connect(&config.notifier)?,
+        storage::connect(&config.storage)?,
+    ))
+}
@@ -0,0 +1,106 @@
+//! Reading `multipart/form-data` request bodies (RFC 7578) into uploaded files. The body is
+//! already buffered by the request body limit, so it is split in place rather than streamed.
+
+use bytes::Bytes;
+use memchr::memmem;
+
+use domain::error::{AppError, AppResult};
+use domain::storage::Upload;
+
+/// Most headers a part may carry.
+const MAX_PART_HEADERS: usize = 16;
+
+/// The file parts of a `multipart/form-data` body, in the order sent. Parts without a
+/// `filename` are form fields and are skipped. A file part without a `Content-Type` is taken
+/// as `application/octet-stream`.
+pub fn parse_uploads(content_type: Option<&str>, body: &Bytes) -> AppResult<Vec<Upload>> {
+    let boundary = content_type
+        .and_then(boundary)
+        .ok_or_else(|| invalid("Send the files as multipart/form-data with a boundary"))?;
+    let delimiter = format!("--{}", boundary);
+    let next_part = format!("\r\n--{}", boundary);
+    let next_part = memmem::Finder::new(next_part.as_bytes());
+
+    let Some(start) = memmem::find(body, delimiter.as_bytes()) else {
+        return Err(invalid("The body has no parts"));
+    };
+    let mut position = start + delimiter.len();
+    let mut uploads = Vec::new();
+    loop {
+        let rest = &body[position..];
+        if rest.starts_with(b"--") {
+            return Ok(uploads);
+        }
+        let Some(rest) = rest.strip_prefix(b"\r\n") else {
+            return Err(invalid("A part boundary is not followed by a line break"));
+        };
+        position += 2;
+
+        let mut headers = [httparse::EMPTY_HEADER; MAX_PART_HEADERS];
+        let (header_len, headers) = match httparse::parse_headers(rest, &mut headers) {
+            Ok(httparse::Status::Complete((len, headers))) => (len, headers),
+            Ok(httparse::Status::Partial) => return Err(invalid("A part's headers are cut off")),
+            Err(e) => return Err(invalid(&format!("A part has invalid headers: {}", e))),
+        };
+        let content_start = position + header_len;
+        let Some(content_len) = next_part.find(&body[content_start..]) else {
+            return Err(invalid("The body is missing its closing boundary"));
+        };
+        let content_end = content_start + content_len;
+
+        let header = |name: &str| {
+            headers
+                .iter()
+                .find(|header| header.name.eq_ignore_ascii_case(name))
+                .and_then(|header| std::str::from_utf8(header.value).ok())
+        };
+        if let Some(file_name) = header("content-disposition").and_then(file_name) {
+            uploads.push(Upload {
+                file_name: Some(file_name).filter(|name| !name.is_empty()),
+                content_type: header("content-type")
+                    .map(|value| value.split(';').next().unwrap_or_default().trim())
+                    .filter(|value| !value.is_empty())
+                    .unwrap_or("application/octet-stream")
+                    .to_lowercase(),
+                body: body.slice(content_start..content_end),
+            });
+        }
+        position = content_end + 2 + delimiter.len();
+    }
+}
+
+/// The `boundary` parameter of a `multipart/form-data` content type.
+fn boundary(content_type: &str) -> Option<&str> {
+    let mut params = content_type.split(';');
+    if !params
+        .next()?
+        .trim()
+        .eq_ignore_ascii_case("multipart/form-data")
+    {
+        return None;
+    }
+    params
+        .filter_map(|param| param.split_once('='))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("boundary"))
+        .map(|(_, value)| value.trim().trim_matches('"'))
+        .filter(|value| !value.is_empty())
+}
+
+/// The `filename` parameter of a part's `Content-Disposition`, set only on file parts.
+fn file_name(disposition: &str) -> Option<String> {
+    disposition
+        .split(';')
+        .skip(1)
+        .filter_map(|param| param.split_once('='))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("filename"))
+        .map(|(_, value)| value.trim().trim_matches('"').to_string())
+}
+
+fn invalid(message: &str) -> AppError {
+    let mut errors = validator::ValidationErrors::new();
+    errors.add(
+        "body",
+        validator::ValidationError::new("multipart").with_message(message.to_string().into()),
+    );
+    AppError::ValidationError(errors)
+}
@@ -0,0 +1,128 @@
+use async_trait::async_trait;
+use lettre::message::Mailbox;
+use lettre::message::header::ContentType;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info};
+
+use domain::error::AppError;
+use domain::models::NotificationMessage;
+use domain::notifications::{LogNotifier, Notifier};
+use domain::services::NotificationService;
+
+use crate::config::{NotifierBackend, NotifierConfig, SmtpConfig, SmtpSecurity};
+
+/// Builds the notifier named by `NOTIFICATION_PROVIDER`.
+pub fn connect(config: &NotifierConfig) -> Result<Arc<dyn Notifier>, AppError> {
+    let notifier: Arc<dyn Notifier> = match config.provider {
+        NotifierBackend::Log => Arc::new(LogNotifier),
+        NotifierBackend::Smtp => Arc::new(SmtpNotifier::new(&config.smtp, &config.recipients)?),
+    };
+    info!("Sending notifications through {}.", notifier.name());
+    Ok(notifier)
+}
+
+/// Sends due notifications every `poll_interval_seconds`.
+///
+/// Does nothing when the poll interval is 0.
+pub async fn run(service: NotificationService) {
+    let config = service.config();
+    if config.poll_interval_seconds == 0 {
+        info!("Notifications are disabled.");
+        return;
+    }
+
+    let mut interval = tokio::time::interval(Duration::from_secs(config.poll_interval_seconds));
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    loop {
+        interval.tick().await;
+        if let Err(e) = service.send_due().await {
+            error!("Failed to send notifications: {:?}", e);
+        }
+    }
+}
+
+/// [`Notifier`] emailing every notification as plain text to the configured recipients.
+pub struct SmtpNotifier {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+    to: Vec<Mailbox>,
+}
+
+impl SmtpNotifier {
+    pub const NAME: &'static str = "smtp";
+
+    pub fn new(config: &SmtpConfig, recipients: &[String]) -> Result<Self, AppError> {
+        let mailbox = |address: &str, name: &str| {
+            address.parse::<Mailbox>().map_err(|e| {
+                AppError::ConfigError(format!("Invalid {} address '{}': {}", name, address, e))
+            })
+        };
+        let from = mailbox(&config.from, "SMTP_FROM")?;
+        let to = recipients
+            .iter()
+            .map(|address| mailbox(address, "NOTIFICATION_RECIPIENTS"))
+            .collect::<Result<_, _>>()?;
+
+        let builder = match config.security {
+            SmtpSecurity::StartTls => {
+                AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&config.host)
+            }
+            SmtpSecurity::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(&config.host),
+            SmtpSecurity::None => Ok(AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(
+                &config.host,
+            )),
+        }
+        .map_err(|e| AppError::ConfigError(format!("Invalid SMTP_HOST '{}': {}", config.host, e)))?
+        .port(config.port)
+        .timeout(Some(Duration::from_secs(config.timeout_seconds)));
+        let builder = if config.username.is_empty() {
+            builder
+        } else {
+            builder.credentials(Credentials::new(
+                config.username.clone(),
+                config.password.clone(),
+            ))
+        };
+
+        Ok(Self {
+            transport: builder.build(),
+            from,
+            to,
+        })
+    }
+}
+
+#[async_trait]
+impl Notifier for SmtpNotifier {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn recipient(&self) -> Option<String> {
+        let to: Vec<String> = self.to.iter().map(|mailbox| mailbox.to_string()).collect();
+        Some(to.join(", "))
+    }
+
+    async fn send(&self, message: &NotificationMessage) -> Result<(), String> {
+        let mut email = Message::builder()
+            .from(self.from.clone())
+            .subject(&message.subject)
+            .header(ContentType::TEXT_PLAIN);
+        for mailbox in &self.to {
+            email = email.to(mailbox.clone());
+        }
+        let email = email
+            .body(message.body.clone())
+            .map_err(|e| format!("Failed to build the email: {}", e))?;
+
+        self.transport
+            .send(email)
+            .await
+            .map(|_| ())
+            .map_err(|e| format!("SMTP delivery failed: {}", e))
+    }
+}
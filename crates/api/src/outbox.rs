@@ -0,0 +1,69 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::{error, info};
+
+use domain::error::AppError;
+use domain::events::EventPublisher;
+use domain::services::OutboxService;
+use persistence::outbox::RedisStreamPublisher;
+
+use crate::config::EventStreamConfig;
+
+/// How often published events past their retention are deleted.
+const PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Connects the Redis stream named by `EVENT_STREAM_REDIS_URL`. Without one, outbox events
+/// only go to webhook subscriptions.
+pub async fn connect(
+    config: &EventStreamConfig,
+) -> Result<Option<Arc<dyn EventPublisher>>, AppError> {
+    let Some(url) = &config.redis_url else {
+        return Ok(None);
+    };
+
+    info!("Connecting to Redis event stream {}...", config.key);
+    let publisher = RedisStreamPublisher::connect(url, config.key.clone(), config.max_len)
+        .await
+        .map_err(|e| {
+            AppError::ConfigError(format!("Failed to connect to the event stream: {}", e))
+        })?;
+
+    Ok(Some(Arc::new(publisher)))
+}
+
+/// Relays outbox events every `poll_interval_seconds`, draining the backlog in full batches,
+/// and deletes published events past their retention once an hour.
+///
+/// Does nothing when the poll interval is 0.
+pub async fn run(service: OutboxService) {
+    let config = service.config();
+    if config.poll_interval_seconds == 0 {
+        info!("Outbox relay is disabled.");
+        return;
+    }
+
+    let mut interval = tokio::time::interval(Duration::from_secs(config.poll_interval_seconds));
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    let mut last_purge: Option<Instant> = None;
+
+    loop {
+        interval.tick().await;
+        loop {
+            match service.relay().await {
+                Ok(published) if published as i64 >= config.batch_size => continue,
+                Ok(_) => break,
+                Err(e) => {
+                    error!("Outbox relay failed: {:?}", e);
+                    break;
+                }
+            }
+        }
+
+        if last_purge.is_none_or(|at| at.elapsed() >= PURGE_INTERVAL) {
+            last_purge = Some(Instant::now());
+            if let Err(e) = service.purge_published().await {
+                error!("Failed to delete published outbox events: {:?}", e);
+            }
+        }
+    }
+}
@@ -0,0 +1,173 @@
+use async_trait::async_trait;
+use bigdecimal::{BigDecimal, Zero};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tracing::info;
+
+use domain::error::{AppError, AppResult};
+use domain::models::{
+    PaymentAuthorization, PaymentNotification, PaymentRequest, PaymentStatus, PaymentType,
+};
+use domain::payments::PaymentProvider;
+
+use crate::config::{PaymentBackend, PaymentConfig};
+
+/// Builds the payment provider named by `PAYMENT_PROVIDER`.
+pub fn connect(config: &PaymentConfig) -> Arc<dyn PaymentProvider> {
+    let provider: Arc<dyn PaymentProvider> = match config.provider {
+        PaymentBackend::Sandbox => {
+            Arc::new(SandboxPaymentProvider::new(config.webhook_secret.clone()))
+        }
+    };
+    info!("Taking payments with the {} provider.", provider.name());
+    provider
+}
+
+/// [`PaymentProvider`] without an external service, for development and tests. Payments of
+/// type `not_defined` are declined and all others authorized; payments are only kept in
+/// memory, so they can no longer be captured or refunded after a restart.
+///
+/// Notifications are JSON bodies such as `{"reference": "sbx_…", "status": "captured"}`,
+/// signed in `X-Payment-Signature` as `sha256=` followed by [`SandboxPaymentProvider::sign`].
+pub struct SandboxPaymentProvider {
+    webhook_secret: String,
+    payments: Mutex<HashMap<String, SandboxPayment>>,
+}
+
+struct SandboxPayment {
+    amount: BigDecimal,
+    captured: BigDecimal,
+    refunded: BigDecimal,
+}
+
+impl SandboxPaymentProvider {
+    pub const NAME: &'static str = "sandbox";
+    pub const SIGNATURE_HEADER: &'static str = "X-Payment-Signature";
+
+    pub fn new(webhook_secret: String) -> Self {
+        Self {
+            webhook_secret,
+            payments: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Hex HMAC-SHA256 of a notification body, keyed with the webhook secret.
+    pub fn sign(secret: &str, body: &[u8]) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+            .expect("HMAC accepts keys of any size");
+        mac.update(body);
+        hex::encode(mac.finalize().into_bytes())
+    }
+
+    fn payment<T>(
+        &self,
+        reference: &str,
+        update: impl FnOnce(&mut SandboxPayment) -> AppResult<T>,
+    ) -> AppResult<T> {
+        let mut payments = self.payments.lock().expect("sandbox payments poisoned");
+        let payment = payments.get_mut(reference).ok_or_else(|| {
+            AppError::PaymentError(format!("Unknown sandbox payment {}", reference))
+        })?;
+        update(payment)
+    }
+}
+
+#[async_trait]
+impl PaymentProvider for SandboxPaymentProvider {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    async fn authorize(&self, request: &PaymentRequest) -> AppResult<PaymentAuthorization> {
+        let reference = format!("sbx_{}", uuid::Uuid::new_v4().simple());
+        if request.payment_type == PaymentType::NotDefined {
+            return Ok(PaymentAuthorization {
+                reference,
+                status: PaymentStatus::Failed,
+            });
+        }
+
+        self.payments
+            .lock()
+            .expect("sandbox payments poisoned")
+            .insert(
+                reference.clone(),
+                SandboxPayment {
+                    amount: request.amount.clone(),
+                    captured: BigDecimal::zero(),
+                    refunded: BigDecimal::zero(),
+                },
+            );
+        Ok(PaymentAuthorization {
+            reference,
+            status: PaymentStatus::Authorized,
+        })
+    }
+
+    async fn capture(&self, reference: &str, amount: &BigDecimal) -> AppResult<PaymentStatus> {
+        self.payment(reference, |payment| {
+            if !payment.captured.is_zero() {
+                return Err(AppError::PaymentError(format!(
+                    "Sandbox payment {} is already captured",
+                    reference
+                )));
+            }
+            if *amount > payment.amount {
+                return Err(AppError::PaymentError(format!(
+                    "Cannot capture {} of a {} authorization",
+                    amount, payment.amount
+                )));
+            }
+            payment.captured = amount.clone();
+            Ok(PaymentStatus::Captured)
+        })
+    }
+
+    async fn refund(&self, reference: &str, amount: &BigDecimal) -> AppResult<PaymentStatus> {
+        self.payment(reference, |payment| {
+            if &payment.refunded + amount > payment.captured {
+                return Err(AppError::PaymentError(format!(
+                    "Cannot refund {} of sandbox payment {}: {} captured, {} refunded",
+                    amount, reference, payment.captured, payment.refunded
+                )));
+            }
+            payment.refunded += amount;
+            Ok(PaymentStatus::Refunded)
+        })
+    }
+
+    fn signature_header(&self) -> &'static str {
+        Self::SIGNATURE_HEADER
+    }
+
+    fn parse_notification(
+        &self,
+        signature: Option<&str>,
+        body: &[u8],
+    ) -> AppResult<PaymentNotification> {
+        if self.webhook_secret.is_empty() {
+            return Err(AppError::FeatureDisabled("Payment notifications"));
+        }
+        let signature = signature
+            .and_then(|value| value.trim().strip_prefix("sha256="))
+            .and_then(|value| hex::decode(value).ok())
+            .ok_or(AppError::InvalidSignature)?;
+        let mut mac = Hmac::<Sha256>::new_from_slice(self.webhook_secret.as_bytes())
+            .expect("HMAC accepts keys of any size");
+        mac.update(body);
+        mac.verify_slice(&signature)
+            .map_err(|_| AppError::InvalidSignature)?;
+
+        serde_json::from_slice(body).map_err(|e| {
+            let mut errors = validator::ValidationErrors::new();
+            errors.add(
+                "body",
+                validator::ValidationError::new("notification")
+                    .with_message(format!("Unreadable notification: {}", e).into()),
+            );
+            AppError::ValidationError(errors)
+        })
+    }
+}
@@ -0,0 +1,320 @@
+use crate::access_log::expose_matched_path;
+use crate::concurrency::ConcurrencyLimit;
+use crate::handlers::*;
+use crate::state::AppState;
+use crate::versioning::{ApiVersion, deprecated_alias, negotiate_version, tag_version};
+use axum::{
+    Extension, Router,
+    http::StatusCode,
+    middleware,
+    routing::{delete, get, post, put},
+};
+use std::time::Duration;
+use tower::Layer;
+use tower_http::timeout::TimeoutLayer;
+
+/// Health probes stay at the root; everything else is served under `/api/v{n}` for every
+/// [`ApiVersion`], and at its pre-versioning path as a deprecated alias of v1.
+///
+/// The health probes and the API have separate concurrency limits, so probes keep answering
+/// while the API sheds load. The limits are passed in so every tenant's router can share them.
+pub fn create_router(
+    state: AppState,
+    request_timeout: Duration,
+    api_limit: ConcurrencyLimit,
+    health_limit: ConcurrencyLimit,
+) -> Router {
+    let mut router = health_limit.apply(
+        Router::new()
+            .route("/health/live", get(liveness_handler))
+            .route("/health/ready", get(readiness_handler)),
+    );
+    for version in ApiVersion::ALL {
+        router = router.nest(
+            &version.prefix(),
+            api_limit.apply(api_routes(&state, version, request_timeout)),
+        );
+    }
+    let legacy = api_limit
+        .apply(api_routes(&state, ApiVersion::V1, request_timeout))
+        .route_layer(middleware::from_fn(deprecated_alias));
+    let router = router
+        .merge(legacy)
+        .route_layer(middleware::from_fn(expose_matched_path))
+        .fallback(route_not_found_handler)
+        .method_not_allowed_fallback(method_not_allowed_handler)
+        .with_state(state);
+
+    // Negotiation rewrites the path, so it has to run before the router matches it
+    Router::new().fallback_service(middleware::from_fn(negotiate_version).layer(router))
+}
+
+/// The routes of one API version, relative to its prefix.
+fn api_routes(
+    state: &AppState,
+    version: ApiVersion,
+    request_timeout: Duration,
+) -> Router<AppState> {
+    Router::new()
+        // Customers
+        .route(
+            "/customers",
+            post(create_customer_handler).get(get_customers_handler),
+        )
+        .route("/customers/export", get(export_customers_handler))
+        .route("/customers/states", get(get_customer_states_handler))
+        .route("/customers/cities", get(get_customer_cities_handler))
+        .route("/customers/merge", post(merge_customers_handler))
+        .route(
+            "/customers/unique/{unique_id}",
+            get(get_unique_customer_handler),
+        )
+        .route(
+            "/customers/{id}",
+            get(get_customer_by_id_handler)
+                .put(update_customer_handler)
+                .delete(delete_customer_handler),
+        )
+        .route("/customers/{id}/restore", post(restore_customer_handler))
+        .route(
+            "/customers/{id}/anonymize",
+            post(anonymize_customer_handler),
+        )
+        .route("/customers/{id}/export", get(export_customer_handler))
+        .route("/customers/{id}/orders", get(get_customer_orders_handler))
+        .route("/customers/{id}/history", get(get_customer_history_handler))
+        .route(
+            "/customers/{id}/addresses",
+            post(create_customer_address_handler).get(get_customer_addresses_handler),
+        )
+        .route(
+            "/customers/{id}/addresses/{address_id}",
+            get(get_customer_address_handler)
+                .put(update_customer_address_handler)
+                .delete(delete_customer_address_handler),
+        )
+        .route(
+            "/customers/{id}/nearby-sellers",
+            get(get_nearby_sellers_handler),
+        )
+        .route(
+            "/customers/{id}/recommendations",
+            get(get_customer_recommendations_handler),
+        )
+        // Sellers
+        .route(
+            "/sellers",
+            post(create_seller_handler).get(get_sellers_handler),
+        )
+        .route("/sellers/badges", get(get_seller_badge_thresholds_handler))
+        .route("/sellers/{id}", get(get_seller_by_id_handler))
+        .route("/sellers/{id}/scorecard", get(get_seller_scorecard_handler))
+        .route(
+            "/sellers/{id}/locations",
+            post(create_stock_location_handler).get(get_stock_locations_handler),
+        )
+        // Inventory
+        .route("/locations/{id}/stock", get(get_location_stock_handler))
+        .route(
+            "/locations/{id}/stock/{product_id}",
+            put(set_location_stock_handler),
+        )
+        .route(
+            "/locations/{id}/stock/{product_id}/adjustments",
+            post(adjust_location_stock_handler),
+        )
+        // Orders
+        .route(
+            "/orders",
+            post(create_order_handler).get(get_orders_handler),
+        )
+        .route("/orders/sample", get(sample_orders_handler))
+        .route("/orders/export", get(export_orders_handler))
+        .route("/orders/late", get(get_late_orders_handler))
+        .route("/orders/statuses", get(get_order_statuses_handler))
+        .route("/orders/{id}", get(get_order_by_id_handler))
+        .route("/orders/{id}/items", post(add_item_to_order_by_id_handler))
+        .route(
+            "/orders/{id}/amendments",
+            post(amend_order_handler).get(get_order_amendments_handler),
+        )
+        .route("/orders/{id}/apply-coupon", post(apply_coupon_handler))
+        .route(
+            "/orders/{id}/freight-quote",
+            post(quote_order_freight_handler),
+        )
+        .route("/freight/estimate", post(estimate_freight_handler))
+        .route("/cep/{code}", get(get_cep_handler))
+        .route(
+            "/orders/{id}/products",
+            get(get_products_by_order_id_handler),
+        )
+        .route(
+            "/orders/{id}/payments",
+            get(get_payments_by_order_id_handler),
+        )
+        .route("/orders/{id}/summary", get(get_order_summary_handler))
+        .route(
+            "/orders/{id}/refunds",
+            post(create_refund_handler).get(get_refunds_handler),
+        )
+        .route("/orders/{id}/reviews", get(get_reviews_by_order_id_handler))
+        // Reviews
+        .route("/reviews", get(get_reviews_handler))
+        // Products
+        .route(
+            "/products",
+            post(create_product_handler).get(get_products_handler),
+        )
+        .route("/products/export", get(export_products_handler))
+        .route("/products/categories", get(get_product_categories_handler))
+        .route(
+            "/products/{id}",
+            get(get_product_by_id_handler).put(update_product_handler),
+        )
+        .route(
+            "/products/{id}/images",
+            post(upload_product_images_handler).get(get_product_images_handler),
+        )
+        .route(
+            "/products/{id}/images/{image_id}",
+            delete(delete_product_image_handler),
+        )
+        .route("/products/{id}/similar", get(get_similar_products_handler))
+        .route("/products/{id}/stock", get(get_product_stock_handler))
+        .route(
+            "/products/embeddings/refresh",
+            post(refresh_product_embeddings_handler),
+        )
+        // Payments
+        .route(
+            "/orders/{id}/payment-transactions",
+            post(authorize_payment_handler).get(get_payment_transactions_handler),
+        )
+        .route(
+            "/payment-transactions/{id}/capture",
+            post(capture_payment_handler),
+        )
+        .route("/payments/webhook", post(payment_webhook_handler))
+        // Coupons
+        .route("/coupons", post(create_coupon_handler))
+        .route("/coupons/{code}", get(get_coupon_handler))
+        // Categories
+        .route("/categories", post(create_category_handler))
+        .route(
+            "/categories/{name}",
+            get(get_category_handler)
+                .put(update_category_handler)
+                .delete(delete_category_handler),
+        )
+        // Support
+        .route(
+            "/support/cases",
+            post(create_support_case_handler).get(get_support_cases_handler),
+        )
+        .route(
+            "/support/cases/{id}",
+            get(get_support_case_handler)
+                .put(update_support_case_handler)
+                .delete(delete_support_case_handler),
+        )
+        .route(
+            "/support/cases/{id}/messages",
+            post(add_support_message_handler),
+        )
+        // Webhooks
+        .route(
+            "/webhooks",
+            post(create_webhook_handler).get(get_webhooks_handler),
+        )
+        .route(
+            "/webhooks/{id}",
+            get(get_webhook_handler).delete(delete_webhook_handler),
+        )
+        .route(
+            "/webhooks/{id}/deliveries",
+            get(get_webhook_deliveries_handler),
+        )
+        .route(
+            "/webhooks/{id}/deliveries/{delivery_id}",
+            get(get_webhook_delivery_handler),
+        )
+        .route("/notifications", get(get_notifications_handler))
+        .route("/notifications/{id}", get(get_notification_handler))
+        // Analytics
+        .route("/analytics/support", get(get_support_analytics_handler))
+        .route("/analytics/reconciliation", get(get_reconciliation_handler))
+        .route("/analytics/payments", get(get_payment_analytics_handler))
+        .route(
+            "/analytics/duplicate-customers",
+            get(get_duplicate_customers_handler),
+        )
+        .route("/stats/today", get(get_today_stats_handler))
+        // NLP corpus
+        .route("/export/reviews/corpus", get(review_corpus_handler))
+        // Diagnostics
+        .route("/admin/diagnostics", get(diagnostics_handler))
+        .route("/admin/stats", get(get_admin_stats_handler))
+        .route("/admin/data-quality", get(get_data_quality_rules_handler))
+        .route(
+            "/admin/data-quality/products",
+            get(get_product_quality_handler),
+        )
+        .route(
+            "/admin/data-quality/products/flagged",
+            get(get_flagged_products_handler),
+        )
+        .route("/admin/data-quality/orders", get(get_order_quality_handler))
+        .route(
+            "/admin/data-quality/orders/flagged",
+            get(get_flagged_orders_handler),
+        )
+        .route(
+            "/admin/read-only",
+            get(get_read_only_handler).put(set_read_only_handler),
+        )
+        // Maintenance
+        .route("/admin/maintenance/refresh-all", post(refresh_all_handler))
+        .route("/admin/cache/flush", post(flush_caches_handler))
+        .route("/admin/archive", post(archive_orders_handler))
+        .route(
+            "/admin/maintenance/jobs/{id}",
+            get(get_maintenance_job_handler),
+        )
+        // Scheduled jobs
+        .route("/admin/jobs", get(get_jobs_handler))
+        .route("/admin/retention", get(retention_dry_run_handler))
+        // Import batches
+        .route("/admin/imports", get(get_import_batches_handler))
+        .route("/admin/imports/{id}", get(get_import_batch_handler))
+        .route("/load-data/jobs/{id}", get(get_load_job_handler))
+        .route(
+            "/load-data/jobs/{id}/errors",
+            get(get_load_job_errors_handler),
+        )
+        // Audit
+        .route("/audit", get(get_audit_entries_handler))
+        .layer(TimeoutLayer::with_status_code(
+            StatusCode::REQUEST_TIMEOUT,
+            request_timeout,
+        ))
+        // Long-poll: holds the request for up to MAX_STATUS_WAIT_SECS, so it sits outside the timeout
+        .route("/orders/{id}/status", get(wait_for_order_status_handler))
+        // Server-sent events: the stream stays open until the client leaves
+        .route("/orders/stream", get(stream_orders_handler))
+        // WebSocket progress feed: open for as long as the load job runs
+        .route("/load-data/jobs/{id}/ws", get(load_job_ws_handler))
+        // Data Loading (registered after the timeout layer: a full import or seed, or undoing one, runs for minutes)
+        .route("/load-data", post(load_data_from_csv_handler))
+        .route("/admin/seed", post(seed_data_handler))
+        .route(
+            "/admin/imports/{id}/rollback",
+            post(rollback_import_batch_handler),
+        )
+        .layer(middleware::from_fn_with_state(
+            state.read_only.clone(),
+            reject_writes_when_read_only,
+        ))
+        .route_layer(middleware::map_response_with_state(version, tag_version))
+        .route_layer(Extension(version))
+}
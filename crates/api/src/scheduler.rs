@@ -0,0 +1,186 @@
+use chrono::Utc;
+use cron::Schedule;
+use std::future::Future;
+use tracing::{error, info};
+
+use domain::error::AppResult;
+use domain::models::RetentionRule;
+use domain::runtime::{
+    ANALYTICS_VIEWS_JOB, DATA_QUALITY_JOB, FAILED_WEBHOOKS_JOB, JobRuns, LATE_ORDERS_JOB,
+    RETENTION_JOB, SENTIMENT_JOB,
+};
+
+use crate::config::JobsConfig;
+use crate::state::AppState;
+
+/// Starts the jobs that work on the whole database rather than one tenant's rows, once for
+/// the server, through the first tenant's services. Each run is recorded in the `job_runs`
+/// of every tenant, so each tenant's `GET /admin/jobs` lists it.
+pub fn start_shared<'a>(config: &JobsConfig, states: impl IntoIterator<Item = &'a AppState>) {
+    let states: Vec<&AppState> = states.into_iter().collect();
+    let Some(first) = states.first() else {
+        return;
+    };
+    let job_runs = states.iter().map(|state| state.job_runs.clone()).collect();
+
+    let maintenance = first.maintenance_service.clone();
+    spawn(
+        ANALYTICS_VIEWS_JOB,
+        config.refresh_analytics_views.clone(),
+        job_runs,
+        move || {
+            let maintenance = maintenance.clone();
+            async move {
+                let refreshed = maintenance.refresh_materialized_views().await?;
+                Ok(format!("refreshed {} materialized view(s)", refreshed))
+            }
+        },
+    );
+}
+
+/// Starts every job over one tenant's rows that has a schedule in `JOBS_*`, each on its own
+/// task so a slow one doesn't hold the others back. Disabled jobs are still listed in
+/// `job_runs`.
+pub fn start_tenant(config: &JobsConfig, state: &AppState) {
+    let webhooks = state.webhook_service.clone();
+    let max_age_hours = config.failed_webhooks_max_age_hours;
+    spawn(
+        FAILED_WEBHOOKS_JOB,
+        config.retry_failed_webhooks.clone(),
+        vec![state.job_runs.clone()],
+        move || {
+            let webhooks = webhooks.clone();
+            async move {
+                let requeued = webhooks.requeue_failed(max_age_hours).await?;
+                Ok(format!("requeued {} failed deliveries", requeued))
+            }
+        },
+    );
+
+    let retention = state.retention_service.clone();
+    spawn(
+        RETENTION_JOB,
+        config.apply_retention.clone(),
+        vec![state.job_runs.clone()],
+        move || {
+            let retention = retention.clone();
+            async move {
+                let report = retention.apply().await?;
+                let affected = |rule| {
+                    report
+                        .rules
+                        .iter()
+                        .find(|report| report.rule == rule)
+                        .map_or(0, |report| report.affected)
+                };
+                Ok(format!(
+                    "deleted {} review(s), anonymized {} customer(s)",
+                    affected(RetentionRule::DeleteOldReviews),
+                    affected(RetentionRule::AnonymizeInactiveCustomers)
+                ))
+            }
+        },
+    );
+
+    let sentiment = state.review_sentiment_service.clone();
+    spawn(
+        SENTIMENT_JOB,
+        config.tag_review_sentiment.clone(),
+        vec![state.job_runs.clone()],
+        move || {
+            let sentiment = sentiment.clone();
+            async move {
+                let tagged = sentiment.tag_untagged().await?;
+                Ok(format!("tagged {} review(s)", tagged))
+            }
+        },
+    );
+
+    let late_orders = state.late_order_service.clone();
+    let max_age_days = config.late_orders_max_age_days;
+    spawn(
+        LATE_ORDERS_JOB,
+        config.flag_late_orders.clone(),
+        vec![state.job_runs.clone()],
+        move || {
+            let late_orders = late_orders.clone();
+            async move {
+                let flagged = late_orders.flag_late_orders(max_age_days).await?;
+                Ok(format!("flagged {} late order(s)", flagged))
+            }
+        },
+    );
+
+    let data_quality = state.data_quality_service.clone();
+    spawn(
+        DATA_QUALITY_JOB,
+        config.evaluate_data_quality.clone(),
+        vec![state.job_runs.clone()],
+        move || {
+            let data_quality = data_quality.clone();
+            async move {
+                let results = data_quality.evaluate_rules().await?;
+                let broken = results
+                    .iter()
+                    .filter(|result| result.violation_count.is_some_and(|count| count > 0))
+                    .count();
+                let failed = results
+                    .iter()
+                    .filter(|result| result.error.is_some())
+                    .count();
+                Ok(format!(
+                    "evaluated {} rule(s): {} broken, {} failed",
+                    results.len(),
+                    broken,
+                    failed
+                ))
+            }
+        },
+    );
+}
+
+/// Runs `job` at every upcoming time of `schedule`, recording each run in every one of
+/// `job_runs`. A run never overlaps the previous one; times missed while it was running are
+/// skipped.
+fn spawn<F, Fut>(job: &'static str, schedule: Option<Schedule>, job_runs: Vec<JobRuns>, run: F)
+where
+    F: Fn() -> Fut + Send + 'static,
+    Fut: Future<Output = AppResult<String>> + Send,
+{
+    for runs in &job_runs {
+        runs.register(job, schedule.as_ref().map(|schedule| schedule.to_string()));
+    }
+    let Some(schedule) = schedule else {
+        info!("Scheduled job {} is disabled.", job);
+        return;
+    };
+
+    tokio::spawn(async move {
+        while let Some(next) = schedule.upcoming(Utc).next() {
+            for runs in &job_runs {
+                runs.schedule_next(job, next);
+            }
+            let wait = (next - Utc::now()).to_std().unwrap_or_default();
+            tokio::time::sleep(wait).await;
+
+            for runs in &job_runs {
+                runs.record_start(job);
+            }
+            match run().await {
+                Ok(detail) => {
+                    info!("Scheduled job {} finished: {}.", job, detail);
+                    for runs in &job_runs {
+                        runs.record_success(job, detail.clone());
+                    }
+                }
+                Err(e) => {
+                    error!("Scheduled job {} failed: {:?}", job, e);
+                    let detail = format!("{:?}", e);
+                    for runs in &job_runs {
+                        runs.record_failure(job, detail.clone());
+                    }
+                }
+            }
+        }
+    });
+}
@@ -0,0 +1,313 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use analytics::services::{
+    CustomerDuplicationService, PaymentAnalyticsService, ReconciliationService,
+    ReviewCorpusService, StatsService,
+};
+use domain::cache::{LookupCache, ResponseCache};
+use domain::carriers::CarrierProvider;
+#[cfg(feature = "test-utils")]
+use domain::carriers::MockCarrier;
+use domain::embeddings::HashingEmbedder;
+use domain::events::{ChangeStream, EventPublisher, OrderStatusEvents};
+#[cfg(feature = "test-utils")]
+use domain::notifications::LogNotifier;
+use domain::notifications::Notifier;
+use domain::payments::PaymentProvider;
+use domain::runtime::{JobRuns, ReadOnlyMode, Readiness};
+use domain::sentiment::LexiconSentimentProvider;
+use domain::services::{
+    AuditService, CategoryService, CouponService, CustomerService, DataQualityService,
+    DiagnosticsService, FreightService, InventoryService, LateOrderService, MaintenanceService,
+    NearbySellerService, NotificationService, OrderService, OutboxService, PaymentService,
+    ProductImageService, ProductService, RetentionService, ReviewSentimentService, SellerService,
+    ShippingService, SimilarityService, SupportService, WebhookService, ZipLookupService,
+};
+#[cfg(feature = "test-utils")]
+use domain::storage::MemoryStorage;
+use domain::storage::ObjectStorage;
+#[cfg(feature = "test-utils")]
+use domain::tenancy::TenantId;
+#[cfg(feature = "test-utils")]
+use domain::zip_lookup::GeolocationZipLookup;
+use domain::zip_lookup::ZipLookup;
+use importer::import::ImportTargets;
+use importer::jobs::LoadJobs;
+use importer::services::ImportService;
+use persistence::cache::MokaLookupStores;
+#[cfg(feature = "test-utils")]
+use persistence::memory::MemoryStore;
+
+use crate::config::AppConfig;
+#[cfg(feature = "test-utils")]
+use crate::database::Database;
+use crate::database::Repositories;
+use crate::id_codec::IdCodec;
+#[cfg(feature = "test-utils")]
+use crate::payments;
+
+#[derive(Clone)]
+pub struct AppState {
+    pub customer_service: CustomerService,
+    pub seller_service: SellerService,
+    pub order_service: OrderService,
+    pub shipping_service: ShippingService,
+    pub freight_service: FreightService,
+    pub zip_lookup_service: ZipLookupService,
+    pub coupon_service: CouponService,
+    pub payment_service: PaymentService,
+    pub nearby_seller_service: NearbySellerService,
+    pub inventory_service: InventoryService,
+    pub product_service: ProductService,
+    pub product_image_service: ProductImageService,
+    pub category_service: CategoryService,
+    pub audit_service: AuditService,
+    pub similarity_service: SimilarityService,
+    pub support_service: SupportService,
+    pub maintenance_service: MaintenanceService,
+    pub diagnostics_service: DiagnosticsService,
+    pub data_quality_service: DataQualityService,
+    pub review_corpus_service: ReviewCorpusService,
+    pub import_service: ImportService,
+    pub load_jobs: LoadJobs,
+    pub stats_service: StatsService,
+    pub reconciliation_service: ReconciliationService,
+    pub payment_analytics_service: PaymentAnalyticsService,
+    pub customer_duplication_service: CustomerDuplicationService,
+    pub webhook_service: WebhookService,
+    pub outbox_service: OutboxService,
+    pub notification_service: NotificationService,
+    pub retention_service: RetentionService,
+    pub review_sentiment_service: ReviewSentimentService,
+    pub late_order_service: LateOrderService,
+    pub id_codec: IdCodec,
+    pub readiness: Readiness,
+    pub job_runs: JobRuns,
+    pub read_only: ReadOnlyMode,
+}
+
+impl AppState {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        config: &AppConfig,
+        repositories: Repositories,
+        readiness: Readiness,
+        read_only: ReadOnlyMode,
+        order_status_events: OrderStatusEvents,
+        cache: ResponseCache,
+        event_publisher: Option<Arc<dyn EventPublisher>>,
+        changes: ChangeStream,
+        carrier: Arc<dyn CarrierProvider>,
+        zip_lookup: Arc<dyn ZipLookup>,
+        payment_provider: Arc<dyn PaymentProvider>,
+        notifier: Arc<dyn Notifier>,
+        storage: Arc<dyn ObjectStorage>,
+    ) -> Self {
+        let job_runs = JobRuns::default();
+        let audit_service = AuditService::new(repositories.audit, changes);
+        let inventory_service = InventoryService::new(
+            repositories.inventory,
+            repositories.products.clone(),
+            audit_service.clone(),
+        );
+        let similarity_service = SimilarityService::new(
+            repositories.embeddings,
+            Arc::new(HashingEmbedder),
+            config.similarity_enabled,
+        );
+
+        let lookups = LookupCache::new(
+            &MokaLookupStores,
+            config.cache.lookup_max_entries,
+            Duration::from_secs(config.cache.lookup_ttl_seconds),
+        );
+        let nearby_seller_service = NearbySellerService::new(
+            repositories.customers.clone(),
+            repositories.sellers.clone(),
+            repositories.geolocation.clone(),
+            lookups.clone(),
+        );
+        let seller_service = SellerService::new(
+            repositories.sellers,
+            audit_service.clone(),
+            config.seller_scorecard,
+        );
+
+        let payment_service = PaymentService::new(
+            payment_provider,
+            repositories.payment_transactions,
+            repositories.orders.clone(),
+            audit_service.clone(),
+        );
+
+        let customer_duplication_service =
+            CustomerDuplicationService::new(repositories.customers.clone());
+        let customer_service = CustomerService::new(
+            repositories.customers,
+            audit_service.clone(),
+            config.delete_policies,
+        );
+        let retention_service = RetentionService::new(
+            repositories.retention,
+            customer_service.clone(),
+            audit_service.clone(),
+            config.retention,
+        );
+
+        Self {
+            customer_service,
+            seller_service,
+            order_service: OrderService::new(
+                repositories.orders.clone(),
+                repositories.coupons.clone(),
+                audit_service.clone(),
+                inventory_service.clone(),
+                config.amendments.clone(),
+                order_status_events,
+                cache.clone(),
+            ),
+            shipping_service: ShippingService::new(
+                carrier,
+                repositories.orders.clone(),
+                repositories.products.clone(),
+                audit_service.clone(),
+                config.amendments.clone(),
+                cache.clone(),
+            ),
+            freight_service: FreightService::new(
+                repositories.geolocation,
+                repositories.products.clone(),
+                lookups.clone(),
+                config.freight.clone(),
+            ),
+            zip_lookup_service: ZipLookupService::new(zip_lookup, lookups.clone()),
+            nearby_seller_service,
+            coupon_service: CouponService::new(repositories.coupons, audit_service.clone()),
+            payment_service,
+            inventory_service,
+            product_image_service: ProductImageService::new(
+                storage,
+                repositories.products.clone(),
+                audit_service.clone(),
+                cache.clone(),
+                lookups.clone(),
+                config.product_images,
+            ),
+            product_service: ProductService::new(
+                repositories.products,
+                audit_service.clone(),
+                cache.clone(),
+                lookups.clone(),
+            ),
+            category_service: CategoryService::new(
+                repositories.categories,
+                audit_service.clone(),
+                lookups.clone(),
+            ),
+            support_service: SupportService::new(
+                repositories.support,
+                audit_service.clone(),
+                config.support,
+                cache.clone(),
+            ),
+            maintenance_service: MaintenanceService::new(
+                repositories.maintenance,
+                similarity_service.clone(),
+                audit_service.clone(),
+                cache.clone(),
+                lookups.clone(),
+                read_only.clone(),
+            ),
+            import_service: ImportService::new(
+                repositories.imports,
+                audit_service.clone(),
+                cache.clone(),
+                lookups,
+                config.import,
+            ),
+            load_jobs: LoadJobs::default(),
+            stats_service: StatsService::new(repositories.stats, cache.clone()),
+            webhook_service: WebhookService::new(
+                repositories.webhooks,
+                audit_service.clone(),
+                config.webhooks,
+            ),
+            id_codec: IdCodec::new(&config.public_ids),
+            reconciliation_service: ReconciliationService::new(repositories.orders.clone()),
+            payment_analytics_service: PaymentAnalyticsService::new(repositories.orders.clone()),
+            customer_duplication_service,
+            review_corpus_service: ReviewCorpusService::new(repositories.orders, &config.corpus),
+            diagnostics_service: DiagnosticsService::new(
+                repositories.diagnostics,
+                repositories.outbox.clone(),
+                readiness.clone(),
+                job_runs.clone(),
+                cache,
+                config.seller_badges_refresh_minutes,
+                config.outbox.poll_interval_seconds > 0,
+            ),
+            data_quality_service: DataQualityService::new(
+                repositories.data_quality,
+                config.data_quality_rules.clone(),
+            ),
+            outbox_service: OutboxService::new(repositories.outbox, event_publisher, config.outbox),
+            notification_service: NotificationService::new(
+                repositories.notifications,
+                notifier,
+                config.notifier.templates.clone(),
+                config.notifications,
+            ),
+            retention_service,
+            review_sentiment_service: ReviewSentimentService::new(
+                repositories.review_sentiments,
+                Arc::new(LexiconSentimentProvider),
+            ),
+            late_order_service: LateOrderService::new(repositories.late_orders),
+            audit_service,
+            similarity_service,
+            readiness,
+            job_runs,
+            read_only,
+        }
+    }
+
+    /// State over fresh in-memory repositories, already marked ready and without a response
+    /// cache, event stream or change stream, and with the mock carrier, geolocation CEP lookup,
+    /// sandbox payment provider and log notifier, so handlers can be exercised without a
+    /// database.
+    #[cfg(feature = "test-utils")]
+    pub fn in_memory(config: &AppConfig) -> Self {
+        let readiness = Readiness::default();
+        readiness.mark_ready();
+        let repositories =
+            Database::Memory(MemoryStore::new()).repositories(config, &TenantId::default());
+        let zip_lookup = Arc::new(GeolocationZipLookup::new(repositories.geolocation.clone()));
+        Self::new(
+            config,
+            repositories,
+            readiness,
+            ReadOnlyMode::new(config.read_only, config.read_only_reason.clone()),
+            OrderStatusEvents::default(),
+            ResponseCache::default(),
+            None,
+            ChangeStream::default(),
+            Arc::new(MockCarrier::default()),
+            zip_lookup,
+            payments::connect(&config.payments),
+            Arc::new(LogNotifier),
+            Arc::new(MemoryStorage::default()),
+        )
+    }
+
+    /// The services a CSV import writes through.
+    pub fn import_targets(&self) -> ImportTargets {
+        ImportTargets {
+            imports: self.import_service.clone(),
+            customers: self.customer_service.clone(),
+            sellers: self.seller_service.clone(),
+            orders: self.order_service.clone(),
+            products: self.product_service.clone(),
+        }
+    }
+}
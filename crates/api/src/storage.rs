@@ -0,0 +1,201 @@
+use async_trait::async_trait;
+use bytes::Bytes;
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::info;
+
+use domain::error::{AppError, AppResult};
+use domain::storage::{MemoryStorage, ObjectStorage};
+
+use crate::config::{S3Config, StorageBackend, StorageConfig};
+
+/// Builds the storage named by `STORAGE_PROVIDER`.
+pub fn connect(config: &StorageConfig) -> Result<Arc<dyn ObjectStorage>, AppError> {
+    let storage: Arc<dyn ObjectStorage> = match config.provider {
+        StorageBackend::Memory => Arc::new(MemoryStorage::default()),
+        StorageBackend::S3 => Arc::new(S3Storage::new(
+            config.s3.clone(),
+            Duration::from_secs(config.timeout_seconds),
+        )?),
+    };
+    info!("Storing uploads with the {} storage.", storage.name());
+    Ok(storage)
+}
+
+/// [`ObjectStorage`] over the S3 REST API, with requests signed by AWS Signature Version 4.
+/// Only `PutObject` and `DeleteObject` are used, so any S3-compatible service will do.
+pub struct S3Storage {
+    client: reqwest::Client,
+    config: S3Config,
+    /// Scheme, host and port of the endpoint.
+    origin: String,
+    /// `Host` header the requests are signed with.
+    host: String,
+    /// Path of the endpoint, if it has one, before the bucket.
+    base_path: String,
+}
+
+impl S3Storage {
+    pub const NAME: &'static str = "s3";
+
+    pub fn new(config: S3Config, timeout: Duration) -> Result<Self, AppError> {
+        let endpoint = reqwest::Url::parse(&config.endpoint)
+            .map_err(|e| AppError::ConfigError(format!("Invalid S3_ENDPOINT: {}", e)))?;
+        let host = match (endpoint.host_str(), endpoint.port()) {
+            (Some(host), Some(port)) => format!("{}:{}", host, port),
+            (Some(host), None) => host.to_string(),
+            (None, _) => {
+                return Err(AppError::ConfigError(
+                    "Invalid S3_ENDPOINT: it has no host".to_string(),
+                ));
+            }
+        };
+        let origin = endpoint.origin().ascii_serialization();
+        let base_path = endpoint.path().trim_end_matches('/').to_string();
+
+        let client = reqwest::Client::builder()
+            .timeout(timeout)
+            .build()
+            .map_err(|e| {
+                AppError::ConfigError(format!("Failed to build the S3 HTTP client: {}", e))
+            })?;
+        Ok(Self {
+            client,
+            config,
+            origin,
+            host,
+            base_path,
+        })
+    }
+
+    /// Path of the object under `key`, URI-encoded as the signature expects.
+    fn object_path(&self, key: &str) -> String {
+        let key = key.split('/').map(uri_encode).collect::<Vec<_>>().join("/");
+        format!(
+            "{}/{}/{}",
+            self.base_path,
+            uri_encode(&self.config.bucket),
+            key
+        )
+    }
+
+    /// Sends a signed request for the object under `key`.
+    async fn send(
+        &self,
+        method: reqwest::Method,
+        key: &str,
+        content_type: Option<&str>,
+        body: Bytes,
+        what: &str,
+    ) -> AppResult<()> {
+        let path = self.object_path(key);
+        let now = Utc::now();
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+        let payload_hash = hex::encode(Sha256::digest(&body));
+
+        let signed_headers = "host;x-amz-content-sha256;x-amz-date";
+        let canonical_request = format!(
+            "{}\n{}\n\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\n{}\n{}",
+            method, path, self.host, payload_hash, amz_date, signed_headers, payload_hash
+        );
+        let scope = format!("{}/{}/s3/aws4_request", date, self.config.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            hex::encode(Sha256::digest(canonical_request.as_bytes()))
+        );
+
+        let signing_key = [
+            date.as_str(),
+            self.config.region.as_str(),
+            "s3",
+            "aws4_request",
+        ]
+        .iter()
+        .fold(
+            format!("AWS4{}", self.config.secret_access_key).into_bytes(),
+            |key, part| hmac_sha256(&key, part.as_bytes()),
+        );
+        let signature = hex::encode(hmac_sha256(&signing_key, string_to_sign.as_bytes()));
+        let authorization = format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            self.config.access_key_id, scope, signed_headers, signature
+        );
+
+        let mut request = self
+            .client
+            .request(method, format!("{}{}", self.origin, path))
+            .header("x-amz-date", amz_date)
+            .header("x-amz-content-sha256", payload_hash)
+            .header("authorization", authorization);
+        if let Some(content_type) = content_type {
+            request = request.header("content-type", content_type);
+        }
+
+        let response =
+            request.body(body).send().await.map_err(|e| {
+                AppError::StorageError(format!("S3 {} request failed: {}", what, e))
+            })?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(AppError::StorageError(format!(
+                "S3 {} request returned {}: {}",
+                what,
+                status,
+                body.chars().take(200).collect::<String>()
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ObjectStorage for S3Storage {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    async fn put(&self, key: &str, content_type: &str, body: Bytes) -> AppResult<String> {
+        self.send(
+            reqwest::Method::PUT,
+            key,
+            Some(content_type),
+            body,
+            "upload",
+        )
+        .await?;
+        Ok(format!("{}/{}", self.config.public_url, key))
+    }
+
+    async fn delete(&self, key: &str) -> AppResult<()> {
+        // S3 answers 204 whether or not the object existed.
+        self.send(reqwest::Method::DELETE, key, None, Bytes::new(), "delete")
+            .await?;
+        Ok(())
+    }
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC takes keys of any length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Percent-encodes everything but the characters SigV4 leaves unreserved.
+fn uri_encode(segment: &str) -> String {
+    segment
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (byte as char).to_string()
+            }
+            other => format!("%{:02X}", other),
+        })
+        .collect()
+}
@@ -0,0 +1,114 @@
+use axum::{
+    Router,
+    extract::Request,
+    http::header,
+    response::{IntoResponse, Response},
+};
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::sync::Arc;
+use tower::ServiceExt;
+
+use domain::error::AppError;
+use domain::tenancy::TenantId;
+
+use crate::config::{TenancyConfig, TenantResolver};
+use crate::error::ApiError;
+
+/// Health probes are answered for the default tenant when a request names none, since
+/// readiness is shared by every tenant and probes can't be told which one to name.
+const HEALTH_PREFIX: &str = "/health/";
+
+/// One router per tenant, each over repositories that only see that tenant's rows, and the
+/// rules for telling which one a request is for.
+#[derive(Clone)]
+struct TenantRouters {
+    config: Arc<TenancyConfig>,
+    routers: Arc<HashMap<TenantId, Router>>,
+}
+
+impl TenantRouters {
+    /// The tenant named by the header or subdomain, which must be one this deployment
+    /// serves.
+    fn resolve(&self, request: &Request) -> Result<TenantId, AppError> {
+        let named = match self.config.resolver {
+            TenantResolver::Off => return Ok(TenantId::default()),
+            TenantResolver::Header => request
+                .headers()
+                .get(&self.config.header)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string),
+            TenantResolver::Subdomain => self.subdomain(request),
+        };
+        let Some(named) = named else {
+            return Err(AppError::TenantNotResolved(match self.config.resolver {
+                TenantResolver::Subdomain => format!(
+                    "Requests must be sent to a tenant subdomain of {}.",
+                    self.config.base_domain.as_deref().unwrap_or_default()
+                ),
+                _ => format!("The {} header is required.", self.config.header),
+            }));
+        };
+
+        named
+            .to_lowercase()
+            .parse::<TenantId>()
+            .ok()
+            .filter(|tenant| self.routers.contains_key(tenant))
+            .ok_or_else(|| {
+                AppError::TenantNotResolved(format!("Unknown tenant '{}'.", named.trim()))
+            })
+    }
+
+    /// The part of the `Host` header left of the base domain, without the port.
+    fn subdomain(&self, request: &Request) -> Option<String> {
+        let base_domain = self.config.base_domain.as_deref()?;
+        let host = request
+            .headers()
+            .get(header::HOST)
+            .and_then(|value| value.to_str().ok())
+            .or_else(|| request.uri().host())?;
+        let host = host.split(':').next().unwrap_or(host).to_lowercase();
+        host.strip_suffix(base_domain)?
+            .strip_suffix('.')
+            .filter(|subdomain| !subdomain.is_empty())
+            .map(str::to_string)
+    }
+
+    async fn route(self, request: Request) -> Response {
+        let tenant = match self.resolve(&request) {
+            Ok(tenant) => tenant,
+            Err(_) if request.uri().path().starts_with(HEALTH_PREFIX) => TenantId::default(),
+            Err(e) => return ApiError(e).into_response(),
+        };
+        let router = self
+            .routers
+            .get(&tenant)
+            .or_else(|| self.routers.values().next())
+            .cloned()
+            .expect("at least one tenant is served");
+        match router.oneshot(request).await {
+            Ok(response) => response,
+            Err(infallible) => match infallible {},
+        }
+    }
+}
+
+/// Routes each request to the router of its tenant. With the resolver off there is only the
+/// default tenant, and its router is returned as is.
+pub fn dispatch(config: &TenancyConfig, mut routers: HashMap<TenantId, Router>) -> Router {
+    if config.resolver == TenantResolver::Off
+        && let Some(router) = routers.remove(&TenantId::default())
+    {
+        return router;
+    }
+
+    let tenants = TenantRouters {
+        config: Arc::new(config.clone()),
+        routers: Arc::new(routers),
+    };
+    Router::new().fallback_service(tower::service_fn(move |request: Request| {
+        let tenants = tenants.clone();
+        async move { Ok::<_, Infallible>(tenants.route(request).await) }
+    }))
+}
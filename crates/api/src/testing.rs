@@ -0,0 +1,130 @@
+//! A throwaway server over a disposable Postgres, for integration tests. Needs a running
+//! Docker daemon.
+
+use std::net::SocketAddr;
+use std::time::Duration;
+use testcontainers::runners::AsyncRunner;
+use testcontainers::{ContainerAsync, ImageExt};
+use testcontainers_modules::postgres::Postgres;
+
+use crate::app;
+use crate::config::{ConfigSource, load_config_from};
+use crate::database::Database;
+
+/// The key the test app accepts on `/export/reviews/corpus`.
+pub const CORPUS_API_KEY: &str = "test-corpus-key";
+
+/// The secret the test app's sandbox payment provider signs notifications with.
+pub const PAYMENT_WEBHOOK_SECRET: &str = "test-payment-secret";
+
+/// The only origin the test app's admin CORS policy allows; public reads allow any.
+pub const ADMIN_ORIGIN: &str = "https://admin.example";
+
+/// A server bound to a local port, backed by its own Postgres container. Dropping it stops the
+/// container.
+pub struct TestApp {
+    pub address: SocketAddr,
+    _database: TestDatabase,
+}
+
+impl TestApp {
+    pub fn url(&self, path: &str) -> String {
+        format!("http://{}{}", self.address, path)
+    }
+}
+
+/// An empty Postgres in a container of its own. Dropping it stops the container.
+pub struct TestDatabase {
+    pub url: String,
+    _postgres: ContainerAsync<Postgres>,
+}
+
+/// Starts Postgres in a container and returns once it accepts connections.
+pub async fn start_test_database() -> TestDatabase {
+    let postgres = Postgres::default()
+        .with_tag("16-alpine")
+        .start()
+        .await
+        .expect("failed to start the Postgres container");
+    let host = postgres.get_host().await.expect("container host");
+    let port = postgres
+        .get_host_port_ipv4(5432)
+        .await
+        .expect("container port");
+    TestDatabase {
+        url: format!("postgres://postgres:postgres@{}:{}/postgres", host, port),
+        _postgres: postgres,
+    }
+}
+
+/// Starts Postgres in a container, migrates it and serves the full router on an ephemeral port.
+/// Returns once `/health/ready` reports ready. Settings not given here come from the
+/// environment and otherwise default as they do for the server.
+pub async fn spawn_test_app() -> TestApp {
+    spawn_test_app_with(&[]).await
+}
+
+/// Like [`spawn_test_app`], with `settings` added to the test configuration.
+pub async fn spawn_test_app_with(settings: &[(&str, &str)]) -> TestApp {
+    let test_database = start_test_database().await;
+
+    let defaults = [
+        ("DATABASE_URL", test_database.url.as_str()),
+        ("CORS_ALLOW_CREDENTIALS", "false"),
+        ("CORS_ADMIN_ALLOWED_ORIGINS", ADMIN_ORIGIN),
+        ("TRUSTED_PROXIES", "127.0.0.1"),
+        ("SELLER_BADGES_REFRESH_MINUTES", "0"),
+        ("WEBHOOK_POLL_INTERVAL_SECONDS", "0"),
+        ("NOTIFICATION_POLL_INTERVAL_SECONDS", "1"),
+        ("CORPUS_API_KEYS", CORPUS_API_KEY),
+        ("PAYMENT_WEBHOOK_SECRET", PAYMENT_WEBHOOK_SECRET),
+    ];
+    let mut config = load_config_from(&ConfigSource::from_values(
+        defaults.into_iter().chain(settings.iter().copied()),
+    ))
+    .expect("invalid test configuration");
+    config.database_url = test_database.url.clone();
+
+    let database = Database::connect(&config)
+        .await
+        .expect("failed to connect to the test database");
+    let router = app(&config, database)
+        .await
+        .expect("failed to build the application");
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("failed to bind a local port");
+    let address = listener.local_addr().expect("bound address");
+    tokio::spawn(async move {
+        axum::serve(
+            listener,
+            router.into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .await
+        .expect("test server failed");
+    });
+
+    let test_app = TestApp {
+        address,
+        _database: test_database,
+    };
+    wait_until_ready(&test_app).await;
+    test_app
+}
+
+async fn wait_until_ready(test_app: &TestApp) {
+    let client = reqwest::Client::new();
+    for _ in 0..100 {
+        let ready = client
+            .get(test_app.url("/health/ready"))
+            .send()
+            .await
+            .is_ok_and(|response| response.status().is_success());
+        if ready {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    panic!("test app did not become ready within 10 seconds");
+}
@@ -0,0 +1,176 @@
+use axum::Router;
+use axum::extract::{ConnectInfo, Request};
+use hyper_util::rt::{TokioExecutor, TokioIo};
+use hyper_util::server::conn::auto;
+use hyper_util::server::graceful::GracefulShutdown;
+use hyper_util::service::TowerToHyperService;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::sync::watch;
+use tokio_rustls::TlsAcceptor;
+use tokio_rustls::rustls::ServerConfig;
+use tokio_rustls::rustls::crypto::ring;
+use tokio_rustls::rustls::pki_types::pem::PemObject;
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use tower::ServiceExt;
+use tracing::{debug, error, info, warn};
+
+use domain::error::AppError;
+
+use crate::config::TlsConfig;
+
+/// Clients that haven't finished the TLS handshake by then are dropped.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long open connections get to finish after shutdown is signalled.
+const SHUTDOWN_GRACE_PERIOD: Duration = Duration::from_secs(30);
+
+/// Pause after an accept error other than a dropped connection, as `axum::serve` does.
+/// Errors like running out of file descriptors last a while, and retrying at once would spin.
+const ACCEPT_ERROR_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Reads the certificate chain and private key into a rustls server config offering HTTP/2
+/// and HTTP/1.1.
+pub fn load_server_config(config: &TlsConfig) -> Result<Arc<ServerConfig>, AppError> {
+    let certs = CertificateDer::pem_file_iter(&config.cert_path)
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| {
+            AppError::ConfigError(format!(
+                "Failed to read TLS_CERT_PATH '{}': {}",
+                config.cert_path.display(),
+                e
+            ))
+        })?;
+    if certs.is_empty() {
+        return Err(AppError::ConfigError(format!(
+            "No certificate in TLS_CERT_PATH '{}'",
+            config.cert_path.display()
+        )));
+    }
+    let key = PrivateKeyDer::from_pem_file(&config.key_path).map_err(|e| {
+        AppError::ConfigError(format!(
+            "Failed to read TLS_KEY_PATH '{}': {}",
+            config.key_path.display(),
+            e
+        ))
+    })?;
+
+    let mut server_config = ServerConfig::builder_with_provider(Arc::new(ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .and_then(|builder| builder.with_no_client_auth().with_single_cert(certs, key))
+        .map_err(|e| AppError::ConfigError(format!("Invalid TLS certificate or key: {}", e)))?;
+    server_config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+    Ok(Arc::new(server_config))
+}
+
+/// Serves `app` over HTTPS until `shutdown` completes, then waits for open connections to
+/// finish. On `SIGHUP` the certificate and key are read again, so renewed certificates are
+/// picked up without a restart; connections already open keep the old one.
+pub async fn serve(
+    listener: TcpListener,
+    app: Router,
+    config: &TlsConfig,
+    shutdown: impl Future<Output = ()>,
+) -> Result<(), AppError> {
+    let (server_config_tx, server_config) = watch::channel(load_server_config(config)?);
+    tokio::spawn(reload_on_sighup(config.clone(), server_config_tx));
+
+    let graceful = GracefulShutdown::new();
+    let mut shutdown = std::pin::pin!(shutdown);
+    loop {
+        let (stream, peer) = tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    if !is_connection_error(&e) {
+                        warn!("Failed to accept a connection: {}", e);
+                        tokio::time::sleep(ACCEPT_ERROR_BACKOFF).await;
+                    }
+                    continue;
+                }
+            },
+            _ = &mut shutdown => break,
+        };
+
+        let acceptor = TlsAcceptor::from(server_config.borrow().clone());
+        // Handlers see the peer address as they do behind `axum::serve`
+        let app = app.clone();
+        let service =
+            TowerToHyperService::new(tower::service_fn(move |mut request: Request<_>| {
+                request.extensions_mut().insert(ConnectInfo(peer));
+                app.clone().oneshot(request)
+            }));
+        let watcher = graceful.watcher();
+        tokio::spawn(async move {
+            let stream =
+                match tokio::time::timeout(HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await {
+                    Ok(Ok(stream)) => stream,
+                    Ok(Err(e)) => {
+                        debug!("TLS handshake with {} failed: {}", peer, e);
+                        return;
+                    }
+                    Err(_) => {
+                        debug!("TLS handshake with {} timed out", peer);
+                        return;
+                    }
+                };
+
+            let builder = auto::Builder::new(TokioExecutor::new());
+            let connection = builder.serve_connection_with_upgrades(TokioIo::new(stream), service);
+            if let Err(e) = watcher.watch(connection.into_owned()).await {
+                debug!("Connection from {} closed with an error: {}", peer, e);
+            }
+        });
+    }
+
+    if tokio::time::timeout(SHUTDOWN_GRACE_PERIOD, graceful.shutdown())
+        .await
+        .is_err()
+    {
+        warn!("Connections still open after the shutdown grace period were dropped.");
+    }
+    Ok(())
+}
+
+/// Errors about the one connection being accepted, which leave the listener fine.
+fn is_connection_error(e: &std::io::Error) -> bool {
+    matches!(
+        e.kind(),
+        std::io::ErrorKind::ConnectionRefused
+            | std::io::ErrorKind::ConnectionAborted
+            | std::io::ErrorKind::ConnectionReset
+    )
+}
+
+/// Reloads the certificate on every `SIGHUP`. A certificate that fails to load is logged and
+/// the previous one stays in use.
+async fn reload_on_sighup(config: TlsConfig, server_config: watch::Sender<Arc<ServerConfig>>) {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{SignalKind, signal};
+
+        let mut hangups = match signal(SignalKind::hangup()) {
+            Ok(hangups) => hangups,
+            Err(e) => {
+                error!("Failed to install the SIGHUP handler: {}", e);
+                return;
+            }
+        };
+        while hangups.recv().await.is_some() {
+            match load_server_config(&config) {
+                Ok(reloaded) => {
+                    server_config.send_replace(reloaded);
+                    info!("Reloaded the TLS certificate.");
+                }
+                Err(e) => error!("Keeping the current TLS certificate: {:?}", e),
+            }
+        }
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = (config, server_config);
+    }
+}
@@ -0,0 +1,173 @@
+//! API versions. Every version's routes are served under `/api/v{n}`; `/api/...` without a
+//! version picks one from the `API-Version` request header, and the unversioned paths from
+//! before versioning are deprecated aliases of v1.
+//!
+//! A breaking change to a DTO ships as a new [`ApiVersion`]: add the variant, register the
+//! changed routes for it in [`crate::routes`], and let handlers that serve both versions take
+//! the [`ApiVersion`] extractor.
+
+use axum::{
+    extract::{FromRequestParts, Request, State},
+    http::{
+        HeaderValue, Uri,
+        header::{self, HeaderName},
+        request::Parts,
+        uri::PathAndQuery,
+    },
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::convert::Infallible;
+
+use domain::error::AppError;
+
+use crate::error::ApiError;
+
+/// Request header choosing the version of an unversioned `/api/...` path, and response header
+/// naming the version that answered.
+pub const API_VERSION_HEADER: HeaderName = HeaderName::from_static("api-version");
+
+/// When the unversioned paths were deprecated (2026-10-16), as an RFC 9745 `Deprecation` date.
+const LEGACY_DEPRECATED_AT: &str = "@1792108800";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ApiVersion {
+    V1,
+}
+
+impl ApiVersion {
+    pub const ALL: [ApiVersion; 1] = [ApiVersion::V1];
+    /// Served when a request doesn't ask for a version.
+    pub const LATEST: ApiVersion = ApiVersion::V1;
+
+    pub fn number(&self) -> u32 {
+        match self {
+            ApiVersion::V1 => 1,
+        }
+    }
+
+    /// Where the version's routes are nested, e.g. `/api/v1`.
+    pub fn prefix(&self) -> String {
+        format!("/api/v{}", self.number())
+    }
+
+    /// Accepts `1` or `v1`.
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        let number = value.strip_prefix(['v', 'V']).unwrap_or(value);
+        Self::ALL
+            .into_iter()
+            .find(|version| number.parse() == Ok(version.number()))
+    }
+}
+
+/// The version the request was routed to; [`ApiVersion::LATEST`] outside the versioned
+/// routes.
+impl<S: Send + Sync> FromRequestParts<S> for ApiVersion {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(parts
+            .extensions
+            .get::<ApiVersion>()
+            .copied()
+            .unwrap_or(Self::LATEST))
+    }
+}
+
+/// `path` without its `/api` or `/api/v{n}` prefix, e.g. `/admin/jobs` for
+/// `/api/v1/admin/jobs`. Other paths are returned as they are.
+pub fn unversioned_path(path: &str) -> &str {
+    let Some(rest) = path
+        .strip_prefix("/api")
+        .filter(|rest| rest.is_empty() || rest.starts_with('/'))
+    else {
+        return path;
+    };
+    let Some(after) = rest.strip_prefix("/v") else {
+        return rest;
+    };
+    let end = after.find('/').unwrap_or(after.len());
+    if end > 0 && after[..end].bytes().all(|b| b.is_ascii_digit()) {
+        &after[end..]
+    } else {
+        rest
+    }
+}
+
+/// Rewrites `/api/...` without a version to the version named by `API-Version`, or the latest
+/// one, before routing. An unknown version is answered with `400`.
+pub async fn negotiate_version(mut request: Request, next: Next) -> Response {
+    let Some(rest) = request
+        .uri()
+        .path()
+        .strip_prefix("/api")
+        .filter(|rest| rest.is_empty() || rest.starts_with('/'))
+    else {
+        return next.run(request).await;
+    };
+    let segment = rest.trim_start_matches('/').split('/').next().unwrap_or("");
+    let is_versioned = segment
+        .strip_prefix('v')
+        .is_some_and(|number| !number.is_empty() && number.bytes().all(|b| b.is_ascii_digit()));
+    if is_versioned {
+        return next.run(request).await;
+    }
+
+    let version = match request.headers().get(&API_VERSION_HEADER) {
+        None => ApiVersion::LATEST,
+        Some(requested) => {
+            let requested = String::from_utf8_lossy(requested.as_bytes()).into_owned();
+            match ApiVersion::parse(&requested) {
+                Some(version) => version,
+                None => {
+                    return ApiError(AppError::UnsupportedApiVersion(requested)).into_response();
+                }
+            }
+        }
+    };
+
+    let mut path = format!("{}{}", version.prefix(), rest);
+    if let Some(query) = request.uri().query() {
+        path = format!("{}?{}", path, query);
+    }
+    let mut parts = request.uri().clone().into_parts();
+    parts.path_and_query = PathAndQuery::try_from(path).ok();
+    if let Ok(uri) = Uri::from_parts(parts) {
+        *request.uri_mut() = uri;
+    }
+
+    let mut response = next.run(request).await;
+    response
+        .headers_mut()
+        .append(header::VARY, HeaderValue::from_static("api-version"));
+    response
+}
+
+/// Names the version that answered in the `API-Version` response header.
+pub async fn tag_version(State(version): State<ApiVersion>, mut response: Response) -> Response {
+    response
+        .headers_mut()
+        .insert(API_VERSION_HEADER, HeaderValue::from(version.number()));
+    response
+}
+
+/// Marks a response to an unversioned path as deprecated and links its v1 successor.
+pub async fn deprecated_alias(request: Request, next: Next) -> Response {
+    let successor = match request.uri().path_and_query() {
+        Some(path) => format!("{}{}", ApiVersion::V1.prefix(), path),
+        None => ApiVersion::V1.prefix(),
+    };
+
+    let mut response = next.run(request).await;
+    let headers = response.headers_mut();
+    headers.insert(
+        HeaderName::from_static("deprecation"),
+        HeaderValue::from_static(LEGACY_DEPRECATED_AT),
+    );
+    if let Ok(link) = HeaderValue::from_str(&format!("<{}>; rel=\"successor-version\"", successor))
+    {
+        headers.append(header::LINK, link);
+    }
+    response
+}
@@ -0,0 +1,128 @@
+use sqlx::{Database as SqlxDatabase, Executor, IntoArguments, Pool};
+use std::time::{Duration, Instant};
+use tracing::{error, info, warn};
+
+use domain::runtime::Readiness;
+use domain::tenancy::TenantId;
+
+use crate::config::WarmupConfig;
+use crate::database::Database;
+use crate::state::AppState;
+
+/// Runs the startup warm-up sequence and flips `readiness` once the service can take traffic.
+/// `tenants` are the states whose caches are primed.
+///
+/// When warm-up is disabled the service is reported ready immediately.
+pub async fn run(
+    database: Database,
+    config: WarmupConfig,
+    readiness: Readiness,
+    tenants: Vec<(TenantId, AppState)>,
+) {
+    if !config.enabled {
+        readiness.mark_ready();
+        return;
+    }
+
+    match database {
+        Database::Postgres { pool, .. } => warm_up(&pool, &config, &readiness, &tenants).await,
+        Database::Sqlite(pool) => warm_up(&pool, &config, &readiness, &tenants).await,
+        #[cfg(feature = "test-utils")]
+        Database::Memory(_) => readiness.mark_ready(),
+    }
+}
+
+async fn warm_up<DB: SqlxDatabase>(
+    pool: &Pool<DB>,
+    config: &WarmupConfig,
+    readiness: &Readiness,
+    tenants: &[(TenantId, AppState)],
+) where
+    for<'c> &'c Pool<DB>: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+{
+    let started = Instant::now();
+    info!("Starting warm-up...");
+
+    warm_pool(pool, config.pool_connections).await;
+    for (tenant, state) in tenants {
+        prime_caches(tenant, state).await;
+    }
+
+    if verify_canary(pool, config).await {
+        readiness.mark_ready();
+        info!(
+            "Warm-up finished in {:?}, service is ready.",
+            started.elapsed()
+        );
+    } else {
+        error!(
+            "Canary query failed after {} attempts, service will stay unready.",
+            config.canary_attempts
+        );
+    }
+}
+
+/// Opens `connections` pool connections up front so the first requests don't pay the handshake.
+async fn warm_pool<DB: SqlxDatabase>(pool: &Pool<DB>, connections: u32) {
+    let target = connections.min(pool.options().get_max_connections());
+    let mut held = Vec::with_capacity(target as usize);
+
+    for _ in 0..target {
+        match pool.acquire().await {
+            Ok(conn) => held.push(conn),
+            Err(e) => {
+                warn!("Failed to open warm-up connection: {:?}", e);
+                break;
+            }
+        }
+    }
+
+    info!("Pre-warmed {} database connections.", held.len());
+}
+
+/// Fills a tenant's caches the way its first requests would: the product categories of
+/// `/products/categories` in the lookup cache and the response cache, and the geolocation
+/// centroids freight estimates and nearby-seller searches start from in the lookup cache.
+async fn prime_caches(tenant: &TenantId, state: &AppState) {
+    match state.product_service.get_category_values().await {
+        Ok(categories) => info!(
+            "Primed product categories of {} ({} values).",
+            tenant,
+            categories.len()
+        ),
+        Err(e) => warn!("Failed to prime product categories of {}: {:?}", tenant, e),
+    }
+    match state.freight_service.prime_locations().await {
+        Ok(locations) => info!(
+            "Primed geolocation centroids of {} ({} prefixes).",
+            tenant, locations
+        ),
+        Err(e) => warn!(
+            "Failed to prime geolocation centroids of {}: {:?}",
+            tenant, e
+        ),
+    }
+}
+
+async fn verify_canary<DB: SqlxDatabase>(pool: &Pool<DB>, config: &WarmupConfig) -> bool
+where
+    for<'c> &'c Pool<DB>: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+{
+    let timeout = Duration::from_secs(config.canary_timeout_seconds);
+
+    for attempt in 1..=config.canary_attempts.max(1) {
+        let canary = sqlx::query("SELECT order_id FROM orders LIMIT 1").fetch_optional(pool);
+
+        match tokio::time::timeout(timeout, canary).await {
+            Ok(Ok(_)) => return true,
+            Ok(Err(e)) => warn!("Canary query attempt {} failed: {:?}", attempt, e),
+            Err(_) => warn!("Canary query attempt {} timed out", attempt),
+        }
+
+        tokio::time::sleep(Duration::from_secs(attempt as u64)).await;
+    }
+
+    false
+}
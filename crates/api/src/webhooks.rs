@@ -0,0 +1,145 @@
+use futures::future::join_all;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::{error, info, warn};
+
+use domain::models::PendingWebhookDelivery;
+use domain::services::WebhookService;
+
+/// Longest response body excerpt kept as a failed attempt's error.
+const ERROR_BODY_LIMIT: usize = 500;
+
+/// Sends due webhook deliveries, polling every `poll_interval_seconds`. Each pass leases a
+/// batch, posts the deliveries concurrently and records every outcome; failed ones are
+/// retried with exponential backoff until `max_attempts`. Deliveries the subscription's state
+/// filter leaves out are dropped unsent.
+///
+/// Does nothing when the poll interval is 0.
+pub async fn run(service: WebhookService) {
+    let config = service.config();
+    if config.poll_interval_seconds == 0 {
+        info!("Webhook delivery is disabled.");
+        return;
+    }
+
+    let client = match reqwest::Client::builder()
+        .timeout(Duration::from_secs(config.timeout_seconds))
+        .build()
+    {
+        Ok(client) => client,
+        Err(e) => {
+            error!("Failed to build the webhook HTTP client: {}", e);
+            return;
+        }
+    };
+
+    let mut interval = tokio::time::interval(Duration::from_secs(config.poll_interval_seconds));
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    loop {
+        interval.tick().await;
+        let deliveries = match service.claim_due().await {
+            Ok(deliveries) => deliveries,
+            Err(e) => {
+                error!("Failed to claim webhook deliveries: {:?}", e);
+                continue;
+            }
+        };
+
+        join_all(
+            deliveries
+                .iter()
+                .map(|delivery| deliver(&service, &client, delivery)),
+        )
+        .await;
+    }
+}
+
+async fn deliver(
+    service: &WebhookService,
+    client: &reqwest::Client,
+    delivery: &PendingWebhookDelivery,
+) {
+    let Some(data) = delivery.data() else {
+        if let Err(e) = service.discard(delivery).await {
+            error!(
+                "Failed to drop filtered webhook delivery {}: {:?}",
+                delivery.delivery_id, e
+            );
+        }
+        return;
+    };
+    let body = serde_json::json!({
+        "delivery_id": delivery.delivery_id,
+        "event": delivery.event,
+        "created_at": delivery.created_at,
+        "data": data,
+    })
+    .to_string();
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+        .to_string();
+
+    let response = client
+        .post(&delivery.url)
+        .header(http::header::CONTENT_TYPE, "application/json")
+        .header("X-Webhook-Event", &delivery.event)
+        .header("X-Webhook-Delivery", delivery.delivery_id.to_string())
+        .header("X-Webhook-Timestamp", &timestamp)
+        .header(
+            "X-Webhook-Signature",
+            format!("sha256={}", sign(&delivery.secret, &timestamp, &body)),
+        )
+        .body(body)
+        .send()
+        .await;
+
+    let result = match response {
+        Ok(response) if response.status().is_success() => {
+            service
+                .record_success(delivery, response.status().as_u16())
+                .await
+        }
+        Ok(response) => {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            let excerpt: String = text.chars().take(ERROR_BODY_LIMIT).collect();
+            let error = format!("HTTP {}: {}", status, excerpt.trim());
+            warn!(
+                "Webhook delivery {} to {} failed: {}",
+                delivery.delivery_id, delivery.url, error
+            );
+            service
+                .record_failure(delivery, Some(status.as_u16()), &error)
+                .await
+        }
+        Err(e) => {
+            warn!(
+                "Webhook delivery {} to {} failed: {}",
+                delivery.delivery_id, delivery.url, e
+            );
+            service.record_failure(delivery, None, &e.to_string()).await
+        }
+    };
+
+    // The lease still runs out, so the delivery is sent again and may arrive twice.
+    if let Err(e) = result {
+        error!(
+            "Failed to record webhook delivery {}: {:?}",
+            delivery.delivery_id, e
+        );
+    }
+}
+
+/// Hex HMAC-SHA256 over `{timestamp}.{body}`, keyed with the subscription's secret.
+fn sign(secret: &str, timestamp: &str, body: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size");
+    mac.update(timestamp.as_bytes());
+    mac.update(b".");
+    mac.update(body.as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
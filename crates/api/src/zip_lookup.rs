@@ -0,0 +1,105 @@
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::info;
+
+use domain::error::{AppError, AppResult};
+use domain::models::CepAddress;
+use domain::repositories::GeolocationRepository;
+use domain::zip_lookup::{GeolocationZipLookup, ZipLookup};
+
+use crate::config::{ZipLookupBackend, ZipLookupConfig};
+
+/// Builds the CEP lookup named by `ZIP_LOOKUP_PROVIDER`.
+pub fn connect(
+    config: &ZipLookupConfig,
+    geolocation: Arc<dyn GeolocationRepository>,
+) -> Result<Arc<dyn ZipLookup>, AppError> {
+    let provider: Arc<dyn ZipLookup> = match config.provider {
+        ZipLookupBackend::ViaCep => Arc::new(ViaCepLookup::new(
+            config.viacep_base_url.clone(),
+            Duration::from_secs(config.timeout_seconds),
+        )?),
+        ZipLookupBackend::Geolocation => Arc::new(GeolocationZipLookup::new(geolocation)),
+    };
+    info!("Looking up CEPs with {}.", provider.name());
+    Ok(provider)
+}
+
+/// [`ZipLookup`] over the public ViaCEP API, which needs no credentials.
+pub struct ViaCepLookup {
+    client: reqwest::Client,
+    base_url: String,
+}
+
+/// A ViaCEP answer. Unassigned CEPs come back with `200` and only `erro` set; blank fields
+/// are empty strings.
+#[derive(Deserialize)]
+struct ViaCepResponse {
+    #[serde(default)]
+    erro: serde_json::Value,
+    #[serde(default)]
+    logradouro: String,
+    #[serde(default)]
+    bairro: String,
+    #[serde(default)]
+    localidade: String,
+    #[serde(default)]
+    uf: String,
+}
+
+impl ViaCepLookup {
+    pub const NAME: &'static str = "viacep";
+
+    pub fn new(base_url: String, timeout: Duration) -> Result<Self, AppError> {
+        let client = reqwest::Client::builder()
+            .timeout(timeout)
+            .build()
+            .map_err(|e| {
+                AppError::ConfigError(format!("Failed to build the ViaCEP HTTP client: {}", e))
+            })?;
+        Ok(Self { client, base_url })
+    }
+}
+
+#[async_trait]
+impl ZipLookup for ViaCepLookup {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    async fn lookup(&self, cep: &str) -> AppResult<Option<CepAddress>> {
+        let response = self
+            .client
+            .get(format!("{}/ws/{}/json/", self.base_url, cep))
+            .send()
+            .await
+            .map_err(|e| AppError::ZipLookupError(format!("ViaCEP request failed: {}", e)))?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(AppError::ZipLookupError(format!(
+                "ViaCEP returned {} for {}",
+                status, cep
+            )));
+        }
+        let body: ViaCepResponse = response
+            .json()
+            .await
+            .map_err(|e| AppError::ZipLookupError(format!("Unexpected ViaCEP response: {}", e)))?;
+
+        // `erro` has been both `true` and `"true"` over the API's life.
+        if matches!(body.erro, serde_json::Value::Bool(true)) || body.erro.as_str() == Some("true")
+        {
+            return Ok(None);
+        }
+        let present = |value: String| Some(value.trim().to_string()).filter(|v| !v.is_empty());
+        Ok(Some(CepAddress {
+            cep: cep.to_string(),
+            street: present(body.logradouro),
+            neighborhood: present(body.bairro),
+            city: body.localidade.trim().to_string(),
+            state: body.uf.trim().to_uppercase(),
+        }))
+    }
+}
@@ -0,0 +1,94 @@
+//! Query timeouts and cancellation against a real Postgres, each test in its own container.
+//!
+//! Run with `cargo test -p api --features test-utils`; Docker must be running.
+
+use std::time::Duration;
+
+use api::config::{ConfigSource, load_config_from};
+use api::database::Database;
+use api::testing::start_test_database;
+use persistence::cancel::cancel_on_drop;
+use sqlx::PgPool;
+
+/// Backends other than the caller's still running a statement that starts with `query`.
+async fn running(pool: &PgPool, query: &str) -> i64 {
+    sqlx::query_scalar(
+        "SELECT COUNT(*) FROM pg_stat_activity
+         WHERE state = 'active' AND pid <> pg_backend_pid() AND query LIKE $1 || '%'",
+    )
+    .bind(query)
+    .fetch_one(pool)
+    .await
+    .expect("pg_stat_activity")
+}
+
+#[tokio::test]
+async fn statement_timeout_applies_to_every_pooled_connection() {
+    let test_database = start_test_database().await;
+    let mut config = load_config_from(&ConfigSource::from_values([
+        ("DATABASE_URL", test_database.url.as_str()),
+        ("DATABASE_STATEMENT_TIMEOUT_MS", "250"),
+    ]))
+    .expect("invalid test configuration");
+    config.database_url = test_database.url.clone();
+    let Database::Postgres { pool, .. } = Database::connect(&config)
+        .await
+        .expect("failed to connect to the test database")
+    else {
+        panic!("expected a Postgres database");
+    };
+
+    let timeout: String = sqlx::query_scalar("SHOW statement_timeout")
+        .fetch_one(&pool)
+        .await
+        .expect("statement_timeout");
+    assert_eq!(timeout, "250ms");
+
+    let error = sqlx::query("SELECT pg_sleep(5)")
+        .execute(&pool)
+        .await
+        .expect_err("the statement should time out");
+    let code = error.as_database_error().and_then(|e| e.code());
+    assert_eq!(code.as_deref(), Some("57014"), "{error}");
+}
+
+#[tokio::test]
+async fn abandoned_queries_are_cancelled_on_the_server() {
+    let test_database = start_test_database().await;
+    let pool = PgPool::connect(&test_database.url)
+        .await
+        .expect("failed to connect to the test database");
+
+    let abandoned = tokio::time::timeout(
+        Duration::from_millis(500),
+        cancel_on_drop(&pool, async |conn| {
+            sqlx::query("SELECT pg_sleep(60)").execute(conn).await
+        }),
+    )
+    .await;
+    assert!(abandoned.is_err(), "the query should still be running");
+
+    for _ in 0..50 {
+        if running(&pool, "SELECT pg_sleep(60)").await == 0 {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    panic!("the abandoned query was still running after 5 seconds");
+}
+
+#[tokio::test]
+async fn finished_queries_return_their_result() {
+    let test_database = start_test_database().await;
+    let pool = PgPool::connect(&test_database.url)
+        .await
+        .expect("failed to connect to the test database");
+
+    let answer: i32 = cancel_on_drop(&pool, async |conn| {
+        sqlx::query_scalar("SELECT 42").fetch_one(conn).await
+    })
+    .await
+    .expect("query");
+    assert_eq!(answer, 42);
+    assert_eq!(running(&pool, "SELECT 42").await, 0);
+}
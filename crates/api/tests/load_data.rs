@@ -0,0 +1,190 @@
+//! `/load-data` over the in-memory repositories, against the sample CSVs in `data/`. The
+//! imports read those paths relative to the working directory, so every test first moves to
+//! the workspace root.
+//!
+//! Run with `cargo test -p api --features test-utils`; no database is needed.
+
+use api::app;
+use api::config::{ConfigSource, load_config_from};
+use api::database::Database;
+use axum::Router;
+use axum::body::{Body, to_bytes};
+use axum::http::{Method, Request, StatusCode, header};
+use serde_json::{Value, json};
+use tower::ServiceExt;
+
+struct Api {
+    router: Router,
+}
+
+impl Api {
+    async fn spawn() -> Self {
+        std::env::set_current_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/../.."))
+            .expect("workspace root");
+        let config = load_config_from(&ConfigSource::from_values([("DATABASE_URL", "memory:")]))
+            .expect("invalid test configuration");
+        let database = Database::connect(&config)
+            .await
+            .expect("in-memory database");
+        let router = app(&config, database)
+            .await
+            .expect("failed to build the application");
+        Self { router }
+    }
+
+    async fn send(&self, method: Method, path: &str, body: Option<Value>) -> (StatusCode, Value) {
+        let request = Request::builder()
+            .method(method)
+            .uri(format!("/api/v1{path}"));
+        let request = match body {
+            Some(body) => request
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string())),
+            None => request.body(Body::empty()),
+        }
+        .expect("valid request");
+
+        let response = self
+            .router
+            .clone()
+            .oneshot(request)
+            .await
+            .expect("request failed");
+        let status = response.status();
+        let bytes = to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("response body");
+        (
+            status,
+            serde_json::from_slice(&bytes)
+                .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(&bytes).into_owned())),
+        )
+    }
+
+    async fn get(&self, path: &str) -> (StatusCode, Value) {
+        self.send(Method::GET, path, None).await
+    }
+
+    async fn post(&self, path: &str, body: Option<Value>) -> (StatusCode, Value) {
+        self.send(Method::POST, path, body).await
+    }
+
+    async fn total(&self, path: &str) -> Value {
+        let (status, page) = self.get(&format!("{path}?page_size=1")).await;
+        assert_eq!(status, StatusCode::OK, "{page}");
+        page["meta"]["total_records"].clone()
+    }
+}
+
+#[tokio::test]
+async fn dry_run_checks_the_files_without_importing_them() {
+    let api = Api::spawn().await;
+    let datasets = json!({ "datasets": ["customers", "sellers"] });
+
+    let (status, report) = api
+        .post("/load-data?dry_run=true", Some(datasets.clone()))
+        .await;
+    assert_eq!(status, StatusCode::OK, "{report}");
+    assert_eq!(report["valid"], true);
+    assert_eq!(report["datasets"][0]["dataset"], "customers");
+    assert_eq!(report["datasets"][0]["valid_count"], 1);
+    assert_eq!(report["datasets"][1]["dataset"], "sellers");
+    assert_eq!(report["datasets"][1]["valid_count"], 1);
+    assert_eq!(api.total("/customers").await, 0);
+    assert_eq!(api.total("/sellers").await, 0);
+    let (status, _) = api.get("/load-data/jobs/1").await;
+    assert_eq!(status, StatusCode::NOT_FOUND, "a dry run starts no job");
+
+    let (status, loaded) = api
+        .post("/load-data", Some(json!({ "datasets": ["customers"] })))
+        .await;
+    assert_eq!(status, StatusCode::OK, "{loaded}");
+    assert_eq!(loaded["success_count"], 1);
+
+    let (status, report) = api.post("/load-data?dry_run=true", Some(datasets)).await;
+    assert_eq!(status, StatusCode::OK, "{report}");
+    assert_eq!(report["valid"], false);
+    let customers = &report["datasets"][0];
+    assert_eq!(customers["error_count"], 1);
+    assert_eq!(customers["errors"][0]["row_number"], 2);
+    assert!(
+        customers["errors"][0]["reason"]
+            .as_str()
+            .is_some_and(|reason| reason.ends_with("already exists")),
+        "{customers}"
+    );
+    assert_eq!(report["datasets"][1]["error_count"], 0);
+
+    let (status, _) = api
+        .post("/load-data?dry_run=true", Some(json!({ "datasets": [] })))
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn load_imports_the_chosen_datasets_from_the_requested_start() {
+    let api = Api::spawn().await;
+
+    let (status, loaded) = api
+        .post(
+            "/load-data",
+            Some(json!({ "datasets": ["sellers", "customers"], "skip_rows": 1 })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{loaded}");
+    assert_eq!(
+        loaded["success_count"], 1,
+        "only the first dataset skips rows"
+    );
+    assert_eq!(api.total("/sellers").await, 0);
+    assert_eq!(api.total("/customers").await, 1);
+    assert_eq!(
+        api.total("/products").await,
+        0,
+        "datasets not named are left out"
+    );
+
+    let (status, job) = api
+        .get(&format!("/load-data/jobs/{}", loaded["job_id"]))
+        .await;
+    assert_eq!(status, StatusCode::OK, "{job}");
+    assert_eq!(job["status"], "completed");
+    assert_eq!(job["datasets"][0]["dataset"], "sellers");
+    assert_eq!(job["datasets"][0]["success_count"], 0);
+    assert_eq!(job["datasets"][1]["dataset"], "customers");
+    assert_eq!(job["datasets"][1]["success_count"], 1);
+}
+
+#[tokio::test]
+async fn resuming_a_finished_job_imports_nothing_again() {
+    let api = Api::spawn().await;
+
+    let (status, loaded) = api
+        .post("/load-data", Some(json!({ "datasets": ["customers"] })))
+        .await;
+    assert_eq!(status, StatusCode::OK, "{loaded}");
+    let job_id = loaded["job_id"].clone();
+
+    let (status, resumed) = api
+        .post("/load-data", Some(json!({ "resume_job_id": job_id })))
+        .await;
+    assert_eq!(status, StatusCode::OK, "{resumed}");
+    assert_eq!(resumed["job_id"], job_id, "a resume continues the same job");
+    assert_eq!(resumed["success_count"], 0);
+    assert_eq!(api.total("/customers").await, 1);
+    let (_, job) = api.get(&format!("/load-data/jobs/{job_id}")).await;
+    assert_eq!(job["datasets"][0]["status"], "completed");
+    assert_eq!(job["datasets"][0]["success_count"], 1);
+
+    let (status, _) = api
+        .post(
+            "/load-data",
+            Some(json!({ "resume_job_id": job_id, "datasets": ["sellers"] })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = api
+        .post("/load-data", Some(json!({ "resume_job_id": 999 })))
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
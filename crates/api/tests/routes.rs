@@ -0,0 +1,1985 @@
+//! End-to-end tests for every route, each against its own Postgres container. `/load-data`
+//! is left out: it imports the Olist CSVs from `data/`, see `load_data.rs`.
+//!
+//! Run with `cargo test -p api --features test-utils`; Docker must be running.
+
+use api::payments::SandboxPaymentProvider;
+use api::testing::{
+    ADMIN_ORIGIN, CORPUS_API_KEY, PAYMENT_WEBHOOK_SECRET, TestApp, spawn_test_app,
+    spawn_test_app_with,
+};
+use chrono::{Duration, NaiveDate, Utc};
+use reqwest::{Client, Method, StatusCode};
+use serde_json::{Value, json};
+
+struct Api {
+    app: TestApp,
+    client: Client,
+}
+
+impl Api {
+    async fn spawn() -> Self {
+        Self {
+            app: spawn_test_app().await,
+            client: Client::new(),
+        }
+    }
+
+    /// `path` under the current API version.
+    fn url(&self, path: &str) -> String {
+        self.app.url(&format!("/api/v1{path}"))
+    }
+
+    async fn send(&self, method: Method, path: &str, body: Option<Value>) -> (StatusCode, Value) {
+        let mut request = self.client.request(method, self.url(path));
+        if let Some(body) = body {
+            request = request.json(&body);
+        }
+        let response = request.send().await.expect("request failed");
+        let status = response.status();
+        let text = response.text().await.expect("response body");
+        (
+            status,
+            serde_json::from_str(&text).unwrap_or(Value::String(text)),
+        )
+    }
+
+    async fn get(&self, path: &str) -> (StatusCode, Value) {
+        self.send(Method::GET, path, None).await
+    }
+
+    async fn post(&self, path: &str, body: Value) -> (StatusCode, Value) {
+        self.send(Method::POST, path, Some(body)).await
+    }
+
+    async fn put(&self, path: &str, body: Value) -> (StatusCode, Value) {
+        self.send(Method::PUT, path, Some(body)).await
+    }
+
+    async fn delete(&self, path: &str) -> (StatusCode, Value) {
+        self.send(Method::DELETE, path, None).await
+    }
+
+    async fn create_customer(&self) -> String {
+        let (status, customer) = self
+            .post(
+                "/customers",
+                json!({
+                    "customer_unique_id": "861eff4711a542e4b93843c6dd7febb0",
+                    "customer_zip_code_prefix": "01310",
+                    "customer_city": "São Paulo",
+                    "customer_state": "SP"
+                }),
+            )
+            .await;
+        assert_eq!(status, StatusCode::CREATED, "{customer}");
+        id(&customer, "customer_id")
+    }
+
+    async fn create_seller(&self) -> String {
+        let (status, seller) = self
+            .post(
+                "/sellers",
+                json!({
+                    "seller_zip_code_prefix": "01311",
+                    "seller_city": "Sao Paulo",
+                    "seller_state": "SP"
+                }),
+            )
+            .await;
+        assert_eq!(status, StatusCode::CREATED, "{seller}");
+        id(&seller, "seller_id")
+    }
+
+    async fn create_product(&self, category: &str) -> String {
+        let (status, product) = self
+            .post(
+                "/products",
+                json!({
+                    "product_category_name": category,
+                    "product_name_lenght": 40,
+                    "product_description_lenght": 300,
+                    "product_photos_qty": 2,
+                    "product_weight_g": 500,
+                    "product_length_cm": 20,
+                    "product_height_cm": 10,
+                    "product_width_cm": 15
+                }),
+            )
+            .await;
+        assert_eq!(status, StatusCode::CREATED, "{product}");
+        id(&product, "product_id")
+    }
+
+    /// An order purchased just now, so it is still inside the amendment window.
+    async fn create_order(&self, customer_id: &str) -> String {
+        let purchased = Utc::now().naive_utc();
+        let (status, order) = self
+            .post(
+                "/orders",
+                json!({
+                    "customer_id": customer_id,
+                    "order_status": "approved",
+                    "order_purchase_timestamp": purchased,
+                    "order_approved_at": purchased,
+                    "order_estimated_delivery_date": purchased + Duration::days(10)
+                }),
+            )
+            .await;
+        assert_eq!(status, StatusCode::CREATED, "{order}");
+        id(&order, "order_id")
+    }
+
+    async fn add_item(&self, order_id: &str, product_id: &str, seller_id: &str) -> Value {
+        let (status, item) = self
+            .post(
+                &format!("/orders/{order_id}/items"),
+                json!({
+                    "order_item_id": 1,
+                    "product_id": product_id,
+                    "seller_id": seller_id,
+                    "shipping_limit_date": Utc::now().naive_utc() + Duration::days(2),
+                    "price": "59.90",
+                    "freight_value": "12.50"
+                }),
+            )
+            .await;
+        assert_eq!(status, StatusCode::CREATED, "{item}");
+        item
+    }
+}
+
+fn id(body: &Value, field: &str) -> String {
+    body[field]
+        .as_str()
+        .unwrap_or_else(|| panic!("no {field} in {body}"))
+        .to_string()
+}
+
+#[tokio::test]
+async fn health_routes_report_live_and_ready() {
+    let api = Api::spawn().await;
+
+    for path in ["/health/live", "/health/ready"] {
+        let response = api.client.get(api.app.url(path)).send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
+
+#[tokio::test]
+async fn unknown_routes_and_methods_answer_with_json_errors() {
+    let api = Api::spawn().await;
+
+    let (status, body) = api.get("/nope").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert!(body["error"].as_str().unwrap().contains("/api/v1/nope"));
+    assert!(body["request_id"].is_string());
+
+    let response = api
+        .client
+        .delete(api.url("/orders"))
+        .header("X-Request-Id", "req-1")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+    assert!(
+        response.headers()["allow"]
+            .to_str()
+            .unwrap()
+            .contains("POST")
+    );
+    assert_eq!(response.headers()["x-request-id"], "req-1");
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["request_id"], "req-1");
+}
+
+#[tokio::test]
+async fn cors_policy_depends_on_the_route_group() {
+    let api = Api::spawn().await;
+    let preflight = |origin: &'static str, method: Method, path: &str| {
+        api.client
+            .request(Method::OPTIONS, api.url(path))
+            .header("Origin", origin)
+            .header("Access-Control-Request-Method", method.as_str())
+            .send()
+    };
+    let allowed = |response: reqwest::Response| {
+        response
+            .headers()
+            .get("access-control-allow-origin")
+            .map(|value| value.to_str().unwrap().to_string())
+    };
+
+    let other = "https://shop.example";
+    let response = preflight(other, Method::GET, "/customers").await.unwrap();
+    assert_eq!(allowed(response).as_deref(), Some("*"));
+    let response = preflight(other, Method::POST, "/customers").await.unwrap();
+    assert_eq!(allowed(response), None);
+    let response = preflight(other, Method::GET, "/admin/jobs").await.unwrap();
+    assert_eq!(allowed(response), None);
+
+    let response = preflight(ADMIN_ORIGIN, Method::POST, "/customers")
+        .await
+        .unwrap();
+    assert_eq!(allowed(response).as_deref(), Some(ADMIN_ORIGIN));
+}
+
+#[tokio::test]
+async fn audit_log_records_the_client_behind_trusted_proxies() {
+    let api = Api::spawn().await;
+    let create = |name: &'static str, forwarded_for: Option<&'static str>| {
+        let mut request = api
+            .client
+            .post(api.url("/categories"))
+            .header("X-Actor", "alice")
+            .json(&json!({ "product_category_name": name }));
+        if let Some(forwarded_for) = forwarded_for {
+            request = request.header("X-Forwarded-For", forwarded_for);
+        }
+        request.send()
+    };
+    let last_actor = || async {
+        let (status, audit) = api.get("/audit?entity=category&page_size=1").await;
+        assert_eq!(status, StatusCode::OK);
+        audit["data"][0]["actor"].clone()
+    };
+
+    // The test server trusts 127.0.0.1, so the forwarded chain names the client
+    let response = create("brinquedos_proxy", Some("203.0.113.7, 127.0.0.1"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    assert_eq!(last_actor().await, "alice (203.0.113.7)");
+
+    let response = create("brinquedos_direto", None).await.unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    assert_eq!(last_actor().await, "alice (127.0.0.1)");
+}
+
+#[tokio::test]
+async fn api_versions_are_negotiated_and_legacy_paths_deprecated() {
+    let api = Api::spawn().await;
+    api.create_customer().await;
+
+    let response = api.client.get(api.url("/customers")).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["api-version"], "1");
+    assert!(response.headers().get("deprecation").is_none());
+
+    let response = api
+        .client
+        .get(api.app.url("/customers?state=SP"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["deprecation"], "@1792108800");
+    assert!(
+        response
+            .headers()
+            .get_all("link")
+            .iter()
+            .any(|link| link == "</api/v1/customers?state=SP>; rel=\"successor-version\"")
+    );
+
+    let negotiated = |version: &'static str| {
+        api.client
+            .get(api.app.url("/api/customers"))
+            .header("API-Version", version)
+            .send()
+    };
+    let response = negotiated("1").await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["api-version"], "1");
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["meta"]["total_records"], 1);
+
+    let response = negotiated("9").await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn customer_routes_cover_the_customer_lifecycle() {
+    let api = Api::spawn().await;
+    let customer_id = api.create_customer().await;
+    let path = format!("/customers/{customer_id}");
+
+    let (status, customer) = api.get(&path).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(customer["customer_state"], "SP");
+
+    let (status, page) = api.get("/customers?state=SP").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(page["meta"]["total_records"], 1);
+    assert_eq!(page["data"][0]["customer_id"], customer_id.as_str());
+
+    let (status, page) = api.get("/customers?fields=customer_id,customer_city").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(page["data"][0]["customer_id"], customer_id.as_str());
+    assert!(page["data"][0].get("customer_state").is_none());
+
+    let (status, states) = api.get("/customers/states").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(states[0]["value"], "SP");
+
+    let (status, cities) = api.get("/customers/cities?state=SP").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(cities[0]["count"], 1);
+
+    let (status, updated) = api
+        .put(
+            &path,
+            json!({ "customer_city": "Campinas", "customer_zip_code_prefix": "13010" }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{updated}");
+    assert_eq!(updated["customer_city"], "Campinas");
+
+    let (status, history) = api.get(&format!("{path}/history")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(history.as_array().map(Vec::len), Some(2));
+
+    let order_id = api.create_order(&customer_id).await;
+    let (status, orders) = api.get(&format!("{path}/orders")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(orders["data"][0]["order_id"], order_id.as_str());
+
+    let twin_id = api.create_customer().await;
+    let (status, unique) = api
+        .get("/customers/unique/861eff4711a542e4b93843c6dd7febb0")
+        .await;
+    assert_eq!(status, StatusCode::OK, "{unique}");
+    assert_eq!(unique["customers"].as_array().map(Vec::len), Some(2));
+    assert!(unique.to_string().contains(&twin_id));
+    assert_eq!(unique["orders"][0]["order_id"], order_id.as_str());
+    assert_eq!(unique["first_order_at"], unique["last_order_at"]);
+    let (status, _) = api.get("/customers/unique/nobody").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, duplicates) = api.get("/analytics/duplicate-customers").await;
+    assert_eq!(status, StatusCode::OK, "{duplicates}");
+    assert_eq!(duplicates["customer_rows"], 2);
+    assert_eq!(duplicates["duplicate_rows"], 1);
+    assert_eq!(duplicates["most_duplicated"][0]["customer_rows"], 2);
+
+    api.create_order(&twin_id).await;
+    let (status, merge) = api
+        .post(
+            "/customers/merge",
+            json!({ "source_customer_id": twin_id, "target_customer_id": customer_id }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{merge}");
+    assert_eq!(merge["orders_moved"], 1);
+    let (status, _) = api.get(&format!("/customers/{twin_id}")).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, orders) = api.get(&format!("{path}/orders")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(orders["meta"]["total_records"], 2);
+    let (status, _) = api
+        .post(
+            "/customers/merge",
+            json!({ "source_customer_id": customer_id, "target_customer_id": customer_id }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, export) = api.get(&format!("{path}/export")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(export["customer"]["customer_id"], customer_id.as_str());
+
+    let (status, export) = api.get("/customers/export?format=ndjson").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(export.to_string().contains(&customer_id));
+
+    let (status, _) = api.delete(&path).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = api.get(&path).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, restored) = api.post(&format!("{path}/restore"), json!({})).await;
+    assert_eq!(status, StatusCode::OK, "{restored}");
+    assert!(restored["deleted_at"].is_null());
+
+    let amendments = format!("/orders/{order_id}/amendments");
+    let (status, amendment) = api
+        .post(&amendments, json!({ "shipping_zip_code_prefix": "20040" }))
+        .await;
+    assert_eq!(status, StatusCode::CREATED, "{amendment}");
+
+    let (status, anonymized) = api.post(&format!("{path}/anonymize"), json!({})).await;
+    assert_eq!(status, StatusCode::OK, "{anonymized}");
+    assert_ne!(anonymized["customer_city"], "Campinas");
+    assert_eq!(anonymized["customer_state"], "XX");
+    let (status, history) = api.get(&amendments).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        history[0]["changes"]["shipping_zip_code_prefix"],
+        json!({ "redacted": true })
+    );
+
+    let (status, amendment) = api
+        .post(&amendments, json!({ "shipping_zip_code_prefix": "20040" }))
+        .await;
+    assert_eq!(status, StatusCode::CREATED, "{amendment}");
+    assert_eq!(
+        amendment["changes"]["shipping_zip_code_prefix"]["from"],
+        "00000"
+    );
+}
+
+#[tokio::test]
+async fn customer_routes_reject_invalid_input() {
+    let api = Api::spawn().await;
+
+    let (status, _) = api
+        .post(
+            "/customers",
+            json!({
+                "customer_unique_id": "861eff4711a542e4b93843c6dd7febb0",
+                "customer_zip_code_prefix": "1",
+                "customer_city": "São Paulo",
+                "customer_state": "SP"
+            }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // Malformed CEPs are rejected before the lookup provider is asked.
+    let (status, _) = api
+        .post(
+            "/customers",
+            json!({
+                "customer_unique_id": "861eff4711a542e4b93843c6dd7febb0",
+                "customer_state": "SP",
+                "customer_cep": "0131-1000"
+            }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _) = api.get("/cep/1234").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _) = api.get("/customers/00000000000000000000000000000000").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _) = api
+        .get("/customers/00000000000000000000000000000000/nearby-sellers")
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _) = api
+        .get("/customers/00000000000000000000000000000000/nearby-sellers?radius_km=0")
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn customer_recommendations_rank_state_best_sellers() {
+    let api = Api::spawn().await;
+    let customer_id = api.create_customer().await;
+    let seller_id = api.create_seller().await;
+    let bought = api.create_product("cama_mesa_banho").await;
+    let popular = api.create_product("cama_mesa_banho").await;
+    let niche = api.create_product("cama_mesa_banho").await;
+    let other_category = api.create_product("esporte_lazer").await;
+
+    let order_id = api.create_order(&customer_id).await;
+    api.add_item(&order_id, &bought, &seller_id).await;
+
+    // Another shopper in the same state.
+    let (status, neighbour) = api
+        .post(
+            "/customers",
+            json!({
+                "customer_unique_id": "0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a",
+                "customer_zip_code_prefix": "01311",
+                "customer_city": "São Paulo",
+                "customer_state": "SP"
+            }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CREATED, "{neighbour}");
+    let neighbour_id = id(&neighbour, "customer_id");
+    for product_id in [&popular, &popular, &niche, &other_category, &bought] {
+        let order_id = api.create_order(&neighbour_id).await;
+        api.add_item(&order_id, product_id, &seller_id).await;
+    }
+
+    let path = format!("/customers/{customer_id}/recommendations");
+    let (status, recommended) = api.get(&path).await;
+    assert_eq!(status, StatusCode::OK, "{recommended}");
+    let ids: Vec<&str> = recommended
+        .as_array()
+        .expect("recommendations")
+        .iter()
+        .filter_map(|product| product["product_id"].as_str())
+        .collect();
+    assert_eq!(ids, [popular.as_str(), niche.as_str()]);
+    assert_eq!(recommended[0]["state_order_count"], 2);
+
+    let (status, recommended) = api.get(&format!("{path}?limit=1")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(recommended.as_array().map(Vec::len), Some(1));
+
+    let (status, _) = api
+        .get("/customers/00000000000000000000000000000000/recommendations")
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn customer_addresses_pick_the_destination_of_new_orders() {
+    let api = Api::spawn().await;
+    let customer_id = api.create_customer().await;
+    let path = format!("/customers/{customer_id}/addresses");
+
+    let (status, home) = api
+        .post(
+            &path,
+            json!({
+                "label": "home",
+                "zip_code_prefix": "20040",
+                "city": "Rio de Janeiro",
+                "state": "RJ"
+            }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CREATED, "{home}");
+    assert_eq!(home["is_default"], true, "the first address is the default");
+    let home_id = home["address_id"].as_i64().expect("address_id");
+
+    let (status, work) = api
+        .post(
+            &path,
+            json!({
+                "label": "work",
+                "zip_code_prefix": "30130",
+                "city": "Belo Horizonte",
+                "state": "MG"
+            }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CREATED, "{work}");
+    assert_eq!(work["is_default"], false);
+    let work_id = work["address_id"].as_i64().expect("address_id");
+
+    let (status, _) = api
+        .post(
+            &path,
+            json!({ "label": "", "zip_code_prefix": "x", "city": "x", "state": "SP" }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // Orders without an address ship to the default one.
+    let seller_id = api.create_seller().await;
+    let product_id = api.create_product("cama_mesa_banho").await;
+    let order_id = api.create_order(&customer_id).await;
+    api.add_item(&order_id, &product_id, &seller_id).await;
+    let (status, quote) = api
+        .send(
+            Method::POST,
+            &format!("/orders/{order_id}/freight-quote"),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{quote}");
+    assert_eq!(quote["destination_zip_code_prefix"], "20040");
+
+    let (status, work) = api
+        .put(&format!("{path}/{work_id}"), json!({ "is_default": true }))
+        .await;
+    assert_eq!(status, StatusCode::OK, "{work}");
+    assert_eq!(work["is_default"], true);
+    let (status, addresses) = api.get(&path).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(addresses[0]["address_id"], work_id);
+    assert_eq!(addresses[1]["is_default"], false);
+
+    let purchased = Utc::now().naive_utc();
+    let order = |shipping_address_id: i64, customer_id: &str| {
+        json!({
+            "customer_id": customer_id,
+            "order_status": "approved",
+            "order_purchase_timestamp": purchased,
+            "order_approved_at": purchased,
+            "order_estimated_delivery_date": purchased + Duration::days(10),
+            "shipping_address_id": shipping_address_id
+        })
+    };
+    let (status, placed) = api.post("/orders", order(home_id, &customer_id)).await;
+    assert_eq!(status, StatusCode::CREATED, "{placed}");
+    let placed_id = id(&placed, "order_id");
+    api.add_item(&placed_id, &product_id, &seller_id).await;
+    let (status, quote) = api
+        .send(
+            Method::POST,
+            &format!("/orders/{placed_id}/freight-quote"),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{quote}");
+    assert_eq!(quote["destination_zip_code_prefix"], "20040");
+
+    // Another customer's address can't be picked.
+    let (status, other) = api
+        .post(
+            "/customers",
+            json!({
+                "customer_unique_id": "0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b",
+                "customer_zip_code_prefix": "01311",
+                "customer_city": "São Paulo",
+                "customer_state": "SP"
+            }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CREATED, "{other}");
+    let (status, _) = api
+        .post("/orders", order(home_id, &id(&other, "customer_id")))
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _) = api.delete(&format!("{path}/{home_id}")).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = api.get(&format!("{path}/{home_id}")).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn merging_customers_moves_addresses_and_location_history_to_the_target() {
+    let api = Api::spawn().await;
+    let target_id = api.create_customer().await;
+    let source_id = api.create_customer().await;
+    let address = |label: &str| {
+        json!({
+            "label": label,
+            "zip_code_prefix": "20040",
+            "city": "Rio de Janeiro",
+            "state": "RJ"
+        })
+    };
+    for (customer_id, label) in [(&target_id, "home"), (&source_id, "work")] {
+        let (status, created) = api
+            .post(
+                &format!("/customers/{customer_id}/addresses"),
+                address(label),
+            )
+            .await;
+        assert_eq!(status, StatusCode::CREATED, "{created}");
+    }
+
+    let (status, merge) = api
+        .post(
+            "/customers/merge",
+            json!({ "source_customer_id": source_id, "target_customer_id": target_id }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{merge}");
+
+    let (status, addresses) = api.get(&format!("/customers/{target_id}/addresses")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(addresses.as_array().map(Vec::len), Some(2));
+    assert_eq!(addresses[0]["label"], "home");
+    assert_eq!(addresses[1]["label"], "work");
+    assert_eq!(
+        addresses[1]["is_default"], false,
+        "the target keeps its default"
+    );
+
+    let (status, history) = api.get(&format!("/customers/{target_id}/history")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(history.as_array().map(Vec::len), Some(2));
+    let open = history
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|version| version["valid_to"].is_null())
+        .count();
+    assert_eq!(open, 1, "only the target's own location stays current");
+
+    for (customer_id, key) in [(&source_id, "merged_into"), (&target_id, "merged_from")] {
+        let (status, audit) = api
+            .get(&format!("/audit?entity=customer&id={customer_id}"))
+            .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(audit["data"][0]["action"], "merge");
+        assert!(audit["data"][0]["diff"][key].is_string(), "{audit}");
+        assert_eq!(audit["data"][0]["diff"]["orders_moved"], 0);
+    }
+}
+
+#[tokio::test]
+async fn seller_and_inventory_routes_track_stock() {
+    let api = Api::spawn().await;
+    let seller_id = api.create_seller().await;
+    let product_id = api.create_product("cama_mesa_banho").await;
+
+    let (status, seller) = api.get(&format!("/sellers/{seller_id}")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(seller["seller_state"], "SP");
+
+    let (status, page) = api.get("/sellers?state=SP").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(page["data"][0]["seller_id"], seller_id.as_str());
+
+    let (status, badges) = api.get("/sellers/badges").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(!badges.as_array().expect("thresholds").is_empty());
+
+    // No orders yet: nothing to measure and too few orders to grade.
+    let (status, scorecard) = api.get(&format!("/sellers/{seller_id}/scorecard")).await;
+    assert_eq!(status, StatusCode::OK, "{scorecard}");
+    assert_eq!(scorecard["order_count"], 0);
+    assert!(scorecard["grade"].is_null(), "{scorecard}");
+    assert_eq!(scorecard["metrics"][1]["metric"], "cancellation_rate");
+    assert!(scorecard["metrics"][1]["value"].is_null(), "{scorecard}");
+    let (status, _) = api
+        .get("/sellers/00000000000000000000000000000000/scorecard")
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let locations = format!("/sellers/{seller_id}/locations");
+    let (status, location) = api
+        .post(
+            &locations,
+            json!({ "name": "Main warehouse", "zip_code_prefix": "01000" }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CREATED, "{location}");
+    let location_id = location["location_id"].as_i64().expect("location id");
+
+    let (status, listed) = api.get(&locations).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(listed[0]["location_id"], location_id);
+
+    let stock = format!("/locations/{location_id}/stock/{product_id}");
+    let (status, set) = api.put(&stock, json!({ "quantity": 5 })).await;
+    assert_eq!(status, StatusCode::OK, "{set}");
+    assert_eq!(set["quantity"], 5);
+
+    let (status, adjusted) = api
+        .post(&format!("{stock}/adjustments"), json!({ "delta": -2 }))
+        .await;
+    assert_eq!(status, StatusCode::OK, "{adjusted}");
+    assert_eq!(adjusted["quantity"], 3);
+
+    let (status, _) = api
+        .post(&format!("{stock}/adjustments"), json!({ "delta": -10 }))
+        .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    let (status, levels) = api.get(&format!("/locations/{location_id}/stock")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(levels[0]["quantity"], 3);
+
+    let (status, totals) = api.get(&format!("/products/{product_id}/stock")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(totals["total_quantity"], 3);
+    assert_eq!(totals["locations"][0]["seller_id"], seller_id.as_str());
+}
+
+#[tokio::test]
+async fn order_routes_cover_items_amendments_and_status() {
+    let api = Api::spawn().await;
+    let customer_id = api.create_customer().await;
+    let seller_id = api.create_seller().await;
+    let product_id = api.create_product("cama_mesa_banho").await;
+    let replacement_id = api.create_product("cama_mesa_banho").await;
+    let order_id = api.create_order(&customer_id).await;
+    let path = format!("/orders/{order_id}");
+
+    api.add_item(&order_id, &product_id, &seller_id).await;
+
+    let (status, order) = api.get(&path).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(order["order_status"], "approved");
+
+    let (status, page) = api.get("/orders?status=approved").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(page["data"][0]["order_id"], order_id.as_str());
+
+    let (status, statuses) = api.get("/orders/statuses").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(statuses[0]["value"], "approved");
+
+    let (status, sample) = api.get("/orders/sample?n=5&stratify_by=state").await;
+    assert_eq!(status, StatusCode::OK, "{sample}");
+    assert_eq!(sample["orders"][0]["order_id"], order_id.as_str());
+
+    let (status, export) = api.get("/orders/export").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(export.to_string().contains(&order_id));
+
+    let (status, products) = api.get(&format!("{path}/products")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(products["products"][0]["product_id"], product_id.as_str());
+
+    let (status, payments) = api.get(&format!("{path}/payments")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(payments, json!([]));
+
+    let (status, reviews) = api.get(&format!("{path}/reviews")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(reviews, json!([]));
+
+    let (status, change) = api
+        .get(&format!("{path}/status?since_version=0&wait=1s"))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(change["changed"], true);
+
+    let (status, amendment) = api
+        .post(
+            &format!("{path}/amendments"),
+            json!({
+                "shipping_zip_code_prefix": "20040",
+                "item_swaps": [{ "order_item_id": 1, "product_id": replacement_id }]
+            }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CREATED, "{amendment}");
+
+    let (status, amendments) = api.get(&format!("{path}/amendments")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(amendments.as_array().map(Vec::len), Some(1));
+
+    let (status, products) = api.get(&format!("{path}/products")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        products["products"][0]["product_id"],
+        replacement_id.as_str()
+    );
+
+    let (status, quote) = api
+        .send(Method::POST, &format!("{path}/freight-quote"), None)
+        .await;
+    assert_eq!(status, StatusCode::OK, "{quote}");
+    assert_eq!(quote["items"][0]["selected"]["service"], "standard");
+    assert_eq!(quote["total_freight"], "22.20");
+    assert_eq!(quote["amendment"], Value::Null);
+
+    let (status, quote) = api
+        .post(
+            &format!("{path}/freight-quote"),
+            json!({ "service": "express", "apply": true }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{quote}");
+    assert_eq!(quote["amendment"]["new_freight"], "39.40");
+
+    let (status, amendments) = api.get(&format!("{path}/amendments")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(amendments.as_array().map(Vec::len), Some(2));
+
+    // The test database has no geolocation rows, so no prefix can be located.
+    let (status, estimate) = api
+        .post(
+            "/freight/estimate",
+            json!({
+                "product_id": product_id,
+                "origin_zip_code_prefix": "01311",
+                "destination_zip_code_prefix": "01310"
+            }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{estimate}");
+    assert!(
+        estimate["error"]
+            .as_str()
+            .is_some_and(|error| error.contains("no coordinates for CEP prefix 01311"))
+    );
+
+    let (status, coupon) = api
+        .post(
+            "/coupons",
+            json!({ "code": "welcome10", "discount_type": "percentage", "value": "10", "max_uses": 1 }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CREATED, "{coupon}");
+    assert_eq!(coupon["code"], "WELCOME10");
+
+    let apply = format!("{path}/apply-coupon");
+    let (status, _) = api.post(&apply, json!({ "code": "NOPE" })).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, totals) = api.post(&apply, json!({ "code": "welcome10" })).await;
+    assert_eq!(status, StatusCode::OK, "{totals}");
+    assert_eq!(totals["discounts"][0]["amount"], "5.99");
+    assert_eq!(totals["discounts"][0]["currency"], "BRL");
+    assert_eq!(totals["currency"], "BRL");
+
+    let (status, summary) = api.get(&format!("{path}/summary")).await;
+    assert_eq!(status, StatusCode::OK, "{summary}");
+    assert_eq!(summary["discount_total"], "5.99");
+    assert_eq!(summary["order_total"], totals["total_value"]);
+    assert_eq!(summary["payments_total"], "0.00");
+    assert_eq!(summary["outstanding_balance"], totals["total_value"]);
+    assert_eq!(summary["payment_mismatch"], true);
+
+    let (status, _) = api.post(&apply, json!({ "code": "WELCOME10" })).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    let (status, coupon) = api.get("/coupons/welcome10").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(coupon["times_used"], 1);
+
+    let (status, _) = api.get("/orders/00000000000000000000000000000000").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn refund_routes_check_the_order_and_payment() {
+    let api = Api::spawn().await;
+    let customer_id = api.create_customer().await;
+    let order_in = |order_status: &str| {
+        let purchased = Utc::now().naive_utc();
+        json!({
+            "customer_id": customer_id,
+            "order_status": order_status,
+            "order_purchase_timestamp": purchased,
+            "order_approved_at": purchased,
+            "order_estimated_delivery_date": purchased + Duration::days(10)
+        })
+    };
+    let refund = json!({ "payment_sequential": 1, "amount": "10.00" });
+
+    let (status, _) = api.post("/orders/missing/refunds", refund.clone()).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (_, approved) = api.post("/orders", order_in("approved")).await;
+    let approved_id = id(&approved, "order_id");
+    let (status, _) = api
+        .post(&format!("/orders/{approved_id}/refunds"), refund.clone())
+        .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    let (_, delivered) = api.post("/orders", order_in("delivered")).await;
+    let delivered_id = id(&delivered, "order_id");
+    let refunds = format!("/orders/{delivered_id}/refunds");
+    let (status, _) = api
+        .post(
+            &refunds,
+            json!({ "payment_sequential": 1, "amount": "1.234" }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = api.post(&refunds, refund).await;
+    assert_eq!(status, StatusCode::NOT_FOUND, "no payments were recorded");
+
+    let (status, listed) = api.get(&refunds).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(listed, json!([]));
+    let (_, totals) = api.get(&format!("/orders/{delivered_id}/products")).await;
+    assert_eq!(totals["refunded_value"], "0.00");
+}
+
+#[tokio::test]
+async fn payment_routes_authorize_capture_and_take_notifications() {
+    let api = Api::spawn().await;
+    let customer_id = api.create_customer().await;
+    let awaiting_payment = |customer_id: &str| {
+        let purchased = Utc::now().naive_utc();
+        json!({
+            "customer_id": customer_id,
+            "order_status": "created",
+            "order_purchase_timestamp": purchased,
+            "order_approved_at": purchased,
+            "order_estimated_delivery_date": purchased + Duration::days(10)
+        })
+    };
+    let (status, order) = api.post("/orders", awaiting_payment(&customer_id)).await;
+    assert_eq!(status, StatusCode::CREATED, "{order}");
+    let order_id = id(&order, "order_id");
+    let transactions = format!("/orders/{order_id}/payment-transactions");
+
+    let (status, transaction) = api
+        .post(
+            &transactions,
+            json!({ "payment_type": "credit_card", "payment_installments": 2, "amount": "72.40" }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CREATED, "{transaction}");
+    assert_eq!(transaction["status"], "authorized");
+    assert_eq!(transaction["provider"], "sandbox");
+    let transaction_id = transaction["transaction_id"]
+        .as_i64()
+        .expect("transaction id");
+    let reference = id(&transaction, "provider_reference");
+
+    let capture = format!("/payment-transactions/{transaction_id}/capture");
+    let (status, transaction) = api.send(Method::POST, &capture, None).await;
+    assert_eq!(status, StatusCode::OK, "{transaction}");
+    assert_eq!(transaction["status"], "captured");
+    let (_, order) = api.get(&format!("/orders/{order_id}")).await;
+    assert_eq!(order["order_status"], "approved");
+
+    // The relay queues the notification and the worker sends it to the log.
+    let mut notifications = Value::Null;
+    for _ in 0..150 {
+        let (status, body) = api
+            .get("/notifications?kind=order_approved&status=sent")
+            .await;
+        assert_eq!(status, StatusCode::OK, "{body}");
+        notifications = body;
+        if notifications["data"][0].is_object() {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+    let notification = &notifications["data"][0];
+    assert_eq!(
+        notification["payload"]["order_id"],
+        order_id.as_str(),
+        "{notifications}"
+    );
+    assert_eq!(notification["channel"], "log");
+    assert_eq!(notification["attempts"], 1);
+    let notification_id = notification["notification_id"]
+        .as_i64()
+        .expect("notification id");
+    let (status, _) = api.get(&format!("/notifications/{notification_id}")).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = api.send(Method::POST, &capture, None).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    let notify = |body: &str, signature: String| {
+        api.client
+            .post(api.url("/payments/webhook"))
+            .header(SandboxPaymentProvider::SIGNATURE_HEADER, signature)
+            .header("Content-Type", "application/json")
+            .body(body.to_string())
+            .send()
+    };
+    let body = json!({ "reference": reference, "status": "refunded" }).to_string();
+    let signature = format!(
+        "sha256={}",
+        SandboxPaymentProvider::sign(PAYMENT_WEBHOOK_SECRET, body.as_bytes())
+    );
+
+    let response = notify(&body, "sha256=00".to_string())
+        .await
+        .expect("webhook");
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let response = notify(&body, signature.clone()).await.expect("webhook");
+    assert_eq!(response.status(), StatusCode::OK);
+    let result: Value = response.json().await.expect("webhook result");
+    assert_eq!(result["status"], "refunded");
+    assert_eq!(result["applied"], true);
+
+    let response = notify(&body, signature).await.expect("webhook");
+    let result: Value = response.json().await.expect("webhook result");
+    assert_eq!(result["applied"], false);
+
+    let (status, listed) = api.get(&transactions).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(listed.as_array().map(Vec::len), Some(1));
+    assert_eq!(listed[0]["status"], "refunded");
+
+    // The sandbox declines payments of an undefined type, which cancels the order.
+    let (_, order) = api.post("/orders", awaiting_payment(&customer_id)).await;
+    let declined_id = id(&order, "order_id");
+    let (status, transaction) = api
+        .post(
+            &format!("/orders/{declined_id}/payment-transactions"),
+            json!({ "payment_type": "not_defined", "payment_installments": 1, "amount": "10.00" }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CREATED, "{transaction}");
+    assert_eq!(transaction["status"], "failed");
+    let (_, order) = api.get(&format!("/orders/{declined_id}")).await;
+    assert_eq!(order["order_status"], "canceled");
+}
+
+#[tokio::test]
+async fn order_stream_pushes_new_orders() {
+    let api = Api::spawn().await;
+    let customer_id = api.create_customer().await;
+
+    let mut stream = api
+        .client
+        .get(api.url("/orders/stream"))
+        .send()
+        .await
+        .expect("request failed");
+    assert_eq!(stream.status(), StatusCode::OK);
+    assert_eq!(
+        stream.headers()["content-type"].to_str().ok(),
+        Some("text/event-stream")
+    );
+
+    let order_id = api.create_order(&customer_id).await;
+
+    let mut received = String::new();
+    while !received.contains("\n\n") {
+        let chunk = stream
+            .chunk()
+            .await
+            .expect("stream failed")
+            .expect("stream ended");
+        received.push_str(&String::from_utf8_lossy(&chunk));
+    }
+    assert!(received.starts_with("event: order.created\n"), "{received}");
+    assert!(received.contains(&order_id), "{received}");
+}
+
+#[tokio::test]
+async fn product_and_category_routes() {
+    let api = Api::spawn().await;
+    let product_id = api.create_product("esporte_lazer").await;
+
+    let (status, product) = api.get(&format!("/products/{product_id}")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(product["product_category_name"], "esporte_lazer");
+
+    let (status, page) = api
+        .get("/products?category_name=esporte_lazer&max_weight_g=1000")
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(page["data"][0]["product_id"], product_id.as_str());
+
+    // Catalog fields start empty, and the product on sale.
+    assert_eq!(product["price"], Value::Null);
+    assert_eq!(product["active"], true);
+    let (status, product) = api
+        .put(
+            &format!("/products/{product_id}"),
+            json!({ "product_name": "Bola de futebol", "price": "89.9", "active": false }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{product}");
+    assert_eq!(product["price"], "89.90");
+    assert_eq!(product["currency"], "BRL");
+    let (_, product) = api.get(&format!("/products/{product_id}")).await;
+    assert_eq!(product["product_name"], "Bola de futebol");
+    assert_eq!(product["active"], false);
+
+    let (status, _) = api.put(&format!("/products/{product_id}"), json!({})).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = api
+        .put(&format!("/products/{product_id}"), json!({ "price": "-1" }))
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (_, page) = api
+        .get("/products?active=false&min_price=80&max_price=90")
+        .await;
+    assert_eq!(page["data"][0]["product_id"], product_id.as_str());
+    let (_, page) = api.get("/products?active=true&min_price=80").await;
+    assert_eq!(page["data"], json!([]));
+    let (status, _) = api.get("/products?min_price=90&max_price=80").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, categories) = api.get("/products/categories").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(categories[0]["value"], "esporte_lazer");
+
+    let (status, export) = api.get("/products/export?format=ndjson").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(export.to_string().contains(&product_id));
+
+    // Similarity is off unless SIMILARITY_ENABLED is set.
+    let (status, _) = api.get(&format!("/products/{product_id}/similar")).await;
+    assert_eq!(status, StatusCode::NOT_IMPLEMENTED);
+    let (status, _) = api.post("/products/embeddings/refresh", json!({})).await;
+    assert_eq!(status, StatusCode::NOT_IMPLEMENTED);
+
+    let (status, category) = api
+        .post(
+            "/categories",
+            json!({ "product_category_name": "brinquedos_teste" }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CREATED, "{category}");
+
+    let (status, _) = api
+        .post(
+            "/categories",
+            json!({ "product_category_name": "brinquedos_teste" }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    let (status, category) = api.get("/categories/brinquedos_teste").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(category["product_category_name_english"], Value::Null);
+
+    let (status, category) = api
+        .put(
+            "/categories/brinquedos_teste",
+            json!({ "product_category_name_english": "test_toys" }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{category}");
+    assert_eq!(category["product_category_name_english"], "test_toys");
+    // The edit drops the cached lookup.
+    let (_, category) = api.get("/categories/brinquedos_teste").await;
+    assert_eq!(category["product_category_name_english"], "test_toys");
+
+    let (status, _) = api.delete("/categories/brinquedos_teste").await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = api.get("/categories/brinquedos_teste").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = api.delete("/categories/brinquedos_teste").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn product_images_are_stored_and_counted() {
+    let api = Api::spawn().await;
+    let product_id = api.create_product("esporte_lazer").await;
+    let (_, product) = api.get(&format!("/products/{product_id}")).await;
+    let photos = product["product_photos_qty"].as_i64().unwrap();
+
+    let upload = |parts: &[(&str, &str, &str)]| {
+        let mut body = String::new();
+        for (name, content_type, content) in parts {
+            body.push_str(&format!(
+                "--XyZ\r\nContent-Disposition: form-data; name=\"files\"; filename=\"{name}\"\r\nContent-Type: {content_type}\r\n\r\n{content}\r\n"
+            ));
+        }
+        body.push_str("--XyZ--\r\n");
+        api.client
+            .post(api.url(&format!("/products/{product_id}/images")))
+            .header("content-type", "multipart/form-data; boundary=XyZ")
+            .body(body)
+            .send()
+    };
+
+    let response = upload(&[
+        ("front.png", "image/png", "png"),
+        ("back.jpg", "image/jpeg", "jpg"),
+    ])
+    .await
+    .unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let images: Value = response.json().await.unwrap();
+    assert_eq!(images[0]["position"], 1);
+    assert_eq!(images[1]["position"], 2);
+    assert!(images[0]["url"].as_str().unwrap().ends_with(".png"));
+    assert!(images[0].get("storage_key").is_none());
+
+    let (_, product) = api.get(&format!("/products/{product_id}")).await;
+    assert_eq!(product["product_photos_qty"], photos + 2);
+
+    // Unsupported types are refused before anything is stored.
+    let response = upload(&[("notes.txt", "text/plain", "hi")]).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let response = api
+        .client
+        .post(api.url(&format!("/products/{product_id}/images")))
+        .json(&json!({}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    // Deleting the first image moves the second up.
+    let first = images[0]["image_id"].as_i64().unwrap();
+    let (status, _) = api
+        .delete(&format!("/products/{product_id}/images/{first}"))
+        .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, remaining) = api.get(&format!("/products/{product_id}/images")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(remaining.as_array().unwrap().len(), 1);
+    assert_eq!(remaining[0]["position"], 1);
+    let (_, product) = api.get(&format!("/products/{product_id}")).await;
+    assert_eq!(product["product_photos_qty"], photos + 1);
+
+    let (status, _) = api
+        .delete(&format!("/products/{product_id}/images/{first}"))
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = api
+        .get("/products/00000000000000000000000000000000/images")
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn support_routes_cover_a_case_from_open_to_resolved() {
+    let api = Api::spawn().await;
+    let customer_id = api.create_customer().await;
+    let order_id = api.create_order(&customer_id).await;
+
+    let (status, case) = api
+        .post(
+            "/support/cases",
+            json!({
+                "order_id": order_id,
+                "category": "delivery_delay",
+                "subject": "Late delivery",
+                "message": "My order has not arrived yet."
+            }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CREATED, "{case}");
+    let case_id = case["case_id"].as_i64().expect("case id");
+    let path = format!("/support/cases/{case_id}");
+
+    let (status, message) = api
+        .post(
+            &format!("{path}/messages"),
+            json!({ "author_type": "agent", "body": "It ships tomorrow." }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CREATED, "{message}");
+
+    let (status, case) = api.get(&path).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(!case["first_responded_at"].is_null());
+
+    let (status, page) = api.get("/support/cases?status=open").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(page["data"][0]["case_id"], case_id);
+
+    let (status, case) = api.put(&path, json!({ "status": "resolved" })).await;
+    assert_eq!(status, StatusCode::OK, "{case}");
+    assert!(!case["resolved_at"].is_null());
+
+    let (status, volume) = api.get("/analytics/support").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(volume[0]["category"], "delivery_delay");
+    assert_eq!(volume[0]["resolved_cases"], 1);
+
+    let (status, _) = api.delete(&path).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = api.get(&path).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+/// The delivery worker is off in tests, so queued deliveries stay pending.
+#[tokio::test]
+async fn webhook_routes_queue_deliveries_for_subscribed_events() {
+    let api = Api::spawn().await;
+
+    let (status, _) = api
+        .post(
+            "/webhooks",
+            json!({ "url": "ftp://example.com/hook", "events": ["order.created"] }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, webhook) = api
+        .post(
+            "/webhooks",
+            json!({
+                "url": "http://127.0.0.1:9/hook",
+                "events": ["order.created", "payment.created", "review.created"]
+            }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CREATED, "{webhook}");
+    assert!(
+        webhook["secret"]
+            .as_str()
+            .expect("secret")
+            .starts_with("whsec_")
+    );
+    let webhook_id = webhook["subscription_id"]
+        .as_i64()
+        .expect("subscription id");
+    let path = format!("/webhooks/{webhook_id}");
+
+    let (status, webhook) = api.get(&path).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(webhook.get("secret").is_none());
+    assert_eq!(webhook["payload_template"], "full");
+
+    let (status, slim) = api
+        .post(
+            "/webhooks",
+            json!({
+                "url": "http://127.0.0.1:9/slim",
+                "events": ["order.created"],
+                "customer_states": ["SP"],
+                "payload_template": "slim"
+            }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CREATED, "{slim}");
+    let slim_path = format!("/webhooks/{}", slim["subscription_id"]);
+    let (status, elsewhere) = api
+        .post(
+            "/webhooks",
+            json!({
+                "url": "http://127.0.0.1:9/rj",
+                "events": ["order.created"],
+                "customer_states": ["RJ"]
+            }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CREATED, "{elsewhere}");
+    let elsewhere_path = format!("/webhooks/{}", elsewhere["subscription_id"]);
+
+    let customer_id = api.create_customer().await;
+    let order_id = api.create_order(&customer_id).await;
+
+    // The outbox relay queues the delivery once it has published the event.
+    let mut deliveries = Value::Null;
+    for _ in 0..100 {
+        let (status, body) = api.get(&format!("{path}/deliveries?status=pending")).await;
+        assert_eq!(status, StatusCode::OK, "{body}");
+        deliveries = body;
+        if deliveries["data"][0].is_object() {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+    assert_eq!(
+        deliveries["data"][0]["event"], "order.created",
+        "{deliveries}"
+    );
+    assert_eq!(
+        deliveries["data"][0]["payload"]["order_id"],
+        order_id.as_str()
+    );
+    let delivery_id = deliveries["data"][0]["delivery_id"]
+        .as_i64()
+        .expect("delivery id");
+    let (status, delivery) = api.get(&format!("{path}/deliveries/{delivery_id}")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(delivery["attempts"], 0);
+
+    // Every subscription to the event gets the whole event queued; the delivery worker,
+    // disabled here, applies their state filters and templates when it sends.
+    let (status, deliveries) = api.get(&format!("{slim_path}/deliveries")).await;
+    assert_eq!(status, StatusCode::OK, "{deliveries}");
+    assert_eq!(
+        deliveries["data"][0]["payload"]["order_id"],
+        order_id.as_str()
+    );
+    assert_eq!(deliveries["data"][0]["payload"]["order_status"], "approved");
+    let (status, deliveries) = api.get(&format!("{elsewhere_path}/deliveries")).await;
+    assert_eq!(status, StatusCode::OK, "{deliveries}");
+    assert_eq!(deliveries["meta"]["total_records"], 1, "{deliveries}");
+
+    let (status, _) = api.delete(&path).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = api.get(&format!("{path}/deliveries")).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn admin_and_analytics_routes() {
+    let api = Api::spawn().await;
+    let customer_id = api.create_customer().await;
+    let seller_id = api.create_seller().await;
+    let product_id = api.create_product("cama_mesa_banho").await;
+    let order_id = api.create_order(&customer_id).await;
+    api.add_item(&order_id, &product_id, &seller_id).await;
+
+    let (status, stats) = api.get("/stats/today").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(stats["orders_count"].is_i64());
+
+    let (status, mismatches) = api.get("/analytics/reconciliation?page_size=100").await;
+    assert_eq!(status, StatusCode::OK);
+    let unpaid = mismatches["data"]
+        .as_array()
+        .expect("data")
+        .iter()
+        .find(|row| row["order_id"] == order_id.as_str())
+        .expect("the unpaid order is listed");
+    assert_eq!(unpaid["payments_total"], "0.00");
+    let (status, _) = api
+        .get("/analytics/reconciliation?from=2026-02-01&to=2026-01-01")
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // Payments only arrive with the Olist import.
+    let (status, payments) = api
+        .get("/analytics/payments?from=2026-01-01&to=2026-12-31")
+        .await;
+    assert_eq!(status, StatusCode::OK, "{payments}");
+    assert_eq!(payments["payment_count"], 0);
+    assert_eq!(payments["total_value"], "0.00");
+    assert_eq!(payments["methods"], json!([]));
+    let (status, _) = api
+        .get("/analytics/payments?from=2026-02-01&to=2026-01-01")
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, diagnostics) = api.get("/admin/diagnostics").await;
+    assert_eq!(status, StatusCode::OK, "{diagnostics}");
+
+    let (status, jobs) = api.get("/admin/jobs").await;
+    assert_eq!(status, StatusCode::OK);
+    let names: Vec<&str> = jobs
+        .as_array()
+        .expect("job list")
+        .iter()
+        .filter_map(|job| job["name"].as_str())
+        .collect();
+    assert_eq!(
+        names,
+        [
+            "apply_retention",
+            "evaluate_data_quality",
+            "flag_late_orders",
+            "refresh_analytics_views",
+            "retry_failed_webhooks",
+            "seller_badges",
+            "tag_review_sentiment"
+        ]
+    );
+    assert_eq!(jobs[1]["schedule"], "0 15 2 * * *");
+    assert_eq!(jobs[2]["schedule"], "0 45 * * * *");
+    assert_eq!(jobs[3]["schedule"], "0 0 * * * *");
+    assert!(jobs[5]["schedule"].is_null(), "{jobs}");
+
+    let (status, retention) = api.get("/admin/retention").await;
+    assert_eq!(status, StatusCode::OK, "{retention}");
+    assert_eq!(retention["dry_run"], true);
+    assert_eq!(retention["rules"][0]["rule"], "delete_old_reviews");
+    assert!(retention["rules"][0]["cutoff"].is_null(), "{retention}");
+    assert_eq!(retention["rules"][1]["affected"], 0);
+
+    let (status, reviews) = api.get("/reviews?sentiment=negative").await;
+    assert_eq!(status, StatusCode::OK, "{reviews}");
+    assert_eq!(reviews["meta"]["total_records"], 0);
+    let (status, _) = api.get("/reviews?sentiment=angry").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // Nothing is flagged until the late-delivery job runs.
+    let (status, late) = api.get("/orders/late").await;
+    assert_eq!(status, StatusCode::OK, "{late}");
+    assert_eq!(late["meta"]["total_records"], 0);
+
+    let (status, stats) = api.get("/admin/stats?exact=true").await;
+    assert_eq!(status, StatusCode::OK, "{stats}");
+    assert_eq!(stats["database"]["row_counts_estimated"], false);
+    assert!(stats["database"]["last_migration"]["version"].is_i64());
+    let customers = stats["database"]["tables"]
+        .as_array()
+        .expect("table list")
+        .iter()
+        .find(|table| table["table_name"] == "customers")
+        .expect("customers table");
+    assert!(customers["row_count"].as_i64() >= Some(1), "{customers}");
+
+    let (status, read_only) = api
+        .put(
+            "/admin/read-only",
+            json!({ "enabled": true, "reason": "nightly import" }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{read_only}");
+    assert_eq!(read_only["enabled"], true);
+    let (status, refused) = api.delete(&format!("/customers/{customer_id}")).await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert!(
+        refused["error"]
+            .as_str()
+            .is_some_and(|error| error.contains("nightly import")),
+        "{refused}"
+    );
+    let (status, refused) = api.post("/admin/seed?customers=1", json!({})).await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE, "{refused}");
+    let (status, _) = api.get(&format!("/customers/{customer_id}")).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, read_only) = api
+        .put("/admin/read-only", json!({ "enabled": false }))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(read_only["reason"].is_null());
+
+    let (status, audit) = api
+        .get(&format!("/audit?entity=customer&id={customer_id}"))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(audit["data"][0]["action"], "create");
+
+    let (status, job) = api.post("/admin/maintenance/refresh-all", json!({})).await;
+    assert_eq!(status, StatusCode::ACCEPTED, "{job}");
+    let job_id = job["job_id"].as_u64().expect("job id");
+    let (status, _) = api.get(&format!("/admin/maintenance/jobs/{job_id}")).await;
+    assert_eq!(status, StatusCode::OK);
+
+    api.get(&format!("/products/{product_id}")).await;
+    let (status, flushed) = api.post("/admin/cache/flush", json!({})).await;
+    assert_eq!(status, StatusCode::OK, "{flushed}");
+    assert!(flushed["lookup_entries"].is_u64());
+
+    let (status, batches) = api.get("/admin/imports").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(batches["data"], json!([]));
+    let (status, _) = api.get("/admin/imports/1").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = api.post("/admin/imports/1/rollback", json!({})).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = api.get("/load-data/jobs/1").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = api.get("/load-data/jobs/1/errors").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _) = api.get("/export/reviews/corpus").await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let response = api
+        .client
+        .get(api.url("/export/reviews/corpus"))
+        .header("x-api-key", CORPUS_API_KEY)
+        .send()
+        .await
+        .expect("request failed");
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "application/x-ndjson");
+}
+
+#[tokio::test]
+async fn archive_route_moves_old_closed_orders_out_of_the_listings() {
+    let api = Api::spawn().await;
+    let customer_id = api.create_customer().await;
+    let seller_id = api.create_seller().await;
+    let product_id = api.create_product("cama_mesa_banho").await;
+    let recent_id = api.create_order(&customer_id).await;
+
+    let purchased = NaiveDate::from_ymd_opt(2016, 10, 4)
+        .and_then(|date| date.and_hms_opt(9, 30, 0))
+        .expect("valid timestamp");
+    let (status, order) = api
+        .post(
+            "/orders",
+            json!({
+                "customer_id": customer_id,
+                "order_status": "delivered",
+                "order_purchase_timestamp": purchased,
+                "order_approved_at": purchased,
+                "order_delivered_customer_date": purchased + Duration::days(6),
+                "order_estimated_delivery_date": purchased + Duration::days(10)
+            }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CREATED, "{order}");
+    let archived_id = id(&order, "order_id");
+    api.add_item(&archived_id, &product_id, &seller_id).await;
+
+    let (status, _) = api
+        .post("/admin/archive?before=2999-01-01", json!({}))
+        .await;
+    assert_eq!(
+        status,
+        StatusCode::BAD_REQUEST,
+        "the cutoff can't be in the future"
+    );
+
+    let (status, job) = api
+        .post("/admin/archive?before=2017-01-01", json!({}))
+        .await;
+    assert_eq!(status, StatusCode::ACCEPTED, "{job}");
+    assert_eq!(job["task"], "archive_orders");
+    assert_eq!(job["before"], "2017-01-01");
+    let job_id = job["job_id"].as_u64().expect("job id");
+    let mut job = job;
+    for _ in 0..50 {
+        if job["status"] != "running" {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        job = api
+            .get(&format!("/admin/maintenance/jobs/{job_id}"))
+            .await
+            .1;
+    }
+    assert_eq!(job["status"], "completed", "{job}");
+    assert_eq!(
+        job["steps"][0]["detail"],
+        "archived 1 order(s) with 1 item(s), 0 payment(s) and 0 review(s)"
+    );
+
+    let (status, orders) = api.get("/orders?page_size=10").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(orders["meta"]["total_records"], 1);
+    assert_eq!(orders["data"][0]["order_id"], recent_id);
+
+    let (status, order) = api.get(&format!("/orders/{archived_id}")).await;
+    assert_eq!(
+        status,
+        StatusCode::OK,
+        "archived orders are still found by id"
+    );
+    assert_eq!(order["order_status"], "delivered");
+    let (status, products) = api.get(&format!("/orders/{archived_id}/products")).await;
+    assert_eq!(status, StatusCode::OK, "{products}");
+    assert_eq!(products["products"][0]["product_id"], product_id);
+    assert_eq!(products["total_value"], "72.40");
+}
+
+#[tokio::test]
+async fn seed_route_generates_the_requested_rows() {
+    let api = Api::spawn().await;
+    let (status, report) = api
+        .post("/admin/seed?customers=20&orders=30&seed=7", json!({}))
+        .await;
+    assert_eq!(status, StatusCode::CREATED, "{report}");
+    assert_eq!(report["seed"], 7);
+    assert_eq!(report["customers"], 20);
+    assert_eq!(report["sellers"], 1);
+    assert_eq!(report["products"], 4);
+    assert_eq!(report["orders"], 30);
+    assert_eq!(report["error_count"], 0);
+
+    let (status, customers) = api.get("/customers?page_size=1").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(customers["meta"]["total_records"], 20);
+    let (status, orders) = api.get("/orders?page_size=1").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(orders["meta"]["total_records"], 30);
+}
+
+#[tokio::test]
+async fn data_quality_routes_flag_incomplete_rows() {
+    let api = Api::spawn().await;
+    api.create_product("cama_mesa_banho").await;
+    let (status, weightless) = api
+        .post(
+            "/products",
+            json!({
+                "product_category_name": "cama_mesa_banho",
+                "product_name_lenght": 40,
+                "product_description_lenght": 300,
+                "product_photos_qty": 2,
+                "product_weight_g": 0,
+                "product_length_cm": 20,
+                "product_height_cm": 10,
+                "product_width_cm": 15
+            }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CREATED, "{weightless}");
+
+    let (status, report) = api.get("/admin/data-quality/products").await;
+    assert_eq!(status, StatusCode::OK, "{report}");
+    assert_eq!(report["checked"], 2);
+    assert_eq!(report["flagged"], 1);
+    assert_eq!(
+        report["issues"][1],
+        json!({ "issue": "missing_weight", "count": 1 })
+    );
+    let (status, flagged) = api
+        .get("/admin/data-quality/products/flagged?issue=missing_weight")
+        .await;
+    assert_eq!(status, StatusCode::OK, "{flagged}");
+    assert_eq!(flagged["meta"]["total_records"], 1);
+    assert_eq!(flagged["data"][0]["product_weight_g"], 0);
+    assert_eq!(flagged["data"][0]["issues"], json!(["missing_weight"]));
+    let (status, flagged) = api
+        .get("/admin/data-quality/products/flagged?issue=missing_category")
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(flagged["data"], json!([]));
+
+    let customer_id = api.create_customer().await;
+    api.create_order(&customer_id).await;
+    let (status, report) = api.get("/admin/data-quality/orders").await;
+    assert_eq!(status, StatusCode::OK, "{report}");
+    assert_eq!(report["flagged"], 1);
+    assert_eq!(
+        report["issues"][0],
+        json!({ "issue": "missing_items", "count": 1 })
+    );
+    let (status, flagged) = api.get("/admin/data-quality/orders/flagged").await;
+    assert_eq!(status, StatusCode::OK, "{flagged}");
+    assert_eq!(flagged["data"][0]["item_count"], 0);
+    assert_eq!(flagged["data"][0]["issues"], json!(["missing_items"]));
+
+    let (status, _) = api
+        .get("/admin/data-quality/orders/flagged?issue=unknown")
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // No rules are configured, so the rule job has nothing to report.
+    let (status, rules) = api.get("/admin/data-quality").await;
+    assert_eq!(status, StatusCode::OK, "{rules}");
+    assert_eq!(rules, json!([]));
+}
+
+#[tokio::test]
+async fn tenants_named_by_header_only_see_their_own_rows() {
+    let app =
+        spawn_test_app_with(&[("TENANT_RESOLVER", "header"), ("TENANTS", "olist,magalu")]).await;
+    let client = Client::new();
+    let url = |path: &str| app.url(&format!("/api/v1{path}"));
+
+    let response = client
+        .post(url("/customers"))
+        .header("x-tenant-id", "olist")
+        .json(&json!({
+            "customer_unique_id": "861eff4711a542e4b93843c6dd7febb0",
+            "customer_zip_code_prefix": "01310",
+            "customer_city": "São Paulo",
+            "customer_state": "SP"
+        }))
+        .send()
+        .await
+        .expect("request failed");
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let customer: Value = response.json().await.expect("response body");
+    let customer_path = format!("/customers/{}", id(&customer, "customer_id"));
+
+    let status_for = |tenant: Option<&'static str>, path: String| {
+        let mut request = client.get(url(&path));
+        if let Some(tenant) = tenant {
+            request = request.header("x-tenant-id", tenant);
+        }
+        async move { request.send().await.expect("request failed").status() }
+    };
+    assert_eq!(
+        status_for(Some("olist"), customer_path.clone()).await,
+        StatusCode::OK
+    );
+    assert_eq!(
+        status_for(Some("magalu"), customer_path.clone()).await,
+        StatusCode::NOT_FOUND
+    );
+    assert_eq!(
+        status_for(Some("amazon"), customer_path.clone()).await,
+        StatusCode::BAD_REQUEST
+    );
+    assert_eq!(
+        status_for(None, customer_path).await,
+        StatusCode::BAD_REQUEST
+    );
+
+    // Probes name no tenant.
+    let response = client
+        .get(app.url("/health/ready"))
+        .send()
+        .await
+        .expect("request failed");
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn concurrency_limit_is_shared_by_every_tenant() {
+    let app = spawn_test_app_with(&[
+        ("TENANT_RESOLVER", "header"),
+        ("TENANTS", "olist,magalu"),
+        ("MAX_CONCURRENT_REQUESTS", "1"),
+        ("LOAD_SHED_RETRY_AFTER_SECONDS", "3"),
+    ])
+    .await;
+    let client = Client::new();
+    let url = |path: &str| app.url(&format!("/api/v1{path}"));
+    let request = |method: Method, tenant: &str, path: &str| {
+        client
+            .request(method, url(path))
+            .header("x-tenant-id", tenant)
+    };
+
+    let customer: Value = request(Method::POST, "olist", "/customers")
+        .json(&json!({
+            "customer_unique_id": "861eff4711a542e4b93843c6dd7febb0",
+            "customer_zip_code_prefix": "01310",
+            "customer_city": "São Paulo",
+            "customer_state": "SP"
+        }))
+        .send()
+        .await
+        .expect("request failed")
+        .json()
+        .await
+        .expect("response body");
+    let purchased = Utc::now().naive_utc();
+    let order: Value = request(Method::POST, "olist", "/orders")
+        .json(&json!({
+            "customer_id": id(&customer, "customer_id"),
+            "order_status": "approved",
+            "order_purchase_timestamp": purchased,
+            "order_approved_at": purchased,
+            "order_estimated_delivery_date": purchased + Duration::days(10)
+        }))
+        .send()
+        .await
+        .expect("request failed")
+        .json()
+        .await
+        .expect("response body");
+    let status_path = format!("/orders/{}/status", id(&order, "order_id"));
+    let current: Value = request(Method::GET, "olist", &status_path)
+        .send()
+        .await
+        .expect("request failed")
+        .json()
+        .await
+        .expect("response body");
+    let version = current["status_version"].as_i64().expect("status version");
+
+    // A long-poll on olist holds the only permit for as long as it waits, and magalu's
+    // router draws on the same one.
+    let waiting = request(
+        Method::GET,
+        "olist",
+        &format!("{status_path}?since_version={version}&wait=3s"),
+    )
+    .send();
+    let waiting = tokio::spawn(waiting);
+    tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+
+    let shed = request(Method::GET, "magalu", "/customers")
+        .send()
+        .await
+        .expect("request failed");
+    assert_eq!(shed.status(), StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(shed.headers()["retry-after"], "3");
+
+    let waited = waiting.await.unwrap().expect("request failed");
+    assert_eq!(waited.status(), StatusCode::OK);
+    let waited: Value = waited.json().await.expect("response body");
+    assert_eq!(waited["changed"], false);
+
+    let served = request(Method::GET, "magalu", "/customers")
+        .send()
+        .await
+        .expect("request failed");
+    assert_eq!(served.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn read_only_mode_is_shared_by_every_tenant() {
+    let app =
+        spawn_test_app_with(&[("TENANT_RESOLVER", "header"), ("TENANTS", "olist,magalu")]).await;
+    let client = Client::new();
+    let url = |path: &str| app.url(&format!("/api/v1{path}"));
+    let request = |method: Method, tenant: &str, path: &str| {
+        client
+            .request(method, url(path))
+            .header("x-tenant-id", tenant)
+    };
+
+    let response = request(Method::PUT, "olist", "/admin/read-only")
+        .json(&json!({ "enabled": true, "reason": "nightly import" }))
+        .send()
+        .await
+        .expect("request failed");
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // Switched on through olist, it refuses magalu's writes too.
+    let response = request(Method::POST, "magalu", "/customers")
+        .json(&json!({
+            "customer_unique_id": "861eff4711a542e4b93843c6dd7febb0",
+            "customer_zip_code_prefix": "01310",
+            "customer_city": "São Paulo",
+            "customer_state": "SP"
+        }))
+        .send()
+        .await
+        .expect("request failed");
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+    let status: Value = request(Method::GET, "magalu", "/admin/read-only")
+        .send()
+        .await
+        .expect("request failed")
+        .json()
+        .await
+        .expect("response body");
+    assert_eq!(status["enabled"], true);
+    assert_eq!(status["reason"], "nightly import");
+}
@@ -0,0 +1,456 @@
+use async_trait::async_trait;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::future::Future;
+use std::hash::Hash;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::warn;
+
+use crate::error::AppResult;
+use crate::ids::ProductId;
+use crate::models::{Category, CepAddress, FilterValue, Product, ZipLocation};
+use crate::tenancy::TenantId;
+
+/// `GET /products` listings and `/products/categories`.
+pub const PRODUCTS: &str = "products";
+/// `GET /analytics/support`.
+pub const SUPPORT_ANALYTICS: &str = "support_analytics";
+/// `GET /stats/today`.
+pub const TODAY_STATS: &str = "today_stats";
+
+/// Key-value store holding cached responses as serialized bytes.
+///
+/// Errors are plain messages: a failing store only costs a cache miss, never a request.
+#[async_trait]
+pub trait CacheStore: Send + Sync {
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, String>;
+    async fn set(&self, key: &str, value: Vec<u8>, ttl: Duration) -> Result<(), String>;
+    /// Removes every key starting with `prefix`, returning how many were removed.
+    async fn delete_prefix(&self, prefix: &str) -> Result<u64, String>;
+    async fn ping(&self) -> Result<(), String>;
+}
+
+/// Read-through cache for the hot read endpoints, grouped into namespaces that the services
+/// invalidate whenever a write could change them. Without a store every read goes to the
+/// database.
+///
+/// Keys start with the tenant, so tenants sharing a store never see, invalidate or flush
+/// each other's entries.
+#[derive(Clone, Default)]
+pub struct ResponseCache {
+    store: Option<Arc<dyn CacheStore>>,
+    ttl: Duration,
+    tenant: TenantId,
+}
+
+impl ResponseCache {
+    pub fn new(store: Arc<dyn CacheStore>, ttl: Duration) -> Self {
+        Self {
+            store: Some(store),
+            ttl,
+            tenant: TenantId::default(),
+        }
+    }
+
+    /// The same store, holding `tenant`'s entries.
+    pub fn for_tenant(&self, tenant: &TenantId) -> Self {
+        Self {
+            tenant: tenant.clone(),
+            ..self.clone()
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.store.is_some()
+    }
+
+    /// Returns the cached value for `key` in `namespace`, or runs `load` and caches its
+    /// result. Errors from `load` are returned as-is and not cached.
+    pub async fn get_or_load<T, F, Fut>(&self, namespace: &str, key: &str, load: F) -> AppResult<T>
+    where
+        T: Serialize + DeserializeOwned,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = AppResult<T>>,
+    {
+        let Some(store) = &self.store else {
+            return load().await;
+        };
+
+        let key = format!("{}:{}:{}", self.tenant, namespace, key);
+        match store.get(&key).await {
+            Ok(Some(bytes)) => match serde_json::from_slice(&bytes) {
+                Ok(value) => return Ok(value),
+                Err(e) => warn!("Discarding unreadable cache entry {}: {}", key, e),
+            },
+            Ok(None) => {}
+            Err(e) => {
+                // The write would most likely fail the same way, so don't wait for it too.
+                warn!("Cache read of {} failed: {}", key, e);
+                return load().await;
+            }
+        }
+
+        let value = load().await?;
+        match serde_json::to_vec(&value) {
+            Ok(bytes) => {
+                if let Err(e) = store.set(&key, bytes, self.ttl).await {
+                    warn!("Cache write of {} failed: {}", key, e);
+                }
+            }
+            Err(e) => warn!("Failed to serialize cache entry {}: {}", key, e),
+        }
+        Ok(value)
+    }
+
+    /// Drops every entry in `namespace`. A failure is logged; the entries then live out
+    /// their TTL.
+    pub async fn invalidate(&self, namespace: &str) {
+        let Some(store) = &self.store else {
+            return;
+        };
+        if let Err(e) = store
+            .delete_prefix(&format!("{}:{}:", self.tenant, namespace))
+            .await
+        {
+            warn!("Cache invalidation of {} failed: {}", namespace, e);
+        }
+    }
+
+    /// Drops every entry of the tenant, returning how many were removed, or `None` without
+    /// a store.
+    pub async fn flush(&self) -> Option<Result<u64, String>> {
+        let store = self.store.as_ref()?;
+        Some(store.delete_prefix(&format!("{}:", self.tenant)).await)
+    }
+
+    /// Round trip to the store, or `None` without one.
+    pub async fn ping(&self) -> Option<Result<(), String>> {
+        let store = self.store.as_ref()?;
+        Some(store.ping().await)
+    }
+}
+
+/// Bounded in-process map holding one kind of [`LookupCache`] entry. Entries expire on their
+/// own after the TTL the store was built with.
+#[async_trait]
+pub trait LookupStore<K, V>: Send + Sync {
+    async fn get(&self, key: &K) -> Option<V>;
+    async fn insert(&self, key: K, value: V);
+    async fn invalidate(&self, key: &K);
+    fn invalidate_all(&self);
+    /// Entries currently held, once pending evictions have run.
+    async fn entry_count(&self) -> u64;
+}
+
+/// Builds the stores behind a [`LookupCache`].
+pub trait LookupStores {
+    /// A store keeping up to `max_entries` entries for `ttl`; 0 disables caching.
+    fn build<K, V>(&self, max_entries: u64, ttl: Duration) -> Arc<dyn LookupStore<K, V>>
+    where
+        K: Hash + Eq + Send + Sync + 'static,
+        V: Clone + Send + Sync + 'static;
+}
+
+/// In-process caches for lookups that practically never change: products are immutable in
+/// this dataset, categories are rarely edited, CEPs are reassigned only exceptionally and
+/// geolocation centroids move only when the geolocation dataset is reloaded.
+/// Each process keeps its own copy, so entries are bounded by size and TTL and dropped on
+/// the writes this process makes.
+#[derive(Clone)]
+pub struct LookupCache {
+    products: Arc<dyn LookupStore<ProductId, Product>>,
+    categories: Arc<dyn LookupStore<String, Category>>,
+    category_values: Arc<dyn LookupStore<(), Vec<FilterValue>>>,
+    /// Results of the CEP lookup provider, by CEP.
+    addresses: Arc<dyn LookupStore<String, CepAddress>>,
+    /// Geolocation centroids, by CEP prefix.
+    locations: Arc<dyn LookupStore<String, ZipLocation>>,
+    max_entries: u64,
+}
+
+impl LookupCache {
+    /// Each lookup keeps up to `max_entries` entries; 0 disables caching.
+    pub fn new(stores: &impl LookupStores, max_entries: u64, ttl: Duration) -> Self {
+        Self {
+            products: stores.build(max_entries, ttl),
+            categories: stores.build(max_entries, ttl),
+            category_values: stores.build(max_entries.min(1), ttl),
+            addresses: stores.build(max_entries, ttl),
+            locations: stores.build(max_entries, ttl),
+            max_entries,
+        }
+    }
+
+    /// Entries each lookup keeps at most; 0 when caching is disabled.
+    pub fn capacity(&self) -> u64 {
+        self.max_entries
+    }
+
+    pub async fn product<F, Fut>(&self, id: &ProductId, load: F) -> AppResult<Option<Product>>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = AppResult<Option<Product>>>,
+    {
+        get_or_load(self.products.as_ref(), id.clone(), load).await
+    }
+
+    pub async fn category<F, Fut>(&self, name: &str, load: F) -> AppResult<Option<Category>>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = AppResult<Option<Category>>>,
+    {
+        get_or_load(self.categories.as_ref(), name.to_string(), load).await
+    }
+
+    pub async fn category_values<F, Fut>(&self, load: F) -> AppResult<Vec<FilterValue>>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = AppResult<Vec<FilterValue>>>,
+    {
+        let values = get_or_load(self.category_values.as_ref(), (), || async {
+            Ok(Some(load().await?))
+        })
+        .await?;
+        Ok(values.unwrap_or_default())
+    }
+
+    pub async fn address<F, Fut>(&self, cep: &str, load: F) -> AppResult<Option<CepAddress>>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = AppResult<Option<CepAddress>>>,
+    {
+        get_or_load(self.addresses.as_ref(), cep.to_string(), load).await
+    }
+
+    /// The centroids of those of `prefixes` that are known. Prefixes not cached are passed to
+    /// `load` in one call.
+    pub async fn locations<F, Fut>(
+        &self,
+        prefixes: &[String],
+        load: F,
+    ) -> AppResult<Vec<ZipLocation>>
+    where
+        F: FnOnce(Vec<String>) -> Fut,
+        Fut: Future<Output = AppResult<Vec<ZipLocation>>>,
+    {
+        let mut found = Vec::with_capacity(prefixes.len());
+        let mut missing = Vec::new();
+        for prefix in prefixes {
+            match self.locations.get(prefix).await {
+                Some(location) => found.push(location),
+                None => missing.push(prefix.clone()),
+            }
+        }
+
+        if !missing.is_empty() {
+            for location in load(missing).await? {
+                self.locations
+                    .insert(
+                        location.geolocation_zip_code_prefix.clone(),
+                        location.clone(),
+                    )
+                    .await;
+                found.push(location);
+            }
+        }
+        Ok(found)
+    }
+
+    /// Caches centroids ahead of their first lookup, e.g. during warm-up.
+    pub async fn prime_locations(&self, locations: Vec<ZipLocation>) {
+        for location in locations {
+            self.locations
+                .insert(location.geolocation_zip_code_prefix.clone(), location)
+                .await;
+        }
+    }
+
+    /// After the geolocation dataset was reloaded.
+    pub fn invalidate_locations(&self) {
+        self.locations.invalidate_all();
+    }
+
+    /// After products were added or removed: the per-category counts change.
+    pub async fn invalidate_category_values(&self) {
+        self.category_values.invalidate(&()).await;
+    }
+
+    /// After products were removed, e.g. by an import rollback.
+    pub async fn invalidate_products(&self) {
+        self.products.invalidate_all();
+        self.invalidate_category_values().await;
+    }
+
+    pub async fn invalidate_product(&self, id: &ProductId) {
+        self.products.invalidate(id).await;
+    }
+
+    pub async fn invalidate_category(&self, name: &str) {
+        self.categories.invalidate(&name.to_string()).await;
+    }
+
+    /// Drops every entry, returning how many there were.
+    pub async fn flush(&self) -> u64 {
+        let entries = self.products.entry_count().await
+            + self.categories.entry_count().await
+            + self.category_values.entry_count().await
+            + self.addresses.entry_count().await
+            + self.locations.entry_count().await;
+
+        self.products.invalidate_all();
+        self.categories.invalidate_all();
+        self.category_values.invalidate_all();
+        self.addresses.invalidate_all();
+        self.locations.invalidate_all();
+        entries
+    }
+}
+
+/// Only found values are cached, so a lookup of a missing key keeps hitting the loader.
+async fn get_or_load<K, V, F, Fut>(
+    cache: &dyn LookupStore<K, V>,
+    key: K,
+    load: F,
+) -> AppResult<Option<V>>
+where
+    V: Clone,
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = AppResult<Option<V>>>,
+{
+    if let Some(value) = cache.get(&key).await {
+        return Ok(Some(value));
+    }
+
+    let value = load().await?;
+    if let Some(value) = &value {
+        cache.insert(key, value.clone()).await;
+    }
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::AppError;
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Default)]
+    struct MapStore {
+        entries: Mutex<HashMap<String, Vec<u8>>>,
+        unreachable: bool,
+    }
+
+    #[async_trait]
+    impl CacheStore for MapStore {
+        async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, String> {
+            if self.unreachable {
+                return Err("connection refused".into());
+            }
+            Ok(self.entries.lock().unwrap().get(key).cloned())
+        }
+
+        async fn set(&self, key: &str, value: Vec<u8>, _ttl: Duration) -> Result<(), String> {
+            self.entries.lock().unwrap().insert(key.to_string(), value);
+            Ok(())
+        }
+
+        async fn delete_prefix(&self, prefix: &str) -> Result<u64, String> {
+            let mut entries = self.entries.lock().unwrap();
+            let before = entries.len();
+            entries.retain(|key, _| !key.starts_with(prefix));
+            Ok((before - entries.len()) as u64)
+        }
+
+        async fn ping(&self) -> Result<(), String> {
+            Ok(())
+        }
+    }
+
+    fn cache(store: MapStore) -> ResponseCache {
+        ResponseCache::new(Arc::new(store), Duration::from_secs(60))
+    }
+
+    /// Loads `value` through `cache`, counting the loads that reached the "database".
+    async fn load(cache: &ResponseCache, namespace: &str, loads: &AtomicUsize, value: u32) -> u32 {
+        cache
+            .get_or_load(namespace, "page=1", || async {
+                loads.fetch_add(1, Ordering::SeqCst);
+                Ok(value)
+            })
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn serves_repeated_reads_from_the_store() {
+        let cache = cache(MapStore::default());
+        let loads = AtomicUsize::new(0);
+        assert_eq!(load(&cache, PRODUCTS, &loads, 1).await, 1);
+        assert_eq!(load(&cache, PRODUCTS, &loads, 2).await, 1);
+        assert_eq!(loads.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn invalidation_drops_only_its_namespace() {
+        let cache = cache(MapStore::default());
+        let loads = AtomicUsize::new(0);
+        load(&cache, PRODUCTS, &loads, 1).await;
+        load(&cache, TODAY_STATS, &loads, 1).await;
+
+        cache.invalidate(PRODUCTS).await;
+        assert_eq!(load(&cache, PRODUCTS, &loads, 2).await, 2);
+        assert_eq!(load(&cache, TODAY_STATS, &loads, 2).await, 1);
+        assert_eq!(loads.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn tenants_do_not_share_entries() {
+        let default_tenant = cache(MapStore::default());
+        let magalu = default_tenant.for_tenant(&"magalu".parse().unwrap());
+        let loads = AtomicUsize::new(0);
+        load(&default_tenant, PRODUCTS, &loads, 1).await;
+        assert_eq!(load(&magalu, PRODUCTS, &loads, 2).await, 2);
+
+        magalu.invalidate(PRODUCTS).await;
+        assert_eq!(load(&default_tenant, PRODUCTS, &loads, 3).await, 1);
+        assert_eq!(default_tenant.flush().await, Some(Ok(1)));
+    }
+
+    #[tokio::test]
+    async fn an_unreachable_store_falls_back_to_the_database() {
+        let cache = cache(MapStore {
+            unreachable: true,
+            ..MapStore::default()
+        });
+        let loads = AtomicUsize::new(0);
+        assert_eq!(load(&cache, PRODUCTS, &loads, 1).await, 1);
+        assert_eq!(load(&cache, PRODUCTS, &loads, 2).await, 2);
+        assert_eq!(loads.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn errors_are_not_cached() {
+        let cache = cache(MapStore::default());
+        let failed: AppResult<u32> = cache
+            .get_or_load(PRODUCTS, "page=1", || async { Err(AppError::NotFound) })
+            .await;
+        assert!(failed.is_err());
+
+        let loads = AtomicUsize::new(0);
+        assert_eq!(load(&cache, PRODUCTS, &loads, 1).await, 1);
+        assert_eq!(loads.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn without_a_store_every_read_loads() {
+        let cache = ResponseCache::default();
+        let loads = AtomicUsize::new(0);
+        load(&cache, PRODUCTS, &loads, 1).await;
+        load(&cache, PRODUCTS, &loads, 1).await;
+        assert_eq!(loads.load(Ordering::SeqCst), 2);
+        assert_eq!(cache.flush().await, None);
+    }
+}
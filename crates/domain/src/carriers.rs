@@ -0,0 +1,160 @@
+use async_trait::async_trait;
+use bigdecimal::BigDecimal;
+use chrono::Utc;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::error::{AppError, AppResult};
+use crate::models::{FreightQuote, Parcel, Shipment, ShippingLabel, TrackingEvent};
+use crate::money::{Money, round_to_centavos};
+
+/// Cubic centimetres per billable kilogram, the divisor Brazilian carriers use to weigh a
+/// parcel by its volume.
+const CUBIC_CM_PER_KG: i64 = 6000;
+
+/// A shipping carrier: quotes freight for a parcel, posts it and tracks it.
+///
+/// Implementations backed by a carrier's API are selected with `CARRIER_PROVIDER` when
+/// building `AppState`.
+#[async_trait]
+pub trait CarrierProvider: Send + Sync {
+    fn name(&self) -> &'static str;
+    /// One quote per service the carrier offers between the parcel's CEPs.
+    async fn quote(&self, parcel: &Parcel) -> AppResult<Vec<FreightQuote>>;
+    async fn create_label(&self, shipment: &Shipment) -> AppResult<ShippingLabel>;
+    /// Events for a tracking code, oldest first.
+    async fn track(&self, tracking_code: &str) -> AppResult<Vec<TrackingEvent>>;
+}
+
+/// The full CEP for a five-digit zip code prefix, which is all the dataset stores: the
+/// first address of the prefix's range.
+pub fn cep_for_prefix(prefix: &str) -> String {
+    format!("{:0<8}", prefix.trim())
+}
+
+/// Grams a carrier bills a parcel for: its weight or its volumetric weight, whichever is
+/// greater.
+pub fn billable_weight_g(parcel: &Parcel) -> i64 {
+    let volume = i64::from(parcel.length_cm.max(0))
+        * i64::from(parcel.height_cm.max(0))
+        * i64::from(parcel.width_cm.max(0));
+    i64::from(parcel.weight_g.max(0)).max(volume * 1000 / CUBIC_CM_PER_KG)
+}
+
+/// Carrier without an external API, for development and tests. Prices grow with the
+/// billable weight and with the number of postal regions (first CEP digit) crossed, and
+/// labels are only kept in memory.
+#[derive(Default)]
+pub struct MockCarrier {
+    labels: Mutex<HashMap<String, ShippingLabelRecord>>,
+}
+
+struct ShippingLabelRecord {
+    reference: String,
+    created_at: chrono::NaiveDateTime,
+}
+
+/// One of the mock carrier's services and its rates.
+struct MockService {
+    name: &'static str,
+    base: &'static str,
+    per_region: &'static str,
+    per_kg: &'static str,
+    days: u32,
+    days_per_region: u32,
+}
+
+impl MockCarrier {
+    pub const NAME: &'static str = "mock";
+
+    const SERVICES: [MockService; 2] = [
+        MockService {
+            name: "standard",
+            base: "12.00",
+            per_region: "4.50",
+            per_kg: "1.20",
+            days: 5,
+            days_per_region: 2,
+        },
+        MockService {
+            name: "express",
+            base: "21.00",
+            per_region: "8.00",
+            per_kg: "2.40",
+            days: 2,
+            days_per_region: 1,
+        },
+    ];
+}
+
+#[async_trait]
+impl CarrierProvider for MockCarrier {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    async fn quote(&self, parcel: &Parcel) -> AppResult<Vec<FreightQuote>> {
+        let region = |cep: &str| cep.chars().next().and_then(|c| c.to_digit(10));
+        let regions = match (region(&parcel.origin_cep), region(&parcel.destination_cep)) {
+            (Some(origin), Some(destination)) => origin.abs_diff(destination),
+            _ => 0,
+        };
+        // Started kilograms, as carriers bill them.
+        let kilograms = (billable_weight_g(parcel) + 999) / 1000;
+
+        let rate = |value: &str| value.parse::<BigDecimal>().unwrap_or_default();
+        Ok(Self::SERVICES
+            .iter()
+            .map(|service| FreightQuote {
+                carrier: Self::NAME,
+                service: service.name.to_string(),
+                price: round_to_centavos(
+                    &(rate(service.base)
+                        + rate(service.per_region) * BigDecimal::from(regions)
+                        + rate(service.per_kg) * BigDecimal::from(kilograms)),
+                )
+                .into(),
+                delivery_days: Some(service.days + service.days_per_region * regions),
+                currency: Money::CURRENCY,
+            })
+            .collect())
+    }
+
+    async fn create_label(&self, shipment: &Shipment) -> AppResult<ShippingLabel> {
+        if !Self::SERVICES
+            .iter()
+            .any(|service| service.name == shipment.service)
+        {
+            return Err(AppError::CarrierError(format!(
+                "Unknown service {}",
+                shipment.service
+            )));
+        }
+
+        let mut labels = self.labels.lock().expect("mock carrier labels poisoned");
+        let tracking_code = format!("MK{:09}BR", labels.len() + 1);
+        labels.insert(
+            tracking_code.clone(),
+            ShippingLabelRecord {
+                reference: shipment.reference.clone(),
+                created_at: Utc::now().naive_utc(),
+            },
+        );
+        Ok(ShippingLabel {
+            carrier: Self::NAME,
+            tracking_code,
+            label_url: None,
+        })
+    }
+
+    async fn track(&self, tracking_code: &str) -> AppResult<Vec<TrackingEvent>> {
+        let labels = self.labels.lock().expect("mock carrier labels poisoned");
+        let label = labels.get(tracking_code).ok_or(AppError::NotFound)?;
+        Ok(vec![TrackingEvent {
+            status: "posted".to_string(),
+            description: format!("Label created for {}", label.reference),
+            location: None,
+            occurred_at: label.created_at,
+        }])
+    }
+}
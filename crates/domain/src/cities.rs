@@ -0,0 +1,55 @@
+/// Trims and collapses runs of whitespace, keeping the spelling otherwise intact.
+pub fn tidy_city(name: &str) -> String {
+    name.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Lowercased, accent-folded form of a city name, used as the key into `city_aliases`.
+/// Must stay in step with the backfill in the `add_canonical_city` migration.
+pub fn fold_city(name: &str) -> String {
+    tidy_city(name)
+        .chars()
+        .flat_map(char::to_lowercase)
+        .map(fold_accent)
+        .collect()
+}
+
+pub(crate) fn fold_accent(c: char) -> char {
+    match c {
+        'á' | 'à' | 'â' | 'ã' | 'ä' => 'a',
+        'é' | 'è' | 'ê' | 'ë' => 'e',
+        'í' | 'ì' | 'î' | 'ï' => 'i',
+        'ó' | 'ò' | 'ô' | 'õ' | 'ö' => 'o',
+        'ú' | 'ù' | 'û' | 'ü' => 'u',
+        'ç' => 'c',
+        'ñ' => 'n',
+        _ => c,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tidies_whitespace_only() {
+        assert_eq!(tidy_city("  São   Paulo \t"), "São Paulo");
+        assert_eq!(tidy_city("Mogi das Cruzes"), "Mogi das Cruzes");
+        assert_eq!(tidy_city("   "), "");
+    }
+
+    #[test]
+    fn folds_case_and_accents() {
+        assert_eq!(fold_city("São Paulo"), "sao paulo");
+        assert_eq!(fold_city(" SÃO  PAULO "), "sao paulo");
+        assert_eq!(fold_city("Florianópolis"), "florianopolis");
+        assert_eq!(fold_city("Foz do Iguaçu"), "foz do iguacu");
+        assert_eq!(fold_city("Itaúna"), "itauna");
+        assert_eq!(fold_city("Mogi-Guaçu"), "mogi-guacu");
+    }
+
+    #[test]
+    fn spellings_of_the_same_city_fold_alike() {
+        assert_eq!(fold_city("Sao Paulo"), fold_city("são paulo"));
+        assert_eq!(fold_city("BRASÍLIA"), fold_city("brasilia"));
+    }
+}
@@ -0,0 +1,236 @@
+use bigdecimal::BigDecimal;
+
+use crate::models::OrderItem;
+use crate::money::round_to_centavos;
+
+/// Rules for post-checkout order amendments.
+#[derive(Clone)]
+pub struct AmendmentConfig {
+    /// Hours after purchase during which an order can still be amended.
+    pub window_hours: i64,
+    pub freight_base: BigDecimal,
+    pub freight_per_region: BigDecimal,
+    pub tax_rate: BigDecimal,
+}
+
+impl AmendmentConfig {
+    /// Freight for one item. The first CEP digit identifies the postal region, so the
+    /// charge grows with the number of regions between origin and destination.
+    pub fn freight_for(&self, origin_zip: &str, destination_zip: &str) -> BigDecimal {
+        let distance = match (cep_region(origin_zip), cep_region(destination_zip)) {
+            (Some(origin), Some(destination)) => origin.abs_diff(destination),
+            _ => 0,
+        };
+        round_to_centavos(
+            &(&self.freight_base + &self.freight_per_region * BigDecimal::from(distance)),
+        )
+    }
+
+    pub fn tax_for(&self, subtotal: &BigDecimal) -> BigDecimal {
+        round_to_centavos(&(subtotal * &self.tax_rate))
+    }
+
+    /// Total freight and tax for a set of items.
+    pub fn order_charges(&self, items: &[&OrderItem]) -> (BigDecimal, BigDecimal) {
+        let freight: BigDecimal = items.iter().map(|item| item.freight_value.amount()).sum();
+        let subtotal: BigDecimal = items.iter().map(|item| item.price.amount()).sum();
+        let tax = self.tax_for(&(subtotal + &freight));
+        (freight, tax)
+    }
+}
+
+fn cep_region(zip: &str) -> Option<u32> {
+    zip.trim().chars().next().and_then(|c| c.to_digit(10))
+}
+
+/// One band of the freight rate table: what shipping costs up to a distance.
+#[derive(Clone, Debug)]
+pub struct FreightRate {
+    /// Distance the band covers up to, in kilometres; `None` covers everything further.
+    pub max_km: Option<f64>,
+    pub base: BigDecimal,
+    /// Charged per started kilogram of billable weight.
+    pub per_kg: BigDecimal,
+}
+
+/// Rate table for distance-based freight estimates.
+#[derive(Clone, Debug)]
+pub struct FreightConfig {
+    /// Bands by increasing `max_km`.
+    pub rates: Vec<FreightRate>,
+}
+
+impl FreightConfig {
+    /// The first band reaching `distance_km`, or the open-ended one.
+    pub fn rate_for(&self, distance_km: f64) -> Option<&FreightRate> {
+        self.rates
+            .iter()
+            .find(|rate| rate.max_km.is_none_or(|max_km| distance_km <= max_km))
+    }
+
+    /// Freight for a parcel of `billable_weight_g` over `distance_km`, with the band used.
+    pub fn freight_for(
+        &self,
+        distance_km: f64,
+        billable_weight_g: i64,
+    ) -> Option<(&FreightRate, BigDecimal)> {
+        let rate = self.rate_for(distance_km)?;
+        let kilograms = (billable_weight_g.max(0) + 999) / 1000;
+        let freight = round_to_centavos(&(&rate.base + &rate.per_kg * BigDecimal::from(kilograms)));
+        Some((rate, freight))
+    }
+}
+
+/// Service-level targets for support cases, in hours from case creation.
+#[derive(Clone, Copy)]
+pub struct SupportConfig {
+    pub first_response_hours: i64,
+    pub resolution_hours: i64,
+}
+
+/// Limits on uploaded product images.
+#[derive(Clone, Copy, Debug)]
+pub struct ProductImageConfig {
+    /// Largest image accepted, in bytes.
+    pub max_bytes: usize,
+}
+
+/// Targets a seller's scorecard is graded against. Rates are fractions, e.g. `0.9` for 90%.
+#[derive(Clone, Copy, Debug)]
+pub struct ScorecardConfig {
+    pub min_on_time_rate: f64,
+    pub max_cancellation_rate: f64,
+    pub min_review_average: f64,
+    /// Sellers with fewer orders than this are not graded.
+    pub min_orders: i64,
+}
+
+/// What deleting a parent does to a relation that still has child rows.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DeletePolicy {
+    /// Refuse the delete with a `409` naming the remaining children.
+    Restrict,
+    /// Delete the parent and leave the children attached to it. Customers are soft-deleted,
+    /// so their orders and cases come back with a restore.
+    #[default]
+    Cascade,
+    /// Keep the children but anonymize the parent first, so they no longer lead back to
+    /// personal data.
+    DetachAnonymize,
+}
+
+impl std::str::FromStr for DeletePolicy {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_lowercase().as_str() {
+            "restrict" => Ok(DeletePolicy::Restrict),
+            "cascade" => Ok(DeletePolicy::Cascade),
+            "detach_anonymize" => Ok(DeletePolicy::DetachAnonymize),
+            other => Err(format!("unknown delete policy '{}'", other)),
+        }
+    }
+}
+
+/// Delete policy per parent/child relation.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DeletePolicyConfig {
+    pub customer_orders: DeletePolicy,
+    pub customer_support_cases: DeletePolicy,
+}
+
+/// How long personal data is kept, for LGPD lifecycle management. Applied by the scheduled
+/// retention job; a rule set to 0 keeps the data forever.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RetentionConfig {
+    /// Years after which a review is deleted, counted from when it was written.
+    pub review_years: u32,
+    /// Years without an order or an address change after which a customer is anonymized.
+    pub inactive_customer_years: u32,
+}
+
+/// Delivery of queued webhook events.
+#[derive(Clone, Copy)]
+pub struct WebhookConfig {
+    /// How often the worker looks for due deliveries; 0 disables delivery.
+    pub poll_interval_seconds: u64,
+    /// Per-request timeout for the subscriber's endpoint.
+    pub timeout_seconds: u64,
+    /// Attempts before a delivery is marked failed.
+    pub max_attempts: u32,
+    /// Wait before the first retry; it doubles with every further attempt.
+    pub retry_base_seconds: u64,
+}
+
+impl WebhookConfig {
+    /// Longest wait between two attempts.
+    const MAX_RETRY_DELAY_SECONDS: u64 = 6 * 60 * 60;
+
+    /// Wait after the `attempts`-th failed attempt.
+    pub fn retry_delay(&self, attempts: u32) -> std::time::Duration {
+        backoff(
+            self.retry_base_seconds,
+            attempts,
+            Self::MAX_RETRY_DELAY_SECONDS,
+        )
+    }
+}
+
+/// Relaying of outbox events to the broker and to webhook subscriptions.
+#[derive(Clone, Copy)]
+pub struct OutboxConfig {
+    /// How often the relay looks for unpublished events; 0 disables it.
+    pub poll_interval_seconds: u64,
+    /// Events leased per relay pass.
+    pub batch_size: i64,
+    /// Wait before retrying a failed publish; it doubles with every further attempt.
+    pub retry_base_seconds: u64,
+    /// Days published events are kept; 0 keeps them forever.
+    pub retention_days: u64,
+}
+
+impl OutboxConfig {
+    /// Longest wait between two attempts. Later events wait behind a failed one, so this is
+    /// much shorter than for webhooks.
+    const MAX_RETRY_DELAY_SECONDS: u64 = 5 * 60;
+
+    /// Wait after the `attempts`-th failed attempt.
+    pub fn retry_delay(&self, attempts: u32) -> std::time::Duration {
+        backoff(
+            self.retry_base_seconds,
+            attempts,
+            Self::MAX_RETRY_DELAY_SECONDS,
+        )
+    }
+}
+
+/// Sending of queued notifications.
+#[derive(Clone, Copy)]
+pub struct NotificationConfig {
+    /// How often the worker looks for due notifications; 0 disables sending.
+    pub poll_interval_seconds: u64,
+    /// Attempts before a notification is marked failed.
+    pub max_attempts: u32,
+    /// Wait before the first retry; it doubles with every further attempt.
+    pub retry_base_seconds: u64,
+}
+
+impl NotificationConfig {
+    /// Longest wait between two attempts.
+    const MAX_RETRY_DELAY_SECONDS: u64 = 60 * 60;
+
+    /// Wait after the `attempts`-th failed attempt.
+    pub fn retry_delay(&self, attempts: u32) -> std::time::Duration {
+        backoff(
+            self.retry_base_seconds,
+            attempts,
+            Self::MAX_RETRY_DELAY_SECONDS,
+        )
+    }
+}
+
+/// `base_seconds` doubled for every attempt after the first, capped at `max_seconds`.
+fn backoff(base_seconds: u64, attempts: u32, max_seconds: u64) -> std::time::Duration {
+    let factor = 2u64.saturating_pow(attempts.saturating_sub(1));
+    std::time::Duration::from_secs(base_seconds.saturating_mul(factor).min(max_seconds))
+}
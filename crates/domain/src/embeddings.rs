@@ -0,0 +1,57 @@
+use async_trait::async_trait;
+
+use crate::error::AppResult;
+
+/// Dimension of the `product_embeddings.embedding` column.
+pub const EMBEDDING_DIMENSIONS: usize = 64;
+
+/// Turns free text (category names, review comments) into a fixed-size vector.
+///
+/// Implementations backed by an external model can be swapped in when building `AppState`.
+#[async_trait]
+pub trait Embedder: Send + Sync {
+    fn dimensions(&self) -> usize;
+    async fn embed(&self, text: &str) -> AppResult<Vec<f32>>;
+}
+
+/// Dependency-free embedder using signed feature hashing of lowercase words.
+///
+/// Hashing is done with FNV-1a so stored vectors stay comparable across builds.
+#[derive(Clone, Default)]
+pub struct HashingEmbedder;
+
+#[async_trait]
+impl Embedder for HashingEmbedder {
+    fn dimensions(&self) -> usize {
+        EMBEDDING_DIMENSIONS
+    }
+
+    async fn embed(&self, text: &str) -> AppResult<Vec<f32>> {
+        let mut vector = vec![0f32; EMBEDDING_DIMENSIONS];
+
+        let lowercase = text.to_lowercase();
+        let words = lowercase
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|word| !word.is_empty());
+
+        for word in words {
+            let hash = fnv1a(word.as_bytes());
+            let index = (hash % EMBEDDING_DIMENSIONS as u64) as usize;
+            let sign = if hash >> 63 == 1 { -1.0 } else { 1.0 };
+            vector[index] += sign;
+        }
+
+        let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+        if norm > 0.0 {
+            vector.iter_mut().for_each(|v| *v /= norm);
+        }
+
+        Ok(vector)
+    }
+}
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
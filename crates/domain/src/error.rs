@@ -0,0 +1,90 @@
+use sqlx::migrate::MigrateError;
+
+pub type AppResult<T> = Result<T, AppError>;
+
+#[derive(Debug)]
+pub enum AppError {
+    DatabaseError(sqlx::Error),
+    MigrationError(MigrateError),
+    CacheError(String),
+    /// The shipping carrier's API failed or refused the request.
+    CarrierError(String),
+    /// The CEP lookup service failed or could not be reached.
+    ZipLookupError(String),
+    /// The payment provider failed or refused the request.
+    PaymentError(String),
+    /// The object storage service failed or refused the request.
+    StorageError(String),
+    NotFound,
+    ConfigError(String),
+    ValidationError(validator::ValidationErrors),
+    NoChangesToUpdate,
+    AlreadyExists(String),
+    FeatureDisabled(&'static str),
+    InsufficientStock(String),
+    AmendmentNotAllowed(String),
+    /// The coupon exists but cannot be applied to the order.
+    CouponRejected(String),
+    /// The payment is not in a status the operation applies to.
+    PaymentNotAllowed(String),
+    /// A provider notification whose signature does not match its body.
+    InvalidSignature,
+    JobAlreadyRunning(String),
+    RollbackNotAllowed(String),
+    DeleteRestricted(String),
+    RequestTimeout,
+    PayloadTooLarge,
+    Unauthorized,
+    /// Seconds until the caller's quota window resets.
+    QuotaExceeded(u64),
+    /// Too many requests are in flight; seconds the client should wait before retrying.
+    Overloaded(u64),
+    /// Writes are refused while the service is in read-only mode, for the given reason.
+    ReadOnly(Option<String>),
+    /// The `API-Version` a request asked for, which this deployment doesn't serve.
+    UnsupportedApiVersion(String),
+    /// No route matches the request, e.g. `GET /nope`.
+    RouteNotFound(String),
+    /// The route exists but not for the request's method, e.g. `DELETE /orders`.
+    MethodNotAllowed(String),
+    /// The request names no tenant this deployment serves; says what was missing or unknown.
+    TenantNotResolved(String),
+    /// A handler panicked; carries the panic message for the log, not the client.
+    Panic(String),
+}
+
+impl From<sqlx::Error> for AppError {
+    fn from(error: sqlx::Error) -> Self {
+        AppError::DatabaseError(error)
+    }
+}
+
+impl From<MigrateError> for AppError {
+    fn from(error: MigrateError) -> Self {
+        AppError::MigrationError(error)
+    }
+}
+
+impl From<validator::ValidationErrors> for AppError {
+    fn from(error: validator::ValidationErrors) -> Self {
+        AppError::ValidationError(error)
+    }
+}
+
+pub fn map_db_error(e: sqlx::Error, resource_name: &str) -> AppError {
+    if let sqlx::Error::Database(db_err) = &e
+        && db_err.is_unique_violation()
+    {
+        return AppError::AlreadyExists(format!("{} already exists", resource_name));
+    }
+    AppError::DatabaseError(e)
+}
+
+pub fn map_stock_error(e: sqlx::Error, product_id: &str) -> AppError {
+    if let sqlx::Error::Database(db_err) = &e
+        && db_err.is_check_violation()
+    {
+        return AppError::InsufficientStock(product_id.to_string());
+    }
+    AppError::DatabaseError(e)
+}
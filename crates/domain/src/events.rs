@@ -0,0 +1,116 @@
+use async_trait::async_trait;
+use tokio::sync::{broadcast, mpsc};
+use tracing::warn;
+
+use crate::models::{ChangeEvent, OrderFeedEvent, OrderStatusChange, OutboxEvent};
+use crate::tenancy::TenantId;
+
+const ORDER_STATUS_CAPACITY: usize = 1024;
+const ORDER_FEED_CAPACITY: usize = 1024;
+
+/// In-process fan-out of order status changes. Subscribers that fall behind by more
+/// than the channel capacity get a `Lagged` error and should re-read from the database.
+#[derive(Clone)]
+pub struct OrderStatusEvents(broadcast::Sender<OrderStatusChange>);
+
+impl Default for OrderStatusEvents {
+    fn default() -> Self {
+        Self(broadcast::channel(ORDER_STATUS_CAPACITY).0)
+    }
+}
+
+impl OrderStatusEvents {
+    pub fn subscribe(&self) -> broadcast::Receiver<OrderStatusChange> {
+        self.0.subscribe()
+    }
+
+    /// Hands a change to every current subscriber. No subscribers is not an error: nobody
+    /// is waiting right now.
+    pub fn publish(&self, change: OrderStatusChange) {
+        let _ = self.0.send(change);
+    }
+}
+
+/// In-process fan-out of new orders and status changes for live dashboards. Like
+/// [`OrderStatusEvents`], subscribers that fall behind get a `Lagged` error.
+#[derive(Clone)]
+pub struct OrderFeed(broadcast::Sender<OrderFeedEvent>);
+
+impl Default for OrderFeed {
+    fn default() -> Self {
+        Self(broadcast::channel(ORDER_FEED_CAPACITY).0)
+    }
+}
+
+impl OrderFeed {
+    pub fn subscribe(&self) -> broadcast::Receiver<OrderFeedEvent> {
+        self.0.subscribe()
+    }
+
+    pub fn publish(&self, event: OrderFeedEvent) {
+        let _ = self.0.send(event);
+    }
+}
+
+/// Message broker the outbox relay publishes events to, before they are handed to webhook
+/// subscriptions. Publishing is at least once: an event whose publish succeeded may be
+/// published again if the relay stops before recording it.
+#[async_trait]
+pub trait EventPublisher: Send + Sync {
+    async fn publish(&self, event: &OutboxEvent) -> Result<(), String>;
+}
+
+/// Hands entity changes to the change stream without waiting for the broker. Changes are
+/// queued up to the channel capacity; when the publisher falls further behind, new changes
+/// are dropped with a warning, so writes never slow down. The default stream is disabled.
+#[derive(Clone, Default)]
+pub struct ChangeStream {
+    sender: Option<mpsc::Sender<ChangeEvent>>,
+    tenant: TenantId,
+}
+
+impl ChangeStream {
+    /// An enabled stream and the receiving end for its publisher task.
+    pub fn channel(capacity: usize) -> (Self, mpsc::Receiver<ChangeEvent>) {
+        let (sender, receiver) = mpsc::channel(capacity.max(1));
+        let stream = Self {
+            sender: Some(sender),
+            tenant: TenantId::default(),
+        };
+        (stream, receiver)
+    }
+
+    /// The same stream, for the changes of `tenant`.
+    pub fn for_tenant(&self, tenant: &TenantId) -> Self {
+        Self {
+            sender: self.sender.clone(),
+            tenant: tenant.clone(),
+        }
+    }
+
+    /// Tenant whose changes this stream carries.
+    pub fn tenant(&self) -> &TenantId {
+        &self.tenant
+    }
+
+    pub fn emit(&self, change: ChangeEvent) {
+        let Some(sender) = &self.sender else {
+            return;
+        };
+        if let Err(mpsc::error::TrySendError::Full(change)) = sender.try_send(change) {
+            warn!(
+                "Change stream is full, dropped the {} of {} {}",
+                change.action.as_str(),
+                change.entity_type,
+                change.entity_id
+            );
+        }
+    }
+}
+
+/// Broker the change stream is published to. Unlike the outbox, publishing is best effort:
+/// changes still queued when the process stops are lost.
+#[async_trait]
+pub trait ChangePublisher: Send + Sync {
+    async fn publish(&self, change: &ChangeEvent) -> Result<(), String>;
+}
@@ -0,0 +1,39 @@
+//! Distances between points on the Earth's surface.
+
+/// Mean Earth radius, in kilometres.
+const EARTH_RADIUS_KM: f64 = 6371.0;
+
+/// Great-circle distance in kilometres between two points given as latitude and longitude
+/// in degrees (haversine formula).
+pub fn haversine_km(from: (f64, f64), to: (f64, f64)) -> f64 {
+    let (lat1, lng1) = (from.0.to_radians(), from.1.to_radians());
+    let (lat2, lng2) = (to.0.to_radians(), to.1.to_radians());
+    let a = ((lat2 - lat1) / 2.0).sin().powi(2)
+        + lat1.cos() * lat2.cos() * ((lng2 - lng1) / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_KM * a.sqrt().min(1.0).asin()
+}
+
+/// A latitude and longitude box, in degrees, used to narrow a radius search to candidates
+/// before their exact distances are computed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GeoBounds {
+    pub min_lat: f64,
+    pub max_lat: f64,
+    pub min_lng: f64,
+    pub max_lng: f64,
+}
+
+impl GeoBounds {
+    /// The smallest box holding every point within `radius_km` of `center`. Longitude
+    /// degrees shrink towards the poles, so the box widens with the latitude.
+    pub fn around(center: (f64, f64), radius_km: f64) -> Self {
+        let lat_delta = (radius_km / EARTH_RADIUS_KM).to_degrees();
+        let lng_delta = (lat_delta / center.0.to_radians().cos().max(0.01)).min(180.0);
+        Self {
+            min_lat: center.0 - lat_delta,
+            max_lat: center.0 + lat_delta,
+            min_lng: center.1 - lng_delta,
+            max_lng: center.1 + lng_delta,
+        }
+    }
+}
@@ -0,0 +1,77 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use uuid::Uuid;
+
+/// Defines a string-backed entity id. Each id is a distinct type, so passing an order id where
+/// a customer id is expected fails to compile; serde and sqlx see the plain string.
+macro_rules! entity_id {
+    ($(#[$meta:meta])* $name:ident, $kind:literal) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, sqlx::Type)]
+        #[serde(transparent)]
+        #[sqlx(transparent)]
+        pub struct $name(String);
+
+        impl $name {
+            /// A fresh random id in the same 32-hex format as the dataset (a UUIDv4 without
+            /// dashes).
+            pub fn generate() -> Self {
+                Self(Uuid::new_v4().simple().to_string())
+            }
+
+            pub fn as_str(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl EntityId for $name {
+            const KIND: &'static str = $kind;
+        }
+
+        impl From<String> for $name {
+            fn from(id: String) -> Self {
+                Self(id)
+            }
+        }
+
+        impl From<$name> for String {
+            fn from(id: $name) -> Self {
+                id.0
+            }
+        }
+
+        impl AsRef<str> for $name {
+            fn as_ref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(&self.0)
+            }
+        }
+    };
+}
+
+/// Common surface of the entity id newtypes.
+pub trait EntityId: AsRef<str> + From<String> {
+    /// Entity name, e.g. `order`; keeps the same raw value distinct across entities.
+    const KIND: &'static str;
+}
+
+entity_id!(CustomerId, "customer");
+entity_id!(OrderId, "order");
+entity_id!(ProductId, "product");
+entity_id!(SellerId, "seller");
+
+/// Olist ids are 32 lowercase hex characters (an MD5 digest).
+pub fn validate_olist_id<T: AsRef<str>>(id: &T) -> Result<(), validator::ValidationError> {
+    let id = id.as_ref();
+    if id.len() == 32 && id.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f')) {
+        Ok(())
+    } else {
+        Err(validator::ValidationError::new("olist_id")
+            .with_message("must be 32 lowercase hexadecimal characters".into()))
+    }
+}
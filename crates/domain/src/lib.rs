@@ -0,0 +1,24 @@
+//! Entities, repository traits and the services implementing the shop's business rules.
+//! Storage and transport live in other crates; everything here talks to the database only
+//! through the traits in [`repositories`].
+
+pub mod cache;
+pub mod carriers;
+pub mod cities;
+pub mod config;
+pub mod embeddings;
+pub mod error;
+pub mod events;
+pub mod geo;
+pub mod ids;
+pub mod models;
+pub mod money;
+pub mod notifications;
+pub mod payments;
+pub mod repositories;
+pub mod runtime;
+pub mod sentiment;
+pub mod services;
+pub mod storage;
+pub mod tenancy;
+pub mod zip_lookup;
@@ -0,0 +1,4011 @@
+use bigdecimal::{BigDecimal, Zero};
+// use chrono::{DateTime, Utc};
+use crate::cities::fold_city;
+use crate::ids::{CustomerId, OrderId, ProductId, SellerId, validate_olist_id};
+use crate::money::{Currency, Money, round_to_centavos};
+use crate::tenancy::TenantId;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use validator::Validate;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PaginationMeta {
+    /// `null` when the listing was asked not to count (`include_total=false`).
+    pub total_records: Option<i64>,
+    pub page: u32,
+    pub page_size: u32,
+    pub total_pages: Option<u32>,
+    /// Whether `total_records` comes from planner statistics rather than a `COUNT(*)`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub total_estimated: bool,
+}
+
+/// What a listing reports as its total, from the `include_total` query parameter.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub enum TotalMode {
+    /// Run a `COUNT(*)` over the filtered rows.
+    #[default]
+    #[serde(rename = "true")]
+    Exact,
+    /// Read the row count from planner statistics instead of counting.
+    #[serde(rename = "estimate")]
+    Estimate,
+    /// Skip counting altogether.
+    #[serde(rename = "false")]
+    Skip,
+}
+
+/// A listing's row count, as produced for a [`TotalMode`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Total {
+    Exact(i64),
+    Estimated(i64),
+    Skipped,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PaginationParams {
+    pub page: Option<u32>,
+    pub page_size: Option<u32>,
+}
+
+impl PaginationParams {
+    pub fn normalize(&self) -> (i64, i64, u32, u32) {
+        let page = self.page.unwrap_or(1).max(1);
+        let page_size = self.page_size.unwrap_or(10).clamp(1, 100);
+
+        let limit = page_size as i64;
+        let offset = ((page - 1) as i64) * limit;
+
+        (limit, offset, page, page_size)
+    }
+}
+
+/// Relative URLs of neighbouring pages, built by the HTTP layer from the request URI.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PaginationLinks {
+    #[serde(rename = "self")]
+    pub self_link: String,
+    pub first: String,
+    /// Absent when the total was not counted.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prev: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PaginatedResponse<T> {
+    pub data: Vec<T>,
+    pub meta: PaginationMeta,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub links: Option<PaginationLinks>,
+}
+
+impl<T> PaginatedResponse<T> {
+    pub fn new(data: Vec<T>, count: i64, page: u32, page_size: u32) -> Self {
+        Self::with_total(data, Total::Exact(count), page, page_size)
+    }
+
+    pub fn with_total(data: Vec<T>, total: Total, page: u32, page_size: u32) -> Self {
+        let (total_records, total_estimated) = match total {
+            Total::Exact(count) => (Some(count), false),
+            Total::Estimated(count) => (Some(count), true),
+            Total::Skipped => (None, false),
+        };
+        let total_pages = total_records.map(|count| {
+            if count == 0 {
+                1
+            } else {
+                (count as f64 / page_size as f64).ceil() as u32
+            }
+        });
+
+        Self {
+            data,
+            meta: PaginationMeta {
+                total_records,
+                page,
+                page_size,
+                total_pages,
+                total_estimated,
+            },
+            links: None,
+        }
+    }
+}
+
+/// Trims a free-text search term, treating a blank one as absent.
+fn search_term(q: &Option<String>) -> Option<String> {
+    q.as_deref()
+        .map(str::trim)
+        .filter(|q| !q.is_empty())
+        .map(str::to_string)
+}
+
+/// A listing row trimmed to the fields named in `fields=`, keyed by field name.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct SparseRow(pub serde_json::Map<String, serde_json::Value>);
+
+/// Parses a comma-separated `fields=` selection against the fields a listing can return.
+/// `None` when the parameter is absent, meaning every field. Unknown names are rejected rather
+/// than skipped, so a typo doesn't quietly return less data.
+fn parse_fields(
+    fields: &Option<String>,
+    allowed: &'static [&'static str],
+) -> Result<Option<Vec<&'static str>>, validator::ValidationErrors> {
+    let Some(fields) = fields else {
+        return Ok(None);
+    };
+
+    let mut selected: Vec<&'static str> = Vec::new();
+    for name in fields
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+    {
+        let Some(field) = allowed.iter().find(|field| **field == name) else {
+            let mut errors = validator::ValidationErrors::new();
+            errors.add(
+                "fields",
+                validator::ValidationError::new("unknown_field").with_message(
+                    format!(
+                        "Unknown field {}, expected one of {}",
+                        name,
+                        allowed.join(", ")
+                    )
+                    .into(),
+                ),
+            );
+            return Err(errors);
+        };
+        if !selected.contains(field) {
+            selected.push(field);
+        }
+    }
+
+    if selected.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(selected))
+}
+
+#[derive(Debug, Deserialize, Default, PartialEq)]
+pub struct SellerFilter {
+    pub q: Option<String>,
+    pub city: Option<String>,
+    pub state: Option<String>,
+    pub badge: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SellerSearchQuery {
+    pub page: Option<u32>,
+    pub page_size: Option<u32>,
+    /// Comma-separated subset of fields to return.
+    pub fields: Option<String>,
+    pub include_total: Option<TotalMode>,
+    /// Fuzzy city search, tolerant of typos and missing accents.
+    pub q: Option<String>,
+    pub city: Option<String>,
+    pub state: Option<String>,
+    pub badge: Option<SellerBadge>,
+}
+
+impl SellerSearchQuery {
+    pub fn pagination(&self) -> PaginationParams {
+        PaginationParams {
+            page: self.page,
+            page_size: self.page_size,
+        }
+    }
+
+    pub fn total_mode(&self) -> TotalMode {
+        self.include_total.unwrap_or_default()
+    }
+
+    pub fn fields(&self) -> Result<Option<Vec<&'static str>>, validator::ValidationErrors> {
+        parse_fields(&self.fields, Seller::FIELDS)
+    }
+
+    pub fn filter(&self) -> SellerFilter {
+        SellerFilter {
+            q: search_term(&self.q),
+            city: self.city.as_deref().map(fold_city),
+            state: self.state.clone(),
+            badge: self.badge.map(|badge| badge.as_str().to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Default, PartialEq)]
+pub struct CustomerFilter {
+    pub q: Option<String>,
+    pub city: Option<String>,
+    pub state: Option<String>,
+    pub include_deleted: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CustomerSearchQuery {
+    pub page: Option<u32>,
+    pub page_size: Option<u32>,
+    /// Comma-separated subset of fields to return.
+    pub fields: Option<String>,
+    pub include_total: Option<TotalMode>,
+    /// Fuzzy city search, tolerant of typos and missing accents.
+    pub q: Option<String>,
+    pub city: Option<String>,
+    pub state: Option<String>,
+    pub include_deleted: Option<bool>,
+}
+
+impl CustomerSearchQuery {
+    pub fn pagination(&self) -> PaginationParams {
+        PaginationParams {
+            page: self.page,
+            page_size: self.page_size,
+        }
+    }
+
+    pub fn total_mode(&self) -> TotalMode {
+        self.include_total.unwrap_or_default()
+    }
+
+    pub fn fields(&self) -> Result<Option<Vec<&'static str>>, validator::ValidationErrors> {
+        parse_fields(&self.fields, Customer::FIELDS)
+    }
+
+    pub fn filter(&self) -> CustomerFilter {
+        CustomerFilter {
+            q: search_term(&self.q),
+            city: self.city.as_deref().map(fold_city),
+            state: self.state.clone(),
+            include_deleted: self.include_deleted.unwrap_or(false),
+        }
+    }
+}
+
+#[derive(Debug, FromRow, Serialize, Clone)]
+pub struct Customer {
+    pub customer_id: CustomerId,
+    pub customer_unique_id: String,
+    pub customer_zip_code_prefix: String,
+    pub customer_city: String,
+    /// Lowercased, accent-folded and alias-mapped city, used by the `city` filter.
+    pub canonical_city: String,
+    pub customer_state: String,
+    pub deleted_at: Option<chrono::NaiveDateTime>,
+}
+
+impl Customer {
+    /// Fields a listing can be trimmed to with `fields=`.
+    pub const FIELDS: &'static [&'static str] = &[
+        "customer_id",
+        "customer_unique_id",
+        "customer_zip_code_prefix",
+        "customer_city",
+        "canonical_city",
+        "customer_state",
+        "deleted_at",
+    ];
+}
+
+/// `POST /customers/merge`: folds the source customer into the target.
+#[derive(Debug, Deserialize, Validate)]
+#[validate(schema(function = "validate_merge_customers"))]
+pub struct MergeCustomersDto {
+    pub source_customer_id: CustomerId,
+    pub target_customer_id: CustomerId,
+}
+
+fn validate_merge_customers(dto: &MergeCustomersDto) -> Result<(), validator::ValidationError> {
+    if dto.source_customer_id == dto.target_customer_id {
+        return Err(validator::ValidationError::new("same_customer")
+            .with_message("source and target must be different customers".into()));
+    }
+    Ok(())
+}
+
+/// What a merge moved from the source customer, now soft-deleted, to the target.
+#[derive(Debug, Serialize)]
+pub struct CustomerMerge {
+    pub source_customer_id: CustomerId,
+    pub target_customer_id: CustomerId,
+    /// Archived orders included.
+    pub orders_moved: u64,
+    pub support_cases_moved: u64,
+    pub source_deleted_at: chrono::NaiveDateTime,
+}
+
+impl CustomerMerge {
+    /// `entry` with the moved counts added to its diff, as `merge` writes it to the audit log.
+    pub fn audit_entry(&self, entry: &NewAuditEntry) -> NewAuditEntry {
+        let mut diff = entry.diff.clone();
+        if let Some(fields) = diff.as_object_mut() {
+            fields.insert("orders_moved".into(), self.orders_moved.into());
+            fields.insert(
+                "support_cases_moved".into(),
+                self.support_cases_moved.into(),
+            );
+        }
+        NewAuditEntry {
+            entity_type: entry.entity_type,
+            entity_id: entry.entity_id.clone(),
+            action: entry.action,
+            actor: entry.actor.clone(),
+            diff,
+        }
+    }
+}
+
+/// Rows still referencing a customer, checked against the delete policies.
+#[derive(Debug, FromRow)]
+pub struct CustomerDependents {
+    pub orders: i64,
+    pub support_cases: i64,
+}
+
+/// One person across the Olist data, which gives every order its own `customer_id` and links
+/// them through `customer_unique_id`.
+#[derive(Debug, Serialize)]
+pub struct UniqueCustomer {
+    pub customer_unique_id: String,
+    pub customers: Vec<Customer>,
+    /// Orders of every linked customer row, newest first.
+    pub orders: Vec<Order>,
+    pub first_order_at: Option<chrono::NaiveDateTime>,
+    pub last_order_at: Option<chrono::NaiveDateTime>,
+}
+
+impl UniqueCustomer {
+    /// `orders` must be newest first.
+    pub fn new(customer_unique_id: String, customers: Vec<Customer>, orders: Vec<Order>) -> Self {
+        Self {
+            customer_unique_id,
+            first_order_at: orders.last().map(|order| order.order_purchase_timestamp),
+            last_order_at: orders.first().map(|order| order.order_purchase_timestamp),
+            customers,
+            orders,
+        }
+    }
+}
+
+/// How many unique customers have `customer_rows` live customer rows.
+#[derive(Debug, Serialize, FromRow)]
+pub struct DuplicationBucket {
+    pub customer_rows: i64,
+    pub unique_customers: i64,
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct DuplicatedCustomer {
+    pub customer_unique_id: String,
+    pub customer_rows: i64,
+}
+
+/// `GET /analytics/duplicate-customers`: how often one person appears under several
+/// `customer_id`s. Deleted customers are left out.
+#[derive(Debug, Serialize)]
+pub struct DuplicateCustomerStats {
+    pub customer_rows: i64,
+    pub unique_customers: i64,
+    /// Unique customers with more than one customer row.
+    pub duplicated_unique_customers: i64,
+    /// Customer rows beyond the first of each unique customer.
+    pub duplicate_rows: i64,
+    /// `duplicate_rows` as a share of `customer_rows`.
+    pub duplicate_ratio: f64,
+    /// Fewest rows per unique customer first.
+    pub distribution: Vec<DuplicationBucket>,
+    pub most_duplicated: Vec<DuplicatedCustomer>,
+}
+
+impl DuplicateCustomerStats {
+    pub fn new(
+        distribution: Vec<DuplicationBucket>,
+        most_duplicated: Vec<DuplicatedCustomer>,
+    ) -> Self {
+        let customer_rows = distribution
+            .iter()
+            .map(|bucket| bucket.customer_rows * bucket.unique_customers)
+            .sum();
+        let unique_customers = distribution
+            .iter()
+            .map(|bucket| bucket.unique_customers)
+            .sum();
+        let duplicated_unique_customers = distribution
+            .iter()
+            .filter(|bucket| bucket.customer_rows > 1)
+            .map(|bucket| bucket.unique_customers)
+            .sum();
+        let duplicate_rows = customer_rows - unique_customers;
+        Self {
+            customer_rows,
+            unique_customers,
+            duplicated_unique_customers,
+            duplicate_rows,
+            duplicate_ratio: if customer_rows > 0 {
+                duplicate_rows as f64 / customer_rows as f64
+            } else {
+                0.0
+            },
+            distribution,
+            most_duplicated,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DuplicateCustomersQuery {
+    /// How many of the most duplicated unique customers to list; 10 when omitted.
+    pub limit: Option<u32>,
+}
+
+impl DuplicateCustomersQuery {
+    pub fn limit(&self) -> i64 {
+        self.limit.unwrap_or(10).clamp(1, 100) as i64
+    }
+}
+
+/// One address a customer has had. `valid_from` is `None` for the first known address and
+/// `valid_to` is `None` for the current one.
+#[derive(Debug, Serialize, FromRow)]
+pub struct CustomerLocationVersion {
+    pub customer_zip_code_prefix: String,
+    pub customer_city: String,
+    pub customer_state: String,
+    pub valid_from: Option<chrono::NaiveDateTime>,
+    pub valid_to: Option<chrono::NaiveDateTime>,
+}
+
+/// An entry in a customer's address book. New orders ship to the address they name, or else to
+/// the default one.
+#[derive(Debug, Serialize, FromRow, Clone)]
+pub struct CustomerAddress {
+    pub address_id: i64,
+    pub customer_id: CustomerId,
+    pub label: String,
+    pub zip_code_prefix: String,
+    pub city: String,
+    pub state: String,
+    pub is_default: bool,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct CreateCustomerAddressDto {
+    /// e.g. `home` or `work`.
+    #[validate(length(min = 1, max = 50))]
+    pub label: String,
+    #[validate(custom(function = "validate_zip_code_prefix"))]
+    pub zip_code_prefix: String,
+    #[validate(length(min = 1, max = 100))]
+    pub city: String,
+    pub state: BrazilState,
+    /// Makes this the default, in place of the current one. A customer's first address is
+    /// the default either way.
+    #[serde(default)]
+    pub is_default: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize, Validate, Default)]
+pub struct UpdateCustomerAddressDto {
+    #[validate(length(min = 1, max = 50))]
+    pub label: Option<String>,
+    #[validate(custom(function = "validate_zip_code_prefix"))]
+    pub zip_code_prefix: Option<String>,
+    #[validate(length(min = 1, max = 100))]
+    pub city: Option<String>,
+    pub state: Option<BrazilState>,
+    /// `true` makes this the default in place of the current one; `false` leaves the customer
+    /// without one.
+    pub is_default: Option<bool>,
+}
+
+/// Confirmation body for delete endpoints, sent when the client asks for
+/// `Prefer: return=representation` instead of a bare `204`.
+#[derive(Debug, Serialize)]
+pub struct DeleteReceipt {
+    pub deleted: bool,
+    pub id: String,
+    pub deleted_at: chrono::NaiveDateTime,
+}
+
+impl DeleteReceipt {
+    pub fn new(id: impl Into<String>, deleted_at: chrono::NaiveDateTime) -> Self {
+        Self {
+            deleted: true,
+            id: id.into(),
+            deleted_at,
+        }
+    }
+}
+
+/// One of the 27 federative units (26 states and the Federal District), by its UF code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum BrazilState {
+    #[serde(rename = "AC")]
+    Acre,
+    #[serde(rename = "AL")]
+    Alagoas,
+    #[serde(rename = "AP")]
+    Amapa,
+    #[serde(rename = "AM")]
+    Amazonas,
+    #[serde(rename = "BA")]
+    Bahia,
+    #[serde(rename = "CE")]
+    Ceara,
+    #[serde(rename = "DF")]
+    DistritoFederal,
+    #[serde(rename = "ES")]
+    EspiritoSanto,
+    #[serde(rename = "GO")]
+    Goias,
+    #[serde(rename = "MA")]
+    Maranhao,
+    #[serde(rename = "MT")]
+    MatoGrosso,
+    #[serde(rename = "MS")]
+    MatoGrossoDoSul,
+    #[serde(rename = "MG")]
+    MinasGerais,
+    #[serde(rename = "PA")]
+    Para,
+    #[serde(rename = "PB")]
+    Paraiba,
+    #[serde(rename = "PR")]
+    Parana,
+    #[serde(rename = "PE")]
+    Pernambuco,
+    #[serde(rename = "PI")]
+    Piaui,
+    #[serde(rename = "RJ")]
+    RioDeJaneiro,
+    #[serde(rename = "RN")]
+    RioGrandeDoNorte,
+    #[serde(rename = "RS")]
+    RioGrandeDoSul,
+    #[serde(rename = "RO")]
+    Rondonia,
+    #[serde(rename = "RR")]
+    Roraima,
+    #[serde(rename = "SC")]
+    SantaCatarina,
+    #[serde(rename = "SP")]
+    SaoPaulo,
+    #[serde(rename = "SE")]
+    Sergipe,
+    #[serde(rename = "TO")]
+    Tocantins,
+}
+
+impl BrazilState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BrazilState::Acre => "AC",
+            BrazilState::Alagoas => "AL",
+            BrazilState::Amapa => "AP",
+            BrazilState::Amazonas => "AM",
+            BrazilState::Bahia => "BA",
+            BrazilState::Ceara => "CE",
+            BrazilState::DistritoFederal => "DF",
+            BrazilState::EspiritoSanto => "ES",
+            BrazilState::Goias => "GO",
+            BrazilState::Maranhao => "MA",
+            BrazilState::MatoGrosso => "MT",
+            BrazilState::MatoGrossoDoSul => "MS",
+            BrazilState::MinasGerais => "MG",
+            BrazilState::Para => "PA",
+            BrazilState::Paraiba => "PB",
+            BrazilState::Parana => "PR",
+            BrazilState::Pernambuco => "PE",
+            BrazilState::Piaui => "PI",
+            BrazilState::RioDeJaneiro => "RJ",
+            BrazilState::RioGrandeDoNorte => "RN",
+            BrazilState::RioGrandeDoSul => "RS",
+            BrazilState::Rondonia => "RO",
+            BrazilState::Roraima => "RR",
+            BrazilState::SantaCatarina => "SC",
+            BrazilState::SaoPaulo => "SP",
+            BrazilState::Sergipe => "SE",
+            BrazilState::Tocantins => "TO",
+        }
+    }
+}
+
+/// CEP prefixes are the first five digits of a postal code; the lowest assigned CEP is 01000.
+fn validate_zip_code_prefix(prefix: &str) -> Result<(), validator::ValidationError> {
+    let valid =
+        prefix.len() == 5 && prefix.bytes().all(|b| b.is_ascii_digit()) && prefix >= "01000";
+    if valid {
+        Ok(())
+    } else {
+        Err(validator::ValidationError::new("zip_code_prefix")
+            .with_message("must be a 5-digit CEP prefix between 01000 and 99999".into()))
+    }
+}
+
+/// A distinct value of a filterable column and how many rows carry it, for populating
+/// filter dropdowns.
+#[derive(Debug, FromRow, Serialize, Deserialize, Clone)]
+pub struct FilterValue {
+    pub value: String,
+    pub count: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CityValuesQuery {
+    pub state: Option<BrazilState>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct CreateCustomerDto {
+    /// Generated by the server when omitted.
+    #[validate(custom(function = "validate_olist_id"))]
+    pub customer_id: Option<CustomerId>,
+    #[validate(length(min = 1))]
+    pub customer_unique_id: String,
+    /// Filled from `customer_cep` when omitted.
+    #[serde(default)]
+    #[validate(custom(function = "validate_zip_code_prefix"))]
+    pub customer_zip_code_prefix: String,
+    /// Filled from `customer_cep` when omitted.
+    #[serde(default)]
+    #[validate(length(min = 1))]
+    pub customer_city: String,
+    pub customer_state: BrazilState,
+    /// Full CEP the location fields are checked against on `POST /customers`; not stored.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub customer_cep: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate, Default)]
+pub struct UpdateCustomerDto {
+    #[validate(length(min = 1))]
+    pub customer_unique_id: Option<String>,
+    #[validate(custom(function = "validate_zip_code_prefix"))]
+    pub customer_zip_code_prefix: Option<String>,
+    #[validate(length(min = 1))]
+    pub customer_city: Option<String>,
+    pub customer_state: Option<BrazilState>,
+}
+
+#[derive(Debug, FromRow, Serialize, Clone)]
+pub struct Seller {
+    pub seller_id: SellerId,
+    pub seller_zip_code_prefix: String,
+    pub seller_city: String,
+    /// Lowercased, accent-folded and alias-mapped city, used by the `city` filter.
+    pub canonical_city: String,
+    pub seller_state: String,
+    /// Badges awarded by the last badge refresh; not selected by write queries.
+    #[sqlx(default)]
+    pub badges: Vec<String>,
+}
+
+impl Seller {
+    /// Fields a listing can be trimmed to with `fields=`.
+    pub const FIELDS: &'static [&'static str] = &[
+        "seller_id",
+        "seller_zip_code_prefix",
+        "seller_city",
+        "canonical_city",
+        "seller_state",
+        "badges",
+    ];
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SellerBadge {
+    FastShipper,
+    TopRated,
+    HighVolume,
+}
+
+impl SellerBadge {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SellerBadge::FastShipper => "fast_shipper",
+            SellerBadge::TopRated => "top_rated",
+            SellerBadge::HighVolume => "high_volume",
+        }
+    }
+}
+
+#[derive(Debug, FromRow, Serialize)]
+pub struct SellerBadgeThreshold {
+    pub badge: String,
+    pub description: String,
+    pub metric: String,
+    pub comparison: String,
+    pub threshold: BigDecimal,
+    pub min_sample: i32,
+}
+
+/// Counts behind a seller's scorecard, over every order with one of their items.
+#[derive(Debug, FromRow, Clone, Default)]
+pub struct SellerMetrics {
+    pub order_count: i64,
+    /// Orders handed to the carrier.
+    pub shipped_count: i64,
+    /// Orders handed to the carrier by the earliest `shipping_limit_date` of the seller's
+    /// items in them.
+    pub on_time_count: i64,
+    pub canceled_count: i64,
+    pub review_count: i64,
+    pub review_average: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScorecardMetric {
+    OnTimeShipmentRate,
+    CancellationRate,
+    ReviewAverage,
+}
+
+/// `A` meets every target, and each missed one drops a grade.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum SellerGrade {
+    A,
+    B,
+    C,
+    D,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ScorecardEntry {
+    pub metric: ScorecardMetric,
+    /// `None` when there is nothing to measure yet, e.g. no reviews.
+    pub value: Option<f64>,
+    /// `gte` or `lte`, like the badge thresholds.
+    pub comparison: &'static str,
+    pub threshold: f64,
+    /// Orders, shipments or reviews the value is computed from.
+    pub sample: i64,
+    pub meets_threshold: Option<bool>,
+}
+
+impl ScorecardEntry {
+    pub fn new(
+        metric: ScorecardMetric,
+        value: Option<f64>,
+        comparison: &'static str,
+        threshold: f64,
+        sample: i64,
+    ) -> Self {
+        let meets_threshold = value.map(|value| match comparison {
+            "lte" => value <= threshold,
+            _ => value >= threshold,
+        });
+        Self {
+            metric,
+            value,
+            comparison,
+            threshold,
+            sample,
+            meets_threshold,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SellerScorecard {
+    pub seller_id: SellerId,
+    pub order_count: i64,
+    /// `None` for sellers with too few orders to grade.
+    pub grade: Option<SellerGrade>,
+    pub metrics: Vec<ScorecardEntry>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct CreateSellerDto {
+    /// Generated by the server when omitted.
+    #[validate(custom(function = "validate_olist_id"))]
+    pub seller_id: Option<SellerId>,
+    #[validate(custom(function = "validate_zip_code_prefix"))]
+    pub seller_zip_code_prefix: String,
+    #[validate(length(min = 1))]
+    pub seller_city: String,
+    pub seller_state: BrazilState,
+}
+
+/// Lifecycle status of an order, stored as its snake_case name in `orders.order_status`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "varchar", rename_all = "snake_case")]
+pub enum OrderStatus {
+    Created,
+    Approved,
+    Invoiced,
+    Processing,
+    Shipped,
+    Delivered,
+    Canceled,
+    Unavailable,
+}
+
+/// Payment method, stored as its snake_case name in `payments.payment_type`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "varchar", rename_all = "snake_case")]
+pub enum PaymentType {
+    CreditCard,
+    DebitCard,
+    Boleto,
+    Voucher,
+    NotDefined,
+}
+
+#[derive(Debug, FromRow, Serialize, Clone)]
+pub struct Order {
+    pub order_id: OrderId,
+    pub customer_id: CustomerId,
+    pub order_status: OrderStatus,
+    pub order_purchase_timestamp: chrono::NaiveDateTime,
+    pub order_approved_at: chrono::NaiveDateTime,
+    pub order_delivered_carrier_date: Option<chrono::NaiveDateTime>,
+    pub order_delivered_customer_date: Option<chrono::NaiveDateTime>,
+    pub order_estimated_delivery_date: chrono::NaiveDateTime,
+}
+
+impl Order {
+    /// Fields a listing can be trimmed to with `fields=`.
+    pub const FIELDS: &'static [&'static str] = &[
+        "order_id",
+        "customer_id",
+        "order_status",
+        "order_purchase_timestamp",
+        "order_approved_at",
+        "order_delivered_carrier_date",
+        "order_delivered_customer_date",
+        "order_estimated_delivery_date",
+    ];
+}
+
+/// An order past its estimated delivery date without a delivered date, as flagged by the
+/// late-delivery check.
+#[derive(Debug, FromRow, Serialize)]
+pub struct LateOrder {
+    #[sqlx(flatten)]
+    #[serde(flatten)]
+    pub order: Order,
+    /// When the check found it late.
+    pub flagged_at: chrono::NaiveDateTime,
+    /// Whole days since its estimated delivery date.
+    pub days_late: i64,
+}
+
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct CreateOrderDto {
+    /// Generated by the server when omitted.
+    #[validate(custom(function = "validate_olist_id"))]
+    pub order_id: Option<OrderId>,
+    #[validate(custom(function = "validate_olist_id"))]
+    pub customer_id: CustomerId,
+    pub order_status: OrderStatus,
+    pub order_purchase_timestamp: chrono::NaiveDateTime,
+    pub order_approved_at: chrono::NaiveDateTime,
+    pub order_delivered_carrier_date: Option<chrono::NaiveDateTime>,
+    pub order_delivered_customer_date: Option<chrono::NaiveDateTime>,
+    pub order_estimated_delivery_date: chrono::NaiveDateTime,
+    /// One of the customer's addresses to ship to. The customer's default address is used
+    /// when omitted, and their own location when they have none.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shipping_address_id: Option<i64>,
+}
+
+#[derive(Debug, FromRow, Serialize, Clone)]
+pub struct OrderProduct {
+    pub product_id: ProductId,
+    pub product_category_name: String,
+    pub product_name_lenght: i32,
+    pub product_description_lenght: i32,
+    pub product_photos_qty: i32,
+    pub product_weight_g: i32,
+    pub product_length_cm: i32,
+    pub product_height_cm: i32,
+    pub product_width_cm: i32,
+    pub shipping_limit_date: chrono::NaiveDateTime,
+    pub price: Money,
+    pub freight_value: Money,
+    #[sqlx(default)]
+    pub currency: Currency,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OrderProductResponse {
+    pub products: Vec<OrderProduct>,
+    /// Coupon discounts on the items' prices, already taken off `total_value`.
+    pub discounts: Vec<OrderDiscount>,
+    pub total_value: Money,
+    /// What has been refunded of the order's payments. `total_value` is not reduced by it.
+    pub refunded_value: Money,
+    /// Currency of every amount above, [`Money::CURRENCY`].
+    pub currency: Currency,
+}
+
+/// One order in a customer data export, with everything attached to it.
+#[derive(Debug, Serialize)]
+pub struct OrderExport {
+    #[serde(flatten)]
+    pub order: Order,
+    pub items: Vec<OrderProduct>,
+    pub payments: Vec<Payment>,
+    pub refunds: Vec<Refund>,
+    pub reviews: Vec<Review>,
+}
+
+/// Row encoding for bulk entity exports.
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    Ndjson,
+}
+
+impl ExportFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+            ExportFormat::Ndjson => "application/x-ndjson",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Ndjson => "ndjson",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExportQuery {
+    #[serde(default)]
+    pub format: ExportFormat,
+}
+
+#[derive(Debug, Deserialize, Default, PartialEq)]
+pub struct OrderFilter {
+    pub status: Option<OrderStatus>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OrderSearchQuery {
+    pub page: Option<u32>,
+    pub page_size: Option<u32>,
+    /// Comma-separated subset of fields to return.
+    pub fields: Option<String>,
+    pub include_total: Option<TotalMode>,
+    pub status: Option<OrderStatus>,
+}
+
+impl OrderSearchQuery {
+    pub fn pagination(&self) -> PaginationParams {
+        PaginationParams {
+            page: self.page,
+            page_size: self.page_size,
+        }
+    }
+
+    pub fn total_mode(&self) -> TotalMode {
+        self.include_total.unwrap_or_default()
+    }
+
+    pub fn fields(&self) -> Result<Option<Vec<&'static str>>, validator::ValidationErrors> {
+        parse_fields(&self.fields, Order::FIELDS)
+    }
+
+    pub fn filter(&self) -> OrderFilter {
+        OrderFilter {
+            status: self.status,
+        }
+    }
+}
+
+/// Largest sample `GET /orders/sample` returns.
+pub const MAX_SAMPLE_SIZE: i64 = 10_000;
+
+/// Dimensions an order sample can be stratified by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SampleStratum {
+    State,
+    Status,
+    PurchaseMonth,
+}
+
+impl SampleStratum {
+    /// SQL expression over `orders o JOIN customers c` identifying the stratum.
+    pub fn column(&self) -> &'static str {
+        match self {
+            SampleStratum::State => "c.customer_state",
+            SampleStratum::Status => "o.order_status",
+            SampleStratum::PurchaseMonth => "date_trunc('month', o.order_purchase_timestamp)",
+        }
+    }
+}
+
+impl std::str::FromStr for SampleStratum {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_lowercase().as_str() {
+            "state" => Ok(SampleStratum::State),
+            "status" => Ok(SampleStratum::Status),
+            "purchase_month" => Ok(SampleStratum::PurchaseMonth),
+            other => Err(format!("unknown stratum '{}'", other)),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OrderSampleQuery {
+    pub n: Option<i64>,
+    /// Comma-separated strata, e.g. `state,status`.
+    pub stratify_by: Option<String>,
+    pub seed: Option<i64>,
+}
+
+impl OrderSampleQuery {
+    pub fn size(&self) -> i64 {
+        self.n.unwrap_or(1000).clamp(1, MAX_SAMPLE_SIZE)
+    }
+
+    pub fn seed(&self) -> i64 {
+        self.seed.unwrap_or(0)
+    }
+
+    pub fn strata(&self) -> Result<Vec<SampleStratum>, validator::ValidationErrors> {
+        let mut strata: Vec<SampleStratum> = Vec::new();
+        for name in self
+            .stratify_by
+            .as_deref()
+            .unwrap_or_default()
+            .split(',')
+            .filter(|name| !name.trim().is_empty())
+        {
+            let stratum = name.parse().map_err(|message: String| {
+                let mut errors = validator::ValidationErrors::new();
+                errors.add(
+                    "stratify_by",
+                    validator::ValidationError::new("unknown_stratum").with_message(message.into()),
+                );
+                errors
+            })?;
+            if !strata.contains(&stratum) {
+                strata.push(stratum);
+            }
+        }
+        Ok(strata)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct OrderSample {
+    pub seed: i64,
+    pub stratify_by: Vec<SampleStratum>,
+    pub size: usize,
+    pub orders: Vec<Order>,
+}
+
+/// Longest `GET /orders/{id}/status` may hold a request open.
+pub const MAX_STATUS_WAIT_SECS: u64 = 60;
+
+/// An order's status and its version, bumped by the database on every status change.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct OrderStatusChange {
+    pub order_id: OrderId,
+    pub order_status: OrderStatus,
+    pub status_version: i32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OrderStatusWaitQuery {
+    /// How long to wait for a change, e.g. `30s`, `1500ms` or `1m`. Plain numbers are seconds.
+    pub wait: Option<String>,
+    pub since_version: Option<i32>,
+}
+
+impl OrderStatusWaitQuery {
+    pub fn wait(&self) -> Result<std::time::Duration, validator::ValidationErrors> {
+        let Some(wait) = self.wait.as_deref().map(str::trim) else {
+            return Ok(std::time::Duration::from_secs(30));
+        };
+
+        let (amount, millis_per_unit) = if let Some(amount) = wait.strip_suffix("ms") {
+            (amount, 1)
+        } else if let Some(amount) = wait.strip_suffix('s') {
+            (amount, 1_000)
+        } else if let Some(amount) = wait.strip_suffix('m') {
+            (amount, 60_000)
+        } else {
+            (wait, 1_000)
+        };
+
+        let millis = amount.parse::<u64>().map_err(|_| {
+            let mut errors = validator::ValidationErrors::new();
+            errors.add(
+                "wait",
+                validator::ValidationError::new("invalid_duration")
+                    .with_message(format!("Invalid wait duration: {}", wait).into()),
+            );
+            errors
+        })?;
+
+        Ok(std::time::Duration::from_millis(
+            millis
+                .saturating_mul(millis_per_unit)
+                .min(MAX_STATUS_WAIT_SECS * 1_000),
+        ))
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct OrderStatusPoll {
+    #[serde(flatten)]
+    pub status: OrderStatusChange,
+    /// `false` when the wait elapsed without the version moving past `since_version`.
+    pub changed: bool,
+}
+
+/// An entry of the live order feed behind `GET /orders/stream`.
+#[derive(Debug, Clone)]
+pub enum OrderFeedEvent {
+    Created(Order),
+    StatusChanged(OrderStatusChange),
+}
+
+impl OrderFeedEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            OrderFeedEvent::Created(_) => "order.created",
+            OrderFeedEvent::StatusChanged(_) => "order.status_changed",
+        }
+    }
+}
+
+#[derive(Debug, FromRow, Serialize, Deserialize, Clone)]
+pub struct Product {
+    pub product_id: ProductId,
+    pub product_category_name: String,
+    pub product_name_lenght: i32,
+    pub product_description_lenght: i32,
+    pub product_photos_qty: i32,
+    pub product_weight_g: i32,
+    pub product_length_cm: i32,
+    pub product_height_cm: i32,
+    pub product_width_cm: i32,
+    /// Catalog fields the Olist data doesn't have; only `active` is always set.
+    pub product_name: Option<String>,
+    pub description: Option<String>,
+    pub price: Option<Money>,
+    /// Whether the product is on sale. Imported products are.
+    pub active: bool,
+    #[sqlx(default)]
+    #[serde(default)]
+    pub currency: Currency,
+}
+
+impl Product {
+    /// Fields a listing can be trimmed to with `fields=`.
+    pub const FIELDS: &'static [&'static str] = &[
+        "product_id",
+        "product_category_name",
+        "product_name_lenght",
+        "product_description_lenght",
+        "product_photos_qty",
+        "product_weight_g",
+        "product_length_cm",
+        "product_height_cm",
+        "product_width_cm",
+        "product_name",
+        "description",
+        "price",
+        "active",
+    ];
+}
+
+/// An image of a product, kept in object storage. A product's images are ordered by
+/// `position`, from 1.
+#[derive(Debug, FromRow, Serialize, Clone)]
+pub struct ProductImage {
+    pub image_id: i64,
+    pub product_id: ProductId,
+    pub position: i32,
+    pub url: String,
+    /// Key of the object in storage.
+    #[serde(skip_serializing)]
+    pub storage_key: String,
+    pub content_type: String,
+    pub size_bytes: i64,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+/// An image just stored, to be added after the product's last one.
+#[derive(Debug, Clone)]
+pub struct NewProductImage {
+    pub storage_key: String,
+    pub url: String,
+    pub content_type: String,
+    pub size_bytes: i64,
+}
+
+#[derive(Debug, FromRow, Serialize)]
+pub struct SimilarProduct {
+    #[sqlx(flatten)]
+    #[serde(flatten)]
+    pub product: Product,
+    pub distance: f64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SimilarProductsQuery {
+    pub limit: Option<u32>,
+}
+
+impl SimilarProductsQuery {
+    pub fn limit(&self) -> i64 {
+        self.limit.unwrap_or(10).clamp(1, 50) as i64
+    }
+}
+
+/// A product recommended to a customer, with how many orders from their state include it.
+#[derive(Debug, FromRow, Serialize)]
+pub struct RecommendedProduct {
+    #[sqlx(flatten)]
+    #[serde(flatten)]
+    pub product: Product,
+    pub state_order_count: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RecommendationsQuery {
+    pub limit: Option<u32>,
+}
+
+impl RecommendationsQuery {
+    pub fn limit(&self) -> i64 {
+        self.limit.unwrap_or(10).clamp(1, 50) as i64
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct CreateProductDto {
+    /// Generated by the server when omitted.
+    #[validate(custom(function = "validate_olist_id"))]
+    pub product_id: Option<ProductId>,
+    #[validate(length(min = 1))]
+    pub product_category_name: String,
+    pub product_name_lenght: i32,
+    pub product_description_lenght: i32,
+    pub product_photos_qty: i32,
+    pub product_weight_g: i32,
+    pub product_length_cm: i32,
+    pub product_height_cm: i32,
+    pub product_width_cm: i32,
+    #[validate(length(min = 1, max = 255))]
+    pub product_name: Option<String>,
+    pub description: Option<String>,
+    #[validate(custom(function = "validate_product_price"))]
+    pub price: Option<BigDecimal>,
+    /// Defaults to `true`.
+    pub active: Option<bool>,
+}
+
+/// Changes to a product's catalog fields; omitted fields are kept.
+#[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateProductDto {
+    #[validate(length(min = 1, max = 255))]
+    pub product_name: Option<String>,
+    pub description: Option<String>,
+    #[validate(custom(function = "validate_product_price"))]
+    pub price: Option<BigDecimal>,
+    pub active: Option<bool>,
+}
+
+fn validate_product_price(price: &BigDecimal) -> Result<(), validator::ValidationError> {
+    if *price >= BigDecimal::zero()
+        && *price < BigDecimal::from(100_000_000)
+        && price.fractional_digit_count() <= 2
+    {
+        Ok(())
+    } else {
+        Err(validator::ValidationError::new("product_price")
+            .with_message("must be between 0 and 99999999.99 with at most two decimals".into()))
+    }
+}
+
+#[derive(Debug, FromRow, Serialize, Clone)]
+pub struct Category {
+    pub product_category_name: String,
+    pub product_category_name_english: Option<String>,
+    pub created_at: chrono::NaiveDateTime,
+    pub updated_at: chrono::NaiveDateTime,
+}
+
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct CreateCategoryDto {
+    #[validate(length(min = 1, max = 100))]
+    pub product_category_name: String,
+    #[validate(length(min = 1, max = 100))]
+    pub product_category_name_english: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct UpdateCategoryDto {
+    #[validate(length(min = 1, max = 100))]
+    pub product_category_name_english: String,
+}
+
+#[derive(Debug, Deserialize, Default, PartialEq)]
+pub struct ProductFilter {
+    pub category_name: Option<String>,
+    pub min_weight_g: Option<i32>,
+    pub max_weight_g: Option<i32>,
+    pub min_length_cm: Option<i32>,
+    pub max_length_cm: Option<i32>,
+    pub min_height_cm: Option<i32>,
+    pub max_height_cm: Option<i32>,
+    pub min_width_cm: Option<i32>,
+    pub max_width_cm: Option<i32>,
+    pub min_photos: Option<i32>,
+    pub max_photos: Option<i32>,
+    pub min_volume_cm3: Option<i64>,
+    pub max_volume_cm3: Option<i64>,
+    pub active: Option<bool>,
+    pub min_price: Option<BigDecimal>,
+    pub max_price: Option<BigDecimal>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[validate(schema(function = "validate_product_ranges"))]
+pub struct ProductSearchQuery {
+    pub page: Option<u32>,
+    pub page_size: Option<u32>,
+    /// Comma-separated subset of fields to return.
+    pub fields: Option<String>,
+    pub include_total: Option<TotalMode>,
+    pub category_name: Option<String>,
+    #[validate(range(min = 0))]
+    pub min_weight_g: Option<i32>,
+    #[validate(range(min = 0))]
+    pub max_weight_g: Option<i32>,
+    #[validate(range(min = 0))]
+    pub min_length_cm: Option<i32>,
+    #[validate(range(min = 0))]
+    pub max_length_cm: Option<i32>,
+    #[validate(range(min = 0))]
+    pub min_height_cm: Option<i32>,
+    #[validate(range(min = 0))]
+    pub max_height_cm: Option<i32>,
+    #[validate(range(min = 0))]
+    pub min_width_cm: Option<i32>,
+    #[validate(range(min = 0))]
+    pub max_width_cm: Option<i32>,
+    #[validate(range(min = 0))]
+    pub min_photos: Option<i32>,
+    #[validate(range(min = 0))]
+    pub max_photos: Option<i32>,
+    /// Length × height × width.
+    #[validate(range(min = 0))]
+    pub min_volume_cm3: Option<i64>,
+    #[validate(range(min = 0))]
+    pub max_volume_cm3: Option<i64>,
+    pub active: Option<bool>,
+    /// Products without a price never match a price bound.
+    pub min_price: Option<BigDecimal>,
+    pub max_price: Option<BigDecimal>,
+}
+
+fn validate_product_ranges(query: &ProductSearchQuery) -> Result<(), validator::ValidationError> {
+    let ranges = [
+        (
+            "weight_g",
+            query.min_weight_g.map(i64::from),
+            query.max_weight_g.map(i64::from),
+        ),
+        (
+            "length_cm",
+            query.min_length_cm.map(i64::from),
+            query.max_length_cm.map(i64::from),
+        ),
+        (
+            "height_cm",
+            query.min_height_cm.map(i64::from),
+            query.max_height_cm.map(i64::from),
+        ),
+        (
+            "width_cm",
+            query.min_width_cm.map(i64::from),
+            query.max_width_cm.map(i64::from),
+        ),
+        (
+            "photos",
+            query.min_photos.map(i64::from),
+            query.max_photos.map(i64::from),
+        ),
+        ("volume_cm3", query.min_volume_cm3, query.max_volume_cm3),
+    ];
+
+    for (name, min, max) in ranges {
+        if let (Some(min), Some(max)) = (min, max)
+            && min > max
+        {
+            return Err(
+                validator::ValidationError::new("inverted_range").with_message(
+                    format!("min_{} must not be greater than max_{}", name, name).into(),
+                ),
+            );
+        }
+    }
+
+    let zero = BigDecimal::zero();
+    if [&query.min_price, &query.max_price]
+        .into_iter()
+        .flatten()
+        .any(|price| *price < zero)
+    {
+        return Err(validator::ValidationError::new("price")
+            .with_message("min_price and max_price must not be negative".into()));
+    }
+    if let (Some(min), Some(max)) = (&query.min_price, &query.max_price)
+        && min > max
+    {
+        return Err(validator::ValidationError::new("inverted_range")
+            .with_message("min_price must not be greater than max_price".into()));
+    }
+    Ok(())
+}
+
+impl ProductSearchQuery {
+    pub fn pagination(&self) -> PaginationParams {
+        PaginationParams {
+            page: self.page,
+            page_size: self.page_size,
+        }
+    }
+
+    pub fn total_mode(&self) -> TotalMode {
+        self.include_total.unwrap_or_default()
+    }
+
+    pub fn fields(&self) -> Result<Option<Vec<&'static str>>, validator::ValidationErrors> {
+        parse_fields(&self.fields, Product::FIELDS)
+    }
+
+    pub fn filter(&self) -> ProductFilter {
+        ProductFilter {
+            category_name: self.category_name.clone(),
+            min_weight_g: self.min_weight_g,
+            max_weight_g: self.max_weight_g,
+            min_length_cm: self.min_length_cm,
+            max_length_cm: self.max_length_cm,
+            min_height_cm: self.min_height_cm,
+            max_height_cm: self.max_height_cm,
+            min_width_cm: self.min_width_cm,
+            max_width_cm: self.max_width_cm,
+            min_photos: self.min_photos,
+            max_photos: self.max_photos,
+            min_volume_cm3: self.min_volume_cm3,
+            max_volume_cm3: self.max_volume_cm3,
+            active: self.active,
+            min_price: self.min_price.clone(),
+            max_price: self.max_price.clone(),
+        }
+    }
+}
+
+#[derive(Debug, FromRow, Serialize, Clone)]
+pub struct Payment {
+    pub order_id: OrderId,
+    pub payment_sequential: i32,
+    pub payment_type: PaymentType,
+    pub payment_installments: i32,
+    pub payment_value: Money,
+    #[sqlx(default)]
+    pub currency: Currency,
+}
+
+/// Money returned to the customer out of one of an order's payments.
+#[derive(Debug, FromRow, Serialize, Clone)]
+pub struct Refund {
+    pub refund_id: i64,
+    pub order_id: OrderId,
+    pub payment_sequential: i32,
+    pub amount: Money,
+    pub reason: Option<String>,
+    pub created_at: chrono::NaiveDateTime,
+    #[sqlx(default)]
+    pub currency: Currency,
+}
+
+#[derive(Debug, Clone)]
+pub struct NewRefund {
+    pub order_id: OrderId,
+    pub payment_sequential: i32,
+    pub amount: BigDecimal,
+    pub reason: Option<String>,
+}
+
+/// A refund of one payment. Without an amount, whatever is left of the payment is refunded.
+#[derive(Debug, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct CreateRefundDto {
+    #[validate(range(min = 1))]
+    pub payment_sequential: i32,
+    #[validate(custom(function = "validate_payment_amount"))]
+    pub amount: Option<BigDecimal>,
+    #[validate(length(max = 500))]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, FromRow, Serialize, Clone)]
+pub struct Review {
+    pub review_id: String,
+    pub order_id: OrderId,
+    pub review_score: i32,
+    pub review_comment_title: Option<String>,
+    pub review_comment_message: Option<String>,
+    pub review_creation_date: chrono::NaiveDateTime,
+    pub review_answer_timestamp: chrono::NaiveDateTime,
+}
+
+/// A raw review comment, before cleaning for the NLP corpus.
+#[derive(Debug, FromRow)]
+pub struct ReviewText {
+    pub review_id: String,
+    pub review_score: i32,
+    pub review_comment_message: String,
+}
+
+/// Tone of a review comment, stored as its snake_case name in `review_sentiments.sentiment`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "varchar", rename_all = "snake_case")]
+pub enum Sentiment {
+    Positive,
+    Neutral,
+    Negative,
+}
+
+impl Sentiment {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Sentiment::Positive => "positive",
+            Sentiment::Neutral => "neutral",
+            Sentiment::Negative => "negative",
+        }
+    }
+}
+
+/// A review with the sentiment tagged on its comment; both are `None` until the tagging job
+/// has reached it, and stay so for reviews without a comment.
+#[derive(Debug, FromRow, Serialize)]
+pub struct SentimentReview {
+    #[sqlx(flatten)]
+    #[serde(flatten)]
+    pub review: Review,
+    pub sentiment: Option<Sentiment>,
+    pub sentiment_tagged_at: Option<chrono::NaiveDateTime>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReviewQuery {
+    pub page: Option<u32>,
+    pub page_size: Option<u32>,
+    pub sentiment: Option<Sentiment>,
+}
+
+impl ReviewQuery {
+    pub fn pagination(&self) -> PaginationParams {
+        PaginationParams {
+            page: self.page,
+            page_size: self.page_size,
+        }
+    }
+}
+
+/// Languages available in the review corpus. The Olist reviews are all Brazilian Portuguese.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CorpusLanguage {
+    #[default]
+    Pt,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReviewCorpusQuery {
+    #[serde(default)]
+    pub lang: CorpusLanguage,
+    /// Minimum length of the cleaned text, in characters.
+    pub min_length: Option<usize>,
+    /// Upper bound on rows for this request; the key's remaining hourly quota also applies.
+    pub limit: Option<u64>,
+}
+
+/// One line of the review corpus export.
+#[derive(Debug, Serialize)]
+pub struct CorpusRecord {
+    pub review_id: String,
+    pub review_score: i32,
+    pub lang: CorpusLanguage,
+    pub text: String,
+}
+
+#[derive(Debug, FromRow, Serialize, Clone)]
+pub struct OrderItem {
+    pub order_item_id: i32,
+    pub order_id: OrderId,
+    pub product_id: ProductId,
+    pub seller_id: SellerId,
+    pub shipping_limit_date: chrono::NaiveDateTime,
+    pub price: Money,
+    pub freight_value: Money,
+    #[sqlx(default)]
+    pub currency: Currency,
+}
+
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct AddItemToOrderDto {
+    pub order_item_id: i32,
+    #[validate(custom(function = "validate_olist_id"))]
+    pub product_id: ProductId,
+    #[validate(custom(function = "validate_olist_id"))]
+    pub seller_id: SellerId,
+    pub shipping_limit_date: chrono::NaiveDateTime,
+    pub price: BigDecimal,
+    pub freight_value: BigDecimal,
+}
+
+#[derive(Debug, FromRow, Serialize, Clone)]
+pub struct AuditEntry {
+    pub audit_id: i64,
+    pub entity_type: String,
+    pub entity_id: String,
+    pub action: String,
+    pub actor: String,
+    pub diff: serde_json::Value,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+#[derive(Debug)]
+pub struct NewAuditEntry {
+    pub entity_type: &'static str,
+    pub entity_id: String,
+    pub action: AuditAction,
+    pub actor: String,
+    pub diff: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    Create,
+    Update,
+    Delete,
+    Restore,
+    Anonymize,
+    Rollback,
+    Merge,
+}
+
+impl AuditAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditAction::Create => "create",
+            AuditAction::Update => "update",
+            AuditAction::Delete => "delete",
+            AuditAction::Restore => "restore",
+            AuditAction::Anonymize => "anonymize",
+            AuditAction::Rollback => "rollback",
+            AuditAction::Merge => "merge",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct AuditFilter {
+    pub entity_type: Option<String>,
+    pub entity_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AuditSearchQuery {
+    pub page: Option<u32>,
+    pub page_size: Option<u32>,
+    pub entity: Option<String>,
+    pub id: Option<String>,
+}
+
+impl AuditSearchQuery {
+    pub fn pagination(&self) -> PaginationParams {
+        PaginationParams {
+            page: self.page,
+            page_size: self.page_size,
+        }
+    }
+
+    pub fn filter(&self) -> AuditFilter {
+        AuditFilter {
+            entity_type: self.entity.clone(),
+            entity_id: self.id.clone(),
+        }
+    }
+}
+
+#[derive(Debug, FromRow, Serialize, Clone)]
+pub struct StockLocation {
+    pub location_id: i64,
+    pub seller_id: SellerId,
+    pub name: String,
+    pub zip_code_prefix: String,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct CreateStockLocationDto {
+    #[validate(length(min = 1, max = 100))]
+    pub name: String,
+    #[validate(custom(function = "validate_zip_code_prefix"))]
+    pub zip_code_prefix: String,
+}
+
+#[derive(Debug, FromRow, Serialize, Clone)]
+pub struct LocationStock {
+    pub location_id: i64,
+    pub product_id: ProductId,
+    pub quantity: i32,
+    pub updated_at: chrono::NaiveDateTime,
+}
+
+/// A product's stock at one location, with the location it is held at.
+#[derive(Debug, FromRow, Serialize, Clone)]
+pub struct ProductLocationStock {
+    pub location_id: i64,
+    pub seller_id: SellerId,
+    pub name: String,
+    pub zip_code_prefix: String,
+    pub quantity: i32,
+    pub updated_at: chrono::NaiveDateTime,
+}
+
+/// Stock of a product across every seller location tracking it.
+#[derive(Debug, Serialize)]
+pub struct ProductStock {
+    pub product_id: ProductId,
+    pub total_quantity: i64,
+    pub locations: Vec<ProductLocationStock>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct SetStockDto {
+    #[validate(range(min = 0))]
+    pub quantity: i32,
+}
+
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct AdjustStockDto {
+    pub delta: i32,
+}
+
+#[derive(Debug, FromRow, Serialize, Clone)]
+pub struct StockAllocation {
+    pub location_id: i64,
+    pub product_id: ProductId,
+    pub quantity: i32,
+}
+
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct AmendOrderDto {
+    #[validate(custom(function = "validate_zip_code_prefix"))]
+    pub shipping_zip_code_prefix: Option<String>,
+    #[serde(default)]
+    #[validate(nested)]
+    pub item_swaps: Vec<ItemSwapDto>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct ItemSwapDto {
+    pub order_item_id: i32,
+    #[validate(custom(function = "validate_olist_id"))]
+    pub product_id: ProductId,
+    /// New unit price; the current price is kept when omitted.
+    pub price: Option<BigDecimal>,
+}
+
+/// An order item together with the seller zip prefix it ships from.
+#[derive(Debug, FromRow, Clone)]
+pub struct OrderItemOrigin {
+    #[sqlx(flatten)]
+    pub item: OrderItem,
+    pub seller_zip_code_prefix: String,
+}
+
+#[derive(Debug, FromRow, Serialize, Clone)]
+pub struct OrderAmendment {
+    pub amendment_id: i64,
+    pub order_id: OrderId,
+    pub actor: String,
+    pub changes: serde_json::Value,
+    pub previous_freight: Money,
+    pub new_freight: Money,
+    pub previous_tax: Money,
+    pub new_tax: Money,
+    pub created_at: chrono::NaiveDateTime,
+    #[sqlx(default)]
+    pub currency: Currency,
+}
+
+#[derive(Debug)]
+pub struct NewOrderAmendment {
+    pub changes: serde_json::Value,
+    pub previous_freight: BigDecimal,
+    pub new_freight: BigDecimal,
+    pub previous_tax: BigDecimal,
+    pub new_tax: BigDecimal,
+}
+
+/// A package as a carrier sees it. CEPs are the full eight digits.
+#[derive(Debug, Clone, Serialize)]
+pub struct Parcel {
+    pub origin_cep: String,
+    pub destination_cep: String,
+    pub weight_g: i32,
+    pub length_cm: i32,
+    pub height_cm: i32,
+    pub width_cm: i32,
+}
+
+/// What a carrier charges to ship a parcel with one of its services.
+#[derive(Debug, Clone, Serialize)]
+pub struct FreightQuote {
+    pub carrier: &'static str,
+    pub service: String,
+    pub price: Money,
+    /// Business days from posting to delivery, when the carrier says.
+    pub delivery_days: Option<u32>,
+    pub currency: Currency,
+}
+
+/// A parcel to post with one of the carrier's services. `reference` is ours, usually the
+/// order id, and is printed on the label.
+#[derive(Debug, Clone)]
+pub struct Shipment {
+    pub reference: String,
+    pub service: String,
+    pub parcel: Parcel,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ShippingLabel {
+    pub carrier: &'static str,
+    pub tracking_code: String,
+    /// Where the printable label can be downloaded, if the carrier hosts it.
+    pub label_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TrackingEvent {
+    /// The carrier's event code.
+    pub status: String,
+    pub description: String,
+    pub location: Option<String>,
+    pub occurred_at: chrono::NaiveDateTime,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FreightQuoteDto {
+    /// Service to select for every item; the cheapest quote is selected when omitted.
+    pub service: Option<String>,
+    /// Set each item's `freight_value` to its selected quote.
+    #[serde(default)]
+    pub apply: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ItemFreightQuote {
+    pub order_item_id: i32,
+    pub product_id: ProductId,
+    pub seller_id: SellerId,
+    /// The item's freight before this quote.
+    pub freight_value: Money,
+    pub quotes: Vec<FreightQuote>,
+    pub selected: Option<FreightQuote>,
+    pub currency: Currency,
+}
+
+/// Carrier quotes for each item of an order, shipped from its seller to the order's
+/// destination.
+#[derive(Debug, Serialize)]
+pub struct OrderFreightQuote {
+    pub order_id: OrderId,
+    pub destination_zip_code_prefix: String,
+    pub items: Vec<ItemFreightQuote>,
+    /// Sum of the selected quotes, once every item has one.
+    pub total_freight: Option<Money>,
+    /// The amendment recording the new freight, when the quote was applied.
+    pub amendment: Option<OrderAmendment>,
+    pub currency: Currency,
+}
+
+/// What a CEP lookup knows about an address range. Street and neighborhood are only known
+/// for CEPs assigned to a single street.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CepAddress {
+    /// The eight digits, without the dash.
+    pub cep: String,
+    pub street: Option<String>,
+    pub neighborhood: Option<String>,
+    pub city: String,
+    pub state: String,
+}
+
+/// Where a CEP prefix lies: the mean of the Olist geolocation dataset's points for it.
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct ZipLocation {
+    pub geolocation_zip_code_prefix: String,
+    pub geolocation_lat: f64,
+    pub geolocation_lng: f64,
+    pub geolocation_city: String,
+    pub geolocation_state: String,
+}
+
+/// A seller with the coordinates of its zip code prefix.
+#[derive(Debug, FromRow)]
+pub struct LocatedSeller {
+    #[sqlx(flatten)]
+    pub seller: Seller,
+    pub geolocation_lat: f64,
+    pub geolocation_lng: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct NearbySeller {
+    #[serde(flatten)]
+    pub seller: Seller,
+    pub distance_km: f64,
+}
+
+/// Sellers within `radius_km` of a customer, nearest first.
+#[derive(Debug, Serialize)]
+pub struct NearbySellers {
+    pub customer_id: CustomerId,
+    pub customer_zip_code_prefix: String,
+    pub radius_km: f64,
+    pub sellers: Vec<NearbySeller>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct NearbySellersQuery {
+    #[validate(range(exclusive_min = 0.0, max = 5000.0))]
+    pub radius_km: Option<f64>,
+    pub limit: Option<u32>,
+}
+
+impl NearbySellersQuery {
+    pub fn radius_km(&self) -> f64 {
+        self.radius_km.unwrap_or(50.0)
+    }
+
+    pub fn limit(&self) -> usize {
+        self.limit.unwrap_or(20).clamp(1, 100) as usize
+    }
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct FreightEstimateDto {
+    /// Weighed and measured for the estimate.
+    #[validate(custom(function = "validate_olist_id"))]
+    pub product_id: ProductId,
+    /// The seller's CEP prefix.
+    #[validate(custom(function = "validate_zip_code_prefix"))]
+    pub origin_zip_code_prefix: String,
+    /// The customer's CEP prefix.
+    #[validate(custom(function = "validate_zip_code_prefix"))]
+    pub destination_zip_code_prefix: String,
+}
+
+/// Freight for shipping one product between two CEP prefixes, priced by the rate table band
+/// covering their distance.
+#[derive(Debug, Serialize)]
+pub struct FreightEstimate {
+    pub product_id: ProductId,
+    pub origin_zip_code_prefix: String,
+    pub destination_zip_code_prefix: String,
+    /// Great-circle distance between the prefixes, in kilometres.
+    pub distance_km: f64,
+    /// The greater of the product's weight and its volumetric weight.
+    pub billable_weight_g: i64,
+    /// Upper bound of the band used; `None` for the band beyond every limit.
+    pub rate_max_km: Option<f64>,
+    pub freight_value: Money,
+    pub currency: Currency,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiscountType {
+    /// `value` percent off the items.
+    Percentage,
+    /// `value` off the items, never more than they cost.
+    Fixed,
+}
+
+impl DiscountType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DiscountType::Percentage => "percentage",
+            DiscountType::Fixed => "fixed",
+        }
+    }
+}
+
+#[derive(Debug, FromRow, Serialize, Clone)]
+pub struct Coupon {
+    pub code: String,
+    pub discount_type: String,
+    pub value: BigDecimal,
+    /// Item subtotal an order needs for the coupon to apply.
+    pub min_order_value: Option<BigDecimal>,
+    pub expires_at: Option<chrono::NaiveDateTime>,
+    /// Orders the coupon can be applied to; unlimited when `None`.
+    pub max_uses: Option<i32>,
+    pub times_used: i32,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+impl Coupon {
+    /// What the coupon takes off an item subtotal, rounded to the cent.
+    pub fn discount_for(&self, subtotal: &BigDecimal) -> BigDecimal {
+        coupon_discount(&self.discount_type, &self.value, subtotal)
+    }
+}
+
+/// What a coupon of `discount_type` and `value` takes off an item subtotal, rounded to the
+/// cent and never more than the subtotal.
+pub fn coupon_discount(
+    discount_type: &str,
+    value: &BigDecimal,
+    subtotal: &BigDecimal,
+) -> BigDecimal {
+    let discount = if discount_type == DiscountType::Percentage.as_str() {
+        round_to_centavos(&(subtotal * value / BigDecimal::from(100)))
+    } else {
+        value.clone()
+    };
+    discount.min(subtotal.clone()).max(BigDecimal::zero())
+}
+
+#[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
+#[validate(schema(function = "validate_coupon_value"))]
+pub struct CreateCouponDto {
+    /// Stored uppercased.
+    #[validate(custom(function = "validate_coupon_code"))]
+    pub code: String,
+    pub discount_type: DiscountType,
+    pub value: BigDecimal,
+    pub min_order_value: Option<BigDecimal>,
+    pub expires_at: Option<chrono::NaiveDateTime>,
+    #[validate(range(min = 1))]
+    pub max_uses: Option<i32>,
+}
+
+fn validate_coupon_code(code: &str) -> Result<(), validator::ValidationError> {
+    if (3..=40).contains(&code.len())
+        && code
+            .bytes()
+            .all(|b| b.is_ascii_uppercase() || b.is_ascii_digit() || b == b'-' || b == b'_')
+    {
+        Ok(())
+    } else {
+        Err(validator::ValidationError::new("coupon_code")
+            .with_message("must be 3 to 40 letters, digits, '-' or '_'".into()))
+    }
+}
+
+fn validate_coupon_value(dto: &CreateCouponDto) -> Result<(), validator::ValidationError> {
+    let zero = BigDecimal::zero();
+    let valid = match dto.discount_type {
+        DiscountType::Percentage => dto.value > zero && dto.value <= BigDecimal::from(100),
+        DiscountType::Fixed => dto.value > zero,
+    } && dto.min_order_value.as_ref().is_none_or(|min| *min >= zero);
+    if valid {
+        Ok(())
+    } else {
+        Err(validator::ValidationError::new("coupon_value").with_message(
+            "value must be positive (at most 100 for a percentage) and min_order_value not negative"
+                .into(),
+        ))
+    }
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct ApplyCouponDto {
+    #[validate(length(min = 1, max = 40))]
+    pub code: String,
+}
+
+/// A discount line of an order: what its coupon takes off the current items.
+#[derive(Debug, Serialize, Clone)]
+pub struct OrderDiscount {
+    pub code: String,
+    pub discount_type: String,
+    pub amount: Money,
+    pub currency: Currency,
+}
+
+/// An order's sums as read in one query for its financial summary.
+#[derive(Debug, FromRow, Clone)]
+pub struct OrderFinancials {
+    pub order_id: OrderId,
+    pub order_status: OrderStatus,
+    pub item_count: i64,
+    pub items_subtotal: BigDecimal,
+    pub freight_total: BigDecimal,
+    pub payment_count: i64,
+    pub payments_total: BigDecimal,
+    pub refunds_total: BigDecimal,
+    pub coupon_code: Option<String>,
+    pub coupon_discount_type: Option<String>,
+    pub coupon_value: Option<BigDecimal>,
+}
+
+/// What an order is worth and what has been paid for it, for reconciliation.
+#[derive(Debug, Serialize)]
+pub struct OrderSummary {
+    pub order_id: OrderId,
+    pub order_status: OrderStatus,
+    pub item_count: i64,
+    pub items_subtotal: Money,
+    pub freight_total: Money,
+    pub discounts: Vec<OrderDiscount>,
+    pub discount_total: Money,
+    /// Items and freight less discounts: what the payments should add up to.
+    pub order_total: Money,
+    pub payment_count: i64,
+    pub payments_total: Money,
+    /// Refunds are reported apart and do not change the balance.
+    pub refunds_total: Money,
+    /// `order_total` less `payments_total`; negative when the order was overpaid.
+    pub outstanding_balance: Money,
+    /// Whether the payments differ from the order total by a centavo or more.
+    pub payment_mismatch: bool,
+    pub currency: Currency,
+}
+
+/// `GET /analytics/reconciliation` parameters. `from` and `to` bound the purchase date, both
+/// included, and either may be left open.
+#[derive(Debug, Deserialize, Validate)]
+#[validate(schema(function = "validate_reconciliation_query"))]
+pub struct ReconciliationQuery {
+    pub from: Option<chrono::NaiveDate>,
+    pub to: Option<chrono::NaiveDate>,
+    /// Largest difference still taken as a match; a centavo when omitted.
+    pub tolerance: Option<BigDecimal>,
+    pub page: Option<u32>,
+    pub page_size: Option<u32>,
+}
+
+impl ReconciliationQuery {
+    pub fn pagination(&self) -> PaginationParams {
+        PaginationParams {
+            page: self.page,
+            page_size: self.page_size,
+        }
+    }
+
+    pub fn filter(&self) -> ReconciliationFilter {
+        let midnight = |date: chrono::NaiveDate| date.and_time(chrono::NaiveTime::MIN);
+        ReconciliationFilter {
+            purchased_from: self.from.map(midnight),
+            purchased_before: self.to.and_then(|to| to.succ_opt()).map(midnight),
+            tolerance: self
+                .tolerance
+                .clone()
+                .unwrap_or_else(|| BigDecimal::new(1.into(), 2)),
+        }
+    }
+}
+
+fn validate_reconciliation_query(
+    query: &ReconciliationQuery,
+) -> Result<(), validator::ValidationError> {
+    if let (Some(from), Some(to)) = (query.from, query.to)
+        && from > to
+    {
+        return Err(validator::ValidationError::new("date_range")
+            .with_message("from must not be after to".into()));
+    }
+    if query
+        .tolerance
+        .as_ref()
+        .is_some_and(|tolerance| *tolerance < BigDecimal::zero())
+    {
+        return Err(validator::ValidationError::new("tolerance")
+            .with_message("tolerance must not be negative".into()));
+    }
+    Ok(())
+}
+
+/// Orders a reconciliation report looks at: purchased in `[purchased_from, purchased_before)`,
+/// with payments and items further apart than `tolerance`.
+#[derive(Debug, Clone)]
+pub struct ReconciliationFilter {
+    pub purchased_from: Option<chrono::NaiveDateTime>,
+    pub purchased_before: Option<chrono::NaiveDateTime>,
+    pub tolerance: BigDecimal,
+}
+
+/// An order whose payments don't add up to its items' prices and freight.
+#[derive(Debug, FromRow, Serialize, Clone)]
+pub struct PaymentMismatch {
+    pub order_id: OrderId,
+    pub order_status: OrderStatus,
+    pub order_purchase_timestamp: chrono::NaiveDateTime,
+    pub item_count: i64,
+    /// Prices and freight of the items.
+    pub items_total: Money,
+    pub payment_count: i64,
+    pub payments_total: Money,
+    /// `payments_total` less `items_total`; negative when the payments fall short.
+    pub difference: Money,
+    #[sqlx(default)]
+    pub currency: Currency,
+}
+
+/// `GET /analytics/payments` parameters. `from` and `to` bound the purchase date of the paid
+/// orders, both included, and either may be left open.
+#[derive(Debug, Deserialize, Validate)]
+#[validate(schema(function = "validate_payment_analytics_query"))]
+pub struct PaymentAnalyticsQuery {
+    pub from: Option<chrono::NaiveDate>,
+    pub to: Option<chrono::NaiveDate>,
+}
+
+impl PaymentAnalyticsQuery {
+    pub fn filter(&self) -> PaymentAnalyticsFilter {
+        let midnight = |date: chrono::NaiveDate| date.and_time(chrono::NaiveTime::MIN);
+        PaymentAnalyticsFilter {
+            purchased_from: self.from.map(midnight),
+            purchased_before: self.to.and_then(|to| to.succ_opt()).map(midnight),
+        }
+    }
+}
+
+fn validate_payment_analytics_query(
+    query: &PaymentAnalyticsQuery,
+) -> Result<(), validator::ValidationError> {
+    if let (Some(from), Some(to)) = (query.from, query.to)
+        && from > to
+    {
+        return Err(validator::ValidationError::new("date_range")
+            .with_message("from must not be after to".into()));
+    }
+    Ok(())
+}
+
+/// Payments of the orders purchased in `[purchased_from, purchased_before)`.
+#[derive(Debug, Clone)]
+pub struct PaymentAnalyticsFilter {
+    pub purchased_from: Option<chrono::NaiveDateTime>,
+    pub purchased_before: Option<chrono::NaiveDateTime>,
+}
+
+/// One payment method's slice of the payments in a [`PaymentAnalytics`] report.
+#[derive(Debug, FromRow, Serialize, Clone)]
+pub struct PaymentMethodStats {
+    pub payment_type: PaymentType,
+    pub payment_count: i64,
+    /// `payment_count` as a share of all payments in the range.
+    pub share: f64,
+    pub total_value: Money,
+    pub average_installments: f64,
+    /// Mean payment value.
+    pub average_ticket: Money,
+    #[sqlx(default)]
+    pub currency: Currency,
+}
+
+/// `GET /analytics/payments`: how orders purchased in a date range were paid, most used
+/// method first. Archived orders are left out.
+#[derive(Debug, Serialize)]
+pub struct PaymentAnalytics {
+    pub from: Option<chrono::NaiveDate>,
+    pub to: Option<chrono::NaiveDate>,
+    pub payment_count: i64,
+    pub total_value: Money,
+    pub methods: Vec<PaymentMethodStats>,
+    pub currency: Currency,
+}
+
+impl PaymentAnalytics {
+    pub fn new(query: &PaymentAnalyticsQuery, methods: Vec<PaymentMethodStats>) -> Self {
+        Self {
+            from: query.from,
+            to: query.to,
+            payment_count: methods.iter().map(|method| method.payment_count).sum(),
+            total_value: methods.iter().map(|method| &method.total_value).sum(),
+            methods,
+            currency: Money::CURRENCY,
+        }
+    }
+}
+
+/// Where a payment stands at its provider, stored as its snake_case name in
+/// `payment_transactions.status`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "varchar", rename_all = "snake_case")]
+pub enum PaymentStatus {
+    /// The amount is reserved but not yet taken.
+    Authorized,
+    Captured,
+    /// The captured amount went back to the customer.
+    Refunded,
+    /// The provider declined the payment.
+    Failed,
+    /// The authorization was voided before capture.
+    Canceled,
+}
+
+impl PaymentStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PaymentStatus::Authorized => "authorized",
+            PaymentStatus::Captured => "captured",
+            PaymentStatus::Refunded => "refunded",
+            PaymentStatus::Failed => "failed",
+            PaymentStatus::Canceled => "canceled",
+        }
+    }
+
+    /// Whether a payment can move from this status to `next`. Authorizations are captured,
+    /// declined or voided, captures can only be refunded, and the other statuses are final.
+    pub fn can_become(&self, next: PaymentStatus) -> bool {
+        matches!(
+            (self, next),
+            (
+                PaymentStatus::Authorized,
+                PaymentStatus::Captured | PaymentStatus::Failed | PaymentStatus::Canceled
+            ) | (PaymentStatus::Captured, PaymentStatus::Refunded)
+        )
+    }
+}
+
+/// A payment made through a payment provider, as opposed to the dataset's `payments` rows.
+#[derive(Debug, FromRow, Serialize, Clone)]
+pub struct PaymentTransaction {
+    pub transaction_id: i64,
+    pub order_id: OrderId,
+    /// Name of the provider that processed the payment.
+    pub provider: String,
+    /// The provider's id for the payment.
+    pub provider_reference: String,
+    pub payment_type: PaymentType,
+    pub payment_installments: i32,
+    pub amount: Money,
+    pub status: PaymentStatus,
+    pub created_at: chrono::NaiveDateTime,
+    pub updated_at: chrono::NaiveDateTime,
+    #[sqlx(default)]
+    pub currency: Currency,
+}
+
+/// A transaction as first recorded, once the provider has answered the authorization.
+#[derive(Debug, Clone)]
+pub struct NewPaymentTransaction {
+    pub order_id: OrderId,
+    pub provider: String,
+    pub provider_reference: String,
+    pub payment_type: PaymentType,
+    pub payment_installments: i32,
+    pub amount: BigDecimal,
+    pub status: PaymentStatus,
+}
+
+/// What a payment provider is asked to authorize.
+#[derive(Debug, Clone, Serialize)]
+pub struct PaymentRequest {
+    pub order_id: OrderId,
+    pub payment_type: PaymentType,
+    pub payment_installments: i32,
+    pub amount: BigDecimal,
+}
+
+/// A provider's answer to an authorization: its id for the payment and whether it was
+/// authorized or declined.
+#[derive(Debug, Clone)]
+pub struct PaymentAuthorization {
+    pub reference: String,
+    pub status: PaymentStatus,
+}
+
+/// A payment status change the provider reports asynchronously, once its signature has been
+/// checked.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PaymentNotification {
+    /// The provider's id for the payment.
+    pub reference: String,
+    pub status: PaymentStatus,
+}
+
+/// What a provider notification did to its transaction.
+#[derive(Debug, Serialize)]
+pub struct PaymentNotificationResult {
+    pub transaction_id: i64,
+    pub status: PaymentStatus,
+    /// `false` for repeated or out-of-order notifications, which change nothing.
+    pub applied: bool,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct AuthorizePaymentDto {
+    pub payment_type: PaymentType,
+    #[validate(range(min = 1, max = 24))]
+    pub payment_installments: i32,
+    #[validate(custom(function = "validate_payment_amount"))]
+    pub amount: BigDecimal,
+}
+
+fn validate_payment_amount(amount: &BigDecimal) -> Result<(), validator::ValidationError> {
+    if *amount > BigDecimal::zero() && amount.fractional_digit_count() <= 2 {
+        Ok(())
+    } else {
+        Err(validator::ValidationError::new("payment_amount")
+            .with_message("must be positive with at most two decimals".into()))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SupportCategory {
+    DeliveryDelay,
+    DamagedItem,
+    WrongItem,
+    MissingItem,
+    Refund,
+    Payment,
+    Other,
+}
+
+impl SupportCategory {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SupportCategory::DeliveryDelay => "delivery_delay",
+            SupportCategory::DamagedItem => "damaged_item",
+            SupportCategory::WrongItem => "wrong_item",
+            SupportCategory::MissingItem => "missing_item",
+            SupportCategory::Refund => "refund",
+            SupportCategory::Payment => "payment",
+            SupportCategory::Other => "other",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SupportStatus {
+    Open,
+    PendingCustomer,
+    Resolved,
+    Closed,
+}
+
+impl SupportStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SupportStatus::Open => "open",
+            SupportStatus::PendingCustomer => "pending_customer",
+            SupportStatus::Resolved => "resolved",
+            SupportStatus::Closed => "closed",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MessageAuthor {
+    Customer,
+    Agent,
+}
+
+impl MessageAuthor {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MessageAuthor::Customer => "customer",
+            MessageAuthor::Agent => "agent",
+        }
+    }
+}
+
+#[derive(Debug, FromRow, Serialize, Clone)]
+pub struct SupportCase {
+    pub case_id: i64,
+    pub order_id: OrderId,
+    pub customer_id: CustomerId,
+    pub category: String,
+    pub status: String,
+    pub subject: String,
+    pub created_at: chrono::NaiveDateTime,
+    pub updated_at: chrono::NaiveDateTime,
+    pub first_response_due_at: chrono::NaiveDateTime,
+    pub resolution_due_at: chrono::NaiveDateTime,
+    pub first_responded_at: Option<chrono::NaiveDateTime>,
+    pub resolved_at: Option<chrono::NaiveDateTime>,
+    pub first_response_breached: bool,
+    pub resolution_breached: bool,
+}
+
+#[derive(Debug, FromRow, Serialize, Clone)]
+pub struct SupportMessage {
+    pub message_id: i64,
+    pub case_id: i64,
+    pub author_type: String,
+    pub author: String,
+    pub body: String,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SupportCaseDetail {
+    #[serde(flatten)]
+    pub case: SupportCase,
+    pub messages: Vec<SupportMessage>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct CreateSupportCaseDto {
+    #[validate(custom(function = "validate_olist_id"))]
+    pub order_id: OrderId,
+    pub category: SupportCategory,
+    #[validate(length(min = 1, max = 200))]
+    pub subject: String,
+    #[validate(length(min = 1, max = 5000))]
+    pub message: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct UpdateSupportCaseDto {
+    pub category: Option<SupportCategory>,
+    pub status: Option<SupportStatus>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct CreateSupportMessageDto {
+    pub author_type: MessageAuthor,
+    #[validate(length(min = 1, max = 5000))]
+    pub body: String,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct SupportCaseFilter {
+    pub status: Option<String>,
+    pub category: Option<String>,
+    pub order_id: Option<OrderId>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SupportCaseSearchQuery {
+    pub page: Option<u32>,
+    pub page_size: Option<u32>,
+    pub status: Option<SupportStatus>,
+    pub category: Option<SupportCategory>,
+    pub order_id: Option<OrderId>,
+}
+
+impl SupportCaseSearchQuery {
+    pub fn pagination(&self) -> PaginationParams {
+        PaginationParams {
+            page: self.page,
+            page_size: self.page_size,
+        }
+    }
+
+    pub fn filter(&self) -> SupportCaseFilter {
+        SupportCaseFilter {
+            status: self.status.map(|status| status.as_str().to_string()),
+            category: self.category.map(|category| category.as_str().to_string()),
+            order_id: self.order_id.clone(),
+        }
+    }
+}
+
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct SupportCaseVolume {
+    pub category: String,
+    pub total_cases: i64,
+    pub open_cases: i64,
+    pub resolved_cases: i64,
+    pub sla_breached_cases: i64,
+    pub avg_resolution_hours: Option<f64>,
+}
+
+/// Steps of the maintenance jobs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MaintenanceStep {
+    RefreshMaterializedViews,
+    PrecomputeRecommendations,
+    ReindexSearch,
+    FlushCaches,
+    ArchiveOrders,
+}
+
+impl MaintenanceStep {
+    /// The post-import refresh, in dependency order.
+    pub const REFRESH_ALL: [MaintenanceStep; 4] = [
+        MaintenanceStep::RefreshMaterializedViews,
+        MaintenanceStep::PrecomputeRecommendations,
+        MaintenanceStep::ReindexSearch,
+        MaintenanceStep::FlushCaches,
+    ];
+
+    pub fn depends_on(&self) -> &'static [MaintenanceStep] {
+        match self {
+            MaintenanceStep::RefreshMaterializedViews | MaintenanceStep::ArchiveOrders => &[],
+            MaintenanceStep::PrecomputeRecommendations => {
+                &[MaintenanceStep::RefreshMaterializedViews]
+            }
+            MaintenanceStep::ReindexSearch => &[MaintenanceStep::PrecomputeRecommendations],
+            MaintenanceStep::FlushCaches => &[
+                MaintenanceStep::RefreshMaterializedViews,
+                MaintenanceStep::ReindexSearch,
+            ],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Pending,
+    Running,
+    Completed,
+    Skipped,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MaintenanceStepReport {
+    pub step: MaintenanceStep,
+    pub depends_on: &'static [MaintenanceStep],
+    pub status: JobStatus,
+    pub detail: Option<String>,
+    pub started_at: Option<chrono::NaiveDateTime>,
+    pub finished_at: Option<chrono::NaiveDateTime>,
+}
+
+/// What a maintenance job does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(tag = "task", rename_all = "snake_case")]
+pub enum MaintenanceTask {
+    /// `POST /admin/maintenance/refresh-all`.
+    RefreshAll,
+    /// `POST /admin/archive`: closed orders purchased before `before` move to the archive.
+    ArchiveOrders { before: chrono::NaiveDate },
+}
+
+impl MaintenanceTask {
+    pub fn steps(&self) -> &'static [MaintenanceStep] {
+        match self {
+            MaintenanceTask::RefreshAll => &MaintenanceStep::REFRESH_ALL,
+            MaintenanceTask::ArchiveOrders { .. } => &[MaintenanceStep::ArchiveOrders],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MaintenanceJob {
+    pub job_id: u64,
+    #[serde(flatten)]
+    pub task: MaintenanceTask,
+    pub status: JobStatus,
+    pub requested_by: String,
+    pub started_at: chrono::NaiveDateTime,
+    pub finished_at: Option<chrono::NaiveDateTime>,
+    pub steps: Vec<MaintenanceStepReport>,
+}
+
+/// A `/load-data` run, importing the bundled datasets one after another.
+#[derive(Debug, Clone, Serialize)]
+pub struct LoadJob {
+    pub job_id: u64,
+    pub status: JobStatus,
+    pub started_at: chrono::NaiveDateTime,
+    pub finished_at: Option<chrono::NaiveDateTime>,
+    pub datasets: Vec<DatasetProgress>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DatasetProgress {
+    pub dataset: &'static str,
+    pub status: JobStatus,
+    pub batch_id: Option<i64>,
+    /// Data rows in the file, counted before the import starts.
+    pub total_rows: Option<usize>,
+    pub rows_done: usize,
+    pub success_count: usize,
+    pub error_count: usize,
+    /// Seconds left at the rate so far.
+    pub eta_seconds: Option<u64>,
+    pub error: Option<String>,
+}
+
+/// `POST /admin/archive` parameters: orders purchased before `before` (midnight, exclusive)
+/// are archived.
+#[derive(Debug, Deserialize, Validate)]
+#[validate(schema(function = "validate_archive_orders_query"))]
+pub struct ArchiveOrdersQuery {
+    pub before: chrono::NaiveDate,
+}
+
+fn validate_archive_orders_query(
+    query: &ArchiveOrdersQuery,
+) -> Result<(), validator::ValidationError> {
+    if query.before > chrono::Utc::now().date_naive() {
+        return Err(validator::ValidationError::new("before")
+            .with_message("before must not be in the future".into()));
+    }
+    Ok(())
+}
+
+/// Rows moved to the archive tables by one archival batch.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ArchivedOrders {
+    pub orders: u64,
+    pub items: u64,
+    pub payments: u64,
+    pub reviews: u64,
+}
+
+/// A data retention rule of [`crate::config::RetentionConfig`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RetentionRule {
+    /// Deletes reviews, archived ones included, written before the cutoff.
+    DeleteOldReviews,
+    /// Anonymizes customers whose last order or address change is before the cutoff.
+    AnonymizeInactiveCustomers,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RetentionRuleReport {
+    pub rule: RetentionRule,
+    /// `None` when the rule is disabled.
+    pub retention_years: Option<u32>,
+    /// Rows from before this are in scope.
+    pub cutoff: Option<chrono::NaiveDateTime>,
+    /// Rows the rule matches on a dry run, or those it deleted or anonymized.
+    pub affected: u64,
+}
+
+/// What the retention rules would do (`dry_run`) or did.
+#[derive(Debug, Clone, Serialize)]
+pub struct RetentionReport {
+    pub dry_run: bool,
+    pub generated_at: chrono::NaiveDateTime,
+    pub rules: Vec<RetentionRuleReport>,
+}
+
+/// Entries dropped by `POST /admin/cache/flush`. `response_entries` is `None` when no
+/// response cache is configured.
+#[derive(Debug, Clone, Serialize)]
+pub struct CacheFlush {
+    pub lookup_entries: u64,
+    pub response_entries: Option<u64>,
+}
+
+/// Traffic-light result of one diagnostics check. Ordered so the worst status wins;
+/// `Skipped` covers components this deployment doesn't have.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthStatus {
+    Skipped,
+    Green,
+    Yellow,
+    Red,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DiagnosticCheck {
+    pub name: &'static str,
+    pub status: HealthStatus,
+    pub detail: String,
+    pub duration_ms: u128,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DiagnosticsReport {
+    pub status: HealthStatus,
+    pub generated_at: chrono::NaiveDateTime,
+    pub checks: Vec<DiagnosticCheck>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct SetReadOnlyDto {
+    pub enabled: bool,
+    /// Shown to clients whose writes are refused.
+    #[validate(length(min = 1, max = 500))]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct AdminStatsQuery {
+    /// Count every table's rows instead of reading the statistics' estimates.
+    #[serde(default)]
+    pub exact: bool,
+}
+
+/// Facts about the running service and its database, for operators.
+#[derive(Debug, Serialize)]
+pub struct AdminStats {
+    pub generated_at: chrono::NaiveDateTime,
+    pub started_at: chrono::NaiveDateTime,
+    pub uptime_seconds: i64,
+    pub database: DatabaseStats,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DatabaseStats {
+    pub latency_ms: u128,
+    pub size_bytes: Option<i64>,
+    pub last_migration: Option<AppliedMigration>,
+    pub pool: Option<PoolStats>,
+    /// Whether the row counts come from the database's statistics rather than counting.
+    pub row_counts_estimated: bool,
+    pub tables: Vec<TableStats>,
+}
+
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct AppliedMigration {
+    pub version: i64,
+    pub description: String,
+    pub installed_on: chrono::NaiveDateTime,
+}
+
+/// Connections of the pool the API queries through.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct PoolStats {
+    pub max_connections: u32,
+    pub open: u32,
+    pub idle: u32,
+    pub in_use: u32,
+    /// `in_use` as a share of `max_connections`, from 0 to 1.
+    pub utilization: f64,
+}
+
+impl PoolStats {
+    pub fn new(max_connections: u32, open: u32, idle: u32) -> Self {
+        let in_use = open.saturating_sub(idle);
+        Self {
+            max_connections,
+            open,
+            idle,
+            in_use,
+            utilization: if max_connections == 0 {
+                0.0
+            } else {
+                in_use as f64 / max_connections as f64
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct TableStats {
+    pub table_name: String,
+    pub row_count: i64,
+    /// On-disk size including indexes, where the backend reports it.
+    pub size_bytes: Option<i64>,
+}
+
+/// What `GET /admin/data-quality/products` checks product rows for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProductIssue {
+    /// A blank `product_category_name`.
+    MissingCategory,
+    /// A zero `product_weight_g`.
+    MissingWeight,
+    /// A zero length, height or width.
+    MissingDimensions,
+    /// A negative count, length, weight or dimension.
+    NegativeValue,
+}
+
+impl ProductIssue {
+    pub const ALL: [ProductIssue; 4] = [
+        ProductIssue::MissingCategory,
+        ProductIssue::MissingWeight,
+        ProductIssue::MissingDimensions,
+        ProductIssue::NegativeValue,
+    ];
+
+    /// The issues `product` has, in [`ProductIssue::ALL`] order.
+    pub fn of(product: &Product) -> Vec<Self> {
+        let dimensions = [
+            product.product_length_cm,
+            product.product_height_cm,
+            product.product_width_cm,
+        ];
+        let counts = [
+            product.product_name_lenght,
+            product.product_description_lenght,
+            product.product_photos_qty,
+            product.product_weight_g,
+        ];
+        Self::ALL
+            .into_iter()
+            .filter(|issue| match issue {
+                ProductIssue::MissingCategory => product.product_category_name.trim().is_empty(),
+                ProductIssue::MissingWeight => product.product_weight_g == 0,
+                ProductIssue::MissingDimensions => dimensions.contains(&0),
+                ProductIssue::NegativeValue => {
+                    counts.iter().chain(&dimensions).any(|value| *value < 0)
+                }
+            })
+            .collect()
+    }
+}
+
+/// What `GET /admin/data-quality/orders` checks order rows for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderIssue {
+    /// No items, on an order that wasn't canceled or unavailable.
+    MissingItems,
+    /// `delivered` without an `order_delivered_customer_date`.
+    MissingDeliveryDate,
+    /// Approved before it was purchased.
+    ApprovedBeforePurchase,
+    /// Handed to the carrier before it was approved.
+    ShippedBeforeApproval,
+    /// Delivered before it was handed to the carrier.
+    DeliveredBeforeShipped,
+    /// Estimated to arrive before it was purchased.
+    EstimatedBeforePurchase,
+}
+
+impl OrderIssue {
+    pub const ALL: [OrderIssue; 6] = [
+        OrderIssue::MissingItems,
+        OrderIssue::MissingDeliveryDate,
+        OrderIssue::ApprovedBeforePurchase,
+        OrderIssue::ShippedBeforeApproval,
+        OrderIssue::DeliveredBeforeShipped,
+        OrderIssue::EstimatedBeforePurchase,
+    ];
+
+    /// The issues an order with `item_count` items has, in [`OrderIssue::ALL`] order.
+    pub fn of(order: &Order, item_count: i64) -> Vec<Self> {
+        let before = |earlier: Option<chrono::NaiveDateTime>, later| matches!((earlier, later), (Some(earlier), Some(later)) if earlier < later);
+        Self::ALL
+            .into_iter()
+            .filter(|issue| match issue {
+                OrderIssue::MissingItems => {
+                    item_count == 0
+                        && !matches!(
+                            order.order_status,
+                            OrderStatus::Canceled | OrderStatus::Unavailable
+                        )
+                }
+                OrderIssue::MissingDeliveryDate => {
+                    order.order_status == OrderStatus::Delivered
+                        && order.order_delivered_customer_date.is_none()
+                }
+                OrderIssue::ApprovedBeforePurchase => {
+                    order.order_approved_at < order.order_purchase_timestamp
+                }
+                OrderIssue::ShippedBeforeApproval => before(
+                    order.order_delivered_carrier_date,
+                    Some(order.order_approved_at),
+                ),
+                OrderIssue::DeliveredBeforeShipped => before(
+                    order.order_delivered_customer_date,
+                    order.order_delivered_carrier_date,
+                ),
+                OrderIssue::EstimatedBeforePurchase => {
+                    order.order_estimated_delivery_date < order.order_purchase_timestamp
+                }
+            })
+            .collect()
+    }
+}
+
+/// `GET /admin/data-quality/{products,orders}`: how many rows were checked, how many have at
+/// least one issue, and how many have each.
+#[derive(Debug, Serialize)]
+pub struct DataQualityReport<I> {
+    pub checked: i64,
+    pub flagged: i64,
+    pub issues: Vec<IssueCount<I>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct IssueCount<I> {
+    pub issue: I,
+    pub count: i64,
+}
+
+/// `GET /admin/data-quality/{products,orders}/flagged` parameters: rows with `issue`, or with
+/// any issue when it is omitted.
+#[derive(Debug, Deserialize)]
+pub struct DataQualityQuery<I> {
+    pub issue: Option<I>,
+    pub page: Option<u32>,
+    pub page_size: Option<u32>,
+}
+
+impl<I> DataQualityQuery<I> {
+    pub fn pagination(&self) -> PaginationParams {
+        PaginationParams {
+            page: self.page,
+            page_size: self.page_size,
+        }
+    }
+}
+
+/// A product with at least one [`ProductIssue`].
+#[derive(Debug, Serialize)]
+pub struct FlaggedProduct {
+    #[serde(flatten)]
+    pub product: Product,
+    pub issues: Vec<ProductIssue>,
+}
+
+impl From<Product> for FlaggedProduct {
+    fn from(product: Product) -> Self {
+        Self {
+            issues: ProductIssue::of(&product),
+            product,
+        }
+    }
+}
+
+/// An order with at least one [`OrderIssue`].
+#[derive(Debug, FromRow, Serialize)]
+pub struct FlaggedOrder {
+    #[sqlx(flatten)]
+    #[serde(flatten)]
+    pub order: Order,
+    pub item_count: i64,
+    /// Filled in by [`FlaggedOrder::with_issues`].
+    #[sqlx(skip)]
+    pub issues: Vec<OrderIssue>,
+}
+
+impl FlaggedOrder {
+    pub fn with_issues(self) -> Self {
+        Self {
+            issues: OrderIssue::of(&self.order, self.item_count),
+            ..self
+        }
+    }
+}
+
+/// How much breaking a [`DataQualityRule`] matters, stored as its snake_case name in
+/// `data_quality_results.severity`.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize, sqlx::Type,
+)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "varchar", rename_all = "snake_case")]
+pub enum RuleSeverity {
+    Info,
+    #[default]
+    Warning,
+    Error,
+}
+
+/// What a [`DataQualityRule`] flags rows for. The comparisons take the rule's `value` or
+/// `other_column` as their right-hand side.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RulePredicate {
+    IsNull,
+    /// Null, or empty once trimmed.
+    IsBlank,
+    Eq,
+    Ne,
+    Lt,
+    Lte,
+    Gt,
+    Gte,
+}
+
+impl RulePredicate {
+    /// The SQL operator of a comparison; `None` for the null checks.
+    pub fn operator(&self) -> Option<&'static str> {
+        match self {
+            RulePredicate::IsNull | RulePredicate::IsBlank => None,
+            RulePredicate::Eq => Some("="),
+            RulePredicate::Ne => Some("<>"),
+            RulePredicate::Lt => Some("<"),
+            RulePredicate::Lte => Some("<="),
+            RulePredicate::Gt => Some(">"),
+            RulePredicate::Gte => Some(">="),
+        }
+    }
+}
+
+/// A check from the file named by `DATA_QUALITY_RULES_PATH`: rows of `table` whose `column`
+/// matches `predicate` break it.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct DataQualityRule {
+    pub name: String,
+    pub table: String,
+    pub column: String,
+    pub predicate: RulePredicate,
+    /// Right-hand side of a comparison: a number, string or boolean.
+    pub value: Option<serde_json::Value>,
+    /// Column compared against instead of `value`, e.g. to catch dates out of order.
+    pub other_column: Option<String>,
+    #[serde(default)]
+    pub severity: RuleSeverity,
+}
+
+impl DataQualityRule {
+    /// Checks the table and columns are plain identifiers, so they can be put in SQL as they
+    /// are, and that comparisons, and only comparisons, have one right-hand side.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() || self.name.len() > 100 {
+            return Err("name must be 1 to 100 characters".to_string());
+        }
+        let identifiers = [
+            Some(&self.table),
+            Some(&self.column),
+            self.other_column.as_ref(),
+        ];
+        if let Some(invalid) = identifiers
+            .into_iter()
+            .flatten()
+            .find(|identifier| !is_identifier(identifier))
+        {
+            return Err(format!("'{}' is not a table or column name", invalid));
+        }
+        match (self.predicate.operator(), &self.value, &self.other_column) {
+            (None, None, None) => Ok(()),
+            (None, _, _) => Err("null checks take no value or other_column".to_string()),
+            (
+                Some(_),
+                Some(
+                    serde_json::Value::Number(_)
+                    | serde_json::Value::String(_)
+                    | serde_json::Value::Bool(_),
+                ),
+                None,
+            )
+            | (Some(_), None, Some(_)) => Ok(()),
+            (Some(_), _, _) => Err(
+                "comparisons take either a number, string or boolean value or an other_column"
+                    .to_string(),
+            ),
+        }
+    }
+
+    /// The rule as an SQL condition on its table's rows, e.g. `product_weight_g = 0`. Only
+    /// meant for rules that passed [`DataQualityRule::validate`].
+    pub fn condition(&self) -> String {
+        let column = &self.column;
+        let Some(operator) = self.predicate.operator() else {
+            return match self.predicate {
+                RulePredicate::IsBlank => {
+                    format!("{column} IS NULL OR TRIM(CAST({column} AS TEXT)) = ''")
+                }
+                _ => format!("{column} IS NULL"),
+            };
+        };
+        let right = match (&self.other_column, &self.value) {
+            (Some(other), _) => other.clone(),
+            (None, Some(serde_json::Value::String(text))) => {
+                format!("'{}'", text.replace('\'', "''"))
+            }
+            (None, Some(serde_json::Value::Bool(flag))) => {
+                if *flag { "TRUE" } else { "FALSE" }.to_string()
+            }
+            (None, Some(value)) => value.to_string(),
+            (None, None) => "NULL".to_string(),
+        };
+        format!("{column} {operator} {right}")
+    }
+}
+
+/// A table or column name: letters, digits and underscores, not starting with a digit.
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    name.len() <= 63
+        && chars
+            .next()
+            .is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// The outcome of a [`DataQualityRule`] at the last run of the `evaluate_data_quality` job.
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct DataQualityRuleResult {
+    pub rule_name: String,
+    pub table_name: String,
+    pub condition: String,
+    pub severity: RuleSeverity,
+    /// Rows of the table; `None` when the rule couldn't be evaluated.
+    pub checked_count: Option<i64>,
+    /// Rows breaking the rule; `None` when it couldn't be evaluated.
+    pub violation_count: Option<i64>,
+    /// Why the rule couldn't be evaluated, e.g. a column that doesn't exist.
+    pub error: Option<String>,
+    pub evaluated_at: chrono::NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportBatchStatus {
+    Running,
+    Completed,
+    Failed,
+    RolledBack,
+}
+
+impl ImportBatchStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ImportBatchStatus::Running => "running",
+            ImportBatchStatus::Completed => "completed",
+            ImportBatchStatus::Failed => "failed",
+            ImportBatchStatus::RolledBack => "rolled_back",
+        }
+    }
+}
+
+/// One CSV import run. The primary keys of the rows it inserted are kept alongside it so the
+/// batch can be rolled back.
+#[derive(Debug, FromRow, Serialize, Clone)]
+pub struct ImportBatch {
+    pub batch_id: i64,
+    pub dataset: String,
+    pub source: String,
+    pub actor: String,
+    pub status: String,
+    pub success_count: i32,
+    pub error_count: i32,
+    pub started_at: chrono::NaiveDateTime,
+    pub finished_at: Option<chrono::NaiveDateTime>,
+    pub rolled_back_at: Option<chrono::NaiveDateTime>,
+}
+
+/// An import batch of a load job and how far it got.
+#[derive(Debug, Clone, FromRow)]
+pub struct LoadJobBatch {
+    pub batch_id: i64,
+    pub dataset: String,
+    pub status: String,
+    pub success_count: i32,
+    pub error_count: i32,
+    /// Last line of the file up to which every row was inserted or recorded as an error.
+    pub checkpoint_line: i64,
+}
+
+/// A load job as stored, with its batches, most recent last.
+#[derive(Debug, Clone)]
+pub struct StoredLoadJob {
+    pub job_id: i64,
+    pub datasets: Vec<String>,
+    pub batches: Vec<LoadJobBatch>,
+}
+
+/// Where an interrupted batch left off. Errors recorded past the checkpoint have been
+/// discarded, since those rows are read again.
+#[derive(Debug, Clone)]
+pub struct BatchResume {
+    pub checkpoint_line: i64,
+    /// Primary keys the batch inserted so far, past the checkpoint too.
+    pub inserted: Vec<String>,
+    pub error_count: i64,
+}
+
+/// A CSV row an import batch could not insert.
+#[derive(Debug, FromRow, Serialize, Clone)]
+pub struct ImportRowError {
+    pub error_id: i64,
+    pub batch_id: i64,
+    pub dataset: String,
+    /// Line of the row in the source file; the header is line 1.
+    pub row_number: i64,
+    /// The row's fields as CSV, or `None` if the line could not be read into fields.
+    pub record: Option<String>,
+    pub reason: String,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NewImportRowError {
+    pub row_number: i64,
+    pub record: Option<String>,
+    pub reason: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct LoadDataQuery {
+    /// Check the files without importing them.
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// What a `/load-data` dry run found: the import would insert every row only if `valid`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ValidationReport {
+    pub valid: bool,
+    pub datasets: Vec<DatasetValidation>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DatasetValidation {
+    pub dataset: &'static str,
+    pub total_rows: usize,
+    pub valid_count: usize,
+    pub error_count: usize,
+    /// The first failing rows, in file order.
+    pub errors: Vec<NewImportRowError>,
+}
+
+/// Failed rows of a load job, as a page of JSON or, with `format`, a download of all of them.
+#[derive(Debug, Deserialize)]
+pub struct ImportErrorQuery {
+    pub page: Option<u32>,
+    pub page_size: Option<u32>,
+    pub format: Option<ExportFormat>,
+}
+
+impl ImportErrorQuery {
+    pub fn pagination(&self) -> PaginationParams {
+        PaginationParams {
+            page: self.page,
+            page_size: self.page_size,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportRollback {
+    #[serde(flatten)]
+    pub batch: ImportBatch,
+    pub rows_deleted: u64,
+}
+
+/// Dashboard counters for the current day, read from the trigger-maintained `stats` table.
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct TodayStats {
+    pub stat_date: chrono::NaiveDate,
+    pub orders_count: i64,
+    pub revenue: Money,
+    /// Import batches still running, whatever day they started.
+    pub active_imports: i64,
+    #[sqlx(default)]
+    #[serde(default)]
+    pub currency: Currency,
+}
+
+/// Events a webhook subscription can be notified of. They are recorded in the outbox by
+/// database triggers, so writes from imports and other processes are covered too.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum WebhookEvent {
+    #[serde(rename = "order.created")]
+    OrderCreated,
+    #[serde(rename = "order.status_changed")]
+    OrderStatusChanged,
+    /// Recorded by the late-delivery check rather than a trigger.
+    #[serde(rename = "order.late")]
+    OrderLate,
+    #[serde(rename = "payment.created")]
+    PaymentCreated,
+    #[serde(rename = "review.created")]
+    ReviewCreated,
+}
+
+impl WebhookEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookEvent::OrderCreated => "order.created",
+            WebhookEvent::OrderStatusChanged => "order.status_changed",
+            WebhookEvent::OrderLate => "order.late",
+            WebhookEvent::PaymentCreated => "payment.created",
+            WebhookEvent::ReviewCreated => "review.created",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateWebhookDto {
+    #[validate(length(max = 2000), custom(function = "validate_webhook_url"))]
+    pub url: String,
+    #[validate(length(min = 1))]
+    pub events: Vec<WebhookEvent>,
+    /// Only events of orders placed by customers in these states; every state when omitted.
+    #[validate(length(min = 1))]
+    pub customer_states: Option<Vec<BrazilState>>,
+    #[serde(default)]
+    pub payload_template: WebhookPayloadTemplate,
+    /// Key for the HMAC signatures; one is generated when omitted.
+    #[validate(length(min = 16, max = 100))]
+    pub secret: Option<String>,
+}
+
+/// What a webhook delivery carries as its `data`, stored as its snake_case name in
+/// `webhook_subscriptions.payload_template`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "varchar", rename_all = "snake_case")]
+pub enum WebhookPayloadTemplate {
+    /// The whole event.
+    #[default]
+    Full,
+    /// Only the ids identifying the event's entity, for subscribers that fetch it themselves.
+    Slim,
+}
+
+impl WebhookPayloadTemplate {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookPayloadTemplate::Full => "full",
+            WebhookPayloadTemplate::Slim => "slim",
+        }
+    }
+
+    /// The `data` of a delivery of the event `payload`: the ids in it (the `*_id` fields and
+    /// `payment_sequential`) for a slim template.
+    pub fn apply(&self, payload: &serde_json::Value) -> serde_json::Value {
+        match (self, payload) {
+            (WebhookPayloadTemplate::Slim, serde_json::Value::Object(fields)) => fields
+                .iter()
+                .filter(|(key, _)| key.ends_with("_id") || *key == "payment_sequential")
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect::<serde_json::Map<_, _>>()
+                .into(),
+            _ => payload.clone(),
+        }
+    }
+}
+
+fn validate_webhook_url(url: &str) -> Result<(), validator::ValidationError> {
+    let host = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))
+        .unwrap_or_default();
+    if !host.is_empty() && !host.starts_with('/') && !url.contains(char::is_whitespace) {
+        Ok(())
+    } else {
+        Err(validator::ValidationError::new("webhook_url")
+            .with_message("must be an absolute http or https URL".into()))
+    }
+}
+
+#[derive(Debug, FromRow, Serialize, Clone)]
+pub struct WebhookSubscription {
+    pub subscription_id: i64,
+    pub url: String,
+    pub events: Vec<String>,
+    /// UF codes; `None` for every state.
+    pub customer_states: Option<Vec<String>>,
+    pub payload_template: WebhookPayloadTemplate,
+    /// Only returned by the create call.
+    #[serde(skip_serializing)]
+    pub secret: String,
+    pub created_by: String,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+/// Response of `POST /webhooks`, the one place the signing secret is shown.
+#[derive(Debug, Serialize)]
+pub struct CreatedWebhook {
+    #[serde(flatten)]
+    pub subscription: WebhookSubscription,
+    pub secret: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookDeliveryStatus {
+    Pending,
+    Succeeded,
+    Failed,
+}
+
+impl WebhookDeliveryStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookDeliveryStatus::Pending => "pending",
+            WebhookDeliveryStatus::Succeeded => "succeeded",
+            WebhookDeliveryStatus::Failed => "failed",
+        }
+    }
+}
+
+/// One event queued for one subscription, with the outcome of its latest attempt.
+/// `status` stays `pending` while retries remain; `next_attempt_at` is cleared once it is
+/// delivered or has failed for good.
+#[derive(Debug, FromRow, Serialize, Clone)]
+pub struct WebhookDelivery {
+    pub delivery_id: i64,
+    pub subscription_id: i64,
+    pub event: String,
+    pub payload: serde_json::Value,
+    pub status: String,
+    pub attempts: i32,
+    pub next_attempt_at: Option<chrono::NaiveDateTime>,
+    pub last_status_code: Option<i32>,
+    pub last_error: Option<String>,
+    pub created_at: chrono::NaiveDateTime,
+    pub delivered_at: Option<chrono::NaiveDateTime>,
+}
+
+/// A delivery leased by the delivery worker, with where and how to send it. `attempts`
+/// already counts the attempt about to be made.
+#[derive(Debug, FromRow, Clone)]
+pub struct PendingWebhookDelivery {
+    pub delivery_id: i64,
+    pub event: String,
+    pub payload: serde_json::Value,
+    pub attempts: i32,
+    pub created_at: chrono::NaiveDateTime,
+    pub url: String,
+    pub secret: String,
+    /// The subscription's UF codes; `None` for every state.
+    pub customer_states: Option<Vec<String>>,
+    pub payload_template: WebhookPayloadTemplate,
+    /// State of the customer who placed the event's order, read when the delivery is leased;
+    /// `None` when the event names no order or it is gone.
+    pub customer_state: Option<String>,
+}
+
+impl PendingWebhookDelivery {
+    /// The `data` to send, shaped by the subscription's payload template, or `None` when its
+    /// customer states leave the event out.
+    pub fn data(&self) -> Option<serde_json::Value> {
+        let wanted = self.customer_states.as_ref().is_none_or(|states| {
+            self.customer_state
+                .as_ref()
+                .is_some_and(|state| states.contains(state))
+        });
+        wanted.then(|| self.payload_template.apply(&self.payload))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WebhookDeliveryQuery {
+    pub page: Option<u32>,
+    pub page_size: Option<u32>,
+    pub status: Option<WebhookDeliveryStatus>,
+}
+
+impl WebhookDeliveryQuery {
+    pub fn pagination(&self) -> PaginationParams {
+        PaginationParams {
+            page: self.page,
+            page_size: self.page_size,
+        }
+    }
+}
+
+/// An order, payment or review event recorded in the outbox in the same transaction as the
+/// write, waiting to be published or already published.
+#[derive(Debug, FromRow, Clone)]
+pub struct OutboxEvent {
+    pub event_id: i64,
+    pub tenant_id: TenantId,
+    pub aggregate_type: String,
+    pub aggregate_id: String,
+    pub event_type: String,
+    pub payload: serde_json::Value,
+    /// Failed publish attempts so far.
+    pub attempts: i32,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+/// Outbox events not published yet.
+#[derive(Debug, FromRow, Clone, Copy)]
+pub struct OutboxBacklog {
+    pub pending: i64,
+    /// How long the oldest of them has been waiting, measured by the database clock.
+    pub oldest_age_seconds: Option<f64>,
+}
+
+/// What a notification is about. Notifications are queued by the outbox relay, in the same
+/// statement that marks their event published.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationKind {
+    /// `order.status_changed` to `approved`.
+    OrderApproved,
+    /// `order.status_changed` to `delivered`.
+    OrderDelivered,
+    /// `order.late`.
+    OrderLate,
+    /// `review.created`.
+    ReviewReceived,
+}
+
+impl NotificationKind {
+    pub const ALL: [NotificationKind; 4] = [
+        NotificationKind::OrderApproved,
+        NotificationKind::OrderDelivered,
+        NotificationKind::OrderLate,
+        NotificationKind::ReviewReceived,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NotificationKind::OrderApproved => "order_approved",
+            NotificationKind::OrderDelivered => "order_delivered",
+            NotificationKind::OrderLate => "order_late",
+            NotificationKind::ReviewReceived => "review_received",
+        }
+    }
+
+    /// The notification an outbox event calls for, if any.
+    pub fn of_event(event_type: &str, payload: &serde_json::Value) -> Option<Self> {
+        match (event_type, payload["order_status"].as_str()) {
+            ("order.status_changed", Some("approved")) => Some(NotificationKind::OrderApproved),
+            ("order.status_changed", Some("delivered")) => Some(NotificationKind::OrderDelivered),
+            ("order.late", _) => Some(NotificationKind::OrderLate),
+            ("review.created", _) => Some(NotificationKind::ReviewReceived),
+            _ => None,
+        }
+    }
+}
+
+impl std::str::FromStr for NotificationKind {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.as_str() == value)
+            .ok_or_else(|| format!("unknown notification kind '{}'", value))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationStatus {
+    Pending,
+    Sent,
+    Failed,
+}
+
+impl NotificationStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NotificationStatus::Pending => "pending",
+            NotificationStatus::Sent => "sent",
+            NotificationStatus::Failed => "failed",
+        }
+    }
+}
+
+/// A queued notification and the outcome of its latest delivery attempt. `channel`,
+/// `recipient` and `subject` are those of that attempt.
+#[derive(Debug, FromRow, Serialize, Clone)]
+pub struct Notification {
+    pub notification_id: i64,
+    pub event_id: i64,
+    pub kind: String,
+    pub payload: serde_json::Value,
+    pub status: String,
+    pub channel: Option<String>,
+    pub recipient: Option<String>,
+    pub subject: Option<String>,
+    pub attempts: i32,
+    pub next_attempt_at: Option<chrono::NaiveDateTime>,
+    pub last_error: Option<String>,
+    pub created_at: chrono::NaiveDateTime,
+    pub sent_at: Option<chrono::NaiveDateTime>,
+}
+
+/// A notification leased by the notification worker. `attempts` already counts the attempt
+/// about to be made.
+#[derive(Debug, FromRow, Clone)]
+pub struct PendingNotification {
+    pub notification_id: i64,
+    pub kind: String,
+    pub payload: serde_json::Value,
+    pub attempts: i32,
+}
+
+/// A notification rendered from its template, ready for a channel.
+#[derive(Debug, Clone)]
+pub struct NotificationMessage {
+    pub kind: NotificationKind,
+    pub subject: String,
+    pub body: String,
+}
+
+/// How one delivery attempt went, as recorded on the notification.
+#[derive(Debug, Clone)]
+pub struct NotificationAttempt<'a> {
+    pub channel: &'a str,
+    pub recipient: Option<&'a str>,
+    pub subject: &'a str,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NotificationQuery {
+    pub page: Option<u32>,
+    pub page_size: Option<u32>,
+    pub status: Option<NotificationStatus>,
+    pub kind: Option<NotificationKind>,
+}
+
+impl NotificationQuery {
+    pub fn pagination(&self) -> PaginationParams {
+        PaginationParams {
+            page: self.page,
+            page_size: self.page_size,
+        }
+    }
+}
+
+/// A create, update or delete of an entity, as recorded in the audit log, for the change
+/// stream. `diff` has the audit log's `{ field: { from, to } }` shape.
+#[derive(Debug, Serialize, Clone)]
+pub struct ChangeEvent {
+    pub tenant_id: TenantId,
+    pub entity_type: &'static str,
+    pub entity_id: String,
+    pub action: AuditAction,
+    pub actor: String,
+    pub diff: serde_json::Value,
+    pub occurred_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_assigned_cep_prefixes() {
+        for prefix in ["01000", "01310", "20040", "99999"] {
+            assert!(validate_zip_code_prefix(prefix).is_ok(), "{prefix}");
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_or_unassigned_cep_prefixes() {
+        for prefix in [
+            "", "1310", "013100", "00999", "00000", "0131a", "01-31", " 1310",
+        ] {
+            assert!(validate_zip_code_prefix(prefix).is_err(), "{prefix:?}");
+        }
+    }
+
+    #[test]
+    fn parses_the_27_uf_codes() {
+        let codes = [
+            "AC", "AL", "AP", "AM", "BA", "CE", "DF", "ES", "GO", "MA", "MT", "MS", "MG", "PA",
+            "PB", "PR", "PE", "PI", "RJ", "RN", "RS", "RO", "RR", "SC", "SP", "SE", "TO",
+        ];
+        for code in codes {
+            let state: BrazilState = serde_json::from_value(code.into()).unwrap();
+            assert_eq!(state.as_str(), code);
+            assert_eq!(serde_json::to_value(state).unwrap(), code);
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_or_lowercase_uf_codes() {
+        for code in ["sp", "XX", "BR", "", "SAO PAULO"] {
+            assert!(
+                serde_json::from_value::<BrazilState>(code.into()).is_err(),
+                "{code:?}"
+            );
+        }
+    }
+
+    fn pending_delivery(
+        customer_states: Option<&[&str]>,
+        payload_template: WebhookPayloadTemplate,
+    ) -> PendingWebhookDelivery {
+        PendingWebhookDelivery {
+            delivery_id: 1,
+            event: "review.created".to_string(),
+            payload: serde_json::json!({
+                "review_id": "7bc2406110b926393aa56f80a40eba40",
+                "order_id": "e481f51cbdc54678b7cc49136f2d6af7",
+                "review_score": 4,
+                "review_comment_message": null
+            }),
+            attempts: 1,
+            created_at: chrono::NaiveDateTime::default(),
+            url: "https://example.com/hooks".to_string(),
+            secret: "whsec_test".to_string(),
+            customer_states: customer_states
+                .map(|states| states.iter().map(|state| state.to_string()).collect()),
+            payload_template,
+            customer_state: Some("SP".to_string()),
+        }
+    }
+
+    #[test]
+    fn webhook_deliveries_carry_the_full_event_by_default() {
+        let delivery = pending_delivery(None, WebhookPayloadTemplate::Full);
+        assert_eq!(delivery.data(), Some(delivery.payload.clone()));
+    }
+
+    #[test]
+    fn slim_webhook_deliveries_carry_only_ids() {
+        let delivery = pending_delivery(None, WebhookPayloadTemplate::Slim);
+        assert_eq!(
+            delivery.data(),
+            Some(serde_json::json!({
+                "review_id": "7bc2406110b926393aa56f80a40eba40",
+                "order_id": "e481f51cbdc54678b7cc49136f2d6af7"
+            }))
+        );
+    }
+
+    #[test]
+    fn webhook_deliveries_are_filtered_by_customer_state() {
+        let delivery = pending_delivery(Some(&["RJ", "SP"]), WebhookPayloadTemplate::Full);
+        assert!(delivery.data().is_some());
+
+        let delivery = pending_delivery(Some(&["RJ"]), WebhookPayloadTemplate::Full);
+        assert_eq!(delivery.data(), None);
+
+        let mut delivery = pending_delivery(Some(&["SP"]), WebhookPayloadTemplate::Full);
+        delivery.customer_state = None;
+        assert_eq!(delivery.data(), None);
+    }
+
+    fn rule(
+        column: &str,
+        predicate: RulePredicate,
+        value: Option<serde_json::Value>,
+        other_column: Option<&str>,
+    ) -> DataQualityRule {
+        DataQualityRule {
+            name: "check".to_string(),
+            table: "orders".to_string(),
+            column: column.to_string(),
+            predicate,
+            value,
+            other_column: other_column.map(str::to_string),
+            severity: RuleSeverity::default(),
+        }
+    }
+
+    #[test]
+    fn data_quality_rules_reject_names_that_are_not_identifiers() {
+        for column in [
+            "",
+            "1st",
+            "order status",
+            "order_id; DROP TABLE orders",
+            "\"order_id\"",
+        ] {
+            assert!(
+                rule(column, RulePredicate::IsNull, None, None)
+                    .validate()
+                    .is_err(),
+                "{column}"
+            );
+        }
+        assert!(
+            rule("order_id", RulePredicate::Eq, None, Some("customer-id"))
+                .validate()
+                .is_err()
+        );
+
+        let mut table = rule("order_id", RulePredicate::IsNull, None, None);
+        table.table = "orders o".to_string();
+        assert!(table.validate().is_err());
+        table.table = "_orders2".to_string();
+        assert!(table.validate().is_ok());
+    }
+
+    #[test]
+    fn data_quality_rules_need_a_right_hand_side_only_for_comparisons() {
+        assert!(
+            rule(
+                "order_id",
+                RulePredicate::IsNull,
+                Some(serde_json::json!(1)),
+                None
+            )
+            .validate()
+            .is_err()
+        );
+        assert!(
+            rule("price", RulePredicate::Gt, None, None)
+                .validate()
+                .is_err()
+        );
+        assert!(
+            rule(
+                "price",
+                RulePredicate::Gt,
+                Some(serde_json::json!([1])),
+                None
+            )
+            .validate()
+            .is_err()
+        );
+        assert!(
+            rule(
+                "price",
+                RulePredicate::Gt,
+                Some(serde_json::json!(0)),
+                Some("freight_value")
+            )
+            .validate()
+            .is_err()
+        );
+        assert!(
+            rule("price", RulePredicate::Gt, Some(serde_json::json!(0)), None)
+                .validate()
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn data_quality_conditions_quote_string_values() {
+        let condition = rule(
+            "order_status",
+            RulePredicate::Eq,
+            Some(serde_json::json!("it's' OR '1'='1")),
+            None,
+        )
+        .condition();
+        assert_eq!(condition, "order_status = 'it''s'' OR ''1''=''1'");
+
+        let condition = rule(
+            "price",
+            RulePredicate::Lte,
+            Some(serde_json::json!(0)),
+            None,
+        );
+        assert_eq!(condition.condition(), "price <= 0");
+        let condition = rule(
+            "active",
+            RulePredicate::Ne,
+            Some(serde_json::json!(true)),
+            None,
+        );
+        assert_eq!(condition.condition(), "active <> TRUE");
+    }
+
+    #[test]
+    fn data_quality_conditions_cover_null_checks_and_other_columns() {
+        assert_eq!(
+            rule("review_comment_message", RulePredicate::IsBlank, None, None).condition(),
+            "review_comment_message IS NULL OR TRIM(CAST(review_comment_message AS TEXT)) = ''"
+        );
+        assert_eq!(
+            rule("order_approved_at", RulePredicate::IsNull, None, None).condition(),
+            "order_approved_at IS NULL"
+        );
+        assert_eq!(
+            rule(
+                "order_delivered_customer_date",
+                RulePredicate::Lt,
+                None,
+                Some("order_purchase_timestamp"),
+            )
+            .condition(),
+            "order_delivered_customer_date < order_purchase_timestamp"
+        );
+    }
+}
@@ -0,0 +1,209 @@
+use bigdecimal::num_bigint::BigInt;
+use bigdecimal::num_traits::Signed;
+use bigdecimal::{BigDecimal, RoundingMode, Zero};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, Sub};
+
+/// An amount of Brazilian reais in a response.
+///
+/// Arithmetic keeps the full precision of the underlying decimal; the amount is only rounded,
+/// half to even, to whole centavos when it is serialized, as a string such as `"59.90"`.
+/// Every response struct with an amount states its [`Currency`] alongside. sqlx sees the plain
+/// decimal.
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash, sqlx::Type)]
+#[sqlx(transparent)]
+pub struct Money(BigDecimal);
+
+impl Money {
+    /// Currency of every amount the API handles.
+    pub const CURRENCY: Currency = Currency::Brl;
+    const SCALE: i64 = 2;
+
+    pub fn new(amount: BigDecimal) -> Self {
+        Self(amount)
+    }
+
+    pub fn zero() -> Self {
+        Self(BigDecimal::zero())
+    }
+
+    pub fn amount(&self) -> &BigDecimal {
+        &self.0
+    }
+
+    pub fn into_amount(self) -> BigDecimal {
+        self.0
+    }
+
+    /// The amount in whole centavos, rounded half to even.
+    pub fn rounded(&self) -> BigDecimal {
+        round_to_centavos(&self.0)
+    }
+}
+
+/// ISO 4217 currency of a [`Money`] amount, serialized as its code. Amounts aren't stored with
+/// a currency, so a `currency` field next to them is filled in by default, or selected as a
+/// constant by the Postgres query macros.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Deserialize, Serialize, sqlx::Type)]
+#[sqlx(type_name = "varchar")]
+pub enum Currency {
+    #[default]
+    #[serde(rename = "BRL")]
+    #[sqlx(rename = "BRL")]
+    Brl,
+}
+
+/// Rounds an amount to whole centavos, half to even, as every computed charge, discount and
+/// total is.
+pub fn round_to_centavos(amount: &BigDecimal) -> BigDecimal {
+    amount.with_scale_round(Money::SCALE, RoundingMode::HalfEven)
+}
+
+impl From<BigDecimal> for Money {
+    fn from(amount: BigDecimal) -> Self {
+        Self(amount)
+    }
+}
+
+impl From<Money> for BigDecimal {
+    fn from(money: Money) -> Self {
+        money.0
+    }
+}
+
+impl fmt::Display for Money {
+    /// Always two decimal places; `BigDecimal` would print a zero amount as `0`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (centavos, _) = self.rounded().into_bigint_and_exponent();
+        let sign = if centavos.is_negative() { "-" } else { "" };
+        let centavos = centavos.abs();
+        let hundred = BigInt::from(100);
+        write!(
+            f,
+            "{}{}.{:02}",
+            sign,
+            &centavos / &hundred,
+            &centavos % &hundred
+        )
+    }
+}
+
+impl Serialize for Money {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Accepts the string form as well as bare numbers, as cached responses and clients may send
+/// either.
+impl<'de> Deserialize<'de> for Money {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        BigDecimal::deserialize(deserializer).map(Self)
+    }
+}
+
+impl Add for Money {
+    type Output = Money;
+
+    fn add(self, rhs: Money) -> Money {
+        Money(self.0 + rhs.0)
+    }
+}
+
+impl Add<&Money> for Money {
+    type Output = Money;
+
+    fn add(self, rhs: &Money) -> Money {
+        Money(self.0 + &rhs.0)
+    }
+}
+
+impl Sub for Money {
+    type Output = Money;
+
+    fn sub(self, rhs: Money) -> Money {
+        Money(self.0 - rhs.0)
+    }
+}
+
+impl Sub<&Money> for Money {
+    type Output = Money;
+
+    fn sub(self, rhs: &Money) -> Money {
+        Money(self.0 - &rhs.0)
+    }
+}
+
+impl Sub<&Money> for &Money {
+    type Output = Money;
+
+    fn sub(self, rhs: &Money) -> Money {
+        Money(&self.0 - &rhs.0)
+    }
+}
+
+impl Sum for Money {
+    fn sum<I: Iterator<Item = Money>>(iter: I) -> Money {
+        iter.fold(Money::zero(), |acc, money| acc + money)
+    }
+}
+
+impl<'a> Sum<&'a Money> for Money {
+    fn sum<I: Iterator<Item = &'a Money>>(iter: I) -> Money {
+        iter.fold(Money::zero(), |acc, money| acc + money)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn money(amount: &str) -> Money {
+        Money::new(BigDecimal::from_str(amount).unwrap())
+    }
+
+    #[test]
+    fn rounds_half_to_even() {
+        assert_eq!(money("0.125").to_string(), "0.12");
+        assert_eq!(money("0.135").to_string(), "0.14");
+        assert_eq!(money("0.126").to_string(), "0.13");
+        assert_eq!(money("2.5").to_string(), "2.50");
+    }
+
+    #[test]
+    fn displays_negative_amounts() {
+        assert_eq!(money("-5.5").to_string(), "-5.50");
+        assert_eq!(money("-0.07").to_string(), "-0.07");
+        assert_eq!(money("-1234.565").to_string(), "-1234.56");
+    }
+
+    #[test]
+    fn displays_zero_with_two_decimals() {
+        assert_eq!(Money::zero().to_string(), "0.00");
+        assert_eq!(money("-0.001").to_string(), "0.00");
+    }
+
+    #[test]
+    fn keeps_precision_until_serialized() {
+        let total: Money = [money("0.005"), money("0.005"), money("0.005")]
+            .iter()
+            .sum();
+        assert_eq!(total.amount(), &BigDecimal::from_str("0.015").unwrap());
+        assert_eq!(serde_json::to_value(&total).unwrap(), "0.02");
+    }
+
+    #[test]
+    fn deserializes_strings_and_numbers() {
+        let from_string: Money = serde_json::from_str("\"59.90\"").unwrap();
+        let from_number: Money = serde_json::from_str("59.9").unwrap();
+        assert_eq!(from_string.rounded(), from_number.rounded());
+    }
+
+    #[test]
+    fn serializes_currency_as_its_code() {
+        assert_eq!(serde_json::to_value(Money::CURRENCY).unwrap(), "BRL");
+    }
+}
@@ -0,0 +1,147 @@
+use async_trait::async_trait;
+use std::collections::HashMap;
+use tracing::info;
+
+use crate::models::{NotificationKind, NotificationMessage};
+
+/// A channel notifications are sent through.
+///
+/// Implementations are selected with `NOTIFICATION_PROVIDER` when building `AppState`.
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    fn name(&self) -> &'static str;
+    /// Who the channel delivers to, as recorded with each attempt; `None` when it has no
+    /// addressees, like the log.
+    fn recipient(&self) -> Option<String> {
+        None
+    }
+    async fn send(&self, message: &NotificationMessage) -> Result<(), String>;
+}
+
+/// [`Notifier`] that only writes notifications to the application log, for development and
+/// deployments without a mail server.
+pub struct LogNotifier;
+
+impl LogNotifier {
+    pub const NAME: &'static str = "log";
+}
+
+#[async_trait]
+impl Notifier for LogNotifier {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    async fn send(&self, message: &NotificationMessage) -> Result<(), String> {
+        info!(
+            kind = message.kind.as_str(),
+            "Notification: {}\n{}", message.subject, message.body
+        );
+        Ok(())
+    }
+}
+
+/// Subject and body of one kind of notification. `{{field}}` placeholders are filled from the
+/// event's payload; unknown or null fields are left empty.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NotificationTemplate {
+    pub subject: String,
+    pub body: String,
+}
+
+impl NotificationTemplate {
+    pub fn new(subject: impl Into<String>, body: impl Into<String>) -> Self {
+        Self {
+            subject: subject.into(),
+            body: body.into(),
+        }
+    }
+
+    /// A template written as a text file: the first line is the subject, the rest the body.
+    pub fn parse(text: &str) -> Self {
+        let (subject, body) = text.split_once('\n').unwrap_or((text, ""));
+        Self::new(subject.trim(), body.trim_matches('\n'))
+    }
+}
+
+/// The templates of every notification kind: built-in ones, some of which may be replaced.
+#[derive(Debug, Clone)]
+pub struct NotificationTemplates(HashMap<NotificationKind, NotificationTemplate>);
+
+impl Default for NotificationTemplates {
+    fn default() -> Self {
+        Self(HashMap::from([
+            (
+                NotificationKind::OrderApproved,
+                NotificationTemplate::new(
+                    "Order {{order_id}} approved",
+                    "Order {{order_id}} went from {{previous_status}} to approved and can be \
+                     prepared for shipping.",
+                ),
+            ),
+            (
+                NotificationKind::OrderDelivered,
+                NotificationTemplate::new(
+                    "Order {{order_id}} delivered",
+                    "Order {{order_id}} was delivered to the customer.",
+                ),
+            ),
+            (
+                NotificationKind::OrderLate,
+                NotificationTemplate::new(
+                    "Order {{order_id}} is late",
+                    "Order {{order_id}} was due by {{order_estimated_delivery_date}} and is still \
+                     {{order_status}}. Check with the carrier.",
+                ),
+            ),
+            (
+                NotificationKind::ReviewReceived,
+                NotificationTemplate::new(
+                    "New {{review_score}}-star review for order {{order_id}}",
+                    "Review {{review_id}} of order {{order_id}} scored {{review_score}} of 5.\n\n\
+                     {{review_comment_title}}\n{{review_comment_message}}",
+                ),
+            ),
+        ]))
+    }
+}
+
+impl NotificationTemplates {
+    /// Replaces the template of `kind`.
+    pub fn with(mut self, kind: NotificationKind, template: NotificationTemplate) -> Self {
+        self.0.insert(kind, template);
+        self
+    }
+
+    pub fn render(
+        &self,
+        kind: NotificationKind,
+        payload: &serde_json::Value,
+    ) -> NotificationMessage {
+        let template = &self.0[&kind];
+        NotificationMessage {
+            kind,
+            subject: fill(&template.subject, payload),
+            body: fill(&template.body, payload),
+        }
+    }
+}
+
+fn fill(template: &str, payload: &serde_json::Value) -> String {
+    let mut filled = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        filled.push_str(&rest[..start]);
+        match &payload[rest[start + 2..start + 2 + len].trim()] {
+            serde_json::Value::Null => {}
+            serde_json::Value::String(value) => filled.push_str(value),
+            value => filled.push_str(&value.to_string()),
+        }
+        rest = &rest[start + 2 + len + 2..];
+    }
+    filled.push_str(rest);
+    filled
+}
@@ -0,0 +1,27 @@
+use async_trait::async_trait;
+use bigdecimal::BigDecimal;
+
+use crate::error::AppResult;
+use crate::models::{PaymentAuthorization, PaymentNotification, PaymentRequest, PaymentStatus};
+
+/// A payment provider: authorizes payments, captures and refunds them, and reports status
+/// changes asynchronously through signed notifications.
+///
+/// Implementations are selected with `PAYMENT_PROVIDER` when building `AppState`.
+#[async_trait]
+pub trait PaymentProvider: Send + Sync {
+    fn name(&self) -> &'static str;
+    /// A declined payment is answered with [`PaymentStatus::Failed`], not an error.
+    async fn authorize(&self, request: &PaymentRequest) -> AppResult<PaymentAuthorization>;
+    async fn capture(&self, reference: &str, amount: &BigDecimal) -> AppResult<PaymentStatus>;
+    async fn refund(&self, reference: &str, amount: &BigDecimal) -> AppResult<PaymentStatus>;
+    /// Header the provider signs its notifications in.
+    fn signature_header(&self) -> &'static str;
+    /// Checks a notification's signature against its raw body and reads it. Fails with
+    /// `InvalidSignature` when the signature is missing or does not match.
+    fn parse_notification(
+        &self,
+        signature: Option<&str>,
+        body: &[u8],
+    ) -> AppResult<PaymentNotification>;
+}
@@ -0,0 +1,792 @@
+use async_trait::async_trait;
+use futures::stream::BoxStream;
+use sqlx::Result as SqlxResult;
+
+use crate::geo::GeoBounds;
+use crate::ids::{CustomerId, OrderId, ProductId, SellerId};
+use crate::models::{
+    AddItemToOrderDto, AppliedMigration, ArchivedOrders, AuditEntry, AuditFilter, BatchResume,
+    BrazilState, Category, Coupon, CreateCategoryDto, CreateCouponDto, CreateCustomerAddressDto,
+    CreateCustomerDto, CreateOrderDto, CreateProductDto, CreateSellerDto, CreateStockLocationDto,
+    CreateSupportCaseDto, CreateSupportMessageDto, Customer, CustomerAddress, CustomerDependents,
+    CustomerFilter, CustomerLocationVersion, CustomerMerge, DataQualityReport, DataQualityRule,
+    DataQualityRuleResult, DuplicatedCustomer, DuplicationBucket, FilterValue, FlaggedOrder,
+    ImportBatch, ImportBatchStatus, ImportRowError, LateOrder, LocatedSeller, LocationStock,
+    NewAuditEntry, NewImportRowError, NewOrderAmendment, NewPaymentTransaction, NewProductImage,
+    NewRefund, Notification, NotificationAttempt, Order, OrderAmendment, OrderFilter,
+    OrderFinancials, OrderIssue, OrderItem, OrderItemOrigin, OrderProduct, OrderStatus,
+    OrderStatusChange, OutboxBacklog, OutboxEvent, PaginationParams, Payment,
+    PaymentAnalyticsFilter, PaymentMethodStats, PaymentMismatch, PaymentStatus, PaymentTransaction,
+    PendingNotification, PendingWebhookDelivery, PoolStats, Product, ProductFilter, ProductImage,
+    ProductIssue, ProductLocationStock, RecommendedProduct, ReconciliationFilter, Refund, Review,
+    ReviewText, SampleStratum, Seller, SellerBadgeThreshold, SellerFilter, SellerMetrics,
+    Sentiment, SentimentReview, SimilarProduct, SparseRow, StockAllocation, StockLocation,
+    StoredLoadJob, SupportCase, SupportCaseFilter, SupportCaseVolume, SupportMessage, TableStats,
+    TodayStats, Total, TotalMode, UpdateCategoryDto, UpdateCustomerAddressDto, UpdateCustomerDto,
+    UpdateProductDto, UpdateSupportCaseDto, WebhookDelivery, WebhookPayloadTemplate,
+    WebhookSubscription, ZipLocation,
+};
+
+#[async_trait]
+pub trait CustomerRepository: Send + Sync {
+    /// `canonical_city` is the folded city name; aliases are resolved in the database.
+    async fn create(
+        &self,
+        id: &CustomerId,
+        dto: CreateCustomerDto,
+        canonical_city: &str,
+    ) -> SqlxResult<Customer>;
+    /// Inserts the rows in one transaction, each under its own savepoint: a row that fails
+    /// is undone on its own and its error returned in its place.
+    async fn create_many(
+        &self,
+        rows: Vec<(CustomerId, CreateCustomerDto, String)>,
+    ) -> SqlxResult<Vec<SqlxResult<Customer>>>;
+    async fn find_all(
+        &self,
+        filter: &CustomerFilter,
+        pagination: &PaginationParams,
+        total: TotalMode,
+    ) -> SqlxResult<(Vec<Customer>, Total)>;
+    /// Like `find_all`, with each row trimmed to `fields` in the query itself.
+    async fn find_all_sparse(
+        &self,
+        filter: &CustomerFilter,
+        pagination: &PaginationParams,
+        fields: &[&'static str],
+        total: TotalMode,
+    ) -> SqlxResult<(Vec<SparseRow>, Total)>;
+    fn stream_all(&self) -> BoxStream<'_, SqlxResult<Customer>>;
+    async fn find_by_id(&self, id: &CustomerId) -> SqlxResult<Option<Customer>>;
+    async fn update(
+        &self,
+        id: &CustomerId,
+        dto: UpdateCustomerDto,
+        canonical_city: Option<&str>,
+    ) -> SqlxResult<Option<Customer>>;
+    /// Soft-deletes the customer, returning the deletion timestamp.
+    async fn delete(&self, id: &CustomerId) -> SqlxResult<Option<chrono::NaiveDateTime>>;
+    async fn restore(&self, id: &CustomerId) -> SqlxResult<Option<Customer>>;
+    /// Scrubs the customer's personal data and records `erasure` in the audit log, in one
+    /// transaction.
+    async fn anonymize(
+        &self,
+        id: &CustomerId,
+        erasure: &NewAuditEntry,
+    ) -> SqlxResult<Option<Customer>>;
+    /// In one transaction, re-points the orders, support cases, addresses and location
+    /// history of `source` to `target`, soft-deletes `source` and records each `audit` entry
+    /// (see [`CustomerMerge::audit_entry`]). `None` when either is missing or deleted.
+    async fn merge(
+        &self,
+        source: &CustomerId,
+        target: &CustomerId,
+        audit: &[NewAuditEntry],
+    ) -> SqlxResult<Option<CustomerMerge>>;
+    async fn count_dependents(&self, id: &CustomerId) -> SqlxResult<CustomerDependents>;
+    /// Address versions recorded by the `customers` location trigger, oldest first.
+    async fn find_location_history(
+        &self,
+        id: &CustomerId,
+    ) -> SqlxResult<Vec<CustomerLocationVersion>>;
+    /// Adds an address to the customer's book. It becomes the default when asked to, taking
+    /// over from the current one, or when the customer has no other address.
+    async fn create_address(
+        &self,
+        id: &CustomerId,
+        dto: CreateCustomerAddressDto,
+    ) -> SqlxResult<CustomerAddress>;
+    /// The default address first, then oldest first.
+    async fn find_addresses(&self, id: &CustomerId) -> SqlxResult<Vec<CustomerAddress>>;
+    async fn find_address(
+        &self,
+        id: &CustomerId,
+        address_id: i64,
+    ) -> SqlxResult<Option<CustomerAddress>>;
+    /// Making the address the default unsets the current one in the same transaction.
+    async fn update_address(
+        &self,
+        id: &CustomerId,
+        address_id: i64,
+        dto: UpdateCustomerAddressDto,
+    ) -> SqlxResult<Option<CustomerAddress>>;
+    /// Returns the deletion timestamp.
+    async fn delete_address(
+        &self,
+        id: &CustomerId,
+        address_id: i64,
+    ) -> SqlxResult<Option<chrono::NaiveDateTime>>;
+    /// Counts of live customers per state, most common first.
+    async fn count_by_state(&self) -> SqlxResult<Vec<FilterValue>>;
+    /// Counts of live customers per canonical city, most common first.
+    async fn count_by_city(&self, state: Option<BrazilState>) -> SqlxResult<Vec<FilterValue>>;
+    /// Live customer rows sharing `unique_id`, by `customer_id`.
+    async fn find_by_unique_id(&self, unique_id: &str) -> SqlxResult<Vec<Customer>>;
+    /// How many unique customers have each number of live customer rows.
+    async fn count_rows_per_unique_id(&self) -> SqlxResult<Vec<DuplicationBucket>>;
+    /// Unique customers with the most live customer rows, at least two each.
+    async fn find_most_duplicated(&self, limit: i64) -> SqlxResult<Vec<DuplicatedCustomer>>;
+}
+
+#[async_trait]
+pub trait SellerRepository: Send + Sync {
+    /// `canonical_city` is the folded city name; aliases are resolved in the database.
+    async fn create(
+        &self,
+        id: &SellerId,
+        dto: CreateSellerDto,
+        canonical_city: &str,
+    ) -> SqlxResult<Seller>;
+    /// See [`CustomerRepository::create_many`].
+    async fn create_many(
+        &self,
+        rows: Vec<(SellerId, CreateSellerDto, String)>,
+    ) -> SqlxResult<Vec<SqlxResult<Seller>>>;
+    async fn find_all(
+        &self,
+        filter: &SellerFilter,
+        pagination: &PaginationParams,
+        total: TotalMode,
+    ) -> SqlxResult<(Vec<Seller>, Total)>;
+    /// Like `find_all`, with each row trimmed to `fields` in the query itself.
+    async fn find_all_sparse(
+        &self,
+        filter: &SellerFilter,
+        pagination: &PaginationParams,
+        fields: &[&'static str],
+        total: TotalMode,
+    ) -> SqlxResult<(Vec<SparseRow>, Total)>;
+    async fn find_by_id(&self, id: &SellerId) -> SqlxResult<Option<Seller>>;
+    /// Sellers whose zip code prefix has coordinates inside `bounds`, in no particular order.
+    async fn find_within(&self, bounds: &GeoBounds) -> SqlxResult<Vec<LocatedSeller>>;
+    async fn refresh_badges(&self) -> SqlxResult<u64>;
+    async fn find_badge_thresholds(&self) -> SqlxResult<Vec<SellerBadgeThreshold>>;
+    /// Scorecard counts of `id`; all zero for a seller without orders.
+    async fn find_metrics(&self, id: &SellerId) -> SqlxResult<SellerMetrics>;
+}
+
+#[async_trait]
+pub trait OrderRepository: Send + Sync {
+    async fn create(&self, id: &OrderId, dto: CreateOrderDto) -> SqlxResult<Order>;
+    /// See [`CustomerRepository::create_many`].
+    async fn create_many(
+        &self,
+        rows: Vec<(OrderId, CreateOrderDto)>,
+    ) -> SqlxResult<Vec<SqlxResult<Order>>>;
+    /// Adds an item and, when the seller tracks the product per location, takes one unit from
+    /// the location closest to `destination_zip_code_prefix` in the same transaction, picked as
+    /// [`InventoryRepository::allocate`] does. `None`, with nothing written, when the product is
+    /// tracked but no location has a unit left.
+    async fn add_item_with_allocation(
+        &self,
+        order_id: &OrderId,
+        dto: AddItemToOrderDto,
+        destination_zip_code_prefix: &str,
+    ) -> SqlxResult<Option<(OrderItem, Option<StockAllocation>)>>;
+    async fn find_all(
+        &self,
+        filter: &OrderFilter,
+        pagination: &PaginationParams,
+        total: TotalMode,
+    ) -> SqlxResult<(Vec<Order>, Total)>;
+    /// Like `find_all`, with each row trimmed to `fields` in the query itself.
+    async fn find_all_sparse(
+        &self,
+        filter: &OrderFilter,
+        pagination: &PaginationParams,
+        fields: &[&'static str],
+        total: TotalMode,
+    ) -> SqlxResult<(Vec<SparseRow>, Total)>;
+    fn stream_all(&self) -> BoxStream<'_, SqlxResult<Order>>;
+    async fn find_by_id(&self, id: &OrderId) -> SqlxResult<Option<Order>>;
+    async fn find_status(&self, id: &OrderId) -> SqlxResult<Option<OrderStatusChange>>;
+    /// Streams every non-empty review comment, oldest first.
+    fn stream_review_texts(&self) -> BoxStream<'_, SqlxResult<ReviewText>>;
+    async fn sample(
+        &self,
+        strata: &[SampleStratum],
+        size: i64,
+        seed: i64,
+    ) -> SqlxResult<Vec<Order>>;
+    async fn find_products_by_order_id(&self, id: &OrderId) -> SqlxResult<Vec<OrderProduct>>;
+    /// The products of several orders in one query, each paired with its order.
+    async fn find_products_by_order_ids(
+        &self,
+        ids: &[OrderId],
+    ) -> SqlxResult<Vec<(OrderId, OrderProduct)>>;
+    async fn find_payments_by_order_id(&self, id: &OrderId) -> SqlxResult<Vec<Payment>>;
+    /// The payments of several orders in one query.
+    async fn find_payments_by_order_ids(&self, ids: &[OrderId]) -> SqlxResult<Vec<Payment>>;
+    /// Records a refund of one of the order's payments, unless it would take the payment's
+    /// refunds past its value. `None` when it would or when there is no such payment.
+    async fn create_refund(&self, refund: NewRefund) -> SqlxResult<Option<Refund>>;
+    /// Oldest first.
+    async fn find_refunds_by_order_id(&self, id: &OrderId) -> SqlxResult<Vec<Refund>>;
+    /// The refunds of several orders in one query, oldest first.
+    async fn find_refunds_by_order_ids(&self, ids: &[OrderId]) -> SqlxResult<Vec<Refund>>;
+    /// An order's item, payment and refund sums and its coupon, in one query.
+    async fn find_financials(&self, id: &OrderId) -> SqlxResult<Option<OrderFinancials>>;
+    /// Orders whose summed payments and summed item prices and freight differ by more than
+    /// the filter's tolerance, oldest purchase first, with the total count.
+    async fn find_payment_mismatches(
+        &self,
+        filter: &ReconciliationFilter,
+        pagination: &PaginationParams,
+    ) -> SqlxResult<(Vec<PaymentMismatch>, i64)>;
+    /// Payments of the orders in the filter's purchase range grouped by method, most used
+    /// first.
+    async fn find_payment_method_stats(
+        &self,
+        filter: &PaymentAnalyticsFilter,
+    ) -> SqlxResult<Vec<PaymentMethodStats>>;
+    async fn find_reviews_by_order_id(&self, id: &OrderId) -> SqlxResult<Vec<Review>>;
+    /// The reviews of several orders in one query.
+    async fn find_reviews_by_order_ids(&self, ids: &[OrderId]) -> SqlxResult<Vec<Review>>;
+    async fn find_by_customer_id(
+        &self,
+        customer_id: &CustomerId,
+        pagination: &PaginationParams,
+    ) -> SqlxResult<(Vec<Order>, i64)>;
+    /// Orders of the live customer rows sharing `unique_id`, newest first.
+    async fn find_by_customer_unique_id(&self, unique_id: &str) -> SqlxResult<Vec<Order>>;
+    /// Up to `limit` of the customer's orders, oldest purchase first and then by id, starting
+    /// after `after`. Each page is a query of its own, so paging holds no connection between
+    /// pages.
+    async fn find_by_customer_id_after(
+        &self,
+        customer_id: &CustomerId,
+        after: Option<&Order>,
+        limit: i64,
+    ) -> SqlxResult<Vec<Order>>;
+    async fn find_destination_zip_code_prefix(
+        &self,
+        order_id: &OrderId,
+    ) -> SqlxResult<Option<String>>;
+    async fn find_item_origins(&self, order_id: &OrderId) -> SqlxResult<Vec<OrderItemOrigin>>;
+    /// Applies an amendment atomically. Each update pairs the item's current product id
+    /// with its new state.
+    async fn apply_amendment(
+        &self,
+        order_id: &OrderId,
+        actor: &str,
+        shipping_zip_code_prefix: Option<&str>,
+        items: &[(ProductId, OrderItem)],
+        amendment: NewOrderAmendment,
+    ) -> SqlxResult<OrderAmendment>;
+    async fn find_amendments(&self, order_id: &OrderId) -> SqlxResult<Vec<OrderAmendment>>;
+    async fn count_by_status(&self) -> SqlxResult<Vec<FilterValue>>;
+}
+
+#[async_trait]
+pub trait ProductRepository: Send + Sync {
+    async fn create(&self, id: &ProductId, dto: CreateProductDto) -> SqlxResult<Product>;
+    /// See [`CustomerRepository::create_many`].
+    async fn create_many(
+        &self,
+        rows: Vec<(ProductId, CreateProductDto)>,
+    ) -> SqlxResult<Vec<SqlxResult<Product>>>;
+    async fn find_all(
+        &self,
+        filter: &ProductFilter,
+        pagination: &PaginationParams,
+        total: TotalMode,
+    ) -> SqlxResult<(Vec<Product>, Total)>;
+    /// Like `find_all`, with each row trimmed to `fields` in the query itself.
+    async fn find_all_sparse(
+        &self,
+        filter: &ProductFilter,
+        pagination: &PaginationParams,
+        fields: &[&'static str],
+        total: TotalMode,
+    ) -> SqlxResult<(Vec<SparseRow>, Total)>;
+    fn stream_all(&self) -> BoxStream<'_, SqlxResult<Product>>;
+    async fn find_by_id(&self, id: &ProductId) -> SqlxResult<Option<Product>>;
+    async fn update(&self, id: &ProductId, dto: UpdateProductDto) -> SqlxResult<Option<Product>>;
+    /// Adds an image after the product's last one and counts it in `product_photos_qty`, in
+    /// one transaction.
+    async fn create_image(
+        &self,
+        id: &ProductId,
+        image: NewProductImage,
+    ) -> SqlxResult<ProductImage>;
+    /// The product's images by position.
+    async fn find_images(&self, id: &ProductId) -> SqlxResult<Vec<ProductImage>>;
+    /// Deletes the image, moving the later ones up a position and uncounting it from
+    /// `product_photos_qty`. Returns the deleted image and the deletion timestamp.
+    async fn delete_image(
+        &self,
+        id: &ProductId,
+        image_id: i64,
+    ) -> SqlxResult<Option<(ProductImage, chrono::NaiveDateTime)>>;
+    async fn count_by_category(&self) -> SqlxResult<Vec<FilterValue>>;
+    /// Products in the categories `customer_unique_id` has bought from that they haven't
+    /// bought yet, most ordered by customers in `state` first.
+    async fn find_recommended(
+        &self,
+        customer_unique_id: &str,
+        state: &str,
+        limit: i64,
+    ) -> SqlxResult<Vec<RecommendedProduct>>;
+}
+
+#[async_trait]
+pub trait CategoryRepository: Send + Sync {
+    async fn create(&self, dto: CreateCategoryDto) -> SqlxResult<Category>;
+    async fn find_by_name(&self, name: &str) -> SqlxResult<Option<Category>>;
+    async fn update(&self, name: &str, dto: UpdateCategoryDto) -> SqlxResult<Option<Category>>;
+    /// Number of products filed under the category.
+    async fn count_products(&self, name: &str) -> SqlxResult<i64>;
+    /// Deletes the category, returning the deletion timestamp.
+    async fn delete(&self, name: &str) -> SqlxResult<Option<chrono::NaiveDateTime>>;
+}
+
+#[async_trait]
+pub trait AuditRepository: Send + Sync {
+    async fn record(&self, entry: NewAuditEntry) -> SqlxResult<AuditEntry>;
+    /// Inserts the entries in one statement.
+    async fn record_many(&self, entries: Vec<NewAuditEntry>) -> SqlxResult<()>;
+    async fn find_all(
+        &self,
+        filter: &AuditFilter,
+        pagination: &PaginationParams,
+    ) -> SqlxResult<(Vec<AuditEntry>, i64)>;
+}
+
+#[async_trait]
+pub trait EmbeddingRepository: Send + Sync {
+    async fn find_embedding_source(&self, product_id: &ProductId) -> SqlxResult<Option<String>>;
+    async fn has_embedding(&self, product_id: &ProductId) -> SqlxResult<bool>;
+    async fn upsert(&self, product_id: &ProductId, embedding: &[f32]) -> SqlxResult<()>;
+    async fn find_products_without_embedding(&self, limit: i64) -> SqlxResult<Vec<ProductId>>;
+    async fn find_similar(
+        &self,
+        product_id: &ProductId,
+        limit: i64,
+    ) -> SqlxResult<Vec<SimilarProduct>>;
+}
+
+#[async_trait]
+pub trait LateOrderRepository: Send + Sync {
+    /// Records an alert, and an `order.late` outbox event, for each order not flagged before
+    /// whose estimated delivery date passed within the last `max_age_days` days without it
+    /// being delivered or canceled. Returns how many were flagged.
+    async fn flag_late_orders(&self, max_age_days: i64) -> SqlxResult<u64>;
+    /// Flagged orders still undelivered, most overdue first.
+    async fn find_late_orders(
+        &self,
+        pagination: &PaginationParams,
+    ) -> SqlxResult<(Vec<LateOrder>, i64)>;
+}
+
+#[async_trait]
+pub trait ReviewSentimentRepository: Send + Sync {
+    /// Up to `limit` reviews with a non-empty comment and no sentiment yet, oldest first.
+    async fn find_untagged(&self, limit: i64) -> SqlxResult<Vec<ReviewText>>;
+    /// Stores the sentiment of each review, replacing any it had; returns how many were stored.
+    async fn save(&self, sentiments: &[(String, Sentiment)]) -> SqlxResult<u64>;
+    /// Newest first, optionally only those tagged with `sentiment`.
+    async fn find_reviews(
+        &self,
+        sentiment: Option<Sentiment>,
+        pagination: &PaginationParams,
+    ) -> SqlxResult<(Vec<SentimentReview>, i64)>;
+}
+
+#[async_trait]
+pub trait InventoryRepository: Send + Sync {
+    async fn create_location(
+        &self,
+        seller_id: &SellerId,
+        dto: CreateStockLocationDto,
+    ) -> SqlxResult<StockLocation>;
+    async fn find_location_by_id(&self, location_id: i64) -> SqlxResult<Option<StockLocation>>;
+    async fn find_locations_by_seller(
+        &self,
+        seller_id: &SellerId,
+    ) -> SqlxResult<Vec<StockLocation>>;
+    async fn find_stock_by_location(&self, location_id: i64) -> SqlxResult<Vec<LocationStock>>;
+    async fn find_stock_by_product(
+        &self,
+        product_id: &ProductId,
+    ) -> SqlxResult<Vec<ProductLocationStock>>;
+    async fn set_stock(
+        &self,
+        location_id: i64,
+        product_id: &ProductId,
+        quantity: i32,
+    ) -> SqlxResult<LocationStock>;
+    async fn adjust_stock(
+        &self,
+        location_id: i64,
+        product_id: &ProductId,
+        delta: i32,
+    ) -> SqlxResult<Option<LocationStock>>;
+    async fn is_tracked(&self, seller_id: &SellerId, product_id: &ProductId) -> SqlxResult<bool>;
+    async fn allocate(
+        &self,
+        seller_id: &SellerId,
+        product_id: &ProductId,
+        quantity: i32,
+        destination_zip_code_prefix: &str,
+    ) -> SqlxResult<Option<StockAllocation>>;
+    async fn restock(
+        &self,
+        seller_id: &SellerId,
+        product_id: &ProductId,
+        quantity: i32,
+        origin_zip_code_prefix: &str,
+    ) -> SqlxResult<Option<StockAllocation>>;
+}
+
+#[async_trait]
+pub trait SupportRepository: Send + Sync {
+    /// Opens a case for an order, with `dto.message` as the first customer message.
+    /// Returns `None` when the order does not exist.
+    async fn create(
+        &self,
+        dto: CreateSupportCaseDto,
+        author: &str,
+        first_response_hours: i64,
+        resolution_hours: i64,
+    ) -> SqlxResult<Option<SupportCase>>;
+    async fn find_all(
+        &self,
+        filter: &SupportCaseFilter,
+        pagination: &PaginationParams,
+    ) -> SqlxResult<(Vec<SupportCase>, i64)>;
+    async fn find_by_id(&self, case_id: i64) -> SqlxResult<Option<SupportCase>>;
+    async fn find_messages(&self, case_id: i64) -> SqlxResult<Vec<SupportMessage>>;
+    async fn update(
+        &self,
+        case_id: i64,
+        dto: UpdateSupportCaseDto,
+    ) -> SqlxResult<Option<SupportCase>>;
+    /// Deletes the case, returning the deletion timestamp.
+    async fn delete(&self, case_id: i64) -> SqlxResult<Option<chrono::NaiveDateTime>>;
+    async fn add_message(
+        &self,
+        case_id: i64,
+        dto: CreateSupportMessageDto,
+        author: &str,
+    ) -> SqlxResult<Option<SupportMessage>>;
+    async fn volume_by_category(&self) -> SqlxResult<Vec<SupportCaseVolume>>;
+}
+
+#[async_trait]
+pub trait MaintenanceRepository: Send + Sync {
+    async fn find_materialized_views(&self) -> SqlxResult<Vec<String>>;
+    async fn refresh_materialized_view(&self, name: &str) -> SqlxResult<()>;
+    /// Rebuilds the indexes backing search endpoints, skipping those whose optional
+    /// extension is not installed. Returns the names of the rebuilt indexes.
+    async fn reindex_search_indexes(&self) -> SqlxResult<Vec<String>>;
+    /// Moves up to `limit` closed orders purchased before `before`, with their items, payments
+    /// and reviews, to the archive tables in one transaction. Orders that amendments, support
+    /// cases, coupons, payment transactions or refunds refer to stay put. `None` when the
+    /// backend keeps no archive.
+    async fn archive_orders(
+        &self,
+        before: chrono::NaiveDateTime,
+        limit: i64,
+    ) -> SqlxResult<Option<ArchivedOrders>>;
+}
+
+#[async_trait]
+pub trait RetentionRepository: Send + Sync {
+    /// Reviews written before `before`, archived ones included.
+    async fn count_reviews_created_before(&self, before: chrono::NaiveDateTime) -> SqlxResult<i64>;
+    /// Deletes up to `limit` of the reviews written before `before`, live ones before archived
+    /// ones, returning how many went.
+    async fn delete_reviews_created_before(
+        &self,
+        before: chrono::NaiveDateTime,
+        limit: i64,
+    ) -> SqlxResult<u64>;
+    /// Customers not yet anonymized whose last order or address change, whichever is later,
+    /// is before `before`. Customers with neither have no known activity and never match.
+    async fn count_inactive_customers(&self, before: chrono::NaiveDateTime) -> SqlxResult<i64>;
+    async fn find_inactive_customers(
+        &self,
+        before: chrono::NaiveDateTime,
+        limit: i64,
+    ) -> SqlxResult<Vec<CustomerId>>;
+}
+
+#[async_trait]
+pub trait DiagnosticsRepository: Send + Sync {
+    async fn ping(&self) -> SqlxResult<()>;
+    /// Seconds this node trails its primary when it is a replica, or the worst replay lag
+    /// among attached replicas when it is the primary. `None` when there is no replication.
+    async fn replica_lag_seconds(&self) -> SqlxResult<Option<f64>>;
+    async fn database_size_bytes(&self) -> SqlxResult<Option<i64>>;
+    async fn last_migration(&self) -> SqlxResult<Option<AppliedMigration>>;
+    /// Every table by name. Row counts are estimated from statistics, where the backend keeps
+    /// them, unless `exact`; the flag says whether they were.
+    async fn table_stats(&self, exact: bool) -> SqlxResult<(Vec<TableStats>, bool)>;
+    /// `None` when there is no connection pool.
+    fn pool_stats(&self) -> Option<PoolStats>;
+}
+
+#[async_trait]
+pub trait DataQualityRepository: Send + Sync {
+    async fn product_report(&self) -> SqlxResult<DataQualityReport<ProductIssue>>;
+    /// Products with `issue`, or with any issue when `None`, by id.
+    async fn find_flagged_products(
+        &self,
+        issue: Option<ProductIssue>,
+        pagination: &PaginationParams,
+    ) -> SqlxResult<(Vec<Product>, i64)>;
+    /// Archived orders are not checked.
+    async fn order_report(&self) -> SqlxResult<DataQualityReport<OrderIssue>>;
+    /// Orders with `issue`, or with any issue when `None`, oldest purchase first. Their
+    /// `issues` are left empty.
+    async fn find_flagged_orders(
+        &self,
+        issue: Option<OrderIssue>,
+        pagination: &PaginationParams,
+    ) -> SqlxResult<(Vec<FlaggedOrder>, i64)>;
+    /// Rows of the rule's table and how many of them break it. Fails when the table or a
+    /// column doesn't exist or the comparison doesn't fit the column's type.
+    async fn evaluate_rule(&self, rule: &DataQualityRule) -> SqlxResult<(i64, i64)>;
+    /// Replaces the stored rule results with `results`, in one transaction.
+    async fn replace_rule_results(&self, results: &[DataQualityRuleResult]) -> SqlxResult<()>;
+    /// Most severe first, then by rule name.
+    async fn find_rule_results(&self) -> SqlxResult<Vec<DataQualityRuleResult>>;
+}
+
+#[async_trait]
+pub trait ImportRepository: Send + Sync {
+    async fn begin_load_job(&self, datasets: &[&str], actor: &str) -> SqlxResult<i64>;
+    async fn find_load_job(&self, job_id: i64) -> SqlxResult<Option<StoredLoadJob>>;
+    async fn begin_batch(
+        &self,
+        dataset: &str,
+        source: &str,
+        actor: &str,
+        load_job_id: Option<i64>,
+    ) -> SqlxResult<ImportBatch>;
+    async fn record_rows(&self, batch_id: i64, entity_ids: &[String]) -> SqlxResult<()>;
+    async fn checkpoint(&self, batch_id: i64, line: i64) -> SqlxResult<()>;
+    /// Marks a running or failed batch running again and drops the errors recorded past its
+    /// checkpoint, in one transaction. Returns `None` if the batch was in another state.
+    async fn resume_batch(&self, batch_id: i64) -> SqlxResult<Option<BatchResume>>;
+    async fn finish_batch(
+        &self,
+        batch_id: i64,
+        status: ImportBatchStatus,
+        success_count: i32,
+        error_count: i32,
+    ) -> SqlxResult<ImportBatch>;
+    async fn find_all(&self, pagination: &PaginationParams) -> SqlxResult<(Vec<ImportBatch>, i64)>;
+    async fn find_by_id(&self, batch_id: i64) -> SqlxResult<Option<ImportBatch>>;
+    async fn record_errors(&self, batch_id: i64, errors: &[NewImportRowError]) -> SqlxResult<()>;
+    /// Failed rows of the given batches, by batch and then line.
+    async fn find_errors(
+        &self,
+        batch_ids: &[i64],
+        pagination: &PaginationParams,
+    ) -> SqlxResult<(Vec<ImportRowError>, i64)>;
+    fn stream_errors<'a>(
+        &'a self,
+        batch_ids: &'a [i64],
+    ) -> BoxStream<'a, SqlxResult<ImportRowError>>;
+    /// Which of `keys` are already in `table`.
+    async fn existing_keys(
+        &self,
+        table: &str,
+        key_column: &str,
+        keys: &[String],
+    ) -> SqlxResult<Vec<String>>;
+    /// Deletes the rows the batch inserted from `table` and marks it rolled back, in one
+    /// transaction. Returns `None` if the batch was not in a finished state.
+    async fn rollback(
+        &self,
+        batch_id: i64,
+        table: &str,
+        key_column: &str,
+    ) -> SqlxResult<Option<(ImportBatch, u64)>>;
+}
+
+#[async_trait]
+pub trait GeolocationRepository: Send + Sync {
+    /// Locations of those of `prefixes` that are known.
+    async fn find_by_prefixes(&self, prefixes: &[String]) -> SqlxResult<Vec<ZipLocation>>;
+    /// Up to `limit` locations, by prefix.
+    async fn find_all(&self, limit: i64) -> SqlxResult<Vec<ZipLocation>>;
+    /// Inserts the locations, replacing those already stored for their prefixes. Returns how
+    /// many were written.
+    async fn upsert_many(&self, locations: &[ZipLocation]) -> SqlxResult<u64>;
+}
+
+#[async_trait]
+pub trait CouponRepository: Send + Sync {
+    /// Stores the coupon under `dto.code` as given.
+    async fn create(&self, dto: CreateCouponDto) -> SqlxResult<Coupon>;
+    async fn find_by_code(&self, code: &str) -> SqlxResult<Option<Coupon>>;
+    /// The coupon applied to the order, if any.
+    async fn find_by_order(&self, order_id: &OrderId) -> SqlxResult<Option<Coupon>>;
+    /// Takes one use of the coupon and attaches it to the order in one transaction.
+    /// Returns `false` when the coupon is used up or has expired; an order that already
+    /// has a coupon fails with a unique violation.
+    async fn redeem(&self, code: &str, order_id: &OrderId) -> SqlxResult<bool>;
+}
+
+/// An order status change made along with a payment's, as `(from, to)`: the order only moves
+/// when it is still in `from`.
+pub type OrderStatusTransition = (OrderStatus, OrderStatus);
+
+#[async_trait]
+pub trait PaymentTransactionRepository: Send + Sync {
+    /// Records the transaction and applies `order_status` to its order in one transaction.
+    async fn create(
+        &self,
+        transaction: NewPaymentTransaction,
+        order_status: Option<OrderStatusTransition>,
+    ) -> SqlxResult<PaymentTransaction>;
+    async fn find_by_id(&self, transaction_id: i64) -> SqlxResult<Option<PaymentTransaction>>;
+    async fn find_by_reference(
+        &self,
+        provider: &str,
+        reference: &str,
+    ) -> SqlxResult<Option<PaymentTransaction>>;
+    /// Oldest first.
+    async fn find_by_order(&self, order_id: &OrderId) -> SqlxResult<Vec<PaymentTransaction>>;
+    /// Moves the transaction from `from` to `to` and applies `order_status` to its order in
+    /// one transaction. `None` when the transaction is no longer in `from`.
+    async fn update_status(
+        &self,
+        transaction_id: i64,
+        from: PaymentStatus,
+        to: PaymentStatus,
+        order_status: Option<OrderStatusTransition>,
+    ) -> SqlxResult<Option<PaymentTransaction>>;
+}
+
+#[async_trait]
+pub trait StatsRepository: Send + Sync {
+    async fn today(&self) -> SqlxResult<TodayStats>;
+}
+
+#[async_trait]
+pub trait WebhookRepository: Send + Sync {
+    async fn create_subscription(
+        &self,
+        url: &str,
+        events: &[String],
+        customer_states: Option<&[String]>,
+        payload_template: WebhookPayloadTemplate,
+        secret: &str,
+        created_by: &str,
+    ) -> SqlxResult<WebhookSubscription>;
+    async fn find_subscriptions(
+        &self,
+        pagination: &PaginationParams,
+    ) -> SqlxResult<(Vec<WebhookSubscription>, i64)>;
+    async fn find_subscription(
+        &self,
+        subscription_id: i64,
+    ) -> SqlxResult<Option<WebhookSubscription>>;
+    /// Deletes the subscription and its delivery log, returning the deletion timestamp.
+    async fn delete_subscription(
+        &self,
+        subscription_id: i64,
+    ) -> SqlxResult<Option<chrono::NaiveDateTime>>;
+    /// Newest first, optionally only those with `status`.
+    async fn find_deliveries(
+        &self,
+        subscription_id: i64,
+        status: Option<&str>,
+        pagination: &PaginationParams,
+    ) -> SqlxResult<(Vec<WebhookDelivery>, i64)>;
+    async fn find_delivery(
+        &self,
+        subscription_id: i64,
+        delivery_id: i64,
+    ) -> SqlxResult<Option<WebhookDelivery>>;
+    /// Leases up to `limit` pending deliveries that are due, oldest first, by moving their
+    /// next attempt `lease_seconds` ahead and counting the attempt. Concurrent workers skip
+    /// each other's leases; a worker that dies mid-delivery leaves the row to be retried
+    /// when its lease runs out. Each comes with the state of the customer who placed the
+    /// event's order, for the subscription's state filter.
+    async fn claim_due_deliveries(
+        &self,
+        limit: i64,
+        lease_seconds: i64,
+    ) -> SqlxResult<Vec<PendingWebhookDelivery>>;
+    async fn mark_delivered(&self, delivery_id: i64, status_code: i32) -> SqlxResult<()>;
+    /// Drops a delivery its subscription's filters leave out, so it is never sent.
+    async fn discard_delivery(&self, delivery_id: i64) -> SqlxResult<()>;
+    /// Records a failed attempt. With `retry_in_seconds` the delivery stays pending until
+    /// then; without, it is marked failed for good.
+    async fn mark_attempt_failed(
+        &self,
+        delivery_id: i64,
+        status_code: Option<i32>,
+        error: &str,
+        retry_in_seconds: Option<i64>,
+    ) -> SqlxResult<()>;
+    /// Makes failed deliveries created within the last `max_age_hours` due again, keeping
+    /// their attempt count, and returns how many there were.
+    async fn requeue_failed_deliveries(&self, max_age_hours: i64) -> SqlxResult<u64>;
+}
+
+#[async_trait]
+pub trait OutboxRepository: Send + Sync {
+    /// Leases up to `limit` unpublished events that are due, in id order, by moving their
+    /// next attempt `lease_seconds` ahead. An earlier event that is leased or waiting for a
+    /// retry holds back every later one, so events are published one relay at a time and in
+    /// order.
+    async fn claim_due_events(
+        &self,
+        limit: i64,
+        lease_seconds: i64,
+    ) -> SqlxResult<Vec<OutboxEvent>>;
+    /// Marks the event published and queues a delivery for every webhook subscription to
+    /// its type and the notification it calls for, if any, atomically. Does nothing when
+    /// the event was already published.
+    async fn mark_published(&self, event_id: i64) -> SqlxResult<()>;
+    /// Records a failed publish and leaves the event unpublished until `retry_in_seconds`.
+    async fn mark_publish_failed(
+        &self,
+        event_id: i64,
+        error: &str,
+        retry_in_seconds: i64,
+    ) -> SqlxResult<()>;
+    /// Ends the lease on events that were claimed but not attempted.
+    async fn release_events(&self, event_ids: &[i64]) -> SqlxResult<()>;
+    async fn backlog(&self) -> SqlxResult<OutboxBacklog>;
+    /// Deletes events published more than `days` ago, returning how many were deleted.
+    async fn delete_published_before(&self, days: i64) -> SqlxResult<u64>;
+}
+
+#[async_trait]
+pub trait NotificationRepository: Send + Sync {
+    /// Newest first, optionally only those with `status` and of `kind`.
+    async fn find_notifications(
+        &self,
+        status: Option<&str>,
+        kind: Option<&str>,
+        pagination: &PaginationParams,
+    ) -> SqlxResult<(Vec<Notification>, i64)>;
+    async fn find_notification(&self, notification_id: i64) -> SqlxResult<Option<Notification>>;
+    /// Leases up to `limit` pending notifications that are due, oldest first, the way
+    /// webhook deliveries are leased.
+    async fn claim_due_notifications(
+        &self,
+        limit: i64,
+        lease_seconds: i64,
+    ) -> SqlxResult<Vec<PendingNotification>>;
+    async fn mark_sent(
+        &self,
+        notification_id: i64,
+        attempt: &NotificationAttempt<'_>,
+    ) -> SqlxResult<()>;
+    /// Records a failed attempt. With `retry_in_seconds` the notification stays pending until
+    /// then; without, it is marked failed for good.
+    async fn mark_attempt_failed(
+        &self,
+        notification_id: i64,
+        attempt: &NotificationAttempt<'_>,
+        error: &str,
+        retry_in_seconds: Option<i64>,
+    ) -> SqlxResult<()>;
+}
@@ -0,0 +1,178 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::models::JobStatus;
+
+/// Name the seller badge refresh is tracked under in [`JobRuns`].
+pub const SELLER_BADGES_JOB: &str = "seller_badges";
+/// Name the scheduled refresh of the analytics materialized views is tracked under.
+pub const ANALYTICS_VIEWS_JOB: &str = "refresh_analytics_views";
+/// Name the scheduled retry of failed webhook deliveries is tracked under.
+pub const FAILED_WEBHOOKS_JOB: &str = "retry_failed_webhooks";
+/// Name the scheduled application of the data retention rules is tracked under.
+pub const RETENTION_JOB: &str = "apply_retention";
+/// Name the scheduled sentiment tagging of new review comments is tracked under.
+pub const SENTIMENT_JOB: &str = "tag_review_sentiment";
+/// Name the scheduled check for orders past their estimated delivery date is tracked under.
+pub const LATE_ORDERS_JOB: &str = "flag_late_orders";
+/// Name the scheduled evaluation of the configured data quality rules is tracked under.
+pub const DATA_QUALITY_JOB: &str = "evaluate_data_quality";
+
+/// Shared flag flipped once startup warm-up has finished and the canary query passed.
+#[derive(Clone, Default)]
+pub struct Readiness(Arc<AtomicBool>);
+
+impl Readiness {
+    pub fn mark_ready(&self) {
+        self.0.store(true, Ordering::Release);
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.0.load(Ordering::Acquire)
+    }
+}
+
+/// Whether the API refuses writes, and why, as reported by `GET /admin/read-only`.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct ReadOnlyStatus {
+    pub enabled: bool,
+    pub reason: Option<String>,
+    /// When the mode was last switched; `None` while it is as configured at startup.
+    pub changed_at: Option<DateTime<Utc>>,
+    pub changed_by: Option<String>,
+}
+
+/// Shared read-only switch. While it is on, the router answers writes with `503`.
+#[derive(Clone, Default)]
+pub struct ReadOnlyMode(Arc<Mutex<ReadOnlyStatus>>);
+
+impl ReadOnlyMode {
+    pub fn new(enabled: bool, reason: Option<String>) -> Self {
+        Self(Arc::new(Mutex::new(ReadOnlyStatus {
+            enabled,
+            reason: reason.filter(|_| enabled),
+            changed_at: None,
+            changed_by: None,
+        })))
+    }
+
+    pub fn status(&self) -> ReadOnlyStatus {
+        self.0.lock().expect("read-only mode poisoned").clone()
+    }
+
+    /// Switches the mode; the reason is dropped when it is switched off.
+    pub fn set(&self, enabled: bool, reason: Option<String>, actor: &str) -> ReadOnlyStatus {
+        let mut status = self.0.lock().expect("read-only mode poisoned");
+        *status = ReadOnlyStatus {
+            enabled,
+            reason: reason.filter(|_| enabled),
+            changed_at: Some(Utc::now()),
+            changed_by: Some(actor.to_string()),
+        };
+        status.clone()
+    }
+}
+
+/// Schedule and outcome of the latest runs of a background job.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct JobRun {
+    /// How often the job runs, e.g. a cron expression; `None` when it is disabled.
+    pub schedule: Option<String>,
+    pub next_run_at: Option<DateTime<Utc>>,
+    pub last_status: Option<JobStatus>,
+    pub last_started_at: Option<DateTime<Utc>>,
+    pub last_duration_ms: Option<u64>,
+    /// What the latest successful run did, e.g. how many rows it touched.
+    pub last_detail: Option<String>,
+    pub last_success: Option<DateTime<Utc>>,
+    pub last_failure: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    pub runs: u64,
+}
+
+/// A job's [`JobRun`] under its name, as listed by `GET /admin/jobs`.
+#[derive(Clone, Debug, Serialize)]
+pub struct JobReport {
+    pub name: &'static str,
+    #[serde(flatten)]
+    pub run: JobRun,
+}
+
+/// Shared record of background job schedules and runs, read by the diagnostics report and
+/// `GET /admin/jobs`.
+#[derive(Clone, Default)]
+pub struct JobRuns(Arc<Mutex<HashMap<&'static str, JobRun>>>);
+
+impl JobRuns {
+    fn update(&self, job: &'static str, update: impl FnOnce(&mut JobRun)) {
+        let mut runs = self.0.lock().expect("job run registry poisoned");
+        update(runs.entry(job).or_default());
+    }
+
+    /// Lists the job, even before its first run. `schedule` is `None` for a disabled job.
+    pub fn register(&self, job: &'static str, schedule: Option<String>) {
+        self.update(job, |run| run.schedule = schedule);
+    }
+
+    pub fn schedule_next(&self, job: &'static str, at: DateTime<Utc>) {
+        self.update(job, |run| run.next_run_at = Some(at));
+    }
+
+    pub fn record_start(&self, job: &'static str) {
+        self.update(job, |run| {
+            run.last_status = Some(JobStatus::Running);
+            run.last_started_at = Some(Utc::now());
+            run.next_run_at = None;
+        });
+    }
+
+    pub fn record_success(&self, job: &'static str, detail: String) {
+        self.update(job, |run| {
+            let now = Utc::now();
+            run.finish(now, JobStatus::Completed);
+            run.last_success = Some(now);
+            run.last_detail = Some(detail);
+        });
+    }
+
+    pub fn record_failure(&self, job: &'static str, error: String) {
+        self.update(job, |run| {
+            let now = Utc::now();
+            run.finish(now, JobStatus::Failed);
+            run.last_failure = Some(now);
+            run.last_error = Some(error);
+        });
+    }
+
+    pub fn get(&self, job: &str) -> Option<JobRun> {
+        let runs = self.0.lock().expect("job run registry poisoned");
+        runs.get(job).cloned()
+    }
+
+    /// Every job, by name.
+    pub fn all(&self) -> Vec<JobReport> {
+        let runs = self.0.lock().expect("job run registry poisoned");
+        let mut reports: Vec<JobReport> = runs
+            .iter()
+            .map(|(name, run)| JobReport {
+                name,
+                run: run.clone(),
+            })
+            .collect();
+        reports.sort_by_key(|report| report.name);
+        reports
+    }
+}
+
+impl JobRun {
+    fn finish(&mut self, now: DateTime<Utc>, status: JobStatus) {
+        self.last_status = Some(status);
+        self.last_duration_ms = self
+            .last_started_at
+            .map(|started| (now - started).num_milliseconds().max(0) as u64);
+        self.runs += 1;
+    }
+}
@@ -0,0 +1,215 @@
+use async_trait::async_trait;
+
+use crate::cities::fold_accent;
+use crate::error::AppResult;
+use crate::models::Sentiment;
+
+/// Tells the tone of a review comment, for CX triage.
+///
+/// Implementations backed by an external model can be swapped in when building `AppState`.
+#[async_trait]
+pub trait SentimentProvider: Send + Sync {
+    async fn classify(&self, text: &str) -> AppResult<Sentiment>;
+}
+
+/// Dependency-free provider counting Portuguese opinion words, as the Olist reviews are
+/// written in Brazilian Portuguese. Accents are ignored.
+///
+/// A negation ("não", "nunca", "nem", "sem") flips the next opinion word within a few words,
+/// so "não recomendo" is negative and "sem problemas" positive. Words like "chegou" only count,
+/// as negative, when negated: "ainda não chegou".
+#[derive(Clone, Default)]
+pub struct LexiconSentimentProvider;
+
+/// Words after a negation that may still be the one it applies to.
+const NEGATION_REACH: usize = 3;
+
+const NEGATIONS: &[&str] = &["nao", "nunca", "nem", "sem"];
+
+const POSITIVE_WORDS: &[&str] = &[
+    "adorei",
+    "amei",
+    "boa",
+    "boas",
+    "bom",
+    "bons",
+    "excelente",
+    "excelentes",
+    "eficiente",
+    "gostei",
+    "legal",
+    "linda",
+    "lindo",
+    "maravilhosa",
+    "maravilhoso",
+    "otima",
+    "otimo",
+    "parabens",
+    "perfeita",
+    "perfeito",
+    "rapida",
+    "rapidez",
+    "rapido",
+    "recomendo",
+    "satisfeita",
+    "satisfeito",
+    "top",
+];
+
+const NEGATIVE_WORDS: &[&str] = &[
+    "absurdo",
+    "atrasada",
+    "atrasado",
+    "atraso",
+    "cancelar",
+    "danificada",
+    "danificado",
+    "decepcao",
+    "decepcionada",
+    "decepcionado",
+    "defeito",
+    "defeituoso",
+    "demora",
+    "demorou",
+    "devolucao",
+    "devolver",
+    "enganosa",
+    "enganoso",
+    "errada",
+    "errado",
+    "faltando",
+    "faltou",
+    "horrivel",
+    "insatisfeita",
+    "insatisfeito",
+    "lixo",
+    "pessima",
+    "pessimo",
+    "problema",
+    "problemas",
+    "quebrada",
+    "quebrado",
+    "reclamacao",
+    "ruim",
+];
+
+/// Neutral on their own; a negation makes them a complaint about the delivery or the product.
+const EXPECTATION_WORDS: &[&str] = &[
+    "chegou",
+    "chegaram",
+    "entregaram",
+    "entregue",
+    "funciona",
+    "funcionou",
+    "recebi",
+    "recebido",
+    "veio",
+];
+
+#[async_trait]
+impl SentimentProvider for LexiconSentimentProvider {
+    async fn classify(&self, text: &str) -> AppResult<Sentiment> {
+        let folded: String = text
+            .chars()
+            .flat_map(char::to_lowercase)
+            .map(fold_accent)
+            .collect();
+        let words = folded
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|word| !word.is_empty());
+
+        let mut score = 0i32;
+        let mut negated_for = 0usize;
+        for word in words {
+            if NEGATIONS.contains(&word) {
+                negated_for = NEGATION_REACH;
+                continue;
+            }
+
+            let polarity = if POSITIVE_WORDS.contains(&word) {
+                1
+            } else if NEGATIVE_WORDS.contains(&word) {
+                -1
+            } else {
+                0
+            };
+
+            if negated_for == 0 {
+                score += polarity;
+            } else if polarity != 0 {
+                score -= polarity;
+                negated_for = 0;
+            } else if EXPECTATION_WORDS.contains(&word) {
+                score -= 1;
+                negated_for = 0;
+            } else {
+                negated_for -= 1;
+            }
+        }
+
+        Ok(match score.signum() {
+            1 => Sentiment::Positive,
+            -1 => Sentiment::Negative,
+            _ => Sentiment::Neutral,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn classify(text: &str) -> Sentiment {
+        LexiconSentimentProvider.classify(text).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn counts_opinion_words() {
+        assert_eq!(
+            classify("Produto ótimo, recomendo!").await,
+            Sentiment::Positive
+        );
+        assert_eq!(
+            classify("Veio quebrado, péssimo").await,
+            Sentiment::Negative
+        );
+        assert_eq!(
+            classify("Bom, mas atrasado e com defeito").await,
+            Sentiment::Negative
+        );
+        assert_eq!(classify("Chegou na terça").await, Sentiment::Neutral);
+        assert_eq!(classify("").await, Sentiment::Neutral);
+    }
+
+    #[tokio::test]
+    async fn ignores_case_and_accents() {
+        assert_eq!(classify("PÉSSIMO").await, Sentiment::Negative);
+        assert_eq!(classify("otimo").await, Sentiment::Positive);
+        assert_eq!(classify("Decepção total").await, Sentiment::Negative);
+    }
+
+    #[tokio::test]
+    async fn negation_flips_the_next_opinion_word() {
+        assert_eq!(classify("Não recomendo").await, Sentiment::Negative);
+        assert_eq!(classify("Sem problemas").await, Sentiment::Positive);
+        assert_eq!(classify("nunca foi tão bom").await, Sentiment::Negative);
+    }
+
+    #[tokio::test]
+    async fn negation_fades_after_a_few_words() {
+        assert_eq!(
+            classify("não sei bem o que dizer, gostei").await,
+            Sentiment::Positive
+        );
+    }
+
+    #[tokio::test]
+    async fn negated_expectations_are_complaints() {
+        assert_eq!(classify("Ainda não chegou").await, Sentiment::Negative);
+        assert_eq!(
+            classify("o produto não funciona").await,
+            Sentiment::Negative
+        );
+        assert_eq!(classify("Recebi o pedido").await, Sentiment::Neutral);
+    }
+}
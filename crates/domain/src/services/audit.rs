@@ -0,0 +1,148 @@
+use chrono::Utc;
+use serde::Serialize;
+use serde_json::Value;
+use std::sync::Arc;
+use tracing::{error, instrument};
+
+use crate::error::AppResult;
+use crate::events::ChangeStream;
+use crate::models::{
+    AuditAction, AuditEntry, AuditSearchQuery, ChangeEvent, NewAuditEntry, PaginatedResponse,
+};
+use crate::repositories::AuditRepository;
+
+use super::audit_diff;
+
+/// The audit log, and the change stream fed from the same entries.
+#[derive(Clone)]
+pub struct AuditService {
+    repository: Arc<dyn AuditRepository>,
+    changes: ChangeStream,
+}
+
+impl AuditService {
+    pub fn new(repository: Arc<dyn AuditRepository>, changes: ChangeStream) -> Self {
+        Self {
+            repository,
+            changes,
+        }
+    }
+
+    /// Records a write operation. Failures are logged rather than returned so
+    /// that an audit outage never rolls back a write that already succeeded.
+    #[instrument(skip(self, before, after))]
+    pub async fn record<T: Serialize + Sync>(
+        &self,
+        entity_type: &'static str,
+        entity_id: &str,
+        action: AuditAction,
+        actor: &str,
+        before: Option<&T>,
+        after: Option<&T>,
+    ) {
+        self.record_event(
+            entity_type,
+            entity_id,
+            action,
+            actor,
+            audit_diff(before, after),
+        )
+        .await;
+    }
+
+    /// Records the creation of each `(entity_id, entity)` pair in one write, as `record`
+    /// would one by one.
+    #[instrument(skip(self, created))]
+    pub async fn record_created<'a, T: Serialize + 'a>(
+        &self,
+        entity_type: &'static str,
+        actor: &str,
+        created: impl IntoIterator<Item = (&'a str, &'a T)>,
+    ) {
+        let entries: Vec<NewAuditEntry> = created
+            .into_iter()
+            .map(|(entity_id, entity)| NewAuditEntry {
+                entity_type,
+                entity_id: entity_id.to_string(),
+                action: AuditAction::Create,
+                actor: actor.to_string(),
+                diff: audit_diff(None, Some(entity)),
+            })
+            .collect();
+        if entries.is_empty() {
+            return;
+        }
+
+        for entry in &entries {
+            self.announce(entry);
+        }
+
+        let count = entries.len();
+        if let Err(e) = self.repository.record_many(entries).await {
+            error!(
+                "Failed to record audit entries for {} {}: {:?}",
+                count, entity_type, e
+            );
+        }
+    }
+
+    /// Records an operation with a caller-built `diff` payload.
+    #[instrument(skip(self, diff))]
+    pub async fn record_event(
+        &self,
+        entity_type: &'static str,
+        entity_id: &str,
+        action: AuditAction,
+        actor: &str,
+        diff: Value,
+    ) {
+        let entry = NewAuditEntry {
+            entity_type,
+            entity_id: entity_id.to_string(),
+            action,
+            actor: actor.to_string(),
+            diff,
+        };
+        self.announce(&entry);
+
+        if let Err(e) = self.repository.record(entry).await {
+            error!(
+                "Failed to record audit entry for {} {}: {:?}",
+                entity_type, entity_id, e
+            );
+        }
+    }
+
+    /// Publishes an entry to the change stream without recording it, for entries a
+    /// repository already wrote in the transaction of the change they describe.
+    pub fn announce(&self, entry: &NewAuditEntry) {
+        self.changes.emit(ChangeEvent {
+            tenant_id: self.changes.tenant().clone(),
+            entity_type: entry.entity_type,
+            entity_id: entry.entity_id.clone(),
+            action: entry.action,
+            actor: entry.actor.clone(),
+            diff: entry.diff.clone(),
+            occurred_at: Utc::now(),
+        });
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_entries(
+        &self,
+        query: AuditSearchQuery,
+    ) -> AppResult<PaginatedResponse<AuditEntry>> {
+        let pagination = query.pagination();
+        let filter = query.filter();
+        let (_, _, page, page_size) = pagination.normalize();
+
+        let (entries, total_records) = self.repository.find_all(&filter, &pagination).await?;
+
+        Ok(PaginatedResponse::new(
+            entries,
+            total_records,
+            page,
+            page_size,
+        ))
+    }
+}
@@ -0,0 +1,143 @@
+use std::sync::Arc;
+use tracing::instrument;
+use validator::Validate;
+
+use crate::cache::LookupCache;
+use crate::error::{AppError, AppResult, map_db_error};
+use crate::models::{AuditAction, Category, CreateCategoryDto, DeleteReceipt, UpdateCategoryDto};
+use crate::repositories::CategoryRepository;
+
+use super::AuditService;
+
+#[derive(Clone)]
+pub struct CategoryService {
+    repository: Arc<dyn CategoryRepository>,
+    audit: AuditService,
+    lookups: LookupCache,
+}
+
+impl CategoryService {
+    pub fn new(
+        repository: Arc<dyn CategoryRepository>,
+        audit: AuditService,
+        lookups: LookupCache,
+    ) -> Self {
+        Self {
+            repository,
+            audit,
+            lookups,
+        }
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_category(&self, name: &str) -> AppResult<Category> {
+        self.lookups
+            .category(name, || async {
+                Ok(self.repository.find_by_name(name).await?)
+            })
+            .await?
+            .ok_or(AppError::NotFound)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn create_category(
+        &self,
+        dto: CreateCategoryDto,
+        actor: &str,
+    ) -> AppResult<Category> {
+        dto.validate()?;
+        let category = self
+            .repository
+            .create(dto)
+            .await
+            .map_err(|e| map_db_error(e, "Category"))?;
+
+        self.audit
+            .record(
+                "category",
+                &category.product_category_name,
+                AuditAction::Create,
+                actor,
+                None,
+                Some(&category),
+            )
+            .await;
+
+        Ok(category)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn update_category(
+        &self,
+        name: &str,
+        dto: UpdateCategoryDto,
+        actor: &str,
+    ) -> AppResult<Category> {
+        dto.validate()?;
+        let before = self
+            .repository
+            .find_by_name(name)
+            .await?
+            .ok_or(AppError::NotFound)?;
+
+        let category = self
+            .repository
+            .update(name, dto)
+            .await?
+            .ok_or(AppError::NotFound)?;
+        self.lookups.invalidate_category(name).await;
+
+        self.audit
+            .record(
+                "category",
+                name,
+                AuditAction::Update,
+                actor,
+                Some(&before),
+                Some(&category),
+            )
+            .await;
+
+        Ok(category)
+    }
+
+    /// Refuses to delete a category that products are still filed under.
+    #[instrument(skip(self))]
+    pub async fn delete_category(&self, name: &str, actor: &str) -> AppResult<DeleteReceipt> {
+        let before = self
+            .repository
+            .find_by_name(name)
+            .await?
+            .ok_or(AppError::NotFound)?;
+
+        let products = self.repository.count_products(name).await?;
+        if products > 0 {
+            return Err(AppError::DeleteRestricted(format!(
+                "Category {} cannot be deleted: it still has {} {}",
+                name,
+                products,
+                if products == 1 { "product" } else { "products" }
+            )));
+        }
+
+        let deleted_at = self
+            .repository
+            .delete(name)
+            .await?
+            .ok_or(AppError::NotFound)?;
+        self.lookups.invalidate_category(name).await;
+
+        self.audit
+            .record(
+                "category",
+                name,
+                AuditAction::Delete,
+                actor,
+                Some(&before),
+                None,
+            )
+            .await;
+
+        Ok(DeleteReceipt::new(name, deleted_at))
+    }
+}
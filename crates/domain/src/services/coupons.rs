@@ -0,0 +1,54 @@
+use std::sync::Arc;
+use tracing::instrument;
+use validator::Validate;
+
+use crate::error::{AppError, AppResult, map_db_error};
+use crate::models::{AuditAction, Coupon, CreateCouponDto};
+use crate::repositories::CouponRepository;
+
+use super::AuditService;
+
+/// Coupons: discount codes customers apply to their orders.
+#[derive(Clone)]
+pub struct CouponService {
+    repository: Arc<dyn CouponRepository>,
+    audit: AuditService,
+}
+
+impl CouponService {
+    pub fn new(repository: Arc<dyn CouponRepository>, audit: AuditService) -> Self {
+        Self { repository, audit }
+    }
+
+    #[instrument(skip(self))]
+    pub async fn create_coupon(&self, mut dto: CreateCouponDto, actor: &str) -> AppResult<Coupon> {
+        dto.code = dto.code.trim().to_uppercase();
+        dto.validate()?;
+        let coupon = self
+            .repository
+            .create(dto)
+            .await
+            .map_err(|e| map_db_error(e, "Coupon"))?;
+
+        self.audit
+            .record(
+                "coupon",
+                &coupon.code,
+                AuditAction::Create,
+                actor,
+                None,
+                Some(&coupon),
+            )
+            .await;
+
+        Ok(coupon)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_coupon(&self, code: &str) -> AppResult<Coupon> {
+        self.repository
+            .find_by_code(&code.trim().to_uppercase())
+            .await?
+            .ok_or(AppError::NotFound)
+    }
+}
@@ -0,0 +1,523 @@
+use serde_json::json;
+use std::sync::Arc;
+use tokio_stream::wrappers::ReceiverStream;
+use tracing::{info, instrument, warn};
+use validator::Validate;
+
+use crate::cities::{fold_city, tidy_city};
+use crate::config::{DeletePolicy, DeletePolicyConfig};
+use crate::error::{AppError, AppResult, map_db_error};
+use crate::ids::CustomerId;
+use crate::models::{
+    AuditAction, CityValuesQuery, CreateCustomerAddressDto, CreateCustomerDto, Customer,
+    CustomerAddress, CustomerLocationVersion, CustomerMerge, CustomerSearchQuery, DeleteReceipt,
+    FilterValue, MergeCustomersDto, NewAuditEntry, PaginatedResponse, SparseRow,
+    UpdateCustomerAddressDto, UpdateCustomerDto,
+};
+use crate::repositories::CustomerRepository;
+
+use super::{AuditService, create_each, export_rows};
+
+#[derive(Clone)]
+pub struct CustomerService {
+    repository: Arc<dyn CustomerRepository>,
+    audit: AuditService,
+    delete_policies: DeletePolicyConfig,
+}
+
+impl CustomerService {
+    pub fn new(
+        repository: Arc<dyn CustomerRepository>,
+        audit: AuditService,
+        delete_policies: DeletePolicyConfig,
+    ) -> Self {
+        Self {
+            repository,
+            audit,
+            delete_policies,
+        }
+    }
+
+    #[instrument(skip(self))]
+    pub async fn create_customer(
+        &self,
+        dto: CreateCustomerDto,
+        actor: &str,
+    ) -> AppResult<Customer> {
+        let (id, dto, canonical_city) = Self::prepare(dto)?;
+        let customer = self
+            .repository
+            .create(&id, dto, &canonical_city)
+            .await
+            .map_err(|e| map_db_error(e, "Customer"))?;
+
+        self.audit
+            .record(
+                "customer",
+                customer.customer_id.as_str(),
+                AuditAction::Create,
+                actor,
+                None,
+                Some(&customer),
+            )
+            .await;
+
+        Ok(customer)
+    }
+
+    /// Creates the customers in one transaction, as [`Self::create_customer`] would one by
+    /// one. Each gets its own result: a rejected row doesn't stop the others.
+    #[instrument(skip(self, dtos), fields(rows = dtos.len()))]
+    pub async fn create_customers(
+        &self,
+        dtos: Vec<CreateCustomerDto>,
+        actor: &str,
+    ) -> AppResult<Vec<AppResult<Customer>>> {
+        let customers = create_each("Customer", dtos, Self::prepare, |rows| {
+            self.repository.create_many(rows)
+        })
+        .await?;
+
+        self.audit
+            .record_created(
+                "customer",
+                actor,
+                customers
+                    .iter()
+                    .flatten()
+                    .map(|customer| (customer.customer_id.as_str(), customer)),
+            )
+            .await;
+
+        Ok(customers)
+    }
+
+    /// Normalizes and validates a new customer as `create_customer` does, without inserting it.
+    pub fn prepare(
+        mut dto: CreateCustomerDto,
+    ) -> AppResult<(CustomerId, CreateCustomerDto, String)> {
+        dto.customer_city = tidy_city(&dto.customer_city);
+        dto.validate()?;
+        let id = dto.customer_id.take().unwrap_or_else(CustomerId::generate);
+        let canonical_city = fold_city(&dto.customer_city);
+        Ok((id, dto, canonical_city))
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_customer_by_id(&self, id: &CustomerId) -> AppResult<Customer> {
+        match self.repository.find_by_id(id).await? {
+            Some(customer) => Ok(customer),
+            None => Err(AppError::NotFound),
+        }
+    }
+
+    /// Every live customer row of one person; 404 when there is none.
+    #[instrument(skip(self))]
+    pub async fn get_customers_by_unique_id(&self, unique_id: &str) -> AppResult<Vec<Customer>> {
+        let customers = self.repository.find_by_unique_id(unique_id).await?;
+        if customers.is_empty() {
+            return Err(AppError::NotFound);
+        }
+        Ok(customers)
+    }
+
+    #[instrument(skip(self, dto), fields(customer_id = %id))]
+    pub async fn update_customer(
+        &self,
+        id: &CustomerId,
+        mut dto: UpdateCustomerDto,
+        actor: &str,
+    ) -> AppResult<Customer> {
+        dto.customer_city = dto.customer_city.as_deref().map(tidy_city);
+        dto.validate()?;
+
+        if dto.customer_unique_id.is_none()
+            && dto.customer_zip_code_prefix.is_none()
+            && dto.customer_city.is_none()
+            && dto.customer_state.is_none()
+        {
+            return Err(AppError::NoChangesToUpdate);
+        }
+
+        let before = self
+            .repository
+            .find_by_id(id)
+            .await?
+            .ok_or(AppError::NotFound)?;
+
+        let canonical_city = dto.customer_city.as_deref().map(fold_city);
+        let customer = match self
+            .repository
+            .update(id, dto, canonical_city.as_deref())
+            .await?
+        {
+            Some(customer) => customer,
+            None => return Err(AppError::NotFound),
+        };
+
+        self.audit
+            .record(
+                "customer",
+                id.as_str(),
+                AuditAction::Update,
+                actor,
+                Some(&before),
+                Some(&customer),
+            )
+            .await;
+
+        Ok(customer)
+    }
+
+    #[instrument(skip(self), fields(customer_id = %id))]
+    pub async fn delete_customer(&self, id: &CustomerId, actor: &str) -> AppResult<DeleteReceipt> {
+        let mut before = self
+            .repository
+            .find_by_id(id)
+            .await?
+            .ok_or(AppError::NotFound)?;
+
+        let dependents = self.repository.count_dependents(id).await?;
+        let relations = [
+            (
+                dependents.orders,
+                "order",
+                "orders",
+                self.delete_policies.customer_orders,
+            ),
+            (
+                dependents.support_cases,
+                "support case",
+                "support cases",
+                self.delete_policies.customer_support_cases,
+            ),
+        ];
+
+        let blocking: Vec<String> = relations
+            .iter()
+            .filter(|(count, _, _, policy)| *count > 0 && *policy == DeletePolicy::Restrict)
+            .map(|(count, singular, plural, _)| {
+                format!("{} {}", count, if *count == 1 { singular } else { plural })
+            })
+            .collect();
+        if !blocking.is_empty() {
+            return Err(AppError::DeleteRestricted(format!(
+                "Customer {} cannot be deleted: it still has {}",
+                id,
+                blocking.join(" and ")
+            )));
+        }
+
+        // Anonymizing also redacts the customer's earlier audit diffs, so the delete entry
+        // must not carry the original snapshot either.
+        if relations
+            .iter()
+            .any(|(count, _, _, policy)| *count > 0 && *policy == DeletePolicy::DetachAnonymize)
+        {
+            before = self.anonymize_customer(id, actor).await?;
+        }
+
+        let deleted_at = self
+            .repository
+            .delete(id)
+            .await?
+            .ok_or(AppError::NotFound)?;
+
+        self.audit
+            .record(
+                "customer",
+                id.as_str(),
+                AuditAction::Delete,
+                actor,
+                Some(&before),
+                None,
+            )
+            .await;
+
+        Ok(DeleteReceipt::new(id.as_str(), deleted_at))
+    }
+
+    #[instrument(skip(self), fields(customer_id = %id))]
+    pub async fn restore_customer(&self, id: &CustomerId, actor: &str) -> AppResult<Customer> {
+        let customer = match self.repository.restore(id).await? {
+            Some(customer) => customer,
+            None => return Err(AppError::NotFound),
+        };
+
+        self.audit
+            .record(
+                "customer",
+                id.as_str(),
+                AuditAction::Restore,
+                actor,
+                None,
+                Some(&customer),
+            )
+            .await;
+
+        Ok(customer)
+    }
+
+    /// Irreversibly scrubs the customer's personal data (LGPD erasure). The audit entry only
+    /// lists which fields were scrubbed, never their previous values, and is written in the
+    /// same transaction: there is no erasure without its record.
+    #[instrument(skip(self), fields(customer_id = %id))]
+    pub async fn anonymize_customer(&self, id: &CustomerId, actor: &str) -> AppResult<Customer> {
+        let erasure = NewAuditEntry {
+            entity_type: "customer",
+            entity_id: id.to_string(),
+            action: AuditAction::Anonymize,
+            actor: actor.to_string(),
+            diff: json!({
+                "anonymized_fields": [
+                    "customer_unique_id",
+                    "customer_zip_code_prefix",
+                    "customer_city",
+                    "customer_state",
+                    "review_comment_title",
+                    "review_comment_message"
+                ]
+            }),
+        };
+        let customer = match self.repository.anonymize(id, &erasure).await? {
+            Some(customer) => customer,
+            None => return Err(AppError::NotFound),
+        };
+
+        self.audit.announce(&erasure);
+        Ok(customer)
+    }
+
+    /// Folds a duplicate identity into another customer: its orders, support cases,
+    /// addresses and location history move to the target and it is soft-deleted. Both
+    /// customers' audit logs record the merge in the same transaction.
+    #[instrument(skip(self))]
+    pub async fn merge_customers(
+        &self,
+        dto: MergeCustomersDto,
+        actor: &str,
+    ) -> AppResult<CustomerMerge> {
+        dto.validate()?;
+        let audit = [
+            NewAuditEntry {
+                entity_type: "customer",
+                entity_id: dto.source_customer_id.to_string(),
+                action: AuditAction::Merge,
+                actor: actor.to_string(),
+                diff: json!({ "merged_into": dto.target_customer_id }),
+            },
+            NewAuditEntry {
+                entity_type: "customer",
+                entity_id: dto.target_customer_id.to_string(),
+                action: AuditAction::Merge,
+                actor: actor.to_string(),
+                diff: json!({ "merged_from": dto.source_customer_id }),
+            },
+        ];
+        let merge = self
+            .repository
+            .merge(&dto.source_customer_id, &dto.target_customer_id, &audit)
+            .await?
+            .ok_or(AppError::NotFound)?;
+
+        for entry in &audit {
+            self.audit.announce(&merge.audit_entry(entry));
+        }
+        info!(
+            "Merged customer {} into {}",
+            merge.source_customer_id, merge.target_customer_id
+        );
+        Ok(merge)
+    }
+
+    /// Returns 404 for deleted customers, like [`Self::get_customer_by_id`].
+    #[instrument(skip(self))]
+    pub async fn get_customer_location_history(
+        &self,
+        id: &CustomerId,
+    ) -> AppResult<Vec<CustomerLocationVersion>> {
+        self.get_customer_by_id(id).await?;
+        Ok(self.repository.find_location_history(id).await?)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn create_address(
+        &self,
+        id: &CustomerId,
+        mut dto: CreateCustomerAddressDto,
+        actor: &str,
+    ) -> AppResult<CustomerAddress> {
+        dto.city = tidy_city(&dto.city);
+        dto.validate()?;
+        self.get_customer_by_id(id).await?;
+
+        let address = self
+            .repository
+            .create_address(id, dto)
+            .await
+            .map_err(|e| map_db_error(e, "Customer address"))?;
+
+        self.audit
+            .record(
+                "customer_address",
+                &address.address_id.to_string(),
+                AuditAction::Create,
+                actor,
+                None,
+                Some(&address),
+            )
+            .await;
+
+        Ok(address)
+    }
+
+    /// Returns 404 for deleted customers, like [`Self::get_customer_by_id`].
+    #[instrument(skip(self))]
+    pub async fn get_addresses(&self, id: &CustomerId) -> AppResult<Vec<CustomerAddress>> {
+        self.get_customer_by_id(id).await?;
+        Ok(self.repository.find_addresses(id).await?)
+    }
+
+    /// 404 unless the address is in this customer's book.
+    #[instrument(skip(self))]
+    pub async fn get_address(
+        &self,
+        id: &CustomerId,
+        address_id: i64,
+    ) -> AppResult<CustomerAddress> {
+        self.get_customer_by_id(id).await?;
+        self.repository
+            .find_address(id, address_id)
+            .await?
+            .ok_or(AppError::NotFound)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn update_address(
+        &self,
+        id: &CustomerId,
+        address_id: i64,
+        mut dto: UpdateCustomerAddressDto,
+        actor: &str,
+    ) -> AppResult<CustomerAddress> {
+        dto.city = dto.city.as_deref().map(tidy_city);
+        dto.validate()?;
+        if dto.label.is_none()
+            && dto.zip_code_prefix.is_none()
+            && dto.city.is_none()
+            && dto.state.is_none()
+            && dto.is_default.is_none()
+        {
+            return Err(AppError::NoChangesToUpdate);
+        }
+
+        let before = self.get_address(id, address_id).await?;
+        let address = self
+            .repository
+            .update_address(id, address_id, dto)
+            .await
+            .map_err(|e| map_db_error(e, "Customer address"))?
+            .ok_or(AppError::NotFound)?;
+
+        self.audit
+            .record(
+                "customer_address",
+                &address_id.to_string(),
+                AuditAction::Update,
+                actor,
+                Some(&before),
+                Some(&address),
+            )
+            .await;
+
+        Ok(address)
+    }
+
+    /// Orders already placed keep the zip code prefix they were given.
+    #[instrument(skip(self))]
+    pub async fn delete_address(
+        &self,
+        id: &CustomerId,
+        address_id: i64,
+        actor: &str,
+    ) -> AppResult<DeleteReceipt> {
+        let before = self.get_address(id, address_id).await?;
+        let deleted_at = self
+            .repository
+            .delete_address(id, address_id)
+            .await?
+            .ok_or(AppError::NotFound)?;
+
+        self.audit
+            .record(
+                "customer_address",
+                &address_id.to_string(),
+                AuditAction::Delete,
+                actor,
+                Some(&before),
+                None,
+            )
+            .await;
+
+        Ok(DeleteReceipt::new(address_id.to_string(), deleted_at))
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_customers(
+        &self,
+        query: CustomerSearchQuery,
+    ) -> AppResult<PaginatedResponse<Customer>> {
+        let pagination = query.pagination();
+        let filter = query.filter();
+
+        let (_, _, page, page_size) = pagination.normalize();
+
+        let (customers, total) = self
+            .repository
+            .find_all(&filter, &pagination, query.total_mode())
+            .await?;
+
+        Ok(PaginatedResponse::with_total(
+            customers, total, page, page_size,
+        ))
+    }
+
+    /// `get_customers` with each row trimmed to the selected fields.
+    #[instrument(skip(self))]
+    pub async fn get_customers_sparse(
+        &self,
+        query: CustomerSearchQuery,
+        fields: &[&'static str],
+    ) -> AppResult<PaginatedResponse<SparseRow>> {
+        let pagination = query.pagination();
+        let filter = query.filter();
+        let (_, _, page, page_size) = pagination.normalize();
+
+        let (customers, total) = self
+            .repository
+            .find_all_sparse(&filter, &pagination, fields, query.total_mode())
+            .await?;
+
+        Ok(PaginatedResponse::with_total(
+            customers, total, page, page_size,
+        ))
+    }
+
+    /// Streams every active customer, one row at a time.
+    #[instrument(skip(self))]
+    pub fn export_all(&self) -> ReceiverStream<sqlx::Result<Customer>> {
+        export_rows("Customer", self.repository.clone(), |repository| {
+            repository.stream_all()
+        })
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_state_values(&self) -> AppResult<Vec<FilterValue>> {
+        Ok(self.repository.count_by_state().await?)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_city_values(&self, query: CityValuesQuery) -> AppResult<Vec<FilterValue>> {
+        Ok(self.repository.count_by_city(query.state).await?)
+    }
+}
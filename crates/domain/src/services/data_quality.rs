@@ -0,0 +1,112 @@
+use chrono::Utc;
+use std::sync::Arc;
+use tracing::{instrument, warn};
+
+use crate::error::AppResult;
+use crate::models::{
+    DataQualityQuery, DataQualityReport, DataQualityRule, DataQualityRuleResult, FlaggedOrder,
+    FlaggedProduct, OrderIssue, PaginatedResponse, ProductIssue,
+};
+use crate::repositories::DataQualityRepository;
+
+/// Product and order rows with missing or impossible values, to guide dataset cleanup, and
+/// the configured rules checked by the `evaluate_data_quality` job.
+#[derive(Clone)]
+pub struct DataQualityService {
+    repository: Arc<dyn DataQualityRepository>,
+    rules: Arc<[DataQualityRule]>,
+}
+
+impl DataQualityService {
+    pub fn new(repository: Arc<dyn DataQualityRepository>, rules: Vec<DataQualityRule>) -> Self {
+        Self {
+            repository,
+            rules: rules.into(),
+        }
+    }
+
+    /// Evaluates every configured rule and stores the outcomes in place of the last run's. A
+    /// rule that can't be evaluated is stored with its error rather than stopping the others.
+    #[instrument(skip(self))]
+    pub async fn evaluate_rules(&self) -> AppResult<Vec<DataQualityRuleResult>> {
+        let mut results = Vec::with_capacity(self.rules.len());
+        for rule in self.rules.iter() {
+            let (checked_count, violation_count, error) =
+                match self.repository.evaluate_rule(rule).await {
+                    Ok((checked, violations)) => (Some(checked), Some(violations), None),
+                    Err(e) => {
+                        warn!(
+                            "Data quality rule {} could not be evaluated: {}",
+                            rule.name, e
+                        );
+                        (None, None, Some(e.to_string()))
+                    }
+                };
+            results.push(DataQualityRuleResult {
+                rule_name: rule.name.clone(),
+                table_name: rule.table.clone(),
+                condition: rule.condition(),
+                severity: rule.severity,
+                checked_count,
+                violation_count,
+                error,
+                evaluated_at: Utc::now().naive_utc(),
+            });
+        }
+        self.repository.replace_rule_results(&results).await?;
+        Ok(results)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_rule_results(&self) -> AppResult<Vec<DataQualityRuleResult>> {
+        Ok(self.repository.find_rule_results().await?)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_product_report(&self) -> AppResult<DataQualityReport<ProductIssue>> {
+        Ok(self.repository.product_report().await?)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_flagged_products(
+        &self,
+        query: DataQualityQuery<ProductIssue>,
+    ) -> AppResult<PaginatedResponse<FlaggedProduct>> {
+        let pagination = query.pagination();
+        let (_, _, page, page_size) = pagination.normalize();
+        let (products, total_count) = self
+            .repository
+            .find_flagged_products(query.issue, &pagination)
+            .await?;
+        Ok(PaginatedResponse::new(
+            products.into_iter().map(FlaggedProduct::from).collect(),
+            total_count,
+            page,
+            page_size,
+        ))
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_order_report(&self) -> AppResult<DataQualityReport<OrderIssue>> {
+        Ok(self.repository.order_report().await?)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_flagged_orders(
+        &self,
+        query: DataQualityQuery<OrderIssue>,
+    ) -> AppResult<PaginatedResponse<FlaggedOrder>> {
+        let pagination = query.pagination();
+        let (_, _, page, page_size) = pagination.normalize();
+        let (orders, total_count) = self
+            .repository
+            .find_flagged_orders(query.issue, &pagination)
+            .await?;
+        Ok(PaginatedResponse::new(
+            orders.into_iter().map(FlaggedOrder::with_issues).collect(),
+            total_count,
+            page,
+            page_size,
+        ))
+    }
+}
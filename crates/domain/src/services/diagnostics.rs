@@ -0,0 +1,304 @@
+use chrono::{Duration, Utc};
+use std::sync::Arc;
+use tracing::instrument;
+
+use crate::cache::ResponseCache;
+use crate::error::AppResult;
+use crate::models::{
+    AdminStats, AdminStatsQuery, DatabaseStats, DiagnosticCheck, DiagnosticsReport, HealthStatus,
+};
+use crate::repositories::{DiagnosticsRepository, OutboxRepository};
+use crate::runtime::{JobRuns, Readiness, SELLER_BADGES_JOB};
+
+const DB_LATENCY_YELLOW: std::time::Duration = std::time::Duration::from_millis(100);
+const DB_LATENCY_RED: std::time::Duration = std::time::Duration::from_secs(1);
+const REPLICA_LAG_YELLOW_SECS: f64 = 5.0;
+const REPLICA_LAG_RED_SECS: f64 = 60.0;
+const OUTBOX_AGE_YELLOW_SECS: f64 = 60.0;
+const OUTBOX_AGE_RED_SECS: f64 = 15.0 * 60.0;
+
+/// Builds the on-call diagnostics report. Each check is independent; a failing one
+/// turns red instead of failing the whole report.
+#[derive(Clone)]
+pub struct DiagnosticsService {
+    repository: Arc<dyn DiagnosticsRepository>,
+    outbox: Arc<dyn OutboxRepository>,
+    readiness: Readiness,
+    job_runs: JobRuns,
+    cache: ResponseCache,
+    seller_badges_refresh_minutes: u64,
+    outbox_relay_enabled: bool,
+    /// When the server started, for its uptime.
+    started_at: chrono::NaiveDateTime,
+}
+
+impl DiagnosticsService {
+    pub fn new(
+        repository: Arc<dyn DiagnosticsRepository>,
+        outbox: Arc<dyn OutboxRepository>,
+        readiness: Readiness,
+        job_runs: JobRuns,
+        cache: ResponseCache,
+        seller_badges_refresh_minutes: u64,
+        outbox_relay_enabled: bool,
+    ) -> Self {
+        Self {
+            repository,
+            outbox,
+            readiness,
+            job_runs,
+            cache,
+            seller_badges_refresh_minutes,
+            outbox_relay_enabled,
+            started_at: Utc::now().naive_utc(),
+        }
+    }
+
+    /// Row counts, size, pool use and schema version of the database, and the server's
+    /// uptime.
+    #[instrument(skip(self))]
+    pub async fn get_stats(&self, query: AdminStatsQuery) -> AppResult<AdminStats> {
+        let started = std::time::Instant::now();
+        self.repository.ping().await?;
+        let latency_ms = started.elapsed().as_millis();
+
+        let (tables, row_counts_estimated) = self.repository.table_stats(query.exact).await?;
+        let database = DatabaseStats {
+            latency_ms,
+            size_bytes: self.repository.database_size_bytes().await?,
+            last_migration: self.repository.last_migration().await?,
+            pool: self.repository.pool_stats(),
+            row_counts_estimated,
+            tables,
+        };
+
+        let now = Utc::now().naive_utc();
+        Ok(AdminStats {
+            generated_at: now,
+            started_at: self.started_at,
+            uptime_seconds: (now - self.started_at).num_seconds(),
+            database,
+        })
+    }
+
+    #[instrument(skip(self))]
+    pub async fn run_checks(&self) -> DiagnosticsReport {
+        let checks = vec![
+            self.check_database_latency().await,
+            self.check_replica_lag().await,
+            self.check_warmup(),
+            self.check_cache().await,
+            not_configured("blob_storage"),
+            self.check_outbox().await,
+            self.check_scheduled_job(SELLER_BADGES_JOB, self.seller_badges_refresh_minutes),
+        ];
+
+        DiagnosticsReport {
+            status: checks
+                .iter()
+                .map(|check| check.status)
+                .max()
+                .unwrap_or(HealthStatus::Skipped),
+            generated_at: Utc::now().naive_utc(),
+            checks,
+        }
+    }
+
+    async fn check_database_latency(&self) -> DiagnosticCheck {
+        let started = std::time::Instant::now();
+        let result = self.repository.ping().await;
+        let elapsed = started.elapsed();
+
+        let (status, detail) = match result {
+            Err(e) => (HealthStatus::Red, format!("Query failed: {}", e)),
+            Ok(()) if elapsed >= DB_LATENCY_RED => (
+                HealthStatus::Red,
+                format!("Round trip took {} ms", elapsed.as_millis()),
+            ),
+            Ok(()) if elapsed >= DB_LATENCY_YELLOW => (
+                HealthStatus::Yellow,
+                format!("Round trip took {} ms", elapsed.as_millis()),
+            ),
+            Ok(()) => (
+                HealthStatus::Green,
+                format!("Round trip took {} ms", elapsed.as_millis()),
+            ),
+        };
+
+        DiagnosticCheck {
+            name: "database_latency",
+            status,
+            detail,
+            duration_ms: elapsed.as_millis(),
+        }
+    }
+
+    async fn check_replica_lag(&self) -> DiagnosticCheck {
+        let started = std::time::Instant::now();
+        let (status, detail) = match self.repository.replica_lag_seconds().await {
+            Err(e) => (HealthStatus::Red, format!("Query failed: {}", e)),
+            Ok(None) => (
+                HealthStatus::Skipped,
+                "No replication configured".to_string(),
+            ),
+            Ok(Some(lag)) => {
+                let status = if lag >= REPLICA_LAG_RED_SECS {
+                    HealthStatus::Red
+                } else if lag >= REPLICA_LAG_YELLOW_SECS {
+                    HealthStatus::Yellow
+                } else {
+                    HealthStatus::Green
+                };
+                (status, format!("Replication lag is {:.1}s", lag))
+            }
+        };
+
+        DiagnosticCheck {
+            name: "replica_lag",
+            status,
+            detail,
+            duration_ms: started.elapsed().as_millis(),
+        }
+    }
+
+    /// Yellow rather than red when Redis is down: reads fall back to the database.
+    async fn check_cache(&self) -> DiagnosticCheck {
+        let started = std::time::Instant::now();
+        let (status, detail) = match self.cache.ping().await {
+            None => return not_configured("cache"),
+            Some(Ok(())) => (
+                HealthStatus::Green,
+                format!("Round trip took {} ms", started.elapsed().as_millis()),
+            ),
+            Some(Err(e)) => (
+                HealthStatus::Yellow,
+                format!("Ping failed, reads bypass the cache: {}", e),
+            ),
+        };
+
+        DiagnosticCheck {
+            name: "cache",
+            status,
+            detail,
+            duration_ms: started.elapsed().as_millis(),
+        }
+    }
+
+    /// Events pile up while the broker is unreachable: each failed event holds back the rest.
+    async fn check_outbox(&self) -> DiagnosticCheck {
+        let started = std::time::Instant::now();
+        let (status, detail) = if !self.outbox_relay_enabled {
+            (HealthStatus::Skipped, "Relay is disabled".to_string())
+        } else {
+            match self.outbox.backlog().await {
+                Err(e) => (HealthStatus::Red, format!("Query failed: {}", e)),
+                Ok(backlog) => match backlog.oldest_age_seconds {
+                    None => (HealthStatus::Green, "No unpublished events".to_string()),
+                    Some(age) => {
+                        let status = if age >= OUTBOX_AGE_RED_SECS {
+                            HealthStatus::Red
+                        } else if age >= OUTBOX_AGE_YELLOW_SECS {
+                            HealthStatus::Yellow
+                        } else {
+                            HealthStatus::Green
+                        };
+                        (
+                            status,
+                            format!(
+                                "{} unpublished events, the oldest waiting {:.0}s",
+                                backlog.pending, age
+                            ),
+                        )
+                    }
+                },
+            }
+        };
+
+        DiagnosticCheck {
+            name: "outbox",
+            status,
+            detail,
+            duration_ms: started.elapsed().as_millis(),
+        }
+    }
+
+    fn check_warmup(&self) -> DiagnosticCheck {
+        let (status, detail) = if self.readiness.is_ready() {
+            (HealthStatus::Green, "Warm-up finished".to_string())
+        } else {
+            (
+                HealthStatus::Yellow,
+                "Warm-up has not finished; /health/ready reports 503".to_string(),
+            )
+        };
+
+        DiagnosticCheck {
+            name: "warmup",
+            status,
+            detail,
+            duration_ms: 0,
+        }
+    }
+
+    /// Red when the job hasn't succeeded within two intervals, yellow when its latest
+    /// run failed or it hasn't completed a first run yet.
+    fn check_scheduled_job(&self, job: &'static str, interval_minutes: u64) -> DiagnosticCheck {
+        let check = |status, detail: String| DiagnosticCheck {
+            name: job,
+            status,
+            detail,
+            duration_ms: 0,
+        };
+
+        if interval_minutes == 0 {
+            return check(HealthStatus::Skipped, "Job is disabled".to_string());
+        }
+
+        let Some(run) = self.job_runs.get(job) else {
+            return check(HealthStatus::Yellow, "No run recorded yet".to_string());
+        };
+
+        let Some(last_success) = run.last_success else {
+            return check(
+                HealthStatus::Red,
+                format!(
+                    "Never succeeded; last error: {}",
+                    run.last_error.unwrap_or_default()
+                ),
+            );
+        };
+
+        let age = Utc::now() - last_success;
+        let failed_since = run.last_failure.is_some_and(|failed| failed > last_success);
+        let status = if age > Duration::minutes((interval_minutes * 2) as i64) {
+            HealthStatus::Red
+        } else if failed_since {
+            HealthStatus::Yellow
+        } else {
+            HealthStatus::Green
+        };
+
+        let mut detail = format!(
+            "Last success at {} ({} minutes ago)",
+            last_success.format("%Y-%m-%d %H:%M:%S UTC"),
+            age.num_minutes()
+        );
+        if failed_since {
+            detail.push_str(&format!(
+                "; latest run failed: {}",
+                run.last_error.unwrap_or_default()
+            ));
+        }
+
+        check(status, detail)
+    }
+}
+
+fn not_configured(name: &'static str) -> DiagnosticCheck {
+    DiagnosticCheck {
+        name,
+        status: HealthStatus::Skipped,
+        detail: "Not configured in this deployment".to_string(),
+        duration_ms: 0,
+    }
+}
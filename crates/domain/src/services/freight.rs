@@ -0,0 +1,144 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::instrument;
+use validator::Validate;
+
+use crate::cache::LookupCache;
+use crate::carriers::{billable_weight_g, cep_for_prefix};
+use crate::config::FreightConfig;
+use crate::error::{AppError, AppResult};
+use crate::geo::haversine_km;
+use crate::models::{FreightEstimate, FreightEstimateDto, Parcel, ZipLocation};
+use crate::money::Money;
+use crate::repositories::{GeolocationRepository, ProductRepository};
+
+/// Freight estimates from the distance between two CEP prefixes and a configurable rate
+/// table, without asking a carrier.
+#[derive(Clone)]
+pub struct FreightService {
+    geolocation: Arc<dyn GeolocationRepository>,
+    products: Arc<dyn ProductRepository>,
+    lookups: LookupCache,
+    config: FreightConfig,
+}
+
+impl FreightService {
+    pub fn new(
+        geolocation: Arc<dyn GeolocationRepository>,
+        products: Arc<dyn ProductRepository>,
+        lookups: LookupCache,
+        config: FreightConfig,
+    ) -> Self {
+        Self {
+            geolocation,
+            products,
+            lookups,
+            config,
+        }
+    }
+
+    /// Prices shipping the product between the prefixes: the haversine distance between
+    /// their coordinates picks the rate table band, which is charged per started kilogram
+    /// of billable weight. Prefixes missing from the geolocation table are rejected.
+    #[instrument(skip(self))]
+    pub async fn estimate(&self, dto: FreightEstimateDto) -> AppResult<FreightEstimate> {
+        dto.validate()?;
+        let product = self
+            .products
+            .find_by_id(&dto.product_id)
+            .await?
+            .ok_or(AppError::NotFound)?;
+
+        let locations: HashMap<String, ZipLocation> = self
+            .lookups
+            .locations(
+                &[
+                    dto.origin_zip_code_prefix.clone(),
+                    dto.destination_zip_code_prefix.clone(),
+                ],
+                |prefixes| async move { Ok(self.geolocation.find_by_prefixes(&prefixes).await?) },
+            )
+            .await?
+            .into_iter()
+            .map(|location| (location.geolocation_zip_code_prefix.clone(), location))
+            .collect();
+        let mut errors = validator::ValidationErrors::new();
+        for (field, prefix) in [
+            ("origin_zip_code_prefix", &dto.origin_zip_code_prefix),
+            (
+                "destination_zip_code_prefix",
+                &dto.destination_zip_code_prefix,
+            ),
+        ] {
+            if !locations.contains_key(prefix) {
+                errors.add(
+                    field,
+                    validator::ValidationError::new("unknown_zip_code_prefix")
+                        .with_message(format!("no coordinates for CEP prefix {}", prefix).into()),
+                );
+            }
+        }
+        if !errors.is_empty() {
+            return Err(AppError::ValidationError(errors));
+        }
+        let origin = &locations[&dto.origin_zip_code_prefix];
+        let destination = &locations[&dto.destination_zip_code_prefix];
+
+        let distance_km = haversine_km(
+            (origin.geolocation_lat, origin.geolocation_lng),
+            (destination.geolocation_lat, destination.geolocation_lng),
+        );
+        let billable_weight_g = billable_weight_g(&Parcel {
+            origin_cep: cep_for_prefix(&dto.origin_zip_code_prefix),
+            destination_cep: cep_for_prefix(&dto.destination_zip_code_prefix),
+            weight_g: product.product_weight_g,
+            length_cm: product.product_length_cm,
+            height_cm: product.product_height_cm,
+            width_cm: product.product_width_cm,
+        });
+        let (rate, freight_value) = self
+            .config
+            .freight_for(distance_km, billable_weight_g)
+            .ok_or_else(|| {
+                AppError::ConfigError(format!(
+                    "FREIGHT_RATE_TABLE has no band for {:.1} km",
+                    distance_km
+                ))
+            })?;
+
+        Ok(FreightEstimate {
+            product_id: product.product_id,
+            origin_zip_code_prefix: dto.origin_zip_code_prefix,
+            destination_zip_code_prefix: dto.destination_zip_code_prefix,
+            distance_km: (distance_km * 10.0).round() / 10.0,
+            billable_weight_g,
+            rate_max_km: rate.max_km,
+            freight_value: freight_value.into(),
+            currency: Money::CURRENCY,
+        })
+    }
+
+    /// Stores the locations, replacing those known for their prefixes. Returns how many were
+    /// written.
+    pub async fn save_locations(&self, locations: &[ZipLocation]) -> AppResult<u64> {
+        let written = self.geolocation.upsert_many(locations).await?;
+        self.lookups.invalidate_locations();
+        Ok(written)
+    }
+
+    /// Caches as many centroids as the lookup cache holds, ahead of the first estimates.
+    /// Returns how many.
+    pub async fn prime_locations(&self) -> AppResult<usize> {
+        let limit = self.lookups.capacity();
+        if limit == 0 {
+            return Ok(0);
+        }
+        let locations = self
+            .geolocation
+            .find_all(i64::try_from(limit).unwrap_or(i64::MAX))
+            .await?;
+        let primed = locations.len();
+        self.lookups.prime_locations(locations).await;
+        Ok(primed)
+    }
+}
@@ -0,0 +1,249 @@
+use serde_json::json;
+use std::sync::Arc;
+use tracing::{error, instrument};
+use validator::Validate;
+
+use crate::error::{AppError, AppResult, map_db_error, map_stock_error};
+use crate::ids::{ProductId, SellerId};
+use crate::models::{
+    AdjustStockDto, AuditAction, CreateStockLocationDto, LocationStock, ProductStock, SetStockDto,
+    StockAllocation, StockLocation,
+};
+use crate::repositories::{InventoryRepository, ProductRepository};
+
+use super::AuditService;
+
+#[derive(Clone)]
+pub struct InventoryService {
+    repository: Arc<dyn InventoryRepository>,
+    products: Arc<dyn ProductRepository>,
+    audit: AuditService,
+}
+
+impl InventoryService {
+    pub fn new(
+        repository: Arc<dyn InventoryRepository>,
+        products: Arc<dyn ProductRepository>,
+        audit: AuditService,
+    ) -> Self {
+        Self {
+            repository,
+            products,
+            audit,
+        }
+    }
+
+    #[instrument(skip(self))]
+    pub async fn create_location(
+        &self,
+        seller_id: &SellerId,
+        dto: CreateStockLocationDto,
+        actor: &str,
+    ) -> AppResult<StockLocation> {
+        dto.validate()?;
+        let location = self
+            .repository
+            .create_location(seller_id, dto)
+            .await
+            .map_err(|e| map_db_error(e, "Stock location"))?;
+
+        self.audit
+            .record(
+                "stock_location",
+                &location.location_id.to_string(),
+                AuditAction::Create,
+                actor,
+                None,
+                Some(&location),
+            )
+            .await;
+
+        Ok(location)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_locations_by_seller(
+        &self,
+        seller_id: &SellerId,
+    ) -> AppResult<Vec<StockLocation>> {
+        Ok(self.repository.find_locations_by_seller(seller_id).await?)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_location_stock(&self, location_id: i64) -> AppResult<Vec<LocationStock>> {
+        self.ensure_location_exists(location_id).await?;
+        Ok(self.repository.find_stock_by_location(location_id).await?)
+    }
+
+    /// A product's stock at every location tracking it, and their sum.
+    #[instrument(skip(self))]
+    pub async fn get_product_stock(&self, product_id: &ProductId) -> AppResult<ProductStock> {
+        if self.products.find_by_id(product_id).await?.is_none() {
+            return Err(AppError::NotFound);
+        }
+        let locations = self.repository.find_stock_by_product(product_id).await?;
+        Ok(ProductStock {
+            product_id: product_id.clone(),
+            total_quantity: locations.iter().map(|l| i64::from(l.quantity)).sum(),
+            locations,
+        })
+    }
+
+    #[instrument(skip(self))]
+    pub async fn set_stock(
+        &self,
+        location_id: i64,
+        product_id: &ProductId,
+        dto: SetStockDto,
+        actor: &str,
+    ) -> AppResult<LocationStock> {
+        dto.validate()?;
+        self.ensure_location_exists(location_id).await?;
+        let stock = self
+            .repository
+            .set_stock(location_id, product_id, dto.quantity)
+            .await?;
+
+        self.audit
+            .record(
+                "location_stock",
+                &format!("{}:{}", location_id, product_id),
+                AuditAction::Update,
+                actor,
+                None,
+                Some(&stock),
+            )
+            .await;
+
+        Ok(stock)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn adjust_stock(
+        &self,
+        location_id: i64,
+        product_id: &ProductId,
+        dto: AdjustStockDto,
+        actor: &str,
+    ) -> AppResult<LocationStock> {
+        dto.validate()?;
+        let stock = self
+            .repository
+            .adjust_stock(location_id, product_id, dto.delta)
+            .await
+            .map_err(|e| map_stock_error(e, product_id.as_str()))?
+            .ok_or(AppError::NotFound)?;
+
+        self.audit
+            .record_event(
+                "location_stock",
+                &format!("{}:{}", location_id, product_id),
+                AuditAction::Update,
+                actor,
+                json!({ "delta": dto.delta, "quantity": stock.quantity }),
+            )
+            .await;
+
+        Ok(stock)
+    }
+
+    /// Reserves stock at the seller's location closest to the destination CEP prefix.
+    /// Products the seller does not track per location are not allocated (`Ok(None)`).
+    #[instrument(skip(self))]
+    pub async fn allocate(
+        &self,
+        seller_id: &SellerId,
+        product_id: &ProductId,
+        quantity: i32,
+        destination_zip_code_prefix: &str,
+        actor: &str,
+    ) -> AppResult<Option<StockAllocation>> {
+        if !self.repository.is_tracked(seller_id, product_id).await? {
+            return Ok(None);
+        }
+
+        let allocation = self
+            .repository
+            .allocate(seller_id, product_id, quantity, destination_zip_code_prefix)
+            .await?
+            .ok_or_else(|| AppError::InsufficientStock(product_id.to_string()))?;
+        self.record_allocation(&allocation, quantity, actor).await;
+
+        Ok(Some(allocation))
+    }
+
+    /// Audits `quantity` units taken by an allocation, including one a repository made in the
+    /// same transaction as the write that needed it.
+    pub async fn record_allocation(
+        &self,
+        allocation: &StockAllocation,
+        quantity: i32,
+        actor: &str,
+    ) {
+        self.audit
+            .record_event(
+                "location_stock",
+                &format!("{}:{}", allocation.location_id, allocation.product_id),
+                AuditAction::Update,
+                actor,
+                json!({ "delta": -quantity, "quantity": allocation.quantity }),
+            )
+            .await;
+    }
+
+    /// Puts units back at the seller's location closest to `origin_zip_code_prefix`, e.g. when an
+    /// order item is swapped for another product. Untracked products are ignored.
+    pub async fn restock(
+        &self,
+        seller_id: &SellerId,
+        product_id: &ProductId,
+        quantity: i32,
+        origin_zip_code_prefix: &str,
+        actor: &str,
+    ) {
+        match self
+            .repository
+            .restock(seller_id, product_id, quantity, origin_zip_code_prefix)
+            .await
+        {
+            Ok(Some(stock)) => {
+                self.audit
+                    .record_event(
+                        "location_stock",
+                        &format!("{}:{}", stock.location_id, product_id),
+                        AuditAction::Update,
+                        actor,
+                        json!({ "delta": quantity, "quantity": stock.quantity }),
+                    )
+                    .await;
+            }
+            Ok(None) => {}
+            Err(e) => error!(
+                "Failed to restock product {} for seller {}: {:?}",
+                product_id, seller_id, e
+            ),
+        }
+    }
+
+    /// Returns an allocated unit to its location. Failures are logged rather than returned
+    /// because the caller is already unwinding from another error.
+    pub async fn release(&self, allocation: &StockAllocation, actor: &str) {
+        let dto = AdjustStockDto { delta: 1 };
+        if let Err(e) = self
+            .adjust_stock(allocation.location_id, &allocation.product_id, dto, actor)
+            .await
+        {
+            error!(
+                "Failed to release stock at location {} for product {}: {:?}",
+                allocation.location_id, allocation.product_id, e
+            );
+        }
+    }
+
+    async fn ensure_location_exists(&self, location_id: i64) -> AppResult<()> {
+        match self.repository.find_location_by_id(location_id).await? {
+            Some(_) => Ok(()),
+            None => Err(AppError::NotFound),
+        }
+    }
+}
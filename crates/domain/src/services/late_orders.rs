@@ -0,0 +1,39 @@
+use std::sync::Arc;
+use tracing::{info, instrument};
+
+use crate::error::AppResult;
+use crate::models::{LateOrder, PaginatedResponse, PaginationParams};
+use crate::repositories::LateOrderRepository;
+
+/// Orders past their estimated delivery date, flagged so ops can chase the carrier.
+#[derive(Clone)]
+pub struct LateOrderService {
+    repository: Arc<dyn LateOrderRepository>,
+}
+
+impl LateOrderService {
+    pub fn new(repository: Arc<dyn LateOrderRepository>) -> Self {
+        Self { repository }
+    }
+
+    /// Flags the orders that became late within the last `max_age_days` days; older ones,
+    /// like most of the imported dataset, are left alone.
+    #[instrument(skip(self))]
+    pub async fn flag_late_orders(&self, max_age_days: i64) -> AppResult<u64> {
+        let flagged = self.repository.flag_late_orders(max_age_days).await?;
+        if flagged > 0 {
+            info!("Flagged {} late order(s)", flagged);
+        }
+        Ok(flagged)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_late_orders(
+        &self,
+        pagination: PaginationParams,
+    ) -> AppResult<PaginatedResponse<LateOrder>> {
+        let (_, _, page, page_size) = pagination.normalize();
+        let (orders, total_count) = self.repository.find_late_orders(&pagination).await?;
+        Ok(PaginatedResponse::new(orders, total_count, page, page_size))
+    }
+}
@@ -0,0 +1,413 @@
+use chrono::{NaiveDate, NaiveTime, Utc};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tracing::{error, info, instrument, warn};
+use validator::Validate;
+
+use crate::cache::{LookupCache, ResponseCache};
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    ArchiveOrdersQuery, ArchivedOrders, AuditAction, CacheFlush, JobStatus, MaintenanceJob,
+    MaintenanceStep, MaintenanceStepReport, MaintenanceTask, SetReadOnlyDto,
+};
+use crate::repositories::MaintenanceRepository;
+use crate::runtime::{ReadOnlyMode, ReadOnlyStatus};
+
+use super::{AuditService, SimilarityService};
+
+/// Embeddings computed per batch when backfilling recommendations.
+pub const EMBEDDING_REFRESH_BATCH_SIZE: i64 = 500;
+
+/// Orders moved to the archive per transaction.
+pub const ORDER_ARCHIVE_BATCH_SIZE: i64 = 1000;
+
+/// Finished maintenance jobs kept for status lookups.
+const MAINTENANCE_JOB_HISTORY: usize = 20;
+
+#[derive(Default)]
+struct MaintenanceJobs {
+    next_id: u64,
+    running: Option<u64>,
+    jobs: HashMap<u64, MaintenanceJob>,
+}
+
+enum StepOutcome {
+    Completed(String),
+    Skipped(String),
+}
+
+/// Runs the post-import refresh steps in dependency order as a background job.
+#[derive(Clone)]
+pub struct MaintenanceService {
+    repository: Arc<dyn MaintenanceRepository>,
+    similarity: SimilarityService,
+    audit: AuditService,
+    cache: ResponseCache,
+    lookups: LookupCache,
+    jobs: Arc<Mutex<MaintenanceJobs>>,
+    read_only: ReadOnlyMode,
+}
+
+impl MaintenanceService {
+    pub fn new(
+        repository: Arc<dyn MaintenanceRepository>,
+        similarity: SimilarityService,
+        audit: AuditService,
+        cache: ResponseCache,
+        lookups: LookupCache,
+        read_only: ReadOnlyMode,
+    ) -> Self {
+        Self {
+            repository,
+            similarity,
+            audit,
+            cache,
+            lookups,
+            jobs: Arc::default(),
+            read_only,
+        }
+    }
+
+    pub fn read_only_status(&self) -> ReadOnlyStatus {
+        self.read_only.status()
+    }
+
+    /// Switches read-only mode on or off for this instance.
+    #[instrument(skip(self))]
+    pub async fn set_read_only(
+        &self,
+        dto: SetReadOnlyDto,
+        actor: &str,
+    ) -> AppResult<ReadOnlyStatus> {
+        dto.validate()?;
+        let status = self.read_only.set(dto.enabled, dto.reason, actor);
+        if status.enabled {
+            warn!(
+                "Read-only mode switched on by {}: {}",
+                actor,
+                status.reason.as_deref().unwrap_or("no reason given")
+            );
+        } else {
+            info!("Read-only mode switched off by {}", actor);
+        }
+
+        self.audit
+            .record_event(
+                "read_only_mode",
+                "service",
+                AuditAction::Update,
+                actor,
+                json!({ "enabled": status.enabled, "reason": status.reason }),
+            )
+            .await;
+        Ok(status)
+    }
+
+    /// Empties the in-process lookups and the response cache, e.g. after editing data
+    /// outside the API.
+    #[instrument(skip(self))]
+    pub async fn flush_caches(&self, actor: &str) -> AppResult<CacheFlush> {
+        let flushed = self.flush().await?;
+        self.audit
+            .record_event(
+                "cache",
+                "all",
+                AuditAction::Delete,
+                actor,
+                json!({
+                    "lookup_entries": flushed.lookup_entries,
+                    "response_entries": flushed.response_entries,
+                }),
+            )
+            .await;
+        Ok(flushed)
+    }
+
+    async fn flush(&self) -> AppResult<CacheFlush> {
+        let lookup_entries = self.lookups.flush().await;
+        let response_entries = self
+            .cache
+            .flush()
+            .await
+            .transpose()
+            .map_err(AppError::CacheError)?;
+        Ok(CacheFlush {
+            lookup_entries,
+            response_entries,
+        })
+    }
+
+    /// Starts a refresh-all job, or fails if a maintenance job is still running.
+    #[instrument(skip(self))]
+    pub async fn start_refresh_all(&self, actor: &str) -> AppResult<MaintenanceJob> {
+        self.start(MaintenanceTask::RefreshAll, actor).await
+    }
+
+    /// Starts moving closed orders purchased before `query.before` to the archive tables, or
+    /// fails if a maintenance job is still running.
+    #[instrument(skip(self))]
+    pub async fn start_archive_orders(
+        &self,
+        query: ArchiveOrdersQuery,
+        actor: &str,
+    ) -> AppResult<MaintenanceJob> {
+        query.validate()?;
+        self.start(
+            MaintenanceTask::ArchiveOrders {
+                before: query.before,
+            },
+            actor,
+        )
+        .await
+    }
+
+    async fn start(&self, task: MaintenanceTask, actor: &str) -> AppResult<MaintenanceJob> {
+        let job = {
+            let mut jobs = self.jobs.lock().expect("maintenance job registry poisoned");
+            if let Some(running) = jobs.running {
+                return Err(AppError::JobAlreadyRunning(format!(
+                    "Maintenance job {} is still running",
+                    running
+                )));
+            }
+
+            jobs.next_id += 1;
+            let job = MaintenanceJob {
+                job_id: jobs.next_id,
+                task,
+                status: JobStatus::Running,
+                requested_by: actor.to_string(),
+                started_at: Utc::now().naive_utc(),
+                finished_at: None,
+                steps: task
+                    .steps()
+                    .iter()
+                    .map(|step| MaintenanceStepReport {
+                        step: *step,
+                        depends_on: step.depends_on(),
+                        status: JobStatus::Pending,
+                        detail: None,
+                        started_at: None,
+                        finished_at: None,
+                    })
+                    .collect(),
+            };
+
+            jobs.running = Some(job.job_id);
+            if jobs.jobs.len() >= MAINTENANCE_JOB_HISTORY
+                && let Some(oldest) = jobs.jobs.keys().min().copied()
+            {
+                jobs.jobs.remove(&oldest);
+            }
+            jobs.jobs.insert(job.job_id, job.clone());
+            job
+        };
+
+        self.audit
+            .record_event(
+                "maintenance_job",
+                &job.job_id.to_string(),
+                AuditAction::Create,
+                actor,
+                json!(task),
+            )
+            .await;
+
+        let service = self.clone();
+        tokio::spawn(async move { service.run_job(job.job_id, task).await });
+
+        Ok(job)
+    }
+
+    /// Refreshes every materialized view the analytics read from, returning how many there
+    /// were.
+    #[instrument(skip(self))]
+    pub async fn refresh_materialized_views(&self) -> AppResult<usize> {
+        let views = self.repository.find_materialized_views().await?;
+        for view in &views {
+            self.repository.refresh_materialized_view(view).await?;
+        }
+        Ok(views.len())
+    }
+
+    pub fn get_job(&self, job_id: u64) -> AppResult<MaintenanceJob> {
+        let jobs = self.jobs.lock().expect("maintenance job registry poisoned");
+        jobs.jobs.get(&job_id).cloned().ok_or(AppError::NotFound)
+    }
+
+    async fn run_job(&self, job_id: u64, task: MaintenanceTask) {
+        info!("Maintenance job {} started", job_id);
+        let mut blocked: Vec<MaintenanceStep> = Vec::new();
+
+        for &step in task.steps() {
+            if let Some(dependency) = step.depends_on().iter().find(|dep| blocked.contains(dep)) {
+                blocked.push(step);
+                self.update_step(
+                    job_id,
+                    step,
+                    JobStatus::Skipped,
+                    Some(format!("dependency {:?} did not complete", dependency)),
+                );
+                continue;
+            }
+
+            self.update_step(job_id, step, JobStatus::Running, None);
+            match self.run_step(step, task).await {
+                Ok(StepOutcome::Completed(detail)) => {
+                    self.update_step(job_id, step, JobStatus::Completed, Some(detail))
+                }
+                Ok(StepOutcome::Skipped(detail)) => {
+                    self.update_step(job_id, step, JobStatus::Skipped, Some(detail))
+                }
+                Err(e) => {
+                    error!("Maintenance step {:?} failed: {:?}", step, e);
+                    blocked.push(step);
+                    self.update_step(job_id, step, JobStatus::Failed, Some(format!("{:?}", e)));
+                }
+            }
+        }
+
+        let mut jobs = self.jobs.lock().expect("maintenance job registry poisoned");
+        jobs.running = None;
+        if let Some(job) = jobs.jobs.get_mut(&job_id) {
+            job.finished_at = Some(Utc::now().naive_utc());
+            job.status = if job.steps.iter().any(|s| s.status == JobStatus::Failed) {
+                JobStatus::Failed
+            } else {
+                JobStatus::Completed
+            };
+        }
+        info!("Maintenance job {} finished", job_id);
+    }
+
+    async fn run_step(
+        &self,
+        step: MaintenanceStep,
+        task: MaintenanceTask,
+    ) -> AppResult<StepOutcome> {
+        match step {
+            MaintenanceStep::RefreshMaterializedViews => {
+                let refreshed = self.refresh_materialized_views().await?;
+                Ok(StepOutcome::Completed(format!(
+                    "refreshed {} materialized view(s)",
+                    refreshed
+                )))
+            }
+            MaintenanceStep::PrecomputeRecommendations => {
+                if !self.similarity.is_enabled() {
+                    return Ok(StepOutcome::Skipped(
+                        "product similarity is disabled".to_string(),
+                    ));
+                }
+                let mut computed = 0;
+                loop {
+                    let batch = self
+                        .similarity
+                        .refresh_missing_embeddings(EMBEDDING_REFRESH_BATCH_SIZE)
+                        .await?;
+                    computed += batch;
+                    if batch == 0 {
+                        break;
+                    }
+                }
+                Ok(StepOutcome::Completed(format!(
+                    "computed {} product embedding(s)",
+                    computed
+                )))
+            }
+            MaintenanceStep::ReindexSearch => {
+                let rebuilt = self.repository.reindex_search_indexes().await?;
+                if rebuilt.is_empty() {
+                    Ok(StepOutcome::Skipped(
+                        "no search indexes present".to_string(),
+                    ))
+                } else {
+                    Ok(StepOutcome::Completed(format!(
+                        "rebuilt {}",
+                        rebuilt.join(", ")
+                    )))
+                }
+            }
+            MaintenanceStep::FlushCaches => {
+                let flushed = self.flush().await?;
+                let mut detail = format!("flushed {} cached lookups", flushed.lookup_entries);
+                if let Some(responses) = flushed.response_entries {
+                    detail.push_str(&format!(" and {} cached responses", responses));
+                }
+                Ok(StepOutcome::Completed(detail))
+            }
+            MaintenanceStep::ArchiveOrders => {
+                let MaintenanceTask::ArchiveOrders { before } = task else {
+                    return Ok(StepOutcome::Skipped("no archive cutoff".to_string()));
+                };
+                self.archive_orders(before).await
+            }
+        }
+    }
+
+    /// Archives in batches of [`ORDER_ARCHIVE_BATCH_SIZE`], each its own transaction, so the
+    /// order tables are never locked for long. Cached listings are flushed once orders moved.
+    async fn archive_orders(&self, before: NaiveDate) -> AppResult<StepOutcome> {
+        let before = before.and_time(NaiveTime::MIN);
+        let mut archived = ArchivedOrders::default();
+        loop {
+            let Some(batch) = self
+                .repository
+                .archive_orders(before, ORDER_ARCHIVE_BATCH_SIZE)
+                .await?
+            else {
+                return Ok(StepOutcome::Skipped(
+                    "this database keeps no order archive".to_string(),
+                ));
+            };
+            archived.orders += batch.orders;
+            archived.items += batch.items;
+            archived.payments += batch.payments;
+            archived.reviews += batch.reviews;
+            if batch.orders < ORDER_ARCHIVE_BATCH_SIZE as u64 {
+                break;
+            }
+        }
+
+        if archived.orders > 0 {
+            self.flush().await?;
+        }
+        info!(
+            "Archived {} orders purchased before {}",
+            archived.orders, before
+        );
+        Ok(StepOutcome::Completed(format!(
+            "archived {} order(s) with {} item(s), {} payment(s) and {} review(s)",
+            archived.orders, archived.items, archived.payments, archived.reviews
+        )))
+    }
+
+    fn update_step(
+        &self,
+        job_id: u64,
+        step: MaintenanceStep,
+        status: JobStatus,
+        detail: Option<String>,
+    ) {
+        let mut jobs = self.jobs.lock().expect("maintenance job registry poisoned");
+        let Some(report) = jobs
+            .jobs
+            .get_mut(&job_id)
+            .and_then(|job| job.steps.iter_mut().find(|report| report.step == step))
+        else {
+            return;
+        };
+
+        let now = Utc::now().naive_utc();
+        match status {
+            JobStatus::Running => report.started_at = Some(now),
+            JobStatus::Completed | JobStatus::Skipped | JobStatus::Failed => {
+                report.finished_at = Some(now)
+            }
+            JobStatus::Pending => {}
+        }
+        report.status = status;
+        report.detail = detail;
+    }
+}
@@ -0,0 +1,181 @@
+mod audit;
+mod categories;
+mod coupons;
+mod customers;
+mod data_quality;
+mod diagnostics;
+mod freight;
+mod inventory;
+mod late_orders;
+mod maintenance;
+mod nearby_sellers;
+mod notifications;
+mod orders;
+mod outbox;
+mod payments;
+mod product_images;
+mod products;
+mod retention;
+mod review_sentiments;
+mod sellers;
+mod shipping;
+mod similarity;
+mod support;
+mod webhooks;
+mod zip_lookup;
+
+pub use audit::AuditService;
+pub use categories::CategoryService;
+pub use coupons::CouponService;
+pub use customers::CustomerService;
+pub use data_quality::DataQualityService;
+pub use diagnostics::DiagnosticsService;
+pub use freight::FreightService;
+pub use inventory::InventoryService;
+pub use late_orders::LateOrderService;
+pub use maintenance::{EMBEDDING_REFRESH_BATCH_SIZE, MaintenanceService, ORDER_ARCHIVE_BATCH_SIZE};
+pub use nearby_sellers::NearbySellerService;
+pub use notifications::NotificationService;
+pub use orders::OrderService;
+pub use outbox::OutboxService;
+pub use payments::PaymentService;
+pub use product_images::ProductImageService;
+pub use products::ProductService;
+pub use retention::RetentionService;
+pub use review_sentiments::ReviewSentimentService;
+pub use sellers::SellerService;
+pub use shipping::ShippingService;
+pub use similarity::SimilarityService;
+pub use support::SupportService;
+pub use webhooks::WebhookService;
+pub use zip_lookup::ZipLookupService;
+
+use bytes::Bytes;
+use futures::StreamExt;
+use futures::stream::BoxStream;
+use serde::Serialize;
+use serde_json::{Map, Value, json};
+use std::io;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tracing::error;
+
+use crate::error::{AppError, AppResult, map_db_error};
+use crate::models::OrderStatus;
+
+/// Statuses after which an order can no longer be amended.
+const LOCKED_ORDER_STATUSES: &[OrderStatus] = &[
+    OrderStatus::Shipped,
+    OrderStatus::Delivered,
+    OrderStatus::Canceled,
+    OrderStatus::Unavailable,
+];
+
+/// Encoded chunks buffered between a streaming export task and the response.
+pub const EXPORT_CHANNEL_CAPACITY: usize = 16;
+
+/// Reads rows from `rows` on a background task, so the stream can outlive the borrow of the
+/// repository. As with the customer export, the task stops as soon as the receiver is dropped.
+fn export_rows<R, T>(
+    entity: &'static str,
+    repository: Arc<R>,
+    rows: fn(&R) -> BoxStream<'_, sqlx::Result<T>>,
+) -> ReceiverStream<sqlx::Result<T>>
+where
+    R: ?Sized + Send + Sync + 'static,
+    T: Send + 'static,
+{
+    let (tx, rx) = mpsc::channel(EXPORT_CHANNEL_CAPACITY);
+
+    tokio::spawn(async move {
+        let mut rows = rows(repository.as_ref());
+        while let Some(row) = rows.next().await {
+            if let Err(e) = &row {
+                error!("{} export failed: {:?}", entity, e);
+            }
+            let failed = row.is_err();
+            if tx.send(row).await.is_err() || failed {
+                return;
+            }
+        }
+    });
+
+    ReceiverStream::new(rx)
+}
+
+/// Returns `false` once the client has gone away.
+pub async fn send_chunk(tx: &mpsc::Sender<io::Result<Bytes>>, chunk: Vec<u8>) -> bool {
+    tx.send(Ok(Bytes::from(chunk))).await.is_ok()
+}
+
+fn validation_error(field: &'static str, code: &'static str, message: String) -> AppError {
+    let mut errors = validator::ValidationErrors::new();
+    errors.add(
+        field,
+        validator::ValidationError::new(code).with_message(message.into()),
+    );
+    AppError::ValidationError(errors)
+}
+
+/// Builds a `{ field: { from, to } }` map containing only the fields that changed.
+/// Runs `prepare` on each row, inserts the rows it accepts with `insert` and returns one
+/// result per row, in order: the rejection, the insert error or the created entity.
+async fn create_each<D, R, T, F, Fut>(
+    resource: &str,
+    dtos: Vec<D>,
+    prepare: impl Fn(D) -> AppResult<R>,
+    insert: F,
+) -> AppResult<Vec<AppResult<T>>>
+where
+    F: FnOnce(Vec<R>) -> Fut,
+    Fut: Future<Output = sqlx::Result<Vec<sqlx::Result<T>>>>,
+{
+    let mut results: Vec<Option<AppResult<T>>> = Vec::with_capacity(dtos.len());
+    let mut accepted = Vec::with_capacity(dtos.len());
+    for dto in dtos {
+        match prepare(dto) {
+            Ok(row) => {
+                accepted.push(row);
+                results.push(None);
+            }
+            Err(e) => results.push(Some(Err(e))),
+        }
+    }
+
+    let mut inserted = insert(accepted).await?.into_iter();
+    Ok(results
+        .into_iter()
+        .map(|result| {
+            result.unwrap_or_else(|| {
+                inserted
+                    .next()
+                    .expect("one insert result per accepted row")
+                    .map_err(|e| map_db_error(e, resource))
+            })
+        })
+        .collect())
+}
+
+fn audit_diff<T: Serialize>(before: Option<&T>, after: Option<&T>) -> Value {
+    let to_map = |value: Option<&T>| -> Map<String, Value> {
+        value
+            .and_then(|v| serde_json::to_value(v).ok())
+            .and_then(|v| v.as_object().cloned())
+            .unwrap_or_default()
+    };
+
+    let before = to_map(before);
+    let after = to_map(after);
+
+    let mut diff = Map::new();
+    for key in before.keys().chain(after.keys()) {
+        let from = before.get(key).cloned().unwrap_or(Value::Null);
+        let to = after.get(key).cloned().unwrap_or(Value::Null);
+        if from != to {
+            diff.insert(key.clone(), json!({ "from": from, "to": to }));
+        }
+    }
+
+    Value::Object(diff)
+}
@@ -0,0 +1,104 @@
+use std::sync::Arc;
+use tracing::instrument;
+use validator::Validate;
+
+use crate::cache::LookupCache;
+use crate::error::{AppError, AppResult};
+use crate::geo::{GeoBounds, haversine_km};
+use crate::ids::CustomerId;
+use crate::models::{NearbySeller, NearbySellers, NearbySellersQuery};
+use crate::repositories::{CustomerRepository, GeolocationRepository, SellerRepository};
+
+use super::validation_error;
+
+/// Marketplace matching by distance: sellers near a customer, from the coordinates of their
+/// zip code prefixes.
+#[derive(Clone)]
+pub struct NearbySellerService {
+    customers: Arc<dyn CustomerRepository>,
+    sellers: Arc<dyn SellerRepository>,
+    geolocation: Arc<dyn GeolocationRepository>,
+    lookups: LookupCache,
+}
+
+impl NearbySellerService {
+    pub fn new(
+        customers: Arc<dyn CustomerRepository>,
+        sellers: Arc<dyn SellerRepository>,
+        geolocation: Arc<dyn GeolocationRepository>,
+        lookups: LookupCache,
+    ) -> Self {
+        Self {
+            customers,
+            sellers,
+            geolocation,
+            lookups,
+        }
+    }
+
+    /// Sellers within the query's radius of the customer, nearest first. Candidates come
+    /// from a bounding box around the customer and are then filtered by their haversine
+    /// distance; sellers whose prefix has no coordinates are never matched.
+    #[instrument(skip(self))]
+    pub async fn find_near_customer(
+        &self,
+        id: &CustomerId,
+        query: &NearbySellersQuery,
+    ) -> AppResult<NearbySellers> {
+        query.validate()?;
+        let customer = self
+            .customers
+            .find_by_id(id)
+            .await?
+            .ok_or(AppError::NotFound)?;
+        let prefix = customer.customer_zip_code_prefix;
+        let origin = self
+            .lookups
+            .locations(std::slice::from_ref(&prefix), |prefixes| async move {
+                Ok(self.geolocation.find_by_prefixes(&prefixes).await?)
+            })
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| {
+                validation_error(
+                    "customer_zip_code_prefix",
+                    "unknown_zip_code_prefix",
+                    format!("no coordinates for CEP prefix {}", prefix),
+                )
+            })?;
+        let center = (origin.geolocation_lat, origin.geolocation_lng);
+        let radius_km = query.radius_km();
+
+        let mut sellers: Vec<NearbySeller> = self
+            .sellers
+            .find_within(&GeoBounds::around(center, radius_km))
+            .await?
+            .into_iter()
+            .filter_map(|located| {
+                let distance_km =
+                    haversine_km(center, (located.geolocation_lat, located.geolocation_lng));
+                (distance_km <= radius_km).then_some(NearbySeller {
+                    seller: located.seller,
+                    distance_km,
+                })
+            })
+            .collect();
+        sellers.sort_by(|a, b| {
+            a.distance_km
+                .total_cmp(&b.distance_km)
+                .then_with(|| a.seller.seller_id.as_str().cmp(b.seller.seller_id.as_str()))
+        });
+        sellers.truncate(query.limit());
+        for seller in &mut sellers {
+            seller.distance_km = (seller.distance_km * 10.0).round() / 10.0;
+        }
+
+        Ok(NearbySellers {
+            customer_id: customer.customer_id,
+            customer_zip_code_prefix: prefix,
+            radius_km,
+            sellers,
+        })
+    }
+}
@@ -0,0 +1,137 @@
+use std::sync::Arc;
+use tracing::{instrument, warn};
+
+use crate::config::NotificationConfig;
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    Notification, NotificationAttempt, NotificationKind, NotificationQuery, PaginatedResponse,
+    PendingNotification,
+};
+use crate::notifications::{NotificationTemplates, Notifier};
+use crate::repositories::NotificationRepository;
+
+/// Notifications of approved and delivered orders and new reviews. The outbox relay queues
+/// them as it publishes the events; the notification worker renders and sends them through
+/// the configured [`Notifier`], retrying failed attempts with exponential backoff.
+#[derive(Clone)]
+pub struct NotificationService {
+    repository: Arc<dyn NotificationRepository>,
+    notifier: Arc<dyn Notifier>,
+    templates: Arc<NotificationTemplates>,
+    config: NotificationConfig,
+}
+
+impl NotificationService {
+    /// Notifications leased per worker pass.
+    const CLAIM_BATCH_SIZE: i64 = 50;
+    /// How long a pass may take before its notifications can be claimed again.
+    const LEASE_SECONDS: i64 = 120;
+
+    pub fn new(
+        repository: Arc<dyn NotificationRepository>,
+        notifier: Arc<dyn Notifier>,
+        templates: NotificationTemplates,
+        config: NotificationConfig,
+    ) -> Self {
+        Self {
+            repository,
+            notifier,
+            templates: Arc::new(templates),
+            config,
+        }
+    }
+
+    pub fn config(&self) -> NotificationConfig {
+        self.config
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_notifications(
+        &self,
+        query: NotificationQuery,
+    ) -> AppResult<PaginatedResponse<Notification>> {
+        let pagination = query.pagination();
+        let (_, _, page, page_size) = pagination.normalize();
+        let (notifications, total_count) = self
+            .repository
+            .find_notifications(
+                query.status.map(|status| status.as_str()),
+                query.kind.map(|kind| kind.as_str()),
+                &pagination,
+            )
+            .await?;
+        Ok(PaginatedResponse::new(
+            notifications,
+            total_count,
+            page,
+            page_size,
+        ))
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_notification(&self, notification_id: i64) -> AppResult<Notification> {
+        self.repository
+            .find_notification(notification_id)
+            .await?
+            .ok_or(AppError::NotFound)
+    }
+
+    /// Sends the next batch of due notifications one after another, returning how many were
+    /// attempted.
+    pub async fn send_due(&self) -> AppResult<usize> {
+        let pending = self
+            .repository
+            .claim_due_notifications(Self::CLAIM_BATCH_SIZE, Self::LEASE_SECONDS)
+            .await?;
+        for notification in &pending {
+            self.send(notification).await?;
+        }
+        Ok(pending.len())
+    }
+
+    async fn send(&self, notification: &PendingNotification) -> AppResult<()> {
+        let recipient = self.notifier.recipient();
+        let kind = match notification.kind.parse::<NotificationKind>() {
+            Ok(kind) => kind,
+            Err(e) => {
+                let attempt = NotificationAttempt {
+                    channel: self.notifier.name(),
+                    recipient: recipient.as_deref(),
+                    subject: "",
+                };
+                return Ok(self
+                    .repository
+                    .mark_attempt_failed(notification.notification_id, &attempt, &e, None)
+                    .await?);
+            }
+        };
+        let message = self.templates.render(kind, &notification.payload);
+        let attempt = NotificationAttempt {
+            channel: self.notifier.name(),
+            recipient: recipient.as_deref(),
+            subject: &message.subject,
+        };
+
+        match self.notifier.send(&message).await {
+            Ok(()) => Ok(self
+                .repository
+                .mark_sent(notification.notification_id, &attempt)
+                .await?),
+            Err(e) => {
+                let attempts = notification.attempts.max(0) as u32;
+                let retry_in = (attempts < self.config.max_attempts)
+                    .then(|| self.config.retry_delay(attempts).as_secs() as i64);
+                if retry_in.is_none() {
+                    warn!(
+                        "Notification {} failed for good after {} attempts: {}",
+                        notification.notification_id, attempts, e
+                    );
+                }
+                Ok(self
+                    .repository
+                    .mark_attempt_failed(notification.notification_id, &attempt, &e, retry_in)
+                    .await?)
+            }
+        }
+    }
+}
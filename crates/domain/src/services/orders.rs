@@ -0,0 +1,958 @@
+use bigdecimal::{BigDecimal, Zero};
+use bytes::Bytes;
+use chrono::{Duration, Utc};
+use serde_json::{Map, Value, json};
+use std::collections::HashMap;
+use std::io;
+use std::sync::Arc;
+use tokio::sync::{broadcast, mpsc};
+use tokio_stream::wrappers::ReceiverStream;
+use tracing::{error, instrument, warn};
+use validator::Validate;
+
+use crate::cache::{self, ResponseCache};
+use crate::config::AmendmentConfig;
+use crate::error::{AppError, AppResult, map_db_error};
+use crate::events::{OrderFeed, OrderStatusEvents};
+use crate::ids::{CustomerId, OrderId, ProductId};
+use crate::models::{
+    AddItemToOrderDto, AmendOrderDto, ApplyCouponDto, AuditAction, Coupon, CreateOrderDto,
+    CreateRefundDto, Customer, FilterValue, NewOrderAmendment, NewRefund, Order, OrderAmendment,
+    OrderDiscount, OrderExport, OrderFeedEvent, OrderFinancials, OrderItem, OrderProduct,
+    OrderProductResponse, OrderSample, OrderSampleQuery, OrderSearchQuery, OrderStatus,
+    OrderStatusPoll, OrderSummary, PaginatedResponse, PaginationParams, Payment, Refund, Review,
+    SparseRow, UniqueCustomer, coupon_discount,
+};
+use crate::money::{Money, round_to_centavos};
+use crate::repositories::{CouponRepository, OrderRepository};
+
+use super::{
+    AuditService, EXPORT_CHANNEL_CAPACITY, InventoryService, LOCKED_ORDER_STATUSES, create_each,
+    export_rows, send_chunk, validation_error,
+};
+
+#[derive(Clone)]
+pub struct OrderService {
+    repository: Arc<dyn OrderRepository>,
+    coupons: Arc<dyn CouponRepository>,
+    audit: AuditService,
+    inventory: InventoryService,
+    amendments: AmendmentConfig,
+    status_events: OrderStatusEvents,
+    feed: OrderFeed,
+    cache: ResponseCache,
+}
+
+impl OrderService {
+    pub fn new(
+        repository: Arc<dyn OrderRepository>,
+        coupons: Arc<dyn CouponRepository>,
+        audit: AuditService,
+        inventory: InventoryService,
+        amendments: AmendmentConfig,
+        status_events: OrderStatusEvents,
+        cache: ResponseCache,
+    ) -> Self {
+        Self {
+            repository,
+            coupons,
+            audit,
+            inventory,
+            amendments,
+            status_events,
+            feed: OrderFeed::default(),
+            cache,
+        }
+    }
+
+    /// New orders created through this service and status changes, as they happen.
+    pub fn subscribe_feed(&self) -> broadcast::Receiver<OrderFeedEvent> {
+        self.feed.subscribe()
+    }
+
+    /// Copies status changes onto the order feed until the status channel closes. Changes
+    /// are only heard on Postgres, so on SQLite the feed carries new orders alone.
+    pub async fn feed_status_changes(&self) {
+        let mut changes = self.status_events.subscribe();
+        loop {
+            match changes.recv().await {
+                Ok(change) => self.feed.publish(OrderFeedEvent::StatusChanged(change)),
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!("Order feed missed {} status changes", skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => return,
+            }
+        }
+    }
+
+    #[instrument(skip(self))]
+    pub async fn create_order(&self, dto: CreateOrderDto, actor: &str) -> AppResult<Order> {
+        let (id, dto) = Self::prepare(dto)?;
+        let order = self
+            .repository
+            .create(&id, dto)
+            .await
+            .map_err(|e| map_db_error(e, "Order"))?;
+        self.cache.invalidate(cache::TODAY_STATS).await;
+
+        self.audit
+            .record(
+                "order",
+                order.order_id.as_str(),
+                AuditAction::Create,
+                actor,
+                None,
+                Some(&order),
+            )
+            .await;
+        self.feed.publish(OrderFeedEvent::Created(order.clone()));
+
+        Ok(order)
+    }
+
+    /// See [`CustomerService::create_customers`].
+    #[instrument(skip(self, dtos), fields(rows = dtos.len()))]
+    pub async fn create_orders(
+        &self,
+        dtos: Vec<CreateOrderDto>,
+        actor: &str,
+    ) -> AppResult<Vec<AppResult<Order>>> {
+        let orders = create_each("Order", dtos, Self::prepare, |rows| {
+            self.repository.create_many(rows)
+        })
+        .await?;
+        self.cache.invalidate(cache::TODAY_STATS).await;
+
+        self.audit
+            .record_created(
+                "order",
+                actor,
+                orders
+                    .iter()
+                    .flatten()
+                    .map(|order| (order.order_id.as_str(), order)),
+            )
+            .await;
+        for order in orders.iter().flatten() {
+            self.feed.publish(OrderFeedEvent::Created(order.clone()));
+        }
+
+        Ok(orders)
+    }
+
+    /// Normalizes and validates a new order as `create_order` does, without inserting it.
+    pub fn prepare(mut dto: CreateOrderDto) -> AppResult<(OrderId, CreateOrderDto)> {
+        dto.validate()?;
+        let id = dto.order_id.take().unwrap_or_else(OrderId::generate);
+        Ok((id, dto))
+    }
+
+    #[instrument(skip(self))]
+    pub async fn add_item_to_order(
+        &self,
+        order_id: &OrderId,
+        dto: AddItemToOrderDto,
+        actor: &str,
+    ) -> AppResult<OrderItem> {
+        dto.validate()?;
+        let destination = self
+            .repository
+            .find_destination_zip_code_prefix(order_id)
+            .await?
+            .ok_or(AppError::NotFound)?;
+
+        let product_id = dto.product_id.clone();
+        let (item, allocation) = self
+            .repository
+            .add_item_with_allocation(order_id, dto, &destination)
+            .await?
+            .ok_or_else(|| AppError::InsufficientStock(product_id.to_string()))?;
+        if let Some(allocation) = &allocation {
+            self.inventory.record_allocation(allocation, 1, actor).await;
+        }
+        self.cache.invalidate(cache::TODAY_STATS).await;
+
+        self.audit
+            .record(
+                "order_item",
+                &format!("{}:{}", item.order_id, item.order_item_id),
+                AuditAction::Create,
+                actor,
+                None,
+                Some(&item),
+            )
+            .await;
+
+        Ok(item)
+    }
+
+    /// Changes the shipping destination and/or swaps items while the order is still inside the
+    /// amendment window, recalculating freight and tax and recording the amendment.
+    #[instrument(skip(self))]
+    pub async fn amend_order(
+        &self,
+        order_id: &OrderId,
+        dto: AmendOrderDto,
+        actor: &str,
+    ) -> AppResult<OrderAmendment> {
+        dto.validate()?;
+        if dto.shipping_zip_code_prefix.is_none() && dto.item_swaps.is_empty() {
+            return Err(AppError::NoChangesToUpdate);
+        }
+
+        let order = self.get_order_by_id(order_id).await?;
+        self.ensure_amendable(&order)?;
+
+        let current_destination = self
+            .repository
+            .find_destination_zip_code_prefix(order_id)
+            .await?
+            .ok_or(AppError::NotFound)?;
+        let destination = dto
+            .shipping_zip_code_prefix
+            .clone()
+            .unwrap_or_else(|| current_destination.clone());
+        let destination_changed = destination != current_destination;
+
+        let origins = self.repository.find_item_origins(order_id).await?;
+        let unknown_item = dto.item_swaps.iter().any(|swap| {
+            !origins
+                .iter()
+                .any(|origin| origin.item.order_item_id == swap.order_item_id)
+        });
+        if unknown_item {
+            return Err(AppError::NotFound);
+        }
+
+        let mut updated: Vec<(ProductId, OrderItem)> = Vec::new();
+        let mut swaps = Vec::new();
+
+        for origin in &origins {
+            let swap = dto
+                .item_swaps
+                .iter()
+                .find(|swap| swap.order_item_id == origin.item.order_item_id);
+            if swap.is_none() && !destination_changed {
+                continue;
+            }
+
+            let mut item = origin.item.clone();
+            if let Some(swap) = swap {
+                item.product_id = swap.product_id.clone();
+                if let Some(price) = &swap.price {
+                    item.price = price.clone().into();
+                }
+                swaps.push(json!({
+                    "order_item_id": item.order_item_id,
+                    "product_id": { "from": origin.item.product_id, "to": item.product_id },
+                    "price": { "from": origin.item.price, "to": item.price },
+                }));
+            }
+            if destination_changed {
+                item.freight_value = self
+                    .amendments
+                    .freight_for(&origin.seller_zip_code_prefix, &destination)
+                    .into();
+            }
+            updated.push((origin.item.product_id.clone(), item));
+        }
+
+        let previous_items: Vec<&OrderItem> = origins.iter().map(|origin| &origin.item).collect();
+        let next_items: Vec<&OrderItem> = origins
+            .iter()
+            .map(|origin| {
+                updated
+                    .iter()
+                    .find(|(_, item)| item.order_item_id == origin.item.order_item_id)
+                    .map(|(_, item)| item)
+                    .unwrap_or(&origin.item)
+            })
+            .collect();
+        let (previous_freight, previous_tax) = self.amendments.order_charges(&previous_items);
+        let (new_freight, new_tax) = self.amendments.order_charges(&next_items);
+
+        let mut changes = Map::new();
+        if destination_changed {
+            changes.insert(
+                "shipping_zip_code_prefix".to_string(),
+                json!({ "from": current_destination, "to": destination }),
+            );
+        }
+        if !swaps.is_empty() {
+            changes.insert("item_swaps".to_string(), Value::Array(swaps));
+        }
+        if changes.is_empty() {
+            return Err(AppError::NoChangesToUpdate);
+        }
+        let changes = Value::Object(changes);
+
+        let mut allocations = Vec::new();
+        for (previous_product_id, item) in &updated {
+            if *previous_product_id == item.product_id {
+                continue;
+            }
+            match self
+                .inventory
+                .allocate(&item.seller_id, &item.product_id, 1, &destination, actor)
+                .await
+            {
+                Ok(allocation) => allocations.extend(allocation),
+                Err(e) => {
+                    for allocation in &allocations {
+                        self.inventory.release(allocation, actor).await;
+                    }
+                    return Err(e);
+                }
+            }
+        }
+
+        let amendment = NewOrderAmendment {
+            changes: changes.clone(),
+            previous_freight,
+            new_freight,
+            previous_tax,
+            new_tax,
+        };
+        let recorded = match self
+            .repository
+            .apply_amendment(
+                order_id,
+                actor,
+                dto.shipping_zip_code_prefix.as_deref(),
+                &updated,
+                amendment,
+            )
+            .await
+        {
+            Ok(recorded) => recorded,
+            Err(e) => {
+                for allocation in &allocations {
+                    self.inventory.release(allocation, actor).await;
+                }
+                return Err(e.into());
+            }
+        };
+        self.cache.invalidate(cache::TODAY_STATS).await;
+
+        for origin in &origins {
+            let swapped = updated.iter().any(|(previous_product_id, item)| {
+                item.order_item_id == origin.item.order_item_id
+                    && *previous_product_id != item.product_id
+            });
+            if swapped {
+                self.inventory
+                    .restock(
+                        &origin.item.seller_id,
+                        &origin.item.product_id,
+                        1,
+                        &current_destination,
+                        actor,
+                    )
+                    .await;
+            }
+        }
+
+        self.audit
+            .record_event(
+                "order",
+                order_id.as_str(),
+                AuditAction::Update,
+                actor,
+                changes,
+            )
+            .await;
+
+        Ok(recorded)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_order_amendments(&self, order_id: &OrderId) -> AppResult<Vec<OrderAmendment>> {
+        self.get_order_by_id(order_id).await?;
+        Ok(self.repository.find_amendments(order_id).await?)
+    }
+
+    fn ensure_amendable(&self, order: &Order) -> AppResult<()> {
+        if order.order_delivered_carrier_date.is_some()
+            || LOCKED_ORDER_STATUSES.contains(&order.order_status)
+        {
+            return Err(AppError::AmendmentNotAllowed(format!(
+                "Order {} has already been handed to the carrier",
+                order.order_id
+            )));
+        }
+
+        let deadline =
+            order.order_purchase_timestamp + Duration::hours(self.amendments.window_hours);
+        if Utc::now().naive_utc() > deadline {
+            return Err(AppError::AmendmentNotAllowed(format!(
+                "The amendment window for order {} closed at {}",
+                order.order_id, deadline
+            )));
+        }
+
+        Ok(())
+    }
+
+    #[instrument(skip(self))]
+    pub async fn sample_orders(&self, query: OrderSampleQuery) -> AppResult<OrderSample> {
+        let strata = query.strata()?;
+        let seed = query.seed();
+        let orders = self.repository.sample(&strata, query.size(), seed).await?;
+
+        Ok(OrderSample {
+            seed,
+            stratify_by: strata,
+            size: orders.len(),
+            orders,
+        })
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_order_by_id(&self, id: &OrderId) -> AppResult<Order> {
+        match self.repository.find_by_id(id).await? {
+            Some(order) => Ok(order),
+            None => Err(AppError::NotFound),
+        }
+    }
+
+    /// Returns the order's status once its version moves past `since_version`, or the
+    /// unchanged status when `wait` elapses. Without `since_version` it returns immediately.
+    #[instrument(skip(self))]
+    pub async fn wait_for_status_change(
+        &self,
+        id: &OrderId,
+        since_version: Option<i32>,
+        wait: std::time::Duration,
+    ) -> AppResult<OrderStatusPoll> {
+        // Subscribe before reading so a change landing in between isn't missed.
+        let mut changes = self.status_events.subscribe();
+        let current = self
+            .repository
+            .find_status(id)
+            .await?
+            .ok_or(AppError::NotFound)?;
+
+        let since_version = match since_version {
+            Some(version) if current.status_version <= version => version,
+            _ => {
+                return Ok(OrderStatusPoll {
+                    status: current,
+                    changed: true,
+                });
+            }
+        };
+
+        let deadline = tokio::time::Instant::now() + wait;
+        loop {
+            match tokio::time::timeout_at(deadline, changes.recv()).await {
+                Ok(Ok(change)) => {
+                    if change.order_id == *id && change.status_version > since_version {
+                        return Ok(OrderStatusPoll {
+                            status: change,
+                            changed: true,
+                        });
+                    }
+                }
+                Ok(Err(broadcast::error::RecvError::Lagged(skipped))) => {
+                    warn!("Order status waiter lagged by {} events", skipped);
+                    let latest = self
+                        .repository
+                        .find_status(id)
+                        .await?
+                        .ok_or(AppError::NotFound)?;
+                    if latest.status_version > since_version {
+                        return Ok(OrderStatusPoll {
+                            status: latest,
+                            changed: true,
+                        });
+                    }
+                }
+                Ok(Err(broadcast::error::RecvError::Closed)) | Err(_) => break,
+            }
+        }
+
+        Ok(OrderStatusPoll {
+            status: current,
+            changed: false,
+        })
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_products_by_order_id(&self, id: &OrderId) -> AppResult<OrderProductResponse> {
+        let products = self.repository.find_products_by_order_id(id).await?;
+        let coupon = self.coupons.find_by_order(id).await?;
+        let refunds = self.repository.find_refunds_by_order_id(id).await?;
+        Ok(order_totals(products, coupon, &refunds))
+    }
+
+    /// Applies a coupon to an order that has not been handed to the carrier yet, taking one
+    /// of its uses. Its discount is worked out from the order's items whenever the total is,
+    /// so later item changes are reflected.
+    #[instrument(skip(self))]
+    pub async fn apply_coupon(
+        &self,
+        order_id: &OrderId,
+        dto: ApplyCouponDto,
+        actor: &str,
+    ) -> AppResult<OrderProductResponse> {
+        dto.validate()?;
+        let code = dto.code.trim().to_uppercase();
+        let order = self.get_order_by_id(order_id).await?;
+        if order.order_delivered_carrier_date.is_some()
+            || LOCKED_ORDER_STATUSES.contains(&order.order_status)
+        {
+            return Err(AppError::CouponRejected(format!(
+                "Order {} has already been handed to the carrier",
+                order.order_id
+            )));
+        }
+        let coupon = self
+            .coupons
+            .find_by_code(&code)
+            .await?
+            .ok_or(AppError::NotFound)?;
+        if let Some(applied) = self.coupons.find_by_order(order_id).await? {
+            return Err(AppError::AlreadyExists(format!(
+                "Order {} already has coupon {}",
+                order_id, applied.code
+            )));
+        }
+
+        let products = self.repository.find_products_by_order_id(order_id).await?;
+        let subtotal = items_subtotal(&products);
+        if let Some(min) = &coupon.min_order_value
+            && subtotal < *min
+        {
+            return Err(AppError::CouponRejected(format!(
+                "Coupon {} needs items worth at least {}; order {} has {}",
+                code, min, order_id, subtotal
+            )));
+        }
+
+        let redeemed = self
+            .coupons
+            .redeem(&code, order_id)
+            .await
+            .map_err(|e| map_db_error(e, "A coupon for this order"))?;
+        if !redeemed {
+            return Err(AppError::CouponRejected(format!(
+                "Coupon {} has expired or has no uses left",
+                code
+            )));
+        }
+
+        self.audit
+            .record(
+                "order_coupon",
+                order_id.as_str(),
+                AuditAction::Create,
+                actor,
+                None,
+                Some(&json!({ "code": code })),
+            )
+            .await;
+
+        let coupon = Coupon {
+            times_used: coupon.times_used + 1,
+            ..coupon
+        };
+        let refunds = self.repository.find_refunds_by_order_id(order_id).await?;
+        Ok(order_totals(products, Some(coupon), &refunds))
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_payments_by_order_id(&self, id: &OrderId) -> AppResult<Vec<Payment>> {
+        let payments = self.repository.find_payments_by_order_id(id).await?;
+        Ok(payments)
+    }
+
+    /// Refunds part or all of one of a delivered or canceled order's payments. Without an
+    /// amount, what is left of the payment is refunded.
+    #[instrument(skip(self))]
+    pub async fn refund_order(
+        &self,
+        order_id: &OrderId,
+        dto: CreateRefundDto,
+        actor: &str,
+    ) -> AppResult<Refund> {
+        dto.validate()?;
+        let order = self.get_order_by_id(order_id).await?;
+        if !matches!(
+            order.order_status,
+            OrderStatus::Delivered | OrderStatus::Canceled
+        ) {
+            return Err(AppError::PaymentNotAllowed(format!(
+                "Order {} can only be refunded once delivered or canceled",
+                order_id
+            )));
+        }
+
+        let payment = self
+            .repository
+            .find_payments_by_order_id(order_id)
+            .await?
+            .into_iter()
+            .find(|payment| payment.payment_sequential == dto.payment_sequential)
+            .ok_or(AppError::NotFound)?;
+        let refunded = self
+            .repository
+            .find_refunds_by_order_id(order_id)
+            .await?
+            .iter()
+            .filter(|refund| refund.payment_sequential == dto.payment_sequential)
+            .fold(BigDecimal::zero(), |acc, refund| {
+                acc + refund.amount.amount()
+            });
+        let refundable = payment.payment_value.amount() - &refunded;
+        if refundable <= BigDecimal::zero() {
+            return Err(AppError::PaymentNotAllowed(format!(
+                "Payment {} of order {} has already been fully refunded",
+                dto.payment_sequential, order_id
+            )));
+        }
+        let amount = dto.amount.unwrap_or_else(|| refundable.clone());
+        if amount > refundable {
+            return Err(validation_error(
+                "amount",
+                "exceeds_refundable",
+                format!(
+                    "At most {} of this payment can be refunded",
+                    round_to_centavos(&refundable)
+                ),
+            ));
+        }
+
+        let refund = self
+            .repository
+            .create_refund(NewRefund {
+                order_id: order_id.clone(),
+                payment_sequential: dto.payment_sequential,
+                amount,
+                reason: dto.reason,
+            })
+            .await?
+            .ok_or_else(|| {
+                AppError::PaymentNotAllowed(format!(
+                    "Payment {} of order {} was refunded concurrently; check its refunds",
+                    dto.payment_sequential, order_id
+                ))
+            })?;
+
+        self.audit
+            .record(
+                "refund",
+                &refund.refund_id.to_string(),
+                AuditAction::Create,
+                actor,
+                None,
+                Some(&refund),
+            )
+            .await;
+        Ok(refund)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_refunds_by_order_id(&self, id: &OrderId) -> AppResult<Vec<Refund>> {
+        self.get_order_by_id(id).await?;
+        let refunds = self.repository.find_refunds_by_order_id(id).await?;
+        Ok(refunds)
+    }
+
+    /// An order's items, freight, discounts, payments and refunds side by side, with what is
+    /// left to pay.
+    #[instrument(skip(self))]
+    pub async fn get_order_summary(&self, id: &OrderId) -> AppResult<OrderSummary> {
+        let financials = self
+            .repository
+            .find_financials(id)
+            .await?
+            .ok_or(AppError::NotFound)?;
+        Ok(order_summary(financials))
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_reviews_by_order_id(&self, id: &OrderId) -> AppResult<Vec<Review>> {
+        let reviews = self.repository.find_reviews_by_order_id(id).await?;
+        Ok(reviews)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_orders(&self, query: OrderSearchQuery) -> AppResult<PaginatedResponse<Order>> {
+        let pagination = query.pagination();
+        let filter = query.filter();
+        let (_, _, page, page_size) = pagination.normalize();
+
+        let (orders, total) = self
+            .repository
+            .find_all(&filter, &pagination, query.total_mode())
+            .await?;
+
+        Ok(PaginatedResponse::with_total(
+            orders, total, page, page_size,
+        ))
+    }
+
+    /// `get_orders` with each row trimmed to the selected fields.
+    #[instrument(skip(self))]
+    pub async fn get_orders_sparse(
+        &self,
+        query: OrderSearchQuery,
+        fields: &[&'static str],
+    ) -> AppResult<PaginatedResponse<SparseRow>> {
+        let pagination = query.pagination();
+        let filter = query.filter();
+        let (_, _, page, page_size) = pagination.normalize();
+
+        let (orders, total) = self
+            .repository
+            .find_all_sparse(&filter, &pagination, fields, query.total_mode())
+            .await?;
+
+        Ok(PaginatedResponse::with_total(
+            orders, total, page, page_size,
+        ))
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_orders_by_customer(
+        &self,
+        customer_id: &CustomerId,
+        pagination: &PaginationParams,
+    ) -> AppResult<PaginatedResponse<Order>> {
+        let (_, _, page, page_size) = pagination.normalize();
+        let (orders, count) = self
+            .repository
+            .find_by_customer_id(customer_id, pagination)
+            .await?;
+
+        Ok(PaginatedResponse::new(orders, count, page, page_size))
+    }
+
+    /// Gathers the orders of `customers`, the rows sharing `unique_id`.
+    #[instrument(skip(self, customers))]
+    pub async fn get_unique_customer(
+        &self,
+        unique_id: String,
+        customers: Vec<Customer>,
+    ) -> AppResult<UniqueCustomer> {
+        let orders = self
+            .repository
+            .find_by_customer_unique_id(&unique_id)
+            .await?;
+        Ok(UniqueCustomer::new(unique_id, customers, orders))
+    }
+
+    /// Streams a customer's full history as a single JSON document
+    /// (`{"customer": ..., "orders": [...]}`), a page of orders at a time, so a
+    /// heavy customer's history is never held in memory and no connection is
+    /// held between pages. The export stops early if the receiver is dropped.
+    #[instrument(skip(self, customer), fields(customer_id = %customer.customer_id))]
+    pub fn export_customer(&self, customer: Customer) -> ReceiverStream<io::Result<Bytes>> {
+        let (tx, rx) = mpsc::channel(EXPORT_CHANNEL_CAPACITY);
+        let repository = self.repository.clone();
+
+        tokio::spawn(async move {
+            if let Err(e) = write_customer_export(repository.as_ref(), &customer, &tx).await {
+                error!(
+                    "Customer export for {} failed: {:?}",
+                    customer.customer_id, e
+                );
+                let _ = tx.send(Err(e)).await;
+            }
+        });
+
+        ReceiverStream::new(rx)
+    }
+
+    /// Streams every order, oldest purchase first, one row at a time.
+    #[instrument(skip(self))]
+    pub fn export_all(&self) -> ReceiverStream<sqlx::Result<Order>> {
+        export_rows("Order", self.repository.clone(), |repository| {
+            repository.stream_all()
+        })
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_status_values(&self) -> AppResult<Vec<FilterValue>> {
+        Ok(self.repository.count_by_status().await?)
+    }
+}
+
+/// Orders read per page of a customer export. Each page takes one query for the orders and one
+/// each for their items, payments, refunds and reviews.
+const EXPORT_ORDER_PAGE: i64 = 100;
+
+async fn write_customer_export(
+    repository: &dyn OrderRepository,
+    customer: &Customer,
+    tx: &mpsc::Sender<io::Result<Bytes>>,
+) -> io::Result<()> {
+    let mut header = b"{\"customer\":".to_vec();
+    header.extend(serde_json::to_vec(customer)?);
+    header.extend(b",\"orders\":[");
+    if !send_chunk(tx, header).await {
+        return Ok(());
+    }
+
+    let mut after: Option<Order> = None;
+    let mut first = true;
+
+    loop {
+        let orders = repository
+            .find_by_customer_id_after(&customer.customer_id, after.as_ref(), EXPORT_ORDER_PAGE)
+            .await
+            .map_err(io::Error::other)?;
+        let last_page = (orders.len() as i64) < EXPORT_ORDER_PAGE;
+        let ids: Vec<OrderId> = orders.iter().map(|order| order.order_id.clone()).collect();
+        if ids.is_empty() {
+            break;
+        }
+
+        let mut items = by_order(
+            repository
+                .find_products_by_order_ids(&ids)
+                .await
+                .map_err(io::Error::other)?,
+        );
+        let mut payments = by_order(
+            repository
+                .find_payments_by_order_ids(&ids)
+                .await
+                .map_err(io::Error::other)?
+                .into_iter()
+                .map(|payment| (payment.order_id.clone(), payment)),
+        );
+        let mut refunds = by_order(
+            repository
+                .find_refunds_by_order_ids(&ids)
+                .await
+                .map_err(io::Error::other)?
+                .into_iter()
+                .map(|refund| (refund.order_id.clone(), refund)),
+        );
+        let mut reviews = by_order(
+            repository
+                .find_reviews_by_order_ids(&ids)
+                .await
+                .map_err(io::Error::other)?
+                .into_iter()
+                .map(|review| (review.order_id.clone(), review)),
+        );
+
+        for order in orders {
+            let id = &order.order_id;
+            let export = OrderExport {
+                items: items.remove(id).unwrap_or_default(),
+                payments: payments.remove(id).unwrap_or_default(),
+                refunds: refunds.remove(id).unwrap_or_default(),
+                reviews: reviews.remove(id).unwrap_or_default(),
+                order,
+            };
+
+            let mut chunk = if first { Vec::new() } else { b",".to_vec() };
+            chunk.extend(serde_json::to_vec(&export)?);
+            first = false;
+
+            if !send_chunk(tx, chunk).await {
+                return Ok(());
+            }
+            after = Some(export.order);
+        }
+
+        if last_page {
+            break;
+        }
+    }
+
+    send_chunk(tx, b"]}".to_vec()).await;
+    Ok(())
+}
+
+/// Rows of a batched query grouped by their order, keeping the query's order within each.
+fn by_order<T>(rows: impl IntoIterator<Item = (OrderId, T)>) -> HashMap<OrderId, Vec<T>> {
+    let mut grouped: HashMap<OrderId, Vec<T>> = HashMap::new();
+    for (order_id, row) in rows {
+        grouped.entry(order_id).or_default().push(row);
+    }
+    grouped
+}
+
+/// Sum of the items' prices, which coupon discounts apply to; freight is not discounted.
+fn items_subtotal(products: &[OrderProduct]) -> BigDecimal {
+    products.iter().fold(BigDecimal::zero(), |acc, product| {
+        acc + product.price.amount()
+    })
+}
+
+/// An order's items with its coupon's discount line, the total after the discount and what
+/// has been refunded.
+fn order_totals(
+    products: Vec<OrderProduct>,
+    coupon: Option<Coupon>,
+    refunds: &[Refund],
+) -> OrderProductResponse {
+    let discounts: Vec<OrderDiscount> = coupon
+        .map(|coupon| OrderDiscount {
+            amount: coupon.discount_for(&items_subtotal(&products)).into(),
+            code: coupon.code,
+            discount_type: coupon.discount_type,
+            currency: Money::CURRENCY,
+        })
+        .into_iter()
+        .collect();
+    let gross: Money = products
+        .iter()
+        .map(|product| product.price.clone() + &product.freight_value)
+        .sum();
+    let total_value = discounts
+        .iter()
+        .fold(gross, |acc, discount| acc - &discount.amount);
+
+    OrderProductResponse {
+        products,
+        discounts,
+        total_value,
+        refunded_value: refunds.iter().map(|refund| &refund.amount).sum(),
+        currency: Money::CURRENCY,
+    }
+}
+
+/// An order's financial summary from its sums, working out the coupon discount as
+/// [`order_totals`] does.
+fn order_summary(financials: OrderFinancials) -> OrderSummary {
+    let discounts: Vec<OrderDiscount> = match (
+        financials.coupon_code,
+        financials.coupon_discount_type,
+        financials.coupon_value,
+    ) {
+        (Some(code), Some(discount_type), Some(value)) => vec![OrderDiscount {
+            amount: coupon_discount(&discount_type, &value, &financials.items_subtotal).into(),
+            code,
+            discount_type,
+            currency: Money::CURRENCY,
+        }],
+        _ => Vec::new(),
+    };
+    let discount_total: Money = discounts.iter().map(|discount| &discount.amount).sum();
+    let order_total =
+        Money::from(&financials.items_subtotal + &financials.freight_total) - &discount_total;
+    let payments_total = Money::from(financials.payments_total);
+    let outstanding_balance = &order_total - &payments_total;
+
+    OrderSummary {
+        order_id: financials.order_id,
+        order_status: financials.order_status,
+        item_count: financials.item_count,
+        items_subtotal: financials.items_subtotal.into(),
+        freight_total: financials.freight_total.into(),
+        discounts,
+        discount_total,
+        order_total,
+        payment_count: financials.payment_count,
+        payments_total,
+        refunds_total: financials.refunds_total.into(),
+        payment_mismatch: !outstanding_balance.rounded().is_zero(),
+        outstanding_balance,
+        currency: Money::CURRENCY,
+    }
+}
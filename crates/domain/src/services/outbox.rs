@@ -0,0 +1,93 @@
+use std::sync::Arc;
+use tracing::warn;
+
+use crate::config::OutboxConfig;
+use crate::error::AppResult;
+use crate::events::EventPublisher;
+use crate::models::OutboxEvent;
+use crate::repositories::OutboxRepository;
+
+/// Publishes the events that database triggers record in the outbox: to the broker when one
+/// is configured, then to the webhook subscriptions. An event is marked published only after
+/// the broker accepted it, so none is lost; one published just before the relay stops may be
+/// published again.
+#[derive(Clone)]
+pub struct OutboxService {
+    repository: Arc<dyn OutboxRepository>,
+    publisher: Option<Arc<dyn EventPublisher>>,
+    config: OutboxConfig,
+}
+
+impl OutboxService {
+    /// How long a relay pass may take before its events can be claimed again.
+    const LEASE_SECONDS: i64 = 120;
+
+    pub fn new(
+        repository: Arc<dyn OutboxRepository>,
+        publisher: Option<Arc<dyn EventPublisher>>,
+        config: OutboxConfig,
+    ) -> Self {
+        Self {
+            repository,
+            publisher,
+            config,
+        }
+    }
+
+    pub fn config(&self) -> OutboxConfig {
+        self.config
+    }
+
+    /// Publishes the next batch of due events in order, returning how many were published.
+    /// Stops at the first failed publish: the events after it wait for its retry.
+    pub async fn relay(&self) -> AppResult<usize> {
+        let events = self
+            .repository
+            .claim_due_events(self.config.batch_size, Self::LEASE_SECONDS)
+            .await?;
+
+        for (published, event) in events.iter().enumerate() {
+            if let Err(e) = self.publish(event).await {
+                let attempts = event.attempts.max(0) as u32 + 1;
+                let retry_in = self.config.retry_delay(attempts);
+                warn!(
+                    "Publishing outbox event {} failed (attempt {}), retrying in {}s: {}",
+                    event.event_id,
+                    attempts,
+                    retry_in.as_secs(),
+                    e
+                );
+                self.repository
+                    .mark_publish_failed(event.event_id, &e, retry_in.as_secs() as i64)
+                    .await?;
+
+                let unattempted: Vec<i64> = events[published + 1..]
+                    .iter()
+                    .map(|event| event.event_id)
+                    .collect();
+                self.repository.release_events(&unattempted).await?;
+                return Ok(published);
+            }
+            self.repository.mark_published(event.event_id).await?;
+        }
+        Ok(events.len())
+    }
+
+    async fn publish(&self, event: &OutboxEvent) -> Result<(), String> {
+        match &self.publisher {
+            Some(publisher) => publisher.publish(event).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Deletes events published longer ago than the retention period, returning how many.
+    pub async fn purge_published(&self) -> AppResult<u64> {
+        if self.config.retention_days == 0 {
+            return Ok(0);
+        }
+        Ok(self
+            .repository
+            .delete_published_before(self.config.retention_days as i64)
+            .await?)
+    }
+}
@@ -0,0 +1,240 @@
+use std::sync::Arc;
+use tracing::{info, instrument};
+use validator::Validate;
+
+use crate::error::{AppError, AppResult, map_db_error};
+use crate::ids::OrderId;
+use crate::models::{
+    AuditAction, AuthorizePaymentDto, NewPaymentTransaction, OrderStatus,
+    PaymentNotificationResult, PaymentRequest, PaymentStatus, PaymentTransaction,
+};
+use crate::payments::PaymentProvider;
+use crate::repositories::{OrderRepository, OrderStatusTransition, PaymentTransactionRepository};
+
+use super::AuditService;
+
+/// The order status change a payment status change makes: a capture approves an order
+/// awaiting payment and a decline or void cancels it. Orders past `created` are left as
+/// they are.
+fn order_transition(status: PaymentStatus) -> Option<OrderStatusTransition> {
+    match status {
+        PaymentStatus::Captured => Some((OrderStatus::Created, OrderStatus::Approved)),
+        PaymentStatus::Failed | PaymentStatus::Canceled => {
+            Some((OrderStatus::Created, OrderStatus::Canceled))
+        }
+        PaymentStatus::Authorized | PaymentStatus::Refunded => None,
+    }
+}
+
+/// Payments through the configured [`PaymentProvider`]. Transactions follow
+/// [`PaymentStatus::can_become`], whether moved by a call to the provider or by one of its
+/// notifications, and move their order along as [`order_transition`] says.
+#[derive(Clone)]
+pub struct PaymentService {
+    provider: Arc<dyn PaymentProvider>,
+    repository: Arc<dyn PaymentTransactionRepository>,
+    orders: Arc<dyn OrderRepository>,
+    audit: AuditService,
+}
+
+impl PaymentService {
+    pub fn new(
+        provider: Arc<dyn PaymentProvider>,
+        repository: Arc<dyn PaymentTransactionRepository>,
+        orders: Arc<dyn OrderRepository>,
+        audit: AuditService,
+    ) -> Self {
+        Self {
+            provider,
+            repository,
+            orders,
+            audit,
+        }
+    }
+
+    /// Header the provider signs its notifications in.
+    pub fn signature_header(&self) -> &'static str {
+        self.provider.signature_header()
+    }
+
+    /// Asks the provider to authorize a payment for the order and records the transaction,
+    /// declined or not.
+    #[instrument(skip(self))]
+    pub async fn authorize(
+        &self,
+        order_id: &OrderId,
+        dto: AuthorizePaymentDto,
+        actor: &str,
+    ) -> AppResult<PaymentTransaction> {
+        dto.validate()?;
+        let order = self
+            .orders
+            .find_by_id(order_id)
+            .await?
+            .ok_or(AppError::NotFound)?;
+        if matches!(
+            order.order_status,
+            OrderStatus::Canceled | OrderStatus::Unavailable
+        ) {
+            return Err(AppError::PaymentNotAllowed(format!(
+                "Order {} is canceled or unavailable and cannot be paid",
+                order_id
+            )));
+        }
+
+        let authorization = self
+            .provider
+            .authorize(&PaymentRequest {
+                order_id: order_id.clone(),
+                payment_type: dto.payment_type,
+                payment_installments: dto.payment_installments,
+                amount: dto.amount.clone(),
+            })
+            .await?;
+        let transaction = self
+            .repository
+            .create(
+                NewPaymentTransaction {
+                    order_id: order_id.clone(),
+                    provider: self.provider.name().to_string(),
+                    provider_reference: authorization.reference,
+                    payment_type: dto.payment_type,
+                    payment_installments: dto.payment_installments,
+                    amount: dto.amount,
+                    status: authorization.status,
+                },
+                order_transition(authorization.status),
+            )
+            .await
+            .map_err(|e| map_db_error(e, "Payment transaction"))?;
+
+        self.audit
+            .record(
+                "payment_transaction",
+                &transaction.transaction_id.to_string(),
+                AuditAction::Create,
+                actor,
+                None,
+                Some(&transaction),
+            )
+            .await;
+
+        Ok(transaction)
+    }
+
+    /// Captures the whole amount of an authorized payment.
+    #[instrument(skip(self))]
+    pub async fn capture(&self, transaction_id: i64, actor: &str) -> AppResult<PaymentTransaction> {
+        let transaction = self
+            .repository
+            .find_by_id(transaction_id)
+            .await?
+            .ok_or(AppError::NotFound)?;
+        if transaction.status != PaymentStatus::Authorized {
+            return Err(AppError::PaymentNotAllowed(format!(
+                "Payment transaction {} is {}; only authorized payments can be captured",
+                transaction_id,
+                transaction.status.as_str()
+            )));
+        }
+
+        let status = self
+            .provider
+            .capture(&transaction.provider_reference, transaction.amount.amount())
+            .await?;
+        let (transaction, _) = self.transition(transaction, status, actor).await?;
+        Ok(transaction)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_order_transactions(
+        &self,
+        order_id: &OrderId,
+    ) -> AppResult<Vec<PaymentTransaction>> {
+        self.orders
+            .find_by_id(order_id)
+            .await?
+            .ok_or(AppError::NotFound)?;
+        Ok(self.repository.find_by_order(order_id).await?)
+    }
+
+    /// Applies a provider notification to its transaction. Notifications are answered with
+    /// success even when they change nothing, so the provider stops resending repeated or
+    /// out-of-order ones.
+    #[instrument(skip(self, body))]
+    pub async fn handle_notification(
+        &self,
+        signature: Option<&str>,
+        body: &[u8],
+    ) -> AppResult<PaymentNotificationResult> {
+        let notification = self.provider.parse_notification(signature, body)?;
+        let transaction = self
+            .repository
+            .find_by_reference(self.provider.name(), &notification.reference)
+            .await?
+            .ok_or(AppError::NotFound)?;
+
+        let (transaction, applied) = self
+            .transition(transaction, notification.status, self.provider.name())
+            .await?;
+        if !applied {
+            info!(
+                "Ignoring {} notification for payment transaction {}, which is {}",
+                notification.status.as_str(),
+                transaction.transaction_id,
+                transaction.status.as_str()
+            );
+        }
+
+        Ok(PaymentNotificationResult {
+            transaction_id: transaction.transaction_id,
+            status: transaction.status,
+            applied,
+        })
+    }
+
+    /// Moves the transaction to `status` when its current one allows it. Returns the
+    /// transaction as it now stands and whether it moved.
+    async fn transition(
+        &self,
+        transaction: PaymentTransaction,
+        status: PaymentStatus,
+        actor: &str,
+    ) -> AppResult<(PaymentTransaction, bool)> {
+        if !transaction.status.can_become(status) {
+            return Ok((transaction, false));
+        }
+
+        let Some(updated) = self
+            .repository
+            .update_status(
+                transaction.transaction_id,
+                transaction.status,
+                status,
+                order_transition(status),
+            )
+            .await?
+        else {
+            // Moved by a concurrent request or notification in the meantime.
+            let current = self
+                .repository
+                .find_by_id(transaction.transaction_id)
+                .await?
+                .ok_or(AppError::NotFound)?;
+            return Ok((current, false));
+        };
+
+        self.audit
+            .record(
+                "payment_transaction",
+                &updated.transaction_id.to_string(),
+                AuditAction::Update,
+                actor,
+                Some(&transaction),
+                Some(&updated),
+            )
+            .await;
+
+        Ok((updated, true))
+    }
+}
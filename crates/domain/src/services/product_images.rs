@@ -0,0 +1,212 @@
+use std::sync::Arc;
+use tracing::{instrument, warn};
+
+use crate::cache::{self, LookupCache, ResponseCache};
+use crate::config::ProductImageConfig;
+use crate::error::{AppError, AppResult, map_db_error};
+use crate::ids::ProductId;
+use crate::models::{AuditAction, DeleteReceipt, NewProductImage, ProductImage};
+use crate::repositories::ProductRepository;
+use crate::storage::{ObjectStorage, Upload};
+
+use super::{AuditService, validation_error};
+
+/// Image types accepted for products, with the extension their objects are stored under.
+const PRODUCT_IMAGE_TYPES: [(&str, &str); 4] = [
+    ("image/jpeg", "jpg"),
+    ("image/png", "png"),
+    ("image/webp", "webp"),
+    ("image/gif", "gif"),
+];
+
+/// Product images: the files go to object storage and the `product_images` table records
+/// where they are served from and in which order.
+#[derive(Clone)]
+pub struct ProductImageService {
+    storage: Arc<dyn ObjectStorage>,
+    products: Arc<dyn ProductRepository>,
+    audit: AuditService,
+    cache: ResponseCache,
+    lookups: LookupCache,
+    config: ProductImageConfig,
+}
+
+impl ProductImageService {
+    pub fn new(
+        storage: Arc<dyn ObjectStorage>,
+        products: Arc<dyn ProductRepository>,
+        audit: AuditService,
+        cache: ResponseCache,
+        lookups: LookupCache,
+        config: ProductImageConfig,
+    ) -> Self {
+        Self {
+            storage,
+            products,
+            audit,
+            cache,
+            lookups,
+            config,
+        }
+    }
+
+    /// Stores the files and adds them after the product's current images, in the order
+    /// uploaded. Every file is checked before any is stored. An image whose row can't be
+    /// written has its object removed again; images added before it are kept.
+    #[instrument(skip(self, uploads), fields(files = uploads.len()))]
+    pub async fn upload_images(
+        &self,
+        id: &ProductId,
+        uploads: Vec<Upload>,
+        actor: &str,
+    ) -> AppResult<Vec<ProductImage>> {
+        self.products
+            .find_by_id(id)
+            .await?
+            .ok_or(AppError::NotFound)?;
+
+        if uploads.is_empty() {
+            return Err(validation_error(
+                "files",
+                "required",
+                "Upload at least one image".to_string(),
+            ));
+        }
+        let extensions = uploads
+            .iter()
+            .map(|upload| self.check(upload))
+            .collect::<AppResult<Vec<_>>>()?;
+
+        let mut images = Vec::with_capacity(uploads.len());
+        for (upload, extension) in uploads.into_iter().zip(extensions) {
+            // Keys leave the product id out, as URLs built from them are public and ids may
+            // be served encoded.
+            let key = format!("products/{}.{}", uuid::Uuid::new_v4().simple(), extension);
+            let size_bytes = upload.body.len() as i64;
+            let url = self
+                .storage
+                .put(&key, &upload.content_type, upload.body)
+                .await?;
+
+            let new_image = NewProductImage {
+                storage_key: key.clone(),
+                url,
+                content_type: upload.content_type,
+                size_bytes,
+            };
+            let image = match self.products.create_image(id, new_image).await {
+                Ok(image) => image,
+                Err(e) => {
+                    self.remove_object(&key).await;
+                    return Err(map_db_error(e, "Product image"));
+                }
+            };
+
+            self.audit
+                .record(
+                    "product_image",
+                    &image.image_id.to_string(),
+                    AuditAction::Create,
+                    actor,
+                    None,
+                    Some(&image),
+                )
+                .await;
+            images.push(image);
+        }
+
+        self.cache.invalidate(cache::PRODUCTS).await;
+        self.lookups.invalidate_product(id).await;
+        Ok(images)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_images(&self, id: &ProductId) -> AppResult<Vec<ProductImage>> {
+        self.products
+            .find_by_id(id)
+            .await?
+            .ok_or(AppError::NotFound)?;
+        Ok(self.products.find_images(id).await?)
+    }
+
+    /// Deletes the image and then its object. An object that can't be removed is only
+    /// logged: the image is already gone from the product.
+    #[instrument(skip(self))]
+    pub async fn delete_image(
+        &self,
+        id: &ProductId,
+        image_id: i64,
+        actor: &str,
+    ) -> AppResult<DeleteReceipt> {
+        let (image, deleted_at) = self
+            .products
+            .delete_image(id, image_id)
+            .await?
+            .ok_or(AppError::NotFound)?;
+        self.cache.invalidate(cache::PRODUCTS).await;
+        self.lookups.invalidate_product(id).await;
+        self.remove_object(&image.storage_key).await;
+
+        self.audit
+            .record(
+                "product_image",
+                &image_id.to_string(),
+                AuditAction::Delete,
+                actor,
+                Some(&image),
+                None,
+            )
+            .await;
+
+        Ok(DeleteReceipt::new(image_id.to_string(), deleted_at))
+    }
+
+    /// The extension to store `upload` under, if it is an image we accept.
+    fn check(&self, upload: &Upload) -> AppResult<&'static str> {
+        let name = upload.file_name.as_deref().unwrap_or("file");
+        let Some(&(_, extension)) = PRODUCT_IMAGE_TYPES
+            .iter()
+            .find(|(content_type, _)| upload.content_type.eq_ignore_ascii_case(content_type))
+        else {
+            return Err(validation_error(
+                "files",
+                "content_type",
+                format!(
+                    "{} is {}; upload a JPEG, PNG, WebP or GIF image",
+                    name, upload.content_type
+                ),
+            ));
+        };
+        if upload.body.is_empty() {
+            return Err(validation_error(
+                "files",
+                "empty",
+                format!("{} is empty", name),
+            ));
+        }
+        if upload.body.len() > self.config.max_bytes {
+            return Err(validation_error(
+                "files",
+                "size",
+                format!(
+                    "{} is {} bytes; images may be at most {} bytes",
+                    name,
+                    upload.body.len(),
+                    self.config.max_bytes
+                ),
+            ));
+        }
+        Ok(extension)
+    }
+
+    async fn remove_object(&self, key: &str) {
+        if let Err(e) = self.storage.delete(key).await {
+            warn!(
+                "Could not remove {} from {} storage: {:?}",
+                key,
+                self.storage.name(),
+                e
+            );
+        }
+    }
+}
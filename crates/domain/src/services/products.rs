@@ -0,0 +1,243 @@
+use std::sync::Arc;
+use tokio_stream::wrappers::ReceiverStream;
+use tracing::{instrument, warn};
+use validator::Validate;
+
+use crate::cache::{self, LookupCache, ResponseCache};
+use crate::error::{AppError, AppResult, map_db_error};
+use crate::ids::ProductId;
+use crate::models::{
+    AuditAction, CreateProductDto, Customer, FilterValue, PaginatedResponse, Product,
+    ProductSearchQuery, RecommendedProduct, SparseRow, UpdateProductDto,
+};
+use crate::repositories::ProductRepository;
+
+use super::{AuditService, create_each, export_rows};
+
+#[derive(Clone)]
+pub struct ProductService {
+    repository: Arc<dyn ProductRepository>,
+    audit: AuditService,
+    cache: ResponseCache,
+    lookups: LookupCache,
+}
+
+impl ProductService {
+    pub fn new(
+        repository: Arc<dyn ProductRepository>,
+        audit: AuditService,
+        cache: ResponseCache,
+        lookups: LookupCache,
+    ) -> Self {
+        Self {
+            repository,
+            audit,
+            cache,
+            lookups,
+        }
+    }
+
+    #[instrument(skip(self))]
+    pub async fn create_product(&self, dto: CreateProductDto, actor: &str) -> AppResult<Product> {
+        let (id, dto) = Self::prepare(dto)?;
+        let product = self
+            .repository
+            .create(&id, dto)
+            .await
+            .map_err(|e| map_db_error(e, "Product"))?;
+        self.cache.invalidate(cache::PRODUCTS).await;
+        self.lookups.invalidate_category_values().await;
+
+        self.audit
+            .record(
+                "product",
+                product.product_id.as_str(),
+                AuditAction::Create,
+                actor,
+                None,
+                Some(&product),
+            )
+            .await;
+
+        Ok(product)
+    }
+
+    /// See [`CustomerService::create_customers`].
+    #[instrument(skip(self, dtos), fields(rows = dtos.len()))]
+    pub async fn create_products(
+        &self,
+        dtos: Vec<CreateProductDto>,
+        actor: &str,
+    ) -> AppResult<Vec<AppResult<Product>>> {
+        let products = create_each("Product", dtos, Self::prepare, |rows| {
+            self.repository.create_many(rows)
+        })
+        .await?;
+        self.cache.invalidate(cache::PRODUCTS).await;
+        self.lookups.invalidate_category_values().await;
+
+        self.audit
+            .record_created(
+                "product",
+                actor,
+                products
+                    .iter()
+                    .flatten()
+                    .map(|product| (product.product_id.as_str(), product)),
+            )
+            .await;
+
+        Ok(products)
+    }
+
+    /// Normalizes and validates a new product as `create_product` does, without inserting it.
+    pub fn prepare(mut dto: CreateProductDto) -> AppResult<(ProductId, CreateProductDto)> {
+        dto.validate()?;
+        let id = dto.product_id.take().unwrap_or_else(ProductId::generate);
+        Ok((id, dto))
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_product_by_id(&self, id: &ProductId) -> AppResult<Product> {
+        self.lookups
+            .product(id, || async { Ok(self.repository.find_by_id(id).await?) })
+            .await?
+            .ok_or(AppError::NotFound)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn update_product(
+        &self,
+        id: &ProductId,
+        dto: UpdateProductDto,
+        actor: &str,
+    ) -> AppResult<Product> {
+        dto.validate()?;
+
+        if dto.product_name.is_none()
+            && dto.description.is_none()
+            && dto.price.is_none()
+            && dto.active.is_none()
+        {
+            return Err(AppError::NoChangesToUpdate);
+        }
+
+        let before = self
+            .repository
+            .find_by_id(id)
+            .await?
+            .ok_or(AppError::NotFound)?;
+
+        let product = self
+            .repository
+            .update(id, dto)
+            .await?
+            .ok_or(AppError::NotFound)?;
+        self.cache.invalidate(cache::PRODUCTS).await;
+        self.lookups.invalidate_product(id).await;
+
+        self.audit
+            .record(
+                "product",
+                id.as_str(),
+                AuditAction::Update,
+                actor,
+                Some(&before),
+                Some(&product),
+            )
+            .await;
+
+        Ok(product)
+    }
+
+    /// Baseline recommendations for `customer`: the products customers in their state order
+    /// most, in the categories they have bought from, leaving out what they already bought.
+    /// Their other customer rows (same `customer_unique_id`) count as theirs.
+    #[instrument(skip(self, customer), fields(customer_id = %customer.customer_id))]
+    pub async fn get_recommendations(
+        &self,
+        customer: &Customer,
+        limit: i64,
+    ) -> AppResult<Vec<RecommendedProduct>> {
+        Ok(self
+            .repository
+            .find_recommended(
+                &customer.customer_unique_id,
+                &customer.customer_state,
+                limit,
+            )
+            .await?)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_products(
+        &self,
+        query: ProductSearchQuery,
+    ) -> AppResult<PaginatedResponse<Product>> {
+        query.validate()?;
+        let key = format!("{:?}", query);
+        self.cache
+            .get_or_load(cache::PRODUCTS, &key, || async {
+                let pagination = query.pagination();
+                let filter = query.filter();
+                let (_, _, page, page_size) = pagination.normalize();
+
+                let (products, total) = self
+                    .repository
+                    .find_all(&filter, &pagination, query.total_mode())
+                    .await?;
+
+                Ok(PaginatedResponse::with_total(
+                    products, total, page, page_size,
+                ))
+            })
+            .await
+    }
+
+    /// `get_products` with each row trimmed to the selected fields.
+    #[instrument(skip(self))]
+    pub async fn get_products_sparse(
+        &self,
+        query: ProductSearchQuery,
+        fields: &[&'static str],
+    ) -> AppResult<PaginatedResponse<SparseRow>> {
+        query.validate()?;
+        let key = format!("{:?}:{:?}", fields, query);
+        self.cache
+            .get_or_load(cache::PRODUCTS, &key, || async {
+                let pagination = query.pagination();
+                let filter = query.filter();
+                let (_, _, page, page_size) = pagination.normalize();
+
+                let (products, total) = self
+                    .repository
+                    .find_all_sparse(&filter, &pagination, fields, query.total_mode())
+                    .await?;
+
+                Ok(PaginatedResponse::with_total(
+                    products, total, page, page_size,
+                ))
+            })
+            .await
+    }
+
+    /// Streams the whole product catalog, one row at a time.
+    #[instrument(skip(self))]
+    pub fn export_all(&self) -> ReceiverStream<sqlx::Result<Product>> {
+        export_rows("Product", self.repository.clone(), |repository| {
+            repository.stream_all()
+        })
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_category_values(&self) -> AppResult<Vec<FilterValue>> {
+        self.lookups
+            .category_values(|| {
+                self.cache
+                    .get_or_load(cache::PRODUCTS, "categories", || async {
+                        Ok(self.repository.count_by_category().await?)
+                    })
+            })
+            .await
+    }
+}
@@ -0,0 +1,195 @@
+use chrono::Utc;
+use serde_json::json;
+use std::sync::Arc;
+use tracing::{info, instrument};
+
+use crate::config::RetentionConfig;
+use crate::error::{AppError, AppResult};
+use crate::models::{AuditAction, RetentionReport, RetentionRule, RetentionRuleReport};
+use crate::repositories::RetentionRepository;
+
+use super::{AuditService, CustomerService};
+
+/// Reviews deleted per statement by the retention job.
+const RETENTION_BATCH_SIZE: i64 = 1000;
+
+/// Applies the data retention rules of [`RetentionConfig`]: deletes old reviews and
+/// anonymizes inactive customers through [`CustomerService::anonymize_customer`], so each
+/// anonymization is audited like a manual one. Run by the scheduler; `GET /admin/retention`
+/// reports what a run would do without changing anything.
+#[derive(Clone)]
+pub struct RetentionService {
+    repository: Arc<dyn RetentionRepository>,
+    customers: CustomerService,
+    audit: AuditService,
+    config: RetentionConfig,
+}
+
+impl RetentionService {
+    /// Actor recorded in the audit log for changes made by the retention rules.
+    pub const ACTOR: &'static str = "retention-policy";
+
+    pub fn new(
+        repository: Arc<dyn RetentionRepository>,
+        customers: CustomerService,
+        audit: AuditService,
+        config: RetentionConfig,
+    ) -> Self {
+        Self {
+            repository,
+            customers,
+            audit,
+            config,
+        }
+    }
+
+    /// Counts what each enabled rule would delete or anonymize right now.
+    #[instrument(skip(self))]
+    pub async fn dry_run(&self) -> AppResult<RetentionReport> {
+        let now = Utc::now().naive_utc();
+        let mut reports = Vec::new();
+        for (rule, years) in self.rules() {
+            let cutoff = Self::cutoff(now, years);
+            let affected = match (rule, cutoff) {
+                (_, None) => 0,
+                (RetentionRule::DeleteOldReviews, Some(cutoff)) => {
+                    self.repository.count_reviews_created_before(cutoff).await?
+                }
+                (RetentionRule::AnonymizeInactiveCustomers, Some(cutoff)) => {
+                    self.repository.count_inactive_customers(cutoff).await?
+                }
+            };
+            reports.push(Self::rule_report(
+                rule,
+                years,
+                cutoff,
+                affected.max(0) as u64,
+            ));
+        }
+
+        Ok(RetentionReport {
+            dry_run: true,
+            generated_at: now,
+            rules: reports,
+        })
+    }
+
+    /// Applies every enabled rule, reviews first so an anonymized customer's reviews that
+    /// are due go too.
+    #[instrument(skip(self))]
+    pub async fn apply(&self) -> AppResult<RetentionReport> {
+        let now = Utc::now().naive_utc();
+        let mut reports = Vec::new();
+        for (rule, years) in self.rules() {
+            let cutoff = Self::cutoff(now, years);
+            let affected = match (rule, cutoff) {
+                (_, None) => 0,
+                (RetentionRule::DeleteOldReviews, Some(cutoff)) => {
+                    self.delete_old_reviews(cutoff).await?
+                }
+                (RetentionRule::AnonymizeInactiveCustomers, Some(cutoff)) => {
+                    self.anonymize_inactive_customers(cutoff).await?
+                }
+            };
+            reports.push(Self::rule_report(rule, years, cutoff, affected));
+        }
+
+        Ok(RetentionReport {
+            dry_run: false,
+            generated_at: now,
+            rules: reports,
+        })
+    }
+
+    fn rules(&self) -> [(RetentionRule, u32); 2] {
+        [
+            (RetentionRule::DeleteOldReviews, self.config.review_years),
+            (
+                RetentionRule::AnonymizeInactiveCustomers,
+                self.config.inactive_customer_years,
+            ),
+        ]
+    }
+
+    /// `None` for a disabled rule, or one reaching back before the calendar starts.
+    fn cutoff(now: chrono::NaiveDateTime, years: u32) -> Option<chrono::NaiveDateTime> {
+        if years == 0 {
+            return None;
+        }
+        now.checked_sub_months(chrono::Months::new(years.saturating_mul(12)))
+    }
+
+    fn rule_report(
+        rule: RetentionRule,
+        years: u32,
+        cutoff: Option<chrono::NaiveDateTime>,
+        affected: u64,
+    ) -> RetentionRuleReport {
+        RetentionRuleReport {
+            rule,
+            retention_years: (years > 0).then_some(years),
+            cutoff,
+            affected,
+        }
+    }
+
+    async fn delete_old_reviews(&self, before: chrono::NaiveDateTime) -> AppResult<u64> {
+        let mut deleted = 0;
+        loop {
+            let batch = self
+                .repository
+                .delete_reviews_created_before(before, RETENTION_BATCH_SIZE)
+                .await?;
+            deleted += batch;
+            if batch == 0 {
+                break;
+            }
+        }
+
+        if deleted > 0 {
+            info!("Deleted {} reviews written before {}", deleted, before);
+            self.audit
+                .record_event(
+                    "review",
+                    "retention",
+                    AuditAction::Delete,
+                    Self::ACTOR,
+                    json!({ "created_before": before, "deleted": deleted }),
+                )
+                .await;
+        }
+        Ok(deleted)
+    }
+
+    async fn anonymize_inactive_customers(&self, before: chrono::NaiveDateTime) -> AppResult<u64> {
+        let mut anonymized = 0;
+        loop {
+            let ids = self
+                .repository
+                .find_inactive_customers(before, RETENTION_BATCH_SIZE)
+                .await?;
+            if ids.is_empty() {
+                break;
+            }
+            for id in &ids {
+                match self.customers.anonymize_customer(id, Self::ACTOR).await {
+                    Ok(_) => anonymized += 1,
+                    // Deleted since it was found
+                    Err(AppError::NotFound) => {}
+                    Err(e) => return Err(e),
+                }
+            }
+            if ids.len() < RETENTION_BATCH_SIZE as usize {
+                break;
+            }
+        }
+
+        if anonymized > 0 {
+            info!(
+                "Anonymized {} customers inactive since {}",
+                anonymized, before
+            );
+        }
+        Ok(anonymized)
+    }
+}
@@ -0,0 +1,78 @@
+use std::sync::Arc;
+use tracing::instrument;
+
+use crate::error::AppResult;
+use crate::models::{PaginatedResponse, ReviewQuery, SentimentReview};
+use crate::repositories::ReviewSentimentRepository;
+use crate::sentiment::SentimentProvider;
+
+/// Reviews tagged per batch by the sentiment tagging job.
+const SENTIMENT_BATCH_SIZE: i64 = 500;
+
+/// Review comments tagged with their sentiment, for CX triage.
+#[derive(Clone)]
+pub struct ReviewSentimentService {
+    repository: Arc<dyn ReviewSentimentRepository>,
+    provider: Arc<dyn SentimentProvider>,
+}
+
+impl ReviewSentimentService {
+    pub fn new(
+        repository: Arc<dyn ReviewSentimentRepository>,
+        provider: Arc<dyn SentimentProvider>,
+    ) -> Self {
+        Self {
+            repository,
+            provider,
+        }
+    }
+
+    /// Tags every review comment that has no sentiment yet, a batch at a time. Returns how
+    /// many were tagged.
+    #[instrument(skip(self))]
+    pub async fn tag_untagged(&self) -> AppResult<u64> {
+        let mut tagged = 0;
+        loop {
+            let reviews = self.repository.find_untagged(SENTIMENT_BATCH_SIZE).await?;
+            if reviews.is_empty() {
+                break;
+            }
+
+            let mut sentiments = Vec::with_capacity(reviews.len());
+            for review in reviews {
+                let sentiment = self
+                    .provider
+                    .classify(&review.review_comment_message)
+                    .await?;
+                sentiments.push((review.review_id, sentiment));
+            }
+
+            let saved = self.repository.save(&sentiments).await?;
+            // Nothing stored means the batch's reviews vanished meanwhile; don't spin on them
+            if saved == 0 {
+                break;
+            }
+            tagged += saved;
+        }
+        Ok(tagged)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_reviews(
+        &self,
+        query: ReviewQuery,
+    ) -> AppResult<PaginatedResponse<SentimentReview>> {
+        let pagination = query.pagination();
+        let (_, _, page, page_size) = pagination.normalize();
+        let (reviews, total_count) = self
+            .repository
+            .find_reviews(query.sentiment, &pagination)
+            .await?;
+        Ok(PaginatedResponse::new(
+            reviews,
+            total_count,
+            page,
+            page_size,
+        ))
+    }
+}
@@ -0,0 +1,208 @@
+use std::sync::Arc;
+use tracing::instrument;
+use validator::Validate;
+
+use crate::cities::{fold_city, tidy_city};
+use crate::config::ScorecardConfig;
+use crate::error::{AppError, AppResult, map_db_error};
+use crate::ids::SellerId;
+use crate::models::{
+    AuditAction, CreateSellerDto, PaginatedResponse, ScorecardEntry, ScorecardMetric, Seller,
+    SellerBadgeThreshold, SellerGrade, SellerScorecard, SellerSearchQuery, SparseRow,
+};
+use crate::repositories::SellerRepository;
+
+use super::{AuditService, create_each};
+
+#[derive(Clone)]
+pub struct SellerService {
+    repository: Arc<dyn SellerRepository>,
+    audit: AuditService,
+    scorecard: ScorecardConfig,
+}
+
+impl SellerService {
+    pub fn new(
+        repository: Arc<dyn SellerRepository>,
+        audit: AuditService,
+        scorecard: ScorecardConfig,
+    ) -> Self {
+        Self {
+            repository,
+            audit,
+            scorecard,
+        }
+    }
+
+    #[instrument(skip(self))]
+    pub async fn create_seller(&self, dto: CreateSellerDto, actor: &str) -> AppResult<Seller> {
+        let (id, dto, canonical_city) = Self::prepare(dto)?;
+        let seller = self
+            .repository
+            .create(&id, dto, &canonical_city)
+            .await
+            .map_err(|e| map_db_error(e, "Seller"))?;
+
+        self.audit
+            .record(
+                "seller",
+                seller.seller_id.as_str(),
+                AuditAction::Create,
+                actor,
+                None,
+                Some(&seller),
+            )
+            .await;
+
+        Ok(seller)
+    }
+
+    /// See [`CustomerService::create_customers`].
+    #[instrument(skip(self, dtos), fields(rows = dtos.len()))]
+    pub async fn create_sellers(
+        &self,
+        dtos: Vec<CreateSellerDto>,
+        actor: &str,
+    ) -> AppResult<Vec<AppResult<Seller>>> {
+        let sellers = create_each("Seller", dtos, Self::prepare, |rows| {
+            self.repository.create_many(rows)
+        })
+        .await?;
+
+        self.audit
+            .record_created(
+                "seller",
+                actor,
+                sellers
+                    .iter()
+                    .flatten()
+                    .map(|seller| (seller.seller_id.as_str(), seller)),
+            )
+            .await;
+
+        Ok(sellers)
+    }
+
+    /// Normalizes and validates a new seller as `create_seller` does, without inserting it.
+    pub fn prepare(mut dto: CreateSellerDto) -> AppResult<(SellerId, CreateSellerDto, String)> {
+        dto.seller_city = tidy_city(&dto.seller_city);
+        dto.validate()?;
+        let id = dto.seller_id.take().unwrap_or_else(SellerId::generate);
+        let canonical_city = fold_city(&dto.seller_city);
+        Ok((id, dto, canonical_city))
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_seller_by_id(&self, id: &SellerId) -> AppResult<Seller> {
+        match self.repository.find_by_id(id).await? {
+            Some(seller) => Ok(seller),
+            None => Err(AppError::NotFound),
+        }
+    }
+
+    /// Grades a seller's on-time shipments, cancellations and reviews against the
+    /// [`ScorecardConfig`] targets.
+    #[instrument(skip(self))]
+    pub async fn get_scorecard(&self, id: &SellerId) -> AppResult<SellerScorecard> {
+        let seller = self.get_seller_by_id(id).await?;
+        let counts = self.repository.find_metrics(id).await?;
+        let config = self.scorecard;
+
+        let rate = |count: i64, sample: i64| {
+            (sample > 0).then(|| (count as f64 / sample as f64 * 10_000.0).round() / 10_000.0)
+        };
+        let metrics = vec![
+            ScorecardEntry::new(
+                ScorecardMetric::OnTimeShipmentRate,
+                rate(counts.on_time_count, counts.shipped_count),
+                "gte",
+                config.min_on_time_rate,
+                counts.shipped_count,
+            ),
+            ScorecardEntry::new(
+                ScorecardMetric::CancellationRate,
+                rate(counts.canceled_count, counts.order_count),
+                "lte",
+                config.max_cancellation_rate,
+                counts.order_count,
+            ),
+            ScorecardEntry::new(
+                ScorecardMetric::ReviewAverage,
+                counts
+                    .review_average
+                    .map(|average| (average * 100.0).round() / 100.0),
+                "gte",
+                config.min_review_average,
+                counts.review_count,
+            ),
+        ];
+
+        let missed = metrics
+            .iter()
+            .filter(|entry| entry.meets_threshold == Some(false))
+            .count();
+        let grade = (counts.order_count >= config.min_orders.max(1)).then_some(match missed {
+            0 => SellerGrade::A,
+            1 => SellerGrade::B,
+            2 => SellerGrade::C,
+            _ => SellerGrade::D,
+        });
+
+        Ok(SellerScorecard {
+            seller_id: seller.seller_id,
+            order_count: counts.order_count,
+            grade,
+            metrics,
+        })
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_sellers(
+        &self,
+        query: SellerSearchQuery,
+    ) -> AppResult<PaginatedResponse<Seller>> {
+        let pagination = query.pagination();
+        let filter = query.filter();
+        let (_, _, page, page_size) = pagination.normalize();
+
+        let (sellers, total) = self
+            .repository
+            .find_all(&filter, &pagination, query.total_mode())
+            .await?;
+
+        Ok(PaginatedResponse::with_total(
+            sellers, total, page, page_size,
+        ))
+    }
+
+    /// `get_sellers` with each row trimmed to the selected fields.
+    #[instrument(skip(self))]
+    pub async fn get_sellers_sparse(
+        &self,
+        query: SellerSearchQuery,
+        fields: &[&'static str],
+    ) -> AppResult<PaginatedResponse<SparseRow>> {
+        let pagination = query.pagination();
+        let filter = query.filter();
+        let (_, _, page, page_size) = pagination.normalize();
+
+        let (sellers, total) = self
+            .repository
+            .find_all_sparse(&filter, &pagination, fields, query.total_mode())
+            .await?;
+
+        Ok(PaginatedResponse::with_total(
+            sellers, total, page, page_size,
+        ))
+    }
+
+    #[instrument(skip(self))]
+    pub async fn refresh_badges(&self) -> AppResult<u64> {
+        Ok(self.repository.refresh_badges().await?)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_badge_thresholds(&self) -> AppResult<Vec<SellerBadgeThreshold>> {
+        Ok(self.repository.find_badge_thresholds().await?)
+    }
+}
@@ -0,0 +1,223 @@
+use serde_json::json;
+use std::sync::Arc;
+use tracing::instrument;
+
+use crate::cache::{self, ResponseCache};
+use crate::carriers::{CarrierProvider, cep_for_prefix};
+use crate::config::AmendmentConfig;
+use crate::error::{AppError, AppResult};
+use crate::ids::OrderId;
+use crate::models::{
+    AuditAction, FreightQuoteDto, ItemFreightQuote, NewOrderAmendment, OrderAmendment,
+    OrderFreightQuote, OrderItem, OrderItemOrigin, Parcel,
+};
+use crate::money::Money;
+use crate::repositories::{OrderRepository, ProductRepository};
+
+use super::{AuditService, LOCKED_ORDER_STATUSES};
+
+/// Freight quotes from the configured carrier for the items of an order.
+#[derive(Clone)]
+pub struct ShippingService {
+    carrier: Arc<dyn CarrierProvider>,
+    orders: Arc<dyn OrderRepository>,
+    products: Arc<dyn ProductRepository>,
+    audit: AuditService,
+    amendments: AmendmentConfig,
+    cache: ResponseCache,
+}
+
+impl ShippingService {
+    pub fn new(
+        carrier: Arc<dyn CarrierProvider>,
+        orders: Arc<dyn OrderRepository>,
+        products: Arc<dyn ProductRepository>,
+        audit: AuditService,
+        amendments: AmendmentConfig,
+        cache: ResponseCache,
+    ) -> Self {
+        Self {
+            carrier,
+            orders,
+            products,
+            audit,
+            amendments,
+            cache,
+        }
+    }
+
+    /// Quotes every item of the order, shipped from its seller's CEP to the order's
+    /// destination, and selects `service` (or the cheapest quote) for each. With `apply`, the
+    /// selected quotes become the items' freight, recorded as an amendment; that is refused
+    /// once the order has been handed to the carrier.
+    #[instrument(skip(self))]
+    pub async fn quote_order(
+        &self,
+        order_id: &OrderId,
+        dto: FreightQuoteDto,
+        actor: &str,
+    ) -> AppResult<OrderFreightQuote> {
+        let order = self
+            .orders
+            .find_by_id(order_id)
+            .await?
+            .ok_or(AppError::NotFound)?;
+        if dto.apply
+            && (order.order_delivered_carrier_date.is_some()
+                || LOCKED_ORDER_STATUSES.contains(&order.order_status))
+        {
+            return Err(AppError::AmendmentNotAllowed(format!(
+                "Order {} has already been handed to the carrier",
+                order.order_id
+            )));
+        }
+
+        let destination = self
+            .orders
+            .find_destination_zip_code_prefix(order_id)
+            .await?
+            .ok_or(AppError::NotFound)?;
+        let origins = self.orders.find_item_origins(order_id).await?;
+
+        let mut items = Vec::with_capacity(origins.len());
+        for origin in &origins {
+            let product = self
+                .products
+                .find_by_id(&origin.item.product_id)
+                .await?
+                .ok_or(AppError::NotFound)?;
+            let parcel = Parcel {
+                origin_cep: cep_for_prefix(&origin.seller_zip_code_prefix),
+                destination_cep: cep_for_prefix(&destination),
+                weight_g: product.product_weight_g,
+                length_cm: product.product_length_cm,
+                height_cm: product.product_height_cm,
+                width_cm: product.product_width_cm,
+            };
+            let quotes = self.carrier.quote(&parcel).await?;
+            let selected = match &dto.service {
+                Some(service) => quotes.iter().find(|quote| quote.service == *service),
+                None => quotes.iter().min_by(|a, b| a.price.cmp(&b.price)),
+            }
+            .cloned();
+
+            items.push(ItemFreightQuote {
+                order_item_id: origin.item.order_item_id,
+                product_id: origin.item.product_id.clone(),
+                seller_id: origin.item.seller_id.clone(),
+                freight_value: origin.item.freight_value.clone(),
+                quotes,
+                selected,
+                currency: Money::CURRENCY,
+            });
+        }
+
+        let total_freight = items
+            .iter()
+            .map(|item| item.selected.as_ref().map(|quote| &quote.price))
+            .collect::<Option<Vec<_>>>()
+            .map(|prices| prices.into_iter().sum());
+
+        let amendment = if dto.apply {
+            self.apply_quotes(order_id, &origins, &items, actor).await?
+        } else {
+            None
+        };
+
+        Ok(OrderFreightQuote {
+            order_id: order.order_id,
+            destination_zip_code_prefix: destination,
+            items,
+            total_freight,
+            amendment,
+            currency: Money::CURRENCY,
+        })
+    }
+
+    /// Sets each item's freight to its selected quote. Returns `None` when every item already
+    /// had that freight.
+    async fn apply_quotes(
+        &self,
+        order_id: &OrderId,
+        origins: &[OrderItemOrigin],
+        items: &[ItemFreightQuote],
+        actor: &str,
+    ) -> AppResult<Option<OrderAmendment>> {
+        let mut updated = Vec::new();
+        let mut changed = Vec::new();
+        for (origin, quoted) in origins.iter().zip(items) {
+            let Some(quote) = &quoted.selected else {
+                let mut errors = validator::ValidationErrors::new();
+                errors.add(
+                    "service",
+                    validator::ValidationError::new("unavailable_service").with_message(
+                        format!(
+                            "{} offers no such service for item {}",
+                            self.carrier.name(),
+                            quoted.order_item_id
+                        )
+                        .into(),
+                    ),
+                );
+                return Err(errors.into());
+            };
+            if origin.item.freight_value == quote.price {
+                continue;
+            }
+
+            let mut item = origin.item.clone();
+            item.freight_value = quote.price.clone();
+            changed.push(json!({
+                "order_item_id": item.order_item_id,
+                "service": quote.service,
+                "freight_value": { "from": origin.item.freight_value, "to": item.freight_value },
+            }));
+            updated.push((item.product_id.clone(), item));
+        }
+        if updated.is_empty() {
+            return Ok(None);
+        }
+
+        let previous_items: Vec<&OrderItem> = origins.iter().map(|origin| &origin.item).collect();
+        let next_items: Vec<&OrderItem> = origins
+            .iter()
+            .map(|origin| {
+                updated
+                    .iter()
+                    .find(|(_, item)| item.order_item_id == origin.item.order_item_id)
+                    .map(|(_, item)| item)
+                    .unwrap_or(&origin.item)
+            })
+            .collect();
+        let (previous_freight, previous_tax) = self.amendments.order_charges(&previous_items);
+        let (new_freight, new_tax) = self.amendments.order_charges(&next_items);
+
+        let changes = json!({
+            "freight_quote": { "carrier": self.carrier.name(), "items": changed },
+        });
+        let amendment = NewOrderAmendment {
+            changes: changes.clone(),
+            previous_freight,
+            new_freight,
+            previous_tax,
+            new_tax,
+        };
+        let recorded = self
+            .orders
+            .apply_amendment(order_id, actor, None, &updated, amendment)
+            .await?;
+        self.cache.invalidate(cache::TODAY_STATS).await;
+
+        self.audit
+            .record_event(
+                "order",
+                order_id.as_str(),
+                AuditAction::Update,
+                actor,
+                changes,
+            )
+            .await;
+
+        Ok(Some(recorded))
+    }
+}
@@ -0,0 +1,96 @@
+use std::sync::Arc;
+use tracing::instrument;
+
+use crate::embeddings::{EMBEDDING_DIMENSIONS, Embedder};
+use crate::error::{AppError, AppResult};
+use crate::ids::ProductId;
+use crate::models::SimilarProduct;
+use crate::repositories::EmbeddingRepository;
+
+#[derive(Clone)]
+pub struct SimilarityService {
+    repository: Arc<dyn EmbeddingRepository>,
+    embedder: Arc<dyn Embedder>,
+    enabled: bool,
+}
+
+impl SimilarityService {
+    pub fn new(
+        repository: Arc<dyn EmbeddingRepository>,
+        embedder: Arc<dyn Embedder>,
+        enabled: bool,
+    ) -> Self {
+        Self {
+            repository,
+            embedder,
+            enabled,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn ensure_enabled(&self) -> AppResult<()> {
+        if self.enabled {
+            Ok(())
+        } else {
+            Err(AppError::FeatureDisabled("Product similarity"))
+        }
+    }
+
+    #[instrument(skip(self))]
+    pub async fn refresh_embedding(&self, product_id: &ProductId) -> AppResult<()> {
+        self.ensure_enabled()?;
+
+        let text = self
+            .repository
+            .find_embedding_source(product_id)
+            .await?
+            .ok_or(AppError::NotFound)?;
+
+        if self.embedder.dimensions() != EMBEDDING_DIMENSIONS {
+            return Err(AppError::ConfigError(format!(
+                "Embedder produces {} dimensions, the embeddings table expects {}",
+                self.embedder.dimensions(),
+                EMBEDDING_DIMENSIONS
+            )));
+        }
+
+        let embedding = self.embedder.embed(&text).await?;
+        self.repository.upsert(product_id, &embedding).await?;
+        Ok(())
+    }
+
+    /// Computes embeddings for up to `batch_size` products that don't have one yet.
+    #[instrument(skip(self))]
+    pub async fn refresh_missing_embeddings(&self, batch_size: i64) -> AppResult<usize> {
+        self.ensure_enabled()?;
+
+        let product_ids = self
+            .repository
+            .find_products_without_embedding(batch_size)
+            .await?;
+
+        for product_id in &product_ids {
+            self.refresh_embedding(product_id).await?;
+        }
+
+        Ok(product_ids.len())
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_similar_products(
+        &self,
+        product_id: &ProductId,
+        limit: i64,
+    ) -> AppResult<Vec<SimilarProduct>> {
+        self.ensure_enabled()?;
+
+        if !self.repository.has_embedding(product_id).await? {
+            self.refresh_embedding(product_id).await?;
+        }
+
+        Ok(self.repository.find_similar(product_id, limit).await?)
+    }
+}
@@ -0,0 +1,194 @@
+use std::sync::Arc;
+use tracing::instrument;
+use validator::Validate;
+
+use crate::cache::{self, ResponseCache};
+use crate::config::SupportConfig;
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    AuditAction, CreateSupportCaseDto, CreateSupportMessageDto, DeleteReceipt, PaginatedResponse,
+    SupportCase, SupportCaseDetail, SupportCaseSearchQuery, SupportCaseVolume, SupportMessage,
+    UpdateSupportCaseDto,
+};
+use crate::repositories::SupportRepository;
+
+use super::AuditService;
+
+#[derive(Clone)]
+pub struct SupportService {
+    repository: Arc<dyn SupportRepository>,
+    audit: AuditService,
+    sla: SupportConfig,
+    cache: ResponseCache,
+}
+
+impl SupportService {
+    pub fn new(
+        repository: Arc<dyn SupportRepository>,
+        audit: AuditService,
+        sla: SupportConfig,
+        cache: ResponseCache,
+    ) -> Self {
+        Self {
+            repository,
+            audit,
+            sla,
+            cache,
+        }
+    }
+
+    #[instrument(skip(self))]
+    pub async fn create_case(
+        &self,
+        dto: CreateSupportCaseDto,
+        actor: &str,
+    ) -> AppResult<SupportCase> {
+        dto.validate()?;
+        let case = self
+            .repository
+            .create(
+                dto,
+                actor,
+                self.sla.first_response_hours,
+                self.sla.resolution_hours,
+            )
+            .await?
+            .ok_or(AppError::NotFound)?;
+        self.cache.invalidate(cache::SUPPORT_ANALYTICS).await;
+
+        self.audit
+            .record(
+                "support_case",
+                &case.case_id.to_string(),
+                AuditAction::Create,
+                actor,
+                None,
+                Some(&case),
+            )
+            .await;
+
+        Ok(case)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_case(&self, case_id: i64) -> AppResult<SupportCaseDetail> {
+        let case = self
+            .repository
+            .find_by_id(case_id)
+            .await?
+            .ok_or(AppError::NotFound)?;
+        let messages = self.repository.find_messages(case_id).await?;
+
+        Ok(SupportCaseDetail { case, messages })
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_cases(
+        &self,
+        query: SupportCaseSearchQuery,
+    ) -> AppResult<PaginatedResponse<SupportCase>> {
+        let pagination = query.pagination();
+        let filter = query.filter();
+        let (_, _, page, page_size) = pagination.normalize();
+
+        let (cases, total_count) = self.repository.find_all(&filter, &pagination).await?;
+
+        Ok(PaginatedResponse::new(cases, total_count, page, page_size))
+    }
+
+    #[instrument(skip(self))]
+    pub async fn update_case(
+        &self,
+        case_id: i64,
+        dto: UpdateSupportCaseDto,
+        actor: &str,
+    ) -> AppResult<SupportCase> {
+        dto.validate()?;
+        if dto.category.is_none() && dto.status.is_none() {
+            return Err(AppError::NoChangesToUpdate);
+        }
+
+        let before = self
+            .repository
+            .find_by_id(case_id)
+            .await?
+            .ok_or(AppError::NotFound)?;
+
+        let case = self
+            .repository
+            .update(case_id, dto)
+            .await?
+            .ok_or(AppError::NotFound)?;
+        self.cache.invalidate(cache::SUPPORT_ANALYTICS).await;
+
+        self.audit
+            .record(
+                "support_case",
+                &case_id.to_string(),
+                AuditAction::Update,
+                actor,
+                Some(&before),
+                Some(&case),
+            )
+            .await;
+
+        Ok(case)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn delete_case(&self, case_id: i64, actor: &str) -> AppResult<DeleteReceipt> {
+        let before = self
+            .repository
+            .find_by_id(case_id)
+            .await?
+            .ok_or(AppError::NotFound)?;
+
+        let deleted_at = self
+            .repository
+            .delete(case_id)
+            .await?
+            .ok_or(AppError::NotFound)?;
+        self.cache.invalidate(cache::SUPPORT_ANALYTICS).await;
+
+        self.audit
+            .record(
+                "support_case",
+                &case_id.to_string(),
+                AuditAction::Delete,
+                actor,
+                Some(&before),
+                None,
+            )
+            .await;
+
+        Ok(DeleteReceipt::new(case_id.to_string(), deleted_at))
+    }
+
+    #[instrument(skip(self))]
+    pub async fn add_message(
+        &self,
+        case_id: i64,
+        dto: CreateSupportMessageDto,
+        actor: &str,
+    ) -> AppResult<SupportMessage> {
+        dto.validate()?;
+        let message = self
+            .repository
+            .add_message(case_id, dto, actor)
+            .await?
+            .ok_or(AppError::NotFound)?;
+        // The first agent reply sets the case's first response time.
+        self.cache.invalidate(cache::SUPPORT_ANALYTICS).await;
+
+        Ok(message)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_volume_by_category(&self) -> AppResult<Vec<SupportCaseVolume>> {
+        self.cache
+            .get_or_load(cache::SUPPORT_ANALYTICS, "by_category", || async {
+                Ok(self.repository.volume_by_category().await?)
+            })
+            .await
+    }
+}
@@ -0,0 +1,258 @@
+use std::sync::Arc;
+use tracing::{instrument, warn};
+use validator::Validate;
+
+use crate::config::WebhookConfig;
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    AuditAction, CreateWebhookDto, CreatedWebhook, DeleteReceipt, PaginatedResponse,
+    PaginationParams, PendingWebhookDelivery, WebhookDelivery, WebhookDeliveryQuery,
+    WebhookSubscription,
+};
+use crate::repositories::WebhookRepository;
+
+use super::AuditService;
+
+/// Webhook subscriptions and their delivery log. Deliveries are queued by the outbox relay
+/// when it publishes an event, and sent by the delivery worker, which leases and settles them through this service.
+/// The worker applies each subscription's state filter and payload template, through
+/// [`PendingWebhookDelivery::data`], so every backend delivers alike.
+#[derive(Clone)]
+pub struct WebhookService {
+    repository: Arc<dyn WebhookRepository>,
+    audit: AuditService,
+    config: WebhookConfig,
+}
+
+impl WebhookService {
+    /// Deliveries leased per worker pass.
+    const CLAIM_BATCH_SIZE: i64 = 50;
+
+    pub fn new(
+        repository: Arc<dyn WebhookRepository>,
+        audit: AuditService,
+        config: WebhookConfig,
+    ) -> Self {
+        Self {
+            repository,
+            audit,
+            config,
+        }
+    }
+
+    pub fn config(&self) -> WebhookConfig {
+        self.config
+    }
+
+    #[instrument(skip(self, dto), fields(url = %dto.url))]
+    pub async fn create_subscription(
+        &self,
+        dto: CreateWebhookDto,
+        actor: &str,
+    ) -> AppResult<CreatedWebhook> {
+        dto.validate()?;
+        let mut events: Vec<String> = dto
+            .events
+            .iter()
+            .map(|event| event.as_str().to_string())
+            .collect();
+        events.sort();
+        events.dedup();
+        let customer_states: Option<Vec<String>> = dto.customer_states.map(|states| {
+            let mut states: Vec<String> = states
+                .iter()
+                .map(|state| state.as_str().to_string())
+                .collect();
+            states.sort();
+            states.dedup();
+            states
+        });
+        let secret = dto
+            .secret
+            .unwrap_or_else(|| format!("whsec_{}", uuid::Uuid::new_v4().simple()));
+
+        let subscription = self
+            .repository
+            .create_subscription(
+                &dto.url,
+                &events,
+                customer_states.as_deref(),
+                dto.payload_template,
+                &secret,
+                actor,
+            )
+            .await?;
+
+        self.audit
+            .record(
+                "webhook_subscription",
+                &subscription.subscription_id.to_string(),
+                AuditAction::Create,
+                actor,
+                None,
+                Some(&subscription),
+            )
+            .await;
+
+        Ok(CreatedWebhook {
+            secret: subscription.secret.clone(),
+            subscription,
+        })
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_subscriptions(
+        &self,
+        pagination: PaginationParams,
+    ) -> AppResult<PaginatedResponse<WebhookSubscription>> {
+        let (_, _, page, page_size) = pagination.normalize();
+        let (subscriptions, total_count) = self.repository.find_subscriptions(&pagination).await?;
+        Ok(PaginatedResponse::new(
+            subscriptions,
+            total_count,
+            page,
+            page_size,
+        ))
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_subscription(&self, subscription_id: i64) -> AppResult<WebhookSubscription> {
+        self.repository
+            .find_subscription(subscription_id)
+            .await?
+            .ok_or(AppError::NotFound)
+    }
+
+    /// Deliveries still pending are dropped with the subscription.
+    #[instrument(skip(self))]
+    pub async fn delete_subscription(
+        &self,
+        subscription_id: i64,
+        actor: &str,
+    ) -> AppResult<DeleteReceipt> {
+        let before = self.get_subscription(subscription_id).await?;
+        let deleted_at = self
+            .repository
+            .delete_subscription(subscription_id)
+            .await?
+            .ok_or(AppError::NotFound)?;
+
+        self.audit
+            .record(
+                "webhook_subscription",
+                &subscription_id.to_string(),
+                AuditAction::Delete,
+                actor,
+                Some(&before),
+                None,
+            )
+            .await;
+
+        Ok(DeleteReceipt::new(subscription_id.to_string(), deleted_at))
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_deliveries(
+        &self,
+        subscription_id: i64,
+        query: WebhookDeliveryQuery,
+    ) -> AppResult<PaginatedResponse<WebhookDelivery>> {
+        self.get_subscription(subscription_id).await?;
+
+        let pagination = query.pagination();
+        let (_, _, page, page_size) = pagination.normalize();
+        let (deliveries, total_count) = self
+            .repository
+            .find_deliveries(
+                subscription_id,
+                query.status.map(|status| status.as_str()),
+                &pagination,
+            )
+            .await?;
+        Ok(PaginatedResponse::new(
+            deliveries,
+            total_count,
+            page,
+            page_size,
+        ))
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_delivery(
+        &self,
+        subscription_id: i64,
+        delivery_id: i64,
+    ) -> AppResult<WebhookDelivery> {
+        self.repository
+            .find_delivery(subscription_id, delivery_id)
+            .await?
+            .ok_or(AppError::NotFound)
+    }
+
+    /// Gives deliveries that failed for good within the last `max_age_hours` one more
+    /// attempt each; those failing again go back to failed.
+    #[instrument(skip(self))]
+    pub async fn requeue_failed(&self, max_age_hours: i64) -> AppResult<u64> {
+        Ok(self
+            .repository
+            .requeue_failed_deliveries(max_age_hours)
+            .await?)
+    }
+
+    /// Leases the next batch of due deliveries, long enough for one request to time out.
+    pub async fn claim_due(&self) -> AppResult<Vec<PendingWebhookDelivery>> {
+        let lease_seconds = self.config.timeout_seconds as i64 + 30;
+        Ok(self
+            .repository
+            .claim_due_deliveries(Self::CLAIM_BATCH_SIZE, lease_seconds)
+            .await?)
+    }
+
+    pub async fn record_success(
+        &self,
+        delivery: &PendingWebhookDelivery,
+        status_code: u16,
+    ) -> AppResult<()> {
+        Ok(self
+            .repository
+            .mark_delivered(delivery.delivery_id, status_code.into())
+            .await?)
+    }
+
+    /// Drops a delivery whose subscription's filters leave its event out.
+    pub async fn discard(&self, delivery: &PendingWebhookDelivery) -> AppResult<()> {
+        Ok(self
+            .repository
+            .discard_delivery(delivery.delivery_id)
+            .await?)
+    }
+
+    /// Schedules the next attempt with exponential backoff, or gives up after
+    /// `max_attempts`.
+    pub async fn record_failure(
+        &self,
+        delivery: &PendingWebhookDelivery,
+        status_code: Option<u16>,
+        error: &str,
+    ) -> AppResult<()> {
+        let attempts = delivery.attempts.max(0) as u32;
+        let retry_in = (attempts < self.config.max_attempts)
+            .then(|| self.config.retry_delay(attempts).as_secs() as i64);
+        if retry_in.is_none() {
+            warn!(
+                "Webhook delivery {} failed for good after {} attempts: {}",
+                delivery.delivery_id, attempts, error
+            );
+        }
+
+        Ok(self
+            .repository
+            .mark_attempt_failed(
+                delivery.delivery_id,
+                status_code.map(i32::from),
+                error,
+                retry_in,
+            )
+            .await?)
+    }
+}
@@ -0,0 +1,104 @@
+use std::sync::Arc;
+use tracing::instrument;
+
+use crate::cache::LookupCache;
+use crate::cities::{fold_city, tidy_city};
+use crate::error::{AppError, AppResult};
+use crate::models::{CepAddress, CreateCustomerDto};
+use crate::zip_lookup::{ZipLookup, normalize_cep};
+
+use super::validation_error;
+
+/// CEP lookups through the configured provider, cached in-process.
+#[derive(Clone)]
+pub struct ZipLookupService {
+    provider: Arc<dyn ZipLookup>,
+    lookups: LookupCache,
+}
+
+impl ZipLookupService {
+    pub fn new(provider: Arc<dyn ZipLookup>, lookups: LookupCache) -> Self {
+        Self { provider, lookups }
+    }
+
+    /// The address range of a CEP written as `00000000` or `00000-000`.
+    #[instrument(skip(self))]
+    pub async fn lookup(&self, code: &str) -> AppResult<CepAddress> {
+        let cep = parse_cep("cep", code)?;
+        self.find(&cep).await?.ok_or(AppError::NotFound)
+    }
+
+    /// Checks a new customer's location against its `customer_cep`, when given: an omitted
+    /// zip code prefix or city is filled from the CEP, and one that was given must match it,
+    /// as must the state. Cities are compared accent- and case-insensitively.
+    #[instrument(skip(self, dto))]
+    pub async fn complete_customer(
+        &self,
+        mut dto: CreateCustomerDto,
+    ) -> AppResult<CreateCustomerDto> {
+        let Some(code) = dto.customer_cep.take() else {
+            return Ok(dto);
+        };
+        let cep = parse_cep("customer_cep", &code)?;
+        let Some(address) = self.find(&cep).await? else {
+            return Err(validation_error(
+                "customer_cep",
+                "unknown_cep",
+                format!("CEP {} is not assigned", code.trim()),
+            ));
+        };
+
+        let mut errors = validator::ValidationErrors::new();
+        let mismatch = |expected: String| {
+            validator::ValidationError::new("cep_mismatch")
+                .with_message(format!("CEP {} {}", code.trim(), expected).into())
+        };
+        let prefix = &cep[..5];
+        if dto.customer_zip_code_prefix.trim().is_empty() {
+            dto.customer_zip_code_prefix = prefix.to_string();
+        } else if dto.customer_zip_code_prefix.trim() != prefix {
+            errors.add(
+                "customer_zip_code_prefix",
+                mismatch(format!("has prefix {}", prefix)),
+            );
+        }
+        if tidy_city(&dto.customer_city).is_empty() {
+            dto.customer_city = address.city.clone();
+        } else if fold_city(&dto.customer_city) != fold_city(&address.city) {
+            errors.add("customer_city", mismatch(format!("is in {}", address.city)));
+        }
+        if !dto
+            .customer_state
+            .as_str()
+            .eq_ignore_ascii_case(&address.state)
+        {
+            errors.add(
+                "customer_state",
+                mismatch(format!("is in {}", address.state)),
+            );
+        }
+
+        if errors.is_empty() {
+            Ok(dto)
+        } else {
+            Err(AppError::ValidationError(errors))
+        }
+    }
+
+    async fn find(&self, cep: &str) -> AppResult<Option<CepAddress>> {
+        self.lookups
+            .address(cep, || self.provider.lookup(cep))
+            .await
+    }
+}
+
+/// The digits of `code`, or a validation error on `field`.
+fn parse_cep(field: &'static str, code: &str) -> AppResult<String> {
+    normalize_cep(code).ok_or_else(|| {
+        validation_error(
+            field,
+            "cep",
+            "must be a CEP of 8 digits, optionally written as 00000-000".to_string(),
+        )
+    })
+}
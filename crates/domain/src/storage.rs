@@ -0,0 +1,62 @@
+use async_trait::async_trait;
+use bytes::Bytes;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::error::AppResult;
+
+/// Where uploaded files are kept, each object under a key such as `products/{uuid}.jpg`.
+///
+/// Implementations backed by a storage service are selected with `STORAGE_PROVIDER` when
+/// building `AppState`.
+#[async_trait]
+pub trait ObjectStorage: Send + Sync {
+    fn name(&self) -> &'static str;
+    /// Stores `body` under `key`, replacing any object there, and returns the URL it is
+    /// served from.
+    async fn put(&self, key: &str, content_type: &str, body: Bytes) -> AppResult<String>;
+    /// Removes the object under `key`. Removing a missing object is not an error.
+    async fn delete(&self, key: &str) -> AppResult<()>;
+}
+
+/// A file received in an upload.
+#[derive(Debug, Clone)]
+pub struct Upload {
+    pub file_name: Option<String>,
+    pub content_type: String,
+    pub body: Bytes,
+}
+
+/// Storage without an external service, for development and tests. Objects live as long as
+/// the process and nothing serves their `memory://` URLs.
+#[derive(Default)]
+pub struct MemoryStorage {
+    objects: Mutex<HashMap<String, (String, Bytes)>>,
+}
+
+impl MemoryStorage {
+    pub const NAME: &'static str = "memory";
+}
+
+#[async_trait]
+impl ObjectStorage for MemoryStorage {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    async fn put(&self, key: &str, content_type: &str, body: Bytes) -> AppResult<String> {
+        self.objects
+            .lock()
+            .expect("memory storage poisoned")
+            .insert(key.to_string(), (content_type.to_string(), body));
+        Ok(format!("memory://{}", key))
+    }
+
+    async fn delete(&self, key: &str) -> AppResult<()> {
+        self.objects
+            .lock()
+            .expect("memory storage poisoned")
+            .remove(key);
+        Ok(())
+    }
+}
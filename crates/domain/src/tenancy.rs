@@ -0,0 +1,124 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// The marketplace a request, row or background job belongs to. One deployment can serve
+/// several marketplaces: every tenant-owned table carries a `tenant_id` column and the
+/// repositories built for a tenant read and write only its rows.
+///
+/// Tenant ids appear in subdomains, headers and cache keys, so they are limited to 1–64
+/// lowercase ASCII letters, digits and hyphens, not starting or ending with a hyphen.
+#[derive(
+    Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize, sqlx::Type,
+)]
+#[serde(try_from = "String", into = "String")]
+#[sqlx(transparent)]
+pub struct TenantId(String);
+
+impl TenantId {
+    /// Tenant of the rows that existed before multi-tenancy, and the only tenant of a
+    /// deployment that doesn't resolve tenants.
+    pub const DEFAULT: &'static str = "default";
+
+    pub const MAX_LEN: usize = 64;
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Default for TenantId {
+    fn default() -> Self {
+        Self(Self::DEFAULT.to_string())
+    }
+}
+
+impl FromStr for TenantId {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        let valid = !value.is_empty()
+            && value.len() <= Self::MAX_LEN
+            && !value.starts_with('-')
+            && !value.ends_with('-')
+            && value
+                .bytes()
+                .all(|b| matches!(b, b'a'..=b'z' | b'0'..=b'9' | b'-'));
+        if valid {
+            Ok(Self(value.to_string()))
+        } else {
+            Err(format!(
+                "invalid tenant '{}': use 1-{} lowercase letters, digits and inner hyphens",
+                value,
+                Self::MAX_LEN
+            ))
+        }
+    }
+}
+
+impl TryFrom<String> for TenantId {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<TenantId> for String {
+    fn from(tenant: TenantId) -> Self {
+        tenant.0
+    }
+}
+
+impl AsRef<str> for TenantId {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for TenantId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_lowercase_letters_digits_and_inner_hyphens() {
+        for value in ["default", "olist", "loja-2", "a", "9"] {
+            assert_eq!(value.parse::<TenantId>().unwrap().as_str(), value);
+        }
+        assert_eq!(" olist ".parse::<TenantId>().unwrap().as_str(), "olist");
+        assert!("a".repeat(TenantId::MAX_LEN).parse::<TenantId>().is_ok());
+    }
+
+    #[test]
+    fn rejects_anything_else() {
+        let too_long = "a".repeat(TenantId::MAX_LEN + 1);
+        for value in [
+            "",
+            "  ",
+            "Olist",
+            "-olist",
+            "olist-",
+            "loja_2",
+            "loja.com",
+            "lojá",
+            too_long.as_str(),
+        ] {
+            assert!(value.parse::<TenantId>().is_err(), "{value:?}");
+        }
+    }
+
+    #[test]
+    fn deserializes_through_the_same_validation() {
+        let tenant: TenantId = serde_json::from_str("\"olist\"").unwrap();
+        assert_eq!(tenant.as_str(), "olist");
+        assert!(serde_json::from_str::<TenantId>("\"Olist\"").is_err());
+        assert_eq!(TenantId::default().as_str(), TenantId::DEFAULT);
+    }
+}
@@ -0,0 +1,62 @@
+use async_trait::async_trait;
+use std::sync::Arc;
+
+use crate::error::AppResult;
+use crate::models::CepAddress;
+use crate::repositories::GeolocationRepository;
+
+/// Resolves a CEP to the address range it identifies.
+///
+/// Implementations are selected with `ZIP_LOOKUP_PROVIDER` when building `AppState`.
+#[async_trait]
+pub trait ZipLookup: Send + Sync {
+    fn name(&self) -> &'static str;
+    /// The address of an eight-digit CEP, or `None` when it is not assigned.
+    async fn lookup(&self, cep: &str) -> AppResult<Option<CepAddress>>;
+}
+
+/// The eight digits of a CEP written as `00000000` or `00000-000`.
+pub fn normalize_cep(code: &str) -> Option<String> {
+    let code = code.trim();
+    let digits = match code.split_once('-') {
+        Some((prefix, suffix)) if prefix.len() == 5 && suffix.len() == 3 => {
+            format!("{}{}", prefix, suffix)
+        }
+        Some(_) => return None,
+        None => code.to_string(),
+    };
+    (digits.len() == 8 && digits.bytes().all(|b| b.is_ascii_digit())).then_some(digits)
+}
+
+/// Lookups answered from the geolocation table, without an external call. Only the city and
+/// state of the CEP's prefix are known.
+pub struct GeolocationZipLookup {
+    geolocation: Arc<dyn GeolocationRepository>,
+}
+
+impl GeolocationZipLookup {
+    pub const NAME: &'static str = "geolocation";
+
+    pub fn new(geolocation: Arc<dyn GeolocationRepository>) -> Self {
+        Self { geolocation }
+    }
+}
+
+#[async_trait]
+impl ZipLookup for GeolocationZipLookup {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    async fn lookup(&self, cep: &str) -> AppResult<Option<CepAddress>> {
+        let prefix = cep.get(..5).unwrap_or(cep).to_string();
+        let location = self.geolocation.find_by_prefixes(&[prefix]).await?;
+        Ok(location.into_iter().next().map(|location| CepAddress {
+            cep: cep.to_string(),
+            street: None,
+            neighborhood: None,
+            city: location.geolocation_city,
+            state: location.geolocation_state,
+        }))
+    }
+}
@@ -0,0 +1,37 @@
+//! Encoding of bulk entity exports, the write-side counterpart of the CSV imports.
+
+use bytes::Bytes;
+use futures::{Stream, StreamExt};
+use serde::Serialize;
+use std::io;
+
+use domain::models::ExportFormat;
+
+/// Encodes `rows` as CSV (with a header) or NDJSON, one row per chunk.
+pub fn encode_rows<S, T>(rows: S, format: ExportFormat) -> impl Stream<Item = io::Result<Bytes>>
+where
+    S: Stream<Item = sqlx::Result<T>>,
+    T: Serialize,
+{
+    let mut header_written = false;
+
+    rows.map(move |row| {
+        let row = row.map_err(io::Error::other)?;
+        let chunk = match format {
+            ExportFormat::Csv => {
+                let mut writer = csv::WriterBuilder::new()
+                    .has_headers(!header_written)
+                    .from_writer(Vec::new());
+                writer.serialize(&row)?;
+                header_written = true;
+                writer.into_inner().map_err(|e| e.into_error())?
+            }
+            ExportFormat::Ndjson => {
+                let mut line = serde_json::to_vec(&row)?;
+                line.push(b'\n');
+                line
+            }
+        };
+        Ok(Bytes::from(chunk))
+    })
+}
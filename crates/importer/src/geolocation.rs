@@ -0,0 +1,123 @@
+//! Import of the Olist geolocation dataset, which lists many points per CEP prefix. They are
+//! averaged into one location per prefix, replacing what was stored for it.
+
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use tracing::warn;
+
+use domain::cities::tidy_city;
+use domain::error::{AppError, AppResult};
+use domain::models::ZipLocation;
+use domain::services::FreightService;
+
+use crate::import::{CHUNK_ROWS, open_csv};
+
+/// Where the Olist geolocation file is expected. It is not bundled: at over a million rows it
+/// is larger than every other dataset together.
+pub const GEOLOCATION_PATH: &str = "data/olist_geolocation_dataset.csv";
+
+/// Latitude and longitude bounds of Brazil. The dataset has a few points abroad or at sea,
+/// which would skew their prefix's mean.
+const LAT_RANGE: (f64, f64) = (-34.0, 5.5);
+const LNG_RANGE: (f64, f64) = (-74.0, -34.0);
+
+#[derive(Deserialize)]
+struct GeolocationRow {
+    geolocation_zip_code_prefix: String,
+    geolocation_lat: f64,
+    geolocation_lng: f64,
+    geolocation_city: String,
+    geolocation_state: String,
+}
+
+/// Running mean of a prefix's points, named after its first one.
+struct PrefixPoints {
+    lat_sum: f64,
+    lng_sum: f64,
+    count: u32,
+    city: String,
+    state: String,
+}
+
+/// What a geolocation import read and wrote.
+#[derive(Debug)]
+pub struct GeolocationImport {
+    pub rows: usize,
+    /// Rows that failed to parse or lie outside Brazil.
+    pub skipped: usize,
+    pub prefixes: u64,
+}
+
+pub async fn import_geolocation(
+    service: &FreightService,
+    file_path: &str,
+) -> AppResult<GeolocationImport> {
+    let (mut rdr, headers) = open_csv(file_path)?;
+    let mut prefixes: BTreeMap<String, PrefixPoints> = BTreeMap::new();
+    let mut report = GeolocationImport {
+        rows: 0,
+        skipped: 0,
+        prefixes: 0,
+    };
+
+    for result in rdr.records() {
+        report.rows += 1;
+        let row = result.map_err(|e| e.to_string()).and_then(|raw| {
+            raw.deserialize::<GeolocationRow>(Some(&headers))
+                .map_err(|e| e.to_string())
+        });
+        let row = match row {
+            Ok(row)
+                if (LAT_RANGE.0..=LAT_RANGE.1).contains(&row.geolocation_lat)
+                    && (LNG_RANGE.0..=LNG_RANGE.1).contains(&row.geolocation_lng) =>
+            {
+                row
+            }
+            Ok(_) => {
+                report.skipped += 1;
+                continue;
+            }
+            Err(e) => {
+                warn!("Skipping geolocation row {}: {}", report.rows + 1, e);
+                report.skipped += 1;
+                continue;
+            }
+        };
+
+        // Leading zeros are lost when the file has been through a spreadsheet.
+        let prefix = format!("{:0>5}", row.geolocation_zip_code_prefix.trim());
+        let points = prefixes.entry(prefix).or_insert_with(|| PrefixPoints {
+            lat_sum: 0.0,
+            lng_sum: 0.0,
+            count: 0,
+            city: tidy_city(&row.geolocation_city),
+            state: row.geolocation_state.trim().to_uppercase(),
+        });
+        points.lat_sum += row.geolocation_lat;
+        points.lng_sum += row.geolocation_lng;
+        points.count += 1;
+    }
+
+    if report.rows > 0 && report.skipped == report.rows {
+        return Err(AppError::ConfigError(format!(
+            "No usable geolocation rows in {}",
+            file_path
+        )));
+    }
+
+    let locations: Vec<ZipLocation> = prefixes
+        .into_iter()
+        .map(|(prefix, points)| ZipLocation {
+            geolocation_zip_code_prefix: prefix,
+            geolocation_lat: points.lat_sum / f64::from(points.count),
+            geolocation_lng: points.lng_sum / f64::from(points.count),
+            geolocation_city: points.city,
+            geolocation_state: points.state,
+        })
+        .collect();
+    for chunk in locations.chunks(CHUNK_ROWS) {
+        report.prefixes += service.save_locations(chunk).await?;
+    }
+
+    Ok(report)
+}
@@ -0,0 +1,664 @@
+use csv::StringRecord;
+use futures::{StreamExt, stream};
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use std::collections::{BTreeMap, HashSet};
+use std::fs::File;
+use tracing::error;
+
+use domain::error::{AppError, AppResult};
+use domain::models::{
+    CreateCustomerDto, CreateOrderDto, CreateProductDto, CreateSellerDto, ImportBatch,
+    ImportBatchStatus, NewImportRowError,
+};
+use domain::services::{CustomerService, OrderService, ProductService, SellerService};
+
+use crate::services::ImportService;
+
+pub const CSV_IMPORT_ACTOR: &str = "system:csv-import";
+
+/// A CSV dataset that can be imported through the services layer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Dataset {
+    Customers,
+    Sellers,
+    Orders,
+    Products,
+}
+
+impl Dataset {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Dataset::Customers => "customers",
+            Dataset::Sellers => "sellers",
+            Dataset::Orders => "orders",
+            Dataset::Products => "products",
+        }
+    }
+
+    /// Primary key of the dataset's table, which is named after the dataset.
+    pub fn key_column(&self) -> &'static str {
+        match self {
+            Dataset::Customers => "customer_id",
+            Dataset::Sellers => "seller_id",
+            Dataset::Orders => "order_id",
+            Dataset::Products => "product_id",
+        }
+    }
+
+    /// The bundled Olist file for this dataset.
+    pub fn default_path(&self) -> &'static str {
+        match self {
+            Dataset::Customers => "data/olist_customers_dataset.csv",
+            Dataset::Sellers => "data/olist_sellers_dataset.csv",
+            Dataset::Orders => "data/olist_orders_dataset.csv",
+            Dataset::Products => "data/olist_products_dataset.csv",
+        }
+    }
+}
+
+/// The services an import writes through: rows go through the same validation and audit
+/// trail as API requests.
+#[derive(Clone)]
+pub struct ImportTargets {
+    pub imports: ImportService,
+    pub customers: CustomerService,
+    pub sellers: SellerService,
+    pub orders: OrderService,
+    pub products: ProductService,
+}
+
+/// Rows inserted together in one transaction, then logged against the batch and reported
+/// as progress.
+pub(crate) const CHUNK_ROWS: usize = 500;
+
+/// Rows a running import has processed so far.
+#[derive(Debug, Clone, Copy)]
+pub struct ImportProgress {
+    pub batch_id: i64,
+    pub success_count: usize,
+    pub error_count: usize,
+}
+
+/// Where an import starts reading its file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum StartAt {
+    #[default]
+    Beginning,
+    /// After this many data rows.
+    SkipRows(usize),
+    /// After the row with this primary key. Nothing is imported if no row has it.
+    AfterId(String),
+}
+
+/// How an import runs, beyond which file it reads.
+#[derive(Debug, Clone, Default)]
+pub struct ImportOptions {
+    pub start: StartAt,
+    /// Load job the new batch belongs to.
+    pub load_job_id: Option<i64>,
+    /// An interrupted batch to continue after its checkpoint instead of beginning a new one.
+    /// `start` is ignored: the checkpoint already covers it.
+    pub resume_batch_id: Option<i64>,
+}
+
+/// Imports one dataset as a new import batch, returning the finished batch. Rows that fail
+/// to parse or to insert are logged and counted rather than aborting the run; the batch is
+/// marked failed only if the file can't be read or its row log can't be written.
+pub async fn import_dataset(
+    targets: &ImportTargets,
+    dataset: Dataset,
+    file_path: &str,
+) -> AppResult<ImportBatch> {
+    import_dataset_with_progress(
+        targets,
+        dataset,
+        file_path,
+        &ImportOptions::default(),
+        &|_| {},
+    )
+    .await
+}
+
+/// [`import_dataset`] with `options`, calling `on_progress` when the batch starts and after
+/// each chunk of `CHUNK_ROWS` rows.
+pub async fn import_dataset_with_progress(
+    targets: &ImportTargets,
+    dataset: Dataset,
+    file_path: &str,
+    options: &ImportOptions,
+    on_progress: &(dyn Fn(ImportProgress) + Sync),
+) -> AppResult<ImportBatch> {
+    let imports = &targets.imports;
+    let (batch_id, start) = match options.resume_batch_id {
+        Some(batch_id) => {
+            let resume = imports.resume_batch(batch_id).await?;
+            let start = Start {
+                at: StartAt::Beginning,
+                checkpoint_line: resume.checkpoint_line,
+                success_count: resume.inserted.len(),
+                error_count: resume.error_count as usize,
+                inserted: resume.inserted.into_iter().collect(),
+                key_column: dataset.key_column(),
+            };
+            (batch_id, start)
+        }
+        None => {
+            let batch = imports
+                .begin_batch(dataset, file_path, CSV_IMPORT_ACTOR, options.load_job_id)
+                .await?;
+            let start = Start {
+                at: options.start.clone(),
+                checkpoint_line: 1,
+                success_count: 0,
+                error_count: 0,
+                inserted: HashSet::new(),
+                key_column: dataset.key_column(),
+            };
+            (batch.batch_id, start)
+        }
+    };
+    on_progress(ImportProgress {
+        batch_id,
+        success_count: start.success_count,
+        error_count: start.error_count,
+    });
+
+    let result = match dataset {
+        Dataset::Customers => {
+            load_csv_data(
+                imports,
+                batch_id,
+                file_path,
+                on_progress,
+                start,
+                |records: Vec<CreateCustomerDto>| {
+                    let service = targets.customers.clone();
+                    async move {
+                        let customers = service.create_customers(records, CSV_IMPORT_ACTOR).await?;
+                        Ok(customers
+                            .into_iter()
+                            .map(|customer| customer.map(|customer| customer.customer_id.into()))
+                            .collect())
+                    }
+                },
+            )
+            .await
+        }
+        Dataset::Sellers => {
+            load_csv_data(
+                imports,
+                batch_id,
+                file_path,
+                on_progress,
+                start,
+                |records: Vec<CreateSellerDto>| {
+                    let service = targets.sellers.clone();
+                    async move {
+                        let sellers = service.create_sellers(records, CSV_IMPORT_ACTOR).await?;
+                        Ok(sellers
+                            .into_iter()
+                            .map(|seller| seller.map(|seller| seller.seller_id.into()))
+                            .collect())
+                    }
+                },
+            )
+            .await
+        }
+        Dataset::Orders => {
+            load_csv_data(
+                imports,
+                batch_id,
+                file_path,
+                on_progress,
+                start,
+                |records: Vec<CreateOrderDto>| {
+                    let service = targets.orders.clone();
+                    async move {
+                        let orders = service.create_orders(records, CSV_IMPORT_ACTOR).await?;
+                        Ok(orders
+                            .into_iter()
+                            .map(|order| order.map(|order| order.order_id.into()))
+                            .collect())
+                    }
+                },
+            )
+            .await
+        }
+        Dataset::Products => {
+            load_csv_data(
+                imports,
+                batch_id,
+                file_path,
+                on_progress,
+                start,
+                |records: Vec<CreateProductDto>| {
+                    let service = targets.products.clone();
+                    async move {
+                        let products = service.create_products(records, CSV_IMPORT_ACTOR).await?;
+                        Ok(products
+                            .into_iter()
+                            .map(|product| product.map(|product| product.product_id.into()))
+                            .collect())
+                    }
+                },
+            )
+            .await
+        }
+    };
+
+    match result {
+        Ok((success_count, error_count)) => {
+            imports
+                .finish_batch(
+                    batch_id,
+                    ImportBatchStatus::Completed,
+                    success_count,
+                    error_count,
+                )
+                .await
+        }
+        Err((e, success_count, error_count)) => {
+            if let Err(finish_err) = imports
+                .finish_batch(
+                    batch_id,
+                    ImportBatchStatus::Failed,
+                    success_count,
+                    error_count,
+                )
+                .await
+            {
+                error!(
+                    "Failed to mark import batch {} as failed: {:?}",
+                    batch_id, finish_err
+                );
+            }
+            Err(e)
+        }
+    }
+}
+
+/// A row read from the file: its line, its fields if they could be read, and the parsed
+/// record or why it didn't parse.
+pub(crate) struct ReadRow<T> {
+    pub(crate) row_number: i64,
+    pub(crate) raw: Option<StringRecord>,
+    pub(crate) parsed: Result<T, String>,
+}
+
+/// Where `load_csv_data` starts: the batch's counts so far and the rows to pass over.
+struct Start {
+    at: StartAt,
+    /// Rows up to this line were handled by an earlier run of the batch.
+    checkpoint_line: i64,
+    /// Keys an earlier run inserted, past the checkpoint too.
+    inserted: HashSet<String>,
+    key_column: &'static str,
+    success_count: usize,
+    error_count: usize,
+}
+
+/// Rows of one chunk that were inserted, by primary key, and that were rejected.
+struct ChunkOutcome {
+    /// Position of the chunk in the file.
+    index: usize,
+    /// Line of the chunk's last row.
+    last_line: i64,
+    inserted: Vec<String>,
+    failed: Vec<NewImportRowError>,
+}
+
+// Generic CSV loader that takes a closure to execute the logic
+// This removes the HTTP roundtrip overhead completely.
+// Rows are read in chunks of `CHUNK_ROWS`, and up to `ImportService::concurrency` chunks are
+// inserted at once. `process_fn` inserts a chunk in one transaction and returns, per row, the
+// primary key logged against the batch or the error logged with the row's reason.
+// Once every chunk up to a line is done, that line is saved as the batch's checkpoint.
+// On failure the counts so far are returned with the error so the batch can record them.
+async fn load_csv_data<T, F, Fut>(
+    imports: &ImportService,
+    batch_id: i64,
+    file_path: &str,
+    on_progress: &(dyn Fn(ImportProgress) + Sync),
+    start: Start,
+    process_fn: F,
+) -> Result<(usize, usize), (AppError, usize, usize)>
+where
+    T: DeserializeOwned + Send + 'static,
+    F: Fn(Vec<T>) -> Fut + Send + Sync + Copy,
+    Fut: std::future::Future<Output = AppResult<Vec<AppResult<String>>>> + Send,
+{
+    let mut success_count = start.success_count;
+    let mut error_count = start.error_count;
+
+    let (rdr, headers) = open_csv(file_path).map_err(|e| (e, success_count, error_count))?;
+    let key_index = headers.iter().position(|name| name == start.key_column);
+    fn key_of(raw: &StringRecord, key_index: Option<usize>) -> Option<&str> {
+        key_index.and_then(|index| raw.get(index))
+    }
+
+    let mut records = rdr.into_records().peekable();
+    let mut skipped_to = start.checkpoint_line;
+    match &start.at {
+        StartAt::Beginning => {}
+        StartAt::SkipRows(rows) => {
+            for result in records.by_ref().take(*rows) {
+                skipped_to = record_line(&result).unwrap_or(skipped_to + 1);
+            }
+        }
+        StartAt::AfterId(id) => {
+            for result in records.by_ref() {
+                skipped_to = record_line(&result).unwrap_or(skipped_to + 1);
+                if matches!(&result, Ok(raw) if key_of(raw, key_index) == Some(id.as_str())) {
+                    break;
+                }
+            }
+        }
+    }
+    while records
+        .next_if(|result| record_line(result).is_some_and(|line| line <= start.checkpoint_line))
+        .is_some()
+    {}
+    if skipped_to > start.checkpoint_line {
+        imports
+            .checkpoint(batch_id, skipped_to)
+            .await
+            .map_err(|e| (e, success_count, error_count))?;
+    }
+    let inserted = start.inserted;
+    let records = records.filter(
+        move |result| !matches!(result, Ok(raw) if key_of(raw, key_index).is_some_and(|key| inserted.contains(key))),
+    );
+
+    let mut chunks = stream::iter(read_chunks::<T>(records, headers, file_path).enumerate())
+        .map(|(index, chunk)| insert_chunk(index, chunk, process_fn, file_path))
+        .buffer_unordered(imports.concurrency());
+
+    // Last lines of finished chunks that can't be checkpointed until those before them finish.
+    let mut finished = BTreeMap::new();
+    let mut next_chunk = 0;
+
+    while let Some(outcome) = chunks.next().await {
+        success_count += outcome.inserted.len();
+        error_count += outcome.failed.len();
+
+        if !outcome.inserted.is_empty() {
+            imports
+                .record_rows(batch_id, &outcome.inserted)
+                .await
+                .map_err(|e| (e, success_count, error_count))?;
+        }
+        if !outcome.failed.is_empty() {
+            imports
+                .record_errors(batch_id, &outcome.failed)
+                .await
+                .map_err(|e| (e, success_count, error_count))?;
+        }
+
+        finished.insert(outcome.index, outcome.last_line);
+        let mut checkpoint = None;
+        while let Some(line) = finished.remove(&next_chunk) {
+            checkpoint = Some(line);
+            next_chunk += 1;
+        }
+        if let Some(line) = checkpoint {
+            imports
+                .checkpoint(batch_id, line)
+                .await
+                .map_err(|e| (e, success_count, error_count))?;
+        }
+
+        on_progress(ImportProgress {
+            batch_id,
+            success_count,
+            error_count,
+        });
+    }
+
+    Ok((success_count, error_count))
+}
+
+/// Opens a CSV file and reads its header.
+pub(crate) fn open_csv(file_path: &str) -> AppResult<(csv::Reader<File>, StringRecord)> {
+    // Flexible, so a row with missing or extra fields is still read and reported with them.
+    let mut rdr = csv::ReaderBuilder::new()
+        .flexible(true)
+        .from_path(file_path)
+        .map_err(|e| {
+            error!("Failed to open CSV file {}: {}", file_path, e);
+            AppError::ConfigError(format!("Failed to open CSV file: {}", e))
+        })?;
+    let headers = rdr.headers().cloned().map_err(|e| {
+        error!("Failed to read the header of CSV file {}: {}", file_path, e);
+        AppError::ConfigError(format!("Failed to read CSV header: {}", e))
+    })?;
+    Ok((rdr, headers))
+}
+
+/// Line of a record in its file, when the reader knows it.
+fn record_line(result: &csv::Result<StringRecord>) -> Option<i64> {
+    let position = match result {
+        Ok(raw) => raw.position(),
+        Err(e) => e.position(),
+    };
+    position.map(|p| p.line() as i64)
+}
+
+/// Reads `records` `CHUNK_ROWS` at a time, parsing each one against `headers`.
+pub(crate) fn read_chunks<T: DeserializeOwned>(
+    mut records: impl Iterator<Item = csv::Result<StringRecord>>,
+    headers: StringRecord,
+    file_path: &str,
+) -> impl Iterator<Item = Vec<ReadRow<T>>> {
+    let mut row_number = 1;
+    std::iter::from_fn(move || {
+        let mut chunk = Vec::with_capacity(CHUNK_ROWS);
+        for result in records.by_ref().take(CHUNK_ROWS) {
+            let row = match result {
+                Ok(raw) => {
+                    row_number = raw.position().map_or(row_number + 1, |p| p.line() as i64);
+                    let parsed = raw.deserialize::<T>(Some(&headers)).map_err(|e| {
+                        error!("CSV Parse Error in {}: {}", file_path, e);
+                        e.to_string()
+                    });
+                    ReadRow {
+                        row_number,
+                        raw: Some(raw),
+                        parsed,
+                    }
+                }
+                Err(e) => {
+                    error!("CSV Parse Error in {}: {}", file_path, e);
+                    row_number = e.position().map_or(row_number + 1, |p| p.line() as i64);
+                    ReadRow {
+                        row_number,
+                        raw: None,
+                        parsed: Err(e.to_string()),
+                    }
+                }
+            };
+            chunk.push(row);
+        }
+        (!chunk.is_empty()).then_some(chunk)
+    })
+}
+
+/// Inserts a chunk's parsed rows through `process_fn`. If the whole chunk fails, every row in
+/// it is rejected with that error.
+async fn insert_chunk<T, F, Fut>(
+    index: usize,
+    chunk: Vec<ReadRow<T>>,
+    process_fn: F,
+    file_path: &str,
+) -> ChunkOutcome
+where
+    F: Fn(Vec<T>) -> Fut,
+    Fut: std::future::Future<Output = AppResult<Vec<AppResult<String>>>>,
+{
+    let mut outcome = ChunkOutcome {
+        index,
+        last_line: chunk.last().map_or(0, |row| row.row_number),
+        inserted: Vec::new(),
+        failed: Vec::new(),
+    };
+    let mut pending = Vec::with_capacity(chunk.len());
+    let mut records = Vec::with_capacity(chunk.len());
+    for row in chunk {
+        match row.parsed {
+            Ok(record) => {
+                records.push(record);
+                pending.push((row.row_number, row.raw));
+            }
+            Err(reason) => outcome.failed.push(NewImportRowError {
+                row_number: row.row_number,
+                record: row.raw.as_ref().and_then(encode_record),
+                reason,
+            }),
+        }
+    }
+    if records.is_empty() {
+        return outcome;
+    }
+
+    let results: Vec<Result<String, String>> = match process_fn(records).await {
+        Ok(results) => results
+            .into_iter()
+            .map(|result| {
+                result.map_err(|e| {
+                    error!("Failed to process record from {}: {:?}", file_path, e);
+                    describe_error(&e)
+                })
+            })
+            .collect(),
+        Err(e) => {
+            error!("Failed to insert a chunk of {}: {:?}", file_path, e);
+            vec![Err(describe_error(&e)); pending.len()]
+        }
+    };
+
+    for ((row_number, raw), result) in pending.into_iter().zip(results) {
+        match result {
+            Ok(id) => outcome.inserted.push(id),
+            Err(reason) => outcome.failed.push(NewImportRowError {
+                row_number,
+                record: raw.as_ref().and_then(encode_record),
+                reason,
+            }),
+        }
+    }
+    outcome
+}
+
+/// A row's fields as one CSV line, for the error report.
+pub(crate) fn encode_record(record: &StringRecord) -> Option<String> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(record).ok()?;
+    let line = String::from_utf8(writer.into_inner().ok()?).ok()?;
+    Some(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+/// Why a row was rejected, worded for whoever fixes the file.
+pub(crate) fn describe_error(e: &AppError) -> String {
+    match e {
+        AppError::ValidationError(errors) => errors.to_string(),
+        AppError::AlreadyExists(message) | AppError::InsufficientStock(message) => message.clone(),
+        AppError::DatabaseError(e) => e.to_string(),
+        other => format!("{:?}", other),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Deserialize)]
+    struct Row {
+        id: String,
+        qty: i32,
+    }
+
+    fn chunks(csv: &str) -> Vec<Vec<ReadRow<Row>>> {
+        let mut rdr = csv::ReaderBuilder::new()
+            .flexible(true)
+            .from_reader(csv.as_bytes());
+        let headers = rdr.headers().cloned().unwrap();
+        let records: Vec<_> = rdr.into_records().collect();
+        read_chunks(records.into_iter(), headers, "rows.csv").collect()
+    }
+
+    #[test]
+    fn reads_rows_in_chunks_with_their_lines() {
+        let rows = CHUNK_ROWS * 2 + 1;
+        let csv = (0..rows).fold("id,qty\n".to_string(), |csv, i| csv + &format!("r{i},1\n"));
+
+        let chunks = chunks(&csv);
+        let sizes: Vec<usize> = chunks.iter().map(Vec::len).collect();
+        assert_eq!(sizes, [CHUNK_ROWS, CHUNK_ROWS, 1]);
+        assert_eq!(chunks[0][0].row_number, 2, "the header is line 1");
+        assert_eq!(chunks[2][0].row_number, rows as i64 + 1);
+        assert_eq!(
+            chunks[2][0].parsed.as_ref().unwrap().id,
+            format!("r{}", rows - 1)
+        );
+    }
+
+    #[test]
+    fn keeps_unparseable_rows_with_their_reason() {
+        let chunks = chunks("id,qty\na,1\nb,many\n");
+        let bad = &chunks[0][1];
+        assert_eq!(bad.row_number, 3);
+        assert!(bad.parsed.is_err());
+        assert_eq!(
+            bad.raw.as_ref().and_then(encode_record).as_deref(),
+            Some("b,many")
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_the_rows_the_insert_refused() {
+        let chunk = chunks("id,qty\na,1\nb,many\ndup,1\n").remove(0);
+        let outcome = insert_chunk(
+            3,
+            chunk,
+            |rows: Vec<Row>| async move {
+                Ok(rows
+                    .into_iter()
+                    .map(|row| match row.id.as_str() {
+                        "dup" => Err(AppError::AlreadyExists("dup exists".to_string())),
+                        id => Ok(format!("{id}:{}", row.qty)),
+                    })
+                    .collect())
+            },
+            "rows.csv",
+        )
+        .await;
+
+        assert_eq!(outcome.index, 3);
+        assert_eq!(outcome.last_line, 4);
+        assert_eq!(outcome.inserted, ["a:1"]);
+        let failed: Vec<(i64, &str)> = outcome
+            .failed
+            .iter()
+            .map(|e| (e.row_number, e.record.as_deref().unwrap_or_default()))
+            .collect();
+        assert_eq!(failed, [(3, "b,many"), (4, "dup,1")]);
+        assert_eq!(outcome.failed[1].reason, "dup exists");
+    }
+
+    #[tokio::test]
+    async fn a_failed_chunk_rejects_every_parsed_row() {
+        let chunk = chunks("id,qty\na,1\nb,2\n").remove(0);
+        let outcome = insert_chunk(
+            0,
+            chunk,
+            |_rows: Vec<Row>| async { Err(AppError::AlreadyExists("chunk refused".to_string())) },
+            "rows.csv",
+        )
+        .await;
+
+        assert!(outcome.inserted.is_empty());
+        let reasons: Vec<&str> = outcome.failed.iter().map(|e| e.reason.as_str()).collect();
+        assert_eq!(reasons, ["chunk refused", "chunk refused"]);
+    }
+}
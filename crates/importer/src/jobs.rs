@@ -0,0 +1,484 @@
+use chrono::Utc;
+use clap::ValueEnum;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tracing::{error, info};
+use validator::Validate;
+
+use domain::error::{AppError, AppResult};
+use domain::models::{
+    DatasetProgress, ImportBatch, ImportBatchStatus, JobStatus, LoadJob, LoadJobBatch,
+};
+
+use crate::import::{
+    CSV_IMPORT_ACTOR, Dataset, ImportOptions, ImportProgress, ImportTargets, StartAt,
+    import_dataset_with_progress,
+};
+
+/// Finished load jobs kept for status lookups.
+const LOAD_JOB_HISTORY: usize = 20;
+
+/// What a `/load-data` run imports. Every field is optional: without a body the default
+/// datasets are loaded from the start.
+#[derive(Debug, Default, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+#[validate(schema(function = "validate_load_request"))]
+pub struct LoadRequest {
+    /// Datasets to load, in order.
+    pub datasets: Option<Vec<Dataset>>,
+    /// Data rows of the first dataset to pass over.
+    pub skip_rows: Option<usize>,
+    /// Start the first dataset after the row with this primary key.
+    pub start_after_id: Option<String>,
+    /// Continue an interrupted job after its checkpoints. The job's own datasets are loaded,
+    /// skipping those it completed.
+    pub resume_job_id: Option<u64>,
+}
+
+fn validate_load_request(request: &LoadRequest) -> Result<(), validator::ValidationError> {
+    let invalid = |code: &'static str, message: &str| {
+        Err(validator::ValidationError::new(code).with_message(message.to_string().into()))
+    };
+    if request.resume_job_id.is_some()
+        && (request.datasets.is_some()
+            || request.skip_rows.is_some()
+            || request.start_after_id.is_some())
+    {
+        return invalid(
+            "resume_exclusive",
+            "resume_job_id can't be combined with datasets, skip_rows or start_after_id",
+        );
+    }
+    if request.skip_rows.is_some() && request.start_after_id.is_some() {
+        return invalid(
+            "start_exclusive",
+            "skip_rows and start_after_id can't both be set",
+        );
+    }
+    if let Some(datasets) = &request.datasets {
+        if datasets.is_empty() {
+            return invalid("empty_datasets", "datasets must name at least one dataset");
+        }
+        if datasets
+            .iter()
+            .enumerate()
+            .any(|(index, dataset)| datasets[..index].contains(dataset))
+        {
+            return invalid("duplicate_dataset", "datasets must not repeat a dataset");
+        }
+    }
+    Ok(())
+}
+
+impl LoadRequest {
+    /// Where the first dataset starts.
+    fn start(&self) -> StartAt {
+        match (self.skip_rows, &self.start_after_id) {
+            (Some(rows), _) => StartAt::SkipRows(rows),
+            (None, Some(id)) => StartAt::AfterId(id.clone()),
+            (None, None) => StartAt::Beginning,
+        }
+    }
+}
+
+/// What a load job does with one of its datasets.
+#[derive(Debug, Clone)]
+enum Step {
+    /// Import it as a new batch.
+    Fresh(StartAt),
+    /// Continue its interrupted batch.
+    Resume(LoadJobBatch),
+    /// Nothing: an earlier run of the job completed it.
+    Done(LoadJobBatch),
+}
+
+#[derive(Default)]
+struct Registry {
+    /// A job is being recorded and isn't in `jobs` yet.
+    starting: bool,
+    jobs: HashMap<u64, watch::Receiver<LoadJob>>,
+}
+
+/// Runs CSV loads as background jobs whose progress can be polled or watched.
+#[derive(Clone, Default)]
+pub struct LoadJobs(Arc<Mutex<Registry>>);
+
+impl LoadJobs {
+    /// Starts the load `request` describes, importing `default_datasets` unless it names
+    /// others, or fails if a load is still running. The handle resolves to the batches
+    /// imported by this run once the last dataset is done.
+    pub async fn start(
+        &self,
+        targets: ImportTargets,
+        request: &LoadRequest,
+        default_datasets: &[Dataset],
+    ) -> AppResult<(LoadJob, JoinHandle<AppResult<Vec<ImportBatch>>>)> {
+        request.validate()?;
+        self.reserve()?;
+        let planned = match request.resume_job_id {
+            Some(job_id) => plan_resume(&targets, job_id).await,
+            None => plan_fresh(&targets, request, default_datasets).await,
+        };
+        let (job_id, steps) = match planned {
+            Ok(planned) => planned,
+            Err(e) => {
+                self.lock().starting = false;
+                return Err(e);
+            }
+        };
+
+        let job = LoadJob {
+            job_id: job_id as u64,
+            status: JobStatus::Running,
+            started_at: Utc::now().naive_utc(),
+            finished_at: None,
+            datasets: steps
+                .iter()
+                .map(|(dataset, step)| {
+                    let mut progress = DatasetProgress {
+                        dataset: dataset.as_str(),
+                        status: JobStatus::Pending,
+                        batch_id: None,
+                        total_rows: None,
+                        rows_done: 0,
+                        success_count: 0,
+                        error_count: 0,
+                        eta_seconds: None,
+                        error: None,
+                    };
+                    if let Step::Done(batch) = step {
+                        progress.status = JobStatus::Completed;
+                        progress.batch_id = Some(batch.batch_id);
+                        progress.success_count = batch.success_count as usize;
+                        progress.error_count = batch.error_count as usize;
+                        progress.rows_done = progress.success_count + progress.error_count;
+                        progress.eta_seconds = Some(0);
+                    }
+                    progress
+                })
+                .collect(),
+        };
+
+        let (sender, receiver) = watch::channel(job.clone());
+        let mut registry = self.lock();
+        registry.starting = false;
+        registry.jobs.remove(&job.job_id);
+        if registry.jobs.len() >= LOAD_JOB_HISTORY
+            && let Some(oldest) = registry.jobs.keys().min().copied()
+        {
+            registry.jobs.remove(&oldest);
+        }
+        registry.jobs.insert(job.job_id, receiver);
+
+        let handle = tokio::spawn(run(sender, targets, job_id, steps));
+        Ok((job, handle))
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Registry> {
+        self.0.lock().expect("load job registry poisoned")
+    }
+
+    /// Claims the right to start a job, which lasts until it is registered.
+    fn reserve(&self) -> AppResult<()> {
+        let mut registry = self.lock();
+        if let Some((running, _)) = registry
+            .jobs
+            .iter()
+            .find(|(_, job)| job.borrow().finished_at.is_none())
+        {
+            return Err(AppError::JobAlreadyRunning(format!(
+                "Load job {} is still running",
+                running
+            )));
+        }
+        if registry.starting {
+            return Err(AppError::JobAlreadyRunning(
+                "Another load job is starting".to_string(),
+            ));
+        }
+        registry.starting = true;
+        Ok(())
+    }
+
+    pub fn get(&self, job_id: u64) -> AppResult<LoadJob> {
+        Ok(self.subscribe(job_id)?.borrow().clone())
+    }
+
+    /// Follows a job's progress; the receiver sees every update until the job finishes.
+    pub fn subscribe(&self, job_id: u64) -> AppResult<watch::Receiver<LoadJob>> {
+        self.lock()
+            .jobs
+            .get(&job_id)
+            .cloned()
+            .ok_or(AppError::NotFound)
+    }
+}
+
+/// Records a new job for the request's datasets; only the first one honours its start.
+async fn plan_fresh(
+    targets: &ImportTargets,
+    request: &LoadRequest,
+    default_datasets: &[Dataset],
+) -> AppResult<(i64, Vec<(Dataset, Step)>)> {
+    let datasets = request.datasets.as_deref().unwrap_or(default_datasets);
+    let job_id = targets
+        .imports
+        .begin_load_job(datasets, CSV_IMPORT_ACTOR)
+        .await?;
+    let steps = datasets
+        .iter()
+        .enumerate()
+        .map(|(index, &dataset)| {
+            let start = if index == 0 {
+                request.start()
+            } else {
+                StartAt::Beginning
+            };
+            (dataset, Step::Fresh(start))
+        })
+        .collect();
+    Ok((job_id, steps))
+}
+
+/// Picks up a stored job where its last run stopped. Each dataset follows its latest batch:
+/// completed ones are done, running or failed ones continue, and the rest start over.
+async fn plan_resume(
+    targets: &ImportTargets,
+    job_id: u64,
+) -> AppResult<(i64, Vec<(Dataset, Step)>)> {
+    let job_id = i64::try_from(job_id).map_err(|_| AppError::NotFound)?;
+    let stored = targets.imports.find_load_job(job_id).await?;
+    let mut steps = Vec::with_capacity(stored.datasets.len());
+    for name in &stored.datasets {
+        let dataset = Dataset::from_str(name, false)
+            .map_err(|_| AppError::ConfigError(format!("Unknown import dataset {}", name)))?;
+        let latest = stored
+            .batches
+            .iter()
+            .rev()
+            .find(|batch| batch.dataset == dataset.as_str());
+        let step = match latest {
+            Some(batch) if batch.status == ImportBatchStatus::Completed.as_str() => {
+                Step::Done(batch.clone())
+            }
+            Some(batch)
+                if batch.status == ImportBatchStatus::Running.as_str()
+                    || batch.status == ImportBatchStatus::Failed.as_str() =>
+            {
+                Step::Resume(batch.clone())
+            }
+            _ => Step::Fresh(StartAt::Beginning),
+        };
+        steps.push((dataset, step));
+    }
+    Ok((job_id, steps))
+}
+
+async fn run(
+    sender: watch::Sender<LoadJob>,
+    targets: ImportTargets,
+    load_job_id: i64,
+    steps: Vec<(Dataset, Step)>,
+) -> AppResult<Vec<ImportBatch>> {
+    let job_id = sender.borrow().job_id;
+    let mut batches = Vec::with_capacity(steps.len());
+    let mut failure = None;
+
+    for (index, (dataset, step)) in steps.into_iter().enumerate() {
+        if failure.is_some() {
+            sender.send_modify(|job| job.datasets[index].status = JobStatus::Skipped);
+            continue;
+        }
+        let options = match step {
+            Step::Done(_) => continue,
+            Step::Fresh(start) => ImportOptions {
+                start,
+                load_job_id: Some(load_job_id),
+                resume_batch_id: None,
+            },
+            Step::Resume(batch) => ImportOptions {
+                start: StartAt::Beginning,
+                load_job_id: Some(load_job_id),
+                resume_batch_id: Some(batch.batch_id),
+            },
+        };
+
+        info!("Starting {} import...", dataset.as_str());
+        let total_rows = count_rows(dataset.default_path()).await;
+        sender.send_modify(|job| {
+            let progress = &mut job.datasets[index];
+            progress.status = JobStatus::Running;
+            progress.total_rows = total_rows;
+        });
+
+        let started = Instant::now();
+        // Rows an earlier run handled don't count towards this run's rate.
+        let resumed_rows = std::sync::OnceLock::new();
+        let on_progress = |update: ImportProgress| {
+            let rows_done = update.success_count + update.error_count;
+            let resumed_rows = *resumed_rows.get_or_init(|| rows_done);
+            sender.send_modify(|job| {
+                let progress = &mut job.datasets[index];
+                progress.batch_id = Some(update.batch_id);
+                progress.success_count = update.success_count;
+                progress.error_count = update.error_count;
+                progress.rows_done = rows_done;
+                progress.eta_seconds = eta_seconds(
+                    started,
+                    rows_done - resumed_rows,
+                    total_rows.map(|total| total.saturating_sub(resumed_rows)),
+                );
+            })
+        };
+
+        match import_dataset_with_progress(
+            &targets,
+            dataset,
+            dataset.default_path(),
+            &options,
+            &on_progress,
+        )
+        .await
+        {
+            Ok(batch) => {
+                sender.send_modify(|job| {
+                    let progress = &mut job.datasets[index];
+                    progress.status = JobStatus::Completed;
+                    progress.eta_seconds = Some(0);
+                });
+                batches.push(batch);
+            }
+            Err(e) => {
+                error!(
+                    "Load job {} failed on {}: {:?}",
+                    job_id,
+                    dataset.as_str(),
+                    e
+                );
+                sender.send_modify(|job| {
+                    let progress = &mut job.datasets[index];
+                    progress.status = JobStatus::Failed;
+                    progress.eta_seconds = None;
+                    progress.error = Some(format!("{:?}", e));
+                });
+                failure = Some(e);
+            }
+        }
+    }
+
+    sender.send_modify(|job| {
+        job.finished_at = Some(Utc::now().naive_utc());
+        job.status = if failure.is_some() {
+            JobStatus::Failed
+        } else {
+            JobStatus::Completed
+        };
+    });
+    info!("Load job {} finished", job_id);
+
+    match failure {
+        Some(e) => Err(e),
+        None => Ok(batches),
+    }
+}
+
+/// Data rows in a CSV file, or `None` if it can't be read; the import reports that error.
+async fn count_rows(file_path: &'static str) -> Option<usize> {
+    tokio::task::spawn_blocking(move || {
+        csv::Reader::from_path(file_path)
+            .ok()
+            .map(|mut reader| reader.records().count())
+    })
+    .await
+    .ok()
+    .flatten()
+}
+
+fn eta_seconds(started: Instant, rows_done: usize, total_rows: Option<usize>) -> Option<u64> {
+    let total_rows = total_rows?;
+    if rows_done == 0 {
+        return None;
+    }
+    let remaining = total_rows.saturating_sub(rows_done);
+    let per_row = started.elapsed().as_secs_f64() / rows_done as f64;
+    Some((per_row * remaining as f64).ceil() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn request(body: serde_json::Value) -> LoadRequest {
+        serde_json::from_value(body).expect("valid load request")
+    }
+
+    fn rejection(body: serde_json::Value) -> String {
+        validate_load_request(&request(body))
+            .expect_err("request should be rejected")
+            .code
+            .into_owned()
+    }
+
+    #[test]
+    fn accepts_a_selection_with_one_start() {
+        assert!(validate_load_request(&LoadRequest::default()).is_ok());
+        assert!(
+            validate_load_request(&request(
+                json!({ "datasets": ["sellers", "customers"], "skip_rows": 10 })
+            ))
+            .is_ok()
+        );
+        assert!(validate_load_request(&request(json!({ "resume_job_id": 3 }))).is_ok());
+    }
+
+    #[test]
+    fn rejects_conflicting_or_empty_selections() {
+        for body in [
+            json!({ "resume_job_id": 3, "datasets": ["customers"] }),
+            json!({ "resume_job_id": 3, "skip_rows": 1 }),
+            json!({ "resume_job_id": 3, "start_after_id": "c1" }),
+        ] {
+            assert_eq!(rejection(body), "resume_exclusive");
+        }
+        assert_eq!(
+            rejection(json!({ "skip_rows": 1, "start_after_id": "c1" })),
+            "start_exclusive"
+        );
+        assert_eq!(rejection(json!({ "datasets": [] })), "empty_datasets");
+        assert_eq!(
+            rejection(json!({ "datasets": ["customers", "sellers", "customers"] })),
+            "duplicate_dataset"
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_fields() {
+        assert!(serde_json::from_value::<LoadRequest>(json!({ "skip": 1 })).is_err());
+    }
+
+    #[test]
+    fn start_follows_the_requested_position() {
+        assert_eq!(LoadRequest::default().start(), StartAt::Beginning);
+        assert_eq!(
+            request(json!({ "skip_rows": 5 })).start(),
+            StartAt::SkipRows(5)
+        );
+        assert_eq!(
+            request(json!({ "start_after_id": "c1" })).start(),
+            StartAt::AfterId("c1".to_string())
+        );
+    }
+
+    #[test]
+    fn eta_needs_a_total_and_some_progress() {
+        let started = Instant::now();
+        assert_eq!(eta_seconds(started, 10, None), None);
+        assert_eq!(eta_seconds(started, 0, Some(10)), None);
+        assert_eq!(eta_seconds(started, 10, Some(10)), Some(0));
+    }
+}
@@ -0,0 +1,10 @@
+//! CSV imports of the Olist datasets, recorded as batches that can be rolled back, dry runs
+//! that only check the files, and generated seed data for development.
+
+pub mod export;
+pub mod geolocation;
+pub mod import;
+pub mod jobs;
+pub mod seed;
+pub mod services;
+pub mod validate;
@@ -0,0 +1,518 @@
+use bigdecimal::BigDecimal;
+use chrono::{Duration, NaiveDateTime, SubsecRound, Utc};
+use rand::distr::Distribution;
+use rand::distr::weighted::WeightedIndex;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info};
+
+use domain::error::AppResult;
+use domain::ids::{CustomerId, ProductId, SellerId};
+use domain::models::{
+    AddItemToOrderDto, BrazilState, CreateCustomerDto, CreateOrderDto, CreateProductDto,
+    CreateSellerDto, OrderStatus,
+};
+
+use crate::import::ImportTargets;
+
+pub const SEED_ACTOR: &str = "system:seed";
+
+pub const MAX_SEED_CUSTOMERS: u32 = 100_000;
+pub const MAX_SEED_ORDERS: u32 = 500_000;
+
+/// How much fake data to generate. Sellers and products default to a share of the customers.
+#[derive(Debug, Default, Deserialize, clap::Args)]
+pub struct SeedOptions {
+    /// Defaults to 1000.
+    #[arg(long)]
+    pub customers: Option<u32>,
+    /// Defaults to 5000.
+    #[arg(long)]
+    pub orders: Option<u32>,
+    /// Defaults to one seller per 20 customers.
+    #[arg(long)]
+    pub sellers: Option<u32>,
+    /// Defaults to one product per 5 customers.
+    #[arg(long)]
+    pub products: Option<u32>,
+    /// Random seed; the same seed generates the same rows (ids aside).
+    #[arg(long)]
+    pub seed: Option<u64>,
+}
+
+impl SeedOptions {
+    pub fn customers(&self) -> u32 {
+        self.customers.unwrap_or(1000).min(MAX_SEED_CUSTOMERS)
+    }
+
+    pub fn orders(&self) -> u32 {
+        self.orders.unwrap_or(5000).min(MAX_SEED_ORDERS)
+    }
+
+    pub fn sellers(&self) -> u32 {
+        self.sellers
+            .unwrap_or(self.customers() / 20)
+            .clamp(1, MAX_SEED_CUSTOMERS)
+    }
+
+    pub fn products(&self) -> u32 {
+        self.products
+            .unwrap_or(self.customers() / 5)
+            .clamp(1, MAX_SEED_CUSTOMERS)
+    }
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct SeedReport {
+    pub seed: u64,
+    pub customers: u32,
+    pub sellers: u32,
+    pub products: u32,
+    pub orders: u32,
+    pub order_items: u32,
+    /// Rows the services rejected; see the logs for why.
+    pub error_count: u32,
+}
+
+/// A city with its CEP prefix range and relative weights for customers and sellers, roughly
+/// following the Olist dataset: sellers are concentrated in São Paulo state.
+struct City {
+    name: &'static str,
+    state: BrazilState,
+    zip_prefixes: (u32, u32),
+    customer_weight: u32,
+    seller_weight: u32,
+}
+
+const CITIES: &[City] = &[
+    city("sao paulo", BrazilState::SaoPaulo, (1000, 5999), 156, 280),
+    city(
+        "rio de janeiro",
+        BrazilState::RioDeJaneiro,
+        (20000, 23799),
+        69,
+        30,
+    ),
+    city(
+        "belo horizonte",
+        BrazilState::MinasGerais,
+        (30000, 31999),
+        28,
+        22,
+    ),
+    city(
+        "brasilia",
+        BrazilState::DistritoFederal,
+        (70000, 72799),
+        21,
+        10,
+    ),
+    city("curitiba", BrazilState::Parana, (80000, 82999), 15, 40),
+    city("campinas", BrazilState::SaoPaulo, (13000, 13139), 15, 40),
+    city(
+        "porto alegre",
+        BrazilState::RioGrandeDoSul,
+        (90000, 91999),
+        14,
+        12,
+    ),
+    city("salvador", BrazilState::Bahia, (40000, 42599), 13, 4),
+    city("guarulhos", BrazilState::SaoPaulo, (7000, 7399), 12, 25),
+    city(
+        "sao bernardo do campo",
+        BrazilState::SaoPaulo,
+        (9600, 9899),
+        9,
+        20,
+    ),
+    city("niteroi", BrazilState::RioDeJaneiro, (24000, 24399), 8, 3),
+    city("santo andre", BrazilState::SaoPaulo, (9000, 9299), 8, 15),
+    city("osasco", BrazilState::SaoPaulo, (6000, 6299), 8, 12),
+    city("santos", BrazilState::SaoPaulo, (11000, 11099), 7, 8),
+    city("goiania", BrazilState::Goias, (74000, 74899), 7, 6),
+    city("fortaleza", BrazilState::Ceara, (60000, 61599), 6, 3),
+    city("recife", BrazilState::Pernambuco, (50000, 52999), 6, 3),
+    city(
+        "florianopolis",
+        BrazilState::SantaCatarina,
+        (88000, 88099),
+        6,
+        8,
+    ),
+    city("sorocaba", BrazilState::SaoPaulo, (18000, 18109), 5, 10),
+    city(
+        "ribeirao preto",
+        BrazilState::SaoPaulo,
+        (14000, 14114),
+        5,
+        15,
+    ),
+    city("belem", BrazilState::Para, (66000, 66999), 4, 1),
+    city("vitoria", BrazilState::EspiritoSanto, (29000, 29099), 4, 3),
+    city(
+        "juiz de fora",
+        BrazilState::MinasGerais,
+        (36000, 36099),
+        4,
+        3,
+    ),
+    city("manaus", BrazilState::Amazonas, (69000, 69099), 3, 1),
+    city("natal", BrazilState::RioGrandeDoNorte, (59000, 59159), 2, 1),
+];
+
+const fn city(
+    name: &'static str,
+    state: BrazilState,
+    zip_prefixes: (u32, u32),
+    customer_weight: u32,
+    seller_weight: u32,
+) -> City {
+    City {
+        name,
+        state,
+        zip_prefixes,
+        customer_weight,
+        seller_weight,
+    }
+}
+
+/// Olist categories with their relative weight and a price range in reais.
+const CATEGORIES: &[(&str, u32, (u32, u32))] = &[
+    ("cama_mesa_banho", 110, (30, 200)),
+    ("beleza_saude", 96, (20, 250)),
+    ("esporte_lazer", 86, (25, 300)),
+    ("moveis_decoracao", 83, (35, 400)),
+    ("informatica_acessorios", 78, (30, 600)),
+    ("utilidades_domesticas", 69, (20, 200)),
+    ("relogios_presentes", 59, (60, 700)),
+    ("telefonia", 45, (15, 350)),
+    ("ferramentas_jardim", 43, (25, 300)),
+    ("automotivo", 42, (20, 300)),
+    ("brinquedos", 41, (20, 250)),
+    ("cool_stuff", 38, (40, 500)),
+    ("perfumaria", 34, (30, 300)),
+    ("bebes", 30, (30, 350)),
+    ("eletronicos", 28, (20, 450)),
+];
+
+/// Order statuses in Olist proportions, per 10 000 orders.
+const STATUSES: &[(OrderStatus, u32)] = &[
+    (OrderStatus::Delivered, 9702),
+    (OrderStatus::Shipped, 111),
+    (OrderStatus::Canceled, 63),
+    (OrderStatus::Unavailable, 61),
+    (OrderStatus::Invoiced, 32),
+    (OrderStatus::Processing, 30),
+    (OrderStatus::Created, 1),
+    (OrderStatus::Approved, 1),
+];
+
+/// Items per order: one for the vast majority.
+const ITEM_COUNTS: &[(i32, u32)] = &[(1, 900), (2, 75), (3, 15), (4, 10)];
+
+/// Purchases are spread over this many days up to now.
+const PURCHASE_WINDOW_DAYS: i64 = 365;
+
+struct SeededProduct {
+    product_id: ProductId,
+    weight_g: i32,
+    price_range: (u32, u32),
+}
+
+/// Generates customers, sellers, products and orders with items, written through the services
+/// so validation and the audit log apply as they do to API writes.
+pub async fn seed(targets: &ImportTargets, options: &SeedOptions) -> AppResult<SeedReport> {
+    let seed = options.seed.unwrap_or_else(rand::random);
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut report = SeedReport {
+        seed,
+        ..SeedReport::default()
+    };
+    info!(
+        "Seeding {} customers, {} sellers, {} products and {} orders (seed {})...",
+        options.customers(),
+        options.sellers(),
+        options.products(),
+        options.orders(),
+        seed
+    );
+
+    let customer_cities = weighted(CITIES.iter().map(|c| c.customer_weight));
+    let mut customer_ids: Vec<CustomerId> = Vec::new();
+    for _ in 0..options.customers() {
+        let city = &CITIES[customer_cities.sample(&mut rng)];
+        let dto = CreateCustomerDto {
+            customer_id: None,
+            customer_unique_id: format!("{:032x}", rng.random::<u128>()),
+            customer_zip_code_prefix: zip_prefix(&mut rng, city),
+            customer_city: city.name.to_string(),
+            customer_state: city.state,
+            customer_cep: None,
+        };
+        match targets.customers.create_customer(dto, SEED_ACTOR).await {
+            Ok(customer) => {
+                customer_ids.push(customer.customer_id);
+                report.customers += 1;
+            }
+            Err(e) => report.failed("customer", e),
+        }
+    }
+
+    let seller_cities = weighted(CITIES.iter().map(|c| c.seller_weight));
+    let mut seller_ids: Vec<SellerId> = Vec::new();
+    for _ in 0..options.sellers() {
+        let city = &CITIES[seller_cities.sample(&mut rng)];
+        let dto = CreateSellerDto {
+            seller_id: None,
+            seller_zip_code_prefix: zip_prefix(&mut rng, city),
+            seller_city: city.name.to_string(),
+            seller_state: city.state,
+        };
+        match targets.sellers.create_seller(dto, SEED_ACTOR).await {
+            Ok(seller) => {
+                seller_ids.push(seller.seller_id);
+                report.sellers += 1;
+            }
+            Err(e) => report.failed("seller", e),
+        }
+    }
+
+    let categories = weighted(CATEGORIES.iter().map(|(_, weight, _)| *weight));
+    let mut products: Vec<SeededProduct> = Vec::new();
+    for _ in 0..options.products() {
+        let (category, _, price_range) = CATEGORIES[categories.sample(&mut rng)];
+        let dto = CreateProductDto {
+            product_id: None,
+            product_category_name: category.to_string(),
+            product_name_lenght: rng.random_range(20..=64),
+            product_description_lenght: rng.random_range(100..=3000),
+            product_photos_qty: rng.random_range(1..=6),
+            // Skewed towards light parcels, as most marketplace items are.
+            product_weight_g: 100 + (rng.random::<f64>().powi(3) * 15_000.0) as i32,
+            product_length_cm: rng.random_range(16..=80),
+            product_height_cm: rng.random_range(2..=60),
+            product_width_cm: rng.random_range(11..=60),
+            product_name: None,
+            description: None,
+            price: None,
+            active: None,
+        };
+        let weight_g = dto.product_weight_g;
+        match targets.products.create_product(dto, SEED_ACTOR).await {
+            Ok(product) => {
+                products.push(SeededProduct {
+                    product_id: product.product_id,
+                    weight_g,
+                    price_range,
+                });
+                report.products += 1;
+            }
+            Err(e) => report.failed("product", e),
+        }
+    }
+
+    if customer_ids.is_empty() || seller_ids.is_empty() || products.is_empty() {
+        info!("Nothing to build orders from, skipping orders.");
+        return Ok(report);
+    }
+
+    let statuses = weighted(STATUSES.iter().map(|(_, weight)| *weight));
+    let item_counts = weighted(ITEM_COUNTS.iter().map(|(_, weight)| *weight));
+    let now = Utc::now().naive_utc().trunc_subsecs(0);
+    for _ in 0..options.orders() {
+        let customer_id = customer_ids[rng.random_range(0..customer_ids.len())].clone();
+        let status = STATUSES[statuses.sample(&mut rng)].0;
+        let dto = order(&mut rng, customer_id, status, now);
+        let shipping_limit_date = dto.order_approved_at + Duration::days(6);
+        let item_count = match dto.order_status {
+            OrderStatus::Unavailable => 0,
+            _ => ITEM_COUNTS[item_counts.sample(&mut rng)].0,
+        };
+
+        let order = match targets.orders.create_order(dto, SEED_ACTOR).await {
+            Ok(order) => order,
+            Err(e) => {
+                report.failed("order", e);
+                continue;
+            }
+        };
+        report.orders += 1;
+
+        for order_item_id in 1..=item_count {
+            let product = &products[rng.random_range(0..products.len())];
+            let (min_price, max_price) = product.price_range;
+            let price_cents = rng.random_range(min_price * 100..=max_price * 100);
+            let freight_cents = 700 + product.weight_g as u32 / 4 + rng.random_range(0..1000);
+            let item = AddItemToOrderDto {
+                order_item_id,
+                product_id: product.product_id.clone(),
+                seller_id: seller_ids[rng.random_range(0..seller_ids.len())].clone(),
+                shipping_limit_date,
+                price: BigDecimal::new(price_cents.into(), 2),
+                freight_value: BigDecimal::new(freight_cents.into(), 2),
+            };
+            match targets
+                .orders
+                .add_item_to_order(&order.order_id, item, SEED_ACTOR)
+                .await
+            {
+                Ok(_) => report.order_items += 1,
+                Err(e) => report.failed("order item", e),
+            }
+        }
+    }
+
+    info!(
+        "Seeded {} customers, {} sellers, {} products, {} orders and {} order items ({} failed).",
+        report.customers,
+        report.sellers,
+        report.products,
+        report.orders,
+        report.order_items,
+        report.error_count
+    );
+    Ok(report)
+}
+
+impl SeedReport {
+    fn failed(&mut self, entity: &str, e: domain::error::AppError) {
+        error!("Failed to seed {}: {:?}", entity, e);
+        self.error_count += 1;
+    }
+}
+
+fn weighted(weights: impl Iterator<Item = u32>) -> WeightedIndex<u32> {
+    WeightedIndex::new(weights).expect("seed weights are positive")
+}
+
+fn zip_prefix(rng: &mut StdRng, city: &City) -> String {
+    let (low, high) = city.zip_prefixes;
+    format!("{:05}", rng.random_range(low..=high))
+}
+
+/// An order purchased within the last year, with milestone dates that fit its status. Orders
+/// too recent to have reached a milestone get the status they would have today instead.
+fn order(
+    rng: &mut StdRng,
+    customer_id: CustomerId,
+    status: OrderStatus,
+    now: NaiveDateTime,
+) -> CreateOrderDto {
+    let purchased = now - Duration::minutes(rng.random_range(0..PURCHASE_WINDOW_DAYS * 24 * 60));
+    let approved = purchased + Duration::minutes(rng.random_range(10..48 * 60));
+    let carrier = approved + Duration::hours(rng.random_range(12..5 * 24));
+    let delivered = carrier + Duration::hours(rng.random_range(2 * 24..20 * 24));
+    let estimated = purchased + Duration::days(rng.random_range(15..35));
+
+    let status = match status {
+        OrderStatus::Delivered | OrderStatus::Shipped if carrier > now => OrderStatus::Processing,
+        OrderStatus::Delivered if delivered > now => OrderStatus::Shipped,
+        status => status,
+    };
+    let (carrier, delivered) = match status {
+        OrderStatus::Delivered => (Some(carrier), Some(delivered)),
+        OrderStatus::Shipped => (Some(carrier), None),
+        _ => (None, None),
+    };
+
+    CreateOrderDto {
+        order_id: None,
+        customer_id,
+        order_status: status,
+        order_purchase_timestamp: purchased,
+        order_approved_at: if status == OrderStatus::Created {
+            purchased
+        } else {
+            approved.min(now)
+        },
+        order_delivered_carrier_date: carrier,
+        order_delivered_customer_date: delivered,
+        order_estimated_delivery_date: estimated,
+        shipping_address_id: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(customers: Option<u32>, orders: Option<u32>) -> SeedOptions {
+        SeedOptions {
+            customers,
+            orders,
+            ..SeedOptions::default()
+        }
+    }
+
+    #[test]
+    fn sizes_default_to_a_share_of_the_customers() {
+        let defaults = SeedOptions::default();
+        assert_eq!(defaults.customers(), 1000);
+        assert_eq!(defaults.orders(), 5000);
+        assert_eq!(defaults.sellers(), 50);
+        assert_eq!(defaults.products(), 200);
+
+        let tiny = options(Some(3), Some(0));
+        assert_eq!(tiny.sellers(), 1, "at least one seller");
+        assert_eq!(tiny.products(), 1, "at least one product");
+        assert_eq!(tiny.orders(), 0);
+    }
+
+    #[test]
+    fn sizes_are_capped() {
+        let huge = options(Some(u32::MAX), Some(u32::MAX));
+        assert_eq!(huge.customers(), MAX_SEED_CUSTOMERS);
+        assert_eq!(huge.orders(), MAX_SEED_ORDERS);
+        assert_eq!(huge.sellers(), MAX_SEED_CUSTOMERS / 20);
+    }
+
+    #[test]
+    fn orders_get_milestones_that_fit_their_status() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let now = Utc::now().naive_utc().trunc_subsecs(0);
+        for _ in 0..500 {
+            for (status, _) in STATUSES {
+                let dto = order(&mut rng, CustomerId::generate(), *status, now);
+                assert!(dto.order_purchase_timestamp <= dto.order_approved_at);
+                assert!(dto.order_approved_at <= now);
+                match dto.order_status {
+                    OrderStatus::Delivered => {
+                        let carrier = dto.order_delivered_carrier_date.expect("carrier date");
+                        let delivered = dto.order_delivered_customer_date.expect("delivery date");
+                        assert!(dto.order_approved_at <= carrier && carrier <= delivered);
+                        assert!(delivered <= now);
+                    }
+                    OrderStatus::Shipped => {
+                        let carrier = dto.order_delivered_carrier_date.expect("carrier date");
+                        assert!(carrier <= now);
+                        assert!(dto.order_delivered_customer_date.is_none());
+                    }
+                    other => {
+                        assert!(dto.order_delivered_carrier_date.is_none(), "{other:?}");
+                        assert!(dto.order_delivered_customer_date.is_none(), "{other:?}");
+                    }
+                }
+                if dto.order_status == OrderStatus::Created {
+                    assert_eq!(dto.order_approved_at, dto.order_purchase_timestamp);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn the_same_seed_draws_the_same_orders() {
+        let now = Utc::now().naive_utc().trunc_subsecs(0);
+        let customer_id = CustomerId::generate();
+        let draw = |seed| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let dto = order(&mut rng, customer_id.clone(), OrderStatus::Delivered, now);
+            (
+                dto.order_purchase_timestamp,
+                dto.order_delivered_customer_date,
+            )
+        };
+        assert_eq!(draw(42), draw(42));
+        assert_ne!(draw(42), draw(43));
+    }
+}
@@ -0,0 +1,284 @@
+use bytes::Bytes;
+use clap::ValueEnum;
+use futures::{Stream, StreamExt};
+use serde_json::json;
+use std::collections::HashSet;
+use std::io;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tracing::{error, instrument};
+
+use domain::cache::{self, LookupCache, ResponseCache};
+use domain::error::{AppError, AppResult};
+use domain::models::{
+    AuditAction, BatchResume, ExportFormat, ImportBatch, ImportBatchStatus, ImportRollback,
+    ImportRowError, NewImportRowError, PaginatedResponse, PaginationParams, StoredLoadJob,
+};
+use domain::repositories::ImportRepository;
+use domain::services::{AuditService, EXPORT_CHANNEL_CAPACITY};
+
+use crate::export::encode_rows;
+use crate::import::Dataset;
+
+/// How CSV imports insert their rows.
+#[derive(Debug, Clone, Copy)]
+pub struct ImportConfig {
+    /// Chunks of rows inserted at once, each in its own transaction.
+    pub concurrency: usize,
+}
+
+/// Bookkeeping for CSV import batches and their rollback.
+#[derive(Clone)]
+pub struct ImportService {
+    repository: Arc<dyn ImportRepository>,
+    audit: AuditService,
+    cache: ResponseCache,
+    lookups: LookupCache,
+    config: ImportConfig,
+}
+
+impl ImportService {
+    pub fn new(
+        repository: Arc<dyn ImportRepository>,
+        audit: AuditService,
+        cache: ResponseCache,
+        lookups: LookupCache,
+        config: ImportConfig,
+    ) -> Self {
+        Self {
+            repository,
+            audit,
+            cache,
+            lookups,
+            config,
+        }
+    }
+
+    pub fn concurrency(&self) -> usize {
+        self.config.concurrency.max(1)
+    }
+
+    /// Records a load of `datasets`, returning its job id; the batches it begins point at it.
+    pub async fn begin_load_job(&self, datasets: &[Dataset], actor: &str) -> AppResult<i64> {
+        let datasets: Vec<&str> = datasets.iter().map(Dataset::as_str).collect();
+        Ok(self.repository.begin_load_job(&datasets, actor).await?)
+    }
+
+    pub async fn find_load_job(&self, job_id: i64) -> AppResult<StoredLoadJob> {
+        self.repository
+            .find_load_job(job_id)
+            .await?
+            .ok_or(AppError::NotFound)
+    }
+
+    pub async fn begin_batch(
+        &self,
+        dataset: Dataset,
+        source: &str,
+        actor: &str,
+        load_job_id: Option<i64>,
+    ) -> AppResult<ImportBatch> {
+        let batch = self
+            .repository
+            .begin_batch(dataset.as_str(), source, actor, load_job_id)
+            .await?;
+        // Running batches are counted in today's stats.
+        self.cache.invalidate(cache::TODAY_STATS).await;
+        Ok(batch)
+    }
+
+    pub async fn record_rows(&self, batch_id: i64, entity_ids: &[String]) -> AppResult<()> {
+        Ok(self.repository.record_rows(batch_id, entity_ids).await?)
+    }
+
+    /// Notes that every row up to `line` of the batch's file has been handled.
+    pub async fn checkpoint(&self, batch_id: i64, line: i64) -> AppResult<()> {
+        Ok(self.repository.checkpoint(batch_id, line).await?)
+    }
+
+    /// Reopens a running or failed batch to continue after its checkpoint. Fails with a
+    /// conflict when the batch has since completed or been rolled back.
+    pub async fn resume_batch(&self, batch_id: i64) -> AppResult<BatchResume> {
+        let resume = self
+            .repository
+            .resume_batch(batch_id)
+            .await?
+            .ok_or_else(|| {
+                AppError::JobAlreadyRunning(format!(
+                    "Import batch {} is no longer resumable",
+                    batch_id
+                ))
+            })?;
+        self.cache.invalidate(cache::TODAY_STATS).await;
+        Ok(resume)
+    }
+
+    pub async fn record_errors(
+        &self,
+        batch_id: i64,
+        errors: &[NewImportRowError],
+    ) -> AppResult<()> {
+        Ok(self.repository.record_errors(batch_id, errors).await?)
+    }
+
+    /// Which of `keys` are already in the dataset's table.
+    pub async fn existing_keys(
+        &self,
+        dataset: Dataset,
+        keys: &[String],
+    ) -> AppResult<HashSet<String>> {
+        let existing = self
+            .repository
+            .existing_keys(dataset.as_str(), dataset.key_column(), keys)
+            .await?;
+        Ok(existing.into_iter().collect())
+    }
+
+    pub async fn finish_batch(
+        &self,
+        batch_id: i64,
+        status: ImportBatchStatus,
+        success_count: usize,
+        error_count: usize,
+    ) -> AppResult<ImportBatch> {
+        let batch = self
+            .repository
+            .finish_batch(
+                batch_id,
+                status,
+                i32::try_from(success_count).unwrap_or(i32::MAX),
+                i32::try_from(error_count).unwrap_or(i32::MAX),
+            )
+            .await?;
+        self.cache.invalidate(cache::TODAY_STATS).await;
+        Ok(batch)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_batches(
+        &self,
+        pagination: PaginationParams,
+    ) -> AppResult<PaginatedResponse<ImportBatch>> {
+        let (_, _, page, page_size) = pagination.normalize();
+        let (batches, total_count) = self.repository.find_all(&pagination).await?;
+        Ok(PaginatedResponse::new(
+            batches,
+            total_count,
+            page,
+            page_size,
+        ))
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_batch(&self, batch_id: i64) -> AppResult<ImportBatch> {
+        self.repository
+            .find_by_id(batch_id)
+            .await?
+            .ok_or(AppError::NotFound)
+    }
+
+    /// Rows the given batches could not import, by batch and then line.
+    #[instrument(skip(self))]
+    pub async fn get_errors(
+        &self,
+        batch_ids: &[i64],
+        pagination: PaginationParams,
+    ) -> AppResult<PaginatedResponse<ImportRowError>> {
+        let (_, _, page, page_size) = pagination.normalize();
+        let (errors, total_count) = self.repository.find_errors(batch_ids, &pagination).await?;
+        Ok(PaginatedResponse::new(errors, total_count, page, page_size))
+    }
+
+    /// Streams every failed row of the given batches, one encoded row per chunk.
+    #[instrument(skip(self))]
+    pub fn export_errors(
+        &self,
+        batch_ids: Vec<i64>,
+        format: ExportFormat,
+    ) -> impl Stream<Item = io::Result<Bytes>> + use<> {
+        let (tx, rx) = mpsc::channel(EXPORT_CHANNEL_CAPACITY);
+        let repository = self.repository.clone();
+
+        tokio::spawn(async move {
+            let mut rows = repository.stream_errors(&batch_ids);
+            while let Some(row) = rows.next().await {
+                if let Err(e) = &row {
+                    error!("Import error export failed: {:?}", e);
+                }
+                let failed = row.is_err();
+                if tx.send(row).await.is_err() || failed {
+                    return;
+                }
+            }
+        });
+
+        encode_rows(ReceiverStream::new(rx), format)
+    }
+
+    /// Deletes every row the batch inserted. Fails with a conflict when the batch is still
+    /// running or already rolled back, or when other rows reference the imported ones
+    /// (e.g. orders imported later for customers from this batch).
+    #[instrument(skip(self))]
+    pub async fn rollback_batch(&self, batch_id: i64, actor: &str) -> AppResult<ImportRollback> {
+        let batch = self.get_batch(batch_id).await?;
+        if batch.status == ImportBatchStatus::RolledBack.as_str() {
+            return Err(AppError::RollbackNotAllowed(format!(
+                "Import batch {} was already rolled back",
+                batch_id
+            )));
+        }
+        if batch.status == ImportBatchStatus::Running.as_str() {
+            return Err(AppError::RollbackNotAllowed(format!(
+                "Import batch {} is still running",
+                batch_id
+            )));
+        }
+
+        let dataset = Dataset::from_str(&batch.dataset, false).map_err(|_| {
+            AppError::ConfigError(format!("Unknown import dataset {}", batch.dataset))
+        })?;
+
+        let (batch, rows_deleted) = self
+            .repository
+            .rollback(batch_id, dataset.as_str(), dataset.key_column())
+            .await
+            .map_err(|e| match &e {
+                sqlx::Error::Database(db_err) if db_err.is_foreign_key_violation() => {
+                    AppError::RollbackNotAllowed(format!(
+                        "Import batch {} cannot be rolled back: other records reference its rows \
+                         (roll back the dependent imports first)",
+                        batch_id
+                    ))
+                }
+                _ => AppError::DatabaseError(e),
+            })?
+            .ok_or_else(|| {
+                AppError::RollbackNotAllowed(format!(
+                    "Import batch {} changed state during rollback",
+                    batch_id
+                ))
+            })?;
+
+        self.cache.invalidate(cache::TODAY_STATS).await;
+        if dataset == Dataset::Products {
+            self.cache.invalidate(cache::PRODUCTS).await;
+            self.lookups.invalidate_products().await;
+        }
+
+        self.audit
+            .record_event(
+                "import_batch",
+                &batch_id.to_string(),
+                AuditAction::Rollback,
+                actor,
+                json!({ "dataset": batch.dataset, "rows_deleted": rows_deleted }),
+            )
+            .await;
+
+        Ok(ImportRollback {
+            batch,
+            rows_deleted,
+        })
+    }
+}
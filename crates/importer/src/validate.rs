@@ -0,0 +1,222 @@
+//! Dry runs of CSV imports: every row is parsed and checked as the import would, without
+//! writing anything.
+
+use serde::de::DeserializeOwned;
+use std::collections::{HashMap, HashSet};
+
+use domain::error::AppResult;
+use domain::models::{
+    CreateCustomerDto, CreateOrderDto, CreateProductDto, CreateSellerDto, DatasetValidation,
+    NewImportRowError, ValidationReport,
+};
+use domain::services::{CustomerService, OrderService, ProductService, SellerService};
+
+use crate::import::{Dataset, ImportTargets, describe_error, encode_record, open_csv, read_chunks};
+
+/// Failing rows listed per dataset; the rest are only counted.
+const VALIDATION_ERROR_SAMPLE: usize = 100;
+
+/// What a row would insert: its primary key and the customer it references, if any.
+struct RowKeys {
+    key: String,
+    customer_id: Option<String>,
+}
+
+/// Checks `datasets` in order. Customers that pass count as existing for the datasets after
+/// them, so orders may reference customers from the same load.
+pub async fn validate_datasets(
+    targets: &ImportTargets,
+    datasets: &[Dataset],
+) -> AppResult<ValidationReport> {
+    let mut customers = HashSet::new();
+    let mut reports = Vec::with_capacity(datasets.len());
+    for &dataset in datasets {
+        let (report, keys) =
+            validate_dataset(targets, dataset, dataset.default_path(), &customers).await?;
+        if dataset == Dataset::Customers {
+            customers.extend(keys);
+        }
+        reports.push(report);
+    }
+
+    Ok(ValidationReport {
+        valid: reports.iter().all(|report| report.error_count == 0),
+        datasets: reports,
+    })
+}
+
+/// Checks one file: each row must parse and validate, its key must be neither in the table
+/// nor repeated in the file, and an order's customer must exist or be in `customers`.
+/// Returns the report and the keys of the rows that passed.
+pub async fn validate_dataset(
+    targets: &ImportTargets,
+    dataset: Dataset,
+    file_path: &str,
+    customers: &HashSet<String>,
+) -> AppResult<(DatasetValidation, HashSet<String>)> {
+    match dataset {
+        Dataset::Customers => {
+            check_csv(
+                targets,
+                dataset,
+                file_path,
+                customers,
+                |dto: CreateCustomerDto| {
+                    let (id, _, _) = CustomerService::prepare(dto)?;
+                    Ok(RowKeys {
+                        key: id.into(),
+                        customer_id: None,
+                    })
+                },
+            )
+            .await
+        }
+        Dataset::Sellers => {
+            check_csv(
+                targets,
+                dataset,
+                file_path,
+                customers,
+                |dto: CreateSellerDto| {
+                    let (id, _, _) = SellerService::prepare(dto)?;
+                    Ok(RowKeys {
+                        key: id.into(),
+                        customer_id: None,
+                    })
+                },
+            )
+            .await
+        }
+        Dataset::Orders => {
+            check_csv(
+                targets,
+                dataset,
+                file_path,
+                customers,
+                |dto: CreateOrderDto| {
+                    let (id, dto) = OrderService::prepare(dto)?;
+                    Ok(RowKeys {
+                        key: id.into(),
+                        customer_id: Some(dto.customer_id.into()),
+                    })
+                },
+            )
+            .await
+        }
+        Dataset::Products => {
+            check_csv(
+                targets,
+                dataset,
+                file_path,
+                customers,
+                |dto: CreateProductDto| {
+                    let (id, _) = ProductService::prepare(dto)?;
+                    Ok(RowKeys {
+                        key: id.into(),
+                        customer_id: None,
+                    })
+                },
+            )
+            .await
+        }
+    }
+}
+
+async fn check_csv<T: DeserializeOwned>(
+    targets: &ImportTargets,
+    dataset: Dataset,
+    file_path: &str,
+    customers: &HashSet<String>,
+    check: impl Fn(T) -> AppResult<RowKeys>,
+) -> AppResult<(DatasetValidation, HashSet<String>)> {
+    let (rdr, headers) = open_csv(file_path)?;
+    let mut report = DatasetValidation {
+        dataset: dataset.as_str(),
+        total_rows: 0,
+        valid_count: 0,
+        error_count: 0,
+        errors: Vec::new(),
+    };
+    // Line of the first valid row with each key, to point duplicates at it.
+    let mut first_lines: HashMap<String, i64> = HashMap::new();
+
+    for chunk in read_chunks::<T>(rdr.into_records(), headers, file_path) {
+        report.total_rows += chunk.len();
+        let checked: Vec<_> = chunk
+            .into_iter()
+            .map(|row| {
+                let keys = row
+                    .parsed
+                    .and_then(|record| check(record).map_err(|e| describe_error(&e)));
+                (row.row_number, row.raw, keys)
+            })
+            .collect();
+
+        let keys: Vec<String> = checked
+            .iter()
+            .filter_map(|(_, _, keys)| keys.as_ref().ok())
+            .map(|keys| keys.key.clone())
+            .collect();
+        let existing = targets.imports.existing_keys(dataset, &keys).await?;
+
+        let referenced: Vec<String> = checked
+            .iter()
+            .filter_map(|(_, _, keys)| keys.as_ref().ok()?.customer_id.clone())
+            .filter(|customer_id| !customers.contains(customer_id))
+            .collect();
+        let existing_customers = if referenced.is_empty() {
+            HashSet::new()
+        } else {
+            targets
+                .imports
+                .existing_keys(Dataset::Customers, &referenced)
+                .await?
+        };
+
+        for (row_number, raw, keys) in checked {
+            let outcome = keys.and_then(|keys| {
+                if existing.contains(&keys.key) {
+                    return Err(format!(
+                        "{} {} already exists",
+                        dataset.key_column(),
+                        keys.key
+                    ));
+                }
+                if let Some(line) = first_lines.get(&keys.key) {
+                    return Err(format!(
+                        "{} {} repeats line {}",
+                        dataset.key_column(),
+                        keys.key,
+                        line
+                    ));
+                }
+                if let Some(customer_id) = &keys.customer_id
+                    && !customers.contains(customer_id)
+                    && !existing_customers.contains(customer_id)
+                {
+                    return Err(format!("customer_id {} does not exist", customer_id));
+                }
+                Ok(keys.key)
+            });
+
+            match outcome {
+                Ok(key) => {
+                    report.valid_count += 1;
+                    first_lines.insert(key, row_number);
+                }
+                Err(reason) => {
+                    report.error_count += 1;
+                    if report.errors.len() < VALIDATION_ERROR_SAMPLE {
+                        report.errors.push(NewImportRowError {
+                            row_number,
+                            record: raw.as_ref().and_then(encode_record),
+                            reason,
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    Ok((report, first_lines.into_keys().collect()))
+}
@@ -0,0 +1,188 @@
+use async_trait::async_trait;
+use moka::future::Cache;
+use redis::AsyncCommands;
+use redis::aio::{ConnectionManager, ConnectionManagerConfig};
+use std::future::Future;
+use std::hash::Hash;
+use std::sync::Arc;
+use std::time::Duration;
+
+use domain::cache::{CacheStore, LookupStore, LookupStores};
+
+/// Prefix of every key the cache writes, so a flush leaves other data in the Redis database alone.
+const KEY_PREFIX: &str = "brazilian_ecommerce:cache:";
+
+/// Keys deleted per `UNLINK` when dropping a prefix.
+const DELETE_BATCH_SIZE: usize = 500;
+
+/// Cache reads sit in front of database queries, so a slow or unreachable Redis is given up
+/// on quickly.
+pub(crate) const COMMAND_TIMEOUT: Duration = Duration::from_millis(500);
+/// Dropping a prefix scans the whole keyspace, so the scan past its first page gets longer.
+const DELETE_PREFIX_TIMEOUT: Duration = Duration::from_secs(10);
+const CONNECTION_TIMEOUT: Duration = Duration::from_secs(2);
+const RECONNECT_MAX_DELAY_MS: u64 = 1000;
+
+/// [`CacheStore`] on Redis. The connection is shared and re-established in the background
+/// after Redis restarts; commands issued meanwhile fail after [`COMMAND_TIMEOUT`].
+#[derive(Clone)]
+pub struct RedisCacheStore {
+    connection: ConnectionManager,
+}
+
+impl RedisCacheStore {
+    pub async fn connect(url: &str) -> redis::RedisResult<Self> {
+        let connection = connection_manager(url).await?;
+        Ok(Self { connection })
+    }
+}
+
+/// Shared connection to `url` that gives up on commands after [`COMMAND_TIMEOUT`] and
+/// reconnects in the background.
+pub(crate) async fn connection_manager(url: &str) -> redis::RedisResult<ConnectionManager> {
+    let client = redis::Client::open(url)?;
+    let config = ConnectionManagerConfig::new()
+        .set_connection_timeout(CONNECTION_TIMEOUT)
+        .set_response_timeout(COMMAND_TIMEOUT)
+        .set_number_of_retries(2)
+        // The default backoff waits up to 100s between reconnect attempts.
+        .set_max_delay(RECONNECT_MAX_DELAY_MS);
+    ConnectionManager::new_with_config(client, config).await
+}
+
+#[async_trait]
+impl CacheStore for RedisCacheStore {
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, String> {
+        let mut connection = self.connection.clone();
+        bounded(
+            COMMAND_TIMEOUT,
+            connection.get(format!("{}{}", KEY_PREFIX, key)),
+        )
+        .await
+    }
+
+    async fn set(&self, key: &str, value: Vec<u8>, ttl: Duration) -> Result<(), String> {
+        let mut connection = self.connection.clone();
+        bounded(
+            COMMAND_TIMEOUT,
+            connection.set_ex(
+                format!("{}{}", KEY_PREFIX, key),
+                value,
+                ttl.as_secs().max(1),
+            ),
+        )
+        .await
+    }
+
+    async fn delete_prefix(&self, prefix: &str) -> Result<u64, String> {
+        let pattern = format!("{}{}*", KEY_PREFIX, escape_glob(prefix));
+        let mut scan = self.connection.clone();
+        let mut connection = self.connection.clone();
+        // The first page answers as fast as any command, so an unreachable Redis fails here.
+        let mut iter = bounded(COMMAND_TIMEOUT, scan.scan_match::<_, String>(&pattern)).await?;
+        bounded(DELETE_PREFIX_TIMEOUT, async {
+            let mut keys = Vec::new();
+            while let Some(key) = iter.next_item().await {
+                keys.push(key);
+            }
+
+            let mut deleted = 0;
+            for batch in keys.chunks(DELETE_BATCH_SIZE) {
+                deleted += connection.unlink::<_, u64>(batch).await?;
+            }
+            Ok(deleted)
+        })
+        .await
+    }
+
+    async fn ping(&self) -> Result<(), String> {
+        let mut connection = self.connection.clone();
+        bounded(COMMAND_TIMEOUT, connection.ping::<String>())
+            .await
+            .map(|_| ())
+    }
+}
+
+/// Runs a command, giving up after `timeout`. While Redis is unreachable the connection
+/// manager keeps retrying, and commands would otherwise wait for it.
+pub(crate) async fn bounded<T>(
+    timeout: Duration,
+    command: impl Future<Output = redis::RedisResult<T>>,
+) -> Result<T, String> {
+    match tokio::time::timeout(timeout, command).await {
+        Ok(result) => result.map_err(|e| e.to_string()),
+        Err(_) => Err(format!("no reply within {} ms", timeout.as_millis())),
+    }
+}
+
+/// Escapes the characters `SCAN MATCH` treats as glob syntax.
+fn escape_glob(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '*' | '?' | '[' | ']' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// [`LookupStores`] on moka, each store an in-process cache bounded by entries and TTL.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MokaLookupStores;
+
+impl LookupStores for MokaLookupStores {
+    fn build<K, V>(&self, max_entries: u64, ttl: Duration) -> Arc<dyn LookupStore<K, V>>
+    where
+        K: Hash + Eq + Send + Sync + 'static,
+        V: Clone + Send + Sync + 'static,
+    {
+        Arc::new(MokaLookupStore(
+            Cache::builder()
+                .max_capacity(max_entries)
+                .time_to_live(ttl)
+                .build(),
+        ))
+    }
+}
+
+struct MokaLookupStore<K, V>(Cache<K, V>);
+
+#[async_trait]
+impl<K, V> LookupStore<K, V> for MokaLookupStore<K, V>
+where
+    K: Hash + Eq + Send + Sync + 'static,
+    V: Clone + Send + Sync + 'static,
+{
+    async fn get(&self, key: &K) -> Option<V> {
+        self.0.get(key).await
+    }
+
+    async fn insert(&self, key: K, value: V) {
+        self.0.insert(key, value).await;
+    }
+
+    async fn invalidate(&self, key: &K) {
+        self.0.invalidate(key).await;
+    }
+
+    fn invalidate_all(&self) {
+        self.0.invalidate_all();
+    }
+
+    async fn entry_count(&self) -> u64 {
+        self.0.run_pending_tasks().await;
+        self.0.entry_count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_glob_syntax_in_prefixes() {
+        assert_eq!(escape_glob("olist:products:"), "olist:products:");
+        assert_eq!(escape_glob("a*b?[c]\\"), "a\\*b\\?\\[c\\]\\\\");
+    }
+}
@@ -0,0 +1,76 @@
+//! Server-side cancellation of queries whose caller stopped waiting. Dropping a sqlx future
+//! only stops reading the reply; without this, a list query abandoned by a disconnected client
+//! or a request timeout keeps running on Postgres until it finishes.
+
+use sqlx::{PgConnection, PgPool, Postgres, Result as SqlxResult, pool::PoolConnection};
+use tracing::{debug, warn};
+
+/// Runs `query` on a connection of its own. If the returned future is dropped before `query`
+/// finishes, the statement running on that connection is cancelled with `pg_cancel_backend`
+/// and the connection is closed rather than handed back to the pool.
+pub async fn cancel_on_drop<T>(
+    pool: &PgPool,
+    query: impl AsyncFnOnce(&mut PgConnection) -> SqlxResult<T>,
+) -> SqlxResult<T> {
+    let mut conn = pool.acquire().await?;
+    let backend_pid: i32 = sqlx::query_scalar("SELECT pg_backend_pid()")
+        .fetch_one(&mut *conn)
+        .await?;
+    let mut guard = CancelGuard {
+        pool: pool.clone(),
+        backend_pid,
+        conn: Some(conn),
+    };
+    let result = query(
+        guard
+            .conn
+            .as_mut()
+            .expect("connection is held until disarmed"),
+    )
+    .await;
+    guard.disarm();
+    result
+}
+
+/// Holds the connection of a query in flight, cancelling the query when dropped while armed.
+struct CancelGuard {
+    pool: PgPool,
+    backend_pid: i32,
+    conn: Option<PoolConnection<Postgres>>,
+}
+
+impl CancelGuard {
+    /// The query finished: the connection goes back to the pool as usual.
+    fn disarm(&mut self) {
+        self.conn.take();
+    }
+}
+
+impl Drop for CancelGuard {
+    fn drop(&mut self) {
+        let Some(conn) = self.conn.take() else {
+            return;
+        };
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+        let pool = self.pool.clone();
+        let backend_pid = self.backend_pid;
+        runtime.spawn(async move {
+            match sqlx::query("SELECT pg_cancel_backend($1)")
+                .bind(backend_pid)
+                .execute(&pool)
+                .await
+            {
+                Ok(_) => debug!("Cancelled abandoned query on backend {}", backend_pid),
+                Err(e) => warn!(
+                    "Failed to cancel abandoned query on backend {}: {}",
+                    backend_pid, e
+                ),
+            }
+            // The cancel signal is delivered asynchronously and could still hit the next query
+            // run on this connection, so it is closed instead of reused
+            let _ = conn.close().await;
+        });
+    }
+}
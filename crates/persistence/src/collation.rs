@@ -0,0 +1,63 @@
+use sqlx::PgPool;
+
+/// How text columns are ordered in `ORDER BY` clauses.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SortCollation {
+    /// The database default collation.
+    #[default]
+    Default,
+    /// The ICU `pt_br_natural` collation created by the migrations (accent and case insensitive).
+    Icu,
+    /// `unaccent(lower(column))` sort keys, for servers built without ICU.
+    Unaccent,
+}
+
+impl SortCollation {
+    /// Whether the database has what the collation sorts with. The migrations skip the ICU
+    /// collation and the `unaccent` extension when the server can't create them, so without
+    /// this check the first sorted query would fail instead.
+    pub async fn is_available(&self, pool: &PgPool) -> sqlx::Result<bool> {
+        let query = match self {
+            SortCollation::Default => return Ok(true),
+            SortCollation::Icu => {
+                "SELECT EXISTS (SELECT 1 FROM pg_collation WHERE collname = 'pt_br_natural')"
+            }
+            SortCollation::Unaccent => {
+                "SELECT EXISTS (SELECT 1 FROM pg_extension WHERE extname = 'unaccent')"
+            }
+        };
+        sqlx::query_scalar(query).fetch_one(pool).await
+    }
+
+    /// Builds the `ORDER BY` expression for a column name known at compile time.
+    pub fn order_by(&self, column: &str) -> String {
+        match self {
+            SortCollation::Default => column.to_string(),
+            SortCollation::Icu => format!("{} COLLATE pt_br_natural", column),
+            SortCollation::Unaccent => format!("unaccent(lower({}))", column),
+        }
+    }
+}
+
+impl std::str::FromStr for SortCollation {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_lowercase().as_str() {
+            "default" => Ok(SortCollation::Default),
+            "icu" => Ok(SortCollation::Icu),
+            "unaccent" => Ok(SortCollation::Unaccent),
+            other => Err(format!("unknown collation '{}'", other)),
+        }
+    }
+}
+
+impl std::fmt::Display for SortCollation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            SortCollation::Default => "default",
+            SortCollation::Icu => "icu",
+            SortCollation::Unaccent => "unaccent",
+        })
+    }
+}
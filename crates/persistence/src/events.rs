@@ -0,0 +1,67 @@
+use domain::events::OrderStatusEvents;
+use domain::models::OrderStatusChange;
+use domain::tenancy::TenantId;
+use serde::Deserialize;
+use sqlx::PgPool;
+use sqlx::postgres::PgListener;
+use std::collections::HashMap;
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+/// Postgres channel the `orders` status trigger publishes to.
+pub const ORDER_STATUS_CHANNEL: &str = "order_status_changed";
+
+const LISTENER_RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// An `order_status_changed` payload. Notifications sent before tenants existed carry no
+/// `tenant_id` and belong to the default tenant.
+#[derive(Deserialize)]
+struct TenantStatusChange {
+    #[serde(default)]
+    tenant_id: TenantId,
+    #[serde(flatten)]
+    change: OrderStatusChange,
+}
+
+/// Relays `order_status_changed` notifications onto the broadcast channel of the tenant
+/// they belong to. Changes of tenants this process doesn't serve are dropped.
+///
+/// The listener reconnects on its own after a dropped connection; notifications sent
+/// while it was down are lost, which is why waiters always re-check the stored version.
+pub async fn run(pool: PgPool, events: HashMap<TenantId, OrderStatusEvents>) {
+    let mut listener = loop {
+        match connect(&pool).await {
+            Ok(listener) => break listener,
+            Err(e) => {
+                error!("Failed to listen for order status changes: {:?}", e);
+                tokio::time::sleep(LISTENER_RETRY_DELAY).await;
+            }
+        }
+    };
+    info!("Listening for order status changes.");
+
+    loop {
+        match listener.recv().await {
+            Ok(notification) => {
+                match serde_json::from_str::<TenantStatusChange>(notification.payload()) {
+                    Ok(TenantStatusChange { tenant_id, change }) => {
+                        if let Some(events) = events.get(&tenant_id) {
+                            events.publish(change);
+                        }
+                    }
+                    Err(e) => warn!("Ignoring malformed order status notification: {}", e),
+                }
+            }
+            Err(e) => {
+                error!("Order status listener failed: {:?}", e);
+                tokio::time::sleep(LISTENER_RETRY_DELAY).await;
+            }
+        }
+    }
+}
+
+async fn connect(pool: &PgPool) -> sqlx::Result<PgListener> {
+    let mut listener = PgListener::connect_with(pool).await?;
+    listener.listen(ORDER_STATUS_CHANNEL).await?;
+    Ok(listener)
+}
@@ -0,0 +1,17 @@
+//! PostgreSQL, SQLite and (with the `test-utils` feature) in-memory implementations of the repository traits in `domain::repositories`,
+//! the Redis store behind `domain::cache`, the Redis stream the outbox relay publishes to and the
+//! change stream publishers.
+
+pub mod cache;
+pub mod cancel;
+pub mod collation;
+pub mod events;
+#[cfg(feature = "test-utils")]
+pub mod memory;
+pub mod outbox;
+mod quality;
+pub mod replica;
+pub mod repositories;
+pub mod retry;
+pub mod sqlite;
+pub mod streaming;
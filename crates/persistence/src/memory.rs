@@ -0,0 +1,4608 @@
+//! In-memory implementations of the repository traits, for unit-testing services and handlers
+//! without a database. Enabled by the `test-utils` feature.
+//!
+//! Every repository built from the same [`MemoryStore`] sees the same tables, so cross-entity
+//! reads (an order's products, a customer's dependents) behave as they do against Postgres.
+//! Each tenant gets its own tables from [`MemoryStore::for_tenant`], including its own copy
+//! of the tables Postgres shares between tenants.
+//! Database-side behaviour is reproduced where services rely on it: key, foreign-key and stock
+//! constraints fail with the matching [`ErrorKind`], location history and stats are kept up to
+//! date, new orders record `order.created` in the outbox, status changes
+//! `order.status_changed` and flagged late orders `order.late`, and support SLA flags are computed on read. City aliases are not resolved, fuzzy city
+//! search is a substring match, and there are no reviews, payments or refunds, since no
+//! repository method writes them.
+
+use bigdecimal::{BigDecimal, Zero};
+use chrono::NaiveDateTime;
+use domain::geo::GeoBounds;
+use domain::ids::{CustomerId, OrderId, ProductId, SellerId};
+use domain::models::{
+    AddItemToOrderDto, AppliedMigration, ArchivedOrders, AuditEntry, AuditFilter, BatchResume,
+    BrazilState, Category, Coupon, CreateCategoryDto, CreateCouponDto, CreateCustomerAddressDto,
+    CreateCustomerDto, CreateOrderDto, CreateProductDto, CreateSellerDto, CreateStockLocationDto,
+    CreateSupportCaseDto, CreateSupportMessageDto, Customer, CustomerAddress, CustomerDependents,
+    CustomerFilter, CustomerLocationVersion, CustomerMerge, DataQualityReport, DataQualityRule,
+    DataQualityRuleResult, DuplicatedCustomer, DuplicationBucket, FilterValue, FlaggedOrder,
+    ImportBatch, ImportBatchStatus, ImportRowError, IssueCount, LateOrder, LoadJobBatch,
+    LocatedSeller, LocationStock, NewAuditEntry, NewImportRowError, NewOrderAmendment,
+    NewPaymentTransaction, NewProductImage, NewRefund, Notification, NotificationAttempt,
+    NotificationKind, NotificationStatus, Order, OrderAmendment, OrderFilter, OrderFinancials,
+    OrderIssue, OrderItem, OrderItemOrigin, OrderProduct, OrderStatus, OrderStatusChange,
+    OutboxBacklog, OutboxEvent, PaginationParams, Payment, PaymentAnalyticsFilter,
+    PaymentMethodStats, PaymentMismatch, PaymentStatus, PaymentTransaction, PendingNotification,
+    PendingWebhookDelivery, PoolStats, Product, ProductFilter, ProductImage, ProductIssue,
+    ProductLocationStock, RecommendedProduct, ReconciliationFilter, Refund, Review, ReviewText,
+    RulePredicate, SampleStratum, Seller, SellerBadgeThreshold, SellerFilter, SellerMetrics,
+    Sentiment, SentimentReview, SimilarProduct, SparseRow, StockAllocation, StockLocation,
+    StoredLoadJob, SupportCase, SupportCaseFilter, SupportCaseVolume, SupportMessage, TableStats,
+    TodayStats, Total, TotalMode, UpdateCategoryDto, UpdateCustomerAddressDto, UpdateCustomerDto,
+    UpdateProductDto, UpdateSupportCaseDto, WebhookDelivery, WebhookDeliveryStatus,
+    WebhookPayloadTemplate, WebhookSubscription, ZipLocation,
+};
+use domain::money::Money;
+use domain::repositories::{
+    AuditRepository, CategoryRepository, CouponRepository, CustomerRepository,
+    DataQualityRepository, DiagnosticsRepository, EmbeddingRepository, GeolocationRepository,
+    ImportRepository, InventoryRepository, LateOrderRepository, MaintenanceRepository,
+    NotificationRepository, OrderRepository, OrderStatusTransition, OutboxRepository,
+    PaymentTransactionRepository, ProductRepository, RetentionRepository,
+    ReviewSentimentRepository, SellerRepository, StatsRepository, SupportRepository,
+    WebhookRepository,
+};
+use domain::tenancy::TenantId;
+
+use crate::sqlite::{cosine_distance, rank_sample};
+
+use async_trait::async_trait;
+use futures::StreamExt;
+use futures::stream::BoxStream;
+use serde::Serialize;
+use sqlx::Result as SqlxResult;
+use sqlx::error::{DatabaseError, ErrorKind};
+use std::borrow::Cow;
+use std::cmp::{Ordering, Reverse};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// Shared tables behind the in-memory repositories. Cloning shares the data.
+#[derive(Clone)]
+pub struct MemoryStore {
+    tables: Arc<Mutex<Tables>>,
+    tenants: Arc<Mutex<HashMap<TenantId, Arc<Mutex<Tables>>>>>,
+}
+
+impl MemoryStore {
+    /// An empty store for the default tenant, with the seller badge thresholds the
+    /// migrations seed.
+    pub fn new() -> Self {
+        let tables = Arc::new(Mutex::new(seeded_tables(TenantId::default())));
+        let tenants = HashMap::from([(TenantId::default(), tables.clone())]);
+        Self {
+            tables,
+            tenants: Arc::new(Mutex::new(tenants)),
+        }
+    }
+
+    /// The store holding `tenant`'s tables, created empty on first use.
+    pub fn for_tenant(&self, tenant: &TenantId) -> Self {
+        let tables = self
+            .tenants
+            .lock()
+            .expect("memory store poisoned")
+            .entry(tenant.clone())
+            .or_insert_with(|| Arc::new(Mutex::new(seeded_tables(tenant.clone()))))
+            .clone();
+        Self {
+            tables,
+            tenants: self.tenants.clone(),
+        }
+    }
+
+    fn tables(&self) -> MutexGuard<'_, Tables> {
+        self.tables.lock().expect("memory store poisoned")
+    }
+}
+
+fn seeded_tables(tenant: TenantId) -> Tables {
+    Tables {
+        tenant,
+        badge_thresholds: vec![
+            threshold(
+                "fast_shipper",
+                "Hands orders to the carrier within 48 hours of approval on average",
+                ("avg_handling_hours", "lte", "48.00"),
+                20,
+            ),
+            threshold(
+                "high_volume",
+                "At least 500 orders sold",
+                ("order_count", "gte", "500.00"),
+                0,
+            ),
+            threshold(
+                "top_rated",
+                "Average review score of 4.5 or more",
+                ("avg_review_score", "gte", "4.50"),
+                20,
+            ),
+        ],
+        ..Tables::default()
+    }
+}
+
+fn threshold(
+    badge: &str,
+    description: &str,
+    (metric, comparison, threshold): (&str, &str, &str),
+    min_sample: i32,
+) -> SellerBadgeThreshold {
+    SellerBadgeThreshold {
+        badge: badge.to_string(),
+        description: description.to_string(),
+        metric: metric.to_string(),
+        comparison: comparison.to_string(),
+        threshold: BigDecimal::from_str(threshold).expect("valid threshold"),
+        min_sample,
+    }
+}
+
+impl Default for MemoryStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+struct StoredOrder {
+    order: Order,
+    status_version: i32,
+    shipping_zip_code_prefix: Option<String>,
+}
+
+struct StoredOutboxEvent {
+    event: OutboxEvent,
+    published_at: Option<NaiveDateTime>,
+    next_attempt_at: Option<NaiveDateTime>,
+    last_error: Option<String>,
+}
+
+#[derive(Default)]
+struct Tables {
+    /// Tenant the tables belong to, stamped on the outbox events they record.
+    tenant: TenantId,
+    customers: Vec<Customer>,
+    location_history: Vec<(CustomerId, CustomerLocationVersion)>,
+    customer_addresses: Vec<CustomerAddress>,
+    sellers: Vec<Seller>,
+    badge_thresholds: Vec<SellerBadgeThreshold>,
+    orders: Vec<StoredOrder>,
+    order_items: Vec<OrderItem>,
+    amendments: Vec<OrderAmendment>,
+    products: Vec<Product>,
+    product_images: Vec<ProductImage>,
+    categories: Vec<Category>,
+    audit_log: Vec<AuditEntry>,
+    embeddings: HashMap<ProductId, Vec<f32>>,
+    locations: Vec<StockLocation>,
+    /// Zip code locations, by prefix.
+    geolocation: HashMap<String, ZipLocation>,
+    stock: Vec<LocationStock>,
+    coupons: Vec<Coupon>,
+    /// Coupon code applied to each order.
+    order_coupons: HashMap<OrderId, String>,
+    payment_transactions: Vec<PaymentTransaction>,
+    support_cases: Vec<SupportCase>,
+    support_messages: Vec<SupportMessage>,
+    import_batches: Vec<ImportBatch>,
+    /// Load job and checkpoint line of each import batch, by batch id.
+    batch_progress: HashMap<i64, (Option<i64>, i64)>,
+    /// Datasets of each load job, by job id.
+    load_jobs: HashMap<i64, Vec<String>>,
+    import_rows: Vec<(i64, String)>,
+    import_errors: Vec<ImportRowError>,
+    webhook_subscriptions: Vec<WebhookSubscription>,
+    webhook_deliveries: Vec<WebhookDelivery>,
+    outbox: Vec<StoredOutboxEvent>,
+    notifications: Vec<Notification>,
+    /// When each late order was flagged, by order id.
+    late_alerts: HashMap<OrderId, NaiveDateTime>,
+    data_quality_results: Vec<DataQualityRuleResult>,
+    sequences: HashMap<&'static str, i64>,
+}
+
+impl Tables {
+    /// Next value of the named serial column, starting at 1.
+    fn next_id(&mut self, sequence: &'static str) -> i64 {
+        let id = self.sequences.entry(sequence).or_default();
+        *id += 1;
+        *id
+    }
+
+    /// What the outbox triggers do.
+    fn record_event(
+        &mut self,
+        aggregate_type: &str,
+        aggregate_id: &str,
+        event_type: &str,
+        payload: serde_json::Value,
+    ) {
+        let event = OutboxEvent {
+            event_id: self.next_id("outbox_events"),
+            tenant_id: self.tenant.clone(),
+            aggregate_type: aggregate_type.to_string(),
+            aggregate_id: aggregate_id.to_string(),
+            event_type: event_type.to_string(),
+            payload,
+            attempts: 0,
+            created_at: now(),
+        };
+        self.outbox.push(StoredOutboxEvent {
+            event,
+            published_at: None,
+            next_attempt_at: Some(now()),
+            last_error: None,
+        });
+    }
+
+    /// One pending delivery per subscription to `event`.
+    fn queue_webhooks(&mut self, event: &str, payload: &serde_json::Value) {
+        let subscriptions: Vec<i64> = self
+            .webhook_subscriptions
+            .iter()
+            .filter(|s| s.events.iter().any(|e| e == event))
+            .map(|s| s.subscription_id)
+            .collect();
+        for subscription_id in subscriptions {
+            let delivery = WebhookDelivery {
+                delivery_id: self.next_id("webhook_deliveries"),
+                subscription_id,
+                event: event.to_string(),
+                payload: payload.clone(),
+                status: WebhookDeliveryStatus::Pending.as_str().to_string(),
+                attempts: 0,
+                next_attempt_at: Some(now()),
+                last_status_code: None,
+                last_error: None,
+                created_at: now(),
+                delivered_at: None,
+            };
+            self.webhook_deliveries.push(delivery);
+        }
+    }
+
+    /// The notification the event calls for, if any.
+    fn queue_notification(&mut self, event_id: i64, event: &str, payload: &serde_json::Value) {
+        let Some(kind) = NotificationKind::of_event(event, payload) else {
+            return;
+        };
+        let notification = Notification {
+            notification_id: self.next_id("notifications"),
+            event_id,
+            kind: kind.as_str().to_string(),
+            payload: payload.clone(),
+            status: NotificationStatus::Pending.as_str().to_string(),
+            channel: None,
+            recipient: None,
+            subject: None,
+            attempts: 0,
+            next_attempt_at: Some(now()),
+            last_error: None,
+            created_at: now(),
+            sent_at: None,
+        };
+        self.notifications.push(notification);
+    }
+
+    fn customer(&self, id: &CustomerId) -> Option<&Customer> {
+        self.customers.iter().find(|c| c.customer_id == *id)
+    }
+
+    fn order(&self, id: &OrderId) -> Option<&StoredOrder> {
+        self.orders.iter().find(|o| o.order.order_id == *id)
+    }
+
+    /// What updating `order_status` does: the triggers bump the status version and record
+    /// `order.status_changed`.
+    fn apply_order_transition(&mut self, id: &OrderId, (from, to): OrderStatusTransition) {
+        let Some(stored) = self
+            .orders
+            .iter_mut()
+            .find(|o| o.order.order_id == *id && o.order.order_status == from)
+        else {
+            return;
+        };
+        if from == to {
+            return;
+        }
+        stored.order.order_status = to;
+        stored.status_version += 1;
+        let payload = serde_json::json!({
+            "order_id": id,
+            "previous_status": from,
+            "order_status": to,
+            "status_version": stored.status_version,
+        });
+        self.record_event("order", id.as_str(), "order.status_changed", payload);
+    }
+
+    fn has_product(&self, id: &ProductId) -> bool {
+        self.products.iter().any(|p| p.product_id == *id)
+    }
+
+    fn has_seller(&self, id: &SellerId) -> bool {
+        self.sellers.iter().any(|s| s.seller_id == *id)
+    }
+}
+
+/// A constraint failure, surfaced as `sqlx::Error::Database` like the real backends report it.
+#[derive(Debug)]
+struct ConstraintViolation {
+    kind: ViolationKind,
+    message: String,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum ViolationKind {
+    Unique,
+    ForeignKey,
+    Check,
+}
+
+impl fmt::Display for ConstraintViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for ConstraintViolation {}
+
+impl DatabaseError for ConstraintViolation {
+    fn message(&self) -> &str {
+        &self.message
+    }
+
+    fn code(&self) -> Option<Cow<'_, str>> {
+        // Postgres SQLSTATE codes, for callers that still match on them.
+        Some(Cow::Borrowed(match self.kind {
+            ViolationKind::Unique => "23505",
+            ViolationKind::ForeignKey => "23503",
+            ViolationKind::Check => "23514",
+        }))
+    }
+
+    fn as_error(&self) -> &(dyn std::error::Error + Send + Sync + 'static) {
+        self
+    }
+
+    fn as_error_mut(&mut self) -> &mut (dyn std::error::Error + Send + Sync + 'static) {
+        self
+    }
+
+    fn into_error(self: Box<Self>) -> Box<dyn std::error::Error + Send + Sync + 'static> {
+        self
+    }
+
+    fn kind(&self) -> ErrorKind {
+        match self.kind {
+            ViolationKind::Unique => ErrorKind::UniqueViolation,
+            ViolationKind::ForeignKey => ErrorKind::ForeignKeyViolation,
+            ViolationKind::Check => ErrorKind::CheckViolation,
+        }
+    }
+}
+
+fn violation(kind: ViolationKind, message: impl Into<String>) -> sqlx::Error {
+    sqlx::Error::Database(Box::new(ConstraintViolation {
+        kind,
+        message: message.into(),
+    }))
+}
+
+fn now() -> NaiveDateTime {
+    chrono::Utc::now().naive_utc()
+}
+
+/// One page of `rows`, with the total the listing asked for.
+fn page<T>(rows: Vec<T>, pagination: &PaginationParams, mode: TotalMode) -> (Vec<T>, Total) {
+    let (rows, count) = page_counted(rows, pagination);
+    let total = match mode {
+        TotalMode::Exact => Total::Exact(count),
+        TotalMode::Estimate => Total::Estimated(count),
+        TotalMode::Skip => Total::Skipped,
+    };
+    (rows, total)
+}
+
+fn page_counted<T>(rows: Vec<T>, pagination: &PaginationParams) -> (Vec<T>, i64) {
+    let (limit, offset, _, _) = pagination.normalize();
+    let count = rows.len() as i64;
+    let rows = rows
+        .into_iter()
+        .skip(offset as usize)
+        .take(limit as usize)
+        .collect();
+    (rows, count)
+}
+
+/// Trims serialized rows to `fields`, the in-memory counterpart of `jsonb_build_object`.
+fn sparse<T: Serialize>(
+    (rows, total): (Vec<T>, Total),
+    fields: &[&str],
+) -> SqlxResult<(Vec<SparseRow>, Total)> {
+    let rows = rows
+        .iter()
+        .map(|row| match serde_json::to_value(row) {
+            Ok(serde_json::Value::Object(mut map)) => Ok(SparseRow(
+                fields
+                    .iter()
+                    .map(|field| {
+                        let value = map.remove(*field).unwrap_or(serde_json::Value::Null);
+                        (field.to_string(), value)
+                    })
+                    .collect(),
+            )),
+            Ok(_) => Ok(SparseRow(serde_json::Map::new())),
+            Err(e) => Err(sqlx::Error::Decode(Box::new(e))),
+        })
+        .collect::<SqlxResult<Vec<_>>>()?;
+    Ok((rows, total))
+}
+
+fn stream<'a, T: Send + 'a>(rows: Vec<T>) -> BoxStream<'a, SqlxResult<T>> {
+    futures::stream::iter(rows.into_iter().map(Ok)).boxed()
+}
+
+/// Counts per value, most common first, like the `GROUP BY` filter-value queries.
+fn count_values<'a>(values: impl Iterator<Item = &'a str>) -> Vec<FilterValue> {
+    let mut counts: HashMap<&str, i64> = HashMap::new();
+    for value in values {
+        *counts.entry(value).or_default() += 1;
+    }
+    let mut counts: Vec<FilterValue> = counts
+        .into_iter()
+        .map(|(value, count)| FilterValue {
+            value: value.to_string(),
+            count,
+        })
+        .collect();
+    counts.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.value.cmp(&b.value)));
+    counts
+}
+
+fn matches_customer(customer: &Customer, filter: &CustomerFilter) -> bool {
+    filter
+        .city
+        .as_ref()
+        .is_none_or(|city| customer.canonical_city == *city)
+        && filter
+            .state
+            .as_ref()
+            .is_none_or(|state| customer.customer_state == *state)
+        && (filter.include_deleted || customer.deleted_at.is_none())
+        && filter.q.as_deref().is_none_or(|q| {
+            customer
+                .canonical_city
+                .contains(&domain::cities::fold_city(q))
+        })
+}
+
+#[derive(Clone)]
+pub struct InMemoryCustomerRepository {
+    store: MemoryStore,
+}
+
+impl InMemoryCustomerRepository {
+    pub fn new(store: MemoryStore) -> Self {
+        Self { store }
+    }
+
+    fn filtered(&self, filter: &CustomerFilter) -> Vec<Customer> {
+        let q = filter.q.as_deref().map(domain::cities::fold_city);
+        let mut customers: Vec<Customer> = self
+            .store
+            .tables()
+            .customers
+            .iter()
+            .filter(|customer| matches_customer(customer, filter))
+            .cloned()
+            .collect();
+        customers.sort_by(|a, b| {
+            let exact = |c: &Customer| q.as_ref() == Some(&c.canonical_city);
+            exact(b)
+                .cmp(&exact(a))
+                .then_with(|| {
+                    a.customer_city
+                        .to_lowercase()
+                        .cmp(&b.customer_city.to_lowercase())
+                })
+                .then_with(|| b.customer_zip_code_prefix.cmp(&a.customer_zip_code_prefix))
+        });
+        customers
+    }
+
+    /// Live customer rows per unique id, most first.
+    fn rows_per_unique_id(&self) -> Vec<FilterValue> {
+        let tables = self.store.tables();
+        count_values(
+            tables
+                .customers
+                .iter()
+                .filter(|c| c.deleted_at.is_none())
+                .map(|c| c.customer_unique_id.as_str()),
+        )
+    }
+}
+
+#[async_trait]
+impl CustomerRepository for InMemoryCustomerRepository {
+    async fn create(
+        &self,
+        id: &CustomerId,
+        dto: CreateCustomerDto,
+        canonical_city: &str,
+    ) -> SqlxResult<Customer> {
+        let mut tables = self.store.tables();
+        if tables.customer(id).is_some() {
+            return Err(violation(
+                ViolationKind::Unique,
+                format!("customer {} already exists", id),
+            ));
+        }
+
+        let customer = Customer {
+            customer_id: id.clone(),
+            customer_unique_id: dto.customer_unique_id,
+            customer_zip_code_prefix: dto.customer_zip_code_prefix,
+            customer_city: dto.customer_city,
+            canonical_city: canonical_city.to_string(),
+            customer_state: dto.customer_state.as_str().to_string(),
+            deleted_at: None,
+        };
+        tables.location_history.push((
+            id.clone(),
+            CustomerLocationVersion {
+                customer_zip_code_prefix: customer.customer_zip_code_prefix.clone(),
+                customer_city: customer.customer_city.clone(),
+                customer_state: customer.customer_state.clone(),
+                valid_from: None,
+                valid_to: None,
+            },
+        ));
+        tables.customers.push(customer.clone());
+        Ok(customer)
+    }
+
+    async fn create_many(
+        &self,
+        rows: Vec<(CustomerId, CreateCustomerDto, String)>,
+    ) -> SqlxResult<Vec<SqlxResult<Customer>>> {
+        let mut results = Vec::with_capacity(rows.len());
+        for (id, dto, canonical_city) in rows {
+            results.push(self.create(&id, dto, &canonical_city).await);
+        }
+        Ok(results)
+    }
+
+    async fn find_all(
+        &self,
+        filter: &CustomerFilter,
+        pagination: &PaginationParams,
+        total: TotalMode,
+    ) -> SqlxResult<(Vec<Customer>, Total)> {
+        Ok(page(self.filtered(filter), pagination, total))
+    }
+
+    async fn find_all_sparse(
+        &self,
+        filter: &CustomerFilter,
+        pagination: &PaginationParams,
+        fields: &[&'static str],
+        total: TotalMode,
+    ) -> SqlxResult<(Vec<SparseRow>, Total)> {
+        sparse(page(self.filtered(filter), pagination, total), fields)
+    }
+
+    fn stream_all(&self) -> BoxStream<'_, SqlxResult<Customer>> {
+        let mut customers: Vec<Customer> = self
+            .store
+            .tables()
+            .customers
+            .iter()
+            .filter(|c| c.deleted_at.is_none())
+            .cloned()
+            .collect();
+        customers.sort_by(|a, b| a.customer_id.as_str().cmp(b.customer_id.as_str()));
+        stream(customers)
+    }
+
+    async fn find_by_id(&self, id: &CustomerId) -> SqlxResult<Option<Customer>> {
+        Ok(self
+            .store
+            .tables()
+            .customer(id)
+            .filter(|c| c.deleted_at.is_none())
+            .cloned())
+    }
+
+    async fn update(
+        &self,
+        id: &CustomerId,
+        dto: UpdateCustomerDto,
+        canonical_city: Option<&str>,
+    ) -> SqlxResult<Option<Customer>> {
+        let mut tables = self.store.tables();
+        let Some(customer) = tables
+            .customers
+            .iter_mut()
+            .find(|c| c.customer_id == *id && c.deleted_at.is_none())
+        else {
+            return Ok(None);
+        };
+
+        let previous = (
+            customer.customer_zip_code_prefix.clone(),
+            customer.customer_city.clone(),
+            customer.customer_state.clone(),
+        );
+        if let Some(unique_id) = dto.customer_unique_id {
+            customer.customer_unique_id = unique_id;
+        }
+        if let Some(zip) = dto.customer_zip_code_prefix {
+            customer.customer_zip_code_prefix = zip;
+        }
+        if let Some(city) = dto.customer_city {
+            customer.customer_city = city;
+        }
+        if let Some(state) = dto.customer_state {
+            customer.customer_state = state.as_str().to_string();
+        }
+        if let Some(canonical_city) = canonical_city {
+            customer.canonical_city = canonical_city.to_string();
+        }
+        let customer = customer.clone();
+
+        let current = (
+            customer.customer_zip_code_prefix.clone(),
+            customer.customer_city.clone(),
+            customer.customer_state.clone(),
+        );
+        if current != previous {
+            let changed_at = now();
+            if let Some((_, version)) = tables
+                .location_history
+                .iter_mut()
+                .find(|(customer_id, v)| customer_id == id && v.valid_to.is_none())
+            {
+                version.valid_to = Some(changed_at);
+            }
+            tables.location_history.push((
+                id.clone(),
+                CustomerLocationVersion {
+                    customer_zip_code_prefix: current.0,
+                    customer_city: current.1,
+                    customer_state: current.2,
+                    valid_from: Some(changed_at),
+                    valid_to: None,
+                },
+            ));
+        }
+
+        Ok(Some(customer))
+    }
+
+    async fn delete(&self, id: &CustomerId) -> SqlxResult<Option<NaiveDateTime>> {
+        let mut tables = self.store.tables();
+        Ok(tables
+            .customers
+            .iter_mut()
+            .find(|c| c.customer_id == *id && c.deleted_at.is_none())
+            .map(|customer| *customer.deleted_at.insert(now())))
+    }
+
+    async fn merge(
+        &self,
+        source: &CustomerId,
+        target: &CustomerId,
+        audit: &[NewAuditEntry],
+    ) -> SqlxResult<Option<CustomerMerge>> {
+        let mut tables = self.store.tables();
+        let live = |id: &CustomerId| {
+            tables
+                .customers
+                .iter()
+                .any(|c| c.customer_id == *id && c.deleted_at.is_none())
+        };
+        if !live(source) || !live(target) {
+            return Ok(None);
+        }
+
+        let mut orders_moved = 0;
+        for stored in tables
+            .orders
+            .iter_mut()
+            .filter(|o| o.order.customer_id == *source)
+        {
+            stored.order.customer_id = target.clone();
+            orders_moved += 1;
+        }
+        let mut support_cases_moved = 0;
+        for case in tables
+            .support_cases
+            .iter_mut()
+            .filter(|c| c.customer_id == *source)
+        {
+            case.customer_id = target.clone();
+            support_cases_moved += 1;
+        }
+        let target_has_default = tables
+            .customer_addresses
+            .iter()
+            .any(|a| a.customer_id == *target && a.is_default);
+        for address in tables
+            .customer_addresses
+            .iter_mut()
+            .filter(|a| a.customer_id == *source)
+        {
+            address.customer_id = target.clone();
+            address.is_default &= !target_has_default;
+        }
+        let source_deleted_at = now();
+        for (customer_id, version) in tables
+            .location_history
+            .iter_mut()
+            .filter(|(customer_id, _)| customer_id == source)
+        {
+            *customer_id = target.clone();
+            version.valid_to.get_or_insert(source_deleted_at);
+        }
+        if let Some(customer) = tables
+            .customers
+            .iter_mut()
+            .find(|c| c.customer_id == *source)
+        {
+            customer.deleted_at = Some(source_deleted_at);
+        }
+
+        let merge = CustomerMerge {
+            source_customer_id: source.clone(),
+            target_customer_id: target.clone(),
+            orders_moved,
+            support_cases_moved,
+            source_deleted_at,
+        };
+        for entry in audit {
+            let entry = merge.audit_entry(entry);
+            let audit_id = tables.next_id("audit_log");
+            tables.audit_log.push(AuditEntry {
+                audit_id,
+                entity_type: entry.entity_type.to_string(),
+                entity_id: entry.entity_id,
+                action: entry.action.as_str().to_string(),
+                actor: entry.actor,
+                diff: entry.diff,
+                created_at: source_deleted_at,
+            });
+        }
+
+        Ok(Some(merge))
+    }
+
+    async fn restore(&self, id: &CustomerId) -> SqlxResult<Option<Customer>> {
+        let mut tables = self.store.tables();
+        Ok(tables
+            .customers
+            .iter_mut()
+            .find(|c| c.customer_id == *id && c.deleted_at.is_some())
+            .map(|customer| {
+                customer.deleted_at = None;
+                customer.clone()
+            }))
+    }
+
+    async fn anonymize(
+        &self,
+        id: &CustomerId,
+        erasure: &NewAuditEntry,
+    ) -> SqlxResult<Option<Customer>> {
+        let mut tables = self.store.tables();
+        let Some(customer) = tables.customers.iter_mut().find(|c| c.customer_id == *id) else {
+            return Ok(None);
+        };
+
+        customer.customer_unique_id = uuid_like();
+        customer.customer_zip_code_prefix = "00000".to_string();
+        customer.customer_city = "anonymized".to_string();
+        customer.canonical_city = "anonymized".to_string();
+        customer.customer_state = "XX".to_string();
+        let customer = customer.clone();
+
+        for stored in tables
+            .orders
+            .iter_mut()
+            .filter(|o| o.order.customer_id == *id)
+        {
+            stored.shipping_zip_code_prefix = Some("00000".to_string());
+        }
+        let order_ids: Vec<OrderId> = tables
+            .orders
+            .iter()
+            .filter(|o| o.order.customer_id == *id)
+            .map(|o| o.order.order_id.clone())
+            .collect();
+        for amendment in tables
+            .amendments
+            .iter_mut()
+            .filter(|a| order_ids.contains(&a.order_id))
+        {
+            if let Some(change) = amendment.changes.get_mut("shipping_zip_code_prefix") {
+                *change = serde_json::json!({ "redacted": true });
+            }
+        }
+
+        let entity_id = id.to_string();
+        for entry in tables
+            .audit_log
+            .iter_mut()
+            .filter(|e| e.entity_type == "customer" && e.entity_id == entity_id)
+        {
+            entry.diff = serde_json::json!({ "redacted": true });
+        }
+        for (_, version) in tables
+            .location_history
+            .iter_mut()
+            .filter(|(customer_id, _)| customer_id == id)
+        {
+            version.customer_zip_code_prefix = "00000".to_string();
+            version.customer_city = "anonymized".to_string();
+            version.customer_state = "XX".to_string();
+        }
+        tables.customer_addresses.retain(|a| a.customer_id != *id);
+
+        let audit_id = tables.next_id("audit_log");
+        tables.audit_log.push(AuditEntry {
+            audit_id,
+            entity_type: erasure.entity_type.to_string(),
+            entity_id: erasure.entity_id.clone(),
+            action: erasure.action.as_str().to_string(),
+            actor: erasure.actor.clone(),
+            diff: erasure.diff.clone(),
+            created_at: now(),
+        });
+
+        Ok(Some(customer))
+    }
+
+    async fn count_dependents(&self, id: &CustomerId) -> SqlxResult<CustomerDependents> {
+        let tables = self.store.tables();
+        Ok(CustomerDependents {
+            orders: tables
+                .orders
+                .iter()
+                .filter(|o| o.order.customer_id == *id)
+                .count() as i64,
+            support_cases: tables
+                .support_cases
+                .iter()
+                .filter(|c| c.customer_id == *id)
+                .count() as i64,
+        })
+    }
+
+    async fn find_location_history(
+        &self,
+        id: &CustomerId,
+    ) -> SqlxResult<Vec<CustomerLocationVersion>> {
+        let tables = self.store.tables();
+        Ok(tables
+            .location_history
+            .iter()
+            .filter(|(customer_id, _)| customer_id == id)
+            .map(|(_, version)| CustomerLocationVersion {
+                customer_zip_code_prefix: version.customer_zip_code_prefix.clone(),
+                customer_city: version.customer_city.clone(),
+                customer_state: version.customer_state.clone(),
+                valid_from: version.valid_from,
+                valid_to: version.valid_to,
+            })
+            .collect())
+    }
+
+    async fn create_address(
+        &self,
+        id: &CustomerId,
+        dto: CreateCustomerAddressDto,
+    ) -> SqlxResult<CustomerAddress> {
+        let mut tables = self.store.tables();
+        if tables.customer(id).is_none() {
+            return Err(violation(
+                ViolationKind::ForeignKey,
+                format!("customer {} does not exist", id),
+            ));
+        }
+
+        let first = !tables
+            .customer_addresses
+            .iter()
+            .any(|a| a.customer_id == *id);
+        if dto.is_default {
+            for address in tables
+                .customer_addresses
+                .iter_mut()
+                .filter(|a| a.customer_id == *id)
+            {
+                address.is_default = false;
+            }
+        }
+        let address = CustomerAddress {
+            address_id: tables.next_id("customer_addresses"),
+            customer_id: id.clone(),
+            label: dto.label,
+            zip_code_prefix: dto.zip_code_prefix,
+            city: dto.city,
+            state: dto.state.as_str().to_string(),
+            is_default: dto.is_default || first,
+            created_at: now(),
+        };
+        tables.customer_addresses.push(address.clone());
+        Ok(address)
+    }
+
+    async fn find_addresses(&self, id: &CustomerId) -> SqlxResult<Vec<CustomerAddress>> {
+        let mut addresses: Vec<CustomerAddress> = self
+            .store
+            .tables()
+            .customer_addresses
+            .iter()
+            .filter(|a| a.customer_id == *id)
+            .cloned()
+            .collect();
+        addresses.sort_by_key(|a| (Reverse(a.is_default), a.address_id));
+        Ok(addresses)
+    }
+
+    async fn find_address(
+        &self,
+        id: &CustomerId,
+        address_id: i64,
+    ) -> SqlxResult<Option<CustomerAddress>> {
+        Ok(self
+            .store
+            .tables()
+            .customer_addresses
+            .iter()
+            .find(|a| a.customer_id == *id && a.address_id == address_id)
+            .cloned())
+    }
+
+    async fn update_address(
+        &self,
+        id: &CustomerId,
+        address_id: i64,
+        dto: UpdateCustomerAddressDto,
+    ) -> SqlxResult<Option<CustomerAddress>> {
+        let mut tables = self.store.tables();
+        if !tables
+            .customer_addresses
+            .iter()
+            .any(|a| a.customer_id == *id && a.address_id == address_id)
+        {
+            return Ok(None);
+        }
+
+        for address in tables
+            .customer_addresses
+            .iter_mut()
+            .filter(|a| a.customer_id == *id)
+        {
+            if address.address_id != address_id {
+                if dto.is_default == Some(true) {
+                    address.is_default = false;
+                }
+                continue;
+            }
+            if let Some(label) = &dto.label {
+                address.label = label.clone();
+            }
+            if let Some(zip_code_prefix) = &dto.zip_code_prefix {
+                address.zip_code_prefix = zip_code_prefix.clone();
+            }
+            if let Some(city) = &dto.city {
+                address.city = city.clone();
+            }
+            if let Some(state) = dto.state {
+                address.state = state.as_str().to_string();
+            }
+            if let Some(is_default) = dto.is_default {
+                address.is_default = is_default;
+            }
+        }
+        Ok(tables
+            .customer_addresses
+            .iter()
+            .find(|a| a.address_id == address_id)
+            .cloned())
+    }
+
+    async fn delete_address(
+        &self,
+        id: &CustomerId,
+        address_id: i64,
+    ) -> SqlxResult<Option<NaiveDateTime>> {
+        let mut tables = self.store.tables();
+        let before = tables.customer_addresses.len();
+        tables
+            .customer_addresses
+            .retain(|a| !(a.customer_id == *id && a.address_id == address_id));
+        if tables.customer_addresses.len() == before {
+            return Ok(None);
+        }
+        Ok(Some(now()))
+    }
+
+    async fn count_by_state(&self) -> SqlxResult<Vec<FilterValue>> {
+        let tables = self.store.tables();
+        Ok(count_values(
+            tables
+                .customers
+                .iter()
+                .filter(|c| c.deleted_at.is_none() && c.canonical_city != "anonymized")
+                .map(|c| c.customer_state.as_str()),
+        ))
+    }
+
+    async fn count_by_city(&self, state: Option<BrazilState>) -> SqlxResult<Vec<FilterValue>> {
+        let tables = self.store.tables();
+        Ok(count_values(
+            tables
+                .customers
+                .iter()
+                .filter(|c| c.deleted_at.is_none() && c.canonical_city != "anonymized")
+                .filter(|c| state.is_none_or(|state| c.customer_state == state.as_str()))
+                .map(|c| c.canonical_city.as_str()),
+        ))
+    }
+
+    async fn find_by_unique_id(&self, unique_id: &str) -> SqlxResult<Vec<Customer>> {
+        let mut customers: Vec<Customer> = self
+            .store
+            .tables()
+            .customers
+            .iter()
+            .filter(|c| c.deleted_at.is_none() && c.customer_unique_id == unique_id)
+            .cloned()
+            .collect();
+        customers.sort_by(|a, b| a.customer_id.as_str().cmp(b.customer_id.as_str()));
+        Ok(customers)
+    }
+
+    async fn count_rows_per_unique_id(&self) -> SqlxResult<Vec<DuplicationBucket>> {
+        let mut buckets: HashMap<i64, i64> = HashMap::new();
+        for rows in self.rows_per_unique_id() {
+            *buckets.entry(rows.count).or_default() += 1;
+        }
+        let mut buckets: Vec<DuplicationBucket> = buckets
+            .into_iter()
+            .map(|(customer_rows, unique_customers)| DuplicationBucket {
+                customer_rows,
+                unique_customers,
+            })
+            .collect();
+        buckets.sort_by_key(|bucket| bucket.customer_rows);
+        Ok(buckets)
+    }
+
+    async fn find_most_duplicated(&self, limit: i64) -> SqlxResult<Vec<DuplicatedCustomer>> {
+        Ok(self
+            .rows_per_unique_id()
+            .into_iter()
+            .filter(|rows| rows.count > 1)
+            .take(limit.max(0) as usize)
+            .map(|rows| DuplicatedCustomer {
+                customer_unique_id: rows.value,
+                customer_rows: rows.count,
+            })
+            .collect())
+    }
+}
+
+/// A random 32-hex value, standing in for `md5(random()::text)`.
+fn uuid_like() -> String {
+    CustomerId::generate().to_string()
+}
+
+fn matches_seller(seller: &Seller, filter: &SellerFilter) -> bool {
+    filter
+        .city
+        .as_ref()
+        .is_none_or(|city| seller.canonical_city == *city)
+        && filter
+            .state
+            .as_ref()
+            .is_none_or(|state| seller.seller_state == *state)
+        && filter
+            .badge
+            .as_ref()
+            .is_none_or(|badge| seller.badges.contains(badge))
+        && filter.q.as_deref().is_none_or(|q| {
+            seller
+                .canonical_city
+                .contains(&domain::cities::fold_city(q))
+        })
+}
+
+#[derive(Clone)]
+pub struct InMemorySellerRepository {
+    store: MemoryStore,
+}
+
+impl InMemorySellerRepository {
+    pub fn new(store: MemoryStore) -> Self {
+        Self { store }
+    }
+
+    fn filtered(&self, filter: &SellerFilter) -> Vec<Seller> {
+        let q = filter.q.as_deref().map(domain::cities::fold_city);
+        let mut sellers: Vec<Seller> = self
+            .store
+            .tables()
+            .sellers
+            .iter()
+            .filter(|seller| matches_seller(seller, filter))
+            .cloned()
+            .collect();
+        sellers.sort_by(|a, b| {
+            let exact = |s: &Seller| q.as_ref() == Some(&s.canonical_city);
+            exact(b)
+                .cmp(&exact(a))
+                .then_with(|| {
+                    a.seller_city
+                        .to_lowercase()
+                        .cmp(&b.seller_city.to_lowercase())
+                })
+                .then_with(|| a.seller_id.as_str().cmp(b.seller_id.as_str()))
+        });
+        sellers
+    }
+}
+
+#[async_trait]
+impl SellerRepository for InMemorySellerRepository {
+    async fn create(
+        &self,
+        id: &SellerId,
+        dto: CreateSellerDto,
+        canonical_city: &str,
+    ) -> SqlxResult<Seller> {
+        let mut tables = self.store.tables();
+        if tables.has_seller(id) {
+            return Err(violation(
+                ViolationKind::Unique,
+                format!("seller {} already exists", id),
+            ));
+        }
+
+        let seller = Seller {
+            seller_id: id.clone(),
+            seller_zip_code_prefix: dto.seller_zip_code_prefix,
+            seller_city: dto.seller_city,
+            canonical_city: canonical_city.to_string(),
+            seller_state: dto.seller_state.as_str().to_string(),
+            badges: Vec::new(),
+        };
+        tables.sellers.push(seller.clone());
+        Ok(seller)
+    }
+
+    async fn create_many(
+        &self,
+        rows: Vec<(SellerId, CreateSellerDto, String)>,
+    ) -> SqlxResult<Vec<SqlxResult<Seller>>> {
+        let mut results = Vec::with_capacity(rows.len());
+        for (id, dto, canonical_city) in rows {
+            results.push(self.create(&id, dto, &canonical_city).await);
+        }
+        Ok(results)
+    }
+
+    async fn find_all(
+        &self,
+        filter: &SellerFilter,
+        pagination: &PaginationParams,
+        total: TotalMode,
+    ) -> SqlxResult<(Vec<Seller>, Total)> {
+        Ok(page(self.filtered(filter), pagination, total))
+    }
+
+    async fn find_all_sparse(
+        &self,
+        filter: &SellerFilter,
+        pagination: &PaginationParams,
+        fields: &[&'static str],
+        total: TotalMode,
+    ) -> SqlxResult<(Vec<SparseRow>, Total)> {
+        sparse(page(self.filtered(filter), pagination, total), fields)
+    }
+
+    async fn find_by_id(&self, id: &SellerId) -> SqlxResult<Option<Seller>> {
+        Ok(self
+            .store
+            .tables()
+            .sellers
+            .iter()
+            .find(|s| s.seller_id == *id)
+            .cloned())
+    }
+
+    async fn find_within(&self, bounds: &GeoBounds) -> SqlxResult<Vec<LocatedSeller>> {
+        let tables = self.store.tables();
+        Ok(tables
+            .sellers
+            .iter()
+            .filter_map(|seller| {
+                let location = tables.geolocation.get(&seller.seller_zip_code_prefix)?;
+                ((bounds.min_lat..=bounds.max_lat).contains(&location.geolocation_lat)
+                    && (bounds.min_lng..=bounds.max_lng).contains(&location.geolocation_lng))
+                .then(|| LocatedSeller {
+                    seller: seller.clone(),
+                    geolocation_lat: location.geolocation_lat,
+                    geolocation_lng: location.geolocation_lng,
+                })
+            })
+            .collect())
+    }
+
+    /// Recomputes badges from order and shipping metrics. There are no reviews in memory, so
+    /// review-based badges are never awarded.
+    async fn refresh_badges(&self) -> SqlxResult<u64> {
+        let mut tables = self.store.tables();
+
+        let mut seller_orders: HashMap<SellerId, HashSet<OrderId>> = HashMap::new();
+        for item in &tables.order_items {
+            seller_orders
+                .entry(item.seller_id.clone())
+                .or_default()
+                .insert(item.order_id.clone());
+        }
+
+        let mut metrics: HashMap<SellerId, Vec<(&'static str, f64, i64)>> = HashMap::new();
+        for (seller_id, order_ids) in &seller_orders {
+            let handling_hours: Vec<f64> = order_ids
+                .iter()
+                .filter_map(|id| tables.order(id))
+                .filter_map(|o| {
+                    o.order.order_delivered_carrier_date.map(|carrier| {
+                        (carrier - o.order.order_approved_at).num_seconds() as f64 / 3600.0
+                    })
+                })
+                .collect();
+
+            let order_count = order_ids.len() as i64;
+            let mut seller_metrics = vec![("order_count", order_count as f64, order_count)];
+            if !handling_hours.is_empty() {
+                seller_metrics.push((
+                    "avg_handling_hours",
+                    handling_hours.iter().sum::<f64>() / handling_hours.len() as f64,
+                    handling_hours.len() as i64,
+                ));
+            }
+            metrics.insert(seller_id.clone(), seller_metrics);
+        }
+
+        let thresholds: Vec<(String, String, String, f64, i64)> = tables
+            .badge_thresholds
+            .iter()
+            .map(|t| {
+                (
+                    t.badge.clone(),
+                    t.metric.clone(),
+                    t.comparison.clone(),
+                    t.threshold.to_string().parse().unwrap_or(f64::NAN),
+                    i64::from(t.min_sample),
+                )
+            })
+            .collect();
+
+        let mut awarded = 0;
+        for seller in &mut tables.sellers {
+            let seller_metrics = metrics.get(&seller.seller_id);
+            let mut badges: Vec<String> = thresholds
+                .iter()
+                .filter(|(_, metric, comparison, threshold, min_sample)| {
+                    seller_metrics
+                        .and_then(|m| m.iter().find(|(name, _, _)| name == metric))
+                        .is_some_and(|(_, value, sample)| {
+                            *sample >= *min_sample
+                                && if comparison == "lte" {
+                                    *value <= *threshold
+                                } else {
+                                    *value >= *threshold
+                                }
+                        })
+                })
+                .map(|(badge, _, _, _, _)| badge.clone())
+                .collect();
+            badges.sort();
+            awarded += badges.len() as u64;
+            seller.badges = badges;
+        }
+
+        Ok(awarded)
+    }
+
+    async fn find_badge_thresholds(&self) -> SqlxResult<Vec<SellerBadgeThreshold>> {
+        let tables = self.store.tables();
+        let mut thresholds: Vec<SellerBadgeThreshold> = tables
+            .badge_thresholds
+            .iter()
+            .map(|t| SellerBadgeThreshold {
+                badge: t.badge.clone(),
+                description: t.description.clone(),
+                metric: t.metric.clone(),
+                comparison: t.comparison.clone(),
+                threshold: t.threshold.clone(),
+                min_sample: t.min_sample,
+            })
+            .collect();
+        thresholds.sort_by(|a, b| a.badge.cmp(&b.badge));
+        Ok(thresholds)
+    }
+
+    async fn find_metrics(&self, id: &SellerId) -> SqlxResult<SellerMetrics> {
+        let tables = self.store.tables();
+
+        let mut shipping_limits: HashMap<&OrderId, NaiveDateTime> = HashMap::new();
+        for item in tables.order_items.iter().filter(|i| i.seller_id == *id) {
+            let limit = shipping_limits
+                .entry(&item.order_id)
+                .or_insert(item.shipping_limit_date);
+            *limit = (*limit).min(item.shipping_limit_date);
+        }
+
+        let mut metrics = SellerMetrics::default();
+        for (order_id, shipping_limit_date) in shipping_limits {
+            let Some(stored) = tables.order(order_id) else {
+                continue;
+            };
+            metrics.order_count += 1;
+            if let Some(carrier) = stored.order.order_delivered_carrier_date {
+                metrics.shipped_count += 1;
+                if carrier <= shipping_limit_date {
+                    metrics.on_time_count += 1;
+                }
+            }
+            if stored.order.order_status == OrderStatus::Canceled {
+                metrics.canceled_count += 1;
+            }
+        }
+        Ok(metrics)
+    }
+}
+
+#[derive(Clone)]
+pub struct InMemoryOrderRepository {
+    store: MemoryStore,
+}
+
+impl InMemoryOrderRepository {
+    pub fn new(store: MemoryStore) -> Self {
+        Self { store }
+    }
+
+    fn filtered(&self, filter: &OrderFilter) -> Vec<Order> {
+        let mut orders: Vec<Order> = self
+            .store
+            .tables()
+            .orders
+            .iter()
+            .filter(|o| filter.status.is_none_or(|s| o.order.order_status == s))
+            .map(|o| o.order.clone())
+            .collect();
+        orders.sort_by_key(|o| Reverse(o.order_purchase_timestamp));
+        orders
+    }
+
+    fn items(&self, order_id: &OrderId) -> Vec<OrderItem> {
+        let mut items: Vec<OrderItem> = self
+            .store
+            .tables()
+            .order_items
+            .iter()
+            .filter(|i| i.order_id == *order_id)
+            .cloned()
+            .collect();
+        items.sort_by_key(|i| i.order_item_id);
+        items
+    }
+}
+
+#[async_trait]
+impl OrderRepository for InMemoryOrderRepository {
+    async fn create(&self, id: &OrderId, dto: CreateOrderDto) -> SqlxResult<Order> {
+        let mut tables = self.store.tables();
+        if tables.order(id).is_some() {
+            return Err(violation(
+                ViolationKind::Unique,
+                format!("order {} already exists", id),
+            ));
+        }
+        if tables.customer(&dto.customer_id).is_none() {
+            return Err(violation(
+                ViolationKind::ForeignKey,
+                format!("customer {} does not exist", dto.customer_id),
+            ));
+        }
+
+        let shipping_zip_code_prefix = tables
+            .customer_addresses
+            .iter()
+            .find(|a| {
+                a.customer_id == dto.customer_id
+                    && match dto.shipping_address_id {
+                        Some(address_id) => a.address_id == address_id,
+                        None => a.is_default,
+                    }
+            })
+            .map(|a| a.zip_code_prefix.clone());
+        let order = Order {
+            order_id: id.clone(),
+            customer_id: dto.customer_id,
+            order_status: dto.order_status,
+            order_purchase_timestamp: dto.order_purchase_timestamp,
+            order_approved_at: dto.order_approved_at,
+            order_delivered_carrier_date: dto.order_delivered_carrier_date,
+            order_delivered_customer_date: dto.order_delivered_customer_date,
+            order_estimated_delivery_date: dto.order_estimated_delivery_date,
+        };
+        tables.orders.push(StoredOrder {
+            order: order.clone(),
+            status_version: 1,
+            shipping_zip_code_prefix,
+        });
+        tables.record_event(
+            "order",
+            order.order_id.as_str(),
+            "order.created",
+            serde_json::json!({
+                "order_id": order.order_id,
+                "customer_id": order.customer_id,
+                "order_status": order.order_status,
+                "order_purchase_timestamp": order.order_purchase_timestamp,
+            }),
+        );
+        Ok(order)
+    }
+
+    async fn create_many(
+        &self,
+        rows: Vec<(OrderId, CreateOrderDto)>,
+    ) -> SqlxResult<Vec<SqlxResult<Order>>> {
+        let mut results = Vec::with_capacity(rows.len());
+        for (id, dto) in rows {
+            results.push(self.create(&id, dto).await);
+        }
+        Ok(results)
+    }
+
+    async fn add_item_with_allocation(
+        &self,
+        order_id: &OrderId,
+        dto: AddItemToOrderDto,
+        destination_zip_code_prefix: &str,
+    ) -> SqlxResult<Option<(OrderItem, Option<StockAllocation>)>> {
+        let mut tables = self.store.tables();
+        let tracked = tables
+            .nearest_stock(&dto.seller_id, &dto.product_id, "", i32::MIN)
+            .is_some();
+        let stock = tables.nearest_stock(
+            &dto.seller_id,
+            &dto.product_id,
+            destination_zip_code_prefix,
+            1,
+        );
+        if tracked && stock.is_none() {
+            return Ok(None);
+        }
+        if tables.order(order_id).is_none()
+            || !tables.has_product(&dto.product_id)
+            || !tables.has_seller(&dto.seller_id)
+        {
+            return Err(violation(
+                ViolationKind::ForeignKey,
+                "order item references a missing order, product or seller",
+            ));
+        }
+        if tables
+            .order_items
+            .iter()
+            .any(|i| i.order_id == *order_id && i.order_item_id == dto.order_item_id)
+        {
+            return Err(violation(
+                ViolationKind::Unique,
+                format!("order item {} already exists", dto.order_item_id),
+            ));
+        }
+
+        let item = OrderItem {
+            order_item_id: dto.order_item_id,
+            order_id: order_id.clone(),
+            product_id: dto.product_id,
+            seller_id: dto.seller_id,
+            shipping_limit_date: dto.shipping_limit_date,
+            price: dto.price.into(),
+            freight_value: dto.freight_value.into(),
+            currency: Money::CURRENCY,
+        };
+        tables.order_items.push(item.clone());
+
+        let allocation = stock.map(|index| {
+            let stock = &mut tables.stock[index];
+            stock.quantity -= 1;
+            stock.updated_at = now();
+            StockAllocation {
+                location_id: stock.location_id,
+                product_id: stock.product_id.clone(),
+                quantity: stock.quantity,
+            }
+        });
+        Ok(Some((item, allocation)))
+    }
+
+    async fn find_all(
+        &self,
+        filter: &OrderFilter,
+        pagination: &PaginationParams,
+        total: TotalMode,
+    ) -> SqlxResult<(Vec<Order>, Total)> {
+        Ok(page(self.filtered(filter), pagination, total))
+    }
+
+    async fn find_all_sparse(
+        &self,
+        filter: &OrderFilter,
+        pagination: &PaginationParams,
+        fields: &[&'static str],
+        total: TotalMode,
+    ) -> SqlxResult<(Vec<SparseRow>, Total)> {
+        sparse(page(self.filtered(filter), pagination, total), fields)
+    }
+
+    fn stream_all(&self) -> BoxStream<'_, SqlxResult<Order>> {
+        let mut orders = self.filtered(&OrderFilter::default());
+        orders.reverse();
+        stream(orders)
+    }
+
+    async fn find_by_id(&self, id: &OrderId) -> SqlxResult<Option<Order>> {
+        Ok(self.store.tables().order(id).map(|o| o.order.clone()))
+    }
+
+    async fn find_status(&self, id: &OrderId) -> SqlxResult<Option<OrderStatusChange>> {
+        Ok(self.store.tables().order(id).map(|o| OrderStatusChange {
+            order_id: o.order.order_id.clone(),
+            order_status: o.order.order_status,
+            status_version: o.status_version,
+        }))
+    }
+
+    fn stream_review_texts(&self) -> BoxStream<'_, SqlxResult<ReviewText>> {
+        stream(Vec::new())
+    }
+
+    async fn sample(
+        &self,
+        strata: &[SampleStratum],
+        size: i64,
+        seed: i64,
+    ) -> SqlxResult<Vec<Order>> {
+        let tables = self.store.tables();
+        let rows = tables
+            .orders
+            .iter()
+            .filter_map(|o| {
+                let customer = tables.customer(&o.order.customer_id)?;
+                let stratum = strata
+                    .iter()
+                    .map(|stratum| match stratum {
+                        SampleStratum::State => customer.customer_state.clone(),
+                        SampleStratum::Status => {
+                            serde_json::to_string(&o.order.order_status).unwrap_or_default()
+                        }
+                        SampleStratum::PurchaseMonth => {
+                            o.order.order_purchase_timestamp.format("%Y-%m").to_string()
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .join("|");
+                Some((stratum, o.order.clone()))
+            })
+            .collect();
+        Ok(rank_sample(rows, size, seed))
+    }
+
+    async fn find_products_by_order_id(&self, id: &OrderId) -> SqlxResult<Vec<OrderProduct>> {
+        let items = self.items(id);
+        let tables = self.store.tables();
+        Ok(items
+            .into_iter()
+            .filter_map(|item| {
+                let product = tables
+                    .products
+                    .iter()
+                    .find(|p| p.product_id == item.product_id)?
+                    .clone();
+                Some(OrderProduct {
+                    product_id: product.product_id,
+                    product_category_name: product.product_category_name,
+                    product_name_lenght: product.product_name_lenght,
+                    product_description_lenght: product.product_description_lenght,
+                    product_photos_qty: product.product_photos_qty,
+                    product_weight_g: product.product_weight_g,
+                    product_length_cm: product.product_length_cm,
+                    product_height_cm: product.product_height_cm,
+                    product_width_cm: product.product_width_cm,
+                    shipping_limit_date: item.shipping_limit_date,
+                    price: item.price,
+                    freight_value: item.freight_value,
+                    currency: Money::CURRENCY,
+                })
+            })
+            .collect())
+    }
+
+    async fn find_products_by_order_ids(
+        &self,
+        ids: &[OrderId],
+    ) -> SqlxResult<Vec<(OrderId, OrderProduct)>> {
+        let mut products = Vec::new();
+        for id in ids {
+            let found = self.find_products_by_order_id(id).await?;
+            products.extend(found.into_iter().map(|product| (id.clone(), product)));
+        }
+        Ok(products)
+    }
+
+    async fn find_payments_by_order_id(&self, _id: &OrderId) -> SqlxResult<Vec<Payment>> {
+        Ok(Vec::new())
+    }
+
+    async fn find_payments_by_order_ids(&self, _ids: &[OrderId]) -> SqlxResult<Vec<Payment>> {
+        Ok(Vec::new())
+    }
+
+    async fn create_refund(&self, _refund: NewRefund) -> SqlxResult<Option<Refund>> {
+        // No payments are kept, so there is nothing to refund.
+        Ok(None)
+    }
+
+    async fn find_refunds_by_order_id(&self, _id: &OrderId) -> SqlxResult<Vec<Refund>> {
+        Ok(Vec::new())
+    }
+
+    async fn find_refunds_by_order_ids(&self, _ids: &[OrderId]) -> SqlxResult<Vec<Refund>> {
+        Ok(Vec::new())
+    }
+
+    async fn find_payment_mismatches(
+        &self,
+        filter: &ReconciliationFilter,
+        pagination: &PaginationParams,
+    ) -> SqlxResult<(Vec<PaymentMismatch>, i64)> {
+        let tables = self.store.tables();
+        let mut mismatches: Vec<PaymentMismatch> = tables
+            .orders
+            .iter()
+            .map(|o| &o.order)
+            .filter(|order| {
+                filter
+                    .purchased_from
+                    .is_none_or(|from| order.order_purchase_timestamp >= from)
+                    && filter
+                        .purchased_before
+                        .is_none_or(|before| order.order_purchase_timestamp < before)
+            })
+            .filter_map(|order| {
+                let items: Vec<&OrderItem> = tables
+                    .order_items
+                    .iter()
+                    .filter(|i| i.order_id == order.order_id)
+                    .collect();
+                let items_total: Money = items
+                    .iter()
+                    .map(|i| i.price.clone() + &i.freight_value)
+                    .sum();
+                // No payments are kept, so every order with items falls short.
+                let difference = Money::zero() - &items_total;
+                (difference.amount().abs() > filter.tolerance).then(|| PaymentMismatch {
+                    order_id: order.order_id.clone(),
+                    order_status: order.order_status,
+                    order_purchase_timestamp: order.order_purchase_timestamp,
+                    item_count: items.len() as i64,
+                    items_total,
+                    payment_count: 0,
+                    payments_total: Money::zero(),
+                    difference,
+                    currency: Money::CURRENCY,
+                })
+            })
+            .collect();
+        mismatches.sort_by(|a, b| {
+            (a.order_purchase_timestamp, a.order_id.as_str())
+                .cmp(&(b.order_purchase_timestamp, b.order_id.as_str()))
+        });
+        Ok(page_counted(mismatches, pagination))
+    }
+
+    async fn find_payment_method_stats(
+        &self,
+        _filter: &PaymentAnalyticsFilter,
+    ) -> SqlxResult<Vec<PaymentMethodStats>> {
+        // No payments are kept, so there is nothing to group.
+        Ok(Vec::new())
+    }
+
+    async fn find_financials(&self, id: &OrderId) -> SqlxResult<Option<OrderFinancials>> {
+        let items = self.items(id);
+        let tables = self.store.tables();
+        let Some(order) = tables.order(id) else {
+            return Ok(None);
+        };
+        let coupon = tables
+            .order_coupons
+            .get(id)
+            .and_then(|code| tables.coupons.iter().find(|c| c.code == *code));
+        Ok(Some(OrderFinancials {
+            order_id: order.order.order_id.clone(),
+            order_status: order.order.order_status,
+            item_count: items.len() as i64,
+            items_subtotal: items.iter().map(|item| item.price.amount()).sum(),
+            freight_total: items.iter().map(|item| item.freight_value.amount()).sum(),
+            payment_count: 0,
+            payments_total: BigDecimal::zero(),
+            refunds_total: BigDecimal::zero(),
+            coupon_code: coupon.map(|c| c.code.clone()),
+            coupon_discount_type: coupon.map(|c| c.discount_type.clone()),
+            coupon_value: coupon.map(|c| c.value.clone()),
+        }))
+    }
+
+    async fn find_reviews_by_order_id(&self, _id: &OrderId) -> SqlxResult<Vec<Review>> {
+        Ok(Vec::new())
+    }
+
+    async fn find_reviews_by_order_ids(&self, _ids: &[OrderId]) -> SqlxResult<Vec<Review>> {
+        Ok(Vec::new())
+    }
+
+    async fn find_by_customer_id(
+        &self,
+        customer_id: &CustomerId,
+        pagination: &PaginationParams,
+    ) -> SqlxResult<(Vec<Order>, i64)> {
+        let orders = self
+            .filtered(&OrderFilter::default())
+            .into_iter()
+            .filter(|o| o.customer_id == *customer_id)
+            .collect();
+        Ok(page_counted(orders, pagination))
+    }
+
+    async fn find_by_customer_unique_id(&self, unique_id: &str) -> SqlxResult<Vec<Order>> {
+        let customer_ids: Vec<CustomerId> = self
+            .store
+            .tables()
+            .customers
+            .iter()
+            .filter(|c| c.deleted_at.is_none() && c.customer_unique_id == unique_id)
+            .map(|c| c.customer_id.clone())
+            .collect();
+        Ok(self
+            .filtered(&OrderFilter::default())
+            .into_iter()
+            .filter(|o| customer_ids.contains(&o.customer_id))
+            .collect())
+    }
+
+    async fn find_by_customer_id_after(
+        &self,
+        customer_id: &CustomerId,
+        after: Option<&Order>,
+        limit: i64,
+    ) -> SqlxResult<Vec<Order>> {
+        let key = |o: &Order| (o.order_purchase_timestamp, o.order_id.to_string());
+        let mut orders: Vec<Order> = self
+            .filtered(&OrderFilter::default())
+            .into_iter()
+            .filter(|o| o.customer_id == *customer_id)
+            .filter(|o| after.is_none_or(|after| key(o) > key(after)))
+            .collect();
+        orders.sort_by_key(key);
+        orders.truncate(limit as usize);
+        Ok(orders)
+    }
+
+    async fn find_destination_zip_code_prefix(
+        &self,
+        order_id: &OrderId,
+    ) -> SqlxResult<Option<String>> {
+        let tables = self.store.tables();
+        Ok(tables.order(order_id).and_then(|o| {
+            o.shipping_zip_code_prefix.clone().or_else(|| {
+                tables
+                    .customer(&o.order.customer_id)
+                    .map(|c| c.customer_zip_code_prefix.clone())
+            })
+        }))
+    }
+
+    async fn find_item_origins(&self, order_id: &OrderId) -> SqlxResult<Vec<OrderItemOrigin>> {
+        let items = self.items(order_id);
+        let tables = self.store.tables();
+        Ok(items
+            .into_iter()
+            .filter_map(|item| {
+                let seller = tables
+                    .sellers
+                    .iter()
+                    .find(|s| s.seller_id == item.seller_id)?;
+                Some(OrderItemOrigin {
+                    seller_zip_code_prefix: seller.seller_zip_code_prefix.clone(),
+                    item,
+                })
+            })
+            .collect())
+    }
+
+    async fn apply_amendment(
+        &self,
+        order_id: &OrderId,
+        actor: &str,
+        shipping_zip_code_prefix: Option<&str>,
+        items: &[(ProductId, OrderItem)],
+        amendment: NewOrderAmendment,
+    ) -> SqlxResult<OrderAmendment> {
+        let mut tables = self.store.tables();
+        if tables.order(order_id).is_none() {
+            return Err(violation(
+                ViolationKind::ForeignKey,
+                format!("order {} does not exist", order_id),
+            ));
+        }
+        if let Some(missing) = items
+            .iter()
+            .find(|(_, item)| !tables.has_product(&item.product_id))
+        {
+            return Err(violation(
+                ViolationKind::ForeignKey,
+                format!("product {} does not exist", missing.1.product_id),
+            ));
+        }
+
+        if let Some(zip) = shipping_zip_code_prefix
+            && let Some(order) = tables
+                .orders
+                .iter_mut()
+                .find(|o| o.order.order_id == *order_id)
+        {
+            order.shipping_zip_code_prefix = Some(zip.to_string());
+        }
+
+        for (previous_product_id, item) in items {
+            if let Some(stored) = tables.order_items.iter_mut().find(|i| {
+                i.order_id == *order_id
+                    && i.order_item_id == item.order_item_id
+                    && i.product_id == *previous_product_id
+                    && i.seller_id == item.seller_id
+            }) {
+                stored.product_id = item.product_id.clone();
+                stored.price = item.price.clone();
+                stored.freight_value = item.freight_value.clone();
+            }
+        }
+
+        let recorded = OrderAmendment {
+            amendment_id: tables.next_id("order_amendments"),
+            order_id: order_id.clone(),
+            actor: actor.to_string(),
+            changes: amendment.changes,
+            previous_freight: amendment.previous_freight.into(),
+            new_freight: amendment.new_freight.into(),
+            previous_tax: amendment.previous_tax.into(),
+            new_tax: amendment.new_tax.into(),
+            created_at: now(),
+            currency: Money::CURRENCY,
+        };
+        tables.amendments.push(recorded.clone());
+        Ok(recorded)
+    }
+
+    async fn find_amendments(&self, order_id: &OrderId) -> SqlxResult<Vec<OrderAmendment>> {
+        Ok(self
+            .store
+            .tables()
+            .amendments
+            .iter()
+            .filter(|a| a.order_id == *order_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn count_by_status(&self) -> SqlxResult<Vec<FilterValue>> {
+        let statuses: Vec<String> = self
+            .store
+            .tables()
+            .orders
+            .iter()
+            .map(|o| {
+                serde_json::to_value(o.order.order_status)
+                    .ok()
+                    .and_then(|v| v.as_str().map(str::to_string))
+                    .unwrap_or_default()
+            })
+            .collect();
+        Ok(count_values(statuses.iter().map(String::as_str)))
+    }
+}
+
+fn matches_product(product: &Product, filter: &ProductFilter) -> bool {
+    let volume = i64::from(product.product_length_cm)
+        * i64::from(product.product_height_cm)
+        * i64::from(product.product_width_cm);
+    let within = |value: i32, min: Option<i32>, max: Option<i32>| {
+        min.is_none_or(|min| value >= min) && max.is_none_or(|max| value <= max)
+    };
+
+    filter
+        .category_name
+        .as_ref()
+        .is_none_or(|name| product.product_category_name == *name)
+        && within(
+            product.product_weight_g,
+            filter.min_weight_g,
+            filter.max_weight_g,
+        )
+        && within(
+            product.product_length_cm,
+            filter.min_length_cm,
+            filter.max_length_cm,
+        )
+        && within(
+            product.product_height_cm,
+            filter.min_height_cm,
+            filter.max_height_cm,
+        )
+        && within(
+            product.product_width_cm,
+            filter.min_width_cm,
+            filter.max_width_cm,
+        )
+        && within(
+            product.product_photos_qty,
+            filter.min_photos,
+            filter.max_photos,
+        )
+        && filter.min_volume_cm3.is_none_or(|min| volume >= min)
+        && filter.max_volume_cm3.is_none_or(|max| volume <= max)
+        && filter.active.is_none_or(|active| product.active == active)
+        && filter.min_price.as_ref().is_none_or(|min| {
+            product
+                .price
+                .as_ref()
+                .is_some_and(|price| price.amount() >= min)
+        })
+        && filter.max_price.as_ref().is_none_or(|max| {
+            product
+                .price
+                .as_ref()
+                .is_some_and(|price| price.amount() <= max)
+        })
+}
+
+#[derive(Clone)]
+pub struct InMemoryProductRepository {
+    store: MemoryStore,
+}
+
+impl InMemoryProductRepository {
+    pub fn new(store: MemoryStore) -> Self {
+        Self { store }
+    }
+
+    fn filtered(&self, filter: &ProductFilter) -> Vec<Product> {
+        let mut products: Vec<Product> = self
+            .store
+            .tables()
+            .products
+            .iter()
+            .filter(|product| matches_product(product, filter))
+            .cloned()
+            .collect();
+        products.sort_by(|a, b| b.product_id.as_str().cmp(a.product_id.as_str()));
+        products
+    }
+}
+
+#[async_trait]
+impl ProductRepository for InMemoryProductRepository {
+    async fn create(&self, id: &ProductId, dto: CreateProductDto) -> SqlxResult<Product> {
+        let mut tables = self.store.tables();
+        if tables.has_product(id) {
+            return Err(violation(
+                ViolationKind::Unique,
+                format!("product {} already exists", id),
+            ));
+        }
+
+        let product = Product {
+            product_id: id.clone(),
+            product_category_name: dto.product_category_name,
+            product_name_lenght: dto.product_name_lenght,
+            product_description_lenght: dto.product_description_lenght,
+            product_photos_qty: dto.product_photos_qty,
+            product_weight_g: dto.product_weight_g,
+            product_length_cm: dto.product_length_cm,
+            product_height_cm: dto.product_height_cm,
+            product_width_cm: dto.product_width_cm,
+            product_name: dto.product_name,
+            description: dto.description,
+            price: dto.price.map(Money::new),
+            active: dto.active.unwrap_or(true),
+            currency: Money::CURRENCY,
+        };
+        tables.products.push(product.clone());
+        Ok(product)
+    }
+
+    async fn create_many(
+        &self,
+        rows: Vec<(ProductId, CreateProductDto)>,
+    ) -> SqlxResult<Vec<SqlxResult<Product>>> {
+        let mut results = Vec::with_capacity(rows.len());
+        for (id, dto) in rows {
+            results.push(self.create(&id, dto).await);
+        }
+        Ok(results)
+    }
+
+    async fn find_all(
+        &self,
+        filter: &ProductFilter,
+        pagination: &PaginationParams,
+        total: TotalMode,
+    ) -> SqlxResult<(Vec<Product>, Total)> {
+        Ok(page(self.filtered(filter), pagination, total))
+    }
+
+    async fn find_all_sparse(
+        &self,
+        filter: &ProductFilter,
+        pagination: &PaginationParams,
+        fields: &[&'static str],
+        total: TotalMode,
+    ) -> SqlxResult<(Vec<SparseRow>, Total)> {
+        sparse(page(self.filtered(filter), pagination, total), fields)
+    }
+
+    fn stream_all(&self) -> BoxStream<'_, SqlxResult<Product>> {
+        let mut products = self.filtered(&ProductFilter::default());
+        products.reverse();
+        stream(products)
+    }
+
+    async fn find_by_id(&self, id: &ProductId) -> SqlxResult<Option<Product>> {
+        Ok(self
+            .store
+            .tables()
+            .products
+            .iter()
+            .find(|p| p.product_id == *id)
+            .cloned())
+    }
+
+    async fn update(&self, id: &ProductId, dto: UpdateProductDto) -> SqlxResult<Option<Product>> {
+        let mut tables = self.store.tables();
+        let Some(product) = tables.products.iter_mut().find(|p| p.product_id == *id) else {
+            return Ok(None);
+        };
+        if let Some(name) = dto.product_name {
+            product.product_name = Some(name);
+        }
+        if let Some(description) = dto.description {
+            product.description = Some(description);
+        }
+        if let Some(price) = dto.price {
+            product.price = Some(Money::new(price));
+        }
+        if let Some(active) = dto.active {
+            product.active = active;
+        }
+        Ok(Some(product.clone()))
+    }
+
+    async fn create_image(
+        &self,
+        id: &ProductId,
+        image: NewProductImage,
+    ) -> SqlxResult<ProductImage> {
+        let mut tables = self.store.tables();
+        let Some(product) = tables.products.iter_mut().find(|p| p.product_id == *id) else {
+            return Err(violation(
+                ViolationKind::ForeignKey,
+                format!("product {} does not exist", id),
+            ));
+        };
+        product.product_photos_qty += 1;
+
+        let position = tables
+            .product_images
+            .iter()
+            .filter(|i| i.product_id == *id)
+            .map(|i| i.position)
+            .max()
+            .unwrap_or(0)
+            + 1;
+        let image = ProductImage {
+            image_id: tables.next_id("product_images"),
+            product_id: id.clone(),
+            position,
+            url: image.url,
+            storage_key: image.storage_key,
+            content_type: image.content_type,
+            size_bytes: image.size_bytes,
+            created_at: now(),
+        };
+        tables.product_images.push(image.clone());
+        Ok(image)
+    }
+
+    async fn find_images(&self, id: &ProductId) -> SqlxResult<Vec<ProductImage>> {
+        let mut images: Vec<ProductImage> = self
+            .store
+            .tables()
+            .product_images
+            .iter()
+            .filter(|i| i.product_id == *id)
+            .cloned()
+            .collect();
+        images.sort_by_key(|i| i.position);
+        Ok(images)
+    }
+
+    async fn delete_image(
+        &self,
+        id: &ProductId,
+        image_id: i64,
+    ) -> SqlxResult<Option<(ProductImage, NaiveDateTime)>> {
+        let mut tables = self.store.tables();
+        let Some(index) = tables
+            .product_images
+            .iter()
+            .position(|i| i.product_id == *id && i.image_id == image_id)
+        else {
+            return Ok(None);
+        };
+        let image = tables.product_images.remove(index);
+        for later in tables
+            .product_images
+            .iter_mut()
+            .filter(|i| i.product_id == *id && i.position > image.position)
+        {
+            later.position -= 1;
+        }
+        if let Some(product) = tables.products.iter_mut().find(|p| p.product_id == *id) {
+            product.product_photos_qty = (product.product_photos_qty - 1).max(0);
+        }
+        Ok(Some((image, now())))
+    }
+
+    async fn count_by_category(&self) -> SqlxResult<Vec<FilterValue>> {
+        let tables = self.store.tables();
+        Ok(count_values(
+            tables
+                .products
+                .iter()
+                .map(|p| p.product_category_name.as_str()),
+        ))
+    }
+
+    async fn find_recommended(
+        &self,
+        customer_unique_id: &str,
+        state: &str,
+        limit: i64,
+    ) -> SqlxResult<Vec<RecommendedProduct>> {
+        let tables = self.store.tables();
+        let product = |id: &ProductId| tables.products.iter().find(|p| p.product_id == *id);
+        let customer = |id: &CustomerId| tables.customers.iter().find(|c| c.customer_id == *id);
+        let orders = || {
+            tables
+                .orders
+                .iter()
+                .filter_map(|o| Some((&o.order, customer(&o.order.customer_id)?)))
+        };
+
+        let bought: HashSet<&ProductId> = orders()
+            .filter(|(_, c)| c.customer_unique_id == customer_unique_id)
+            .flat_map(|(o, _)| {
+                tables
+                    .order_items
+                    .iter()
+                    .filter(move |i| i.order_id == o.order_id)
+            })
+            .map(|i| &i.product_id)
+            .collect();
+        let categories: HashSet<&str> = bought
+            .iter()
+            .filter_map(|id| product(id))
+            .map(|p| p.product_category_name.as_str())
+            .collect();
+
+        let mut state_orders: HashMap<&ProductId, HashSet<&OrderId>> = HashMap::new();
+        for (order, _) in orders()
+            .filter(|(o, c)| c.customer_state == state && o.order_status != OrderStatus::Canceled)
+        {
+            for item in tables
+                .order_items
+                .iter()
+                .filter(|i| i.order_id == order.order_id)
+            {
+                state_orders
+                    .entry(&item.product_id)
+                    .or_default()
+                    .insert(&order.order_id);
+            }
+        }
+
+        let mut recommended: Vec<RecommendedProduct> = state_orders
+            .into_iter()
+            .filter(|(id, _)| !bought.contains(id))
+            .filter_map(|(id, orders)| {
+                let product = product(id)?;
+                categories
+                    .contains(product.product_category_name.as_str())
+                    .then(|| RecommendedProduct {
+                        product: product.clone(),
+                        state_order_count: orders.len() as i64,
+                    })
+            })
+            .collect();
+        recommended.sort_by(|a, b| {
+            b.state_order_count.cmp(&a.state_order_count).then_with(|| {
+                a.product
+                    .product_id
+                    .as_str()
+                    .cmp(b.product.product_id.as_str())
+            })
+        });
+        recommended.truncate(limit as usize);
+        Ok(recommended)
+    }
+}
+
+#[derive(Clone)]
+pub struct InMemoryCategoryRepository {
+    store: MemoryStore,
+}
+
+impl InMemoryCategoryRepository {
+    pub fn new(store: MemoryStore) -> Self {
+        Self { store }
+    }
+}
+
+#[async_trait]
+impl CategoryRepository for InMemoryCategoryRepository {
+    async fn create(&self, dto: CreateCategoryDto) -> SqlxResult<Category> {
+        let mut tables = self.store.tables();
+        if tables
+            .categories
+            .iter()
+            .any(|c| c.product_category_name == dto.product_category_name)
+        {
+            return Err(violation(
+                ViolationKind::Unique,
+                format!("category {} already exists", dto.product_category_name),
+            ));
+        }
+
+        let created_at = now();
+        let category = Category {
+            product_category_name: dto.product_category_name,
+            product_category_name_english: dto.product_category_name_english,
+            created_at,
+            updated_at: created_at,
+        };
+        tables.categories.push(category.clone());
+        Ok(category)
+    }
+
+    async fn find_by_name(&self, name: &str) -> SqlxResult<Option<Category>> {
+        Ok(self
+            .store
+            .tables()
+            .categories
+            .iter()
+            .find(|c| c.product_category_name == name)
+            .cloned())
+    }
+
+    async fn update(&self, name: &str, dto: UpdateCategoryDto) -> SqlxResult<Option<Category>> {
+        let mut tables = self.store.tables();
+        Ok(tables
+            .categories
+            .iter_mut()
+            .find(|c| c.product_category_name == name)
+            .map(|category| {
+                category.product_category_name_english = Some(dto.product_category_name_english);
+                category.updated_at = now();
+                category.clone()
+            }))
+    }
+
+    async fn count_products(&self, name: &str) -> SqlxResult<i64> {
+        Ok(self
+            .store
+            .tables()
+            .products
+            .iter()
+            .filter(|p| p.product_category_name == name)
+            .count() as i64)
+    }
+
+    async fn delete(&self, name: &str) -> SqlxResult<Option<NaiveDateTime>> {
+        let mut tables = self.store.tables();
+        let before = tables.categories.len();
+        tables
+            .categories
+            .retain(|c| c.product_category_name != name);
+        Ok((tables.categories.len() < before).then(now))
+    }
+}
+
+#[derive(Clone)]
+pub struct InMemoryAuditRepository {
+    store: MemoryStore,
+}
+
+impl InMemoryAuditRepository {
+    pub fn new(store: MemoryStore) -> Self {
+        Self { store }
+    }
+}
+
+#[async_trait]
+impl AuditRepository for InMemoryAuditRepository {
+    async fn record(&self, entry: NewAuditEntry) -> SqlxResult<AuditEntry> {
+        let mut tables = self.store.tables();
+        let entry = AuditEntry {
+            audit_id: tables.next_id("audit_log"),
+            entity_type: entry.entity_type.to_string(),
+            entity_id: entry.entity_id,
+            action: entry.action.as_str().to_string(),
+            actor: entry.actor,
+            diff: entry.diff,
+            created_at: now(),
+        };
+        tables.audit_log.push(entry.clone());
+        Ok(entry)
+    }
+
+    async fn record_many(&self, entries: Vec<NewAuditEntry>) -> SqlxResult<()> {
+        for entry in entries {
+            self.record(entry).await?;
+        }
+        Ok(())
+    }
+
+    async fn find_all(
+        &self,
+        filter: &AuditFilter,
+        pagination: &PaginationParams,
+    ) -> SqlxResult<(Vec<AuditEntry>, i64)> {
+        let mut entries: Vec<AuditEntry> = self
+            .store
+            .tables()
+            .audit_log
+            .iter()
+            .filter(|e| {
+                filter
+                    .entity_type
+                    .as_ref()
+                    .is_none_or(|t| e.entity_type == *t)
+                    && filter
+                        .entity_id
+                        .as_ref()
+                        .is_none_or(|id| e.entity_id == *id)
+            })
+            .cloned()
+            .collect();
+        entries.sort_by(|a, b| {
+            b.created_at
+                .cmp(&a.created_at)
+                .then_with(|| b.audit_id.cmp(&a.audit_id))
+        });
+        Ok(page_counted(entries, pagination))
+    }
+}
+
+#[derive(Clone)]
+pub struct InMemoryEmbeddingRepository {
+    store: MemoryStore,
+}
+
+impl InMemoryEmbeddingRepository {
+    pub fn new(store: MemoryStore) -> Self {
+        Self { store }
+    }
+}
+
+#[async_trait]
+impl EmbeddingRepository for InMemoryEmbeddingRepository {
+    /// Just the category name: the review text the database version appends isn't stored here.
+    async fn find_embedding_source(&self, product_id: &ProductId) -> SqlxResult<Option<String>> {
+        Ok(self
+            .store
+            .tables()
+            .products
+            .iter()
+            .find(|p| p.product_id == *product_id)
+            .map(|p| format!("{} ", p.product_category_name)))
+    }
+
+    async fn has_embedding(&self, product_id: &ProductId) -> SqlxResult<bool> {
+        Ok(self.store.tables().embeddings.contains_key(product_id))
+    }
+
+    async fn upsert(&self, product_id: &ProductId, embedding: &[f32]) -> SqlxResult<()> {
+        let mut tables = self.store.tables();
+        if !tables.has_product(product_id) {
+            return Err(violation(
+                ViolationKind::ForeignKey,
+                format!("product {} does not exist", product_id),
+            ));
+        }
+        tables
+            .embeddings
+            .insert(product_id.clone(), embedding.to_vec());
+        Ok(())
+    }
+
+    async fn find_products_without_embedding(&self, limit: i64) -> SqlxResult<Vec<ProductId>> {
+        let tables = self.store.tables();
+        Ok(tables
+            .products
+            .iter()
+            .filter(|p| !tables.embeddings.contains_key(&p.product_id))
+            .take(limit.max(0) as usize)
+            .map(|p| p.product_id.clone())
+            .collect())
+    }
+
+    async fn find_similar(
+        &self,
+        product_id: &ProductId,
+        limit: i64,
+    ) -> SqlxResult<Vec<SimilarProduct>> {
+        let tables = self.store.tables();
+        let Some(target) = tables.embeddings.get(product_id) else {
+            return Ok(Vec::new());
+        };
+
+        let mut similar: Vec<SimilarProduct> = tables
+            .products
+            .iter()
+            .filter(|p| p.product_id != *product_id)
+            .filter_map(|p| {
+                let embedding = tables.embeddings.get(&p.product_id)?;
+                Some(SimilarProduct {
+                    product: p.clone(),
+                    distance: cosine_distance(target, embedding),
+                })
+            })
+            .collect();
+        similar.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+        similar.truncate(limit.max(0) as usize);
+        Ok(similar)
+    }
+}
+
+#[derive(Clone)]
+pub struct InMemoryLateOrderRepository {
+    store: MemoryStore,
+}
+
+impl InMemoryLateOrderRepository {
+    pub fn new(store: MemoryStore) -> Self {
+        Self { store }
+    }
+}
+
+/// Past its estimated delivery date without having arrived.
+fn is_late(order: &Order, now: NaiveDateTime) -> bool {
+    order.order_estimated_delivery_date < now
+        && order.order_delivered_customer_date.is_none()
+        && !matches!(
+            order.order_status,
+            OrderStatus::Delivered | OrderStatus::Canceled | OrderStatus::Unavailable
+        )
+}
+
+#[async_trait]
+impl LateOrderRepository for InMemoryLateOrderRepository {
+    async fn flag_late_orders(&self, max_age_days: i64) -> SqlxResult<u64> {
+        let mut tables = self.store.tables();
+        let now = now();
+        let oldest = now - chrono::Duration::days(max_age_days);
+
+        let mut late: Vec<Order> = tables
+            .orders
+            .iter()
+            .map(|stored| &stored.order)
+            .filter(|o| is_late(o, now) && o.order_estimated_delivery_date >= oldest)
+            .filter(|o| !tables.late_alerts.contains_key(&o.order_id))
+            .cloned()
+            .collect();
+        late.sort_by(|a, b| {
+            (a.order_estimated_delivery_date, a.order_id.as_str())
+                .cmp(&(b.order_estimated_delivery_date, b.order_id.as_str()))
+        });
+
+        for order in &late {
+            tables.late_alerts.insert(order.order_id.clone(), now);
+            let payload = serde_json::json!({
+                "order_id": order.order_id,
+                "customer_id": order.customer_id,
+                "order_status": order.order_status,
+                "order_delivered_carrier_date": order.order_delivered_carrier_date,
+                "order_estimated_delivery_date": order.order_estimated_delivery_date,
+            });
+            tables.record_event("order", order.order_id.as_str(), "order.late", payload);
+        }
+        Ok(late.len() as u64)
+    }
+
+    async fn find_late_orders(
+        &self,
+        pagination: &PaginationParams,
+    ) -> SqlxResult<(Vec<LateOrder>, i64)> {
+        let tables = self.store.tables();
+        let now = now();
+
+        let mut rows: Vec<LateOrder> = tables
+            .orders
+            .iter()
+            .map(|stored| &stored.order)
+            .filter(|o| is_late(o, now))
+            .filter_map(|o| {
+                let flagged_at = *tables.late_alerts.get(&o.order_id)?;
+                Some(LateOrder {
+                    order: o.clone(),
+                    flagged_at,
+                    days_late: (now - o.order_estimated_delivery_date).num_days(),
+                })
+            })
+            .collect();
+        rows.sort_by(|a, b| {
+            (
+                a.order.order_estimated_delivery_date,
+                a.order.order_id.as_str(),
+            )
+                .cmp(&(
+                    b.order.order_estimated_delivery_date,
+                    b.order.order_id.as_str(),
+                ))
+        });
+        Ok(page_counted(rows, pagination))
+    }
+}
+
+/// There are no reviews in memory, so there is nothing to tag or list.
+#[derive(Clone)]
+pub struct InMemoryReviewSentimentRepository;
+
+#[async_trait]
+impl ReviewSentimentRepository for InMemoryReviewSentimentRepository {
+    async fn find_untagged(&self, _limit: i64) -> SqlxResult<Vec<ReviewText>> {
+        Ok(Vec::new())
+    }
+
+    async fn save(&self, _sentiments: &[(String, Sentiment)]) -> SqlxResult<u64> {
+        Ok(0)
+    }
+
+    async fn find_reviews(
+        &self,
+        _sentiment: Option<Sentiment>,
+        _pagination: &PaginationParams,
+    ) -> SqlxResult<(Vec<SentimentReview>, i64)> {
+        Ok((Vec::new(), 0))
+    }
+}
+
+#[derive(Clone)]
+pub struct InMemoryInventoryRepository {
+    store: MemoryStore,
+}
+
+impl InMemoryInventoryRepository {
+    pub fn new(store: MemoryStore) -> Self {
+        Self { store }
+    }
+}
+
+/// Distance between two CEP prefixes, used to pick the nearest stock location.
+fn zip_distance(a: &str, b: &str) -> i64 {
+    let parse = |zip: &str| zip.parse::<i64>().unwrap_or(0);
+    (parse(a) - parse(b)).abs()
+}
+
+impl Tables {
+    /// Index into `stock` of the seller location holding `product_id` nearest to `zip`,
+    /// among those with at least `min_quantity` units.
+    fn nearest_stock(
+        &self,
+        seller_id: &SellerId,
+        product_id: &ProductId,
+        zip: &str,
+        min_quantity: i32,
+    ) -> Option<usize> {
+        self.stock
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| s.product_id == *product_id && s.quantity >= min_quantity)
+            .filter_map(|(index, s)| {
+                let location = self
+                    .locations
+                    .iter()
+                    .find(|l| l.location_id == s.location_id && l.seller_id == *seller_id)?;
+                Some((
+                    zip_distance(&location.zip_code_prefix, zip),
+                    location.location_id,
+                    index,
+                ))
+            })
+            .min()
+            .map(|(_, _, index)| index)
+    }
+}
+
+#[async_trait]
+impl InventoryRepository for InMemoryInventoryRepository {
+    async fn create_location(
+        &self,
+        seller_id: &SellerId,
+        dto: CreateStockLocationDto,
+    ) -> SqlxResult<StockLocation> {
+        let mut tables = self.store.tables();
+        if !tables.has_seller(seller_id) {
+            return Err(violation(
+                ViolationKind::ForeignKey,
+                format!("seller {} does not exist", seller_id),
+            ));
+        }
+
+        let location = StockLocation {
+            location_id: tables.next_id("stock_locations"),
+            seller_id: seller_id.clone(),
+            name: dto.name,
+            zip_code_prefix: dto.zip_code_prefix,
+            created_at: now(),
+        };
+        tables.locations.push(location.clone());
+        Ok(location)
+    }
+
+    async fn find_location_by_id(&self, location_id: i64) -> SqlxResult<Option<StockLocation>> {
+        Ok(self
+            .store
+            .tables()
+            .locations
+            .iter()
+            .find(|l| l.location_id == location_id)
+            .cloned())
+    }
+
+    async fn find_locations_by_seller(
+        &self,
+        seller_id: &SellerId,
+    ) -> SqlxResult<Vec<StockLocation>> {
+        Ok(self
+            .store
+            .tables()
+            .locations
+            .iter()
+            .filter(|l| l.seller_id == *seller_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn find_stock_by_location(&self, location_id: i64) -> SqlxResult<Vec<LocationStock>> {
+        let mut stock: Vec<LocationStock> = self
+            .store
+            .tables()
+            .stock
+            .iter()
+            .filter(|s| s.location_id == location_id)
+            .cloned()
+            .collect();
+        stock.sort_by(|a, b| a.product_id.as_str().cmp(b.product_id.as_str()));
+        Ok(stock)
+    }
+
+    async fn find_stock_by_product(
+        &self,
+        product_id: &ProductId,
+    ) -> SqlxResult<Vec<ProductLocationStock>> {
+        let tables = self.store.tables();
+        let mut stock: Vec<ProductLocationStock> = tables
+            .stock
+            .iter()
+            .filter(|s| s.product_id == *product_id)
+            .filter_map(|s| {
+                let location = tables
+                    .locations
+                    .iter()
+                    .find(|l| l.location_id == s.location_id)?;
+                Some(ProductLocationStock {
+                    location_id: location.location_id,
+                    seller_id: location.seller_id.clone(),
+                    name: location.name.clone(),
+                    zip_code_prefix: location.zip_code_prefix.clone(),
+                    quantity: s.quantity,
+                    updated_at: s.updated_at,
+                })
+            })
+            .collect();
+        stock.sort_by(|a, b| {
+            (a.seller_id.as_str(), a.location_id).cmp(&(b.seller_id.as_str(), b.location_id))
+        });
+        Ok(stock)
+    }
+
+    async fn set_stock(
+        &self,
+        location_id: i64,
+        product_id: &ProductId,
+        quantity: i32,
+    ) -> SqlxResult<LocationStock> {
+        let mut tables = self.store.tables();
+        if !tables.has_product(product_id)
+            || !tables
+                .locations
+                .iter()
+                .any(|l| l.location_id == location_id)
+        {
+            return Err(violation(
+                ViolationKind::ForeignKey,
+                "stock references a missing location or product",
+            ));
+        }
+        if quantity < 0 {
+            return Err(violation(
+                ViolationKind::Check,
+                "stock quantity cannot be negative",
+            ));
+        }
+
+        let stock = LocationStock {
+            location_id,
+            product_id: product_id.clone(),
+            quantity,
+            updated_at: now(),
+        };
+        match tables
+            .stock
+            .iter_mut()
+            .find(|s| s.location_id == location_id && s.product_id == *product_id)
+        {
+            Some(existing) => *existing = stock.clone(),
+            None => tables.stock.push(stock.clone()),
+        }
+        Ok(stock)
+    }
+
+    async fn adjust_stock(
+        &self,
+        location_id: i64,
+        product_id: &ProductId,
+        delta: i32,
+    ) -> SqlxResult<Option<LocationStock>> {
+        let mut tables = self.store.tables();
+        let Some(stock) = tables
+            .stock
+            .iter_mut()
+            .find(|s| s.location_id == location_id && s.product_id == *product_id)
+        else {
+            return Ok(None);
+        };
+        if stock.quantity + delta < 0 {
+            return Err(violation(
+                ViolationKind::Check,
+                "stock quantity cannot be negative",
+            ));
+        }
+
+        stock.quantity += delta;
+        stock.updated_at = now();
+        Ok(Some(stock.clone()))
+    }
+
+    async fn is_tracked(&self, seller_id: &SellerId, product_id: &ProductId) -> SqlxResult<bool> {
+        let tables = self.store.tables();
+        Ok(tables.stock.iter().any(|s| {
+            s.product_id == *product_id
+                && tables
+                    .locations
+                    .iter()
+                    .any(|l| l.location_id == s.location_id && l.seller_id == *seller_id)
+        }))
+    }
+
+    async fn allocate(
+        &self,
+        seller_id: &SellerId,
+        product_id: &ProductId,
+        quantity: i32,
+        destination_zip_code_prefix: &str,
+    ) -> SqlxResult<Option<StockAllocation>> {
+        let mut tables = self.store.tables();
+        let Some(index) =
+            tables.nearest_stock(seller_id, product_id, destination_zip_code_prefix, quantity)
+        else {
+            return Ok(None);
+        };
+
+        let stock = &mut tables.stock[index];
+        stock.quantity -= quantity;
+        stock.updated_at = now();
+        Ok(Some(StockAllocation {
+            location_id: stock.location_id,
+            product_id: stock.product_id.clone(),
+            quantity: stock.quantity,
+        }))
+    }
+
+    async fn restock(
+        &self,
+        seller_id: &SellerId,
+        product_id: &ProductId,
+        quantity: i32,
+        origin_zip_code_prefix: &str,
+    ) -> SqlxResult<Option<StockAllocation>> {
+        let mut tables = self.store.tables();
+        let Some(index) =
+            tables.nearest_stock(seller_id, product_id, origin_zip_code_prefix, i32::MIN)
+        else {
+            return Ok(None);
+        };
+
+        let stock = &mut tables.stock[index];
+        stock.quantity += quantity;
+        stock.updated_at = now();
+        Ok(Some(StockAllocation {
+            location_id: stock.location_id,
+            product_id: stock.product_id.clone(),
+            quantity: stock.quantity,
+        }))
+    }
+}
+
+/// The case with its SLA breach flags evaluated as of now.
+fn with_breaches(case: &SupportCase) -> SupportCase {
+    let now = now();
+    SupportCase {
+        first_response_breached: case.first_responded_at.unwrap_or(now)
+            > case.first_response_due_at,
+        resolution_breached: case.resolved_at.unwrap_or(now) > case.resolution_due_at,
+        ..case.clone()
+    }
+}
+
+#[derive(Clone)]
+pub struct InMemorySupportRepository {
+    store: MemoryStore,
+}
+
+impl InMemorySupportRepository {
+    pub fn new(store: MemoryStore) -> Self {
+        Self { store }
+    }
+}
+
+#[async_trait]
+impl SupportRepository for InMemorySupportRepository {
+    async fn create(
+        &self,
+        dto: CreateSupportCaseDto,
+        author: &str,
+        first_response_hours: i64,
+        resolution_hours: i64,
+    ) -> SqlxResult<Option<SupportCase>> {
+        let mut tables = self.store.tables();
+        let Some(customer_id) = tables
+            .order(&dto.order_id)
+            .map(|o| o.order.customer_id.clone())
+        else {
+            return Ok(None);
+        };
+
+        let created_at = now();
+        let case = SupportCase {
+            case_id: tables.next_id("support_cases"),
+            order_id: dto.order_id,
+            customer_id,
+            category: dto.category.as_str().to_string(),
+            status: "open".to_string(),
+            subject: dto.subject,
+            created_at,
+            updated_at: created_at,
+            first_response_due_at: created_at + chrono::Duration::hours(first_response_hours),
+            resolution_due_at: created_at + chrono::Duration::hours(resolution_hours),
+            first_responded_at: None,
+            resolved_at: None,
+            first_response_breached: false,
+            resolution_breached: false,
+        };
+        let message = SupportMessage {
+            message_id: tables.next_id("support_case_messages"),
+            case_id: case.case_id,
+            author_type: "customer".to_string(),
+            author: author.to_string(),
+            body: dto.message,
+            created_at,
+        };
+        tables.support_cases.push(case.clone());
+        tables.support_messages.push(message);
+        Ok(Some(with_breaches(&case)))
+    }
+
+    async fn find_all(
+        &self,
+        filter: &SupportCaseFilter,
+        pagination: &PaginationParams,
+    ) -> SqlxResult<(Vec<SupportCase>, i64)> {
+        let mut cases: Vec<SupportCase> = self
+            .store
+            .tables()
+            .support_cases
+            .iter()
+            .filter(|c| {
+                filter.status.as_ref().is_none_or(|s| c.status == *s)
+                    && filter.category.as_ref().is_none_or(|s| c.category == *s)
+                    && filter.order_id.as_ref().is_none_or(|id| c.order_id == *id)
+            })
+            .map(with_breaches)
+            .collect();
+        cases.sort_by(|a, b| {
+            b.created_at
+                .cmp(&a.created_at)
+                .then_with(|| b.case_id.cmp(&a.case_id))
+        });
+        Ok(page_counted(cases, pagination))
+    }
+
+    async fn find_by_id(&self, case_id: i64) -> SqlxResult<Option<SupportCase>> {
+        Ok(self
+            .store
+            .tables()
+            .support_cases
+            .iter()
+            .find(|c| c.case_id == case_id)
+            .map(with_breaches))
+    }
+
+    async fn find_messages(&self, case_id: i64) -> SqlxResult<Vec<SupportMessage>> {
+        Ok(self
+            .store
+            .tables()
+            .support_messages
+            .iter()
+            .filter(|m| m.case_id == case_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn update(
+        &self,
+        case_id: i64,
+        dto: UpdateSupportCaseDto,
+    ) -> SqlxResult<Option<SupportCase>> {
+        let mut tables = self.store.tables();
+        let Some(case) = tables
+            .support_cases
+            .iter_mut()
+            .find(|c| c.case_id == case_id)
+        else {
+            return Ok(None);
+        };
+
+        let now = now();
+        if let Some(category) = dto.category {
+            case.category = category.as_str().to_string();
+        }
+        if let Some(status) = dto.status {
+            case.status = status.as_str().to_string();
+            case.resolved_at = match status.as_str() {
+                "resolved" | "closed" => Some(case.resolved_at.unwrap_or(now)),
+                _ => None,
+            };
+        }
+        case.updated_at = now;
+        Ok(Some(with_breaches(case)))
+    }
+
+    async fn delete(&self, case_id: i64) -> SqlxResult<Option<NaiveDateTime>> {
+        let mut tables = self.store.tables();
+        let before = tables.support_cases.len();
+        tables.support_cases.retain(|c| c.case_id != case_id);
+        if tables.support_cases.len() == before {
+            return Ok(None);
+        }
+        tables.support_messages.retain(|m| m.case_id != case_id);
+        Ok(Some(now()))
+    }
+
+    async fn add_message(
+        &self,
+        case_id: i64,
+        dto: CreateSupportMessageDto,
+        author: &str,
+    ) -> SqlxResult<Option<SupportMessage>> {
+        let mut tables = self.store.tables();
+        let author_type = dto.author_type.as_str();
+        let now = now();
+        let Some(case) = tables
+            .support_cases
+            .iter_mut()
+            .find(|c| c.case_id == case_id)
+        else {
+            return Ok(None);
+        };
+
+        if author_type == "agent" {
+            case.first_responded_at.get_or_insert(now);
+        } else if case.status == "pending_customer" {
+            case.status = "open".to_string();
+        }
+        case.updated_at = now;
+
+        let message = SupportMessage {
+            message_id: tables.next_id("support_case_messages"),
+            case_id,
+            author_type: author_type.to_string(),
+            author: author.to_string(),
+            body: dto.body,
+            created_at: now,
+        };
+        tables.support_messages.push(message.clone());
+        Ok(Some(message))
+    }
+
+    async fn volume_by_category(&self) -> SqlxResult<Vec<SupportCaseVolume>> {
+        let tables = self.store.tables();
+        let mut volumes: HashMap<&str, (SupportCaseVolume, Vec<f64>)> = HashMap::new();
+        for case in tables.support_cases.iter().map(with_breaches) {
+            let (volume, resolution_hours) = volumes
+                .entry(
+                    tables
+                        .support_cases
+                        .iter()
+                        .find(|c| c.case_id == case.case_id)
+                        .map_or("", |c| c.category.as_str()),
+                )
+                .or_insert_with(|| {
+                    (
+                        SupportCaseVolume {
+                            category: case.category.clone(),
+                            total_cases: 0,
+                            open_cases: 0,
+                            resolved_cases: 0,
+                            sla_breached_cases: 0,
+                            avg_resolution_hours: None,
+                        },
+                        Vec::new(),
+                    )
+                });
+            volume.total_cases += 1;
+            match case.status.as_str() {
+                "open" | "pending_customer" => volume.open_cases += 1,
+                _ => volume.resolved_cases += 1,
+            }
+            if case.first_response_breached || case.resolution_breached {
+                volume.sla_breached_cases += 1;
+            }
+            if let Some(resolved_at) = case.resolved_at {
+                resolution_hours
+                    .push((resolved_at - case.created_at).num_seconds() as f64 / 3600.0);
+            }
+        }
+
+        let mut volumes: Vec<SupportCaseVolume> = volumes
+            .into_values()
+            .map(|(mut volume, hours)| {
+                if !hours.is_empty() {
+                    volume.avg_resolution_hours =
+                        Some(hours.iter().sum::<f64>() / hours.len() as f64);
+                }
+                volume
+            })
+            .collect();
+        volumes.sort_by(|a, b| {
+            b.total_cases
+                .cmp(&a.total_cases)
+                .then_with(|| a.category.cmp(&b.category))
+        });
+        Ok(volumes)
+    }
+}
+
+/// There are no materialized views or search indexes in memory, so every step is a no-op.
+#[derive(Clone)]
+pub struct InMemoryMaintenanceRepository;
+
+#[async_trait]
+impl MaintenanceRepository for InMemoryMaintenanceRepository {
+    async fn find_materialized_views(&self) -> SqlxResult<Vec<String>> {
+        Ok(Vec::new())
+    }
+
+    async fn refresh_materialized_view(&self, _name: &str) -> SqlxResult<()> {
+        Ok(())
+    }
+
+    async fn reindex_search_indexes(&self) -> SqlxResult<Vec<String>> {
+        Ok(Vec::new())
+    }
+
+    async fn archive_orders(
+        &self,
+        _before: chrono::NaiveDateTime,
+        _limit: i64,
+    ) -> SqlxResult<Option<ArchivedOrders>> {
+        Ok(None)
+    }
+}
+
+/// Always reachable, never replicated, and without tables or a pool to report on.
+#[derive(Clone)]
+pub struct InMemoryDiagnosticsRepository;
+
+#[async_trait]
+impl DiagnosticsRepository for InMemoryDiagnosticsRepository {
+    async fn ping(&self) -> SqlxResult<()> {
+        Ok(())
+    }
+
+    async fn replica_lag_seconds(&self) -> SqlxResult<Option<f64>> {
+        Ok(None)
+    }
+
+    async fn database_size_bytes(&self) -> SqlxResult<Option<i64>> {
+        Ok(None)
+    }
+
+    async fn last_migration(&self) -> SqlxResult<Option<AppliedMigration>> {
+        Ok(None)
+    }
+
+    async fn table_stats(&self, _exact: bool) -> SqlxResult<(Vec<TableStats>, bool)> {
+        Ok((Vec::new(), false))
+    }
+
+    fn pool_stats(&self) -> Option<PoolStats> {
+        None
+    }
+}
+
+#[derive(Clone)]
+pub struct InMemoryDataQualityRepository {
+    store: MemoryStore,
+}
+
+impl InMemoryDataQualityRepository {
+    pub fn new(store: MemoryStore) -> Self {
+        Self { store }
+    }
+
+    /// Each order with its item count and issues, oldest purchase first.
+    fn checked_orders(&self) -> Vec<(FlaggedOrder, Vec<OrderIssue>)> {
+        let tables = self.store.tables();
+        let mut orders: Vec<(FlaggedOrder, Vec<OrderIssue>)> = tables
+            .orders
+            .iter()
+            .map(|stored| {
+                let item_count = tables
+                    .order_items
+                    .iter()
+                    .filter(|item| item.order_id == stored.order.order_id)
+                    .count() as i64;
+                let order = FlaggedOrder {
+                    order: stored.order.clone(),
+                    item_count,
+                    issues: Vec::new(),
+                };
+                (order, OrderIssue::of(&stored.order, item_count))
+            })
+            .collect();
+        orders.sort_by(|(a, _), (b, _)| {
+            (a.order.order_purchase_timestamp, a.order.order_id.as_str())
+                .cmp(&(b.order.order_purchase_timestamp, b.order.order_id.as_str()))
+        });
+        orders
+    }
+}
+
+/// The rows of a table a rule can name, as JSON objects keyed by column.
+fn table_rows(tables: &Tables, table: &str) -> SqlxResult<Vec<serde_json::Value>> {
+    let rows = match table {
+        "customers" => serde_json::to_value(&tables.customers),
+        "sellers" => serde_json::to_value(&tables.sellers),
+        "orders" => serde_json::to_value(
+            tables
+                .orders
+                .iter()
+                .map(|stored| &stored.order)
+                .collect::<Vec<_>>(),
+        ),
+        "order_items" => serde_json::to_value(&tables.order_items),
+        "products" => serde_json::to_value(&tables.products),
+        "product_categories" => serde_json::to_value(&tables.categories),
+        _ => return Err(sqlx::Error::Protocol(format!("no such table: {}", table))),
+    };
+    match rows.map_err(|e| sqlx::Error::Decode(Box::new(e)))? {
+        serde_json::Value::Array(rows) => Ok(rows),
+        _ => Ok(Vec::new()),
+    }
+}
+
+/// Whether `row` breaks `rule`. As in SQL, a comparison with a null never matches.
+fn breaks_rule(row: &serde_json::Value, rule: &DataQualityRule) -> SqlxResult<bool> {
+    let column = |name: &str| {
+        row.get(name)
+            .ok_or_else(|| sqlx::Error::ColumnNotFound(name.to_string()))
+    };
+    let value = column(&rule.column)?;
+    let ordering = || -> SqlxResult<Option<Ordering>> {
+        let right = match &rule.other_column {
+            Some(other) => column(other)?,
+            None => rule.value.as_ref().unwrap_or(&serde_json::Value::Null),
+        };
+        Ok(compare_json(value, right))
+    };
+    Ok(match rule.predicate {
+        RulePredicate::IsNull => value.is_null(),
+        RulePredicate::IsBlank => match value {
+            serde_json::Value::Null => true,
+            serde_json::Value::String(text) => text.trim().is_empty(),
+            _ => false,
+        },
+        RulePredicate::Eq => ordering()?.is_some_and(Ordering::is_eq),
+        RulePredicate::Ne => ordering()?.is_some_and(Ordering::is_ne),
+        RulePredicate::Lt => ordering()?.is_some_and(Ordering::is_lt),
+        RulePredicate::Lte => ordering()?.is_some_and(Ordering::is_le),
+        RulePredicate::Gt => ordering()?.is_some_and(Ordering::is_gt),
+        RulePredicate::Gte => ordering()?.is_some_and(Ordering::is_ge),
+    })
+}
+
+/// Orders two JSON values: numbers, and strings holding numbers such as amounts, by value;
+/// other strings and booleans as they are. `None` when either is null or they don't compare.
+fn compare_json(left: &serde_json::Value, right: &serde_json::Value) -> Option<Ordering> {
+    let number = |value: &serde_json::Value| match value {
+        serde_json::Value::Number(number) => number.as_f64(),
+        serde_json::Value::String(text) => text.parse::<f64>().ok(),
+        _ => None,
+    };
+    if let (Some(left), Some(right)) = (number(left), number(right)) {
+        return left.partial_cmp(&right);
+    }
+    match (left, right) {
+        (serde_json::Value::String(left), serde_json::Value::String(right)) => {
+            Some(left.cmp(right))
+        }
+        (serde_json::Value::Bool(left), serde_json::Value::Bool(right)) => Some(left.cmp(right)),
+        _ => None,
+    }
+}
+
+/// Tallies the issues of each checked row, `all` giving the order to report them in.
+fn quality_report<I: Copy + PartialEq>(rows: &[Vec<I>], all: &[I]) -> DataQualityReport<I> {
+    DataQualityReport {
+        checked: rows.len() as i64,
+        flagged: rows.iter().filter(|issues| !issues.is_empty()).count() as i64,
+        issues: all
+            .iter()
+            .map(|&issue| IssueCount {
+                issue,
+                count: rows.iter().filter(|issues| issues.contains(&issue)).count() as i64,
+            })
+            .collect(),
+    }
+}
+
+/// Whether a row with `issues` is listed for `issue`, or for any issue when `None`.
+fn has_issue<I: PartialEq>(issues: &[I], issue: Option<I>) -> bool {
+    match issue {
+        Some(issue) => issues.contains(&issue),
+        None => !issues.is_empty(),
+    }
+}
+
+#[async_trait]
+impl DataQualityRepository for InMemoryDataQualityRepository {
+    async fn product_report(&self) -> SqlxResult<DataQualityReport<ProductIssue>> {
+        let tables = self.store.tables();
+        let issues: Vec<Vec<ProductIssue>> = tables.products.iter().map(ProductIssue::of).collect();
+        Ok(quality_report(&issues, &ProductIssue::ALL))
+    }
+
+    async fn find_flagged_products(
+        &self,
+        issue: Option<ProductIssue>,
+        pagination: &PaginationParams,
+    ) -> SqlxResult<(Vec<Product>, i64)> {
+        let tables = self.store.tables();
+        let mut products: Vec<Product> = tables
+            .products
+            .iter()
+            .filter(|product| has_issue(&ProductIssue::of(product), issue))
+            .cloned()
+            .collect();
+        products.sort_by(|a, b| a.product_id.as_str().cmp(b.product_id.as_str()));
+        Ok(page_counted(products, pagination))
+    }
+
+    async fn order_report(&self) -> SqlxResult<DataQualityReport<OrderIssue>> {
+        let issues: Vec<Vec<OrderIssue>> = self
+            .checked_orders()
+            .into_iter()
+            .map(|(_, issues)| issues)
+            .collect();
+        Ok(quality_report(&issues, &OrderIssue::ALL))
+    }
+
+    async fn find_flagged_orders(
+        &self,
+        issue: Option<OrderIssue>,
+        pagination: &PaginationParams,
+    ) -> SqlxResult<(Vec<FlaggedOrder>, i64)> {
+        let orders = self
+            .checked_orders()
+            .into_iter()
+            .filter(|(_, issues)| has_issue(issues, issue))
+            .map(|(order, _)| order)
+            .collect();
+        Ok(page_counted(orders, pagination))
+    }
+
+    async fn evaluate_rule(&self, rule: &DataQualityRule) -> SqlxResult<(i64, i64)> {
+        let tables = self.store.tables();
+        let rows = table_rows(&tables, &rule.table)?;
+        let mut violations = 0;
+        for row in &rows {
+            if breaks_rule(row, rule)? {
+                violations += 1;
+            }
+        }
+        Ok((rows.len() as i64, violations))
+    }
+
+    async fn replace_rule_results(&self, results: &[DataQualityRuleResult]) -> SqlxResult<()> {
+        self.store.tables().data_quality_results = results.to_vec();
+        Ok(())
+    }
+
+    async fn find_rule_results(&self) -> SqlxResult<Vec<DataQualityRuleResult>> {
+        let mut results = self.store.tables().data_quality_results.clone();
+        results.sort_by(|a, b| {
+            b.severity
+                .cmp(&a.severity)
+                .then_with(|| a.rule_name.cmp(&b.rule_name))
+        });
+        Ok(results)
+    }
+}
+
+#[derive(Clone)]
+pub struct InMemoryImportRepository {
+    store: MemoryStore,
+}
+
+impl InMemoryImportRepository {
+    pub fn new(store: MemoryStore) -> Self {
+        Self { store }
+    }
+
+    fn errors(&self, batch_ids: &[i64]) -> Vec<ImportRowError> {
+        let mut errors: Vec<ImportRowError> = self
+            .store
+            .tables()
+            .import_errors
+            .iter()
+            .filter(|e| batch_ids.contains(&e.batch_id))
+            .cloned()
+            .collect();
+        errors.sort_by_key(|e| (e.batch_id, e.row_number, e.error_id));
+        errors
+    }
+}
+
+impl Tables {
+    /// Whether any row outside `table` still references one of `ids`.
+    fn is_referenced(&self, table: &str, ids: &HashSet<&str>) -> bool {
+        match table {
+            "customers" => self
+                .orders
+                .iter()
+                .any(|o| ids.contains(o.order.customer_id.as_str())),
+            "sellers" => {
+                self.order_items
+                    .iter()
+                    .any(|i| ids.contains(i.seller_id.as_str()))
+                    || self
+                        .locations
+                        .iter()
+                        .any(|l| ids.contains(l.seller_id.as_str()))
+            }
+            "products" => {
+                self.order_items
+                    .iter()
+                    .any(|i| ids.contains(i.product_id.as_str()))
+                    || self
+                        .stock
+                        .iter()
+                        .any(|s| ids.contains(s.product_id.as_str()))
+            }
+            "orders" => {
+                self.order_items
+                    .iter()
+                    .any(|i| ids.contains(i.order_id.as_str()))
+                    || self
+                        .support_cases
+                        .iter()
+                        .any(|c| ids.contains(c.order_id.as_str()))
+            }
+            _ => false,
+        }
+    }
+
+    /// Deletes the rows of `table` whose key is in `ids`, returning how many went.
+    /// Primary keys of the rows in `table`, for the tables imports write to.
+    fn keys(&self, table: &str) -> HashSet<&str> {
+        match table {
+            "customers" => self
+                .customers
+                .iter()
+                .map(|c| c.customer_id.as_str())
+                .collect(),
+            "sellers" => self.sellers.iter().map(|s| s.seller_id.as_str()).collect(),
+            "products" => self
+                .products
+                .iter()
+                .map(|p| p.product_id.as_str())
+                .collect(),
+            "orders" => self
+                .orders
+                .iter()
+                .map(|o| o.order.order_id.as_str())
+                .collect(),
+            _ => HashSet::new(),
+        }
+    }
+
+    fn delete_rows(&mut self, table: &str, ids: &HashSet<&str>) -> u64 {
+        fn remove<T>(rows: &mut Vec<T>, doomed: impl Fn(&T) -> bool) -> u64 {
+            let before = rows.len();
+            rows.retain(|row| !doomed(row));
+            (before - rows.len()) as u64
+        }
+
+        match table {
+            "customers" => {
+                self.location_history
+                    .retain(|(id, _)| !ids.contains(id.as_str()));
+                remove(&mut self.customers, |c| {
+                    ids.contains(c.customer_id.as_str())
+                })
+            }
+            "sellers" => remove(&mut self.sellers, |s| ids.contains(s.seller_id.as_str())),
+            "products" => {
+                self.embeddings.retain(|id, _| !ids.contains(id.as_str()));
+                remove(&mut self.products, |p| ids.contains(p.product_id.as_str()))
+            }
+            "orders" => {
+                self.payment_transactions
+                    .retain(|t| !ids.contains(t.order_id.as_str()));
+                remove(&mut self.orders, |o| {
+                    ids.contains(o.order.order_id.as_str())
+                })
+            }
+            _ => 0,
+        }
+    }
+}
+
+#[async_trait]
+impl ImportRepository for InMemoryImportRepository {
+    async fn begin_load_job(&self, datasets: &[&str], _actor: &str) -> SqlxResult<i64> {
+        let mut tables = self.store.tables();
+        let job_id = tables.next_id("load_jobs");
+        tables.load_jobs.insert(
+            job_id,
+            datasets.iter().map(|dataset| dataset.to_string()).collect(),
+        );
+        Ok(job_id)
+    }
+
+    async fn find_load_job(&self, job_id: i64) -> SqlxResult<Option<StoredLoadJob>> {
+        let tables = self.store.tables();
+        let Some(datasets) = tables.load_jobs.get(&job_id) else {
+            return Ok(None);
+        };
+        let batches = tables
+            .import_batches
+            .iter()
+            .filter_map(|b| {
+                let (load_job_id, checkpoint_line) = tables.batch_progress.get(&b.batch_id)?;
+                (*load_job_id == Some(job_id)).then(|| LoadJobBatch {
+                    batch_id: b.batch_id,
+                    dataset: b.dataset.clone(),
+                    status: b.status.clone(),
+                    success_count: b.success_count,
+                    error_count: b.error_count,
+                    checkpoint_line: *checkpoint_line,
+                })
+            })
+            .collect();
+        Ok(Some(StoredLoadJob {
+            job_id,
+            datasets: datasets.clone(),
+            batches,
+        }))
+    }
+
+    async fn begin_batch(
+        &self,
+        dataset: &str,
+        source: &str,
+        actor: &str,
+        load_job_id: Option<i64>,
+    ) -> SqlxResult<ImportBatch> {
+        let mut tables = self.store.tables();
+        let batch = ImportBatch {
+            batch_id: tables.next_id("import_batches"),
+            dataset: dataset.to_string(),
+            source: source.to_string(),
+            actor: actor.to_string(),
+            status: ImportBatchStatus::Running.as_str().to_string(),
+            success_count: 0,
+            error_count: 0,
+            started_at: now(),
+            finished_at: None,
+            rolled_back_at: None,
+        };
+        tables.import_batches.push(batch.clone());
+        tables
+            .batch_progress
+            .insert(batch.batch_id, (load_job_id, 1));
+        Ok(batch)
+    }
+
+    async fn checkpoint(&self, batch_id: i64, line: i64) -> SqlxResult<()> {
+        if let Some((_, checkpoint_line)) = self.store.tables().batch_progress.get_mut(&batch_id) {
+            *checkpoint_line = line;
+        }
+        Ok(())
+    }
+
+    async fn resume_batch(&self, batch_id: i64) -> SqlxResult<Option<BatchResume>> {
+        let mut tables = self.store.tables();
+        let Some(batch) = tables
+            .import_batches
+            .iter_mut()
+            .find(|b| b.batch_id == batch_id && matches!(b.status.as_str(), "running" | "failed"))
+        else {
+            return Ok(None);
+        };
+        batch.status = ImportBatchStatus::Running.as_str().to_string();
+        batch.finished_at = None;
+
+        let checkpoint_line = tables
+            .batch_progress
+            .get(&batch_id)
+            .map_or(1, |(_, line)| *line);
+        tables
+            .import_errors
+            .retain(|e| e.batch_id != batch_id || e.row_number <= checkpoint_line);
+        Ok(Some(BatchResume {
+            checkpoint_line,
+            inserted: tables
+                .import_rows
+                .iter()
+                .filter(|(batch, _)| *batch == batch_id)
+                .map(|(_, id)| id.clone())
+                .collect(),
+            error_count: tables
+                .import_errors
+                .iter()
+                .filter(|e| e.batch_id == batch_id)
+                .count() as i64,
+        }))
+    }
+
+    async fn record_rows(&self, batch_id: i64, entity_ids: &[String]) -> SqlxResult<()> {
+        let mut tables = self.store.tables();
+        for entity_id in entity_ids {
+            if !tables
+                .import_rows
+                .iter()
+                .any(|(batch, id)| *batch == batch_id && id == entity_id)
+            {
+                tables.import_rows.push((batch_id, entity_id.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    async fn finish_batch(
+        &self,
+        batch_id: i64,
+        status: ImportBatchStatus,
+        success_count: i32,
+        error_count: i32,
+    ) -> SqlxResult<ImportBatch> {
+        let mut tables = self.store.tables();
+        let batch = tables
+            .import_batches
+            .iter_mut()
+            .find(|b| b.batch_id == batch_id)
+            .ok_or(sqlx::Error::RowNotFound)?;
+        batch.status = status.as_str().to_string();
+        batch.success_count = success_count;
+        batch.error_count = error_count;
+        batch.finished_at = Some(now());
+        Ok(batch.clone())
+    }
+
+    async fn find_all(&self, pagination: &PaginationParams) -> SqlxResult<(Vec<ImportBatch>, i64)> {
+        let mut batches = self.store.tables().import_batches.clone();
+        batches.sort_by(|a, b| {
+            b.started_at
+                .cmp(&a.started_at)
+                .then_with(|| b.batch_id.cmp(&a.batch_id))
+        });
+        Ok(page_counted(batches, pagination))
+    }
+
+    async fn find_by_id(&self, batch_id: i64) -> SqlxResult<Option<ImportBatch>> {
+        Ok(self
+            .store
+            .tables()
+            .import_batches
+            .iter()
+            .find(|b| b.batch_id == batch_id)
+            .cloned())
+    }
+
+    async fn record_errors(&self, batch_id: i64, errors: &[NewImportRowError]) -> SqlxResult<()> {
+        let mut tables = self.store.tables();
+        let dataset = tables
+            .import_batches
+            .iter()
+            .find(|b| b.batch_id == batch_id)
+            .map(|b| b.dataset.clone())
+            .ok_or_else(|| {
+                violation(
+                    ViolationKind::ForeignKey,
+                    format!("import batch {} does not exist", batch_id),
+                )
+            })?;
+        for import_error in errors {
+            let row = ImportRowError {
+                error_id: tables.next_id("import_errors"),
+                batch_id,
+                dataset: dataset.clone(),
+                row_number: import_error.row_number,
+                record: import_error.record.clone(),
+                reason: import_error.reason.clone(),
+                created_at: now(),
+            };
+            tables.import_errors.push(row);
+        }
+        Ok(())
+    }
+
+    async fn find_errors(
+        &self,
+        batch_ids: &[i64],
+        pagination: &PaginationParams,
+    ) -> SqlxResult<(Vec<ImportRowError>, i64)> {
+        Ok(page_counted(self.errors(batch_ids), pagination))
+    }
+
+    fn stream_errors<'a>(
+        &'a self,
+        batch_ids: &'a [i64],
+    ) -> BoxStream<'a, SqlxResult<ImportRowError>> {
+        stream(self.errors(batch_ids))
+    }
+
+    async fn existing_keys(
+        &self,
+        table: &str,
+        _key_column: &str,
+        keys: &[String],
+    ) -> SqlxResult<Vec<String>> {
+        let tables = self.store.tables();
+        let existing = tables.keys(table);
+        Ok(keys
+            .iter()
+            .filter(|key| existing.contains(key.as_str()))
+            .cloned()
+            .collect())
+    }
+
+    async fn rollback(
+        &self,
+        batch_id: i64,
+        table: &str,
+        _key_column: &str,
+    ) -> SqlxResult<Option<(ImportBatch, u64)>> {
+        let mut tables = self.store.tables();
+        let finished = tables
+            .import_batches
+            .iter()
+            .any(|b| b.batch_id == batch_id && matches!(b.status.as_str(), "completed" | "failed"));
+        if !finished {
+            return Ok(None);
+        }
+
+        let rows: Vec<String> = tables
+            .import_rows
+            .iter()
+            .filter(|(batch, _)| *batch == batch_id)
+            .map(|(_, id)| id.clone())
+            .collect();
+        let ids: HashSet<&str> = rows.iter().map(String::as_str).collect();
+        if tables.is_referenced(table, &ids) {
+            return Err(violation(
+                ViolationKind::ForeignKey,
+                format!("rows of import batch {} are still referenced", batch_id),
+            ));
+        }
+
+        let deleted = tables.delete_rows(table, &ids);
+        let batch = tables
+            .import_batches
+            .iter_mut()
+            .find(|b| b.batch_id == batch_id)
+            .ok_or(sqlx::Error::RowNotFound)?;
+        batch.status = ImportBatchStatus::RolledBack.as_str().to_string();
+        batch.rolled_back_at = Some(now());
+        Ok(Some((batch.clone(), deleted)))
+    }
+}
+
+#[derive(Clone)]
+pub struct InMemoryCouponRepository {
+    store: MemoryStore,
+}
+
+impl InMemoryCouponRepository {
+    pub fn new(store: MemoryStore) -> Self {
+        Self { store }
+    }
+}
+
+#[async_trait]
+impl CouponRepository for InMemoryCouponRepository {
+    async fn create(&self, dto: CreateCouponDto) -> SqlxResult<Coupon> {
+        let mut tables = self.store.tables();
+        if tables.coupons.iter().any(|c| c.code == dto.code) {
+            return Err(violation(
+                ViolationKind::Unique,
+                format!("coupon {} already exists", dto.code),
+            ));
+        }
+        let coupon = Coupon {
+            code: dto.code,
+            discount_type: dto.discount_type.as_str().to_string(),
+            value: dto.value,
+            min_order_value: dto.min_order_value,
+            expires_at: dto.expires_at,
+            max_uses: dto.max_uses,
+            times_used: 0,
+            created_at: now(),
+        };
+        tables.coupons.push(coupon.clone());
+        Ok(coupon)
+    }
+
+    async fn find_by_code(&self, code: &str) -> SqlxResult<Option<Coupon>> {
+        let tables = self.store.tables();
+        Ok(tables.coupons.iter().find(|c| c.code == code).cloned())
+    }
+
+    async fn find_by_order(&self, order_id: &OrderId) -> SqlxResult<Option<Coupon>> {
+        let tables = self.store.tables();
+        Ok(tables
+            .order_coupons
+            .get(order_id)
+            .and_then(|code| tables.coupons.iter().find(|c| c.code == *code))
+            .cloned())
+    }
+
+    async fn redeem(&self, code: &str, order_id: &OrderId) -> SqlxResult<bool> {
+        let mut tables = self.store.tables();
+        if tables.order(order_id).is_none() {
+            return Err(violation(
+                ViolationKind::ForeignKey,
+                format!("order {} does not exist", order_id),
+            ));
+        }
+        if tables.order_coupons.contains_key(order_id) {
+            return Err(violation(
+                ViolationKind::Unique,
+                format!("order {} already has a coupon", order_id),
+            ));
+        }
+        let now = now();
+        let Some(coupon) = tables.coupons.iter_mut().find(|c| {
+            c.code == code
+                && c.max_uses.is_none_or(|max| c.times_used < max)
+                && c.expires_at.is_none_or(|expires| expires > now)
+        }) else {
+            return Ok(false);
+        };
+        coupon.times_used += 1;
+        tables
+            .order_coupons
+            .insert(order_id.clone(), code.to_string());
+        Ok(true)
+    }
+}
+
+#[derive(Clone)]
+pub struct InMemoryPaymentTransactionRepository {
+    store: MemoryStore,
+}
+
+impl InMemoryPaymentTransactionRepository {
+    pub fn new(store: MemoryStore) -> Self {
+        Self { store }
+    }
+}
+
+#[async_trait]
+impl PaymentTransactionRepository for InMemoryPaymentTransactionRepository {
+    async fn create(
+        &self,
+        transaction: NewPaymentTransaction,
+        order_status: Option<OrderStatusTransition>,
+    ) -> SqlxResult<PaymentTransaction> {
+        let mut tables = self.store.tables();
+        if tables.order(&transaction.order_id).is_none() {
+            return Err(violation(
+                ViolationKind::ForeignKey,
+                format!("order {} does not exist", transaction.order_id),
+            ));
+        }
+        if tables.payment_transactions.iter().any(|t| {
+            t.provider == transaction.provider
+                && t.provider_reference == transaction.provider_reference
+        }) {
+            return Err(violation(
+                ViolationKind::Unique,
+                format!(
+                    "payment {} of {} already exists",
+                    transaction.provider_reference, transaction.provider
+                ),
+            ));
+        }
+
+        let created = PaymentTransaction {
+            transaction_id: tables.next_id("payment_transactions"),
+            order_id: transaction.order_id,
+            provider: transaction.provider,
+            provider_reference: transaction.provider_reference,
+            payment_type: transaction.payment_type,
+            payment_installments: transaction.payment_installments,
+            amount: transaction.amount.into(),
+            status: transaction.status,
+            created_at: now(),
+            updated_at: now(),
+            currency: Money::CURRENCY,
+        };
+        tables.payment_transactions.push(created.clone());
+        if let Some(order_status) = order_status {
+            tables.apply_order_transition(&created.order_id, order_status);
+        }
+        Ok(created)
+    }
+
+    async fn find_by_id(&self, transaction_id: i64) -> SqlxResult<Option<PaymentTransaction>> {
+        let tables = self.store.tables();
+        Ok(tables
+            .payment_transactions
+            .iter()
+            .find(|t| t.transaction_id == transaction_id)
+            .cloned())
+    }
+
+    async fn find_by_reference(
+        &self,
+        provider: &str,
+        reference: &str,
+    ) -> SqlxResult<Option<PaymentTransaction>> {
+        let tables = self.store.tables();
+        Ok(tables
+            .payment_transactions
+            .iter()
+            .find(|t| t.provider == provider && t.provider_reference == reference)
+            .cloned())
+    }
+
+    async fn find_by_order(&self, order_id: &OrderId) -> SqlxResult<Vec<PaymentTransaction>> {
+        let tables = self.store.tables();
+        Ok(tables
+            .payment_transactions
+            .iter()
+            .filter(|t| t.order_id == *order_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn update_status(
+        &self,
+        transaction_id: i64,
+        from: PaymentStatus,
+        to: PaymentStatus,
+        order_status: Option<OrderStatusTransition>,
+    ) -> SqlxResult<Option<PaymentTransaction>> {
+        let mut tables = self.store.tables();
+        let Some(transaction) = tables
+            .payment_transactions
+            .iter_mut()
+            .find(|t| t.transaction_id == transaction_id && t.status == from)
+        else {
+            return Ok(None);
+        };
+        transaction.status = to;
+        transaction.updated_at = now();
+        let updated = transaction.clone();
+        if let Some(order_status) = order_status {
+            tables.apply_order_transition(&updated.order_id, order_status);
+        }
+        Ok(Some(updated))
+    }
+}
+
+#[derive(Clone)]
+pub struct InMemoryGeolocationRepository {
+    store: MemoryStore,
+}
+
+impl InMemoryGeolocationRepository {
+    pub fn new(store: MemoryStore) -> Self {
+        Self { store }
+    }
+}
+
+#[async_trait]
+impl GeolocationRepository for InMemoryGeolocationRepository {
+    async fn find_by_prefixes(&self, prefixes: &[String]) -> SqlxResult<Vec<ZipLocation>> {
+        let tables = self.store.tables();
+        Ok(prefixes
+            .iter()
+            .filter_map(|prefix| tables.geolocation.get(prefix).cloned())
+            .collect())
+    }
+
+    async fn find_all(&self, limit: i64) -> SqlxResult<Vec<ZipLocation>> {
+        let tables = self.store.tables();
+        let mut locations: Vec<ZipLocation> = tables.geolocation.values().cloned().collect();
+        locations.sort_by(|a, b| {
+            a.geolocation_zip_code_prefix
+                .cmp(&b.geolocation_zip_code_prefix)
+        });
+        locations.truncate(limit as usize);
+        Ok(locations)
+    }
+
+    async fn upsert_many(&self, locations: &[ZipLocation]) -> SqlxResult<u64> {
+        let mut tables = self.store.tables();
+        for location in locations {
+            tables.geolocation.insert(
+                location.geolocation_zip_code_prefix.clone(),
+                location.clone(),
+            );
+        }
+        Ok(locations.len() as u64)
+    }
+}
+
+#[derive(Clone)]
+pub struct InMemoryStatsRepository {
+    store: MemoryStore,
+}
+
+impl InMemoryStatsRepository {
+    pub fn new(store: MemoryStore) -> Self {
+        Self { store }
+    }
+}
+
+#[async_trait]
+impl StatsRepository for InMemoryStatsRepository {
+    /// Counted from the tables on each call rather than kept by triggers.
+    async fn today(&self) -> SqlxResult<TodayStats> {
+        let tables = self.store.tables();
+        let today = chrono::Utc::now().date_naive();
+
+        let todays_orders: HashSet<&OrderId> = tables
+            .orders
+            .iter()
+            .filter(|o| o.order.order_purchase_timestamp.date() == today)
+            .map(|o| &o.order.order_id)
+            .collect();
+        let revenue = tables
+            .order_items
+            .iter()
+            .filter(|i| todays_orders.contains(&i.order_id))
+            .map(|i| i.price.clone() + &i.freight_value)
+            .sum();
+
+        Ok(TodayStats {
+            stat_date: today,
+            orders_count: todays_orders.len() as i64,
+            revenue,
+            active_imports: tables
+                .import_batches
+                .iter()
+                .filter(|b| b.status == ImportBatchStatus::Running.as_str())
+                .count() as i64,
+            currency: Money::CURRENCY,
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct InMemoryWebhookRepository {
+    store: MemoryStore,
+}
+
+impl InMemoryWebhookRepository {
+    pub fn new(store: MemoryStore) -> Self {
+        Self { store }
+    }
+}
+
+#[async_trait]
+impl WebhookRepository for InMemoryWebhookRepository {
+    async fn create_subscription(
+        &self,
+        url: &str,
+        events: &[String],
+        customer_states: Option<&[String]>,
+        payload_template: WebhookPayloadTemplate,
+        secret: &str,
+        created_by: &str,
+    ) -> SqlxResult<WebhookSubscription> {
+        let mut tables = self.store.tables();
+        let subscription = WebhookSubscription {
+            subscription_id: tables.next_id("webhook_subscriptions"),
+            url: url.to_string(),
+            events: events.to_vec(),
+            customer_states: customer_states.map(<[String]>::to_vec),
+            payload_template,
+            secret: secret.to_string(),
+            created_by: created_by.to_string(),
+            created_at: now(),
+        };
+        tables.webhook_subscriptions.push(subscription.clone());
+        Ok(subscription)
+    }
+
+    async fn find_subscriptions(
+        &self,
+        pagination: &PaginationParams,
+    ) -> SqlxResult<(Vec<WebhookSubscription>, i64)> {
+        let subscriptions = self.store.tables().webhook_subscriptions.clone();
+        Ok(page_counted(subscriptions, pagination))
+    }
+
+    async fn find_subscription(
+        &self,
+        subscription_id: i64,
+    ) -> SqlxResult<Option<WebhookSubscription>> {
+        Ok(self
+            .store
+            .tables()
+            .webhook_subscriptions
+            .iter()
+            .find(|s| s.subscription_id == subscription_id)
+            .cloned())
+    }
+
+    async fn delete_subscription(&self, subscription_id: i64) -> SqlxResult<Option<NaiveDateTime>> {
+        let mut tables = self.store.tables();
+        let before = tables.webhook_subscriptions.len();
+        tables
+            .webhook_subscriptions
+            .retain(|s| s.subscription_id != subscription_id);
+        if tables.webhook_subscriptions.len() == before {
+            return Ok(None);
+        }
+        tables
+            .webhook_deliveries
+            .retain(|d| d.subscription_id != subscription_id);
+        Ok(Some(now()))
+    }
+
+    async fn find_deliveries(
+        &self,
+        subscription_id: i64,
+        status: Option<&str>,
+        pagination: &PaginationParams,
+    ) -> SqlxResult<(Vec<WebhookDelivery>, i64)> {
+        let mut deliveries: Vec<WebhookDelivery> = self
+            .store
+            .tables()
+            .webhook_deliveries
+            .iter()
+            .filter(|d| d.subscription_id == subscription_id)
+            .filter(|d| status.is_none_or(|status| d.status == status))
+            .cloned()
+            .collect();
+        deliveries.sort_by(|a, b| {
+            b.created_at
+                .cmp(&a.created_at)
+                .then_with(|| b.delivery_id.cmp(&a.delivery_id))
+        });
+        Ok(page_counted(deliveries, pagination))
+    }
+
+    async fn find_delivery(
+        &self,
+        subscription_id: i64,
+        delivery_id: i64,
+    ) -> SqlxResult<Option<WebhookDelivery>> {
+        Ok(self
+            .store
+            .tables()
+            .webhook_deliveries
+            .iter()
+            .find(|d| d.subscription_id == subscription_id && d.delivery_id == delivery_id)
+            .cloned())
+    }
+
+    async fn claim_due_deliveries(
+        &self,
+        limit: i64,
+        lease_seconds: i64,
+    ) -> SqlxResult<Vec<PendingWebhookDelivery>> {
+        let mut tables = self.store.tables();
+        let now = now();
+        let mut due: Vec<usize> = (0..tables.webhook_deliveries.len())
+            .filter(|&i| {
+                let d = &tables.webhook_deliveries[i];
+                d.status == WebhookDeliveryStatus::Pending.as_str()
+                    && d.next_attempt_at.is_some_and(|at| at <= now)
+            })
+            .collect();
+        due.sort_by_key(|&i| {
+            let d = &tables.webhook_deliveries[i];
+            (d.next_attempt_at, d.delivery_id)
+        });
+        due.truncate(limit.max(0) as usize);
+
+        let mut claimed = Vec::with_capacity(due.len());
+        for i in due {
+            let delivery = &mut tables.webhook_deliveries[i];
+            delivery.attempts += 1;
+            delivery.next_attempt_at = Some(now + chrono::Duration::seconds(lease_seconds));
+            let delivery = delivery.clone();
+            let Some(subscription) = tables
+                .webhook_subscriptions
+                .iter()
+                .find(|s| s.subscription_id == delivery.subscription_id)
+            else {
+                continue;
+            };
+            let customer_state = delivery
+                .payload
+                .get("order_id")
+                .and_then(|order_id| order_id.as_str())
+                .and_then(|order_id| tables.order(&OrderId::from(order_id.to_string())))
+                .and_then(|stored| tables.customer(&stored.order.customer_id))
+                .map(|customer| customer.customer_state.clone());
+            claimed.push(PendingWebhookDelivery {
+                delivery_id: delivery.delivery_id,
+                event: delivery.event,
+                payload: delivery.payload,
+                attempts: delivery.attempts,
+                created_at: delivery.created_at,
+                url: subscription.url.clone(),
+                secret: subscription.secret.clone(),
+                customer_states: subscription.customer_states.clone(),
+                payload_template: subscription.payload_template,
+                customer_state,
+            });
+        }
+        Ok(claimed)
+    }
+
+    async fn discard_delivery(&self, delivery_id: i64) -> SqlxResult<()> {
+        self.store
+            .tables()
+            .webhook_deliveries
+            .retain(|d| d.delivery_id != delivery_id);
+        Ok(())
+    }
+
+    async fn mark_delivered(&self, delivery_id: i64, status_code: i32) -> SqlxResult<()> {
+        let mut tables = self.store.tables();
+        if let Some(delivery) = tables
+            .webhook_deliveries
+            .iter_mut()
+            .find(|d| d.delivery_id == delivery_id)
+        {
+            delivery.status = WebhookDeliveryStatus::Succeeded.as_str().to_string();
+            delivery.last_status_code = Some(status_code);
+            delivery.last_error = None;
+            delivery.next_attempt_at = None;
+            delivery.delivered_at = Some(now());
+        }
+        Ok(())
+    }
+
+    async fn mark_attempt_failed(
+        &self,
+        delivery_id: i64,
+        status_code: Option<i32>,
+        error: &str,
+        retry_in_seconds: Option<i64>,
+    ) -> SqlxResult<()> {
+        let mut tables = self.store.tables();
+        if let Some(delivery) = tables
+            .webhook_deliveries
+            .iter_mut()
+            .find(|d| d.delivery_id == delivery_id)
+        {
+            let status = match retry_in_seconds {
+                Some(_) => WebhookDeliveryStatus::Pending,
+                None => WebhookDeliveryStatus::Failed,
+            };
+            delivery.status = status.as_str().to_string();
+            delivery.last_status_code = status_code;
+            delivery.last_error = Some(error.to_string());
+            delivery.next_attempt_at =
+                retry_in_seconds.map(|seconds| now() + chrono::Duration::seconds(seconds));
+        }
+        Ok(())
+    }
+
+    async fn requeue_failed_deliveries(&self, max_age_hours: i64) -> SqlxResult<u64> {
+        let since = now() - chrono::Duration::hours(max_age_hours);
+        let mut tables = self.store.tables();
+        let mut requeued = 0;
+        for delivery in tables
+            .webhook_deliveries
+            .iter_mut()
+            .filter(|d| d.status == WebhookDeliveryStatus::Failed.as_str() && d.created_at >= since)
+        {
+            delivery.status = WebhookDeliveryStatus::Pending.as_str().to_string();
+            delivery.next_attempt_at = Some(now());
+            requeued += 1;
+        }
+        Ok(requeued)
+    }
+}
+
+pub struct InMemoryOutboxRepository {
+    store: MemoryStore,
+}
+
+impl InMemoryOutboxRepository {
+    pub fn new(store: MemoryStore) -> Self {
+        Self { store }
+    }
+}
+
+#[async_trait]
+impl OutboxRepository for InMemoryOutboxRepository {
+    async fn claim_due_events(
+        &self,
+        limit: i64,
+        lease_seconds: i64,
+    ) -> SqlxResult<Vec<OutboxEvent>> {
+        let mut tables = self.store.tables();
+        let now = now();
+        let lease_until = now + chrono::Duration::seconds(lease_seconds);
+        // Events are stored in id order.
+        let claimed = tables
+            .outbox
+            .iter_mut()
+            .filter(|e| e.published_at.is_none())
+            .take_while(|e| e.next_attempt_at.is_none_or(|at| at <= now))
+            .take(limit.max(0) as usize)
+            .map(|e| {
+                e.next_attempt_at = Some(lease_until);
+                e.event.clone()
+            })
+            .collect();
+        Ok(claimed)
+    }
+
+    async fn mark_published(&self, event_id: i64) -> SqlxResult<()> {
+        let mut tables = self.store.tables();
+        let Some(stored) = tables
+            .outbox
+            .iter_mut()
+            .find(|e| e.event.event_id == event_id && e.published_at.is_none())
+        else {
+            return Ok(());
+        };
+        stored.published_at = Some(now());
+        stored.next_attempt_at = None;
+        stored.last_error = None;
+        let (event_type, payload) = (
+            stored.event.event_type.clone(),
+            stored.event.payload.clone(),
+        );
+        tables.queue_webhooks(&event_type, &payload);
+        tables.queue_notification(event_id, &event_type, &payload);
+        Ok(())
+    }
+
+    async fn mark_publish_failed(
+        &self,
+        event_id: i64,
+        error: &str,
+        retry_in_seconds: i64,
+    ) -> SqlxResult<()> {
+        let mut tables = self.store.tables();
+        if let Some(stored) = tables
+            .outbox
+            .iter_mut()
+            .find(|e| e.event.event_id == event_id)
+        {
+            stored.event.attempts += 1;
+            stored.last_error = Some(error.to_string());
+            stored.next_attempt_at = Some(now() + chrono::Duration::seconds(retry_in_seconds));
+        }
+        Ok(())
+    }
+
+    async fn release_events(&self, event_ids: &[i64]) -> SqlxResult<()> {
+        let mut tables = self.store.tables();
+        for stored in tables.outbox.iter_mut() {
+            if stored.published_at.is_none() && event_ids.contains(&stored.event.event_id) {
+                stored.next_attempt_at = Some(now());
+            }
+        }
+        Ok(())
+    }
+
+    async fn backlog(&self) -> SqlxResult<OutboxBacklog> {
+        let tables = self.store.tables();
+        let pending: Vec<&StoredOutboxEvent> = tables
+            .outbox
+            .iter()
+            .filter(|e| e.published_at.is_none())
+            .collect();
+        Ok(OutboxBacklog {
+            pending: pending.len() as i64,
+            oldest_age_seconds: pending
+                .first()
+                .map(|e| (now() - e.event.created_at).num_milliseconds() as f64 / 1000.0),
+        })
+    }
+
+    async fn delete_published_before(&self, days: i64) -> SqlxResult<u64> {
+        let mut tables = self.store.tables();
+        let cutoff = now() - chrono::Duration::days(days);
+        let before = tables.outbox.len();
+        tables
+            .outbox
+            .retain(|e| e.published_at.is_none_or(|at| at >= cutoff));
+        Ok((before - tables.outbox.len()) as u64)
+    }
+}
+
+pub struct InMemoryNotificationRepository {
+    store: MemoryStore,
+}
+
+impl InMemoryNotificationRepository {
+    pub fn new(store: MemoryStore) -> Self {
+        Self { store }
+    }
+}
+
+#[async_trait]
+impl NotificationRepository for InMemoryNotificationRepository {
+    async fn find_notifications(
+        &self,
+        status: Option<&str>,
+        kind: Option<&str>,
+        pagination: &PaginationParams,
+    ) -> SqlxResult<(Vec<Notification>, i64)> {
+        let mut notifications: Vec<Notification> = self
+            .store
+            .tables()
+            .notifications
+            .iter()
+            .filter(|n| status.is_none_or(|status| n.status == status))
+            .filter(|n| kind.is_none_or(|kind| n.kind == kind))
+            .cloned()
+            .collect();
+        notifications.sort_by(|a, b| {
+            b.created_at
+                .cmp(&a.created_at)
+                .then_with(|| b.notification_id.cmp(&a.notification_id))
+        });
+        Ok(page_counted(notifications, pagination))
+    }
+
+    async fn find_notification(&self, notification_id: i64) -> SqlxResult<Option<Notification>> {
+        Ok(self
+            .store
+            .tables()
+            .notifications
+            .iter()
+            .find(|n| n.notification_id == notification_id)
+            .cloned())
+    }
+
+    async fn claim_due_notifications(
+        &self,
+        limit: i64,
+        lease_seconds: i64,
+    ) -> SqlxResult<Vec<PendingNotification>> {
+        let mut tables = self.store.tables();
+        let now = now();
+        let mut due: Vec<&mut Notification> = tables
+            .notifications
+            .iter_mut()
+            .filter(|n| {
+                n.status == NotificationStatus::Pending.as_str()
+                    && n.next_attempt_at.is_some_and(|at| at <= now)
+            })
+            .collect();
+        due.sort_by_key(|n| (n.next_attempt_at, n.notification_id));
+        due.truncate(limit.max(0) as usize);
+
+        Ok(due
+            .into_iter()
+            .map(|notification| {
+                notification.attempts += 1;
+                notification.next_attempt_at = Some(now + chrono::Duration::seconds(lease_seconds));
+                PendingNotification {
+                    notification_id: notification.notification_id,
+                    kind: notification.kind.clone(),
+                    payload: notification.payload.clone(),
+                    attempts: notification.attempts,
+                }
+            })
+            .collect())
+    }
+
+    async fn mark_sent(
+        &self,
+        notification_id: i64,
+        attempt: &NotificationAttempt<'_>,
+    ) -> SqlxResult<()> {
+        let mut tables = self.store.tables();
+        if let Some(notification) = tables
+            .notifications
+            .iter_mut()
+            .find(|n| n.notification_id == notification_id)
+        {
+            record_attempt(notification, attempt);
+            notification.status = NotificationStatus::Sent.as_str().to_string();
+            notification.last_error = None;
+            notification.next_attempt_at = None;
+            notification.sent_at = Some(now());
+        }
+        Ok(())
+    }
+
+    async fn mark_attempt_failed(
+        &self,
+        notification_id: i64,
+        attempt: &NotificationAttempt<'_>,
+        error: &str,
+        retry_in_seconds: Option<i64>,
+    ) -> SqlxResult<()> {
+        let mut tables = self.store.tables();
+        if let Some(notification) = tables
+            .notifications
+            .iter_mut()
+            .find(|n| n.notification_id == notification_id)
+        {
+            let status = match retry_in_seconds {
+                Some(_) => NotificationStatus::Pending,
+                None => NotificationStatus::Failed,
+            };
+            record_attempt(notification, attempt);
+            notification.status = status.as_str().to_string();
+            notification.last_error = Some(error.to_string());
+            notification.next_attempt_at =
+                retry_in_seconds.map(|seconds| now() + chrono::Duration::seconds(seconds));
+        }
+        Ok(())
+    }
+}
+
+fn record_attempt(notification: &mut Notification, attempt: &NotificationAttempt<'_>) {
+    notification.channel = Some(attempt.channel.to_string());
+    notification.recipient = attempt.recipient.map(str::to_string);
+    notification.subject = Some(attempt.subject.to_string());
+}
+
+/// There are no reviews in memory; customers are judged by their orders and address changes.
+pub struct InMemoryRetentionRepository {
+    store: MemoryStore,
+}
+
+impl InMemoryRetentionRepository {
+    pub fn new(store: MemoryStore) -> Self {
+        Self { store }
+    }
+
+    fn inactive_customers(tables: &Tables, before: NaiveDateTime) -> Vec<CustomerId> {
+        let mut ids: Vec<CustomerId> = tables
+            .customers
+            .iter()
+            .filter(|c| c.customer_city != "anonymized")
+            .filter(|c| {
+                let ordered = tables
+                    .orders
+                    .iter()
+                    .filter(|o| o.order.customer_id == c.customer_id)
+                    .map(|o| o.order.order_purchase_timestamp);
+                let moved = tables
+                    .location_history
+                    .iter()
+                    .filter(|(id, _)| *id == c.customer_id)
+                    .filter_map(|(_, version)| version.valid_from);
+                ordered.chain(moved).max().is_some_and(|at| at < before)
+            })
+            .map(|c| c.customer_id.clone())
+            .collect();
+        ids.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+        ids
+    }
+}
+
+#[async_trait]
+impl RetentionRepository for InMemoryRetentionRepository {
+    async fn count_reviews_created_before(&self, _before: NaiveDateTime) -> SqlxResult<i64> {
+        Ok(0)
+    }
+
+    async fn delete_reviews_created_before(
+        &self,
+        _before: NaiveDateTime,
+        _limit: i64,
+    ) -> SqlxResult<u64> {
+        Ok(0)
+    }
+
+    async fn count_inactive_customers(&self, before: NaiveDateTime) -> SqlxResult<i64> {
+        let tables = self.store.tables();
+        Ok(Self::inactive_customers(&tables, before).len() as i64)
+    }
+
+    async fn find_inactive_customers(
+        &self,
+        before: NaiveDateTime,
+        limit: i64,
+    ) -> SqlxResult<Vec<CustomerId>> {
+        let tables = self.store.tables();
+        let mut ids = Self::inactive_customers(&tables, before);
+        ids.truncate(limit.max(0) as usize);
+        Ok(ids)
+    }
+}
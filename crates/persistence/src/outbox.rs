@@ -0,0 +1,60 @@
+use async_trait::async_trait;
+use redis::AsyncCommands;
+use redis::aio::ConnectionManager;
+use redis::streams::StreamMaxlen;
+
+use domain::events::EventPublisher;
+use domain::models::OutboxEvent;
+
+use crate::cache::{COMMAND_TIMEOUT, bounded, connection_manager};
+
+/// [`EventPublisher`] appending each event to a Redis stream, trimmed to roughly `max_len`
+/// entries. An event can be appended twice when the relay stops right after publishing it,
+/// so consumers deduplicate on `event_id`.
+#[derive(Clone)]
+pub struct RedisStreamPublisher {
+    connection: ConnectionManager,
+    stream: String,
+    max_len: usize,
+}
+
+impl RedisStreamPublisher {
+    pub async fn connect(url: &str, stream: String, max_len: usize) -> redis::RedisResult<Self> {
+        let connection = connection_manager(url).await?;
+        Ok(Self {
+            connection,
+            stream,
+            max_len,
+        })
+    }
+}
+
+#[async_trait]
+impl EventPublisher for RedisStreamPublisher {
+    async fn publish(&self, event: &OutboxEvent) -> Result<(), String> {
+        let fields = [
+            ("event_id", event.event_id.to_string()),
+            ("tenant_id", event.tenant_id.to_string()),
+            ("event_type", event.event_type.clone()),
+            ("aggregate_type", event.aggregate_type.clone()),
+            ("aggregate_id", event.aggregate_id.clone()),
+            (
+                "created_at",
+                event.created_at.format("%Y-%m-%dT%H:%M:%S%.f").to_string(),
+            ),
+            ("payload", event.payload.to_string()),
+        ];
+        let mut connection = self.connection.clone();
+        bounded(
+            COMMAND_TIMEOUT,
+            connection.xadd_maxlen::<_, _, _, _, String>(
+                &self.stream,
+                StreamMaxlen::Approx(self.max_len),
+                "*",
+                &fields,
+            ),
+        )
+        .await
+        .map(|_| ())
+    }
+}
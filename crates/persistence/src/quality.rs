@@ -0,0 +1,146 @@
+//! SQL for the data quality checks and configured rules, shared by the PostgreSQL and SQLite
+//! repositories. The check conditions mirror `ProductIssue::of` and `OrderIssue::of`, which the
+//! in-memory store uses. Every query covers one tenant's rows.
+
+use domain::models::{DataQualityReport, DataQualityRule, IssueCount, OrderIssue, ProductIssue};
+use domain::tenancy::TenantId;
+
+/// Tables of reference data shared by every tenant, which have no `tenant_id` column.
+const SHARED_TABLES: &[&str] = &["geolocation", "city_aliases", "seller_badge_thresholds"];
+
+/// Condition on `column` holding `tenant`. Tenant ids are limited to lowercase letters, digits
+/// and hyphens, so they can be put in SQL as they are.
+fn tenant_condition(column: &str, tenant: &TenantId) -> String {
+    format!("{} = '{}'", column, tenant)
+}
+
+/// Condition on a `products` row having `issue`.
+fn product_condition(issue: ProductIssue) -> &'static str {
+    match issue {
+        ProductIssue::MissingCategory => "TRIM(product_category_name) = ''",
+        ProductIssue::MissingWeight => "product_weight_g = 0",
+        ProductIssue::MissingDimensions => {
+            "0 IN (product_length_cm, product_height_cm, product_width_cm)"
+        }
+        ProductIssue::NegativeValue => {
+            r#"product_name_lenght < 0 OR product_description_lenght < 0
+               OR product_photos_qty < 0 OR product_weight_g < 0 OR product_length_cm < 0
+               OR product_height_cm < 0 OR product_width_cm < 0"#
+        }
+    }
+}
+
+/// Condition on an `orders o` row having `issue`.
+fn order_condition(issue: OrderIssue) -> &'static str {
+    match issue {
+        OrderIssue::MissingItems => {
+            r#"NOT EXISTS (SELECT 1 FROM order_items i WHERE i.order_id = o.order_id)
+               AND o.order_status NOT IN ('canceled', 'unavailable')"#
+        }
+        OrderIssue::MissingDeliveryDate => {
+            "o.order_status = 'delivered' AND o.order_delivered_customer_date IS NULL"
+        }
+        OrderIssue::ApprovedBeforePurchase => "o.order_approved_at < o.order_purchase_timestamp",
+        OrderIssue::ShippedBeforeApproval => "o.order_delivered_carrier_date < o.order_approved_at",
+        OrderIssue::DeliveredBeforeShipped => {
+            "o.order_delivered_customer_date < o.order_delivered_carrier_date"
+        }
+        OrderIssue::EstimatedBeforePurchase => {
+            "o.order_estimated_delivery_date < o.order_purchase_timestamp"
+        }
+    }
+}
+
+/// `(a) OR (b) ...`: any of the conditions.
+fn any_of(conditions: &[&str]) -> String {
+    conditions
+        .iter()
+        .map(|condition| format!("({})", condition))
+        .collect::<Vec<_>>()
+        .join(" OR ")
+}
+
+/// `issue`'s condition on `tenant`'s `products`, or any issue's when `None`.
+pub(crate) fn product_filter(issue: Option<ProductIssue>, tenant: &TenantId) -> String {
+    let issues = match issue {
+        Some(issue) => product_condition(issue).to_string(),
+        None => any_of(&ProductIssue::ALL.map(product_condition)),
+    };
+    format!("{} AND ({})", tenant_condition("tenant_id", tenant), issues)
+}
+
+/// `issue`'s condition on `tenant`'s `orders o`, or any issue's when `None`.
+pub(crate) fn order_filter(issue: Option<OrderIssue>, tenant: &TenantId) -> String {
+    let issues = match issue {
+        Some(issue) => order_condition(issue).to_string(),
+        None => any_of(&OrderIssue::ALL.map(order_condition)),
+    };
+    format!(
+        "{} AND ({})",
+        tenant_condition("o.tenant_id", tenant),
+        issues
+    )
+}
+
+/// Select list counting every row, the rows with any of `conditions` and then the rows with
+/// each, in that order.
+fn report_columns(conditions: &[&str]) -> String {
+    let mut columns = vec![
+        "COUNT(*)".to_string(),
+        format!("COUNT(*) FILTER (WHERE {})", any_of(conditions)),
+    ];
+    columns.extend(
+        conditions
+            .iter()
+            .map(|condition| format!("COUNT(*) FILTER (WHERE {})", condition)),
+    );
+    columns.join(", ")
+}
+
+/// Query whose single row [`report`] reads into `tenant`'s product report.
+pub(crate) fn product_report_query(tenant: &TenantId) -> String {
+    format!(
+        "SELECT {} FROM products WHERE {}",
+        report_columns(&ProductIssue::ALL.map(product_condition)),
+        tenant_condition("tenant_id", tenant)
+    )
+}
+
+/// Query whose single row [`report`] reads into `tenant`'s order report.
+pub(crate) fn order_report_query(tenant: &TenantId) -> String {
+    format!(
+        "SELECT {} FROM orders o WHERE {}",
+        report_columns(&OrderIssue::ALL.map(order_condition)),
+        tenant_condition("o.tenant_id", tenant)
+    )
+}
+
+/// Query counting `tenant`'s rows of a rule's table and the rows breaking it; rules on shared
+/// tables count every row. The rule must have passed validation, which keeps its names plain
+/// identifiers.
+pub(crate) fn rule_query(rule: &DataQualityRule, tenant: &TenantId) -> String {
+    let query = format!(
+        "SELECT COUNT(*), COUNT(*) FILTER (WHERE {}) FROM {}",
+        rule.condition(),
+        rule.table
+    );
+    if SHARED_TABLES.contains(&rule.table.as_str()) {
+        query
+    } else {
+        format!("{} WHERE {}", query, tenant_condition("tenant_id", tenant))
+    }
+}
+
+/// Builds a report from the counts a report query selects, `issues` being the `ALL` list the
+/// query was built from.
+pub(crate) fn report<I: Copy>(counts: &[i64], issues: &[I]) -> DataQualityReport<I> {
+    DataQualityReport {
+        checked: counts[0],
+        flagged: counts[1],
+        issues: issues
+            .iter()
+            .zip(&counts[2..])
+            .map(|(&issue, &count)| IssueCount { issue, count })
+            .collect(),
+    }
+}
@@ -0,0 +1,219 @@
+//! Routing of repository reads to a Postgres read replica, with the primary standing in while
+//! the replica is unreachable.
+
+use std::future::Future;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use sqlx::{PgPool, Result as SqlxResult};
+use tracing::{debug, info, warn};
+
+use crate::retry::{RetryPolicy, is_unavailable};
+
+/// Where repository reads run: the replica while it answers, the primary otherwise. Writes
+/// never go through here.
+#[derive(Clone)]
+pub struct ReadPool {
+    primary: PgPool,
+    replica: Option<Replica>,
+}
+
+#[derive(Clone)]
+struct Replica {
+    pool: PgPool,
+    /// How often an unavailable replica is probed.
+    recheck_after: Duration,
+    /// Whether the last probe or read got an answer.
+    available: Arc<AtomicBool>,
+}
+
+impl ReadPool {
+    /// Reads from `primary` only.
+    pub fn primary(primary: PgPool) -> Self {
+        Self {
+            primary,
+            replica: None,
+        }
+    }
+
+    /// Reads from `replica` once it has answered a probe. Whenever it stops answering, reads
+    /// go to `primary` and the replica is probed every `recheck_after` until it is back. Needs
+    /// a Tokio runtime.
+    pub fn with_replica(primary: PgPool, replica: PgPool, recheck_after: Duration) -> Self {
+        let replica = Replica {
+            pool: replica,
+            recheck_after,
+            available: Arc::new(AtomicBool::new(false)),
+        };
+        replica.probe_until_available();
+        Self {
+            primary,
+            replica: Some(replica),
+        }
+    }
+
+    /// Runs `query` on the replica, or on the primary when there is none or it is unavailable,
+    /// retrying transient failures under `retry`. A replica that can't be reached is marked
+    /// unavailable and the same attempt moves on to the primary, so a replica outage costs no
+    /// errors.
+    pub async fn read<'a, T, F, Fut>(&'a self, retry: &RetryPolicy, mut query: F) -> SqlxResult<T>
+    where
+        F: FnMut(&'a PgPool) -> Fut,
+        Fut: Future<Output = SqlxResult<T>>,
+    {
+        retry
+            .read(|| {
+                let replica = self
+                    .replica
+                    .as_ref()
+                    .filter(|replica| replica.available.load(Ordering::Relaxed))
+                    .map(|replica| (replica, query(&replica.pool)));
+                let primary = query(&self.primary);
+                async move {
+                    if let Some((replica, on_replica)) = replica {
+                        match on_replica.await {
+                            Err(e) if is_unavailable(&e) => replica.mark_unavailable(&e),
+                            result => return result,
+                        }
+                    }
+                    primary.await
+                }
+            })
+            .await
+    }
+}
+
+impl Replica {
+    fn mark_unavailable(&self, e: &sqlx::Error) {
+        if self.available.swap(false, Ordering::Relaxed) {
+            warn!("Read replica unavailable, reading from the primary: {}", e);
+            self.probe_until_available();
+        }
+    }
+
+    /// Pings the replica in the background until it answers, then lets reads back onto it.
+    fn probe_until_available(&self) {
+        let replica = self.clone();
+        tokio::spawn(async move {
+            loop {
+                match sqlx::query("SELECT 1").execute(&replica.pool).await {
+                    Ok(_) => {
+                        replica.available.store(true, Ordering::Relaxed);
+                        info!("Reading from the replica.");
+                        return;
+                    }
+                    Err(e) => debug!("Read replica still unavailable: {}", e),
+                }
+                tokio::time::sleep(replica.recheck_after).await;
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
+    use std::sync::Mutex;
+
+    /// A pool that never connects here; queries below only look at which host it is for.
+    fn pool(host: &str) -> PgPool {
+        PgPoolOptions::new().connect_lazy_with(PgConnectOptions::new().host(host).port(1))
+    }
+
+    /// A pool with a replica that is taken as available, skipping the startup probe.
+    fn with_available_replica() -> ReadPool {
+        ReadPool {
+            primary: pool("primary"),
+            replica: Some(Replica {
+                pool: pool("replica"),
+                recheck_after: Duration::from_secs(3600),
+                available: Arc::new(AtomicBool::new(true)),
+            }),
+        }
+    }
+
+    fn no_retries() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 1,
+            ..RetryPolicy::default()
+        }
+    }
+
+    /// Reads through `reads`, the replica answering with `on_replica`, and returns the result
+    /// with the hosts that ran the query in order.
+    async fn read(
+        reads: &ReadPool,
+        on_replica: fn() -> SqlxResult<&'static str>,
+    ) -> (SqlxResult<&'static str>, Vec<String>) {
+        let hosts = Mutex::new(Vec::new());
+        let result = reads
+            .read(&no_retries(), |pool| {
+                let host = pool.connect_options().get_host().to_string();
+                let hosts = &hosts;
+                async move {
+                    hosts.lock().unwrap().push(host.clone());
+                    if host == "replica" {
+                        on_replica()
+                    } else {
+                        Ok("primary")
+                    }
+                }
+            })
+            .await;
+        (result, hosts.into_inner().unwrap())
+    }
+
+    fn replica_available(reads: &ReadPool) -> bool {
+        reads
+            .replica
+            .as_ref()
+            .unwrap()
+            .available
+            .load(Ordering::Relaxed)
+    }
+
+    #[tokio::test]
+    async fn reads_from_the_primary_without_a_replica() {
+        let reads = ReadPool::primary(pool("primary"));
+        let (result, hosts) = read(&reads, || Ok("replica")).await;
+        assert_eq!(result.unwrap(), "primary");
+        assert_eq!(hosts, ["primary"]);
+    }
+
+    #[tokio::test]
+    async fn reads_from_an_available_replica() {
+        let reads = with_available_replica();
+        let (result, hosts) = read(&reads, || Ok("replica")).await;
+        assert_eq!(result.unwrap(), "replica");
+        assert_eq!(hosts, ["replica"]);
+    }
+
+    #[tokio::test]
+    async fn falls_back_to_the_primary_when_the_replica_is_unreachable() {
+        let reads = with_available_replica();
+
+        let (result, hosts) = read(&reads, || Err(sqlx::Error::PoolTimedOut)).await;
+        assert_eq!(result.unwrap(), "primary", "the same attempt moves on");
+        assert_eq!(hosts, ["replica", "primary"]);
+        assert!(!replica_available(&reads));
+
+        let (result, hosts) = read(&reads, || Ok("replica")).await;
+        assert_eq!(result.unwrap(), "primary");
+        assert_eq!(
+            hosts,
+            ["primary"],
+            "the replica is skipped until a probe answers"
+        );
+    }
+
+    #[tokio::test]
+    async fn returns_query_errors_from_the_replica() {
+        let reads = with_available_replica();
+        let (result, hosts) = read(&reads, || Err(sqlx::Error::RowNotFound)).await;
+        assert!(matches!(result, Err(sqlx::Error::RowNotFound)));
+        assert_eq!(hosts, ["replica"]);
+        assert!(replica_available(&reads));
+    }
+}
@@ -0,0 +1,7795 @@
+use domain::geo::GeoBounds;
+use domain::ids::{CustomerId, OrderId, ProductId, SellerId};
+use domain::models::{
+    AddItemToOrderDto, AppliedMigration, ArchivedOrders, AuditEntry, AuditFilter, BatchResume,
+    BrazilState, Category, Coupon, CreateCategoryDto, CreateCouponDto, CreateCustomerAddressDto,
+    CreateCustomerDto, CreateOrderDto, CreateProductDto, CreateSellerDto, CreateStockLocationDto,
+    CreateSupportCaseDto, CreateSupportMessageDto, Customer, CustomerAddress, CustomerDependents,
+    CustomerFilter, CustomerLocationVersion, CustomerMerge, DataQualityReport, DataQualityRule,
+    DataQualityRuleResult, DuplicatedCustomer, DuplicationBucket, FilterValue, FlaggedOrder,
+    ImportBatch, ImportBatchStatus, ImportRowError, LateOrder, LoadJobBatch, LocatedSeller,
+    LocationStock, NewAuditEntry, NewImportRowError, NewOrderAmendment, NewPaymentTransaction,
+    NewProductImage, NewRefund, Notification, NotificationAttempt, Order, OrderAmendment,
+    OrderFilter, OrderFinancials, OrderIssue, OrderItem, OrderItemOrigin, OrderProduct,
+    OrderStatus, OrderStatusChange, OutboxBacklog, OutboxEvent, PaginationParams, Payment,
+    PaymentAnalyticsFilter, PaymentMethodStats, PaymentMismatch, PaymentStatus, PaymentTransaction,
+    PaymentType, PendingNotification, PendingWebhookDelivery, PoolStats, Product, ProductFilter,
+    ProductImage, ProductIssue, ProductLocationStock, RecommendedProduct, ReconciliationFilter,
+    Refund, Review, ReviewText, RuleSeverity, SampleStratum, Seller, SellerBadgeThreshold,
+    SellerFilter, SellerMetrics, Sentiment, SentimentReview, SimilarProduct, SparseRow,
+    StockAllocation, StockLocation, StoredLoadJob, SupportCase, SupportCaseFilter,
+    SupportCaseVolume, SupportMessage, TableStats, TodayStats, Total, TotalMode, UpdateCategoryDto,
+    UpdateCustomerAddressDto, UpdateCustomerDto, UpdateProductDto, UpdateSupportCaseDto,
+    WebhookDelivery, WebhookPayloadTemplate, WebhookSubscription, ZipLocation,
+};
+use domain::money::{Currency, Money};
+use domain::repositories::{
+    AuditRepository, CategoryRepository, CouponRepository, CustomerRepository,
+    DataQualityRepository, DiagnosticsRepository, EmbeddingRepository, GeolocationRepository,
+    ImportRepository, InventoryRepository, LateOrderRepository, MaintenanceRepository,
+    NotificationRepository, OrderRepository, OrderStatusTransition, OutboxRepository,
+    PaymentTransactionRepository, ProductRepository, RetentionRepository,
+    ReviewSentimentRepository, SellerRepository, StatsRepository, SupportRepository,
+    WebhookRepository,
+};
+use domain::tenancy::TenantId;
+
+use crate::cancel::cancel_on_drop;
+use crate::collation::SortCollation;
+use crate::quality;
+use crate::replica::ReadPool;
+use crate::retry::RetryPolicy;
+
+use async_trait::async_trait;
+use futures::future::BoxFuture;
+use futures::stream::BoxStream;
+use sqlx::postgres::{PgArguments, PgRow};
+use sqlx::query::QueryAs;
+use sqlx::types::Json;
+use sqlx::{
+    Acquire, FromRow, PgConnection, PgExecutor, PgPool, Postgres, Result as SqlxResult, Row,
+};
+use tracing::{error, info, instrument};
+
+/// A listing row with the filtered total from `COUNT(*) OVER ()` alongside, so a page and its
+/// count come back in one round trip. The total is `NULL` when the listing did not ask for it.
+pub(crate) struct Counted<T> {
+    pub(crate) row: T,
+    pub(crate) total_count: Option<i64>,
+}
+
+impl<'r, T: FromRow<'r, PgRow>> FromRow<'r, PgRow> for Counted<T> {
+    fn from_row(row: &'r PgRow) -> SqlxResult<Self> {
+        Ok(Self {
+            row: T::from_row(row)?,
+            total_count: row.try_get("total_count")?,
+        })
+    }
+}
+
+/// Window column giving each row of a page the filtered total, when `total` asks for an exact
+/// count.
+fn total_column(total: TotalMode) -> &'static str {
+    match total {
+        TotalMode::Exact => "COUNT(*) OVER () AS total_count",
+        TotalMode::Estimate | TotalMode::Skip => "NULL::bigint AS total_count",
+    }
+}
+
+/// Splits a page into its rows and the window total. An empty first page means nothing
+/// matched; past the last page there is no row to read the total from, so it is `None` and
+/// the caller counts separately.
+pub(crate) fn split_counted<T>(rows: Vec<Counted<T>>, offset: i64) -> (Vec<T>, Option<i64>) {
+    let total_count = match rows.first() {
+        Some(first) => first.total_count,
+        None if offset == 0 => Some(0),
+        None => None,
+    };
+    (
+        rows.into_iter().map(|counted| counted.row).collect(),
+        total_count,
+    )
+}
+
+/// Runs `insert` on each row in one transaction, each under its own savepoint so a failed
+/// row is rolled back alone. Returns one result per row, in order; only a failure of the
+/// transaction itself fails the whole call.
+async fn insert_each<T, R>(
+    pool: &PgPool,
+    rows: Vec<T>,
+    insert: impl for<'c> Fn(&'c mut PgConnection, T) -> BoxFuture<'c, SqlxResult<R>>,
+) -> SqlxResult<Vec<SqlxResult<R>>> {
+    let mut tx = pool.begin().await?;
+    let mut results = Vec::with_capacity(rows.len());
+    for row in rows {
+        let mut savepoint = tx.begin().await?;
+        let result = insert(&mut savepoint, row).await;
+        if result.is_ok() {
+            savepoint.commit().await?;
+        } else {
+            savepoint.rollback().await?;
+        }
+        results.push(result);
+    }
+    tx.commit().await?;
+    Ok(results)
+}
+
+/// Counts the rows a listing's filter matches, as `mode` asks. `count` and `plan` are the
+/// listing's `SELECT COUNT(*)` and `EXPLAIN (FORMAT JSON)` queries with the filter bound.
+/// Estimates read the planner's row estimate; every listing is filtered at least by tenant, so
+/// the table-wide `pg_class.reltuples` would count other tenants' rows too.
+async fn count_rows<'q>(
+    conn: &mut PgConnection,
+    mode: TotalMode,
+    count: QueryAs<'q, Postgres, (i64,), PgArguments>,
+    plan: QueryAs<'q, Postgres, (Json<serde_json::Value>,), PgArguments>,
+) -> SqlxResult<Total> {
+    match mode {
+        TotalMode::Skip => return Ok(Total::Skipped),
+        TotalMode::Estimate => {
+            let (plan,) = plan.fetch_one(&mut *conn).await?;
+            if let Some(rows) = plan.0[0]["Plan"]["Plan Rows"].as_f64() {
+                return Ok(Total::Estimated(rows as i64));
+            }
+        }
+        TotalMode::Exact => {}
+    }
+
+    let (count,) = count.fetch_one(&mut *conn).await?;
+    Ok(Total::Exact(count))
+}
+
+/// A single `jsonb` column holding the selected fields of a listing row. Field names come from
+/// the model's `FIELDS` allowlist, so they are safe to splice into the query; `computed` gives
+/// the expression for fields that are not plain columns.
+fn sparse_columns(fields: &[&str], computed: &[(&str, &str)]) -> String {
+    let pairs: Vec<String> = fields
+        .iter()
+        .map(|field| {
+            let expression = computed
+                .iter()
+                .find(|(name, _)| name == field)
+                .map_or(*field, |(_, expression)| *expression);
+            format!("'{}', {}", field, expression)
+        })
+        .collect();
+    format!("jsonb_build_object({})", pairs.join(", "))
+}
+
+/// `WHERE` clause for [`CustomerFilter`] within a tenant, bound by [`bind_customer_filter`] as
+/// `$1`..`$5`.
+const CUSTOMER_FILTER: &str = r#"
+    ($1::text IS NULL OR canonical_city = resolve_city_alias($1))
+    AND ($2::text IS NULL OR customer_state = $2)
+    AND ($3 OR deleted_at IS NULL)
+    AND ($4::text IS NULL OR fuzzy_matches(customer_city, $4))
+    AND tenant_id = $5
+"#;
+
+fn bind_customer_filter<'q, O>(
+    query: QueryAs<'q, Postgres, O, PgArguments>,
+    filter: &'q CustomerFilter,
+    tenant: &'q TenantId,
+) -> QueryAs<'q, Postgres, O, PgArguments> {
+    query
+        .bind(&filter.city)
+        .bind(&filter.state)
+        .bind(filter.include_deleted)
+        .bind(&filter.q)
+        .bind(tenant.as_str())
+}
+
+#[derive(Clone)]
+pub struct PgCustomerRepository {
+    pool: PgPool,
+    reads: ReadPool,
+    collation: SortCollation,
+    retry: RetryPolicy,
+    tenant: TenantId,
+}
+
+impl PgCustomerRepository {
+    pub fn new(
+        pool: PgPool,
+        reads: ReadPool,
+        collation: SortCollation,
+        retry: RetryPolicy,
+        tenant: TenantId,
+    ) -> Self {
+        Self {
+            pool,
+            reads,
+            collation,
+            retry,
+            tenant,
+        }
+    }
+
+    async fn total(
+        &self,
+        conn: &mut PgConnection,
+        filter: &CustomerFilter,
+        mode: TotalMode,
+    ) -> SqlxResult<Total> {
+        let count = format!("SELECT COUNT(*) FROM customers WHERE {}", CUSTOMER_FILTER);
+        let plan = format!(
+            "EXPLAIN (FORMAT JSON) SELECT 1 FROM customers WHERE {}",
+            CUSTOMER_FILTER
+        );
+        count_rows(
+            conn,
+            mode,
+            bind_customer_filter(sqlx::query_as(&count), filter, &self.tenant),
+            bind_customer_filter(sqlx::query_as(&plan), filter, &self.tenant),
+        )
+        .await
+        .map_err(|e| {
+            error!("Error counting customers: {:?}", e);
+            e
+        })
+    }
+
+    /// A listing page selecting `columns` and the window total for `total`, filtered as `$1`..`$5` and paged by `$6`/`$7`.
+    fn page_query(&self, columns: &str, total: TotalMode) -> String {
+        format!(
+            r#"
+            SELECT {}, {}
+            FROM customers
+            WHERE {}
+            ORDER BY fuzzy_similarity(customer_city, $4) DESC NULLS LAST, {},
+                customer_zip_code_prefix DESC
+            LIMIT $6 OFFSET $7
+            "#,
+            columns,
+            total_column(total),
+            CUSTOMER_FILTER,
+            self.collation.order_by("customer_city")
+        )
+    }
+}
+
+async fn insert_customer<'e>(
+    executor: impl PgExecutor<'e>,
+    tenant: &TenantId,
+    id: &CustomerId,
+    dto: &CreateCustomerDto,
+    canonical_city: &str,
+) -> SqlxResult<Customer> {
+    sqlx::query_as!(
+        Customer,
+        r#"
+        INSERT INTO customers (
+            customer_id, customer_unique_id, customer_zip_code_prefix,
+            customer_city, customer_state, canonical_city, tenant_id
+        )
+        VALUES ($1, $2, $3, $4, $5, resolve_city_alias($6), $7)
+        RETURNING
+            customer_id AS "customer_id: CustomerId", customer_unique_id,
+            customer_zip_code_prefix, customer_city, canonical_city, customer_state,
+            deleted_at
+        "#,
+        id.as_str(),
+        dto.customer_unique_id,
+        dto.customer_zip_code_prefix,
+        dto.customer_city,
+        dto.customer_state.as_str(),
+        canonical_city,
+        tenant.as_str(),
+    )
+    .fetch_one(executor)
+    .await
+    .map_err(|e| {
+        error!("Error creating customer: {:?}", e);
+        e
+    })
+}
+
+#[async_trait]
+impl CustomerRepository for PgCustomerRepository {
+    async fn create(
+        &self,
+        id: &CustomerId,
+        dto: CreateCustomerDto,
+        canonical_city: &str,
+    ) -> SqlxResult<Customer> {
+        let dto = &dto;
+        self.retry
+            .write(|| async move {
+                insert_customer(&self.pool, &self.tenant, id, dto, canonical_city).await
+            })
+            .await
+    }
+
+    async fn create_many(
+        &self,
+        rows: Vec<(CustomerId, CreateCustomerDto, String)>,
+    ) -> SqlxResult<Vec<SqlxResult<Customer>>> {
+        insert_each(&self.pool, rows, |conn, (id, dto, city)| {
+            let tenant = self.tenant.clone();
+            Box::pin(async move { insert_customer(conn, &tenant, &id, &dto, &city).await })
+        })
+        .await
+    }
+
+    async fn find_all(
+        &self,
+        filter: &CustomerFilter,
+        pagination: &PaginationParams,
+        total: TotalMode,
+    ) -> SqlxResult<(Vec<Customer>, Total)> {
+        self.reads
+            .read(&self.retry, |pool| {
+                cancel_on_drop(pool, async move |conn| {
+                    let (limit, offset, _, _) = pagination.normalize();
+
+                    let query = self.page_query(
+                        r#"
+                            customer_id, customer_unique_id,
+                            customer_zip_code_prefix, customer_city, canonical_city, customer_state,
+                            deleted_at
+                        "#,
+                        total,
+                    );
+                    let rows = bind_customer_filter(
+                        sqlx::query_as::<_, Counted<Customer>>(&query),
+                        filter,
+                        &self.tenant,
+                    )
+                    .bind(limit)
+                    .bind(offset)
+                    .fetch_all(&mut *conn)
+                    .await
+                    .map_err(|e| {
+                        error!("Error fetching customers: {:?}", e);
+                        e
+                    })?;
+
+                    let (customers, window_total) = split_counted(rows, offset);
+                    let total = match (total, window_total) {
+                        (TotalMode::Exact, Some(count)) => Total::Exact(count),
+                        (mode, _) => self.total(conn, filter, mode).await?,
+                    };
+
+                    Ok((customers, total))
+                })
+            })
+            .await
+    }
+
+    async fn find_all_sparse(
+        &self,
+        filter: &CustomerFilter,
+        pagination: &PaginationParams,
+        fields: &[&'static str],
+        total: TotalMode,
+    ) -> SqlxResult<(Vec<SparseRow>, Total)> {
+        self.reads
+            .read(&self.retry, |pool| {
+                cancel_on_drop(pool, async move |conn| {
+                    let (limit, offset, _, _) = pagination.normalize();
+
+                    let query = self.page_query(&sparse_columns(fields, &[]), total);
+                    let rows = bind_customer_filter(
+                        sqlx::query_as::<_, Counted<(Json<SparseRow>,)>>(&query),
+                        filter,
+                        &self.tenant,
+                    )
+                    .bind(limit)
+                    .bind(offset)
+                    .fetch_all(&mut *conn)
+                    .await
+                    .map_err(|e| {
+                        error!("Error fetching sparse customers: {:?}", e);
+                        e
+                    })?;
+
+                    let (rows, window_total) = split_counted(rows, offset);
+                    let total = match (total, window_total) {
+                        (TotalMode::Exact, Some(count)) => Total::Exact(count),
+                        (mode, _) => self.total(conn, filter, mode).await?,
+                    };
+
+                    Ok((rows.into_iter().map(|(row,)| row.0).collect(), total))
+                })
+            })
+            .await
+    }
+
+    fn stream_all(&self) -> BoxStream<'_, SqlxResult<Customer>> {
+        sqlx::query_as!(
+            Customer,
+            r#"
+            SELECT
+                customer_id AS "customer_id: CustomerId", customer_unique_id,
+                customer_zip_code_prefix, customer_city, canonical_city, customer_state,
+                deleted_at
+            FROM customers
+            WHERE deleted_at IS NULL AND tenant_id = $1
+            ORDER BY customer_id
+            "#,
+            self.tenant.as_str(),
+        )
+        .fetch(&self.pool)
+    }
+
+    async fn find_by_id(&self, id: &CustomerId) -> SqlxResult<Option<Customer>> {
+        self.reads
+            .read(&self.retry, |pool| async move {
+                sqlx::query_as!(
+                    Customer,
+                    r#"
+                    SELECT
+                        customer_id AS "customer_id: CustomerId", customer_unique_id,
+                        customer_zip_code_prefix, customer_city, canonical_city, customer_state,
+                        deleted_at
+                    FROM customers
+                    WHERE customer_id = $1 AND deleted_at IS NULL AND tenant_id = $2
+                    "#,
+                    id.as_str(),
+                    self.tenant.as_str(),
+                )
+                .fetch_optional(pool)
+                .await
+                .map_err(|e| {
+                    error!("Error fetching customer by id: {:?}", e);
+                    e
+                })
+            })
+            .await
+    }
+
+    #[instrument(skip(self, dto), fields(customer_id = %id))]
+    async fn update(
+        &self,
+        id: &CustomerId,
+        dto: UpdateCustomerDto,
+        canonical_city: Option<&str>,
+    ) -> SqlxResult<Option<Customer>> {
+        let dto = &dto;
+        self.retry
+            .write(|| async move {
+                let result = sqlx::query_as!(
+                    Customer,
+                    r#"
+                    UPDATE customers
+                    SET
+                        customer_unique_id = COALESCE($2, customer_unique_id),
+                        customer_zip_code_prefix = COALESCE($3, customer_zip_code_prefix),
+                        customer_city = COALESCE($4, customer_city),
+                        customer_state = COALESCE($5, customer_state),
+                        canonical_city = COALESCE(resolve_city_alias($6), canonical_city)
+                    WHERE customer_id = $1 AND deleted_at IS NULL AND tenant_id = $7
+                    RETURNING
+                        customer_id AS "customer_id: CustomerId", customer_unique_id,
+                        customer_zip_code_prefix, customer_city, canonical_city, customer_state,
+                        deleted_at
+                    "#,
+                    id.as_str(),
+                    dto.customer_unique_id,
+                    dto.customer_zip_code_prefix,
+                    dto.customer_city,
+                    dto.customer_state.map(|state| state.as_str()),
+                    canonical_city,
+                    self.tenant.as_str(),
+                )
+                .fetch_optional(&self.pool)
+                .await;
+
+                match &result {
+                    Ok(Some(_)) => info!("Customer updated successfully"),
+                    Ok(None) => info!("Customer not found for update"),
+                    Err(e) => error!("Error updating customer: {:?}", e),
+                }
+
+                result
+            })
+            .await
+    }
+
+    #[instrument(skip(self), fields(customer_id = %id))]
+    async fn delete(&self, id: &CustomerId) -> SqlxResult<Option<chrono::NaiveDateTime>> {
+        self.retry
+            .write(|| async move {
+                let result = sqlx::query_scalar!(
+                    r#"
+                    UPDATE customers
+                    SET deleted_at = NOW()
+                    WHERE customer_id = $1 AND deleted_at IS NULL AND tenant_id = $2
+                    RETURNING deleted_at AS "deleted_at!"
+                    "#,
+                    id.as_str(),
+                    self.tenant.as_str(),
+                )
+                .fetch_optional(&self.pool)
+                .await;
+
+                match &result {
+                    Ok(Some(_)) => info!("Customer deleted successfully"),
+                    Ok(None) => info!("Customer not found for deletion"),
+                    Err(e) => error!("Error deleting customer: {:?}", e),
+                }
+
+                result
+            })
+            .await
+    }
+
+    #[instrument(skip(self), fields(customer_id = %id))]
+    async fn restore(&self, id: &CustomerId) -> SqlxResult<Option<Customer>> {
+        self.retry
+            .write(|| async move {
+                let result = sqlx::query_as!(
+                    Customer,
+                    r#"
+                    UPDATE customers
+                    SET deleted_at = NULL
+                    WHERE customer_id = $1 AND deleted_at IS NOT NULL AND tenant_id = $2
+                    RETURNING
+                        customer_id AS "customer_id: CustomerId", customer_unique_id,
+                        customer_zip_code_prefix, customer_city, canonical_city, customer_state,
+                        deleted_at
+                    "#,
+                    id.as_str(),
+                    self.tenant.as_str(),
+                )
+                .fetch_optional(&self.pool)
+                .await;
+
+                match &result {
+                    Ok(Some(_)) => info!("Customer restored successfully"),
+                    Ok(None) => info!("Deleted customer not found for restore"),
+                    Err(e) => error!("Error restoring customer: {:?}", e),
+                }
+
+                result
+            })
+            .await
+    }
+
+    #[instrument(skip(self), fields(customer_id = %id))]
+    async fn anonymize(
+        &self,
+        id: &CustomerId,
+        erasure: &NewAuditEntry,
+    ) -> SqlxResult<Option<Customer>> {
+        self.retry
+            .write(|| async move {
+                let result = async {
+                    let mut tx = self.pool.begin().await?;
+
+                    let customer = sqlx::query_as!(
+                        Customer,
+                        r#"
+                        UPDATE customers
+                        SET
+                            customer_unique_id = md5(random()::text || clock_timestamp()::text),
+                            customer_zip_code_prefix = '00000',
+                            customer_city = 'anonymized',
+                            canonical_city = 'anonymized',
+                            customer_state = 'XX'
+                        WHERE customer_id = $1 AND tenant_id = $2
+                        RETURNING
+                            customer_id AS "customer_id: CustomerId", customer_unique_id,
+                            customer_zip_code_prefix, customer_city, canonical_city, customer_state,
+                            deleted_at
+                        "#,
+                        id.as_str(),
+                        self.tenant.as_str(),
+                    )
+                    .fetch_optional(&mut *tx)
+                    .await?;
+
+                    let Some(customer) = customer else {
+                        return Ok(None);
+                    };
+
+                    sqlx::query!(
+                        r#"
+                        UPDATE reviews
+                        SET review_comment_title = NULL, review_comment_message = NULL
+                        WHERE tenant_id = $2
+                            AND order_id IN (
+                                SELECT order_id FROM orders WHERE customer_id = $1 AND tenant_id = $2
+                            )
+                        "#,
+                        id.as_str(),
+                        self.tenant.as_str(),
+                    )
+                    .execute(&mut *tx)
+                    .await?;
+
+                    sqlx::query!(
+                        r#"
+                        UPDATE reviews_archive
+                        SET review_comment_title = NULL, review_comment_message = NULL
+                        WHERE tenant_id = $2
+                            AND order_id IN (
+                                SELECT order_id FROM orders_archive
+                                WHERE customer_id = $1 AND tenant_id = $2
+                            )
+                        "#,
+                        id.as_str(),
+                        self.tenant.as_str(),
+                    )
+                    .execute(&mut *tx)
+                    .await?;
+
+                    sqlx::query!(
+                        r#"
+                        UPDATE orders
+                        SET shipping_zip_code_prefix = '00000'
+                        WHERE customer_id = $1 AND tenant_id = $2
+                        "#,
+                        id.as_str(),
+                        self.tenant.as_str(),
+                    )
+                    .execute(&mut *tx)
+                    .await?;
+
+                    sqlx::query!(
+                        r#"
+                        UPDATE orders_archive
+                        SET shipping_zip_code_prefix = '00000'
+                        WHERE customer_id = $1 AND tenant_id = $2
+                        "#,
+                        id.as_str(),
+                        self.tenant.as_str(),
+                    )
+                    .execute(&mut *tx)
+                    .await?;
+
+                    sqlx::query!(
+                        r#"
+                        UPDATE order_amendments
+                        SET changes = jsonb_set(
+                            changes, '{shipping_zip_code_prefix}', '{"redacted": true}'::jsonb
+                        )
+                        WHERE tenant_id = $2
+                            AND changes ? 'shipping_zip_code_prefix'
+                            AND order_id IN (
+                                SELECT order_id FROM orders WHERE customer_id = $1 AND tenant_id = $2
+                            )
+                        "#,
+                        id.as_str(),
+                        self.tenant.as_str(),
+                    )
+                    .execute(&mut *tx)
+                    .await?;
+
+                    sqlx::query!(
+                        r#"
+                        UPDATE audit_log
+                        SET diff = '{"redacted": true}'::jsonb
+                        WHERE entity_type = 'customer' AND entity_id = $1 AND tenant_id = $2
+                        "#,
+                        id.as_str(),
+                        self.tenant.as_str(),
+                    )
+                    .execute(&mut *tx)
+                    .await?;
+
+                    sqlx::query!(
+                        r#"
+                        UPDATE customer_location_history
+                        SET customer_zip_code_prefix = '00000', customer_city = 'anonymized',
+                            customer_state = 'XX'
+                        WHERE customer_id = $1 AND tenant_id = $2
+                        "#,
+                        id.as_str(),
+                        self.tenant.as_str(),
+                    )
+                    .execute(&mut *tx)
+                    .await?;
+
+                    sqlx::query!(
+                        "DELETE FROM customer_addresses WHERE customer_id = $1 AND tenant_id = $2",
+                        id.as_str(),
+                        self.tenant.as_str(),
+                    )
+                    .execute(&mut *tx)
+                    .await?;
+
+                    // After the redaction above, which would otherwise blank it too.
+                    sqlx::query!(
+                        r#"
+                        INSERT INTO audit_log (entity_type, entity_id, action, actor, diff, tenant_id)
+                        VALUES ($1, $2, $3, $4, $5, $6)
+                        "#,
+                        erasure.entity_type,
+                        erasure.entity_id,
+                        erasure.action.as_str(),
+                        erasure.actor,
+                        erasure.diff,
+                        self.tenant.as_str(),
+                    )
+                    .execute(&mut *tx)
+                    .await?;
+
+                    tx.commit().await?;
+                    Ok(Some(customer))
+                }
+                .await;
+
+                match &result {
+                    Ok(Some(_)) => info!("Customer anonymized successfully"),
+                    Ok(None) => info!("Customer not found for anonymization"),
+                    Err(e) => error!("Error anonymizing customer: {:?}", e),
+                }
+
+                result
+            })
+            .await
+    }
+
+    #[instrument(skip(self), fields(source_customer_id = %source, target_customer_id = %target))]
+    async fn merge(
+        &self,
+        source: &CustomerId,
+        target: &CustomerId,
+        audit: &[NewAuditEntry],
+    ) -> SqlxResult<Option<CustomerMerge>> {
+        self.retry
+            .write(|| async move {
+                let result = async {
+                    let mut tx = self.pool.begin().await?;
+
+                    // Locked in id order, so concurrent merges of the same pair can't deadlock
+                    let live = sqlx::query_scalar!(
+                        r#"
+                        SELECT customer_id FROM customers
+                        WHERE customer_id IN ($1, $2) AND deleted_at IS NULL AND tenant_id = $3
+                        ORDER BY customer_id
+                        FOR UPDATE
+                        "#,
+                        source.as_str(),
+                        target.as_str(),
+                        self.tenant.as_str(),
+                    )
+                    .fetch_all(&mut *tx)
+                    .await?;
+                    if live.len() < 2 {
+                        return Ok(None);
+                    }
+
+                    let live_orders = sqlx::query!(
+                        "UPDATE orders SET customer_id = $2 WHERE customer_id = $1 AND tenant_id = $3",
+                        source.as_str(),
+                        target.as_str(),
+                        self.tenant.as_str(),
+                    )
+                    .execute(&mut *tx)
+                    .await?
+                    .rows_affected();
+                    let archived_orders = sqlx::query!(
+                        "UPDATE orders_archive SET customer_id = $2 WHERE customer_id = $1 AND tenant_id = $3",
+                        source.as_str(),
+                        target.as_str(),
+                        self.tenant.as_str(),
+                    )
+                    .execute(&mut *tx)
+                    .await?
+                    .rows_affected();
+
+                    let support_cases_moved = sqlx::query!(
+                        "UPDATE support_cases SET customer_id = $2 WHERE customer_id = $1 AND tenant_id = $3",
+                        source.as_str(),
+                        target.as_str(),
+                        self.tenant.as_str(),
+                    )
+                    .execute(&mut *tx)
+                    .await?
+                    .rows_affected();
+
+                    // Moved addresses stay non-default unless the target has no default yet.
+                    sqlx::query!(
+                        r#"
+                        UPDATE customer_addresses
+                        SET
+                            customer_id = $2::VARCHAR,
+                            is_default = is_default AND NOT EXISTS (
+                                SELECT 1 FROM customer_addresses
+                                WHERE customer_id = $2::VARCHAR AND tenant_id = $3 AND is_default
+                            )
+                        WHERE customer_id = $1 AND tenant_id = $3
+                        "#,
+                        source.as_str(),
+                        target.as_str(),
+                        self.tenant.as_str(),
+                    )
+                    .execute(&mut *tx)
+                    .await?;
+
+                    // The target keeps its own current location; the source's closes here.
+                    sqlx::query!(
+                        r#"
+                        UPDATE customer_location_history
+                        SET
+                            customer_id = $2,
+                            valid_to = COALESCE(valid_to, LOCALTIMESTAMP)
+                        WHERE customer_id = $1 AND tenant_id = $3
+                        "#,
+                        source.as_str(),
+                        target.as_str(),
+                        self.tenant.as_str(),
+                    )
+                    .execute(&mut *tx)
+                    .await?;
+
+                    let source_deleted_at = sqlx::query_scalar!(
+                        r#"
+                        UPDATE customers
+                        SET deleted_at = NOW()
+                        WHERE customer_id = $1 AND tenant_id = $2
+                        RETURNING deleted_at AS "deleted_at!"
+                        "#,
+                        source.as_str(),
+                        self.tenant.as_str(),
+                    )
+                    .fetch_one(&mut *tx)
+                    .await?;
+
+                    let merge = CustomerMerge {
+                        source_customer_id: source.clone(),
+                        target_customer_id: target.clone(),
+                        orders_moved: live_orders + archived_orders,
+                        support_cases_moved,
+                        source_deleted_at,
+                    };
+                    for entry in audit {
+                        let entry = merge.audit_entry(entry);
+                        sqlx::query!(
+                            r#"
+                            INSERT INTO audit_log (entity_type, entity_id, action, actor, diff, tenant_id)
+                            VALUES ($1, $2, $3, $4, $5, $6)
+                            "#,
+                            entry.entity_type,
+                            entry.entity_id,
+                            entry.action.as_str(),
+                            entry.actor,
+                            entry.diff,
+                            self.tenant.as_str(),
+                        )
+                        .execute(&mut *tx)
+                        .await?;
+                    }
+
+                    tx.commit().await?;
+                    Ok(Some(merge))
+                }
+                .await;
+
+                match &result {
+                    Ok(Some(_)) => info!("Customers merged successfully"),
+                    Ok(None) => info!("Customer not found for merge"),
+                    Err(e) => error!("Error merging customers: {:?}", e),
+                }
+
+                result
+            })
+            .await
+    }
+
+    async fn count_dependents(&self, id: &CustomerId) -> SqlxResult<CustomerDependents> {
+        self.reads
+            .read(&self.retry, |pool| async move {
+                sqlx::query_as!(
+                    CustomerDependents,
+                    r#"
+                    SELECT
+                        (
+                            SELECT COUNT(*) FROM orders WHERE customer_id = $1 AND tenant_id = $2
+                        ) AS "orders!",
+                        (
+                            SELECT COUNT(*) FROM support_cases
+                            WHERE customer_id = $1 AND tenant_id = $2
+                        ) AS "support_cases!"
+                    "#,
+                    id.as_str(),
+                    self.tenant.as_str(),
+                )
+                .fetch_one(pool)
+                .await
+                .map_err(|e| {
+                    error!("Error counting customer dependents: {:?}", e);
+                    e
+                })
+            })
+            .await
+    }
+
+    async fn find_location_history(
+        &self,
+        id: &CustomerId,
+    ) -> SqlxResult<Vec<CustomerLocationVersion>> {
+        self.reads
+            .read(&self.retry, |pool| async move {
+                sqlx::query_as!(
+                    CustomerLocationVersion,
+                    r#"
+                    SELECT
+                        customer_zip_code_prefix, customer_city, customer_state,
+                        valid_from, valid_to
+                    FROM customer_location_history
+                    WHERE customer_id = $1 AND tenant_id = $2
+                    ORDER BY valid_from NULLS FIRST, history_id
+                    "#,
+                    id.as_str(),
+                    self.tenant.as_str(),
+                )
+                .fetch_all(pool)
+                .await
+                .map_err(|e| {
+                    error!("Error fetching customer location history: {:?}", e);
+                    e
+                })
+            })
+            .await
+    }
+
+    #[instrument(skip(self, dto), fields(customer_id = %id))]
+    async fn create_address(
+        &self,
+        id: &CustomerId,
+        dto: CreateCustomerAddressDto,
+    ) -> SqlxResult<CustomerAddress> {
+        let dto = &dto;
+        self.retry
+            .write(|| async move {
+                let result = async {
+                    let mut tx = self.pool.begin().await?;
+
+                    if dto.is_default {
+                        sqlx::query!(
+                            r#"
+                            UPDATE customer_addresses SET is_default = FALSE
+                            WHERE customer_id = $1 AND is_default AND tenant_id = $2
+                            "#,
+                            id.as_str(),
+                            self.tenant.as_str(),
+                        )
+                        .execute(&mut *tx)
+                        .await?;
+                    }
+
+                    let address = sqlx::query_as!(
+                        CustomerAddress,
+                        r#"
+                        INSERT INTO customer_addresses (
+                            customer_id, label, zip_code_prefix, city, state, is_default,
+                            tenant_id
+                        )
+                        VALUES (
+                            $1::VARCHAR, $2, $3, $4, $5,
+                            $6 OR NOT EXISTS (
+                                SELECT 1 FROM customer_addresses
+                                WHERE customer_id = $1::VARCHAR AND tenant_id = $7::VARCHAR
+                            ),
+                            $7::VARCHAR
+                        )
+                        RETURNING
+                            address_id, customer_id AS "customer_id: CustomerId", label,
+                            zip_code_prefix, city, state, is_default, created_at
+                        "#,
+                        id.as_str(),
+                        dto.label,
+                        dto.zip_code_prefix,
+                        dto.city,
+                        dto.state.as_str(),
+                        dto.is_default,
+                        self.tenant.as_str(),
+                    )
+                    .fetch_one(&mut *tx)
+                    .await?;
+
+                    tx.commit().await?;
+                    Ok(address)
+                }
+                .await;
+
+                if let Err(e) = &result {
+                    error!("Error creating customer address: {:?}", e);
+                }
+                result
+            })
+            .await
+    }
+
+    async fn find_addresses(&self, id: &CustomerId) -> SqlxResult<Vec<CustomerAddress>> {
+        self.reads
+            .read(&self.retry, |pool| async move {
+                sqlx::query_as!(
+                    CustomerAddress,
+                    r#"
+                    SELECT
+                        address_id, customer_id AS "customer_id: CustomerId", label,
+                        zip_code_prefix, city, state, is_default, created_at
+                    FROM customer_addresses
+                    WHERE customer_id = $1 AND tenant_id = $2
+                    ORDER BY is_default DESC, address_id
+                    "#,
+                    id.as_str(),
+                    self.tenant.as_str(),
+                )
+                .fetch_all(pool)
+                .await
+                .map_err(|e| {
+                    error!("Error fetching customer addresses: {:?}", e);
+                    e
+                })
+            })
+            .await
+    }
+
+    async fn find_address(
+        &self,
+        id: &CustomerId,
+        address_id: i64,
+    ) -> SqlxResult<Option<CustomerAddress>> {
+        self.reads
+            .read(&self.retry, |pool| async move {
+                sqlx::query_as!(
+                    CustomerAddress,
+                    r#"
+                    SELECT
+                        address_id, customer_id AS "customer_id: CustomerId", label,
+                        zip_code_prefix, city, state, is_default, created_at
+                    FROM customer_addresses
+                    WHERE customer_id = $1 AND address_id = $2 AND tenant_id = $3
+                    "#,
+                    id.as_str(),
+                    address_id,
+                    self.tenant.as_str(),
+                )
+                .fetch_optional(pool)
+                .await
+                .map_err(|e| {
+                    error!("Error fetching customer address: {:?}", e);
+                    e
+                })
+            })
+            .await
+    }
+
+    #[instrument(skip(self, dto), fields(customer_id = %id))]
+    async fn update_address(
+        &self,
+        id: &CustomerId,
+        address_id: i64,
+        dto: UpdateCustomerAddressDto,
+    ) -> SqlxResult<Option<CustomerAddress>> {
+        let dto = &dto;
+        self.retry
+            .write(|| async move {
+                let result = async {
+                    let mut tx = self.pool.begin().await?;
+
+                    if dto.is_default == Some(true) {
+                        sqlx::query!(
+                            r#"
+                            UPDATE customer_addresses SET is_default = FALSE
+                            WHERE customer_id = $1 AND is_default AND address_id <> $2
+                                AND tenant_id = $3
+                            "#,
+                            id.as_str(),
+                            address_id,
+                            self.tenant.as_str(),
+                        )
+                        .execute(&mut *tx)
+                        .await?;
+                    }
+
+                    let address = sqlx::query_as!(
+                        CustomerAddress,
+                        r#"
+                        UPDATE customer_addresses
+                        SET
+                            label = COALESCE($3, label),
+                            zip_code_prefix = COALESCE($4, zip_code_prefix),
+                            city = COALESCE($5, city),
+                            state = COALESCE($6, state),
+                            is_default = COALESCE($7, is_default)
+                        WHERE customer_id = $1 AND address_id = $2 AND tenant_id = $8
+                        RETURNING
+                            address_id, customer_id AS "customer_id: CustomerId", label,
+                            zip_code_prefix, city, state, is_default, created_at
+                        "#,
+                        id.as_str(),
+                        address_id,
+                        dto.label,
+                        dto.zip_code_prefix,
+                        dto.city,
+                        dto.state.map(|state| state.as_str()),
+                        dto.is_default,
+                        self.tenant.as_str(),
+                    )
+                    .fetch_optional(&mut *tx)
+                    .await?;
+
+                    tx.commit().await?;
+                    Ok(address)
+                }
+                .await;
+
+                if let Err(e) = &result {
+                    error!("Error updating customer address: {:?}", e);
+                }
+                result
+            })
+            .await
+    }
+
+    #[instrument(skip(self), fields(customer_id = %id))]
+    async fn delete_address(
+        &self,
+        id: &CustomerId,
+        address_id: i64,
+    ) -> SqlxResult<Option<chrono::NaiveDateTime>> {
+        self.retry
+            .write(|| async move {
+                sqlx::query_scalar!(
+                    r#"
+                    DELETE FROM customer_addresses
+                    WHERE customer_id = $1 AND address_id = $2 AND tenant_id = $3
+                    RETURNING LOCALTIMESTAMP AS "deleted_at!"
+                    "#,
+                    id.as_str(),
+                    address_id,
+                    self.tenant.as_str(),
+                )
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(|e| {
+                    error!("Error deleting customer address: {:?}", e);
+                    e
+                })
+            })
+            .await
+    }
+
+    /// Anonymized customers are left out: their state is a placeholder, not a filter value.
+    async fn count_by_state(&self) -> SqlxResult<Vec<FilterValue>> {
+        self.reads
+            .read(&self.retry, |pool| async move {
+                sqlx::query_as!(
+                    FilterValue,
+                    r#"
+                    SELECT customer_state AS value, COUNT(*) AS "count!"
+                    FROM customers
+                    WHERE deleted_at IS NULL AND canonical_city <> 'anonymized' AND tenant_id = $1
+                    GROUP BY customer_state
+                    ORDER BY COUNT(*) DESC, value
+                    "#,
+                    self.tenant.as_str(),
+                )
+                .fetch_all(pool)
+                .await
+                .map_err(|e| {
+                    error!("Error counting customers by state: {:?}", e);
+                    e
+                })
+            })
+            .await
+    }
+
+    /// Anonymized customers are left out: their city is a placeholder, not a filter value.
+    async fn count_by_city(&self, state: Option<BrazilState>) -> SqlxResult<Vec<FilterValue>> {
+        self.reads
+            .read(&self.retry, |pool| async move {
+                sqlx::query_as::<_, FilterValue>(&format!(
+                    r#"
+                    SELECT canonical_city AS value, COUNT(*) AS count
+                    FROM customers
+                    WHERE deleted_at IS NULL
+                      AND canonical_city <> 'anonymized'
+                      AND ($1::text IS NULL OR customer_state = $1)
+                      AND tenant_id = $2
+                    GROUP BY canonical_city
+                    ORDER BY COUNT(*) DESC, {}
+                    "#,
+                    self.collation.order_by("canonical_city")
+                ))
+                .bind(state.map(|state| state.as_str()))
+                .bind(self.tenant.as_str())
+                .fetch_all(pool)
+                .await
+                .map_err(|e| {
+                    error!("Error counting customers by city: {:?}", e);
+                    e
+                })
+            })
+            .await
+    }
+
+    async fn find_by_unique_id(&self, unique_id: &str) -> SqlxResult<Vec<Customer>> {
+        self.reads
+            .read(&self.retry, |pool| async move {
+                sqlx::query_as!(
+                    Customer,
+                    r#"
+                    SELECT
+                        customer_id AS "customer_id: CustomerId", customer_unique_id,
+                        customer_zip_code_prefix, customer_city, canonical_city, customer_state,
+                        deleted_at
+                    FROM customers
+                    WHERE customer_unique_id = $1 AND deleted_at IS NULL AND tenant_id = $2
+                    ORDER BY customer_id
+                    "#,
+                    unique_id,
+                    self.tenant.as_str(),
+                )
+                .fetch_all(pool)
+                .await
+                .map_err(|e| {
+                    error!("Error fetching customers by unique id: {:?}", e);
+                    e
+                })
+            })
+            .await
+    }
+
+    async fn count_rows_per_unique_id(&self) -> SqlxResult<Vec<DuplicationBucket>> {
+        self.reads
+            .read(&self.retry, |pool| async move {
+                sqlx::query_as!(
+                    DuplicationBucket,
+                    r#"
+                    SELECT customer_rows AS "customer_rows!", COUNT(*) AS "unique_customers!"
+                    FROM (
+                        SELECT COUNT(*) AS customer_rows
+                        FROM customers
+                        WHERE deleted_at IS NULL AND tenant_id = $1
+                        GROUP BY customer_unique_id
+                    ) per_unique_id
+                    GROUP BY customer_rows
+                    ORDER BY customer_rows
+                    "#,
+                    self.tenant.as_str(),
+                )
+                .fetch_all(pool)
+                .await
+                .map_err(|e| {
+                    error!("Error counting customer rows per unique id: {:?}", e);
+                    e
+                })
+            })
+            .await
+    }
+
+    async fn find_most_duplicated(&self, limit: i64) -> SqlxResult<Vec<DuplicatedCustomer>> {
+        self.reads
+            .read(&self.retry, |pool| async move {
+                sqlx::query_as!(
+                    DuplicatedCustomer,
+                    r#"
+                    SELECT customer_unique_id, COUNT(*) AS "customer_rows!"
+                    FROM customers
+                    WHERE deleted_at IS NULL AND tenant_id = $2
+                    GROUP BY customer_unique_id
+                    HAVING COUNT(*) > 1
+                    ORDER BY COUNT(*) DESC, customer_unique_id
+                    LIMIT $1
+                    "#,
+                    limit,
+                    self.tenant.as_str(),
+                )
+                .fetch_all(pool)
+                .await
+                .map_err(|e| {
+                    error!("Error finding duplicated customers: {:?}", e);
+                    e
+                })
+            })
+            .await
+    }
+}
+
+/// Badge names awarded to seller `s`.
+const SELLER_BADGES: &str = "ARRAY(SELECT b.badge::text FROM seller_badges b WHERE b.seller_id = s.seller_id ORDER BY b.badge)";
+
+/// `WHERE` clause for [`SellerFilter`] over a tenant's sellers `s`, bound by
+/// [`bind_seller_filter`] as `$1`..`$5`.
+const SELLER_FILTER: &str = r#"
+    ($1::text IS NULL OR canonical_city = resolve_city_alias($1))
+    AND ($2::text IS NULL OR seller_state = $2)
+    AND ($3::text IS NULL OR EXISTS (
+        SELECT 1 FROM seller_badges b
+        WHERE b.seller_id = s.seller_id AND b.badge = $3
+    ))
+    AND ($4::text IS NULL OR fuzzy_matches(seller_city, $4))
+    AND s.tenant_id = $5
+"#;
+
+fn bind_seller_filter<'q, O>(
+    query: QueryAs<'q, Postgres, O, PgArguments>,
+    filter: &'q SellerFilter,
+    tenant: &'q TenantId,
+) -> QueryAs<'q, Postgres, O, PgArguments> {
+    query
+        .bind(&filter.city)
+        .bind(&filter.state)
+        .bind(&filter.badge)
+        .bind(&filter.q)
+        .bind(tenant.as_str())
+}
+
+#[derive(Clone)]
+pub struct PgSellerRepository {
+    pool: PgPool,
+    reads: ReadPool,
+    collation: SortCollation,
+    retry: RetryPolicy,
+    tenant: TenantId,
+}
+
+impl PgSellerRepository {
+    pub fn new(
+        pool: PgPool,
+        reads: ReadPool,
+        collation: SortCollation,
+        retry: RetryPolicy,
+        tenant: TenantId,
+    ) -> Self {
+        Self {
+            pool,
+            reads,
+            collation,
+            retry,
+            tenant,
+        }
+    }
+
+    async fn total(
+        &self,
+        conn: &mut PgConnection,
+        filter: &SellerFilter,
+        mode: TotalMode,
+    ) -> SqlxResult<Total> {
+        let count = format!("SELECT COUNT(*) FROM sellers s WHERE {}", SELLER_FILTER);
+        let plan = format!(
+            "EXPLAIN (FORMAT JSON) SELECT 1 FROM sellers s WHERE {}",
+            SELLER_FILTER
+        );
+        count_rows(
+            conn,
+            mode,
+            bind_seller_filter(sqlx::query_as(&count), filter, &self.tenant),
+            bind_seller_filter(sqlx::query_as(&plan), filter, &self.tenant),
+        )
+        .await
+        .map_err(|e| {
+            error!("Error counting sellers: {:?}", e);
+            e
+        })
+    }
+
+    /// A listing page selecting `columns` and the window total for `total`, filtered as `$1`..`$5` and paged by `$6`/`$7`.
+    fn page_query(&self, columns: &str, total: TotalMode) -> String {
+        format!(
+            r#"
+            SELECT {}, {}
+            FROM sellers s
+            WHERE {}
+            ORDER BY fuzzy_similarity(seller_city, $4) DESC NULLS LAST, {}, seller_id
+            LIMIT $6 OFFSET $7
+            "#,
+            columns,
+            total_column(total),
+            SELLER_FILTER,
+            self.collation.order_by("seller_city")
+        )
+    }
+}
+
+async fn insert_seller<'e>(
+    executor: impl PgExecutor<'e>,
+    tenant: &TenantId,
+    id: &SellerId,
+    dto: &CreateSellerDto,
+    canonical_city: &str,
+) -> SqlxResult<Seller> {
+    sqlx::query_as!(
+        Seller,
+        r#"
+        INSERT INTO sellers (
+            seller_id, seller_zip_code_prefix,
+            seller_city, seller_state, canonical_city, tenant_id
+        )
+        VALUES ($1, $2, $3, $4, resolve_city_alias($5), $6)
+        RETURNING
+            seller_id AS "seller_id: SellerId", seller_zip_code_prefix,
+            seller_city, canonical_city, seller_state, ARRAY[]::text[] AS "badges!"
+        "#,
+        id.as_str(),
+        dto.seller_zip_code_prefix,
+        dto.seller_city,
+        dto.seller_state.as_str(),
+        canonical_city,
+        tenant.as_str(),
+    )
+    .fetch_one(executor)
+    .await
+    .map_err(|e| {
+        error!("Error creating seller: {:?}", e);
+        e
+    })
+}
+
+#[async_trait]
+impl SellerRepository for PgSellerRepository {
+    async fn create(
+        &self,
+        id: &SellerId,
+        dto: CreateSellerDto,
+        canonical_city: &str,
+    ) -> SqlxResult<Seller> {
+        let dto = &dto;
+        self.retry
+            .write(|| async move {
+                insert_seller(&self.pool, &self.tenant, id, dto, canonical_city).await
+            })
+            .await
+    }
+
+    async fn create_many(
+        &self,
+        rows: Vec<(SellerId, CreateSellerDto, String)>,
+    ) -> SqlxResult<Vec<SqlxResult<Seller>>> {
+        insert_each(&self.pool, rows, |conn, (id, dto, city)| {
+            let tenant = self.tenant.clone();
+            Box::pin(async move { insert_seller(conn, &tenant, &id, &dto, &city).await })
+        })
+        .await
+    }
+
+    async fn find_all(
+        &self,
+        filter: &SellerFilter,
+        pagination: &PaginationParams,
+        total: TotalMode,
+    ) -> SqlxResult<(Vec<Seller>, Total)> {
+        self.reads
+            .read(&self.retry, |pool| {
+                cancel_on_drop(pool, async move |conn| {
+                    let (limit, offset, _, _) = pagination.normalize();
+
+                    let query = self.page_query(
+                        &format!(
+                            r#"
+                                seller_id,
+                                seller_zip_code_prefix,
+                                seller_city,
+                                canonical_city,
+                                seller_state,
+                                {} AS badges
+                            "#,
+                            SELLER_BADGES
+                        ),
+                        total,
+                    );
+                    let rows = bind_seller_filter(
+                        sqlx::query_as::<_, Counted<Seller>>(&query),
+                        filter,
+                        &self.tenant,
+                    )
+                    .bind(limit)
+                    .bind(offset)
+                    .fetch_all(&mut *conn)
+                    .await
+                    .map_err(|e| {
+                        error!("Error fetching sellers: {:?}", e);
+                        e
+                    })?;
+
+                    let (sellers, window_total) = split_counted(rows, offset);
+                    let total = match (total, window_total) {
+                        (TotalMode::Exact, Some(count)) => Total::Exact(count),
+                        (mode, _) => self.total(conn, filter, mode).await?,
+                    };
+
+                    Ok((sellers, total))
+                })
+            })
+            .await
+    }
+
+    async fn find_all_sparse(
+        &self,
+        filter: &SellerFilter,
+        pagination: &PaginationParams,
+        fields: &[&'static str],
+        total: TotalMode,
+    ) -> SqlxResult<(Vec<SparseRow>, Total)> {
+        self.reads
+            .read(&self.retry, |pool| {
+                cancel_on_drop(pool, async move |conn| {
+                    let (limit, offset, _, _) = pagination.normalize();
+
+                    let query = self
+                        .page_query(&sparse_columns(fields, &[("badges", SELLER_BADGES)]), total);
+                    let rows = bind_seller_filter(
+                        sqlx::query_as::<_, Counted<(Json<SparseRow>,)>>(&query),
+                        filter,
+                        &self.tenant,
+                    )
+                    .bind(limit)
+                    .bind(offset)
+                    .fetch_all(&mut *conn)
+                    .await
+                    .map_err(|e| {
+                        error!("Error fetching sparse sellers: {:?}", e);
+                        e
+                    })?;
+
+                    let (rows, window_total) = split_counted(rows, offset);
+                    let total = match (total, window_total) {
+                        (TotalMode::Exact, Some(count)) => Total::Exact(count),
+                        (mode, _) => self.total(conn, filter, mode).await?,
+                    };
+
+                    Ok((rows.into_iter().map(|(row,)| row.0).collect(), total))
+                })
+            })
+            .await
+    }
+
+    async fn find_by_id(&self, id: &SellerId) -> SqlxResult<Option<Seller>> {
+        self.reads
+            .read(&self.retry, |pool| async move {
+                sqlx::query_as!(
+                    Seller,
+                    r#"
+                    SELECT
+                        seller_id AS "seller_id: SellerId", seller_zip_code_prefix,
+                        seller_city, canonical_city, seller_state,
+                        ARRAY(
+                            SELECT b.badge::text FROM seller_badges b
+                            WHERE b.seller_id = s.seller_id ORDER BY b.badge
+                        ) AS "badges!"
+                    FROM sellers s WHERE seller_id = $1 AND tenant_id = $2
+                    "#,
+                    id.as_str(),
+                    self.tenant.as_str(),
+                )
+                .fetch_optional(pool)
+                .await
+                .map_err(|e| {
+                    error!("Error fetching seller by id: {:?}", e);
+                    e
+                })
+            })
+            .await
+    }
+
+    async fn find_within(&self, bounds: &GeoBounds) -> SqlxResult<Vec<LocatedSeller>> {
+        self.reads
+            .read(&self.retry, |pool| async move {
+                sqlx::query_as::<_, LocatedSeller>(&format!(
+                    r#"
+                    SELECT
+                        s.seller_id, s.seller_zip_code_prefix,
+                        s.seller_city, s.canonical_city, s.seller_state,
+                        {} AS badges,
+                        g.geolocation_lat, g.geolocation_lng
+                    FROM sellers s
+                    JOIN geolocation g ON g.geolocation_zip_code_prefix = s.seller_zip_code_prefix
+                    WHERE g.geolocation_lat BETWEEN $1 AND $2
+                      AND g.geolocation_lng BETWEEN $3 AND $4
+                      AND s.tenant_id = $5
+                    "#,
+                    SELLER_BADGES
+                ))
+                .bind(bounds.min_lat)
+                .bind(bounds.max_lat)
+                .bind(bounds.min_lng)
+                .bind(bounds.max_lng)
+                .bind(self.tenant.as_str())
+                .fetch_all(pool)
+                .await
+                .map_err(|e| {
+                    error!("Error fetching sellers near a location: {:?}", e);
+                    e
+                })
+            })
+            .await
+    }
+
+    /// Recomputes the badges of every seller of the tenant from order, shipping and review
+    /// metrics against the thresholds in `seller_badge_thresholds`, replacing the previous set
+    /// atomically.
+    #[instrument(skip(self))]
+    async fn refresh_badges(&self) -> SqlxResult<u64> {
+        self.retry.write(|| async move {
+            let result = async {
+                let mut tx = self.pool.begin().await?;
+
+                sqlx::query!(
+                    "DELETE FROM seller_badges WHERE tenant_id = $1",
+                    self.tenant.as_str(),
+                )
+                    .execute(&mut *tx)
+                    .await?;
+
+                let inserted = sqlx::query!(
+                    r#"
+                    WITH seller_orders AS (
+                        SELECT DISTINCT oi.seller_id, oi.order_id
+                        FROM order_items oi
+                        WHERE oi.tenant_id = $1
+                    ),
+                    order_metrics AS (
+                        SELECT
+                            so.seller_id,
+                            COUNT(*) AS order_count,
+                            COUNT(o.order_delivered_carrier_date) AS handled_count,
+                            AVG(EXTRACT(EPOCH FROM
+                                o.order_delivered_carrier_date - o.order_approved_at) / 3600
+                            ) AS avg_handling_hours
+                        FROM seller_orders so
+                        JOIN orders o ON o.order_id = so.order_id
+                        GROUP BY so.seller_id
+                    ),
+                    review_metrics AS (
+                        SELECT so.seller_id, AVG(r.review_score) AS avg_review_score, COUNT(*) AS review_count
+                        FROM seller_orders so
+                        JOIN reviews r ON r.order_id = so.order_id
+                        GROUP BY so.seller_id
+                    ),
+                    seller_metrics AS (
+                        SELECT seller_id, 'avg_handling_hours' AS metric,
+                               avg_handling_hours::numeric AS value, handled_count AS sample
+                        FROM order_metrics
+                        UNION ALL
+                        SELECT seller_id, 'order_count', order_count::numeric, order_count
+                        FROM order_metrics
+                        UNION ALL
+                        SELECT seller_id, 'avg_review_score', avg_review_score::numeric, review_count
+                        FROM review_metrics
+                    )
+                    INSERT INTO seller_badges (seller_id, badge, metric_value, tenant_id)
+                    SELECT m.seller_id, t.badge, round(m.value, 2), s.tenant_id
+                    FROM seller_metrics m
+                    JOIN seller_badge_thresholds t ON t.metric = m.metric
+                    JOIN sellers s ON s.seller_id = m.seller_id
+                    WHERE s.tenant_id = $1
+                      AND m.value IS NOT NULL
+                      AND m.sample >= t.min_sample
+                      AND CASE t.comparison
+                            WHEN 'lte' THEN m.value <= t.threshold
+                            ELSE m.value >= t.threshold
+                          END
+                    "#,
+                    self.tenant.as_str(),
+                )
+                .execute(&mut *tx)
+                .await?;
+
+                tx.commit().await?;
+                Ok(inserted.rows_affected())
+            }
+            .await;
+
+            match &result {
+                Ok(count) => info!("Awarded {} seller badges", count),
+                Err(e) => error!("Error refreshing seller badges: {:?}", e),
+            }
+
+            result
+        })
+        .await
+    }
+
+    async fn find_badge_thresholds(&self) -> SqlxResult<Vec<SellerBadgeThreshold>> {
+        self.reads
+            .read(&self.retry, |pool| async move {
+                sqlx::query_as!(
+                    SellerBadgeThreshold,
+                    r#"
+                    SELECT badge, description, metric, comparison, threshold, min_sample
+                    FROM seller_badge_thresholds
+                    ORDER BY badge
+                    "#,
+                )
+                .fetch_all(pool)
+                .await
+                .map_err(|e| {
+                    error!("Error fetching seller badge thresholds: {:?}", e);
+                    e
+                })
+            })
+            .await
+    }
+
+    async fn find_metrics(&self, id: &SellerId) -> SqlxResult<SellerMetrics> {
+        self.reads
+            .read(&self.retry, |pool| async move {
+                sqlx::query_as!(
+                    SellerMetrics,
+                    r#"
+                    WITH seller_orders AS (
+                        SELECT order_id, MIN(shipping_limit_date) AS shipping_limit_date
+                        FROM order_items
+                        WHERE seller_id = $1 AND tenant_id = $2
+                        GROUP BY order_id
+                    ),
+                    review_metrics AS (
+                        SELECT COUNT(*) AS review_count, AVG(r.review_score)::float8 AS review_average
+                        FROM seller_orders so
+                        JOIN reviews r ON r.order_id = so.order_id
+                    )
+                    SELECT
+                        COUNT(o.order_id) AS "order_count!",
+                        COUNT(o.order_delivered_carrier_date) AS "shipped_count!",
+                        COUNT(*) FILTER (
+                            WHERE o.order_delivered_carrier_date <= so.shipping_limit_date
+                        ) AS "on_time_count!",
+                        COUNT(*) FILTER (WHERE o.order_status = 'canceled') AS "canceled_count!",
+                        (SELECT review_count FROM review_metrics) AS "review_count!",
+                        (SELECT review_average FROM review_metrics) AS review_average
+                    FROM seller_orders so
+                    JOIN orders o ON o.order_id = so.order_id
+                    "#,
+                    id.as_str(),
+                    self.tenant.as_str(),
+                )
+                .fetch_one(pool)
+                .await
+                .map_err(|e| {
+                    error!("Error fetching seller metrics: {:?}", e);
+                    e
+                })
+            })
+            .await
+    }
+}
+
+/// `WHERE` clause for [`OrderFilter`] within a tenant, bound by [`bind_order_filter`] as
+/// `$1`/`$2`.
+const ORDER_FILTER: &str = "($1::text IS NULL OR order_status = $1) AND tenant_id = $2";
+
+/// A tenant's orders purchased in a [`ReconciliationFilter`]'s range with their item and
+/// payment sums, bound as `$1`/`$2` and the tenant as `$3`; the caller keeps those further
+/// apart than the tolerance.
+const ORDER_SUMS: &str = r#"
+    SELECT
+        o.order_id, o.order_status, o.order_purchase_timestamp,
+        (SELECT COUNT(*) FROM order_items i WHERE i.order_id = o.order_id) AS item_count,
+        (SELECT COALESCE(SUM(i.price + i.freight_value), 0) FROM order_items i
+         WHERE i.order_id = o.order_id) AS items_total,
+        (SELECT COUNT(*) FROM payments p WHERE p.order_id = o.order_id) AS payment_count,
+        (SELECT COALESCE(SUM(p.payment_value), 0) FROM payments p
+         WHERE p.order_id = o.order_id) AS payments_total
+    FROM orders o
+    WHERE ($1::timestamp IS NULL OR o.order_purchase_timestamp >= $1)
+      AND ($2::timestamp IS NULL OR o.order_purchase_timestamp < $2)
+      AND o.tenant_id = $3
+"#;
+
+fn bind_order_filter<'q, O>(
+    query: QueryAs<'q, Postgres, O, PgArguments>,
+    filter: &'q OrderFilter,
+    tenant: &'q TenantId,
+) -> QueryAs<'q, Postgres, O, PgArguments> {
+    query.bind(filter.status).bind(tenant.as_str())
+}
+
+#[derive(Clone)]
+pub struct PgOrderRepository {
+    pool: PgPool,
+    reads: ReadPool,
+    retry: RetryPolicy,
+    tenant: TenantId,
+}
+
+impl PgOrderRepository {
+    pub fn new(pool: PgPool, reads: ReadPool, retry: RetryPolicy, tenant: TenantId) -> Self {
+        Self {
+            pool,
+            reads,
+            retry,
+            tenant,
+        }
+    }
+
+    async fn total(
+        &self,
+        conn: &mut PgConnection,
+        filter: &OrderFilter,
+        mode: TotalMode,
+    ) -> SqlxResult<Total> {
+        let count = format!("SELECT COUNT(*) FROM orders WHERE {}", ORDER_FILTER);
+        let plan = format!(
+            "EXPLAIN (FORMAT JSON) SELECT 1 FROM orders WHERE {}",
+            ORDER_FILTER
+        );
+        count_rows(
+            conn,
+            mode,
+            bind_order_filter(sqlx::query_as(&count), filter, &self.tenant),
+            bind_order_filter(sqlx::query_as(&plan), filter, &self.tenant),
+        )
+        .await
+        .map_err(|e| {
+            tracing::error!("Error counting orders: {:?}", e);
+            e
+        })
+    }
+
+    /// A listing page selecting `columns` and the window total for `total`, filtered as `$1`/`$2` and paged by `$3`/`$4`.
+    fn page_query(&self, columns: &str, total: TotalMode) -> String {
+        format!(
+            r#"
+            SELECT {}, {}
+            FROM orders
+            WHERE {}
+            ORDER BY order_purchase_timestamp DESC
+            LIMIT $3 OFFSET $4
+            "#,
+            columns,
+            total_column(total),
+            ORDER_FILTER
+        )
+    }
+}
+
+async fn insert_order<'e>(
+    executor: impl PgExecutor<'e>,
+    tenant: &TenantId,
+    id: &OrderId,
+    dto: &CreateOrderDto,
+) -> SqlxResult<Order> {
+    sqlx::query_as!(
+        Order,
+        r#"
+        INSERT INTO orders (
+            order_id, customer_id, order_status,
+            order_purchase_timestamp, order_approved_at,
+            order_delivered_carrier_date, order_delivered_customer_date,
+            order_estimated_delivery_date, shipping_zip_code_prefix, tenant_id
+        )
+        VALUES (
+            $1, $2::VARCHAR, $3, $4, $5, $6, $7, $8,
+            (
+                SELECT zip_code_prefix FROM customer_addresses
+                WHERE customer_id = $2::VARCHAR
+                  AND tenant_id = $10::VARCHAR
+                  AND (address_id = $9 OR ($9 IS NULL AND is_default))
+            ),
+            $10::VARCHAR
+        )
+        RETURNING
+            order_id AS "order_id: OrderId", customer_id AS "customer_id: CustomerId",
+            order_status AS "order_status: OrderStatus",
+            order_purchase_timestamp, order_approved_at,
+            order_delivered_carrier_date, order_delivered_customer_date,
+            order_estimated_delivery_date
+        "#,
+        id.as_str(),
+        dto.customer_id.as_str(),
+        dto.order_status as OrderStatus,
+        dto.order_purchase_timestamp,
+        dto.order_approved_at,
+        dto.order_delivered_carrier_date,
+        dto.order_delivered_customer_date,
+        dto.order_estimated_delivery_date,
+        dto.shipping_address_id,
+        tenant.as_str(),
+    )
+    .fetch_one(executor)
+    .await
+    .map_err(|e| {
+        error!("Error creating order: {:?}", e);
+        e
+    })
+}
+
+#[async_trait]
+impl OrderRepository for PgOrderRepository {
+    async fn create(&self, id: &OrderId, dto: CreateOrderDto) -> SqlxResult<Order> {
+        let dto = &dto;
+        self.retry
+            .write(|| async move { insert_order(&self.pool, &self.tenant, id, dto).await })
+            .await
+    }
+
+    async fn create_many(
+        &self,
+        rows: Vec<(OrderId, CreateOrderDto)>,
+    ) -> SqlxResult<Vec<SqlxResult<Order>>> {
+        insert_each(&self.pool, rows, |conn, (id, dto)| {
+            let tenant = self.tenant.clone();
+            Box::pin(async move { insert_order(conn, &tenant, &id, &dto).await })
+        })
+        .await
+    }
+
+    async fn add_item_with_allocation(
+        &self,
+        order_id: &OrderId,
+        dto: AddItemToOrderDto,
+        destination_zip_code_prefix: &str,
+    ) -> SqlxResult<Option<(OrderItem, Option<StockAllocation>)>> {
+        let dto = &dto;
+        self.retry
+            .write(|| async move {
+                let result = async {
+                    let mut tx = self.pool.begin().await?;
+
+                    let tracked = sqlx::query_scalar!(
+                        r#"
+                        SELECT EXISTS(
+                            SELECT 1
+                            FROM location_stock ls
+                            JOIN stock_locations l ON l.location_id = ls.location_id
+                            WHERE l.seller_id = $1 AND ls.product_id = $2 AND ls.tenant_id = $3
+                        ) AS "tracked!"
+                        "#,
+                        dto.seller_id.as_str(),
+                        dto.product_id.as_str(),
+                        self.tenant.as_str(),
+                    )
+                    .fetch_one(&mut *tx)
+                    .await?;
+
+                    // Same nearest-location choice as PgInventoryRepository::allocate.
+                    let allocation = if tracked {
+                        let Some(allocation) = sqlx::query_as!(
+                            StockAllocation,
+                            r#"
+                            WITH candidate AS (
+                                SELECT ls.location_id
+                                FROM location_stock ls
+                                JOIN stock_locations l ON l.location_id = ls.location_id
+                                WHERE l.seller_id = $1
+                                  AND ls.product_id = $2
+                                  AND ls.quantity >= 1
+                                  AND ls.tenant_id = $4
+                                ORDER BY
+                                    abs(
+                                        NULLIF(regexp_replace(l.zip_code_prefix, '\D', '', 'g'), '')::bigint
+                                        - NULLIF(regexp_replace($3, '\D', '', 'g'), '')::bigint
+                                    ) NULLS LAST,
+                                    l.location_id
+                                LIMIT 1
+                                FOR UPDATE OF ls
+                            )
+                            UPDATE location_stock ls
+                            SET quantity = ls.quantity - 1, updated_at = NOW()
+                            FROM candidate
+                            WHERE ls.location_id = candidate.location_id AND ls.product_id = $2
+                            RETURNING
+                                ls.location_id, ls.product_id AS "product_id: ProductId", ls.quantity
+                            "#,
+                            dto.seller_id.as_str(),
+                            dto.product_id.as_str(),
+                            destination_zip_code_prefix,
+                            self.tenant.as_str(),
+                        )
+                        .fetch_optional(&mut *tx)
+                        .await?
+                        else {
+                            return Ok(None);
+                        };
+                        Some(allocation)
+                    } else {
+                        None
+                    };
+
+                    let item = sqlx::query_as!(
+                        OrderItem,
+                        r#"
+                        INSERT INTO order_items (
+                            order_item_id, order_id, product_id, seller_id,
+                            shipping_limit_date, price, freight_value, tenant_id
+                        )
+                        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                        RETURNING
+                            order_item_id, order_id AS "order_id: OrderId",
+                            product_id AS "product_id: ProductId", seller_id AS "seller_id: SellerId",
+                            shipping_limit_date, price, freight_value,
+                            'BRL'::VARCHAR AS "currency!: Currency"
+                        "#,
+                        dto.order_item_id,
+                        order_id.as_str(),
+                        dto.product_id.as_str(),
+                        dto.seller_id.as_str(),
+                        dto.shipping_limit_date,
+                        dto.price,
+                        dto.freight_value,
+                        self.tenant.as_str(),
+                    )
+                    .fetch_one(&mut *tx)
+                    .await?;
+
+                    tx.commit().await?;
+                    Ok(Some((item, allocation)))
+                }
+                .await;
+
+                if let Err(e) = &result {
+                    tracing::error!("Error adding item to order: {:?}", e);
+                }
+                result
+            })
+            .await
+    }
+
+    async fn find_all(
+        &self,
+        filter: &OrderFilter,
+        pagination: &PaginationParams,
+        total: TotalMode,
+    ) -> SqlxResult<(Vec<Order>, Total)> {
+        self.reads
+            .read(&self.retry, |pool| {
+                cancel_on_drop(pool, async move |conn| {
+                    let (limit, offset, _, _) = pagination.normalize();
+
+                    let query = self.page_query(
+                        r#"
+                            order_id, customer_id, order_status,
+                            order_purchase_timestamp, order_approved_at,
+                            order_delivered_carrier_date, order_delivered_customer_date,
+                            order_estimated_delivery_date
+                        "#,
+                        total,
+                    );
+                    let rows = bind_order_filter(
+                        sqlx::query_as::<_, Counted<Order>>(&query),
+                        filter,
+                        &self.tenant,
+                    )
+                    .bind(limit)
+                    .bind(offset)
+                    .fetch_all(&mut *conn)
+                    .await
+                    .map_err(|e| {
+                        tracing::error!("Error fetching orders: {:?}", e);
+                        e
+                    })?;
+
+                    let (orders, window_total) = split_counted(rows, offset);
+                    let total = match (total, window_total) {
+                        (TotalMode::Exact, Some(count)) => Total::Exact(count),
+                        (mode, _) => self.total(conn, filter, mode).await?,
+                    };
+
+                    Ok((orders, total))
+                })
+            })
+            .await
+    }
+
+    async fn find_all_sparse(
+        &self,
+        filter: &OrderFilter,
+        pagination: &PaginationParams,
+        fields: &[&'static str],
+        total: TotalMode,
+    ) -> SqlxResult<(Vec<SparseRow>, Total)> {
+        self.reads
+            .read(&self.retry, |pool| {
+                cancel_on_drop(pool, async move |conn| {
+                    let (limit, offset, _, _) = pagination.normalize();
+
+                    let query = self.page_query(&sparse_columns(fields, &[]), total);
+                    let rows = bind_order_filter(
+                        sqlx::query_as::<_, Counted<(Json<SparseRow>,)>>(&query),
+                        filter,
+                        &self.tenant,
+                    )
+                    .bind(limit)
+                    .bind(offset)
+                    .fetch_all(&mut *conn)
+                    .await
+                    .map_err(|e| {
+                        error!("Error fetching sparse orders: {:?}", e);
+                        e
+                    })?;
+
+                    let (rows, window_total) = split_counted(rows, offset);
+                    let total = match (total, window_total) {
+                        (TotalMode::Exact, Some(count)) => Total::Exact(count),
+                        (mode, _) => self.total(conn, filter, mode).await?,
+                    };
+
+                    Ok((rows.into_iter().map(|(row,)| row.0).collect(), total))
+                })
+            })
+            .await
+    }
+
+    async fn find_by_id(&self, id: &OrderId) -> SqlxResult<Option<Order>> {
+        self.reads
+            .read(&self.retry, |pool| async move {
+                sqlx::query_as!(
+                    Order,
+                    r#"
+                    SELECT
+                        order_id AS "order_id!: OrderId",
+                        customer_id AS "customer_id!: CustomerId",
+                        order_status AS "order_status!: OrderStatus",
+                        order_purchase_timestamp AS "order_purchase_timestamp!",
+                        order_approved_at AS "order_approved_at!",
+                        order_delivered_carrier_date, order_delivered_customer_date,
+                        order_estimated_delivery_date AS "order_estimated_delivery_date!"
+                    FROM orders WHERE order_id = $1 AND tenant_id = $2
+                    UNION ALL
+                    SELECT
+                        order_id, customer_id, order_status,
+                        order_purchase_timestamp, order_approved_at,
+                        order_delivered_carrier_date, order_delivered_customer_date,
+                        order_estimated_delivery_date
+                    FROM orders_archive WHERE order_id = $1 AND tenant_id = $2
+                    "#,
+                    id.as_str(),
+                    self.tenant.as_str(),
+                )
+                .fetch_optional(pool)
+                .await
+                .map_err(|e| {
+                    tracing::error!("Error fetching order by id: {:?}", e);
+                    e
+                })
+            })
+            .await
+    }
+
+    async fn find_by_customer_id(
+        &self,
+        customer_id: &CustomerId,
+        pagination: &PaginationParams,
+    ) -> SqlxResult<(Vec<Order>, i64)> {
+        self.reads
+            .read(&self.retry, |pool| async move {
+                let (limit, offset, _, _) = pagination.normalize();
+
+                let rows = sqlx::query!(
+                    r#"
+                    SELECT
+                        order_id AS "order_id: OrderId",
+                        customer_id AS "customer_id: CustomerId",
+                        order_status AS "order_status: OrderStatus",
+                        order_purchase_timestamp, order_approved_at,
+                        order_delivered_carrier_date, order_delivered_customer_date,
+                        order_estimated_delivery_date,
+                        COUNT(*) OVER () AS total_count
+                    FROM orders
+                    WHERE customer_id = $1 AND tenant_id = $4
+                    ORDER BY order_purchase_timestamp DESC
+                    LIMIT $2 OFFSET $3
+                    "#,
+                    customer_id.as_str(),
+                    limit,
+                    offset,
+                    self.tenant.as_str(),
+                )
+                .fetch_all(pool)
+                .await
+                .map_err(|e| {
+                    error!("Error fetching orders for customer: {:?}", e);
+                    e
+                })?
+                .into_iter()
+                .map(|row| Counted {
+                    row: Order {
+                        order_id: row.order_id,
+                        customer_id: row.customer_id,
+                        order_status: row.order_status,
+                        order_purchase_timestamp: row.order_purchase_timestamp,
+                        order_approved_at: row.order_approved_at,
+                        order_delivered_carrier_date: row.order_delivered_carrier_date,
+                        order_delivered_customer_date: row.order_delivered_customer_date,
+                        order_estimated_delivery_date: row.order_estimated_delivery_date,
+                    },
+                    total_count: row.total_count,
+                })
+                .collect();
+
+                let (orders, total_count) = match split_counted(rows, offset) {
+                    (orders, Some(total_count)) => (orders, total_count),
+                    // Past the last page no row carries the window total.
+                    (orders, None) => {
+                        let count = sqlx::query_scalar!(
+                            r#"
+                            SELECT COUNT(*) AS "count!" FROM orders
+                            WHERE customer_id = $1 AND tenant_id = $2
+                            "#,
+                            customer_id.as_str(),
+                            self.tenant.as_str(),
+                        )
+                        .fetch_one(pool)
+                        .await
+                        .map_err(|e| {
+                            error!("Error counting orders for customer: {:?}", e);
+                            e
+                        })?;
+                        (orders, count)
+                    }
+                };
+
+                Ok((orders, total_count))
+            })
+            .await
+    }
+
+    async fn find_destination_zip_code_prefix(
+        &self,
+        order_id: &OrderId,
+    ) -> SqlxResult<Option<String>> {
+        self.reads
+            .read(&self.retry, |pool| async move {
+                sqlx::query_scalar!(
+                    r#"
+                    SELECT COALESCE(o.shipping_zip_code_prefix, c.customer_zip_code_prefix) AS "zip!"
+                    FROM orders o
+                    JOIN customers c ON c.customer_id = o.customer_id
+                    WHERE o.order_id = $1 AND o.tenant_id = $2
+                    "#,
+                    order_id.as_str(),
+                    self.tenant.as_str(),
+                )
+                .fetch_optional(pool)
+                .await
+                .map_err(|e| {
+                    error!("Error fetching order destination: {:?}", e);
+                    e
+                })
+            })
+            .await
+    }
+
+    /// Draws a reproducible sample: orders are ranked inside each stratum by a hash of their id
+    /// and the seed, then the lowest relative ranks are taken across strata, so every stratum
+    /// contributes in proportion to its size.
+    async fn sample(
+        &self,
+        strata: &[SampleStratum],
+        size: i64,
+        seed: i64,
+    ) -> SqlxResult<Vec<Order>> {
+        self.reads
+            .read(&self.retry, |pool| async move {
+                let partition = if strata.is_empty() {
+                    "true".to_string()
+                } else {
+                    strata
+                        .iter()
+                        .map(|stratum| stratum.column())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                };
+
+                sqlx::query_as::<_, Order>(&format!(
+                    r#"
+                    WITH ranked AS (
+                        SELECT
+                            o.order_id, o.customer_id, o.order_status,
+                            o.order_purchase_timestamp, o.order_approved_at,
+                            o.order_delivered_carrier_date, o.order_delivered_customer_date,
+                            o.order_estimated_delivery_date,
+                            md5(o.order_id || ':' || $2::text) AS sample_key,
+                            row_number() OVER (
+                                PARTITION BY {partition}
+                                ORDER BY md5(o.order_id || ':' || $2::text)
+                            ) AS stratum_rank,
+                            count(*) OVER (PARTITION BY {partition}) AS stratum_size
+                        FROM orders o
+                        JOIN customers c ON c.customer_id = o.customer_id
+                        WHERE o.tenant_id = $3
+                    )
+                    SELECT
+                        order_id, customer_id, order_status,
+                        order_purchase_timestamp, order_approved_at,
+                        order_delivered_carrier_date, order_delivered_customer_date,
+                        order_estimated_delivery_date
+                    FROM ranked
+                    ORDER BY stratum_rank::float8 / stratum_size, sample_key
+                    LIMIT $1
+                    "#
+                ))
+                .bind(size)
+                .bind(seed)
+                .bind(self.tenant.as_str())
+                .fetch_all(pool)
+                .await
+                .map_err(|e| {
+                    error!("Error sampling orders: {:?}", e);
+                    e
+                })
+            })
+            .await
+    }
+
+    async fn find_item_origins(&self, order_id: &OrderId) -> SqlxResult<Vec<OrderItemOrigin>> {
+        self.reads
+            .read(&self.retry, |pool| async move {
+                let rows = sqlx::query!(
+                    r#"
+                    SELECT
+                        oi.order_item_id, oi.order_id AS "order_id: OrderId",
+                        oi.product_id AS "product_id: ProductId",
+                        oi.seller_id AS "seller_id: SellerId",
+                        oi.shipping_limit_date, oi.price AS "price: Money",
+                        oi.freight_value AS "freight_value: Money",
+                        s.seller_zip_code_prefix
+                    FROM order_items oi
+                    JOIN sellers s ON s.seller_id = oi.seller_id
+                    WHERE oi.order_id = $1 AND oi.tenant_id = $2
+                    ORDER BY oi.order_item_id
+                    "#,
+                    order_id.as_str(),
+                    self.tenant.as_str(),
+                )
+                .fetch_all(pool)
+                .await
+                .map_err(|e| {
+                    error!("Error fetching order items with origin: {:?}", e);
+                    e
+                })?;
+                Ok(rows
+                    .into_iter()
+                    .map(|row| OrderItemOrigin {
+                        item: OrderItem {
+                            order_item_id: row.order_item_id,
+                            order_id: row.order_id,
+                            product_id: row.product_id,
+                            seller_id: row.seller_id,
+                            shipping_limit_date: row.shipping_limit_date,
+                            price: row.price,
+                            freight_value: row.freight_value,
+                            currency: Money::CURRENCY,
+                        },
+                        seller_zip_code_prefix: row.seller_zip_code_prefix,
+                    })
+                    .collect())
+            })
+            .await
+    }
+
+    #[instrument(skip(self, items, amendment), fields(order_id = %order_id))]
+    async fn apply_amendment(
+        &self,
+        order_id: &OrderId,
+        actor: &str,
+        shipping_zip_code_prefix: Option<&str>,
+        items: &[(ProductId, OrderItem)],
+        amendment: NewOrderAmendment,
+    ) -> SqlxResult<OrderAmendment> {
+        let shipping_zip_code_prefix = &shipping_zip_code_prefix;
+        let amendment = &amendment;
+        self.retry
+            .write(|| async move {
+                let result = async {
+                    let mut tx = self.pool.begin().await?;
+
+                    if let Some(zip) = shipping_zip_code_prefix {
+                        sqlx::query!(
+                            r#"
+                            UPDATE orders SET shipping_zip_code_prefix = $2
+                            WHERE order_id = $1 AND tenant_id = $3
+                            "#,
+                            order_id.as_str(),
+                            zip,
+                            self.tenant.as_str(),
+                        )
+                        .execute(&mut *tx)
+                        .await?;
+                    }
+
+                    for (previous_product_id, item) in items {
+                        sqlx::query!(
+                            r#"
+                            UPDATE order_items
+                            SET product_id = $4, price = $5, freight_value = $6
+                            WHERE order_id = $1 AND order_item_id = $2 AND product_id = $3
+                              AND seller_id = $7 AND tenant_id = $8
+                            "#,
+                            order_id.as_str(),
+                            item.order_item_id,
+                            previous_product_id.as_str(),
+                            item.product_id.as_str(),
+                            item.price.amount(),
+                            item.freight_value.amount(),
+                            item.seller_id.as_str(),
+                            self.tenant.as_str(),
+                        )
+                        .execute(&mut *tx)
+                        .await?;
+                    }
+
+                    let recorded = sqlx::query_as!(
+                        OrderAmendment,
+                        r#"
+                        INSERT INTO order_amendments (
+                            order_id, actor, changes,
+                            previous_freight, new_freight, previous_tax, new_tax, tenant_id
+                        )
+                        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                        RETURNING
+                            amendment_id, order_id, actor, changes,
+                            previous_freight, new_freight, previous_tax, new_tax, created_at,
+                            'BRL'::VARCHAR AS "currency!: Currency"
+                        "#,
+                        order_id.as_str(),
+                        actor,
+                        amendment.changes,
+                        amendment.previous_freight,
+                        amendment.new_freight,
+                        amendment.previous_tax,
+                        amendment.new_tax,
+                        self.tenant.as_str(),
+                    )
+                    .fetch_one(&mut *tx)
+                    .await?;
+
+                    tx.commit().await?;
+                    Ok(recorded)
+                }
+                .await;
+
+                match &result {
+                    Ok(_) => info!("Order amended successfully"),
+                    Err(e) => error!("Error amending order: {:?}", e),
+                }
+
+                result
+            })
+            .await
+    }
+
+    async fn find_amendments(&self, order_id: &OrderId) -> SqlxResult<Vec<OrderAmendment>> {
+        self.reads
+            .read(&self.retry, |pool| async move {
+                sqlx::query_as!(
+                    OrderAmendment,
+                    r#"
+                    SELECT
+                        amendment_id, order_id, actor, changes,
+                        previous_freight, new_freight, previous_tax, new_tax, created_at,
+                        'BRL'::VARCHAR AS "currency!: Currency"
+                    FROM order_amendments
+                    WHERE order_id = $1 AND tenant_id = $2
+                    ORDER BY created_at, amendment_id
+                    "#,
+                    order_id.as_str(),
+                    self.tenant.as_str(),
+                )
+                .fetch_all(pool)
+                .await
+                .map_err(|e| {
+                    error!("Error fetching order amendments: {:?}", e);
+                    e
+                })
+            })
+            .await
+    }
+
+    async fn find_status(&self, id: &OrderId) -> SqlxResult<Option<OrderStatusChange>> {
+        self.reads
+            .read(&self.retry, |pool| async move {
+                sqlx::query_as!(
+                    OrderStatusChange,
+                    r#"
+                    SELECT
+                        order_id AS "order_id: OrderId",
+                        order_status AS "order_status: OrderStatus",
+                        status_version
+                    FROM orders WHERE order_id = $1 AND tenant_id = $2
+                    "#,
+                    id.as_str(),
+                    self.tenant.as_str(),
+                )
+                .fetch_optional(pool)
+                .await
+                .map_err(|e| {
+                    error!("Error fetching order status: {:?}", e);
+                    e
+                })
+            })
+            .await
+    }
+
+    fn stream_review_texts(&self) -> BoxStream<'_, SqlxResult<ReviewText>> {
+        sqlx::query_as!(
+            ReviewText,
+            r#"
+            SELECT review_id, review_score, review_comment_message AS "review_comment_message!"
+            FROM reviews
+            WHERE NULLIF(TRIM(review_comment_message), '') IS NOT NULL AND tenant_id = $1
+            ORDER BY review_creation_date, review_id
+            "#,
+            self.tenant.as_str(),
+        )
+        .fetch(&self.pool)
+    }
+
+    fn stream_all(&self) -> BoxStream<'_, SqlxResult<Order>> {
+        sqlx::query_as!(
+            Order,
+            r#"
+            SELECT
+                order_id AS "order_id: OrderId", customer_id AS "customer_id: CustomerId",
+                order_status AS "order_status: OrderStatus",
+                order_purchase_timestamp, order_approved_at,
+                order_delivered_carrier_date, order_delivered_customer_date,
+                order_estimated_delivery_date
+            FROM orders
+            WHERE tenant_id = $1
+            ORDER BY order_purchase_timestamp, order_id
+            "#,
+            self.tenant.as_str(),
+        )
+        .fetch(&self.pool)
+    }
+
+    async fn find_by_customer_unique_id(&self, unique_id: &str) -> SqlxResult<Vec<Order>> {
+        self.reads
+            .read(&self.retry, |pool| async move {
+                sqlx::query_as!(
+                    Order,
+                    r#"
+                    SELECT
+                        o.order_id AS "order_id: OrderId", o.customer_id AS "customer_id: CustomerId",
+                        o.order_status AS "order_status: OrderStatus",
+                        o.order_purchase_timestamp, o.order_approved_at,
+                        o.order_delivered_carrier_date, o.order_delivered_customer_date,
+                        o.order_estimated_delivery_date
+                    FROM orders o
+                    JOIN customers c ON c.customer_id = o.customer_id
+                    WHERE c.customer_unique_id = $1 AND c.deleted_at IS NULL AND c.tenant_id = $2
+                    ORDER BY o.order_purchase_timestamp DESC, o.order_id
+                    "#,
+                    unique_id,
+                    self.tenant.as_str(),
+                )
+                .fetch_all(pool)
+                .await
+                .map_err(|e| {
+                    error!("Error fetching orders by customer unique id: {:?}", e);
+                    e
+                })
+            })
+            .await
+    }
+
+    async fn find_by_customer_id_after(
+        &self,
+        customer_id: &CustomerId,
+        after: Option<&Order>,
+        limit: i64,
+    ) -> SqlxResult<Vec<Order>> {
+        let after_timestamp = after.map(|order| order.order_purchase_timestamp);
+        let after_id = after.map(|order| order.order_id.as_str());
+        self.reads
+            .read(&self.retry, |pool| async move {
+                sqlx::query_as!(
+                    Order,
+                    r#"
+                    SELECT
+                        order_id AS "order_id: OrderId", customer_id AS "customer_id: CustomerId",
+                        order_status AS "order_status: OrderStatus",
+                        order_purchase_timestamp, order_approved_at,
+                        order_delivered_carrier_date, order_delivered_customer_date,
+                        order_estimated_delivery_date
+                    FROM orders
+                    WHERE customer_id = $1 AND tenant_id = $2
+                        AND ($3::TIMESTAMP IS NULL
+                            OR (order_purchase_timestamp, order_id) > ($3, $4::VARCHAR))
+                    ORDER BY order_purchase_timestamp, order_id
+                    LIMIT $5
+                    "#,
+                    customer_id.as_str(),
+                    self.tenant.as_str(),
+                    after_timestamp,
+                    after_id,
+                    limit,
+                )
+                .fetch_all(pool)
+                .await
+                .map_err(|e| {
+                    error!("Error fetching a page of customer orders: {:?}", e);
+                    e
+                })
+            })
+            .await
+    }
+
+    async fn find_products_by_order_id(&self, id: &OrderId) -> SqlxResult<Vec<OrderProduct>> {
+        self.reads
+            .read(&self.retry, |pool| async move {
+                sqlx::query_as!(
+                    OrderProduct,
+                    r#"
+                    SELECT
+                        p.product_id,
+                        p.product_category_name,
+                        p.product_name_lenght,
+                        p.product_description_lenght,
+                        p.product_photos_qty,
+                        p.product_weight_g,
+                        p.product_length_cm,
+                        p.product_height_cm,
+                        p.product_width_cm,
+                        oi.shipping_limit_date AS "shipping_limit_date!",
+                        oi.price AS "price!",
+                        oi.freight_value AS "freight_value!",
+                        'BRL'::VARCHAR AS "currency!: Currency"
+                    FROM products p
+                    INNER JOIN (
+                        SELECT product_id, shipping_limit_date, price, freight_value
+                        FROM order_items WHERE order_id = $1 AND tenant_id = $2
+                        UNION ALL
+                        SELECT product_id, shipping_limit_date, price, freight_value
+                        FROM order_items_archive WHERE order_id = $1 AND tenant_id = $2
+                    ) oi ON p.product_id = oi.product_id
+                    "#,
+                    id.as_str(),
+                    self.tenant.as_str(),
+                )
+                .fetch_all(pool)
+                .await
+                .map_err(|e| {
+                    tracing::error!("Error fetching products for order: {:?}", e);
+                    e
+                })
+            })
+            .await
+    }
+
+    async fn find_products_by_order_ids(
+        &self,
+        ids: &[OrderId],
+    ) -> SqlxResult<Vec<(OrderId, OrderProduct)>> {
+        let ids: Vec<String> = ids.iter().map(|id| id.as_str().to_owned()).collect();
+        let ids = &ids;
+        self.reads
+            .read(&self.retry, |pool| async move {
+                let rows = sqlx::query!(
+                    r#"
+                    SELECT
+                        oi.order_id AS "order_id!: OrderId",
+                        p.product_id AS "product_id: ProductId",
+                        p.product_category_name,
+                        p.product_name_lenght,
+                        p.product_description_lenght,
+                        p.product_photos_qty,
+                        p.product_weight_g,
+                        p.product_length_cm,
+                        p.product_height_cm,
+                        p.product_width_cm,
+                        oi.shipping_limit_date AS "shipping_limit_date!",
+                        oi.price AS "price!: Money",
+                        oi.freight_value AS "freight_value!: Money"
+                    FROM products p
+                    INNER JOIN (
+                        SELECT order_id, product_id, shipping_limit_date, price, freight_value
+                        FROM order_items WHERE order_id = ANY($1) AND tenant_id = $2
+                        UNION ALL
+                        SELECT order_id, product_id, shipping_limit_date, price, freight_value
+                        FROM order_items_archive WHERE order_id = ANY($1) AND tenant_id = $2
+                    ) oi ON p.product_id = oi.product_id
+                    "#,
+                    ids.as_slice(),
+                    self.tenant.as_str(),
+                )
+                .fetch_all(pool)
+                .await
+                .map_err(|e| {
+                    tracing::error!("Error fetching products for orders: {:?}", e);
+                    e
+                })?;
+                Ok(rows
+                    .into_iter()
+                    .map(|row| {
+                        let product = OrderProduct {
+                            product_id: row.product_id,
+                            product_category_name: row.product_category_name,
+                            product_name_lenght: row.product_name_lenght,
+                            product_description_lenght: row.product_description_lenght,
+                            product_photos_qty: row.product_photos_qty,
+                            product_weight_g: row.product_weight_g,
+                            product_length_cm: row.product_length_cm,
+                            product_height_cm: row.product_height_cm,
+                            product_width_cm: row.product_width_cm,
+                            shipping_limit_date: row.shipping_limit_date,
+                            price: row.price,
+                            freight_value: row.freight_value,
+                            currency: Money::CURRENCY,
+                        };
+                        (row.order_id, product)
+                    })
+                    .collect())
+            })
+            .await
+    }
+
+    async fn find_payments_by_order_id(&self, id: &OrderId) -> SqlxResult<Vec<Payment>> {
+        self.reads
+            .read(&self.retry, |pool| async move {
+                sqlx::query_as!(
+                    Payment,
+                    r#"
+                    SELECT
+                        order_id AS "order_id!: OrderId",
+                        payment_sequential AS "payment_sequential!",
+                        payment_type AS "payment_type!: PaymentType",
+                        payment_installments AS "payment_installments!",
+                        payment_value AS "payment_value!",
+                        'BRL'::VARCHAR AS "currency!: Currency"
+                    FROM payments
+                    WHERE order_id = $1 AND tenant_id = $2
+                    UNION ALL
+                    SELECT
+                        order_id, payment_sequential, payment_type,
+                        payment_installments, payment_value,
+                        'BRL'
+                    FROM payments_archive
+                    WHERE order_id = $1 AND tenant_id = $2
+                    "#,
+                    id.as_str(),
+                    self.tenant.as_str(),
+                )
+                .fetch_all(pool)
+                .await
+                .map_err(|e| {
+                    tracing::error!("Error fetching payments for order: {:?}", e);
+                    e
+                })
+            })
+            .await
+    }
+
+    async fn find_payments_by_order_ids(&self, ids: &[OrderId]) -> SqlxResult<Vec<Payment>> {
+        let ids: Vec<String> = ids.iter().map(|id| id.as_str().to_owned()).collect();
+        let ids = &ids;
+        self.reads
+            .read(&self.retry, |pool| async move {
+                sqlx::query_as!(
+                    Payment,
+                    r#"
+                    SELECT
+                        order_id AS "order_id!: OrderId",
+                        payment_sequential AS "payment_sequential!",
+                        payment_type AS "payment_type!: PaymentType",
+                        payment_installments AS "payment_installments!",
+                        payment_value AS "payment_value!",
+                        'BRL'::VARCHAR AS "currency!: Currency"
+                    FROM payments
+                    WHERE order_id = ANY($1) AND tenant_id = $2
+                    UNION ALL
+                    SELECT
+                        order_id, payment_sequential, payment_type,
+                        payment_installments, payment_value,
+                        'BRL'
+                    FROM payments_archive
+                    WHERE order_id = ANY($1) AND tenant_id = $2
+                    "#,
+                    ids.as_slice(),
+                    self.tenant.as_str(),
+                )
+                .fetch_all(pool)
+                .await
+                .map_err(|e| {
+                    tracing::error!("Error fetching payments for orders: {:?}", e);
+                    e
+                })
+            })
+            .await
+    }
+
+    async fn create_refund(&self, refund: NewRefund) -> SqlxResult<Option<Refund>> {
+        let refund = &refund;
+        self.retry
+            .write(|| async move {
+                let result = async {
+                    let mut tx = self.pool.begin().await?;
+
+                    // Locking the payment queues concurrent refunds of it, so each one's total below
+                    // sees the refunds committed before it.
+                    let payment = sqlx::query_scalar!(
+                        r#"
+                        SELECT payment_value
+                        FROM payments
+                        WHERE order_id = $1 AND payment_sequential = $2 AND tenant_id = $3
+                        FOR UPDATE
+                        "#,
+                        refund.order_id.as_str(),
+                        refund.payment_sequential,
+                        self.tenant.as_str(),
+                    )
+                    .fetch_optional(&mut *tx)
+                    .await?;
+                    let Some(payment_value) = payment else {
+                        return Ok(None);
+                    };
+
+                    let created = sqlx::query_as!(
+                        Refund,
+                        r#"
+                        INSERT INTO refunds (order_id, payment_sequential, amount, reason, tenant_id)
+                        SELECT $1::VARCHAR, $2::INTEGER, $3::NUMERIC, $4::TEXT, $6::VARCHAR
+                        WHERE (
+                            SELECT COALESCE(SUM(amount), 0)
+                            FROM refunds
+                            WHERE order_id = $1 AND payment_sequential = $2
+                        ) + $3 <= $5
+                        RETURNING
+                            refund_id, order_id AS "order_id: OrderId", payment_sequential, amount,
+                            reason, created_at,
+                            'BRL'::VARCHAR AS "currency!: Currency"
+                        "#,
+                        refund.order_id.as_str(),
+                        refund.payment_sequential,
+                        refund.amount,
+                        refund.reason,
+                        payment_value,
+                        self.tenant.as_str(),
+                    )
+                    .fetch_optional(&mut *tx)
+                    .await?;
+
+                    tx.commit().await?;
+                    Ok(created)
+                }
+                .await;
+
+                if let Err(e) = &result {
+                    error!("Error creating refund: {:?}", e);
+                }
+                result
+            })
+            .await
+    }
+
+    async fn find_refunds_by_order_id(&self, id: &OrderId) -> SqlxResult<Vec<Refund>> {
+        self.reads
+            .read(&self.retry, |pool| async move {
+                sqlx::query_as!(
+                    Refund,
+                    r#"
+                    SELECT
+                        refund_id, order_id AS "order_id: OrderId", payment_sequential, amount, reason,
+                        created_at,
+                        'BRL'::VARCHAR AS "currency!: Currency"
+                    FROM refunds
+                    WHERE order_id = $1 AND tenant_id = $2
+                    ORDER BY created_at, refund_id
+                    "#,
+                    id.as_str(),
+                    self.tenant.as_str(),
+                )
+                .fetch_all(pool)
+                .await
+                .map_err(|e| {
+                    tracing::error!("Error fetching refunds for order: {:?}", e);
+                    e
+                })
+            })
+            .await
+    }
+
+    async fn find_refunds_by_order_ids(&self, ids: &[OrderId]) -> SqlxResult<Vec<Refund>> {
+        let ids: Vec<String> = ids.iter().map(|id| id.as_str().to_owned()).collect();
+        let ids = &ids;
+        self.reads
+            .read(&self.retry, |pool| async move {
+                sqlx::query_as!(
+                    Refund,
+                    r#"
+                    SELECT
+                        refund_id, order_id AS "order_id: OrderId", payment_sequential, amount, reason,
+                        created_at,
+                        'BRL'::VARCHAR AS "currency!: Currency"
+                    FROM refunds
+                    WHERE order_id = ANY($1) AND tenant_id = $2
+                    ORDER BY created_at, refund_id
+                    "#,
+                    ids.as_slice(),
+                    self.tenant.as_str(),
+                )
+                .fetch_all(pool)
+                .await
+                .map_err(|e| {
+                    tracing::error!("Error fetching refunds for orders: {:?}", e);
+                    e
+                })
+            })
+            .await
+    }
+
+    async fn find_payment_mismatches(
+        &self,
+        filter: &ReconciliationFilter,
+        pagination: &PaginationParams,
+    ) -> SqlxResult<(Vec<PaymentMismatch>, i64)> {
+        self.reads
+            .read(&self.retry, |pool| async move {
+                let (limit, offset, _, _) = pagination.normalize();
+
+                let rows = sqlx::query_as::<_, Counted<PaymentMismatch>>(&format!(
+                    r#"
+                    SELECT
+                        order_id, order_status, order_purchase_timestamp, item_count, items_total,
+                        payment_count, payments_total, payments_total - items_total AS difference,
+                        COUNT(*) OVER () AS total_count
+                    FROM ({}) sums
+                    WHERE ABS(payments_total - items_total) > $4
+                    ORDER BY order_purchase_timestamp, order_id
+                    LIMIT $5 OFFSET $6
+                    "#,
+                    ORDER_SUMS
+                ))
+                .bind(filter.purchased_from)
+                .bind(filter.purchased_before)
+                .bind(self.tenant.as_str())
+                .bind(&filter.tolerance)
+                .bind(limit)
+                .bind(offset)
+                .fetch_all(pool)
+                .await
+                .map_err(|e| {
+                    error!("Error fetching payment mismatches: {:?}", e);
+                    e
+                })?;
+
+                let (mismatches, total_count) = match split_counted(rows, offset) {
+                    (mismatches, Some(total_count)) => (mismatches, total_count),
+                    // Past the last page no row carries the window total.
+                    (mismatches, None) => {
+                        let count = sqlx::query_scalar::<_, i64>(&format!(
+                            r#"
+                            SELECT COUNT(*) FROM ({}) sums
+                            WHERE ABS(payments_total - items_total) > $4
+                            "#,
+                            ORDER_SUMS
+                        ))
+                        .bind(filter.purchased_from)
+                        .bind(filter.purchased_before)
+                        .bind(self.tenant.as_str())
+                        .bind(&filter.tolerance)
+                        .fetch_one(pool)
+                        .await
+                        .map_err(|e| {
+                            error!("Error counting payment mismatches: {:?}", e);
+                            e
+                        })?;
+                        (mismatches, count)
+                    }
+                };
+
+                Ok((mismatches, total_count))
+            })
+            .await
+    }
+
+    async fn find_payment_method_stats(
+        &self,
+        filter: &PaymentAnalyticsFilter,
+    ) -> SqlxResult<Vec<PaymentMethodStats>> {
+        self.reads
+            .read(&self.retry, |pool| async move {
+                sqlx::query_as!(
+                    PaymentMethodStats,
+                    r#"
+                    SELECT
+                        p.payment_type AS "payment_type!: PaymentType",
+                        COUNT(*) AS "payment_count!",
+                        COUNT(*)::float8 / SUM(COUNT(*)) OVER () AS "share!",
+                        SUM(p.payment_value) AS "total_value!: Money",
+                        ROUND(AVG(p.payment_installments), 2)::float8 AS "average_installments!",
+                        ROUND(AVG(p.payment_value), 2) AS "average_ticket!: Money",
+                        'BRL'::VARCHAR AS "currency!: Currency"
+                    FROM payments p
+                    JOIN orders o ON o.order_id = p.order_id
+                    WHERE ($1::timestamp IS NULL OR o.order_purchase_timestamp >= $1)
+                      AND ($2::timestamp IS NULL OR o.order_purchase_timestamp < $2)
+                      AND o.tenant_id = $3
+                    GROUP BY p.payment_type
+                    ORDER BY COUNT(*) DESC, p.payment_type
+                    "#,
+                    filter.purchased_from,
+                    filter.purchased_before,
+                    self.tenant.as_str(),
+                )
+                .fetch_all(pool)
+                .await
+                .map_err(|e| {
+                    error!("Error fetching payment method stats: {:?}", e);
+                    e
+                })
+            })
+            .await
+    }
+
+    async fn find_financials(&self, id: &OrderId) -> SqlxResult<Option<OrderFinancials>> {
+        self.reads
+            .read(&self.retry, |pool| async move {
+                sqlx::query_as!(
+                    OrderFinancials,
+                    r#"
+                    SELECT
+                        o.order_id AS "order_id: OrderId",
+                        o.order_status AS "order_status: OrderStatus",
+                        (SELECT COUNT(*) FROM order_items i WHERE i.order_id = o.order_id)
+                            AS "item_count!",
+                        (SELECT COALESCE(SUM(i.price), 0) FROM order_items i
+                         WHERE i.order_id = o.order_id) AS "items_subtotal!",
+                        (SELECT COALESCE(SUM(i.freight_value), 0) FROM order_items i
+                         WHERE i.order_id = o.order_id) AS "freight_total!",
+                        (SELECT COUNT(*) FROM payments p WHERE p.order_id = o.order_id)
+                            AS "payment_count!",
+                        (SELECT COALESCE(SUM(p.payment_value), 0) FROM payments p
+                         WHERE p.order_id = o.order_id) AS "payments_total!",
+                        (SELECT COALESCE(SUM(r.amount), 0) FROM refunds r
+                         WHERE r.order_id = o.order_id) AS "refunds_total!",
+                        c.code AS "coupon_code?",
+                        c.discount_type AS "coupon_discount_type?",
+                        c.value AS "coupon_value?"
+                    FROM orders o
+                    LEFT JOIN order_coupons oc ON oc.order_id = o.order_id
+                    LEFT JOIN coupons c ON c.tenant_id = oc.tenant_id AND c.code = oc.code
+                    WHERE o.order_id = $1 AND o.tenant_id = $2
+                    "#,
+                    id.as_str(),
+                    self.tenant.as_str(),
+                )
+                .fetch_optional(pool)
+                .await
+                .map_err(|e| {
+                    tracing::error!("Error fetching order financials: {:?}", e);
+                    e
+                })
+            })
+            .await
+    }
+
+    async fn find_reviews_by_order_id(&self, id: &OrderId) -> SqlxResult<Vec<Review>> {
+        self.reads
+            .read(&self.retry, |pool| async move {
+                sqlx::query_as!(
+                    Review,
+                    r#"
+                    SELECT
+                        review_id AS "review_id!",
+                        order_id AS "order_id!: OrderId",
+                        review_score AS "review_score!",
+                        review_comment_title,
+                        review_comment_message,
+                        review_creation_date AS "review_creation_date!",
+                        review_answer_timestamp AS "review_answer_timestamp!"
+                    FROM reviews
+                    WHERE order_id = $1 AND tenant_id = $2
+                    UNION ALL
+                    SELECT
+                        review_id, order_id, review_score, review_comment_title,
+                        review_comment_message, review_creation_date, review_answer_timestamp
+                    FROM reviews_archive
+                    WHERE order_id = $1 AND tenant_id = $2
+                    "#,
+                    id.as_str(),
+                    self.tenant.as_str(),
+                )
+                .fetch_all(pool)
+                .await
+                .map_err(|e| {
+                    tracing::error!("Error fetching reviews for order: {:?}", e);
+                    e
+                })
+            })
+            .await
+    }
+
+    async fn find_reviews_by_order_ids(&self, ids: &[OrderId]) -> SqlxResult<Vec<Review>> {
+        let ids: Vec<String> = ids.iter().map(|id| id.as_str().to_owned()).collect();
+        let ids = &ids;
+        self.reads
+            .read(&self.retry, |pool| async move {
+                sqlx::query_as!(
+                    Review,
+                    r#"
+                    SELECT
+                        review_id AS "review_id!",
+                        order_id AS "order_id!: OrderId",
+                        review_score AS "review_score!",
+                        review_comment_title,
+                        review_comment_message,
+                        review_creation_date AS "review_creation_date!",
+                        review_answer_timestamp AS "review_answer_timestamp!"
+                    FROM reviews
+                    WHERE order_id = ANY($1) AND tenant_id = $2
+                    UNION ALL
+                    SELECT
+                        review_id, order_id, review_score, review_comment_title,
+                        review_comment_message, review_creation_date, review_answer_timestamp
+                    FROM reviews_archive
+                    WHERE order_id = ANY($1) AND tenant_id = $2
+                    "#,
+                    ids.as_slice(),
+                    self.tenant.as_str(),
+                )
+                .fetch_all(pool)
+                .await
+                .map_err(|e| {
+                    tracing::error!("Error fetching reviews for orders: {:?}", e);
+                    e
+                })
+            })
+            .await
+    }
+
+    async fn count_by_status(&self) -> SqlxResult<Vec<FilterValue>> {
+        self.reads
+            .read(&self.retry, |pool| async move {
+                sqlx::query_as!(
+                    FilterValue,
+                    r#"
+                    SELECT order_status AS value, COUNT(*) AS "count!"
+                    FROM orders
+                    WHERE tenant_id = $1
+                    GROUP BY order_status
+                    ORDER BY COUNT(*) DESC, value
+                    "#,
+                    self.tenant.as_str(),
+                )
+                .fetch_all(pool)
+                .await
+                .map_err(|e| {
+                    error!("Error counting orders by status: {:?}", e);
+                    e
+                })
+            })
+            .await
+    }
+}
+
+/// `WHERE` clause for [`ProductFilter`] within a tenant, bound by [`bind_product_filter`] as
+/// `$1`..`$17`.
+const PRODUCT_FILTER: &str = r#"
+    ($1::text IS NULL OR product_category_name = $1)
+    AND ($2::int IS NULL OR product_weight_g >= $2)
+    AND ($3::int IS NULL OR product_weight_g <= $3)
+    AND ($4::int IS NULL OR product_length_cm >= $4)
+    AND ($5::int IS NULL OR product_length_cm <= $5)
+    AND ($6::int IS NULL OR product_height_cm >= $6)
+    AND ($7::int IS NULL OR product_height_cm <= $7)
+    AND ($8::int IS NULL OR product_width_cm >= $8)
+    AND ($9::int IS NULL OR product_width_cm <= $9)
+    AND ($10::int IS NULL OR product_photos_qty >= $10)
+    AND ($11::int IS NULL OR product_photos_qty <= $11)
+    AND ($12::bigint IS NULL
+        OR product_length_cm::bigint * product_height_cm * product_width_cm >= $12)
+    AND ($13::bigint IS NULL
+        OR product_length_cm::bigint * product_height_cm * product_width_cm <= $13)
+    AND ($14::bool IS NULL OR active = $14)
+    AND ($15::numeric IS NULL OR price >= $15)
+    AND ($16::numeric IS NULL OR price <= $16)
+    AND tenant_id = $17
+"#;
+
+fn bind_product_filter<'q, O>(
+    query: QueryAs<'q, Postgres, O, PgArguments>,
+    filter: &'q ProductFilter,
+    tenant: &'q TenantId,
+) -> QueryAs<'q, Postgres, O, PgArguments> {
+    query
+        .bind(&filter.category_name)
+        .bind(filter.min_weight_g)
+        .bind(filter.max_weight_g)
+        .bind(filter.min_length_cm)
+        .bind(filter.max_length_cm)
+        .bind(filter.min_height_cm)
+        .bind(filter.max_height_cm)
+        .bind(filter.min_width_cm)
+        .bind(filter.max_width_cm)
+        .bind(filter.min_photos)
+        .bind(filter.max_photos)
+        .bind(filter.min_volume_cm3)
+        .bind(filter.max_volume_cm3)
+        .bind(filter.active)
+        .bind(&filter.min_price)
+        .bind(&filter.max_price)
+        .bind(tenant.as_str())
+}
+
+#[derive(Clone)]
+pub struct PgProductRepository {
+    pool: PgPool,
+    reads: ReadPool,
+    collation: SortCollation,
+    retry: RetryPolicy,
+    tenant: TenantId,
+}
+
+impl PgProductRepository {
+    pub fn new(
+        pool: PgPool,
+        reads: ReadPool,
+        collation: SortCollation,
+        retry: RetryPolicy,
+        tenant: TenantId,
+    ) -> Self {
+        Self {
+            pool,
+            reads,
+            collation,
+            retry,
+            tenant,
+        }
+    }
+
+    async fn total(
+        &self,
+        conn: &mut PgConnection,
+        filter: &ProductFilter,
+        mode: TotalMode,
+    ) -> SqlxResult<Total> {
+        let count = format!("SELECT COUNT(*) FROM products WHERE {}", PRODUCT_FILTER);
+        let plan = format!(
+            "EXPLAIN (FORMAT JSON) SELECT 1 FROM products WHERE {}",
+            PRODUCT_FILTER
+        );
+        count_rows(
+            conn,
+            mode,
+            bind_product_filter(sqlx::query_as(&count), filter, &self.tenant),
+            bind_product_filter(sqlx::query_as(&plan), filter, &self.tenant),
+        )
+        .await
+        .map_err(|e| {
+            error!("Error counting products: {:?}", e);
+            e
+        })
+    }
+
+    /// A listing page selecting `columns` and the window total for `total`, filtered as `$1`..`$17` and paged by `$18`/`$19`.
+    fn page_query(&self, columns: &str, total: TotalMode) -> String {
+        format!(
+            r#"
+            SELECT {}, {}
+            FROM products
+            WHERE {}
+            ORDER BY product_id DESC
+            LIMIT $18 OFFSET $19
+            "#,
+            columns,
+            total_column(total),
+            PRODUCT_FILTER
+        )
+    }
+}
+
+async fn insert_product<'e>(
+    executor: impl PgExecutor<'e>,
+    tenant: &TenantId,
+    id: &ProductId,
+    dto: &CreateProductDto,
+) -> SqlxResult<Product> {
+    sqlx::query_as!(
+        Product,
+        r#"
+        INSERT INTO products (
+            product_id, product_category_name, product_name_lenght,
+            product_description_lenght, product_photos_qty, product_weight_g,
+            product_length_cm, product_height_cm, product_width_cm,
+            product_name, description, price, active, tenant_id
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, COALESCE($13, TRUE), $14)
+        RETURNING
+            product_id AS "product_id: ProductId", product_category_name, product_name_lenght,
+            product_description_lenght, product_photos_qty, product_weight_g,
+            product_length_cm, product_height_cm, product_width_cm,
+            product_name, description, price AS "price: Money", active,
+            'BRL'::VARCHAR AS "currency!: Currency"
+        "#,
+        id.as_str(),
+        dto.product_category_name,
+        dto.product_name_lenght,
+        dto.product_description_lenght,
+        dto.product_photos_qty,
+        dto.product_weight_g,
+        dto.product_length_cm,
+        dto.product_height_cm,
+        dto.product_width_cm,
+        dto.product_name,
+        dto.description,
+        dto.price,
+        dto.active,
+        tenant.as_str(),
+    )
+    .fetch_one(executor)
+    .await
+    .map_err(|e| {
+        error!("Error creating product: {:?}", e);
+        e
+    })
+}
+
+#[async_trait]
+impl ProductRepository for PgProductRepository {
+    async fn create(&self, id: &ProductId, dto: CreateProductDto) -> SqlxResult<Product> {
+        let dto = &dto;
+        self.retry
+            .write(|| async move { insert_product(&self.pool, &self.tenant, id, dto).await })
+            .await
+    }
+
+    async fn create_many(
+        &self,
+        rows: Vec<(ProductId, CreateProductDto)>,
+    ) -> SqlxResult<Vec<SqlxResult<Product>>> {
+        insert_each(&self.pool, rows, |conn, (id, dto)| {
+            let tenant = self.tenant.clone();
+            Box::pin(async move { insert_product(conn, &tenant, &id, &dto).await })
+        })
+        .await
+    }
+
+    async fn find_all(
+        &self,
+        filter: &ProductFilter,
+        pagination: &PaginationParams,
+        total: TotalMode,
+    ) -> SqlxResult<(Vec<Product>, Total)> {
+        self.reads
+            .read(&self.retry, |pool| {
+                cancel_on_drop(pool, async move |conn| {
+                    let (limit, offset, _, _) = pagination.normalize();
+
+                    let query = self.page_query(
+                        r#"
+                            product_id, product_category_name, product_name_lenght,
+                            product_description_lenght, product_photos_qty, product_weight_g,
+                            product_length_cm, product_height_cm, product_width_cm,
+                            product_name, description, price, active
+                        "#,
+                        total,
+                    );
+                    let rows = bind_product_filter(
+                        sqlx::query_as::<_, Counted<Product>>(&query),
+                        filter,
+                        &self.tenant,
+                    )
+                    .bind(limit)
+                    .bind(offset)
+                    .fetch_all(&mut *conn)
+                    .await
+                    .map_err(|e| {
+                        tracing::error!("Error fetching products: {:?}", e);
+                        e
+                    })?;
+
+                    let (products, window_total) = split_counted(rows, offset);
+                    let total = match (total, window_total) {
+                        (TotalMode::Exact, Some(count)) => Total::Exact(count),
+                        (mode, _) => self.total(conn, filter, mode).await?,
+                    };
+
+                    Ok((products, total))
+                })
+            })
+            .await
+    }
+
+    async fn find_all_sparse(
+        &self,
+        filter: &ProductFilter,
+        pagination: &PaginationParams,
+        fields: &[&'static str],
+        total: TotalMode,
+    ) -> SqlxResult<(Vec<SparseRow>, Total)> {
+        self.reads
+            .read(&self.retry, |pool| {
+                cancel_on_drop(pool, async move |conn| {
+                    let (limit, offset, _, _) = pagination.normalize();
+
+                    let query = self
+                        .page_query(&sparse_columns(fields, &[("price", "price::text")]), total);
+                    let rows = bind_product_filter(
+                        sqlx::query_as::<_, Counted<(Json<SparseRow>,)>>(&query),
+                        filter,
+                        &self.tenant,
+                    )
+                    .bind(limit)
+                    .bind(offset)
+                    .fetch_all(&mut *conn)
+                    .await
+                    .map_err(|e| {
+                        error!("Error fetching sparse products: {:?}", e);
+                        e
+                    })?;
+
+                    let (rows, window_total) = split_counted(rows, offset);
+                    let total = match (total, window_total) {
+                        (TotalMode::Exact, Some(count)) => Total::Exact(count),
+                        (mode, _) => self.total(conn, filter, mode).await?,
+                    };
+
+                    Ok((rows.into_iter().map(|(row,)| row.0).collect(), total))
+                })
+            })
+            .await
+    }
+
+    fn stream_all(&self) -> BoxStream<'_, SqlxResult<Product>> {
+        sqlx::query_as!(
+            Product,
+            r#"
+            SELECT
+                product_id AS "product_id: ProductId", product_category_name, product_name_lenght,
+                product_description_lenght, product_photos_qty, product_weight_g,
+                product_length_cm, product_height_cm, product_width_cm,
+                product_name, description, price AS "price: Money", active,
+                'BRL'::VARCHAR AS "currency!: Currency"
+            FROM products
+            WHERE tenant_id = $1
+            ORDER BY product_id
+            "#,
+            self.tenant.as_str(),
+        )
+        .fetch(&self.pool)
+    }
+
+    async fn find_by_id(&self, id: &ProductId) -> SqlxResult<Option<Product>> {
+        self.reads.read(&self.retry, |pool| async move {
+            sqlx::query_as!(
+                Product,
+                r#"
+                SELECT
+                    product_id AS "product_id: ProductId", product_category_name, product_name_lenght,
+                    product_description_lenght, product_photos_qty, product_weight_g,
+                    product_length_cm, product_height_cm, product_width_cm,
+                    product_name, description, price AS "price: Money", active,
+                    'BRL'::VARCHAR AS "currency!: Currency"
+                FROM products WHERE product_id = $1 AND tenant_id = $2
+                "#,
+                id.as_str(),
+                self.tenant.as_str(),
+            )
+            .fetch_optional(pool)
+            .await
+            .map_err(|e| {
+                error!("Error fetching product by id: {:?}", e);
+                e
+            })
+        })
+        .await
+    }
+
+    async fn update(&self, id: &ProductId, dto: UpdateProductDto) -> SqlxResult<Option<Product>> {
+        let dto = &dto;
+        self.retry
+            .write(|| async move {
+                sqlx::query_as!(
+                    Product,
+                    r#"
+                    UPDATE products SET
+                        product_name = COALESCE($2, product_name),
+                        description = COALESCE($3, description),
+                        price = COALESCE($4, price),
+                        active = COALESCE($5, active)
+                    WHERE product_id = $1 AND tenant_id = $6
+                    RETURNING
+                        product_id AS "product_id: ProductId", product_category_name,
+                        product_name_lenght, product_description_lenght, product_photos_qty,
+                        product_weight_g, product_length_cm, product_height_cm, product_width_cm,
+                        product_name, description, price AS "price: Money", active,
+                        'BRL'::VARCHAR AS "currency!: Currency"
+                    "#,
+                    id.as_str(),
+                    dto.product_name,
+                    dto.description,
+                    dto.price,
+                    dto.active,
+                    self.tenant.as_str(),
+                )
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(|e| {
+                    error!("Error updating product: {:?}", e);
+                    e
+                })
+            })
+            .await
+    }
+
+    #[instrument(skip(self, image), fields(product_id = %id))]
+    async fn create_image(
+        &self,
+        id: &ProductId,
+        image: NewProductImage,
+    ) -> SqlxResult<ProductImage> {
+        let image = &image;
+        self.retry
+            .write(|| async move {
+                let result = async {
+                    let mut tx = self.pool.begin().await?;
+
+                    // Counting the photo first locks the product row, so concurrent uploads
+                    // take positions one after the other.
+                    sqlx::query!(
+                        r#"
+                        UPDATE products SET product_photos_qty = product_photos_qty + 1
+                        WHERE product_id = $1 AND tenant_id = $2
+                        "#,
+                        id.as_str(),
+                        self.tenant.as_str(),
+                    )
+                    .execute(&mut *tx)
+                    .await?;
+
+                    let image = sqlx::query_as!(
+                        ProductImage,
+                        r#"
+                        INSERT INTO product_images (
+                            product_id, position, url, storage_key, content_type, size_bytes,
+                            tenant_id
+                        )
+                        VALUES (
+                            $1::VARCHAR,
+                            (
+                                SELECT COALESCE(MAX(position), 0) + 1
+                                FROM product_images WHERE product_id = $1::VARCHAR
+                            ),
+                            $2, $3, $4, $5, $6
+                        )
+                        RETURNING
+                            image_id, product_id AS "product_id: ProductId", position, url,
+                            storage_key, content_type, size_bytes, created_at
+                        "#,
+                        id.as_str(),
+                        image.url,
+                        image.storage_key,
+                        image.content_type,
+                        image.size_bytes,
+                        self.tenant.as_str(),
+                    )
+                    .fetch_one(&mut *tx)
+                    .await?;
+
+                    tx.commit().await?;
+                    Ok(image)
+                }
+                .await;
+
+                if let Err(e) = &result {
+                    error!("Error creating product image: {:?}", e);
+                }
+                result
+            })
+            .await
+    }
+
+    async fn find_images(&self, id: &ProductId) -> SqlxResult<Vec<ProductImage>> {
+        self.reads
+            .read(&self.retry, |pool| async move {
+                sqlx::query_as!(
+                    ProductImage,
+                    r#"
+                    SELECT
+                        image_id, product_id AS "product_id: ProductId", position, url,
+                        storage_key, content_type, size_bytes, created_at
+                    FROM product_images
+                    WHERE product_id = $1 AND tenant_id = $2
+                    ORDER BY position
+                    "#,
+                    id.as_str(),
+                    self.tenant.as_str(),
+                )
+                .fetch_all(pool)
+                .await
+                .map_err(|e| {
+                    error!("Error fetching product images: {:?}", e);
+                    e
+                })
+            })
+            .await
+    }
+
+    #[instrument(skip(self), fields(product_id = %id))]
+    async fn delete_image(
+        &self,
+        id: &ProductId,
+        image_id: i64,
+    ) -> SqlxResult<Option<(ProductImage, chrono::NaiveDateTime)>> {
+        self.retry
+            .write(|| async move {
+                let result = async {
+                    let mut tx = self.pool.begin().await?;
+
+                    sqlx::query!(
+                        r#"
+                        SELECT 1 AS locked FROM products
+                        WHERE product_id = $1 AND tenant_id = $2
+                        FOR UPDATE
+                        "#,
+                        id.as_str(),
+                        self.tenant.as_str(),
+                    )
+                    .fetch_optional(&mut *tx)
+                    .await?;
+
+                    let Some(row) = sqlx::query!(
+                        r#"
+                        DELETE FROM product_images
+                        WHERE product_id = $1 AND image_id = $2 AND tenant_id = $3
+                        RETURNING
+                            image_id, product_id AS "product_id: ProductId", position, url,
+                            storage_key, content_type, size_bytes, created_at,
+                            LOCALTIMESTAMP AS "deleted_at!"
+                        "#,
+                        id.as_str(),
+                        image_id,
+                        self.tenant.as_str(),
+                    )
+                    .fetch_optional(&mut *tx)
+                    .await?
+                    else {
+                        return Ok(None);
+                    };
+
+                    sqlx::query!(
+                        r#"
+                        UPDATE product_images SET position = position - 1
+                        WHERE product_id = $1 AND position > $2
+                        "#,
+                        id.as_str(),
+                        row.position,
+                    )
+                    .execute(&mut *tx)
+                    .await?;
+
+                    sqlx::query!(
+                        r#"
+                        UPDATE products
+                        SET product_photos_qty = GREATEST(product_photos_qty - 1, 0)
+                        WHERE product_id = $1 AND tenant_id = $2
+                        "#,
+                        id.as_str(),
+                        self.tenant.as_str(),
+                    )
+                    .execute(&mut *tx)
+                    .await?;
+
+                    tx.commit().await?;
+                    let image = ProductImage {
+                        image_id: row.image_id,
+                        product_id: row.product_id,
+                        position: row.position,
+                        url: row.url,
+                        storage_key: row.storage_key,
+                        content_type: row.content_type,
+                        size_bytes: row.size_bytes,
+                        created_at: row.created_at,
+                    };
+                    Ok(Some((image, row.deleted_at)))
+                }
+                .await;
+
+                if let Err(e) = &result {
+                    error!("Error deleting product image: {:?}", e);
+                }
+                result
+            })
+            .await
+    }
+
+    async fn count_by_category(&self) -> SqlxResult<Vec<FilterValue>> {
+        self.reads
+            .read(&self.retry, |pool| async move {
+                sqlx::query_as::<_, FilterValue>(&format!(
+                    r#"
+                    SELECT product_category_name AS value, COUNT(*) AS count
+                    FROM products
+                    WHERE tenant_id = $1
+                    GROUP BY product_category_name
+                    ORDER BY COUNT(*) DESC, {}
+                    "#,
+                    self.collation.order_by("product_category_name")
+                ))
+                .bind(self.tenant.as_str())
+                .fetch_all(pool)
+                .await
+                .map_err(|e| {
+                    error!("Error counting products by category: {:?}", e);
+                    e
+                })
+            })
+            .await
+    }
+
+    async fn find_recommended(
+        &self,
+        customer_unique_id: &str,
+        state: &str,
+        limit: i64,
+    ) -> SqlxResult<Vec<RecommendedProduct>> {
+        self.reads
+            .read(&self.retry, |pool| async move {
+                let rows = sqlx::query!(
+                    r#"
+                    WITH purchased AS (
+                        SELECT DISTINCT p.product_id, p.product_category_name
+                        FROM customers c
+                        JOIN orders o ON o.customer_id = c.customer_id
+                        JOIN order_items oi ON oi.order_id = o.order_id
+                        JOIN products p ON p.product_id = oi.product_id
+                        WHERE c.customer_unique_id = $1 AND c.tenant_id = $4
+                    )
+                    SELECT
+                        p.product_id AS "product_id: ProductId", p.product_category_name,
+                        p.product_name_lenght, p.product_description_lenght, p.product_photos_qty,
+                        p.product_weight_g, p.product_length_cm, p.product_height_cm,
+                        p.product_width_cm, p.product_name, p.description,
+                        p.price AS "price: Money", p.active,
+                        COUNT(DISTINCT o.order_id) AS "state_order_count!"
+                    FROM products p
+                    JOIN order_items oi ON oi.product_id = p.product_id
+                    JOIN orders o ON o.order_id = oi.order_id
+                    JOIN customers c ON c.customer_id = o.customer_id
+                    WHERE p.product_category_name IN (SELECT product_category_name FROM purchased)
+                      AND p.product_id NOT IN (SELECT product_id FROM purchased)
+                      AND c.customer_state = $2
+                      AND o.order_status <> 'canceled'
+                      AND o.tenant_id = $4
+                    GROUP BY p.product_id
+                    ORDER BY COUNT(DISTINCT o.order_id) DESC, p.product_id
+                    LIMIT $3
+                    "#,
+                    customer_unique_id,
+                    state,
+                    limit,
+                    self.tenant.as_str(),
+                )
+                .fetch_all(pool)
+                .await
+                .map_err(|e| {
+                    error!("Error fetching recommended products: {:?}", e);
+                    e
+                })?;
+                Ok(rows
+                    .into_iter()
+                    .map(|row| RecommendedProduct {
+                        product: Product {
+                            product_id: row.product_id,
+                            product_category_name: row.product_category_name,
+                            product_name_lenght: row.product_name_lenght,
+                            product_description_lenght: row.product_description_lenght,
+                            product_photos_qty: row.product_photos_qty,
+                            product_weight_g: row.product_weight_g,
+                            product_length_cm: row.product_length_cm,
+                            product_height_cm: row.product_height_cm,
+                            product_width_cm: row.product_width_cm,
+                            product_name: row.product_name,
+                            description: row.description,
+                            price: row.price,
+                            active: row.active,
+                            currency: Money::CURRENCY,
+                        },
+                        state_order_count: row.state_order_count,
+                    })
+                    .collect())
+            })
+            .await
+    }
+}
+
+#[derive(Clone)]
+pub struct PgCategoryRepository {
+    pool: PgPool,
+    reads: ReadPool,
+    retry: RetryPolicy,
+    tenant: TenantId,
+}
+
+impl PgCategoryRepository {
+    pub fn new(pool: PgPool, reads: ReadPool, retry: RetryPolicy, tenant: TenantId) -> Self {
+        Self {
+            pool,
+            reads,
+            retry,
+            tenant,
+        }
+    }
+}
+
+#[async_trait]
+impl CategoryRepository for PgCategoryRepository {
+    async fn create(&self, dto: CreateCategoryDto) -> SqlxResult<Category> {
+        let dto = &dto;
+        self.retry.write(|| async move {
+            sqlx::query_as!(
+                Category,
+                r#"
+                INSERT INTO product_categories (
+                    product_category_name, product_category_name_english, tenant_id
+                )
+                VALUES ($1, $2, $3)
+                RETURNING product_category_name, product_category_name_english, created_at, updated_at
+                "#,
+                dto.product_category_name,
+                dto.product_category_name_english,
+                self.tenant.as_str(),
+            )
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| {
+                error!("Error creating category: {:?}", e);
+                e
+            })
+        })
+        .await
+    }
+
+    async fn find_by_name(&self, name: &str) -> SqlxResult<Option<Category>> {
+        self.reads
+            .read(&self.retry, |pool| async move {
+                sqlx::query_as!(
+                    Category,
+                    r#"
+                    SELECT product_category_name, product_category_name_english, created_at, updated_at
+                    FROM product_categories
+                    WHERE product_category_name = $1 AND tenant_id = $2
+                    "#,
+                    name,
+                    self.tenant.as_str(),
+                )
+                .fetch_optional(pool)
+                .await
+                .map_err(|e| {
+                    error!("Error fetching category by name: {:?}", e);
+                    e
+                })
+            })
+            .await
+    }
+
+    #[instrument(skip(self))]
+    async fn update(&self, name: &str, dto: UpdateCategoryDto) -> SqlxResult<Option<Category>> {
+        let dto = &dto;
+        self.retry
+            .write(|| async move {
+                let result = sqlx::query_as!(
+                    Category,
+                    r#"
+                    UPDATE product_categories
+                    SET product_category_name_english = $2, updated_at = NOW()
+                    WHERE product_category_name = $1 AND tenant_id = $3
+                    RETURNING product_category_name, product_category_name_english, created_at, updated_at
+                    "#,
+                    name,
+                    dto.product_category_name_english,
+                    self.tenant.as_str(),
+                )
+                .fetch_optional(&self.pool)
+                .await;
+
+                match &result {
+                    Ok(Some(_)) => info!("Category updated successfully"),
+                    Ok(None) => info!("Category not found for update"),
+                    Err(e) => error!("Error updating category: {:?}", e),
+                }
+
+                result
+            })
+            .await
+    }
+
+    async fn count_products(&self, name: &str) -> SqlxResult<i64> {
+        self.reads
+            .read(&self.retry, |pool| async move {
+                sqlx::query_scalar!(
+                    r#"
+                    SELECT COUNT(*) AS "count!" FROM products
+                    WHERE product_category_name = $1 AND tenant_id = $2
+                    "#,
+                    name,
+                    self.tenant.as_str(),
+                )
+                .fetch_one(pool)
+                .await
+                .map_err(|e| {
+                    error!("Error counting category products: {:?}", e);
+                    e
+                })
+            })
+            .await
+    }
+
+    #[instrument(skip(self))]
+    async fn delete(&self, name: &str) -> SqlxResult<Option<chrono::NaiveDateTime>> {
+        self.retry
+            .write(|| async move {
+                let result = sqlx::query_scalar!(
+                    r#"
+                    DELETE FROM product_categories
+                    WHERE product_category_name = $1 AND tenant_id = $2
+                    RETURNING LOCALTIMESTAMP AS "deleted_at!"
+                    "#,
+                    name,
+                    self.tenant.as_str(),
+                )
+                .fetch_optional(&self.pool)
+                .await;
+
+                match &result {
+                    Ok(Some(_)) => info!("Deleted category {}", name),
+                    Ok(None) => info!("Category {} not found for deletion", name),
+                    Err(e) => error!("Error deleting category: {:?}", e),
+                }
+
+                result
+            })
+            .await
+    }
+}
+
+#[derive(Clone)]
+pub struct PgAuditRepository {
+    pool: PgPool,
+    reads: ReadPool,
+    retry: RetryPolicy,
+    tenant: TenantId,
+}
+
+impl PgAuditRepository {
+    pub fn new(pool: PgPool, reads: ReadPool, retry: RetryPolicy, tenant: TenantId) -> Self {
+        Self {
+            pool,
+            reads,
+            retry,
+            tenant,
+        }
+    }
+}
+
+#[async_trait]
+impl AuditRepository for PgAuditRepository {
+    async fn record(&self, entry: NewAuditEntry) -> SqlxResult<AuditEntry> {
+        let entry = &entry;
+        self.retry
+            .write(|| async move {
+                sqlx::query_as!(
+                    AuditEntry,
+                    r#"
+                    INSERT INTO audit_log (entity_type, entity_id, action, actor, diff, tenant_id)
+                    VALUES ($1, $2, $3, $4, $5, $6)
+                    RETURNING
+                        audit_id, entity_type, entity_id, action,
+                        actor, diff, created_at
+                    "#,
+                    entry.entity_type,
+                    entry.entity_id,
+                    entry.action.as_str(),
+                    entry.actor,
+                    entry.diff,
+                    self.tenant.as_str(),
+                )
+                .fetch_one(&self.pool)
+                .await
+                .map_err(|e| {
+                    error!("Error recording audit entry: {:?}", e);
+                    e
+                })
+            })
+            .await
+    }
+
+    async fn record_many(&self, entries: Vec<NewAuditEntry>) -> SqlxResult<()> {
+        let mut entity_types = Vec::with_capacity(entries.len());
+        let mut entity_ids = Vec::with_capacity(entries.len());
+        let mut actions = Vec::with_capacity(entries.len());
+        let mut actors = Vec::with_capacity(entries.len());
+        let mut diffs = Vec::with_capacity(entries.len());
+        for entry in entries {
+            entity_types.push(entry.entity_type.to_string());
+            entity_ids.push(entry.entity_id);
+            actions.push(entry.action.as_str().to_string());
+            actors.push(entry.actor);
+            diffs.push(entry.diff);
+        }
+
+        let (entity_types, entity_ids, actions, actors, diffs) =
+            (&entity_types, &entity_ids, &actions, &actors, &diffs);
+        self.retry
+            .write(|| async move {
+                sqlx::query!(
+                    r#"
+                    INSERT INTO audit_log (entity_type, entity_id, action, actor, diff, tenant_id)
+                    SELECT *, $6 FROM UNNEST($1::text[], $2::text[], $3::text[], $4::text[], $5::jsonb[])
+                    "#,
+                    entity_types,
+                    entity_ids,
+                    actions,
+                    actors,
+                    diffs,
+                    self.tenant.as_str(),
+                )
+                .execute(&self.pool)
+                .await
+                .map(|_| ())
+                .map_err(|e| {
+                    error!("Error recording audit entries: {:?}", e);
+                    e
+                })
+            })
+            .await
+    }
+
+    async fn find_all(
+        &self,
+        filter: &AuditFilter,
+        pagination: &PaginationParams,
+    ) -> SqlxResult<(Vec<AuditEntry>, i64)> {
+        self.reads
+            .read(&self.retry, |pool| {
+                cancel_on_drop(pool, async move |conn| {
+                    let (limit, offset, _, _) = pagination.normalize();
+
+                    let rows = sqlx::query_as::<_, Counted<AuditEntry>>(
+                        r#"
+                        SELECT
+                            audit_id, entity_type, entity_id, action,
+                            actor, diff, created_at,
+                            COUNT(*) OVER () AS total_count
+                        FROM audit_log
+                        WHERE ($1::text IS NULL OR entity_type = $1)
+                          AND ($2::text IS NULL OR entity_id = $2)
+                          AND tenant_id = $3
+                        ORDER BY created_at DESC, audit_id DESC
+                        LIMIT $4 OFFSET $5
+                        "#,
+                    )
+                    .bind(&filter.entity_type)
+                    .bind(&filter.entity_id)
+                    .bind(self.tenant.as_str())
+                    .bind(limit)
+                    .bind(offset)
+                    .fetch_all(&mut *conn)
+                    .await
+                    .map_err(|e| {
+                        error!("Error fetching audit entries: {:?}", e);
+                        e
+                    })?;
+
+                    let (entries, total_count) = match split_counted(rows, offset) {
+                        (entries, Some(total_count)) => (entries, total_count),
+                        // Past the last page no row carries the window total.
+                        (entries, None) => {
+                            let count = sqlx::query_scalar!(
+                                r#"
+                                SELECT COUNT(*) AS "count!" FROM audit_log
+                                WHERE ($1::text IS NULL OR entity_type = $1)
+                                  AND ($2::text IS NULL OR entity_id = $2)
+                                  AND tenant_id = $3
+                                "#,
+                                filter.entity_type.as_deref(),
+                                filter.entity_id.as_deref(),
+                                self.tenant.as_str(),
+                            )
+                            .fetch_one(&mut *conn)
+                            .await
+                            .map_err(|e| {
+                                error!("Error counting audit entries: {:?}", e);
+                                e
+                            })?;
+                            (entries, count)
+                        }
+                    };
+
+                    Ok((entries, total_count))
+                })
+            })
+            .await
+    }
+}
+
+#[derive(Clone)]
+pub struct PgEmbeddingRepository {
+    pool: PgPool,
+    tenant: TenantId,
+}
+
+impl PgEmbeddingRepository {
+    pub fn new(pool: PgPool, tenant: TenantId) -> Self {
+        Self { pool, tenant }
+    }
+}
+
+/// Formats an embedding as a pgvector text literal (`[0.1,0.2,...]`).
+fn vector_literal(embedding: &[f32]) -> String {
+    let values: Vec<String> = embedding.iter().map(|v| v.to_string()).collect();
+    format!("[{}]", values.join(","))
+}
+
+#[async_trait]
+impl EmbeddingRepository for PgEmbeddingRepository {
+    async fn find_embedding_source(&self, product_id: &ProductId) -> SqlxResult<Option<String>> {
+        let row: Option<(String,)> = sqlx::query_as(
+            r#"
+            SELECT
+                p.product_category_name || ' ' || COALESCE(string_agg(
+                    concat_ws(' ', r.review_comment_title, r.review_comment_message), ' '
+                ), '')
+            FROM products p
+            LEFT JOIN order_items oi ON oi.product_id = p.product_id
+            LEFT JOIN reviews r ON r.order_id = oi.order_id
+            WHERE p.product_id = $1 AND p.tenant_id = $2
+            GROUP BY p.product_id, p.product_category_name
+            "#,
+        )
+        .bind(product_id)
+        .bind(self.tenant.as_str())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching embedding source: {:?}", e);
+            e
+        })?;
+
+        Ok(row.map(|(text,)| text))
+    }
+
+    async fn has_embedding(&self, product_id: &ProductId) -> SqlxResult<bool> {
+        let row: (bool,) = sqlx::query_as(
+            r#"
+            SELECT EXISTS(
+                SELECT 1 FROM product_embeddings WHERE product_id = $1 AND tenant_id = $2
+            )
+            "#,
+        )
+        .bind(product_id)
+        .bind(self.tenant.as_str())
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error checking product embedding: {:?}", e);
+            e
+        })?;
+
+        Ok(row.0)
+    }
+
+    async fn upsert(&self, product_id: &ProductId, embedding: &[f32]) -> SqlxResult<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO product_embeddings (product_id, embedding, updated_at, tenant_id)
+            VALUES ($1, $2::vector, NOW(), $3)
+            ON CONFLICT (product_id)
+            DO UPDATE SET embedding = EXCLUDED.embedding, updated_at = NOW()
+            "#,
+        )
+        .bind(product_id)
+        .bind(vector_literal(embedding))
+        .bind(self.tenant.as_str())
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error storing product embedding: {:?}", e);
+            e
+        })?;
+
+        Ok(())
+    }
+
+    async fn find_products_without_embedding(&self, limit: i64) -> SqlxResult<Vec<ProductId>> {
+        let rows: Vec<(ProductId,)> = sqlx::query_as(
+            r#"
+            SELECT p.product_id
+            FROM products p
+            LEFT JOIN product_embeddings pe ON pe.product_id = p.product_id
+            WHERE pe.product_id IS NULL AND p.tenant_id = $2
+            LIMIT $1
+            "#,
+        )
+        .bind(limit)
+        .bind(self.tenant.as_str())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching products without embedding: {:?}", e);
+            e
+        })?;
+
+        Ok(rows.into_iter().map(|(id,)| id).collect())
+    }
+
+    async fn find_similar(
+        &self,
+        product_id: &ProductId,
+        limit: i64,
+    ) -> SqlxResult<Vec<SimilarProduct>> {
+        sqlx::query_as::<_, SimilarProduct>(
+            r#"
+            SELECT
+                p.product_id, p.product_category_name, p.product_name_lenght,
+                p.product_description_lenght, p.product_photos_qty, p.product_weight_g,
+                p.product_length_cm, p.product_height_cm, p.product_width_cm,
+                p.product_name, p.description, p.price, p.active,
+                pe.embedding <=> target.embedding AS distance
+            FROM product_embeddings target
+            JOIN product_embeddings pe
+                ON pe.tenant_id = target.tenant_id AND pe.product_id <> target.product_id
+            JOIN products p ON p.product_id = pe.product_id
+            WHERE target.product_id = $1 AND target.tenant_id = $3
+            ORDER BY pe.embedding <=> target.embedding
+            LIMIT $2
+            "#,
+        )
+        .bind(product_id)
+        .bind(limit)
+        .bind(self.tenant.as_str())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching similar products: {:?}", e);
+            e
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct PgLateOrderRepository {
+    pool: PgPool,
+    tenant: TenantId,
+}
+
+impl PgLateOrderRepository {
+    pub fn new(pool: PgPool, tenant: TenantId) -> Self {
+        Self { pool, tenant }
+    }
+}
+
+#[async_trait]
+impl LateOrderRepository for PgLateOrderRepository {
+    /// One statement, so the alerts and their outbox events commit together.
+    async fn flag_late_orders(&self, max_age_days: i64) -> SqlxResult<u64> {
+        sqlx::query!(
+            r#"
+            WITH flagged AS (
+                INSERT INTO late_delivery_alerts (order_id, order_estimated_delivery_date, tenant_id)
+                SELECT order_id, order_estimated_delivery_date, tenant_id
+                FROM orders
+                WHERE tenant_id = $2
+                  AND order_estimated_delivery_date < NOW()
+                  AND order_estimated_delivery_date >= NOW() - make_interval(days => $1::int)
+                  AND order_delivered_customer_date IS NULL
+                  AND order_status NOT IN ('delivered', 'canceled', 'unavailable')
+                ON CONFLICT (order_id) DO NOTHING
+                RETURNING order_id
+            )
+            INSERT INTO outbox_events (aggregate_type, aggregate_id, event_type, payload, tenant_id)
+            SELECT 'order', o.order_id, 'order.late', jsonb_build_object(
+                'order_id', o.order_id,
+                'customer_id', o.customer_id,
+                'order_status', o.order_status,
+                'order_delivered_carrier_date', o.order_delivered_carrier_date,
+                'order_estimated_delivery_date', o.order_estimated_delivery_date
+            ), o.tenant_id
+            FROM flagged f
+            JOIN orders o ON o.order_id = f.order_id
+            ORDER BY o.order_estimated_delivery_date, o.order_id
+            "#,
+            max_age_days as i32,
+            self.tenant.as_str(),
+        )
+        .execute(&self.pool)
+        .await
+        .map(|result| result.rows_affected())
+        .map_err(|e| {
+            error!("Error flagging late orders: {:?}", e);
+            e
+        })
+    }
+
+    async fn find_late_orders(
+        &self,
+        pagination: &PaginationParams,
+    ) -> SqlxResult<(Vec<LateOrder>, i64)> {
+        let (limit, offset, _, _) = pagination.normalize();
+
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                o.order_id AS "order_id: OrderId",
+                o.customer_id AS "customer_id: CustomerId",
+                o.order_status AS "order_status: OrderStatus",
+                o.order_purchase_timestamp, o.order_approved_at,
+                o.order_delivered_carrier_date, o.order_delivered_customer_date,
+                o.order_estimated_delivery_date,
+                a.flagged_at,
+                EXTRACT(DAY FROM NOW() - o.order_estimated_delivery_date)::BIGINT AS "days_late!",
+                COUNT(*) OVER () AS total_count
+            FROM late_delivery_alerts a
+            JOIN orders o ON o.order_id = a.order_id
+            WHERE a.tenant_id = $3
+              AND o.order_estimated_delivery_date < NOW()
+              AND o.order_delivered_customer_date IS NULL
+              AND o.order_status NOT IN ('delivered', 'canceled', 'unavailable')
+            ORDER BY o.order_estimated_delivery_date, o.order_id
+            LIMIT $1 OFFSET $2
+            "#,
+            limit,
+            offset,
+            self.tenant.as_str(),
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching late orders: {:?}", e);
+            e
+        })?
+        .into_iter()
+        .map(|row| Counted {
+            row: LateOrder {
+                order: Order {
+                    order_id: row.order_id,
+                    customer_id: row.customer_id,
+                    order_status: row.order_status,
+                    order_purchase_timestamp: row.order_purchase_timestamp,
+                    order_approved_at: row.order_approved_at,
+                    order_delivered_carrier_date: row.order_delivered_carrier_date,
+                    order_delivered_customer_date: row.order_delivered_customer_date,
+                    order_estimated_delivery_date: row.order_estimated_delivery_date,
+                },
+                flagged_at: row.flagged_at,
+                days_late: row.days_late,
+            },
+            total_count: row.total_count,
+        })
+        .collect();
+
+        match split_counted(rows, offset) {
+            (orders, Some(total_count)) => Ok((orders, total_count)),
+            (orders, None) => {
+                let count = sqlx::query_scalar!(
+                    r#"
+                    SELECT COUNT(*) AS "count!"
+                    FROM late_delivery_alerts a
+                    JOIN orders o ON o.order_id = a.order_id
+                    WHERE a.tenant_id = $1
+                      AND o.order_estimated_delivery_date < NOW()
+                      AND o.order_delivered_customer_date IS NULL
+                      AND o.order_status NOT IN ('delivered', 'canceled', 'unavailable')
+                    "#,
+                    self.tenant.as_str(),
+                )
+                .fetch_one(&self.pool)
+                .await
+                .map_err(|e| {
+                    error!("Error counting late orders: {:?}", e);
+                    e
+                })?;
+                Ok((orders, count))
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct PgReviewSentimentRepository {
+    pool: PgPool,
+    tenant: TenantId,
+}
+
+impl PgReviewSentimentRepository {
+    pub fn new(pool: PgPool, tenant: TenantId) -> Self {
+        Self { pool, tenant }
+    }
+}
+
+#[async_trait]
+impl ReviewSentimentRepository for PgReviewSentimentRepository {
+    async fn find_untagged(&self, limit: i64) -> SqlxResult<Vec<ReviewText>> {
+        sqlx::query_as!(
+            ReviewText,
+            r#"
+            SELECT r.review_id, r.review_score, r.review_comment_message AS "review_comment_message!"
+            FROM reviews r
+            LEFT JOIN review_sentiments rs ON rs.review_id = r.review_id
+            WHERE rs.review_id IS NULL AND NULLIF(TRIM(r.review_comment_message), '') IS NOT NULL
+              AND r.tenant_id = $2
+            ORDER BY r.review_creation_date, r.review_id
+            LIMIT $1
+            "#,
+            limit,
+            self.tenant.as_str(),
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching untagged reviews: {:?}", e);
+            e
+        })
+    }
+
+    async fn save(&self, sentiments: &[(String, Sentiment)]) -> SqlxResult<u64> {
+        let review_ids: Vec<String> = sentiments.iter().map(|(id, _)| id.clone()).collect();
+        let labels: Vec<String> = sentiments
+            .iter()
+            .map(|(_, sentiment)| sentiment.as_str().to_string())
+            .collect();
+
+        // Joined to reviews so a review deleted since it was read is skipped, not a violation
+        sqlx::query!(
+            r#"
+            INSERT INTO review_sentiments (review_id, sentiment, tagged_at, tenant_id)
+            SELECT t.review_id, t.sentiment, NOW(), r.tenant_id
+            FROM UNNEST($1::text[], $2::text[]) AS t(review_id, sentiment)
+            JOIN reviews r ON r.review_id = t.review_id AND r.tenant_id = $3
+            ON CONFLICT (review_id) DO UPDATE
+            SET sentiment = EXCLUDED.sentiment, tagged_at = EXCLUDED.tagged_at
+            "#,
+            &review_ids,
+            &labels,
+            self.tenant.as_str(),
+        )
+        .execute(&self.pool)
+        .await
+        .map(|result| result.rows_affected())
+        .map_err(|e| {
+            error!("Error saving review sentiments: {:?}", e);
+            e
+        })
+    }
+
+    async fn find_reviews(
+        &self,
+        sentiment: Option<Sentiment>,
+        pagination: &PaginationParams,
+    ) -> SqlxResult<(Vec<SentimentReview>, i64)> {
+        let (limit, offset, _, _) = pagination.normalize();
+        let sentiment = sentiment.map(|sentiment| sentiment.as_str());
+
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                r.review_id, r.order_id AS "order_id: OrderId", r.review_score,
+                r.review_comment_title, r.review_comment_message, r.review_creation_date,
+                r.review_answer_timestamp,
+                rs.sentiment AS "sentiment?: Sentiment",
+                rs.tagged_at AS "sentiment_tagged_at?",
+                COUNT(*) OVER () AS total_count
+            FROM reviews r
+            LEFT JOIN review_sentiments rs ON rs.review_id = r.review_id
+            WHERE ($1::TEXT IS NULL OR rs.sentiment = $1) AND r.tenant_id = $4
+            ORDER BY r.review_creation_date DESC, r.review_id DESC
+            LIMIT $2 OFFSET $3
+            "#,
+            sentiment,
+            limit,
+            offset,
+            self.tenant.as_str(),
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching reviews: {:?}", e);
+            e
+        })?
+        .into_iter()
+        .map(|row| Counted {
+            row: SentimentReview {
+                review: Review {
+                    review_id: row.review_id,
+                    order_id: row.order_id,
+                    review_score: row.review_score,
+                    review_comment_title: row.review_comment_title,
+                    review_comment_message: row.review_comment_message,
+                    review_creation_date: row.review_creation_date,
+                    review_answer_timestamp: row.review_answer_timestamp,
+                },
+                sentiment: row.sentiment,
+                sentiment_tagged_at: row.sentiment_tagged_at,
+            },
+            total_count: row.total_count,
+        })
+        .collect();
+
+        match split_counted(rows, offset) {
+            (reviews, Some(total_count)) => Ok((reviews, total_count)),
+            (reviews, None) => {
+                let count = sqlx::query_scalar!(
+                    r#"
+                    SELECT COUNT(*) AS "count!"
+                    FROM reviews r
+                    LEFT JOIN review_sentiments rs ON rs.review_id = r.review_id
+                    WHERE ($1::TEXT IS NULL OR rs.sentiment = $1) AND r.tenant_id = $2
+                    "#,
+                    sentiment,
+                    self.tenant.as_str(),
+                )
+                .fetch_one(&self.pool)
+                .await
+                .map_err(|e| {
+                    error!("Error counting reviews: {:?}", e);
+                    e
+                })?;
+                Ok((reviews, count))
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct PgInventoryRepository {
+    pool: PgPool,
+    reads: ReadPool,
+    retry: RetryPolicy,
+    tenant: TenantId,
+}
+
+impl PgInventoryRepository {
+    pub fn new(pool: PgPool, reads: ReadPool, retry: RetryPolicy, tenant: TenantId) -> Self {
+        Self {
+            pool,
+            reads,
+            retry,
+            tenant,
+        }
+    }
+}
+
+#[async_trait]
+impl InventoryRepository for PgInventoryRepository {
+    async fn create_location(
+        &self,
+        seller_id: &SellerId,
+        dto: CreateStockLocationDto,
+    ) -> SqlxResult<StockLocation> {
+        let dto = &dto;
+        self.retry.write(|| async move {
+            sqlx::query_as!(
+                StockLocation,
+                r#"
+                INSERT INTO stock_locations (seller_id, name, zip_code_prefix, tenant_id)
+                VALUES ($1, $2, $3, $4)
+                RETURNING
+                    location_id, seller_id AS "seller_id: SellerId", name, zip_code_prefix, created_at
+                "#,
+                seller_id.as_str(),
+                dto.name,
+                dto.zip_code_prefix,
+                self.tenant.as_str(),
+            )
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| {
+                error!("Error creating stock location: {:?}", e);
+                e
+            })
+        })
+        .await
+    }
+
+    async fn find_location_by_id(&self, location_id: i64) -> SqlxResult<Option<StockLocation>> {
+        self.reads.read(&self.retry, |pool| async move {
+            sqlx::query_as!(
+                StockLocation,
+                r#"
+                SELECT
+                    location_id, seller_id AS "seller_id: SellerId", name, zip_code_prefix, created_at
+                FROM stock_locations WHERE location_id = $1 AND tenant_id = $2
+                "#,
+                location_id,
+                self.tenant.as_str(),
+            )
+            .fetch_optional(pool)
+            .await
+            .map_err(|e| {
+                error!("Error fetching stock location by id: {:?}", e);
+                e
+            })
+        })
+        .await
+    }
+
+    async fn find_locations_by_seller(
+        &self,
+        seller_id: &SellerId,
+    ) -> SqlxResult<Vec<StockLocation>> {
+        self.reads.read(&self.retry, |pool| async move {
+            sqlx::query_as!(
+                StockLocation,
+                r#"
+                SELECT
+                    location_id, seller_id AS "seller_id: SellerId", name, zip_code_prefix, created_at
+                FROM stock_locations
+                WHERE seller_id = $1 AND tenant_id = $2
+                ORDER BY location_id
+                "#,
+                seller_id.as_str(),
+                self.tenant.as_str(),
+            )
+            .fetch_all(pool)
+            .await
+            .map_err(|e| {
+                error!("Error fetching stock locations for seller: {:?}", e);
+                e
+            })
+        })
+        .await
+    }
+
+    async fn find_stock_by_location(&self, location_id: i64) -> SqlxResult<Vec<LocationStock>> {
+        self.reads
+            .read(&self.retry, |pool| async move {
+                sqlx::query_as!(
+                    LocationStock,
+                    r#"
+                    SELECT location_id, product_id AS "product_id: ProductId", quantity, updated_at
+                    FROM location_stock
+                    WHERE location_id = $1 AND tenant_id = $2
+                    ORDER BY product_id
+                    "#,
+                    location_id,
+                    self.tenant.as_str(),
+                )
+                .fetch_all(pool)
+                .await
+                .map_err(|e| {
+                    error!("Error fetching stock for location: {:?}", e);
+                    e
+                })
+            })
+            .await
+    }
+
+    async fn find_stock_by_product(
+        &self,
+        product_id: &ProductId,
+    ) -> SqlxResult<Vec<ProductLocationStock>> {
+        self.reads
+            .read(&self.retry, |pool| async move {
+                sqlx::query_as!(
+                    ProductLocationStock,
+                    r#"
+                    SELECT l.location_id, l.seller_id AS "seller_id: SellerId", l.name,
+                           l.zip_code_prefix, s.quantity, s.updated_at
+                    FROM location_stock s
+                    JOIN stock_locations l ON l.location_id = s.location_id
+                    WHERE s.product_id = $1 AND s.tenant_id = $2
+                    ORDER BY l.seller_id, l.location_id
+                    "#,
+                    product_id.as_str(),
+                    self.tenant.as_str(),
+                )
+                .fetch_all(pool)
+                .await
+                .map_err(|e| {
+                    error!("Error fetching stock for product: {:?}", e);
+                    e
+                })
+            })
+            .await
+    }
+
+    async fn set_stock(
+        &self,
+        location_id: i64,
+        product_id: &ProductId,
+        quantity: i32,
+    ) -> SqlxResult<LocationStock> {
+        self.retry
+            .write(|| async move {
+                sqlx::query_as!(
+                    LocationStock,
+                    r#"
+                    INSERT INTO location_stock (location_id, product_id, quantity, tenant_id)
+                    VALUES ($1, $2, $3, $4)
+                    ON CONFLICT (location_id, product_id)
+                    DO UPDATE SET quantity = EXCLUDED.quantity, updated_at = NOW()
+                    RETURNING location_id, product_id AS "product_id: ProductId", quantity, updated_at
+                    "#,
+                    location_id,
+                    product_id.as_str(),
+                    quantity,
+                    self.tenant.as_str(),
+                )
+                .fetch_one(&self.pool)
+                .await
+                .map_err(|e| {
+                    error!("Error setting stock: {:?}", e);
+                    e
+                })
+            })
+            .await
+    }
+
+    async fn adjust_stock(
+        &self,
+        location_id: i64,
+        product_id: &ProductId,
+        delta: i32,
+    ) -> SqlxResult<Option<LocationStock>> {
+        self.retry
+            .write(|| async move {
+                sqlx::query_as!(
+                    LocationStock,
+                    r#"
+                    UPDATE location_stock
+                    SET quantity = quantity + $3, updated_at = NOW()
+                    WHERE location_id = $1 AND product_id = $2 AND tenant_id = $4
+                    RETURNING location_id, product_id AS "product_id: ProductId", quantity, updated_at
+                    "#,
+                    location_id,
+                    product_id.as_str(),
+                    delta,
+                    self.tenant.as_str(),
+                )
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(|e| {
+                    error!("Error adjusting stock: {:?}", e);
+                    e
+                })
+            })
+            .await
+    }
+
+    async fn is_tracked(&self, seller_id: &SellerId, product_id: &ProductId) -> SqlxResult<bool> {
+        self.reads
+            .read(&self.retry, |pool| async move {
+                sqlx::query_scalar!(
+                    r#"
+                    SELECT EXISTS(
+                        SELECT 1
+                        FROM location_stock ls
+                        JOIN stock_locations l ON l.location_id = ls.location_id
+                        WHERE l.seller_id = $1 AND ls.product_id = $2 AND ls.tenant_id = $3
+                    ) AS "tracked!"
+                    "#,
+                    seller_id.as_str(),
+                    product_id.as_str(),
+                    self.tenant.as_str(),
+                )
+                .fetch_one(pool)
+                .await
+                .map_err(|e| {
+                    error!("Error checking stock tracking: {:?}", e);
+                    e
+                })
+            })
+            .await
+    }
+
+    /// Decrements stock at the seller location whose CEP prefix is numerically closest to the
+    /// destination (CEPs are assigned by region, so nearby prefixes are nearby places).
+    async fn allocate(
+        &self,
+        seller_id: &SellerId,
+        product_id: &ProductId,
+        quantity: i32,
+        destination_zip_code_prefix: &str,
+    ) -> SqlxResult<Option<StockAllocation>> {
+        self.retry
+            .write(|| async move {
+                sqlx::query_as!(
+                    StockAllocation,
+                    r#"
+                    WITH candidate AS (
+                        SELECT ls.location_id
+                        FROM location_stock ls
+                        JOIN stock_locations l ON l.location_id = ls.location_id
+                        WHERE l.seller_id = $1
+                          AND ls.product_id = $2
+                          AND ls.quantity >= $3
+                          AND ls.tenant_id = $5
+                        ORDER BY
+                            abs(
+                                NULLIF(regexp_replace(l.zip_code_prefix, '\D', '', 'g'), '')::bigint
+                                - NULLIF(regexp_replace($4, '\D', '', 'g'), '')::bigint
+                            ) NULLS LAST,
+                            l.location_id
+                        LIMIT 1
+                        FOR UPDATE OF ls
+                    )
+                    UPDATE location_stock ls
+                    SET quantity = ls.quantity - $3, updated_at = NOW()
+                    FROM candidate
+                    WHERE ls.location_id = candidate.location_id AND ls.product_id = $2
+                    RETURNING ls.location_id, ls.product_id AS "product_id: ProductId", ls.quantity
+                    "#,
+                    seller_id.as_str(),
+                    product_id.as_str(),
+                    quantity,
+                    destination_zip_code_prefix,
+                    self.tenant.as_str(),
+                )
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(|e| {
+                    error!("Error allocating stock: {:?}", e);
+                    e
+                })
+            })
+            .await
+    }
+
+    /// Returns stock to the seller location tracking the product whose CEP prefix is
+    /// closest to where the goods come back from.
+    async fn restock(
+        &self,
+        seller_id: &SellerId,
+        product_id: &ProductId,
+        quantity: i32,
+        origin_zip_code_prefix: &str,
+    ) -> SqlxResult<Option<StockAllocation>> {
+        self.retry
+            .write(|| async move {
+                sqlx::query_as!(
+                    StockAllocation,
+                    r#"
+                    WITH candidate AS (
+                        SELECT ls.location_id
+                        FROM location_stock ls
+                        JOIN stock_locations l ON l.location_id = ls.location_id
+                        WHERE l.seller_id = $1 AND ls.product_id = $2 AND ls.tenant_id = $5
+                        ORDER BY
+                            abs(
+                                NULLIF(regexp_replace(l.zip_code_prefix, '\D', '', 'g'), '')::bigint
+                                - NULLIF(regexp_replace($4, '\D', '', 'g'), '')::bigint
+                            ) NULLS LAST,
+                            l.location_id
+                        LIMIT 1
+                        FOR UPDATE OF ls
+                    )
+                    UPDATE location_stock ls
+                    SET quantity = ls.quantity + $3, updated_at = NOW()
+                    FROM candidate
+                    WHERE ls.location_id = candidate.location_id AND ls.product_id = $2
+                    RETURNING ls.location_id, ls.product_id AS "product_id: ProductId", ls.quantity
+                    "#,
+                    seller_id.as_str(),
+                    product_id.as_str(),
+                    quantity,
+                    origin_zip_code_prefix,
+                    self.tenant.as_str(),
+                )
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(|e| {
+                    error!("Error restocking: {:?}", e);
+                    e
+                })
+            })
+            .await
+    }
+}
+
+/// Columns selected for `SupportCase` listings, including the computed SLA breach flags. The
+/// single-case queries spell the same list out, since `query_as!` needs a literal.
+const SUPPORT_CASE_COLUMNS: &str = r#"
+    case_id, order_id, customer_id, category, status, subject,
+    created_at, updated_at, first_response_due_at, resolution_due_at,
+    first_responded_at, resolved_at,
+    COALESCE(first_responded_at, NOW()) > first_response_due_at AS first_response_breached,
+    COALESCE(resolved_at, NOW()) > resolution_due_at AS resolution_breached
+"#;
+
+#[derive(Clone)]
+pub struct PgSupportRepository {
+    pool: PgPool,
+    reads: ReadPool,
+    retry: RetryPolicy,
+    tenant: TenantId,
+}
+
+impl PgSupportRepository {
+    pub fn new(pool: PgPool, reads: ReadPool, retry: RetryPolicy, tenant: TenantId) -> Self {
+        Self {
+            pool,
+            reads,
+            retry,
+            tenant,
+        }
+    }
+}
+
+#[async_trait]
+impl SupportRepository for PgSupportRepository {
+    #[instrument(skip(self, dto))]
+    async fn create(
+        &self,
+        dto: CreateSupportCaseDto,
+        author: &str,
+        first_response_hours: i64,
+        resolution_hours: i64,
+    ) -> SqlxResult<Option<SupportCase>> {
+        let dto = &dto;
+        self.retry
+            .write(|| async move {
+                let result = async {
+                    let mut tx = self.pool.begin().await?;
+
+                    let case = sqlx::query_as!(
+                        SupportCase,
+                        r#"
+                        INSERT INTO support_cases (
+                            order_id, customer_id, category, subject,
+                            first_response_due_at, resolution_due_at, tenant_id
+                        )
+                        SELECT
+                            order_id, customer_id, $2, $3,
+                            NOW() + make_interval(hours => $4::int),
+                            NOW() + make_interval(hours => $5::int),
+                            tenant_id
+                        FROM orders
+                        WHERE order_id = $1 AND tenant_id = $6
+                        RETURNING
+                            case_id, order_id AS "order_id: OrderId",
+                            customer_id AS "customer_id: CustomerId", category, status, subject,
+                            created_at, updated_at, first_response_due_at, resolution_due_at,
+                            first_responded_at, resolved_at,
+                            COALESCE(first_responded_at, NOW()) > first_response_due_at
+                                AS "first_response_breached!",
+                            COALESCE(resolved_at, NOW()) > resolution_due_at AS "resolution_breached!"
+                        "#,
+                        dto.order_id.as_str(),
+                        dto.category.as_str(),
+                        &dto.subject,
+                        first_response_hours as i64,
+                        resolution_hours as i64,
+                        self.tenant.as_str(),
+                    )
+                    .fetch_optional(&mut *tx)
+                    .await?;
+
+                    let Some(case) = case else {
+                        return Ok(None);
+                    };
+
+                    sqlx::query!(
+                        r#"
+                        INSERT INTO support_case_messages (case_id, author_type, author, body, tenant_id)
+                        VALUES ($1, 'customer', $2, $3, $4)
+                        "#,
+                        case.case_id,
+                        author,
+                        &dto.message,
+                        self.tenant.as_str(),
+                    )
+                    .execute(&mut *tx)
+                    .await?;
+
+                    tx.commit().await?;
+                    Ok(Some(case))
+                }
+                .await;
+
+                match &result {
+                    Ok(Some(_)) => info!("Support case created successfully"),
+                    Ok(None) => info!("Order not found for support case"),
+                    Err(e) => error!("Error creating support case: {:?}", e),
+                }
+
+                result
+            })
+            .await
+    }
+
+    async fn find_all(
+        &self,
+        filter: &SupportCaseFilter,
+        pagination: &PaginationParams,
+    ) -> SqlxResult<(Vec<SupportCase>, i64)> {
+        self.reads
+            .read(&self.retry, |pool| {
+                cancel_on_drop(pool, async move |conn| {
+                    let (limit, offset, _, _) = pagination.normalize();
+
+                    let rows = sqlx::query_as::<_, Counted<SupportCase>>(&format!(
+                        r#"
+                        SELECT {}, COUNT(*) OVER () AS total_count
+                        FROM support_cases
+                        WHERE ($1::text IS NULL OR status = $1)
+                          AND ($2::text IS NULL OR category = $2)
+                          AND ($3::text IS NULL OR order_id = $3)
+                          AND tenant_id = $4
+                        ORDER BY created_at DESC, case_id DESC
+                        LIMIT $5 OFFSET $6
+                        "#,
+                        SUPPORT_CASE_COLUMNS
+                    ))
+                    .bind(&filter.status)
+                    .bind(&filter.category)
+                    .bind(&filter.order_id)
+                    .bind(self.tenant.as_str())
+                    .bind(limit)
+                    .bind(offset)
+                    .fetch_all(&mut *conn)
+                    .await
+                    .map_err(|e| {
+                        error!("Error fetching support cases: {:?}", e);
+                        e
+                    })?;
+
+                    let (cases, total_count) = match split_counted(rows, offset) {
+                        (cases, Some(total_count)) => (cases, total_count),
+                        // Past the last page no row carries the window total.
+                        (cases, None) => {
+                            let count = sqlx::query_scalar!(
+                                r#"
+                                SELECT COUNT(*) AS "count!" FROM support_cases
+                                WHERE ($1::text IS NULL OR status = $1)
+                                  AND ($2::text IS NULL OR category = $2)
+                                  AND ($3::text IS NULL OR order_id = $3)
+                                  AND tenant_id = $4
+                                "#,
+                                filter.status.as_deref(),
+                                filter.category.as_deref(),
+                                filter.order_id.as_ref().map(|id| id.as_str()),
+                                self.tenant.as_str(),
+                            )
+                            .fetch_one(&mut *conn)
+                            .await
+                            .map_err(|e| {
+                                error!("Error counting support cases: {:?}", e);
+                                e
+                            })?;
+                            (cases, count)
+                        }
+                    };
+
+                    Ok((cases, total_count))
+                })
+            })
+            .await
+    }
+
+    async fn find_by_id(&self, case_id: i64) -> SqlxResult<Option<SupportCase>> {
+        self.reads
+            .read(&self.retry, |pool| async move {
+                sqlx::query_as!(
+                    SupportCase,
+                    r#"
+                    SELECT
+                        case_id, order_id AS "order_id: OrderId",
+                        customer_id AS "customer_id: CustomerId", category, status, subject,
+                        created_at, updated_at, first_response_due_at, resolution_due_at,
+                        first_responded_at, resolved_at,
+                        COALESCE(first_responded_at, NOW()) > first_response_due_at
+                            AS "first_response_breached!",
+                        COALESCE(resolved_at, NOW()) > resolution_due_at AS "resolution_breached!"
+                    FROM support_cases WHERE case_id = $1 AND tenant_id = $2
+                    "#,
+                    case_id,
+                    self.tenant.as_str(),
+                )
+                .fetch_optional(pool)
+                .await
+                .map_err(|e| {
+                    error!("Error fetching support case by id: {:?}", e);
+                    e
+                })
+            })
+            .await
+    }
+
+    async fn find_messages(&self, case_id: i64) -> SqlxResult<Vec<SupportMessage>> {
+        self.reads
+            .read(&self.retry, |pool| async move {
+                sqlx::query_as!(
+                    SupportMessage,
+                    r#"
+                    SELECT message_id, case_id, author_type, author, body, created_at
+                    FROM support_case_messages
+                    WHERE case_id = $1 AND tenant_id = $2
+                    ORDER BY created_at, message_id
+                    "#,
+                    case_id,
+                    self.tenant.as_str(),
+                )
+                .fetch_all(pool)
+                .await
+                .map_err(|e| {
+                    error!("Error fetching support case messages: {:?}", e);
+                    e
+                })
+            })
+            .await
+    }
+
+    #[instrument(skip(self))]
+    async fn update(
+        &self,
+        case_id: i64,
+        dto: UpdateSupportCaseDto,
+    ) -> SqlxResult<Option<SupportCase>> {
+        let dto = &dto;
+        self.retry
+            .write(|| async move {
+                let result = sqlx::query_as!(
+                    SupportCase,
+                    r#"
+                    UPDATE support_cases
+                    SET
+                        category = COALESCE($2, category),
+                        status = COALESCE($3, status),
+                        resolved_at = CASE
+                            WHEN $3::text IS NULL THEN resolved_at
+                            WHEN $3 IN ('resolved', 'closed') THEN COALESCE(resolved_at, NOW())
+                            ELSE NULL
+                        END,
+                        updated_at = NOW()
+                    WHERE case_id = $1 AND tenant_id = $4
+                    RETURNING
+                        case_id, order_id AS "order_id: OrderId",
+                        customer_id AS "customer_id: CustomerId", category, status, subject,
+                        created_at, updated_at, first_response_due_at, resolution_due_at,
+                        first_responded_at, resolved_at,
+                        COALESCE(first_responded_at, NOW()) > first_response_due_at
+                            AS "first_response_breached!",
+                        COALESCE(resolved_at, NOW()) > resolution_due_at AS "resolution_breached!"
+                    "#,
+                    case_id,
+                    dto.category.map(|category| category.as_str()),
+                    dto.status.map(|status| status.as_str()),
+                    self.tenant.as_str(),
+                )
+                .fetch_optional(&self.pool)
+                .await;
+
+                match &result {
+                    Ok(Some(_)) => info!("Support case updated successfully"),
+                    Ok(None) => info!("Support case not found for update"),
+                    Err(e) => error!("Error updating support case: {:?}", e),
+                }
+
+                result
+            })
+            .await
+    }
+
+    #[instrument(skip(self))]
+    async fn delete(&self, case_id: i64) -> SqlxResult<Option<chrono::NaiveDateTime>> {
+        self.retry
+            .write(|| async move {
+                let result = sqlx::query_scalar!(
+                    r#"
+                    DELETE FROM support_cases WHERE case_id = $1 AND tenant_id = $2
+                    RETURNING LOCALTIMESTAMP AS "deleted_at!"
+                    "#,
+                    case_id,
+                    self.tenant.as_str(),
+                )
+                .fetch_optional(&self.pool)
+                .await;
+
+                match &result {
+                    Ok(Some(_)) => info!("Deleted support case {}", case_id),
+                    Ok(None) => info!("Support case {} not found for deletion", case_id),
+                    Err(e) => error!("Error deleting support case: {:?}", e),
+                }
+
+                result
+            })
+            .await
+    }
+
+    /// Stores the message and moves the case along: the first agent reply stops the
+    /// first-response timer, and a customer reply reopens a case waiting on the customer.
+    #[instrument(skip(self, dto))]
+    async fn add_message(
+        &self,
+        case_id: i64,
+        dto: CreateSupportMessageDto,
+        author: &str,
+    ) -> SqlxResult<Option<SupportMessage>> {
+        let dto = &dto;
+        self.retry
+            .write(|| async move {
+                let result = async {
+                    let mut tx = self.pool.begin().await?;
+
+                    let updated = sqlx::query!(
+                        r#"
+                        UPDATE support_cases
+                        SET
+                            first_responded_at = CASE
+                                WHEN $2 = 'agent' THEN COALESCE(first_responded_at, NOW())
+                                ELSE first_responded_at
+                            END,
+                            status = CASE
+                                WHEN $2 = 'customer' AND status = 'pending_customer' THEN 'open'
+                                ELSE status
+                            END,
+                            updated_at = NOW()
+                        WHERE case_id = $1 AND tenant_id = $3
+                        "#,
+                        case_id,
+                        dto.author_type.as_str(),
+                        self.tenant.as_str(),
+                    )
+                    .execute(&mut *tx)
+                    .await?;
+
+                    if updated.rows_affected() == 0 {
+                        return Ok(None);
+                    }
+
+                    let message = sqlx::query_as!(
+                        SupportMessage,
+                        r#"
+                        INSERT INTO support_case_messages (case_id, author_type, author, body, tenant_id)
+                        VALUES ($1, $2, $3, $4, $5)
+                        RETURNING message_id, case_id, author_type, author, body, created_at
+                        "#,
+                        case_id,
+                        dto.author_type.as_str(),
+                        author,
+                        &dto.body,
+                        self.tenant.as_str(),
+                    )
+                    .fetch_one(&mut *tx)
+                    .await?;
+
+                    tx.commit().await?;
+                    Ok(Some(message))
+                }
+                .await;
+
+                if let Err(e) = &result {
+                    error!("Error adding support case message: {:?}", e);
+                }
+
+                result
+            })
+            .await
+    }
+
+    async fn volume_by_category(&self) -> SqlxResult<Vec<SupportCaseVolume>> {
+        self.reads
+            .read(&self.retry, |pool| async move {
+                sqlx::query_as!(
+                    SupportCaseVolume,
+                    r#"
+                    SELECT
+                        category,
+                        COUNT(*) AS "total_cases!",
+                        COUNT(*) FILTER (WHERE status IN ('open', 'pending_customer')) AS "open_cases!",
+                        COUNT(*) FILTER (WHERE status IN ('resolved', 'closed')) AS "resolved_cases!",
+                        COUNT(*) FILTER (
+                            WHERE COALESCE(first_responded_at, NOW()) > first_response_due_at
+                               OR COALESCE(resolved_at, NOW()) > resolution_due_at
+                        ) AS "sla_breached_cases!",
+                        (AVG(EXTRACT(EPOCH FROM resolved_at - created_at)) / 3600)::float8
+                            AS avg_resolution_hours
+                    FROM support_cases
+                    WHERE tenant_id = $1
+                    GROUP BY category
+                    ORDER BY COUNT(*) DESC, category
+                    "#,
+                    self.tenant.as_str(),
+                )
+                .fetch_all(pool)
+                .await
+                .map_err(|e| {
+                    error!("Error aggregating support case volume: {:?}", e);
+                    e
+                })
+            })
+            .await
+    }
+}
+
+/// Indexes serving search-style lookups, rebuilt after bulk imports.
+const SEARCH_INDEXES: &[&str] = &["idx_product_embeddings_embedding"];
+
+#[derive(Clone)]
+pub struct PgMaintenanceRepository {
+    pool: PgPool,
+    tenant: TenantId,
+}
+
+impl PgMaintenanceRepository {
+    pub fn new(pool: PgPool, tenant: TenantId) -> Self {
+        Self { pool, tenant }
+    }
+}
+
+#[async_trait]
+impl MaintenanceRepository for PgMaintenanceRepository {
+    async fn find_materialized_views(&self) -> SqlxResult<Vec<String>> {
+        sqlx::query_scalar!(
+            r#"
+            SELECT matviewname::text AS "name!"
+            FROM pg_matviews
+            WHERE schemaname = current_schema()
+            ORDER BY matviewname
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error listing materialized views: {:?}", e);
+            e
+        })
+    }
+
+    #[instrument(skip(self))]
+    async fn refresh_materialized_view(&self, name: &str) -> SqlxResult<()> {
+        // Identifiers can't be bound; `name` comes from pg_matviews and is quoted here.
+        sqlx::query(&format!(
+            "REFRESH MATERIALIZED VIEW \"{}\"",
+            name.replace('"', "\"\"")
+        ))
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error refreshing materialized view {}: {:?}", name, e);
+            e
+        })?;
+
+        Ok(())
+    }
+
+    #[instrument(skip(self))]
+    async fn reindex_search_indexes(&self) -> SqlxResult<Vec<String>> {
+        let mut rebuilt = Vec::new();
+
+        for index in SEARCH_INDEXES {
+            let exists =
+                sqlx::query_scalar!(r#"SELECT to_regclass($1) IS NOT NULL AS "exists!""#, index)
+                    .fetch_one(&self.pool)
+                    .await?;
+            if !exists {
+                continue;
+            }
+
+            sqlx::query(&format!("REINDEX INDEX CONCURRENTLY {}", index))
+                .execute(&self.pool)
+                .await
+                .map_err(|e| {
+                    error!("Error reindexing {}: {:?}", index, e);
+                    e
+                })?;
+            rebuilt.push(index.to_string());
+        }
+
+        Ok(rebuilt)
+    }
+
+    #[instrument(skip(self))]
+    async fn archive_orders(
+        &self,
+        before: chrono::NaiveDateTime,
+        limit: i64,
+    ) -> SqlxResult<Option<ArchivedOrders>> {
+        let result = async {
+            let mut tx = self.pool.begin().await?;
+
+            // Locking the orders keeps new items, payments or references from landing on them
+            // until they are gone.
+            let batch = sqlx::query!(
+                r#"
+                SELECT o.order_id, o.order_purchase_timestamp
+                FROM orders o
+                WHERE o.order_purchase_timestamp < $1
+                  AND o.tenant_id = $3
+                  AND o.order_status IN ('delivered', 'canceled', 'unavailable')
+                  AND NOT EXISTS (SELECT 1 FROM order_amendments a WHERE a.order_id = o.order_id)
+                  AND NOT EXISTS (SELECT 1 FROM support_cases s WHERE s.order_id = o.order_id)
+                  AND NOT EXISTS (SELECT 1 FROM order_coupons c WHERE c.order_id = o.order_id)
+                  AND NOT EXISTS (
+                      SELECT 1 FROM payment_transactions t WHERE t.order_id = o.order_id
+                  )
+                  AND NOT EXISTS (SELECT 1 FROM refunds r WHERE r.order_id = o.order_id)
+                ORDER BY o.order_purchase_timestamp
+                LIMIT $2
+                FOR UPDATE OF o SKIP LOCKED
+                "#,
+                before,
+                limit,
+                self.tenant.as_str(),
+            )
+            .fetch_all(&mut *tx)
+            .await?;
+
+            let (Some(first), Some(last)) = (batch.first(), batch.last()) else {
+                return Ok(ArchivedOrders::default());
+            };
+            let order_ids: Vec<String> = batch.iter().map(|row| row.order_id.clone()).collect();
+
+            sqlx::query!(
+                "SELECT ensure_order_archive_partitions($1, $2)",
+                first.order_purchase_timestamp,
+                last.order_purchase_timestamp,
+            )
+            .execute(&mut *tx)
+            .await?;
+
+            let orders = sqlx::query!(
+                r#"
+                INSERT INTO orders_archive (
+                    order_id, customer_id, order_status,
+                    order_purchase_timestamp, order_approved_at,
+                    order_delivered_carrier_date, order_delivered_customer_date,
+                    order_estimated_delivery_date, shipping_zip_code_prefix, status_version, tenant_id
+                )
+                SELECT
+                    order_id, customer_id, order_status,
+                    order_purchase_timestamp, order_approved_at,
+                    order_delivered_carrier_date, order_delivered_customer_date,
+                    order_estimated_delivery_date, shipping_zip_code_prefix, status_version, tenant_id
+                FROM orders
+                WHERE order_id = ANY($1)
+                "#,
+                &order_ids,
+            )
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+
+            let items = sqlx::query!(
+                r#"
+                INSERT INTO order_items_archive (
+                    order_item_id, order_id, product_id, seller_id,
+                    shipping_limit_date, price, freight_value, order_purchase_timestamp, tenant_id
+                )
+                SELECT
+                    i.order_item_id, i.order_id, i.product_id, i.seller_id,
+                    i.shipping_limit_date, i.price, i.freight_value, o.order_purchase_timestamp,
+                    i.tenant_id
+                FROM order_items i
+                INNER JOIN orders o ON o.order_id = i.order_id
+                WHERE i.order_id = ANY($1)
+                "#,
+                &order_ids,
+            )
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+
+            let payments = sqlx::query!(
+                r#"
+                INSERT INTO payments_archive (
+                    order_id, payment_sequential, payment_type,
+                    payment_installments, payment_value, tenant_id
+                )
+                SELECT
+                    order_id, payment_sequential, payment_type,
+                    payment_installments, payment_value, tenant_id
+                FROM payments
+                WHERE order_id = ANY($1)
+                "#,
+                &order_ids,
+            )
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+
+            let reviews = sqlx::query!(
+                r#"
+                INSERT INTO reviews_archive (
+                    review_id, order_id, review_score, review_comment_title,
+                    review_comment_message, review_creation_date, review_answer_timestamp, tenant_id
+                )
+                SELECT
+                    review_id, order_id, review_score, review_comment_title,
+                    review_comment_message, review_creation_date, review_answer_timestamp, tenant_id
+                FROM reviews
+                WHERE order_id = ANY($1)
+                "#,
+                &order_ids,
+            )
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+
+            sqlx::query!(
+                "DELETE FROM order_items WHERE order_id = ANY($1)",
+                &order_ids
+            )
+            .execute(&mut *tx)
+            .await?;
+            sqlx::query!("DELETE FROM payments WHERE order_id = ANY($1)", &order_ids)
+                .execute(&mut *tx)
+                .await?;
+            sqlx::query!("DELETE FROM reviews WHERE order_id = ANY($1)", &order_ids)
+                .execute(&mut *tx)
+                .await?;
+            sqlx::query!("DELETE FROM orders WHERE order_id = ANY($1)", &order_ids)
+                .execute(&mut *tx)
+                .await?;
+
+            // The delete triggers took the orders and their revenue off the dashboard counters;
+            // archived orders still happened, so they go back on.
+            sqlx::query!(
+                r#"
+                SELECT bump_stats($2, day, orders_count, revenue, 0)
+                FROM (
+                    SELECT
+                        o.order_purchase_timestamp::date AS day,
+                        COUNT(*) AS orders_count,
+                        COALESCE(SUM(i.revenue), 0) AS revenue
+                    FROM orders_archive o
+                    LEFT JOIN (
+                        SELECT order_id, SUM(price + freight_value) AS revenue
+                        FROM order_items_archive
+                        WHERE order_id = ANY($1)
+                        GROUP BY order_id
+                    ) i ON i.order_id = o.order_id
+                    WHERE o.order_id = ANY($1)
+                    GROUP BY o.order_purchase_timestamp::date
+                ) archived
+                "#,
+                &order_ids,
+                self.tenant.as_str(),
+            )
+            .execute(&mut *tx)
+            .await?;
+
+            tx.commit().await?;
+            Ok(ArchivedOrders {
+                orders,
+                items,
+                payments,
+                reviews,
+            })
+        }
+        .await;
+
+        if let Err(e) = &result {
+            error!("Error archiving orders: {:?}", e);
+        }
+        result.map(Some)
+    }
+}
+
+#[derive(Clone)]
+pub struct PgDiagnosticsRepository {
+    pool: PgPool,
+}
+
+impl PgDiagnosticsRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl DiagnosticsRepository for PgDiagnosticsRepository {
+    async fn ping(&self) -> SqlxResult<()> {
+        sqlx::query!("SELECT 1 AS ping")
+            .fetch_one(&self.pool)
+            .await
+            .map(|_| ())
+            .map_err(|e| {
+                error!("Diagnostics ping failed: {:?}", e);
+                e
+            })
+    }
+
+    async fn replica_lag_seconds(&self) -> SqlxResult<Option<f64>> {
+        sqlx::query_scalar!(
+            r#"
+            SELECT CASE
+                WHEN pg_is_in_recovery()
+                    THEN EXTRACT(EPOCH FROM NOW() - pg_last_xact_replay_timestamp())::float8
+                ELSE (SELECT EXTRACT(EPOCH FROM MAX(replay_lag))::float8 FROM pg_stat_replication)
+            END
+            "#,
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error reading replica lag: {:?}", e);
+            e
+        })
+    }
+
+    async fn database_size_bytes(&self) -> SqlxResult<Option<i64>> {
+        sqlx::query_scalar!("SELECT pg_database_size(current_database())")
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| {
+                error!("Error reading the database size: {:?}", e);
+                e
+            })
+    }
+
+    async fn last_migration(&self) -> SqlxResult<Option<AppliedMigration>> {
+        sqlx::query_as!(
+            AppliedMigration,
+            r#"
+            SELECT version, description, installed_on AT TIME ZONE 'UTC' AS "installed_on!"
+            FROM _sqlx_migrations
+            WHERE success
+            ORDER BY version DESC
+            LIMIT 1
+            "#,
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error reading the applied migrations: {:?}", e);
+            e
+        })
+    }
+
+    #[instrument(skip(self))]
+    async fn table_stats(&self, exact: bool) -> SqlxResult<(Vec<TableStats>, bool)> {
+        let mut tables = sqlx::query_as!(
+            TableStats,
+            r#"
+            SELECT
+                relname::text AS "table_name!",
+                n_live_tup AS "row_count!",
+                pg_total_relation_size(relid) AS "size_bytes"
+            FROM pg_stat_user_tables
+            WHERE schemaname = current_schema()
+            ORDER BY relname
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error reading table statistics: {:?}", e);
+            e
+        })?;
+        if !exact {
+            return Ok((tables, true));
+        }
+
+        for table in &mut tables {
+            // Identifiers can't be bound; `table_name` comes from pg_stat_user_tables and is
+            // quoted here.
+            table.row_count = sqlx::query_scalar(&format!(
+                "SELECT COUNT(*) FROM \"{}\"",
+                table.table_name.replace('"', "\"\"")
+            ))
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| {
+                error!("Error counting the rows of {}: {:?}", table.table_name, e);
+                e
+            })?;
+        }
+        Ok((tables, false))
+    }
+
+    fn pool_stats(&self) -> Option<PoolStats> {
+        Some(PoolStats::new(
+            self.pool.options().get_max_connections(),
+            self.pool.size(),
+            self.pool.num_idle() as u32,
+        ))
+    }
+}
+
+#[derive(Clone)]
+pub struct PgDataQualityRepository {
+    pool: PgPool,
+    tenant: TenantId,
+}
+
+impl PgDataQualityRepository {
+    pub fn new(pool: PgPool, tenant: TenantId) -> Self {
+        Self { pool, tenant }
+    }
+
+    async fn counts(&self, query: &str) -> SqlxResult<Vec<i64>> {
+        let row = sqlx::query(query)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| {
+                error!("Error counting data quality issues: {:?}", e);
+                e
+            })?;
+        (0..row.len()).map(|index| row.try_get(index)).collect()
+    }
+}
+
+#[async_trait]
+impl DataQualityRepository for PgDataQualityRepository {
+    async fn product_report(&self) -> SqlxResult<DataQualityReport<ProductIssue>> {
+        let counts = self
+            .counts(&quality::product_report_query(&self.tenant))
+            .await?;
+        Ok(quality::report(&counts, &ProductIssue::ALL))
+    }
+
+    async fn find_flagged_products(
+        &self,
+        issue: Option<ProductIssue>,
+        pagination: &PaginationParams,
+    ) -> SqlxResult<(Vec<Product>, i64)> {
+        let (limit, offset, _, _) = pagination.normalize();
+        let filter = quality::product_filter(issue, &self.tenant);
+
+        let rows = sqlx::query_as::<_, Counted<Product>>(&format!(
+            r#"
+            SELECT
+                product_id, product_category_name, product_name_lenght,
+                product_description_lenght, product_photos_qty, product_weight_g,
+                product_length_cm, product_height_cm, product_width_cm,
+                product_name, description, price, active,
+                COUNT(*) OVER () AS total_count
+            FROM products
+            WHERE {}
+            ORDER BY product_id
+            LIMIT $1 OFFSET $2
+            "#,
+            filter
+        ))
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching flagged products: {:?}", e);
+            e
+        })?;
+
+        match split_counted(rows, offset) {
+            (products, Some(total_count)) => Ok((products, total_count)),
+            (products, None) => {
+                let count = sqlx::query_scalar::<_, i64>(&format!(
+                    "SELECT COUNT(*) FROM products WHERE {}",
+                    filter
+                ))
+                .fetch_one(&self.pool)
+                .await
+                .map_err(|e| {
+                    error!("Error counting flagged products: {:?}", e);
+                    e
+                })?;
+                Ok((products, count))
+            }
+        }
+    }
+
+    async fn order_report(&self) -> SqlxResult<DataQualityReport<OrderIssue>> {
+        let counts = self
+            .counts(&quality::order_report_query(&self.tenant))
+            .await?;
+        Ok(quality::report(&counts, &OrderIssue::ALL))
+    }
+
+    async fn find_flagged_orders(
+        &self,
+        issue: Option<OrderIssue>,
+        pagination: &PaginationParams,
+    ) -> SqlxResult<(Vec<FlaggedOrder>, i64)> {
+        let (limit, offset, _, _) = pagination.normalize();
+        let filter = quality::order_filter(issue, &self.tenant);
+
+        let rows = sqlx::query_as::<_, Counted<FlaggedOrder>>(&format!(
+            r#"
+            SELECT
+                o.order_id, o.customer_id, o.order_status, o.order_purchase_timestamp,
+                o.order_approved_at, o.order_delivered_carrier_date,
+                o.order_delivered_customer_date, o.order_estimated_delivery_date,
+                (SELECT COUNT(*) FROM order_items i WHERE i.order_id = o.order_id) AS item_count,
+                COUNT(*) OVER () AS total_count
+            FROM orders o
+            WHERE {}
+            ORDER BY o.order_purchase_timestamp, o.order_id
+            LIMIT $1 OFFSET $2
+            "#,
+            filter
+        ))
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching flagged orders: {:?}", e);
+            e
+        })?;
+
+        match split_counted(rows, offset) {
+            (orders, Some(total_count)) => Ok((orders, total_count)),
+            (orders, None) => {
+                let count = sqlx::query_scalar::<_, i64>(&format!(
+                    "SELECT COUNT(*) FROM orders o WHERE {}",
+                    filter
+                ))
+                .fetch_one(&self.pool)
+                .await
+                .map_err(|e| {
+                    error!("Error counting flagged orders: {:?}", e);
+                    e
+                })?;
+                Ok((orders, count))
+            }
+        }
+    }
+
+    async fn evaluate_rule(&self, rule: &DataQualityRule) -> SqlxResult<(i64, i64)> {
+        let counts = self
+            .counts(&quality::rule_query(rule, &self.tenant))
+            .await?;
+        Ok((counts[0], counts[1]))
+    }
+
+    async fn replace_rule_results(&self, results: &[DataQualityRuleResult]) -> SqlxResult<()> {
+        let result = async {
+            let mut tx = self.pool.begin().await?;
+            sqlx::query!(
+                "DELETE FROM data_quality_results WHERE tenant_id = $1",
+                self.tenant.as_str(),
+            )
+            .execute(&mut *tx)
+            .await?;
+            for result in results {
+                sqlx::query!(
+                    r#"
+                    INSERT INTO data_quality_results (
+                        rule_name, table_name, condition, severity, checked_count,
+                        violation_count, error, evaluated_at, tenant_id
+                    )
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                    "#,
+                    &result.rule_name,
+                    &result.table_name,
+                    &result.condition,
+                    result.severity as RuleSeverity,
+                    result.checked_count,
+                    result.violation_count,
+                    result.error,
+                    result.evaluated_at,
+                    self.tenant.as_str(),
+                )
+                .execute(&mut *tx)
+                .await?;
+            }
+            tx.commit().await
+        }
+        .await;
+
+        result.map_err(|e| {
+            error!("Error saving data quality rule results: {:?}", e);
+            e
+        })
+    }
+
+    async fn find_rule_results(&self) -> SqlxResult<Vec<DataQualityRuleResult>> {
+        sqlx::query_as!(
+            DataQualityRuleResult,
+            r#"
+            SELECT
+                rule_name, table_name, condition, severity AS "severity: RuleSeverity",
+                checked_count, violation_count, error, evaluated_at
+            FROM data_quality_results
+            WHERE tenant_id = $1
+            ORDER BY
+                CASE severity WHEN 'error' THEN 0 WHEN 'warning' THEN 1 ELSE 2 END,
+                rule_name
+            "#,
+            self.tenant.as_str(),
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching data quality rule results: {:?}", e);
+            e
+        })
+    }
+}
+
+/// Columns selected for `ImportBatch` listings; the single-batch queries spell them out for
+/// `query_as!`.
+const IMPORT_BATCH_COLUMNS: &str = r#"
+    batch_id, dataset, source, actor, status, success_count, error_count,
+    started_at, finished_at, rolled_back_at
+"#;
+
+#[derive(Clone)]
+pub struct PgImportRepository {
+    pool: PgPool,
+    tenant: TenantId,
+}
+
+impl PgImportRepository {
+    pub fn new(pool: PgPool, tenant: TenantId) -> Self {
+        Self { pool, tenant }
+    }
+}
+
+#[async_trait]
+impl ImportRepository for PgImportRepository {
+    async fn begin_load_job(&self, datasets: &[&str], actor: &str) -> SqlxResult<i64> {
+        sqlx::query_scalar!(
+            r#"
+            INSERT INTO load_jobs (datasets, actor, tenant_id)
+            VALUES ($1, $2, $3)
+            RETURNING job_id
+            "#,
+            datasets.join(","),
+            actor,
+            self.tenant.as_str(),
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error creating load job: {:?}", e);
+            e
+        })
+    }
+
+    async fn find_load_job(&self, job_id: i64) -> SqlxResult<Option<StoredLoadJob>> {
+        let datasets = sqlx::query_scalar!(
+            "SELECT datasets FROM load_jobs WHERE job_id = $1 AND tenant_id = $2",
+            job_id,
+            self.tenant.as_str(),
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        let Some(datasets) = datasets else {
+            return Ok(None);
+        };
+
+        let batches = sqlx::query_as!(
+            LoadJobBatch,
+            r#"
+            SELECT batch_id, dataset, status, success_count, error_count, checkpoint_line
+            FROM import_batches
+            WHERE load_job_id = $1
+            ORDER BY batch_id
+            "#,
+            job_id
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching load job {}: {:?}", job_id, e);
+            e
+        })?;
+
+        Ok(Some(StoredLoadJob {
+            job_id,
+            datasets: datasets.split(',').map(str::to_string).collect(),
+            batches,
+        }))
+    }
+
+    async fn begin_batch(
+        &self,
+        dataset: &str,
+        source: &str,
+        actor: &str,
+        load_job_id: Option<i64>,
+    ) -> SqlxResult<ImportBatch> {
+        sqlx::query_as!(
+            ImportBatch,
+            r#"
+            INSERT INTO import_batches (dataset, source, actor, load_job_id, tenant_id)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING
+                batch_id, dataset, source, actor, status, success_count, error_count,
+                started_at, finished_at, rolled_back_at
+            "#,
+            dataset,
+            source,
+            actor,
+            load_job_id,
+            self.tenant.as_str(),
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error creating import batch: {:?}", e);
+            e
+        })
+    }
+
+    async fn record_rows(&self, batch_id: i64, entity_ids: &[String]) -> SqlxResult<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO import_batch_rows (batch_id, entity_id, tenant_id)
+            SELECT $1, UNNEST($2::text[]), $3
+            ON CONFLICT DO NOTHING
+            "#,
+            batch_id,
+            entity_ids,
+            self.tenant.as_str(),
+        )
+        .execute(&self.pool)
+        .await
+        .map(|_| ())
+        .map_err(|e| {
+            error!("Error recording import batch rows: {:?}", e);
+            e
+        })
+    }
+
+    async fn checkpoint(&self, batch_id: i64, line: i64) -> SqlxResult<()> {
+        sqlx::query!(
+            "UPDATE import_batches SET checkpoint_line = $2 WHERE batch_id = $1",
+            batch_id,
+            line,
+        )
+        .execute(&self.pool)
+        .await
+        .map(|_| ())
+        .map_err(|e| {
+            error!("Error checkpointing import batch {}: {:?}", batch_id, e);
+            e
+        })
+    }
+
+    async fn resume_batch(&self, batch_id: i64) -> SqlxResult<Option<BatchResume>> {
+        let result = async {
+            let mut tx = self.pool.begin().await?;
+
+            let checkpoint_line = sqlx::query_scalar!(
+                r#"
+                UPDATE import_batches
+                SET status = 'running', finished_at = NULL
+                WHERE batch_id = $1 AND tenant_id = $2 AND status IN ('running', 'failed')
+                RETURNING checkpoint_line
+                "#,
+                batch_id,
+                self.tenant.as_str(),
+            )
+            .fetch_optional(&mut *tx)
+            .await?;
+            let Some(checkpoint_line) = checkpoint_line else {
+                return Ok(None);
+            };
+
+            sqlx::query!(
+                "DELETE FROM import_errors WHERE batch_id = $1 AND row_number > $2",
+                batch_id,
+                checkpoint_line,
+            )
+            .execute(&mut *tx)
+            .await?;
+            let error_count = sqlx::query_scalar!(
+                r#"SELECT COUNT(*) AS "count!" FROM import_errors WHERE batch_id = $1"#,
+                batch_id,
+            )
+            .fetch_one(&mut *tx)
+            .await?;
+            let inserted = sqlx::query_scalar!(
+                "SELECT entity_id FROM import_batch_rows WHERE batch_id = $1",
+                batch_id,
+            )
+            .fetch_all(&mut *tx)
+            .await?;
+
+            tx.commit().await?;
+            Ok(Some(BatchResume {
+                checkpoint_line,
+                inserted,
+                error_count,
+            }))
+        }
+        .await;
+
+        result.map_err(|e: sqlx::Error| {
+            error!("Error resuming import batch {}: {:?}", batch_id, e);
+            e
+        })
+    }
+
+    async fn finish_batch(
+        &self,
+        batch_id: i64,
+        status: ImportBatchStatus,
+        success_count: i32,
+        error_count: i32,
+    ) -> SqlxResult<ImportBatch> {
+        sqlx::query_as!(
+            ImportBatch,
+            r#"
+            UPDATE import_batches
+            SET status = $2, success_count = $3, error_count = $4, finished_at = NOW()
+            WHERE batch_id = $1
+            RETURNING
+                batch_id, dataset, source, actor, status, success_count, error_count,
+                started_at, finished_at, rolled_back_at
+            "#,
+            batch_id,
+            status.as_str(),
+            success_count,
+            error_count,
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error finishing import batch: {:?}", e);
+            e
+        })
+    }
+
+    async fn find_all(&self, pagination: &PaginationParams) -> SqlxResult<(Vec<ImportBatch>, i64)> {
+        let (limit, offset, _, _) = pagination.normalize();
+
+        let rows = sqlx::query_as::<_, Counted<ImportBatch>>(&format!(
+            r#"
+            SELECT {}, COUNT(*) OVER () AS total_count
+            FROM import_batches
+            WHERE tenant_id = $3
+            ORDER BY started_at DESC, batch_id DESC
+            LIMIT $1 OFFSET $2
+            "#,
+            IMPORT_BATCH_COLUMNS
+        ))
+        .bind(limit)
+        .bind(offset)
+        .bind(self.tenant.as_str())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching import batches: {:?}", e);
+            e
+        })?;
+
+        let (batches, total_count) = match split_counted(rows, offset) {
+            (batches, Some(total_count)) => (batches, total_count),
+            // Past the last page no row carries the window total.
+            (batches, None) => {
+                let count = sqlx::query_scalar!(
+                    r#"SELECT COUNT(*) AS "count!" FROM import_batches WHERE tenant_id = $1"#,
+                    self.tenant.as_str(),
+                )
+                .fetch_one(&self.pool)
+                .await
+                .map_err(|e| {
+                    error!("Error counting import batches: {:?}", e);
+                    e
+                })?;
+                (batches, count)
+            }
+        };
+
+        Ok((batches, total_count))
+    }
+
+    async fn find_by_id(&self, batch_id: i64) -> SqlxResult<Option<ImportBatch>> {
+        sqlx::query_as!(
+            ImportBatch,
+            r#"
+            SELECT
+                batch_id, dataset, source, actor, status, success_count, error_count,
+                started_at, finished_at, rolled_back_at
+            FROM import_batches WHERE batch_id = $1 AND tenant_id = $2
+            "#,
+            batch_id,
+            self.tenant.as_str(),
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching import batch by id: {:?}", e);
+            e
+        })
+    }
+
+    async fn record_errors(&self, batch_id: i64, errors: &[NewImportRowError]) -> SqlxResult<()> {
+        let row_numbers: Vec<i64> = errors.iter().map(|e| e.row_number).collect();
+        let records: Vec<Option<String>> = errors.iter().map(|e| e.record.clone()).collect();
+        let reasons: Vec<String> = errors.iter().map(|e| e.reason.clone()).collect();
+
+        sqlx::query!(
+            r#"
+            INSERT INTO import_errors (batch_id, row_number, record, reason, tenant_id)
+            SELECT $1, *, $5 FROM UNNEST($2::bigint[], $3::text[], $4::text[])
+            "#,
+            batch_id,
+            &row_numbers,
+            &records as &[Option<String>],
+            &reasons,
+            self.tenant.as_str(),
+        )
+        .execute(&self.pool)
+        .await
+        .map(|_| ())
+        .map_err(|e| {
+            error!("Error recording import errors: {:?}", e);
+            e
+        })
+    }
+
+    async fn find_errors(
+        &self,
+        batch_ids: &[i64],
+        pagination: &PaginationParams,
+    ) -> SqlxResult<(Vec<ImportRowError>, i64)> {
+        let (limit, offset, _, _) = pagination.normalize();
+
+        let errors = sqlx::query_as!(
+            ImportRowError,
+            r#"
+            SELECT
+                e.error_id, e.batch_id, b.dataset, e.row_number, e.record, e.reason,
+                e.created_at
+            FROM import_errors e
+            JOIN import_batches b ON b.batch_id = e.batch_id
+            WHERE e.batch_id = ANY($1) AND e.tenant_id = $4
+            ORDER BY e.batch_id, e.row_number, e.error_id
+            LIMIT $2 OFFSET $3
+            "#,
+            batch_ids,
+            limit,
+            offset,
+            self.tenant.as_str(),
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching import errors: {:?}", e);
+            e
+        })?;
+
+        let total_count = sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*) AS "count!" FROM import_errors
+            WHERE batch_id = ANY($1) AND tenant_id = $2
+            "#,
+            batch_ids,
+            self.tenant.as_str(),
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error counting import errors: {:?}", e);
+            e
+        })?;
+
+        Ok((errors, total_count))
+    }
+
+    fn stream_errors<'a>(
+        &'a self,
+        batch_ids: &'a [i64],
+    ) -> BoxStream<'a, SqlxResult<ImportRowError>> {
+        sqlx::query_as!(
+            ImportRowError,
+            r#"
+            SELECT
+                e.error_id, e.batch_id, b.dataset, e.row_number, e.record, e.reason,
+                e.created_at
+            FROM import_errors e
+            JOIN import_batches b ON b.batch_id = e.batch_id
+            WHERE e.batch_id = ANY($1) AND e.tenant_id = $2
+            ORDER BY e.batch_id, e.row_number, e.error_id
+            "#,
+            batch_ids,
+            self.tenant.as_str(),
+        )
+        .fetch(&self.pool)
+    }
+
+    #[instrument(skip(self))]
+    async fn existing_keys(
+        &self,
+        table: &str,
+        key_column: &str,
+        keys: &[String],
+    ) -> SqlxResult<Vec<String>> {
+        sqlx::query_scalar(&format!(
+            "SELECT {key_column}::text FROM {table} WHERE {key_column} = ANY($1) AND tenant_id = $2"
+        ))
+        .bind(keys)
+        .bind(self.tenant.as_str())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error looking up existing {}: {:?}", table, e);
+            e
+        })
+    }
+
+    async fn rollback(
+        &self,
+        batch_id: i64,
+        table: &str,
+        key_column: &str,
+    ) -> SqlxResult<Option<(ImportBatch, u64)>> {
+        let result = async {
+            let mut tx = self.pool.begin().await?;
+
+            let batch = sqlx::query_as!(
+                ImportBatch,
+                r#"
+                UPDATE import_batches
+                SET status = 'rolled_back', rolled_back_at = NOW()
+                WHERE batch_id = $1 AND tenant_id = $2 AND status IN ('completed', 'failed')
+                RETURNING
+                    batch_id, dataset, source, actor, status, success_count, error_count,
+                    started_at, finished_at, rolled_back_at
+                "#,
+                batch_id,
+                self.tenant.as_str(),
+            )
+            .fetch_optional(&mut *tx)
+            .await?;
+
+            let Some(batch) = batch else {
+                return Ok(None);
+            };
+
+            let deleted = sqlx::query(&format!(
+                r#"
+                DELETE FROM {table}
+                WHERE tenant_id = $2 AND {key_column} IN (
+                    SELECT entity_id FROM import_batch_rows WHERE batch_id = $1
+                )
+                "#
+            ))
+            .bind(batch_id)
+            .bind(self.tenant.as_str())
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+
+            tx.commit().await?;
+            Ok(Some((batch, deleted)))
+        }
+        .await;
+
+        match &result {
+            Ok(Some((_, deleted))) => info!("Import batch rolled back, {} rows deleted", deleted),
+            Ok(None) => info!("Import batch not in a state that can be rolled back"),
+            Err(e) => error!("Error rolling back import batch: {:?}", e),
+        }
+
+        result
+    }
+}
+
+#[derive(Clone)]
+pub struct PgCouponRepository {
+    pool: PgPool,
+    reads: ReadPool,
+    retry: RetryPolicy,
+    tenant: TenantId,
+}
+
+impl PgCouponRepository {
+    pub fn new(pool: PgPool, reads: ReadPool, retry: RetryPolicy, tenant: TenantId) -> Self {
+        Self {
+            pool,
+            reads,
+            retry,
+            tenant,
+        }
+    }
+}
+
+#[async_trait]
+impl CouponRepository for PgCouponRepository {
+    async fn create(&self, dto: CreateCouponDto) -> SqlxResult<Coupon> {
+        let dto = &dto;
+        self.retry
+            .write(|| async move {
+                sqlx::query_as!(
+                    Coupon,
+                    r#"
+                    INSERT INTO coupons (
+                        code, discount_type, value, min_order_value, expires_at, max_uses,
+                        tenant_id
+                    )
+                    VALUES ($1, $2, $3, $4, $5, $6, $7)
+                    RETURNING code, discount_type, value, min_order_value, expires_at, max_uses,
+                              times_used, created_at
+                    "#,
+                    &dto.code,
+                    dto.discount_type.as_str(),
+                    dto.value,
+                    dto.min_order_value,
+                    dto.expires_at,
+                    dto.max_uses,
+                    self.tenant.as_str(),
+                )
+                .fetch_one(&self.pool)
+                .await
+                .map_err(|e| {
+                    error!("Error creating coupon: {:?}", e);
+                    e
+                })
+            })
+            .await
+    }
+
+    async fn find_by_code(&self, code: &str) -> SqlxResult<Option<Coupon>> {
+        self.reads
+            .read(&self.retry, |pool| async move {
+                sqlx::query_as!(
+                    Coupon,
+                    r#"
+                    SELECT code, discount_type, value, min_order_value, expires_at, max_uses,
+                           times_used, created_at
+                    FROM coupons
+                    WHERE code = $1 AND tenant_id = $2
+                    "#,
+                    code,
+                    self.tenant.as_str(),
+                )
+                .fetch_optional(pool)
+                .await
+                .map_err(|e| {
+                    error!("Error fetching coupon: {:?}", e);
+                    e
+                })
+            })
+            .await
+    }
+
+    async fn find_by_order(&self, order_id: &OrderId) -> SqlxResult<Option<Coupon>> {
+        self.reads
+            .read(&self.retry, |pool| async move {
+                sqlx::query_as!(
+                    Coupon,
+                    r#"
+                    SELECT c.code, c.discount_type, c.value, c.min_order_value, c.expires_at,
+                           c.max_uses, c.times_used, c.created_at
+                    FROM order_coupons oc
+                    JOIN coupons c ON c.tenant_id = oc.tenant_id AND c.code = oc.code
+                    WHERE oc.order_id = $1 AND oc.tenant_id = $2
+                    "#,
+                    order_id.as_str(),
+                    self.tenant.as_str(),
+                )
+                .fetch_optional(pool)
+                .await
+                .map_err(|e| {
+                    error!("Error fetching order coupon: {:?}", e);
+                    e
+                })
+            })
+            .await
+    }
+
+    #[instrument(skip(self))]
+    async fn redeem(&self, code: &str, order_id: &OrderId) -> SqlxResult<bool> {
+        self.retry
+            .write(|| async move {
+                let result = async {
+                    let mut tx = self.pool.begin().await?;
+
+                    let taken = sqlx::query!(
+                        r#"
+                        UPDATE coupons
+                        SET times_used = times_used + 1
+                        WHERE code = $1 AND tenant_id = $2
+                          AND (max_uses IS NULL OR times_used < max_uses)
+                          AND (expires_at IS NULL OR expires_at > NOW())
+                        "#,
+                        code,
+                        self.tenant.as_str(),
+                    )
+                    .execute(&mut *tx)
+                    .await?
+                    .rows_affected();
+                    if taken == 0 {
+                        return Ok(false);
+                    }
+
+                    sqlx::query!(
+                        "INSERT INTO order_coupons (order_id, code, tenant_id) VALUES ($1, $2, $3)",
+                        order_id.as_str(),
+                        code,
+                        self.tenant.as_str(),
+                    )
+                    .execute(&mut *tx)
+                    .await?;
+
+                    tx.commit().await?;
+                    Ok(true)
+                }
+                .await;
+
+                if let Err(e) = &result {
+                    error!("Error redeeming coupon: {:?}", e);
+                }
+                result
+            })
+            .await
+    }
+}
+
+#[derive(Clone)]
+pub struct PgPaymentTransactionRepository {
+    pool: PgPool,
+    reads: ReadPool,
+    retry: RetryPolicy,
+    tenant: TenantId,
+}
+
+impl PgPaymentTransactionRepository {
+    pub fn new(pool: PgPool, reads: ReadPool, retry: RetryPolicy, tenant: TenantId) -> Self {
+        Self {
+            pool,
+            reads,
+            retry,
+            tenant,
+        }
+    }
+}
+
+/// Moves an order whose payment changed status, if it is still in the transition's `from`.
+async fn apply_order_transition(
+    conn: &mut PgConnection,
+    tenant: &TenantId,
+    order_id: &OrderId,
+    (from, to): OrderStatusTransition,
+) -> SqlxResult<()> {
+    sqlx::query!(
+        r#"
+        UPDATE orders SET order_status = $3
+        WHERE order_id = $1 AND order_status = $2 AND tenant_id = $4
+        "#,
+        order_id.as_str(),
+        from as OrderStatus,
+        to as OrderStatus,
+        tenant.as_str(),
+    )
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+#[async_trait]
+impl PaymentTransactionRepository for PgPaymentTransactionRepository {
+    #[instrument(skip(self))]
+    async fn create(
+        &self,
+        transaction: NewPaymentTransaction,
+        order_status: Option<OrderStatusTransition>,
+    ) -> SqlxResult<PaymentTransaction> {
+        let transaction = &transaction;
+        self.retry
+            .write(|| async move {
+                let result = async {
+                    let mut tx = self.pool.begin().await?;
+
+                    let created = sqlx::query_as!(
+                        PaymentTransaction,
+                        r#"
+                        INSERT INTO payment_transactions (
+                            order_id, provider, provider_reference, payment_type,
+                            payment_installments, amount, status, tenant_id
+                        )
+                        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                        RETURNING
+                            transaction_id, order_id AS "order_id: OrderId", provider,
+                            provider_reference, payment_type AS "payment_type: PaymentType",
+                            payment_installments, amount, status AS "status: PaymentStatus",
+                            created_at, updated_at,
+                            'BRL'::VARCHAR AS "currency!: Currency"
+                        "#,
+                        transaction.order_id.as_str(),
+                        &transaction.provider,
+                        &transaction.provider_reference,
+                        transaction.payment_type as PaymentType,
+                        transaction.payment_installments,
+                        transaction.amount,
+                        transaction.status as PaymentStatus,
+                        self.tenant.as_str(),
+                    )
+                    .fetch_one(&mut *tx)
+                    .await?;
+                    if let Some(order_status) = order_status {
+                        apply_order_transition(
+                            &mut tx,
+                            &self.tenant,
+                            &created.order_id,
+                            order_status,
+                        )
+                        .await?;
+                    }
+
+                    tx.commit().await?;
+                    Ok(created)
+                }
+                .await;
+
+                if let Err(e) = &result {
+                    error!("Error creating payment transaction: {:?}", e);
+                }
+                result
+            })
+            .await
+    }
+
+    async fn find_by_id(&self, transaction_id: i64) -> SqlxResult<Option<PaymentTransaction>> {
+        self.reads
+            .read(&self.retry, |pool| async move {
+                sqlx::query_as!(
+                    PaymentTransaction,
+                    r#"
+                    SELECT
+                        transaction_id, order_id AS "order_id: OrderId", provider,
+                        provider_reference, payment_type AS "payment_type: PaymentType",
+                        payment_installments, amount, status AS "status: PaymentStatus",
+                        created_at, updated_at,
+                        'BRL'::VARCHAR AS "currency!: Currency"
+                    FROM payment_transactions
+                    WHERE transaction_id = $1 AND tenant_id = $2
+                    "#,
+                    transaction_id,
+                    self.tenant.as_str(),
+                )
+                .fetch_optional(pool)
+                .await
+                .map_err(|e| {
+                    error!("Error fetching payment transaction: {:?}", e);
+                    e
+                })
+            })
+            .await
+    }
+
+    async fn find_by_reference(
+        &self,
+        provider: &str,
+        reference: &str,
+    ) -> SqlxResult<Option<PaymentTransaction>> {
+        self.reads
+            .read(&self.retry, |pool| async move {
+                sqlx::query_as!(
+                    PaymentTransaction,
+                    r#"
+                    SELECT
+                        transaction_id, order_id AS "order_id: OrderId", provider,
+                        provider_reference, payment_type AS "payment_type: PaymentType",
+                        payment_installments, amount, status AS "status: PaymentStatus",
+                        created_at, updated_at,
+                        'BRL'::VARCHAR AS "currency!: Currency"
+                    FROM payment_transactions
+                    WHERE provider = $1 AND provider_reference = $2 AND tenant_id = $3
+                    "#,
+                    provider,
+                    reference,
+                    self.tenant.as_str(),
+                )
+                .fetch_optional(pool)
+                .await
+                .map_err(|e| {
+                    error!("Error fetching payment transaction: {:?}", e);
+                    e
+                })
+            })
+            .await
+    }
+
+    async fn find_by_order(&self, order_id: &OrderId) -> SqlxResult<Vec<PaymentTransaction>> {
+        self.reads
+            .read(&self.retry, |pool| async move {
+                sqlx::query_as!(
+                    PaymentTransaction,
+                    r#"
+                    SELECT
+                        transaction_id, order_id AS "order_id: OrderId", provider,
+                        provider_reference, payment_type AS "payment_type: PaymentType",
+                        payment_installments, amount, status AS "status: PaymentStatus",
+                        created_at, updated_at,
+                        'BRL'::VARCHAR AS "currency!: Currency"
+                    FROM payment_transactions
+                    WHERE order_id = $1 AND tenant_id = $2
+                    ORDER BY transaction_id
+                    "#,
+                    order_id.as_str(),
+                    self.tenant.as_str(),
+                )
+                .fetch_all(pool)
+                .await
+                .map_err(|e| {
+                    error!("Error fetching order payment transactions: {:?}", e);
+                    e
+                })
+            })
+            .await
+    }
+
+    #[instrument(skip(self))]
+    async fn update_status(
+        &self,
+        transaction_id: i64,
+        from: PaymentStatus,
+        to: PaymentStatus,
+        order_status: Option<OrderStatusTransition>,
+    ) -> SqlxResult<Option<PaymentTransaction>> {
+        self.retry
+            .write(|| async move {
+                let result = async {
+                    let mut tx = self.pool.begin().await?;
+
+                    let updated = sqlx::query_as!(
+                        PaymentTransaction,
+                        r#"
+                        UPDATE payment_transactions
+                        SET status = $3, updated_at = NOW()
+                        WHERE transaction_id = $1 AND status = $2 AND tenant_id = $4
+                        RETURNING
+                            transaction_id, order_id AS "order_id: OrderId", provider,
+                            provider_reference, payment_type AS "payment_type: PaymentType",
+                            payment_installments, amount, status AS "status: PaymentStatus",
+                            created_at, updated_at,
+                            'BRL'::VARCHAR AS "currency!: Currency"
+                        "#,
+                        transaction_id,
+                        from as PaymentStatus,
+                        to as PaymentStatus,
+                        self.tenant.as_str(),
+                    )
+                    .fetch_optional(&mut *tx)
+                    .await?;
+                    let Some(updated) = updated else {
+                        return Ok(None);
+                    };
+                    if let Some(order_status) = order_status {
+                        apply_order_transition(
+                            &mut tx,
+                            &self.tenant,
+                            &updated.order_id,
+                            order_status,
+                        )
+                        .await?;
+                    }
+
+                    tx.commit().await?;
+                    Ok(Some(updated))
+                }
+                .await;
+
+                if let Err(e) = &result {
+                    error!("Error updating payment transaction: {:?}", e);
+                }
+                result
+            })
+            .await
+    }
+}
+
+#[derive(Clone)]
+pub struct PgGeolocationRepository {
+    pool: PgPool,
+}
+
+impl PgGeolocationRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl GeolocationRepository for PgGeolocationRepository {
+    async fn find_by_prefixes(&self, prefixes: &[String]) -> SqlxResult<Vec<ZipLocation>> {
+        sqlx::query_as!(
+            ZipLocation,
+            r#"
+            SELECT geolocation_zip_code_prefix, geolocation_lat, geolocation_lng,
+                   geolocation_city, geolocation_state
+            FROM geolocation
+            WHERE geolocation_zip_code_prefix = ANY($1)
+            "#,
+            prefixes,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching zip code locations: {:?}", e);
+            e
+        })
+    }
+
+    async fn find_all(&self, limit: i64) -> SqlxResult<Vec<ZipLocation>> {
+        sqlx::query_as!(
+            ZipLocation,
+            r#"
+            SELECT geolocation_zip_code_prefix, geolocation_lat, geolocation_lng,
+                   geolocation_city, geolocation_state
+            FROM geolocation
+            ORDER BY geolocation_zip_code_prefix
+            LIMIT $1
+            "#,
+            limit,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error listing zip code locations: {:?}", e);
+            e
+        })
+    }
+
+    #[instrument(skip(self, locations), fields(count = locations.len()))]
+    async fn upsert_many(&self, locations: &[ZipLocation]) -> SqlxResult<u64> {
+        let prefixes: Vec<String> = locations
+            .iter()
+            .map(|l| l.geolocation_zip_code_prefix.clone())
+            .collect();
+        let lats: Vec<f64> = locations.iter().map(|l| l.geolocation_lat).collect();
+        let lngs: Vec<f64> = locations.iter().map(|l| l.geolocation_lng).collect();
+        let cities: Vec<String> = locations
+            .iter()
+            .map(|l| l.geolocation_city.clone())
+            .collect();
+        let states: Vec<String> = locations
+            .iter()
+            .map(|l| l.geolocation_state.clone())
+            .collect();
+
+        sqlx::query!(
+            r#"
+            INSERT INTO geolocation (
+                geolocation_zip_code_prefix, geolocation_lat, geolocation_lng,
+                geolocation_city, geolocation_state
+            )
+            SELECT * FROM UNNEST($1::text[], $2::float8[], $3::float8[], $4::text[], $5::text[])
+            ON CONFLICT (geolocation_zip_code_prefix) DO UPDATE
+            SET geolocation_lat = EXCLUDED.geolocation_lat,
+                geolocation_lng = EXCLUDED.geolocation_lng,
+                geolocation_city = EXCLUDED.geolocation_city,
+                geolocation_state = EXCLUDED.geolocation_state,
+                updated_at = NOW()
+            "#,
+            &prefixes,
+            &lats,
+            &lngs,
+            &cities,
+            &states,
+        )
+        .execute(&self.pool)
+        .await
+        .map(|result| result.rows_affected())
+        .map_err(|e| {
+            error!("Error saving zip code locations: {:?}", e);
+            e
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct PgStatsRepository {
+    reads: ReadPool,
+    retry: RetryPolicy,
+    tenant: TenantId,
+}
+
+impl PgStatsRepository {
+    pub fn new(reads: ReadPool, retry: RetryPolicy, tenant: TenantId) -> Self {
+        Self {
+            reads,
+            retry,
+            tenant,
+        }
+    }
+}
+
+#[async_trait]
+impl StatsRepository for PgStatsRepository {
+    async fn today(&self) -> SqlxResult<TodayStats> {
+        self.reads
+            .read(&self.retry, |pool| async move {
+                sqlx::query_as!(
+                    TodayStats,
+                    r#"
+                    SELECT
+                        CURRENT_DATE AS "stat_date!",
+                        COALESCE(today.orders_count, 0) AS "orders_count!",
+                        COALESCE(today.revenue, 0) AS "revenue!",
+                        (
+                            SELECT COALESCE(SUM(active_imports), 0)::BIGINT
+                            FROM stats WHERE tenant_id = $1
+                        ) AS "active_imports!",
+                        'BRL'::VARCHAR AS "currency!: Currency"
+                    FROM (SELECT 1) AS one
+                    LEFT JOIN stats today ON today.tenant_id = $1 AND today.stat_date = CURRENT_DATE
+                    "#,
+                    self.tenant.as_str(),
+                )
+                .fetch_one(pool)
+                .await
+                .map_err(|e| {
+                    error!("Error fetching today's stats: {:?}", e);
+                    e
+                })
+            })
+            .await
+    }
+}
+
+/// Columns selected for `WebhookSubscription` listings; the single-row queries spell them out
+/// for `query_as!`.
+const WEBHOOK_SUBSCRIPTION_COLUMNS: &str = r#"
+    subscription_id, url, events, customer_states, payload_template, secret, created_by,
+    created_at
+"#;
+
+/// Columns selected for `WebhookDelivery` listings.
+const WEBHOOK_DELIVERY_COLUMNS: &str = r#"
+    delivery_id, subscription_id, event, payload, status, attempts, next_attempt_at,
+    last_status_code, last_error, created_at, delivered_at
+"#;
+
+#[derive(Clone)]
+pub struct PgWebhookRepository {
+    pool: PgPool,
+    tenant: TenantId,
+}
+
+impl PgWebhookRepository {
+    pub fn new(pool: PgPool, tenant: TenantId) -> Self {
+        Self { pool, tenant }
+    }
+}
+
+#[async_trait]
+impl WebhookRepository for PgWebhookRepository {
+    async fn create_subscription(
+        &self,
+        url: &str,
+        events: &[String],
+        customer_states: Option<&[String]>,
+        payload_template: WebhookPayloadTemplate,
+        secret: &str,
+        created_by: &str,
+    ) -> SqlxResult<WebhookSubscription> {
+        sqlx::query_as!(
+            WebhookSubscription,
+            r#"
+            INSERT INTO webhook_subscriptions
+                (url, events, customer_states, payload_template, secret, created_by, tenant_id)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING subscription_id, url, events, customer_states,
+                      payload_template AS "payload_template: WebhookPayloadTemplate", secret,
+                      created_by, created_at
+            "#,
+            url,
+            events,
+            customer_states,
+            payload_template as WebhookPayloadTemplate,
+            secret,
+            created_by,
+            self.tenant.as_str(),
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error creating webhook subscription: {:?}", e);
+            e
+        })
+    }
+
+    async fn find_subscriptions(
+        &self,
+        pagination: &PaginationParams,
+    ) -> SqlxResult<(Vec<WebhookSubscription>, i64)> {
+        let (limit, offset, _, _) = pagination.normalize();
+
+        let rows = sqlx::query_as::<_, Counted<WebhookSubscription>>(&format!(
+            r#"
+            SELECT {}, COUNT(*) OVER () AS total_count
+            FROM webhook_subscriptions
+            WHERE tenant_id = $3
+            ORDER BY subscription_id
+            LIMIT $1 OFFSET $2
+            "#,
+            WEBHOOK_SUBSCRIPTION_COLUMNS
+        ))
+        .bind(limit)
+        .bind(offset)
+        .bind(self.tenant.as_str())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching webhook subscriptions: {:?}", e);
+            e
+        })?;
+
+        match split_counted(rows, offset) {
+            (subscriptions, Some(total_count)) => Ok((subscriptions, total_count)),
+            // Past the last page no row carries the window total.
+            (subscriptions, None) => {
+                let count = sqlx::query_scalar!(
+                    r#"SELECT COUNT(*) AS "count!" FROM webhook_subscriptions WHERE tenant_id = $1"#,
+                    self.tenant.as_str(),
+                )
+                .fetch_one(&self.pool)
+                .await
+                .map_err(|e| {
+                    error!("Error counting webhook subscriptions: {:?}", e);
+                    e
+                })?;
+                Ok((subscriptions, count))
+            }
+        }
+    }
+
+    async fn find_subscription(
+        &self,
+        subscription_id: i64,
+    ) -> SqlxResult<Option<WebhookSubscription>> {
+        sqlx::query_as!(
+            WebhookSubscription,
+            r#"
+            SELECT subscription_id, url, events, customer_states,
+                   payload_template AS "payload_template: WebhookPayloadTemplate", secret,
+                   created_by, created_at
+            FROM webhook_subscriptions WHERE subscription_id = $1 AND tenant_id = $2
+            "#,
+            subscription_id,
+            self.tenant.as_str(),
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching webhook subscription by id: {:?}", e);
+            e
+        })
+    }
+
+    async fn delete_subscription(
+        &self,
+        subscription_id: i64,
+    ) -> SqlxResult<Option<chrono::NaiveDateTime>> {
+        let result = sqlx::query_scalar!(
+            r#"
+            DELETE FROM webhook_subscriptions WHERE subscription_id = $1 AND tenant_id = $2
+            RETURNING LOCALTIMESTAMP AS "deleted_at!"
+            "#,
+            subscription_id,
+            self.tenant.as_str(),
+        )
+        .fetch_optional(&self.pool)
+        .await;
+
+        match &result {
+            Ok(Some(_)) => info!("Deleted webhook subscription {}", subscription_id),
+            Ok(None) => info!(
+                "Webhook subscription {} not found for deletion",
+                subscription_id
+            ),
+            Err(e) => error!("Error deleting webhook subscription: {:?}", e),
+        }
+
+        result
+    }
+
+    async fn find_deliveries(
+        &self,
+        subscription_id: i64,
+        status: Option<&str>,
+        pagination: &PaginationParams,
+    ) -> SqlxResult<(Vec<WebhookDelivery>, i64)> {
+        let (limit, offset, _, _) = pagination.normalize();
+
+        let rows = sqlx::query_as::<_, Counted<WebhookDelivery>>(&format!(
+            r#"
+            SELECT {}, COUNT(*) OVER () AS total_count
+            FROM webhook_deliveries
+            WHERE subscription_id = $1 AND ($2::text IS NULL OR status = $2) AND tenant_id = $5
+            ORDER BY created_at DESC, delivery_id DESC
+            LIMIT $3 OFFSET $4
+            "#,
+            WEBHOOK_DELIVERY_COLUMNS
+        ))
+        .bind(subscription_id)
+        .bind(status)
+        .bind(limit)
+        .bind(offset)
+        .bind(self.tenant.as_str())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching webhook deliveries: {:?}", e);
+            e
+        })?;
+
+        match split_counted(rows, offset) {
+            (deliveries, Some(total_count)) => Ok((deliveries, total_count)),
+            (deliveries, None) => {
+                let count = sqlx::query_scalar!(
+                    r#"
+                    SELECT COUNT(*) AS "count!" FROM webhook_deliveries
+                    WHERE subscription_id = $1 AND ($2::text IS NULL OR status = $2) AND tenant_id = $3
+                    "#,
+                    subscription_id,
+                    status,
+                    self.tenant.as_str(),
+                )
+                .fetch_one(&self.pool)
+                .await
+                .map_err(|e| {
+                    error!("Error counting webhook deliveries: {:?}", e);
+                    e
+                })?;
+                Ok((deliveries, count))
+            }
+        }
+    }
+
+    async fn find_delivery(
+        &self,
+        subscription_id: i64,
+        delivery_id: i64,
+    ) -> SqlxResult<Option<WebhookDelivery>> {
+        sqlx::query_as!(
+            WebhookDelivery,
+            r#"
+            SELECT
+                delivery_id, subscription_id, event, payload, status, attempts, next_attempt_at,
+                last_status_code, last_error, created_at, delivered_at
+            FROM webhook_deliveries
+            WHERE subscription_id = $1 AND delivery_id = $2 AND tenant_id = $3
+            "#,
+            subscription_id,
+            delivery_id,
+            self.tenant.as_str(),
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching webhook delivery by id: {:?}", e);
+            e
+        })
+    }
+
+    async fn claim_due_deliveries(
+        &self,
+        limit: i64,
+        lease_seconds: i64,
+    ) -> SqlxResult<Vec<PendingWebhookDelivery>> {
+        sqlx::query_as!(
+            PendingWebhookDelivery,
+            r#"
+            UPDATE webhook_deliveries d
+            SET attempts = d.attempts + 1,
+                next_attempt_at = NOW() + make_interval(secs => $2)
+            FROM webhook_subscriptions s
+            WHERE s.subscription_id = d.subscription_id
+              AND d.delivery_id IN (
+                  SELECT delivery_id FROM webhook_deliveries
+                  WHERE status = 'pending' AND next_attempt_at <= NOW() AND tenant_id = $3
+                  ORDER BY next_attempt_at, delivery_id
+                  LIMIT $1
+                  FOR UPDATE SKIP LOCKED
+              )
+            RETURNING d.delivery_id, d.event, d.payload, d.attempts, d.created_at, s.url, s.secret,
+                s.customer_states,
+                s.payload_template AS "payload_template: WebhookPayloadTemplate",
+                (
+                    SELECT c.customer_state
+                    FROM orders o
+                    JOIN customers c
+                        ON c.tenant_id = o.tenant_id AND c.customer_id = o.customer_id
+                    WHERE o.tenant_id = d.tenant_id AND o.order_id = d.payload->>'order_id'
+                ) AS customer_state
+            "#,
+            limit,
+            lease_seconds as f64,
+            self.tenant.as_str(),
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error claiming webhook deliveries: {:?}", e);
+            e
+        })
+    }
+
+    async fn mark_delivered(&self, delivery_id: i64, status_code: i32) -> SqlxResult<()> {
+        sqlx::query!(
+            r#"
+            UPDATE webhook_deliveries
+            SET status = 'succeeded', last_status_code = $2, last_error = NULL,
+                next_attempt_at = NULL, delivered_at = NOW()
+            WHERE delivery_id = $1
+            "#,
+            delivery_id,
+            status_code,
+        )
+        .execute(&self.pool)
+        .await
+        .map(|_| ())
+        .map_err(|e| {
+            error!("Error recording webhook delivery: {:?}", e);
+            e
+        })
+    }
+
+    async fn discard_delivery(&self, delivery_id: i64) -> SqlxResult<()> {
+        sqlx::query!(
+            "DELETE FROM webhook_deliveries WHERE delivery_id = $1",
+            delivery_id,
+        )
+        .execute(&self.pool)
+        .await
+        .map(|_| ())
+        .map_err(|e| {
+            error!("Error dropping webhook delivery: {:?}", e);
+            e
+        })
+    }
+
+    async fn mark_attempt_failed(
+        &self,
+        delivery_id: i64,
+        status_code: Option<i32>,
+        error: &str,
+        retry_in_seconds: Option<i64>,
+    ) -> SqlxResult<()> {
+        sqlx::query!(
+            r#"
+            UPDATE webhook_deliveries
+            SET status = CASE WHEN $4::float8 IS NULL THEN 'failed' ELSE 'pending' END,
+                last_status_code = $2, last_error = $3,
+                next_attempt_at = NOW() + make_interval(secs => $4::float8)
+            WHERE delivery_id = $1
+            "#,
+            delivery_id,
+            status_code,
+            error,
+            retry_in_seconds.map(|seconds| seconds as f64),
+        )
+        .execute(&self.pool)
+        .await
+        .map(|_| ())
+        .map_err(|e| {
+            error!("Error recording failed webhook delivery: {:?}", e);
+            e
+        })
+    }
+
+    #[instrument(skip(self))]
+    async fn requeue_failed_deliveries(&self, max_age_hours: i64) -> SqlxResult<u64> {
+        sqlx::query!(
+            r#"
+            UPDATE webhook_deliveries
+            SET status = 'pending', next_attempt_at = NOW()
+            WHERE status = 'failed' AND tenant_id = $2
+              AND created_at >= NOW() - make_interval(hours => $1::int)
+            "#,
+            max_age_hours as i32,
+            self.tenant.as_str(),
+        )
+        .execute(&self.pool)
+        .await
+        .map(|result| result.rows_affected())
+        .map_err(|e| {
+            error!("Error requeueing failed webhook deliveries: {:?}", e);
+            e
+        })
+    }
+}
+
+pub struct PgOutboxRepository {
+    pool: PgPool,
+    tenant: TenantId,
+}
+
+impl PgOutboxRepository {
+    pub fn new(pool: PgPool, tenant: TenantId) -> Self {
+        Self { pool, tenant }
+    }
+}
+
+#[async_trait]
+impl OutboxRepository for PgOutboxRepository {
+    async fn claim_due_events(
+        &self,
+        limit: i64,
+        lease_seconds: i64,
+    ) -> SqlxResult<Vec<OutboxEvent>> {
+        let result = async {
+            let mut tx = self.pool.begin().await?;
+
+            // Claims are serialized per tenant, so a relay starting meanwhile sees these leases
+            // and waits behind them rather than publishing later events first.
+            sqlx::query("SELECT pg_advisory_xact_lock(hashtext('outbox_relay:' || $1))")
+                .bind(self.tenant.as_str())
+                .execute(&mut *tx)
+                .await?;
+
+            let mut events = sqlx::query_as!(
+                OutboxEvent,
+                r#"
+                UPDATE outbox_events
+                SET next_attempt_at = NOW() + make_interval(secs => $2)
+                WHERE event_id IN (
+                    SELECT e.event_id
+                    FROM outbox_events e
+                    WHERE e.published_at IS NULL
+                      AND e.tenant_id = $3
+                      AND e.next_attempt_at <= NOW()
+                      AND NOT EXISTS (
+                          SELECT 1 FROM outbox_events earlier
+                          WHERE earlier.published_at IS NULL
+                            AND earlier.tenant_id = e.tenant_id
+                            AND earlier.event_id < e.event_id
+                            AND earlier.next_attempt_at > NOW()
+                      )
+                    ORDER BY e.event_id
+                    LIMIT $1
+                )
+                RETURNING
+                    event_id, tenant_id AS "tenant_id: TenantId", aggregate_type, aggregate_id,
+                    event_type, payload, attempts, created_at
+                "#,
+                limit,
+                lease_seconds as f64,
+                self.tenant.as_str(),
+            )
+            .fetch_all(&mut *tx)
+            .await?;
+
+            tx.commit().await?;
+            events.sort_by_key(|event| event.event_id);
+            Ok(events)
+        }
+        .await;
+
+        if let Err(e) = &result {
+            error!("Error claiming outbox events: {:?}", e);
+        }
+        result
+    }
+
+    async fn mark_published(&self, event_id: i64) -> SqlxResult<()> {
+        sqlx::query!(
+            r#"
+            WITH published AS (
+                UPDATE outbox_events
+                SET published_at = NOW(), next_attempt_at = NULL, last_error = NULL
+                WHERE event_id = $1 AND published_at IS NULL
+                RETURNING event_id, event_type, payload, tenant_id
+            ),
+            deliveries AS (
+                INSERT INTO webhook_deliveries (subscription_id, event, payload, tenant_id)
+                SELECT s.subscription_id, p.event_type, p.payload, p.tenant_id
+                FROM published p
+                JOIN webhook_subscriptions s
+                    ON s.tenant_id = p.tenant_id AND p.event_type = ANY(s.events)
+            )
+            INSERT INTO notifications (event_id, kind, payload, tenant_id)
+            SELECT event_id,
+                   CASE event_type
+                       WHEN 'review.created' THEN 'review_received'
+                       WHEN 'order.late' THEN 'order_late'
+                       ELSE 'order_' || (payload->>'order_status')
+                   END,
+                   payload,
+                   tenant_id
+            FROM published
+            WHERE event_type IN ('review.created', 'order.late')
+               OR (event_type = 'order.status_changed'
+                   AND payload->>'order_status' IN ('approved', 'delivered'))
+            "#,
+            event_id,
+        )
+        .execute(&self.pool)
+        .await
+        .map(|_| ())
+        .map_err(|e| {
+            error!("Error marking outbox event published: {:?}", e);
+            e
+        })
+    }
+
+    async fn mark_publish_failed(
+        &self,
+        event_id: i64,
+        error: &str,
+        retry_in_seconds: i64,
+    ) -> SqlxResult<()> {
+        sqlx::query!(
+            r#"
+            UPDATE outbox_events
+            SET attempts = attempts + 1, last_error = $2,
+                next_attempt_at = NOW() + make_interval(secs => $3)
+            WHERE event_id = $1
+            "#,
+            event_id,
+            error,
+            retry_in_seconds as f64,
+        )
+        .execute(&self.pool)
+        .await
+        .map(|_| ())
+        .map_err(|e| {
+            error!("Error recording failed outbox publish: {:?}", e);
+            e
+        })
+    }
+
+    async fn release_events(&self, event_ids: &[i64]) -> SqlxResult<()> {
+        if event_ids.is_empty() {
+            return Ok(());
+        }
+        sqlx::query!(
+            r#"
+            UPDATE outbox_events SET next_attempt_at = NOW()
+            WHERE event_id = ANY($1) AND published_at IS NULL
+            "#,
+            event_ids,
+        )
+        .execute(&self.pool)
+        .await
+        .map(|_| ())
+        .map_err(|e| {
+            error!("Error releasing outbox events: {:?}", e);
+            e
+        })
+    }
+
+    async fn backlog(&self) -> SqlxResult<OutboxBacklog> {
+        sqlx::query_as!(
+            OutboxBacklog,
+            r#"
+            SELECT
+                COUNT(*) AS "pending!",
+                EXTRACT(EPOCH FROM LOCALTIMESTAMP - MIN(created_at))::float8 AS oldest_age_seconds
+            FROM outbox_events
+            WHERE published_at IS NULL AND tenant_id = $1
+            "#,
+            self.tenant.as_str(),
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error reading outbox backlog: {:?}", e);
+            e
+        })
+    }
+
+    async fn delete_published_before(&self, days: i64) -> SqlxResult<u64> {
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM outbox_events
+            WHERE published_at < NOW() - make_interval(days => $1) AND tenant_id = $2
+            "#,
+            days as i32,
+            self.tenant.as_str(),
+        )
+        .execute(&self.pool)
+        .await
+        .map(|result| result.rows_affected());
+
+        match &result {
+            Ok(deleted) if *deleted > 0 => info!("Deleted {} published outbox events", deleted),
+            Ok(_) => {}
+            Err(e) => error!("Error deleting published outbox events: {:?}", e),
+        }
+        result
+    }
+}
+
+/// Columns selected for `Notification` listings; `find_notification` spells them out for
+/// `query_as!`.
+const NOTIFICATION_COLUMNS: &str = r#"
+    notification_id, event_id, kind, payload, status, channel, recipient, subject, attempts,
+    next_attempt_at, last_error, created_at, sent_at
+"#;
+
+pub struct PgNotificationRepository {
+    pool: PgPool,
+    tenant: TenantId,
+}
+
+impl PgNotificationRepository {
+    pub fn new(pool: PgPool, tenant: TenantId) -> Self {
+        Self { pool, tenant }
+    }
+}
+
+#[async_trait]
+impl NotificationRepository for PgNotificationRepository {
+    async fn find_notifications(
+        &self,
+        status: Option<&str>,
+        kind: Option<&str>,
+        pagination: &PaginationParams,
+    ) -> SqlxResult<(Vec<Notification>, i64)> {
+        let (limit, offset, _, _) = pagination.normalize();
+
+        let rows = sqlx::query_as::<_, Counted<Notification>>(&format!(
+            r#"
+            SELECT {}, COUNT(*) OVER () AS total_count
+            FROM notifications
+            WHERE ($1::text IS NULL OR status = $1) AND ($2::text IS NULL OR kind = $2)
+              AND tenant_id = $5
+            ORDER BY created_at DESC, notification_id DESC
+            LIMIT $3 OFFSET $4
+            "#,
+            NOTIFICATION_COLUMNS
+        ))
+        .bind(status)
+        .bind(kind)
+        .bind(limit)
+        .bind(offset)
+        .bind(self.tenant.as_str())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching notifications: {:?}", e);
+            e
+        })?;
+
+        match split_counted(rows, offset) {
+            (notifications, Some(total_count)) => Ok((notifications, total_count)),
+            (notifications, None) => {
+                let count = sqlx::query_scalar!(
+                    r#"
+                    SELECT COUNT(*) AS "count!" FROM notifications
+                    WHERE ($1::text IS NULL OR status = $1) AND ($2::text IS NULL OR kind = $2)
+                      AND tenant_id = $3
+                    "#,
+                    status,
+                    kind,
+                    self.tenant.as_str(),
+                )
+                .fetch_one(&self.pool)
+                .await
+                .map_err(|e| {
+                    error!("Error counting notifications: {:?}", e);
+                    e
+                })?;
+                Ok((notifications, count))
+            }
+        }
+    }
+
+    async fn find_notification(&self, notification_id: i64) -> SqlxResult<Option<Notification>> {
+        sqlx::query_as!(
+            Notification,
+            r#"
+            SELECT
+                notification_id, event_id, kind, payload, status, channel, recipient, subject,
+                attempts, next_attempt_at, last_error, created_at, sent_at
+            FROM notifications
+            WHERE notification_id = $1 AND tenant_id = $2
+            "#,
+            notification_id,
+            self.tenant.as_str(),
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching notification: {:?}", e);
+            e
+        })
+    }
+
+    async fn claim_due_notifications(
+        &self,
+        limit: i64,
+        lease_seconds: i64,
+    ) -> SqlxResult<Vec<PendingNotification>> {
+        sqlx::query_as!(
+            PendingNotification,
+            r#"
+            UPDATE notifications
+            SET attempts = attempts + 1,
+                next_attempt_at = NOW() + make_interval(secs => $2)
+            WHERE notification_id IN (
+                SELECT notification_id FROM notifications
+                WHERE status = 'pending' AND next_attempt_at <= NOW() AND tenant_id = $3
+                ORDER BY next_attempt_at, notification_id
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING notification_id, kind, payload, attempts
+            "#,
+            limit,
+            lease_seconds as f64,
+            self.tenant.as_str(),
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error claiming notifications: {:?}", e);
+            e
+        })
+    }
+
+    async fn mark_sent(
+        &self,
+        notification_id: i64,
+        attempt: &NotificationAttempt<'_>,
+    ) -> SqlxResult<()> {
+        sqlx::query!(
+            r#"
+            UPDATE notifications
+            SET status = 'sent', channel = $2, recipient = $3, subject = $4, last_error = NULL,
+                next_attempt_at = NULL, sent_at = NOW()
+            WHERE notification_id = $1
+            "#,
+            notification_id,
+            attempt.channel,
+            attempt.recipient,
+            attempt.subject,
+        )
+        .execute(&self.pool)
+        .await
+        .map(|_| ())
+        .map_err(|e| {
+            error!("Error recording notification: {:?}", e);
+            e
+        })
+    }
+
+    async fn mark_attempt_failed(
+        &self,
+        notification_id: i64,
+        attempt: &NotificationAttempt<'_>,
+        error: &str,
+        retry_in_seconds: Option<i64>,
+    ) -> SqlxResult<()> {
+        sqlx::query!(
+            r#"
+            UPDATE notifications
+            SET status = CASE WHEN $6::float8 IS NULL THEN 'failed' ELSE 'pending' END,
+                channel = $2, recipient = $3, subject = $4, last_error = $5,
+                next_attempt_at = NOW() + make_interval(secs => $6::float8)
+            WHERE notification_id = $1
+            "#,
+            notification_id,
+            attempt.channel,
+            attempt.recipient,
+            attempt.subject,
+            error,
+            retry_in_seconds.map(|seconds| seconds as f64),
+        )
+        .execute(&self.pool)
+        .await
+        .map(|_| ())
+        .map_err(|e| {
+            error!("Error recording failed notification: {:?}", e);
+            e
+        })
+    }
+}
+
+pub struct PgRetentionRepository {
+    pool: PgPool,
+    tenant: TenantId,
+}
+
+impl PgRetentionRepository {
+    pub fn new(pool: PgPool, tenant: TenantId) -> Self {
+        Self { pool, tenant }
+    }
+}
+
+#[async_trait]
+impl RetentionRepository for PgRetentionRepository {
+    async fn count_reviews_created_before(&self, before: chrono::NaiveDateTime) -> SqlxResult<i64> {
+        sqlx::query_scalar!(
+            r#"
+            SELECT
+                (SELECT COUNT(*) FROM reviews WHERE review_creation_date < $1 AND tenant_id = $2)
+                + (
+                    SELECT COUNT(*) FROM reviews_archive
+                    WHERE review_creation_date < $1 AND tenant_id = $2
+                )
+                AS "count!"
+            "#,
+            before,
+            self.tenant.as_str(),
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error counting reviews due for deletion: {:?}", e);
+            e
+        })
+    }
+
+    async fn delete_reviews_created_before(
+        &self,
+        before: chrono::NaiveDateTime,
+        limit: i64,
+    ) -> SqlxResult<u64> {
+        let result = async {
+            let mut tx = self.pool.begin().await?;
+
+            let live = sqlx::query!(
+                r#"
+                DELETE FROM reviews
+                WHERE review_id IN (
+                    SELECT review_id FROM reviews
+                    WHERE review_creation_date < $1 AND tenant_id = $3
+                    LIMIT $2
+                )
+                "#,
+                before,
+                limit,
+                self.tenant.as_str(),
+            )
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+
+            // The archive only gets what is left of the batch after the live table.
+            let remaining = limit - live as i64;
+            let archived = if remaining > 0 {
+                sqlx::query!(
+                    r#"
+                    DELETE FROM reviews_archive
+                    WHERE review_id IN (
+                        SELECT review_id FROM reviews_archive
+                        WHERE review_creation_date < $1 AND tenant_id = $3
+                        LIMIT $2
+                    )
+                    "#,
+                    before,
+                    remaining,
+                    self.tenant.as_str(),
+                )
+                .execute(&mut *tx)
+                .await?
+                .rows_affected()
+            } else {
+                0
+            };
+
+            tx.commit().await?;
+            Ok(live + archived)
+        }
+        .await;
+
+        if let Err(e) = &result {
+            error!("Error deleting reviews written before {}: {:?}", before, e);
+        }
+        result
+    }
+
+    async fn count_inactive_customers(&self, before: chrono::NaiveDateTime) -> SqlxResult<i64> {
+        sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*) AS "count!"
+            FROM customers c
+            WHERE c.customer_city <> 'anonymized'
+              AND c.tenant_id = $2
+              AND GREATEST(
+                  (SELECT MAX(order_purchase_timestamp) FROM orders o
+                   WHERE o.customer_id = c.customer_id),
+                  (SELECT MAX(order_purchase_timestamp) FROM orders_archive a
+                   WHERE a.customer_id = c.customer_id),
+                  (SELECT MAX(valid_from) FROM customer_location_history h
+                   WHERE h.customer_id = c.customer_id)
+              ) < $1
+            "#,
+            before,
+            self.tenant.as_str(),
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error counting inactive customers: {:?}", e);
+            e
+        })
+    }
+
+    async fn find_inactive_customers(
+        &self,
+        before: chrono::NaiveDateTime,
+        limit: i64,
+    ) -> SqlxResult<Vec<CustomerId>> {
+        // GREATEST skips NULLs, so only customers without any known activity compare as NULL
+        sqlx::query_scalar!(
+            r#"
+            SELECT c.customer_id AS "customer_id!: CustomerId"
+            FROM customers c
+            WHERE c.customer_city <> 'anonymized'
+              AND c.tenant_id = $3
+              AND GREATEST(
+                  (SELECT MAX(order_purchase_timestamp) FROM orders o
+                   WHERE o.customer_id = c.customer_id),
+                  (SELECT MAX(order_purchase_timestamp) FROM orders_archive a
+                   WHERE a.customer_id = c.customer_id),
+                  (SELECT MAX(valid_from) FROM customer_location_history h
+                   WHERE h.customer_id = c.customer_id)
+              ) < $1
+            ORDER BY c.customer_id
+            LIMIT $2
+            "#,
+            before,
+            limit,
+            self.tenant.as_str(),
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error finding inactive customers: {:?}", e);
+            e
+        })
+    }
+}
@@ -0,0 +1,282 @@
+//! Retrying of database calls that failed for transient reasons, so a brief Postgres failover
+//! or a serialization conflict is absorbed instead of surfacing as a `500`.
+
+use std::future::Future;
+use std::time::Duration;
+
+use sqlx::Result as SqlxResult;
+use tracing::warn;
+
+/// SQLSTATEs after which the statement certainly did not take effect: a serialization
+/// failure or deadlock rolled the transaction back, a demoted primary refused the write, or the
+/// server refused the connection while starting up or failing over.
+const ROLLED_BACK_STATES: &[&str] = &["40001", "40P01", "25006", "57P03", "08001", "08004"];
+
+/// SQLSTATEs of a connection lost mid-call (`08xxx`, administrator or crash shutdown). The
+/// statement may or may not have committed, so only reads retry them.
+const CONNECTION_LOST_STATES: &[&str] = &["08000", "08003", "08006", "57P01", "57P02"];
+
+/// How often, and how patiently, transient failures are retried.
+#[derive(Clone, Copy, Debug)]
+pub struct RetryPolicy {
+    /// Attempts in total, the first included; 1 disables retries.
+    pub max_attempts: u32,
+    /// Upper bound of the wait before the first retry; it doubles with every further one.
+    pub base_delay: Duration,
+    /// Longest wait between two attempts.
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(50),
+            max_delay: Duration::from_secs(1),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Runs a query that changes nothing, retrying it after any transient failure, dropped
+    /// connections included.
+    pub async fn read<T, F, Fut>(&self, operation: F) -> SqlxResult<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = SqlxResult<T>>,
+    {
+        self.run(operation, |e| is_rolled_back(e) || is_connection_lost(e))
+            .await
+    }
+
+    /// Runs a write, retrying it only after failures that guarantee it did not take effect. A
+    /// connection lost mid-write is returned as is, since the write may have committed.
+    pub async fn write<T, F, Fut>(&self, operation: F) -> SqlxResult<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = SqlxResult<T>>,
+    {
+        self.run(operation, is_rolled_back).await
+    }
+
+    async fn run<T, F, Fut>(
+        &self,
+        mut operation: F,
+        retryable: impl Fn(&sqlx::Error) -> bool,
+    ) -> SqlxResult<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = SqlxResult<T>>,
+    {
+        let mut attempt = 1;
+        loop {
+            match operation().await {
+                Err(e) if attempt < self.max_attempts && retryable(&e) => {
+                    let delay = self.delay(attempt);
+                    warn!(
+                        "Transient database error on attempt {} of {}, retrying in {:?}: {}",
+                        attempt, self.max_attempts, delay, e
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// A random wait up to the exponential backoff for `attempt` ("full jitter"), so callers
+    /// that failed together don't retry together.
+    fn delay(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        let ceiling = self.base_delay.saturating_mul(factor).min(self.max_delay);
+        ceiling.mul_f64(rand::random::<f64>())
+    }
+}
+
+fn sqlstate(e: &sqlx::Error) -> Option<String> {
+    match e {
+        sqlx::Error::Database(db_err) => db_err.code().map(|code| code.into_owned()),
+        _ => None,
+    }
+}
+
+fn is_rolled_back(e: &sqlx::Error) -> bool {
+    matches!(e, sqlx::Error::PoolTimedOut)
+        || sqlstate(e).is_some_and(|code| ROLLED_BACK_STATES.contains(&code.as_str()))
+}
+
+fn is_connection_lost(e: &sqlx::Error) -> bool {
+    matches!(e, sqlx::Error::Io(_))
+        || sqlstate(e).is_some_and(|code| CONNECTION_LOST_STATES.contains(&code.as_str()))
+}
+
+/// Whether `e` says the server couldn't be reached or is going away, rather than that the
+/// query itself failed.
+pub(crate) fn is_unavailable(e: &sqlx::Error) -> bool {
+    matches!(
+        e,
+        sqlx::Error::PoolTimedOut | sqlx::Error::PoolClosed | sqlx::Error::Tls(_)
+    ) || is_connection_lost(e)
+        || sqlstate(e).is_some_and(|code| code.starts_with("08") || code == "57P03")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::error::{DatabaseError, ErrorKind};
+    use std::borrow::Cow;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    /// A server error carrying just a SQLSTATE.
+    #[derive(Debug)]
+    struct StateError(&'static str);
+
+    impl std::fmt::Display for StateError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "SQLSTATE {}", self.0)
+        }
+    }
+
+    impl std::error::Error for StateError {}
+
+    impl DatabaseError for StateError {
+        fn message(&self) -> &str {
+            self.0
+        }
+
+        fn code(&self) -> Option<Cow<'_, str>> {
+            Some(Cow::Borrowed(self.0))
+        }
+
+        fn as_error(&self) -> &(dyn std::error::Error + Send + Sync + 'static) {
+            self
+        }
+
+        fn as_error_mut(&mut self) -> &mut (dyn std::error::Error + Send + Sync + 'static) {
+            self
+        }
+
+        fn into_error(self: Box<Self>) -> Box<dyn std::error::Error + Send + Sync + 'static> {
+            self
+        }
+
+        fn kind(&self) -> ErrorKind {
+            ErrorKind::Other
+        }
+    }
+
+    fn state(code: &'static str) -> sqlx::Error {
+        sqlx::Error::database(StateError(code))
+    }
+
+    fn reset() -> sqlx::Error {
+        sqlx::Error::Io(std::io::ErrorKind::ConnectionReset.into())
+    }
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(1),
+        }
+    }
+
+    /// Runs `policy` over an operation failing with `error` every time, and returns how many
+    /// attempts it made.
+    async fn attempts(write: bool, error: fn() -> sqlx::Error) -> u32 {
+        let calls = AtomicU32::new(0);
+        let operation = || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err::<(), _>(error())
+        };
+        let result = if write {
+            policy().write(operation).await
+        } else {
+            policy().read(operation).await
+        };
+        assert!(result.is_err());
+        calls.into_inner()
+    }
+
+    #[test]
+    fn classifies_rolled_back_states() {
+        for code in ["40001", "40P01", "25006", "57P03", "08001", "08004"] {
+            assert!(is_rolled_back(&state(code)), "{code}");
+            assert!(!is_connection_lost(&state(code)), "{code}");
+        }
+        assert!(is_rolled_back(&sqlx::Error::PoolTimedOut));
+    }
+
+    #[test]
+    fn classifies_connection_lost_states() {
+        for code in ["08000", "08003", "08006", "57P01", "57P02"] {
+            assert!(is_connection_lost(&state(code)), "{code}");
+            assert!(!is_rolled_back(&state(code)), "{code}");
+        }
+        assert!(is_connection_lost(&reset()));
+    }
+
+    #[test]
+    fn leaves_query_errors_alone() {
+        for code in ["23505", "23503", "42P01", "22P02"] {
+            assert!(!is_rolled_back(&state(code)), "{code}");
+            assert!(!is_connection_lost(&state(code)), "{code}");
+            assert!(!is_unavailable(&state(code)), "{code}");
+        }
+        assert!(!is_rolled_back(&sqlx::Error::RowNotFound));
+        assert!(!is_unavailable(&sqlx::Error::RowNotFound));
+    }
+
+    #[test]
+    fn reports_unreachable_servers_as_unavailable() {
+        for code in ["08000", "08001", "08P01", "57P01", "57P03"] {
+            assert!(is_unavailable(&state(code)), "{code}");
+        }
+        assert!(is_unavailable(&sqlx::Error::PoolTimedOut));
+        assert!(is_unavailable(&sqlx::Error::PoolClosed));
+        assert!(!is_unavailable(&state("40001")));
+    }
+
+    #[test]
+    fn caps_the_backoff() {
+        let policy = RetryPolicy {
+            max_attempts: 10,
+            base_delay: Duration::from_millis(50),
+            max_delay: Duration::from_millis(120),
+        };
+        assert!(policy.delay(1) <= Duration::from_millis(50));
+        assert!(policy.delay(2) <= Duration::from_millis(100));
+        assert!(policy.delay(9) <= Duration::from_millis(120));
+        assert!(policy.delay(u32::MAX) <= Duration::from_millis(120));
+    }
+
+    #[tokio::test]
+    async fn reads_retry_lost_connections() {
+        assert_eq!(attempts(false, || state("40001")).await, 3);
+        assert_eq!(attempts(false, reset).await, 3);
+        assert_eq!(attempts(false, || state("23505")).await, 1);
+    }
+
+    #[tokio::test]
+    async fn writes_retry_only_what_rolled_back() {
+        assert_eq!(attempts(true, || state("40P01")).await, 3);
+        assert_eq!(attempts(true, reset).await, 1);
+        assert_eq!(attempts(true, || state("57P01")).await, 1);
+    }
+
+    #[tokio::test]
+    async fn stops_retrying_once_an_attempt_succeeds() {
+        let calls = AtomicU32::new(0);
+        let result = policy()
+            .write(|| async {
+                match calls.fetch_add(1, Ordering::SeqCst) {
+                    0 => Err(state("40001")),
+                    n => Ok(n),
+                }
+            })
+            .await;
+        assert_eq!(result.unwrap(), 1);
+        assert_eq!(calls.into_inner(), 2);
+    }
+}
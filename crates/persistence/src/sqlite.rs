@@ -0,0 +1,6795 @@
+//! SQLite implementations of the repository traits, for demos and tests without Postgres.
+//!
+//! They run against the schema in `migrations/sqlite`. Postgres-only features degrade rather
+//! than fail: estimated totals are exact counts, city search is a substring match on the
+//! canonical city, similarity search compares embeddings in memory, and there are no
+//! materialized views, search indexes or replicas to maintain.
+
+use bigdecimal::BigDecimal;
+use domain::cities::fold_city;
+use domain::geo::GeoBounds;
+use domain::ids::{CustomerId, OrderId, ProductId, SellerId};
+use domain::models::{
+    AddItemToOrderDto, AppliedMigration, ArchivedOrders, AuditEntry, AuditFilter, BatchResume,
+    BrazilState, Category, Coupon, CreateCategoryDto, CreateCouponDto, CreateCustomerAddressDto,
+    CreateCustomerDto, CreateOrderDto, CreateProductDto, CreateSellerDto, CreateStockLocationDto,
+    CreateSupportCaseDto, CreateSupportMessageDto, Customer, CustomerAddress, CustomerDependents,
+    CustomerFilter, CustomerLocationVersion, CustomerMerge, DataQualityReport, DataQualityRule,
+    DataQualityRuleResult, DuplicatedCustomer, DuplicationBucket, FilterValue, FlaggedOrder,
+    ImportBatch, ImportBatchStatus, ImportRowError, LateOrder, LoadJobBatch, LocatedSeller,
+    LocationStock, NewAuditEntry, NewImportRowError, NewOrderAmendment, NewPaymentTransaction,
+    NewProductImage, NewRefund, Notification, NotificationAttempt, Order, OrderAmendment,
+    OrderFilter, OrderFinancials, OrderIssue, OrderItem, OrderItemOrigin, OrderProduct,
+    OrderStatusChange, OutboxBacklog, OutboxEvent, PaginationParams, Payment,
+    PaymentAnalyticsFilter, PaymentMethodStats, PaymentMismatch, PaymentStatus, PaymentTransaction,
+    PendingNotification, PendingWebhookDelivery, PoolStats, Product, ProductFilter, ProductImage,
+    ProductIssue, ProductLocationStock, RecommendedProduct, ReconciliationFilter, Refund, Review,
+    ReviewText, SampleStratum, Seller, SellerBadgeThreshold, SellerFilter, SellerMetrics,
+    Sentiment, SentimentReview, SimilarProduct, SparseRow, StockAllocation, StockLocation,
+    StoredLoadJob, SupportCase, SupportCaseFilter, SupportCaseVolume, SupportMessage, TableStats,
+    TodayStats, Total, TotalMode, UpdateCategoryDto, UpdateCustomerAddressDto, UpdateCustomerDto,
+    UpdateProductDto, UpdateSupportCaseDto, WebhookDelivery, WebhookPayloadTemplate,
+    WebhookSubscription, ZipLocation,
+};
+use domain::money::{Money, round_to_centavos};
+use domain::repositories::{
+    AuditRepository, CategoryRepository, CouponRepository, CustomerRepository,
+    DataQualityRepository, DiagnosticsRepository, EmbeddingRepository, GeolocationRepository,
+    ImportRepository, InventoryRepository, LateOrderRepository, MaintenanceRepository,
+    NotificationRepository, OrderRepository, OrderStatusTransition, OutboxRepository,
+    PaymentTransactionRepository, ProductRepository, RetentionRepository,
+    ReviewSentimentRepository, SellerRepository, StatsRepository, SupportRepository,
+    WebhookRepository,
+};
+use domain::tenancy::TenantId;
+
+use crate::quality;
+use crate::repositories::{Counted, split_counted};
+
+use async_trait::async_trait;
+use futures::future::BoxFuture;
+use futures::stream::BoxStream;
+use sha2::{Digest, Sha256};
+use sqlx::query::QueryAs;
+use sqlx::sqlite::{SqliteArguments, SqliteRow};
+use sqlx::types::Json;
+use sqlx::{
+    Acquire, FromRow, Result as SqlxResult, Row, Sqlite, SqliteConnection, SqliteExecutor,
+    SqlitePool,
+};
+use std::collections::HashMap;
+use std::str::FromStr;
+use tracing::{error, info, instrument};
+
+impl<'r, T: FromRow<'r, SqliteRow>> FromRow<'r, SqliteRow> for Counted<T> {
+    fn from_row(row: &'r SqliteRow) -> SqlxResult<Self> {
+        Ok(Self {
+            row: T::from_row(row)?,
+            total_count: row.try_get("total_count")?,
+        })
+    }
+}
+
+/// A model read from a SQLite row by hand, for the models whose fields SQLite can't decode
+/// directly (`NUMERIC` money columns, and badge and event arrays).
+struct Decoded<T>(T);
+
+/// Reads a money column, as a `BigDecimal` or a `Money`. SQLite keeps `NUMERIC` values as
+/// integers or floats, so the text form is parsed and rounded back to the two decimals the
+/// Postgres columns carry.
+fn decimal<T: From<BigDecimal>>(row: &SqliteRow, column: &str) -> SqlxResult<T> {
+    let text: String = row.try_get_unchecked(column)?;
+    parse_decimal(&text, column)
+}
+
+/// [`decimal`] for a nullable column.
+fn optional_decimal<T: From<BigDecimal>>(row: &SqliteRow, column: &str) -> SqlxResult<Option<T>> {
+    let text: Option<String> = row.try_get_unchecked(column)?;
+    text.map(|text| parse_decimal(&text, column)).transpose()
+}
+
+fn parse_decimal<T: From<BigDecimal>>(text: &str, column: &str) -> SqlxResult<T> {
+    BigDecimal::from_str(text)
+        .map(|value| round_to_centavos(&value).into())
+        .map_err(|e| sqlx::Error::ColumnDecode {
+            index: column.to_string(),
+            source: Box::new(e),
+        })
+}
+
+impl FromRow<'_, SqliteRow> for Decoded<Seller> {
+    fn from_row(row: &SqliteRow) -> SqlxResult<Self> {
+        let Json(badges): Json<Vec<String>> = row.try_get("badges")?;
+        Ok(Self(Seller {
+            seller_id: row.try_get("seller_id")?,
+            seller_zip_code_prefix: row.try_get("seller_zip_code_prefix")?,
+            seller_city: row.try_get("seller_city")?,
+            canonical_city: row.try_get("canonical_city")?,
+            seller_state: row.try_get("seller_state")?,
+            badges,
+        }))
+    }
+}
+
+impl FromRow<'_, SqliteRow> for Decoded<LocatedSeller> {
+    fn from_row(row: &SqliteRow) -> SqlxResult<Self> {
+        let Decoded(seller) = Decoded::<Seller>::from_row(row)?;
+        Ok(Self(LocatedSeller {
+            seller,
+            geolocation_lat: row.try_get("geolocation_lat")?,
+            geolocation_lng: row.try_get("geolocation_lng")?,
+        }))
+    }
+}
+
+impl FromRow<'_, SqliteRow> for Decoded<Coupon> {
+    fn from_row(row: &SqliteRow) -> SqlxResult<Self> {
+        let min_order_value: Option<String> = row.try_get_unchecked("min_order_value")?;
+        Ok(Self(Coupon {
+            code: row.try_get("code")?,
+            discount_type: row.try_get("discount_type")?,
+            value: decimal(row, "value")?,
+            min_order_value: match min_order_value {
+                Some(_) => Some(decimal(row, "min_order_value")?),
+                None => None,
+            },
+            expires_at: row.try_get("expires_at")?,
+            max_uses: row.try_get("max_uses")?,
+            times_used: row.try_get("times_used")?,
+            created_at: row.try_get("created_at")?,
+        }))
+    }
+}
+
+impl FromRow<'_, SqliteRow> for Decoded<SellerBadgeThreshold> {
+    fn from_row(row: &SqliteRow) -> SqlxResult<Self> {
+        Ok(Self(SellerBadgeThreshold {
+            badge: row.try_get("badge")?,
+            description: row.try_get("description")?,
+            metric: row.try_get("metric")?,
+            comparison: row.try_get("comparison")?,
+            threshold: decimal(row, "threshold")?,
+            min_sample: row.try_get("min_sample")?,
+        }))
+    }
+}
+
+impl FromRow<'_, SqliteRow> for Decoded<OrderItem> {
+    fn from_row(row: &SqliteRow) -> SqlxResult<Self> {
+        Ok(Self(OrderItem {
+            order_item_id: row.try_get("order_item_id")?,
+            order_id: row.try_get("order_id")?,
+            product_id: row.try_get("product_id")?,
+            seller_id: row.try_get("seller_id")?,
+            shipping_limit_date: row.try_get("shipping_limit_date")?,
+            price: decimal(row, "price")?,
+            freight_value: decimal(row, "freight_value")?,
+            currency: Money::CURRENCY,
+        }))
+    }
+}
+
+impl FromRow<'_, SqliteRow> for Decoded<OrderItemOrigin> {
+    fn from_row(row: &SqliteRow) -> SqlxResult<Self> {
+        Ok(Self(OrderItemOrigin {
+            item: Decoded::<OrderItem>::from_row(row)?.0,
+            seller_zip_code_prefix: row.try_get("seller_zip_code_prefix")?,
+        }))
+    }
+}
+
+impl FromRow<'_, SqliteRow> for Decoded<Product> {
+    fn from_row(row: &SqliteRow) -> SqlxResult<Self> {
+        Ok(Self(Product {
+            product_id: row.try_get("product_id")?,
+            product_category_name: row.try_get("product_category_name")?,
+            product_name_lenght: row.try_get("product_name_lenght")?,
+            product_description_lenght: row.try_get("product_description_lenght")?,
+            product_photos_qty: row.try_get("product_photos_qty")?,
+            product_weight_g: row.try_get("product_weight_g")?,
+            product_length_cm: row.try_get("product_length_cm")?,
+            product_height_cm: row.try_get("product_height_cm")?,
+            product_width_cm: row.try_get("product_width_cm")?,
+            product_name: row.try_get("product_name")?,
+            description: row.try_get("description")?,
+            price: optional_decimal(row, "price")?,
+            active: row.try_get("active")?,
+            currency: Money::CURRENCY,
+        }))
+    }
+}
+
+impl FromRow<'_, SqliteRow> for Decoded<RecommendedProduct> {
+    fn from_row(row: &SqliteRow) -> SqlxResult<Self> {
+        Ok(Self(RecommendedProduct {
+            product: Decoded::<Product>::from_row(row)?.0,
+            state_order_count: row.try_get("state_order_count")?,
+        }))
+    }
+}
+
+impl FromRow<'_, SqliteRow> for Decoded<OrderProduct> {
+    fn from_row(row: &SqliteRow) -> SqlxResult<Self> {
+        Ok(Self(OrderProduct {
+            product_id: row.try_get("product_id")?,
+            product_category_name: row.try_get("product_category_name")?,
+            product_name_lenght: row.try_get("product_name_lenght")?,
+            product_description_lenght: row.try_get("product_description_lenght")?,
+            product_photos_qty: row.try_get("product_photos_qty")?,
+            product_weight_g: row.try_get("product_weight_g")?,
+            product_length_cm: row.try_get("product_length_cm")?,
+            product_height_cm: row.try_get("product_height_cm")?,
+            product_width_cm: row.try_get("product_width_cm")?,
+            shipping_limit_date: row.try_get("shipping_limit_date")?,
+            price: decimal(row, "price")?,
+            freight_value: decimal(row, "freight_value")?,
+            currency: Money::CURRENCY,
+        }))
+    }
+}
+
+impl FromRow<'_, SqliteRow> for Decoded<(OrderId, OrderProduct)> {
+    fn from_row(row: &SqliteRow) -> SqlxResult<Self> {
+        let product = Decoded::<OrderProduct>::from_row(row)?.0;
+        Ok(Self((row.try_get("order_id")?, product)))
+    }
+}
+
+impl FromRow<'_, SqliteRow> for Decoded<Payment> {
+    fn from_row(row: &SqliteRow) -> SqlxResult<Self> {
+        Ok(Self(Payment {
+            order_id: row.try_get("order_id")?,
+            payment_sequential: row.try_get("payment_sequential")?,
+            payment_type: row.try_get("payment_type")?,
+            payment_installments: row.try_get("payment_installments")?,
+            payment_value: decimal(row, "payment_value")?,
+            currency: Money::CURRENCY,
+        }))
+    }
+}
+
+impl FromRow<'_, SqliteRow> for Decoded<PaymentTransaction> {
+    fn from_row(row: &SqliteRow) -> SqlxResult<Self> {
+        Ok(Self(PaymentTransaction {
+            transaction_id: row.try_get("transaction_id")?,
+            order_id: row.try_get("order_id")?,
+            provider: row.try_get("provider")?,
+            provider_reference: row.try_get("provider_reference")?,
+            payment_type: row.try_get("payment_type")?,
+            payment_installments: row.try_get("payment_installments")?,
+            amount: decimal(row, "amount")?,
+            status: row.try_get("status")?,
+            created_at: row.try_get("created_at")?,
+            updated_at: row.try_get("updated_at")?,
+            currency: Money::CURRENCY,
+        }))
+    }
+}
+
+impl FromRow<'_, SqliteRow> for Decoded<Refund> {
+    fn from_row(row: &SqliteRow) -> SqlxResult<Self> {
+        Ok(Self(Refund {
+            refund_id: row.try_get("refund_id")?,
+            order_id: row.try_get("order_id")?,
+            payment_sequential: row.try_get("payment_sequential")?,
+            amount: decimal(row, "amount")?,
+            reason: row.try_get("reason")?,
+            created_at: row.try_get("created_at")?,
+            currency: Money::CURRENCY,
+        }))
+    }
+}
+
+impl FromRow<'_, SqliteRow> for Decoded<OrderFinancials> {
+    fn from_row(row: &SqliteRow) -> SqlxResult<Self> {
+        let coupon_value: Option<String> = row.try_get_unchecked("coupon_value")?;
+        Ok(Self(OrderFinancials {
+            order_id: row.try_get("order_id")?,
+            order_status: row.try_get("order_status")?,
+            item_count: row.try_get("item_count")?,
+            items_subtotal: decimal(row, "items_subtotal")?,
+            freight_total: decimal(row, "freight_total")?,
+            payment_count: row.try_get("payment_count")?,
+            payments_total: decimal(row, "payments_total")?,
+            refunds_total: decimal(row, "refunds_total")?,
+            coupon_code: row.try_get("coupon_code")?,
+            coupon_discount_type: row.try_get("coupon_discount_type")?,
+            coupon_value: match coupon_value {
+                Some(_) => Some(decimal(row, "coupon_value")?),
+                None => None,
+            },
+        }))
+    }
+}
+
+impl FromRow<'_, SqliteRow> for Decoded<PaymentMismatch> {
+    fn from_row(row: &SqliteRow) -> SqlxResult<Self> {
+        Ok(Self(PaymentMismatch {
+            order_id: row.try_get("order_id")?,
+            order_status: row.try_get("order_status")?,
+            order_purchase_timestamp: row.try_get("order_purchase_timestamp")?,
+            item_count: row.try_get("item_count")?,
+            items_total: decimal(row, "items_total")?,
+            payment_count: row.try_get("payment_count")?,
+            payments_total: decimal(row, "payments_total")?,
+            difference: decimal(row, "difference")?,
+            currency: Money::CURRENCY,
+        }))
+    }
+}
+
+impl FromRow<'_, SqliteRow> for Decoded<PaymentMethodStats> {
+    fn from_row(row: &SqliteRow) -> SqlxResult<Self> {
+        Ok(Self(PaymentMethodStats {
+            payment_type: row.try_get("payment_type")?,
+            payment_count: row.try_get("payment_count")?,
+            share: row.try_get("share")?,
+            total_value: decimal(row, "total_value")?,
+            average_installments: row.try_get("average_installments")?,
+            average_ticket: decimal(row, "average_ticket")?,
+            currency: Money::CURRENCY,
+        }))
+    }
+}
+
+impl FromRow<'_, SqliteRow> for Decoded<OrderAmendment> {
+    fn from_row(row: &SqliteRow) -> SqlxResult<Self> {
+        Ok(Self(OrderAmendment {
+            amendment_id: row.try_get("amendment_id")?,
+            order_id: row.try_get("order_id")?,
+            actor: row.try_get("actor")?,
+            changes: row.try_get("changes")?,
+            previous_freight: decimal(row, "previous_freight")?,
+            new_freight: decimal(row, "new_freight")?,
+            previous_tax: decimal(row, "previous_tax")?,
+            new_tax: decimal(row, "new_tax")?,
+            created_at: row.try_get("created_at")?,
+            currency: Money::CURRENCY,
+        }))
+    }
+}
+
+impl FromRow<'_, SqliteRow> for Decoded<TodayStats> {
+    fn from_row(row: &SqliteRow) -> SqlxResult<Self> {
+        Ok(Self(TodayStats {
+            stat_date: row.try_get("stat_date")?,
+            orders_count: row.try_get("orders_count")?,
+            revenue: decimal(row, "revenue")?,
+            active_imports: row.try_get("active_imports")?,
+            currency: Money::CURRENCY,
+        }))
+    }
+}
+
+impl FromRow<'_, SqliteRow> for Decoded<WebhookSubscription> {
+    fn from_row(row: &SqliteRow) -> SqlxResult<Self> {
+        let Json(events): Json<Vec<String>> = row.try_get("events")?;
+        let customer_states: Option<Json<Vec<String>>> = row.try_get("customer_states")?;
+        Ok(Self(WebhookSubscription {
+            subscription_id: row.try_get("subscription_id")?,
+            url: row.try_get("url")?,
+            events,
+            customer_states: customer_states.map(|Json(states)| states),
+            payload_template: row.try_get("payload_template")?,
+            secret: row.try_get("secret")?,
+            created_by: row.try_get("created_by")?,
+            created_at: row.try_get("created_at")?,
+        }))
+    }
+}
+
+impl FromRow<'_, SqliteRow> for Decoded<PendingWebhookDelivery> {
+    fn from_row(row: &SqliteRow) -> SqlxResult<Self> {
+        let customer_states: Option<Json<Vec<String>>> = row.try_get("customer_states")?;
+        Ok(Self(PendingWebhookDelivery {
+            delivery_id: row.try_get("delivery_id")?,
+            event: row.try_get("event")?,
+            payload: row.try_get("payload")?,
+            attempts: row.try_get("attempts")?,
+            created_at: row.try_get("created_at")?,
+            url: row.try_get("url")?,
+            secret: row.try_get("secret")?,
+            customer_states: customer_states.map(|Json(states)| states),
+            payload_template: row.try_get("payload_template")?,
+            customer_state: row.try_get("customer_state")?,
+        }))
+    }
+}
+
+/// Window column giving each row of a page the filtered total, when `total` asks for a count.
+/// SQLite has no planner estimates, so estimated totals are counted exactly too.
+fn total_column(total: TotalMode) -> &'static str {
+    match total {
+        TotalMode::Exact | TotalMode::Estimate => "COUNT(*) OVER () AS total_count",
+        TotalMode::Skip => "NULL AS total_count",
+    }
+}
+
+/// The total for a listing page, counting separately when the page carried no window total.
+async fn page_total<'q>(
+    pool: &SqlitePool,
+    mode: TotalMode,
+    window_total: Option<i64>,
+    count: QueryAs<'q, Sqlite, (i64,), SqliteArguments<'q>>,
+) -> SqlxResult<Total> {
+    let exact = match (mode, window_total) {
+        (TotalMode::Skip, _) => return Ok(Total::Skipped),
+        (_, Some(count)) => count,
+        (_, None) => count.fetch_one(pool).await?.0,
+    };
+    Ok(match mode {
+        TotalMode::Estimate => Total::Estimated(exact),
+        _ => Total::Exact(exact),
+    })
+}
+
+/// Runs `insert` on each row in one transaction, each under its own savepoint; see the
+/// Postgres `insert_each`.
+async fn insert_each<T, R>(
+    pool: &SqlitePool,
+    rows: Vec<T>,
+    insert: impl for<'c> Fn(&'c mut SqliteConnection, T) -> BoxFuture<'c, SqlxResult<R>>,
+) -> SqlxResult<Vec<SqlxResult<R>>> {
+    let mut tx = pool.begin().await?;
+    let mut results = Vec::with_capacity(rows.len());
+    for row in rows {
+        let mut savepoint = tx.begin().await?;
+        let result = insert(&mut savepoint, row).await;
+        if result.is_ok() {
+            savepoint.commit().await?;
+        } else {
+            savepoint.rollback().await?;
+        }
+        results.push(result);
+    }
+    tx.commit().await?;
+    Ok(results)
+}
+
+/// A single JSON column holding the selected fields of a listing row, like the Postgres
+/// `sparse_columns`.
+fn sparse_columns(fields: &[&str], computed: &[(&str, &str)]) -> String {
+    let pairs: Vec<String> = fields
+        .iter()
+        .map(|field| {
+            let expression = computed
+                .iter()
+                .find(|(name, _)| name == field)
+                .map_or(*field, |(_, expression)| *expression);
+            format!("'{}', {}", field, expression)
+        })
+        .collect();
+    format!("json_object({})", pairs.join(", "))
+}
+
+/// Maps a folded city name through `city_aliases`, like the Postgres `resolve_city_alias`.
+fn resolve_city_alias(parameter: &str) -> String {
+    format!(
+        "COALESCE((SELECT a.canonical_city FROM city_aliases a WHERE a.alias = {0}), {0})",
+        parameter
+    )
+}
+
+const CUSTOMER_COLUMNS: &str = r#"
+    customer_id, customer_unique_id, customer_zip_code_prefix, customer_city, canonical_city,
+    customer_state, deleted_at
+"#;
+
+const CUSTOMER_ADDRESS_COLUMNS: &str = r#"
+    address_id, customer_id, label, zip_code_prefix, city, state, is_default, created_at
+"#;
+
+/// `WHERE` clause for [`CustomerFilter`] within a tenant, bound by [`bind_customer_filter`] as
+/// `?1`..`?5`. Fuzzy search matches the folded query inside the canonical city.
+fn customer_filter() -> String {
+    format!(
+        r#"
+        (?1 IS NULL OR canonical_city = {})
+        AND (?2 IS NULL OR customer_state = ?2)
+        AND (?3 OR deleted_at IS NULL)
+        AND (?4 IS NULL OR canonical_city LIKE '%' || ?4 || '%')
+        AND tenant_id = ?5
+        "#,
+        resolve_city_alias("?1")
+    )
+}
+
+fn bind_customer_filter<'q, O>(
+    query: QueryAs<'q, Sqlite, O, SqliteArguments<'q>>,
+    filter: &'q CustomerFilter,
+    tenant: &'q TenantId,
+) -> QueryAs<'q, Sqlite, O, SqliteArguments<'q>> {
+    query
+        .bind(&filter.city)
+        .bind(&filter.state)
+        .bind(filter.include_deleted)
+        .bind(filter.q.as_deref().map(fold_city))
+        .bind(tenant.as_str())
+}
+
+#[derive(Clone)]
+pub struct SqliteCustomerRepository {
+    pool: SqlitePool,
+    tenant: TenantId,
+}
+
+impl SqliteCustomerRepository {
+    pub fn new(pool: SqlitePool, tenant: TenantId) -> Self {
+        Self { pool, tenant }
+    }
+
+    async fn total(
+        &self,
+        filter: &CustomerFilter,
+        mode: TotalMode,
+        window_total: Option<i64>,
+    ) -> SqlxResult<Total> {
+        let count = format!("SELECT COUNT(*) FROM customers WHERE {}", customer_filter());
+        page_total(
+            &self.pool,
+            mode,
+            window_total,
+            bind_customer_filter(sqlx::query_as(&count), filter, &self.tenant),
+        )
+        .await
+        .map_err(|e| {
+            error!("Error counting customers: {:?}", e);
+            e
+        })
+    }
+
+    /// A listing page selecting `columns`, filtered as `?1`..`?5` and paged by `?6`/`?7`.
+    fn page_query(&self, columns: &str, total: TotalMode) -> String {
+        format!(
+            r#"
+            SELECT {}, {}
+            FROM customers
+            WHERE {}
+            ORDER BY (canonical_city = ?4) DESC, customer_city COLLATE NOCASE,
+                customer_zip_code_prefix DESC
+            LIMIT ?6 OFFSET ?7
+            "#,
+            columns,
+            total_column(total),
+            customer_filter()
+        )
+    }
+}
+
+async fn insert_customer<'e>(
+    executor: impl SqliteExecutor<'e>,
+    tenant: &TenantId,
+    id: &CustomerId,
+    dto: CreateCustomerDto,
+    canonical_city: &str,
+) -> SqlxResult<Customer> {
+    sqlx::query_as::<_, Customer>(&format!(
+        r#"
+        INSERT INTO customers (
+            customer_id, customer_unique_id, customer_zip_code_prefix,
+            customer_city, customer_state, canonical_city, tenant_id
+        )
+        VALUES (?1, ?2, ?3, ?4, ?5, {}, ?7)
+        RETURNING {}
+        "#,
+        resolve_city_alias("?6"),
+        CUSTOMER_COLUMNS
+    ))
+    .bind(id.as_str())
+    .bind(&dto.customer_unique_id)
+    .bind(&dto.customer_zip_code_prefix)
+    .bind(&dto.customer_city)
+    .bind(dto.customer_state.as_str())
+    .bind(canonical_city)
+    .bind(tenant.as_str())
+    .fetch_one(executor)
+    .await
+    .map_err(|e| {
+        error!("Error creating customer: {:?}", e);
+        e
+    })
+}
+
+#[async_trait]
+impl CustomerRepository for SqliteCustomerRepository {
+    async fn create(
+        &self,
+        id: &CustomerId,
+        dto: CreateCustomerDto,
+        canonical_city: &str,
+    ) -> SqlxResult<Customer> {
+        insert_customer(&self.pool, &self.tenant, id, dto, canonical_city).await
+    }
+
+    async fn create_many(
+        &self,
+        rows: Vec<(CustomerId, CreateCustomerDto, String)>,
+    ) -> SqlxResult<Vec<SqlxResult<Customer>>> {
+        insert_each(&self.pool, rows, |conn, (id, dto, city)| {
+            let tenant = self.tenant.clone();
+            Box::pin(async move { insert_customer(conn, &tenant, &id, dto, &city).await })
+        })
+        .await
+    }
+
+    async fn find_all(
+        &self,
+        filter: &CustomerFilter,
+        pagination: &PaginationParams,
+        total: TotalMode,
+    ) -> SqlxResult<(Vec<Customer>, Total)> {
+        let (limit, offset, _, _) = pagination.normalize();
+
+        let query = self.page_query(CUSTOMER_COLUMNS, total);
+        let rows = bind_customer_filter(
+            sqlx::query_as::<_, Counted<Customer>>(&query),
+            filter,
+            &self.tenant,
+        )
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching customers: {:?}", e);
+            e
+        })?;
+
+        let (customers, window_total) = split_counted(rows, offset);
+        let total = self.total(filter, total, window_total).await?;
+
+        Ok((customers, total))
+    }
+
+    async fn find_all_sparse(
+        &self,
+        filter: &CustomerFilter,
+        pagination: &PaginationParams,
+        fields: &[&'static str],
+        total: TotalMode,
+    ) -> SqlxResult<(Vec<SparseRow>, Total)> {
+        let (limit, offset, _, _) = pagination.normalize();
+
+        let query = self.page_query(&sparse_columns(fields, &[]), total);
+        let rows = bind_customer_filter(
+            sqlx::query_as::<_, Counted<(Json<SparseRow>,)>>(&query),
+            filter,
+            &self.tenant,
+        )
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching sparse customers: {:?}", e);
+            e
+        })?;
+
+        let (rows, window_total) = split_counted(rows, offset);
+        let total = self.total(filter, total, window_total).await?;
+
+        Ok((rows.into_iter().map(|(row,)| row.0).collect(), total))
+    }
+
+    fn stream_all(&self) -> BoxStream<'_, SqlxResult<Customer>> {
+        sqlx::query_as::<_, Customer>(
+            r#"
+            SELECT
+                customer_id, customer_unique_id, customer_zip_code_prefix, customer_city,
+                canonical_city, customer_state, deleted_at
+            FROM customers
+            WHERE deleted_at IS NULL AND tenant_id = ?1
+            ORDER BY customer_id
+            "#,
+        )
+        .bind(self.tenant.as_str())
+        .fetch(&self.pool)
+    }
+
+    async fn find_by_id(&self, id: &CustomerId) -> SqlxResult<Option<Customer>> {
+        sqlx::query_as::<_, Customer>(&format!(
+            "SELECT {} FROM customers WHERE customer_id = ?1 AND deleted_at IS NULL AND tenant_id = ?2",
+            CUSTOMER_COLUMNS
+        ))
+        .bind(id.as_str())
+        .bind(self.tenant.as_str())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching customer by id: {:?}", e);
+            e
+        })
+    }
+
+    #[instrument(skip(self, dto), fields(customer_id = %id))]
+    async fn update(
+        &self,
+        id: &CustomerId,
+        dto: UpdateCustomerDto,
+        canonical_city: Option<&str>,
+    ) -> SqlxResult<Option<Customer>> {
+        let result = sqlx::query_as::<_, Customer>(&format!(
+            r#"
+            UPDATE customers
+            SET
+                customer_unique_id = COALESCE(?2, customer_unique_id),
+                customer_zip_code_prefix = COALESCE(?3, customer_zip_code_prefix),
+                customer_city = COALESCE(?4, customer_city),
+                customer_state = COALESCE(?5, customer_state),
+                canonical_city = COALESCE({}, canonical_city)
+            WHERE customer_id = ?1 AND deleted_at IS NULL AND tenant_id = ?7
+            RETURNING {}
+            "#,
+            resolve_city_alias("?6"),
+            CUSTOMER_COLUMNS
+        ))
+        .bind(id.as_str())
+        .bind(&dto.customer_unique_id)
+        .bind(&dto.customer_zip_code_prefix)
+        .bind(&dto.customer_city)
+        .bind(dto.customer_state.map(|state| state.as_str()))
+        .bind(canonical_city)
+        .bind(self.tenant.as_str())
+        .fetch_optional(&self.pool)
+        .await;
+
+        match &result {
+            Ok(Some(_)) => info!("Customer updated successfully"),
+            Ok(None) => info!("Customer not found for update"),
+            Err(e) => error!("Error updating customer: {:?}", e),
+        }
+
+        result
+    }
+
+    #[instrument(skip(self), fields(customer_id = %id))]
+    async fn delete(&self, id: &CustomerId) -> SqlxResult<Option<chrono::NaiveDateTime>> {
+        let result = sqlx::query_scalar::<_, chrono::NaiveDateTime>(
+            r#"
+            UPDATE customers
+            SET deleted_at = datetime('now')
+            WHERE customer_id = ?1 AND deleted_at IS NULL AND tenant_id = ?2
+            RETURNING deleted_at
+            "#,
+        )
+        .bind(id.as_str())
+        .bind(self.tenant.as_str())
+        .fetch_optional(&self.pool)
+        .await;
+
+        match &result {
+            Ok(Some(_)) => info!("Customer deleted successfully"),
+            Ok(None) => info!("Customer not found for deletion"),
+            Err(e) => error!("Error deleting customer: {:?}", e),
+        }
+
+        result
+    }
+
+    #[instrument(skip(self), fields(customer_id = %id))]
+    async fn restore(&self, id: &CustomerId) -> SqlxResult<Option<Customer>> {
+        let result = sqlx::query_as::<_, Customer>(&format!(
+            r#"
+            UPDATE customers
+            SET deleted_at = NULL
+            WHERE customer_id = ?1 AND deleted_at IS NOT NULL AND tenant_id = ?2
+            RETURNING {}
+            "#,
+            CUSTOMER_COLUMNS
+        ))
+        .bind(id.as_str())
+        .bind(self.tenant.as_str())
+        .fetch_optional(&self.pool)
+        .await;
+
+        match &result {
+            Ok(Some(_)) => info!("Customer restored successfully"),
+            Ok(None) => info!("Deleted customer not found for restore"),
+            Err(e) => error!("Error restoring customer: {:?}", e),
+        }
+
+        result
+    }
+
+    #[instrument(skip(self), fields(customer_id = %id))]
+    async fn anonymize(
+        &self,
+        id: &CustomerId,
+        erasure: &NewAuditEntry,
+    ) -> SqlxResult<Option<Customer>> {
+        let result = async {
+            let mut tx = self.pool.begin().await?;
+
+            let customer = sqlx::query_as::<_, Customer>(&format!(
+                r#"
+                UPDATE customers
+                SET
+                    customer_unique_id = lower(hex(randomblob(16))),
+                    customer_zip_code_prefix = '00000',
+                    customer_city = 'anonymized',
+                    canonical_city = 'anonymized',
+                    customer_state = 'XX'
+                WHERE customer_id = ?1 AND tenant_id = ?2
+                RETURNING {}
+                "#,
+                CUSTOMER_COLUMNS
+            ))
+            .bind(id.as_str())
+            .bind(self.tenant.as_str())
+            .fetch_optional(&mut *tx)
+            .await?;
+
+            let Some(customer) = customer else {
+                return Ok(None);
+            };
+
+            sqlx::query(
+                r#"
+                UPDATE reviews
+                SET review_comment_title = NULL, review_comment_message = NULL
+                WHERE tenant_id = ?2
+                    AND order_id IN (
+                        SELECT order_id FROM orders WHERE customer_id = ?1 AND tenant_id = ?2
+                    )
+                "#,
+            )
+            .bind(id.as_str())
+            .bind(self.tenant.as_str())
+            .execute(&mut *tx)
+            .await?;
+
+            sqlx::query(
+                r#"
+                UPDATE orders
+                SET shipping_zip_code_prefix = '00000'
+                WHERE customer_id = ?1 AND tenant_id = ?2
+                "#,
+            )
+            .bind(id.as_str())
+            .bind(self.tenant.as_str())
+            .execute(&mut *tx)
+            .await?;
+
+            sqlx::query(
+                r#"
+                UPDATE order_amendments
+                SET changes = json_set(
+                    changes, '$.shipping_zip_code_prefix', json('{"redacted": true}')
+                )
+                WHERE tenant_id = ?2
+                    AND json_type(changes, '$.shipping_zip_code_prefix') IS NOT NULL
+                    AND order_id IN (
+                        SELECT order_id FROM orders WHERE customer_id = ?1 AND tenant_id = ?2
+                    )
+                "#,
+            )
+            .bind(id.as_str())
+            .bind(self.tenant.as_str())
+            .execute(&mut *tx)
+            .await?;
+
+            sqlx::query(
+                r#"
+                UPDATE audit_log
+                SET diff = '{"redacted": true}'
+                WHERE entity_type = 'customer' AND entity_id = ?1 AND tenant_id = ?2
+                "#,
+            )
+            .bind(id.as_str())
+            .bind(self.tenant.as_str())
+            .execute(&mut *tx)
+            .await?;
+
+            sqlx::query(
+                r#"
+                UPDATE customer_location_history
+                SET customer_zip_code_prefix = '00000', customer_city = 'anonymized',
+                    customer_state = 'XX'
+                WHERE customer_id = ?1 AND tenant_id = ?2
+                "#,
+            )
+            .bind(id.as_str())
+            .bind(self.tenant.as_str())
+            .execute(&mut *tx)
+            .await?;
+
+            sqlx::query("DELETE FROM customer_addresses WHERE customer_id = ?1 AND tenant_id = ?2")
+                .bind(id.as_str())
+                .bind(self.tenant.as_str())
+                .execute(&mut *tx)
+                .await?;
+
+            // After the redaction above, which would otherwise blank it too.
+            sqlx::query(
+                r#"
+                INSERT INTO audit_log (entity_type, entity_id, action, actor, diff, tenant_id)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+                "#,
+            )
+            .bind(erasure.entity_type)
+            .bind(&erasure.entity_id)
+            .bind(erasure.action.as_str())
+            .bind(&erasure.actor)
+            .bind(&erasure.diff)
+            .bind(self.tenant.as_str())
+            .execute(&mut *tx)
+            .await?;
+
+            tx.commit().await?;
+            Ok(Some(customer))
+        }
+        .await;
+
+        match &result {
+            Ok(Some(_)) => info!("Customer anonymized successfully"),
+            Ok(None) => info!("Customer not found for anonymization"),
+            Err(e) => error!("Error anonymizing customer: {:?}", e),
+        }
+
+        result
+    }
+
+    #[instrument(skip(self), fields(source_customer_id = %source, target_customer_id = %target))]
+    async fn merge(
+        &self,
+        source: &CustomerId,
+        target: &CustomerId,
+        audit: &[NewAuditEntry],
+    ) -> SqlxResult<Option<CustomerMerge>> {
+        let result = async {
+            let mut tx = self.pool.begin().await?;
+
+            let live = sqlx::query_scalar::<_, i64>(
+                r#"
+                SELECT COUNT(*) FROM customers
+                WHERE customer_id IN (?1, ?2) AND deleted_at IS NULL AND tenant_id = ?3
+                "#,
+            )
+            .bind(source.as_str())
+            .bind(target.as_str())
+            .bind(self.tenant.as_str())
+            .fetch_one(&mut *tx)
+            .await?;
+            if live < 2 {
+                return Ok(None);
+            }
+
+            let orders_moved = sqlx::query(
+                "UPDATE orders SET customer_id = ?2 WHERE customer_id = ?1 AND tenant_id = ?3",
+            )
+            .bind(source.as_str())
+            .bind(target.as_str())
+            .bind(self.tenant.as_str())
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+
+            let support_cases_moved = sqlx::query(
+                "UPDATE support_cases SET customer_id = ?2 WHERE customer_id = ?1 AND tenant_id = ?3",
+            )
+            .bind(source.as_str())
+            .bind(target.as_str())
+            .bind(self.tenant.as_str())
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+
+            // Moved addresses stay non-default unless the target has no default yet.
+            sqlx::query(
+                r#"
+                UPDATE customer_addresses
+                SET
+                    customer_id = ?2,
+                    is_default = is_default AND NOT EXISTS (
+                        SELECT 1 FROM customer_addresses
+                        WHERE customer_id = ?2 AND tenant_id = ?3 AND is_default
+                    )
+                WHERE customer_id = ?1 AND tenant_id = ?3
+                "#,
+            )
+            .bind(source.as_str())
+            .bind(target.as_str())
+            .bind(self.tenant.as_str())
+            .execute(&mut *tx)
+            .await?;
+
+            // The target keeps its own current location; the source's closes here.
+            sqlx::query(
+                r#"
+                UPDATE customer_location_history
+                SET customer_id = ?2, valid_to = COALESCE(valid_to, datetime('now'))
+                WHERE customer_id = ?1 AND tenant_id = ?3
+                "#,
+            )
+            .bind(source.as_str())
+            .bind(target.as_str())
+            .bind(self.tenant.as_str())
+            .execute(&mut *tx)
+            .await?;
+
+            let source_deleted_at = sqlx::query_scalar::<_, chrono::NaiveDateTime>(
+                r#"
+                UPDATE customers
+                SET deleted_at = datetime('now')
+                WHERE customer_id = ?1 AND tenant_id = ?2
+                RETURNING deleted_at
+                "#,
+            )
+            .bind(source.as_str())
+            .bind(self.tenant.as_str())
+            .fetch_one(&mut *tx)
+            .await?;
+
+            let merge = CustomerMerge {
+                source_customer_id: source.clone(),
+                target_customer_id: target.clone(),
+                orders_moved,
+                support_cases_moved,
+                source_deleted_at,
+            };
+            for entry in audit {
+                let entry = merge.audit_entry(entry);
+                sqlx::query(
+                    r#"
+                    INSERT INTO audit_log (entity_type, entity_id, action, actor, diff, tenant_id)
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+                    "#,
+                )
+                .bind(entry.entity_type)
+                .bind(&entry.entity_id)
+                .bind(entry.action.as_str())
+                .bind(&entry.actor)
+                .bind(&entry.diff)
+                .bind(self.tenant.as_str())
+                .execute(&mut *tx)
+                .await?;
+            }
+
+            tx.commit().await?;
+            Ok(Some(merge))
+        }
+        .await;
+
+        match &result {
+            Ok(Some(_)) => info!("Customers merged successfully"),
+            Ok(None) => info!("Customer not found for merge"),
+            Err(e) => error!("Error merging customers: {:?}", e),
+        }
+
+        result
+    }
+
+    async fn count_dependents(&self, id: &CustomerId) -> SqlxResult<CustomerDependents> {
+        sqlx::query_as::<_, CustomerDependents>(
+            r#"
+            SELECT
+                (SELECT COUNT(*) FROM orders WHERE customer_id = ?1 AND tenant_id = ?2) AS orders,
+                (
+                    SELECT COUNT(*) FROM support_cases WHERE customer_id = ?1 AND tenant_id = ?2
+                ) AS support_cases
+            "#,
+        )
+        .bind(id.as_str())
+        .bind(self.tenant.as_str())
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error counting customer dependents: {:?}", e);
+            e
+        })
+    }
+
+    async fn find_location_history(
+        &self,
+        id: &CustomerId,
+    ) -> SqlxResult<Vec<CustomerLocationVersion>> {
+        sqlx::query_as::<_, CustomerLocationVersion>(
+            r#"
+            SELECT
+                customer_zip_code_prefix, customer_city, customer_state,
+                valid_from, valid_to
+            FROM customer_location_history
+            WHERE customer_id = ?1 AND tenant_id = ?2
+            ORDER BY valid_from NULLS FIRST, history_id
+            "#,
+        )
+        .bind(id.as_str())
+        .bind(self.tenant.as_str())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching customer location history: {:?}", e);
+            e
+        })
+    }
+
+    #[instrument(skip(self, dto), fields(customer_id = %id))]
+    async fn create_address(
+        &self,
+        id: &CustomerId,
+        dto: CreateCustomerAddressDto,
+    ) -> SqlxResult<CustomerAddress> {
+        let result = async {
+            let mut tx = self.pool.begin().await?;
+
+            if dto.is_default {
+                sqlx::query(
+                    r#"
+                    UPDATE customer_addresses SET is_default = FALSE
+                    WHERE customer_id = ?1 AND is_default AND tenant_id = ?2
+                    "#,
+                )
+                .bind(id.as_str())
+                .bind(self.tenant.as_str())
+                .execute(&mut *tx)
+                .await?;
+            }
+
+            let address = sqlx::query_as::<_, CustomerAddress>(&format!(
+                r#"
+                INSERT INTO customer_addresses (
+                    customer_id, label, zip_code_prefix, city, state, is_default, tenant_id
+                )
+                VALUES (
+                    ?1, ?2, ?3, ?4, ?5,
+                    ?6 OR NOT EXISTS (
+                        SELECT 1 FROM customer_addresses WHERE customer_id = ?1 AND tenant_id = ?7
+                    ),
+                    ?7
+                )
+                RETURNING {}
+                "#,
+                CUSTOMER_ADDRESS_COLUMNS
+            ))
+            .bind(id.as_str())
+            .bind(&dto.label)
+            .bind(&dto.zip_code_prefix)
+            .bind(&dto.city)
+            .bind(dto.state.as_str())
+            .bind(dto.is_default)
+            .bind(self.tenant.as_str())
+            .fetch_one(&mut *tx)
+            .await?;
+
+            tx.commit().await?;
+            Ok(address)
+        }
+        .await;
+
+        if let Err(e) = &result {
+            error!("Error creating customer address: {:?}", e);
+        }
+        result
+    }
+
+    async fn find_addresses(&self, id: &CustomerId) -> SqlxResult<Vec<CustomerAddress>> {
+        sqlx::query_as::<_, CustomerAddress>(&format!(
+            r#"
+            SELECT {}
+            FROM customer_addresses
+            WHERE customer_id = ?1 AND tenant_id = ?2
+            ORDER BY is_default DESC, address_id
+            "#,
+            CUSTOMER_ADDRESS_COLUMNS
+        ))
+        .bind(id.as_str())
+        .bind(self.tenant.as_str())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching customer addresses: {:?}", e);
+            e
+        })
+    }
+
+    async fn find_address(
+        &self,
+        id: &CustomerId,
+        address_id: i64,
+    ) -> SqlxResult<Option<CustomerAddress>> {
+        sqlx::query_as::<_, CustomerAddress>(&format!(
+            r#"
+            SELECT {}
+            FROM customer_addresses
+            WHERE customer_id = ?1 AND address_id = ?2 AND tenant_id = ?3
+            "#,
+            CUSTOMER_ADDRESS_COLUMNS
+        ))
+        .bind(id.as_str())
+        .bind(address_id)
+        .bind(self.tenant.as_str())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching customer address: {:?}", e);
+            e
+        })
+    }
+
+    #[instrument(skip(self, dto), fields(customer_id = %id))]
+    async fn update_address(
+        &self,
+        id: &CustomerId,
+        address_id: i64,
+        dto: UpdateCustomerAddressDto,
+    ) -> SqlxResult<Option<CustomerAddress>> {
+        let result = async {
+            let mut tx = self.pool.begin().await?;
+
+            if dto.is_default == Some(true) {
+                sqlx::query(
+                    r#"
+                    UPDATE customer_addresses SET is_default = FALSE
+                    WHERE customer_id = ?1 AND is_default AND address_id <> ?2 AND tenant_id = ?3
+                    "#,
+                )
+                .bind(id.as_str())
+                .bind(address_id)
+                .bind(self.tenant.as_str())
+                .execute(&mut *tx)
+                .await?;
+            }
+
+            let address = sqlx::query_as::<_, CustomerAddress>(&format!(
+                r#"
+                UPDATE customer_addresses
+                SET
+                    label = COALESCE(?3, label),
+                    zip_code_prefix = COALESCE(?4, zip_code_prefix),
+                    city = COALESCE(?5, city),
+                    state = COALESCE(?6, state),
+                    is_default = COALESCE(?7, is_default)
+                WHERE customer_id = ?1 AND address_id = ?2 AND tenant_id = ?8
+                RETURNING {}
+                "#,
+                CUSTOMER_ADDRESS_COLUMNS
+            ))
+            .bind(id.as_str())
+            .bind(address_id)
+            .bind(&dto.label)
+            .bind(&dto.zip_code_prefix)
+            .bind(&dto.city)
+            .bind(dto.state.map(|state| state.as_str()))
+            .bind(dto.is_default)
+            .bind(self.tenant.as_str())
+            .fetch_optional(&mut *tx)
+            .await?;
+
+            tx.commit().await?;
+            Ok(address)
+        }
+        .await;
+
+        if let Err(e) = &result {
+            error!("Error updating customer address: {:?}", e);
+        }
+        result
+    }
+
+    #[instrument(skip(self), fields(customer_id = %id))]
+    async fn delete_address(
+        &self,
+        id: &CustomerId,
+        address_id: i64,
+    ) -> SqlxResult<Option<chrono::NaiveDateTime>> {
+        sqlx::query_scalar::<_, chrono::NaiveDateTime>(
+            r#"
+            DELETE FROM customer_addresses
+            WHERE customer_id = ?1 AND address_id = ?2 AND tenant_id = ?3
+            RETURNING datetime('now')
+            "#,
+        )
+        .bind(id.as_str())
+        .bind(address_id)
+        .bind(self.tenant.as_str())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error deleting customer address: {:?}", e);
+            e
+        })
+    }
+
+    /// Anonymized customers are left out: their state is a placeholder, not a filter value.
+    async fn count_by_state(&self) -> SqlxResult<Vec<FilterValue>> {
+        sqlx::query_as::<_, FilterValue>(
+            r#"
+            SELECT customer_state AS value, COUNT(*) AS count
+            FROM customers
+            WHERE deleted_at IS NULL AND canonical_city <> 'anonymized' AND tenant_id = ?1
+            GROUP BY customer_state
+            ORDER BY COUNT(*) DESC, value
+            "#,
+        )
+        .bind(self.tenant.as_str())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error counting customers by state: {:?}", e);
+            e
+        })
+    }
+
+    /// Anonymized customers are left out: their city is a placeholder, not a filter value.
+    async fn count_by_city(&self, state: Option<BrazilState>) -> SqlxResult<Vec<FilterValue>> {
+        sqlx::query_as::<_, FilterValue>(
+            r#"
+            SELECT canonical_city AS value, COUNT(*) AS count
+            FROM customers
+            WHERE deleted_at IS NULL
+              AND canonical_city <> 'anonymized'
+              AND (?1 IS NULL OR customer_state = ?1)
+              AND tenant_id = ?2
+            GROUP BY canonical_city
+            ORDER BY COUNT(*) DESC, value COLLATE NOCASE
+            "#,
+        )
+        .bind(state.map(|state| state.as_str()))
+        .bind(self.tenant.as_str())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error counting customers by city: {:?}", e);
+            e
+        })
+    }
+
+    async fn find_by_unique_id(&self, unique_id: &str) -> SqlxResult<Vec<Customer>> {
+        sqlx::query_as::<_, Customer>(&format!(
+            r#"
+            SELECT {} FROM customers
+            WHERE customer_unique_id = ?1 AND deleted_at IS NULL AND tenant_id = ?2
+            ORDER BY customer_id
+            "#,
+            CUSTOMER_COLUMNS
+        ))
+        .bind(unique_id)
+        .bind(self.tenant.as_str())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching customers by unique id: {:?}", e);
+            e
+        })
+    }
+
+    async fn count_rows_per_unique_id(&self) -> SqlxResult<Vec<DuplicationBucket>> {
+        sqlx::query_as::<_, DuplicationBucket>(
+            r#"
+            SELECT customer_rows, COUNT(*) AS unique_customers
+            FROM (
+                SELECT COUNT(*) AS customer_rows
+                FROM customers
+                WHERE deleted_at IS NULL AND tenant_id = ?1
+                GROUP BY customer_unique_id
+            )
+            GROUP BY customer_rows
+            ORDER BY customer_rows
+            "#,
+        )
+        .bind(self.tenant.as_str())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error counting customer rows per unique id: {:?}", e);
+            e
+        })
+    }
+
+    async fn find_most_duplicated(&self, limit: i64) -> SqlxResult<Vec<DuplicatedCustomer>> {
+        sqlx::query_as::<_, DuplicatedCustomer>(
+            r#"
+            SELECT customer_unique_id, COUNT(*) AS customer_rows
+            FROM customers
+            WHERE deleted_at IS NULL AND tenant_id = ?2
+            GROUP BY customer_unique_id
+            HAVING COUNT(*) > 1
+            ORDER BY COUNT(*) DESC, customer_unique_id
+            LIMIT ?1
+            "#,
+        )
+        .bind(limit)
+        .bind(self.tenant.as_str())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error finding duplicated customers: {:?}", e);
+            e
+        })
+    }
+}
+
+/// Badge names awarded to seller `s`, as a JSON array in badge order.
+const SELLER_BADGES: &str = r#"(
+    SELECT json_group_array(badge) FROM (
+        SELECT b.badge FROM seller_badges b WHERE b.seller_id = s.seller_id ORDER BY b.badge
+    )
+)"#;
+
+/// `WHERE` clause for [`SellerFilter`] over sellers `s` within a tenant, bound by
+/// [`bind_seller_filter`] as `?1`..`?5`.
+fn seller_filter() -> String {
+    format!(
+        r#"
+        (?1 IS NULL OR canonical_city = {})
+        AND (?2 IS NULL OR seller_state = ?2)
+        AND (?3 IS NULL OR EXISTS (
+            SELECT 1 FROM seller_badges b
+            WHERE b.seller_id = s.seller_id AND b.badge = ?3
+        ))
+        AND (?4 IS NULL OR canonical_city LIKE '%' || ?4 || '%')
+        AND s.tenant_id = ?5
+        "#,
+        resolve_city_alias("?1")
+    )
+}
+
+fn bind_seller_filter<'q, O>(
+    query: QueryAs<'q, Sqlite, O, SqliteArguments<'q>>,
+    filter: &'q SellerFilter,
+    tenant: &'q TenantId,
+) -> QueryAs<'q, Sqlite, O, SqliteArguments<'q>> {
+    query
+        .bind(&filter.city)
+        .bind(&filter.state)
+        .bind(&filter.badge)
+        .bind(filter.q.as_deref().map(fold_city))
+        .bind(tenant.as_str())
+}
+
+/// Sellers, sorted by city with SQLite's case-insensitive collation; the Postgres
+/// [`SortCollation`](crate::collation::SortCollation) options have no SQLite equivalent.
+#[derive(Clone)]
+pub struct SqliteSellerRepository {
+    pool: SqlitePool,
+    tenant: TenantId,
+}
+
+impl SqliteSellerRepository {
+    pub fn new(pool: SqlitePool, tenant: TenantId) -> Self {
+        Self { pool, tenant }
+    }
+
+    async fn total(
+        &self,
+        filter: &SellerFilter,
+        mode: TotalMode,
+        window_total: Option<i64>,
+    ) -> SqlxResult<Total> {
+        let count = format!("SELECT COUNT(*) FROM sellers s WHERE {}", seller_filter());
+        page_total(
+            &self.pool,
+            mode,
+            window_total,
+            bind_seller_filter(sqlx::query_as(&count), filter, &self.tenant),
+        )
+        .await
+        .map_err(|e| {
+            error!("Error counting sellers: {:?}", e);
+            e
+        })
+    }
+
+    /// A listing page selecting `columns`, filtered as `?1`..`?5` and paged by `?6`/`?7`.
+    fn page_query(&self, columns: &str, total: TotalMode) -> String {
+        format!(
+            r#"
+            SELECT {}, {}
+            FROM sellers s
+            WHERE {}
+            ORDER BY (canonical_city = ?4) DESC, seller_city COLLATE NOCASE, seller_id
+            LIMIT ?6 OFFSET ?7
+            "#,
+            columns,
+            total_column(total),
+            seller_filter()
+        )
+    }
+}
+
+async fn insert_seller<'e>(
+    executor: impl SqliteExecutor<'e>,
+    tenant: &TenantId,
+    id: &SellerId,
+    dto: CreateSellerDto,
+    canonical_city: &str,
+) -> SqlxResult<Seller> {
+    sqlx::query_as::<_, Decoded<Seller>>(&format!(
+        r#"
+        INSERT INTO sellers (
+            seller_id, seller_zip_code_prefix,
+            seller_city, seller_state, canonical_city, tenant_id
+        )
+        VALUES (?1, ?2, ?3, ?4, {}, ?6)
+        RETURNING
+            seller_id, seller_zip_code_prefix,
+            seller_city, canonical_city, seller_state, '[]' AS badges
+        "#,
+        resolve_city_alias("?5")
+    ))
+    .bind(id.as_str())
+    .bind(&dto.seller_zip_code_prefix)
+    .bind(&dto.seller_city)
+    .bind(dto.seller_state.as_str())
+    .bind(canonical_city)
+    .bind(tenant.as_str())
+    .fetch_one(executor)
+    .await
+    .map(|seller| seller.0)
+    .map_err(|e| {
+        error!("Error creating seller: {:?}", e);
+        e
+    })
+}
+
+#[async_trait]
+impl SellerRepository for SqliteSellerRepository {
+    async fn create(
+        &self,
+        id: &SellerId,
+        dto: CreateSellerDto,
+        canonical_city: &str,
+    ) -> SqlxResult<Seller> {
+        insert_seller(&self.pool, &self.tenant, id, dto, canonical_city).await
+    }
+
+    async fn create_many(
+        &self,
+        rows: Vec<(SellerId, CreateSellerDto, String)>,
+    ) -> SqlxResult<Vec<SqlxResult<Seller>>> {
+        insert_each(&self.pool, rows, |conn, (id, dto, city)| {
+            let tenant = self.tenant.clone();
+            Box::pin(async move { insert_seller(conn, &tenant, &id, dto, &city).await })
+        })
+        .await
+    }
+
+    async fn find_all(
+        &self,
+        filter: &SellerFilter,
+        pagination: &PaginationParams,
+        total: TotalMode,
+    ) -> SqlxResult<(Vec<Seller>, Total)> {
+        let (limit, offset, _, _) = pagination.normalize();
+
+        let query = self.page_query(
+            &format!(
+                r#"
+                seller_id,
+                seller_zip_code_prefix,
+                seller_city,
+                canonical_city,
+                seller_state,
+                {} AS badges
+            "#,
+                SELLER_BADGES
+            ),
+            total,
+        );
+        let rows = bind_seller_filter(
+            sqlx::query_as::<_, Counted<Decoded<Seller>>>(&query),
+            filter,
+            &self.tenant,
+        )
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching sellers: {:?}", e);
+            e
+        })?;
+
+        let (sellers, window_total) = split_counted(rows, offset);
+        let total = self.total(filter, total, window_total).await?;
+
+        Ok((sellers.into_iter().map(|seller| seller.0).collect(), total))
+    }
+
+    async fn find_all_sparse(
+        &self,
+        filter: &SellerFilter,
+        pagination: &PaginationParams,
+        fields: &[&'static str],
+        total: TotalMode,
+    ) -> SqlxResult<(Vec<SparseRow>, Total)> {
+        let (limit, offset, _, _) = pagination.normalize();
+
+        let badges = format!("json({})", SELLER_BADGES);
+        let query = self.page_query(&sparse_columns(fields, &[("badges", &badges)]), total);
+        let rows = bind_seller_filter(
+            sqlx::query_as::<_, Counted<(Json<SparseRow>,)>>(&query),
+            filter,
+            &self.tenant,
+        )
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching sparse sellers: {:?}", e);
+            e
+        })?;
+
+        let (rows, window_total) = split_counted(rows, offset);
+        let total = self.total(filter, total, window_total).await?;
+
+        Ok((rows.into_iter().map(|(row,)| row.0).collect(), total))
+    }
+
+    async fn find_by_id(&self, id: &SellerId) -> SqlxResult<Option<Seller>> {
+        sqlx::query_as::<_, Decoded<Seller>>(&format!(
+            r#"
+            SELECT
+                seller_id, seller_zip_code_prefix,
+                seller_city, canonical_city, seller_state,
+                {} AS badges
+            FROM sellers s WHERE seller_id = ?1 AND tenant_id = ?2
+            "#,
+            SELLER_BADGES
+        ))
+        .bind(id.as_str())
+        .bind(self.tenant.as_str())
+        .fetch_optional(&self.pool)
+        .await
+        .map(|seller| seller.map(|seller| seller.0))
+        .map_err(|e| {
+            error!("Error fetching seller by id: {:?}", e);
+            e
+        })
+    }
+
+    async fn find_within(&self, bounds: &GeoBounds) -> SqlxResult<Vec<LocatedSeller>> {
+        sqlx::query_as::<_, Decoded<LocatedSeller>>(&format!(
+            r#"
+            SELECT
+                s.seller_id, s.seller_zip_code_prefix,
+                s.seller_city, s.canonical_city, s.seller_state,
+                {} AS badges,
+                g.geolocation_lat, g.geolocation_lng
+            FROM sellers s
+            JOIN geolocation g ON g.geolocation_zip_code_prefix = s.seller_zip_code_prefix
+            WHERE g.geolocation_lat BETWEEN ?1 AND ?2
+              AND g.geolocation_lng BETWEEN ?3 AND ?4
+              AND s.tenant_id = ?5
+            "#,
+            SELLER_BADGES
+        ))
+        .bind(bounds.min_lat)
+        .bind(bounds.max_lat)
+        .bind(bounds.min_lng)
+        .bind(bounds.max_lng)
+        .bind(self.tenant.as_str())
+        .fetch_all(&self.pool)
+        .await
+        .map(|rows| rows.into_iter().map(|row| row.0).collect())
+        .map_err(|e| {
+            error!("Error fetching sellers near a location: {:?}", e);
+            e
+        })
+    }
+
+    /// Recomputes every seller's badges from order, shipping and review metrics against the
+    /// thresholds in `seller_badge_thresholds`, replacing the previous set atomically.
+    #[instrument(skip(self))]
+    async fn refresh_badges(&self) -> SqlxResult<u64> {
+        let result = async {
+            let mut tx = self.pool.begin().await?;
+
+            sqlx::query("DELETE FROM seller_badges WHERE tenant_id = ?1")
+                .bind(self.tenant.as_str())
+                .execute(&mut *tx)
+                .await?;
+
+            let inserted = sqlx::query(
+                r#"
+                WITH seller_orders AS (
+                    SELECT DISTINCT oi.seller_id, oi.order_id
+                    FROM order_items oi
+                    WHERE oi.tenant_id = ?1
+                ),
+                order_metrics AS (
+                    SELECT
+                        so.seller_id,
+                        COUNT(*) AS order_count,
+                        COUNT(o.order_delivered_carrier_date) AS handled_count,
+                        AVG(
+                            (julianday(o.order_delivered_carrier_date)
+                                - julianday(o.order_approved_at)) * 24
+                        ) AS avg_handling_hours
+                    FROM seller_orders so
+                    JOIN orders o ON o.order_id = so.order_id
+                    GROUP BY so.seller_id
+                ),
+                review_metrics AS (
+                    SELECT so.seller_id, AVG(r.review_score) AS avg_review_score, COUNT(*) AS review_count
+                    FROM seller_orders so
+                    JOIN reviews r ON r.order_id = so.order_id
+                    GROUP BY so.seller_id
+                ),
+                seller_metrics AS (
+                    SELECT seller_id, 'avg_handling_hours' AS metric,
+                           avg_handling_hours AS value, handled_count AS sample
+                    FROM order_metrics
+                    UNION ALL
+                    SELECT seller_id, 'order_count', order_count, order_count
+                    FROM order_metrics
+                    UNION ALL
+                    SELECT seller_id, 'avg_review_score', avg_review_score, review_count
+                    FROM review_metrics
+                )
+                INSERT INTO seller_badges (seller_id, badge, metric_value, tenant_id)
+                SELECT m.seller_id, t.badge, round(m.value, 2), s.tenant_id
+                FROM seller_metrics m
+                JOIN seller_badge_thresholds t ON t.metric = m.metric
+                JOIN sellers s ON s.seller_id = m.seller_id
+                WHERE s.tenant_id = ?1
+                  AND m.value IS NOT NULL
+                  AND m.sample >= t.min_sample
+                  AND CASE t.comparison
+                        WHEN 'lte' THEN m.value <= t.threshold
+                        ELSE m.value >= t.threshold
+                      END
+                "#,
+            )
+            .bind(self.tenant.as_str())
+            .execute(&mut *tx)
+            .await?;
+
+            tx.commit().await?;
+            Ok(inserted.rows_affected())
+        }
+        .await;
+
+        match &result {
+            Ok(count) => info!("Awarded {} seller badges", count),
+            Err(e) => error!("Error refreshing seller badges: {:?}", e),
+        }
+
+        result
+    }
+
+    async fn find_badge_thresholds(&self) -> SqlxResult<Vec<SellerBadgeThreshold>> {
+        sqlx::query_as::<_, Decoded<SellerBadgeThreshold>>(
+            r#"
+            SELECT badge, description, metric, comparison, threshold, min_sample
+            FROM seller_badge_thresholds
+            ORDER BY badge
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map(|thresholds| {
+            thresholds
+                .into_iter()
+                .map(|threshold| threshold.0)
+                .collect()
+        })
+        .map_err(|e| {
+            error!("Error fetching seller badge thresholds: {:?}", e);
+            e
+        })
+    }
+
+    async fn find_metrics(&self, id: &SellerId) -> SqlxResult<SellerMetrics> {
+        sqlx::query_as::<_, SellerMetrics>(
+            r#"
+            WITH seller_orders AS (
+                SELECT order_id, MIN(shipping_limit_date) AS shipping_limit_date
+                FROM order_items
+                WHERE seller_id = ?1 AND tenant_id = ?2
+                GROUP BY order_id
+            )
+            SELECT
+                COUNT(o.order_id) AS order_count,
+                COUNT(o.order_delivered_carrier_date) AS shipped_count,
+                COUNT(*) FILTER (
+                    WHERE o.order_delivered_carrier_date <= so.shipping_limit_date
+                ) AS on_time_count,
+                COUNT(*) FILTER (WHERE o.order_status = 'canceled') AS canceled_count,
+                (
+                    SELECT COUNT(*) FROM seller_orders s JOIN reviews r ON r.order_id = s.order_id
+                ) AS review_count,
+                (
+                    SELECT AVG(r.review_score)
+                    FROM seller_orders s
+                    JOIN reviews r ON r.order_id = s.order_id
+                ) AS review_average
+            FROM seller_orders so
+            JOIN orders o ON o.order_id = so.order_id
+            "#,
+        )
+        .bind(id.as_str())
+        .bind(self.tenant.as_str())
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching seller metrics: {:?}", e);
+            e
+        })
+    }
+}
+
+const ORDER_COLUMNS: &str = r#"
+    order_id, customer_id, order_status,
+    order_purchase_timestamp, order_approved_at,
+    order_delivered_carrier_date, order_delivered_customer_date,
+    order_estimated_delivery_date
+"#;
+
+const ORDER_ITEM_COLUMNS: &str = r#"
+    order_item_id, order_id, product_id, seller_id,
+    shipping_limit_date, price, freight_value
+"#;
+
+const ORDER_AMENDMENT_COLUMNS: &str = r#"
+    amendment_id, order_id, actor, changes,
+    previous_freight, new_freight, previous_tax, new_tax, created_at
+"#;
+
+/// `WHERE` clause for [`OrderFilter`] within a tenant, bound by [`bind_order_filter`] as
+/// `?1`/`?2`.
+const ORDER_FILTER: &str = "(?1 IS NULL OR order_status = ?1) AND tenant_id = ?2";
+
+fn bind_order_filter<'q, O>(
+    query: QueryAs<'q, Sqlite, O, SqliteArguments<'q>>,
+    filter: &'q OrderFilter,
+    tenant: &'q TenantId,
+) -> QueryAs<'q, Sqlite, O, SqliteArguments<'q>> {
+    query.bind(filter.status).bind(tenant.as_str())
+}
+
+/// SQL expression over `orders o JOIN customers c` identifying the stratum; the SQLite form
+/// of [`SampleStratum::column`].
+fn stratum_column(stratum: SampleStratum) -> &'static str {
+    match stratum {
+        SampleStratum::State => "c.customer_state",
+        SampleStratum::Status => "o.order_status",
+        SampleStratum::PurchaseMonth => "strftime('%Y-%m', o.order_purchase_timestamp)",
+    }
+}
+
+/// Picks `size` orders from `(stratum, order)` pairs, ranking each stratum by a hash of the
+/// order id and `seed` and interleaving strata by relative rank, so every stratum is
+/// represented in proportion to its size and the same seed gives the same sample.
+pub(crate) fn rank_sample(rows: Vec<(String, Order)>, size: i64, seed: i64) -> Vec<Order> {
+    let mut by_stratum: HashMap<String, Vec<(Vec<u8>, Order)>> = HashMap::new();
+    for (stratum, order) in rows {
+        let key = Sha256::digest(format!("{}:{}", order.order_id, seed)).to_vec();
+        by_stratum.entry(stratum).or_default().push((key, order));
+    }
+
+    let mut ranked = Vec::new();
+    for mut orders in by_stratum.into_values() {
+        orders.sort_by(|a, b| a.0.cmp(&b.0));
+        let stratum_size = orders.len() as f64;
+        for (rank, (key, order)) in orders.into_iter().enumerate() {
+            ranked.push(((rank + 1) as f64 / stratum_size, key, order));
+        }
+    }
+    ranked.sort_by(|a, b| a.0.total_cmp(&b.0).then_with(|| a.1.cmp(&b.1)));
+
+    ranked
+        .into_iter()
+        .take(size.max(0) as usize)
+        .map(|(_, _, order)| order)
+        .collect()
+}
+
+#[derive(Clone)]
+pub struct SqliteOrderRepository {
+    pool: SqlitePool,
+    tenant: TenantId,
+}
+
+impl SqliteOrderRepository {
+    pub fn new(pool: SqlitePool, tenant: TenantId) -> Self {
+        Self { pool, tenant }
+    }
+
+    async fn total(
+        &self,
+        filter: &OrderFilter,
+        mode: TotalMode,
+        window_total: Option<i64>,
+    ) -> SqlxResult<Total> {
+        let count = format!("SELECT COUNT(*) FROM orders WHERE {}", ORDER_FILTER);
+        page_total(
+            &self.pool,
+            mode,
+            window_total,
+            bind_order_filter(sqlx::query_as(&count), filter, &self.tenant),
+        )
+        .await
+        .map_err(|e| {
+            error!("Error counting orders: {:?}", e);
+            e
+        })
+    }
+
+    /// A listing page selecting `columns`, filtered as `?1`/`?2` and paged by `?3`/`?4`.
+    fn page_query(&self, columns: &str, total: TotalMode) -> String {
+        format!(
+            r#"
+            SELECT {}, {}
+            FROM orders
+            WHERE {}
+            ORDER BY order_purchase_timestamp DESC
+            LIMIT ?3 OFFSET ?4
+            "#,
+            columns,
+            total_column(total),
+            ORDER_FILTER
+        )
+    }
+}
+
+async fn insert_order<'e>(
+    executor: impl SqliteExecutor<'e>,
+    tenant: &TenantId,
+    id: &OrderId,
+    dto: CreateOrderDto,
+) -> SqlxResult<Order> {
+    sqlx::query_as::<_, Order>(&format!(
+        r#"
+        INSERT INTO orders (
+            order_id, customer_id, order_status,
+            order_purchase_timestamp, order_approved_at,
+            order_delivered_carrier_date, order_delivered_customer_date,
+            order_estimated_delivery_date, shipping_zip_code_prefix, tenant_id
+        )
+        VALUES (
+            ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8,
+            (
+                SELECT zip_code_prefix FROM customer_addresses
+                WHERE customer_id = ?2
+                  AND tenant_id = ?10
+                  AND (address_id = ?9 OR (?9 IS NULL AND is_default))
+            ),
+            ?10
+        )
+        RETURNING {}
+        "#,
+        ORDER_COLUMNS
+    ))
+    .bind(id.as_str())
+    .bind(dto.customer_id.as_str())
+    .bind(dto.order_status)
+    .bind(dto.order_purchase_timestamp)
+    .bind(dto.order_approved_at)
+    .bind(dto.order_delivered_carrier_date)
+    .bind(dto.order_delivered_customer_date)
+    .bind(dto.order_estimated_delivery_date)
+    .bind(dto.shipping_address_id)
+    .bind(tenant.as_str())
+    .fetch_one(executor)
+    .await
+    .map_err(|e| {
+        error!("Error creating order: {:?}", e);
+        e
+    })
+}
+
+#[async_trait]
+impl OrderRepository for SqliteOrderRepository {
+    async fn create(&self, id: &OrderId, dto: CreateOrderDto) -> SqlxResult<Order> {
+        insert_order(&self.pool, &self.tenant, id, dto).await
+    }
+
+    async fn create_many(
+        &self,
+        rows: Vec<(OrderId, CreateOrderDto)>,
+    ) -> SqlxResult<Vec<SqlxResult<Order>>> {
+        insert_each(&self.pool, rows, |conn, (id, dto)| {
+            let tenant = self.tenant.clone();
+            Box::pin(async move { insert_order(conn, &tenant, &id, dto).await })
+        })
+        .await
+    }
+
+    async fn add_item_with_allocation(
+        &self,
+        order_id: &OrderId,
+        dto: AddItemToOrderDto,
+        destination_zip_code_prefix: &str,
+    ) -> SqlxResult<Option<(OrderItem, Option<StockAllocation>)>> {
+        let result = async {
+            let mut tx = self.pool.begin().await?;
+
+            let tracked = sqlx::query_scalar::<_, bool>(
+                r#"
+                SELECT EXISTS(
+                    SELECT 1
+                    FROM location_stock ls
+                    JOIN stock_locations l ON l.location_id = ls.location_id
+                    WHERE l.seller_id = ?1 AND ls.product_id = ?2 AND ls.tenant_id = ?3
+                )
+                "#,
+            )
+            .bind(dto.seller_id.as_str())
+            .bind(dto.product_id.as_str())
+            .bind(self.tenant.as_str())
+            .fetch_one(&mut *tx)
+            .await?;
+
+            // Same nearest-location choice as SqliteInventoryRepository::allocate.
+            let allocation = if tracked {
+                let Some(allocation) = sqlx::query_as::<_, StockAllocation>(
+                    r#"
+                    UPDATE location_stock
+                    SET quantity = quantity - 1, updated_at = datetime('now')
+                    WHERE product_id = ?2 AND location_id = (
+                        SELECT ls.location_id
+                        FROM location_stock ls
+                        JOIN stock_locations l ON l.location_id = ls.location_id
+                        WHERE l.seller_id = ?1
+                          AND ls.product_id = ?2
+                          AND ls.quantity >= 1
+                          AND ls.tenant_id = ?4
+                        ORDER BY
+                            abs(CAST(l.zip_code_prefix AS INTEGER) - CAST(?3 AS INTEGER)),
+                            l.location_id
+                        LIMIT 1
+                    )
+                    RETURNING location_id, product_id, quantity
+                    "#,
+                )
+                .bind(dto.seller_id.as_str())
+                .bind(dto.product_id.as_str())
+                .bind(destination_zip_code_prefix)
+                .bind(self.tenant.as_str())
+                .fetch_optional(&mut *tx)
+                .await?
+                else {
+                    return Ok(None);
+                };
+                Some(allocation)
+            } else {
+                None
+            };
+
+            let item = sqlx::query_as::<_, Decoded<OrderItem>>(&format!(
+                r#"
+                INSERT INTO order_items (
+                    order_item_id, order_id, product_id, seller_id,
+                    shipping_limit_date, price, freight_value, tenant_id
+                )
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+                RETURNING {}
+                "#,
+                ORDER_ITEM_COLUMNS
+            ))
+            .bind(dto.order_item_id)
+            .bind(order_id.as_str())
+            .bind(dto.product_id.as_str())
+            .bind(dto.seller_id.as_str())
+            .bind(dto.shipping_limit_date)
+            .bind(dto.price.to_string())
+            .bind(dto.freight_value.to_string())
+            .bind(self.tenant.as_str())
+            .fetch_one(&mut *tx)
+            .await?;
+
+            tx.commit().await?;
+            Ok(Some((item.0, allocation)))
+        }
+        .await;
+
+        if let Err(e) = &result {
+            error!("Error adding item to order: {:?}", e);
+        }
+        result
+    }
+
+    async fn find_all(
+        &self,
+        filter: &OrderFilter,
+        pagination: &PaginationParams,
+        total: TotalMode,
+    ) -> SqlxResult<(Vec<Order>, Total)> {
+        let (limit, offset, _, _) = pagination.normalize();
+
+        let query = self.page_query(ORDER_COLUMNS, total);
+        let rows = bind_order_filter(
+            sqlx::query_as::<_, Counted<Order>>(&query),
+            filter,
+            &self.tenant,
+        )
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching orders: {:?}", e);
+            e
+        })?;
+
+        let (orders, window_total) = split_counted(rows, offset);
+        let total = self.total(filter, total, window_total).await?;
+
+        Ok((orders, total))
+    }
+
+    async fn find_all_sparse(
+        &self,
+        filter: &OrderFilter,
+        pagination: &PaginationParams,
+        fields: &[&'static str],
+        total: TotalMode,
+    ) -> SqlxResult<(Vec<SparseRow>, Total)> {
+        let (limit, offset, _, _) = pagination.normalize();
+
+        let query = self.page_query(&sparse_columns(fields, &[]), total);
+        let rows = bind_order_filter(
+            sqlx::query_as::<_, Counted<(Json<SparseRow>,)>>(&query),
+            filter,
+            &self.tenant,
+        )
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching sparse orders: {:?}", e);
+            e
+        })?;
+
+        let (rows, window_total) = split_counted(rows, offset);
+        let total = self.total(filter, total, window_total).await?;
+
+        Ok((rows.into_iter().map(|(row,)| row.0).collect(), total))
+    }
+
+    fn stream_all(&self) -> BoxStream<'_, SqlxResult<Order>> {
+        sqlx::query_as::<_, Order>(
+            r#"
+            SELECT
+                order_id, customer_id, order_status,
+                order_purchase_timestamp, order_approved_at,
+                order_delivered_carrier_date, order_delivered_customer_date,
+                order_estimated_delivery_date
+            FROM orders
+            WHERE tenant_id = ?1
+            ORDER BY order_purchase_timestamp, order_id
+            "#,
+        )
+        .bind(self.tenant.as_str())
+        .fetch(&self.pool)
+    }
+
+    async fn find_by_id(&self, id: &OrderId) -> SqlxResult<Option<Order>> {
+        sqlx::query_as::<_, Order>(&format!(
+            "SELECT {} FROM orders WHERE order_id = ?1 AND tenant_id = ?2",
+            ORDER_COLUMNS
+        ))
+        .bind(id.as_str())
+        .bind(self.tenant.as_str())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching order by id: {:?}", e);
+            e
+        })
+    }
+
+    async fn find_status(&self, id: &OrderId) -> SqlxResult<Option<OrderStatusChange>> {
+        sqlx::query_as::<_, OrderStatusChange>(
+            r#"
+            SELECT order_id, order_status, status_version
+            FROM orders
+            WHERE order_id = ?1 AND tenant_id = ?2
+            "#,
+        )
+        .bind(id.as_str())
+        .bind(self.tenant.as_str())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching order status: {:?}", e);
+            e
+        })
+    }
+
+    fn stream_review_texts(&self) -> BoxStream<'_, SqlxResult<ReviewText>> {
+        sqlx::query_as::<_, ReviewText>(
+            r#"
+            SELECT review_id, review_score, review_comment_message
+            FROM reviews
+            WHERE NULLIF(TRIM(review_comment_message), '') IS NOT NULL AND tenant_id = ?1
+            ORDER BY review_creation_date, review_id
+            "#,
+        )
+        .bind(self.tenant.as_str())
+        .fetch(&self.pool)
+    }
+
+    /// Draws a reproducible sample the way the Postgres repository does. SQLite has no hash
+    /// function, so the ranking happens in [`rank_sample`] rather than in the query.
+    async fn sample(
+        &self,
+        strata: &[SampleStratum],
+        size: i64,
+        seed: i64,
+    ) -> SqlxResult<Vec<Order>> {
+        let stratum = if strata.is_empty() {
+            "''".to_string()
+        } else {
+            strata
+                .iter()
+                .map(|stratum| format!("COALESCE({}, '')", stratum_column(*stratum)))
+                .collect::<Vec<_>>()
+                .join(" || '|' || ")
+        };
+
+        let rows = sqlx::query(&format!(
+            r#"
+            SELECT
+                o.order_id, o.customer_id, o.order_status,
+                o.order_purchase_timestamp, o.order_approved_at,
+                o.order_delivered_carrier_date, o.order_delivered_customer_date,
+                o.order_estimated_delivery_date,
+                {stratum} AS stratum
+            FROM orders o
+            JOIN customers c ON c.customer_id = o.customer_id
+            WHERE o.tenant_id = ?1
+            "#
+        ))
+        .bind(self.tenant.as_str())
+        .try_map(|row: SqliteRow| {
+            let stratum: String = row.try_get("stratum")?;
+            Ok((stratum, Order::from_row(&row)?))
+        })
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error sampling orders: {:?}", e);
+            e
+        })?;
+
+        Ok(rank_sample(rows, size, seed))
+    }
+
+    async fn find_products_by_order_id(&self, id: &OrderId) -> SqlxResult<Vec<OrderProduct>> {
+        sqlx::query_as::<_, Decoded<OrderProduct>>(
+            r#"
+            SELECT
+                p.product_id,
+                p.product_category_name,
+                p.product_name_lenght,
+                p.product_description_lenght,
+                p.product_photos_qty,
+                p.product_weight_g,
+                p.product_length_cm,
+                p.product_height_cm,
+                p.product_width_cm,
+                oi.shipping_limit_date,
+                oi.price,
+                oi.freight_value
+            FROM products p
+            INNER JOIN order_items oi ON p.product_id = oi.product_id
+            WHERE oi.order_id = ?1 AND oi.tenant_id = ?2
+            "#,
+        )
+        .bind(id.as_str())
+        .bind(self.tenant.as_str())
+        .fetch_all(&self.pool)
+        .await
+        .map(|products| products.into_iter().map(|product| product.0).collect())
+        .map_err(|e| {
+            error!("Error fetching products for order: {:?}", e);
+            e
+        })
+    }
+
+    async fn find_products_by_order_ids(
+        &self,
+        ids: &[OrderId],
+    ) -> SqlxResult<Vec<(OrderId, OrderProduct)>> {
+        sqlx::query_as::<_, Decoded<(OrderId, OrderProduct)>>(
+            r#"
+            SELECT
+                oi.order_id,
+                p.product_id,
+                p.product_category_name,
+                p.product_name_lenght,
+                p.product_description_lenght,
+                p.product_photos_qty,
+                p.product_weight_g,
+                p.product_length_cm,
+                p.product_height_cm,
+                p.product_width_cm,
+                oi.shipping_limit_date,
+                oi.price,
+                oi.freight_value
+            FROM products p
+            INNER JOIN order_items oi ON p.product_id = oi.product_id
+            WHERE oi.order_id IN (SELECT value FROM json_each(?1)) AND oi.tenant_id = ?2
+            "#,
+        )
+        .bind(Json(ids))
+        .bind(self.tenant.as_str())
+        .fetch_all(&self.pool)
+        .await
+        .map(|products| products.into_iter().map(|product| product.0).collect())
+        .map_err(|e| {
+            error!("Error fetching products for orders: {:?}", e);
+            e
+        })
+    }
+
+    async fn find_payments_by_order_id(&self, id: &OrderId) -> SqlxResult<Vec<Payment>> {
+        sqlx::query_as::<_, Decoded<Payment>>(
+            r#"
+            SELECT
+                order_id, payment_sequential, payment_type, payment_installments, payment_value
+            FROM payments
+            WHERE order_id = ?1 AND tenant_id = ?2
+            "#,
+        )
+        .bind(id.as_str())
+        .bind(self.tenant.as_str())
+        .fetch_all(&self.pool)
+        .await
+        .map(|payments| payments.into_iter().map(|payment| payment.0).collect())
+        .map_err(|e| {
+            error!("Error fetching payments for order: {:?}", e);
+            e
+        })
+    }
+
+    async fn find_payments_by_order_ids(&self, ids: &[OrderId]) -> SqlxResult<Vec<Payment>> {
+        sqlx::query_as::<_, Decoded<Payment>>(
+            r#"
+            SELECT
+                order_id, payment_sequential, payment_type, payment_installments, payment_value
+            FROM payments
+            WHERE order_id IN (SELECT value FROM json_each(?1)) AND tenant_id = ?2
+            "#,
+        )
+        .bind(Json(ids))
+        .bind(self.tenant.as_str())
+        .fetch_all(&self.pool)
+        .await
+        .map(|payments| payments.into_iter().map(|payment| payment.0).collect())
+        .map_err(|e| {
+            error!("Error fetching payments for orders: {:?}", e);
+            e
+        })
+    }
+
+    async fn create_refund(&self, refund: NewRefund) -> SqlxResult<Option<Refund>> {
+        // One statement, so the refunds it totals cannot change before it writes. Amounts are
+        // compared in cents as NUMERIC columns hold floats here.
+        sqlx::query_as::<_, Decoded<Refund>>(
+            r#"
+            INSERT INTO refunds (order_id, payment_sequential, amount, reason, tenant_id)
+            SELECT p.order_id, p.payment_sequential, ?3, ?4, p.tenant_id
+            FROM payments p
+            WHERE p.order_id = ?1 AND p.payment_sequential = ?2 AND p.tenant_id = ?5
+                AND ROUND((
+                    SELECT COALESCE(SUM(r.amount), 0)
+                    FROM refunds r
+                    WHERE r.order_id = ?1 AND r.payment_sequential = ?2
+                ) * 100 + ?3 * 100) <= ROUND(p.payment_value * 100)
+            RETURNING refund_id, order_id, payment_sequential, amount, reason, created_at
+            "#,
+        )
+        .bind(refund.order_id.as_str())
+        .bind(refund.payment_sequential)
+        .bind(refund.amount.to_string())
+        .bind(&refund.reason)
+        .bind(self.tenant.as_str())
+        .fetch_optional(&self.pool)
+        .await
+        .map(|created| created.map(|refund| refund.0))
+        .map_err(|e| {
+            error!("Error creating refund: {:?}", e);
+            e
+        })
+    }
+
+    async fn find_refunds_by_order_id(&self, id: &OrderId) -> SqlxResult<Vec<Refund>> {
+        sqlx::query_as::<_, Decoded<Refund>>(
+            r#"
+            SELECT refund_id, order_id, payment_sequential, amount, reason, created_at
+            FROM refunds
+            WHERE order_id = ?1 AND tenant_id = ?2
+            ORDER BY created_at, refund_id
+            "#,
+        )
+        .bind(id.as_str())
+        .bind(self.tenant.as_str())
+        .fetch_all(&self.pool)
+        .await
+        .map(|refunds| refunds.into_iter().map(|refund| refund.0).collect())
+        .map_err(|e| {
+            error!("Error fetching refunds for order: {:?}", e);
+            e
+        })
+    }
+
+    async fn find_refunds_by_order_ids(&self, ids: &[OrderId]) -> SqlxResult<Vec<Refund>> {
+        sqlx::query_as::<_, Decoded<Refund>>(
+            r#"
+            SELECT refund_id, order_id, payment_sequential, amount, reason, created_at
+            FROM refunds
+            WHERE order_id IN (SELECT value FROM json_each(?1)) AND tenant_id = ?2
+            ORDER BY created_at, refund_id
+            "#,
+        )
+        .bind(Json(ids))
+        .bind(self.tenant.as_str())
+        .fetch_all(&self.pool)
+        .await
+        .map(|refunds| refunds.into_iter().map(|refund| refund.0).collect())
+        .map_err(|e| {
+            error!("Error fetching refunds for orders: {:?}", e);
+            e
+        })
+    }
+
+    async fn find_payment_mismatches(
+        &self,
+        filter: &ReconciliationFilter,
+        pagination: &PaginationParams,
+    ) -> SqlxResult<(Vec<PaymentMismatch>, i64)> {
+        let (limit, offset, _, _) = pagination.normalize();
+
+        let rows = sqlx::query_as::<_, Counted<Decoded<PaymentMismatch>>>(&format!(
+            r#"
+            SELECT
+                order_id, order_status, order_purchase_timestamp, item_count, items_total,
+                payment_count, payments_total, payments_total - items_total AS difference,
+                COUNT(*) OVER () AS total_count
+            FROM ({}) sums
+            WHERE {}
+            ORDER BY order_purchase_timestamp, order_id
+            LIMIT ?5 OFFSET ?6
+            "#,
+            ORDER_SUMS, PAYMENT_MISMATCH
+        ))
+        .bind(filter.purchased_from)
+        .bind(filter.purchased_before)
+        .bind(self.tenant.as_str())
+        .bind(filter.tolerance.to_string())
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching payment mismatches: {:?}", e);
+            e
+        })?;
+
+        let (mismatches, total_count) = match split_counted(rows, offset) {
+            (mismatches, Some(total_count)) => (mismatches, total_count),
+            // Past the last page no row carries the window total.
+            (mismatches, None) => {
+                let count = sqlx::query_scalar::<_, i64>(&format!(
+                    "SELECT COUNT(*) FROM ({}) sums WHERE {}",
+                    ORDER_SUMS, PAYMENT_MISMATCH
+                ))
+                .bind(filter.purchased_from)
+                .bind(filter.purchased_before)
+                .bind(self.tenant.as_str())
+                .bind(filter.tolerance.to_string())
+                .fetch_one(&self.pool)
+                .await
+                .map_err(|e| {
+                    error!("Error counting payment mismatches: {:?}", e);
+                    e
+                })?;
+                (mismatches, count)
+            }
+        };
+
+        Ok((
+            mismatches.into_iter().map(|mismatch| mismatch.0).collect(),
+            total_count,
+        ))
+    }
+
+    async fn find_payment_method_stats(
+        &self,
+        filter: &PaymentAnalyticsFilter,
+    ) -> SqlxResult<Vec<PaymentMethodStats>> {
+        sqlx::query_as::<_, Decoded<PaymentMethodStats>>(
+            r#"
+            SELECT
+                p.payment_type,
+                COUNT(*) AS payment_count,
+                CAST(COUNT(*) AS REAL) / SUM(COUNT(*)) OVER () AS share,
+                SUM(p.payment_value) AS total_value,
+                ROUND(AVG(p.payment_installments), 2) AS average_installments,
+                AVG(p.payment_value) AS average_ticket
+            FROM payments p
+            JOIN orders o ON o.order_id = p.order_id
+            WHERE (?1 IS NULL OR o.order_purchase_timestamp >= ?1)
+              AND (?2 IS NULL OR o.order_purchase_timestamp < ?2)
+              AND o.tenant_id = ?3
+            GROUP BY p.payment_type
+            ORDER BY payment_count DESC, p.payment_type
+            "#,
+        )
+        .bind(filter.purchased_from)
+        .bind(filter.purchased_before)
+        .bind(self.tenant.as_str())
+        .fetch_all(&self.pool)
+        .await
+        .map(|stats| stats.into_iter().map(|stats| stats.0).collect())
+        .map_err(|e| {
+            error!("Error fetching payment method stats: {:?}", e);
+            e
+        })
+    }
+
+    async fn find_financials(&self, id: &OrderId) -> SqlxResult<Option<OrderFinancials>> {
+        sqlx::query_as::<_, Decoded<OrderFinancials>>(
+            r#"
+            SELECT
+                o.order_id,
+                o.order_status,
+                (SELECT COUNT(*) FROM order_items i WHERE i.order_id = o.order_id) AS item_count,
+                (SELECT COALESCE(SUM(i.price), 0) FROM order_items i
+                 WHERE i.order_id = o.order_id) AS items_subtotal,
+                (SELECT COALESCE(SUM(i.freight_value), 0) FROM order_items i
+                 WHERE i.order_id = o.order_id) AS freight_total,
+                (SELECT COUNT(*) FROM payments p WHERE p.order_id = o.order_id) AS payment_count,
+                (SELECT COALESCE(SUM(p.payment_value), 0) FROM payments p
+                 WHERE p.order_id = o.order_id) AS payments_total,
+                (SELECT COALESCE(SUM(r.amount), 0) FROM refunds r
+                 WHERE r.order_id = o.order_id) AS refunds_total,
+                c.code AS coupon_code,
+                c.discount_type AS coupon_discount_type,
+                c.value AS coupon_value
+            FROM orders o
+            LEFT JOIN order_coupons oc ON oc.order_id = o.order_id
+            LEFT JOIN coupons c ON c.tenant_id = oc.tenant_id AND c.code = oc.code
+            WHERE o.order_id = ?1 AND o.tenant_id = ?2
+            "#,
+        )
+        .bind(id.as_str())
+        .bind(self.tenant.as_str())
+        .fetch_optional(&self.pool)
+        .await
+        .map(|financials| financials.map(|financials| financials.0))
+        .map_err(|e| {
+            error!("Error fetching order financials: {:?}", e);
+            e
+        })
+    }
+
+    async fn find_reviews_by_order_id(&self, id: &OrderId) -> SqlxResult<Vec<Review>> {
+        sqlx::query_as::<_, Review>(
+            r#"
+            SELECT
+                review_id, order_id, review_score, review_comment_title,
+                review_comment_message, review_creation_date, review_answer_timestamp
+            FROM reviews
+            WHERE order_id = ?1 AND tenant_id = ?2
+            "#,
+        )
+        .bind(id.as_str())
+        .bind(self.tenant.as_str())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching reviews for order: {:?}", e);
+            e
+        })
+    }
+
+    async fn find_reviews_by_order_ids(&self, ids: &[OrderId]) -> SqlxResult<Vec<Review>> {
+        sqlx::query_as::<_, Review>(
+            r#"
+            SELECT
+                review_id, order_id, review_score, review_comment_title,
+                review_comment_message, review_creation_date, review_answer_timestamp
+            FROM reviews
+            WHERE order_id IN (SELECT value FROM json_each(?1)) AND tenant_id = ?2
+            "#,
+        )
+        .bind(Json(ids))
+        .bind(self.tenant.as_str())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching reviews for orders: {:?}", e);
+            e
+        })
+    }
+
+    async fn find_by_customer_id(
+        &self,
+        customer_id: &CustomerId,
+        pagination: &PaginationParams,
+    ) -> SqlxResult<(Vec<Order>, i64)> {
+        let (limit, offset, _, _) = pagination.normalize();
+
+        let rows = sqlx::query_as::<_, Counted<Order>>(&format!(
+            r#"
+            SELECT {}, COUNT(*) OVER () AS total_count
+            FROM orders
+            WHERE customer_id = ?1 AND tenant_id = ?2
+            ORDER BY order_purchase_timestamp DESC
+            LIMIT ?3 OFFSET ?4
+            "#,
+            ORDER_COLUMNS
+        ))
+        .bind(customer_id.as_str())
+        .bind(self.tenant.as_str())
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching orders for customer: {:?}", e);
+            e
+        })?;
+
+        let (orders, total_count) = match split_counted(rows, offset) {
+            (orders, Some(total_count)) => (orders, total_count),
+            // Past the last page no row carries the window total.
+            (orders, None) => {
+                let count = sqlx::query_scalar::<_, i64>(
+                    "SELECT COUNT(*) FROM orders WHERE customer_id = ?1 AND tenant_id = ?2",
+                )
+                .bind(customer_id.as_str())
+                .bind(self.tenant.as_str())
+                .fetch_one(&self.pool)
+                .await
+                .map_err(|e| {
+                    error!("Error counting orders for customer: {:?}", e);
+                    e
+                })?;
+                (orders, count)
+            }
+        };
+
+        Ok((orders, total_count))
+    }
+
+    async fn find_by_customer_unique_id(&self, unique_id: &str) -> SqlxResult<Vec<Order>> {
+        sqlx::query_as::<_, Order>(
+            r#"
+            SELECT
+                o.order_id, o.customer_id, o.order_status,
+                o.order_purchase_timestamp, o.order_approved_at,
+                o.order_delivered_carrier_date, o.order_delivered_customer_date,
+                o.order_estimated_delivery_date
+            FROM orders o
+            JOIN customers c ON c.customer_id = o.customer_id
+            WHERE c.customer_unique_id = ?1 AND c.deleted_at IS NULL AND c.tenant_id = ?2
+            ORDER BY o.order_purchase_timestamp DESC, o.order_id
+            "#,
+        )
+        .bind(unique_id)
+        .bind(self.tenant.as_str())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching orders by customer unique id: {:?}", e);
+            e
+        })
+    }
+
+    async fn find_by_customer_id_after(
+        &self,
+        customer_id: &CustomerId,
+        after: Option<&Order>,
+        limit: i64,
+    ) -> SqlxResult<Vec<Order>> {
+        sqlx::query_as::<_, Order>(
+            r#"
+            SELECT
+                order_id, customer_id, order_status,
+                order_purchase_timestamp, order_approved_at,
+                order_delivered_carrier_date, order_delivered_customer_date,
+                order_estimated_delivery_date
+            FROM orders
+            WHERE customer_id = ?1 AND tenant_id = ?2
+                AND (?3 IS NULL OR (order_purchase_timestamp, order_id) > (?3, ?4))
+            ORDER BY order_purchase_timestamp, order_id
+            LIMIT ?5
+            "#,
+        )
+        .bind(customer_id.as_str())
+        .bind(self.tenant.as_str())
+        .bind(after.map(|order| order.order_purchase_timestamp))
+        .bind(after.map(|order| order.order_id.as_str()))
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching a page of customer orders: {:?}", e);
+            e
+        })
+    }
+
+    async fn find_destination_zip_code_prefix(
+        &self,
+        order_id: &OrderId,
+    ) -> SqlxResult<Option<String>> {
+        sqlx::query_scalar::<_, String>(
+            r#"
+            SELECT COALESCE(o.shipping_zip_code_prefix, c.customer_zip_code_prefix)
+            FROM orders o
+            JOIN customers c ON c.customer_id = o.customer_id
+            WHERE o.order_id = ?1 AND o.tenant_id = ?2
+            "#,
+        )
+        .bind(order_id.as_str())
+        .bind(self.tenant.as_str())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching order destination: {:?}", e);
+            e
+        })
+    }
+
+    async fn find_item_origins(&self, order_id: &OrderId) -> SqlxResult<Vec<OrderItemOrigin>> {
+        sqlx::query_as::<_, Decoded<OrderItemOrigin>>(
+            r#"
+            SELECT
+                oi.order_item_id, oi.order_id, oi.product_id, oi.seller_id,
+                oi.shipping_limit_date, oi.price, oi.freight_value,
+                s.seller_zip_code_prefix
+            FROM order_items oi
+            JOIN sellers s ON s.seller_id = oi.seller_id
+            WHERE oi.order_id = ?1 AND oi.tenant_id = ?2
+            ORDER BY oi.order_item_id
+            "#,
+        )
+        .bind(order_id.as_str())
+        .bind(self.tenant.as_str())
+        .fetch_all(&self.pool)
+        .await
+        .map(|origins| origins.into_iter().map(|origin| origin.0).collect())
+        .map_err(|e| {
+            error!("Error fetching order items with origin: {:?}", e);
+            e
+        })
+    }
+
+    #[instrument(skip(self, items, amendment), fields(order_id = %order_id))]
+    async fn apply_amendment(
+        &self,
+        order_id: &OrderId,
+        actor: &str,
+        shipping_zip_code_prefix: Option<&str>,
+        items: &[(ProductId, OrderItem)],
+        amendment: NewOrderAmendment,
+    ) -> SqlxResult<OrderAmendment> {
+        let result = async {
+            let mut tx = self.pool.begin().await?;
+
+            if let Some(zip) = shipping_zip_code_prefix {
+                sqlx::query(
+                    r#"
+                    UPDATE orders SET shipping_zip_code_prefix = ?2
+                    WHERE order_id = ?1 AND tenant_id = ?3
+                    "#,
+                )
+                .bind(order_id.as_str())
+                .bind(zip)
+                .bind(self.tenant.as_str())
+                .execute(&mut *tx)
+                .await?;
+            }
+
+            for (previous_product_id, item) in items {
+                sqlx::query(
+                    r#"
+                    UPDATE order_items
+                    SET product_id = ?4, price = ?5, freight_value = ?6
+                    WHERE order_id = ?1 AND order_item_id = ?2 AND product_id = ?3
+                      AND seller_id = ?7 AND tenant_id = ?8
+                    "#,
+                )
+                .bind(order_id.as_str())
+                .bind(item.order_item_id)
+                .bind(previous_product_id.as_str())
+                .bind(item.product_id.as_str())
+                .bind(item.price.to_string())
+                .bind(item.freight_value.to_string())
+                .bind(item.seller_id.as_str())
+                .bind(self.tenant.as_str())
+                .execute(&mut *tx)
+                .await?;
+            }
+
+            let recorded = sqlx::query_as::<_, Decoded<OrderAmendment>>(&format!(
+                r#"
+                INSERT INTO order_amendments (
+                    order_id, actor, changes,
+                    previous_freight, new_freight, previous_tax, new_tax, tenant_id
+                )
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+                RETURNING {}
+                "#,
+                ORDER_AMENDMENT_COLUMNS
+            ))
+            .bind(order_id.as_str())
+            .bind(actor)
+            .bind(&amendment.changes)
+            .bind(amendment.previous_freight.to_string())
+            .bind(amendment.new_freight.to_string())
+            .bind(amendment.previous_tax.to_string())
+            .bind(amendment.new_tax.to_string())
+            .bind(self.tenant.as_str())
+            .fetch_one(&mut *tx)
+            .await?;
+
+            tx.commit().await?;
+            Ok(recorded.0)
+        }
+        .await;
+
+        match &result {
+            Ok(_) => info!("Order amended successfully"),
+            Err(e) => error!("Error amending order: {:?}", e),
+        }
+
+        result
+    }
+
+    async fn find_amendments(&self, order_id: &OrderId) -> SqlxResult<Vec<OrderAmendment>> {
+        sqlx::query_as::<_, Decoded<OrderAmendment>>(&format!(
+            r#"
+            SELECT {}
+            FROM order_amendments
+            WHERE order_id = ?1 AND tenant_id = ?2
+            ORDER BY created_at, amendment_id
+            "#,
+            ORDER_AMENDMENT_COLUMNS
+        ))
+        .bind(order_id.as_str())
+        .bind(self.tenant.as_str())
+        .fetch_all(&self.pool)
+        .await
+        .map(|amendments| {
+            amendments
+                .into_iter()
+                .map(|amendment| amendment.0)
+                .collect()
+        })
+        .map_err(|e| {
+            error!("Error fetching order amendments: {:?}", e);
+            e
+        })
+    }
+
+    async fn count_by_status(&self) -> SqlxResult<Vec<FilterValue>> {
+        sqlx::query_as::<_, FilterValue>(
+            r#"
+            SELECT order_status AS value, COUNT(*) AS count
+            FROM orders
+            WHERE tenant_id = ?1
+            GROUP BY order_status
+            ORDER BY COUNT(*) DESC, value
+            "#,
+        )
+        .bind(self.tenant.as_str())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error counting orders by status: {:?}", e);
+            e
+        })
+    }
+}
+
+const PRODUCT_COLUMNS: &str = r#"
+    product_id, product_category_name, product_name_lenght,
+    product_description_lenght, product_photos_qty, product_weight_g,
+    product_length_cm, product_height_cm, product_width_cm,
+    product_name, description, price, active
+"#;
+
+const PRODUCT_IMAGE_COLUMNS: &str = r#"
+    image_id, product_id, position, url, storage_key, content_type, size_bytes, created_at
+"#;
+
+/// Sparse product columns `json_object` would not render as the full listing does: the price
+/// as the text a `Money` serializes to, and `active` as a boolean rather than 0 or 1.
+const PRODUCT_SPARSE_COLUMNS: &[(&str, &str)] = &[
+    (
+        "price",
+        "CASE WHEN price IS NULL THEN NULL ELSE printf('%.2f', price) END",
+    ),
+    ("active", "json(iif(active, 'true', 'false'))"),
+];
+
+/// `WHERE` clause for [`ProductFilter`] within a tenant, bound by [`bind_product_filter`] as
+/// `?1`..`?17`.
+const PRODUCT_FILTER: &str = r#"
+    (?1 IS NULL OR product_category_name = ?1)
+    AND (?2 IS NULL OR product_weight_g >= ?2)
+    AND (?3 IS NULL OR product_weight_g <= ?3)
+    AND (?4 IS NULL OR product_length_cm >= ?4)
+    AND (?5 IS NULL OR product_length_cm <= ?5)
+    AND (?6 IS NULL OR product_height_cm >= ?6)
+    AND (?7 IS NULL OR product_height_cm <= ?7)
+    AND (?8 IS NULL OR product_width_cm >= ?8)
+    AND (?9 IS NULL OR product_width_cm <= ?9)
+    AND (?10 IS NULL OR product_photos_qty >= ?10)
+    AND (?11 IS NULL OR product_photos_qty <= ?11)
+    AND (?12 IS NULL OR product_length_cm * product_height_cm * product_width_cm >= ?12)
+    AND (?13 IS NULL OR product_length_cm * product_height_cm * product_width_cm <= ?13)
+    AND (?14 IS NULL OR active = ?14)
+    AND (?15 IS NULL OR price >= ?15)
+    AND (?16 IS NULL OR price <= ?16)
+    AND tenant_id = ?17
+"#;
+
+fn bind_product_filter<'q, O>(
+    query: QueryAs<'q, Sqlite, O, SqliteArguments<'q>>,
+    filter: &'q ProductFilter,
+    tenant: &'q TenantId,
+) -> QueryAs<'q, Sqlite, O, SqliteArguments<'q>> {
+    query
+        .bind(&filter.category_name)
+        .bind(filter.min_weight_g)
+        .bind(filter.max_weight_g)
+        .bind(filter.min_length_cm)
+        .bind(filter.max_length_cm)
+        .bind(filter.min_height_cm)
+        .bind(filter.max_height_cm)
+        .bind(filter.min_width_cm)
+        .bind(filter.max_width_cm)
+        .bind(filter.min_photos)
+        .bind(filter.max_photos)
+        .bind(filter.min_volume_cm3)
+        .bind(filter.max_volume_cm3)
+        .bind(filter.active)
+        .bind(filter.min_price.as_ref().map(ToString::to_string))
+        .bind(filter.max_price.as_ref().map(ToString::to_string))
+        .bind(tenant.as_str())
+}
+
+#[derive(Clone)]
+pub struct SqliteProductRepository {
+    pool: SqlitePool,
+    tenant: TenantId,
+}
+
+impl SqliteProductRepository {
+    pub fn new(pool: SqlitePool, tenant: TenantId) -> Self {
+        Self { pool, tenant }
+    }
+
+    async fn total(
+        &self,
+        filter: &ProductFilter,
+        mode: TotalMode,
+        window_total: Option<i64>,
+    ) -> SqlxResult<Total> {
+        let count = format!("SELECT COUNT(*) FROM products WHERE {}", PRODUCT_FILTER);
+        page_total(
+            &self.pool,
+            mode,
+            window_total,
+            bind_product_filter(sqlx::query_as(&count), filter, &self.tenant),
+        )
+        .await
+        .map_err(|e| {
+            error!("Error counting products: {:?}", e);
+            e
+        })
+    }
+
+    /// A listing page selecting `columns`, filtered as `?1`..`?17` and paged by `?18`/`?19`.
+    fn page_query(&self, columns: &str, total: TotalMode) -> String {
+        format!(
+            r#"
+            SELECT {}, {}
+            FROM products
+            WHERE {}
+            ORDER BY product_id DESC
+            LIMIT ?18 OFFSET ?19
+            "#,
+            columns,
+            total_column(total),
+            PRODUCT_FILTER
+        )
+    }
+}
+
+async fn insert_product<'e>(
+    executor: impl SqliteExecutor<'e>,
+    tenant: &TenantId,
+    id: &ProductId,
+    dto: CreateProductDto,
+) -> SqlxResult<Product> {
+    sqlx::query_as::<_, Decoded<Product>>(&format!(
+        r#"
+        INSERT INTO products (
+            product_id, product_category_name, product_name_lenght,
+            product_description_lenght, product_photos_qty, product_weight_g,
+            product_length_cm, product_height_cm, product_width_cm,
+            product_name, description, price, active, tenant_id
+        )
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, COALESCE(?13, TRUE), ?14)
+        RETURNING {}
+        "#,
+        PRODUCT_COLUMNS
+    ))
+    .bind(id.as_str())
+    .bind(&dto.product_category_name)
+    .bind(dto.product_name_lenght)
+    .bind(dto.product_description_lenght)
+    .bind(dto.product_photos_qty)
+    .bind(dto.product_weight_g)
+    .bind(dto.product_length_cm)
+    .bind(dto.product_height_cm)
+    .bind(dto.product_width_cm)
+    .bind(&dto.product_name)
+    .bind(&dto.description)
+    .bind(dto.price.as_ref().map(ToString::to_string))
+    .bind(dto.active)
+    .bind(tenant.as_str())
+    .fetch_one(executor)
+    .await
+    .map(|Decoded(product)| product)
+    .map_err(|e| {
+        error!("Error creating product: {:?}", e);
+        e
+    })
+}
+
+#[async_trait]
+impl ProductRepository for SqliteProductRepository {
+    async fn create(&self, id: &ProductId, dto: CreateProductDto) -> SqlxResult<Product> {
+        insert_product(&self.pool, &self.tenant, id, dto).await
+    }
+
+    async fn create_many(
+        &self,
+        rows: Vec<(ProductId, CreateProductDto)>,
+    ) -> SqlxResult<Vec<SqlxResult<Product>>> {
+        insert_each(&self.pool, rows, |conn, (id, dto)| {
+            let tenant = self.tenant.clone();
+            Box::pin(async move { insert_product(conn, &tenant, &id, dto).await })
+        })
+        .await
+    }
+
+    async fn find_all(
+        &self,
+        filter: &ProductFilter,
+        pagination: &PaginationParams,
+        total: TotalMode,
+    ) -> SqlxResult<(Vec<Product>, Total)> {
+        let (limit, offset, _, _) = pagination.normalize();
+
+        let query = self.page_query(PRODUCT_COLUMNS, total);
+        let rows = bind_product_filter(
+            sqlx::query_as::<_, Counted<Decoded<Product>>>(&query),
+            filter,
+            &self.tenant,
+        )
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching products: {:?}", e);
+            e
+        })?;
+
+        let (products, window_total) = split_counted(rows, offset);
+        let total = self.total(filter, total, window_total).await?;
+
+        Ok((
+            products.into_iter().map(|product| product.0).collect(),
+            total,
+        ))
+    }
+
+    async fn find_all_sparse(
+        &self,
+        filter: &ProductFilter,
+        pagination: &PaginationParams,
+        fields: &[&'static str],
+        total: TotalMode,
+    ) -> SqlxResult<(Vec<SparseRow>, Total)> {
+        let (limit, offset, _, _) = pagination.normalize();
+
+        let query = self.page_query(&sparse_columns(fields, PRODUCT_SPARSE_COLUMNS), total);
+        let rows = bind_product_filter(
+            sqlx::query_as::<_, Counted<(Json<SparseRow>,)>>(&query),
+            filter,
+            &self.tenant,
+        )
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching sparse products: {:?}", e);
+            e
+        })?;
+
+        let (rows, window_total) = split_counted(rows, offset);
+        let total = self.total(filter, total, window_total).await?;
+
+        Ok((rows.into_iter().map(|(row,)| row.0).collect(), total))
+    }
+
+    fn stream_all(&self) -> BoxStream<'_, SqlxResult<Product>> {
+        sqlx::query(
+            r#"
+            SELECT
+                product_id, product_category_name, product_name_lenght,
+                product_description_lenght, product_photos_qty, product_weight_g,
+                product_length_cm, product_height_cm, product_width_cm,
+                product_name, description, price, active
+            FROM products
+            WHERE tenant_id = ?1
+            ORDER BY product_id
+            "#,
+        )
+        .bind(self.tenant.as_str())
+        .try_map(|row: SqliteRow| {
+            Decoded::<Product>::from_row(&row).map(|Decoded(product)| product)
+        })
+        .fetch(&self.pool)
+    }
+
+    async fn find_by_id(&self, id: &ProductId) -> SqlxResult<Option<Product>> {
+        sqlx::query_as::<_, Decoded<Product>>(&format!(
+            "SELECT {} FROM products WHERE product_id = ?1 AND tenant_id = ?2",
+            PRODUCT_COLUMNS
+        ))
+        .bind(id.as_str())
+        .bind(self.tenant.as_str())
+        .fetch_optional(&self.pool)
+        .await
+        .map(|product| product.map(|Decoded(product)| product))
+        .map_err(|e| {
+            error!("Error fetching product by id: {:?}", e);
+            e
+        })
+    }
+
+    async fn update(&self, id: &ProductId, dto: UpdateProductDto) -> SqlxResult<Option<Product>> {
+        sqlx::query_as::<_, Decoded<Product>>(&format!(
+            r#"
+            UPDATE products SET
+                product_name = COALESCE(?2, product_name),
+                description = COALESCE(?3, description),
+                price = COALESCE(?4, price),
+                active = COALESCE(?5, active)
+            WHERE product_id = ?1 AND tenant_id = ?6
+            RETURNING {}
+            "#,
+            PRODUCT_COLUMNS
+        ))
+        .bind(id.as_str())
+        .bind(&dto.product_name)
+        .bind(&dto.description)
+        .bind(dto.price.as_ref().map(ToString::to_string))
+        .bind(dto.active)
+        .bind(self.tenant.as_str())
+        .fetch_optional(&self.pool)
+        .await
+        .map(|product| product.map(|Decoded(product)| product))
+        .map_err(|e| {
+            error!("Error updating product: {:?}", e);
+            e
+        })
+    }
+
+    #[instrument(skip(self, image), fields(product_id = %id))]
+    async fn create_image(
+        &self,
+        id: &ProductId,
+        image: NewProductImage,
+    ) -> SqlxResult<ProductImage> {
+        let result = async {
+            let mut tx = self.pool.begin().await?;
+
+            sqlx::query(
+                r#"
+                UPDATE products SET product_photos_qty = product_photos_qty + 1
+                WHERE product_id = ?1 AND tenant_id = ?2
+                "#,
+            )
+            .bind(id.as_str())
+            .bind(self.tenant.as_str())
+            .execute(&mut *tx)
+            .await?;
+
+            let image = sqlx::query_as::<_, ProductImage>(&format!(
+                r#"
+                INSERT INTO product_images (
+                    product_id, position, url, storage_key, content_type, size_bytes, tenant_id
+                )
+                VALUES (
+                    ?1,
+                    (SELECT COALESCE(MAX(position), 0) + 1 FROM product_images WHERE product_id = ?1),
+                    ?2, ?3, ?4, ?5, ?6
+                )
+                RETURNING {}
+                "#,
+                PRODUCT_IMAGE_COLUMNS
+            ))
+            .bind(id.as_str())
+            .bind(&image.url)
+            .bind(&image.storage_key)
+            .bind(&image.content_type)
+            .bind(image.size_bytes)
+            .bind(self.tenant.as_str())
+            .fetch_one(&mut *tx)
+            .await?;
+
+            tx.commit().await?;
+            Ok(image)
+        }
+        .await;
+
+        if let Err(e) = &result {
+            error!("Error creating product image: {:?}", e);
+        }
+        result
+    }
+
+    async fn find_images(&self, id: &ProductId) -> SqlxResult<Vec<ProductImage>> {
+        sqlx::query_as::<_, ProductImage>(&format!(
+            r#"
+            SELECT {}
+            FROM product_images
+            WHERE product_id = ?1 AND tenant_id = ?2
+            ORDER BY position
+            "#,
+            PRODUCT_IMAGE_COLUMNS
+        ))
+        .bind(id.as_str())
+        .bind(self.tenant.as_str())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching product images: {:?}", e);
+            e
+        })
+    }
+
+    #[instrument(skip(self), fields(product_id = %id))]
+    async fn delete_image(
+        &self,
+        id: &ProductId,
+        image_id: i64,
+    ) -> SqlxResult<Option<(ProductImage, chrono::NaiveDateTime)>> {
+        let result = async {
+            let mut tx = self.pool.begin().await?;
+
+            let Some(image) = sqlx::query_as::<_, ProductImage>(&format!(
+                r#"
+                DELETE FROM product_images
+                WHERE product_id = ?1 AND image_id = ?2 AND tenant_id = ?3
+                RETURNING {}
+                "#,
+                PRODUCT_IMAGE_COLUMNS
+            ))
+            .bind(id.as_str())
+            .bind(image_id)
+            .bind(self.tenant.as_str())
+            .fetch_optional(&mut *tx)
+            .await?
+            else {
+                return Ok(None);
+            };
+
+            sqlx::query(
+                r#"
+                UPDATE product_images SET position = position - 1
+                WHERE product_id = ?1 AND position > ?2 AND tenant_id = ?3
+                "#,
+            )
+            .bind(id.as_str())
+            .bind(image.position)
+            .bind(self.tenant.as_str())
+            .execute(&mut *tx)
+            .await?;
+
+            let deleted_at = sqlx::query_scalar::<_, chrono::NaiveDateTime>(
+                r#"
+                UPDATE products SET product_photos_qty = MAX(product_photos_qty - 1, 0)
+                WHERE product_id = ?1 AND tenant_id = ?2
+                RETURNING datetime('now')
+                "#,
+            )
+            .bind(id.as_str())
+            .bind(self.tenant.as_str())
+            .fetch_one(&mut *tx)
+            .await?;
+
+            tx.commit().await?;
+            Ok(Some((image, deleted_at)))
+        }
+        .await;
+
+        if let Err(e) = &result {
+            error!("Error deleting product image: {:?}", e);
+        }
+        result
+    }
+
+    async fn count_by_category(&self) -> SqlxResult<Vec<FilterValue>> {
+        sqlx::query_as::<_, FilterValue>(
+            r#"
+            SELECT product_category_name AS value, COUNT(*) AS count
+            FROM products
+            WHERE tenant_id = ?1
+            GROUP BY product_category_name
+            ORDER BY COUNT(*) DESC, value COLLATE NOCASE
+            "#,
+        )
+        .bind(self.tenant.as_str())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error counting products by category: {:?}", e);
+            e
+        })
+    }
+
+    async fn find_recommended(
+        &self,
+        customer_unique_id: &str,
+        state: &str,
+        limit: i64,
+    ) -> SqlxResult<Vec<RecommendedProduct>> {
+        sqlx::query_as::<_, Decoded<RecommendedProduct>>(
+            r#"
+            WITH purchased AS (
+                SELECT DISTINCT p.product_id, p.product_category_name
+                FROM customers c
+                JOIN orders o ON o.customer_id = c.customer_id
+                JOIN order_items oi ON oi.order_id = o.order_id
+                JOIN products p ON p.product_id = oi.product_id
+                WHERE c.customer_unique_id = ?1 AND c.tenant_id = ?4
+            )
+            SELECT
+                p.product_id, p.product_category_name, p.product_name_lenght,
+                p.product_description_lenght, p.product_photos_qty, p.product_weight_g,
+                p.product_length_cm, p.product_height_cm, p.product_width_cm,
+                p.product_name, p.description, p.price, p.active,
+                COUNT(DISTINCT o.order_id) AS state_order_count
+            FROM products p
+            JOIN order_items oi ON oi.product_id = p.product_id
+            JOIN orders o ON o.order_id = oi.order_id
+            JOIN customers c ON c.customer_id = o.customer_id
+            WHERE p.product_category_name IN (SELECT product_category_name FROM purchased)
+              AND p.product_id NOT IN (SELECT product_id FROM purchased)
+              AND c.customer_state = ?2
+              AND o.order_status <> 'canceled'
+              AND p.tenant_id = ?4
+            GROUP BY p.product_id
+            ORDER BY state_order_count DESC, p.product_id
+            LIMIT ?3
+            "#,
+        )
+        .bind(customer_unique_id)
+        .bind(state)
+        .bind(limit)
+        .bind(self.tenant.as_str())
+        .fetch_all(&self.pool)
+        .await
+        .map(|products| products.into_iter().map(|product| product.0).collect())
+        .map_err(|e| {
+            error!("Error fetching recommended products: {:?}", e);
+            e
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct SqliteCategoryRepository {
+    pool: SqlitePool,
+    tenant: TenantId,
+}
+
+impl SqliteCategoryRepository {
+    pub fn new(pool: SqlitePool, tenant: TenantId) -> Self {
+        Self { pool, tenant }
+    }
+}
+
+#[async_trait]
+impl CategoryRepository for SqliteCategoryRepository {
+    async fn create(&self, dto: CreateCategoryDto) -> SqlxResult<Category> {
+        sqlx::query_as::<_, Category>(
+            r#"
+            INSERT INTO product_categories (
+                product_category_name, product_category_name_english, tenant_id
+            )
+            VALUES (?1, ?2, ?3)
+            RETURNING *
+            "#,
+        )
+        .bind(&dto.product_category_name)
+        .bind(&dto.product_category_name_english)
+        .bind(self.tenant.as_str())
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error creating category: {:?}", e);
+            e
+        })
+    }
+
+    async fn find_by_name(&self, name: &str) -> SqlxResult<Option<Category>> {
+        sqlx::query_as::<_, Category>(
+            "SELECT * FROM product_categories WHERE product_category_name = ?1 AND tenant_id = ?2",
+        )
+        .bind(name)
+        .bind(self.tenant.as_str())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching category by name: {:?}", e);
+            e
+        })
+    }
+
+    #[instrument(skip(self))]
+    async fn update(&self, name: &str, dto: UpdateCategoryDto) -> SqlxResult<Option<Category>> {
+        let result = sqlx::query_as::<_, Category>(
+            r#"
+            UPDATE product_categories
+            SET product_category_name_english = ?2, updated_at = datetime('now')
+            WHERE product_category_name = ?1 AND tenant_id = ?3
+            RETURNING *
+            "#,
+        )
+        .bind(name)
+        .bind(&dto.product_category_name_english)
+        .bind(self.tenant.as_str())
+        .fetch_optional(&self.pool)
+        .await;
+
+        match &result {
+            Ok(Some(_)) => info!("Category updated successfully"),
+            Ok(None) => info!("Category not found for update"),
+            Err(e) => error!("Error updating category: {:?}", e),
+        }
+
+        result
+    }
+
+    async fn count_products(&self, name: &str) -> SqlxResult<i64> {
+        sqlx::query_scalar::<_, i64>(
+            "SELECT COUNT(*) FROM products WHERE product_category_name = ?1 AND tenant_id = ?2",
+        )
+        .bind(name)
+        .bind(self.tenant.as_str())
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error counting category products: {:?}", e);
+            e
+        })
+    }
+
+    #[instrument(skip(self))]
+    async fn delete(&self, name: &str) -> SqlxResult<Option<chrono::NaiveDateTime>> {
+        let result = sqlx::query_scalar::<_, chrono::NaiveDateTime>(
+            r#"
+            DELETE FROM product_categories WHERE product_category_name = ?1 AND tenant_id = ?2
+            RETURNING datetime('now')
+            "#,
+        )
+        .bind(name)
+        .bind(self.tenant.as_str())
+        .fetch_optional(&self.pool)
+        .await;
+
+        match &result {
+            Ok(Some(_)) => info!("Deleted category {}", name),
+            Ok(None) => info!("Category {} not found for deletion", name),
+            Err(e) => error!("Error deleting category: {:?}", e),
+        }
+
+        result
+    }
+}
+
+/// A tenant's orders purchased in a [`ReconciliationFilter`]'s range with their item and
+/// payment sums, bound as `?1`/`?2` and the tenant as `?3`; see the Postgres repository.
+const ORDER_SUMS: &str = r#"
+    SELECT
+        o.order_id, o.order_status, o.order_purchase_timestamp,
+        (SELECT COUNT(*) FROM order_items i WHERE i.order_id = o.order_id) AS item_count,
+        (SELECT COALESCE(SUM(i.price + i.freight_value), 0) FROM order_items i
+         WHERE i.order_id = o.order_id) AS items_total,
+        (SELECT COUNT(*) FROM payments p WHERE p.order_id = o.order_id) AS payment_count,
+        (SELECT COALESCE(SUM(p.payment_value), 0) FROM payments p
+         WHERE p.order_id = o.order_id) AS payments_total
+    FROM orders o
+    WHERE (?1 IS NULL OR o.order_purchase_timestamp >= ?1)
+      AND (?2 IS NULL OR o.order_purchase_timestamp < ?2)
+      AND o.tenant_id = ?3
+"#;
+
+/// Sums further apart than the tolerance bound as `?4`, compared in centavos since the sums
+/// are floats here.
+const PAYMENT_MISMATCH: &str =
+    "ABS(ROUND(payments_total * 100) - ROUND(items_total * 100)) > ROUND(?4 * 100)";
+
+/// `WHERE` clause for [`AuditFilter`] within a tenant, bound as `?1`/`?2` and the tenant as
+/// `?3`.
+const AUDIT_FILTER: &str = r#"
+    (?1 IS NULL OR entity_type = ?1)
+    AND (?2 IS NULL OR entity_id = ?2)
+    AND tenant_id = ?3
+"#;
+
+#[derive(Clone)]
+pub struct SqliteAuditRepository {
+    pool: SqlitePool,
+    tenant: TenantId,
+}
+
+impl SqliteAuditRepository {
+    pub fn new(pool: SqlitePool, tenant: TenantId) -> Self {
+        Self { pool, tenant }
+    }
+}
+
+#[async_trait]
+impl AuditRepository for SqliteAuditRepository {
+    async fn record(&self, entry: NewAuditEntry) -> SqlxResult<AuditEntry> {
+        sqlx::query_as::<_, AuditEntry>(
+            r#"
+            INSERT INTO audit_log (entity_type, entity_id, action, actor, diff, tenant_id)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            RETURNING
+                audit_id, entity_type, entity_id, action,
+                actor, diff, created_at
+            "#,
+        )
+        .bind(entry.entity_type)
+        .bind(&entry.entity_id)
+        .bind(entry.action.as_str())
+        .bind(&entry.actor)
+        .bind(&entry.diff)
+        .bind(self.tenant.as_str())
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error recording audit entry: {:?}", e);
+            e
+        })
+    }
+
+    async fn record_many(&self, entries: Vec<NewAuditEntry>) -> SqlxResult<()> {
+        let mut tx = self.pool.begin().await?;
+        for entry in &entries {
+            sqlx::query(
+                r#"
+                INSERT INTO audit_log (entity_type, entity_id, action, actor, diff, tenant_id)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+                "#,
+            )
+            .bind(entry.entity_type)
+            .bind(&entry.entity_id)
+            .bind(entry.action.as_str())
+            .bind(&entry.actor)
+            .bind(&entry.diff)
+            .bind(self.tenant.as_str())
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| {
+                error!("Error recording audit entries: {:?}", e);
+                e
+            })?;
+        }
+        tx.commit().await
+    }
+
+    async fn find_all(
+        &self,
+        filter: &AuditFilter,
+        pagination: &PaginationParams,
+    ) -> SqlxResult<(Vec<AuditEntry>, i64)> {
+        let (limit, offset, _, _) = pagination.normalize();
+
+        let rows = sqlx::query_as::<_, Counted<AuditEntry>>(&format!(
+            r#"
+            SELECT
+                audit_id, entity_type, entity_id, action,
+                actor, diff, created_at,
+                COUNT(*) OVER () AS total_count
+            FROM audit_log
+            WHERE {}
+            ORDER BY created_at DESC, audit_id DESC
+            LIMIT ?4 OFFSET ?5
+            "#,
+            AUDIT_FILTER
+        ))
+        .bind(&filter.entity_type)
+        .bind(&filter.entity_id)
+        .bind(self.tenant.as_str())
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching audit entries: {:?}", e);
+            e
+        })?;
+
+        let (entries, total_count) = match split_counted(rows, offset) {
+            (entries, Some(total_count)) => (entries, total_count),
+            // Past the last page no row carries the window total.
+            (entries, None) => {
+                let count = sqlx::query_scalar::<_, i64>(&format!(
+                    "SELECT COUNT(*) FROM audit_log WHERE {}",
+                    AUDIT_FILTER
+                ))
+                .bind(&filter.entity_type)
+                .bind(&filter.entity_id)
+                .bind(self.tenant.as_str())
+                .fetch_one(&self.pool)
+                .await
+                .map_err(|e| {
+                    error!("Error counting audit entries: {:?}", e);
+                    e
+                })?;
+                (entries, count)
+            }
+        };
+
+        Ok((entries, total_count))
+    }
+}
+
+/// Embeddings stored as JSON arrays. Without a vector index, similarity search compares the
+/// target against every stored embedding in memory, which is fine at demo scale.
+#[derive(Clone)]
+pub struct SqliteEmbeddingRepository {
+    pool: SqlitePool,
+    tenant: TenantId,
+}
+
+impl SqliteEmbeddingRepository {
+    pub fn new(pool: SqlitePool, tenant: TenantId) -> Self {
+        Self { pool, tenant }
+    }
+}
+
+/// Cosine distance, matching pgvector's `<=>` operator.
+pub(crate) fn cosine_distance(a: &[f32], b: &[f32]) -> f64 {
+    let (mut dot, mut norm_a, mut norm_b) = (0.0f64, 0.0f64, 0.0f64);
+    for (x, y) in a.iter().zip(b) {
+        dot += f64::from(*x) * f64::from(*y);
+        norm_a += f64::from(*x) * f64::from(*x);
+        norm_b += f64::from(*y) * f64::from(*y);
+    }
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 1.0;
+    }
+    1.0 - dot / (norm_a.sqrt() * norm_b.sqrt())
+}
+
+#[async_trait]
+impl EmbeddingRepository for SqliteEmbeddingRepository {
+    async fn find_embedding_source(&self, product_id: &ProductId) -> SqlxResult<Option<String>> {
+        sqlx::query_scalar::<_, String>(
+            r#"
+            SELECT
+                p.product_category_name || ' ' || COALESCE(group_concat(
+                    NULLIF(TRIM(
+                        COALESCE(r.review_comment_title, '') || ' '
+                            || COALESCE(r.review_comment_message, '')
+                    ), ''),
+                    ' '
+                ), '')
+            FROM products p
+            LEFT JOIN order_items oi ON oi.product_id = p.product_id
+            LEFT JOIN reviews r ON r.order_id = oi.order_id
+            WHERE p.product_id = ?1 AND p.tenant_id = ?2
+            GROUP BY p.product_id, p.product_category_name
+            "#,
+        )
+        .bind(product_id.as_str())
+        .bind(self.tenant.as_str())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching embedding source: {:?}", e);
+            e
+        })
+    }
+
+    async fn has_embedding(&self, product_id: &ProductId) -> SqlxResult<bool> {
+        sqlx::query_scalar::<_, bool>(
+            r#"
+            SELECT EXISTS(
+                SELECT 1 FROM product_embeddings WHERE product_id = ?1 AND tenant_id = ?2
+            )
+            "#,
+        )
+        .bind(product_id.as_str())
+        .bind(self.tenant.as_str())
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error checking product embedding: {:?}", e);
+            e
+        })
+    }
+
+    async fn upsert(&self, product_id: &ProductId, embedding: &[f32]) -> SqlxResult<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO product_embeddings (product_id, embedding, updated_at, tenant_id)
+            VALUES (?1, ?2, datetime('now'), ?3)
+            ON CONFLICT (product_id)
+            DO UPDATE SET embedding = excluded.embedding, updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(product_id.as_str())
+        .bind(Json(embedding))
+        .bind(self.tenant.as_str())
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error storing product embedding: {:?}", e);
+            e
+        })?;
+
+        Ok(())
+    }
+
+    async fn find_products_without_embedding(&self, limit: i64) -> SqlxResult<Vec<ProductId>> {
+        sqlx::query_scalar::<_, ProductId>(
+            r#"
+            SELECT p.product_id
+            FROM products p
+            LEFT JOIN product_embeddings pe ON pe.product_id = p.product_id
+            WHERE pe.product_id IS NULL AND p.tenant_id = ?2
+            LIMIT ?1
+            "#,
+        )
+        .bind(limit)
+        .bind(self.tenant.as_str())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching products without embedding: {:?}", e);
+            e
+        })
+    }
+
+    async fn find_similar(
+        &self,
+        product_id: &ProductId,
+        limit: i64,
+    ) -> SqlxResult<Vec<SimilarProduct>> {
+        let result = async {
+            let Some(Json(target)) = sqlx::query_scalar::<_, Json<Vec<f32>>>(
+                "SELECT embedding FROM product_embeddings WHERE product_id = ?1 AND tenant_id = ?2",
+            )
+            .bind(product_id.as_str())
+            .bind(self.tenant.as_str())
+            .fetch_optional(&self.pool)
+            .await?
+            else {
+                return Ok(Vec::new());
+            };
+
+            let candidates = sqlx::query(&format!(
+                r#"
+                SELECT {}, pe.embedding
+                FROM product_embeddings pe
+                JOIN products p ON p.product_id = pe.product_id
+                WHERE pe.product_id <> ?1 AND pe.tenant_id = ?2
+                "#,
+                PRODUCT_COLUMNS
+                    .split(',')
+                    .map(|column| format!("p.{}", column.trim()))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ))
+            .bind(product_id.as_str())
+            .bind(self.tenant.as_str())
+            .try_map(|row: SqliteRow| {
+                let Json(embedding): Json<Vec<f32>> = row.try_get("embedding")?;
+                Ok(SimilarProduct {
+                    product: Decoded::<Product>::from_row(&row)?.0,
+                    distance: cosine_distance(&target, &embedding),
+                })
+            })
+            .fetch_all(&self.pool)
+            .await?;
+
+            let mut similar = candidates;
+            similar.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+            similar.truncate(limit.max(0) as usize);
+            Ok(similar)
+        }
+        .await;
+
+        if let Err(e) = &result {
+            error!("Error fetching similar products: {:?}", e);
+        }
+
+        result
+    }
+}
+
+#[derive(Clone)]
+pub struct SqliteLateOrderRepository {
+    pool: SqlitePool,
+    tenant: TenantId,
+}
+
+impl SqliteLateOrderRepository {
+    pub fn new(pool: SqlitePool, tenant: TenantId) -> Self {
+        Self { pool, tenant }
+    }
+}
+
+/// Orders past their estimated delivery date that haven't arrived, as `o`.
+const LATE_ORDER: &str = r#"
+    o.order_estimated_delivery_date < datetime('now')
+    AND o.order_delivered_customer_date IS NULL
+    AND o.order_status NOT IN ('delivered', 'canceled', 'unavailable')
+"#;
+
+#[async_trait]
+impl LateOrderRepository for SqliteLateOrderRepository {
+    async fn flag_late_orders(&self, max_age_days: i64) -> SqlxResult<u64> {
+        let result = async {
+            let mut tx = self.pool.begin().await?;
+
+            let flagged = sqlx::query_scalar::<_, String>(&format!(
+                r#"
+                INSERT INTO late_delivery_alerts (order_id, order_estimated_delivery_date, tenant_id)
+                SELECT o.order_id, o.order_estimated_delivery_date, o.tenant_id
+                FROM orders o
+                WHERE {LATE_ORDER}
+                  AND o.order_estimated_delivery_date >= datetime('now', '-' || ?1 || ' days')
+                  AND o.tenant_id = ?2
+                ON CONFLICT (order_id) DO NOTHING
+                RETURNING order_id
+                "#
+            ))
+            .bind(max_age_days)
+            .bind(self.tenant.as_str())
+            .fetch_all(&mut *tx)
+            .await?;
+
+            sqlx::query(
+                r#"
+                INSERT INTO outbox_events (
+                    aggregate_type, aggregate_id, event_type, payload, tenant_id
+                )
+                SELECT 'order', o.order_id, 'order.late', json_object(
+                    'order_id', o.order_id,
+                    'customer_id', o.customer_id,
+                    'order_status', o.order_status,
+                    'order_delivered_carrier_date',
+                        replace(o.order_delivered_carrier_date, ' ', 'T'),
+                    'order_estimated_delivery_date',
+                        replace(o.order_estimated_delivery_date, ' ', 'T')
+                ), o.tenant_id
+                FROM orders o
+                WHERE o.order_id IN (SELECT value FROM json_each(?1))
+                ORDER BY o.order_estimated_delivery_date, o.order_id
+                "#,
+            )
+            .bind(Json(&flagged))
+            .execute(&mut *tx)
+            .await?;
+
+            tx.commit().await?;
+            Ok(flagged.len() as u64)
+        }
+        .await;
+
+        if let Err(e) = &result {
+            error!("Error flagging late orders: {:?}", e);
+        }
+        result
+    }
+
+    async fn find_late_orders(
+        &self,
+        pagination: &PaginationParams,
+    ) -> SqlxResult<(Vec<LateOrder>, i64)> {
+        let (limit, offset, _, _) = pagination.normalize();
+
+        let rows = sqlx::query_as::<_, Counted<LateOrder>>(&format!(
+            r#"
+            SELECT
+                o.order_id, o.customer_id, o.order_status, o.order_purchase_timestamp,
+                o.order_approved_at, o.order_delivered_carrier_date,
+                o.order_delivered_customer_date, o.order_estimated_delivery_date,
+                a.flagged_at,
+                CAST(julianday('now') - julianday(o.order_estimated_delivery_date) AS INTEGER)
+                    AS days_late,
+                COUNT(*) OVER () AS total_count
+            FROM late_delivery_alerts a
+            JOIN orders o ON o.order_id = a.order_id
+            WHERE {LATE_ORDER} AND a.tenant_id = ?3
+            ORDER BY o.order_estimated_delivery_date, o.order_id
+            LIMIT ?1 OFFSET ?2
+            "#
+        ))
+        .bind(limit)
+        .bind(offset)
+        .bind(self.tenant.as_str())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching late orders: {:?}", e);
+            e
+        })?;
+
+        match split_counted(rows, offset) {
+            (orders, Some(total_count)) => Ok((orders, total_count)),
+            (orders, None) => {
+                let count = sqlx::query_scalar::<_, i64>(&format!(
+                    r#"
+                    SELECT COUNT(*)
+                    FROM late_delivery_alerts a
+                    JOIN orders o ON o.order_id = a.order_id
+                    WHERE {LATE_ORDER} AND a.tenant_id = ?1
+                    "#
+                ))
+                .bind(self.tenant.as_str())
+                .fetch_one(&self.pool)
+                .await
+                .map_err(|e| {
+                    error!("Error counting late orders: {:?}", e);
+                    e
+                })?;
+                Ok((orders, count))
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct SqliteReviewSentimentRepository {
+    pool: SqlitePool,
+    tenant: TenantId,
+}
+
+impl SqliteReviewSentimentRepository {
+    pub fn new(pool: SqlitePool, tenant: TenantId) -> Self {
+        Self { pool, tenant }
+    }
+}
+
+#[async_trait]
+impl ReviewSentimentRepository for SqliteReviewSentimentRepository {
+    async fn find_untagged(&self, limit: i64) -> SqlxResult<Vec<ReviewText>> {
+        sqlx::query_as::<_, ReviewText>(
+            r#"
+            SELECT r.review_id, r.review_score, r.review_comment_message
+            FROM reviews r
+            LEFT JOIN review_sentiments rs ON rs.review_id = r.review_id
+            WHERE rs.review_id IS NULL AND NULLIF(TRIM(r.review_comment_message), '') IS NOT NULL
+              AND r.tenant_id = ?2
+            ORDER BY r.review_creation_date, r.review_id
+            LIMIT ?1
+            "#,
+        )
+        .bind(limit)
+        .bind(self.tenant.as_str())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching untagged reviews: {:?}", e);
+            e
+        })
+    }
+
+    async fn save(&self, sentiments: &[(String, Sentiment)]) -> SqlxResult<u64> {
+        // Without the WHERE, SQLite would read ON CONFLICT as the join's constraint
+        sqlx::query(
+            r#"
+            INSERT INTO review_sentiments (review_id, sentiment, tagged_at, tenant_id)
+            SELECT
+                json_extract(t.value, '$[0]'), json_extract(t.value, '$[1]'), datetime('now'),
+                r.tenant_id
+            FROM json_each(?1) t
+            JOIN reviews r ON r.review_id = json_extract(t.value, '$[0]') AND r.tenant_id = ?2
+            WHERE true
+            ON CONFLICT (review_id) DO UPDATE
+            SET sentiment = excluded.sentiment, tagged_at = excluded.tagged_at
+            "#,
+        )
+        .bind(Json(sentiments))
+        .bind(self.tenant.as_str())
+        .execute(&self.pool)
+        .await
+        .map(|result| result.rows_affected())
+        .map_err(|e| {
+            error!("Error saving review sentiments: {:?}", e);
+            e
+        })
+    }
+
+    async fn find_reviews(
+        &self,
+        sentiment: Option<Sentiment>,
+        pagination: &PaginationParams,
+    ) -> SqlxResult<(Vec<SentimentReview>, i64)> {
+        let (limit, offset, _, _) = pagination.normalize();
+        let sentiment = sentiment.map(|sentiment| sentiment.as_str());
+
+        let rows = sqlx::query_as::<_, Counted<SentimentReview>>(
+            r#"
+            SELECT
+                r.review_id, r.order_id, r.review_score, r.review_comment_title,
+                r.review_comment_message, r.review_creation_date, r.review_answer_timestamp,
+                rs.sentiment, rs.tagged_at AS sentiment_tagged_at,
+                COUNT(*) OVER () AS total_count
+            FROM reviews r
+            LEFT JOIN review_sentiments rs ON rs.review_id = r.review_id
+            WHERE (?1 IS NULL OR rs.sentiment = ?1) AND r.tenant_id = ?4
+            ORDER BY r.review_creation_date DESC, r.review_id DESC
+            LIMIT ?2 OFFSET ?3
+            "#,
+        )
+        .bind(sentiment)
+        .bind(limit)
+        .bind(offset)
+        .bind(self.tenant.as_str())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching reviews: {:?}", e);
+            e
+        })?;
+
+        match split_counted(rows, offset) {
+            (reviews, Some(total_count)) => Ok((reviews, total_count)),
+            (reviews, None) => {
+                let count = sqlx::query_scalar::<_, i64>(
+                    r#"
+                    SELECT COUNT(*)
+                    FROM reviews r
+                    LEFT JOIN review_sentiments rs ON rs.review_id = r.review_id
+                    WHERE (?1 IS NULL OR rs.sentiment = ?1) AND r.tenant_id = ?2
+                    "#,
+                )
+                .bind(sentiment)
+                .bind(self.tenant.as_str())
+                .fetch_one(&self.pool)
+                .await
+                .map_err(|e| {
+                    error!("Error counting reviews: {:?}", e);
+                    e
+                })?;
+                Ok((reviews, count))
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct SqliteInventoryRepository {
+    pool: SqlitePool,
+    tenant: TenantId,
+}
+
+impl SqliteInventoryRepository {
+    pub fn new(pool: SqlitePool, tenant: TenantId) -> Self {
+        Self { pool, tenant }
+    }
+}
+
+#[async_trait]
+impl InventoryRepository for SqliteInventoryRepository {
+    async fn create_location(
+        &self,
+        seller_id: &SellerId,
+        dto: CreateStockLocationDto,
+    ) -> SqlxResult<StockLocation> {
+        sqlx::query_as::<_, StockLocation>(
+            r#"
+            INSERT INTO stock_locations (seller_id, name, zip_code_prefix, tenant_id)
+            VALUES (?1, ?2, ?3, ?4)
+            RETURNING location_id, seller_id, name, zip_code_prefix, created_at
+            "#,
+        )
+        .bind(seller_id.as_str())
+        .bind(&dto.name)
+        .bind(&dto.zip_code_prefix)
+        .bind(self.tenant.as_str())
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error creating stock location: {:?}", e);
+            e
+        })
+    }
+
+    async fn find_location_by_id(&self, location_id: i64) -> SqlxResult<Option<StockLocation>> {
+        sqlx::query_as::<_, StockLocation>(
+            r#"
+            SELECT location_id, seller_id, name, zip_code_prefix, created_at
+            FROM stock_locations WHERE location_id = ?1 AND tenant_id = ?2
+            "#,
+        )
+        .bind(location_id)
+        .bind(self.tenant.as_str())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching stock location by id: {:?}", e);
+            e
+        })
+    }
+
+    async fn find_locations_by_seller(
+        &self,
+        seller_id: &SellerId,
+    ) -> SqlxResult<Vec<StockLocation>> {
+        sqlx::query_as::<_, StockLocation>(
+            r#"
+            SELECT location_id, seller_id, name, zip_code_prefix, created_at
+            FROM stock_locations
+            WHERE seller_id = ?1 AND tenant_id = ?2
+            ORDER BY location_id
+            "#,
+        )
+        .bind(seller_id.as_str())
+        .bind(self.tenant.as_str())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching stock locations for seller: {:?}", e);
+            e
+        })
+    }
+
+    async fn find_stock_by_location(&self, location_id: i64) -> SqlxResult<Vec<LocationStock>> {
+        sqlx::query_as::<_, LocationStock>(
+            r#"
+            SELECT location_id, product_id, quantity, updated_at
+            FROM location_stock
+            WHERE location_id = ?1 AND tenant_id = ?2
+            ORDER BY product_id
+            "#,
+        )
+        .bind(location_id)
+        .bind(self.tenant.as_str())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching stock for location: {:?}", e);
+            e
+        })
+    }
+
+    async fn find_stock_by_product(
+        &self,
+        product_id: &ProductId,
+    ) -> SqlxResult<Vec<ProductLocationStock>> {
+        sqlx::query_as::<_, ProductLocationStock>(
+            r#"
+            SELECT l.location_id, l.seller_id, l.name, l.zip_code_prefix, s.quantity, s.updated_at
+            FROM location_stock s
+            JOIN stock_locations l ON l.location_id = s.location_id
+            WHERE s.product_id = ?1 AND s.tenant_id = ?2
+            ORDER BY l.seller_id, l.location_id
+            "#,
+        )
+        .bind(product_id.as_str())
+        .bind(self.tenant.as_str())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching stock for product: {:?}", e);
+            e
+        })
+    }
+
+    async fn set_stock(
+        &self,
+        location_id: i64,
+        product_id: &ProductId,
+        quantity: i32,
+    ) -> SqlxResult<LocationStock> {
+        sqlx::query_as::<_, LocationStock>(
+            r#"
+            INSERT INTO location_stock (location_id, product_id, quantity, tenant_id)
+            VALUES (?1, ?2, ?3, ?4)
+            ON CONFLICT (location_id, product_id)
+            DO UPDATE SET quantity = excluded.quantity, updated_at = datetime('now')
+            RETURNING location_id, product_id, quantity, updated_at
+            "#,
+        )
+        .bind(location_id)
+        .bind(product_id.as_str())
+        .bind(quantity)
+        .bind(self.tenant.as_str())
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error setting stock: {:?}", e);
+            e
+        })
+    }
+
+    async fn adjust_stock(
+        &self,
+        location_id: i64,
+        product_id: &ProductId,
+        delta: i32,
+    ) -> SqlxResult<Option<LocationStock>> {
+        sqlx::query_as::<_, LocationStock>(
+            r#"
+            UPDATE location_stock
+            SET quantity = quantity + ?3, updated_at = datetime('now')
+            WHERE location_id = ?1 AND product_id = ?2 AND tenant_id = ?4
+            RETURNING location_id, product_id, quantity, updated_at
+            "#,
+        )
+        .bind(location_id)
+        .bind(product_id.as_str())
+        .bind(delta)
+        .bind(self.tenant.as_str())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error adjusting stock: {:?}", e);
+            e
+        })
+    }
+
+    async fn is_tracked(&self, seller_id: &SellerId, product_id: &ProductId) -> SqlxResult<bool> {
+        sqlx::query_scalar::<_, bool>(
+            r#"
+            SELECT EXISTS(
+                SELECT 1
+                FROM location_stock ls
+                JOIN stock_locations l ON l.location_id = ls.location_id
+                WHERE l.seller_id = ?1 AND ls.product_id = ?2 AND ls.tenant_id = ?3
+            )
+            "#,
+        )
+        .bind(seller_id.as_str())
+        .bind(product_id.as_str())
+        .bind(self.tenant.as_str())
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error checking stock tracking: {:?}", e);
+            e
+        })
+    }
+
+    /// Decrements stock at the seller location whose CEP prefix is numerically closest to the
+    /// destination (CEPs are assigned by region, so nearby prefixes are nearby places).
+    async fn allocate(
+        &self,
+        seller_id: &SellerId,
+        product_id: &ProductId,
+        quantity: i32,
+        destination_zip_code_prefix: &str,
+    ) -> SqlxResult<Option<StockAllocation>> {
+        sqlx::query_as::<_, StockAllocation>(
+            r#"
+            UPDATE location_stock
+            SET quantity = quantity - ?3, updated_at = datetime('now')
+            WHERE product_id = ?2 AND location_id = (
+                SELECT ls.location_id
+                FROM location_stock ls
+                JOIN stock_locations l ON l.location_id = ls.location_id
+                WHERE l.seller_id = ?1
+                  AND ls.product_id = ?2
+                  AND ls.quantity >= ?3
+                  AND ls.tenant_id = ?5
+                ORDER BY
+                    abs(CAST(l.zip_code_prefix AS INTEGER) - CAST(?4 AS INTEGER)),
+                    l.location_id
+                LIMIT 1
+            )
+            RETURNING location_id, product_id, quantity
+            "#,
+        )
+        .bind(seller_id.as_str())
+        .bind(product_id.as_str())
+        .bind(quantity)
+        .bind(destination_zip_code_prefix)
+        .bind(self.tenant.as_str())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error allocating stock: {:?}", e);
+            e
+        })
+    }
+
+    /// Returns stock to the seller location tracking the product whose CEP prefix is
+    /// closest to where the goods come back from.
+    async fn restock(
+        &self,
+        seller_id: &SellerId,
+        product_id: &ProductId,
+        quantity: i32,
+        origin_zip_code_prefix: &str,
+    ) -> SqlxResult<Option<StockAllocation>> {
+        sqlx::query_as::<_, StockAllocation>(
+            r#"
+            UPDATE location_stock
+            SET quantity = quantity + ?3, updated_at = datetime('now')
+            WHERE product_id = ?2 AND location_id = (
+                SELECT ls.location_id
+                FROM location_stock ls
+                JOIN stock_locations l ON l.location_id = ls.location_id
+                WHERE l.seller_id = ?1 AND ls.product_id = ?2 AND ls.tenant_id = ?5
+                ORDER BY
+                    abs(CAST(l.zip_code_prefix AS INTEGER) - CAST(?4 AS INTEGER)),
+                    l.location_id
+                LIMIT 1
+            )
+            RETURNING location_id, product_id, quantity
+            "#,
+        )
+        .bind(seller_id.as_str())
+        .bind(product_id.as_str())
+        .bind(quantity)
+        .bind(origin_zip_code_prefix)
+        .bind(self.tenant.as_str())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error restocking: {:?}", e);
+            e
+        })
+    }
+}
+
+/// Columns selected for `SupportCase`, including the computed SLA breach flags.
+const SUPPORT_CASE_COLUMNS: &str = r#"
+    case_id, order_id, customer_id, category, status, subject,
+    created_at, updated_at, first_response_due_at, resolution_due_at,
+    first_responded_at, resolved_at,
+    COALESCE(first_responded_at, datetime('now')) > first_response_due_at
+        AS first_response_breached,
+    COALESCE(resolved_at, datetime('now')) > resolution_due_at AS resolution_breached
+"#;
+
+/// `WHERE` clause for [`SupportCaseFilter`] within a tenant, bound as `?1`..`?3` and the
+/// tenant as `?4`.
+const SUPPORT_CASE_FILTER: &str = r#"
+    (?1 IS NULL OR status = ?1)
+    AND (?2 IS NULL OR category = ?2)
+    AND (?3 IS NULL OR order_id = ?3)
+    AND tenant_id = ?4
+"#;
+
+#[derive(Clone)]
+pub struct SqliteSupportRepository {
+    pool: SqlitePool,
+    tenant: TenantId,
+}
+
+impl SqliteSupportRepository {
+    pub fn new(pool: SqlitePool, tenant: TenantId) -> Self {
+        Self { pool, tenant }
+    }
+}
+
+#[async_trait]
+impl SupportRepository for SqliteSupportRepository {
+    #[instrument(skip(self, dto))]
+    async fn create(
+        &self,
+        dto: CreateSupportCaseDto,
+        author: &str,
+        first_response_hours: i64,
+        resolution_hours: i64,
+    ) -> SqlxResult<Option<SupportCase>> {
+        let result = async {
+            let mut tx = self.pool.begin().await?;
+
+            let case = sqlx::query_as::<_, SupportCase>(&format!(
+                r#"
+                INSERT INTO support_cases (
+                    order_id, customer_id, category, subject,
+                    first_response_due_at, resolution_due_at, tenant_id
+                )
+                SELECT
+                    order_id, customer_id, ?2, ?3,
+                    datetime('now', ?4 || ' hours'),
+                    datetime('now', ?5 || ' hours'),
+                    tenant_id
+                FROM orders
+                WHERE order_id = ?1 AND tenant_id = ?6
+                RETURNING {}
+                "#,
+                SUPPORT_CASE_COLUMNS
+            ))
+            .bind(dto.order_id.as_str())
+            .bind(dto.category.as_str())
+            .bind(&dto.subject)
+            .bind(format!("{:+}", first_response_hours))
+            .bind(format!("{:+}", resolution_hours))
+            .bind(self.tenant.as_str())
+            .fetch_optional(&mut *tx)
+            .await?;
+
+            let Some(case) = case else {
+                return Ok(None);
+            };
+
+            sqlx::query(
+                r#"
+                INSERT INTO support_case_messages (case_id, author_type, author, body, tenant_id)
+                VALUES (?1, 'customer', ?2, ?3, ?4)
+                "#,
+            )
+            .bind(case.case_id)
+            .bind(author)
+            .bind(&dto.message)
+            .bind(self.tenant.as_str())
+            .execute(&mut *tx)
+            .await?;
+
+            tx.commit().await?;
+            Ok(Some(case))
+        }
+        .await;
+
+        match &result {
+            Ok(Some(_)) => info!("Support case created successfully"),
+            Ok(None) => info!("Order not found for support case"),
+            Err(e) => error!("Error creating support case: {:?}", e),
+        }
+
+        result
+    }
+
+    async fn find_all(
+        &self,
+        filter: &SupportCaseFilter,
+        pagination: &PaginationParams,
+    ) -> SqlxResult<(Vec<SupportCase>, i64)> {
+        let (limit, offset, _, _) = pagination.normalize();
+
+        let rows = sqlx::query_as::<_, Counted<SupportCase>>(&format!(
+            r#"
+            SELECT {}, COUNT(*) OVER () AS total_count
+            FROM support_cases
+            WHERE {}
+            ORDER BY created_at DESC, case_id DESC
+            LIMIT ?5 OFFSET ?6
+            "#,
+            SUPPORT_CASE_COLUMNS, SUPPORT_CASE_FILTER
+        ))
+        .bind(&filter.status)
+        .bind(&filter.category)
+        .bind(&filter.order_id)
+        .bind(self.tenant.as_str())
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching support cases: {:?}", e);
+            e
+        })?;
+
+        let (cases, total_count) = match split_counted(rows, offset) {
+            (cases, Some(total_count)) => (cases, total_count),
+            // Past the last page no row carries the window total.
+            (cases, None) => {
+                let count = sqlx::query_scalar::<_, i64>(&format!(
+                    "SELECT COUNT(*) FROM support_cases WHERE {}",
+                    SUPPORT_CASE_FILTER
+                ))
+                .bind(&filter.status)
+                .bind(&filter.category)
+                .bind(&filter.order_id)
+                .bind(self.tenant.as_str())
+                .fetch_one(&self.pool)
+                .await
+                .map_err(|e| {
+                    error!("Error counting support cases: {:?}", e);
+                    e
+                })?;
+                (cases, count)
+            }
+        };
+
+        Ok((cases, total_count))
+    }
+
+    async fn find_by_id(&self, case_id: i64) -> SqlxResult<Option<SupportCase>> {
+        sqlx::query_as::<_, SupportCase>(&format!(
+            "SELECT {} FROM support_cases WHERE case_id = ?1 AND tenant_id = ?2",
+            SUPPORT_CASE_COLUMNS
+        ))
+        .bind(case_id)
+        .bind(self.tenant.as_str())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching support case by id: {:?}", e);
+            e
+        })
+    }
+
+    async fn find_messages(&self, case_id: i64) -> SqlxResult<Vec<SupportMessage>> {
+        sqlx::query_as::<_, SupportMessage>(
+            r#"
+            SELECT message_id, case_id, author_type, author, body, created_at
+            FROM support_case_messages
+            WHERE case_id = ?1 AND tenant_id = ?2
+            ORDER BY created_at, message_id
+            "#,
+        )
+        .bind(case_id)
+        .bind(self.tenant.as_str())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching support case messages: {:?}", e);
+            e
+        })
+    }
+
+    #[instrument(skip(self))]
+    async fn update(
+        &self,
+        case_id: i64,
+        dto: UpdateSupportCaseDto,
+    ) -> SqlxResult<Option<SupportCase>> {
+        let result = sqlx::query_as::<_, SupportCase>(&format!(
+            r#"
+            UPDATE support_cases
+            SET
+                category = COALESCE(?2, category),
+                status = COALESCE(?3, status),
+                resolved_at = CASE
+                    WHEN ?3 IS NULL THEN resolved_at
+                    WHEN ?3 IN ('resolved', 'closed') THEN COALESCE(resolved_at, datetime('now'))
+                    ELSE NULL
+                END,
+                updated_at = datetime('now')
+            WHERE case_id = ?1 AND tenant_id = ?4
+            RETURNING {}
+            "#,
+            SUPPORT_CASE_COLUMNS
+        ))
+        .bind(case_id)
+        .bind(dto.category.map(|category| category.as_str()))
+        .bind(dto.status.map(|status| status.as_str()))
+        .bind(self.tenant.as_str())
+        .fetch_optional(&self.pool)
+        .await;
+
+        match &result {
+            Ok(Some(_)) => info!("Support case updated successfully"),
+            Ok(None) => info!("Support case not found for update"),
+            Err(e) => error!("Error updating support case: {:?}", e),
+        }
+
+        result
+    }
+
+    #[instrument(skip(self))]
+    async fn delete(&self, case_id: i64) -> SqlxResult<Option<chrono::NaiveDateTime>> {
+        let result = sqlx::query_scalar::<_, chrono::NaiveDateTime>(
+            r#"
+            DELETE FROM support_cases WHERE case_id = ?1 AND tenant_id = ?2
+            RETURNING datetime('now')
+            "#,
+        )
+        .bind(case_id)
+        .bind(self.tenant.as_str())
+        .fetch_optional(&self.pool)
+        .await;
+
+        match &result {
+            Ok(Some(_)) => info!("Deleted support case {}", case_id),
+            Ok(None) => info!("Support case {} not found for deletion", case_id),
+            Err(e) => error!("Error deleting support case: {:?}", e),
+        }
+
+        result
+    }
+
+    /// Stores the message and moves the case along: the first agent reply stops the
+    /// first-response timer, and a customer reply reopens a case waiting on the customer.
+    #[instrument(skip(self, dto))]
+    async fn add_message(
+        &self,
+        case_id: i64,
+        dto: CreateSupportMessageDto,
+        author: &str,
+    ) -> SqlxResult<Option<SupportMessage>> {
+        let result = async {
+            let mut tx = self.pool.begin().await?;
+
+            let updated = sqlx::query(
+                r#"
+                UPDATE support_cases
+                SET
+                    first_responded_at = CASE
+                        WHEN ?2 = 'agent' THEN COALESCE(first_responded_at, datetime('now'))
+                        ELSE first_responded_at
+                    END,
+                    status = CASE
+                        WHEN ?2 = 'customer' AND status = 'pending_customer' THEN 'open'
+                        ELSE status
+                    END,
+                    updated_at = datetime('now')
+                WHERE case_id = ?1 AND tenant_id = ?3
+                "#,
+            )
+            .bind(case_id)
+            .bind(dto.author_type.as_str())
+            .bind(self.tenant.as_str())
+            .execute(&mut *tx)
+            .await?;
+
+            if updated.rows_affected() == 0 {
+                return Ok(None);
+            }
+
+            let message = sqlx::query_as::<_, SupportMessage>(
+                r#"
+                INSERT INTO support_case_messages (case_id, author_type, author, body, tenant_id)
+                VALUES (?1, ?2, ?3, ?4, ?5)
+                RETURNING message_id, case_id, author_type, author, body, created_at
+                "#,
+            )
+            .bind(case_id)
+            .bind(dto.author_type.as_str())
+            .bind(author)
+            .bind(&dto.body)
+            .bind(self.tenant.as_str())
+            .fetch_one(&mut *tx)
+            .await?;
+
+            tx.commit().await?;
+            Ok(Some(message))
+        }
+        .await;
+
+        if let Err(e) = &result {
+            error!("Error adding support case message: {:?}", e);
+        }
+
+        result
+    }
+
+    async fn volume_by_category(&self) -> SqlxResult<Vec<SupportCaseVolume>> {
+        sqlx::query_as::<_, SupportCaseVolume>(
+            r#"
+            SELECT
+                category,
+                COUNT(*) AS total_cases,
+                COUNT(*) FILTER (WHERE status IN ('open', 'pending_customer')) AS open_cases,
+                COUNT(*) FILTER (WHERE status IN ('resolved', 'closed')) AS resolved_cases,
+                COUNT(*) FILTER (
+                    WHERE COALESCE(first_responded_at, datetime('now')) > first_response_due_at
+                       OR COALESCE(resolved_at, datetime('now')) > resolution_due_at
+                ) AS sla_breached_cases,
+                AVG((julianday(resolved_at) - julianday(created_at)) * 24)
+                    AS avg_resolution_hours
+            FROM support_cases
+            WHERE tenant_id = ?1
+            GROUP BY category
+            ORDER BY COUNT(*) DESC, category
+            "#,
+        )
+        .bind(self.tenant.as_str())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error aggregating support case volume: {:?}", e);
+            e
+        })
+    }
+}
+
+/// SQLite has no materialized views and no search indexes to rebuild, so every step is a no-op.
+#[derive(Clone)]
+pub struct SqliteMaintenanceRepository;
+
+#[async_trait]
+impl MaintenanceRepository for SqliteMaintenanceRepository {
+    async fn find_materialized_views(&self) -> SqlxResult<Vec<String>> {
+        Ok(Vec::new())
+    }
+
+    async fn refresh_materialized_view(&self, _name: &str) -> SqlxResult<()> {
+        Ok(())
+    }
+
+    async fn reindex_search_indexes(&self) -> SqlxResult<Vec<String>> {
+        Ok(Vec::new())
+    }
+
+    async fn archive_orders(
+        &self,
+        _before: chrono::NaiveDateTime,
+        _limit: i64,
+    ) -> SqlxResult<Option<ArchivedOrders>> {
+        Ok(None)
+    }
+}
+
+#[derive(Clone)]
+pub struct SqliteDiagnosticsRepository {
+    pool: SqlitePool,
+}
+
+impl SqliteDiagnosticsRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl DiagnosticsRepository for SqliteDiagnosticsRepository {
+    async fn ping(&self) -> SqlxResult<()> {
+        sqlx::query("SELECT 1")
+            .fetch_one(&self.pool)
+            .await
+            .map(|_| ())
+            .map_err(|e| {
+                error!("Diagnostics ping failed: {:?}", e);
+                e
+            })
+    }
+
+    /// A SQLite file has no replicas.
+    async fn replica_lag_seconds(&self) -> SqlxResult<Option<f64>> {
+        Ok(None)
+    }
+
+    async fn database_size_bytes(&self) -> SqlxResult<Option<i64>> {
+        sqlx::query_scalar(
+            "SELECT page_count * page_size FROM pragma_page_count(), pragma_page_size()",
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error reading the database size: {:?}", e);
+            e
+        })
+    }
+
+    async fn last_migration(&self) -> SqlxResult<Option<AppliedMigration>> {
+        sqlx::query_as(
+            r#"
+            SELECT version, description, installed_on
+            FROM _sqlx_migrations
+            WHERE success
+            ORDER BY version DESC
+            LIMIT 1
+            "#,
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error reading the applied migrations: {:?}", e);
+            e
+        })
+    }
+
+    /// SQLite keeps no row statistics, so rows are always counted; per-table sizes are not
+    /// reported.
+    #[instrument(skip(self))]
+    async fn table_stats(&self, _exact: bool) -> SqlxResult<(Vec<TableStats>, bool)> {
+        let names: Vec<String> = sqlx::query_scalar(
+            r#"
+            SELECT name FROM sqlite_master
+            WHERE type = 'table' AND name NOT LIKE 'sqlite_%'
+            ORDER BY name
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut tables = Vec::with_capacity(names.len());
+        for table_name in names {
+            // Identifiers can't be bound; `table_name` comes from sqlite_master and is quoted
+            // here.
+            let row_count = sqlx::query_scalar(&format!(
+                "SELECT COUNT(*) FROM \"{}\"",
+                table_name.replace('"', "\"\"")
+            ))
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| {
+                error!("Error counting the rows of {}: {:?}", table_name, e);
+                e
+            })?;
+            tables.push(TableStats {
+                table_name,
+                row_count,
+                size_bytes: None,
+            });
+        }
+        Ok((tables, false))
+    }
+
+    fn pool_stats(&self) -> Option<PoolStats> {
+        Some(PoolStats::new(
+            self.pool.options().get_max_connections(),
+            self.pool.size(),
+            self.pool.num_idle() as u32,
+        ))
+    }
+}
+
+#[derive(Clone)]
+pub struct SqliteDataQualityRepository {
+    pool: SqlitePool,
+    tenant: TenantId,
+}
+
+impl SqliteDataQualityRepository {
+    pub fn new(pool: SqlitePool, tenant: TenantId) -> Self {
+        Self { pool, tenant }
+    }
+
+    async fn counts(&self, query: &str) -> SqlxResult<Vec<i64>> {
+        let row = sqlx::query(query)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| {
+                error!("Error counting data quality issues: {:?}", e);
+                e
+            })?;
+        (0..row.len()).map(|index| row.try_get(index)).collect()
+    }
+}
+
+#[async_trait]
+impl DataQualityRepository for SqliteDataQualityRepository {
+    async fn product_report(&self) -> SqlxResult<DataQualityReport<ProductIssue>> {
+        let counts = self
+            .counts(&quality::product_report_query(&self.tenant))
+            .await?;
+        Ok(quality::report(&counts, &ProductIssue::ALL))
+    }
+
+    async fn find_flagged_products(
+        &self,
+        issue: Option<ProductIssue>,
+        pagination: &PaginationParams,
+    ) -> SqlxResult<(Vec<Product>, i64)> {
+        let (limit, offset, _, _) = pagination.normalize();
+        let filter = quality::product_filter(issue, &self.tenant);
+
+        let rows = sqlx::query_as::<_, Counted<Decoded<Product>>>(&format!(
+            r#"
+            SELECT {}, COUNT(*) OVER () AS total_count
+            FROM products
+            WHERE {}
+            ORDER BY product_id
+            LIMIT ?1 OFFSET ?2
+            "#,
+            PRODUCT_COLUMNS, filter
+        ))
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching flagged products: {:?}", e);
+            e
+        })?;
+
+        let (products, window_total) = split_counted(rows, offset);
+        let products = products.into_iter().map(|product| product.0).collect();
+        match window_total {
+            Some(total_count) => Ok((products, total_count)),
+            None => {
+                let count = sqlx::query_scalar::<_, i64>(&format!(
+                    "SELECT COUNT(*) FROM products WHERE {}",
+                    filter
+                ))
+                .fetch_one(&self.pool)
+                .await
+                .map_err(|e| {
+                    error!("Error counting flagged products: {:?}", e);
+                    e
+                })?;
+                Ok((products, count))
+            }
+        }
+    }
+
+    async fn order_report(&self) -> SqlxResult<DataQualityReport<OrderIssue>> {
+        let counts = self
+            .counts(&quality::order_report_query(&self.tenant))
+            .await?;
+        Ok(quality::report(&counts, &OrderIssue::ALL))
+    }
+
+    async fn find_flagged_orders(
+        &self,
+        issue: Option<OrderIssue>,
+        pagination: &PaginationParams,
+    ) -> SqlxResult<(Vec<FlaggedOrder>, i64)> {
+        let (limit, offset, _, _) = pagination.normalize();
+        let filter = quality::order_filter(issue, &self.tenant);
+
+        let rows = sqlx::query_as::<_, Counted<FlaggedOrder>>(&format!(
+            r#"
+            SELECT
+                o.order_id, o.customer_id, o.order_status, o.order_purchase_timestamp,
+                o.order_approved_at, o.order_delivered_carrier_date,
+                o.order_delivered_customer_date, o.order_estimated_delivery_date,
+                (SELECT COUNT(*) FROM order_items i WHERE i.order_id = o.order_id) AS item_count,
+                COUNT(*) OVER () AS total_count
+            FROM orders o
+            WHERE {}
+            ORDER BY o.order_purchase_timestamp, o.order_id
+            LIMIT ?1 OFFSET ?2
+            "#,
+            filter
+        ))
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching flagged orders: {:?}", e);
+            e
+        })?;
+
+        match split_counted(rows, offset) {
+            (orders, Some(total_count)) => Ok((orders, total_count)),
+            (orders, None) => {
+                let count = sqlx::query_scalar::<_, i64>(&format!(
+                    "SELECT COUNT(*) FROM orders o WHERE {}",
+                    filter
+                ))
+                .fetch_one(&self.pool)
+                .await
+                .map_err(|e| {
+                    error!("Error counting flagged orders: {:?}", e);
+                    e
+                })?;
+                Ok((orders, count))
+            }
+        }
+    }
+
+    async fn evaluate_rule(&self, rule: &DataQualityRule) -> SqlxResult<(i64, i64)> {
+        let counts = self
+            .counts(&quality::rule_query(rule, &self.tenant))
+            .await?;
+        Ok((counts[0], counts[1]))
+    }
+
+    async fn replace_rule_results(&self, results: &[DataQualityRuleResult]) -> SqlxResult<()> {
+        let result = async {
+            let mut tx = self.pool.begin().await?;
+            sqlx::query("DELETE FROM data_quality_results WHERE tenant_id = ?1")
+                .bind(self.tenant.as_str())
+                .execute(&mut *tx)
+                .await?;
+            for result in results {
+                sqlx::query(
+                    r#"
+                    INSERT INTO data_quality_results (
+                        rule_name, table_name, condition, severity, checked_count,
+                        violation_count, error, evaluated_at, tenant_id
+                    )
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+                    "#,
+                )
+                .bind(&result.rule_name)
+                .bind(&result.table_name)
+                .bind(&result.condition)
+                .bind(result.severity)
+                .bind(result.checked_count)
+                .bind(result.violation_count)
+                .bind(&result.error)
+                .bind(result.evaluated_at)
+                .bind(self.tenant.as_str())
+                .execute(&mut *tx)
+                .await?;
+            }
+            tx.commit().await
+        }
+        .await;
+
+        result.map_err(|e| {
+            error!("Error saving data quality rule results: {:?}", e);
+            e
+        })
+    }
+
+    async fn find_rule_results(&self) -> SqlxResult<Vec<DataQualityRuleResult>> {
+        sqlx::query_as::<_, DataQualityRuleResult>(
+            r#"
+            SELECT
+                rule_name, table_name, condition, severity, checked_count, violation_count,
+                error, evaluated_at
+            FROM data_quality_results
+            WHERE tenant_id = ?1
+            ORDER BY
+                CASE severity WHEN 'error' THEN 0 WHEN 'warning' THEN 1 ELSE 2 END,
+                rule_name
+            "#,
+        )
+        .bind(self.tenant.as_str())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching data quality rule results: {:?}", e);
+            e
+        })
+    }
+}
+
+/// Columns selected for `ImportBatch`.
+const IMPORT_BATCH_COLUMNS: &str = r#"
+    batch_id, dataset, source, actor, status, success_count, error_count,
+    started_at, finished_at, rolled_back_at
+"#;
+
+/// Failed rows of the batches in the JSON array `?1` belonging to tenant `?2`, by batch and then
+/// line.
+const IMPORT_ERRORS_QUERY: &str = r#"
+    SELECT
+        e.error_id, e.batch_id, b.dataset, e.row_number, e.record, e.reason, e.created_at
+    FROM import_errors e
+    JOIN import_batches b ON b.batch_id = e.batch_id
+    WHERE e.batch_id IN (SELECT value FROM json_each(?1)) AND e.tenant_id = ?2
+    ORDER BY e.batch_id, e.row_number, e.error_id
+"#;
+
+#[derive(Clone)]
+pub struct SqliteImportRepository {
+    pool: SqlitePool,
+    tenant: TenantId,
+}
+
+impl SqliteImportRepository {
+    pub fn new(pool: SqlitePool, tenant: TenantId) -> Self {
+        Self { pool, tenant }
+    }
+}
+
+#[async_trait]
+impl ImportRepository for SqliteImportRepository {
+    async fn begin_load_job(&self, datasets: &[&str], actor: &str) -> SqlxResult<i64> {
+        sqlx::query_scalar(
+            r#"
+            INSERT INTO load_jobs (datasets, actor, tenant_id)
+            VALUES (?1, ?2, ?3)
+            RETURNING job_id
+            "#,
+        )
+        .bind(datasets.join(","))
+        .bind(actor)
+        .bind(self.tenant.as_str())
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error creating load job: {:?}", e);
+            e
+        })
+    }
+
+    async fn find_load_job(&self, job_id: i64) -> SqlxResult<Option<StoredLoadJob>> {
+        let datasets: Option<String> = sqlx::query_scalar(
+            "SELECT datasets FROM load_jobs WHERE job_id = ?1 AND tenant_id = ?2",
+        )
+        .bind(job_id)
+        .bind(self.tenant.as_str())
+        .fetch_optional(&self.pool)
+        .await?;
+        let Some(datasets) = datasets else {
+            return Ok(None);
+        };
+
+        let batches = sqlx::query_as::<_, LoadJobBatch>(
+            r#"
+            SELECT batch_id, dataset, status, success_count, error_count, checkpoint_line
+            FROM import_batches
+            WHERE load_job_id = ?1
+            ORDER BY batch_id
+            "#,
+        )
+        .bind(job_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching load job {}: {:?}", job_id, e);
+            e
+        })?;
+
+        Ok(Some(StoredLoadJob {
+            job_id,
+            datasets: datasets.split(',').map(str::to_string).collect(),
+            batches,
+        }))
+    }
+
+    async fn begin_batch(
+        &self,
+        dataset: &str,
+        source: &str,
+        actor: &str,
+        load_job_id: Option<i64>,
+    ) -> SqlxResult<ImportBatch> {
+        sqlx::query_as::<_, ImportBatch>(&format!(
+            r#"
+            INSERT INTO import_batches (dataset, source, actor, load_job_id, tenant_id)
+            VALUES (?1, ?2, ?3, ?4, ?5)
+            RETURNING {}
+            "#,
+            IMPORT_BATCH_COLUMNS
+        ))
+        .bind(dataset)
+        .bind(source)
+        .bind(actor)
+        .bind(load_job_id)
+        .bind(self.tenant.as_str())
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error creating import batch: {:?}", e);
+            e
+        })
+    }
+
+    async fn record_rows(&self, batch_id: i64, entity_ids: &[String]) -> SqlxResult<()> {
+        sqlx::query(
+            r#"
+            INSERT OR IGNORE INTO import_batch_rows (batch_id, entity_id, tenant_id)
+            SELECT ?1, value, ?3 FROM json_each(?2)
+            "#,
+        )
+        .bind(batch_id)
+        .bind(Json(entity_ids))
+        .bind(self.tenant.as_str())
+        .execute(&self.pool)
+        .await
+        .map(|_| ())
+        .map_err(|e| {
+            error!("Error recording import batch rows: {:?}", e);
+            e
+        })
+    }
+
+    async fn checkpoint(&self, batch_id: i64, line: i64) -> SqlxResult<()> {
+        sqlx::query("UPDATE import_batches SET checkpoint_line = ?2 WHERE batch_id = ?1")
+            .bind(batch_id)
+            .bind(line)
+            .execute(&self.pool)
+            .await
+            .map(|_| ())
+            .map_err(|e| {
+                error!("Error checkpointing import batch {}: {:?}", batch_id, e);
+                e
+            })
+    }
+
+    async fn resume_batch(&self, batch_id: i64) -> SqlxResult<Option<BatchResume>> {
+        let result = async {
+            let mut tx = self.pool.begin().await?;
+
+            let checkpoint_line: Option<i64> = sqlx::query_scalar(
+                r#"
+                UPDATE import_batches
+                SET status = 'running', finished_at = NULL
+                WHERE batch_id = ?1 AND tenant_id = ?2 AND status IN ('running', 'failed')
+                RETURNING checkpoint_line
+                "#,
+            )
+            .bind(batch_id)
+            .bind(self.tenant.as_str())
+            .fetch_optional(&mut *tx)
+            .await?;
+            let Some(checkpoint_line) = checkpoint_line else {
+                return Ok(None);
+            };
+
+            sqlx::query("DELETE FROM import_errors WHERE batch_id = ?1 AND row_number > ?2")
+                .bind(batch_id)
+                .bind(checkpoint_line)
+                .execute(&mut *tx)
+                .await?;
+            let error_count: i64 =
+                sqlx::query_scalar("SELECT COUNT(*) FROM import_errors WHERE batch_id = ?1")
+                    .bind(batch_id)
+                    .fetch_one(&mut *tx)
+                    .await?;
+            let inserted: Vec<String> =
+                sqlx::query_scalar("SELECT entity_id FROM import_batch_rows WHERE batch_id = ?1")
+                    .bind(batch_id)
+                    .fetch_all(&mut *tx)
+                    .await?;
+
+            tx.commit().await?;
+            Ok(Some(BatchResume {
+                checkpoint_line,
+                inserted,
+                error_count,
+            }))
+        }
+        .await;
+
+        result.map_err(|e: sqlx::Error| {
+            error!("Error resuming import batch {}: {:?}", batch_id, e);
+            e
+        })
+    }
+
+    async fn finish_batch(
+        &self,
+        batch_id: i64,
+        status: ImportBatchStatus,
+        success_count: i32,
+        error_count: i32,
+    ) -> SqlxResult<ImportBatch> {
+        sqlx::query_as::<_, ImportBatch>(&format!(
+            r#"
+            UPDATE import_batches
+            SET status = ?2, success_count = ?3, error_count = ?4, finished_at = datetime('now')
+            WHERE batch_id = ?1
+            RETURNING {}
+            "#,
+            IMPORT_BATCH_COLUMNS
+        ))
+        .bind(batch_id)
+        .bind(status.as_str())
+        .bind(success_count)
+        .bind(error_count)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error finishing import batch: {:?}", e);
+            e
+        })
+    }
+
+    async fn find_all(&self, pagination: &PaginationParams) -> SqlxResult<(Vec<ImportBatch>, i64)> {
+        let (limit, offset, _, _) = pagination.normalize();
+
+        let rows = sqlx::query_as::<_, Counted<ImportBatch>>(&format!(
+            r#"
+            SELECT {}, COUNT(*) OVER () AS total_count
+            FROM import_batches
+            WHERE tenant_id = ?3
+            ORDER BY started_at DESC, batch_id DESC
+            LIMIT ?1 OFFSET ?2
+            "#,
+            IMPORT_BATCH_COLUMNS
+        ))
+        .bind(limit)
+        .bind(offset)
+        .bind(self.tenant.as_str())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching import batches: {:?}", e);
+            e
+        })?;
+
+        let (batches, total_count) = match split_counted(rows, offset) {
+            (batches, Some(total_count)) => (batches, total_count),
+            // Past the last page no row carries the window total.
+            (batches, None) => {
+                let count = sqlx::query_scalar::<_, i64>(
+                    "SELECT COUNT(*) FROM import_batches WHERE tenant_id = ?1",
+                )
+                .bind(self.tenant.as_str())
+                .fetch_one(&self.pool)
+                .await
+                .map_err(|e| {
+                    error!("Error counting import batches: {:?}", e);
+                    e
+                })?;
+                (batches, count)
+            }
+        };
+
+        Ok((batches, total_count))
+    }
+
+    async fn find_by_id(&self, batch_id: i64) -> SqlxResult<Option<ImportBatch>> {
+        sqlx::query_as::<_, ImportBatch>(&format!(
+            "SELECT {} FROM import_batches WHERE batch_id = ?1 AND tenant_id = ?2",
+            IMPORT_BATCH_COLUMNS
+        ))
+        .bind(batch_id)
+        .bind(self.tenant.as_str())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching import batch by id: {:?}", e);
+            e
+        })
+    }
+
+    async fn record_errors(&self, batch_id: i64, errors: &[NewImportRowError]) -> SqlxResult<()> {
+        let mut tx = self.pool.begin().await?;
+        for import_error in errors {
+            sqlx::query(
+                r#"
+                INSERT INTO import_errors (batch_id, row_number, record, reason, tenant_id)
+                VALUES (?1, ?2, ?3, ?4, ?5)
+                "#,
+            )
+            .bind(batch_id)
+            .bind(import_error.row_number)
+            .bind(&import_error.record)
+            .bind(&import_error.reason)
+            .bind(self.tenant.as_str())
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| {
+                error!("Error recording import errors: {:?}", e);
+                e
+            })?;
+        }
+        tx.commit().await
+    }
+
+    async fn find_errors(
+        &self,
+        batch_ids: &[i64],
+        pagination: &PaginationParams,
+    ) -> SqlxResult<(Vec<ImportRowError>, i64)> {
+        let (limit, offset, _, _) = pagination.normalize();
+
+        let errors = sqlx::query_as::<_, ImportRowError>(&format!(
+            "{} LIMIT ?3 OFFSET ?4",
+            IMPORT_ERRORS_QUERY
+        ))
+        .bind(Json(batch_ids))
+        .bind(self.tenant.as_str())
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching import errors: {:?}", e);
+            e
+        })?;
+
+        let total_count = sqlx::query_scalar::<_, i64>(
+            r#"
+            SELECT COUNT(*) FROM import_errors
+            WHERE batch_id IN (SELECT value FROM json_each(?1)) AND tenant_id = ?2
+            "#,
+        )
+        .bind(Json(batch_ids))
+        .bind(self.tenant.as_str())
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error counting import errors: {:?}", e);
+            e
+        })?;
+
+        Ok((errors, total_count))
+    }
+
+    fn stream_errors<'a>(
+        &'a self,
+        batch_ids: &'a [i64],
+    ) -> BoxStream<'a, SqlxResult<ImportRowError>> {
+        sqlx::query_as::<_, ImportRowError>(IMPORT_ERRORS_QUERY)
+            .bind(Json(batch_ids))
+            .bind(self.tenant.as_str())
+            .fetch(&self.pool)
+    }
+
+    #[instrument(skip(self))]
+    async fn existing_keys(
+        &self,
+        table: &str,
+        key_column: &str,
+        keys: &[String],
+    ) -> SqlxResult<Vec<String>> {
+        sqlx::query_scalar(&format!(
+            r#"
+            SELECT {key_column} FROM {table}
+            WHERE {key_column} IN (SELECT value FROM json_each(?1)) AND tenant_id = ?2
+            "#
+        ))
+        .bind(Json(keys))
+        .bind(self.tenant.as_str())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error looking up existing {}: {:?}", table, e);
+            e
+        })
+    }
+
+    async fn rollback(
+        &self,
+        batch_id: i64,
+        table: &str,
+        key_column: &str,
+    ) -> SqlxResult<Option<(ImportBatch, u64)>> {
+        let result = async {
+            let mut tx = self.pool.begin().await?;
+
+            let batch = sqlx::query_as::<_, ImportBatch>(&format!(
+                r#"
+                UPDATE import_batches
+                SET status = 'rolled_back', rolled_back_at = datetime('now')
+                WHERE batch_id = ?1 AND tenant_id = ?2 AND status IN ('completed', 'failed')
+                RETURNING {}
+                "#,
+                IMPORT_BATCH_COLUMNS
+            ))
+            .bind(batch_id)
+            .bind(self.tenant.as_str())
+            .fetch_optional(&mut *tx)
+            .await?;
+
+            let Some(batch) = batch else {
+                return Ok(None);
+            };
+
+            let deleted = sqlx::query(&format!(
+                r#"
+                DELETE FROM {table}
+                WHERE tenant_id = ?2 AND {key_column} IN (
+                    SELECT entity_id FROM import_batch_rows WHERE batch_id = ?1
+                )
+                "#
+            ))
+            .bind(batch_id)
+            .bind(self.tenant.as_str())
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+
+            tx.commit().await?;
+            Ok(Some((batch, deleted)))
+        }
+        .await;
+
+        match &result {
+            Ok(Some((_, deleted))) => info!("Import batch rolled back, {} rows deleted", deleted),
+            Ok(None) => info!("Import batch not in a state that can be rolled back"),
+            Err(e) => error!("Error rolling back import batch: {:?}", e),
+        }
+
+        result
+    }
+}
+
+#[derive(Clone)]
+pub struct SqliteCouponRepository {
+    pool: SqlitePool,
+    tenant: TenantId,
+}
+
+impl SqliteCouponRepository {
+    pub fn new(pool: SqlitePool, tenant: TenantId) -> Self {
+        Self { pool, tenant }
+    }
+}
+
+const COUPON_COLUMNS: &str = r#"
+    c.code, c.discount_type, c.value, c.min_order_value, c.expires_at, c.max_uses,
+    c.times_used, c.created_at
+"#;
+
+#[async_trait]
+impl CouponRepository for SqliteCouponRepository {
+    async fn create(&self, dto: CreateCouponDto) -> SqlxResult<Coupon> {
+        sqlx::query_as::<_, Decoded<Coupon>>(
+            r#"
+            INSERT INTO coupons (
+                code, discount_type, value, min_order_value, expires_at, max_uses, tenant_id
+            )
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+            RETURNING code, discount_type, value, min_order_value, expires_at, max_uses,
+                      times_used, created_at
+            "#,
+        )
+        .bind(&dto.code)
+        .bind(dto.discount_type.as_str())
+        .bind(dto.value.to_string())
+        .bind(dto.min_order_value.as_ref().map(ToString::to_string))
+        .bind(dto.expires_at)
+        .bind(dto.max_uses)
+        .bind(self.tenant.as_str())
+        .fetch_one(&self.pool)
+        .await
+        .map(|coupon| coupon.0)
+        .map_err(|e| {
+            error!("Error creating coupon: {:?}", e);
+            e
+        })
+    }
+
+    async fn find_by_code(&self, code: &str) -> SqlxResult<Option<Coupon>> {
+        sqlx::query_as::<_, Decoded<Coupon>>(&format!(
+            "SELECT {} FROM coupons c WHERE c.code = ?1 AND c.tenant_id = ?2",
+            COUPON_COLUMNS
+        ))
+        .bind(code)
+        .bind(self.tenant.as_str())
+        .fetch_optional(&self.pool)
+        .await
+        .map(|coupon| coupon.map(|coupon| coupon.0))
+        .map_err(|e| {
+            error!("Error fetching coupon: {:?}", e);
+            e
+        })
+    }
+
+    async fn find_by_order(&self, order_id: &OrderId) -> SqlxResult<Option<Coupon>> {
+        sqlx::query_as::<_, Decoded<Coupon>>(&format!(
+            r#"
+            SELECT {} FROM order_coupons oc
+            JOIN coupons c ON c.tenant_id = oc.tenant_id AND c.code = oc.code
+            WHERE oc.order_id = ?1 AND oc.tenant_id = ?2
+            "#,
+            COUPON_COLUMNS
+        ))
+        .bind(order_id.as_str())
+        .bind(self.tenant.as_str())
+        .fetch_optional(&self.pool)
+        .await
+        .map(|coupon| coupon.map(|coupon| coupon.0))
+        .map_err(|e| {
+            error!("Error fetching order coupon: {:?}", e);
+            e
+        })
+    }
+
+    #[instrument(skip(self))]
+    async fn redeem(&self, code: &str, order_id: &OrderId) -> SqlxResult<bool> {
+        let result = async {
+            let mut tx = self.pool.begin().await?;
+
+            let taken = sqlx::query(
+                r#"
+                UPDATE coupons
+                SET times_used = times_used + 1
+                WHERE code = ?1 AND tenant_id = ?2
+                  AND (max_uses IS NULL OR times_used < max_uses)
+                  AND (expires_at IS NULL OR expires_at > datetime('now'))
+                "#,
+            )
+            .bind(code)
+            .bind(self.tenant.as_str())
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+            if taken == 0 {
+                return Ok(false);
+            }
+
+            sqlx::query(
+                "INSERT INTO order_coupons (order_id, code, tenant_id) VALUES (?1, ?2, ?3)",
+            )
+            .bind(order_id.as_str())
+            .bind(code)
+            .bind(self.tenant.as_str())
+            .execute(&mut *tx)
+            .await?;
+
+            tx.commit().await?;
+            Ok(true)
+        }
+        .await;
+
+        if let Err(e) = &result {
+            error!("Error redeeming coupon: {:?}", e);
+        }
+        result
+    }
+}
+
+#[derive(Clone)]
+pub struct SqlitePaymentTransactionRepository {
+    pool: SqlitePool,
+    tenant: TenantId,
+}
+
+impl SqlitePaymentTransactionRepository {
+    pub fn new(pool: SqlitePool, tenant: TenantId) -> Self {
+        Self { pool, tenant }
+    }
+}
+
+const PAYMENT_TRANSACTION_COLUMNS: &str = r#"
+    transaction_id, order_id, provider, provider_reference, payment_type,
+    payment_installments, amount, status, created_at, updated_at
+"#;
+
+/// Moves an order whose payment changed status, if it is still in the transition's `from`.
+async fn apply_order_transition(
+    conn: &mut SqliteConnection,
+    tenant: &TenantId,
+    order_id: &OrderId,
+    (from, to): OrderStatusTransition,
+) -> SqlxResult<()> {
+    sqlx::query(
+        r#"
+        UPDATE orders SET order_status = ?3
+        WHERE order_id = ?1 AND order_status = ?2 AND tenant_id = ?4
+        "#,
+    )
+    .bind(order_id.as_str())
+    .bind(from)
+    .bind(to)
+    .bind(tenant.as_str())
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+#[async_trait]
+impl PaymentTransactionRepository for SqlitePaymentTransactionRepository {
+    #[instrument(skip(self))]
+    async fn create(
+        &self,
+        transaction: NewPaymentTransaction,
+        order_status: Option<OrderStatusTransition>,
+    ) -> SqlxResult<PaymentTransaction> {
+        let result = async {
+            let mut tx = self.pool.begin().await?;
+
+            let created = sqlx::query_as::<_, Decoded<PaymentTransaction>>(&format!(
+                r#"
+                INSERT INTO payment_transactions (
+                    order_id, provider, provider_reference, payment_type,
+                    payment_installments, amount, status, tenant_id
+                )
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+                RETURNING {}
+                "#,
+                PAYMENT_TRANSACTION_COLUMNS
+            ))
+            .bind(transaction.order_id.as_str())
+            .bind(&transaction.provider)
+            .bind(&transaction.provider_reference)
+            .bind(transaction.payment_type)
+            .bind(transaction.payment_installments)
+            .bind(transaction.amount.to_string())
+            .bind(transaction.status)
+            .bind(self.tenant.as_str())
+            .fetch_one(&mut *tx)
+            .await?
+            .0;
+            if let Some(order_status) = order_status {
+                apply_order_transition(&mut tx, &self.tenant, &created.order_id, order_status)
+                    .await?;
+            }
+
+            tx.commit().await?;
+            Ok(created)
+        }
+        .await;
+
+        if let Err(e) = &result {
+            error!("Error creating payment transaction: {:?}", e);
+        }
+        result
+    }
+
+    async fn find_by_id(&self, transaction_id: i64) -> SqlxResult<Option<PaymentTransaction>> {
+        sqlx::query_as::<_, Decoded<PaymentTransaction>>(&format!(
+            "SELECT {} FROM payment_transactions WHERE transaction_id = ?1 AND tenant_id = ?2",
+            PAYMENT_TRANSACTION_COLUMNS
+        ))
+        .bind(transaction_id)
+        .bind(self.tenant.as_str())
+        .fetch_optional(&self.pool)
+        .await
+        .map(|transaction| transaction.map(|transaction| transaction.0))
+        .map_err(|e| {
+            error!("Error fetching payment transaction: {:?}", e);
+            e
+        })
+    }
+
+    async fn find_by_reference(
+        &self,
+        provider: &str,
+        reference: &str,
+    ) -> SqlxResult<Option<PaymentTransaction>> {
+        sqlx::query_as::<_, Decoded<PaymentTransaction>>(&format!(
+            r#"
+            SELECT {} FROM payment_transactions
+            WHERE provider = ?1 AND provider_reference = ?2 AND tenant_id = ?3
+            "#,
+            PAYMENT_TRANSACTION_COLUMNS
+        ))
+        .bind(provider)
+        .bind(reference)
+        .bind(self.tenant.as_str())
+        .fetch_optional(&self.pool)
+        .await
+        .map(|transaction| transaction.map(|transaction| transaction.0))
+        .map_err(|e| {
+            error!("Error fetching payment transaction: {:?}", e);
+            e
+        })
+    }
+
+    async fn find_by_order(&self, order_id: &OrderId) -> SqlxResult<Vec<PaymentTransaction>> {
+        sqlx::query_as::<_, Decoded<PaymentTransaction>>(&format!(
+            r#"
+            SELECT {} FROM payment_transactions
+            WHERE order_id = ?1 AND tenant_id = ?2
+            ORDER BY transaction_id
+            "#,
+            PAYMENT_TRANSACTION_COLUMNS
+        ))
+        .bind(order_id.as_str())
+        .bind(self.tenant.as_str())
+        .fetch_all(&self.pool)
+        .await
+        .map(|transactions| transactions.into_iter().map(|t| t.0).collect())
+        .map_err(|e| {
+            error!("Error fetching order payment transactions: {:?}", e);
+            e
+        })
+    }
+
+    #[instrument(skip(self))]
+    async fn update_status(
+        &self,
+        transaction_id: i64,
+        from: PaymentStatus,
+        to: PaymentStatus,
+        order_status: Option<OrderStatusTransition>,
+    ) -> SqlxResult<Option<PaymentTransaction>> {
+        let result = async {
+            let mut tx = self.pool.begin().await?;
+
+            let updated = sqlx::query_as::<_, Decoded<PaymentTransaction>>(&format!(
+                r#"
+                UPDATE payment_transactions
+                SET status = ?3, updated_at = datetime('now')
+                WHERE transaction_id = ?1 AND status = ?2 AND tenant_id = ?4
+                RETURNING {}
+                "#,
+                PAYMENT_TRANSACTION_COLUMNS
+            ))
+            .bind(transaction_id)
+            .bind(from)
+            .bind(to)
+            .bind(self.tenant.as_str())
+            .fetch_optional(&mut *tx)
+            .await?;
+            let Some(Decoded(updated)) = updated else {
+                return Ok(None);
+            };
+            if let Some(order_status) = order_status {
+                apply_order_transition(&mut tx, &self.tenant, &updated.order_id, order_status)
+                    .await?;
+            }
+
+            tx.commit().await?;
+            Ok(Some(updated))
+        }
+        .await;
+
+        if let Err(e) = &result {
+            error!("Error updating payment transaction: {:?}", e);
+        }
+        result
+    }
+}
+
+#[derive(Clone)]
+pub struct SqliteGeolocationRepository {
+    pool: SqlitePool,
+}
+
+impl SqliteGeolocationRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl GeolocationRepository for SqliteGeolocationRepository {
+    async fn find_by_prefixes(&self, prefixes: &[String]) -> SqlxResult<Vec<ZipLocation>> {
+        sqlx::query_as::<_, ZipLocation>(
+            r#"
+            SELECT geolocation_zip_code_prefix, geolocation_lat, geolocation_lng,
+                   geolocation_city, geolocation_state
+            FROM geolocation
+            WHERE geolocation_zip_code_prefix IN (SELECT value FROM json_each(?1))
+            "#,
+        )
+        .bind(Json(prefixes))
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching zip code locations: {:?}", e);
+            e
+        })
+    }
+
+    async fn find_all(&self, limit: i64) -> SqlxResult<Vec<ZipLocation>> {
+        sqlx::query_as::<_, ZipLocation>(
+            r#"
+            SELECT geolocation_zip_code_prefix, geolocation_lat, geolocation_lng,
+                   geolocation_city, geolocation_state
+            FROM geolocation
+            ORDER BY geolocation_zip_code_prefix
+            LIMIT ?1
+            "#,
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error listing zip code locations: {:?}", e);
+            e
+        })
+    }
+
+    #[instrument(skip(self, locations), fields(count = locations.len()))]
+    async fn upsert_many(&self, locations: &[ZipLocation]) -> SqlxResult<u64> {
+        let result = async {
+            let mut tx = self.pool.begin().await?;
+            let mut written = 0;
+            for location in locations {
+                written += sqlx::query(
+                    r#"
+                    INSERT INTO geolocation (
+                        geolocation_zip_code_prefix, geolocation_lat, geolocation_lng,
+                        geolocation_city, geolocation_state
+                    )
+                    VALUES (?1, ?2, ?3, ?4, ?5)
+                    ON CONFLICT (geolocation_zip_code_prefix) DO UPDATE
+                    SET geolocation_lat = excluded.geolocation_lat,
+                        geolocation_lng = excluded.geolocation_lng,
+                        geolocation_city = excluded.geolocation_city,
+                        geolocation_state = excluded.geolocation_state,
+                        updated_at = CURRENT_TIMESTAMP
+                    "#,
+                )
+                .bind(&location.geolocation_zip_code_prefix)
+                .bind(location.geolocation_lat)
+                .bind(location.geolocation_lng)
+                .bind(&location.geolocation_city)
+                .bind(&location.geolocation_state)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected();
+            }
+            tx.commit().await?;
+            Ok(written)
+        }
+        .await;
+
+        result.map_err(|e| {
+            error!("Error saving zip code locations: {:?}", e);
+            e
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct SqliteStatsRepository {
+    pool: SqlitePool,
+    tenant: TenantId,
+}
+
+impl SqliteStatsRepository {
+    pub fn new(pool: SqlitePool, tenant: TenantId) -> Self {
+        Self { pool, tenant }
+    }
+}
+
+#[async_trait]
+impl StatsRepository for SqliteStatsRepository {
+    async fn today(&self) -> SqlxResult<TodayStats> {
+        sqlx::query_as::<_, Decoded<TodayStats>>(
+            r#"
+            SELECT
+                date('now') AS stat_date,
+                COALESCE(today.orders_count, 0) AS orders_count,
+                COALESCE(today.revenue, 0) AS revenue,
+                (
+                    SELECT COALESCE(SUM(active_imports), 0) FROM stats WHERE tenant_id = ?1
+                ) AS active_imports
+            FROM (SELECT 1) AS one
+            LEFT JOIN stats today ON today.tenant_id = ?1 AND today.stat_date = date('now')
+            "#,
+        )
+        .bind(self.tenant.as_str())
+        .fetch_one(&self.pool)
+        .await
+        .map(|stats| stats.0)
+        .map_err(|e| {
+            error!("Error fetching today's stats: {:?}", e);
+            e
+        })
+    }
+}
+
+/// Columns selected for `WebhookSubscription` rows.
+const WEBHOOK_SUBSCRIPTION_COLUMNS: &str = r#"
+    subscription_id, url, events, customer_states, payload_template, secret, created_by,
+    created_at
+"#;
+
+/// Columns selected for `WebhookDelivery` rows.
+const WEBHOOK_DELIVERY_COLUMNS: &str = r#"
+    delivery_id, subscription_id, event, payload, status, attempts, next_attempt_at,
+    last_status_code, last_error, created_at, delivered_at
+"#;
+
+/// Webhook subscriptions and deliveries. SQLite allows one writer at a time, so a claim is
+/// a plain `UPDATE` rather than a `SKIP LOCKED` lease.
+#[derive(Clone)]
+pub struct SqliteWebhookRepository {
+    pool: SqlitePool,
+    tenant: TenantId,
+}
+
+impl SqliteWebhookRepository {
+    pub fn new(pool: SqlitePool, tenant: TenantId) -> Self {
+        Self { pool, tenant }
+    }
+}
+
+#[async_trait]
+impl WebhookRepository for SqliteWebhookRepository {
+    async fn create_subscription(
+        &self,
+        url: &str,
+        events: &[String],
+        customer_states: Option<&[String]>,
+        payload_template: WebhookPayloadTemplate,
+        secret: &str,
+        created_by: &str,
+    ) -> SqlxResult<WebhookSubscription> {
+        sqlx::query_as::<_, Decoded<WebhookSubscription>>(&format!(
+            r#"
+            INSERT INTO webhook_subscriptions
+                (url, events, customer_states, payload_template, secret, created_by, tenant_id)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+            RETURNING {}
+            "#,
+            WEBHOOK_SUBSCRIPTION_COLUMNS
+        ))
+        .bind(url)
+        .bind(Json(events))
+        .bind(customer_states.map(Json))
+        .bind(payload_template)
+        .bind(secret)
+        .bind(created_by)
+        .bind(self.tenant.as_str())
+        .fetch_one(&self.pool)
+        .await
+        .map(|Decoded(subscription)| subscription)
+        .map_err(|e| {
+            error!("Error creating webhook subscription: {:?}", e);
+            e
+        })
+    }
+
+    async fn find_subscriptions(
+        &self,
+        pagination: &PaginationParams,
+    ) -> SqlxResult<(Vec<WebhookSubscription>, i64)> {
+        let (limit, offset, _, _) = pagination.normalize();
+
+        let rows = sqlx::query_as::<_, Counted<Decoded<WebhookSubscription>>>(&format!(
+            r#"
+            SELECT {}, COUNT(*) OVER () AS total_count
+            FROM webhook_subscriptions
+            WHERE tenant_id = ?3
+            ORDER BY subscription_id
+            LIMIT ?1 OFFSET ?2
+            "#,
+            WEBHOOK_SUBSCRIPTION_COLUMNS
+        ))
+        .bind(limit)
+        .bind(offset)
+        .bind(self.tenant.as_str())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching webhook subscriptions: {:?}", e);
+            e
+        })?;
+
+        let (subscriptions, total_count) = split_counted(rows, offset);
+        let subscriptions = subscriptions
+            .into_iter()
+            .map(|Decoded(subscription)| subscription)
+            .collect();
+        match total_count {
+            Some(total_count) => Ok((subscriptions, total_count)),
+            // Past the last page no row carries the window total.
+            None => {
+                let count = sqlx::query_scalar::<_, i64>(
+                    "SELECT COUNT(*) FROM webhook_subscriptions WHERE tenant_id = ?1",
+                )
+                .bind(self.tenant.as_str())
+                .fetch_one(&self.pool)
+                .await
+                .map_err(|e| {
+                    error!("Error counting webhook subscriptions: {:?}", e);
+                    e
+                })?;
+                Ok((subscriptions, count))
+            }
+        }
+    }
+
+    async fn find_subscription(
+        &self,
+        subscription_id: i64,
+    ) -> SqlxResult<Option<WebhookSubscription>> {
+        sqlx::query_as::<_, Decoded<WebhookSubscription>>(&format!(
+            "SELECT {} FROM webhook_subscriptions WHERE subscription_id = ?1 AND tenant_id = ?2",
+            WEBHOOK_SUBSCRIPTION_COLUMNS
+        ))
+        .bind(subscription_id)
+        .bind(self.tenant.as_str())
+        .fetch_optional(&self.pool)
+        .await
+        .map(|subscription| subscription.map(|Decoded(subscription)| subscription))
+        .map_err(|e| {
+            error!("Error fetching webhook subscription by id: {:?}", e);
+            e
+        })
+    }
+
+    async fn delete_subscription(
+        &self,
+        subscription_id: i64,
+    ) -> SqlxResult<Option<chrono::NaiveDateTime>> {
+        let result = sqlx::query_scalar::<_, chrono::NaiveDateTime>(
+            r#"
+            DELETE FROM webhook_subscriptions WHERE subscription_id = ?1 AND tenant_id = ?2
+            RETURNING datetime('now')
+            "#,
+        )
+        .bind(subscription_id)
+        .bind(self.tenant.as_str())
+        .fetch_optional(&self.pool)
+        .await;
+
+        match &result {
+            Ok(Some(_)) => info!("Deleted webhook subscription {}", subscription_id),
+            Ok(None) => info!(
+                "Webhook subscription {} not found for deletion",
+                subscription_id
+            ),
+            Err(e) => error!("Error deleting webhook subscription: {:?}", e),
+        }
+
+        result
+    }
+
+    async fn find_deliveries(
+        &self,
+        subscription_id: i64,
+        status: Option<&str>,
+        pagination: &PaginationParams,
+    ) -> SqlxResult<(Vec<WebhookDelivery>, i64)> {
+        let (limit, offset, _, _) = pagination.normalize();
+
+        let rows = sqlx::query_as::<_, Counted<WebhookDelivery>>(&format!(
+            r#"
+            SELECT {}, COUNT(*) OVER () AS total_count
+            FROM webhook_deliveries
+            WHERE subscription_id = ?1 AND (?2 IS NULL OR status = ?2) AND tenant_id = ?5
+            ORDER BY created_at DESC, delivery_id DESC
+            LIMIT ?3 OFFSET ?4
+            "#,
+            WEBHOOK_DELIVERY_COLUMNS
+        ))
+        .bind(subscription_id)
+        .bind(status)
+        .bind(limit)
+        .bind(offset)
+        .bind(self.tenant.as_str())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching webhook deliveries: {:?}", e);
+            e
+        })?;
+
+        match split_counted(rows, offset) {
+            (deliveries, Some(total_count)) => Ok((deliveries, total_count)),
+            (deliveries, None) => {
+                let count = sqlx::query_scalar::<_, i64>(
+                    r#"
+                    SELECT COUNT(*) FROM webhook_deliveries
+                    WHERE subscription_id = ?1 AND (?2 IS NULL OR status = ?2) AND tenant_id = ?3
+                    "#,
+                )
+                .bind(subscription_id)
+                .bind(status)
+                .bind(self.tenant.as_str())
+                .fetch_one(&self.pool)
+                .await
+                .map_err(|e| {
+                    error!("Error counting webhook deliveries: {:?}", e);
+                    e
+                })?;
+                Ok((deliveries, count))
+            }
+        }
+    }
+
+    async fn find_delivery(
+        &self,
+        subscription_id: i64,
+        delivery_id: i64,
+    ) -> SqlxResult<Option<WebhookDelivery>> {
+        sqlx::query_as::<_, WebhookDelivery>(&format!(
+            r#"
+            SELECT {} FROM webhook_deliveries
+            WHERE subscription_id = ?1 AND delivery_id = ?2 AND tenant_id = ?3
+            "#,
+            WEBHOOK_DELIVERY_COLUMNS
+        ))
+        .bind(subscription_id)
+        .bind(delivery_id)
+        .bind(self.tenant.as_str())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching webhook delivery by id: {:?}", e);
+            e
+        })
+    }
+
+    async fn claim_due_deliveries(
+        &self,
+        limit: i64,
+        lease_seconds: i64,
+    ) -> SqlxResult<Vec<PendingWebhookDelivery>> {
+        let result = async {
+            let mut tx = self.pool.begin().await?;
+
+            let claimed = sqlx::query_scalar::<_, i64>(
+                r#"
+                UPDATE webhook_deliveries
+                SET attempts = attempts + 1,
+                    next_attempt_at = datetime('now', '+' || ?2 || ' seconds')
+                WHERE delivery_id IN (
+                    SELECT delivery_id FROM webhook_deliveries
+                    WHERE status = 'pending' AND next_attempt_at <= datetime('now')
+                      AND tenant_id = ?3
+                    ORDER BY next_attempt_at, delivery_id
+                    LIMIT ?1
+                )
+                RETURNING delivery_id
+                "#,
+            )
+            .bind(limit)
+            .bind(lease_seconds)
+            .bind(self.tenant.as_str())
+            .fetch_all(&mut *tx)
+            .await?;
+
+            // RETURNING can't reach the subscription, so its URL, secret and filters are read
+            // after.
+            let deliveries = sqlx::query_as::<_, Decoded<PendingWebhookDelivery>>(
+                r#"
+                SELECT d.delivery_id, d.event, d.payload, d.attempts, d.created_at, s.url, s.secret,
+                    s.customer_states, s.payload_template,
+                    (
+                        SELECT c.customer_state
+                        FROM orders o
+                        JOIN customers c
+                            ON c.tenant_id = o.tenant_id AND c.customer_id = o.customer_id
+                        WHERE o.tenant_id = d.tenant_id
+                          AND o.order_id = json_extract(d.payload, '$.order_id')
+                    ) AS customer_state
+                FROM webhook_deliveries d
+                JOIN webhook_subscriptions s ON s.subscription_id = d.subscription_id
+                WHERE d.delivery_id IN (SELECT value FROM json_each(?1))
+                "#,
+            )
+            .bind(Json(&claimed))
+            .fetch_all(&mut *tx)
+            .await?;
+
+            tx.commit().await?;
+            Ok(deliveries.into_iter().map(|delivery| delivery.0).collect())
+        }
+        .await;
+
+        if let Err(e) = &result {
+            error!("Error claiming webhook deliveries: {:?}", e);
+        }
+        result
+    }
+
+    async fn mark_delivered(&self, delivery_id: i64, status_code: i32) -> SqlxResult<()> {
+        sqlx::query(
+            r#"
+            UPDATE webhook_deliveries
+            SET status = 'succeeded', last_status_code = ?2, last_error = NULL,
+                next_attempt_at = NULL, delivered_at = datetime('now')
+            WHERE delivery_id = ?1
+            "#,
+        )
+        .bind(delivery_id)
+        .bind(status_code)
+        .execute(&self.pool)
+        .await
+        .map(|_| ())
+        .map_err(|e| {
+            error!("Error recording webhook delivery: {:?}", e);
+            e
+        })
+    }
+
+    async fn discard_delivery(&self, delivery_id: i64) -> SqlxResult<()> {
+        sqlx::query("DELETE FROM webhook_deliveries WHERE delivery_id = ?1")
+            .bind(delivery_id)
+            .execute(&self.pool)
+            .await
+            .map(|_| ())
+            .map_err(|e| {
+                error!("Error dropping webhook delivery: {:?}", e);
+                e
+            })
+    }
+
+    async fn mark_attempt_failed(
+        &self,
+        delivery_id: i64,
+        status_code: Option<i32>,
+        error: &str,
+        retry_in_seconds: Option<i64>,
+    ) -> SqlxResult<()> {
+        sqlx::query(
+            r#"
+            UPDATE webhook_deliveries
+            SET status = CASE WHEN ?4 IS NULL THEN 'failed' ELSE 'pending' END,
+                last_status_code = ?2, last_error = ?3,
+                next_attempt_at = datetime('now', '+' || ?4 || ' seconds')
+            WHERE delivery_id = ?1
+            "#,
+        )
+        .bind(delivery_id)
+        .bind(status_code)
+        .bind(error)
+        .bind(retry_in_seconds)
+        .execute(&self.pool)
+        .await
+        .map(|_| ())
+        .map_err(|e| {
+            error!("Error recording failed webhook delivery: {:?}", e);
+            e
+        })
+    }
+
+    async fn requeue_failed_deliveries(&self, max_age_hours: i64) -> SqlxResult<u64> {
+        sqlx::query(
+            r#"
+            UPDATE webhook_deliveries
+            SET status = 'pending', next_attempt_at = datetime('now')
+            WHERE status = 'failed' AND tenant_id = ?2
+              AND created_at >= datetime('now', '-' || ?1 || ' hours')
+            "#,
+        )
+        .bind(max_age_hours)
+        .bind(self.tenant.as_str())
+        .execute(&self.pool)
+        .await
+        .map(|result| result.rows_affected())
+        .map_err(|e| {
+            error!("Error requeueing failed webhook deliveries: {:?}", e);
+            e
+        })
+    }
+}
+
+pub struct SqliteOutboxRepository {
+    pool: SqlitePool,
+    tenant: TenantId,
+}
+
+impl SqliteOutboxRepository {
+    pub fn new(pool: SqlitePool, tenant: TenantId) -> Self {
+        Self { pool, tenant }
+    }
+}
+
+#[async_trait]
+impl OutboxRepository for SqliteOutboxRepository {
+    async fn claim_due_events(
+        &self,
+        limit: i64,
+        lease_seconds: i64,
+    ) -> SqlxResult<Vec<OutboxEvent>> {
+        // A single statement, and SQLite has one writer at a time: a concurrent relay sees
+        // these leases once it gets to write.
+        let mut events = sqlx::query_as::<_, OutboxEvent>(
+            r#"
+            UPDATE outbox_events
+            SET next_attempt_at = datetime('now', '+' || ?2 || ' seconds')
+            WHERE event_id IN (
+                SELECT e.event_id
+                FROM outbox_events e
+                WHERE e.published_at IS NULL
+                  AND e.tenant_id = ?3
+                  AND e.next_attempt_at <= datetime('now')
+                  AND NOT EXISTS (
+                      SELECT 1 FROM outbox_events earlier
+                      WHERE earlier.published_at IS NULL
+                        AND earlier.tenant_id = e.tenant_id
+                        AND earlier.event_id < e.event_id
+                        AND earlier.next_attempt_at > datetime('now')
+                  )
+                ORDER BY e.event_id
+                LIMIT ?1
+            )
+            RETURNING
+                event_id, tenant_id, aggregate_type, aggregate_id, event_type, payload, attempts,
+                created_at
+            "#,
+        )
+        .bind(limit)
+        .bind(lease_seconds)
+        .bind(self.tenant.as_str())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error claiming outbox events: {:?}", e);
+            e
+        })?;
+
+        events.sort_by_key(|event| event.event_id);
+        Ok(events)
+    }
+
+    async fn mark_published(&self, event_id: i64) -> SqlxResult<()> {
+        let result = async {
+            let mut tx = self.pool.begin().await?;
+
+            sqlx::query(
+                r#"
+                INSERT INTO webhook_deliveries (subscription_id, event, payload, tenant_id)
+                SELECT s.subscription_id, e.event_type, e.payload, e.tenant_id
+                FROM outbox_events e
+                JOIN webhook_subscriptions s
+                    ON s.tenant_id = e.tenant_id
+                    AND EXISTS (SELECT 1 FROM json_each(s.events) WHERE value = e.event_type)
+                WHERE e.event_id = ?1 AND e.published_at IS NULL
+                "#,
+            )
+            .bind(event_id)
+            .execute(&mut *tx)
+            .await?;
+
+            sqlx::query(
+                r#"
+                INSERT INTO notifications (event_id, kind, payload, tenant_id)
+                SELECT event_id,
+                       CASE event_type
+                           WHEN 'review.created' THEN 'review_received'
+                           WHEN 'order.late' THEN 'order_late'
+                           ELSE 'order_' || json_extract(payload, '$.order_status')
+                       END,
+                       payload,
+                       tenant_id
+                FROM outbox_events
+                WHERE event_id = ?1 AND published_at IS NULL
+                  AND (event_type IN ('review.created', 'order.late')
+                       OR (event_type = 'order.status_changed'
+                           AND json_extract(payload, '$.order_status') IN ('approved', 'delivered')))
+                "#,
+            )
+            .bind(event_id)
+            .execute(&mut *tx)
+            .await?;
+
+            sqlx::query(
+                r#"
+                UPDATE outbox_events
+                SET published_at = datetime('now'), next_attempt_at = NULL, last_error = NULL
+                WHERE event_id = ?1 AND published_at IS NULL
+                "#,
+            )
+            .bind(event_id)
+            .execute(&mut *tx)
+            .await?;
+
+            tx.commit().await
+        }
+        .await;
+
+        if let Err(e) = &result {
+            error!("Error marking outbox event published: {:?}", e);
+        }
+        result
+    }
+
+    async fn mark_publish_failed(
+        &self,
+        event_id: i64,
+        error: &str,
+        retry_in_seconds: i64,
+    ) -> SqlxResult<()> {
+        sqlx::query(
+            r#"
+            UPDATE outbox_events
+            SET attempts = attempts + 1, last_error = ?2,
+                next_attempt_at = datetime('now', '+' || ?3 || ' seconds')
+            WHERE event_id = ?1
+            "#,
+        )
+        .bind(event_id)
+        .bind(error)
+        .bind(retry_in_seconds)
+        .execute(&self.pool)
+        .await
+        .map(|_| ())
+        .map_err(|e| {
+            error!("Error recording failed outbox publish: {:?}", e);
+            e
+        })
+    }
+
+    async fn release_events(&self, event_ids: &[i64]) -> SqlxResult<()> {
+        if event_ids.is_empty() {
+            return Ok(());
+        }
+        sqlx::query(
+            r#"
+            UPDATE outbox_events SET next_attempt_at = datetime('now')
+            WHERE event_id IN (SELECT value FROM json_each(?1)) AND published_at IS NULL
+            "#,
+        )
+        .bind(Json(event_ids))
+        .execute(&self.pool)
+        .await
+        .map(|_| ())
+        .map_err(|e| {
+            error!("Error releasing outbox events: {:?}", e);
+            e
+        })
+    }
+
+    async fn backlog(&self) -> SqlxResult<OutboxBacklog> {
+        sqlx::query_as::<_, OutboxBacklog>(
+            r#"
+            SELECT
+                COUNT(*) AS pending,
+                (julianday('now') - julianday(MIN(created_at))) * 86400.0 AS oldest_age_seconds
+            FROM outbox_events
+            WHERE published_at IS NULL AND tenant_id = ?1
+            "#,
+        )
+        .bind(self.tenant.as_str())
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error reading outbox backlog: {:?}", e);
+            e
+        })
+    }
+
+    async fn delete_published_before(&self, days: i64) -> SqlxResult<u64> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM outbox_events
+            WHERE published_at < datetime('now', '-' || ?1 || ' days') AND tenant_id = ?2
+            "#,
+        )
+        .bind(days)
+        .bind(self.tenant.as_str())
+        .execute(&self.pool)
+        .await
+        .map(|result| result.rows_affected());
+
+        match &result {
+            Ok(deleted) if *deleted > 0 => info!("Deleted {} published outbox events", deleted),
+            Ok(_) => {}
+            Err(e) => error!("Error deleting published outbox events: {:?}", e),
+        }
+        result
+    }
+}
+
+/// Columns selected for `Notification` rows.
+const NOTIFICATION_COLUMNS: &str = r#"
+    notification_id, event_id, kind, payload, status, channel, recipient, subject, attempts,
+    next_attempt_at, last_error, created_at, sent_at
+"#;
+
+pub struct SqliteNotificationRepository {
+    pool: SqlitePool,
+    tenant: TenantId,
+}
+
+impl SqliteNotificationRepository {
+    pub fn new(pool: SqlitePool, tenant: TenantId) -> Self {
+        Self { pool, tenant }
+    }
+}
+
+#[async_trait]
+impl NotificationRepository for SqliteNotificationRepository {
+    async fn find_notifications(
+        &self,
+        status: Option<&str>,
+        kind: Option<&str>,
+        pagination: &PaginationParams,
+    ) -> SqlxResult<(Vec<Notification>, i64)> {
+        let (limit, offset, _, _) = pagination.normalize();
+
+        let rows = sqlx::query_as::<_, Counted<Notification>>(&format!(
+            r#"
+            SELECT {}, COUNT(*) OVER () AS total_count
+            FROM notifications
+            WHERE (?1 IS NULL OR status = ?1) AND (?2 IS NULL OR kind = ?2) AND tenant_id = ?5
+            ORDER BY created_at DESC, notification_id DESC
+            LIMIT ?3 OFFSET ?4
+            "#,
+            NOTIFICATION_COLUMNS
+        ))
+        .bind(status)
+        .bind(kind)
+        .bind(limit)
+        .bind(offset)
+        .bind(self.tenant.as_str())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching notifications: {:?}", e);
+            e
+        })?;
+
+        match split_counted(rows, offset) {
+            (notifications, Some(total_count)) => Ok((notifications, total_count)),
+            (notifications, None) => {
+                let count = sqlx::query_scalar::<_, i64>(
+                    r#"
+                    SELECT COUNT(*) FROM notifications
+                    WHERE (?1 IS NULL OR status = ?1) AND (?2 IS NULL OR kind = ?2)
+                      AND tenant_id = ?3
+                    "#,
+                )
+                .bind(status)
+                .bind(kind)
+                .bind(self.tenant.as_str())
+                .fetch_one(&self.pool)
+                .await
+                .map_err(|e| {
+                    error!("Error counting notifications: {:?}", e);
+                    e
+                })?;
+                Ok((notifications, count))
+            }
+        }
+    }
+
+    async fn find_notification(&self, notification_id: i64) -> SqlxResult<Option<Notification>> {
+        sqlx::query_as::<_, Notification>(&format!(
+            "SELECT {} FROM notifications WHERE notification_id = ?1 AND tenant_id = ?2",
+            NOTIFICATION_COLUMNS
+        ))
+        .bind(notification_id)
+        .bind(self.tenant.as_str())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error fetching notification: {:?}", e);
+            e
+        })
+    }
+
+    async fn claim_due_notifications(
+        &self,
+        limit: i64,
+        lease_seconds: i64,
+    ) -> SqlxResult<Vec<PendingNotification>> {
+        sqlx::query_as::<_, PendingNotification>(
+            r#"
+            UPDATE notifications
+            SET attempts = attempts + 1,
+                next_attempt_at = datetime('now', '+' || ?2 || ' seconds')
+            WHERE notification_id IN (
+                SELECT notification_id FROM notifications
+                WHERE status = 'pending' AND next_attempt_at <= datetime('now')
+                  AND tenant_id = ?3
+                ORDER BY next_attempt_at, notification_id
+                LIMIT ?1
+            )
+            RETURNING notification_id, kind, payload, attempts
+            "#,
+        )
+        .bind(limit)
+        .bind(lease_seconds)
+        .bind(self.tenant.as_str())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error claiming notifications: {:?}", e);
+            e
+        })
+    }
+
+    async fn mark_sent(
+        &self,
+        notification_id: i64,
+        attempt: &NotificationAttempt<'_>,
+    ) -> SqlxResult<()> {
+        sqlx::query(
+            r#"
+            UPDATE notifications
+            SET status = 'sent', channel = ?2, recipient = ?3, subject = ?4, last_error = NULL,
+                next_attempt_at = NULL, sent_at = datetime('now')
+            WHERE notification_id = ?1
+            "#,
+        )
+        .bind(notification_id)
+        .bind(attempt.channel)
+        .bind(attempt.recipient)
+        .bind(attempt.subject)
+        .execute(&self.pool)
+        .await
+        .map(|_| ())
+        .map_err(|e| {
+            error!("Error recording notification: {:?}", e);
+            e
+        })
+    }
+
+    async fn mark_attempt_failed(
+        &self,
+        notification_id: i64,
+        attempt: &NotificationAttempt<'_>,
+        error: &str,
+        retry_in_seconds: Option<i64>,
+    ) -> SqlxResult<()> {
+        sqlx::query(
+            r#"
+            UPDATE notifications
+            SET status = CASE WHEN ?6 IS NULL THEN 'failed' ELSE 'pending' END,
+                channel = ?2, recipient = ?3, subject = ?4, last_error = ?5,
+                next_attempt_at = datetime('now', '+' || ?6 || ' seconds')
+            WHERE notification_id = ?1
+            "#,
+        )
+        .bind(notification_id)
+        .bind(attempt.channel)
+        .bind(attempt.recipient)
+        .bind(attempt.subject)
+        .bind(error)
+        .bind(retry_in_seconds)
+        .execute(&self.pool)
+        .await
+        .map(|_| ())
+        .map_err(|e| {
+            error!("Error recording failed notification: {:?}", e);
+            e
+        })
+    }
+}
+
+pub struct SqliteRetentionRepository {
+    pool: SqlitePool,
+    tenant: TenantId,
+}
+
+impl SqliteRetentionRepository {
+    pub fn new(pool: SqlitePool, tenant: TenantId) -> Self {
+        Self { pool, tenant }
+    }
+}
+
+/// Last order or address change of customer `c`; NULL when it has neither. Multi-argument
+/// `MAX` returns NULL if any argument is, so the dates are aggregated instead.
+const CUSTOMER_LAST_ACTIVITY: &str = r#"
+    (SELECT MAX(active_at) FROM (
+        SELECT order_purchase_timestamp AS active_at FROM orders o
+        WHERE o.customer_id = c.customer_id
+        UNION ALL
+        SELECT valid_from FROM customer_location_history h
+        WHERE h.customer_id = c.customer_id
+    ))
+"#;
+
+#[async_trait]
+impl RetentionRepository for SqliteRetentionRepository {
+    async fn count_reviews_created_before(&self, before: chrono::NaiveDateTime) -> SqlxResult<i64> {
+        sqlx::query_scalar::<_, i64>(
+            "SELECT COUNT(*) FROM reviews WHERE review_creation_date < ?1 AND tenant_id = ?2",
+        )
+        .bind(before)
+        .bind(self.tenant.as_str())
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error counting reviews due for deletion: {:?}", e);
+            e
+        })
+    }
+
+    async fn delete_reviews_created_before(
+        &self,
+        before: chrono::NaiveDateTime,
+        limit: i64,
+    ) -> SqlxResult<u64> {
+        sqlx::query(
+            r#"
+            DELETE FROM reviews
+            WHERE review_id IN (
+                SELECT review_id FROM reviews
+                WHERE review_creation_date < ?1 AND tenant_id = ?3
+                LIMIT ?2
+            )
+            "#,
+        )
+        .bind(before)
+        .bind(limit)
+        .bind(self.tenant.as_str())
+        .execute(&self.pool)
+        .await
+        .map(|result| result.rows_affected())
+        .map_err(|e| {
+            error!("Error deleting reviews written before {}: {:?}", before, e);
+            e
+        })
+    }
+
+    async fn count_inactive_customers(&self, before: chrono::NaiveDateTime) -> SqlxResult<i64> {
+        sqlx::query_scalar::<_, i64>(&format!(
+            r#"
+            SELECT COUNT(*) FROM customers c
+            WHERE c.customer_city <> 'anonymized' AND c.tenant_id = ?2
+              AND {CUSTOMER_LAST_ACTIVITY} < ?1
+            "#
+        ))
+        .bind(before)
+        .bind(self.tenant.as_str())
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error counting inactive customers: {:?}", e);
+            e
+        })
+    }
+
+    async fn find_inactive_customers(
+        &self,
+        before: chrono::NaiveDateTime,
+        limit: i64,
+    ) -> SqlxResult<Vec<CustomerId>> {
+        sqlx::query_scalar::<_, CustomerId>(&format!(
+            r#"
+            SELECT c.customer_id FROM customers c
+            WHERE c.customer_city <> 'anonymized' AND c.tenant_id = ?3
+              AND {CUSTOMER_LAST_ACTIVITY} < ?1
+            ORDER BY c.customer_id
+            LIMIT ?2
+            "#
+        ))
+        .bind(before)
+        .bind(limit)
+        .bind(self.tenant.as_str())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Error finding inactive customers: {:?}", e);
+            e
+        })
+    }
+}
@@ -0,0 +1,262 @@
+//! Publishers for the change stream: every entity write recorded in the audit log, sent to
+//! Kafka (`kafka` feature) or NATS (`nats` feature) as JSON or Avro.
+
+use domain::models::ChangeEvent;
+
+/// Avro schema of an encoded change. `diff` is the audit log's JSON diff as text. `tenant_id`
+/// comes last with a default, so readers on this schema still resolve changes written before
+/// it was added.
+pub const CHANGE_EVENT_AVRO_SCHEMA: &str = r#"{"type":"record","name":"ChangeEvent","namespace":"brazilian_ecommerce","fields":[{"name":"entity_type","type":"string"},{"name":"entity_id","type":"string"},{"name":"action","type":"string"},{"name":"actor","type":"string"},{"name":"diff","type":"string"},{"name":"occurred_at","type":{"type":"long","logicalType":"timestamp-micros"}},{"name":"tenant_id","type":"string","default":"default"}]}"#;
+
+/// How changes are encoded on the wire.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ChangeFormat {
+    #[default]
+    Json,
+    /// Binary Avro datums of [`CHANGE_EVENT_AVRO_SCHEMA`], without a container header.
+    Avro,
+}
+
+impl ChangeFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ChangeFormat::Json => "application/json",
+            ChangeFormat::Avro => "avro/binary",
+        }
+    }
+
+    pub fn encode(&self, change: &ChangeEvent) -> Vec<u8> {
+        match self {
+            ChangeFormat::Json => serde_json::to_vec(change).unwrap_or_default(),
+            ChangeFormat::Avro => {
+                let mut out = Vec::new();
+                avro_string(&mut out, change.entity_type);
+                avro_string(&mut out, &change.entity_id);
+                avro_string(&mut out, change.action.as_str());
+                avro_string(&mut out, &change.actor);
+                avro_string(&mut out, &change.diff.to_string());
+                avro_long(&mut out, change.occurred_at.timestamp_micros());
+                avro_string(&mut out, change.tenant_id.as_str());
+                out
+            }
+        }
+    }
+}
+
+impl std::str::FromStr for ChangeFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_lowercase().as_str() {
+            "json" => Ok(ChangeFormat::Json),
+            "avro" => Ok(ChangeFormat::Avro),
+            other => Err(format!("unknown change format '{}'", other)),
+        }
+    }
+}
+
+/// Zig-zag variable-length encoding, as Avro writes `int` and `long`.
+fn avro_long(out: &mut Vec<u8>, value: i64) {
+    let mut n = ((value << 1) ^ (value >> 63)) as u64;
+    while n >= 0x80 {
+        out.push((n as u8) | 0x80);
+        n >>= 7;
+    }
+    out.push(n as u8);
+}
+
+fn avro_string(out: &mut Vec<u8>, value: &str) {
+    avro_long(out, value.len() as i64);
+    out.extend_from_slice(value.as_bytes());
+}
+
+/// Partitioning key: changes to one entity keep their order.
+#[cfg(feature = "kafka")]
+fn change_key(change: &ChangeEvent) -> String {
+    format!("{}:{}", change.entity_type, change.entity_id)
+}
+
+#[cfg(feature = "kafka")]
+pub use kafka::KafkaChangePublisher;
+
+#[cfg(feature = "kafka")]
+mod kafka {
+    use async_trait::async_trait;
+    use rdkafka::ClientConfig;
+    use rdkafka::message::{Header, OwnedHeaders};
+    use rdkafka::producer::{FutureProducer, FutureRecord};
+    use std::time::Duration;
+
+    use domain::events::ChangePublisher;
+    use domain::models::ChangeEvent;
+
+    use super::{ChangeFormat, change_key};
+
+    /// How long a change may wait in the producer queue when it is full.
+    const QUEUE_TIMEOUT: Duration = Duration::from_secs(5);
+
+    /// [`ChangePublisher`] producing to a Kafka topic, keyed by `{entity_type}:{entity_id}`.
+    pub struct KafkaChangePublisher {
+        producer: FutureProducer,
+        topic: String,
+        format: ChangeFormat,
+    }
+
+    impl KafkaChangePublisher {
+        /// `brokers` is the comma-separated bootstrap server list. The producer connects
+        /// lazily, so an unreachable cluster shows up as failed publishes.
+        pub fn connect(brokers: &str, topic: String, format: ChangeFormat) -> Result<Self, String> {
+            let producer = ClientConfig::new()
+                .set("bootstrap.servers", brokers)
+                .set("message.timeout.ms", "30000")
+                .create()
+                .map_err(|e| e.to_string())?;
+            Ok(Self {
+                producer,
+                topic,
+                format,
+            })
+        }
+    }
+
+    #[async_trait]
+    impl ChangePublisher for KafkaChangePublisher {
+        async fn publish(&self, change: &ChangeEvent) -> Result<(), String> {
+            let key = change_key(change);
+            let payload = self.format.encode(change);
+            let record = FutureRecord::to(&self.topic)
+                .key(&key)
+                .payload(&payload)
+                .headers(OwnedHeaders::new().insert(Header {
+                    key: "content-type",
+                    value: Some(self.format.content_type()),
+                }));
+            self.producer
+                .send(record, QUEUE_TIMEOUT)
+                .await
+                .map(|_| ())
+                .map_err(|(e, _)| e.to_string())
+        }
+    }
+}
+
+#[cfg(feature = "nats")]
+pub use nats::NatsChangePublisher;
+
+#[cfg(feature = "nats")]
+mod nats {
+    use async_nats::HeaderMap;
+    use async_trait::async_trait;
+
+    use domain::events::ChangePublisher;
+    use domain::models::ChangeEvent;
+
+    use super::ChangeFormat;
+
+    /// [`ChangePublisher`] publishing to the NATS subject `{subject_prefix}.{entity_type}`.
+    pub struct NatsChangePublisher {
+        client: async_nats::Client,
+        subject_prefix: String,
+        format: ChangeFormat,
+    }
+
+    impl NatsChangePublisher {
+        pub async fn connect(
+            url: &str,
+            subject_prefix: String,
+            format: ChangeFormat,
+        ) -> Result<Self, String> {
+            let client = async_nats::connect(url).await.map_err(|e| e.to_string())?;
+            Ok(Self {
+                client,
+                subject_prefix,
+                format,
+            })
+        }
+    }
+
+    #[async_trait]
+    impl ChangePublisher for NatsChangePublisher {
+        async fn publish(&self, change: &ChangeEvent) -> Result<(), String> {
+            let mut headers = HeaderMap::new();
+            headers.insert("Content-Type", self.format.content_type());
+            self.client
+                .publish_with_headers(
+                    format!("{}.{}", self.subject_prefix, change.entity_type),
+                    headers,
+                    self.format.encode(change).into(),
+                )
+                .await
+                .map_err(|e| e.to_string())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+    use domain::models::AuditAction;
+
+    fn change() -> ChangeEvent {
+        ChangeEvent {
+            tenant_id: "magalu".parse().unwrap(),
+            entity_type: "customer",
+            entity_id: "c1".to_string(),
+            action: AuditAction::Update,
+            actor: "ops".to_string(),
+            diff: serde_json::json!({ "customer_city": { "from": "a", "to": "b" } }),
+            occurred_at: Utc.timestamp_micros(1).unwrap(),
+        }
+    }
+
+    #[test]
+    fn writes_longs_as_zig_zag_varints() {
+        let encode = |value| {
+            let mut out = Vec::new();
+            avro_long(&mut out, value);
+            out
+        };
+        assert_eq!(encode(0), [0x00]);
+        assert_eq!(encode(-1), [0x01]);
+        assert_eq!(encode(1), [0x02]);
+        assert_eq!(encode(-64), [0x7f]);
+        assert_eq!(encode(64), [0x80, 0x01]);
+    }
+
+    #[test]
+    fn encodes_avro_fields_in_schema_order() {
+        let diff = change().diff.to_string();
+        let mut expected = vec![16];
+        expected.extend_from_slice(b"customer");
+        expected.push(4);
+        expected.extend_from_slice(b"c1");
+        expected.push(12);
+        expected.extend_from_slice(b"update");
+        expected.push(6);
+        expected.extend_from_slice(b"ops");
+        avro_long(&mut expected, diff.len() as i64);
+        expected.extend_from_slice(diff.as_bytes());
+        expected.push(2);
+        expected.push(12);
+        expected.extend_from_slice(b"magalu");
+
+        assert_eq!(ChangeFormat::Avro.encode(&change()), expected);
+    }
+
+    #[test]
+    fn encodes_json_with_the_tenant() {
+        let json: serde_json::Value =
+            serde_json::from_slice(&ChangeFormat::Json.encode(&change())).unwrap();
+        assert_eq!(json["tenant_id"], "magalu");
+        assert_eq!(json["action"], "update");
+        assert_eq!(json["diff"]["customer_city"]["to"], "b");
+    }
+
+    #[test]
+    fn parses_formats() {
+        assert_eq!(" AVRO ".parse(), Ok(ChangeFormat::Avro));
+        assert_eq!("json".parse(), Ok(ChangeFormat::Json));
+        assert!("protobuf".parse::<ChangeFormat>().is_err());
+    }
+}
@@ -0,0 +1,3 @@
+-- Revert: Remove soft delete support from customers table
+DROP INDEX IF EXISTS idx_customers_deleted_at;
+ALTER TABLE customers DROP COLUMN IF EXISTS deleted_at;
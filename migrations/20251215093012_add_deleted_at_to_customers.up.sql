@@ -0,0 +1,4 @@
+-- Migration: Add soft delete support to customers table
+ALTER TABLE customers ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMP;
+
+CREATE INDEX idx_customers_deleted_at ON customers(deleted_at);
@@ -0,0 +1,3 @@
+-- Revert: Drop the Portuguese sort collation
+-- unaccent is left installed, as it may have been there before and other schemas may use it.
+DROP COLLATION IF EXISTS pt_br_natural;
@@ -0,0 +1,21 @@
+-- Migration: Create Portuguese sort collation and unaccent extension
+-- Both are optional: servers built without ICU or contrib keep the default collation.
+DO $$
+BEGIN
+    CREATE COLLATION IF NOT EXISTS pt_br_natural (
+        provider = icu,
+        locale = 'pt-BR-u-ks-level1',
+        deterministic = false
+    );
+EXCEPTION WHEN OTHERS THEN
+    RAISE NOTICE 'ICU collation pt_br_natural not created: %', SQLERRM;
+END
+$$;
+
+DO $$
+BEGIN
+    CREATE EXTENSION IF NOT EXISTS unaccent;
+EXCEPTION WHEN OTHERS THEN
+    RAISE NOTICE 'unaccent extension not created: %', SQLERRM;
+END
+$$;
@@ -0,0 +1,3 @@
+-- Revert: Drop product_embeddings table
+-- The vector extension is left installed, as it may have been there before.
+DROP TABLE IF EXISTS product_embeddings;
@@ -0,0 +1,27 @@
+-- Migration: Create product_embeddings table (requires pgvector)
+-- Skipped with a notice when the vector extension is not installed on the server.
+DO $$
+BEGIN
+    CREATE EXTENSION IF NOT EXISTS vector;
+
+    CREATE TABLE IF NOT EXISTS product_embeddings (
+        product_id VARCHAR(32) PRIMARY KEY,
+        embedding vector(64) NOT NULL,
+        updated_at TIMESTAMP NOT NULL DEFAULT NOW(),
+        CONSTRAINT fk_product_embeddings
+            FOREIGN KEY (product_id)
+            REFERENCES products(product_id)
+            ON DELETE CASCADE
+            ON UPDATE NO ACTION
+    );
+
+    BEGIN
+        CREATE INDEX IF NOT EXISTS idx_product_embeddings_embedding
+            ON product_embeddings USING hnsw (embedding vector_cosine_ops);
+    EXCEPTION WHEN OTHERS THEN
+        RAISE NOTICE 'HNSW index not created: %', SQLERRM;
+    END;
+EXCEPTION WHEN OTHERS THEN
+    RAISE NOTICE 'pgvector not available, product similarity disabled: %', SQLERRM;
+END
+$$;
@@ -0,0 +1,3 @@
+-- Revert: Drop stock_locations and location_stock tables
+DROP TABLE IF EXISTS location_stock;
+DROP TABLE IF EXISTS stock_locations;
@@ -0,0 +1,35 @@
+-- Migration: Create stock_locations and location_stock tables
+CREATE TABLE IF NOT EXISTS stock_locations (
+    location_id BIGSERIAL PRIMARY KEY,
+    seller_id VARCHAR(32) NOT NULL,
+    name VARCHAR(100) NOT NULL,
+    zip_code_prefix VARCHAR(10) NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    CONSTRAINT fk_seller_stock_locations
+        FOREIGN KEY (seller_id)
+        REFERENCES sellers(seller_id)
+        ON DELETE NO ACTION
+        ON UPDATE NO ACTION
+);
+
+CREATE INDEX idx_stock_locations_seller_id ON stock_locations(seller_id);
+
+CREATE TABLE IF NOT EXISTS location_stock (
+    location_id BIGINT NOT NULL,
+    product_id VARCHAR(32) NOT NULL,
+    quantity INTEGER NOT NULL CHECK (quantity >= 0),
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (location_id, product_id),
+    CONSTRAINT fk_location_location_stock
+        FOREIGN KEY (location_id)
+        REFERENCES stock_locations(location_id)
+        ON DELETE CASCADE
+        ON UPDATE NO ACTION,
+    CONSTRAINT fk_product_location_stock
+        FOREIGN KEY (product_id)
+        REFERENCES products(product_id)
+        ON DELETE NO ACTION
+        ON UPDATE NO ACTION
+);
+
+CREATE INDEX idx_location_stock_product_id ON location_stock(product_id);
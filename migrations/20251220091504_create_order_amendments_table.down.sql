@@ -0,0 +1,3 @@
+-- Revert: Drop order_amendments table and the shipping destination override
+DROP TABLE IF EXISTS order_amendments;
+ALTER TABLE orders DROP COLUMN IF EXISTS shipping_zip_code_prefix;
@@ -0,0 +1,21 @@
+-- Migration: Add shipping destination override to orders and create order_amendments table
+ALTER TABLE orders ADD COLUMN IF NOT EXISTS shipping_zip_code_prefix VARCHAR(10);
+
+CREATE TABLE IF NOT EXISTS order_amendments (
+    amendment_id BIGSERIAL PRIMARY KEY,
+    order_id VARCHAR(32) NOT NULL,
+    actor VARCHAR(100) NOT NULL,
+    changes JSONB NOT NULL,
+    previous_freight DECIMAL(10, 2) NOT NULL,
+    new_freight DECIMAL(10, 2) NOT NULL,
+    previous_tax DECIMAL(10, 2) NOT NULL,
+    new_tax DECIMAL(10, 2) NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    CONSTRAINT fk_order_order_amendments
+        FOREIGN KEY (order_id)
+        REFERENCES orders(order_id)
+        ON DELETE NO ACTION
+        ON UPDATE NO ACTION
+);
+
+CREATE INDEX idx_order_amendments_order_id ON order_amendments(order_id);
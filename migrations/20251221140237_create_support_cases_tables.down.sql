@@ -0,0 +1,3 @@
+-- Revert: Drop support_case_messages and support_cases tables
+DROP TABLE IF EXISTS support_case_messages;
+DROP TABLE IF EXISTS support_cases;
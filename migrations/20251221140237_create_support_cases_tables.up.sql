@@ -0,0 +1,51 @@
+-- Migration: Create support_cases and support_case_messages tables
+CREATE TABLE IF NOT EXISTS support_cases (
+    case_id BIGSERIAL PRIMARY KEY,
+    order_id VARCHAR(32) NOT NULL,
+    customer_id VARCHAR(32) NOT NULL,
+    category VARCHAR(32) NOT NULL
+        CHECK (category IN (
+            'delivery_delay', 'damaged_item', 'wrong_item', 'missing_item',
+            'refund', 'payment', 'other'
+        )),
+    status VARCHAR(20) NOT NULL DEFAULT 'open'
+        CHECK (status IN ('open', 'pending_customer', 'resolved', 'closed')),
+    subject VARCHAR(200) NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    first_response_due_at TIMESTAMP NOT NULL,
+    resolution_due_at TIMESTAMP NOT NULL,
+    first_responded_at TIMESTAMP,
+    resolved_at TIMESTAMP,
+    CONSTRAINT fk_order_support_cases
+        FOREIGN KEY (order_id)
+        REFERENCES orders(order_id)
+        ON DELETE NO ACTION
+        ON UPDATE NO ACTION,
+    CONSTRAINT fk_customer_support_cases
+        FOREIGN KEY (customer_id)
+        REFERENCES customers(customer_id)
+        ON DELETE NO ACTION
+        ON UPDATE NO ACTION
+);
+
+CREATE INDEX idx_support_cases_order_id ON support_cases(order_id);
+CREATE INDEX idx_support_cases_customer_id ON support_cases(customer_id);
+CREATE INDEX idx_support_cases_status ON support_cases(status);
+CREATE INDEX idx_support_cases_category ON support_cases(category);
+
+CREATE TABLE IF NOT EXISTS support_case_messages (
+    message_id BIGSERIAL PRIMARY KEY,
+    case_id BIGINT NOT NULL,
+    author_type VARCHAR(16) NOT NULL CHECK (author_type IN ('customer', 'agent')),
+    author VARCHAR(100) NOT NULL,
+    body TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    CONSTRAINT fk_case_support_case_messages
+        FOREIGN KEY (case_id)
+        REFERENCES support_cases(case_id)
+        ON DELETE CASCADE
+        ON UPDATE NO ACTION
+);
+
+CREATE INDEX idx_support_case_messages_case_id ON support_case_messages(case_id);
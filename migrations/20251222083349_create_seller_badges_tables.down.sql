@@ -0,0 +1,3 @@
+-- Revert: Drop seller_badges and seller_badge_thresholds tables
+DROP TABLE IF EXISTS seller_badges;
+DROP TABLE IF EXISTS seller_badge_thresholds;
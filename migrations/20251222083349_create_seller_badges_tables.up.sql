@@ -0,0 +1,41 @@
+-- Migration: Create seller_badge_thresholds and seller_badges tables
+-- Thresholds live in a table so they can be tuned without a deploy; the badge job reads them.
+CREATE TABLE IF NOT EXISTS seller_badge_thresholds (
+    badge VARCHAR(32) PRIMARY KEY,
+    description TEXT NOT NULL,
+    metric VARCHAR(32) NOT NULL
+        CHECK (metric IN ('avg_handling_hours', 'avg_review_score', 'order_count')),
+    comparison VARCHAR(3) NOT NULL CHECK (comparison IN ('lte', 'gte')),
+    threshold NUMERIC(10, 2) NOT NULL,
+    min_sample INTEGER NOT NULL DEFAULT 0
+);
+
+INSERT INTO seller_badge_thresholds (badge, description, metric, comparison, threshold, min_sample)
+VALUES
+    ('fast_shipper', 'Hands orders to the carrier within 48 hours of approval on average',
+        'avg_handling_hours', 'lte', 48, 20),
+    ('top_rated', 'Average review score of 4.5 or more',
+        'avg_review_score', 'gte', 4.5, 20),
+    ('high_volume', 'At least 500 orders sold',
+        'order_count', 'gte', 500, 0)
+ON CONFLICT (badge) DO NOTHING;
+
+CREATE TABLE IF NOT EXISTS seller_badges (
+    seller_id VARCHAR(32) NOT NULL,
+    badge VARCHAR(32) NOT NULL,
+    metric_value NUMERIC(12, 2) NOT NULL,
+    computed_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (seller_id, badge),
+    CONSTRAINT fk_seller_seller_badges
+        FOREIGN KEY (seller_id)
+        REFERENCES sellers(seller_id)
+        ON DELETE CASCADE
+        ON UPDATE NO ACTION,
+    CONSTRAINT fk_threshold_seller_badges
+        FOREIGN KEY (badge)
+        REFERENCES seller_badge_thresholds(badge)
+        ON DELETE CASCADE
+        ON UPDATE NO ACTION
+);
+
+CREATE INDEX idx_seller_badges_badge ON seller_badges(badge);
@@ -0,0 +1,6 @@
+-- Revert: Stop versioning order status changes
+DROP TRIGGER IF EXISTS trg_orders_status_notify ON orders;
+DROP TRIGGER IF EXISTS trg_orders_status_version ON orders;
+DROP FUNCTION IF EXISTS notify_order_status_change();
+DROP FUNCTION IF EXISTS bump_order_status_version();
+ALTER TABLE orders DROP COLUMN IF EXISTS status_version;
@@ -0,0 +1,39 @@
+-- Migration: Version order status changes and notify listeners
+-- Every status change bumps status_version and is published on the order_status_changed
+-- channel, so long-poll and streaming clients see updates no matter which process made them.
+ALTER TABLE orders ADD COLUMN IF NOT EXISTS status_version INTEGER NOT NULL DEFAULT 1;
+
+CREATE OR REPLACE FUNCTION bump_order_status_version() RETURNS trigger AS $$
+BEGIN
+    NEW.status_version := OLD.status_version + 1;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION notify_order_status_change() RETURNS trigger AS $$
+BEGIN
+    PERFORM pg_notify(
+        'order_status_changed',
+        json_build_object(
+            'order_id', NEW.order_id,
+            'order_status', NEW.order_status,
+            'status_version', NEW.status_version
+        )::text
+    );
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trg_orders_status_version ON orders;
+CREATE TRIGGER trg_orders_status_version
+    BEFORE UPDATE OF order_status ON orders
+    FOR EACH ROW
+    WHEN (OLD.order_status IS DISTINCT FROM NEW.order_status)
+    EXECUTE FUNCTION bump_order_status_version();
+
+DROP TRIGGER IF EXISTS trg_orders_status_notify ON orders;
+CREATE TRIGGER trg_orders_status_notify
+    AFTER UPDATE OF order_status ON orders
+    FOR EACH ROW
+    WHEN (OLD.order_status IS DISTINCT FROM NEW.order_status)
+    EXECUTE FUNCTION notify_order_status_change();
@@ -0,0 +1,7 @@
+-- Revert: Drop fuzzy city search helpers and trigram indexes
+-- pg_trgm is left installed, as it may have been there before.
+DROP INDEX IF EXISTS idx_sellers_city_trgm;
+DROP INDEX IF EXISTS idx_customers_city_trgm;
+DROP FUNCTION IF EXISTS fuzzy_similarity(TEXT, TEXT);
+DROP FUNCTION IF EXISTS fuzzy_matches(TEXT, TEXT);
+DROP FUNCTION IF EXISTS search_normalize(TEXT);
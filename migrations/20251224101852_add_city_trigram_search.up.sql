@@ -0,0 +1,59 @@
+-- Migration: Add fuzzy city search helpers and trigram indexes
+-- pg_trgm and unaccent are both optional: without them search falls back to a
+-- case-insensitive substring match, and no trigram indexes are created.
+DO $$
+BEGIN
+    CREATE EXTENSION IF NOT EXISTS pg_trgm;
+EXCEPTION WHEN OTHERS THEN
+    RAISE NOTICE 'pg_trgm extension not created: %', SQLERRM;
+END
+$$;
+
+-- Declared IMMUTABLE (unaccent itself is only STABLE) so it can back expression indexes.
+DO $$
+BEGIN
+    IF EXISTS (SELECT 1 FROM pg_extension WHERE extname = 'unaccent') THEN
+        CREATE OR REPLACE FUNCTION search_normalize(value TEXT) RETURNS TEXT
+            LANGUAGE sql IMMUTABLE PARALLEL SAFE
+            AS $f$ SELECT lower(unaccent('unaccent'::regdictionary, value)) $f$;
+    ELSE
+        CREATE OR REPLACE FUNCTION search_normalize(value TEXT) RETURNS TEXT
+            LANGUAGE sql IMMUTABLE PARALLEL SAFE
+            AS $f$ SELECT lower(value) $f$;
+    END IF;
+END
+$$;
+
+DO $$
+BEGIN
+    IF EXISTS (SELECT 1 FROM pg_extension WHERE extname = 'pg_trgm') THEN
+        CREATE OR REPLACE FUNCTION fuzzy_matches(value TEXT, query TEXT) RETURNS BOOLEAN
+            LANGUAGE sql STABLE PARALLEL SAFE
+            AS $f$
+                SELECT search_normalize(value) % search_normalize(query)
+                    OR search_normalize(query) <% search_normalize(value)
+                    OR search_normalize(value) LIKE '%' || search_normalize(query) || '%'
+            $f$;
+
+        CREATE OR REPLACE FUNCTION fuzzy_similarity(value TEXT, query TEXT) RETURNS REAL
+            LANGUAGE sql IMMUTABLE PARALLEL SAFE
+            AS $f$ SELECT similarity(search_normalize(value), search_normalize(query)) $f$;
+
+        CREATE INDEX IF NOT EXISTS idx_customers_city_trgm
+            ON customers USING gin (search_normalize(customer_city) gin_trgm_ops);
+        CREATE INDEX IF NOT EXISTS idx_sellers_city_trgm
+            ON sellers USING gin (search_normalize(seller_city) gin_trgm_ops);
+    ELSE
+        CREATE OR REPLACE FUNCTION fuzzy_matches(value TEXT, query TEXT) RETURNS BOOLEAN
+            LANGUAGE sql IMMUTABLE PARALLEL SAFE
+            AS $f$ SELECT search_normalize(value) LIKE '%' || search_normalize(query) || '%' $f$;
+
+        CREATE OR REPLACE FUNCTION fuzzy_similarity(value TEXT, query TEXT) RETURNS REAL
+            LANGUAGE sql IMMUTABLE PARALLEL SAFE
+            AS $f$
+                SELECT CASE WHEN search_normalize(value) = search_normalize(query)
+                    THEN 1.0 ELSE 0.0 END::REAL
+            $f$;
+    END IF;
+END
+$$;
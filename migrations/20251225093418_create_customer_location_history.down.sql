@@ -0,0 +1,5 @@
+-- Revert: Stop tracking customer location changes
+DROP TRIGGER IF EXISTS trg_customers_location_update ON customers;
+DROP TRIGGER IF EXISTS trg_customers_location_insert ON customers;
+DROP FUNCTION IF EXISTS record_customer_location();
+DROP TABLE IF EXISTS customer_location_history;
@@ -0,0 +1,58 @@
+-- Migration: Track customer location changes with validity periods
+-- One row per address a customer has had. The current address has an open valid_to; the first
+-- known address has an open valid_from, since customers imported from the dataset carry no
+-- move-in date. An order placed at time t belongs to the row where
+-- (valid_from IS NULL OR valid_from <= t) AND (valid_to IS NULL OR t < valid_to).
+CREATE TABLE IF NOT EXISTS customer_location_history (
+    history_id BIGSERIAL PRIMARY KEY,
+    customer_id VARCHAR(32) NOT NULL REFERENCES customers(customer_id) ON DELETE CASCADE,
+    customer_zip_code_prefix VARCHAR(10) NOT NULL,
+    customer_city VARCHAR(100) NOT NULL,
+    customer_state VARCHAR(2) NOT NULL,
+    valid_from TIMESTAMP,
+    valid_to TIMESTAMP,
+    CONSTRAINT chk_customer_location_period CHECK (valid_from IS NULL OR valid_to IS NULL OR valid_from <= valid_to)
+);
+
+CREATE INDEX idx_customer_location_history_customer ON customer_location_history(customer_id, valid_from);
+CREATE UNIQUE INDEX idx_customer_location_history_current
+    ON customer_location_history(customer_id) WHERE valid_to IS NULL;
+
+INSERT INTO customer_location_history (customer_id, customer_zip_code_prefix, customer_city, customer_state)
+SELECT customer_id, customer_zip_code_prefix, customer_city, customer_state
+FROM customers;
+
+CREATE OR REPLACE FUNCTION record_customer_location() RETURNS trigger AS $$
+BEGIN
+    IF TG_OP = 'UPDATE' THEN
+        UPDATE customer_location_history
+        SET valid_to = LOCALTIMESTAMP
+        WHERE customer_id = NEW.customer_id AND valid_to IS NULL;
+    END IF;
+
+    INSERT INTO customer_location_history (
+        customer_id, customer_zip_code_prefix, customer_city, customer_state, valid_from
+    )
+    VALUES (
+        NEW.customer_id, NEW.customer_zip_code_prefix, NEW.customer_city, NEW.customer_state,
+        CASE WHEN TG_OP = 'UPDATE' THEN LOCALTIMESTAMP END
+    );
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trg_customers_location_insert ON customers;
+CREATE TRIGGER trg_customers_location_insert
+    AFTER INSERT ON customers
+    FOR EACH ROW
+    EXECUTE FUNCTION record_customer_location();
+
+DROP TRIGGER IF EXISTS trg_customers_location_update ON customers;
+CREATE TRIGGER trg_customers_location_update
+    AFTER UPDATE OF customer_zip_code_prefix, customer_city, customer_state ON customers
+    FOR EACH ROW
+    WHEN (
+        (OLD.customer_zip_code_prefix, OLD.customer_city, OLD.customer_state)
+        IS DISTINCT FROM (NEW.customer_zip_code_prefix, NEW.customer_city, NEW.customer_state)
+    )
+    EXECUTE FUNCTION record_customer_location();
@@ -0,0 +1,7 @@
+-- Revert: Remove canonical city names from customers and sellers
+DROP INDEX IF EXISTS idx_sellers_canonical_city;
+ALTER TABLE sellers DROP COLUMN IF EXISTS canonical_city;
+DROP INDEX IF EXISTS idx_customers_canonical_city;
+ALTER TABLE customers DROP COLUMN IF EXISTS canonical_city;
+DROP FUNCTION IF EXISTS resolve_city_alias(TEXT);
+DROP TABLE IF EXISTS city_aliases;
@@ -0,0 +1,57 @@
+-- Migration: Add canonical city names to customers and sellers
+-- canonical_city is the city trimmed, lowercased and accent-folded, then mapped through
+-- city_aliases. The services compute the folded form on write; resolve_city_alias() applies
+-- the alias table so new aliases only need a row here, not a deploy.
+CREATE TABLE IF NOT EXISTS city_aliases (
+    alias VARCHAR(100) PRIMARY KEY,
+    canonical_city VARCHAR(100) NOT NULL
+);
+
+INSERT INTO city_aliases (alias, canonical_city) VALUES
+    ('sp', 'sao paulo'),
+    ('sampa', 'sao paulo'),
+    ('sao paulo sp', 'sao paulo'),
+    ('sao paulo - sp', 'sao paulo'),
+    ('sao paulo / sao paulo', 'sao paulo'),
+    ('sao paulo, sao paulo', 'sao paulo'),
+    ('rj', 'rio de janeiro'),
+    ('rio de janeiro, rio de janeiro, brasil', 'rio de janeiro'),
+    ('rio de janeiro / rio de janeiro', 'rio de janeiro'),
+    ('bh', 'belo horizonte'),
+    ('belo horizont', 'belo horizonte'),
+    ('poa', 'porto alegre'),
+    ('floripa', 'florianopolis'),
+    ('brasilia df', 'brasilia'),
+    ('sbc', 'sao bernardo do campo'),
+    ('sao bernardo do capo', 'sao bernardo do campo'),
+    ('santo andre/sao paulo', 'santo andre'),
+    ('ribeirao preto / sao paulo', 'ribeirao preto'),
+    ('mogi das cruzes / sp', 'mogi das cruzes'),
+    ('sao jose dos pinhais/pr', 'sao jose dos pinhais'),
+    ('arraial d''ajuda (porto seguro)', 'arraial d''ajuda')
+ON CONFLICT (alias) DO NOTHING;
+
+CREATE OR REPLACE FUNCTION resolve_city_alias(folded TEXT) RETURNS TEXT
+    LANGUAGE sql STABLE PARALLEL SAFE
+    AS $$ SELECT COALESCE((SELECT canonical_city FROM city_aliases WHERE alias = folded), folded) $$;
+
+-- Same folding as cities::fold_city, used once to backfill existing rows.
+CREATE OR REPLACE FUNCTION pg_temp.fold_city(city TEXT) RETURNS TEXT
+    LANGUAGE sql IMMUTABLE
+    AS $$
+        SELECT lower(translate(
+            regexp_replace(btrim(city), '\s+', ' ', 'g'),
+            'áàâãäéèêëíìîïóòôõöúùûüçñÁÀÂÃÄÉÈÊËÍÌÎÏÓÒÔÕÖÚÙÛÜÇÑ',
+            'aaaaaeeeeiiiiooooouuuucnaaaaaeeeeiiiiooooouuuucn'
+        ))
+    $$;
+
+ALTER TABLE customers ADD COLUMN IF NOT EXISTS canonical_city VARCHAR(100);
+UPDATE customers SET canonical_city = resolve_city_alias(pg_temp.fold_city(customer_city));
+ALTER TABLE customers ALTER COLUMN canonical_city SET NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_customers_canonical_city ON customers(canonical_city);
+
+ALTER TABLE sellers ADD COLUMN IF NOT EXISTS canonical_city VARCHAR(100);
+UPDATE sellers SET canonical_city = resolve_city_alias(pg_temp.fold_city(seller_city));
+ALTER TABLE sellers ALTER COLUMN canonical_city SET NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_sellers_canonical_city ON sellers(canonical_city);
@@ -0,0 +1,3 @@
+-- Revert: Drop import_batch_rows and import_batches tables
+DROP TABLE IF EXISTS import_batch_rows;
+DROP TABLE IF EXISTS import_batches;
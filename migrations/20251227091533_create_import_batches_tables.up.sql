@@ -0,0 +1,29 @@
+-- Migration: Create import_batches and import_batch_rows tables
+-- Every CSV import run is a batch; import_batch_rows lists the primary keys it inserted so the
+-- batch can be rolled back. Imports only ever insert, so rolling back means deleting those rows.
+CREATE TABLE IF NOT EXISTS import_batches (
+    batch_id BIGSERIAL PRIMARY KEY,
+    dataset VARCHAR(20) NOT NULL
+        CHECK (dataset IN ('customers', 'sellers', 'orders', 'products')),
+    source VARCHAR(500) NOT NULL,
+    actor VARCHAR(100) NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'running'
+        CHECK (status IN ('running', 'completed', 'failed', 'rolled_back')),
+    success_count INTEGER NOT NULL DEFAULT 0,
+    error_count INTEGER NOT NULL DEFAULT 0,
+    started_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    finished_at TIMESTAMP,
+    rolled_back_at TIMESTAMP
+);
+
+CREATE INDEX idx_import_batches_started_at ON import_batches(started_at);
+
+CREATE TABLE IF NOT EXISTS import_batch_rows (
+    batch_id BIGINT NOT NULL,
+    entity_id VARCHAR(32) NOT NULL,
+    PRIMARY KEY (batch_id, entity_id),
+    CONSTRAINT fk_import_batch_rows_batch
+        FOREIGN KEY (batch_id)
+        REFERENCES import_batches(batch_id)
+        ON DELETE CASCADE
+);
@@ -0,0 +1,3 @@
+-- Revert: Lift the order_status and payment_type restrictions
+ALTER TABLE payments DROP CONSTRAINT IF EXISTS chk_payments_type;
+ALTER TABLE orders DROP CONSTRAINT IF EXISTS chk_orders_status;
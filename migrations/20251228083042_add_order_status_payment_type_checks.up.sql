@@ -0,0 +1,12 @@
+-- Migration: Restrict order_status and payment_type to the values of their Rust enums
+-- NOT VALID: enforced for every new write without failing on rows loaded before the check existed.
+ALTER TABLE orders DROP CONSTRAINT IF EXISTS chk_orders_status;
+ALTER TABLE orders ADD CONSTRAINT chk_orders_status
+    CHECK (order_status IN (
+        'created', 'approved', 'invoiced', 'processing',
+        'shipped', 'delivered', 'canceled', 'unavailable'
+    )) NOT VALID;
+
+ALTER TABLE payments DROP CONSTRAINT IF EXISTS chk_payments_type;
+ALTER TABLE payments ADD CONSTRAINT chk_payments_type
+    CHECK (payment_type IN ('credit_card', 'debit_card', 'boleto', 'voucher', 'not_defined')) NOT VALID;
@@ -0,0 +1,10 @@
+-- Revert: Drop the dashboard counters and the triggers that maintain them
+DROP TRIGGER IF EXISTS trg_import_batches_stats ON import_batches;
+DROP TRIGGER IF EXISTS trg_order_items_stats ON order_items;
+DROP TRIGGER IF EXISTS trg_orders_stats_update ON orders;
+DROP TRIGGER IF EXISTS trg_orders_stats ON orders;
+DROP FUNCTION IF EXISTS count_import_stats();
+DROP FUNCTION IF EXISTS count_order_item_stats();
+DROP FUNCTION IF EXISTS count_order_stats();
+DROP FUNCTION IF EXISTS bump_stats(DATE, BIGINT, DECIMAL, INTEGER);
+DROP TABLE IF EXISTS stats;
@@ -0,0 +1,123 @@
+-- Migration: Pre-aggregated dashboard counters
+-- One row per day. Triggers on orders, order_items and import_batches adjust the counters in
+-- the same transaction as the write, so they never drift from the underlying tables and
+-- concurrent writers serialize on the row for that day instead of rescanning orders.
+--   orders_count   orders whose purchase timestamp falls on the day
+--   revenue        item price + freight of those orders
+--   active_imports running import batches that started on the day (sum over all rows for the
+--                  total, so a batch spanning midnight is still counted once)
+CREATE TABLE IF NOT EXISTS stats (
+    stat_date DATE PRIMARY KEY,
+    orders_count BIGINT NOT NULL DEFAULT 0,
+    revenue DECIMAL(14, 2) NOT NULL DEFAULT 0,
+    active_imports INTEGER NOT NULL DEFAULT 0,
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE OR REPLACE FUNCTION bump_stats(
+    day DATE,
+    orders_delta BIGINT,
+    revenue_delta DECIMAL,
+    imports_delta INTEGER
+) RETURNS void AS $$
+    INSERT INTO stats (stat_date, orders_count, revenue, active_imports, updated_at)
+    VALUES (day, orders_delta, revenue_delta, imports_delta, NOW())
+    ON CONFLICT (stat_date) DO UPDATE SET
+        orders_count = stats.orders_count + EXCLUDED.orders_count,
+        revenue = stats.revenue + EXCLUDED.revenue,
+        active_imports = stats.active_imports + EXCLUDED.active_imports,
+        updated_at = EXCLUDED.updated_at;
+$$ LANGUAGE sql;
+
+CREATE OR REPLACE FUNCTION count_order_stats() RETURNS trigger AS $$
+DECLARE
+    order_revenue DECIMAL;
+BEGIN
+    IF TG_OP IN ('UPDATE', 'DELETE') THEN
+        PERFORM bump_stats(OLD.order_purchase_timestamp::date, -1, 0, 0);
+    END IF;
+    IF TG_OP IN ('INSERT', 'UPDATE') THEN
+        PERFORM bump_stats(NEW.order_purchase_timestamp::date, 1, 0, 0);
+    END IF;
+
+    -- A changed purchase date moves the order's items to the new day.
+    IF TG_OP = 'UPDATE' THEN
+        SELECT COALESCE(SUM(price + freight_value), 0) INTO order_revenue
+        FROM order_items
+        WHERE order_id = NEW.order_id;
+
+        PERFORM bump_stats(OLD.order_purchase_timestamp::date, 0, -order_revenue, 0);
+        PERFORM bump_stats(NEW.order_purchase_timestamp::date, 0, order_revenue, 0);
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION count_order_item_stats() RETURNS trigger AS $$
+BEGIN
+    IF TG_OP IN ('UPDATE', 'DELETE') THEN
+        PERFORM bump_stats(o.order_purchase_timestamp::date, 0, -(OLD.price + OLD.freight_value), 0)
+        FROM orders o
+        WHERE o.order_id = OLD.order_id;
+    END IF;
+    IF TG_OP IN ('INSERT', 'UPDATE') THEN
+        PERFORM bump_stats(o.order_purchase_timestamp::date, 0, NEW.price + NEW.freight_value, 0)
+        FROM orders o
+        WHERE o.order_id = NEW.order_id;
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION count_import_stats() RETURNS trigger AS $$
+BEGIN
+    IF TG_OP IN ('UPDATE', 'DELETE') AND OLD.status = 'running' THEN
+        PERFORM bump_stats(OLD.started_at::date, 0, 0, -1);
+    END IF;
+    IF TG_OP IN ('INSERT', 'UPDATE') AND NEW.status = 'running' THEN
+        PERFORM bump_stats(NEW.started_at::date, 0, 0, 1);
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+-- Triggers go in before the backfill: creating them locks the tables against writes until
+-- this migration commits, so nothing lands between the backfill and the first trigger run.
+DROP TRIGGER IF EXISTS trg_orders_stats ON orders;
+CREATE TRIGGER trg_orders_stats
+    AFTER INSERT OR DELETE ON orders
+    FOR EACH ROW
+    EXECUTE FUNCTION count_order_stats();
+
+DROP TRIGGER IF EXISTS trg_orders_stats_update ON orders;
+CREATE TRIGGER trg_orders_stats_update
+    AFTER UPDATE OF order_purchase_timestamp ON orders
+    FOR EACH ROW
+    WHEN (OLD.order_purchase_timestamp::date IS DISTINCT FROM NEW.order_purchase_timestamp::date)
+    EXECUTE FUNCTION count_order_stats();
+
+DROP TRIGGER IF EXISTS trg_order_items_stats ON order_items;
+CREATE TRIGGER trg_order_items_stats
+    AFTER INSERT OR UPDATE OF price, freight_value, order_id OR DELETE ON order_items
+    FOR EACH ROW
+    EXECUTE FUNCTION count_order_item_stats();
+
+DROP TRIGGER IF EXISTS trg_import_batches_stats ON import_batches;
+CREATE TRIGGER trg_import_batches_stats
+    AFTER INSERT OR UPDATE OF status OR DELETE ON import_batches
+    FOR EACH ROW
+    EXECUTE FUNCTION count_import_stats();
+
+INSERT INTO stats (stat_date, orders_count, revenue)
+SELECT o.order_purchase_timestamp::date, COUNT(*), COALESCE(SUM(i.revenue), 0)
+FROM orders o
+LEFT JOIN (
+    SELECT order_id, SUM(price + freight_value) AS revenue
+    FROM order_items
+    GROUP BY order_id
+) i ON i.order_id = o.order_id
+GROUP BY o.order_purchase_timestamp::date;
+
+SELECT bump_stats(started_at::date, 0, 0, 1)
+FROM import_batches
+WHERE status = 'running';
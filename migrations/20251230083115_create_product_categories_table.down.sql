@@ -0,0 +1,2 @@
+-- Revert: Drop product categories table
+DROP TABLE IF EXISTS product_categories;
@@ -0,0 +1,14 @@
+-- Migration: Create product categories table
+-- Portuguese category names as used in products.product_category_name, with their English
+-- translation. Seeded with every category already in use so existing products are covered.
+CREATE TABLE IF NOT EXISTS product_categories (
+    product_category_name VARCHAR(100) PRIMARY KEY,
+    product_category_name_english VARCHAR(100),
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+INSERT INTO product_categories (product_category_name)
+SELECT DISTINCT product_category_name
+FROM products
+ON CONFLICT (product_category_name) DO NOTHING;
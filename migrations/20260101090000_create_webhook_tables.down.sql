@@ -0,0 +1,9 @@
+-- Revert: Drop webhook tables and the triggers that queue deliveries
+DROP TRIGGER IF EXISTS trg_reviews_webhook_insert ON reviews;
+DROP TRIGGER IF EXISTS trg_orders_webhook_status ON orders;
+DROP TRIGGER IF EXISTS trg_orders_webhook_insert ON orders;
+DROP FUNCTION IF EXISTS queue_review_webhooks();
+DROP FUNCTION IF EXISTS queue_order_webhooks();
+DROP FUNCTION IF EXISTS queue_webhook_deliveries(TEXT, JSONB);
+DROP TABLE IF EXISTS webhook_deliveries;
+DROP TABLE IF EXISTS webhook_subscriptions;
@@ -0,0 +1,101 @@
+-- Migration: Create webhook_subscriptions and webhook_deliveries tables
+-- webhook_deliveries is an outbox: triggers on orders and reviews queue one row per matching
+-- subscription in the same transaction as the write, so events from imports and from other
+-- processes are delivered too. The delivery worker leases due rows by pushing next_attempt_at
+-- forward, and records each attempt's outcome on the row.
+CREATE TABLE IF NOT EXISTS webhook_subscriptions (
+    subscription_id BIGSERIAL PRIMARY KEY,
+    url VARCHAR(2000) NOT NULL,
+    events TEXT[] NOT NULL
+        CHECK (events <@ ARRAY['order.created', 'order.status_changed', 'review.created']),
+    secret VARCHAR(100) NOT NULL,
+    created_by VARCHAR(100) NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS webhook_deliveries (
+    delivery_id BIGSERIAL PRIMARY KEY,
+    subscription_id BIGINT NOT NULL,
+    event VARCHAR(40) NOT NULL,
+    payload JSONB NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'succeeded', 'failed')),
+    attempts INTEGER NOT NULL DEFAULT 0,
+    next_attempt_at TIMESTAMP DEFAULT NOW(),
+    last_status_code INTEGER,
+    last_error TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    delivered_at TIMESTAMP,
+    CONSTRAINT fk_webhook_deliveries_subscription
+        FOREIGN KEY (subscription_id)
+        REFERENCES webhook_subscriptions(subscription_id)
+        ON DELETE CASCADE
+);
+
+CREATE INDEX idx_webhook_deliveries_subscription
+    ON webhook_deliveries(subscription_id, created_at);
+CREATE INDEX idx_webhook_deliveries_due
+    ON webhook_deliveries(next_attempt_at)
+    WHERE status = 'pending';
+
+CREATE OR REPLACE FUNCTION queue_webhook_deliveries(event_name TEXT, payload JSONB)
+RETURNS void AS $$
+    INSERT INTO webhook_deliveries (subscription_id, event, payload)
+    SELECT subscription_id, event_name, payload
+    FROM webhook_subscriptions
+    WHERE event_name = ANY(events);
+$$ LANGUAGE sql;
+
+CREATE OR REPLACE FUNCTION queue_order_webhooks() RETURNS trigger AS $$
+BEGIN
+    IF TG_OP = 'INSERT' THEN
+        PERFORM queue_webhook_deliveries('order.created', jsonb_build_object(
+            'order_id', NEW.order_id,
+            'customer_id', NEW.customer_id,
+            'order_status', NEW.order_status,
+            'order_purchase_timestamp', NEW.order_purchase_timestamp
+        ));
+    ELSE
+        PERFORM queue_webhook_deliveries('order.status_changed', jsonb_build_object(
+            'order_id', NEW.order_id,
+            'previous_status', OLD.order_status,
+            'order_status', NEW.order_status,
+            'status_version', NEW.status_version
+        ));
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION queue_review_webhooks() RETURNS trigger AS $$
+BEGIN
+    PERFORM queue_webhook_deliveries('review.created', jsonb_build_object(
+        'review_id', NEW.review_id,
+        'order_id', NEW.order_id,
+        'review_score', NEW.review_score,
+        'review_comment_title', NEW.review_comment_title,
+        'review_comment_message', NEW.review_comment_message,
+        'review_creation_date', NEW.review_creation_date
+    ));
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trg_orders_webhook_insert ON orders;
+CREATE TRIGGER trg_orders_webhook_insert
+    AFTER INSERT ON orders
+    FOR EACH ROW
+    EXECUTE FUNCTION queue_order_webhooks();
+
+DROP TRIGGER IF EXISTS trg_orders_webhook_status ON orders;
+CREATE TRIGGER trg_orders_webhook_status
+    AFTER UPDATE OF order_status ON orders
+    FOR EACH ROW
+    WHEN (OLD.order_status IS DISTINCT FROM NEW.order_status)
+    EXECUTE FUNCTION queue_order_webhooks();
+
+DROP TRIGGER IF EXISTS trg_reviews_webhook_insert ON reviews;
+CREATE TRIGGER trg_reviews_webhook_insert
+    AFTER INSERT ON reviews
+    FOR EACH ROW
+    EXECUTE FUNCTION queue_review_webhooks();
@@ -0,0 +1,4 @@
+-- Revert: Webhook entity filters and payload templates
+ALTER TABLE webhook_subscriptions
+    DROP COLUMN IF EXISTS payload_template,
+    DROP COLUMN IF EXISTS customer_states;
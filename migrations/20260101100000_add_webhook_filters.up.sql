@@ -0,0 +1,9 @@
+-- Migration: Webhook entity filters and payload templates
+-- customer_states limits a subscription to events of orders placed by customers in those
+-- states (NULL for every state). payload_template picks what a delivery carries: the whole
+-- event (full) or only the ids identifying its entity (slim). Both are applied by the
+-- delivery worker as it sends each delivery.
+ALTER TABLE webhook_subscriptions
+    ADD COLUMN IF NOT EXISTS customer_states TEXT[],
+    ADD COLUMN IF NOT EXISTS payload_template VARCHAR(10) NOT NULL DEFAULT 'full'
+        CHECK (payload_template IN ('full', 'slim'));
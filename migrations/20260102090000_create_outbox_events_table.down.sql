@@ -0,0 +1,78 @@
+-- Revert: Drop the outbox_events table and queue webhook deliveries from triggers again
+-- Events still in the outbox are lost, and payment.created is dropped from subscriptions.
+DROP TRIGGER IF EXISTS trg_reviews_outbox_insert ON reviews;
+DROP TRIGGER IF EXISTS trg_payments_outbox_insert ON payments;
+DROP TRIGGER IF EXISTS trg_orders_outbox_status ON orders;
+DROP TRIGGER IF EXISTS trg_orders_outbox_insert ON orders;
+DROP FUNCTION IF EXISTS record_review_events();
+DROP FUNCTION IF EXISTS record_payment_events();
+DROP FUNCTION IF EXISTS record_order_events();
+DROP FUNCTION IF EXISTS record_outbox_event(TEXT, TEXT, TEXT, JSONB);
+DROP TABLE IF EXISTS outbox_events;
+
+UPDATE webhook_subscriptions SET events = array_remove(events, 'payment.created');
+ALTER TABLE webhook_subscriptions DROP CONSTRAINT IF EXISTS webhook_subscriptions_events_check;
+ALTER TABLE webhook_subscriptions ADD CONSTRAINT webhook_subscriptions_events_check
+    CHECK (events <@ ARRAY['order.created', 'order.status_changed', 'review.created']);
+
+CREATE OR REPLACE FUNCTION queue_webhook_deliveries(event_name TEXT, payload JSONB)
+RETURNS void AS $$
+    INSERT INTO webhook_deliveries (subscription_id, event, payload)
+    SELECT subscription_id, event_name, payload
+    FROM webhook_subscriptions
+    WHERE event_name = ANY(events);
+$$ LANGUAGE sql;
+
+CREATE OR REPLACE FUNCTION queue_order_webhooks() RETURNS trigger AS $$
+BEGIN
+    IF TG_OP = 'INSERT' THEN
+        PERFORM queue_webhook_deliveries('order.created', jsonb_build_object(
+            'order_id', NEW.order_id,
+            'customer_id', NEW.customer_id,
+            'order_status', NEW.order_status,
+            'order_purchase_timestamp', NEW.order_purchase_timestamp
+        ));
+    ELSE
+        PERFORM queue_webhook_deliveries('order.status_changed', jsonb_build_object(
+            'order_id', NEW.order_id,
+            'previous_status', OLD.order_status,
+            'order_status', NEW.order_status,
+            'status_version', NEW.status_version
+        ));
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION queue_review_webhooks() RETURNS trigger AS $$
+BEGIN
+    PERFORM queue_webhook_deliveries('review.created', jsonb_build_object(
+        'review_id', NEW.review_id,
+        'order_id', NEW.order_id,
+        'review_score', NEW.review_score,
+        'review_comment_title', NEW.review_comment_title,
+        'review_comment_message', NEW.review_comment_message,
+        'review_creation_date', NEW.review_creation_date
+    ));
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trg_orders_webhook_insert ON orders;
+CREATE TRIGGER trg_orders_webhook_insert
+    AFTER INSERT ON orders
+    FOR EACH ROW
+    EXECUTE FUNCTION queue_order_webhooks();
+
+DROP TRIGGER IF EXISTS trg_orders_webhook_status ON orders;
+CREATE TRIGGER trg_orders_webhook_status
+    AFTER UPDATE OF order_status ON orders
+    FOR EACH ROW
+    WHEN (OLD.order_status IS DISTINCT FROM NEW.order_status)
+    EXECUTE FUNCTION queue_order_webhooks();
+
+DROP TRIGGER IF EXISTS trg_reviews_webhook_insert ON reviews;
+CREATE TRIGGER trg_reviews_webhook_insert
+    AFTER INSERT ON reviews
+    FOR EACH ROW
+    EXECUTE FUNCTION queue_review_webhooks();
@@ -0,0 +1,118 @@
+-- Migration: Create the outbox_events table and route webhook events through it
+-- Triggers on orders, payments and reviews record every event in the same transaction as the
+-- write, so a rolled-back write leaves no event and a committed one is never lost. The relay
+-- publishes events in id order, to the broker if one is configured, and then queues the
+-- webhook deliveries in the same statement that marks the event published.
+CREATE TABLE IF NOT EXISTS outbox_events (
+    event_id BIGSERIAL PRIMARY KEY,
+    aggregate_type VARCHAR(40) NOT NULL,
+    aggregate_id VARCHAR(64) NOT NULL,
+    event_type VARCHAR(40) NOT NULL,
+    payload JSONB NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    published_at TIMESTAMP,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    next_attempt_at TIMESTAMP DEFAULT NOW(),
+    last_error TEXT
+);
+
+CREATE INDEX idx_outbox_events_unpublished
+    ON outbox_events(event_id)
+    WHERE published_at IS NULL;
+CREATE INDEX idx_outbox_events_published_at
+    ON outbox_events(published_at)
+    WHERE published_at IS NOT NULL;
+
+CREATE OR REPLACE FUNCTION record_outbox_event(
+    aggregate_type TEXT,
+    aggregate_id TEXT,
+    event_type TEXT,
+    payload JSONB
+) RETURNS void AS $$
+    INSERT INTO outbox_events (aggregate_type, aggregate_id, event_type, payload)
+    VALUES (aggregate_type, aggregate_id, event_type, payload);
+$$ LANGUAGE sql;
+
+CREATE OR REPLACE FUNCTION record_order_events() RETURNS trigger AS $$
+BEGIN
+    IF TG_OP = 'INSERT' THEN
+        PERFORM record_outbox_event('order', NEW.order_id, 'order.created', jsonb_build_object(
+            'order_id', NEW.order_id,
+            'customer_id', NEW.customer_id,
+            'order_status', NEW.order_status,
+            'order_purchase_timestamp', NEW.order_purchase_timestamp
+        ));
+    ELSE
+        PERFORM record_outbox_event('order', NEW.order_id, 'order.status_changed', jsonb_build_object(
+            'order_id', NEW.order_id,
+            'previous_status', OLD.order_status,
+            'order_status', NEW.order_status,
+            'status_version', NEW.status_version
+        ));
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION record_payment_events() RETURNS trigger AS $$
+BEGIN
+    PERFORM record_outbox_event('payment', NEW.order_id, 'payment.created', jsonb_build_object(
+        'order_id', NEW.order_id,
+        'payment_sequential', NEW.payment_sequential,
+        'payment_type', NEW.payment_type,
+        'payment_installments', NEW.payment_installments,
+        'payment_value', NEW.payment_value::TEXT
+    ));
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION record_review_events() RETURNS trigger AS $$
+BEGIN
+    PERFORM record_outbox_event('review', NEW.review_id, 'review.created', jsonb_build_object(
+        'review_id', NEW.review_id,
+        'order_id', NEW.order_id,
+        'review_score', NEW.review_score,
+        'review_comment_title', NEW.review_comment_title,
+        'review_comment_message', NEW.review_comment_message,
+        'review_creation_date', NEW.review_creation_date
+    ));
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trg_orders_webhook_insert ON orders;
+DROP TRIGGER IF EXISTS trg_orders_webhook_status ON orders;
+DROP TRIGGER IF EXISTS trg_reviews_webhook_insert ON reviews;
+DROP FUNCTION IF EXISTS queue_order_webhooks();
+DROP FUNCTION IF EXISTS queue_review_webhooks();
+DROP FUNCTION IF EXISTS queue_webhook_deliveries(TEXT, JSONB);
+
+DROP TRIGGER IF EXISTS trg_orders_outbox_insert ON orders;
+CREATE TRIGGER trg_orders_outbox_insert
+    AFTER INSERT ON orders
+    FOR EACH ROW
+    EXECUTE FUNCTION record_order_events();
+
+DROP TRIGGER IF EXISTS trg_orders_outbox_status ON orders;
+CREATE TRIGGER trg_orders_outbox_status
+    AFTER UPDATE OF order_status ON orders
+    FOR EACH ROW
+    WHEN (OLD.order_status IS DISTINCT FROM NEW.order_status)
+    EXECUTE FUNCTION record_order_events();
+
+DROP TRIGGER IF EXISTS trg_payments_outbox_insert ON payments;
+CREATE TRIGGER trg_payments_outbox_insert
+    AFTER INSERT ON payments
+    FOR EACH ROW
+    EXECUTE FUNCTION record_payment_events();
+
+DROP TRIGGER IF EXISTS trg_reviews_outbox_insert ON reviews;
+CREATE TRIGGER trg_reviews_outbox_insert
+    AFTER INSERT ON reviews
+    FOR EACH ROW
+    EXECUTE FUNCTION record_review_events();
+
+ALTER TABLE webhook_subscriptions DROP CONSTRAINT IF EXISTS webhook_subscriptions_events_check;
+ALTER TABLE webhook_subscriptions ADD CONSTRAINT webhook_subscriptions_events_check
+    CHECK (events <@ ARRAY['order.created', 'order.status_changed', 'payment.created', 'review.created']);
@@ -0,0 +1,18 @@
+-- Migration: Create the import_errors table
+-- One row per CSV row an import batch could not insert: its line in the source file, the row
+-- as read (NULL when the line could not be parsed into fields) and why it failed, so the data
+-- can be fixed and imported again. Kept when the batch is rolled back.
+CREATE TABLE IF NOT EXISTS import_errors (
+    error_id BIGSERIAL PRIMARY KEY,
+    batch_id BIGINT NOT NULL,
+    row_number BIGINT NOT NULL,
+    record TEXT,
+    reason TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    CONSTRAINT fk_import_errors_batch
+        FOREIGN KEY (batch_id)
+        REFERENCES import_batches(batch_id)
+        ON DELETE CASCADE
+);
+
+CREATE INDEX idx_import_errors_batch_row ON import_errors(batch_id, row_number);
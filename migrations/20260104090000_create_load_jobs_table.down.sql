@@ -0,0 +1,6 @@
+-- Revert: Drop the load_jobs table and import batch checkpoints
+DROP INDEX IF EXISTS idx_import_batches_load_job;
+ALTER TABLE import_batches
+    DROP COLUMN IF EXISTS checkpoint_line,
+    DROP COLUMN IF EXISTS load_job_id;
+DROP TABLE IF EXISTS load_jobs;
@@ -0,0 +1,18 @@
+-- Migration: Create the load_jobs table and import batch checkpoints
+-- A load job imports several datasets, one batch each. Jobs are kept so that an interrupted
+-- load can be resumed under the same id. checkpoint_line is the last line of the batch's file
+-- up to which every row has been inserted or recorded as an error; a resumed batch continues
+-- after it.
+CREATE TABLE IF NOT EXISTS load_jobs (
+    job_id BIGSERIAL PRIMARY KEY,
+    datasets VARCHAR(100) NOT NULL,
+    actor VARCHAR(100) NOT NULL,
+    started_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+ALTER TABLE import_batches
+    ADD COLUMN load_job_id BIGINT
+        CONSTRAINT fk_import_batches_load_job REFERENCES load_jobs(job_id) ON DELETE SET NULL,
+    ADD COLUMN checkpoint_line BIGINT NOT NULL DEFAULT 1;
+
+CREATE INDEX idx_import_batches_load_job ON import_batches(load_job_id);
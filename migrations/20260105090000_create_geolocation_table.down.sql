@@ -0,0 +1,2 @@
+-- Revert: Drop geolocation table
+DROP TABLE IF EXISTS geolocation;
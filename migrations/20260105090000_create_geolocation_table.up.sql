@@ -0,0 +1,11 @@
+-- Migration: Create geolocation table
+-- Coordinates per CEP prefix, averaged over the Olist geolocation dataset's points for it,
+-- for distance-based freight estimates.
+CREATE TABLE IF NOT EXISTS geolocation (
+    geolocation_zip_code_prefix VARCHAR(10) PRIMARY KEY,
+    geolocation_lat DOUBLE PRECISION NOT NULL,
+    geolocation_lng DOUBLE PRECISION NOT NULL,
+    geolocation_city VARCHAR(100) NOT NULL,
+    geolocation_state VARCHAR(2) NOT NULL,
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,3 @@
+-- Revert: Drop order_coupons and coupons tables
+DROP TABLE IF EXISTS order_coupons;
+DROP TABLE IF EXISTS coupons;
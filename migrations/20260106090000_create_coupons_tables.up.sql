@@ -0,0 +1,32 @@
+-- Migration: Create coupons and order_coupons tables
+CREATE TABLE IF NOT EXISTS coupons (
+    code VARCHAR(40) PRIMARY KEY,
+    discount_type VARCHAR(20) NOT NULL CHECK (discount_type IN ('percentage', 'fixed')),
+    value DECIMAL(10, 2) NOT NULL CHECK (value > 0),
+    min_order_value DECIMAL(10, 2) CHECK (min_order_value >= 0),
+    expires_at TIMESTAMP,
+    max_uses INTEGER CHECK (max_uses > 0),
+    times_used INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    CONSTRAINT chk_coupon_uses CHECK (max_uses IS NULL OR times_used <= max_uses)
+);
+
+-- At most one coupon per order. The discount is not stored: it is worked out from the
+-- order's current items whenever the order total is.
+CREATE TABLE IF NOT EXISTS order_coupons (
+    order_id VARCHAR(32) PRIMARY KEY,
+    code VARCHAR(40) NOT NULL,
+    applied_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    CONSTRAINT fk_order_order_coupons
+        FOREIGN KEY (order_id)
+        REFERENCES orders(order_id)
+        ON DELETE CASCADE
+        ON UPDATE NO ACTION,
+    CONSTRAINT fk_coupon_order_coupons
+        FOREIGN KEY (code)
+        REFERENCES coupons(code)
+        ON DELETE NO ACTION
+        ON UPDATE NO ACTION
+);
+
+CREATE INDEX idx_order_coupons_code ON order_coupons(code);
@@ -0,0 +1,27 @@
+-- Migration: Create the payment_transactions table
+-- Payments made through a payment provider. The dataset's payments table keeps one row per
+-- payment sequential and has no provider state, so transactions are kept apart from it.
+CREATE TABLE IF NOT EXISTS payment_transactions (
+    transaction_id BIGSERIAL PRIMARY KEY,
+    order_id VARCHAR(32) NOT NULL,
+    provider VARCHAR(40) NOT NULL,
+    provider_reference VARCHAR(100) NOT NULL,
+    payment_type VARCHAR(20) NOT NULL CHECK (
+        payment_type IN ('credit_card', 'debit_card', 'boleto', 'voucher', 'not_defined')
+    ),
+    payment_installments INTEGER NOT NULL CHECK (payment_installments > 0),
+    amount DECIMAL(10, 2) NOT NULL CHECK (amount > 0),
+    status VARCHAR(20) NOT NULL CHECK (
+        status IN ('authorized', 'captured', 'refunded', 'failed', 'canceled')
+    ),
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    CONSTRAINT uq_payment_transactions_reference UNIQUE (provider, provider_reference),
+    CONSTRAINT fk_order_payment_transactions
+        FOREIGN KEY (order_id)
+        REFERENCES orders(order_id)
+        ON DELETE CASCADE
+        ON UPDATE NO ACTION
+);
+
+CREATE INDEX idx_payment_transactions_order_id ON payment_transactions(order_id);
@@ -0,0 +1,2 @@
+-- Revert: Drop the refunds table
+DROP TABLE IF EXISTS refunds;
@@ -0,0 +1,18 @@
+-- Migration: Create the refunds table
+-- Refunds of an order's payments. The payments table is keyed by order only, so the payment
+-- sequential is checked when a refund is recorded rather than by a foreign key.
+CREATE TABLE IF NOT EXISTS refunds (
+    refund_id BIGSERIAL PRIMARY KEY,
+    order_id VARCHAR(32) NOT NULL,
+    payment_sequential INTEGER NOT NULL CHECK (payment_sequential > 0),
+    amount DECIMAL(10, 2) NOT NULL CHECK (amount > 0),
+    reason TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    CONSTRAINT fk_order_refunds
+        FOREIGN KEY (order_id)
+        REFERENCES orders(order_id)
+        ON DELETE CASCADE
+        ON UPDATE NO ACTION
+);
+
+CREATE INDEX idx_refunds_order_id ON refunds(order_id);
@@ -0,0 +1,27 @@
+-- Migration: Create the notifications table
+-- Notifications of approved and delivered orders and new reviews. The outbox relay queues one
+-- in the same statement that marks its event published; the notification worker sends it and
+-- records every attempt on the row, retrying until it is sent or has failed for good. Outbox
+-- events are purged after their retention, so event_id is not a foreign key.
+CREATE TABLE IF NOT EXISTS notifications (
+    notification_id BIGSERIAL PRIMARY KEY,
+    event_id BIGINT NOT NULL,
+    kind VARCHAR(40) NOT NULL
+        CHECK (kind IN ('order_approved', 'order_delivered', 'review_received')),
+    payload JSONB NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'sent', 'failed')),
+    channel VARCHAR(40),
+    recipient TEXT,
+    subject TEXT,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    next_attempt_at TIMESTAMP DEFAULT NOW(),
+    last_error TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    sent_at TIMESTAMP
+);
+
+CREATE INDEX idx_notifications_created_at ON notifications(created_at);
+CREATE INDEX idx_notifications_due
+    ON notifications(next_attempt_at)
+    WHERE status = 'pending';
@@ -0,0 +1,7 @@
+-- Revert: Drop the order archive tables
+-- Archived orders are lost; restore them before reverting if they are still needed.
+DROP FUNCTION IF EXISTS ensure_order_archive_partitions(TIMESTAMP, TIMESTAMP);
+DROP TABLE IF EXISTS reviews_archive;
+DROP TABLE IF EXISTS payments_archive;
+DROP TABLE IF EXISTS order_items_archive;
+DROP TABLE IF EXISTS orders_archive;
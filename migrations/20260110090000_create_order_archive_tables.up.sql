@@ -0,0 +1,83 @@
+-- Migration: Archive tables for old orders
+-- POST /admin/archive moves closed orders purchased before a cutoff, with their items, payments
+-- and reviews, out of the tables the API lists and writes, so those stay small as data grows.
+-- orders itself can't be partitioned: the unique keys of a partitioned table must include the
+-- partition key, and eight tables reference orders(order_id) alone. Nothing references the
+-- archive, so orders_archive and order_items_archive are partitioned by purchase year instead;
+-- ensure_order_archive_partitions creates a year's partitions the first time it is archived.
+CREATE TABLE IF NOT EXISTS orders_archive (
+    order_id VARCHAR(32) NOT NULL,
+    customer_id VARCHAR(32) NOT NULL,
+    order_status VARCHAR(12) NOT NULL,
+    order_purchase_timestamp TIMESTAMP NOT NULL,
+    order_approved_at TIMESTAMP NOT NULL,
+    order_delivered_carrier_date TIMESTAMP,
+    order_delivered_customer_date TIMESTAMP,
+    order_estimated_delivery_date TIMESTAMP NOT NULL,
+    shipping_zip_code_prefix VARCHAR(10),
+    status_version INTEGER NOT NULL,
+    archived_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (order_id, order_purchase_timestamp)
+) PARTITION BY RANGE (order_purchase_timestamp);
+
+CREATE INDEX IF NOT EXISTS idx_orders_archive_customer_id ON orders_archive(customer_id);
+
+-- Items carry their order's purchase timestamp, so they land in the same year's partition.
+CREATE TABLE IF NOT EXISTS order_items_archive (
+    order_item_id INTEGER NOT NULL,
+    order_id VARCHAR(32) NOT NULL,
+    product_id VARCHAR(32) NOT NULL,
+    seller_id VARCHAR(32) NOT NULL,
+    shipping_limit_date TIMESTAMP NOT NULL,
+    price DECIMAL(10, 2) NOT NULL,
+    freight_value DECIMAL(10, 2) NOT NULL,
+    order_purchase_timestamp TIMESTAMP NOT NULL,
+    PRIMARY KEY (order_item_id, order_id, product_id, seller_id, order_purchase_timestamp)
+) PARTITION BY RANGE (order_purchase_timestamp);
+
+CREATE INDEX IF NOT EXISTS idx_order_items_archive_order_id ON order_items_archive(order_id);
+
+CREATE TABLE IF NOT EXISTS payments_archive (
+    order_id VARCHAR(32) PRIMARY KEY,
+    payment_sequential INTEGER NOT NULL,
+    payment_type VARCHAR(20) NOT NULL,
+    payment_installments INTEGER NOT NULL,
+    payment_value DECIMAL(10, 2) NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS reviews_archive (
+    review_id VARCHAR(32) PRIMARY KEY,
+    order_id VARCHAR(32) NOT NULL,
+    review_score INTEGER NOT NULL,
+    review_comment_title VARCHAR(30),
+    review_comment_message TEXT,
+    review_creation_date TIMESTAMP NOT NULL,
+    review_answer_timestamp TIMESTAMP NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_reviews_archive_order_id ON reviews_archive(order_id);
+
+-- Creates the yearly partitions of orders_archive and order_items_archive covering
+-- [purchased_from, purchased_to], e.g. orders_archive_2017 for 2017.
+CREATE OR REPLACE FUNCTION ensure_order_archive_partitions(
+    purchased_from TIMESTAMP,
+    purchased_to TIMESTAMP
+) RETURNS void AS $$
+DECLARE
+    year_start TIMESTAMP := date_trunc('year', purchased_from);
+    parent TEXT;
+BEGIN
+    WHILE year_start <= purchased_to LOOP
+        FOREACH parent IN ARRAY ARRAY['orders_archive', 'order_items_archive'] LOOP
+            EXECUTE format(
+                'CREATE TABLE IF NOT EXISTS %I PARTITION OF %I FOR VALUES FROM (%L) TO (%L)',
+                parent || '_' || to_char(year_start, 'YYYY'),
+                parent,
+                year_start,
+                year_start + INTERVAL '1 year'
+            );
+        END LOOP;
+        year_start := year_start + INTERVAL '1 year';
+    END LOOP;
+END;
+$$ LANGUAGE plpgsql;
@@ -0,0 +1,4 @@
+-- Revert: Drop the data retention indexes
+DROP INDEX IF EXISTS idx_orders_customer_id;
+DROP INDEX IF EXISTS idx_reviews_archive_creation_date;
+DROP INDEX IF EXISTS idx_reviews_creation_date;
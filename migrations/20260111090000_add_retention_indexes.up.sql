@@ -0,0 +1,6 @@
+-- Migration: Indexes for the data retention rules
+-- The retention job looks reviews up by creation date and each customer's last order, both
+-- unindexed until now.
+CREATE INDEX IF NOT EXISTS idx_reviews_creation_date ON reviews(review_creation_date);
+CREATE INDEX IF NOT EXISTS idx_reviews_archive_creation_date ON reviews_archive(review_creation_date);
+CREATE INDEX IF NOT EXISTS idx_orders_customer_id ON orders(customer_id);
@@ -0,0 +1,2 @@
+-- Revert: Drop the customer_unique_id index
+DROP INDEX IF EXISTS idx_customers_unique_id;
@@ -0,0 +1,4 @@
+-- Migration: Index customers by customer_unique_id
+-- The Olist data gives every order its own customer_id; customer_unique_id links the rows of
+-- one person, and GET /customers/unique/{unique_id} looks them up by it.
+CREATE INDEX IF NOT EXISTS idx_customers_unique_id ON customers(customer_unique_id);
@@ -0,0 +1,16 @@
+-- Migration: Create the review_sentiments table
+-- The sentiment of each review comment, as tagged by the tag_review_sentiment job for CX
+-- triage. Kept beside reviews rather than in it so re-tagging with another provider never
+-- rewrites imported rows; it goes with its review when that is deleted or archived.
+CREATE TABLE IF NOT EXISTS review_sentiments (
+    review_id VARCHAR(32) PRIMARY KEY,
+    sentiment VARCHAR(8) NOT NULL CHECK (sentiment IN ('positive', 'neutral', 'negative')),
+    tagged_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    CONSTRAINT fk_review_sentiments
+        FOREIGN KEY (review_id)
+        REFERENCES reviews(review_id)
+        ON DELETE CASCADE
+        ON UPDATE NO ACTION
+);
+
+CREATE INDEX IF NOT EXISTS idx_review_sentiments_sentiment ON review_sentiments(sentiment);
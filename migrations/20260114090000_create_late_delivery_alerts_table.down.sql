@@ -0,0 +1,13 @@
+-- Revert: Drop late delivery alerts
+-- order.late is dropped from subscriptions, and unsent order_late notifications with it.
+DELETE FROM notifications WHERE kind = 'order_late';
+ALTER TABLE notifications DROP CONSTRAINT IF EXISTS notifications_kind_check;
+ALTER TABLE notifications ADD CONSTRAINT notifications_kind_check
+    CHECK (kind IN ('order_approved', 'order_delivered', 'review_received'));
+
+UPDATE webhook_subscriptions SET events = array_remove(events, 'order.late');
+ALTER TABLE webhook_subscriptions DROP CONSTRAINT IF EXISTS webhook_subscriptions_events_check;
+ALTER TABLE webhook_subscriptions ADD CONSTRAINT webhook_subscriptions_events_check
+    CHECK (events <@ ARRAY['order.created', 'order.status_changed', 'payment.created', 'review.created']);
+
+DROP TABLE IF EXISTS late_delivery_alerts;
@@ -0,0 +1,25 @@
+-- Migration: Late delivery alerts
+-- The flag_late_orders job records an alert for each order it finds past its estimated
+-- delivery date without a delivered date, and an order.late outbox event in the same
+-- transaction, so ops hear of it through webhooks and notifications. An order is flagged once;
+-- its alert goes with it when it is deleted or archived.
+CREATE TABLE IF NOT EXISTS late_delivery_alerts (
+    order_id VARCHAR(32) PRIMARY KEY,
+    order_estimated_delivery_date TIMESTAMP NOT NULL,
+    flagged_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    CONSTRAINT fk_late_delivery_alerts
+        FOREIGN KEY (order_id)
+        REFERENCES orders(order_id)
+        ON DELETE CASCADE
+        ON UPDATE NO ACTION
+);
+
+ALTER TABLE webhook_subscriptions DROP CONSTRAINT IF EXISTS webhook_subscriptions_events_check;
+ALTER TABLE webhook_subscriptions ADD CONSTRAINT webhook_subscriptions_events_check
+    CHECK (events <@ ARRAY[
+        'order.created', 'order.status_changed', 'order.late', 'payment.created', 'review.created'
+    ]);
+
+ALTER TABLE notifications DROP CONSTRAINT IF EXISTS notifications_kind_check;
+ALTER TABLE notifications ADD CONSTRAINT notifications_kind_check
+    CHECK (kind IN ('order_approved', 'order_delivered', 'order_late', 'review_received'));
@@ -0,0 +1,2 @@
+-- Revert: Drop data quality rule results
+DROP TABLE IF EXISTS data_quality_results;
@@ -0,0 +1,15 @@
+-- Migration: Data quality rule results
+-- The evaluate_data_quality job checks every rule in the file named by DATA_QUALITY_RULES_PATH
+-- and replaces these rows with the outcome, one per rule, for GET /admin/data-quality. A rule
+-- that couldn't be evaluated, say on a column that doesn't exist, keeps its error instead of
+-- counts.
+CREATE TABLE IF NOT EXISTS data_quality_results (
+    rule_name VARCHAR(100) PRIMARY KEY,
+    table_name VARCHAR(63) NOT NULL,
+    condition TEXT NOT NULL,
+    severity VARCHAR(10) NOT NULL CHECK (severity IN ('info', 'warning', 'error')),
+    checked_count BIGINT,
+    violation_count BIGINT,
+    error TEXT,
+    evaluated_at TIMESTAMP NOT NULL
+);
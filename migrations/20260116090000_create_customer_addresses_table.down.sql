@@ -0,0 +1,2 @@
+-- Revert: Drop the customer address book
+DROP TABLE IF EXISTS customer_addresses;
@@ -0,0 +1,27 @@
+-- Migration: Customer address book
+-- The Olist schema has one location per customer_id; customers can keep further addresses
+-- here. A new order ships to the address it names, or else to the customer's default address,
+-- its zip code prefix copied onto orders.shipping_zip_code_prefix so later edits to the book
+-- don't move orders already placed.
+CREATE TABLE IF NOT EXISTS customer_addresses (
+    address_id BIGSERIAL PRIMARY KEY,
+    customer_id VARCHAR(32) NOT NULL,
+    label VARCHAR(50) NOT NULL,
+    zip_code_prefix VARCHAR(10) NOT NULL,
+    city VARCHAR(100) NOT NULL,
+    state VARCHAR(2) NOT NULL,
+    is_default BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    CONSTRAINT fk_customer_customer_addresses
+        FOREIGN KEY (customer_id)
+        REFERENCES customers(customer_id)
+        ON DELETE CASCADE
+        ON UPDATE NO ACTION
+);
+
+CREATE INDEX IF NOT EXISTS idx_customer_addresses_customer_id ON customer_addresses(customer_id);
+
+-- At most one default address per customer.
+CREATE UNIQUE INDEX IF NOT EXISTS idx_customer_addresses_default
+    ON customer_addresses(customer_id)
+    WHERE is_default;
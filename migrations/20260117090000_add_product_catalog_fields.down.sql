@@ -0,0 +1,6 @@
+-- Revert: Remove catalog fields from products
+ALTER TABLE products
+    DROP COLUMN IF EXISTS active,
+    DROP COLUMN IF EXISTS price,
+    DROP COLUMN IF EXISTS description,
+    DROP COLUMN IF EXISTS product_name;
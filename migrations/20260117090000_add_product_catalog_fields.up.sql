@@ -0,0 +1,9 @@
+-- Migration: Add catalog fields to products
+-- The Olist products carry measurements and the lengths of their anonymized name and
+-- description, but no name, description or price. These columns hold them for products the
+-- store sells. Imported products stay on sale, with the new fields left empty.
+ALTER TABLE products
+    ADD COLUMN IF NOT EXISTS product_name VARCHAR(255),
+    ADD COLUMN IF NOT EXISTS description TEXT,
+    ADD COLUMN IF NOT EXISTS price DECIMAL(10, 2) CHECK (price >= 0),
+    ADD COLUMN IF NOT EXISTS active BOOLEAN NOT NULL DEFAULT TRUE;
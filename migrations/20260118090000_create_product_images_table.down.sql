@@ -0,0 +1,2 @@
+-- Revert: Drop product images
+DROP TABLE IF EXISTS product_images;
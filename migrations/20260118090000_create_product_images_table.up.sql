@@ -0,0 +1,22 @@
+-- Migration: Product images
+-- Uploaded images live in object storage; this table keeps where each is served from and the
+-- order they are shown in. products.product_photos_qty counts a product's rows here on top of
+-- the photos the Olist dataset reported for it.
+CREATE TABLE IF NOT EXISTS product_images (
+    image_id BIGSERIAL PRIMARY KEY,
+    product_id VARCHAR(32) NOT NULL,
+    position INTEGER NOT NULL CHECK (position >= 1),
+    url TEXT NOT NULL,
+    storage_key VARCHAR(255) NOT NULL UNIQUE,
+    content_type VARCHAR(100) NOT NULL,
+    size_bytes BIGINT NOT NULL CHECK (size_bytes >= 0),
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    CONSTRAINT fk_product_product_images
+        FOREIGN KEY (product_id)
+        REFERENCES products(product_id)
+        ON DELETE CASCADE
+        ON UPDATE NO ACTION
+);
+
+CREATE INDEX IF NOT EXISTS idx_product_images_product_position
+    ON product_images(product_id, position);
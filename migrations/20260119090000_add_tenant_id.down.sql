@@ -0,0 +1,332 @@
+-- Revert: Multi-tenancy keyed by marketplace
+-- Refuses to run while any tenant but the default one has data: dropping tenant_id would merge
+-- their rows into one store, and the per-tenant keys could no longer be unique.
+DO $$
+DECLARE
+    tenant_table TEXT;
+    found BOOLEAN;
+BEGIN
+    FOR tenant_table IN
+        SELECT table_name FROM information_schema.columns
+        WHERE table_schema = current_schema() AND column_name = 'tenant_id'
+    LOOP
+        EXECUTE format('SELECT EXISTS (SELECT 1 FROM %I WHERE tenant_id <> %L)', tenant_table, 'default')
+            INTO found;
+        IF found THEN
+            RAISE EXCEPTION '% holds rows of tenants other than default; remove them before reverting', tenant_table;
+        END IF;
+    END LOOP;
+END
+$$;
+
+-- Triggers write rows without a tenant again.
+DROP FUNCTION IF EXISTS bump_stats(VARCHAR, DATE, BIGINT, DECIMAL, INTEGER);
+CREATE OR REPLACE FUNCTION bump_stats(
+    day DATE,
+    orders_delta BIGINT,
+    revenue_delta DECIMAL,
+    imports_delta INTEGER
+) RETURNS void AS $$
+    INSERT INTO stats (stat_date, orders_count, revenue, active_imports, updated_at)
+    VALUES (day, orders_delta, revenue_delta, imports_delta, NOW())
+    ON CONFLICT (stat_date) DO UPDATE SET
+        orders_count = stats.orders_count + EXCLUDED.orders_count,
+        revenue = stats.revenue + EXCLUDED.revenue,
+        active_imports = stats.active_imports + EXCLUDED.active_imports,
+        updated_at = EXCLUDED.updated_at;
+$$ LANGUAGE sql;
+
+CREATE OR REPLACE FUNCTION count_order_stats() RETURNS trigger AS $$
+DECLARE
+    order_revenue DECIMAL;
+BEGIN
+    IF TG_OP IN ('UPDATE', 'DELETE') THEN
+        PERFORM bump_stats(OLD.order_purchase_timestamp::date, -1, 0, 0);
+    END IF;
+    IF TG_OP IN ('INSERT', 'UPDATE') THEN
+        PERFORM bump_stats(NEW.order_purchase_timestamp::date, 1, 0, 0);
+    END IF;
+
+    -- A changed purchase date moves the order's items to the new day.
+    IF TG_OP = 'UPDATE' THEN
+        SELECT COALESCE(SUM(price + freight_value), 0) INTO order_revenue
+        FROM order_items
+        WHERE order_id = NEW.order_id;
+
+        PERFORM bump_stats(OLD.order_purchase_timestamp::date, 0, -order_revenue, 0);
+        PERFORM bump_stats(NEW.order_purchase_timestamp::date, 0, order_revenue, 0);
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION count_order_item_stats() RETURNS trigger AS $$
+BEGIN
+    IF TG_OP IN ('UPDATE', 'DELETE') THEN
+        PERFORM bump_stats(o.order_purchase_timestamp::date, 0, -(OLD.price + OLD.freight_value), 0)
+        FROM orders o
+        WHERE o.order_id = OLD.order_id;
+    END IF;
+    IF TG_OP IN ('INSERT', 'UPDATE') THEN
+        PERFORM bump_stats(o.order_purchase_timestamp::date, 0, NEW.price + NEW.freight_value, 0)
+        FROM orders o
+        WHERE o.order_id = NEW.order_id;
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION count_import_stats() RETURNS trigger AS $$
+BEGIN
+    IF TG_OP IN ('UPDATE', 'DELETE') AND OLD.status = 'running' THEN
+        PERFORM bump_stats(OLD.started_at::date, 0, 0, -1);
+    END IF;
+    IF TG_OP IN ('INSERT', 'UPDATE') AND NEW.status = 'running' THEN
+        PERFORM bump_stats(NEW.started_at::date, 0, 0, 1);
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION record_customer_location() RETURNS trigger AS $$
+BEGIN
+    IF TG_OP = 'UPDATE' THEN
+        UPDATE customer_location_history
+        SET valid_to = LOCALTIMESTAMP
+        WHERE customer_id = NEW.customer_id AND valid_to IS NULL;
+    END IF;
+
+    INSERT INTO customer_location_history (
+        customer_id, customer_zip_code_prefix, customer_city, customer_state, valid_from
+    )
+    VALUES (
+        NEW.customer_id, NEW.customer_zip_code_prefix, NEW.customer_city, NEW.customer_state,
+        CASE WHEN TG_OP = 'UPDATE' THEN LOCALTIMESTAMP END
+    );
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP FUNCTION IF EXISTS record_outbox_event(TEXT, TEXT, TEXT, TEXT, JSONB);
+CREATE OR REPLACE FUNCTION record_outbox_event(
+    aggregate_type TEXT,
+    aggregate_id TEXT,
+    event_type TEXT,
+    payload JSONB
+) RETURNS void AS $$
+    INSERT INTO outbox_events (aggregate_type, aggregate_id, event_type, payload)
+    VALUES (aggregate_type, aggregate_id, event_type, payload);
+$$ LANGUAGE sql;
+
+CREATE OR REPLACE FUNCTION record_order_events() RETURNS trigger AS $$
+BEGIN
+    IF TG_OP = 'INSERT' THEN
+        PERFORM record_outbox_event('order', NEW.order_id, 'order.created', jsonb_build_object(
+            'order_id', NEW.order_id,
+            'customer_id', NEW.customer_id,
+            'order_status', NEW.order_status,
+            'order_purchase_timestamp', NEW.order_purchase_timestamp
+        ));
+    ELSE
+        PERFORM record_outbox_event('order', NEW.order_id, 'order.status_changed', jsonb_build_object(
+            'order_id', NEW.order_id,
+            'previous_status', OLD.order_status,
+            'order_status', NEW.order_status,
+            'status_version', NEW.status_version
+        ));
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION record_payment_events() RETURNS trigger AS $$
+BEGIN
+    PERFORM record_outbox_event('payment', NEW.order_id, 'payment.created', jsonb_build_object(
+        'order_id', NEW.order_id,
+        'payment_sequential', NEW.payment_sequential,
+        'payment_type', NEW.payment_type,
+        'payment_installments', NEW.payment_installments,
+        'payment_value', NEW.payment_value::TEXT
+    ));
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION record_review_events() RETURNS trigger AS $$
+BEGIN
+    PERFORM record_outbox_event('review', NEW.review_id, 'review.created', jsonb_build_object(
+        'review_id', NEW.review_id,
+        'order_id', NEW.order_id,
+        'review_score', NEW.review_score,
+        'review_comment_title', NEW.review_comment_title,
+        'review_comment_message', NEW.review_comment_message,
+        'review_creation_date', NEW.review_creation_date
+    ));
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION notify_order_status_change() RETURNS trigger AS $$
+BEGIN
+    PERFORM pg_notify(
+        'order_status_changed',
+        json_build_object(
+            'order_id', NEW.order_id,
+            'order_status', NEW.order_status,
+            'status_version', NEW.status_version
+        )::text
+    );
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+-- Keys and foreign keys go back to the entity ids alone.
+ALTER TABLE data_quality_results DROP CONSTRAINT IF EXISTS data_quality_results_pkey;
+ALTER TABLE data_quality_results ADD PRIMARY KEY (rule_name);
+
+ALTER TABLE stats DROP CONSTRAINT IF EXISTS stats_pkey;
+ALTER TABLE stats ADD PRIMARY KEY (stat_date);
+
+ALTER TABLE order_coupons DROP CONSTRAINT IF EXISTS fk_coupon_order_coupons;
+ALTER TABLE coupons DROP CONSTRAINT IF EXISTS coupons_pkey;
+ALTER TABLE coupons ADD PRIMARY KEY (code);
+ALTER TABLE order_coupons ADD CONSTRAINT fk_coupon_order_coupons
+    FOREIGN KEY (code)
+    REFERENCES coupons(code)
+    ON DELETE NO ACTION
+    ON UPDATE NO ACTION;
+
+ALTER TABLE product_categories DROP CONSTRAINT IF EXISTS product_categories_pkey;
+ALTER TABLE product_categories ADD PRIMARY KEY (product_category_name);
+
+ALTER TABLE product_images DROP CONSTRAINT IF EXISTS fk_product_product_images;
+ALTER TABLE product_images ADD CONSTRAINT fk_product_product_images
+    FOREIGN KEY (product_id) REFERENCES products(product_id) ON DELETE CASCADE;
+ALTER TABLE customer_addresses DROP CONSTRAINT IF EXISTS fk_customer_customer_addresses;
+ALTER TABLE customer_addresses ADD CONSTRAINT fk_customer_customer_addresses
+    FOREIGN KEY (customer_id) REFERENCES customers(customer_id) ON DELETE CASCADE;
+ALTER TABLE late_delivery_alerts DROP CONSTRAINT IF EXISTS fk_late_delivery_alerts;
+ALTER TABLE late_delivery_alerts ADD CONSTRAINT fk_late_delivery_alerts
+    FOREIGN KEY (order_id) REFERENCES orders(order_id) ON DELETE CASCADE;
+ALTER TABLE review_sentiments DROP CONSTRAINT IF EXISTS fk_review_sentiments;
+ALTER TABLE review_sentiments ADD CONSTRAINT fk_review_sentiments
+    FOREIGN KEY (review_id) REFERENCES reviews(review_id) ON DELETE CASCADE;
+ALTER TABLE refunds DROP CONSTRAINT IF EXISTS fk_order_refunds;
+ALTER TABLE refunds ADD CONSTRAINT fk_order_refunds
+    FOREIGN KEY (order_id) REFERENCES orders(order_id) ON DELETE CASCADE;
+ALTER TABLE payment_transactions DROP CONSTRAINT IF EXISTS fk_order_payment_transactions;
+ALTER TABLE payment_transactions ADD CONSTRAINT fk_order_payment_transactions
+    FOREIGN KEY (order_id) REFERENCES orders(order_id) ON DELETE CASCADE;
+ALTER TABLE order_coupons DROP CONSTRAINT IF EXISTS fk_order_order_coupons;
+ALTER TABLE order_coupons ADD CONSTRAINT fk_order_order_coupons
+    FOREIGN KEY (order_id) REFERENCES orders(order_id) ON DELETE CASCADE;
+ALTER TABLE import_errors DROP CONSTRAINT IF EXISTS fk_import_errors_batch;
+ALTER TABLE import_errors ADD CONSTRAINT fk_import_errors_batch
+    FOREIGN KEY (batch_id) REFERENCES import_batches(batch_id) ON DELETE CASCADE;
+ALTER TABLE webhook_deliveries DROP CONSTRAINT IF EXISTS fk_webhook_deliveries_subscription;
+ALTER TABLE webhook_deliveries ADD CONSTRAINT fk_webhook_deliveries_subscription
+    FOREIGN KEY (subscription_id) REFERENCES webhook_subscriptions(subscription_id) ON DELETE CASCADE;
+ALTER TABLE import_batch_rows DROP CONSTRAINT IF EXISTS fk_import_batch_rows_batch;
+ALTER TABLE import_batch_rows ADD CONSTRAINT fk_import_batch_rows_batch
+    FOREIGN KEY (batch_id) REFERENCES import_batches(batch_id) ON DELETE CASCADE;
+ALTER TABLE import_batches DROP CONSTRAINT IF EXISTS fk_import_batches_load_job;
+ALTER TABLE import_batches ADD CONSTRAINT fk_import_batches_load_job
+    FOREIGN KEY (load_job_id) REFERENCES load_jobs(job_id) ON DELETE SET NULL;
+ALTER TABLE customer_location_history DROP CONSTRAINT IF EXISTS customer_location_history_customer_id_fkey;
+ALTER TABLE customer_location_history ADD CONSTRAINT customer_location_history_customer_id_fkey
+    FOREIGN KEY (customer_id) REFERENCES customers(customer_id) ON DELETE CASCADE;
+ALTER TABLE seller_badges DROP CONSTRAINT IF EXISTS fk_seller_seller_badges;
+ALTER TABLE seller_badges ADD CONSTRAINT fk_seller_seller_badges
+    FOREIGN KEY (seller_id) REFERENCES sellers(seller_id) ON DELETE CASCADE;
+ALTER TABLE support_case_messages DROP CONSTRAINT IF EXISTS fk_case_support_case_messages;
+ALTER TABLE support_case_messages ADD CONSTRAINT fk_case_support_case_messages
+    FOREIGN KEY (case_id) REFERENCES support_cases(case_id) ON DELETE CASCADE;
+ALTER TABLE support_cases DROP CONSTRAINT IF EXISTS fk_customer_support_cases;
+ALTER TABLE support_cases ADD CONSTRAINT fk_customer_support_cases
+    FOREIGN KEY (customer_id) REFERENCES customers(customer_id);
+ALTER TABLE support_cases DROP CONSTRAINT IF EXISTS fk_order_support_cases;
+ALTER TABLE support_cases ADD CONSTRAINT fk_order_support_cases
+    FOREIGN KEY (order_id) REFERENCES orders(order_id);
+ALTER TABLE order_amendments DROP CONSTRAINT IF EXISTS fk_order_order_amendments;
+ALTER TABLE order_amendments ADD CONSTRAINT fk_order_order_amendments
+    FOREIGN KEY (order_id) REFERENCES orders(order_id);
+ALTER TABLE location_stock DROP CONSTRAINT IF EXISTS fk_product_location_stock;
+ALTER TABLE location_stock ADD CONSTRAINT fk_product_location_stock
+    FOREIGN KEY (product_id) REFERENCES products(product_id);
+ALTER TABLE location_stock DROP CONSTRAINT IF EXISTS fk_location_location_stock;
+ALTER TABLE location_stock ADD CONSTRAINT fk_location_location_stock
+    FOREIGN KEY (location_id) REFERENCES stock_locations(location_id) ON DELETE CASCADE;
+ALTER TABLE stock_locations DROP CONSTRAINT IF EXISTS fk_seller_stock_locations;
+ALTER TABLE stock_locations ADD CONSTRAINT fk_seller_stock_locations
+    FOREIGN KEY (seller_id) REFERENCES sellers(seller_id);
+ALTER TABLE reviews DROP CONSTRAINT IF EXISTS fk_order_reviews;
+ALTER TABLE reviews ADD CONSTRAINT fk_order_reviews
+    FOREIGN KEY (order_id) REFERENCES orders(order_id);
+ALTER TABLE payments DROP CONSTRAINT IF EXISTS fk_order_payments;
+ALTER TABLE payments ADD CONSTRAINT fk_order_payments
+    FOREIGN KEY (order_id) REFERENCES orders(order_id);
+ALTER TABLE order_items DROP CONSTRAINT IF EXISTS fk_seller_order_items;
+ALTER TABLE order_items ADD CONSTRAINT fk_seller_order_items
+    FOREIGN KEY (seller_id) REFERENCES sellers(seller_id);
+ALTER TABLE order_items DROP CONSTRAINT IF EXISTS fk_product_order_items;
+ALTER TABLE order_items ADD CONSTRAINT fk_product_order_items
+    FOREIGN KEY (product_id) REFERENCES products(product_id);
+ALTER TABLE order_items DROP CONSTRAINT IF EXISTS fk_order_order_items;
+ALTER TABLE order_items ADD CONSTRAINT fk_order_order_items
+    FOREIGN KEY (order_id) REFERENCES orders(order_id);
+ALTER TABLE orders DROP CONSTRAINT IF EXISTS fk_customer_orders;
+ALTER TABLE orders ADD CONSTRAINT fk_customer_orders
+    FOREIGN KEY (customer_id) REFERENCES customers(customer_id);
+
+ALTER TABLE webhook_subscriptions DROP CONSTRAINT IF EXISTS uq_webhook_subscriptions_tenant;
+ALTER TABLE import_batches DROP CONSTRAINT IF EXISTS uq_import_batches_tenant;
+ALTER TABLE load_jobs DROP CONSTRAINT IF EXISTS uq_load_jobs_tenant;
+ALTER TABLE support_cases DROP CONSTRAINT IF EXISTS uq_support_cases_tenant;
+ALTER TABLE stock_locations DROP CONSTRAINT IF EXISTS uq_stock_locations_tenant;
+ALTER TABLE reviews DROP CONSTRAINT IF EXISTS uq_reviews_tenant;
+ALTER TABLE products DROP CONSTRAINT IF EXISTS uq_products_tenant;
+ALTER TABLE orders DROP CONSTRAINT IF EXISTS uq_orders_tenant;
+ALTER TABLE sellers DROP CONSTRAINT IF EXISTS uq_sellers_tenant;
+ALTER TABLE customers DROP CONSTRAINT IF EXISTS uq_customers_tenant;
+
+DROP INDEX IF EXISTS idx_orders_tenant;
+
+ALTER TABLE webhook_subscriptions DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE webhook_deliveries DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE support_cases DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE support_case_messages DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE stock_locations DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE stats DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE sellers DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE seller_badges DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE reviews_archive DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE reviews DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE review_sentiments DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE refunds DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE products DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE product_images DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE IF EXISTS product_embeddings DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE product_categories DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE payments_archive DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE payments DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE payment_transactions DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE outbox_events DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE orders_archive DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE orders DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE order_items_archive DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE order_items DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE order_coupons DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE order_amendments DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE notifications DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE location_stock DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE load_jobs DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE late_delivery_alerts DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE import_errors DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE import_batches DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE import_batch_rows DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE data_quality_results DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE customers DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE customer_location_history DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE customer_addresses DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE coupons DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE audit_log DROP COLUMN IF EXISTS tenant_id;
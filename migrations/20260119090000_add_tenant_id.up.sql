@@ -0,0 +1,329 @@
+-- Migration: Multi-tenancy keyed by marketplace
+-- Every table holding a marketplace's data gets a tenant_id, and the repositories built for a
+-- tenant filter on it in every query. Rows from before this migration belong to the default
+-- tenant. Entity ids are generated (or, for the dataset, MD5 digests) and stay globally
+-- unique, so lookups by id keep using their primary keys and check tenant_id on the row they
+-- find; only tables keyed by a name a marketplace picks get tenant_id in their key.
+-- geolocation, city_aliases and seller_badge_thresholds are reference data shared by all
+-- tenants. product_embeddings only exists where pgvector is installed.
+ALTER TABLE audit_log ADD COLUMN IF NOT EXISTS tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE coupons ADD COLUMN IF NOT EXISTS tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE customer_addresses ADD COLUMN IF NOT EXISTS tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE customer_location_history ADD COLUMN IF NOT EXISTS tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE customers ADD COLUMN IF NOT EXISTS tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE data_quality_results ADD COLUMN IF NOT EXISTS tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE import_batch_rows ADD COLUMN IF NOT EXISTS tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE import_batches ADD COLUMN IF NOT EXISTS tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE import_errors ADD COLUMN IF NOT EXISTS tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE late_delivery_alerts ADD COLUMN IF NOT EXISTS tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE load_jobs ADD COLUMN IF NOT EXISTS tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE location_stock ADD COLUMN IF NOT EXISTS tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE notifications ADD COLUMN IF NOT EXISTS tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE order_amendments ADD COLUMN IF NOT EXISTS tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE order_coupons ADD COLUMN IF NOT EXISTS tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE order_items ADD COLUMN IF NOT EXISTS tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE order_items_archive ADD COLUMN IF NOT EXISTS tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE orders ADD COLUMN IF NOT EXISTS tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE orders_archive ADD COLUMN IF NOT EXISTS tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE outbox_events ADD COLUMN IF NOT EXISTS tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE payment_transactions ADD COLUMN IF NOT EXISTS tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE payments ADD COLUMN IF NOT EXISTS tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE payments_archive ADD COLUMN IF NOT EXISTS tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE product_categories ADD COLUMN IF NOT EXISTS tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE IF EXISTS product_embeddings ADD COLUMN IF NOT EXISTS tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE product_images ADD COLUMN IF NOT EXISTS tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE products ADD COLUMN IF NOT EXISTS tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE refunds ADD COLUMN IF NOT EXISTS tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE review_sentiments ADD COLUMN IF NOT EXISTS tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE reviews ADD COLUMN IF NOT EXISTS tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE reviews_archive ADD COLUMN IF NOT EXISTS tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE seller_badges ADD COLUMN IF NOT EXISTS tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE sellers ADD COLUMN IF NOT EXISTS tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE stats ADD COLUMN IF NOT EXISTS tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE stock_locations ADD COLUMN IF NOT EXISTS tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE support_case_messages ADD COLUMN IF NOT EXISTS tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE support_cases ADD COLUMN IF NOT EXISTS tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE webhook_deliveries ADD COLUMN IF NOT EXISTS tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE webhook_subscriptions ADD COLUMN IF NOT EXISTS tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+
+-- The list endpoints page through one tenant's orders.
+CREATE INDEX IF NOT EXISTS idx_orders_tenant ON orders(tenant_id, order_purchase_timestamp);
+
+-- A row may only reference rows of its own tenant: every referenced table gets a unique
+-- (tenant_id, id) key, which also serves the list endpoints paging through one tenant's
+-- customers, sellers and products, and every foreign key between tenant tables includes
+-- tenant_id. A reference to another tenant's row then fails like one to a missing row.
+ALTER TABLE customers ADD CONSTRAINT uq_customers_tenant UNIQUE (tenant_id, customer_id);
+ALTER TABLE sellers ADD CONSTRAINT uq_sellers_tenant UNIQUE (tenant_id, seller_id);
+ALTER TABLE orders ADD CONSTRAINT uq_orders_tenant UNIQUE (tenant_id, order_id);
+ALTER TABLE products ADD CONSTRAINT uq_products_tenant UNIQUE (tenant_id, product_id);
+ALTER TABLE reviews ADD CONSTRAINT uq_reviews_tenant UNIQUE (tenant_id, review_id);
+ALTER TABLE stock_locations ADD CONSTRAINT uq_stock_locations_tenant UNIQUE (tenant_id, location_id);
+ALTER TABLE support_cases ADD CONSTRAINT uq_support_cases_tenant UNIQUE (tenant_id, case_id);
+ALTER TABLE load_jobs ADD CONSTRAINT uq_load_jobs_tenant UNIQUE (tenant_id, job_id);
+ALTER TABLE import_batches ADD CONSTRAINT uq_import_batches_tenant UNIQUE (tenant_id, batch_id);
+ALTER TABLE webhook_subscriptions ADD CONSTRAINT uq_webhook_subscriptions_tenant UNIQUE (tenant_id, subscription_id);
+
+ALTER TABLE orders DROP CONSTRAINT IF EXISTS fk_customer_orders;
+ALTER TABLE orders ADD CONSTRAINT fk_customer_orders
+    FOREIGN KEY (tenant_id, customer_id) REFERENCES customers(tenant_id, customer_id);
+ALTER TABLE order_items DROP CONSTRAINT IF EXISTS fk_order_order_items;
+ALTER TABLE order_items ADD CONSTRAINT fk_order_order_items
+    FOREIGN KEY (tenant_id, order_id) REFERENCES orders(tenant_id, order_id);
+ALTER TABLE order_items DROP CONSTRAINT IF EXISTS fk_product_order_items;
+ALTER TABLE order_items ADD CONSTRAINT fk_product_order_items
+    FOREIGN KEY (tenant_id, product_id) REFERENCES products(tenant_id, product_id);
+ALTER TABLE order_items DROP CONSTRAINT IF EXISTS fk_seller_order_items;
+ALTER TABLE order_items ADD CONSTRAINT fk_seller_order_items
+    FOREIGN KEY (tenant_id, seller_id) REFERENCES sellers(tenant_id, seller_id);
+ALTER TABLE payments DROP CONSTRAINT IF EXISTS fk_order_payments;
+ALTER TABLE payments ADD CONSTRAINT fk_order_payments
+    FOREIGN KEY (tenant_id, order_id) REFERENCES orders(tenant_id, order_id);
+ALTER TABLE reviews DROP CONSTRAINT IF EXISTS fk_order_reviews;
+ALTER TABLE reviews ADD CONSTRAINT fk_order_reviews
+    FOREIGN KEY (tenant_id, order_id) REFERENCES orders(tenant_id, order_id);
+ALTER TABLE stock_locations DROP CONSTRAINT IF EXISTS fk_seller_stock_locations;
+ALTER TABLE stock_locations ADD CONSTRAINT fk_seller_stock_locations
+    FOREIGN KEY (tenant_id, seller_id) REFERENCES sellers(tenant_id, seller_id);
+ALTER TABLE location_stock DROP CONSTRAINT IF EXISTS fk_location_location_stock;
+ALTER TABLE location_stock ADD CONSTRAINT fk_location_location_stock
+    FOREIGN KEY (tenant_id, location_id) REFERENCES stock_locations(tenant_id, location_id) ON DELETE CASCADE;
+ALTER TABLE location_stock DROP CONSTRAINT IF EXISTS fk_product_location_stock;
+ALTER TABLE location_stock ADD CONSTRAINT fk_product_location_stock
+    FOREIGN KEY (tenant_id, product_id) REFERENCES products(tenant_id, product_id);
+ALTER TABLE order_amendments DROP CONSTRAINT IF EXISTS fk_order_order_amendments;
+ALTER TABLE order_amendments ADD CONSTRAINT fk_order_order_amendments
+    FOREIGN KEY (tenant_id, order_id) REFERENCES orders(tenant_id, order_id);
+ALTER TABLE support_cases DROP CONSTRAINT IF EXISTS fk_order_support_cases;
+ALTER TABLE support_cases ADD CONSTRAINT fk_order_support_cases
+    FOREIGN KEY (tenant_id, order_id) REFERENCES orders(tenant_id, order_id);
+ALTER TABLE support_cases DROP CONSTRAINT IF EXISTS fk_customer_support_cases;
+ALTER TABLE support_cases ADD CONSTRAINT fk_customer_support_cases
+    FOREIGN KEY (tenant_id, customer_id) REFERENCES customers(tenant_id, customer_id);
+ALTER TABLE support_case_messages DROP CONSTRAINT IF EXISTS fk_case_support_case_messages;
+ALTER TABLE support_case_messages ADD CONSTRAINT fk_case_support_case_messages
+    FOREIGN KEY (tenant_id, case_id) REFERENCES support_cases(tenant_id, case_id) ON DELETE CASCADE;
+ALTER TABLE seller_badges DROP CONSTRAINT IF EXISTS fk_seller_seller_badges;
+ALTER TABLE seller_badges ADD CONSTRAINT fk_seller_seller_badges
+    FOREIGN KEY (tenant_id, seller_id) REFERENCES sellers(tenant_id, seller_id) ON DELETE CASCADE;
+ALTER TABLE customer_location_history DROP CONSTRAINT IF EXISTS customer_location_history_customer_id_fkey;
+ALTER TABLE customer_location_history ADD CONSTRAINT customer_location_history_customer_id_fkey
+    FOREIGN KEY (tenant_id, customer_id) REFERENCES customers(tenant_id, customer_id) ON DELETE CASCADE;
+ALTER TABLE import_batches DROP CONSTRAINT IF EXISTS fk_import_batches_load_job;
+ALTER TABLE import_batches ADD CONSTRAINT fk_import_batches_load_job
+    FOREIGN KEY (tenant_id, load_job_id) REFERENCES load_jobs(tenant_id, job_id) ON DELETE SET NULL (load_job_id);
+ALTER TABLE import_batch_rows DROP CONSTRAINT IF EXISTS fk_import_batch_rows_batch;
+ALTER TABLE import_batch_rows ADD CONSTRAINT fk_import_batch_rows_batch
+    FOREIGN KEY (tenant_id, batch_id) REFERENCES import_batches(tenant_id, batch_id) ON DELETE CASCADE;
+ALTER TABLE webhook_deliveries DROP CONSTRAINT IF EXISTS fk_webhook_deliveries_subscription;
+ALTER TABLE webhook_deliveries ADD CONSTRAINT fk_webhook_deliveries_subscription
+    FOREIGN KEY (tenant_id, subscription_id) REFERENCES webhook_subscriptions(tenant_id, subscription_id) ON DELETE CASCADE;
+ALTER TABLE import_errors DROP CONSTRAINT IF EXISTS fk_import_errors_batch;
+ALTER TABLE import_errors ADD CONSTRAINT fk_import_errors_batch
+    FOREIGN KEY (tenant_id, batch_id) REFERENCES import_batches(tenant_id, batch_id) ON DELETE CASCADE;
+ALTER TABLE order_coupons DROP CONSTRAINT IF EXISTS fk_order_order_coupons;
+ALTER TABLE order_coupons ADD CONSTRAINT fk_order_order_coupons
+    FOREIGN KEY (tenant_id, order_id) REFERENCES orders(tenant_id, order_id) ON DELETE CASCADE;
+ALTER TABLE payment_transactions DROP CONSTRAINT IF EXISTS fk_order_payment_transactions;
+ALTER TABLE payment_transactions ADD CONSTRAINT fk_order_payment_transactions
+    FOREIGN KEY (tenant_id, order_id) REFERENCES orders(tenant_id, order_id) ON DELETE CASCADE;
+ALTER TABLE refunds DROP CONSTRAINT IF EXISTS fk_order_refunds;
+ALTER TABLE refunds ADD CONSTRAINT fk_order_refunds
+    FOREIGN KEY (tenant_id, order_id) REFERENCES orders(tenant_id, order_id) ON DELETE CASCADE;
+ALTER TABLE review_sentiments DROP CONSTRAINT IF EXISTS fk_review_sentiments;
+ALTER TABLE review_sentiments ADD CONSTRAINT fk_review_sentiments
+    FOREIGN KEY (tenant_id, review_id) REFERENCES reviews(tenant_id, review_id) ON DELETE CASCADE;
+ALTER TABLE late_delivery_alerts DROP CONSTRAINT IF EXISTS fk_late_delivery_alerts;
+ALTER TABLE late_delivery_alerts ADD CONSTRAINT fk_late_delivery_alerts
+    FOREIGN KEY (tenant_id, order_id) REFERENCES orders(tenant_id, order_id) ON DELETE CASCADE;
+ALTER TABLE customer_addresses DROP CONSTRAINT IF EXISTS fk_customer_customer_addresses;
+ALTER TABLE customer_addresses ADD CONSTRAINT fk_customer_customer_addresses
+    FOREIGN KEY (tenant_id, customer_id) REFERENCES customers(tenant_id, customer_id) ON DELETE CASCADE;
+ALTER TABLE product_images DROP CONSTRAINT IF EXISTS fk_product_product_images;
+ALTER TABLE product_images ADD CONSTRAINT fk_product_product_images
+    FOREIGN KEY (tenant_id, product_id) REFERENCES products(tenant_id, product_id) ON DELETE CASCADE;
+
+-- Category names, coupon codes, dashboard days and rule names are per tenant.
+ALTER TABLE product_categories DROP CONSTRAINT IF EXISTS product_categories_pkey;
+ALTER TABLE product_categories ADD PRIMARY KEY (tenant_id, product_category_name);
+
+ALTER TABLE order_coupons DROP CONSTRAINT IF EXISTS fk_coupon_order_coupons;
+ALTER TABLE coupons DROP CONSTRAINT IF EXISTS coupons_pkey;
+ALTER TABLE coupons ADD PRIMARY KEY (tenant_id, code);
+ALTER TABLE order_coupons ADD CONSTRAINT fk_coupon_order_coupons
+    FOREIGN KEY (tenant_id, code)
+    REFERENCES coupons(tenant_id, code)
+    ON DELETE NO ACTION
+    ON UPDATE NO ACTION;
+
+ALTER TABLE stats DROP CONSTRAINT IF EXISTS stats_pkey;
+ALTER TABLE stats ADD PRIMARY KEY (tenant_id, stat_date);
+
+ALTER TABLE data_quality_results DROP CONSTRAINT IF EXISTS data_quality_results_pkey;
+ALTER TABLE data_quality_results ADD PRIMARY KEY (tenant_id, rule_name);
+
+-- Rows written by triggers belong to the tenant of the row that fired them.
+DROP FUNCTION IF EXISTS bump_stats(DATE, BIGINT, DECIMAL, INTEGER);
+CREATE OR REPLACE FUNCTION bump_stats(
+    tenant VARCHAR,
+    day DATE,
+    orders_delta BIGINT,
+    revenue_delta DECIMAL,
+    imports_delta INTEGER
+) RETURNS void AS $$
+    INSERT INTO stats (tenant_id, stat_date, orders_count, revenue, active_imports, updated_at)
+    VALUES (tenant, day, orders_delta, revenue_delta, imports_delta, NOW())
+    ON CONFLICT (tenant_id, stat_date) DO UPDATE SET
+        orders_count = stats.orders_count + EXCLUDED.orders_count,
+        revenue = stats.revenue + EXCLUDED.revenue,
+        active_imports = stats.active_imports + EXCLUDED.active_imports,
+        updated_at = EXCLUDED.updated_at;
+$$ LANGUAGE sql;
+
+CREATE OR REPLACE FUNCTION count_order_stats() RETURNS trigger AS $$
+DECLARE
+    order_revenue DECIMAL;
+BEGIN
+    IF TG_OP IN ('UPDATE', 'DELETE') THEN
+        PERFORM bump_stats(OLD.tenant_id, OLD.order_purchase_timestamp::date, -1, 0, 0);
+    END IF;
+    IF TG_OP IN ('INSERT', 'UPDATE') THEN
+        PERFORM bump_stats(NEW.tenant_id, NEW.order_purchase_timestamp::date, 1, 0, 0);
+    END IF;
+
+    -- A changed purchase date moves the order's items to the new day.
+    IF TG_OP = 'UPDATE' THEN
+        SELECT COALESCE(SUM(price + freight_value), 0) INTO order_revenue
+        FROM order_items
+        WHERE order_id = NEW.order_id;
+
+        PERFORM bump_stats(OLD.tenant_id, OLD.order_purchase_timestamp::date, 0, -order_revenue, 0);
+        PERFORM bump_stats(NEW.tenant_id, NEW.order_purchase_timestamp::date, 0, order_revenue, 0);
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION count_order_item_stats() RETURNS trigger AS $$
+BEGIN
+    IF TG_OP IN ('UPDATE', 'DELETE') THEN
+        PERFORM bump_stats(o.tenant_id, o.order_purchase_timestamp::date, 0, -(OLD.price + OLD.freight_value), 0)
+        FROM orders o
+        WHERE o.order_id = OLD.order_id;
+    END IF;
+    IF TG_OP IN ('INSERT', 'UPDATE') THEN
+        PERFORM bump_stats(o.tenant_id, o.order_purchase_timestamp::date, 0, NEW.price + NEW.freight_value, 0)
+        FROM orders o
+        WHERE o.order_id = NEW.order_id;
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION count_import_stats() RETURNS trigger AS $$
+BEGIN
+    IF TG_OP IN ('UPDATE', 'DELETE') AND OLD.status = 'running' THEN
+        PERFORM bump_stats(OLD.tenant_id, OLD.started_at::date, 0, 0, -1);
+    END IF;
+    IF TG_OP IN ('INSERT', 'UPDATE') AND NEW.status = 'running' THEN
+        PERFORM bump_stats(NEW.tenant_id, NEW.started_at::date, 0, 0, 1);
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION record_customer_location() RETURNS trigger AS $$
+BEGIN
+    IF TG_OP = 'UPDATE' THEN
+        UPDATE customer_location_history
+        SET valid_to = LOCALTIMESTAMP
+        WHERE customer_id = NEW.customer_id AND valid_to IS NULL;
+    END IF;
+
+    INSERT INTO customer_location_history (
+        tenant_id, customer_id, customer_zip_code_prefix, customer_city, customer_state,
+        valid_from
+    )
+    VALUES (
+        NEW.tenant_id, NEW.customer_id, NEW.customer_zip_code_prefix, NEW.customer_city,
+        NEW.customer_state, CASE WHEN TG_OP = 'UPDATE' THEN LOCALTIMESTAMP END
+    );
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP FUNCTION IF EXISTS record_outbox_event(TEXT, TEXT, TEXT, JSONB);
+CREATE OR REPLACE FUNCTION record_outbox_event(
+    tenant TEXT,
+    aggregate_type TEXT,
+    aggregate_id TEXT,
+    event_type TEXT,
+    payload JSONB
+) RETURNS void AS $$
+    INSERT INTO outbox_events (tenant_id, aggregate_type, aggregate_id, event_type, payload)
+    VALUES (tenant, aggregate_type, aggregate_id, event_type, payload);
+$$ LANGUAGE sql;
+
+CREATE OR REPLACE FUNCTION record_order_events() RETURNS trigger AS $$
+BEGIN
+    IF TG_OP = 'INSERT' THEN
+        PERFORM record_outbox_event(NEW.tenant_id, 'order', NEW.order_id, 'order.created', jsonb_build_object(
+            'order_id', NEW.order_id,
+            'customer_id', NEW.customer_id,
+            'order_status', NEW.order_status,
+            'order_purchase_timestamp', NEW.order_purchase_timestamp
+        ));
+    ELSE
+        PERFORM record_outbox_event(NEW.tenant_id, 'order', NEW.order_id, 'order.status_changed', jsonb_build_object(
+            'order_id', NEW.order_id,
+            'previous_status', OLD.order_status,
+            'order_status', NEW.order_status,
+            'status_version', NEW.status_version
+        ));
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION record_payment_events() RETURNS trigger AS $$
+BEGIN
+    PERFORM record_outbox_event(NEW.tenant_id, 'payment', NEW.order_id, 'payment.created', jsonb_build_object(
+        'order_id', NEW.order_id,
+        'payment_sequential', NEW.payment_sequential,
+        'payment_type', NEW.payment_type,
+        'payment_installments', NEW.payment_installments,
+        'payment_value', NEW.payment_value::TEXT
+    ));
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION record_review_events() RETURNS trigger AS $$
+BEGIN
+    PERFORM record_outbox_event(NEW.tenant_id, 'review', NEW.review_id, 'review.created', jsonb_build_object(
+        'review_id', NEW.review_id,
+        'order_id', NEW.order_id,
+        'review_score', NEW.review_score,
+        'review_comment_title', NEW.review_comment_title,
+        'review_comment_message', NEW.review_comment_message,
+        'review_creation_date', NEW.review_creation_date
+    ));
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+-- Listeners hand each change to the waiters of the order's tenant.
+CREATE OR REPLACE FUNCTION notify_order_status_change() RETURNS trigger AS $$
+BEGIN
+    PERFORM pg_notify(
+        'order_status_changed',
+        json_build_object(
+            'tenant_id', NEW.tenant_id,
+            'order_id', NEW.order_id,
+            'order_status', NEW.order_status,
+            'status_version', NEW.status_version
+        )::text
+    );
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
@@ -0,0 +1,23 @@
+-- Revert: Drop the full schema of the SQLite backend.
+DROP TABLE IF EXISTS stats;
+DROP TABLE IF EXISTS import_batch_rows;
+DROP TABLE IF EXISTS import_batches;
+DROP TABLE IF EXISTS seller_badges;
+DROP TABLE IF EXISTS seller_badge_thresholds;
+DROP TABLE IF EXISTS support_case_messages;
+DROP TABLE IF EXISTS support_cases;
+DROP TABLE IF EXISTS order_amendments;
+DROP TABLE IF EXISTS location_stock;
+DROP TABLE IF EXISTS stock_locations;
+DROP TABLE IF EXISTS product_embeddings;
+DROP TABLE IF EXISTS audit_log;
+DROP TABLE IF EXISTS reviews;
+DROP TABLE IF EXISTS payments;
+DROP TABLE IF EXISTS order_items;
+DROP TABLE IF EXISTS product_categories;
+DROP TABLE IF EXISTS products;
+DROP TABLE IF EXISTS orders;
+DROP TABLE IF EXISTS customer_location_history;
+DROP TABLE IF EXISTS city_aliases;
+DROP TABLE IF EXISTS customers;
+DROP TABLE IF EXISTS sellers;
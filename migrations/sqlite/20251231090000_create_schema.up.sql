@@ -0,0 +1,446 @@
+-- Migration: Create the full schema for the SQLite backend
+-- The same tables as the Postgres migrations, without the parts SQLite has no equivalent for:
+-- no ICU collation, trigram indexes or pgvector (embeddings are stored as JSON arrays and
+-- compared in the application), and no status change notifications. Money columns are NUMERIC
+-- and read back rounded to two decimals. Triggers keep location history, status versions and
+-- the dashboard counters in step with the writes, as the Postgres triggers do. Foreign keys are
+-- enforced by the connection options, since SQLite turns them off by default.
+CREATE TABLE IF NOT EXISTS sellers (
+    seller_id VARCHAR(32) PRIMARY KEY,
+    seller_zip_code_prefix VARCHAR(10) NOT NULL,
+    seller_city VARCHAR(100) NOT NULL,
+    seller_state VARCHAR(2) NOT NULL,
+    canonical_city VARCHAR(100) NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_sellers_zip_code_prefix ON sellers(seller_zip_code_prefix);
+CREATE INDEX IF NOT EXISTS idx_sellers_state ON sellers(seller_state);
+CREATE INDEX IF NOT EXISTS idx_sellers_canonical_city ON sellers(canonical_city);
+
+CREATE TABLE IF NOT EXISTS customers (
+    customer_id VARCHAR(32) PRIMARY KEY,
+    customer_unique_id VARCHAR(32) NOT NULL,
+    customer_zip_code_prefix VARCHAR(10) NOT NULL,
+    customer_city VARCHAR(100) NOT NULL,
+    customer_state VARCHAR(2) NOT NULL,
+    canonical_city VARCHAR(100) NOT NULL,
+    deleted_at TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_customers_zip_code_prefix ON customers(customer_zip_code_prefix);
+CREATE INDEX IF NOT EXISTS idx_customers_state ON customers(customer_state);
+CREATE INDEX IF NOT EXISTS idx_customers_canonical_city ON customers(canonical_city);
+CREATE INDEX IF NOT EXISTS idx_customers_deleted_at ON customers(deleted_at);
+
+CREATE TABLE IF NOT EXISTS city_aliases (
+    alias VARCHAR(100) PRIMARY KEY,
+    canonical_city VARCHAR(100) NOT NULL
+);
+
+INSERT OR IGNORE INTO city_aliases (alias, canonical_city) VALUES
+    ('sp', 'sao paulo'),
+    ('sampa', 'sao paulo'),
+    ('sao paulo sp', 'sao paulo'),
+    ('sao paulo - sp', 'sao paulo'),
+    ('sao paulo / sao paulo', 'sao paulo'),
+    ('sao paulo, sao paulo', 'sao paulo'),
+    ('rj', 'rio de janeiro'),
+    ('rio de janeiro, rio de janeiro, brasil', 'rio de janeiro'),
+    ('rio de janeiro / rio de janeiro', 'rio de janeiro'),
+    ('bh', 'belo horizonte'),
+    ('belo horizont', 'belo horizonte'),
+    ('poa', 'porto alegre'),
+    ('floripa', 'florianopolis'),
+    ('brasilia df', 'brasilia'),
+    ('sbc', 'sao bernardo do campo'),
+    ('sao bernardo do capo', 'sao bernardo do campo'),
+    ('santo andre/sao paulo', 'santo andre'),
+    ('ribeirao preto / sao paulo', 'ribeirao preto'),
+    ('mogi das cruzes / sp', 'mogi das cruzes'),
+    ('sao jose dos pinhais/pr', 'sao jose dos pinhais'),
+    ('arraial d''ajuda (porto seguro)', 'arraial d''ajuda');
+
+CREATE TABLE IF NOT EXISTS customer_location_history (
+    history_id INTEGER PRIMARY KEY,
+    customer_id VARCHAR(32) NOT NULL REFERENCES customers(customer_id) ON DELETE CASCADE,
+    customer_zip_code_prefix VARCHAR(10) NOT NULL,
+    customer_city VARCHAR(100) NOT NULL,
+    customer_state VARCHAR(2) NOT NULL,
+    valid_from TIMESTAMP,
+    valid_to TIMESTAMP,
+    CHECK (valid_from IS NULL OR valid_to IS NULL OR valid_from <= valid_to)
+);
+
+CREATE INDEX IF NOT EXISTS idx_customer_location_history_customer
+    ON customer_location_history(customer_id, valid_from);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_customer_location_history_current
+    ON customer_location_history(customer_id) WHERE valid_to IS NULL;
+
+CREATE TRIGGER IF NOT EXISTS trg_customers_location_insert
+    AFTER INSERT ON customers
+BEGIN
+    INSERT INTO customer_location_history (
+        customer_id, customer_zip_code_prefix, customer_city, customer_state
+    )
+    VALUES (NEW.customer_id, NEW.customer_zip_code_prefix, NEW.customer_city, NEW.customer_state);
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_customers_location_update
+    AFTER UPDATE OF customer_zip_code_prefix, customer_city, customer_state ON customers
+    WHEN OLD.customer_zip_code_prefix IS NOT NEW.customer_zip_code_prefix
+        OR OLD.customer_city IS NOT NEW.customer_city
+        OR OLD.customer_state IS NOT NEW.customer_state
+BEGIN
+    UPDATE customer_location_history
+    SET valid_to = datetime('now')
+    WHERE customer_id = NEW.customer_id AND valid_to IS NULL;
+
+    INSERT INTO customer_location_history (
+        customer_id, customer_zip_code_prefix, customer_city, customer_state, valid_from
+    )
+    VALUES (
+        NEW.customer_id, NEW.customer_zip_code_prefix, NEW.customer_city, NEW.customer_state,
+        datetime('now')
+    );
+END;
+
+CREATE TABLE IF NOT EXISTS orders (
+    order_id VARCHAR(32) PRIMARY KEY,
+    customer_id VARCHAR(32) NOT NULL REFERENCES customers(customer_id),
+    order_status VARCHAR(12) NOT NULL
+        CHECK (order_status IN (
+            'created', 'approved', 'invoiced', 'processing',
+            'shipped', 'delivered', 'canceled', 'unavailable'
+        )),
+    order_purchase_timestamp TIMESTAMP NOT NULL,
+    order_approved_at TIMESTAMP NOT NULL,
+    order_delivered_carrier_date TIMESTAMP,
+    order_delivered_customer_date TIMESTAMP,
+    order_estimated_delivery_date TIMESTAMP NOT NULL,
+    shipping_zip_code_prefix VARCHAR(10),
+    status_version INTEGER NOT NULL DEFAULT 1
+);
+
+CREATE INDEX IF NOT EXISTS idx_orders_customer_id ON orders(customer_id);
+CREATE INDEX IF NOT EXISTS idx_orders_status ON orders(order_status);
+CREATE INDEX IF NOT EXISTS idx_orders_purchase_timestamp ON orders(order_purchase_timestamp);
+
+CREATE TRIGGER IF NOT EXISTS trg_orders_status_version
+    AFTER UPDATE OF order_status ON orders
+    WHEN OLD.order_status IS NOT NEW.order_status
+BEGIN
+    UPDATE orders SET status_version = OLD.status_version + 1 WHERE order_id = NEW.order_id;
+END;
+
+CREATE TABLE IF NOT EXISTS products (
+    product_id VARCHAR(32) PRIMARY KEY,
+    product_category_name VARCHAR(100) NOT NULL,
+    product_name_lenght INTEGER NOT NULL,
+    product_description_lenght INTEGER NOT NULL,
+    product_photos_qty INTEGER NOT NULL,
+    product_weight_g INTEGER NOT NULL,
+    product_length_cm INTEGER NOT NULL,
+    product_height_cm INTEGER NOT NULL,
+    product_width_cm INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_products_category_name ON products(product_category_name);
+
+CREATE TABLE IF NOT EXISTS product_categories (
+    product_category_name VARCHAR(100) PRIMARY KEY,
+    product_category_name_english VARCHAR(100),
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS order_items (
+    order_item_id INTEGER NOT NULL,
+    order_id VARCHAR(32) NOT NULL REFERENCES orders(order_id),
+    product_id VARCHAR(32) NOT NULL REFERENCES products(product_id),
+    seller_id VARCHAR(32) NOT NULL REFERENCES sellers(seller_id),
+    shipping_limit_date TIMESTAMP NOT NULL,
+    price NUMERIC NOT NULL,
+    freight_value NUMERIC NOT NULL,
+    PRIMARY KEY (order_item_id, order_id, product_id, seller_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_order_items_order_id ON order_items(order_id);
+
+CREATE TABLE IF NOT EXISTS payments (
+    order_id VARCHAR(32) PRIMARY KEY REFERENCES orders(order_id),
+    payment_sequential INTEGER NOT NULL,
+    payment_type VARCHAR(20) NOT NULL
+        CHECK (payment_type IN ('credit_card', 'debit_card', 'boleto', 'voucher', 'not_defined')),
+    payment_installments INTEGER NOT NULL,
+    payment_value NUMERIC NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS reviews (
+    review_id VARCHAR(32) PRIMARY KEY,
+    order_id VARCHAR(32) NOT NULL REFERENCES orders(order_id),
+    review_score INTEGER NOT NULL,
+    review_comment_title VARCHAR(30),
+    review_comment_message TEXT,
+    review_creation_date TIMESTAMP NOT NULL,
+    review_answer_timestamp TIMESTAMP NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_reviews_order_id ON reviews(order_id);
+
+CREATE TABLE IF NOT EXISTS audit_log (
+    audit_id INTEGER PRIMARY KEY,
+    entity_type VARCHAR(32) NOT NULL,
+    entity_id VARCHAR(64) NOT NULL,
+    action VARCHAR(16) NOT NULL,
+    actor VARCHAR(100) NOT NULL,
+    diff TEXT NOT NULL DEFAULT '{}',
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_audit_log_entity ON audit_log(entity_type, entity_id);
+CREATE INDEX IF NOT EXISTS idx_audit_log_created_at ON audit_log(created_at);
+
+CREATE TABLE IF NOT EXISTS product_embeddings (
+    product_id VARCHAR(32) PRIMARY KEY REFERENCES products(product_id) ON DELETE CASCADE,
+    embedding TEXT NOT NULL,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS stock_locations (
+    location_id INTEGER PRIMARY KEY,
+    seller_id VARCHAR(32) NOT NULL REFERENCES sellers(seller_id),
+    name VARCHAR(100) NOT NULL,
+    zip_code_prefix VARCHAR(10) NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_stock_locations_seller_id ON stock_locations(seller_id);
+
+CREATE TABLE IF NOT EXISTS location_stock (
+    location_id INTEGER NOT NULL REFERENCES stock_locations(location_id) ON DELETE CASCADE,
+    product_id VARCHAR(32) NOT NULL REFERENCES products(product_id),
+    quantity INTEGER NOT NULL CHECK (quantity >= 0),
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (location_id, product_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_location_stock_product_id ON location_stock(product_id);
+
+CREATE TABLE IF NOT EXISTS order_amendments (
+    amendment_id INTEGER PRIMARY KEY,
+    order_id VARCHAR(32) NOT NULL REFERENCES orders(order_id),
+    actor VARCHAR(100) NOT NULL,
+    changes TEXT NOT NULL,
+    previous_freight NUMERIC NOT NULL,
+    new_freight NUMERIC NOT NULL,
+    previous_tax NUMERIC NOT NULL,
+    new_tax NUMERIC NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_order_amendments_order_id ON order_amendments(order_id);
+
+CREATE TABLE IF NOT EXISTS support_cases (
+    case_id INTEGER PRIMARY KEY,
+    order_id VARCHAR(32) NOT NULL REFERENCES orders(order_id),
+    customer_id VARCHAR(32) NOT NULL REFERENCES customers(customer_id),
+    category VARCHAR(32) NOT NULL
+        CHECK (category IN (
+            'delivery_delay', 'damaged_item', 'wrong_item', 'missing_item',
+            'refund', 'payment', 'other'
+        )),
+    status VARCHAR(20) NOT NULL DEFAULT 'open'
+        CHECK (status IN ('open', 'pending_customer', 'resolved', 'closed')),
+    subject VARCHAR(200) NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    first_response_due_at TIMESTAMP NOT NULL,
+    resolution_due_at TIMESTAMP NOT NULL,
+    first_responded_at TIMESTAMP,
+    resolved_at TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_support_cases_order_id ON support_cases(order_id);
+CREATE INDEX IF NOT EXISTS idx_support_cases_customer_id ON support_cases(customer_id);
+CREATE INDEX IF NOT EXISTS idx_support_cases_status ON support_cases(status);
+
+CREATE TABLE IF NOT EXISTS support_case_messages (
+    message_id INTEGER PRIMARY KEY,
+    case_id INTEGER NOT NULL REFERENCES support_cases(case_id) ON DELETE CASCADE,
+    author_type VARCHAR(16) NOT NULL CHECK (author_type IN ('customer', 'agent')),
+    author VARCHAR(100) NOT NULL,
+    body TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_support_case_messages_case_id ON support_case_messages(case_id);
+
+CREATE TABLE IF NOT EXISTS seller_badge_thresholds (
+    badge VARCHAR(32) PRIMARY KEY,
+    description TEXT NOT NULL,
+    metric VARCHAR(32) NOT NULL
+        CHECK (metric IN ('avg_handling_hours', 'avg_review_score', 'order_count')),
+    comparison VARCHAR(3) NOT NULL CHECK (comparison IN ('lte', 'gte')),
+    threshold NUMERIC NOT NULL,
+    min_sample INTEGER NOT NULL DEFAULT 0
+);
+
+INSERT OR IGNORE INTO seller_badge_thresholds
+    (badge, description, metric, comparison, threshold, min_sample)
+VALUES
+    ('fast_shipper', 'Hands orders to the carrier within 48 hours of approval on average',
+        'avg_handling_hours', 'lte', 48, 20),
+    ('top_rated', 'Average review score of 4.5 or more',
+        'avg_review_score', 'gte', 4.5, 20),
+    ('high_volume', 'At least 500 orders sold',
+        'order_count', 'gte', 500, 0);
+
+CREATE TABLE IF NOT EXISTS seller_badges (
+    seller_id VARCHAR(32) NOT NULL REFERENCES sellers(seller_id) ON DELETE CASCADE,
+    badge VARCHAR(32) NOT NULL REFERENCES seller_badge_thresholds(badge) ON DELETE CASCADE,
+    metric_value NUMERIC NOT NULL,
+    computed_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (seller_id, badge)
+);
+
+CREATE INDEX IF NOT EXISTS idx_seller_badges_badge ON seller_badges(badge);
+
+CREATE TABLE IF NOT EXISTS import_batches (
+    batch_id INTEGER PRIMARY KEY,
+    dataset VARCHAR(20) NOT NULL
+        CHECK (dataset IN ('customers', 'sellers', 'orders', 'products')),
+    source VARCHAR(500) NOT NULL,
+    actor VARCHAR(100) NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'running'
+        CHECK (status IN ('running', 'completed', 'failed', 'rolled_back')),
+    success_count INTEGER NOT NULL DEFAULT 0,
+    error_count INTEGER NOT NULL DEFAULT 0,
+    started_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    finished_at TIMESTAMP,
+    rolled_back_at TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_import_batches_started_at ON import_batches(started_at);
+
+CREATE TABLE IF NOT EXISTS import_batch_rows (
+    batch_id INTEGER NOT NULL REFERENCES import_batches(batch_id) ON DELETE CASCADE,
+    entity_id VARCHAR(32) NOT NULL,
+    PRIMARY KEY (batch_id, entity_id)
+);
+
+-- Dashboard counters, one row per day; see the Postgres stats migration for the columns.
+CREATE TABLE IF NOT EXISTS stats (
+    stat_date DATE PRIMARY KEY,
+    orders_count INTEGER NOT NULL DEFAULT 0,
+    revenue NUMERIC NOT NULL DEFAULT 0,
+    active_imports INTEGER NOT NULL DEFAULT 0,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TRIGGER IF NOT EXISTS trg_orders_stats_insert
+    AFTER INSERT ON orders
+BEGIN
+    INSERT OR IGNORE INTO stats (stat_date) VALUES (date(NEW.order_purchase_timestamp));
+    UPDATE stats SET orders_count = orders_count + 1, updated_at = CURRENT_TIMESTAMP
+    WHERE stat_date = date(NEW.order_purchase_timestamp);
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_orders_stats_delete
+    AFTER DELETE ON orders
+BEGIN
+    UPDATE stats SET orders_count = orders_count - 1, updated_at = CURRENT_TIMESTAMP
+    WHERE stat_date = date(OLD.order_purchase_timestamp);
+END;
+
+-- A changed purchase date moves the order and its items' revenue to the new day.
+CREATE TRIGGER IF NOT EXISTS trg_orders_stats_update
+    AFTER UPDATE OF order_purchase_timestamp ON orders
+    WHEN date(OLD.order_purchase_timestamp) IS NOT date(NEW.order_purchase_timestamp)
+BEGIN
+    UPDATE stats
+    SET
+        orders_count = orders_count - 1,
+        revenue = revenue - (
+            SELECT COALESCE(SUM(price + freight_value), 0)
+            FROM order_items WHERE order_id = NEW.order_id
+        ),
+        updated_at = CURRENT_TIMESTAMP
+    WHERE stat_date = date(OLD.order_purchase_timestamp);
+
+    INSERT OR IGNORE INTO stats (stat_date) VALUES (date(NEW.order_purchase_timestamp));
+    UPDATE stats
+    SET
+        orders_count = orders_count + 1,
+        revenue = revenue + (
+            SELECT COALESCE(SUM(price + freight_value), 0)
+            FROM order_items WHERE order_id = NEW.order_id
+        ),
+        updated_at = CURRENT_TIMESTAMP
+    WHERE stat_date = date(NEW.order_purchase_timestamp);
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_order_items_stats_insert
+    AFTER INSERT ON order_items
+BEGIN
+    UPDATE stats
+    SET revenue = revenue + NEW.price + NEW.freight_value, updated_at = CURRENT_TIMESTAMP
+    WHERE stat_date = (
+        SELECT date(order_purchase_timestamp) FROM orders WHERE order_id = NEW.order_id
+    );
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_order_items_stats_update
+    AFTER UPDATE OF price, freight_value, order_id ON order_items
+BEGIN
+    UPDATE stats
+    SET revenue = revenue - OLD.price - OLD.freight_value, updated_at = CURRENT_TIMESTAMP
+    WHERE stat_date = (
+        SELECT date(order_purchase_timestamp) FROM orders WHERE order_id = OLD.order_id
+    );
+    UPDATE stats
+    SET revenue = revenue + NEW.price + NEW.freight_value, updated_at = CURRENT_TIMESTAMP
+    WHERE stat_date = (
+        SELECT date(order_purchase_timestamp) FROM orders WHERE order_id = NEW.order_id
+    );
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_order_items_stats_delete
+    AFTER DELETE ON order_items
+BEGIN
+    UPDATE stats
+    SET revenue = revenue - OLD.price - OLD.freight_value, updated_at = CURRENT_TIMESTAMP
+    WHERE stat_date = (
+        SELECT date(order_purchase_timestamp) FROM orders WHERE order_id = OLD.order_id
+    );
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_import_batches_stats_insert
+    AFTER INSERT ON import_batches
+    WHEN NEW.status = 'running'
+BEGIN
+    INSERT OR IGNORE INTO stats (stat_date) VALUES (date(NEW.started_at));
+    UPDATE stats SET active_imports = active_imports + 1, updated_at = CURRENT_TIMESTAMP
+    WHERE stat_date = date(NEW.started_at);
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_import_batches_stats_update
+    AFTER UPDATE OF status ON import_batches
+    WHEN (OLD.status = 'running') IS NOT (NEW.status = 'running')
+BEGIN
+    INSERT OR IGNORE INTO stats (stat_date) VALUES (date(NEW.started_at));
+    UPDATE stats
+    SET
+        active_imports = active_imports
+            + (CASE WHEN NEW.status = 'running' THEN 1 ELSE 0 END)
+            - (CASE WHEN OLD.status = 'running' THEN 1 ELSE 0 END),
+        updated_at = CURRENT_TIMESTAMP
+    WHERE stat_date = date(NEW.started_at);
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_import_batches_stats_delete
+    AFTER DELETE ON import_batches
+    WHEN OLD.status = 'running'
+BEGIN
+    UPDATE stats SET active_imports = active_imports - 1, updated_at = CURRENT_TIMESTAMP
+    WHERE stat_date = date(OLD.started_at);
+END;
@@ -0,0 +1,6 @@
+-- Revert: Drop webhook tables and the triggers that queue deliveries.
+DROP TRIGGER IF EXISTS trg_reviews_webhook_insert;
+DROP TRIGGER IF EXISTS trg_orders_webhook_status;
+DROP TRIGGER IF EXISTS trg_orders_webhook_insert;
+DROP TABLE IF EXISTS webhook_deliveries;
+DROP TABLE IF EXISTS webhook_subscriptions;
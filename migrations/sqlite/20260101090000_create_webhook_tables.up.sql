@@ -0,0 +1,78 @@
+-- Webhook subscriptions and their delivery outbox; see the Postgres webhook migration.
+-- events is a JSON array of event names. Timestamps in payloads use the ISO 8601 form Postgres
+-- renders.
+CREATE TABLE IF NOT EXISTS webhook_subscriptions (
+    subscription_id INTEGER PRIMARY KEY,
+    url VARCHAR(2000) NOT NULL,
+    events TEXT NOT NULL,
+    secret VARCHAR(100) NOT NULL,
+    created_by VARCHAR(100) NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS webhook_deliveries (
+    delivery_id INTEGER PRIMARY KEY,
+    subscription_id INTEGER NOT NULL
+        REFERENCES webhook_subscriptions(subscription_id) ON DELETE CASCADE,
+    event VARCHAR(40) NOT NULL,
+    payload TEXT NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'succeeded', 'failed')),
+    attempts INTEGER NOT NULL DEFAULT 0,
+    next_attempt_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    last_status_code INTEGER,
+    last_error TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    delivered_at TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_subscription
+    ON webhook_deliveries(subscription_id, created_at);
+CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_due
+    ON webhook_deliveries(next_attempt_at)
+    WHERE status = 'pending';
+
+CREATE TRIGGER IF NOT EXISTS trg_orders_webhook_insert
+    AFTER INSERT ON orders
+BEGIN
+    INSERT INTO webhook_deliveries (subscription_id, event, payload)
+    SELECT s.subscription_id, 'order.created', json_object(
+        'order_id', NEW.order_id,
+        'customer_id', NEW.customer_id,
+        'order_status', NEW.order_status,
+        'order_purchase_timestamp', replace(NEW.order_purchase_timestamp, ' ', 'T')
+    )
+    FROM webhook_subscriptions s
+    WHERE EXISTS (SELECT 1 FROM json_each(s.events) WHERE value = 'order.created');
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_orders_webhook_status
+    AFTER UPDATE OF order_status ON orders
+    WHEN OLD.order_status IS NOT NEW.order_status
+BEGIN
+    INSERT INTO webhook_deliveries (subscription_id, event, payload)
+    SELECT s.subscription_id, 'order.status_changed', json_object(
+        'order_id', NEW.order_id,
+        'previous_status', OLD.order_status,
+        'order_status', NEW.order_status,
+        'status_version', NEW.status_version
+    )
+    FROM webhook_subscriptions s
+    WHERE EXISTS (SELECT 1 FROM json_each(s.events) WHERE value = 'order.status_changed');
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_reviews_webhook_insert
+    AFTER INSERT ON reviews
+BEGIN
+    INSERT INTO webhook_deliveries (subscription_id, event, payload)
+    SELECT s.subscription_id, 'review.created', json_object(
+        'review_id', NEW.review_id,
+        'order_id', NEW.order_id,
+        'review_score', NEW.review_score,
+        'review_comment_title', NEW.review_comment_title,
+        'review_comment_message', NEW.review_comment_message,
+        'review_creation_date', replace(NEW.review_creation_date, ' ', 'T')
+    )
+    FROM webhook_subscriptions s
+    WHERE EXISTS (SELECT 1 FROM json_each(s.events) WHERE value = 'review.created');
+END;
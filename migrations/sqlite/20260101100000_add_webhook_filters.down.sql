@@ -0,0 +1,3 @@
+-- Revert: Remove webhook entity filters and payload templates.
+ALTER TABLE webhook_subscriptions DROP COLUMN payload_template;
+ALTER TABLE webhook_subscriptions DROP COLUMN customer_states;
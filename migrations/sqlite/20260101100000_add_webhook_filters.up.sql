@@ -0,0 +1,5 @@
+-- Webhook entity filters and payload templates; see the Postgres migration. customer_states
+-- is a JSON array of UF codes.
+ALTER TABLE webhook_subscriptions ADD COLUMN customer_states TEXT;
+ALTER TABLE webhook_subscriptions ADD COLUMN payload_template VARCHAR(10) NOT NULL DEFAULT 'full'
+    CHECK (payload_template IN ('full', 'slim'));
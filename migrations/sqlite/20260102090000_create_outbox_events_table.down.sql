@@ -0,0 +1,52 @@
+-- Revert: Drop the outbox and queue webhook deliveries from triggers again. Events still in
+-- the outbox are lost.
+DROP TRIGGER IF EXISTS trg_reviews_outbox_insert;
+DROP TRIGGER IF EXISTS trg_payments_outbox_insert;
+DROP TRIGGER IF EXISTS trg_orders_outbox_status;
+DROP TRIGGER IF EXISTS trg_orders_outbox_insert;
+DROP TABLE IF EXISTS outbox_events;
+
+CREATE TRIGGER IF NOT EXISTS trg_orders_webhook_insert
+    AFTER INSERT ON orders
+BEGIN
+    INSERT INTO webhook_deliveries (subscription_id, event, payload)
+    SELECT s.subscription_id, 'order.created', json_object(
+        'order_id', NEW.order_id,
+        'customer_id', NEW.customer_id,
+        'order_status', NEW.order_status,
+        'order_purchase_timestamp', replace(NEW.order_purchase_timestamp, ' ', 'T')
+    )
+    FROM webhook_subscriptions s
+    WHERE EXISTS (SELECT 1 FROM json_each(s.events) WHERE value = 'order.created');
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_orders_webhook_status
+    AFTER UPDATE OF order_status ON orders
+    WHEN OLD.order_status IS NOT NEW.order_status
+BEGIN
+    INSERT INTO webhook_deliveries (subscription_id, event, payload)
+    SELECT s.subscription_id, 'order.status_changed', json_object(
+        'order_id', NEW.order_id,
+        'previous_status', OLD.order_status,
+        'order_status', NEW.order_status,
+        'status_version', NEW.status_version
+    )
+    FROM webhook_subscriptions s
+    WHERE EXISTS (SELECT 1 FROM json_each(s.events) WHERE value = 'order.status_changed');
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_reviews_webhook_insert
+    AFTER INSERT ON reviews
+BEGIN
+    INSERT INTO webhook_deliveries (subscription_id, event, payload)
+    SELECT s.subscription_id, 'review.created', json_object(
+        'review_id', NEW.review_id,
+        'order_id', NEW.order_id,
+        'review_score', NEW.review_score,
+        'review_comment_title', NEW.review_comment_title,
+        'review_comment_message', NEW.review_comment_message,
+        'review_creation_date', replace(NEW.review_creation_date, ' ', 'T')
+    )
+    FROM webhook_subscriptions s
+    WHERE EXISTS (SELECT 1 FROM json_each(s.events) WHERE value = 'review.created');
+END;
@@ -0,0 +1,77 @@
+-- Outbox of order, payment and review events; see the Postgres outbox migration. Replaces the
+-- triggers that queued webhook deliveries directly: the relay queues them when it publishes.
+CREATE TABLE IF NOT EXISTS outbox_events (
+    event_id INTEGER PRIMARY KEY,
+    aggregate_type VARCHAR(40) NOT NULL,
+    aggregate_id VARCHAR(64) NOT NULL,
+    event_type VARCHAR(40) NOT NULL,
+    payload TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    published_at TIMESTAMP,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    next_attempt_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    last_error TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_outbox_events_unpublished
+    ON outbox_events(event_id)
+    WHERE published_at IS NULL;
+CREATE INDEX IF NOT EXISTS idx_outbox_events_published_at
+    ON outbox_events(published_at)
+    WHERE published_at IS NOT NULL;
+
+DROP TRIGGER IF EXISTS trg_orders_webhook_insert;
+DROP TRIGGER IF EXISTS trg_orders_webhook_status;
+DROP TRIGGER IF EXISTS trg_reviews_webhook_insert;
+
+CREATE TRIGGER IF NOT EXISTS trg_orders_outbox_insert
+    AFTER INSERT ON orders
+BEGIN
+    INSERT INTO outbox_events (aggregate_type, aggregate_id, event_type, payload)
+    VALUES ('order', NEW.order_id, 'order.created', json_object(
+        'order_id', NEW.order_id,
+        'customer_id', NEW.customer_id,
+        'order_status', NEW.order_status,
+        'order_purchase_timestamp', replace(NEW.order_purchase_timestamp, ' ', 'T')
+    ));
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_orders_outbox_status
+    AFTER UPDATE OF order_status ON orders
+    WHEN OLD.order_status IS NOT NEW.order_status
+BEGIN
+    INSERT INTO outbox_events (aggregate_type, aggregate_id, event_type, payload)
+    VALUES ('order', NEW.order_id, 'order.status_changed', json_object(
+        'order_id', NEW.order_id,
+        'previous_status', OLD.order_status,
+        'order_status', NEW.order_status,
+        'status_version', NEW.status_version
+    ));
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_payments_outbox_insert
+    AFTER INSERT ON payments
+BEGIN
+    INSERT INTO outbox_events (aggregate_type, aggregate_id, event_type, payload)
+    VALUES ('payment', NEW.order_id, 'payment.created', json_object(
+        'order_id', NEW.order_id,
+        'payment_sequential', NEW.payment_sequential,
+        'payment_type', NEW.payment_type,
+        'payment_installments', NEW.payment_installments,
+        'payment_value', printf('%.2f', NEW.payment_value)
+    ));
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_reviews_outbox_insert
+    AFTER INSERT ON reviews
+BEGIN
+    INSERT INTO outbox_events (aggregate_type, aggregate_id, event_type, payload)
+    VALUES ('review', NEW.review_id, 'review.created', json_object(
+        'review_id', NEW.review_id,
+        'order_id', NEW.order_id,
+        'review_score', NEW.review_score,
+        'review_comment_title', NEW.review_comment_title,
+        'review_comment_message', NEW.review_comment_message,
+        'review_creation_date', replace(NEW.review_creation_date, ' ', 'T')
+    ));
+END;
@@ -0,0 +1,2 @@
+-- Revert: Drop the import_errors table.
+DROP TABLE IF EXISTS import_errors;
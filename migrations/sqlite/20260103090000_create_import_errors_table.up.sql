@@ -0,0 +1,11 @@
+-- Rows import batches could not insert; see the Postgres import_errors migration.
+CREATE TABLE IF NOT EXISTS import_errors (
+    error_id INTEGER PRIMARY KEY,
+    batch_id INTEGER NOT NULL REFERENCES import_batches(batch_id) ON DELETE CASCADE,
+    row_number INTEGER NOT NULL,
+    record TEXT,
+    reason TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_import_errors_batch_row ON import_errors(batch_id, row_number);
@@ -0,0 +1,88 @@
+-- Revert: Drop the load_jobs table and import batch checkpoints.
+-- SQLite can't drop a column with a foreign key, so import_batches is rebuilt. Renaming it
+-- points the foreign keys of its rows and errors at the old table, so those are rebuilt too,
+-- and the old tables dropped children first so no cascade reaches the copied rows.
+ALTER TABLE import_errors RENAME TO import_errors_old;
+ALTER TABLE import_batch_rows RENAME TO import_batch_rows_old;
+ALTER TABLE import_batches RENAME TO import_batches_old;
+
+CREATE TABLE import_batches (
+    batch_id INTEGER PRIMARY KEY,
+    dataset VARCHAR(20) NOT NULL
+        CHECK (dataset IN ('customers', 'sellers', 'orders', 'products')),
+    source VARCHAR(500) NOT NULL,
+    actor VARCHAR(100) NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'running'
+        CHECK (status IN ('running', 'completed', 'failed', 'rolled_back')),
+    success_count INTEGER NOT NULL DEFAULT 0,
+    error_count INTEGER NOT NULL DEFAULT 0,
+    started_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    finished_at TIMESTAMP,
+    rolled_back_at TIMESTAMP
+);
+
+CREATE TABLE import_batch_rows (
+    batch_id INTEGER NOT NULL REFERENCES import_batches(batch_id) ON DELETE CASCADE,
+    entity_id VARCHAR(32) NOT NULL,
+    PRIMARY KEY (batch_id, entity_id)
+);
+
+CREATE TABLE import_errors (
+    error_id INTEGER PRIMARY KEY,
+    batch_id INTEGER NOT NULL REFERENCES import_batches(batch_id) ON DELETE CASCADE,
+    row_number INTEGER NOT NULL,
+    record TEXT,
+    reason TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+INSERT INTO import_batches (
+    batch_id, dataset, source, actor, status, success_count, error_count, started_at,
+    finished_at, rolled_back_at
+)
+SELECT
+    batch_id, dataset, source, actor, status, success_count, error_count, started_at,
+    finished_at, rolled_back_at
+FROM import_batches_old;
+INSERT INTO import_batch_rows SELECT * FROM import_batch_rows_old;
+INSERT INTO import_errors SELECT * FROM import_errors_old;
+
+DROP TABLE import_errors_old;
+DROP TABLE import_batch_rows_old;
+DROP TABLE import_batches_old;
+
+CREATE INDEX IF NOT EXISTS idx_import_batches_started_at ON import_batches(started_at);
+CREATE INDEX IF NOT EXISTS idx_import_errors_batch_row ON import_errors(batch_id, row_number);
+
+CREATE TRIGGER IF NOT EXISTS trg_import_batches_stats_insert
+    AFTER INSERT ON import_batches
+    WHEN NEW.status = 'running'
+BEGIN
+    INSERT OR IGNORE INTO stats (stat_date) VALUES (date(NEW.started_at));
+    UPDATE stats SET active_imports = active_imports + 1, updated_at = CURRENT_TIMESTAMP
+    WHERE stat_date = date(NEW.started_at);
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_import_batches_stats_update
+    AFTER UPDATE OF status ON import_batches
+    WHEN (OLD.status = 'running') IS NOT (NEW.status = 'running')
+BEGIN
+    INSERT OR IGNORE INTO stats (stat_date) VALUES (date(NEW.started_at));
+    UPDATE stats
+    SET
+        active_imports = active_imports
+            + (CASE WHEN NEW.status = 'running' THEN 1 ELSE 0 END)
+            - (CASE WHEN OLD.status = 'running' THEN 1 ELSE 0 END),
+        updated_at = CURRENT_TIMESTAMP
+    WHERE stat_date = date(NEW.started_at);
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_import_batches_stats_delete
+    AFTER DELETE ON import_batches
+    WHEN OLD.status = 'running'
+BEGIN
+    UPDATE stats SET active_imports = active_imports - 1, updated_at = CURRENT_TIMESTAMP
+    WHERE stat_date = date(OLD.started_at);
+END;
+
+DROP TABLE IF EXISTS load_jobs;
@@ -0,0 +1,13 @@
+-- Load jobs and import batch checkpoints; see the Postgres load_jobs migration.
+CREATE TABLE IF NOT EXISTS load_jobs (
+    job_id INTEGER PRIMARY KEY,
+    datasets VARCHAR(100) NOT NULL,
+    actor VARCHAR(100) NOT NULL,
+    started_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+ALTER TABLE import_batches
+    ADD COLUMN load_job_id INTEGER REFERENCES load_jobs(job_id) ON DELETE SET NULL;
+ALTER TABLE import_batches ADD COLUMN checkpoint_line INTEGER NOT NULL DEFAULT 1;
+
+CREATE INDEX IF NOT EXISTS idx_import_batches_load_job ON import_batches(load_job_id);
@@ -0,0 +1,2 @@
+-- Revert: Drop the geolocation table.
+DROP TABLE IF EXISTS geolocation;
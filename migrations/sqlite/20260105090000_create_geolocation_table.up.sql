@@ -0,0 +1,9 @@
+-- Coordinates per CEP prefix; see the Postgres geolocation migration.
+CREATE TABLE IF NOT EXISTS geolocation (
+    geolocation_zip_code_prefix VARCHAR(10) PRIMARY KEY,
+    geolocation_lat REAL NOT NULL,
+    geolocation_lng REAL NOT NULL,
+    geolocation_city VARCHAR(100) NOT NULL,
+    geolocation_state VARCHAR(2) NOT NULL,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
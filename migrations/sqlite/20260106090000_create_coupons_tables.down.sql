@@ -0,0 +1,3 @@
+-- Revert: Drop the order_coupons and coupons tables.
+DROP TABLE IF EXISTS order_coupons;
+DROP TABLE IF EXISTS coupons;
@@ -0,0 +1,20 @@
+-- Coupons and the orders they are applied to; see the Postgres coupons migration.
+CREATE TABLE IF NOT EXISTS coupons (
+    code VARCHAR(40) PRIMARY KEY,
+    discount_type VARCHAR(20) NOT NULL CHECK (discount_type IN ('percentage', 'fixed')),
+    value NUMERIC NOT NULL CHECK (value > 0),
+    min_order_value NUMERIC CHECK (min_order_value >= 0),
+    expires_at TIMESTAMP,
+    max_uses INTEGER CHECK (max_uses > 0),
+    times_used INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CHECK (max_uses IS NULL OR times_used <= max_uses)
+);
+
+CREATE TABLE IF NOT EXISTS order_coupons (
+    order_id VARCHAR(32) PRIMARY KEY REFERENCES orders(order_id) ON DELETE CASCADE,
+    code VARCHAR(40) NOT NULL REFERENCES coupons(code),
+    applied_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_order_coupons_code ON order_coupons(code);
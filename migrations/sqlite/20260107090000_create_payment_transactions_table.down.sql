@@ -0,0 +1,2 @@
+-- Revert: Drop the payment_transactions table.
+DROP TABLE IF EXISTS payment_transactions;
@@ -0,0 +1,20 @@
+-- Payments made through a payment provider; see the Postgres payment_transactions migration.
+CREATE TABLE IF NOT EXISTS payment_transactions (
+    transaction_id INTEGER PRIMARY KEY,
+    order_id VARCHAR(32) NOT NULL REFERENCES orders(order_id) ON DELETE CASCADE,
+    provider VARCHAR(40) NOT NULL,
+    provider_reference VARCHAR(100) NOT NULL,
+    payment_type VARCHAR(20) NOT NULL CHECK (
+        payment_type IN ('credit_card', 'debit_card', 'boleto', 'voucher', 'not_defined')
+    ),
+    payment_installments INTEGER NOT NULL CHECK (payment_installments > 0),
+    amount NUMERIC NOT NULL CHECK (amount > 0),
+    status VARCHAR(20) NOT NULL CHECK (
+        status IN ('authorized', 'captured', 'refunded', 'failed', 'canceled')
+    ),
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (provider, provider_reference)
+);
+
+CREATE INDEX IF NOT EXISTS idx_payment_transactions_order_id ON payment_transactions(order_id);
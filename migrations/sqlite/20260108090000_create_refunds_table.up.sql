@@ -0,0 +1,11 @@
+-- Refunds of an order's payments; see the Postgres refunds migration.
+CREATE TABLE IF NOT EXISTS refunds (
+    refund_id INTEGER PRIMARY KEY,
+    order_id VARCHAR(32) NOT NULL REFERENCES orders(order_id) ON DELETE CASCADE,
+    payment_sequential INTEGER NOT NULL CHECK (payment_sequential > 0),
+    amount NUMERIC NOT NULL CHECK (amount > 0),
+    reason TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_refunds_order_id ON refunds(order_id);
@@ -0,0 +1,2 @@
+-- Revert: Drop the notifications table.
+DROP TABLE IF EXISTS notifications;
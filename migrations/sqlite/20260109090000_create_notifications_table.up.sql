@@ -0,0 +1,23 @@
+-- Notifications queued by the outbox relay; see the Postgres notifications migration.
+CREATE TABLE IF NOT EXISTS notifications (
+    notification_id INTEGER PRIMARY KEY,
+    event_id INTEGER NOT NULL,
+    kind VARCHAR(40) NOT NULL
+        CHECK (kind IN ('order_approved', 'order_delivered', 'review_received')),
+    payload TEXT NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'sent', 'failed')),
+    channel VARCHAR(40),
+    recipient TEXT,
+    subject TEXT,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    next_attempt_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    last_error TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    sent_at TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_notifications_created_at ON notifications(created_at);
+CREATE INDEX IF NOT EXISTS idx_notifications_due
+    ON notifications(next_attempt_at)
+    WHERE status = 'pending';
@@ -0,0 +1,2 @@
+-- Revert: Drop the review retention index.
+DROP INDEX IF EXISTS idx_reviews_creation_date;
@@ -0,0 +1,2 @@
+-- Index for the review retention rule; see the Postgres retention indexes migration.
+CREATE INDEX IF NOT EXISTS idx_reviews_creation_date ON reviews(review_creation_date);
@@ -0,0 +1,2 @@
+-- Revert: Drop the unique customer lookup index.
+DROP INDEX IF EXISTS idx_customers_unique_id;
@@ -0,0 +1,2 @@
+-- Index for unique customer lookups; see the Postgres customer_unique_id index migration.
+CREATE INDEX IF NOT EXISTS idx_customers_unique_id ON customers(customer_unique_id);
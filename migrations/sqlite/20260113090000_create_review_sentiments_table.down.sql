@@ -0,0 +1,2 @@
+-- Revert: Drop the review_sentiments table.
+DROP TABLE IF EXISTS review_sentiments;
@@ -0,0 +1,8 @@
+-- Sentiments of review comments; see the Postgres review_sentiments migration.
+CREATE TABLE IF NOT EXISTS review_sentiments (
+    review_id VARCHAR(32) PRIMARY KEY REFERENCES reviews(review_id) ON DELETE CASCADE,
+    sentiment VARCHAR(8) NOT NULL CHECK (sentiment IN ('positive', 'neutral', 'negative')),
+    tagged_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_review_sentiments_sentiment ON review_sentiments(sentiment);
@@ -0,0 +1,31 @@
+-- Revert: Drop late delivery alerts. Unsent order_late notifications are dropped too, as the
+-- rebuilt notifications table no longer allows them.
+DROP TABLE IF EXISTS late_delivery_alerts;
+DROP INDEX IF EXISTS idx_orders_estimated_delivery_date;
+
+CREATE TABLE notifications_new (
+    notification_id INTEGER PRIMARY KEY,
+    event_id INTEGER NOT NULL,
+    kind VARCHAR(40) NOT NULL
+        CHECK (kind IN ('order_approved', 'order_delivered', 'review_received')),
+    payload TEXT NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'sent', 'failed')),
+    channel VARCHAR(40),
+    recipient TEXT,
+    subject TEXT,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    next_attempt_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    last_error TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    sent_at TIMESTAMP
+);
+
+INSERT INTO notifications_new SELECT * FROM notifications WHERE kind <> 'order_late';
+DROP TABLE notifications;
+ALTER TABLE notifications_new RENAME TO notifications;
+
+CREATE INDEX IF NOT EXISTS idx_notifications_created_at ON notifications(created_at);
+CREATE INDEX IF NOT EXISTS idx_notifications_due
+    ON notifications(next_attempt_at)
+    WHERE status = 'pending';
@@ -0,0 +1,37 @@
+-- Late delivery alerts; see the Postgres late_delivery_alerts migration.
+CREATE TABLE IF NOT EXISTS late_delivery_alerts (
+    order_id VARCHAR(32) PRIMARY KEY REFERENCES orders(order_id) ON DELETE CASCADE,
+    order_estimated_delivery_date TIMESTAMP NOT NULL,
+    flagged_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- The Postgres schema has had this index from the start.
+CREATE INDEX IF NOT EXISTS idx_orders_estimated_delivery_date ON orders(order_estimated_delivery_date);
+
+-- SQLite can't alter a CHECK constraint, so notifications is rebuilt to allow order_late.
+CREATE TABLE notifications_new (
+    notification_id INTEGER PRIMARY KEY,
+    event_id INTEGER NOT NULL,
+    kind VARCHAR(40) NOT NULL
+        CHECK (kind IN ('order_approved', 'order_delivered', 'order_late', 'review_received')),
+    payload TEXT NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'sent', 'failed')),
+    channel VARCHAR(40),
+    recipient TEXT,
+    subject TEXT,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    next_attempt_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    last_error TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    sent_at TIMESTAMP
+);
+
+INSERT INTO notifications_new SELECT * FROM notifications;
+DROP TABLE notifications;
+ALTER TABLE notifications_new RENAME TO notifications;
+
+CREATE INDEX IF NOT EXISTS idx_notifications_created_at ON notifications(created_at);
+CREATE INDEX IF NOT EXISTS idx_notifications_due
+    ON notifications(next_attempt_at)
+    WHERE status = 'pending';
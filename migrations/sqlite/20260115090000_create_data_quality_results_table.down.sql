@@ -0,0 +1,2 @@
+-- Revert: Drop the data_quality_results table.
+DROP TABLE IF EXISTS data_quality_results;
@@ -0,0 +1,11 @@
+-- Data quality rule results; see the Postgres data_quality_results migration.
+CREATE TABLE IF NOT EXISTS data_quality_results (
+    rule_name VARCHAR(100) PRIMARY KEY,
+    table_name VARCHAR(63) NOT NULL,
+    condition TEXT NOT NULL,
+    severity VARCHAR(10) NOT NULL CHECK (severity IN ('info', 'warning', 'error')),
+    checked_count INTEGER,
+    violation_count INTEGER,
+    error TEXT,
+    evaluated_at TIMESTAMP NOT NULL
+);
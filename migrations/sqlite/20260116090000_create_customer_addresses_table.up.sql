@@ -0,0 +1,17 @@
+-- Customer address book; see the Postgres customer_addresses migration.
+CREATE TABLE IF NOT EXISTS customer_addresses (
+    address_id INTEGER PRIMARY KEY,
+    customer_id VARCHAR(32) NOT NULL REFERENCES customers(customer_id) ON DELETE CASCADE,
+    label VARCHAR(50) NOT NULL,
+    zip_code_prefix VARCHAR(10) NOT NULL,
+    city VARCHAR(100) NOT NULL,
+    state VARCHAR(2) NOT NULL,
+    is_default BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_customer_addresses_customer_id ON customer_addresses(customer_id);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_customer_addresses_default
+    ON customer_addresses(customer_id)
+    WHERE is_default;
@@ -0,0 +1,5 @@
+-- Revert: Remove the product catalog fields.
+ALTER TABLE products DROP COLUMN active;
+ALTER TABLE products DROP COLUMN price;
+ALTER TABLE products DROP COLUMN description;
+ALTER TABLE products DROP COLUMN product_name;
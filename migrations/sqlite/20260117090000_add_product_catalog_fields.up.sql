@@ -0,0 +1,5 @@
+-- Product catalog fields; see the Postgres add_product_catalog_fields migration.
+ALTER TABLE products ADD COLUMN product_name VARCHAR(255);
+ALTER TABLE products ADD COLUMN description TEXT;
+ALTER TABLE products ADD COLUMN price DECIMAL(10, 2) CHECK (price >= 0);
+ALTER TABLE products ADD COLUMN active BOOLEAN NOT NULL DEFAULT TRUE;
@@ -0,0 +1,2 @@
+-- Revert: Drop the product_images table.
+DROP TABLE IF EXISTS product_images;
@@ -0,0 +1,14 @@
+-- Product images; see the Postgres product_images migration.
+CREATE TABLE IF NOT EXISTS product_images (
+    image_id INTEGER PRIMARY KEY,
+    product_id VARCHAR(32) NOT NULL REFERENCES products(product_id) ON DELETE CASCADE,
+    position INTEGER NOT NULL CHECK (position >= 1),
+    url TEXT NOT NULL,
+    storage_key VARCHAR(255) NOT NULL UNIQUE,
+    content_type VARCHAR(100) NOT NULL,
+    size_bytes BIGINT NOT NULL CHECK (size_bytes >= 0),
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_product_images_product_position
+    ON product_images(product_id, position);
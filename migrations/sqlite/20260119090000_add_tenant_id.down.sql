@@ -0,0 +1,452 @@
+-- Revert: Multi-tenancy keyed by marketplace; see the Postgres add_tenant_id revert.
+-- Refuses to run while any tenant but the default one has data, through a trigger on a
+-- temporary table, as SQLite can only raise errors from triggers.
+CREATE TEMP TABLE tenant_revert_guard (tenant_table TEXT);
+CREATE TEMP TRIGGER tenant_revert_guard_abort
+    BEFORE INSERT ON tenant_revert_guard
+BEGIN
+    SELECT RAISE(ABORT, 'tenants other than default hold rows; remove them before reverting');
+END;
+
+INSERT INTO tenant_revert_guard (tenant_table)
+SELECT 'audit_log' WHERE EXISTS (SELECT 1 FROM audit_log WHERE tenant_id <> 'default')
+UNION ALL
+SELECT 'coupons' WHERE EXISTS (SELECT 1 FROM coupons WHERE tenant_id <> 'default')
+UNION ALL
+SELECT 'customer_addresses' WHERE EXISTS (SELECT 1 FROM customer_addresses WHERE tenant_id <> 'default')
+UNION ALL
+SELECT 'customer_location_history' WHERE EXISTS (SELECT 1 FROM customer_location_history WHERE tenant_id <> 'default')
+UNION ALL
+SELECT 'customers' WHERE EXISTS (SELECT 1 FROM customers WHERE tenant_id <> 'default')
+UNION ALL
+SELECT 'data_quality_results' WHERE EXISTS (SELECT 1 FROM data_quality_results WHERE tenant_id <> 'default')
+UNION ALL
+SELECT 'import_batch_rows' WHERE EXISTS (SELECT 1 FROM import_batch_rows WHERE tenant_id <> 'default')
+UNION ALL
+SELECT 'import_batches' WHERE EXISTS (SELECT 1 FROM import_batches WHERE tenant_id <> 'default')
+UNION ALL
+SELECT 'import_errors' WHERE EXISTS (SELECT 1 FROM import_errors WHERE tenant_id <> 'default')
+UNION ALL
+SELECT 'late_delivery_alerts' WHERE EXISTS (SELECT 1 FROM late_delivery_alerts WHERE tenant_id <> 'default')
+UNION ALL
+SELECT 'load_jobs' WHERE EXISTS (SELECT 1 FROM load_jobs WHERE tenant_id <> 'default')
+UNION ALL
+SELECT 'location_stock' WHERE EXISTS (SELECT 1 FROM location_stock WHERE tenant_id <> 'default')
+UNION ALL
+SELECT 'notifications' WHERE EXISTS (SELECT 1 FROM notifications WHERE tenant_id <> 'default')
+UNION ALL
+SELECT 'order_amendments' WHERE EXISTS (SELECT 1 FROM order_amendments WHERE tenant_id <> 'default')
+UNION ALL
+SELECT 'order_coupons' WHERE EXISTS (SELECT 1 FROM order_coupons WHERE tenant_id <> 'default')
+UNION ALL
+SELECT 'order_items' WHERE EXISTS (SELECT 1 FROM order_items WHERE tenant_id <> 'default')
+UNION ALL
+SELECT 'orders' WHERE EXISTS (SELECT 1 FROM orders WHERE tenant_id <> 'default')
+UNION ALL
+SELECT 'outbox_events' WHERE EXISTS (SELECT 1 FROM outbox_events WHERE tenant_id <> 'default')
+UNION ALL
+SELECT 'payment_transactions' WHERE EXISTS (SELECT 1 FROM payment_transactions WHERE tenant_id <> 'default')
+UNION ALL
+SELECT 'payments' WHERE EXISTS (SELECT 1 FROM payments WHERE tenant_id <> 'default')
+UNION ALL
+SELECT 'product_categories' WHERE EXISTS (SELECT 1 FROM product_categories WHERE tenant_id <> 'default')
+UNION ALL
+SELECT 'product_embeddings' WHERE EXISTS (SELECT 1 FROM product_embeddings WHERE tenant_id <> 'default')
+UNION ALL
+SELECT 'product_images' WHERE EXISTS (SELECT 1 FROM product_images WHERE tenant_id <> 'default')
+UNION ALL
+SELECT 'products' WHERE EXISTS (SELECT 1 FROM products WHERE tenant_id <> 'default')
+UNION ALL
+SELECT 'refunds' WHERE EXISTS (SELECT 1 FROM refunds WHERE tenant_id <> 'default')
+UNION ALL
+SELECT 'review_sentiments' WHERE EXISTS (SELECT 1 FROM review_sentiments WHERE tenant_id <> 'default')
+UNION ALL
+SELECT 'reviews' WHERE EXISTS (SELECT 1 FROM reviews WHERE tenant_id <> 'default')
+UNION ALL
+SELECT 'seller_badges' WHERE EXISTS (SELECT 1 FROM seller_badges WHERE tenant_id <> 'default')
+UNION ALL
+SELECT 'sellers' WHERE EXISTS (SELECT 1 FROM sellers WHERE tenant_id <> 'default')
+UNION ALL
+SELECT 'stats' WHERE EXISTS (SELECT 1 FROM stats WHERE tenant_id <> 'default')
+UNION ALL
+SELECT 'stock_locations' WHERE EXISTS (SELECT 1 FROM stock_locations WHERE tenant_id <> 'default')
+UNION ALL
+SELECT 'support_case_messages' WHERE EXISTS (SELECT 1 FROM support_case_messages WHERE tenant_id <> 'default')
+UNION ALL
+SELECT 'support_cases' WHERE EXISTS (SELECT 1 FROM support_cases WHERE tenant_id <> 'default')
+UNION ALL
+SELECT 'webhook_deliveries' WHERE EXISTS (SELECT 1 FROM webhook_deliveries WHERE tenant_id <> 'default')
+UNION ALL
+SELECT 'webhook_subscriptions' WHERE EXISTS (SELECT 1 FROM webhook_subscriptions WHERE tenant_id <> 'default');
+DROP TABLE tenant_revert_guard;
+
+DROP TRIGGER IF EXISTS trg_product_images_tenant_update;
+DROP TRIGGER IF EXISTS trg_product_images_tenant_insert;
+DROP TRIGGER IF EXISTS trg_customer_addresses_tenant_update;
+DROP TRIGGER IF EXISTS trg_customer_addresses_tenant_insert;
+DROP TRIGGER IF EXISTS trg_late_delivery_alerts_tenant_update;
+DROP TRIGGER IF EXISTS trg_late_delivery_alerts_tenant_insert;
+DROP TRIGGER IF EXISTS trg_review_sentiments_tenant_update;
+DROP TRIGGER IF EXISTS trg_review_sentiments_tenant_insert;
+DROP TRIGGER IF EXISTS trg_refunds_tenant_update;
+DROP TRIGGER IF EXISTS trg_refunds_tenant_insert;
+DROP TRIGGER IF EXISTS trg_payment_transactions_tenant_update;
+DROP TRIGGER IF EXISTS trg_payment_transactions_tenant_insert;
+DROP TRIGGER IF EXISTS trg_order_coupons_tenant_update;
+DROP TRIGGER IF EXISTS trg_order_coupons_tenant_insert;
+DROP TRIGGER IF EXISTS trg_import_errors_tenant_update;
+DROP TRIGGER IF EXISTS trg_import_errors_tenant_insert;
+DROP TRIGGER IF EXISTS trg_webhook_deliveries_tenant_update;
+DROP TRIGGER IF EXISTS trg_webhook_deliveries_tenant_insert;
+DROP TRIGGER IF EXISTS trg_import_batch_rows_tenant_update;
+DROP TRIGGER IF EXISTS trg_import_batch_rows_tenant_insert;
+DROP TRIGGER IF EXISTS trg_import_batches_tenant_update;
+DROP TRIGGER IF EXISTS trg_import_batches_tenant_insert;
+DROP TRIGGER IF EXISTS trg_seller_badges_tenant_update;
+DROP TRIGGER IF EXISTS trg_seller_badges_tenant_insert;
+DROP TRIGGER IF EXISTS trg_support_case_messages_tenant_update;
+DROP TRIGGER IF EXISTS trg_support_case_messages_tenant_insert;
+DROP TRIGGER IF EXISTS trg_support_cases_tenant_update;
+DROP TRIGGER IF EXISTS trg_support_cases_tenant_insert;
+DROP TRIGGER IF EXISTS trg_order_amendments_tenant_update;
+DROP TRIGGER IF EXISTS trg_order_amendments_tenant_insert;
+DROP TRIGGER IF EXISTS trg_location_stock_tenant_update;
+DROP TRIGGER IF EXISTS trg_location_stock_tenant_insert;
+DROP TRIGGER IF EXISTS trg_stock_locations_tenant_update;
+DROP TRIGGER IF EXISTS trg_stock_locations_tenant_insert;
+DROP TRIGGER IF EXISTS trg_product_embeddings_tenant_update;
+DROP TRIGGER IF EXISTS trg_product_embeddings_tenant_insert;
+DROP TRIGGER IF EXISTS trg_reviews_tenant_update;
+DROP TRIGGER IF EXISTS trg_reviews_tenant_insert;
+DROP TRIGGER IF EXISTS trg_payments_tenant_update;
+DROP TRIGGER IF EXISTS trg_payments_tenant_insert;
+DROP TRIGGER IF EXISTS trg_order_items_tenant_update;
+DROP TRIGGER IF EXISTS trg_order_items_tenant_insert;
+DROP TRIGGER IF EXISTS trg_orders_tenant_update;
+DROP TRIGGER IF EXISTS trg_orders_tenant_insert;
+DROP TRIGGER IF EXISTS trg_customer_location_history_tenant_update;
+DROP TRIGGER IF EXISTS trg_customer_location_history_tenant_insert;
+
+DROP TRIGGER IF EXISTS trg_reviews_outbox_insert;
+DROP TRIGGER IF EXISTS trg_payments_outbox_insert;
+DROP TRIGGER IF EXISTS trg_orders_outbox_status;
+DROP TRIGGER IF EXISTS trg_orders_outbox_insert;
+DROP TRIGGER IF EXISTS trg_import_batches_stats_delete;
+DROP TRIGGER IF EXISTS trg_import_batches_stats_update;
+DROP TRIGGER IF EXISTS trg_import_batches_stats_insert;
+DROP TRIGGER IF EXISTS trg_order_items_stats_delete;
+DROP TRIGGER IF EXISTS trg_order_items_stats_update;
+DROP TRIGGER IF EXISTS trg_order_items_stats_insert;
+DROP TRIGGER IF EXISTS trg_orders_stats_update;
+DROP TRIGGER IF EXISTS trg_orders_stats_delete;
+DROP TRIGGER IF EXISTS trg_orders_stats_insert;
+DROP TRIGGER IF EXISTS trg_customers_location_update;
+DROP TRIGGER IF EXISTS trg_customers_location_insert;
+
+-- The tables rebuilt with tenant_id in their key are rebuilt back.
+CREATE TABLE data_quality_results_new (
+    rule_name VARCHAR(100) PRIMARY KEY,
+    table_name VARCHAR(63) NOT NULL,
+    condition TEXT NOT NULL,
+    severity VARCHAR(10) NOT NULL CHECK (severity IN ('info', 'warning', 'error')),
+    checked_count INTEGER,
+    violation_count INTEGER,
+    error TEXT,
+    evaluated_at TIMESTAMP NOT NULL
+);
+
+INSERT INTO data_quality_results_new (
+    rule_name, table_name, condition, severity, checked_count, violation_count, error,
+    evaluated_at
+)
+SELECT
+    rule_name, table_name, condition, severity, checked_count, violation_count, error,
+    evaluated_at
+FROM data_quality_results;
+DROP TABLE data_quality_results;
+ALTER TABLE data_quality_results_new RENAME TO data_quality_results;
+
+CREATE TABLE stats_new (
+    stat_date DATE PRIMARY KEY,
+    orders_count INTEGER NOT NULL DEFAULT 0,
+    revenue NUMERIC NOT NULL DEFAULT 0,
+    active_imports INTEGER NOT NULL DEFAULT 0,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+INSERT INTO stats_new (stat_date, orders_count, revenue, active_imports, updated_at)
+SELECT stat_date, orders_count, revenue, active_imports, updated_at FROM stats;
+DROP TABLE stats;
+ALTER TABLE stats_new RENAME TO stats;
+
+ALTER TABLE coupons RENAME TO coupons_old;
+ALTER TABLE order_coupons RENAME TO order_coupons_old;
+
+CREATE TABLE coupons (
+    code VARCHAR(40) PRIMARY KEY,
+    discount_type VARCHAR(20) NOT NULL CHECK (discount_type IN ('percentage', 'fixed')),
+    value NUMERIC NOT NULL CHECK (value > 0),
+    min_order_value NUMERIC CHECK (min_order_value >= 0),
+    expires_at TIMESTAMP,
+    max_uses INTEGER CHECK (max_uses > 0),
+    times_used INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CHECK (max_uses IS NULL OR times_used <= max_uses)
+);
+
+CREATE TABLE order_coupons (
+    order_id VARCHAR(32) PRIMARY KEY REFERENCES orders(order_id) ON DELETE CASCADE,
+    code VARCHAR(40) NOT NULL REFERENCES coupons(code),
+    applied_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+INSERT INTO coupons (
+    code, discount_type, value, min_order_value, expires_at, max_uses, times_used, created_at
+)
+SELECT code, discount_type, value, min_order_value, expires_at, max_uses, times_used, created_at
+FROM coupons_old;
+INSERT INTO order_coupons (order_id, code, applied_at)
+SELECT order_id, code, applied_at FROM order_coupons_old;
+DROP TABLE order_coupons_old;
+DROP TABLE coupons_old;
+
+CREATE INDEX IF NOT EXISTS idx_order_coupons_code ON order_coupons(code);
+
+CREATE TABLE product_categories_new (
+    product_category_name VARCHAR(100) PRIMARY KEY,
+    product_category_name_english VARCHAR(100),
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+INSERT INTO product_categories_new (
+    product_category_name, product_category_name_english, created_at, updated_at
+)
+SELECT product_category_name, product_category_name_english, created_at, updated_at
+FROM product_categories;
+DROP TABLE product_categories;
+ALTER TABLE product_categories_new RENAME TO product_categories;
+
+DROP INDEX IF EXISTS idx_products_tenant;
+DROP INDEX IF EXISTS idx_orders_tenant;
+DROP INDEX IF EXISTS idx_sellers_tenant;
+DROP INDEX IF EXISTS idx_customers_tenant;
+
+ALTER TABLE webhook_subscriptions DROP COLUMN tenant_id;
+ALTER TABLE webhook_deliveries DROP COLUMN tenant_id;
+ALTER TABLE support_cases DROP COLUMN tenant_id;
+ALTER TABLE support_case_messages DROP COLUMN tenant_id;
+ALTER TABLE stock_locations DROP COLUMN tenant_id;
+ALTER TABLE sellers DROP COLUMN tenant_id;
+ALTER TABLE seller_badges DROP COLUMN tenant_id;
+ALTER TABLE reviews DROP COLUMN tenant_id;
+ALTER TABLE review_sentiments DROP COLUMN tenant_id;
+ALTER TABLE refunds DROP COLUMN tenant_id;
+ALTER TABLE products DROP COLUMN tenant_id;
+ALTER TABLE product_images DROP COLUMN tenant_id;
+ALTER TABLE product_embeddings DROP COLUMN tenant_id;
+ALTER TABLE payments DROP COLUMN tenant_id;
+ALTER TABLE payment_transactions DROP COLUMN tenant_id;
+ALTER TABLE outbox_events DROP COLUMN tenant_id;
+ALTER TABLE orders DROP COLUMN tenant_id;
+ALTER TABLE order_items DROP COLUMN tenant_id;
+ALTER TABLE order_amendments DROP COLUMN tenant_id;
+ALTER TABLE notifications DROP COLUMN tenant_id;
+ALTER TABLE location_stock DROP COLUMN tenant_id;
+ALTER TABLE load_jobs DROP COLUMN tenant_id;
+ALTER TABLE late_delivery_alerts DROP COLUMN tenant_id;
+ALTER TABLE import_errors DROP COLUMN tenant_id;
+ALTER TABLE import_batches DROP COLUMN tenant_id;
+ALTER TABLE import_batch_rows DROP COLUMN tenant_id;
+ALTER TABLE customers DROP COLUMN tenant_id;
+ALTER TABLE customer_location_history DROP COLUMN tenant_id;
+ALTER TABLE customer_addresses DROP COLUMN tenant_id;
+ALTER TABLE audit_log DROP COLUMN tenant_id;
+
+CREATE TRIGGER IF NOT EXISTS trg_customers_location_insert
+    AFTER INSERT ON customers
+BEGIN
+    INSERT INTO customer_location_history (
+        customer_id, customer_zip_code_prefix, customer_city, customer_state
+    )
+    VALUES (NEW.customer_id, NEW.customer_zip_code_prefix, NEW.customer_city, NEW.customer_state);
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_customers_location_update
+    AFTER UPDATE OF customer_zip_code_prefix, customer_city, customer_state ON customers
+    WHEN OLD.customer_zip_code_prefix IS NOT NEW.customer_zip_code_prefix
+        OR OLD.customer_city IS NOT NEW.customer_city
+        OR OLD.customer_state IS NOT NEW.customer_state
+BEGIN
+    UPDATE customer_location_history
+    SET valid_to = datetime('now')
+    WHERE customer_id = NEW.customer_id AND valid_to IS NULL;
+
+    INSERT INTO customer_location_history (
+        customer_id, customer_zip_code_prefix, customer_city, customer_state, valid_from
+    )
+    VALUES (
+        NEW.customer_id, NEW.customer_zip_code_prefix, NEW.customer_city, NEW.customer_state,
+        datetime('now')
+    );
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_orders_stats_insert
+    AFTER INSERT ON orders
+BEGIN
+    INSERT OR IGNORE INTO stats (stat_date) VALUES (date(NEW.order_purchase_timestamp));
+    UPDATE stats SET orders_count = orders_count + 1, updated_at = CURRENT_TIMESTAMP
+    WHERE stat_date = date(NEW.order_purchase_timestamp);
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_orders_stats_delete
+    AFTER DELETE ON orders
+BEGIN
+    UPDATE stats SET orders_count = orders_count - 1, updated_at = CURRENT_TIMESTAMP
+    WHERE stat_date = date(OLD.order_purchase_timestamp);
+END;
+
+-- A changed purchase date moves the order and its items' revenue to the new day.
+CREATE TRIGGER IF NOT EXISTS trg_orders_stats_update
+    AFTER UPDATE OF order_purchase_timestamp ON orders
+    WHEN date(OLD.order_purchase_timestamp) IS NOT date(NEW.order_purchase_timestamp)
+BEGIN
+    UPDATE stats
+    SET
+        orders_count = orders_count - 1,
+        revenue = revenue - (
+            SELECT COALESCE(SUM(price + freight_value), 0)
+            FROM order_items WHERE order_id = NEW.order_id
+        ),
+        updated_at = CURRENT_TIMESTAMP
+    WHERE stat_date = date(OLD.order_purchase_timestamp);
+
+    INSERT OR IGNORE INTO stats (stat_date) VALUES (date(NEW.order_purchase_timestamp));
+    UPDATE stats
+    SET
+        orders_count = orders_count + 1,
+        revenue = revenue + (
+            SELECT COALESCE(SUM(price + freight_value), 0)
+            FROM order_items WHERE order_id = NEW.order_id
+        ),
+        updated_at = CURRENT_TIMESTAMP
+    WHERE stat_date = date(NEW.order_purchase_timestamp);
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_order_items_stats_insert
+    AFTER INSERT ON order_items
+BEGIN
+    UPDATE stats
+    SET revenue = revenue + NEW.price + NEW.freight_value, updated_at = CURRENT_TIMESTAMP
+    WHERE stat_date = (
+        SELECT date(order_purchase_timestamp) FROM orders WHERE order_id = NEW.order_id
+    );
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_order_items_stats_update
+    AFTER UPDATE OF price, freight_value, order_id ON order_items
+BEGIN
+    UPDATE stats
+    SET revenue = revenue - OLD.price - OLD.freight_value, updated_at = CURRENT_TIMESTAMP
+    WHERE stat_date = (
+        SELECT date(order_purchase_timestamp) FROM orders WHERE order_id = OLD.order_id
+    );
+    UPDATE stats
+    SET revenue = revenue + NEW.price + NEW.freight_value, updated_at = CURRENT_TIMESTAMP
+    WHERE stat_date = (
+        SELECT date(order_purchase_timestamp) FROM orders WHERE order_id = NEW.order_id
+    );
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_order_items_stats_delete
+    AFTER DELETE ON order_items
+BEGIN
+    UPDATE stats
+    SET revenue = revenue - OLD.price - OLD.freight_value, updated_at = CURRENT_TIMESTAMP
+    WHERE stat_date = (
+        SELECT date(order_purchase_timestamp) FROM orders WHERE order_id = OLD.order_id
+    );
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_import_batches_stats_insert
+    AFTER INSERT ON import_batches
+    WHEN NEW.status = 'running'
+BEGIN
+    INSERT OR IGNORE INTO stats (stat_date) VALUES (date(NEW.started_at));
+    UPDATE stats SET active_imports = active_imports + 1, updated_at = CURRENT_TIMESTAMP
+    WHERE stat_date = date(NEW.started_at);
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_import_batches_stats_update
+    AFTER UPDATE OF status ON import_batches
+    WHEN (OLD.status = 'running') IS NOT (NEW.status = 'running')
+BEGIN
+    INSERT OR IGNORE INTO stats (stat_date) VALUES (date(NEW.started_at));
+    UPDATE stats
+    SET
+        active_imports = active_imports
+            + (CASE WHEN NEW.status = 'running' THEN 1 ELSE 0 END)
+            - (CASE WHEN OLD.status = 'running' THEN 1 ELSE 0 END),
+        updated_at = CURRENT_TIMESTAMP
+    WHERE stat_date = date(NEW.started_at);
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_import_batches_stats_delete
+    AFTER DELETE ON import_batches
+    WHEN OLD.status = 'running'
+BEGIN
+    UPDATE stats SET active_imports = active_imports - 1, updated_at = CURRENT_TIMESTAMP
+    WHERE stat_date = date(OLD.started_at);
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_orders_outbox_insert
+    AFTER INSERT ON orders
+BEGIN
+    INSERT INTO outbox_events (aggregate_type, aggregate_id, event_type, payload)
+    VALUES ('order', NEW.order_id, 'order.created', json_object(
+        'order_id', NEW.order_id,
+        'customer_id', NEW.customer_id,
+        'order_status', NEW.order_status,
+        'order_purchase_timestamp', replace(NEW.order_purchase_timestamp, ' ', 'T')
+    ));
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_orders_outbox_status
+    AFTER UPDATE OF order_status ON orders
+    WHEN OLD.order_status IS NOT NEW.order_status
+BEGIN
+    INSERT INTO outbox_events (aggregate_type, aggregate_id, event_type, payload)
+    VALUES ('order', NEW.order_id, 'order.status_changed', json_object(
+        'order_id', NEW.order_id,
+        'previous_status', OLD.order_status,
+        'order_status', NEW.order_status,
+        'status_version', NEW.status_version
+    ));
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_payments_outbox_insert
+    AFTER INSERT ON payments
+BEGIN
+    INSERT INTO outbox_events (aggregate_type, aggregate_id, event_type, payload)
+    VALUES ('payment', NEW.order_id, 'payment.created', json_object(
+        'order_id', NEW.order_id,
+        'payment_sequential', NEW.payment_sequential,
+        'payment_type', NEW.payment_type,
+        'payment_installments', NEW.payment_installments,
+        'payment_value', printf('%.2f', NEW.payment_value)
+    ));
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_reviews_outbox_insert
+    AFTER INSERT ON reviews
+BEGIN
+    INSERT INTO outbox_events (aggregate_type, aggregate_id, event_type, payload)
+    VALUES ('review', NEW.review_id, 'review.created', json_object(
+        'review_id', NEW.review_id,
+        'order_id', NEW.order_id,
+        'review_score', NEW.review_score,
+        'review_comment_title', NEW.review_comment_title,
+        'review_comment_message', NEW.review_comment_message,
+        'review_creation_date', replace(NEW.review_creation_date, ' ', 'T')
+    ));
+END;
@@ -0,0 +1,785 @@
+-- Multi-tenancy keyed by marketplace; see the Postgres add_tenant_id migration.
+ALTER TABLE audit_log ADD COLUMN tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE customer_addresses ADD COLUMN tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE customer_location_history ADD COLUMN tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE customers ADD COLUMN tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE import_batch_rows ADD COLUMN tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE import_batches ADD COLUMN tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE import_errors ADD COLUMN tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE late_delivery_alerts ADD COLUMN tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE load_jobs ADD COLUMN tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE location_stock ADD COLUMN tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE notifications ADD COLUMN tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE order_amendments ADD COLUMN tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE order_items ADD COLUMN tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE orders ADD COLUMN tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE outbox_events ADD COLUMN tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE payment_transactions ADD COLUMN tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE payments ADD COLUMN tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE product_embeddings ADD COLUMN tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE product_images ADD COLUMN tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE products ADD COLUMN tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE refunds ADD COLUMN tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE review_sentiments ADD COLUMN tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE reviews ADD COLUMN tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE seller_badges ADD COLUMN tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE sellers ADD COLUMN tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE stock_locations ADD COLUMN tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE support_case_messages ADD COLUMN tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE support_cases ADD COLUMN tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE webhook_deliveries ADD COLUMN tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE webhook_subscriptions ADD COLUMN tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+
+CREATE INDEX IF NOT EXISTS idx_customers_tenant ON customers(tenant_id, customer_id);
+CREATE INDEX IF NOT EXISTS idx_sellers_tenant ON sellers(tenant_id, seller_id);
+CREATE INDEX IF NOT EXISTS idx_orders_tenant ON orders(tenant_id, order_purchase_timestamp);
+CREATE INDEX IF NOT EXISTS idx_products_tenant ON products(tenant_id, product_id);
+
+-- The triggers are recreated below so the rows they write belong to the tenant of the row
+-- that fired them; dropping them first also lets stats be rebuilt underneath them.
+DROP TRIGGER IF EXISTS trg_customers_location_insert;
+DROP TRIGGER IF EXISTS trg_customers_location_update;
+DROP TRIGGER IF EXISTS trg_orders_stats_insert;
+DROP TRIGGER IF EXISTS trg_orders_stats_delete;
+DROP TRIGGER IF EXISTS trg_orders_stats_update;
+DROP TRIGGER IF EXISTS trg_order_items_stats_insert;
+DROP TRIGGER IF EXISTS trg_order_items_stats_update;
+DROP TRIGGER IF EXISTS trg_order_items_stats_delete;
+DROP TRIGGER IF EXISTS trg_import_batches_stats_insert;
+DROP TRIGGER IF EXISTS trg_import_batches_stats_update;
+DROP TRIGGER IF EXISTS trg_import_batches_stats_delete;
+DROP TRIGGER IF EXISTS trg_orders_outbox_insert;
+DROP TRIGGER IF EXISTS trg_orders_outbox_status;
+DROP TRIGGER IF EXISTS trg_payments_outbox_insert;
+DROP TRIGGER IF EXISTS trg_reviews_outbox_insert;
+
+-- SQLite can't alter a primary key, so the tables keyed by names a marketplace picks are
+-- rebuilt with tenant_id in their key.
+CREATE TABLE product_categories_new (
+    tenant_id VARCHAR(64) NOT NULL DEFAULT 'default',
+    product_category_name VARCHAR(100) NOT NULL,
+    product_category_name_english VARCHAR(100),
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (tenant_id, product_category_name)
+);
+
+INSERT INTO product_categories_new (
+    product_category_name, product_category_name_english, created_at, updated_at
+)
+SELECT product_category_name, product_category_name_english, created_at, updated_at
+FROM product_categories;
+DROP TABLE product_categories;
+ALTER TABLE product_categories_new RENAME TO product_categories;
+
+-- Renaming coupons first points order_coupons' foreign key at the old table, so both can be
+-- dropped once their rows are copied.
+ALTER TABLE coupons RENAME TO coupons_old;
+ALTER TABLE order_coupons RENAME TO order_coupons_old;
+
+CREATE TABLE coupons (
+    tenant_id VARCHAR(64) NOT NULL DEFAULT 'default',
+    code VARCHAR(40) NOT NULL,
+    discount_type VARCHAR(20) NOT NULL CHECK (discount_type IN ('percentage', 'fixed')),
+    value NUMERIC NOT NULL CHECK (value > 0),
+    min_order_value NUMERIC CHECK (min_order_value >= 0),
+    expires_at TIMESTAMP,
+    max_uses INTEGER CHECK (max_uses > 0),
+    times_used INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CHECK (max_uses IS NULL OR times_used <= max_uses),
+    PRIMARY KEY (tenant_id, code)
+);
+
+CREATE TABLE order_coupons (
+    order_id VARCHAR(32) PRIMARY KEY REFERENCES orders(order_id) ON DELETE CASCADE,
+    tenant_id VARCHAR(64) NOT NULL DEFAULT 'default',
+    code VARCHAR(40) NOT NULL,
+    applied_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (tenant_id, code) REFERENCES coupons(tenant_id, code)
+);
+
+INSERT INTO coupons (
+    code, discount_type, value, min_order_value, expires_at, max_uses, times_used, created_at
+)
+SELECT code, discount_type, value, min_order_value, expires_at, max_uses, times_used, created_at
+FROM coupons_old;
+INSERT INTO order_coupons (order_id, code, applied_at)
+SELECT order_id, code, applied_at FROM order_coupons_old;
+DROP TABLE order_coupons_old;
+DROP TABLE coupons_old;
+
+CREATE INDEX IF NOT EXISTS idx_order_coupons_code ON order_coupons(tenant_id, code);
+
+CREATE TABLE stats_new (
+    tenant_id VARCHAR(64) NOT NULL DEFAULT 'default',
+    stat_date DATE NOT NULL,
+    orders_count INTEGER NOT NULL DEFAULT 0,
+    revenue NUMERIC NOT NULL DEFAULT 0,
+    active_imports INTEGER NOT NULL DEFAULT 0,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (tenant_id, stat_date)
+);
+
+INSERT INTO stats_new (stat_date, orders_count, revenue, active_imports, updated_at)
+SELECT stat_date, orders_count, revenue, active_imports, updated_at FROM stats;
+DROP TABLE stats;
+ALTER TABLE stats_new RENAME TO stats;
+
+CREATE TABLE data_quality_results_new (
+    tenant_id VARCHAR(64) NOT NULL DEFAULT 'default',
+    rule_name VARCHAR(100) NOT NULL,
+    table_name VARCHAR(63) NOT NULL,
+    condition TEXT NOT NULL,
+    severity VARCHAR(10) NOT NULL CHECK (severity IN ('info', 'warning', 'error')),
+    checked_count INTEGER,
+    violation_count INTEGER,
+    error TEXT,
+    evaluated_at TIMESTAMP NOT NULL,
+    PRIMARY KEY (tenant_id, rule_name)
+);
+
+INSERT INTO data_quality_results_new (
+    rule_name, table_name, condition, severity, checked_count, violation_count, error,
+    evaluated_at
+)
+SELECT
+    rule_name, table_name, condition, severity, checked_count, violation_count, error,
+    evaluated_at
+FROM data_quality_results;
+DROP TABLE data_quality_results;
+ALTER TABLE data_quality_results_new RENAME TO data_quality_results;
+
+CREATE TRIGGER trg_customers_location_insert
+    AFTER INSERT ON customers
+BEGIN
+    INSERT INTO customer_location_history (
+        tenant_id, customer_id, customer_zip_code_prefix, customer_city, customer_state
+    )
+    VALUES (
+        NEW.tenant_id, NEW.customer_id, NEW.customer_zip_code_prefix, NEW.customer_city,
+        NEW.customer_state
+    );
+END;
+
+CREATE TRIGGER trg_customers_location_update
+    AFTER UPDATE OF customer_zip_code_prefix, customer_city, customer_state ON customers
+    WHEN OLD.customer_zip_code_prefix IS NOT NEW.customer_zip_code_prefix
+        OR OLD.customer_city IS NOT NEW.customer_city
+        OR OLD.customer_state IS NOT NEW.customer_state
+BEGIN
+    UPDATE customer_location_history
+    SET valid_to = datetime('now')
+    WHERE customer_id = NEW.customer_id AND valid_to IS NULL;
+
+    INSERT INTO customer_location_history (
+        tenant_id, customer_id, customer_zip_code_prefix, customer_city, customer_state,
+        valid_from
+    )
+    VALUES (
+        NEW.tenant_id, NEW.customer_id, NEW.customer_zip_code_prefix, NEW.customer_city,
+        NEW.customer_state, datetime('now')
+    );
+END;
+
+CREATE TRIGGER trg_orders_stats_insert
+    AFTER INSERT ON orders
+BEGIN
+    INSERT OR IGNORE INTO stats (tenant_id, stat_date)
+    VALUES (NEW.tenant_id, date(NEW.order_purchase_timestamp));
+    UPDATE stats SET orders_count = orders_count + 1, updated_at = CURRENT_TIMESTAMP
+    WHERE tenant_id = NEW.tenant_id AND stat_date = date(NEW.order_purchase_timestamp);
+END;
+
+CREATE TRIGGER trg_orders_stats_delete
+    AFTER DELETE ON orders
+BEGIN
+    UPDATE stats SET orders_count = orders_count - 1, updated_at = CURRENT_TIMESTAMP
+    WHERE tenant_id = OLD.tenant_id AND stat_date = date(OLD.order_purchase_timestamp);
+END;
+
+CREATE TRIGGER trg_orders_stats_update
+    AFTER UPDATE OF order_purchase_timestamp ON orders
+    WHEN date(OLD.order_purchase_timestamp) IS NOT date(NEW.order_purchase_timestamp)
+BEGIN
+    UPDATE stats
+    SET
+        orders_count = orders_count - 1,
+        revenue = revenue - (
+            SELECT COALESCE(SUM(price + freight_value), 0)
+            FROM order_items WHERE order_id = NEW.order_id
+        ),
+        updated_at = CURRENT_TIMESTAMP
+    WHERE tenant_id = OLD.tenant_id AND stat_date = date(OLD.order_purchase_timestamp);
+
+    INSERT OR IGNORE INTO stats (tenant_id, stat_date)
+    VALUES (NEW.tenant_id, date(NEW.order_purchase_timestamp));
+    UPDATE stats
+    SET
+        orders_count = orders_count + 1,
+        revenue = revenue + (
+            SELECT COALESCE(SUM(price + freight_value), 0)
+            FROM order_items WHERE order_id = NEW.order_id
+        ),
+        updated_at = CURRENT_TIMESTAMP
+    WHERE tenant_id = NEW.tenant_id AND stat_date = date(NEW.order_purchase_timestamp);
+END;
+
+CREATE TRIGGER trg_order_items_stats_insert
+    AFTER INSERT ON order_items
+BEGIN
+    UPDATE stats
+    SET revenue = revenue + NEW.price + NEW.freight_value, updated_at = CURRENT_TIMESTAMP
+    WHERE (tenant_id, stat_date) = (
+        SELECT tenant_id, date(order_purchase_timestamp) FROM orders WHERE order_id = NEW.order_id
+    );
+END;
+
+CREATE TRIGGER trg_order_items_stats_update
+    AFTER UPDATE OF price, freight_value, order_id ON order_items
+BEGIN
+    UPDATE stats
+    SET revenue = revenue - OLD.price - OLD.freight_value, updated_at = CURRENT_TIMESTAMP
+    WHERE (tenant_id, stat_date) = (
+        SELECT tenant_id, date(order_purchase_timestamp) FROM orders WHERE order_id = OLD.order_id
+    );
+    UPDATE stats
+    SET revenue = revenue + NEW.price + NEW.freight_value, updated_at = CURRENT_TIMESTAMP
+    WHERE (tenant_id, stat_date) = (
+        SELECT tenant_id, date(order_purchase_timestamp) FROM orders WHERE order_id = NEW.order_id
+    );
+END;
+
+CREATE TRIGGER trg_order_items_stats_delete
+    AFTER DELETE ON order_items
+BEGIN
+    UPDATE stats
+    SET revenue = revenue - OLD.price - OLD.freight_value, updated_at = CURRENT_TIMESTAMP
+    WHERE (tenant_id, stat_date) = (
+        SELECT tenant_id, date(order_purchase_timestamp) FROM orders WHERE order_id = OLD.order_id
+    );
+END;
+
+CREATE TRIGGER trg_import_batches_stats_insert
+    AFTER INSERT ON import_batches
+    WHEN NEW.status = 'running'
+BEGIN
+    INSERT OR IGNORE INTO stats (tenant_id, stat_date) VALUES (NEW.tenant_id, date(NEW.started_at));
+    UPDATE stats SET active_imports = active_imports + 1, updated_at = CURRENT_TIMESTAMP
+    WHERE tenant_id = NEW.tenant_id AND stat_date = date(NEW.started_at);
+END;
+
+CREATE TRIGGER trg_import_batches_stats_update
+    AFTER UPDATE OF status ON import_batches
+    WHEN (OLD.status = 'running') IS NOT (NEW.status = 'running')
+BEGIN
+    INSERT OR IGNORE INTO stats (tenant_id, stat_date) VALUES (NEW.tenant_id, date(NEW.started_at));
+    UPDATE stats
+    SET
+        active_imports = active_imports
+            + (CASE WHEN NEW.status = 'running' THEN 1 ELSE 0 END)
+            - (CASE WHEN OLD.status = 'running' THEN 1 ELSE 0 END),
+        updated_at = CURRENT_TIMESTAMP
+    WHERE tenant_id = NEW.tenant_id AND stat_date = date(NEW.started_at);
+END;
+
+CREATE TRIGGER trg_import_batches_stats_delete
+    AFTER DELETE ON import_batches
+    WHEN OLD.status = 'running'
+BEGIN
+    UPDATE stats SET active_imports = active_imports - 1, updated_at = CURRENT_TIMESTAMP
+    WHERE tenant_id = OLD.tenant_id AND stat_date = date(OLD.started_at);
+END;
+
+CREATE TRIGGER trg_orders_outbox_insert
+    AFTER INSERT ON orders
+BEGIN
+    INSERT INTO outbox_events (tenant_id, aggregate_type, aggregate_id, event_type, payload)
+    VALUES (NEW.tenant_id, 'order', NEW.order_id, 'order.created', json_object(
+        'order_id', NEW.order_id,
+        'customer_id', NEW.customer_id,
+        'order_status', NEW.order_status,
+        'order_purchase_timestamp', replace(NEW.order_purchase_timestamp, ' ', 'T')
+    ));
+END;
+
+CREATE TRIGGER trg_orders_outbox_status
+    AFTER UPDATE OF order_status ON orders
+    WHEN OLD.order_status IS NOT NEW.order_status
+BEGIN
+    INSERT INTO outbox_events (tenant_id, aggregate_type, aggregate_id, event_type, payload)
+    VALUES (NEW.tenant_id, 'order', NEW.order_id, 'order.status_changed', json_object(
+        'order_id', NEW.order_id,
+        'previous_status', OLD.order_status,
+        'order_status', NEW.order_status,
+        'status_version', NEW.status_version
+    ));
+END;
+
+CREATE TRIGGER trg_payments_outbox_insert
+    AFTER INSERT ON payments
+BEGIN
+    INSERT INTO outbox_events (tenant_id, aggregate_type, aggregate_id, event_type, payload)
+    VALUES (NEW.tenant_id, 'payment', NEW.order_id, 'payment.created', json_object(
+        'order_id', NEW.order_id,
+        'payment_sequential', NEW.payment_sequential,
+        'payment_type', NEW.payment_type,
+        'payment_installments', NEW.payment_installments,
+        'payment_value', printf('%.2f', NEW.payment_value)
+    ));
+END;
+
+CREATE TRIGGER trg_reviews_outbox_insert
+    AFTER INSERT ON reviews
+BEGIN
+    INSERT INTO outbox_events (tenant_id, aggregate_type, aggregate_id, event_type, payload)
+    VALUES (NEW.tenant_id, 'review', NEW.review_id, 'review.created', json_object(
+        'review_id', NEW.review_id,
+        'order_id', NEW.order_id,
+        'review_score', NEW.review_score,
+        'review_comment_title', NEW.review_comment_title,
+        'review_comment_message', NEW.review_comment_message,
+        'review_creation_date', replace(NEW.review_creation_date, ' ', 'T')
+    ));
+END;
+
+-- SQLite can't alter foreign keys without rebuilding every table, so instead of the composite
+-- keys Postgres gets, triggers check that a row only references rows of its own tenant. A
+-- reference to another tenant's row then fails like one to a missing row.
+CREATE TRIGGER trg_customer_location_history_tenant_insert
+    BEFORE INSERT ON customer_location_history
+    WHEN NOT EXISTS (
+        SELECT 1 FROM customers WHERE customer_id = NEW.customer_id AND tenant_id = NEW.tenant_id
+    )
+BEGIN
+    SELECT RAISE(ABORT, 'FOREIGN KEY constraint failed');
+END;
+
+CREATE TRIGGER trg_customer_location_history_tenant_update
+    BEFORE UPDATE OF tenant_id, customer_id ON customer_location_history
+    WHEN NOT EXISTS (
+        SELECT 1 FROM customers WHERE customer_id = NEW.customer_id AND tenant_id = NEW.tenant_id
+    )
+BEGIN
+    SELECT RAISE(ABORT, 'FOREIGN KEY constraint failed');
+END;
+
+CREATE TRIGGER trg_orders_tenant_insert
+    BEFORE INSERT ON orders
+    WHEN NOT EXISTS (
+        SELECT 1 FROM customers WHERE customer_id = NEW.customer_id AND tenant_id = NEW.tenant_id
+    )
+BEGIN
+    SELECT RAISE(ABORT, 'FOREIGN KEY constraint failed');
+END;
+
+CREATE TRIGGER trg_orders_tenant_update
+    BEFORE UPDATE OF tenant_id, customer_id ON orders
+    WHEN NOT EXISTS (
+        SELECT 1 FROM customers WHERE customer_id = NEW.customer_id AND tenant_id = NEW.tenant_id
+    )
+BEGIN
+    SELECT RAISE(ABORT, 'FOREIGN KEY constraint failed');
+END;
+
+CREATE TRIGGER trg_order_items_tenant_insert
+    BEFORE INSERT ON order_items
+    WHEN NOT EXISTS (
+        SELECT 1 FROM orders WHERE order_id = NEW.order_id AND tenant_id = NEW.tenant_id
+    )
+    OR NOT EXISTS (
+        SELECT 1 FROM products WHERE product_id = NEW.product_id AND tenant_id = NEW.tenant_id
+    )
+    OR NOT EXISTS (
+        SELECT 1 FROM sellers WHERE seller_id = NEW.seller_id AND tenant_id = NEW.tenant_id
+    )
+BEGIN
+    SELECT RAISE(ABORT, 'FOREIGN KEY constraint failed');
+END;
+
+CREATE TRIGGER trg_order_items_tenant_update
+    BEFORE UPDATE OF tenant_id, order_id, product_id, seller_id ON order_items
+    WHEN NOT EXISTS (
+        SELECT 1 FROM orders WHERE order_id = NEW.order_id AND tenant_id = NEW.tenant_id
+    )
+    OR NOT EXISTS (
+        SELECT 1 FROM products WHERE product_id = NEW.product_id AND tenant_id = NEW.tenant_id
+    )
+    OR NOT EXISTS (
+        SELECT 1 FROM sellers WHERE seller_id = NEW.seller_id AND tenant_id = NEW.tenant_id
+    )
+BEGIN
+    SELECT RAISE(ABORT, 'FOREIGN KEY constraint failed');
+END;
+
+CREATE TRIGGER trg_payments_tenant_insert
+    BEFORE INSERT ON payments
+    WHEN NOT EXISTS (
+        SELECT 1 FROM orders WHERE order_id = NEW.order_id AND tenant_id = NEW.tenant_id
+    )
+BEGIN
+    SELECT RAISE(ABORT, 'FOREIGN KEY constraint failed');
+END;
+
+CREATE TRIGGER trg_payments_tenant_update
+    BEFORE UPDATE OF tenant_id, order_id ON payments
+    WHEN NOT EXISTS (
+        SELECT 1 FROM orders WHERE order_id = NEW.order_id AND tenant_id = NEW.tenant_id
+    )
+BEGIN
+    SELECT RAISE(ABORT, 'FOREIGN KEY constraint failed');
+END;
+
+CREATE TRIGGER trg_reviews_tenant_insert
+    BEFORE INSERT ON reviews
+    WHEN NOT EXISTS (
+        SELECT 1 FROM orders WHERE order_id = NEW.order_id AND tenant_id = NEW.tenant_id
+    )
+BEGIN
+    SELECT RAISE(ABORT, 'FOREIGN KEY constraint failed');
+END;
+
+CREATE TRIGGER trg_reviews_tenant_update
+    BEFORE UPDATE OF tenant_id, order_id ON reviews
+    WHEN NOT EXISTS (
+        SELECT 1 FROM orders WHERE order_id = NEW.order_id AND tenant_id = NEW.tenant_id
+    )
+BEGIN
+    SELECT RAISE(ABORT, 'FOREIGN KEY constraint failed');
+END;
+
+CREATE TRIGGER trg_product_embeddings_tenant_insert
+    BEFORE INSERT ON product_embeddings
+    WHEN NOT EXISTS (
+        SELECT 1 FROM products WHERE product_id = NEW.product_id AND tenant_id = NEW.tenant_id
+    )
+BEGIN
+    SELECT RAISE(ABORT, 'FOREIGN KEY constraint failed');
+END;
+
+CREATE TRIGGER trg_product_embeddings_tenant_update
+    BEFORE UPDATE OF tenant_id, product_id ON product_embeddings
+    WHEN NOT EXISTS (
+        SELECT 1 FROM products WHERE product_id = NEW.product_id AND tenant_id = NEW.tenant_id
+    )
+BEGIN
+    SELECT RAISE(ABORT, 'FOREIGN KEY constraint failed');
+END;
+
+CREATE TRIGGER trg_stock_locations_tenant_insert
+    BEFORE INSERT ON stock_locations
+    WHEN NOT EXISTS (
+        SELECT 1 FROM sellers WHERE seller_id = NEW.seller_id AND tenant_id = NEW.tenant_id
+    )
+BEGIN
+    SELECT RAISE(ABORT, 'FOREIGN KEY constraint failed');
+END;
+
+CREATE TRIGGER trg_stock_locations_tenant_update
+    BEFORE UPDATE OF tenant_id, seller_id ON stock_locations
+    WHEN NOT EXISTS (
+        SELECT 1 FROM sellers WHERE seller_id = NEW.seller_id AND tenant_id = NEW.tenant_id
+    )
+BEGIN
+    SELECT RAISE(ABORT, 'FOREIGN KEY constraint failed');
+END;
+
+CREATE TRIGGER trg_location_stock_tenant_insert
+    BEFORE INSERT ON location_stock
+    WHEN NOT EXISTS (
+        SELECT 1 FROM stock_locations WHERE location_id = NEW.location_id AND tenant_id = NEW.tenant_id
+    )
+    OR NOT EXISTS (
+        SELECT 1 FROM products WHERE product_id = NEW.product_id AND tenant_id = NEW.tenant_id
+    )
+BEGIN
+    SELECT RAISE(ABORT, 'FOREIGN KEY constraint failed');
+END;
+
+CREATE TRIGGER trg_location_stock_tenant_update
+    BEFORE UPDATE OF tenant_id, location_id, product_id ON location_stock
+    WHEN NOT EXISTS (
+        SELECT 1 FROM stock_locations WHERE location_id = NEW.location_id AND tenant_id = NEW.tenant_id
+    )
+    OR NOT EXISTS (
+        SELECT 1 FROM products WHERE product_id = NEW.product_id AND tenant_id = NEW.tenant_id
+    )
+BEGIN
+    SELECT RAISE(ABORT, 'FOREIGN KEY constraint failed');
+END;
+
+CREATE TRIGGER trg_order_amendments_tenant_insert
+    BEFORE INSERT ON order_amendments
+    WHEN NOT EXISTS (
+        SELECT 1 FROM orders WHERE order_id = NEW.order_id AND tenant_id = NEW.tenant_id
+    )
+BEGIN
+    SELECT RAISE(ABORT, 'FOREIGN KEY constraint failed');
+END;
+
+CREATE TRIGGER trg_order_amendments_tenant_update
+    BEFORE UPDATE OF tenant_id, order_id ON order_amendments
+    WHEN NOT EXISTS (
+        SELECT 1 FROM orders WHERE order_id = NEW.order_id AND tenant_id = NEW.tenant_id
+    )
+BEGIN
+    SELECT RAISE(ABORT, 'FOREIGN KEY constraint failed');
+END;
+
+CREATE TRIGGER trg_support_cases_tenant_insert
+    BEFORE INSERT ON support_cases
+    WHEN NOT EXISTS (
+        SELECT 1 FROM orders WHERE order_id = NEW.order_id AND tenant_id = NEW.tenant_id
+    )
+    OR NOT EXISTS (
+        SELECT 1 FROM customers WHERE customer_id = NEW.customer_id AND tenant_id = NEW.tenant_id
+    )
+BEGIN
+    SELECT RAISE(ABORT, 'FOREIGN KEY constraint failed');
+END;
+
+CREATE TRIGGER trg_support_cases_tenant_update
+    BEFORE UPDATE OF tenant_id, order_id, customer_id ON support_cases
+    WHEN NOT EXISTS (
+        SELECT 1 FROM orders WHERE order_id = NEW.order_id AND tenant_id = NEW.tenant_id
+    )
+    OR NOT EXISTS (
+        SELECT 1 FROM customers WHERE customer_id = NEW.customer_id AND tenant_id = NEW.tenant_id
+    )
+BEGIN
+    SELECT RAISE(ABORT, 'FOREIGN KEY constraint failed');
+END;
+
+CREATE TRIGGER trg_support_case_messages_tenant_insert
+    BEFORE INSERT ON support_case_messages
+    WHEN NOT EXISTS (
+        SELECT 1 FROM support_cases WHERE case_id = NEW.case_id AND tenant_id = NEW.tenant_id
+    )
+BEGIN
+    SELECT RAISE(ABORT, 'FOREIGN KEY constraint failed');
+END;
+
+CREATE TRIGGER trg_support_case_messages_tenant_update
+    BEFORE UPDATE OF tenant_id, case_id ON support_case_messages
+    WHEN NOT EXISTS (
+        SELECT 1 FROM support_cases WHERE case_id = NEW.case_id AND tenant_id = NEW.tenant_id
+    )
+BEGIN
+    SELECT RAISE(ABORT, 'FOREIGN KEY constraint failed');
+END;
+
+CREATE TRIGGER trg_seller_badges_tenant_insert
+    BEFORE INSERT ON seller_badges
+    WHEN NOT EXISTS (
+        SELECT 1 FROM sellers WHERE seller_id = NEW.seller_id AND tenant_id = NEW.tenant_id
+    )
+BEGIN
+    SELECT RAISE(ABORT, 'FOREIGN KEY constraint failed');
+END;
+
+CREATE TRIGGER trg_seller_badges_tenant_update
+    BEFORE UPDATE OF tenant_id, seller_id ON seller_badges
+    WHEN NOT EXISTS (
+        SELECT 1 FROM sellers WHERE seller_id = NEW.seller_id AND tenant_id = NEW.tenant_id
+    )
+BEGIN
+    SELECT RAISE(ABORT, 'FOREIGN KEY constraint failed');
+END;
+
+CREATE TRIGGER trg_import_batches_tenant_insert
+    BEFORE INSERT ON import_batches
+    WHEN (NEW.load_job_id IS NOT NULL AND NOT EXISTS (
+        SELECT 1 FROM load_jobs WHERE job_id = NEW.load_job_id AND tenant_id = NEW.tenant_id
+    ))
+BEGIN
+    SELECT RAISE(ABORT, 'FOREIGN KEY constraint failed');
+END;
+
+CREATE TRIGGER trg_import_batches_tenant_update
+    BEFORE UPDATE OF tenant_id, load_job_id ON import_batches
+    WHEN (NEW.load_job_id IS NOT NULL AND NOT EXISTS (
+        SELECT 1 FROM load_jobs WHERE job_id = NEW.load_job_id AND tenant_id = NEW.tenant_id
+    ))
+BEGIN
+    SELECT RAISE(ABORT, 'FOREIGN KEY constraint failed');
+END;
+
+CREATE TRIGGER trg_import_batch_rows_tenant_insert
+    BEFORE INSERT ON import_batch_rows
+    WHEN NOT EXISTS (
+        SELECT 1 FROM import_batches WHERE batch_id = NEW.batch_id AND tenant_id = NEW.tenant_id
+    )
+BEGIN
+    SELECT RAISE(ABORT, 'FOREIGN KEY constraint failed');
+END;
+
+CREATE TRIGGER trg_import_batch_rows_tenant_update
+    BEFORE UPDATE OF tenant_id, batch_id ON import_batch_rows
+    WHEN NOT EXISTS (
+        SELECT 1 FROM import_batches WHERE batch_id = NEW.batch_id AND tenant_id = NEW.tenant_id
+    )
+BEGIN
+    SELECT RAISE(ABORT, 'FOREIGN KEY constraint failed');
+END;
+
+CREATE TRIGGER trg_webhook_deliveries_tenant_insert
+    BEFORE INSERT ON webhook_deliveries
+    WHEN NOT EXISTS (
+        SELECT 1 FROM webhook_subscriptions WHERE subscription_id = NEW.subscription_id AND tenant_id = NEW.tenant_id
+    )
+BEGIN
+    SELECT RAISE(ABORT, 'FOREIGN KEY constraint failed');
+END;
+
+CREATE TRIGGER trg_webhook_deliveries_tenant_update
+    BEFORE UPDATE OF tenant_id, subscription_id ON webhook_deliveries
+    WHEN NOT EXISTS (
+        SELECT 1 FROM webhook_subscriptions WHERE subscription_id = NEW.subscription_id AND tenant_id = NEW.tenant_id
+    )
+BEGIN
+    SELECT RAISE(ABORT, 'FOREIGN KEY constraint failed');
+END;
+
+CREATE TRIGGER trg_import_errors_tenant_insert
+    BEFORE INSERT ON import_errors
+    WHEN NOT EXISTS (
+        SELECT 1 FROM import_batches WHERE batch_id = NEW.batch_id AND tenant_id = NEW.tenant_id
+    )
+BEGIN
+    SELECT RAISE(ABORT, 'FOREIGN KEY constraint failed');
+END;
+
+CREATE TRIGGER trg_import_errors_tenant_update
+    BEFORE UPDATE OF tenant_id, batch_id ON import_errors
+    WHEN NOT EXISTS (
+        SELECT 1 FROM import_batches WHERE batch_id = NEW.batch_id AND tenant_id = NEW.tenant_id
+    )
+BEGIN
+    SELECT RAISE(ABORT, 'FOREIGN KEY constraint failed');
+END;
+
+CREATE TRIGGER trg_order_coupons_tenant_insert
+    BEFORE INSERT ON order_coupons
+    WHEN NOT EXISTS (
+        SELECT 1 FROM orders WHERE order_id = NEW.order_id AND tenant_id = NEW.tenant_id
+    )
+BEGIN
+    SELECT RAISE(ABORT, 'FOREIGN KEY constraint failed');
+END;
+
+CREATE TRIGGER trg_order_coupons_tenant_update
+    BEFORE UPDATE OF tenant_id, order_id ON order_coupons
+    WHEN NOT EXISTS (
+        SELECT 1 FROM orders WHERE order_id = NEW.order_id AND tenant_id = NEW.tenant_id
+    )
+BEGIN
+    SELECT RAISE(ABORT, 'FOREIGN KEY constraint failed');
+END;
+
+CREATE TRIGGER trg_payment_transactions_tenant_insert
+    BEFORE INSERT ON payment_transactions
+    WHEN NOT EXISTS (
+        SELECT 1 FROM orders WHERE order_id = NEW.order_id AND tenant_id = NEW.tenant_id
+    )
+BEGIN
+    SELECT RAISE(ABORT, 'FOREIGN KEY constraint failed');
+END;
+
+CREATE TRIGGER trg_payment_transactions_tenant_update
+    BEFORE UPDATE OF tenant_id, order_id ON payment_transactions
+    WHEN NOT EXISTS (
+        SELECT 1 FROM orders WHERE order_id = NEW.order_id AND tenant_id = NEW.tenant_id
+    )
+BEGIN
+    SELECT RAISE(ABORT, 'FOREIGN KEY constraint failed');
+END;
+
+CREATE TRIGGER trg_refunds_tenant_insert
+    BEFORE INSERT ON refunds
+    WHEN NOT EXISTS (
+        SELECT 1 FROM orders WHERE order_id = NEW.order_id AND tenant_id = NEW.tenant_id
+    )
+BEGIN
+    SELECT RAISE(ABORT, 'FOREIGN KEY constraint failed');
+END;
+
+CREATE TRIGGER trg_refunds_tenant_update
+    BEFORE UPDATE OF tenant_id, order_id ON refunds
+    WHEN NOT EXISTS (
+        SELECT 1 FROM orders WHERE order_id = NEW.order_id AND tenant_id = NEW.tenant_id
+    )
+BEGIN
+    SELECT RAISE(ABORT, 'FOREIGN KEY constraint failed');
+END;
+
+CREATE TRIGGER trg_review_sentiments_tenant_insert
+    BEFORE INSERT ON review_sentiments
+    WHEN NOT EXISTS (
+        SELECT 1 FROM reviews WHERE review_id = NEW.review_id AND tenant_id = NEW.tenant_id
+    )
+BEGIN
+    SELECT RAISE(ABORT, 'FOREIGN KEY constraint failed');
+END;
+
+CREATE TRIGGER trg_review_sentiments_tenant_update
+    BEFORE UPDATE OF tenant_id, review_id ON review_sentiments
+    WHEN NOT EXISTS (
+        SELECT 1 FROM reviews WHERE review_id = NEW.review_id AND tenant_id = NEW.tenant_id
+    )
+BEGIN
+    SELECT RAISE(ABORT, 'FOREIGN KEY constraint failed');
+END;
+
+CREATE TRIGGER trg_late_delivery_alerts_tenant_insert
+    BEFORE INSERT ON late_delivery_alerts
+    WHEN NOT EXISTS (
+        SELECT 1 FROM orders WHERE order_id = NEW.order_id AND tenant_id = NEW.tenant_id
+    )
+BEGIN
+    SELECT RAISE(ABORT, 'FOREIGN KEY constraint failed');
+END;
+
+CREATE TRIGGER trg_late_delivery_alerts_tenant_update
+    BEFORE UPDATE OF tenant_id, order_id ON late_delivery_alerts
+    WHEN NOT EXISTS (
+        SELECT 1 FROM orders WHERE order_id = NEW.order_id AND tenant_id = NEW.tenant_id
+    )
+BEGIN
+    SELECT RAISE(ABORT, 'FOREIGN KEY constraint failed');
+END;
+
+CREATE TRIGGER trg_customer_addresses_tenant_insert
+    BEFORE INSERT ON customer_addresses
+    WHEN NOT EXISTS (
+        SELECT 1 FROM customers WHERE customer_id = NEW.customer_id AND tenant_id = NEW.tenant_id
+    )
+BEGIN
+    SELECT RAISE(ABORT, 'FOREIGN KEY constraint failed');
+END;
+
+CREATE TRIGGER trg_customer_addresses_tenant_update
+    BEFORE UPDATE OF tenant_id, customer_id ON customer_addresses
+    WHEN NOT EXISTS (
+        SELECT 1 FROM customers WHERE customer_id = NEW.customer_id AND tenant_id = NEW.tenant_id
+    )
+BEGIN
+    SELECT RAISE(ABORT, 'FOREIGN KEY constraint failed');
+END;
+
+CREATE TRIGGER trg_product_images_tenant_insert
+    BEFORE INSERT ON product_images
+    WHEN NOT EXISTS (
+        SELECT 1 FROM products WHERE product_id = NEW.product_id AND tenant_id = NEW.tenant_id
+    )
+BEGIN
+    SELECT RAISE(ABORT, 'FOREIGN KEY constraint failed');
+END;
+
+CREATE TRIGGER trg_product_images_tenant_update
+    BEFORE UPDATE OF tenant_id, product_id ON product_images
+    WHEN NOT EXISTS (
+        SELECT 1 FROM products WHERE product_id = NEW.product_id AND tenant_id = NEW.tenant_id
+    )
+BEGIN
+    SELECT RAISE(ABORT, 'FOREIGN KEY constraint failed');
+END;